@@ -11,6 +11,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 "proto/base_node.proto",
                 "proto/wallet.proto",
                 "proto/validator_node.proto",
+                "proto/remote_signer.proto",
             ],
             &["proto"],
         )?;
@@ -26,7 +26,7 @@ use std::{
 };
 
 use tari_common_types::transaction::{TransactionDirection, TransactionStatus, TxId};
-use tari_core::transactions::transaction_components::Transaction;
+use tari_core::transactions::{fee::FeeBreakdown, transaction_components::Transaction};
 use tari_crypto::ristretto::RistrettoSecretKey;
 use tari_utilities::ByteArray;
 
@@ -76,6 +76,26 @@ impl TryFrom<grpc::Transaction> for Transaction {
     }
 }
 
+impl From<FeeBreakdown> for grpc::GetFeeBreakdownResponse {
+    fn from(breakdown: FeeBreakdown) -> Self {
+        let weight = breakdown.weight;
+        Self {
+            num_kernels: weight.num_kernels as u64,
+            num_inputs: weight.num_inputs as u64,
+            num_outputs: weight.num_outputs as u64,
+            rounded_features_and_scripts_byte_size: weight.rounded_features_and_scripts_byte_size as u64,
+            kernels_weight: weight.kernels_weight,
+            inputs_weight: weight.inputs_weight,
+            outputs_weight: weight.outputs_weight,
+            features_and_scripts_weight: weight.features_and_scripts_weight,
+            total_weight: weight.total(),
+            fee_per_gram: breakdown.fee_per_gram.as_u64(),
+            fee: breakdown.fee.as_u64(),
+            normalized_fee: breakdown.normalized_fee.as_u64(),
+        }
+    }
+}
+
 impl From<TransactionDirection> for grpc::TransactionDirection {
     fn from(status: TransactionDirection) -> Self {
         use TransactionDirection::{Inbound, Outbound, Unknown};
@@ -103,6 +123,8 @@ impl From<TransactionStatus> for grpc::TransactionStatus {
             FauxUnconfirmed => grpc::TransactionStatus::FauxUnconfirmed,
             FauxConfirmed => grpc::TransactionStatus::FauxConfirmed,
             Queued => grpc::TransactionStatus::Queued,
+            ImportedHistory => grpc::TransactionStatus::ImportedHistory,
+            AwaitingCosigners => grpc::TransactionStatus::AwaitingCosigners,
         }
     }
 }
@@ -23,6 +23,9 @@ pub mod authentication;
 
 pub mod conversions;
 
+#[cfg(feature = "remote_signer")]
+pub mod remote_signer;
+
 pub mod tari_rpc {
     tonic::include_proto!("tari.rpc");
 }
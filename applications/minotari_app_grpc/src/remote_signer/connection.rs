@@ -0,0 +1,222 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fs, path::PathBuf};
+
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_core::transactions::transaction_components::{TransactionInputVersion, TransactionOutputVersion};
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
+use tari_utilities::ByteArray;
+use thiserror::Error;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig, Endpoint, Identity};
+
+use crate::tari_rpc::{
+    signing_daemon_client::SigningDaemonClient,
+    RemoteDiffieHellmanSharedSecretRequest,
+    RemoteKey,
+    RemotePublicKeyRequest,
+    RemoteScriptOffsetRequest,
+    RemoteScriptSignatureRequest,
+    RemoteSenderMetadataSignatureRequest,
+};
+
+#[derive(Debug, Error)]
+pub enum RemoteSignerError {
+    #[error("Invalid signing daemon address `{0}`")]
+    InvalidAddress(String),
+    #[error("Could not read `{path}`: {source}")]
+    Certificate { path: PathBuf, source: std::io::Error },
+    #[error("Could not connect to the signing daemon: {0}")]
+    Transport(#[from] tonic::transport::Error),
+    #[error("The signing daemon rejected the request: {0}")]
+    Status(#[from] tonic::Status),
+    #[error("The signing daemon returned an invalid {0}")]
+    InvalidResponse(&'static str),
+}
+
+impl From<RemoteSignerError> for KeyManagerServiceError {
+    fn from(err: RemoteSignerError) -> Self {
+        KeyManagerServiceError::SigningDeviceError(err.to_string())
+    }
+}
+
+/// Where to find the signing daemon and the certificates used to authenticate both ends of the connection. The
+/// daemon's certificate must be signed by the given CA, and the daemon must trust the CA of the client certificate.
+#[derive(Debug, Clone)]
+pub struct RemoteSignerConfig {
+    /// The daemon's gRPC address, e.g. `https://signer.example.com:18150`
+    pub address: String,
+    /// The name the daemon's certificate is issued for, if it differs from the host in `address`
+    pub domain_name: Option<String>,
+    pub ca_certificate_path: PathBuf,
+    pub client_certificate_path: PathBuf,
+    pub client_key_path: PathBuf,
+}
+
+/// A mutually-authenticated gRPC connection to a signing daemon
+#[derive(Clone)]
+pub struct RemoteSignerConnection {
+    client: SigningDaemonClient<Channel>,
+}
+
+impl RemoteSignerConnection {
+    pub async fn connect(config: &RemoteSignerConfig) -> Result<Self, RemoteSignerError> {
+        let ca_certificate = Certificate::from_pem(read_pem(&config.ca_certificate_path)?);
+        let identity = Identity::from_pem(
+            read_pem(&config.client_certificate_path)?,
+            read_pem(&config.client_key_path)?,
+        );
+        let mut tls_config = ClientTlsConfig::new().ca_certificate(ca_certificate).identity(identity);
+        if let Some(domain_name) = &config.domain_name {
+            tls_config = tls_config.domain_name(domain_name.clone());
+        }
+        let channel = Endpoint::from_shared(config.address.clone())
+            .map_err(|_| RemoteSignerError::InvalidAddress(config.address.clone()))?
+            .tls_config(tls_config)?
+            .connect()
+            .await?;
+        Ok(Self {
+            client: SigningDaemonClient::new(channel),
+        })
+    }
+
+    pub async fn get_public_key(&self, key: RemoteKey) -> Result<PublicKey, RemoteSignerError> {
+        let response = self
+            .client
+            .clone()
+            .get_remote_public_key(RemotePublicKeyRequest { key: Some(key) })
+            .await?
+            .into_inner();
+        parse_public_key(&response.public_key, "public key")
+    }
+
+    /// The Diffie-Hellman shared secret of a daemon key and the given public key, as a point
+    pub async fn get_diffie_hellman_shared_secret(
+        &self,
+        key: RemoteKey,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, RemoteSignerError> {
+        let response = self
+            .client
+            .clone()
+            .get_remote_diffie_hellman_shared_secret(RemoteDiffieHellmanSharedSecretRequest {
+                key: Some(key),
+                public_key: public_key.to_vec(),
+            })
+            .await?
+            .into_inner();
+        parse_public_key(&response.shared_secret, "shared secret")
+    }
+
+    /// Has the daemon sign for the script key of an input being spent. Returns the public script key, the daemon's
+    /// public nonce and its part of the script signature.
+    pub async fn get_script_signature(
+        &self,
+        script_key: RemoteKey,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), RemoteSignerError> {
+        let response = self
+            .client
+            .clone()
+            .get_remote_script_signature(RemoteScriptSignatureRequest {
+                script_key: Some(script_key),
+                txi_version: u32::from(txi_version.as_u8()),
+                ephemeral_commitment: ephemeral_commitment.to_vec(),
+                commitment: commitment.to_vec(),
+                script_message: script_message.to_vec(),
+            })
+            .await?
+            .into_inner();
+        Ok((
+            parse_public_key(&response.script_public_key, "script public key")?,
+            parse_public_key(&response.ephemeral_pubkey, "script signature nonce")?,
+            parse_private_key(&response.u_y, "script signature")?,
+        ))
+    }
+
+    /// The sum of the given script keys minus the sum of the given sender offset keys. The daemon only answers if at
+    /// least one of its session sender offset keys is subtracted, and forgets those keys once it has.
+    pub async fn get_script_offset(
+        &self,
+        script_keys: Vec<RemoteKey>,
+        sender_offset_keys: Vec<RemoteKey>,
+    ) -> Result<PrivateKey, RemoteSignerError> {
+        let response = self
+            .client
+            .clone()
+            .get_remote_script_offset(RemoteScriptOffsetRequest {
+                script_keys,
+                sender_offset_keys,
+            })
+            .await?
+            .into_inner();
+        parse_private_key(&response.script_offset, "script offset")
+    }
+
+    /// Has the daemon make the sender's part of an output's metadata signature. Returns the public nonce and the
+    /// signature scalar.
+    pub async fn get_sender_metadata_signature(
+        &self,
+        nonce: RemoteKey,
+        sender_offset_key: RemoteKey,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), RemoteSignerError> {
+        let response = self
+            .client
+            .clone()
+            .get_remote_sender_metadata_signature(RemoteSenderMetadataSignatureRequest {
+                nonce: Some(nonce),
+                sender_offset_key: Some(sender_offset_key),
+                txo_version: u32::from(txo_version.as_u8()),
+                commitment: commitment.to_vec(),
+                ephemeral_commitment: ephemeral_commitment.to_vec(),
+                metadata_signature_message: metadata_signature_message.to_vec(),
+            })
+            .await?
+            .into_inner();
+        Ok((
+            parse_public_key(&response.ephemeral_pubkey, "metadata signature nonce")?,
+            parse_private_key(&response.u_y, "metadata signature")?,
+        ))
+    }
+}
+
+pub(super) fn read_pem(path: &PathBuf) -> Result<Vec<u8>, RemoteSignerError> {
+    fs::read(path).map_err(|source| RemoteSignerError::Certificate {
+        path: path.clone(),
+        source,
+    })
+}
+
+fn parse_public_key(bytes: &[u8], field: &'static str) -> Result<PublicKey, RemoteSignerError> {
+    PublicKey::from_canonical_bytes(bytes).map_err(|_| RemoteSignerError::InvalidResponse(field))
+}
+
+fn parse_private_key(bytes: &[u8], field: &'static str) -> Result<PrivateKey, RemoteSignerError> {
+    PrivateKey::from_canonical_bytes(bytes).map_err(|_| RemoteSignerError::InvalidResponse(field))
+}
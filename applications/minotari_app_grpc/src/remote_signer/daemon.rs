@@ -0,0 +1,380 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    future::Future,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use rand::rngs::OsRng;
+use tari_common_types::types::{ComAndPubSignature, Commitment, CommitmentFactory, PrivateKey, PublicKey};
+use tari_core::transactions::{
+    key_manager::{SecretTransactionKeyManagerInterface, TransactionKeyManagerBranch},
+    transaction_components::{TransactionInput, TransactionInputVersion, TransactionOutput, TransactionOutputVersion},
+};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+use tari_key_manager::key_manager_service::KeyId;
+use tari_utilities::ByteArray;
+use tonic::{
+    transport::{Certificate, Identity, Server, ServerTlsConfig},
+    Request,
+    Response,
+    Status,
+};
+
+use crate::{
+    remote_signer::{connection::read_pem, RemoteSignerError},
+    tari_rpc::{
+        signing_daemon_server::{SigningDaemon, SigningDaemonServer},
+        RemoteDiffieHellmanSharedSecretRequest,
+        RemoteDiffieHellmanSharedSecretResponse,
+        RemoteKey,
+        RemotePublicKeyRequest,
+        RemotePublicKeyResponse,
+        RemoteScriptOffsetRequest,
+        RemoteScriptOffsetResponse,
+        RemoteScriptSignatureRequest,
+        RemoteScriptSignatureResponse,
+        RemoteSenderMetadataSignatureRequest,
+        RemoteSenderMetadataSignatureResponse,
+    },
+};
+
+/// The most session keys the daemon holds at once, which bounds the memory used by abandoned transactions
+const MAX_SESSION_KEYS: usize = 100_000;
+
+/// Where the signing daemon listens and the certificates used to authenticate both ends of its connections
+#[derive(Debug, Clone)]
+pub struct SigningDaemonConfig {
+    pub listen_address: SocketAddr,
+    pub certificate_path: PathBuf,
+    pub key_path: PathBuf,
+    /// Only clients with a certificate signed by this CA are served
+    pub client_ca_certificate_path: PathBuf,
+}
+
+/// A sender offset key or sender nonce generated by the daemon for the current session
+struct SessionKey {
+    private_key: PrivateKey,
+    has_signed: bool,
+}
+
+/// The signing daemon behind [RemoteSignerClient](crate::remote_signer::RemoteSignerClient). Script keys are derived
+/// by the key manager it wraps, typically backed by an HSM.
+///
+/// Sender offset keys and sender nonces are not derived from the key manager's seed: the daemon generates them at
+/// random the first time the wallet asks for them and forgets them once they have been used. Each nonce and each
+/// sender offset key makes at most one metadata signature, and a script offset is only returned if it subtracts at
+/// least one sender offset key, which is then forgotten. A script offset is therefore always masked by keys no client
+/// ever learns or can use again, so clients cannot combine the daemon's answers to recover a script key.
+#[derive(Clone)]
+pub struct SigningDaemonService<KM> {
+    key_manager: KM,
+    session_keys: Arc<Mutex<HashMap<(String, u64), SessionKey>>>,
+}
+
+impl<KM> SigningDaemonService<KM>
+where KM: SecretTransactionKeyManagerInterface
+{
+    pub fn new(key_manager: KM) -> Self {
+        Self {
+            key_manager,
+            session_keys: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Serves the daemon over mutually-authenticated TLS until `shutdown` resolves
+    pub async fn serve<F>(self, config: &SigningDaemonConfig, shutdown: F) -> Result<(), RemoteSignerError>
+    where F: Future<Output = ()> + Send {
+        let identity = Identity::from_pem(read_pem(&config.certificate_path)?, read_pem(&config.key_path)?);
+        let client_ca = Certificate::from_pem(read_pem(&config.client_ca_certificate_path)?);
+        Server::builder()
+            .tls_config(ServerTlsConfig::new().identity(identity).client_ca_root(client_ca))?
+            .add_service(SigningDaemonServer::new(self))
+            .serve_with_shutdown(config.listen_address, shutdown)
+            .await?;
+        Ok(())
+    }
+
+    fn lock_session_keys(&self) -> Result<MutexGuard<'_, HashMap<(String, u64), SessionKey>>, Status> {
+        self.session_keys
+            .lock()
+            .map_err(|_| Status::internal("Session key lock poisoned"))
+    }
+
+    /// The public key of a session key, generating the key if the daemon does not hold it yet
+    fn session_public_key(&self, key: RemoteKey) -> Result<PublicKey, Status> {
+        let mut session_keys = self.lock_session_keys()?;
+        let entry = (key.branch, key.index);
+        if !session_keys.contains_key(&entry) {
+            if session_keys.len() >= MAX_SESSION_KEYS {
+                return Err(Status::resource_exhausted("Too many unused sender offset keys and nonces"));
+            }
+            session_keys.insert(entry.clone(), SessionKey {
+                private_key: PrivateKey::random(&mut OsRng),
+                has_signed: false,
+            });
+        }
+        Ok(PublicKey::from_secret_key(&session_keys[&entry].private_key))
+    }
+
+    async fn script_private_key(&self, key: RemoteKey) -> Result<PrivateKey, Status> {
+        if !is_script_branch(&key.branch) {
+            return Err(Status::invalid_argument(format!(
+                "`{}` is not a script key branch",
+                key.branch
+            )));
+        }
+        self.key_manager
+            .get_private_key(&KeyId::Managed {
+                branch: key.branch,
+                index: key.index,
+            })
+            .await
+            .map_err(|e| Status::internal(e.to_string()))
+    }
+
+    #[cfg(test)]
+    fn session_key_count(&self) -> usize {
+        self.session_keys.lock().unwrap().len()
+    }
+}
+
+#[tonic::async_trait]
+impl<KM> SigningDaemon for SigningDaemonService<KM>
+where KM: SecretTransactionKeyManagerInterface
+{
+    async fn get_remote_public_key(
+        &self,
+        request: Request<RemotePublicKeyRequest>,
+    ) -> Result<Response<RemotePublicKeyResponse>, Status> {
+        let key = required_key(request.into_inner().key)?;
+        let public_key = if is_session_branch(&key.branch) {
+            self.session_public_key(key)?
+        } else {
+            PublicKey::from_secret_key(&self.script_private_key(key).await?)
+        };
+        Ok(Response::new(RemotePublicKeyResponse {
+            public_key: public_key.to_vec(),
+        }))
+    }
+
+    async fn get_remote_script_signature(
+        &self,
+        request: Request<RemoteScriptSignatureRequest>,
+    ) -> Result<Response<RemoteScriptSignatureResponse>, Status> {
+        let request = request.into_inner();
+        let script_private_key = self.script_private_key(required_key(request.script_key)?).await?;
+        let txi_version = u8::try_from(request.txi_version)
+            .ok()
+            .and_then(|v| TransactionInputVersion::try_from(v).ok())
+            .ok_or_else(|| Status::invalid_argument("Invalid input version"))?;
+
+        let r_y = PrivateKey::random(&mut OsRng);
+        let ephemeral_pubkey = PublicKey::from_secret_key(&r_y);
+        let script_public_key = PublicKey::from_secret_key(&script_private_key);
+        let challenge = TransactionInput::finalize_script_signature_challenge(
+            &txi_version,
+            &parse_commitment(&request.ephemeral_commitment)?,
+            &ephemeral_pubkey,
+            &script_public_key,
+            &parse_commitment(&request.commitment)?,
+            &parse_message(&request.script_message)?,
+        );
+        let e = PrivateKey::from_uniform_bytes(&challenge).map_err(|e| Status::internal(e.to_string()))?;
+        let u_y = &r_y + &(&e * &script_private_key);
+        Ok(Response::new(RemoteScriptSignatureResponse {
+            script_public_key: script_public_key.to_vec(),
+            ephemeral_pubkey: ephemeral_pubkey.to_vec(),
+            u_y: u_y.to_vec(),
+        }))
+    }
+
+    async fn get_remote_script_offset(
+        &self,
+        request: Request<RemoteScriptOffsetRequest>,
+    ) -> Result<Response<RemoteScriptOffsetResponse>, Status> {
+        let request = request.into_inner();
+        if request.sender_offset_keys.is_empty() {
+            return Err(Status::invalid_argument(
+                "A script offset must subtract at least one sender offset key",
+            ));
+        }
+        let sender_offset_keys = request
+            .sender_offset_keys
+            .into_iter()
+            .map(|key| (key.branch, key.index))
+            .collect::<Vec<_>>();
+        if sender_offset_keys.iter().collect::<HashSet<_>>().len() != sender_offset_keys.len() {
+            return Err(Status::invalid_argument("Duplicate sender offset key"));
+        }
+        // The sender offset keys are taken out of the session, so no later request can use them
+        let mut script_offset = PrivateKey::default();
+        {
+            let mut session_keys = self.lock_session_keys()?;
+            for entry in &sender_offset_keys {
+                if entry.0 != TransactionKeyManagerBranch::SenderOffset.get_branch_key() ||
+                    !session_keys.contains_key(entry)
+                {
+                    return Err(Status::failed_precondition(format!(
+                        "`{}:{}` is not a sender offset key of this session",
+                        entry.0, entry.1
+                    )));
+                }
+            }
+            for entry in &sender_offset_keys {
+                if let Some(key) = session_keys.remove(entry) {
+                    script_offset = &script_offset - &key.private_key;
+                }
+            }
+        }
+        for key in request.script_keys {
+            script_offset = &script_offset + &self.script_private_key(key).await?;
+        }
+        Ok(Response::new(RemoteScriptOffsetResponse {
+            script_offset: script_offset.to_vec(),
+        }))
+    }
+
+    async fn get_remote_sender_metadata_signature(
+        &self,
+        request: Request<RemoteSenderMetadataSignatureRequest>,
+    ) -> Result<Response<RemoteSenderMetadataSignatureResponse>, Status> {
+        let request = request.into_inner();
+        let nonce = required_key(request.nonce)?;
+        let sender_offset_key = required_key(request.sender_offset_key)?;
+        if nonce.branch != TransactionKeyManagerBranch::Nonce.get_branch_key() ||
+            sender_offset_key.branch != TransactionKeyManagerBranch::SenderOffset.get_branch_key()
+        {
+            return Err(Status::invalid_argument(
+                "Metadata signatures are made with a sender offset key and a sender nonce",
+            ));
+        }
+        let txo_version = u8::try_from(request.txo_version)
+            .ok()
+            .and_then(|v| TransactionOutputVersion::try_from(v).ok())
+            .ok_or_else(|| Status::invalid_argument("Invalid output version"))?;
+        let commitment = parse_commitment(&request.commitment)?;
+        let ephemeral_commitment = parse_commitment(&request.ephemeral_commitment)?;
+        let message = parse_message(&request.metadata_signature_message)?;
+
+        // A nonce is only ever used once, and a sender offset key only ever makes one signature
+        let (ephemeral_private_key, sender_offset_private_key) = {
+            let mut session_keys = self.lock_session_keys()?;
+            let sender_offset_entry = (sender_offset_key.branch, sender_offset_key.index);
+            match session_keys.get(&sender_offset_entry) {
+                Some(key) if !key.has_signed => {},
+                Some(_) => {
+                    return Err(Status::failed_precondition(
+                        "The sender offset key has already made a metadata signature",
+                    ))
+                },
+                None => return Err(Status::failed_precondition("Unknown sender offset key")),
+            }
+            let nonce = session_keys
+                .remove(&(nonce.branch, nonce.index))
+                .ok_or_else(|| Status::failed_precondition("Unknown or already used nonce"))?;
+            let sender_offset = session_keys
+                .get_mut(&sender_offset_entry)
+                .ok_or_else(|| Status::failed_precondition("Unknown sender offset key"))?;
+            sender_offset.has_signed = true;
+            (nonce.private_key, sender_offset.private_key.clone())
+        };
+
+        let ephemeral_pubkey = PublicKey::from_secret_key(&ephemeral_private_key);
+        let challenge = TransactionOutput::finalize_metadata_signature_challenge(
+            &txo_version,
+            &PublicKey::from_secret_key(&sender_offset_private_key),
+            &ephemeral_commitment,
+            &ephemeral_pubkey,
+            &commitment,
+            &message,
+        );
+        let signature = ComAndPubSignature::sign(
+            &PrivateKey::default(),
+            &PrivateKey::default(),
+            &sender_offset_private_key,
+            &PrivateKey::default(),
+            &PrivateKey::default(),
+            &ephemeral_private_key,
+            &challenge,
+            &CommitmentFactory::default(),
+        )
+        .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(RemoteSenderMetadataSignatureResponse {
+            ephemeral_pubkey: ephemeral_pubkey.to_vec(),
+            u_y: signature.u_y().to_vec(),
+        }))
+    }
+
+    async fn get_remote_diffie_hellman_shared_secret(
+        &self,
+        request: Request<RemoteDiffieHellmanSharedSecretRequest>,
+    ) -> Result<Response<RemoteDiffieHellmanSharedSecretResponse>, Status> {
+        let request = request.into_inner();
+        let key = required_key(request.key)?;
+        if key.branch != TransactionKeyManagerBranch::SenderOffset.get_branch_key() {
+            return Err(Status::invalid_argument(
+                "Shared secrets are only derived with sender offset keys",
+            ));
+        }
+        let public_key =
+            PublicKey::from_canonical_bytes(&request.public_key).map_err(|e| Status::invalid_argument(e.to_string()))?;
+        // Makes sure the key exists before it is used
+        self.session_public_key(key.clone())?;
+        let session_keys = self.lock_session_keys()?;
+        let private_key = &session_keys
+            .get(&(key.branch, key.index))
+            .ok_or_else(|| Status::failed_precondition("Unknown sender offset key"))?
+            .private_key;
+        Ok(Response::new(RemoteDiffieHellmanSharedSecretResponse {
+            shared_secret: (private_key * &public_key).to_vec(),
+        }))
+    }
+}
+
+/// Sender offset keys and sender nonces are generated by the daemon for each session
+fn is_session_branch(branch: &str) -> bool {
+    branch == TransactionKeyManagerBranch::SenderOffset.get_branch_key() ||
+        branch == TransactionKeyManagerBranch::Nonce.get_branch_key()
+}
+
+fn is_script_branch(branch: &str) -> bool {
+    branch == TransactionKeyManagerBranch::ScriptKey.get_branch_key() ||
+        branch == TransactionKeyManagerBranch::CoinbaseScript.get_branch_key()
+}
+
+fn required_key(key: Option<RemoteKey>) -> Result<RemoteKey, Status> {
+    key.ok_or_else(|| Status::invalid_argument("Missing key"))
+}
+
+fn parse_commitment(bytes: &[u8]) -> Result<Commitment, Status> {
+    Commitment::from_canonical_bytes(bytes).map_err(|e| Status::invalid_argument(e.to_string()))
+}
+
+fn parse_message(bytes: &[u8]) -> Result<[u8; 32], Status> {
+    <[u8; 32]>::try_from(bytes).map_err(|_| Status::invalid_argument("Invalid message"))
+}
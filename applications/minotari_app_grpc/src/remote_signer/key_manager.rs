@@ -0,0 +1,128 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_core::transactions::{
+    key_manager::{ExternalSigner, ExternalSignerKeyManager, TariKeyId, TransactionKeyManagerBranch},
+    transaction_components::{TransactionInputVersion, TransactionOutputVersion},
+};
+use tari_key_manager::key_manager_service::{KeyId, KeyManagerServiceError};
+
+use crate::{remote_signer::RemoteSignerConnection, tari_rpc::RemoteKey};
+
+/// The key branches held by the signing daemon. The wallet host only tracks their indices.
+pub const REMOTE_KEY_BRANCHES: [TransactionKeyManagerBranch; 4] = [
+    TransactionKeyManagerBranch::ScriptKey,
+    TransactionKeyManagerBranch::CoinbaseScript,
+    TransactionKeyManagerBranch::SenderOffset,
+    TransactionKeyManagerBranch::Nonce,
+];
+
+/// A transaction key manager that forwards signing with the script, sender offset and sender nonce keys to an external
+/// signing daemon, so those keys can live in an HSM-backed service separate from the wallet host. Everything else,
+/// including the commitment masks, is delegated to the host key manager it wraps.
+///
+/// Without the script key an output cannot be spent, so funds can only leave the wallet through the daemon.
+pub type RemoteSignerClient<KM> = ExternalSignerKeyManager<KM, RemoteSignerConnection>;
+
+#[async_trait::async_trait]
+impl ExternalSigner for RemoteSignerConnection {
+    type Key = RemoteKey;
+
+    fn description(&self) -> &'static str {
+        "the signing daemon"
+    }
+
+    fn holds_branch(&self, branch: &str) -> bool {
+        REMOTE_KEY_BRANCHES.iter().any(|b| b.get_branch_key() == branch)
+    }
+
+    fn signer_key(&self, key_id: &TariKeyId) -> Option<RemoteKey> {
+        match key_id {
+            KeyId::Managed { branch, index } if self.holds_branch(branch) => Some(RemoteKey {
+                branch: branch.clone(),
+                index: *index,
+            }),
+            _ => None,
+        }
+    }
+
+    async fn get_public_key(&self, key: RemoteKey) -> Result<PublicKey, KeyManagerServiceError> {
+        Ok(RemoteSignerConnection::get_public_key(self, key).await?)
+    }
+
+    async fn get_diffie_hellman_shared_secret(
+        &self,
+        key: RemoteKey,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, KeyManagerServiceError> {
+        Ok(RemoteSignerConnection::get_diffie_hellman_shared_secret(self, key, public_key).await?)
+    }
+
+    async fn get_script_signature(
+        &self,
+        script_key: RemoteKey,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), KeyManagerServiceError> {
+        Ok(RemoteSignerConnection::get_script_signature(
+            self,
+            script_key,
+            txi_version,
+            ephemeral_commitment,
+            commitment,
+            script_message,
+        )
+        .await?)
+    }
+
+    async fn get_script_offset(
+        &self,
+        script_keys: Vec<RemoteKey>,
+        sender_offset_keys: Vec<RemoteKey>,
+    ) -> Result<PrivateKey, KeyManagerServiceError> {
+        Ok(RemoteSignerConnection::get_script_offset(self, script_keys, sender_offset_keys).await?)
+    }
+
+    async fn get_sender_metadata_signature(
+        &self,
+        nonce: RemoteKey,
+        sender_offset_key: RemoteKey,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), KeyManagerServiceError> {
+        Ok(RemoteSignerConnection::get_sender_metadata_signature(
+            self,
+            nonce,
+            sender_offset_key,
+            txo_version,
+            commitment,
+            ephemeral_commitment,
+            metadata_signature_message,
+        )
+        .await?)
+    }
+}
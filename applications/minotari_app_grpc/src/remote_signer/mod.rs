@@ -0,0 +1,348 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+mod connection;
+pub use connection::{RemoteSignerConfig, RemoteSignerConnection, RemoteSignerError};
+
+mod daemon;
+pub use daemon::{SigningDaemonConfig, SigningDaemonService};
+
+mod key_manager;
+pub use key_manager::{RemoteSignerClient, REMOTE_KEY_BRANCHES};
+
+#[cfg(test)]
+mod test {
+    use std::{net::SocketAddr, path::PathBuf};
+
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{Commitment, CommitmentFactory, PrivateKey, PublicKey};
+    use tari_comms::types::CommsDHKE;
+    use tari_core::{
+        one_sided::diffie_hellman_stealth_domain_hasher,
+        transactions::{
+            key_manager::{
+                SecretTransactionKeyManagerInterface,
+                TransactionKeyManagerBranch,
+                TransactionKeyManagerInterface,
+            },
+            test_helpers::{create_test_core_key_manager_with_memory_db, TestKeyManager},
+            transaction_components::{TransactionInput, TransactionInputVersion, TransactionOutputVersion},
+        },
+    };
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_key_manager::key_manager_service::{KeyId, KeyManagerInterface};
+    use tari_utilities::ByteArray;
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
+
+    use super::*;
+    use crate::tari_rpc::{signing_daemon_server::SigningDaemonServer, RemoteKey};
+
+    type TestSigningDaemon = SigningDaemonService<TestKeyManager>;
+
+    fn test_data(file: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("test_data")
+            .join("remote_signer")
+            .join(file)
+    }
+
+    /// Serves the daemon over TLS, only accepting clients with a certificate issued by the test CA
+    async fn start_daemon(daemon: TestSigningDaemon) -> SocketAddr {
+        let identity = Identity::from_pem(
+            std::fs::read(test_data("server.pem")).unwrap(),
+            std::fs::read(test_data("server.key")).unwrap(),
+        );
+        let client_ca = Certificate::from_pem(std::fs::read(test_data("ca.pem")).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = Server::builder()
+            .tls_config(ServerTlsConfig::new().identity(identity).client_ca_root(client_ca))
+            .unwrap()
+            .add_service(SigningDaemonServer::new(daemon))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+        tokio::spawn(server);
+        address
+    }
+
+    fn client_config(address: SocketAddr, client: &str) -> RemoteSignerConfig {
+        RemoteSignerConfig {
+            address: format!("https://{}", address),
+            domain_name: Some("localhost".to_string()),
+            ca_certificate_path: test_data("ca.pem"),
+            client_certificate_path: test_data(&format!("{}.pem", client)),
+            client_key_path: test_data(&format!("{}.key", client)),
+        }
+    }
+
+    fn test_daemon() -> (TestSigningDaemon, TestKeyManager) {
+        let key_manager = create_test_core_key_manager_with_memory_db();
+        (SigningDaemonService::new(key_manager.clone()), key_manager)
+    }
+
+    async fn connect_client(address: SocketAddr) -> RemoteSignerClient<TestKeyManager> {
+        let connection = RemoteSignerConnection::connect(&client_config(address, "client"))
+            .await
+            .unwrap();
+        RemoteSignerClient::new(create_test_core_key_manager_with_memory_db(), connection)
+    }
+
+    #[tokio::test]
+    async fn it_rejects_clients_with_an_untrusted_certificate() {
+        let (daemon, _) = test_daemon();
+        let address = start_daemon(daemon.clone()).await;
+
+        // The handshake may only fail once the first request is made. A nonce is generated by the first request for
+        // it, so the daemon's session shows whether the request was served.
+        let key = RemoteKey {
+            branch: TransactionKeyManagerBranch::Nonce.get_branch_key(),
+            index: 1,
+        };
+        if let Ok(connection) = RemoteSignerConnection::connect(&client_config(address, "untrusted_client")).await {
+            assert!(connection.get_public_key(key.clone()).await.is_err());
+        }
+        assert_eq!(daemon.session_key_count(), 0);
+
+        let connection = RemoteSignerConnection::connect(&client_config(address, "client"))
+            .await
+            .unwrap();
+        connection.get_public_key(key).await.unwrap();
+        assert_eq!(daemon.session_key_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_round_trips_script_signatures_and_offsets_through_the_daemon() {
+        let (daemon, daemon_key_manager) = test_daemon();
+        let address = start_daemon(daemon).await;
+        let client = connect_client(address).await;
+
+        let (script_key_id, script_public_key) = client
+            .get_next_key(TransactionKeyManagerBranch::ScriptKey.get_branch_key())
+            .await
+            .unwrap();
+        // The key is the daemon's, not derived from the wallet's seed
+        assert_eq!(
+            script_public_key,
+            daemon_key_manager
+                .get_public_key_at_key_id(&script_key_id)
+                .await
+                .unwrap()
+        );
+        let (spend_key_id, _) = client
+            .get_next_key(TransactionKeyManagerBranch::CommitmentMask.get_branch_key())
+            .await
+            .unwrap();
+
+        let value = PrivateKey::from(5000u64);
+        let txi_version = TransactionInputVersion::get_current_version();
+        let script_message = [3u8; 32];
+        let signature = client
+            .get_script_signature(&script_key_id, &spend_key_id, &value, &txi_version, &script_message)
+            .await
+            .unwrap();
+        let commitment = client.get_commitment(&spend_key_id, &value).await.unwrap();
+        let challenge = TransactionInput::finalize_script_signature_challenge(
+            &txi_version,
+            signature.ephemeral_commitment(),
+            signature.ephemeral_pubkey(),
+            &script_public_key,
+            &commitment,
+            &script_message,
+        );
+        assert!(signature.verify_challenge(
+            &commitment,
+            &script_public_key,
+            &challenge,
+            &CommitmentFactory::default(),
+            &mut OsRng
+        ));
+        // The signature does not verify for another message
+        let other_challenge = TransactionInput::finalize_script_signature_challenge(
+            &txi_version,
+            signature.ephemeral_commitment(),
+            signature.ephemeral_pubkey(),
+            &script_public_key,
+            &commitment,
+            &[4u8; 32],
+        );
+        assert!(!signature.verify_challenge(
+            &commitment,
+            &script_public_key,
+            &other_challenge,
+            &CommitmentFactory::default(),
+            &mut OsRng
+        ));
+
+        let (sender_offset_key_id, sender_offset_public_key) = client
+            .get_next_key(TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+            .await
+            .unwrap();
+        let script_offset = client
+            .get_script_offset(&[script_key_id.clone()], &[sender_offset_key_id.clone()])
+            .await
+            .unwrap();
+        assert_eq!(
+            PublicKey::from_secret_key(&script_offset),
+            &script_public_key - &sender_offset_public_key
+        );
+        // The sender offset key has been used up
+        assert!(client
+            .get_script_offset(&[script_key_id], &[sender_offset_key_id])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn it_never_returns_a_script_offset_that_reveals_a_script_key() {
+        let (daemon, daemon_key_manager) = test_daemon();
+        let address = start_daemon(daemon).await;
+        let connection = RemoteSignerConnection::connect(&client_config(address, "client"))
+            .await
+            .unwrap();
+        let script_key = RemoteKey {
+            branch: TransactionKeyManagerBranch::ScriptKey.get_branch_key(),
+            index: 1,
+        };
+        let sender_offset_key = RemoteKey {
+            branch: TransactionKeyManagerBranch::SenderOffset.get_branch_key(),
+            index: 1,
+        };
+
+        // No sender offset key to mask the script key
+        assert!(connection
+            .get_script_offset(vec![script_key.clone()], vec![])
+            .await
+            .is_err());
+        // A sender offset key the daemon has not generated, e.g. one on a branch derived from its seed
+        let seed_key = RemoteKey {
+            branch: TransactionKeyManagerBranch::ScriptKey.get_branch_key(),
+            index: 2,
+        };
+        assert!(connection
+            .get_script_offset(vec![script_key.clone()], vec![seed_key])
+            .await
+            .is_err());
+        assert!(connection
+            .get_script_offset(vec![script_key.clone()], vec![sender_offset_key.clone()])
+            .await
+            .is_err());
+
+        // Subtracting the same sender offset key twice from two script keys would reveal their difference
+        connection.get_public_key(sender_offset_key.clone()).await.unwrap();
+        let script_offset = connection
+            .get_script_offset(vec![script_key.clone()], vec![sender_offset_key.clone()])
+            .await
+            .unwrap();
+        let script_private_key = daemon_key_manager
+            .get_private_key(&KeyId::Managed {
+                branch: script_key.branch.clone(),
+                index: script_key.index,
+            })
+            .await
+            .unwrap();
+        assert_ne!(script_offset, script_private_key);
+        let other_script_key = RemoteKey {
+            branch: TransactionKeyManagerBranch::ScriptKey.get_branch_key(),
+            index: 3,
+        };
+        assert!(connection
+            .get_script_offset(vec![other_script_key], vec![sender_offset_key])
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn it_makes_one_metadata_signature_per_nonce_and_sender_offset_key() {
+        let (daemon, _) = test_daemon();
+        let address = start_daemon(daemon).await;
+        let connection = RemoteSignerConnection::connect(&client_config(address, "client"))
+            .await
+            .unwrap();
+        let key = |branch: TransactionKeyManagerBranch, index| RemoteKey {
+            branch: branch.get_branch_key(),
+            index,
+        };
+        let nonce = key(TransactionKeyManagerBranch::Nonce, 1);
+        let other_nonce = key(TransactionKeyManagerBranch::Nonce, 2);
+        let sender_offset_key = key(TransactionKeyManagerBranch::SenderOffset, 1);
+        let other_sender_offset_key = key(TransactionKeyManagerBranch::SenderOffset, 2);
+        for key in [&nonce, &other_nonce, &sender_offset_key, &other_sender_offset_key] {
+            connection.get_public_key(key.clone()).await.unwrap();
+        }
+        let commitment = |k: &PrivateKey| Commitment::from_public_key(&PublicKey::from_secret_key(k));
+        let (a, _) = PublicKey::random_keypair(&mut OsRng);
+        let (b, _) = PublicKey::random_keypair(&mut OsRng);
+        let sign = |nonce: &RemoteKey, sender_offset_key: &RemoteKey| {
+            let connection = connection.clone();
+            let (nonce, sender_offset_key) = (nonce.clone(), sender_offset_key.clone());
+            let (commitment, ephemeral_commitment) = (commitment(&a), commitment(&b));
+            async move {
+                connection
+                    .get_sender_metadata_signature(
+                        nonce,
+                        sender_offset_key,
+                        TransactionOutputVersion::get_current_version(),
+                        &commitment,
+                        &ephemeral_commitment,
+                        &[1u8; 32],
+                    )
+                    .await
+            }
+        };
+
+        sign(&nonce, &sender_offset_key).await.unwrap();
+        // Two signatures with the same nonce, or with the same sender offset key, could reveal the sender offset key
+        assert!(sign(&nonce, &other_sender_offset_key).await.is_err());
+        assert!(sign(&other_nonce, &sender_offset_key).await.is_err());
+        sign(&other_nonce, &other_sender_offset_key).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_derives_one_sided_shared_secrets_with_the_daemon_sender_offset_key() {
+        let (daemon, _) = test_daemon();
+        let address = start_daemon(daemon).await;
+        let client = connect_client(address).await;
+        let (recipient_private_key, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+
+        let (sender_offset_key_id, sender_offset_public_key) = client
+            .get_next_key(TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+            .await
+            .unwrap();
+        let shared_secret = client
+            .get_diffie_hellman_shared_secret(&sender_offset_key_id, &recipient_public_key)
+            .await
+            .unwrap();
+
+        // The recipient derives the same secret from the sender offset public key published in the output
+        let recipient_shared_secret = CommsDHKE::new(&recipient_private_key, &sender_offset_public_key);
+        assert_eq!(shared_secret.as_bytes(), recipient_shared_secret.as_bytes());
+        let stealth_hasher = client
+            .get_diffie_hellman_stealth_domain_hasher(&sender_offset_key_id, &recipient_public_key)
+            .await
+            .unwrap();
+        assert_eq!(
+            stealth_hasher.as_ref(),
+            diffie_hellman_stealth_domain_hasher(&recipient_private_key, &sender_offset_public_key).as_ref()
+        );
+    }
+}
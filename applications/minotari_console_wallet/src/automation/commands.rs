@@ -37,11 +37,17 @@ use log::*;
 use minotari_wallet::{
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
-    transaction_service::handle::{TransactionEvent, TransactionServiceHandle},
+    transaction_service::{
+        config::TransactionRoutingMechanism,
+        handle::{TransactionEvent, TransactionServiceHandle},
+        offline_signing::{SignedTransaction, UnsignedTransaction},
+    },
+    util::reauthentication::SensitiveOperation,
     TransactionStage,
     WalletConfig,
     WalletSqlite,
 };
+use rpassword::prompt_password_stdout;
 use serde::{de::DeserializeOwned, Serialize};
 use sha2::Sha256;
 use strum_macros::{Display, EnumIter, EnumString};
@@ -63,7 +69,8 @@ use tari_core::transactions::{
     transaction_components::{OutputFeatures, TransactionOutput, WalletOutput},
 };
 use tari_crypto::ristretto::RistrettoSecretKey;
-use tari_utilities::{hex::Hex, ByteArray};
+use tari_key_manager::mnemonic::MnemonicLanguage;
+use tari_utilities::{hex::Hex, ByteArray, SafePassword};
 use tokio::{
     sync::{broadcast, mpsc},
     time::{sleep, timeout},
@@ -113,18 +120,35 @@ pub async fn send_tari(
     amount: MicroMinotari,
     destination: TariAddress,
     message: String,
+    routing: Option<TransactionRoutingMechanism>,
 ) -> Result<TxId, CommandError> {
-    wallet_transaction_service
-        .send_transaction(
-            destination,
-            amount,
-            UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            fee_per_gram * uT,
-            message,
-        )
-        .await
-        .map_err(CommandError::TransactionServiceError)
+    match routing {
+        Some(routing) => {
+            wallet_transaction_service
+                .send_transaction_with_routing(
+                    destination,
+                    amount,
+                    UtxoSelectionCriteria::default(),
+                    fee_per_gram * uT,
+                    message,
+                    routing,
+                )
+                .await
+        },
+        None => {
+            wallet_transaction_service
+                .send_transaction(
+                    destination,
+                    amount,
+                    UtxoSelectionCriteria::default(),
+                    OutputFeatures::default(),
+                    fee_per_gram * uT,
+                    message,
+                )
+                .await
+        },
+    }
+    .map_err(CommandError::TransactionServiceError)
 }
 
 pub async fn burn_tari(
@@ -418,7 +442,7 @@ pub async fn make_it_rain(
                     // Send transaction
                     let tx_id = match transaction_type {
                         MakeItRainTransactionType::Interactive => {
-                            send_tari(tx_service, fee, amount, address.clone(), msg.clone()).await
+                            send_tari(tx_service, fee, amount, address.clone(), msg.clone(), None).await
                         },
                         MakeItRainTransactionType::OneSided => {
                             send_one_sided(
@@ -683,6 +707,7 @@ pub async fn command_runner(
                     args.amount,
                     args.destination,
                     args.message,
+                    args.routing,
                 )
                 .await
                 {
@@ -978,6 +1003,173 @@ pub async fn command_runner(
                 debug!(target: LOG_TARGET, "Registering VN tx_id {}", tx_id);
                 tx_ids.push(tx_id);
             },
+            ExportDerivationScheme(args) => match wallet.export_derivation_scheme().await {
+                Ok(export) => {
+                    if let Some(file) = args.output_file {
+                        if let Err(e) = write_json_file(&file, &export) {
+                            eprintln!("ExportDerivationScheme error! {}", e);
+                        } else {
+                            println!("Derivation scheme written to {}", file.display());
+                        }
+                    } else {
+                        match export.to_json() {
+                            Ok(json) => println!("{}", json),
+                            Err(e) => eprintln!("ExportDerivationScheme error! {}", e),
+                        }
+                    }
+                },
+                Err(e) => eprintln!("ExportDerivationScheme error! {}", e),
+            },
+            ExportSeedShares(args) => {
+                let passphrase = match prompt_reauthentication_passphrase(&wallet, SensitiveOperation::SeedExport) {
+                    Ok(passphrase) => passphrase,
+                    Err(e) => {
+                        eprintln!("ExportSeedShares error! {}", e);
+                        continue;
+                    },
+                };
+                match wallet
+                    .export_seed_shares(args.threshold, args.shares, &MnemonicLanguage::English, passphrase)
+                    .await
+                {
+                    Ok(shares) => {
+                        println!(
+                            "Any {} of these {} shares recover the wallet, keep each one in a separate place:",
+                            args.threshold, args.shares
+                        );
+                        for (i, share) in shares.iter().enumerate() {
+                            println!();
+                            println!("Share {}: {}", i + 1, share.join(" ").reveal());
+                        }
+                    },
+                    Err(e) => eprintln!("ExportSeedShares error! {}", e),
+                }
+            },
+            CreateBackup(args) => {
+                let passphrase = match prompt_reauthentication_passphrase(&wallet, SensitiveOperation::BackupCreation) {
+                    Ok(passphrase) => passphrase,
+                    Err(e) => {
+                        eprintln!("CreateBackup error! {}", e);
+                        continue;
+                    },
+                };
+                match wallet.create_backup(&args.output_file, passphrase).await {
+                    Ok(()) => println!("Wallet backup written to {}", args.output_file.display()),
+                    Err(e) => eprintln!("CreateBackup error! {}", e),
+                }
+            },
+            CreateSendTemplate(args) => match transaction_service
+                .create_send_template(
+                    args.name.clone(),
+                    args.destination,
+                    args.amount,
+                    args.message,
+                    args.fee_priority,
+                    args.one_sided,
+                )
+                .await
+            {
+                Ok(id) => println!("Created send template '{}' with id {}", args.name, id),
+                Err(e) => eprintln!("CreateSendTemplate error! {}", e),
+            },
+            ListSendTemplates => match transaction_service.get_send_templates().await {
+                Ok(templates) => {
+                    println!("{} send template(s):", templates.len());
+                    for t in templates {
+                        println!(
+                            "{}: '{}' to {}, amount {}, fee priority {}, {}, message '{}'",
+                            t.id,
+                            t.name,
+                            t.destination,
+                            t.default_amount
+                                .map(|a| a.to_string())
+                                .unwrap_or_else(|| "<none>".to_string()),
+                            t.fee_priority,
+                            if t.one_sided { "one-sided" } else { "interactive" },
+                            t.message
+                        );
+                    }
+                },
+                Err(e) => eprintln!("ListSendTemplates error! {}", e),
+            },
+            DeleteSendTemplate(args) => match transaction_service.delete_send_template(args.id).await {
+                Ok(()) => println!("Deleted send template {}", args.id),
+                Err(e) => eprintln!("DeleteSendTemplate error! {}", e),
+            },
+            SendFromTemplate(args) => match transaction_service.send_from_template(args.id, args.amount).await {
+                Ok(tx_id) => {
+                    debug!(target: LOG_TARGET, "send-from-template concluded with tx_id {}", tx_id);
+                    tx_ids.push(tx_id);
+                },
+                Err(e) => eprintln!("SendFromTemplate error! {}", e),
+            },
+            CreateUnsignedTransaction(args) => match transaction_service
+                .create_unsigned_transaction(
+                    args.destination,
+                    args.amount,
+                    UtxoSelectionCriteria::default(),
+                    config.fee_per_gram.into(),
+                    args.message,
+                )
+                .await
+            {
+                Ok(unsigned) => match write_offline_transaction_file(&args.output_file, unsigned.to_bytes()) {
+                    Ok(()) => println!(
+                        "Unsigned transaction {} written to {}",
+                        unsigned.tx_id,
+                        args.output_file.display()
+                    ),
+                    Err(e) => {
+                        eprintln!("CreateUnsignedTransaction error! {}", e);
+                        if let Err(e) = transaction_service.cancel_offline_transaction(unsigned.tx_id).await {
+                            eprintln!("CreateUnsignedTransaction error! {}", e);
+                        }
+                    },
+                },
+                Err(e) => eprintln!("CreateUnsignedTransaction error! {}", e),
+            },
+            SignUnsignedTransaction(args) => {
+                let unsigned = match fs::read(&args.input_file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| UnsignedTransaction::from_bytes(&bytes).map_err(|e| e.to_string()))
+                {
+                    Ok(unsigned) => unsigned,
+                    Err(e) => {
+                        eprintln!("SignUnsignedTransaction error! {}", e);
+                        continue;
+                    },
+                };
+                match transaction_service.sign_unsigned_transaction(unsigned).await {
+                    Ok(signed) => match write_offline_transaction_file(&args.output_file, signed.to_bytes()) {
+                        Ok(()) => println!(
+                            "Signed transaction {} written to {}",
+                            signed.tx_id,
+                            args.output_file.display()
+                        ),
+                        Err(e) => eprintln!("SignUnsignedTransaction error! {}", e),
+                    },
+                    Err(e) => eprintln!("SignUnsignedTransaction error! {}", e),
+                }
+            },
+            ImportSignedTransaction(args) => {
+                let signed = match fs::read(&args.input_file)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| SignedTransaction::from_bytes(&bytes).map_err(|e| e.to_string()))
+                {
+                    Ok(signed) => signed,
+                    Err(e) => {
+                        eprintln!("ImportSignedTransaction error! {}", e);
+                        continue;
+                    },
+                };
+                match transaction_service.import_signed_transaction(signed).await {
+                    Ok(tx_id) => {
+                        debug!(target: LOG_TARGET, "import-signed-transaction concluded with tx_id {}", tx_id);
+                        tx_ids.push(tx_id);
+                    },
+                    Err(e) => eprintln!("ImportSignedTransaction error! {}", e),
+                }
+            },
         }
     }
 
@@ -1059,6 +1251,18 @@ fn write_utxos_to_csv_file(utxos: Vec<(WalletOutput, Commitment)>, file_path: Pa
     Ok(())
 }
 #[allow(dead_code)]
+/// Prompts for the wallet passphrase if the re-authentication policy requires it for `operation`
+fn prompt_reauthentication_passphrase(
+    wallet: &WalletSqlite,
+    operation: SensitiveOperation,
+) -> Result<Option<SafePassword>, CommandError> {
+    if !wallet.reauthentication.policy().requires_passphrase(operation) {
+        return Ok(None);
+    }
+    let passphrase = prompt_password_stdout("Wallet passphrase: ").map_err(|e| CommandError::General(e.to_string()))?;
+    Ok(Some(SafePassword::from(passphrase)))
+}
+
 fn write_json_file<P: AsRef<Path>, T: Serialize>(path: P, data: &T) -> Result<(), CommandError> {
     fs::create_dir_all(path.as_ref().parent().unwrap()).map_err(|e| CommandError::JsonFile(e.to_string()))?;
     let file = File::create(path).map_err(|e| CommandError::JsonFile(e.to_string()))?;
@@ -1066,6 +1270,11 @@ fn write_json_file<P: AsRef<Path>, T: Serialize>(path: P, data: &T) -> Result<()
     Ok(())
 }
 
+fn write_offline_transaction_file<E: ToString>(path: &Path, bytes: Result<Vec<u8>, E>) -> Result<(), String> {
+    let bytes = bytes.map_err(|e| e.to_string())?;
+    fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
 #[allow(dead_code)]
 fn read_json_file<P: AsRef<Path>, T: DeserializeOwned>(path: P) -> Result<T, CommandError> {
     let file = File::open(path).map_err(|e| CommandError::JsonFile(e.to_string()))?;
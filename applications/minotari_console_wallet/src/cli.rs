@@ -29,6 +29,7 @@ use std::{
 use chrono::{DateTime, Utc};
 use clap::{Args, Parser, Subcommand};
 use minotari_app_utilities::{common_cli_args::CommonCliArgs, utilities::UniPublicKey};
+use minotari_wallet::transaction_service::{config::TransactionRoutingMechanism, handle::FeePriority};
 use tari_common::configuration::{ConfigOverrideProvider, Network};
 use tari_common_types::tari_address::TariAddress;
 use tari_comms::multiaddr::Multiaddr;
@@ -52,16 +53,30 @@ pub struct Cli {
     /// possible.
     #[clap(long, env = "MINOTARI_WALLET_PASSWORD", hide_env_values = true)]
     pub password: Option<SafePassword>,
-    /// Change the password for the console wallet and exit
+    /// Change the password for the console wallet, re-encrypting its database with a new key, and exit
     #[clap(long, alias = "update-password")]
     pub change_password: bool,
+    /// Restore the wallet database from an encrypted backup snapshot before starting. The snapshot is checked before
+    /// the existing database is replaced; the replaced database is kept with a `.pre-restore` suffix.
+    #[clap(long, parse(from_os_str))]
+    pub restore_backup: Option<PathBuf>,
+    /// Check that any pending wallet database migrations apply cleanly, using a copy of the database, and exit
+    #[clap(long)]
+    pub dry_run: bool,
     /// Force wallet recovery
     #[clap(long, alias = "recover")]
     pub recovery: bool,
+    /// Scan from the genesis block during recovery instead of starting from the wallet birthday
+    #[clap(long)]
+    pub full_scan: bool,
     /// Supply the optional wallet seed words for recovery on the command line. They should be in one string space
     /// separated. e.g. --seed-words "seed1 seed2 ..."
     #[clap(long, alias = "seed-words")]
     pub seed_words: Option<SeedWords>,
+    /// Supply an optional seed passphrase, the "25th word", when creating or recovering a wallet. The same seed words
+    /// with a different seed passphrase give a different wallet.
+    #[clap(long, env = "MINOTARI_WALLET_SEED_PASSPHRASE", hide_env_values = true)]
+    pub seed_passphrase: Option<SafePassword>,
     /// Supply the optional file name to save the wallet seed words into
     #[clap(long, aliases = &["seed_words_file_name", "seed-words-file"], parse(from_os_str))]
     pub seed_words_file_name: Option<PathBuf>,
@@ -97,6 +112,7 @@ impl ConfigOverrideProvider for Cli {
         overrides.push(("wallet.network".to_string(), network.to_string()));
         overrides.push(("wallet.override_from".to_string(), network.to_string()));
         overrides.push(("p2p.seeds.override_from".to_string(), network.to_string()));
+        overrides.push(("metrics.override_from".to_string(), network.to_string()));
         // Either of these configs enable grpc
         if let Some(ref addr) = self.grpc_address {
             overrides.push(("wallet.grpc_enabled".to_string(), "true".to_string()));
@@ -133,6 +149,16 @@ pub enum CliCommands {
     ClaimShaAtomicSwapRefund(ClaimShaAtomicSwapRefundArgs),
     RevalidateWalletDb,
     RegisterValidatorNode(RegisterValidatorNodeArgs),
+    ExportDerivationScheme(ExportDerivationSchemeArgs),
+    ExportSeedShares(ExportSeedSharesArgs),
+    CreateBackup(CreateBackupArgs),
+    CreateSendTemplate(CreateSendTemplateArgs),
+    ListSendTemplates,
+    DeleteSendTemplate(SendTemplateIdArgs),
+    SendFromTemplate(SendFromTemplateArgs),
+    CreateUnsignedTransaction(CreateUnsignedTransactionArgs),
+    SignUnsignedTransaction(SignUnsignedTransactionArgs),
+    ImportSignedTransaction(ImportSignedTransactionArgs),
 }
 
 #[derive(Debug, Args, Clone)]
@@ -146,6 +172,10 @@ pub struct SendMinotariArgs {
     pub destination: TariAddress,
     #[clap(short, long, default_value = "<No message>")]
     pub message: String,
+    /// Overrides the configured routing for this transaction: `direct-only` fails immediately if the recipient cannot
+    /// be reached directly, `saf-only` sends via store-and-forward only. Only applies to interactive transactions.
+    #[clap(long, parse(try_from_str = parse_routing_mechanism))]
+    pub routing: Option<TransactionRoutingMechanism>,
 }
 
 #[derive(Debug, Args, Clone)]
@@ -240,6 +270,103 @@ pub struct ExportUtxosArgs {
     pub output_file: Option<PathBuf>,
 }
 
+#[derive(Debug, Args, Clone)]
+pub struct ExportDerivationSchemeArgs {
+    #[clap(short, long)]
+    pub output_file: Option<PathBuf>,
+}
+
+/// Splits the seed into shares of which any `threshold` recover the wallet, for backups without a single point of
+/// failure
+#[derive(Debug, Args, Clone)]
+pub struct ExportSeedSharesArgs {
+    /// The number of shares needed to recover the seed
+    pub threshold: u8,
+    /// The number of shares to create
+    pub shares: u8,
+}
+
+/// Writes an encrypted snapshot of the wallet database, restored with `--restore-backup`
+#[derive(Debug, Args, Clone)]
+pub struct CreateBackupArgs {
+    pub output_file: PathBuf,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct CreateSendTemplateArgs {
+    pub name: String,
+    pub destination: TariAddress,
+    /// The amount sent when no amount is given to send-from-template
+    #[clap(short, long)]
+    pub amount: Option<MicroMinotari>,
+    #[clap(short, long, default_value = "<No message>")]
+    pub message: String,
+    /// One of low, medium or high
+    #[clap(short, long, parse(try_from_str = parse_fee_priority), default_value = "medium")]
+    pub fee_priority: FeePriority,
+    #[clap(short, long)]
+    pub one_sided: bool,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SendTemplateIdArgs {
+    pub id: u64,
+}
+
+#[derive(Debug, Args, Clone)]
+pub struct SendFromTemplateArgs {
+    pub id: u64,
+    /// Overrides the template's default amount
+    #[clap(short, long)]
+    pub amount: Option<MicroMinotari>,
+}
+
+/// Selects the inputs of a one-sided transaction and writes it to a file to be signed by the offline wallet
+#[derive(Debug, Args, Clone)]
+pub struct CreateUnsignedTransactionArgs {
+    pub amount: MicroMinotari,
+    pub destination: TariAddress,
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+    #[clap(short, long, default_value = "<No message>")]
+    pub message: String,
+}
+
+/// Signs an unsigned transaction file on the offline wallet and writes the signed transaction to a file
+#[derive(Debug, Args, Clone)]
+pub struct SignUnsignedTransactionArgs {
+    pub input_file: PathBuf,
+    #[clap(short, long)]
+    pub output_file: PathBuf,
+}
+
+/// Checks and broadcasts a transaction signed by the offline wallet
+#[derive(Debug, Args, Clone)]
+pub struct ImportSignedTransactionArgs {
+    pub input_file: PathBuf,
+}
+
+fn parse_fee_priority(arg: &str) -> Result<FeePriority, String> {
+    match arg.to_lowercase().as_str() {
+        "low" => Ok(FeePriority::Low),
+        "medium" => Ok(FeePriority::Medium),
+        "high" => Ok(FeePriority::High),
+        _ => Err(format!("Invalid fee priority '{}', expected low, medium or high", arg)),
+    }
+}
+
+fn parse_routing_mechanism(arg: &str) -> Result<TransactionRoutingMechanism, String> {
+    match arg.to_lowercase().as_str() {
+        "direct-only" => Ok(TransactionRoutingMechanism::DirectOnly),
+        "saf-only" => Ok(TransactionRoutingMechanism::StoreAndForwardOnly),
+        "direct-and-saf" => Ok(TransactionRoutingMechanism::DirectAndStoreAndForward),
+        _ => Err(format!(
+            "Invalid routing '{}', expected direct-only, saf-only or direct-and-saf",
+            arg
+        )),
+    }
+}
+
 #[derive(Debug, Args, Clone)]
 pub struct SetBaseNodeArgs {
     pub public_key: UniPublicKey,
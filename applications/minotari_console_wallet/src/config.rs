@@ -28,12 +28,17 @@ use minotari_wallet::WalletConfig;
 use tari_common::{configuration::CommonConfig, ConfigurationError, DefaultConfigLoader};
 use tari_p2p::{auto_update::AutoUpdateConfig, PeerSeedsConfig};
 
+#[cfg(feature = "metrics")]
+use crate::metrics::MetricsConfig;
+
 #[derive(Clone, Debug)]
 pub struct ApplicationConfig {
     pub common: CommonConfig,
     pub auto_update: AutoUpdateConfig,
     pub wallet: WalletConfig,
     pub peer_seeds: PeerSeedsConfig,
+    #[cfg(feature = "metrics")]
+    pub metrics: MetricsConfig,
 }
 
 impl ApplicationConfig {
@@ -43,6 +48,8 @@ impl ApplicationConfig {
             auto_update: AutoUpdateConfig::load_from(cfg)?,
             wallet: WalletConfig::load_from(cfg)?,
             peer_seeds: PeerSeedsConfig::load_from(cfg)?,
+            #[cfg(feature = "metrics")]
+            metrics: MetricsConfig::load_from(cfg)?,
         };
 
         config.wallet.p2p.user_agent = format!("tari/wallet/{}", consts::APP_VERSION_NUMBER);
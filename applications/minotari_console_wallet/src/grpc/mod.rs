@@ -1,14 +1,17 @@
 // Copyright 2022 The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
+mod recovery;
 mod wallet_grpc_server;
 
-use minotari_app_grpc::tari_rpc::TransactionEvent;
-use minotari_wallet::transaction_service::storage::models::{
-    CompletedTransaction,
-    InboundTransaction,
-    OutboundTransaction,
+use std::fmt::Display;
+
+use minotari_app_grpc::tari_rpc::{TransactionEvent, TransferResult};
+use minotari_wallet::{
+    error::{ErrorCategory, ErrorHint, RetryHint},
+    transaction_service::storage::models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
 };
+use tonic::{metadata::MetadataMap, Code, Status};
 
 pub use self::wallet_grpc_server::*;
 
@@ -30,6 +33,7 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             amount: completed.amount.as_u64(),
             message: completed.message.to_string(),
             is_coinbase: completed.is_coinbase(),
+            counterparty_alias: String::new(),
         },
         TransactionWrapper::Outbound(outbound) => TransactionEvent {
             event,
@@ -41,6 +45,7 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             amount: outbound.amount.as_u64(),
             message: outbound.message,
             is_coinbase: false,
+            counterparty_alias: String::new(),
         },
         TransactionWrapper::Inbound(inbound) => TransactionEvent {
             event,
@@ -55,6 +60,51 @@ pub fn convert_to_transaction_event(event: String, source: TransactionWrapper) -
             // To determine whether a transaction is coinbase
             // we will check whether the message contains `Coinbase`.
             is_coinbase: inbound.message.to_lowercase().contains("coinbase"),
+            counterparty_alias: String::new(),
+        },
+    }
+}
+
+/// Converts a wallet service error into a gRPC status. The error category, code and retry hint are added to the
+/// status metadata as `x-wallet-error-category`, `x-wallet-error-code` and `x-wallet-retry-hint`, so clients can
+/// decide whether to retry without parsing the message.
+pub fn wallet_error_status<E: ErrorHint + Display>(err: &E) -> Status {
+    let category = err.category();
+    let code = match category {
+        ErrorCategory::InvalidRequest => Code::InvalidArgument,
+        ErrorCategory::InsufficientFunds => Code::FailedPrecondition,
+        ErrorCategory::NotFound => Code::NotFound,
+        ErrorCategory::Connectivity | ErrorCategory::NotReady | ErrorCategory::Shutdown => Code::Unavailable,
+        ErrorCategory::Storage | ErrorCategory::Internal => Code::Internal,
+    };
+    let mut metadata = MetadataMap::new();
+    let entries = [
+        ("x-wallet-error-category", category.to_string()),
+        ("x-wallet-error-code", err.error_code().to_string()),
+        ("x-wallet-retry-hint", err.retry_hint().to_string()),
+    ];
+    for (key, value) in entries {
+        if let Ok(value) = value.parse() {
+            metadata.insert(key, value);
+        }
+    }
+    Status::with_metadata(code, err.to_string(), metadata)
+}
+
+/// Builds the result for a transfer that failed with the given error
+pub fn failed_transfer_result<E: ErrorHint + Display>(address: String, err: &E) -> TransferResult {
+    let retry_hint = err.retry_hint();
+    TransferResult {
+        address,
+        transaction_id: Default::default(),
+        is_success: false,
+        failure_message: err.to_string(),
+        error_code: err.error_code(),
+        error_category: err.category().to_string(),
+        is_retryable: retry_hint.is_retryable(),
+        retry_at_height: match retry_hint {
+            RetryHint::RetryAtHeight(height) => height,
+            _ => 0,
         },
     }
 }
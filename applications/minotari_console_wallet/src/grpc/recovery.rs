@@ -0,0 +1,320 @@
+// Copyright 2023 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::sync::{Arc, Mutex};
+
+use log::*;
+use minotari_app_grpc::tari_rpc::{RecoveryProgressEvent, RecoveryStatus};
+use minotari_wallet::{
+    connectivity_service::WalletConnectivityHandle,
+    storage::sqlite_db::wallet::WalletSqliteDatabase,
+    utxo_scanner_service::{
+        handle::UtxoScannerEvent,
+        service::UtxoScannerService,
+        throughput::ScanThroughput,
+        uxto_scanner_service_builder::UtxoScannerMode,
+    },
+    WalletSqlite,
+};
+use tari_comms::types::CommsPublicKey;
+use tari_shutdown::Shutdown;
+use tokio::{sync::broadcast, task};
+
+const LOG_TARGET: &str = "wallet::ui::grpc::recovery";
+
+/// Runs wallet recoveries requested over gRPC and publishes their progress to any number of subscribers
+#[derive(Clone)]
+pub struct GrpcRecovery {
+    running: Arc<Mutex<Option<(u64, Shutdown)>>>,
+    progress_publisher: broadcast::Sender<RecoveryProgressEvent>,
+}
+
+impl Default for GrpcRecovery {
+    fn default() -> Self {
+        let (progress_publisher, _) = broadcast::channel(100);
+        Self {
+            running: Arc::new(Mutex::new(None)),
+            progress_publisher,
+        }
+    }
+}
+
+impl GrpcRecovery {
+    pub fn subscribe(&self) -> broadcast::Receiver<RecoveryProgressEvent> {
+        self.progress_publisher.subscribe()
+    }
+
+    /// Starts a recovery from the given base nodes. Returns false if a recovery is already running.
+    pub fn start(&self, wallet: &WalletSqlite, peers: Vec<CommsPublicKey>, retry_limit: usize) -> bool {
+        let mut running = self.running.lock().expect("recovery lock poisoned");
+        if running.is_some() {
+            return false;
+        }
+        let shutdown = Shutdown::new();
+        let mut recovery_task = UtxoScannerService::<WalletSqliteDatabase, WalletConnectivityHandle>::builder()
+            .with_peers(peers)
+            .with_retry_limit(retry_limit)
+            .with_mode(UtxoScannerMode::Recovery)
+            .build_with_wallet(wallet, shutdown.to_signal());
+        let event_stream = recovery_task.get_event_receiver();
+
+        let id = rand::random();
+        *running = Some((id, shutdown));
+        task::spawn(recovery_task.run());
+        task::spawn(self.clone().publish_progress(id, event_stream));
+        true
+    }
+
+    /// Stops the running recovery. Returns false if no recovery was running.
+    pub fn stop(&self) -> bool {
+        match self.running.lock().expect("recovery lock poisoned").take() {
+            Some((_, mut shutdown)) => {
+                shutdown.trigger();
+                let _size = self.progress_publisher.send(RecoveryProgressEvent {
+                    status: RecoveryStatus::Stopped.into(),
+                    ..Default::default()
+                });
+                true
+            },
+            None => false,
+        }
+    }
+
+    async fn publish_progress(self, id: u64, mut event_stream: broadcast::Receiver<UtxoScannerEvent>) {
+        let mut throughput = ScanThroughput::default();
+        let mut summary = None;
+        loop {
+            let event = match event_stream.recv().await {
+                Ok(UtxoScannerEvent::ConnectingToBaseNode(peer)) => RecoveryProgressEvent {
+                    status: RecoveryStatus::Connecting.into(),
+                    message: format!("Connecting to base node {}", peer),
+                    ..Default::default()
+                },
+                Ok(UtxoScannerEvent::ConnectedToBaseNode(peer, latency)) => RecoveryProgressEvent {
+                    status: RecoveryStatus::InProgress.into(),
+                    message: format!("Connected to base node {} (latency = {:.2?})", peer, latency),
+                    ..Default::default()
+                },
+                Ok(UtxoScannerEvent::Progress {
+                    current_height,
+                    tip_height,
+                    value_recovered,
+                    throughput: latest,
+                }) => {
+                    throughput = latest;
+                    RecoveryProgressEvent {
+                        status: RecoveryStatus::InProgress.into(),
+                        current_height,
+                        tip_height,
+                        value_recovered: value_recovered.as_u64(),
+                        eta_seconds: throughput.eta.map(|d| d.as_secs()).unwrap_or_default(),
+                        blocks_per_second: throughput.blocks_per_second,
+                        outputs_per_second: throughput.outputs_per_second,
+                        ..Default::default()
+                    }
+                },
+                Ok(UtxoScannerEvent::BatchScanned { batch, totals }) => RecoveryProgressEvent {
+                    status: RecoveryStatus::InProgress.into(),
+                    current_height: batch.current_height,
+                    tip_height: batch.tip_height,
+                    num_recovered: totals.num_found,
+                    value_recovered: totals.value_recovered.as_u64(),
+                    // The scanning rate is only reported with progress events, so this is the latest estimate
+                    eta_seconds: throughput.eta.map(|d| d.as_secs()).unwrap_or_default(),
+                    blocks_per_second: throughput.blocks_per_second,
+                    outputs_per_second: throughput.outputs_per_second,
+                    blocks_scanned: totals.num_blocks,
+                    outputs_scanned: totals.num_outputs,
+                    rpc_latency_ms: u64::try_from(batch.rpc_latency.as_millis()).unwrap_or(u64::MAX),
+                    ..Default::default()
+                },
+                // Kept for the completion event that follows
+                Ok(UtxoScannerEvent::Summary(stats)) => {
+                    summary = Some(stats);
+                    continue;
+                },
+                Ok(UtxoScannerEvent::ConnectionFailedToBaseNode {
+                    peer,
+                    num_retries,
+                    retry_limit,
+                    error,
+                }) => RecoveryProgressEvent {
+                    status: RecoveryStatus::Retrying.into(),
+                    message: format!(
+                        "Base node connection error to {} (retries {} of {}: {})",
+                        peer, num_retries, retry_limit, error
+                    ),
+                    ..Default::default()
+                },
+                Ok(UtxoScannerEvent::ScanningRoundFailed {
+                    num_retries,
+                    retry_limit,
+                    error,
+                }) => RecoveryProgressEvent {
+                    status: RecoveryStatus::Retrying.into(),
+                    message: format!(
+                        "Attempt {}/{}: Failed to complete wallet recovery {}",
+                        num_retries, retry_limit, error
+                    ),
+                    ..Default::default()
+                },
+                Ok(UtxoScannerEvent::Completed {
+                    final_height,
+                    num_recovered,
+                    value_recovered,
+                    time_taken,
+                }) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Recovery complete! Scanned {} blocks in {:.2?}, recovered {} outputs worth {}",
+                        final_height,
+                        time_taken,
+                        num_recovered,
+                        value_recovered
+                    );
+                    let summary = summary.take().unwrap_or_default();
+                    self.finish(id, RecoveryProgressEvent {
+                        status: RecoveryStatus::Completed.into(),
+                        current_height: final_height,
+                        tip_height: final_height,
+                        num_recovered,
+                        value_recovered: value_recovered.as_u64(),
+                        blocks_scanned: summary.num_blocks,
+                        outputs_scanned: summary.num_outputs,
+                        rpc_latency_ms: u64::try_from(summary.average_rpc_latency().as_millis()).unwrap_or(u64::MAX),
+                        ..Default::default()
+                    });
+                    break;
+                },
+                Ok(UtxoScannerEvent::ScanningFailed) => {
+                    self.finish(id, RecoveryProgressEvent {
+                        status: RecoveryStatus::Failed.into(),
+                        message: "Recovery failed, all retries have been exhausted".to_string(),
+                        ..Default::default()
+                    });
+                    break;
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    warn!(target: LOG_TARGET, "Missed {} recovery events", n);
+                    continue;
+                },
+                // The recovery task has exited, either because it was stopped or because it failed to start
+                Err(broadcast::error::RecvError::Closed) => {
+                    self.finish(id, RecoveryProgressEvent {
+                        status: RecoveryStatus::Stopped.into(),
+                        ..Default::default()
+                    });
+                    break;
+                },
+            };
+            let _size = self.progress_publisher.send(event);
+        }
+    }
+
+    /// Publishes the final event of the recovery with the given id, unless it has already been stopped
+    fn finish(&self, id: u64, event: RecoveryProgressEvent) {
+        let mut running = self.running.lock().expect("recovery lock poisoned");
+        if running.as_ref().map(|(running_id, _)| *running_id) == Some(id) {
+            *running = None;
+            let _size = self.progress_publisher.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use minotari_wallet::utxo_scanner_service::handle::ScanStats;
+    use tari_comms::peer_manager::NodeId;
+    use tari_core::transactions::tari_amount::MicroMinotari;
+    use tokio::time::timeout;
+
+    use super::*;
+
+    fn start_publishing(recovery: &GrpcRecovery) -> broadcast::Sender<UtxoScannerEvent> {
+        let (event_publisher, event_stream) = broadcast::channel(10);
+        *recovery.running.lock().unwrap() = Some((1, Shutdown::new()));
+        task::spawn(recovery.clone().publish_progress(1, event_stream));
+        event_publisher
+    }
+
+    #[tokio::test]
+    async fn it_publishes_progress_until_completed() {
+        let recovery = GrpcRecovery::default();
+        let mut progress = recovery.subscribe();
+        let events = start_publishing(&recovery);
+
+        events
+            .send(UtxoScannerEvent::ConnectingToBaseNode(NodeId::default()))
+            .unwrap();
+        events
+            .send(UtxoScannerEvent::Progress {
+                current_height: 10,
+                tip_height: 100,
+                value_recovered: MicroMinotari(500),
+                throughput: ScanThroughput {
+                    blocks_per_second: 2.5,
+                    outputs_per_second: 5.0,
+                    eta: Some(Duration::from_secs(36)),
+                },
+            })
+            .unwrap();
+        events
+            .send(UtxoScannerEvent::Summary(ScanStats {
+                num_blocks: 100,
+                num_outputs: 200,
+                num_batches: 4,
+                total_rpc_latency: Duration::from_millis(400),
+                ..Default::default()
+            }))
+            .unwrap();
+        events
+            .send(UtxoScannerEvent::Completed {
+                final_height: 100,
+                num_recovered: 3,
+                value_recovered: MicroMinotari(700),
+                time_taken: Duration::from_secs(40),
+            })
+            .unwrap();
+
+        let event = progress.recv().await.unwrap();
+        assert_eq!(event.status, i32::from(RecoveryStatus::Connecting));
+
+        let event = progress.recv().await.unwrap();
+        assert_eq!(event.status, i32::from(RecoveryStatus::InProgress));
+        assert_eq!(event.current_height, 10);
+        assert_eq!(event.tip_height, 100);
+        assert_eq!(event.value_recovered, 500);
+        assert_eq!(event.eta_seconds, 36);
+        assert!((event.blocks_per_second - 2.5).abs() < f64::EPSILON);
+
+        let event = progress.recv().await.unwrap();
+        assert_eq!(event.status, i32::from(RecoveryStatus::Completed));
+        assert_eq!(event.current_height, 100);
+        assert_eq!(event.num_recovered, 3);
+        assert_eq!(event.value_recovered, 700);
+        assert_eq!(event.blocks_scanned, 100);
+        assert_eq!(event.outputs_scanned, 200);
+        assert_eq!(event.rpc_latency_ms, 100);
+
+        assert!(recovery.running.lock().unwrap().is_none());
+        assert!(!recovery.stop());
+    }
+
+    #[tokio::test]
+    async fn it_publishes_a_single_event_when_stopped() {
+        let recovery = GrpcRecovery::default();
+        let mut progress = recovery.subscribe();
+        let events = start_publishing(&recovery);
+
+        assert!(recovery.stop());
+        let event = progress.recv().await.unwrap();
+        assert_eq!(event.status, i32::from(RecoveryStatus::Stopped));
+
+        // The scanner exiting after being stopped must not publish a second stopped event
+        drop(events);
+        assert!(timeout(Duration::from_millis(100), progress.recv()).await.is_err());
+        assert!(!recovery.stop());
+    }
+}
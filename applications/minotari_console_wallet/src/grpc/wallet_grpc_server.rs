@@ -30,8 +30,12 @@ use futures::{
 use log::*;
 use minotari_app_grpc::tari_rpc::{
     self,
+    get_completed_transactions_request,
+    get_transaction_info_request,
     payment_recipient::PaymentType,
     wallet_server,
+    BurnProof,
+    BurnStatus,
     CheckConnectivityResponse,
     ClaimHtlcRefundRequest,
     ClaimHtlcRefundResponse,
@@ -44,14 +48,24 @@ use minotari_app_grpc::tari_rpc::{
     CreateBurnTransactionResponse,
     CreateTemplateRegistrationRequest,
     CreateTemplateRegistrationResponse,
+    CreateUnsignedTransactionRequest,
+    CreateUnsignedTransactionResponse,
+    FreezeOutputsRequest,
+    FreezeOutputsResponse,
     GetAddressResponse,
     GetBalanceRequest,
     GetBalanceResponse,
+    GetBurnProofsRequest,
+    GetBurnProofsResponse,
     GetCoinbaseRequest,
     GetCoinbaseResponse,
     GetCompletedTransactionsRequest,
     GetCompletedTransactionsResponse,
     GetConnectivityRequest,
+    GetFeeBreakdownRequest,
+    GetFeeBreakdownResponse,
+    GetHistoricalBalanceRequest,
+    GetHistoricalBalanceResponse,
     GetIdentityRequest,
     GetIdentityResponse,
     GetTransactionInfoRequest,
@@ -59,8 +73,13 @@ use minotari_app_grpc::tari_rpc::{
     GetUnspentAmountsResponse,
     GetVersionRequest,
     GetVersionResponse,
+    ImportSignedTransactionRequest,
+    ImportSignedTransactionResponse,
     ImportUtxosRequest,
     ImportUtxosResponse,
+    MarkBurnClaimedRequest,
+    MarkBurnClaimedResponse,
+    RecoveryProgressEvent,
     RegisterValidatorNodeRequest,
     RegisterValidatorNodeResponse,
     RevalidateRequest,
@@ -69,22 +88,32 @@ use minotari_app_grpc::tari_rpc::{
     SendShaAtomicSwapResponse,
     SetBaseNodeRequest,
     SetBaseNodeResponse,
+    SignUnsignedTransactionRequest,
+    SignUnsignedTransactionResponse,
+    StartRecoveryRequest,
+    StartRecoveryResponse,
+    StopRecoveryResponse,
     TransactionDirection,
     TransactionEvent,
     TransactionEventRequest,
     TransactionEventResponse,
     TransactionInfo,
+    TransactionMempoolState,
     TransactionStatus,
     TransferRequest,
     TransferResponse,
     TransferResult,
+    UnfreezeOutputsRequest,
+    UnfreezeOutputsResponse,
 };
 use minotari_wallet::{
     connectivity_service::{OnlineStatus, WalletConnectivityInterface},
     error::WalletStorageError,
     output_manager_service::{handle::OutputManagerHandle, UtxoSelectionCriteria},
     transaction_service::{
-        handle::TransactionServiceHandle,
+        error::TransactionServiceError,
+        handle::{MempoolTransactionState, ScheduledPayment, SendOrQueueResult, TransactionServiceHandle},
+        offline_signing::{SignedTransaction, UnsignedTransaction},
         storage::models::{self, WalletTransaction},
     },
     WalletSqlite,
@@ -92,33 +121,43 @@ use minotari_wallet::{
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::TxId,
-    types::{BlockHash, PublicKey, Signature},
+    types::{BlockHash, Commitment, PublicKey, Signature},
 };
 use tari_comms::{multiaddr::Multiaddr, types::CommsPublicKey, CommsNode};
 use tari_core::{
     consensus::{ConsensusBuilderError, ConsensusConstants, ConsensusManager},
     transactions::{
+        fee::Fee,
         tari_amount::{MicroMinotari, T},
         transaction_components::{
             CodeTemplateRegistration,
             OutputFeatures,
             OutputType,
             SideChainFeature,
+            Transaction,
             UnblindedOutput,
         },
     },
 };
 use tari_script::script;
-use tari_utilities::{hex::Hex, ByteArray};
+use tari_utilities::{hex::Hex, ByteArray, SafePassword};
 use tokio::{sync::broadcast, task};
 use tonic::{Request, Response, Status};
 
 use crate::{
-    grpc::{convert_to_transaction_event, TransactionWrapper},
+    grpc::{
+        convert_to_transaction_event,
+        failed_transfer_result,
+        recovery::GrpcRecovery,
+        wallet_error_status,
+        TransactionWrapper,
+    },
     notifier::{CANCELLED, CONFIRMATION, MINED, NEW_BLOCK_MINED, QUEUED, RECEIVED, SENT},
 };
 
 const LOG_TARGET: &str = "wallet::ui::grpc";
+/// Request metadata key used to supply the wallet passphrase for operations that require re-authentication
+const PASSPHRASE_METADATA_KEY: &str = "x-wallet-passphrase";
 
 async fn send_transaction_event(
     transaction_event: TransactionEvent,
@@ -138,22 +177,80 @@ async fn send_transaction_event(
 pub struct WalletGrpcServer {
     wallet: WalletSqlite,
     rules: ConsensusManager,
+    recovery: GrpcRecovery,
 }
 
 impl WalletGrpcServer {
     pub fn new(wallet: WalletSqlite) -> Result<Self, ConsensusBuilderError> {
         let rules = ConsensusManager::builder(wallet.network.as_network()).build()?;
-        Ok(Self { wallet, rules })
+        Ok(Self {
+            wallet,
+            rules,
+            recovery: GrpcRecovery::default(),
+        })
     }
 
     fn get_transaction_service(&self) -> TransactionServiceHandle {
         self.wallet.transaction_service.clone()
     }
 
+    /// Returns the transaction service handle, supplying the wallet passphrase from the request metadata if present
+    fn get_authorized_transaction_service<T>(&self, request: &Request<T>) -> TransactionServiceHandle {
+        match request
+            .metadata()
+            .get(PASSPHRASE_METADATA_KEY)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(passphrase) => self
+                .wallet
+                .transaction_service
+                .with_passphrase(SafePassword::from(passphrase.to_string())),
+            None => self.get_transaction_service(),
+        }
+    }
+
     fn get_output_manager_service(&self) -> OutputManagerHandle {
         self.wallet.output_manager_service.clone()
     }
 
+    /// Returns the output manager handle carrying the passphrase the caller supplied for re-authentication, if any
+    fn get_authorized_output_manager_service<T>(&self, request: &Request<T>) -> OutputManagerHandle {
+        match request
+            .metadata()
+            .get(PASSPHRASE_METADATA_KEY)
+            .and_then(|v| v.to_str().ok())
+        {
+            Some(passphrase) => self
+                .wallet
+                .output_manager_service
+                .with_passphrase(SafePassword::from(passphrase.to_string())),
+            None => self.get_output_manager_service(),
+        }
+    }
+
+    /// Returns the confirmation threshold requested by a caller together with the chain tip height that confirmations
+    /// are counted from, or `None` when the wallet's configured threshold applies
+    async fn get_confirmation_threshold(
+        &self,
+        confirmations_required: Option<u64>,
+    ) -> Result<Option<ConfirmationThreshold>, Status> {
+        let Some(num_confirmations_required) = confirmations_required else {
+            return Ok(None);
+        };
+        let tip_height = self
+            .wallet
+            .base_node_service
+            .clone()
+            .get_chain_metadata()
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?
+            .map(|metadata| metadata.height_of_longest_chain());
+        Ok(Some(ConfirmationThreshold {
+            num_confirmations_required,
+            tip_height,
+        }))
+    }
+
     fn comms(&self) -> &CommsNode {
         &self.wallet.comms
     }
@@ -174,6 +271,7 @@ impl WalletGrpcServer {
 #[tonic::async_trait]
 impl wallet_server::Wallet for WalletGrpcServer {
     type GetCompletedTransactionsStream = mpsc::Receiver<Result<GetCompletedTransactionsResponse, Status>>;
+    type StreamRecoveryProgressStream = mpsc::Receiver<Result<RecoveryProgressEvent, Status>>;
     type StreamTransactionEventsStream = mpsc::Receiver<Result<TransactionEventResponse, Status>>;
 
     async fn get_version(&self, _: Request<GetVersionRequest>) -> Result<Response<GetVersionResponse>, Status> {
@@ -274,6 +372,27 @@ impl wallet_server::Wallet for WalletGrpcServer {
         }))
     }
 
+    async fn get_historical_balance(
+        &self,
+        request: Request<GetHistoricalBalanceRequest>,
+    ) -> Result<Response<GetHistoricalBalanceResponse>, Status> {
+        use tari_rpc::get_historical_balance_request::Cutoff;
+
+        let mut output_service = self.get_output_manager_service();
+        let balance = match request.into_inner().cutoff {
+            Some(Cutoff::Height(height)) => output_service.get_balance_at_height(height).await,
+            Some(Cutoff::Timestamp(timestamp)) => output_service.get_balance_at_timestamp(timestamp).await,
+            None => return Err(Status::invalid_argument("Either a height or a timestamp is required")),
+        }
+        .map_err(|e| Status::internal(format!("GetHistoricalBalance error! {}", e)))?;
+
+        Ok(Response::new(GetHistoricalBalanceResponse {
+            height: balance.height.unwrap_or_default(),
+            balance: balance.balance.as_u64(),
+            num_outputs: balance.num_outputs,
+        }))
+    }
+
     async fn get_unspent_amounts(
         &self,
         _: Request<tari_rpc::Empty>,
@@ -300,12 +419,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
         output_service
             .revalidate_all_outputs()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(|e| wallet_error_status(&e))?;
         let mut tx_service = self.get_transaction_service();
         tx_service
             .revalidate_all_transactions()
             .await
-            .map_err(|e| Status::unknown(e.to_string()))?;
+            .map_err(|e| wallet_error_status(&e))?;
         Ok(Response::new(RevalidateResponse {}))
     }
 
@@ -319,7 +438,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let coinbase = tx_service
             .generate_coinbase_transaction(request.reward.into(), request.fee.into(), request.height, request.extra)
             .await
-            .map_err(|err| Status::unknown(err.to_string()))?;
+            .map_err(|err| wallet_error_status(&err))?;
 
         let coinbase = coinbase.try_into().map_err(Status::internal)?;
         Ok(Response::new(GetCoinbaseResponse {
@@ -331,6 +450,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<SendShaAtomicSwapRequest>,
     ) -> Result<Response<SendShaAtomicSwapResponse>, Status> {
+        let mut transaction_service = self.get_authorized_transaction_service(&request);
         let message = request
             .into_inner()
             .recipient
@@ -338,7 +458,6 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let address = TariAddress::from_hex(&message.address)
             .map_err(|_| Status::internal("Destination address is malformed".to_string()))?;
 
-        let mut transaction_service = self.get_transaction_service();
         let response = match transaction_service
             .send_sha_atomic_swap_transaction(
                 address.clone(),
@@ -387,6 +506,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<ClaimShaAtomicSwapRequest>,
     ) -> Result<Response<ClaimShaAtomicSwapResponse>, Status> {
+        let mut output_manager_service = self.get_authorized_output_manager_service(&request);
         let message = request.into_inner();
         let pre_image = CommsPublicKey::from_hex(&message.pre_image)
             .map_err(|_| Status::internal("pre_image is malformed".to_string()))?;
@@ -394,7 +514,6 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map_err(|_| Status::internal("Output hash is malformed".to_string()))?;
         debug!(target: LOG_TARGET, "Trying to claim HTLC with hash {}", output.to_hex());
         let mut transaction_service = self.get_transaction_service();
-        let mut output_manager_service = self.get_output_manager_service();
         let response = match output_manager_service
             .create_claim_sha_atomic_swap_transaction(output, pre_image, message.fee_per_gram.into())
             .await
@@ -414,23 +533,14 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         transaction_id: tx_id.as_u64(),
                         is_success: true,
                         failure_message: Default::default(),
+                        ..Default::default()
                     },
-                    Err(e) => TransferResult {
-                        address: Default::default(),
-                        transaction_id: Default::default(),
-                        is_success: false,
-                        failure_message: e.to_string(),
-                    },
+                    Err(e) => failed_transfer_result(Default::default(), &e),
                 }
             },
             Err(e) => {
                 warn!(target: LOG_TARGET, "Failed to claim SHA - XTR atomic swap: {}", e);
-                TransferResult {
-                    address: Default::default(),
-                    transaction_id: Default::default(),
-                    is_success: false,
-                    failure_message: e.to_string(),
-                }
+                failed_transfer_result(Default::default(), &e)
             },
         };
 
@@ -443,12 +553,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<ClaimHtlcRefundRequest>,
     ) -> Result<Response<ClaimHtlcRefundResponse>, Status> {
+        let mut output_manager_service = self.get_authorized_output_manager_service(&request);
         let message = request.into_inner();
         let output = BlockHash::from_hex(&message.output_hash)
             .map_err(|_| Status::internal("Output hash is malformed".to_string()))?;
 
         let mut transaction_service = self.get_transaction_service();
-        let mut output_manager_service = self.get_output_manager_service();
         debug!(target: LOG_TARGET, "Trying to claim HTLC with hash {}", output.to_hex());
         let response = match output_manager_service
             .create_htlc_refund_transaction(output, message.fee_per_gram.into())
@@ -464,23 +574,14 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         transaction_id: tx_id.as_u64(),
                         is_success: true,
                         failure_message: Default::default(),
+                        ..Default::default()
                     },
-                    Err(e) => TransferResult {
-                        address: Default::default(),
-                        transaction_id: Default::default(),
-                        is_success: false,
-                        failure_message: e.to_string(),
-                    },
+                    Err(e) => failed_transfer_result(Default::default(), &e),
                 }
             },
             Err(e) => {
                 warn!(target: LOG_TARGET, "Failed to claim HTLC refund transaction: {}", e);
-                TransferResult {
-                    address: Default::default(),
-                    transaction_id: Default::default(),
-                    is_success: false,
-                    failure_message: e.to_string(),
-                }
+                failed_transfer_result(Default::default(), &e)
             },
         };
 
@@ -490,6 +591,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
     }
 
     async fn transfer(&self, request: Request<TransferRequest>) -> Result<Response<TransferResponse>, Status> {
+        let transaction_service = self.get_authorized_transaction_service(&request);
         let message = request.into_inner();
         let recipients = message
             .recipients
@@ -498,6 +600,18 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .map(|(idx, dest)| -> Result<_, String> {
                 let address = TariAddress::from_hex(&dest.address)
                     .map_err(|_| format!("Destination address at index {} is malformed", idx))?;
+                let queueable = dest.input_commitments.is_empty();
+                let selection_criteria = if queueable {
+                    UtxoSelectionCriteria::default()
+                } else {
+                    let commitments = dest
+                        .input_commitments
+                        .iter()
+                        .map(|c| Commitment::from_bytes(c))
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| format!("Input commitments for the recipient at index {} are malformed", idx))?;
+                    UtxoSelectionCriteria::specific(commitments)
+                };
                 Ok((
                     dest.address,
                     address,
@@ -505,50 +619,72 @@ impl wallet_server::Wallet for WalletGrpcServer {
                     dest.fee_per_gram,
                     dest.message,
                     dest.payment_type,
+                    selection_criteria,
+                    queueable,
                 ))
             })
             .collect::<Result<Vec<_>, _>>()
             .map_err(Status::invalid_argument)?;
 
         let mut transfers = Vec::new();
-        for (hex_address, address, amount, fee_per_gram, message, payment_type) in recipients {
-            let mut transaction_service = self.get_transaction_service();
+        for (hex_address, address, amount, fee_per_gram, message, payment_type, selection_criteria, queueable) in
+            recipients
+        {
+            let mut transaction_service = transaction_service.clone();
             transfers.push(async move {
                 (
                     hex_address,
-                    if payment_type == PaymentType::StandardMimblewimble as i32 {
+                    // Transfers that spend specific inputs cannot be queued, as those inputs may be spent by the time
+                    // the wallet is back online
+                    if queueable &&
+                        (payment_type == PaymentType::StandardMimblewimble as i32 ||
+                            payment_type == PaymentType::OneSided as i32)
+                    {
+                        transaction_service
+                            .send_or_queue_transaction(ScheduledPayment {
+                                destination: address,
+                                amount: amount.into(),
+                                fee_per_gram: fee_per_gram.into(),
+                                message,
+                                one_sided: payment_type == PaymentType::OneSided as i32,
+                            })
+                            .await
+                    } else if payment_type == PaymentType::StandardMimblewimble as i32 {
                         transaction_service
                             .send_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
                             )
                             .await
+                            .map(SendOrQueueResult::Sent)
                     } else if payment_type == PaymentType::OneSided as i32 {
                         transaction_service
                             .send_one_sided_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
                             )
                             .await
+                            .map(SendOrQueueResult::Sent)
                     } else {
                         transaction_service
                             .send_one_sided_to_stealth_address_transaction(
                                 address,
                                 amount.into(),
-                                UtxoSelectionCriteria::default(),
+                                selection_criteria,
                                 OutputFeatures::default(),
                                 fee_per_gram.into(),
                                 message,
                             )
                             .await
+                            .map(SendOrQueueResult::Sent)
                     },
                 )
             });
@@ -559,23 +695,25 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let results = transfers_results
             .into_iter()
             .map(|(address, result)| match result {
-                Ok(tx_id) => TransferResult {
+                Ok(SendOrQueueResult::Sent(tx_id)) => TransferResult {
                     address,
                     transaction_id: tx_id.into(),
                     is_success: true,
                     failure_message: Default::default(),
+                    ..Default::default()
+                },
+                Ok(SendOrQueueResult::Queued(queue_id)) => TransferResult {
+                    address,
+                    is_success: true,
+                    queue_id,
+                    ..Default::default()
                 },
                 Err(err) => {
                     warn!(
                         target: LOG_TARGET,
                         "Failed to send transaction for address `{}`: {}", address, err
                     );
-                    TransferResult {
-                        address,
-                        transaction_id: Default::default(),
-                        is_success: false,
-                        failure_message: err.to_string(),
-                    }
+                    failed_transfer_result(address, &err)
                 },
             })
             .collect();
@@ -587,9 +725,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<CreateBurnTransactionRequest>,
     ) -> Result<Response<CreateBurnTransactionResponse>, Status> {
+        let mut transaction_service = self.get_authorized_transaction_service(&request);
         let message = request.into_inner();
 
-        let mut transaction_service = self.get_transaction_service();
         debug!(target: LOG_TARGET, "Trying to burn {} Minotari", message.amount);
         let response = match transaction_service
             .burn_tari(
@@ -638,6 +776,11 @@ impl wallet_server::Wallet for WalletGrpcServer {
         request: Request<GetTransactionInfoRequest>,
     ) -> Result<Response<GetTransactionInfoResponse>, Status> {
         let message = request.into_inner();
+        let confirmation_threshold = self
+            .get_confirmation_threshold(message.confirmation_threshold.map(|threshold| match threshold {
+                get_transaction_info_request::ConfirmationThreshold::ConfirmationsRequired(n) => n,
+            }))
+            .await?;
 
         let queries = message.transaction_ids.into_iter().map(|tx_id| {
             let tx_id = tx_id.into();
@@ -654,13 +797,30 @@ impl wallet_server::Wallet for WalletGrpcServer {
             .await
             .map(|tx| tx.into_iter())
             .map_err(|err| Status::unknown(err.to_string()))?;
+        let mempool_states = self
+            .get_transaction_service()
+            .get_mempool_states()
+            .await
+            .map_err(|err| wallet_error_status(&err))?;
+
+        let transactions = transactions.collect::<Vec<_>>();
+        let mut aliases = self
+            .get_transaction_service()
+            .get_counterparty_aliases(transactions.iter().map(|(tx_id, _)| *tx_id).collect())
+            .await
+            .map_err(|err| wallet_error_status(&err))?;
 
         let wallet_pk = self.wallet.comms.node_identity_ref().public_key();
         let wallet_network = self.wallet.network.as_network();
         let wallet_address = TariAddress::new(wallet_pk.clone(), wallet_network);
         let transactions = transactions
+            .into_iter()
             .map(|(tx_id, tx)| match tx {
-                Some(tx) => convert_wallet_transaction_into_transaction_info(tx, &wallet_address),
+                Some(tx) => TransactionInfo {
+                    mempool_state: mempool_states.get(&tx_id).map(convert_mempool_state),
+                    counterparty_alias: aliases.remove(&tx_id).unwrap_or_default(),
+                    ..convert_wallet_transaction_into_transaction_info(tx, &wallet_address, confirmation_threshold)
+                },
                 None => TransactionInfo::not_found(tx_id),
             })
             .collect();
@@ -692,8 +852,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                             match transaction_service.get_any_transaction(tx_id).await {
                                                 Ok(found_transaction) => {
                                                     if let Some(WalletTransaction::PendingOutbound(tx)) = found_transaction {
-                                                        let transaction_event = convert_to_transaction_event(NEW_BLOCK_MINED.to_string(),
+                                                        let mut transaction_event = convert_to_transaction_event(NEW_BLOCK_MINED.to_string(),
                                                             TransactionWrapper::Outbound(Box::new(tx)));
+                                                        set_counterparty_alias(&mut transaction_event, tx_id, &mut transaction_service).await;
                                                         send_transaction_event(transaction_event, &mut sender).await;
                                                     }
 
@@ -708,11 +869,12 @@ impl wallet_server::Wallet for WalletGrpcServer {
                                             match transaction_service.get_any_transaction(tx_id).await{
                                                 Ok(Some(wallet_tx)) => {
                                                     use WalletTransaction::*;
-                                                    let transaction_event = match wallet_tx {
+                                                    let mut transaction_event = match wallet_tx {
                                                         Completed(tx)  => convert_to_transaction_event(CANCELLED.to_string(), TransactionWrapper::Completed(Box::new(tx))),
                                                         PendingInbound(tx) => convert_to_transaction_event(CANCELLED.to_string(), TransactionWrapper::Inbound(Box::new(tx))),
                                                         PendingOutbound(tx) => convert_to_transaction_event(CANCELLED.to_string(), TransactionWrapper::Outbound(Box::new(tx))),
                                                     };
+                                                    set_counterparty_alias(&mut transaction_event, tx_id, &mut transaction_service).await;
                                                     send_transaction_event(transaction_event, &mut sender).await;
                                                 },
                                                 Err(e) => error!(target: LOG_TARGET, "Transaction service error: {}", e),
@@ -749,17 +911,31 @@ impl wallet_server::Wallet for WalletGrpcServer {
 
     async fn get_completed_transactions(
         &self,
-        _request: Request<GetCompletedTransactionsRequest>,
+        request: Request<GetCompletedTransactionsRequest>,
     ) -> Result<Response<Self::GetCompletedTransactionsStream>, Status> {
         debug!(
             target: LOG_TARGET,
             "Incoming GRPC request for GetAllCompletedTransactions"
         );
+        let message = request.into_inner();
+        let confirmation_threshold = self
+            .get_confirmation_threshold(message.confirmation_threshold.map(|threshold| match threshold {
+                get_completed_transactions_request::ConfirmationThreshold::ConfirmationsRequired(n) => n,
+            }))
+            .await?;
         let mut transaction_service = self.get_transaction_service();
         let transactions = transaction_service
             .get_completed_transactions()
             .await
             .map_err(|err| Status::not_found(format!("No completed transactions found: {:?}", err)))?;
+        let mempool_states = transaction_service
+            .get_mempool_states()
+            .await
+            .map_err(|err| wallet_error_status(&err))?;
+        let mut aliases = transaction_service
+            .get_counterparty_aliases(transactions.keys().copied().collect())
+            .await
+            .map_err(|err| wallet_error_status(&err))?;
 
         let (mut sender, receiver) = mpsc::channel(transactions.len());
         task::spawn(async move {
@@ -769,7 +945,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
                         tx_id: txn.tx_id.into(),
                         source_address: txn.source_address.to_bytes().to_vec(),
                         dest_address: txn.destination_address.to_bytes().to_vec(),
-                        status: TransactionStatus::from(txn.status) as i32,
+                        status: TransactionStatus::from(completed_transaction_status(&txn, confirmation_threshold))
+                            as i32,
                         amount: txn.amount.into(),
                         is_cancelled: txn.cancelled.is_some(),
                         direction: TransactionDirection::from(txn.direction) as i32,
@@ -782,6 +959,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
                             .get_signature()
                             .to_vec(),
                         message: txn.message,
+                        mempool_state: mempool_states.get(&txn.tx_id).map(convert_mempool_state),
+                        counterparty_alias: aliases.remove(&txn.tx_id).unwrap_or_default(),
                     }),
                 };
                 match sender.send(Ok(response)).await {
@@ -804,9 +983,9 @@ impl wallet_server::Wallet for WalletGrpcServer {
     }
 
     async fn coin_split(&self, request: Request<CoinSplitRequest>) -> Result<Response<CoinSplitResponse>, Status> {
-        let message = request.into_inner();
-
         let mut wallet = self.wallet.clone();
+        wallet.output_manager_service = self.get_authorized_output_manager_service(&request);
+        let message = request.into_inner();
 
         let tx_id = wallet
             .coin_split(
@@ -943,7 +1122,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<CreateTemplateRegistrationRequest>,
     ) -> Result<Response<CreateTemplateRegistrationResponse>, Status> {
-        let mut output_manager = self.wallet.output_manager_service.clone();
+        let mut output_manager = self.get_authorized_output_manager_service(&request);
         let mut transaction_service = self.wallet.transaction_service.clone();
         let message = request.into_inner();
 
@@ -970,7 +1149,7 @@ impl wallet_server::Wallet for WalletGrpcServer {
         let (tx_id, transaction) = output_manager
             .create_send_to_self_with_output(vec![output], fee_per_gram.into(), UtxoSelectionCriteria::default())
             .await
-            .map_err(|e| Status::internal(e.to_string()))?;
+            .map_err(|e| wallet_error_status(&e))?;
 
         debug!(
             target: LOG_TARGET,
@@ -1000,8 +1179,8 @@ impl wallet_server::Wallet for WalletGrpcServer {
         &self,
         request: Request<RegisterValidatorNodeRequest>,
     ) -> Result<Response<RegisterValidatorNodeResponse>, Status> {
+        let mut transaction_service = self.get_authorized_transaction_service(&request);
         let request = request.into_inner();
-        let mut transaction_service = self.get_transaction_service();
         let validator_node_public_key = CommsPublicKey::from_canonical_bytes(&request.validator_node_public_key)
             .map_err(|_| Status::internal("Destination address is malformed".to_string()))?;
         let validator_node_signature = request
@@ -1042,6 +1221,285 @@ impl wallet_server::Wallet for WalletGrpcServer {
         };
         Ok(Response::new(response))
     }
+
+    async fn start_recovery(
+        &self,
+        request: Request<StartRecoveryRequest>,
+    ) -> Result<Response<StartRecoveryResponse>, Status> {
+        let request = request.into_inner();
+        let mut peers = request
+            .base_node_public_keys
+            .iter()
+            .map(|key| PublicKey::from_hex(key))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Base node public key was not a valid pub key: {}", e)))?;
+        if peers.is_empty() {
+            let current_base_node = self
+                .wallet
+                .wallet_connectivity
+                .get_current_base_node_peer_public_key()
+                .ok_or_else(|| Status::failed_precondition("No base node has been set"))?;
+            peers.push(current_base_node);
+        }
+
+        let retry_limit = usize::try_from(request.retry_limit)
+            .map_err(|_| Status::invalid_argument("Retry limit is too large".to_string()))?;
+        if !self.recovery.start(&self.wallet, peers, retry_limit) {
+            return Err(Status::already_exists("A recovery is already running"));
+        }
+        info!(target: LOG_TARGET, "Wallet recovery started over gRPC");
+        Ok(Response::new(StartRecoveryResponse {}))
+    }
+
+    async fn stop_recovery(&self, _: Request<tari_rpc::Empty>) -> Result<Response<StopRecoveryResponse>, Status> {
+        let was_running = self.recovery.stop();
+        if was_running {
+            info!(target: LOG_TARGET, "Wallet recovery stopped over gRPC");
+        }
+        Ok(Response::new(StopRecoveryResponse { was_running }))
+    }
+
+    async fn stream_recovery_progress(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<Self::StreamRecoveryProgressStream>, Status> {
+        let (mut sender, receiver) = mpsc::channel(100);
+        let mut progress_events = self.recovery.subscribe();
+
+        task::spawn(async move {
+            loop {
+                match progress_events.recv().await {
+                    Ok(event) => {
+                        if sender.send(Ok(event)).await.is_err() {
+                            debug!(target: LOG_TARGET, "Recovery progress stream closed by client");
+                            break;
+                        }
+                    },
+                    Err(broadcast::error::RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Missed {} recovery progress events", n);
+                    },
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+
+        Ok(Response::new(receiver))
+    }
+
+    async fn get_fee_breakdown(
+        &self,
+        request: Request<GetFeeBreakdownRequest>,
+    ) -> Result<Response<GetFeeBreakdownResponse>, Status> {
+        use tari_rpc::get_fee_breakdown_request::Subject;
+
+        let request = request.into_inner();
+        let fee_per_gram = MicroMinotari::from(request.fee_per_gram);
+        let constants = self.get_consensus_constants().map_err(|e| {
+            error!(target: LOG_TARGET, "Failed to get consensus constants: {}", e);
+            Status::internal("failed to fetch consensus constants")
+        })?;
+        let fee_calc = Fee::new(*constants.transaction_weight_params());
+
+        let breakdown = match request.subject {
+            Some(Subject::Transaction(tx)) => {
+                let tx = Transaction::try_from(tx).map_err(Status::invalid_argument)?;
+                tx.calculate_fee_breakdown(constants.transaction_weight_params(), fee_per_gram)
+                    .map_err(|e| Status::invalid_argument(format!("Transaction could not be serialized: {}", e)))?
+            },
+            Some(Subject::Planned(planned)) if planned.output_features_and_scripts_sizes.is_empty() => self
+                .get_output_manager_service()
+                .fee_breakdown(
+                    fee_per_gram,
+                    planned.num_kernels as usize,
+                    planned.num_inputs as usize,
+                    planned.num_outputs as usize,
+                )
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?,
+            Some(Subject::Planned(planned)) => {
+                if planned.output_features_and_scripts_sizes.len() as u64 != planned.num_outputs {
+                    return Err(Status::invalid_argument(
+                        "output_features_and_scripts_sizes must contain one entry per output",
+                    ));
+                }
+                let rounded_size = planned
+                    .output_features_and_scripts_sizes
+                    .iter()
+                    .map(|size| fee_calc.weighting().round_up_features_and_scripts_size(*size as usize))
+                    .fold(0usize, usize::saturating_add);
+                fee_calc.breakdown(
+                    fee_per_gram,
+                    planned.num_kernels as usize,
+                    planned.num_inputs as usize,
+                    planned.num_outputs as usize,
+                    rounded_size,
+                )
+            },
+            None => {
+                return Err(Status::invalid_argument(
+                    "Either a transaction or a planned shape is required",
+                ))
+            },
+        };
+
+        Ok(Response::new(breakdown.into()))
+    }
+
+    async fn get_burn_proofs(
+        &self,
+        request: Request<GetBurnProofsRequest>,
+    ) -> Result<Response<GetBurnProofsResponse>, Status> {
+        let request = request.into_inner();
+        let mut transaction_service = self.get_transaction_service();
+        let records = if request.transaction_ids.is_empty() {
+            transaction_service
+                .get_burn_proofs(None)
+                .await
+                .map_err(|e| Status::internal(e.to_string()))?
+        } else {
+            let mut records = Vec::with_capacity(request.transaction_ids.len());
+            for tx_id in request.transaction_ids {
+                let record = transaction_service
+                    .get_burn_proof(tx_id.into())
+                    .await
+                    .map_err(|e| Status::internal(e.to_string()))?
+                    .ok_or_else(|| Status::not_found(format!("No burn found with transaction id {}", tx_id)))?;
+                records.push(record);
+            }
+            records
+        };
+
+        Ok(Response::new(GetBurnProofsResponse {
+            burn_proofs: records.into_iter().map(convert_burn_record).collect(),
+        }))
+    }
+
+    async fn mark_burn_claimed(
+        &self,
+        request: Request<MarkBurnClaimedRequest>,
+    ) -> Result<Response<MarkBurnClaimedResponse>, Status> {
+        let tx_id = request.into_inner().transaction_id.into();
+        self.get_transaction_service()
+            .mark_burn_claimed(tx_id)
+            .await
+            .map_err(|e| match e {
+                TransactionServiceError::BurnNotFound(_) => Status::not_found(e.to_string()),
+                TransactionServiceError::BurnNotClaimable { .. } => Status::failed_precondition(e.to_string()),
+                _ => wallet_error_status(&e),
+            })?;
+        Ok(Response::new(MarkBurnClaimedResponse {}))
+    }
+
+    async fn freeze_outputs(
+        &self,
+        request: Request<FreezeOutputsRequest>,
+    ) -> Result<Response<FreezeOutputsResponse>, Status> {
+        let commitments = convert_commitments(&request.into_inner().commitments)?;
+        self.get_output_manager_service()
+            .freeze_outputs(commitments)
+            .await
+            .map_err(|e| wallet_error_status(&e))?;
+        Ok(Response::new(FreezeOutputsResponse {}))
+    }
+
+    async fn unfreeze_outputs(
+        &self,
+        request: Request<UnfreezeOutputsRequest>,
+    ) -> Result<Response<UnfreezeOutputsResponse>, Status> {
+        let commitments = convert_commitments(&request.into_inner().commitments)?;
+        self.get_output_manager_service()
+            .unfreeze_outputs(commitments)
+            .await
+            .map_err(|e| wallet_error_status(&e))?;
+        Ok(Response::new(UnfreezeOutputsResponse {}))
+    }
+
+    async fn create_unsigned_transaction(
+        &self,
+        request: Request<CreateUnsignedTransactionRequest>,
+    ) -> Result<Response<CreateUnsignedTransactionResponse>, Status> {
+        let mut transaction_service = self.get_authorized_transaction_service(&request);
+        let message = request.into_inner();
+        let address = TariAddress::from_hex(&message.address)
+            .map_err(|_| Status::invalid_argument("Destination address is malformed".to_string()))?;
+        let unsigned = transaction_service
+            .create_unsigned_transaction(
+                address,
+                message.amount.into(),
+                UtxoSelectionCriteria::default(),
+                message.fee_per_gram.into(),
+                message.message,
+            )
+            .await
+            .map_err(|e| wallet_error_status(&e))?;
+        let bytes = unsigned.to_bytes().map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(CreateUnsignedTransactionResponse {
+            transaction_id: unsigned.tx_id.as_u64(),
+            unsigned_transaction: bytes,
+        }))
+    }
+
+    async fn sign_unsigned_transaction(
+        &self,
+        request: Request<SignUnsignedTransactionRequest>,
+    ) -> Result<Response<SignUnsignedTransactionResponse>, Status> {
+        let mut transaction_service = self.get_authorized_transaction_service(&request);
+        let unsigned = UnsignedTransaction::from_bytes(&request.into_inner().unsigned_transaction)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let signed = transaction_service
+            .sign_unsigned_transaction(unsigned)
+            .await
+            .map_err(|e| wallet_error_status(&e))?;
+        let bytes = signed.to_bytes().map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(SignUnsignedTransactionResponse {
+            signed_transaction: bytes,
+        }))
+    }
+
+    async fn import_signed_transaction(
+        &self,
+        request: Request<ImportSignedTransactionRequest>,
+    ) -> Result<Response<ImportSignedTransactionResponse>, Status> {
+        let signed = SignedTransaction::from_bytes(&request.into_inner().signed_transaction)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        let tx_id = self
+            .get_transaction_service()
+            .import_signed_transaction(signed)
+            .await
+            .map_err(|e| wallet_error_status(&e))?;
+        Ok(Response::new(ImportSignedTransactionResponse {
+            transaction_id: tx_id.as_u64(),
+        }))
+    }
+}
+
+fn convert_commitments(commitments: &[Vec<u8>]) -> Result<Vec<Commitment>, Status> {
+    commitments
+        .iter()
+        .map(|c| Commitment::from_bytes(c))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Status::invalid_argument("Commitments are malformed"))
+}
+
+fn convert_burn_record(record: models::BurnRecord) -> BurnProof {
+    let status = match record.status {
+        models::BurnStatus::Pending => BurnStatus::Pending,
+        models::BurnStatus::Claimable => BurnStatus::Claimable,
+        models::BurnStatus::Claimed => BurnStatus::Claimed,
+        models::BurnStatus::Cancelled => BurnStatus::Cancelled,
+    };
+    BurnProof {
+        transaction_id: record.tx_id.as_u64(),
+        amount: record.amount.as_u64(),
+        claim_public_key: record.claim_public_key.map(|k| k.to_vec()).unwrap_or_default(),
+        reciprocal_claim_public_key: record.proof.reciprocal_claim_public_key.to_vec(),
+        commitment: record.proof.commitment.to_vec(),
+        ownership_proof: record.proof.ownership_proof.map(CommitmentSignature::from),
+        range_proof: record.proof.range_proof.to_vec(),
+        status: status as i32,
+        burned_at: record.burned_at.timestamp() as u64,
+        claimed_at: record.claimed_at.map(|t| t.timestamp() as u64).unwrap_or_default(),
+    }
 }
 
 async fn handle_completed_tx(
@@ -1052,8 +1510,9 @@ async fn handle_completed_tx(
 ) {
     match transaction_service.get_completed_transaction(tx_id).await {
         Ok(completed) => {
-            let transaction_event =
+            let mut transaction_event =
                 convert_to_transaction_event(event.to_string(), TransactionWrapper::Completed(Box::new(completed)));
+            set_counterparty_alias(&mut transaction_event, tx_id, transaction_service).await;
             send_transaction_event(transaction_event, sender).await;
         },
         Err(e) => error!(target: LOG_TARGET, "Transaction service error: {}", e),
@@ -1069,8 +1528,9 @@ async fn handle_pending_outbound(
     match transaction_service.get_pending_outbound_transactions().await {
         Ok(mut txs) => {
             if let Some(tx) = txs.remove(&tx_id) {
-                let transaction_event =
+                let mut transaction_event =
                     convert_to_transaction_event(event.to_string(), TransactionWrapper::Outbound(Box::new(tx)));
+                set_counterparty_alias(&mut transaction_event, tx_id, transaction_service).await;
                 send_transaction_event(transaction_event, sender).await;
             } else {
                 error!(target: LOG_TARGET, "Not found in pending outbound set tx_id: {}", tx_id);
@@ -1091,12 +1551,44 @@ fn simple_event(event: &str) -> TransactionEvent {
         amount: 0,
         message: String::default(),
         is_coinbase: false,
+        counterparty_alias: String::new(),
+    }
+}
+
+async fn set_counterparty_alias(
+    transaction_event: &mut TransactionEvent,
+    tx_id: TxId,
+    transaction_service: &mut TransactionServiceHandle,
+) {
+    match transaction_service.get_counterparty_alias(tx_id).await {
+        Ok(alias) => transaction_event.counterparty_alias = alias.unwrap_or_default(),
+        Err(e) => warn!(target: LOG_TARGET, "Could not resolve the counterparty alias of {}: {}", tx_id, e),
+    }
+}
+
+/// A confirmation threshold requested by a gRPC caller in place of the one the wallet is configured with
+#[derive(Debug, Clone, Copy)]
+struct ConfirmationThreshold {
+    num_confirmations_required: u64,
+    tip_height: Option<u64>,
+}
+
+fn completed_transaction_status(
+    tx: &models::CompletedTransaction,
+    confirmation_threshold: Option<ConfirmationThreshold>,
+) -> tari_common_types::transaction::TransactionStatus {
+    match confirmation_threshold {
+        Some(threshold) => {
+            tx.status_with_confirmations_required(threshold.num_confirmations_required, threshold.tip_height)
+        },
+        None => tx.status,
     }
 }
 
 fn convert_wallet_transaction_into_transaction_info(
     tx: models::WalletTransaction,
     wallet_address: &TariAddress,
+    confirmation_threshold: Option<ConfirmationThreshold>,
 ) -> TransactionInfo {
     use models::WalletTransaction::{Completed, PendingInbound, PendingOutbound};
     match tx {
@@ -1112,6 +1604,8 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: tx.timestamp.timestamp() as u64,
             message: tx.message,
+            mempool_state: None,
+            counterparty_alias: String::new(),
         },
         PendingOutbound(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
@@ -1125,12 +1619,14 @@ fn convert_wallet_transaction_into_transaction_info(
             excess_sig: Default::default(),
             timestamp: tx.timestamp.timestamp() as u64,
             message: tx.message,
+            mempool_state: None,
+            counterparty_alias: String::new(),
         },
         Completed(tx) => TransactionInfo {
             tx_id: tx.tx_id.into(),
             source_address: tx.source_address.to_bytes().to_vec(),
             dest_address: tx.destination_address.to_bytes().to_vec(),
-            status: TransactionStatus::from(tx.status) as i32,
+            status: TransactionStatus::from(completed_transaction_status(&tx, confirmation_threshold)) as i32,
             amount: tx.amount.into(),
             is_cancelled: tx.cancelled.is_some(),
             direction: TransactionDirection::from(tx.direction) as i32,
@@ -1142,6 +1638,17 @@ fn convert_wallet_transaction_into_transaction_info(
                 .map(|s| s.get_signature().to_vec())
                 .unwrap_or_default(),
             message: tx.message,
+            mempool_state: None,
+            counterparty_alias: String::new(),
         },
     }
 }
+
+fn convert_mempool_state(state: &MempoolTransactionState) -> TransactionMempoolState {
+    TransactionMempoolState {
+        in_mempool: state.in_mempool,
+        fee_per_gram: state.fee_per_gram.as_u64(),
+        feerate_percentile: state.feerate_percentile.map(u32::from).unwrap_or_default(),
+        checked_at: u64::try_from(state.checked_at.timestamp()).unwrap_or_default(),
+    }
+}
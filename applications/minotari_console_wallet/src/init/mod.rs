@@ -22,7 +22,12 @@
 
 #![allow(dead_code, unused)]
 
-use std::{fs, path::PathBuf, str::FromStr, sync::Arc};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
 
 use log::*;
 use minotari_app_utilities::identity_management::setup_node_identity;
@@ -31,7 +36,12 @@ use minotari_wallet::{
     output_manager_service::storage::database::OutputManagerDatabase,
     storage::{
         database::{WalletBackend, WalletDatabase},
-        sqlite_utilities::initialize_sqlite_database_backends,
+        sqlite_db::{snapshot::restore_snapshot, wallet::WalletSqliteDatabase},
+        sqlite_utilities::{
+            dry_run_migrations as dry_run_wallet_migrations,
+            initialize_sqlite_database_backends,
+            run_migration_and_create_sqlite_connection,
+        },
     },
     wallet::{derive_comms_secret_key, read_or_create_master_seed},
     Wallet,
@@ -241,26 +251,85 @@ pub async fn change_password(
     shutdown_signal: ShutdownSignal,
     non_interactive_mode: bool,
 ) -> Result<(), ExitError> {
-    let mut wallet = init_wallet(
-        config,
+    // Only open the wallet database, as no wallet service may use it while it is being re-encrypted
+    let connection =
+        run_migration_and_create_sqlite_connection(&config.wallet.db_file, config.wallet.db_connection_pool_size)?;
+    let wallet_db = WalletDatabase::new(WalletSqliteDatabase::open_for_key_rotation(
+        connection,
         existing.clone(),
-        None,
-        None,
-        shutdown_signal,
-        non_interactive_mode,
-    )
-    .await?;
+    )?);
+    if wallet_db.is_key_rotation_pending()? {
+        println!("Resuming an interrupted password change; enter the same new password as before.");
+    }
 
     // Get a new passphrase
     let new = get_new_passphrase("New wallet passphrase: ", "Confirm new passphrase: ")?;
 
-    // Use the existing and new passphrases to attempt to change the wallet passphrase
-    wallet.db.change_passphrase(&existing, &new).map_err(|e| match e {
-        WalletStorageError::InvalidPassphrase => {
-            ExitError::new(ExitCode::IncorrectOrEmptyPassword, "Your password was not changed.")
-        },
-        _ => ExitError::new(ExitCode::DatabaseError, "Your password was not changed."),
-    })
+    // Re-encrypt the wallet database with a fresh key protected by the new passphrase
+    wallet_db
+        .rotate_encryption_key(&existing, &new, |progress| {
+            println!(
+                "Re-encrypted {} ({} rows) [{}/{}]",
+                progress.table, progress.rows, progress.tables_completed, progress.total_tables
+            );
+        })
+        .map_err(|e| {
+            error!(target: LOG_TARGET, "Could not change the wallet password: {}", e);
+            match e {
+                WalletStorageError::InvalidPassphrase => {
+                    ExitError::new(ExitCode::IncorrectOrEmptyPassword, "Your password was not changed.")
+                },
+                _ => ExitError::new(
+                    ExitCode::DatabaseError,
+                    "Your password was not changed. Run the password change again to complete it.",
+                ),
+            }
+        })
+}
+
+/// Restores the wallet database from an encrypted backup snapshot, returning the password that unlocks it.
+pub fn restore_backup(
+    config: &ApplicationConfig,
+    snapshot: &Path,
+    password: Option<SafePassword>,
+) -> Result<SafePassword, ExitError> {
+    let password = match password {
+        Some(password) => password,
+        None => prompt_password("Backup password: ")?,
+    };
+    if let Some(parent) = config.wallet.db_file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| ExitError::new(ExitCode::WalletError, format!("Error creating Wallet folder. {}", e)))?;
+    }
+
+    restore_snapshot(snapshot, &config.wallet.db_file, &password).map_err(|e| match e {
+        WalletStorageError::InvalidPassphrase => ExitError::new(
+            ExitCode::IncorrectOrEmptyPassword,
+            "The backup could not be decrypted with this password.",
+        ),
+        e => ExitError::new(ExitCode::DatabaseError, format!("The backup was not restored: {}", e)),
+    })?;
+    println!("Wallet restored from '{}'.", snapshot.display());
+
+    Ok(password)
+}
+
+/// Apply the pending migrations of the wallet database to a copy of it and report the outcome, without touching the
+/// database itself
+pub fn dry_run_migrations(config: &ApplicationConfig) -> Result<(), ExitError> {
+    if !config.wallet.db_file.exists() {
+        println!("There is no wallet database at '{}'.", config.wallet.db_file.display());
+        return Ok(());
+    }
+    let report = dry_run_wallet_migrations(&config.wallet.db_file).map_err(|e| {
+        ExitError::new(
+            ExitCode::DatabaseError,
+            format!("The wallet database migrations failed the dry run: {}", e),
+        )
+    })?;
+    println!("{}.", report);
+
+    Ok(())
 }
 
 /// Populates the PeerConfig struct from:
@@ -380,6 +449,7 @@ pub async fn init_wallet(
     arg_password: SafePassword,
     seed_words_file_name: Option<PathBuf>,
     recovery_seed: Option<CipherSeed>,
+    seed_passphrase: Option<SafePassword>,
     shutdown_signal: ShutdownSignal,
     non_interactive_mode: bool,
 ) -> Result<WalletSqlite, ExitError> {
@@ -400,7 +470,15 @@ pub async fn init_wallet(
 
     // wallet should be encrypted from the beginning, so we must require a password to be provided by the user
     let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend, key_manager_backend) =
-        initialize_sqlite_database_backends(db_path, arg_password, config.wallet.db_connection_pool_size)?;
+        initialize_sqlite_database_backends(db_path, arg_password.clone(), config.wallet.db_connection_pool_size)
+            .map_err(|e| match e {
+                WalletStorageError::KeyRotationPending => ExitError::new(
+                    ExitCode::DatabaseError,
+                    "A wallet password change was interrupted. Run with --change-password and the same new password \
+                     to complete it.",
+                ),
+                e => e.into(),
+            })?;
 
     let wallet_db = WalletDatabase::new(wallet_backend);
     let output_db = OutputManagerDatabase::new(output_manager_backend.clone());
@@ -416,7 +494,7 @@ pub async fn init_wallet(
         config.wallet.p2p.public_addresses.clone()
     };
 
-    let master_seed = read_or_create_master_seed(recovery_seed.clone(), &wallet_db)?;
+    let master_seed = read_or_create_master_seed(recovery_seed.clone(), seed_passphrase, &wallet_db)?;
 
     let node_identity = match config.wallet.identity_file.as_ref() {
         Some(identity_file) => {
@@ -466,6 +544,12 @@ pub async fn init_wallet(
         WalletError::CommsInitializationError(cie) => cie.to_exit_error(),
         e => ExitError::new(ExitCode::WalletError, format!("Error creating Wallet Container: {}", e)),
     })?;
+    if let Some(report) = wallet.storage_integrity_report.as_ref().filter(|r| !r.is_clean()) {
+        println!("{}", report);
+        if !report.repaired {
+            println!("Set `wallet.storage_integrity_repair = true` to repair these issues on the next startup.");
+        }
+    }
     if let Some(hs) = wallet.comms.hidden_service() {
         wallet
             .db
@@ -474,7 +558,10 @@ pub async fn init_wallet(
     }
 
     if let Some(file_name) = seed_words_file_name {
-        let seed_words = wallet.get_seed_words(&MnemonicLanguage::English)?.join(" ");
+        let seed_words = wallet
+            .export_seed_words(&MnemonicLanguage::English, Some(arg_password))
+            .await?
+            .join(" ");
         let _result = fs::write(file_name, seed_words.reveal()).map_err(|e| {
             ExitError::new(
                 ExitCode::WalletError,
@@ -559,14 +646,19 @@ fn setup_identity_from_db<D: WalletBackend + 'static>(
     Ok(node_identity)
 }
 
-/// Starts the wallet by setting the base node peer, and restarting the transaction and broadcast protocols.
+/// Starts the wallet by setting the base node peers, and restarting the transaction and broadcast protocols. The first
+/// base node is preferred, the others are failed over to.
 pub async fn start_wallet(
     wallet: &mut WalletSqlite,
-    base_node: &Peer,
+    base_nodes: &[Peer],
     wallet_mode: &WalletMode,
 ) -> Result<(), ExitError> {
     debug!(target: LOG_TARGET, "Setting base node peer");
 
+    let base_node = base_nodes
+        .first()
+        .ok_or_else(|| ExitError::new(ExitCode::ConfigError, "No base node peer to connect to!"))?;
+
     let net_address = base_node
         .addresses
         .best()
@@ -581,6 +673,14 @@ pub async fn start_wallet(
                 format!("Error setting wallet base node peer. {}", e),
             )
         })?;
+    if base_nodes.len() > 1 {
+        wallet.set_base_node_peers(base_nodes.to_vec()).await.map_err(|e| {
+            ExitError::new(
+                ExitCode::WalletError,
+                format!("Error setting wallet fallback base node peers. {}", e),
+            )
+        })?;
+    }
 
     // Restart transaction protocols if not running in script or command modes
     if !matches!(wallet_mode, WalletMode::Command(_)) && !matches!(wallet_mode, WalletMode::Script(_)) {
@@ -623,8 +723,10 @@ async fn validate_txos(wallet: &mut WalletSqlite) -> Result<(), ExitError> {
     Ok(())
 }
 
-pub(crate) fn confirm_seed_words(wallet: &mut WalletSqlite) -> Result<(), ExitError> {
-    let seed_words = wallet.get_seed_words(&MnemonicLanguage::English)?;
+pub(crate) async fn confirm_seed_words(wallet: &mut WalletSqlite, passphrase: SafePassword) -> Result<(), ExitError> {
+    let seed_words = wallet
+        .export_seed_words(&MnemonicLanguage::English, Some(passphrase))
+        .await?;
 
     println!();
     println!("=========================");
@@ -656,9 +758,7 @@ pub(crate) fn confirm_seed_words(wallet: &mut WalletSqlite) -> Result<(), ExitEr
     }
 }
 
-pub(crate) fn confirm_direct_only_send(wallet: &mut WalletSqlite) -> Result<(), ExitError> {
-    let seed_words = wallet.get_seed_words(&MnemonicLanguage::English)?;
-
+pub(crate) fn confirm_direct_only_send() -> Result<(), ExitError> {
     println!();
     println!("=========================");
     println!("       IMPORTANT!        ");
@@ -28,6 +28,8 @@ mod cli;
 mod config;
 mod grpc;
 mod init;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod notifier;
 mod recovery;
 mod ui;
@@ -46,11 +48,20 @@ pub use cli::{
     SetBaseNodeArgs,
     WhoisArgs,
 };
-use init::{change_password, get_base_node_peer_config, init_wallet, start_wallet, tari_splash_screen, WalletBoot};
+use init::{
+    change_password,
+    dry_run_migrations,
+    get_base_node_peer_config,
+    init_wallet,
+    restore_backup,
+    start_wallet,
+    tari_splash_screen,
+    WalletBoot,
+};
 use log::*;
 use minotari_app_utilities::{common_cli_args::CommonCliArgs, consts, network_check::is_network_choice_valid};
 use minotari_wallet::transaction_service::config::TransactionRoutingMechanism;
-use recovery::{get_seed_from_seed_words, prompt_private_key_from_seed_words};
+use recovery::{get_seed_from_seed_words, prompt_private_key_from_seed_words, prompt_seed_passphrase};
 use tari_common::{
     configuration::bootstrap::ApplicationType,
     exit_codes::{ExitCode, ExitError},
@@ -65,6 +76,8 @@ use wallet_modes::{command_mode, grpc_mode, recovery_mode, script_mode, tui_mode
 
 pub use crate::config::ApplicationConfig;
 use crate::init::{boot_with_password, confirm_direct_only_send, confirm_seed_words, wallet_mode};
+#[cfg(feature = "metrics")]
+pub use crate::metrics::MetricsConfig;
 
 pub const LOG_TARGET: &str = "wallet::console_wallet::main";
 
@@ -86,8 +99,12 @@ pub fn run_wallet(shutdown: &mut Shutdown, runtime: Runtime, config: &mut Applic
         },
         password: None,
         change_password: false,
+        restore_backup: None,
+        dry_run: false,
         recovery: false,
+        full_scan: false,
         seed_words: None,
+        seed_passphrase: None,
         seed_words_file_name: None,
         non_interactive_mode: true,
         input_file: None,
@@ -125,10 +142,22 @@ pub fn run_wallet_with_cli(
         tari_splash_screen("Console Wallet");
     }
 
+    if let Some(ref snapshot) = cli.restore_backup {
+        info!(target: LOG_TARGET, "Restoring wallet from backup '{}'", snapshot.display());
+        let password = restore_backup(config, snapshot, password)?;
+        // Don't ask for the password again when opening the restored wallet
+        config.wallet.password = Some(password);
+    }
+
+    if cli.dry_run {
+        return dry_run_migrations(config);
+    }
+
     // check for recovery based on existence of wallet file
     let (mut boot_mode, password) = boot_with_password(&cli, &config.wallet)?;
 
     let recovery_seed = get_recovery_seed(boot_mode, &cli)?;
+    let seed_passphrase = get_seed_passphrase(boot_mode, &cli)?;
 
     // get command line password if provided
     let seed_words_file_name = cli.seed_words_file_name.clone();
@@ -145,6 +174,16 @@ pub fn run_wallet_with_cli(
         ));
     }
 
+    #[cfg(feature = "metrics")]
+    {
+        metrics::install(
+            ApplicationType::ConsoleWallet,
+            &config.metrics,
+            &runtime,
+            shutdown.to_signal(),
+        );
+    }
+
     // Run our own Tor instance, if configured
     // This is currently only possible on linux/macos
     #[cfg(all(unix, feature = "libtor"))]
@@ -164,9 +203,10 @@ pub fn run_wallet_with_cli(
     // initialize wallet
     let mut wallet = runtime.block_on(init_wallet(
         config,
-        password,
+        password.clone(),
         seed_words_file_name,
         recovery_seed,
+        seed_passphrase,
         shutdown_signal,
         cli.non_interactive_mode,
     ))?;
@@ -175,7 +215,7 @@ pub fn run_wallet_with_cli(
         config.wallet.transaction_service_config.transaction_routing_mechanism ==
             TransactionRoutingMechanism::DirectOnly
     {
-        match confirm_direct_only_send(&mut wallet) {
+        match confirm_direct_only_send() {
             Ok(()) => {
                 print!("\x1Bc"); // Clear the screen
             },
@@ -187,7 +227,7 @@ pub fn run_wallet_with_cli(
 
     // if wallet is being set for the first time, wallet seed words are prompted on the screen
     if !cli.non_interactive_mode && not_recovery && on_init {
-        match confirm_seed_words(&mut wallet) {
+        match runtime.block_on(confirm_seed_words(&mut wallet, password)) {
             Ok(()) => {
                 print!("\x1Bc"); // Clear the screen
             },
@@ -206,12 +246,12 @@ pub fn run_wallet_with_cli(
     // get base node/s
     let base_node_config =
         runtime.block_on(get_base_node_peer_config(config, &mut wallet, cli.non_interactive_mode))?;
-    let base_node_selected = base_node_config.get_base_node_peer()?;
+    let base_nodes_selected = base_node_config.get_base_node_peers()?;
 
     let wallet_mode = wallet_mode(&cli, boot_mode);
 
     // start wallet
-    runtime.block_on(start_wallet(&mut wallet, &base_node_selected, &wallet_mode))?;
+    runtime.block_on(start_wallet(&mut wallet, &base_nodes_selected, &wallet_mode))?;
 
     debug!(target: LOG_TARGET, "Starting app");
 
@@ -230,9 +270,14 @@ pub fn run_wallet_with_cli(
             *command,
         ),
 
-        WalletMode::RecoveryDaemon | WalletMode::RecoveryTui => {
-            recovery_mode(handle, &base_node_config, &config.wallet, wallet_mode, wallet.clone())
-        },
+        WalletMode::RecoveryDaemon | WalletMode::RecoveryTui => recovery_mode(
+            handle,
+            &base_node_config,
+            &config.wallet,
+            wallet_mode,
+            wallet.clone(),
+            cli.full_scan,
+        ),
         WalletMode::Invalid => Err(ExitError::new(
             ExitCode::InputError,
             "Invalid wallet mode - are you trying too many command options at once?",
@@ -254,6 +299,18 @@ fn get_password(config: &ApplicationConfig, cli: &Cli) -> Option<SafePassword> {
         .map(|s| s.to_owned())
 }
 
+fn get_seed_passphrase(boot_mode: WalletBoot, cli: &Cli) -> Result<Option<SafePassword>, ExitError> {
+    if cli.seed_passphrase.is_some() {
+        return Ok(cli.seed_passphrase.clone());
+    }
+    // Only ask when recovering interactively, the seed passphrase of a new wallet has to be chosen explicitly
+    if matches!(boot_mode, WalletBoot::Recovery) && !cli.non_interactive_mode {
+        prompt_seed_passphrase()
+    } else {
+        Ok(None)
+    }
+}
+
 fn get_recovery_seed(boot_mode: WalletBoot, cli: &Cli) -> Result<Option<CipherSeed>, ExitError> {
     if matches!(boot_mode, WalletBoot::Recovery) {
         let seed = if let Some(ref seed_words) = cli.seed_words {
@@ -31,12 +31,18 @@ use minotari_wallet::{
     utxo_scanner_service::{handle::UtxoScannerEvent, service::UtxoScannerService},
     WalletSqlite,
 };
+use rpassword::prompt_password_stdout;
 use rustyline::Editor;
 use tari_common::exit_codes::{ExitCode, ExitError};
 use tari_crypto::tari_utilities::Hidden;
-use tari_key_manager::{cipher_seed::CipherSeed, mnemonic::Mnemonic, SeedWords};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    mnemonic::Mnemonic,
+    seed_shares::{combine_seed_share_words, SeedShare},
+    SeedWords,
+};
 use tari_shutdown::Shutdown;
-use tari_utilities::hex::Hex;
+use tari_utilities::{hex::Hex, SafePassword};
 use tokio::sync::broadcast;
 use zeroize::{Zeroize, Zeroizing};
 
@@ -52,15 +58,22 @@ pub fn prompt_private_key_from_seed_words() -> Result<CipherSeed, ExitError> {
     loop {
         println!("Recovery Mode");
         println!();
-        println!("Type or paste all of your seed words on one line, only separated by spaces.");
-        let input = Hidden::hide(rl.readline(">> ").map_err(|e| ExitError::new(ExitCode::IOError, e))?);
-        let seed_words: SeedWords = SeedWords::new(
-            input
-                .reveal()
-                .split_whitespace()
-                .map(|s| Hidden::hide(s.to_string()))
-                .collect(),
+        println!(
+            "Type or paste all of your seed words on one line, only separated by spaces. If the seed was split into \
+             shares, enter the words of one share."
         );
+        let seed_words = read_seed_words(&mut rl)?;
+        if let Ok(first_share) = SeedShare::from_mnemonic(&seed_words) {
+            let shares = prompt_remaining_seed_shares(&mut rl, seed_words, first_share.threshold())?;
+            match combine_seed_share_words(&shares).and_then(|words| CipherSeed::from_mnemonic(&words, None)) {
+                Ok(seed) => break Ok(seed),
+                Err(e) => {
+                    debug!(target: LOG_TARGET, "Error recovering the seed from its shares: {}", e);
+                    println!("Failed to recover the seed from its shares: {}", e);
+                    continue;
+                },
+            }
+        }
 
         match CipherSeed::from_mnemonic(&seed_words, None) {
             Ok(seed) => break Ok(seed),
@@ -73,6 +86,48 @@ pub fn prompt_private_key_from_seed_words() -> Result<CipherSeed, ExitError> {
     }
 }
 
+/// Prompts for shares until there are as many as are needed to recover the seed
+fn prompt_remaining_seed_shares(
+    rl: &mut Editor<()>,
+    first_share: SeedWords,
+    threshold: u8,
+) -> Result<Vec<SeedWords>, ExitError> {
+    let mut shares = vec![first_share];
+    while shares.len() < usize::from(threshold) {
+        println!("Share {} of {}:", shares.len() + 1, threshold);
+        let words = read_seed_words(rl)?;
+        match SeedShare::from_mnemonic(&words) {
+            Ok(_) => shares.push(words),
+            Err(e) => println!("That is not a valid share ({}), did you type it correctly?", e),
+        }
+    }
+    Ok(shares)
+}
+
+fn read_seed_words(rl: &mut Editor<()>) -> Result<SeedWords, ExitError> {
+    let input = Hidden::hide(rl.readline(">> ").map_err(|e| ExitError::new(ExitCode::IOError, e))?);
+    Ok(SeedWords::new(
+        input
+            .reveal()
+            .split_whitespace()
+            .map(|s| Hidden::hide(s.to_string()))
+            .collect(),
+    ))
+}
+
+/// Prompt the user for the optional seed passphrase the wallet was created with.
+pub fn prompt_seed_passphrase() -> Result<Option<SafePassword>, ExitError> {
+    let passphrase = Zeroizing::new(
+        prompt_password_stdout("Seed passphrase, leave empty if the wallet was created without one: ")
+            .map_err(|e| ExitError::new(ExitCode::IOError, e))?,
+    );
+    if passphrase.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(SafePassword::from(passphrase.to_string())))
+    }
+}
+
 /// Return seed matching the seed words.
 pub fn get_seed_from_seed_words(seed_words: &SeedWords) -> Result<CipherSeed, ExitError> {
     debug!(target: LOG_TARGET, "Return seed derived from the provided seed words");
@@ -94,6 +149,7 @@ pub async fn wallet_recovery(
     wallet: &WalletSqlite,
     base_node_config: &PeerConfig,
     retry_limit: usize,
+    full_scan: bool,
 ) -> Result<(), ExitError> {
     println!("\nPress Ctrl-C to stop the recovery process\n");
     // We dont care about the shutdown signal here, so we just create one
@@ -122,6 +178,7 @@ pub async fn wallet_recovery(
         .with_peers(peer_public_keys)
         // Do not make this a small number as wallet recovery needs to be resilient
         .with_retry_limit(retry_limit)
+        .with_full_scan(full_scan)
         .build_with_wallet(wallet, shutdown_signal);
 
     let mut event_stream = recovery_task.get_event_receiver();
@@ -140,23 +197,61 @@ pub async fn wallet_recovery(
             Ok(UtxoScannerEvent::Progress {
                 current_height,
                 tip_height,
+                throughput,
+                ..
             }) => {
                 let percentage_progress = (current_height * 100) / tip_height;
+                let eta = throughput
+                    .eta
+                    .map(|eta| format!(", about {:.0?} remaining", eta))
+                    .unwrap_or_default();
                 debug!(
                     target: LOG_TARGET,
-                    "{}: Recovery process {}% complete (Block {} of {}).",
+                    "{}: Recovery process {}% complete (Block {} of {}, {:.2} blocks/s, {:.2} outputs/s{}).",
                     Local::now(),
                     percentage_progress,
                     current_height,
-                    tip_height
+                    tip_height,
+                    throughput.blocks_per_second,
+                    throughput.outputs_per_second,
+                    eta
                 );
                 println!(
-                    "{}: Recovery process {}% complete (Block {} of {}).",
+                    "{}: Recovery process {}% complete (Block {} of {}, {:.2} blocks/s{}).",
                     Local::now(),
                     percentage_progress,
                     current_height,
-                    tip_height
+                    tip_height,
+                    throughput.blocks_per_second,
+                    eta
+                );
+            },
+            Ok(UtxoScannerEvent::BatchScanned { batch, totals }) => {
+                trace!(
+                    target: LOG_TARGET,
+                    "Scanned {} outputs of block {} of {} in {:.2?}, found {} worth {} ({} blocks scanned so far)",
+                    batch.num_outputs,
+                    batch.current_height,
+                    batch.tip_height,
+                    batch.rpc_latency,
+                    batch.num_found,
+                    batch.value_found,
+                    totals.num_blocks
+                );
+            },
+            Ok(UtxoScannerEvent::Summary(stats)) => {
+                let summary = format!(
+                    "Scanned {} outputs in {} blocks (heights {} to {}) at {:.2} blocks/s, average base node latency \
+                     {:.2?}",
+                    stats.num_outputs,
+                    stats.num_blocks,
+                    stats.start_height,
+                    stats.current_height,
+                    stats.blocks_per_second(),
+                    stats.average_rpc_latency()
                 );
+                info!(target: LOG_TARGET, "{}", summary);
+                println!("{}", summary);
             },
             Ok(UtxoScannerEvent::ScanningRoundFailed {
                 num_retries,
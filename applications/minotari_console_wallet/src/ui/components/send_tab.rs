@@ -272,7 +272,6 @@ impl SendTab {
                                     match Handle::current().block_on(app_state.send_one_sided_transaction(
                                         self.to_field.clone(),
                                         amount.into(),
-                                        UtxoSelectionCriteria::default(),
                                         fee_per_gram,
                                         self.message_field.clone(),
                                         tx,
@@ -311,7 +310,6 @@ impl SendTab {
                                     match Handle::current().block_on(app_state.send_transaction(
                                         self.to_field.clone(),
                                         amount.into(),
-                                        UtxoSelectionCriteria::default(),
                                         fee_per_gram,
                                         self.message_field.clone(),
                                         tx,
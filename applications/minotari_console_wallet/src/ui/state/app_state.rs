@@ -34,6 +34,7 @@ use minotari_wallet::{
     base_node_service::{handle::BaseNodeEventReceiver, service::BaseNodeState},
     connectivity_service::{OnlineStatus, WalletConnectivityHandle, WalletConnectivityInterface},
     output_manager_service::{handle::OutputManagerEventReceiver, service::Balance, UtxoSelectionCriteria},
+    payment_request::PaymentRequest,
     transaction_service::{
         handle::TransactionEventReceiver,
         storage::models::{CompletedTransaction, TxCancellationReason},
@@ -61,6 +62,7 @@ use tari_core::transactions::{
     transaction_components::{OutputFeatures, TemplateType, TransactionError},
     weight::TransactionWeight,
 };
+use tari_p2p::services::liveness::LivenessEventReceiver;
 use tari_shutdown::ShutdownSignal;
 use tari_utilities::hex::{from_hex, Hex};
 use tokio::{
@@ -205,7 +207,9 @@ impl AppState {
     }
 
     pub async fn check_connectivity(&mut self) {
+        // The connectivity service fails over between base nodes itself when it has more than one to choose from
         if self.get_custom_base_node().is_none() &&
+            self.wallet_connectivity.get_base_node_peers().len() < 2 &&
             self.wallet_connectivity.get_connectivity_status() == OnlineStatus::Offline
         {
             let current = self.get_selected_base_node();
@@ -294,7 +298,6 @@ impl AppState {
         &mut self,
         address: String,
         amount: u64,
-        selection_criteria: UtxoSelectionCriteria,
         fee_per_gram: u64,
         message: String,
         result_tx: watch::Sender<UiTransactionSendStatus>,
@@ -306,15 +309,11 @@ impl AppState {
                 .map_err(|_| UiError::PublicKeyParseError)?,
         };
 
-        let output_features = OutputFeatures { ..Default::default() };
-
         let fee_per_gram = fee_per_gram * uT;
         let tx_service_handle = inner.wallet.transaction_service.clone();
         tokio::spawn(send_transaction_task(
             address,
             MicroMinotari::from(amount),
-            selection_criteria,
-            output_features,
             message,
             fee_per_gram,
             tx_service_handle,
@@ -328,7 +327,6 @@ impl AppState {
         &mut self,
         address: String,
         amount: u64,
-        selection_criteria: UtxoSelectionCriteria,
         fee_per_gram: u64,
         message: String,
         result_tx: watch::Sender<UiTransactionSendStatus>,
@@ -339,15 +337,11 @@ impl AppState {
             Err(_) => TariAddress::from_bytes(&from_hex(&address).map_err(|_| UiError::PublicKeyParseError)?)
                 .map_err(|_| UiError::PublicKeyParseError)?,
         };
-        let output_features = OutputFeatures { ..Default::default() };
-
         let fee_per_gram = fee_per_gram * uT;
         let tx_service_handle = inner.wallet.transaction_service.clone();
         tokio::spawn(send_one_sided_transaction_task(
             address,
             MicroMinotari::from(amount),
-            selection_criteria,
-            output_features,
             message,
             fee_per_gram,
             tx_service_handle,
@@ -996,6 +990,10 @@ impl AppStateInner {
         self.wallet.contacts_service.get_contacts_liveness_event_stream()
     }
 
+    pub fn get_liveness_event_stream(&self) -> LivenessEventReceiver {
+        self.wallet.liveness_service.get_event_stream()
+    }
+
     pub fn get_output_manager_service_event_stream(&self) -> OutputManagerEventReceiver {
         self.wallet.output_manager_service.get_event_stream()
     }
@@ -1074,6 +1072,37 @@ impl AppStateInner {
         Ok(())
     }
 
+    /// Replaces the custom base node with the identity it rotated to, so that the new identity is used after a restart
+    pub fn follow_custom_base_node_rotation(&mut self, previous: &NodeId, peer: Peer) -> Result<(), UiError> {
+        if self
+            .data
+            .base_node_peer_custom
+            .as_ref()
+            .map_or(true, |custom| custom.node_id != *previous)
+        {
+            return Ok(());
+        }
+
+        self.wallet
+            .db
+            .set_client_key_value(CUSTOM_BASE_NODE_PUBLIC_KEY_KEY.to_string(), peer.public_key.to_string())?;
+        self.wallet.db.set_client_key_value(
+            CUSTOM_BASE_NODE_ADDRESS_KEY.to_string(),
+            peer.addresses.best().ok_or(UiError::NoAddress)?.to_string(),
+        )?;
+        info!(
+            target: LOG_TARGET,
+            "Custom base node {} rotated its identity to {}", previous, peer.public_key
+        );
+
+        self.data.base_node_peer_custom = Some(peer.clone());
+        if let Some((_, custom)) = self.data.base_node_list.first_mut() {
+            *custom = peer;
+        }
+        self.updated = true;
+        Ok(())
+    }
+
     pub async fn clear_custom_base_node_peer(&mut self) -> Result<(), UiError> {
         let previous = self.data.base_node_previous.clone();
         self.wallet
@@ -1245,11 +1274,7 @@ pub struct EventListItem {
 impl AppStateData {
     pub fn new(wallet_identity: &WalletIdentity, base_node_selected: Peer, base_node_config: PeerConfig) -> Self {
         let eid = wallet_identity.address.to_emoji_string();
-        let qr_link = format!(
-            "tari://{}/transactions/send?tariAddress={}",
-            wallet_identity.network,
-            wallet_identity.address.to_hex()
-        );
+        let qr_link = PaymentRequest::new(wallet_identity.address.clone()).to_uri();
         let code = QrCode::new(qr_link).unwrap();
         let image = code
             .render::<unicode::Dense1x2>()
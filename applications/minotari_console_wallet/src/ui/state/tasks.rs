@@ -28,7 +28,13 @@ use log::{error, warn};
 use minotari_wallet::{
     output_manager_service::UtxoSelectionCriteria,
     storage::{database::WalletDatabase, sqlite_db::wallet::WalletSqliteDatabase},
-    transaction_service::handle::{TransactionEvent, TransactionSendStatus, TransactionServiceHandle},
+    transaction_service::handle::{
+        ScheduledPayment,
+        SendOrQueueResult,
+        TransactionEvent,
+        TransactionSendStatus,
+        TransactionServiceHandle,
+    },
 };
 use rand::{random, rngs::OsRng};
 use tari_common_types::{
@@ -58,8 +64,6 @@ const LOG_TARGET: &str = "wallet::console_wallet::tasks ";
 pub async fn send_transaction_task(
     address: TariAddress,
     amount: MicroMinotari,
-    selection_criteria: UtxoSelectionCriteria,
-    output_features: OutputFeatures,
     message: String,
     fee_per_gram: MicroMinotari,
     mut transaction_service_handle: TransactionServiceHandle,
@@ -69,20 +73,22 @@ pub async fn send_transaction_task(
     let mut event_stream = transaction_service_handle.get_event_stream();
     let mut send_status = TransactionSendStatus::default();
     match transaction_service_handle
-        .send_transaction(
-            address,
+        .send_or_queue_transaction(ScheduledPayment {
+            destination: address,
             amount,
-            selection_criteria,
-            output_features,
             fee_per_gram,
             message,
-        )
+            one_sided: false,
+        })
         .await
     {
         Err(e) => {
             let _result = result_tx.send(UiTransactionSendStatus::Error(UiError::from(e).to_string()));
         },
-        Ok(our_tx_id) => {
+        Ok(SendOrQueueResult::Queued(_)) => {
+            let _result = result_tx.send(UiTransactionSendStatus::Queued);
+        },
+        Ok(SendOrQueueResult::Sent(our_tx_id)) => {
             loop {
                 let next_event = event_stream.recv().await;
                 match next_event {
@@ -134,8 +140,6 @@ pub async fn send_transaction_task(
 pub async fn send_one_sided_transaction_task(
     address: TariAddress,
     amount: MicroMinotari,
-    selection_criteria: UtxoSelectionCriteria,
-    output_features: OutputFeatures,
     message: String,
     fee_per_gram: MicroMinotari,
     mut transaction_service_handle: TransactionServiceHandle,
@@ -144,20 +148,22 @@ pub async fn send_one_sided_transaction_task(
     let _result = result_tx.send(UiTransactionSendStatus::Initiated);
     let mut event_stream = transaction_service_handle.get_event_stream();
     match transaction_service_handle
-        .send_one_sided_transaction(
-            address,
+        .send_or_queue_transaction(ScheduledPayment {
+            destination: address,
             amount,
-            selection_criteria,
-            output_features,
             fee_per_gram,
             message,
-        )
+            one_sided: true,
+        })
         .await
     {
         Err(e) => {
             let _result = result_tx.send(UiTransactionSendStatus::Error(UiError::from(e).to_string()));
         },
-        Ok(our_tx_id) => {
+        Ok(SendOrQueueResult::Queued(_)) => {
+            let _result = result_tx.send(UiTransactionSendStatus::Queued);
+        },
+        Ok(SendOrQueueResult::Sent(our_tx_id)) => {
             loop {
                 match event_stream.recv().await {
                     Ok(event) => {
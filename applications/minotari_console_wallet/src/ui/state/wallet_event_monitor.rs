@@ -25,13 +25,17 @@ use std::{ops::Deref, sync::Arc};
 use log::*;
 use minotari_wallet::{
     base_node_service::{handle::BaseNodeEvent, service::BaseNodeState},
-    connectivity_service::WalletConnectivityInterface,
+    connectivity_service::{BaseNodeChangeReason, WalletConnectivityEvent, WalletConnectivityInterface},
     output_manager_service::handle::OutputManagerEvent,
     transaction_service::handle::TransactionEvent,
 };
 use tari_common_types::transaction::TxId;
-use tari_comms::{connectivity::ConnectivityEvent, peer_manager::Peer};
+use tari_comms::{
+    connectivity::ConnectivityEvent,
+    peer_manager::{NodeId, Peer},
+};
 use tari_contacts::contacts_service::handle::ContactsLivenessEvent;
+use tari_p2p::services::liveness::LivenessEvent;
 use tokio::sync::{broadcast, RwLock};
 
 use crate::{
@@ -72,6 +76,7 @@ impl WalletEventMonitor {
         let wallet_connectivity = self.app_state_inner.read().await.get_wallet_connectivity();
         let mut connectivity_status = wallet_connectivity.get_connectivity_status_watch();
         let mut base_node_changed = wallet_connectivity.get_current_base_node_watcher();
+        let mut wallet_connectivity_events = wallet_connectivity.get_event_stream();
 
         let mut base_node_events = self.app_state_inner.read().await.get_base_node_event_stream();
         // let mut software_update_notif = self
@@ -83,6 +88,7 @@ impl WalletEventMonitor {
         //     .clone();
 
         let mut contacts_liveness_events = self.app_state_inner.read().await.get_contacts_liveness_event_stream();
+        let mut liveness_events = self.app_state_inner.read().await.get_liveness_event_stream();
 
         info!(target: LOG_TARGET, "Wallet Event Monitor starting");
         loop {
@@ -135,6 +141,122 @@ impl WalletEventMonitor {
                                     self.trigger_balance_refresh();
                                     notifier.transaction_cancelled(tx_id);
                                 },
+                                TransactionEvent::InputsSpentElsewhere{tx_id, conflicts} => {
+                                    self.add_notification(format!(
+                                        "Transaction Cancelled - TxId: {}, {} input(s) already spent elsewhere",
+                                        tx_id,
+                                        conflicts.len()
+                                    )).await;
+                                },
+                                TransactionEvent::ScheduledTransactionSucceeded{schedule_id, tx_id} => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(format!(
+                                        "Scheduled Transaction Sent - Schedule: {}, TxId: {}",
+                                        schedule_id,
+                                        tx_id
+                                    )).await;
+                                },
+                                TransactionEvent::ScheduledTransactionFailed{schedule_id, reason} => {
+                                    self.add_notification(format!(
+                                        "Scheduled Transaction Failed - Schedule: {}, {}",
+                                        schedule_id,
+                                        reason
+                                    )).await;
+                                },
+                                TransactionEvent::ScheduledTransactionExpired(schedule_id) => {
+                                    self.add_notification(format!(
+                                        "Scheduled Transaction Expired - Schedule: {}",
+                                        schedule_id
+                                    )).await;
+                                },
+                                TransactionEvent::TransactionQueued(queue_id) => {
+                                    self.add_notification(format!(
+                                        "Transaction Queued Until Online - Queue: {}",
+                                        queue_id
+                                    )).await;
+                                },
+                                TransactionEvent::QueuedTransactionSent{queue_id, tx_id} => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(format!(
+                                        "Queued Transaction Sent - Queue: {}, TxId: {}",
+                                        queue_id,
+                                        tx_id
+                                    )).await;
+                                },
+                                TransactionEvent::QueuedTransactionFailed{queue_id, reason} => {
+                                    self.add_notification(format!(
+                                        "Queued Transaction Failed - Queue: {}, {}",
+                                        queue_id,
+                                        reason
+                                    )).await;
+                                },
+                                TransactionEvent::QueuedTransactionExpired(queue_id) => {
+                                    self.add_notification(format!(
+                                        "Queued Transaction Expired - Queue: {}",
+                                        queue_id
+                                    )).await;
+                                },
+                                TransactionEvent::RecurringPaymentSucceeded{payment_id, tx_id} => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(format!(
+                                        "Recurring Payment Sent - Plan: {}, TxId: {}",
+                                        payment_id,
+                                        tx_id
+                                    )).await;
+                                },
+                                TransactionEvent::RecurringPaymentFailed{payment_id, reason} => {
+                                    self.add_notification(format!(
+                                        "Recurring Payment Failed - Plan: {}, {}",
+                                        payment_id,
+                                        reason
+                                    )).await;
+                                },
+                                TransactionEvent::RecurringPaymentPaused{payment_id, reason} => {
+                                    self.add_notification(format!(
+                                        "Recurring Payment Paused - Plan: {}, {}",
+                                        payment_id,
+                                        reason
+                                    )).await;
+                                },
+                                TransactionEvent::RecurringPaymentCompleted(payment_id) => {
+                                    self.add_notification(format!(
+                                        "Recurring Payment Completed - Plan: {}",
+                                        payment_id
+                                    )).await;
+                                },
+                                TransactionEvent::TransactionHistoryImported(count) => {
+                                    self.trigger_full_tx_state_refresh().await;
+                                    self.add_notification(format!(
+                                        "Transaction History Imported - {} transactions",
+                                        count
+                                    )).await;
+                                },
+                                TransactionEvent::TransactionFeeBumped{tx_id, fee} => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.trigger_balance_refresh();
+                                    self.add_notification(format!(
+                                        "Transaction Fee Bumped - TxId: {}, new fee: {}",
+                                        tx_id,
+                                        fee
+                                    )).await;
+                                },
+                                TransactionEvent::AtomicSwapUpdated{swap_id, state} => {
+                                    self.add_notification(format!(
+                                        "Atomic Swap Updated - Swap: {}, {}",
+                                        swap_id,
+                                        state
+                                    )).await;
+                                },
+                                TransactionEvent::MultisigSigningUpdated{signing_id, state} => {
+                                    self.add_notification(format!(
+                                        "Multisig Signing Updated - Signing: {}, {}",
+                                        signing_id,
+                                        state
+                                    )).await;
+                                },
                                 TransactionEvent::ReceivedTransaction(tx_id) => {
                                     self.trigger_tx_state_refresh(tx_id).await;
                                     self.trigger_balance_refresh();
@@ -147,6 +269,12 @@ impl WalletEventMonitor {
                                         format!("Transaction Reply Received - TxId: {}", tx_id)
                                     ).await;
                                 },
+                                TransactionEvent::TransactionAwaitingCosigners(tx_id) => {
+                                    self.trigger_tx_state_refresh(tx_id).await;
+                                    self.add_notification(
+                                        format!("Transaction Awaiting Cosigners - TxId: {}", tx_id)
+                                    ).await;
+                                },
                                 TransactionEvent::TransactionBroadcast(tx_id) => {
                                     self.trigger_tx_state_refresh(tx_id).await;
                                     self.trigger_balance_refresh();
@@ -220,6 +348,26 @@ impl WalletEventMonitor {
                         Err(broadcast::error::RecvError::Closed) => {}
                     }
                 },
+                result = wallet_connectivity_events.recv() => {
+                    match result {
+                        Ok(msg) => {
+                            trace!(target: LOG_TARGET, "Wallet Event Monitor received wallet connectivity event {:?}", msg);
+                            if let WalletConnectivityEvent::ActiveBaseNodeChanged {
+                                previous: Some(previous),
+                                reason: BaseNodeChangeReason::IdentityRotated,
+                                ..
+                            } = &*msg {
+                                if let Some(peer) = wallet_connectivity.get_current_base_node_peer() {
+                                    self.trigger_custom_base_node_rotation(previous, peer).await;
+                                }
+                            }
+                        },
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!(target: LOG_TARGET, "Missed {} from Wallet connectivity events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {}
+                    }
+                },
                 _ = base_node_changed.changed() => {
                     let peer = base_node_changed.borrow().as_ref().cloned();
                     if let Some(peer) = peer {
@@ -231,8 +379,18 @@ impl WalletEventMonitor {
                     match result {
                         Ok(msg) => {
                             trace!(target: LOG_TARGET, "Wallet Event Monitor received base node event {:?}", msg);
-                            if let BaseNodeEvent::BaseNodeStateChanged(state) = (*msg).clone() {
+                            match (*msg).clone() {
+                                BaseNodeEvent::BaseNodeStateChanged(state) => {
                                     self.trigger_base_node_state_refresh(state).await;
+                                },
+                                BaseNodeEvent::Reorged { depth, new_tip, .. } => {
+                                    self.add_notification(format!(
+                                        "Chain Reorganization - {} block(s) replaced, new tip {}",
+                                        depth,
+                                        new_tip.height
+                                    )).await;
+                                },
+                                BaseNodeEvent::NewBlockDetected(..) => {},
                             }
                         },
                         Err(broadcast::error::RecvError::Lagged(n)) => {
@@ -266,6 +424,10 @@ impl WalletEventMonitor {
                                     );
                                     self.trigger_contacts_refresh().await;
                                 }
+                                ContactsLivenessEvent::ContactSaved(_) |
+                                ContactsLivenessEvent::ContactRemoved(_) => {
+                                    self.trigger_contacts_refresh().await;
+                                }
                                 ContactsLivenessEvent::NetworkSilence => {},
                             }
                         }
@@ -275,6 +437,28 @@ impl WalletEventMonitor {
                         Err(broadcast::error::RecvError::Closed) => {}
                     }
                 }
+                result = liveness_events.recv() => {
+                    match result {
+                        Ok(msg) => match &*msg {
+                            LivenessEvent::ClockSkewDetected(skew) => {
+                                self.add_notification(format!(
+                                    "Local Clock Skew Detected - offset {}, transactions with time locks may be \
+                                     rejected",
+                                    skew
+                                )).await;
+                            },
+                            LivenessEvent::ClockSkewResolved(skew) => {
+                                self.add_notification(format!("Local Clock Skew Resolved - offset {}", skew)).await;
+                            },
+                            // Only clock skew is shown, contact liveness is handled by the contacts events
+                            _ => (),
+                        },
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!(target: LOG_TARGET, "Missed {} from Liveness events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {}
+                    }
+                }
                 _ = shutdown_signal.wait() => {
                     info!(
                         target: LOG_TARGET,
@@ -348,6 +532,14 @@ impl WalletEventMonitor {
         }
     }
 
+    async fn trigger_custom_base_node_rotation(&mut self, previous: &NodeId, peer: Peer) {
+        let mut inner = self.app_state_inner.write().await;
+
+        if let Err(e) = inner.follow_custom_base_node_rotation(previous, peer) {
+            warn!(target: LOG_TARGET, "Error refresh app_state: {}", e);
+        }
+    }
+
     fn trigger_balance_refresh(&mut self) {
         if let Err(e) = self.balance_enquiry_debounce_tx.send(()) {
             warn!(target: LOG_TARGET, "Error refresh app_state: {}", e);
@@ -22,7 +22,7 @@
 
 #![allow(dead_code, unused)]
 
-use std::{fs, io::Stdout, path::PathBuf};
+use std::{fs, io::Stdout, iter, path::PathBuf};
 
 use clap::Parser;
 use log::*;
@@ -115,6 +115,26 @@ impl PeerConfig {
         }
     }
 
+    /// Get the base node peers the wallet fails over between, in order of preference. The prioritised base node peer
+    /// (see [PeerConfig::get_base_node_peer]) comes first, followed by the other configured base node peers or, if
+    /// there are none, the other peer seeds. A custom base node is used on its own.
+    pub fn get_base_node_peers(&self) -> Result<Vec<Peer>, ExitError> {
+        let preferred = self.get_base_node_peer()?;
+        if self.base_node_custom.is_some() {
+            return Ok(vec![preferred]);
+        }
+        let fallbacks = if self.base_node_peers.is_empty() {
+            &self.peer_seeds
+        } else {
+            &self.base_node_peers
+        };
+        let others = fallbacks
+            .iter()
+            .filter(|p| p.public_key != preferred.public_key)
+            .cloned();
+        Ok(iter::once(preferred.clone()).chain(others).collect())
+    }
+
     /// Returns all the peers from the PeerConfig.
     /// In order: Custom base node, service peers, peer seeds.
     pub fn get_all_peers(&self) -> Vec<Peer> {
@@ -335,6 +355,7 @@ pub fn recovery_mode(
     wallet_config: &WalletConfig,
     wallet_mode: WalletMode,
     wallet: WalletSqlite,
+    full_scan: bool,
 ) -> Result<(), ExitError> {
     // Do not remove this println!
     const CUCUMBER_TEST_MARKER_A: &str = "Minotari Console Wallet running... (Recovery mode started)";
@@ -345,6 +366,7 @@ pub fn recovery_mode(
         &wallet,
         base_node_config,
         wallet_config.recovery_retry_limit,
+        full_scan,
     )) {
         Ok(_) => println!("Wallet recovered!"),
         Err(e) => {
@@ -483,6 +505,16 @@ mod test {
                 CliCommands::ClaimShaAtomicSwapRefund(_) => {},
                 CliCommands::RevalidateWalletDb => {},
                 CliCommands::RegisterValidatorNode(_) => {},
+                CliCommands::ExportDerivationScheme(_) => {},
+                CliCommands::ExportSeedShares(_) => {},
+                CliCommands::CreateBackup(_) => {},
+                CliCommands::CreateSendTemplate(_) => {},
+                CliCommands::ListSendTemplates => {},
+                CliCommands::DeleteSendTemplate(_) => {},
+                CliCommands::SendFromTemplate(_) => {},
+                CliCommands::CreateUnsignedTransaction(_) => {},
+                CliCommands::SignUnsignedTransaction(_) => {},
+                CliCommands::ImportSignedTransaction(_) => {},
             }
         }
         assert!(get_balance && send_tari && burn_tari && make_it_rain && coin_split && discover_peer && whois);
@@ -0,0 +1,293 @@
+//  Copyright 2020, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! An append-only audit log of the block templates composed and blocks submitted by the proxy, stored as JSON lines so
+//! that pool operators can reconcile orphaned or missing blocks after the fact.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::MmProxyError;
+
+const LOG_TARGET: &str = "minotari_mm_proxy::audit_log";
+
+/// A single audit log entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AuditRecord {
+    /// A Minotari block template was merged into a Monero block template and handed to the miner
+    TemplateComposed {
+        timestamp: i64,
+        tari_height: u64,
+        merge_mining_hash: String,
+        monero_seed_hash: String,
+        monero_difficulty: u64,
+        tari_difficulty: u64,
+    },
+    /// The miner submitted a solved Monero block
+    BlockSubmitted {
+        timestamp: i64,
+        tari_height: Option<u64>,
+        merge_mining_hash: String,
+        monero_block_hash: String,
+        tari_header_hash: Option<String>,
+        outcome: SubmissionOutcome,
+    },
+}
+
+impl AuditRecord {
+    pub fn template_composed(
+        tari_height: u64,
+        merge_mining_hash: String,
+        monero_seed_hash: String,
+        monero_difficulty: u64,
+        tari_difficulty: u64,
+    ) -> Self {
+        AuditRecord::TemplateComposed {
+            timestamp: Utc::now().timestamp(),
+            tari_height,
+            merge_mining_hash,
+            monero_seed_hash,
+            monero_difficulty,
+            tari_difficulty,
+        }
+    }
+
+    pub fn block_submitted(
+        tari_height: Option<u64>,
+        merge_mining_hash: String,
+        monero_block_hash: String,
+        tari_header_hash: Option<String>,
+        outcome: SubmissionOutcome,
+    ) -> Self {
+        AuditRecord::BlockSubmitted {
+            timestamp: Utc::now().timestamp(),
+            tari_height,
+            merge_mining_hash,
+            monero_block_hash,
+            tari_header_hash,
+            outcome,
+        }
+    }
+
+    pub fn tari_height(&self) -> Option<u64> {
+        match self {
+            AuditRecord::TemplateComposed { tari_height, .. } => Some(*tari_height),
+            AuditRecord::BlockSubmitted { tari_height, .. } => *tari_height,
+        }
+    }
+
+    pub fn merge_mining_hash(&self) -> &str {
+        match self {
+            AuditRecord::TemplateComposed { merge_mining_hash, .. } |
+            AuditRecord::BlockSubmitted { merge_mining_hash, .. } => merge_mining_hash,
+        }
+    }
+}
+
+/// The outcome of a block submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum SubmissionOutcome {
+    /// The Minotari base node accepted the block
+    Accepted,
+    /// The Minotari base node rejected the block
+    Rejected { reason: String },
+    /// The achieved difficulty did not meet the Minotari target, so the block was not submitted to the base node
+    InsufficientDifficulty { achieved: u64, target: u64 },
+    /// No block template matching the merge mining hash was found, e.g. it expired or was already submitted
+    TemplateNotFound,
+}
+
+/// Criteria for querying the audit log. Unset criteria match every record.
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    pub from_height: Option<u64>,
+    pub to_height: Option<u64>,
+    pub merge_mining_hash: Option<String>,
+    pub limit: Option<usize>,
+}
+
+impl AuditQuery {
+    fn matches(&self, record: &AuditRecord) -> bool {
+        if let Some(hash) = &self.merge_mining_hash {
+            if !record.merge_mining_hash().eq_ignore_ascii_case(hash) {
+                return false;
+            }
+        }
+        if self.from_height.is_none() && self.to_height.is_none() {
+            return true;
+        }
+        record.tari_height().map_or(false, |height| {
+            self.from_height.map_or(true, |from| height >= from) && self.to_height.map_or(true, |to| height <= to)
+        })
+    }
+}
+
+/// Append-only JSON lines audit log. A disabled log silently discards records.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    path: Option<PathBuf>,
+    lock: Arc<Mutex<()>>,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path: Some(path),
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            path: None,
+            lock: Arc::new(Mutex::new(())),
+        }
+    }
+
+    /// Append a record to the log. Failing to write the audit log must never interrupt mining, so errors are only
+    /// logged.
+    pub fn record(&self, record: &AuditRecord) {
+        if let Err(err) = self.try_record(record) {
+            warn!(target: LOG_TARGET, "Failed to write merge mining audit record: {}", err);
+        }
+    }
+
+    fn try_record(&self, record: &AuditRecord) -> Result<(), MmProxyError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let _guard = self.lock.lock().expect("audit log lock poisoned");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        file.write_all(line.as_bytes())?;
+        Ok(())
+    }
+
+    /// Returns the records matching the query, oldest first
+    pub fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, MmProxyError> {
+        let Some(path) = &self.path else {
+            return Ok(Vec::new());
+        };
+        let _guard = self.lock.lock().expect("audit log lock poisoned");
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = serde_json::from_str::<AuditRecord>(&line)?;
+            if query.matches(&record) {
+                records.push(record);
+            }
+        }
+        if let Some(limit) = query.limit {
+            // Keep the most recent records
+            let skip = records.len().saturating_sub(limit);
+            records.drain(..skip);
+        }
+        Ok(records)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_records_and_queries() {
+        let path = std::env::temp_dir().join(format!("mm_audit_{}.jsonl", Utc::now().timestamp_nanos()));
+        let audit_log = AuditLog::new(path.clone());
+        audit_log.record(&AuditRecord::template_composed(
+            10,
+            "aa".to_string(),
+            "00".to_string(),
+            5,
+            6,
+        ));
+        audit_log.record(&AuditRecord::template_composed(
+            11,
+            "bb".to_string(),
+            "00".to_string(),
+            5,
+            6,
+        ));
+        audit_log.record(&AuditRecord::block_submitted(
+            Some(11),
+            "bb".to_string(),
+            "cc".to_string(),
+            Some("dd".to_string()),
+            SubmissionOutcome::Accepted,
+        ));
+        audit_log.record(&AuditRecord::block_submitted(
+            None,
+            "ee".to_string(),
+            "ff".to_string(),
+            None,
+            SubmissionOutcome::TemplateNotFound,
+        ));
+
+        assert_eq!(audit_log.query(&AuditQuery::default()).unwrap().len(), 4);
+        let records = audit_log
+            .query(&AuditQuery {
+                from_height: Some(11),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        let records = audit_log
+            .query(&AuditQuery {
+                merge_mining_hash: Some("EE".to_string()),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(records[0].tari_height(), None);
+        let records = audit_log
+            .query(&AuditQuery {
+                limit: Some(1),
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(records[0].merge_mining_hash(), "ee");
+
+        assert!(AuditLog::disabled().query(&AuditQuery::default()).unwrap().is_empty());
+        fs::remove_file(path).unwrap();
+    }
+}
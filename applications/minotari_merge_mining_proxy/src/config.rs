@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::path::PathBuf;
+
 use minotari_wallet_grpc_client::GrpcAuthentication;
 use serde::{Deserialize, Serialize};
 use tari_common::{
@@ -71,6 +73,10 @@ pub struct MergeMiningProxyConfig {
     pub coinbase_extra: String,
     /// Selected network
     pub network: Network,
+    /// Record every composed block template and block submission in the audit log
+    pub enable_audit_log: bool,
+    /// The audit log file. A relative path is relative to the base path.
+    pub audit_log_file: PathBuf,
 }
 
 impl Default for MergeMiningProxyConfig {
@@ -92,6 +98,8 @@ impl Default for MergeMiningProxyConfig {
             max_randomx_vms: 5,
             coinbase_extra: "tari_merge_mining_proxy".to_string(),
             network: Default::default(),
+            enable_audit_log: true,
+            audit_log_file: PathBuf::from("merge_mining_audit.jsonl"),
         }
     }
 }
@@ -23,6 +23,7 @@
 // non-64-bit not supported
 minotari_app_utilities::deny_non_64_bit_archs!();
 
+mod audit_log;
 mod block_template_data;
 mod block_template_protocol;
 mod cli;
@@ -22,6 +22,7 @@
 
 use minotari_merge_mining_proxy::Cli;
 
+mod audit_log;
 mod block_template_data;
 mod block_template_protocol;
 mod cli;
@@ -43,17 +43,16 @@ use minotari_node_grpc_client::{grpc, grpc::base_node_client::BaseNodeClient};
 use minotari_wallet_grpc_client::{grpc::wallet_client::WalletClient, ClientAuthenticationInterceptor};
 use reqwest::{ResponseBuilderExt, Url};
 use serde_json as json;
-use tari_core::proof_of_work::{
-    monero_rx,
-    monero_rx::FixedByteArray,
-    randomx_difficulty,
-    randomx_factory::RandomXFactory,
+use tari_core::{
+    blocks::BlockHeader,
+    proof_of_work::{monero_rx, monero_rx::FixedByteArray, randomx_difficulty, randomx_factory::RandomXFactory},
 };
 use tari_utilities::hex::Hex;
 use tonic::{codegen::InterceptedService, transport::Channel};
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::{
+    audit_log::{AuditLog, AuditQuery, AuditRecord, SubmissionOutcome},
     block_template_data::BlockTemplateRepository,
     block_template_protocol::{BlockTemplateProtocol, MoneroMiningData},
     common::{json_rpc, monero_rpc::CoreRpcErrorCode, proxy, proxy::convert_json_to_hyper_json_response},
@@ -80,6 +79,7 @@ impl MergeMiningProxyService {
         wallet_client: WalletClient<InterceptedService<Channel, ClientAuthenticationInterceptor>>,
         block_templates: BlockTemplateRepository,
         randomx_factory: RandomXFactory,
+        audit_log: AuditLog,
     ) -> Self {
         debug!(target: LOG_TARGET, "Config: {:?}", config);
         Self {
@@ -93,6 +93,7 @@ impl MergeMiningProxyService {
                 current_monerod_server: Arc::new(RwLock::new(None)),
                 last_assigned_monerod_server: Arc::new(RwLock::new(None)),
                 randomx_factory,
+                audit_log,
             },
         }
     }
@@ -164,6 +165,7 @@ struct InnerService {
     current_monerod_server: Arc<RwLock<Option<String>>>,
     last_assigned_monerod_server: Arc<RwLock<Option<String>>>,
     randomx_factory: RandomXFactory,
+    audit_log: AuditLog,
 }
 
 impl InnerService {
@@ -247,6 +249,7 @@ impl InnerService {
             let hash = monero_rx::extract_tari_hash_from_block(&monero_block)?.ok_or_else(|| {
                 MmProxyError::MissingDataError("Could not find Minotari header in coinbase".to_string())
             })?;
+            let monero_block_hash = hex::encode(monero_rx::calculate_monero_block_hash(&monero_block)?);
 
             debug!(
                 target: LOG_TARGET,
@@ -262,6 +265,13 @@ impl InnerService {
                         "Block `{}` submitted but no matching block template was found, possible duplicate submission",
                         hex::encode(hash)
                     );
+                    self.audit_log.record(&AuditRecord::block_submitted(
+                        None,
+                        hex::encode(hash),
+                        monero_block_hash,
+                        None,
+                        SubmissionOutcome::TemplateNotFound,
+                    ));
                     continue;
                 },
             };
@@ -274,7 +284,8 @@ impl InnerService {
             let height = header_mut.height;
             BorshSerialize::serialize(&monero_data, &mut header_mut.pow.as_mut().unwrap().pow_data)
                 .map_err(|err| MmProxyError::ConversionError(err.to_string()))?;
-            let tari_header = header_mut.clone().try_into().map_err(MmProxyError::ConversionError)?;
+            let tari_header: BlockHeader = header_mut.clone().try_into().map_err(MmProxyError::ConversionError)?;
+            let tari_header_hash = Some(tari_header.hash().to_hex());
             let mut base_node_client = self.base_node_client.clone();
             let start = Instant::now();
             let achieved_target = if self.config.check_tari_difficulty_before_submit {
@@ -321,6 +332,13 @@ impl InnerService {
                             );
                         }
                         self.block_templates.remove(&hash).await;
+                        self.audit_log.record(&AuditRecord::block_submitted(
+                            Some(height),
+                            hex::encode(hash),
+                            monero_block_hash,
+                            tari_header_hash,
+                            SubmissionOutcome::Accepted,
+                        ));
                     },
                     Err(err) => {
                         debug!(
@@ -330,6 +348,15 @@ impl InnerService {
                             start.elapsed(),
                             err
                         );
+                        self.audit_log.record(&AuditRecord::block_submitted(
+                            Some(height),
+                            hex::encode(hash),
+                            monero_block_hash,
+                            tari_header_hash,
+                            SubmissionOutcome::Rejected {
+                                reason: err.message().to_string(),
+                            },
+                        ));
 
                         if !self.config.submit_to_origin {
                             // When "submit to origin" is turned off the block is never submitted to monerod, and so we
@@ -343,7 +370,18 @@ impl InnerService {
                         }
                     },
                 }
-            };
+            } else {
+                self.audit_log.record(&AuditRecord::block_submitted(
+                    Some(height),
+                    hex::encode(hash),
+                    monero_block_hash,
+                    tari_header_hash,
+                    SubmissionOutcome::InsufficientDifficulty {
+                        achieved: achieved_target,
+                        target: block_data.tari_difficulty,
+                    },
+                ));
+            }
             self.block_templates.remove_outdated().await;
         }
 
@@ -476,6 +514,13 @@ impl InnerService {
             }),
         );
 
+        self.audit_log.record(&AuditRecord::template_composed(
+            tari_height,
+            mining_hash.to_hex(),
+            final_block_template_data.template.monero_seed.to_hex(),
+            final_block_template_data.template.monero_difficulty,
+            final_block_template_data.template.tari_difficulty,
+        ));
         self.block_templates
             .save(mining_hash, final_block_template_data.template)
             .await;
@@ -798,8 +843,29 @@ impl InnerService {
         }
     }
 
+    /// Serves `GET /audit_log`, optionally filtered by the `from_height`, `to_height`, `merge_mining_hash` and `limit`
+    /// query parameters. This request is answered by the proxy and never forwarded to monerod.
+    fn handle_get_audit_log(&self, request: &Request<Bytes>) -> Result<Response<Body>, MmProxyError> {
+        let mut query = AuditQuery::default();
+        for (key, value) in url::form_urlencoded::parse(request.uri().query().unwrap_or_default().as_bytes()) {
+            let parse_err = |_| MmProxyError::ConversionError(format!("Invalid value `{}` for `{}`", value, key));
+            match key.as_ref() {
+                "from_height" => query.from_height = Some(value.parse().map_err(parse_err)?),
+                "to_height" => query.to_height = Some(value.parse().map_err(parse_err)?),
+                "limit" => query.limit = Some(value.parse().map_err(parse_err)?),
+                "merge_mining_hash" => query.merge_mining_hash = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        let records = self.audit_log.query(&query)?;
+        proxy::json_response(StatusCode::OK, &json!({ "records": records }))
+    }
+
     async fn handle(self, method_name: &str, request: Request<Bytes>) -> Result<Response<Body>, MmProxyError> {
         let start = Instant::now();
+        if request.method() == Method::GET && request.uri().path() == "/audit_log" {
+            return self.handle_get_audit_log(&request);
+        }
 
         debug!(
             target: LOG_TARGET,
@@ -41,6 +41,7 @@ use tonic::{
 };
 
 use crate::{
+    audit_log::AuditLog,
     block_template_data::BlockTemplateRepository,
     config::MergeMiningProxyConfig,
     error::MmProxyError,
@@ -68,6 +69,13 @@ pub async fn start_merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
 
     let listen_addr = multiaddr_to_socketaddr(&config.listener_address)?;
     let randomx_factory = RandomXFactory::new(config.max_randomx_vms);
+    let audit_log = if config.enable_audit_log {
+        let path = cli.common.get_base_path().join(&config.audit_log_file);
+        info!(target: LOG_TARGET, "Recording merge mining audit log to {}", path.display());
+        AuditLog::new(path)
+    } else {
+        AuditLog::disabled()
+    };
     let randomx_service = MergeMiningProxyService::new(
         config,
         client,
@@ -75,6 +83,7 @@ pub async fn start_merge_miner(cli: Cli) -> Result<(), anyhow::Error> {
         wallet_client,
         BlockTemplateRepository::new(),
         randomx_factory,
+        audit_log,
     );
     let service = make_service_fn(|_conn| future::ready(Result::<_, Infallible>::Ok(randomx_service.clone())));
 
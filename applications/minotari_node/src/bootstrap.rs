@@ -28,13 +28,20 @@ use tari_common::{
     configuration::bootstrap::ApplicationType,
     exit_codes::{ExitCode, ExitError},
 };
-use tari_comms::{peer_manager::Peer, protocol::rpc::RpcServer, NodeIdentity, UnspawnedCommsNode};
+use tari_common_types::types::PublicKey;
+use tari_comms::{
+    peer_manager::{NodeId, Peer},
+    protocol::rpc::RpcServer,
+    NodeIdentity,
+    UnspawnedCommsNode,
+};
 use tari_comms_dht::Dht;
 use tari_core::{
     base_node,
     base_node::{
         chain_metadata_service::ChainMetadataServiceInitializer,
         service::BaseNodeServiceInitializer,
+        snapshot::SnapshotServiceInitializer,
         state_machine_service::initializer::BaseNodeStateMachineInitializer,
         LocalNodeCommsInterface,
         StateMachineHandle,
@@ -58,8 +65,9 @@ use tari_p2p::{
 };
 use tari_service_framework::{ServiceHandles, StackBuilder};
 use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::Hex;
 
-use crate::ApplicationConfig;
+use crate::{identity_rotation, ApplicationConfig};
 
 const LOG_TARGET: &str = "c::bn::initialization";
 /// The minimum buffer size for the base node pubsub_connector channel
@@ -101,6 +109,27 @@ where B: BlockchainBackend + 'static
 
         debug!(target: LOG_TARGET, "{} sync peer(s) configured", sync_peers.len());
 
+        let sync_allowlist = base_node_config
+            .sync_allowlist
+            .iter()
+            .map(|s| PublicKey::from_hex(s).map(|pk| NodeId::from_public_key(&pk)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| ExitError::new(ExitCode::ConfigError, format!("Invalid sync allowlist entry: {}", e)))?;
+        if !sync_allowlist.is_empty() {
+            info!(
+                target: LOG_TARGET,
+                "Only serving sync to {} allowlisted peer(s)",
+                sync_allowlist.len()
+            );
+        }
+
+        let tip_height = self
+            .db
+            .get_chain_metadata()
+            .map_err(|e| ExitError::new(ExitCode::DatabaseError, e))?
+            .height_of_longest_chain();
+        let max_clock_skew = Duration::from_secs(self.rules.consensus_constants(tip_height).future_time_limit());
+
         let mempool_sync = MempoolSyncInitializer::new(mempool_config, self.mempool.clone());
         let mempool_protocol = mempool_sync.get_protocol_extension();
 
@@ -138,10 +167,18 @@ where B: BlockchainBackend + 'static
                 peer_message_subscriptions.clone(),
             ))
             .add_initializer(mempool_sync)
+            .add_initializer(SnapshotServiceInitializer::new(
+                peer_message_subscriptions.clone(),
+                self.db.clone().into(),
+                base_node_config.state_machine.snapshot_interval,
+                base_node_config.state_machine.snapshot_advertisement_period,
+            ))
             .add_initializer(LivenessInitializer::new(
                 LivenessConfig {
                     auto_ping_interval: Some(base_node_config.metadata_auto_ping_interval),
                     monitored_peers: sync_peers.clone(),
+                    max_clock_skew: Some(max_clock_skew),
+                    ntp_servers: base_node_config.ntp_servers.clone(),
                     ..Default::default()
                 },
                 peer_message_subscriptions,
@@ -163,11 +200,13 @@ where B: BlockchainBackend + 'static
             .expect("P2pInitializer was not added to the stack or did not add UnspawnedCommsNode");
 
         let comms = comms.add_protocol_extension(mempool_protocol);
-        let comms = Self::setup_rpc_services(comms, &handles, self.db.into(), &p2p_config);
+        let comms = Self::setup_rpc_services(comms, &handles, self.db.into(), &p2p_config, sync_allowlist);
         let comms = initialization::spawn_comms_using_transport(comms, p2p_config.transport.clone())
             .await
             .map_err(|e| e.to_exit_error())?;
 
+        identity_rotation::serve_previous_onion_address(&base_node_config, &p2p_config.transport.tor, &comms).await?;
+
         // Save final node identity after comms has initialized. This is required because the public_address can be
         // changed by comms during initialization when using tor.
         match p2p_config.transport.transport_type {
@@ -192,6 +231,7 @@ where B: BlockchainBackend + 'static
         handles: &ServiceHandles,
         db: AsyncBlockchainDb<B>,
         config: &P2pConfig,
+        sync_allowlist: Vec<NodeId>,
     ) -> UnspawnedCommsNode {
         let dht = handles.expect_handle::<Dht>();
         let base_node_service = handles.expect_handle::<LocalNodeCommsInterface>();
@@ -206,6 +246,7 @@ where B: BlockchainBackend + 'static
             .add_service(base_node::create_base_node_sync_rpc_service(
                 db.clone(),
                 base_node_service,
+                sync_allowlist,
             ))
             .add_service(mempool::create_mempool_rpc_service(
                 handles.expect_handle::<MempoolHandle>(),
@@ -30,7 +30,7 @@ use tari_common::{
 use tari_comms::{peer_manager::NodeIdentity, protocol::rpc::RpcServerHandle, CommsNode};
 use tari_comms_dht::Dht;
 use tari_core::{
-    base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, StateMachineHandle},
+    base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface, ReorgAlarm, StateMachineHandle},
     chain_storage::{create_lmdb_database, BlockchainDatabase, ChainStorageError, LMDBDatabase, Validators},
     consensus::ConsensusManager,
     mempool::{service::LocalMempoolService, Mempool},
@@ -105,6 +105,11 @@ impl BaseNodeContext {
         self.base_node_handles.expect_handle()
     }
 
+    /// Returns the chain reorg depth alarm
+    pub fn reorg_alarm(&self) -> ReorgAlarm {
+        self.base_node_handles.expect_handle()
+    }
+
     /// Returns this node's identity.
     pub fn base_node_identity(&self) -> Arc<NodeIdentity> {
         self.base_node_comms.node_identity()
@@ -0,0 +1,85 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+
+use super::{CommandContext, HandleCommand};
+use crate::{table::Table, utils::format_duration_basic};
+
+/// Lists the RPC sessions currently being served by this node
+#[derive(Debug, Parser)]
+pub struct Args {}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, _: Args) -> Result<(), Error> {
+        self.list_rpc_sessions().await
+    }
+}
+
+impl CommandContext {
+    /// Function to process the list-rpc-sessions command
+    pub async fn list_rpc_sessions(&mut self) -> Result<(), Error> {
+        let sessions = self.rpc_server.get_active_sessions().await?;
+        if sessions.is_empty() {
+            println!("No active RPC sessions.");
+            return Ok(());
+        }
+
+        let num_sessions = sessions.len();
+        let peer_manager = self.comms.peer_manager();
+        let mut table = Table::new();
+        table.set_titles(vec![
+            "Session",
+            "NodeId",
+            "User Agent",
+            "Protocol",
+            "Requests",
+            "Bytes Out",
+            "Duration",
+        ]);
+        for session in sessions {
+            let user_agent = peer_manager
+                .find_by_node_id(&session.peer_node_id)
+                .await?
+                .map(|peer| peer.user_agent)
+                .filter(|ua| !ua.is_empty())
+                .unwrap_or_else(|| "<unknown>".to_string());
+            table.add_row(row![
+                session.session_id,
+                session.peer_node_id,
+                user_agent,
+                session.protocol_name(),
+                session.requests_served,
+                session.bytes_out,
+                format_duration_basic(session.duration),
+            ]);
+        }
+
+        table.print_stdout();
+
+        println!("{} active RPC session(s)", num_sessions);
+        Ok(())
+    }
+}
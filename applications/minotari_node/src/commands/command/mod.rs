@@ -41,12 +41,14 @@ mod list_connections;
 mod list_headers;
 mod list_peers;
 mod list_reorgs;
+mod list_rpc_sessions;
 mod list_validator_nodes;
 mod period_stats;
 mod ping_peer;
 mod quit;
 mod reset_offline_peers;
 mod rewind_blockchain;
+mod rotate_identity;
 mod search_kernel;
 mod search_utxo;
 mod status;
@@ -71,7 +73,7 @@ use tari_comms::{
     CommsNode,
     NodeIdentity,
 };
-use tari_comms_dht::{DhtDiscoveryRequester, MetricsCollectorHandle};
+use tari_comms_dht::{DhtDiscoveryRequester, DhtRequester, MetricsCollectorHandle};
 use tari_core::{
     base_node::{state_machine_service::states::StatusInfo, LocalNodeCommsInterface},
     blocks::ChainHeader,
@@ -110,12 +112,14 @@ pub enum Command {
     PingPeer(ping_peer::Args),
     ResetOfflinePeers(reset_offline_peers::Args),
     RewindBlockchain(rewind_blockchain::Args),
+    RotateIdentity(rotate_identity::Args),
     AddPeer(add_peer::ArgsAddPeer),
     BanPeer(ban_peer::ArgsBan),
     UnbanPeer(ban_peer::ArgsUnban),
     UnbanAllPeers(unban_all_peers::Args),
     ListBannedPeers(list_banned_peers::Args),
     ListConnections(list_connections::Args),
+    ListRpcSessions(list_rpc_sessions::Args),
     ListHeaders(list_headers::Args),
     CheckDb(check_db::Args),
     PeriodStats(period_stats::Args),
@@ -154,6 +158,7 @@ pub struct CommandContext {
     consensus_rules: ConsensusManager,
     blockchain_db: AsyncBlockchainDb<LMDBDatabase>,
     discovery_service: DhtDiscoveryRequester,
+    dht_requester: DhtRequester,
     dht_metrics_collector: MetricsCollectorHandle,
     rpc_server: RpcServerHandle,
     base_node_identity: Arc<NodeIdentity>,
@@ -174,6 +179,7 @@ impl CommandContext {
             consensus_rules: ctx.consensus_rules().clone(),
             blockchain_db: ctx.blockchain_db().into(),
             discovery_service: ctx.base_node_dht().discovery_service_requester(),
+            dht_requester: ctx.base_node_dht().dht_requester(),
             dht_metrics_collector: ctx.base_node_dht().metrics_collector(),
             rpc_server: ctx.rpc_server(),
             base_node_identity: ctx.base_node_identity(),
@@ -205,12 +211,14 @@ impl CommandContext {
                 Command::UnbanPeer(_) |
                 Command::GetPeer(_) |
                 Command::ResetOfflinePeers(_) |
+                Command::RotateIdentity(_) |
                 Command::DialPeer(_) |
                 Command::PingPeer(_) |
                 Command::DiscoverPeer(_) |
                 Command::ListPeers(_) |
                 Command::ListBannedPeers(_) |
                 Command::ListConnections(_) |
+                Command::ListRpcSessions(_) |
                 Command::GetNetworkStats(_) |
                 Command::BlockTiming(_) |
                 Command::GetChainMetadata(_) |
@@ -273,6 +281,7 @@ impl HandleCommand<Command> for CommandContext {
             Command::UnbanPeer(args) => self.handle_command(args).await,
             Command::ResetOfflinePeers(args) => self.handle_command(args).await,
             Command::RewindBlockchain(args) => self.handle_command(args).await,
+            Command::RotateIdentity(args) => self.handle_command(args).await,
             Command::UnbanAllPeers(args) => self.handle_command(args).await,
             Command::ListHeaders(args) => self.handle_command(args).await,
             Command::CheckDb(args) => self.handle_command(args).await,
@@ -285,6 +294,7 @@ impl HandleCommand<Command> for CommandContext {
             Command::SearchUtxo(args) => self.handle_command(args).await,
             Command::SearchKernel(args) => self.handle_command(args).await,
             Command::ListConnections(args) => self.handle_command(args).await,
+            Command::ListRpcSessions(args) => self.handle_command(args).await,
             Command::GetMempoolStats(args) => self.handle_command(args).await,
             Command::GetMempoolState(args) => self.handle_command(args).await,
             Command::GetMempoolTx(args) => self.handle_command(args).await,
@@ -0,0 +1,59 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use anyhow::Error;
+use async_trait::async_trait;
+use clap::Parser;
+
+use super::{CommandContext, HandleCommand};
+use crate::identity_rotation::{next_identity_file, prepare_identity_rotation};
+
+/// Rotate the node identity. The new identity is announced to the network and is used once the node is restarted
+/// after the identity rotation window has elapsed.
+#[derive(Debug, Parser)]
+pub struct Args {}
+
+#[async_trait]
+impl HandleCommand<Args> for CommandContext {
+    async fn handle_command(&mut self, _: Args) -> Result<(), Error> {
+        self.rotate_identity().await
+    }
+}
+
+impl CommandContext {
+    pub async fn rotate_identity(&mut self) -> Result<(), Error> {
+        let (next_identity, rotation) = prepare_identity_rotation(&self.config.base_node, &self.base_node_identity)?;
+        self.dht_requester
+            .send_identity_rotation(&rotation, &next_identity)
+            .await?;
+
+        println!("Identity rotation announced");
+        println!("Current public key: {}", rotation.previous_public_key());
+        println!("New public key: {}", rotation.new_public_key());
+        println!(
+            "The new identity has been saved to {}. It will be used when the node is restarted after {}s.",
+            next_identity_file(&self.config.base_node.identity_file).display(),
+            self.config.base_node.identity_rotation_window.as_secs()
+        );
+        Ok(())
+    }
+}
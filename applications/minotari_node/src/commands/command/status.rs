@@ -28,11 +28,15 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 use clap::Parser;
 use minotari_app_utilities::consts;
 use tari_comms::connection_manager::LivenessStatus;
+use tari_core::blocks::ChainStats;
 use tokio::time;
 
 use super::{CommandContext, HandleCommand};
 use crate::commands::status_line::{StatusLine, StatusLineOutput};
 
+/// The number of blocks below the tip used to calculate the block time statistics
+const STATUS_CHAIN_STATS_WINDOW: u64 = 60;
+
 /// Prints out the status of this node
 #[derive(Debug, Parser)]
 pub struct Args {
@@ -83,6 +87,21 @@ impl CommandContext {
             ),
         );
 
+        let headers = self
+            .node_service
+            .get_headers(height.saturating_sub(STATUS_CHAIN_STATS_WINDOW)..=height)
+            .await?;
+        let chain_stats = ChainStats::from_headers(&headers);
+        status_line.add_field(
+            "Block time",
+            format!(
+                "{:.0}s (Sha3 {:.0}s, RandomX {:.0}s)",
+                chain_stats.average_block_interval,
+                chain_stats.sha3x.average_block_interval,
+                chain_stats.randomx.average_block_interval
+            ),
+        );
+
         let constants = self
             .consensus_rules
             .consensus_constants(metadata.height_of_longest_chain());
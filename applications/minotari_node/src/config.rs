@@ -99,6 +99,11 @@ pub struct BaseNodeConfig {
     pub use_libtor: bool,
     /// A path to the file that stores the tor hidden service private key, if using the tor transport.
     pub tor_identity_file: PathBuf,
+    /// The time that a rotated node identity stays in use after the rotation was announced, giving peers time to learn
+    /// about the new identity before the node switches over to it on restart. When using Tor, the onion address of the
+    /// replaced identity is served for the same time again after the switch.
+    #[serde(with = "serializers::seconds")]
+    pub identity_rotation_window: Duration,
     /// The type of database backend to use
     pub db_type: DatabaseType,
     /// The lmdb config settings
@@ -115,6 +120,9 @@ pub struct BaseNodeConfig {
     pub p2p: P2pConfig,
     /// If set this node will only sync to the nodes in this set
     pub force_sync_peers: StringList,
+    /// If set, this node only serves block and horizon sync to peers with these public keys. Other peers can still
+    /// gossip blocks and transactions with this node.
+    pub sync_allowlist: StringList,
     /// The maximum amount of time to wait for remote base node responses for messaging-based requests.
     #[serde(with = "serializers::seconds")]
     pub messaging_request_timeout: Duration,
@@ -130,6 +138,8 @@ pub struct BaseNodeConfig {
     /// Liveness meta data auto ping interval between peers
     #[serde(with = "serializers::seconds")]
     pub metadata_auto_ping_interval: Duration,
+    /// NTP servers (`host:port`) used alongside peer timestamps to detect local clock skew
+    pub ntp_servers: Vec<String>,
     /// The state_machine config settings
     pub state_machine: BaseNodeStateMachineConfig,
     /// Obscure GRPC error responses
@@ -157,11 +167,13 @@ impl Default for BaseNodeConfig {
                 GrpcMethod::GetTipInfo,
                 GrpcMethod::Identify,
                 GrpcMethod::GetNetworkStatus,
+                GrpcMethod::ListRpcSessions,
             ],
             grpc_authentication: GrpcAuthentication::default(),
             identity_file: PathBuf::from("config/base_node_id.json"),
             use_libtor: false,
             tor_identity_file: PathBuf::from("config/base_node_tor_id.json"),
+            identity_rotation_window: Duration::from_secs(24 * 60 * 60),
             p2p,
             db_type: DatabaseType::Lmdb,
             lmdb: Default::default(),
@@ -170,12 +182,14 @@ impl Default for BaseNodeConfig {
             max_randomx_vms: 5,
             bypass_range_proof_verification: false,
             force_sync_peers: StringList::default(),
+            sync_allowlist: StringList::default(),
             messaging_request_timeout: Duration::from_secs(60),
             storage: Default::default(),
             mempool: Default::default(),
             status_line_interval: Duration::from_secs(5),
             buffer_size: 1_500,
             metadata_auto_ping_interval: Duration::from_secs(30),
+            ntp_servers: vec![],
             state_machine: Default::default(),
             report_grpc_error: false,
         }
@@ -220,6 +234,7 @@ pub enum GrpcMethod {
     GetHeaderByHash,
     GetBlocks,
     GetBlockTiming,
+    GetChainStats,
     GetConstants,
     GetBlockSize,
     GetBlockFees,
@@ -227,12 +242,15 @@ pub enum GrpcMethod {
     CheckForUpdates,
     GetTokensInCirculation,
     GetNetworkDifficulty,
+    GetPowAlgorithmStatus,
     GetNewBlockTemplate,
+    StreamNewBlockTemplates,
     GetNewBlock,
     GetNewBlockBlob,
     SubmitBlock,
     SubmitBlockBlob,
     SubmitTransaction,
+    TestSubmitTransaction,
     GetSyncInfo,
     GetSyncProgress,
     GetTipInfo,
@@ -245,6 +263,7 @@ pub enum GrpcMethod {
     Identify,
     GetNetworkStatus,
     ListConnectedPeers,
+    ListRpcSessions,
     GetMempoolStats,
     GetActiveValidatorNodes,
     GetShardKey,
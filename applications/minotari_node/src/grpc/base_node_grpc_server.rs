@@ -23,6 +23,7 @@
 use std::{
     cmp,
     convert::{TryFrom, TryInto},
+    time::Duration,
 };
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -35,15 +36,16 @@ use minotari_app_grpc::{
 };
 use minotari_app_utilities::consts;
 use tari_common_types::types::{Commitment, FixedHash, PublicKey, Signature};
-use tari_comms::{Bytes, CommsNode};
+use tari_comms::{protocol::rpc::RpcServerHandle, Bytes, CommsNode};
 use tari_core::{
     base_node::{
         comms_interface::CommsInterfaceError,
         state_machine_service::states::StateInfo,
         LocalNodeCommsInterface,
+        ReorgAlarm,
         StateMachineHandle,
     },
-    blocks::{Block, BlockHeader, NewBlockTemplate},
+    blocks::{Block, BlockHeader, ChainStats, NewBlockTemplate, PowAlgoStats},
     chain_storage::ChainStorageError,
     consensus::{emission::Emission, ConsensusManager, NetworkConsensus},
     iterators::NonOverlappingIntegerPairIter,
@@ -60,7 +62,15 @@ use crate::{
     builder::BaseNodeContext,
     config::GrpcMethod,
     grpc::{
-        blocks::{block_fees, block_heights, block_size, GET_BLOCKS_MAX_HEIGHTS, GET_BLOCKS_PAGE_SIZE},
+        block_templates::{BlockTemplateSource, DEFAULT_MEMPOOL_POLL_INTERVAL},
+        blocks::{
+            block_fees,
+            block_heights,
+            block_size,
+            BlockBodyFilter,
+            GET_BLOCKS_MAX_HEIGHTS,
+            GET_BLOCKS_PAGE_SIZE,
+        },
         hash_rate::HashRateMovingAverage,
         helpers::{mean, median},
     },
@@ -90,9 +100,11 @@ pub struct BaseNodeGrpcServer {
     mempool_service: LocalMempoolService,
     network: NetworkConsensus,
     state_machine_handle: StateMachineHandle,
+    reorg_alarm: ReorgAlarm,
     consensus_rules: ConsensusManager,
     software_updater: SoftwareUpdaterHandle,
     comms: CommsNode,
+    rpc_server: RpcServerHandle,
     liveness: LivenessHandle,
     report_grpc_error: bool,
     deny_methods: Vec<GrpcMethod>,
@@ -105,9 +117,11 @@ impl BaseNodeGrpcServer {
             mempool_service: ctx.local_mempool(),
             network: ctx.network().into(),
             state_machine_handle: ctx.state_machine(),
+            reorg_alarm: ctx.reorg_alarm(),
             consensus_rules: ctx.consensus_rules().clone(),
             software_updater: ctx.software_updater(),
             comms: ctx.base_node_comms().clone(),
+            rpc_server: ctx.rpc_server(),
             liveness: ctx.liveness(),
             report_grpc_error: ctx.get_report_grpc_error(),
             deny_methods,
@@ -121,6 +135,21 @@ impl BaseNodeGrpcServer {
     fn is_method_enabled(&self, grpc_method: GrpcMethod) -> bool {
         !self.deny_methods.contains(&grpc_method)
     }
+
+    fn block_template_source(&self) -> BlockTemplateSource {
+        BlockTemplateSource {
+            node_service: self.node_service.clone(),
+            mempool_service: self.mempool_service.clone(),
+            state_machine_handle: self.state_machine_handle.clone(),
+            liveness: self.liveness.clone(),
+            consensus_rules: self.consensus_rules.clone(),
+            report_error_flag: self.report_error_flag(),
+        }
+    }
+
+    async fn check_clock_skew(&self, height: u64) -> Result<(), Status> {
+        self.block_template_source().check_clock_skew(height).await
+    }
 }
 
 pub fn obscure_error_if_true(report: bool, status: Status) -> Status {
@@ -132,6 +161,20 @@ pub fn obscure_error_if_true(report: bool, status: Status) -> Status {
     }
 }
 
+fn submit_transaction_result(storage: &TxStorageResponse) -> tari_rpc::SubmitTransactionResult {
+    match storage {
+        TxStorageResponse::UnconfirmedPool => tari_rpc::SubmitTransactionResult::Accepted,
+        TxStorageResponse::ReorgPool |
+        TxStorageResponse::NotStoredAlreadySpent |
+        TxStorageResponse::NotStoredAlreadyMined => tari_rpc::SubmitTransactionResult::AlreadyMined,
+        TxStorageResponse::NotStored |
+        TxStorageResponse::NotStoredOrphan |
+        TxStorageResponse::NotStoredConsensus |
+        TxStorageResponse::NotStoredFeeTooLow |
+        TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResult::Rejected,
+    }
+}
+
 pub async fn get_heights(
     request: &tari_rpc::HeightRequest,
     handler: LocalNodeCommsInterface,
@@ -154,6 +197,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
     type ListHeadersStream = mpsc::Receiver<Result<tari_rpc::BlockHeaderResponse, Status>>;
     type SearchKernelsStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
     type SearchUtxosStream = mpsc::Receiver<Result<tari_rpc::HistoricalBlock, Status>>;
+    type StreamNewBlockTemplatesStream = mpsc::Receiver<Result<tari_rpc::NewBlockTemplateResponse, Status>>;
 
     #[allow(clippy::too_many_lines)]
     async fn get_network_difficulty(
@@ -276,6 +320,50 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(rx))
     }
 
+    async fn get_pow_algorithm_status(
+        &self,
+        _request: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::PowAlgorithmStatusResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::GetPowAlgorithmStatus) {
+            return Err(Status::permission_denied(
+                "`GetPowAlgorithmStatus` method not made available",
+            ));
+        }
+        let report_error_flag = self.report_error_flag();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for GetPowAlgorithmStatus");
+        let mut handler = self.node_service.clone();
+
+        let tip_height = handler
+            .get_metadata()
+            .await
+            .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e.to_string())))?
+            .height_of_longest_chain();
+        let statuses = handler.get_pow_algorithm_status().await.map_err(|e| {
+            error!(target: LOG_TARGET, "Could not get proof of work status: {}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+
+        let response = tari_rpc::PowAlgorithmStatusResponse {
+            tip_height,
+            algorithms: statuses
+                .into_iter()
+                .map(|status| tari_rpc::PowAlgorithmStatus {
+                    pow_algo: Some(tari_rpc::PowAlgo {
+                        pow_algo: status.pow_algo as i32,
+                    }),
+                    target_difficulty: status.target_difficulty.as_u64(),
+                    estimated_hash_rate: status.estimated_hash_rate,
+                    target_block_time: status.target_block_time,
+                    average_block_time: status.average_block_time.unwrap_or_default(),
+                    last_block_height: status.last_block_height.unwrap_or_default(),
+                    last_block_timestamp: status.last_block_timestamp.map(|t| t.as_u64()).unwrap_or_default(),
+                    num_recent_blocks: status.num_recent_blocks,
+                })
+                .collect(),
+        };
+        Ok(Response::new(response))
+    }
+
     async fn get_mempool_transactions(
         &self,
         request: Request<tari_rpc::GetMempoolTransactionsRequest>,
@@ -545,40 +633,61 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             )
         })?;
 
-        let mut handler = self.node_service.clone();
+        let (response, _, _) = self
+            .block_template_source()
+            .new_block_template(algo, request.max_weight)
+            .await?;
 
-        let new_template = handler
-            .get_new_block_template(algo, request.max_weight)
-            .await
+        debug!(target: LOG_TARGET, "Sending GetNewBlockTemplate response to client");
+        Ok(Response::new(response))
+    }
+
+    async fn stream_new_block_templates(
+        &self,
+        request: Request<tari_rpc::StreamNewBlockTemplatesRequest>,
+    ) -> Result<Response<Self::StreamNewBlockTemplatesStream>, Status> {
+        if !self.is_method_enabled(GrpcMethod::StreamNewBlockTemplates) {
+            return Err(Status::permission_denied(
+                "`StreamNewBlockTemplates` method not made available",
+            ));
+        }
+        let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        debug!(target: LOG_TARGET, "Incoming GRPC request for StreamNewBlockTemplates");
+        trace!(target: LOG_TARGET, "Request {:?}", request);
+        let algo = request
+            .algo
+            .map(|algo| u64::try_from(algo.pow_algo))
+            .ok_or_else(|| obscure_error_if_true(report_error_flag, Status::invalid_argument("PoW algo not provided")))?
             .map_err(|e| {
-                warn!(
-                    target: LOG_TARGET,
-                    "Could not get new block template: {}",
-                    e.to_string()
-                );
-                obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument(format!("Invalid PoW algo '{}'", e)),
+                )
             })?;
-
-        let status_watch = self.state_machine_handle.get_status_info_watch();
-        let pow = algo as i32;
-        let response = tari_rpc::NewBlockTemplateResponse {
-            miner_data: Some(tari_rpc::MinerData {
-                reward: new_template.reward.into(),
-                target_difficulty: new_template.target_difficulty.as_u64(),
-                total_fees: new_template.total_fees.into(),
-                algo: Some(tari_rpc::PowAlgo { pow_algo: pow }),
-            }),
-            new_block_template: Some(
-                new_template
-                    .try_into()
-                    .map_err(|e| obscure_error_if_true(report_error_flag, Status::internal(e)))?,
-            ),
-
-            initial_sync_achieved: status_watch.borrow().bootstrapped,
+        let algo = PowAlgorithm::try_from(algo).map_err(|e| {
+            obscure_error_if_true(
+                report_error_flag,
+                Status::invalid_argument(format!("Invalid PoW algo '{}'", e)),
+            )
+        })?;
+        let mempool_poll_interval = if request.mempool_poll_interval_ms == 0 {
+            DEFAULT_MEMPOOL_POLL_INTERVAL
+        } else {
+            Duration::from_millis(request.mempool_poll_interval_ms)
         };
 
-        debug!(target: LOG_TARGET, "Sending GetNewBlockTemplate response to client");
-        Ok(Response::new(response))
+        let (tx, rx) = mpsc::channel(1);
+        task::spawn(self.block_template_source().stream_templates(
+            algo,
+            request.max_weight,
+            mempool_poll_interval,
+            request.min_fee_increase,
+            tx,
+        ));
+
+        debug!(target: LOG_TARGET, "Streaming block templates to client");
+        Ok(Response::new(rx))
     }
 
     async fn get_new_block(
@@ -597,6 +706,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 Status::invalid_argument(format!("Malformed block template provided: {}", s)),
             )
         })?;
+        self.check_clock_skew(block_template.header.height).await?;
 
         let mut handler = self.node_service.clone();
 
@@ -660,6 +770,7 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                 Status::invalid_argument(format!("Invalid block template: {}", s)),
             )
         })?;
+        self.check_clock_skew(block_template.header.height).await?;
 
         let mut handler = self.node_service.clone();
 
@@ -824,28 +935,58 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
             error!(target: LOG_TARGET, "Error submitting:{}", e);
             obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
         })?;
-        let response = match res {
-            TxStorageResponse::UnconfirmedPool => tari_rpc::SubmitTransactionResponse {
-                result: tari_rpc::SubmitTransactionResult::Accepted.into(),
-            },
-            TxStorageResponse::ReorgPool |
-            TxStorageResponse::NotStoredAlreadySpent |
-            TxStorageResponse::NotStoredAlreadyMined => tari_rpc::SubmitTransactionResponse {
-                result: tari_rpc::SubmitTransactionResult::AlreadyMined.into(),
-            },
-            TxStorageResponse::NotStored |
-            TxStorageResponse::NotStoredOrphan |
-            TxStorageResponse::NotStoredConsensus |
-            TxStorageResponse::NotStoredFeeTooLow |
-            TxStorageResponse::NotStoredTimeLocked => tari_rpc::SubmitTransactionResponse {
-                result: tari_rpc::SubmitTransactionResult::Rejected.into(),
-            },
+        let response = tari_rpc::SubmitTransactionResponse {
+            result: submit_transaction_result(&res).into(),
         };
 
         debug!(target: LOG_TARGET, "Sending SubmitTransaction response to client");
         Ok(Response::new(response))
     }
 
+    async fn test_submit_transaction(
+        &self,
+        request: Request<tari_rpc::SubmitTransactionRequest>,
+    ) -> Result<Response<tari_rpc::TestSubmitTransactionResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::TestSubmitTransaction) {
+            return Err(Status::permission_denied(
+                "`TestSubmitTransaction` method not made available",
+            ));
+        }
+        let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        let txn: Transaction = request
+            .transaction
+            .ok_or_else(|| obscure_error_if_true(report_error_flag, Status::invalid_argument("Transaction is empty")))?
+            .try_into()
+            .map_err(|e| {
+                obscure_error_if_true(
+                    report_error_flag,
+                    Status::invalid_argument(format!("Invalid transaction provided: {}", e)),
+                )
+            })?;
+        debug!(
+            target: LOG_TARGET,
+            "Received TestSubmitTransaction request from client ({} kernels, {} outputs, {} inputs)",
+            txn.body.kernels().len(),
+            txn.body.outputs().len(),
+            txn.body.inputs().len()
+        );
+
+        let mut handler = self.mempool_service.clone();
+        let res = handler.test_submit_transaction(txn).await.map_err(|e| {
+            error!(target: LOG_TARGET, "Error test submitting:{}", e);
+            obscure_error_if_true(report_error_flag, Status::internal(e.to_string()))
+        })?;
+        let response = tari_rpc::TestSubmitTransactionResponse {
+            result: submit_transaction_result(&res.storage).into(),
+            fee_per_gram: res.fee_per_gram.as_u64(),
+            rejection_reason: res.rejection_reason.unwrap_or_default(),
+        };
+
+        debug!(target: LOG_TARGET, "Sending TestSubmitTransaction response to client");
+        Ok(Response::new(response))
+    }
+
     async fn transaction_state(
         &self,
         request: Request<tari_rpc::TransactionStateRequest>,
@@ -983,9 +1124,18 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         let request = request.into_inner();
         debug!(
             target: LOG_TARGET,
-            "Incoming GRPC request for GetBlocks: {:?}", request.heights
+            "Incoming GRPC request for GetBlocks: {:?} (only coinbases: {}, only kernels: {}, exclude range proofs: {})",
+            request.heights,
+            request.only_coinbases,
+            request.only_kernels,
+            request.exclude_range_proofs
         );
 
+        let filter = BlockBodyFilter {
+            only_coinbases: request.only_coinbases,
+            only_kernels: request.only_kernels,
+            exclude_range_proofs: request.exclude_range_proofs,
+        };
         let mut heights = request.heights;
         if heights.is_empty() {
             return Err(obscure_error_if_true(
@@ -1023,12 +1173,17 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
                         "GetBlock GRPC sending block #{}",
                         block.header().height
                     );
-                    let result = block.try_into().map_err(|err| {
-                        obscure_error_if_true(
-                            report_error_flag,
-                            Status::internal(format!("Could not provide block: {}", err)),
-                        )
-                    });
+                    let result = tari_rpc::HistoricalBlock::try_from(block)
+                        .map(|mut block| {
+                            filter.apply(&mut block);
+                            block
+                        })
+                        .map_err(|err| {
+                            obscure_error_if_true(
+                                report_error_flag,
+                                Status::internal(format!("Could not provide block: {}", err)),
+                            )
+                        });
                     if tx.send(result).await.is_err() {
                         warn!(
                             target: LOG_TARGET,
@@ -1063,10 +1218,13 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         // Determine if we are bootstrapped
         let status_watch = self.state_machine_handle.get_status_info_watch();
         let state: tari_rpc::BaseNodeState = (&status_watch.borrow().state_info).into();
+        let reorg_alarm = self.reorg_alarm.status();
         let response = tari_rpc::TipInfoResponse {
             metadata: Some(meta.into()),
             initial_sync_achieved: status_watch.borrow().bootstrapped,
             base_node_state: state.into(),
+            reorg_alarm_raised: reorg_alarm.is_some(),
+            reorg_alarm_depth: reorg_alarm.map(|a| a.depth).unwrap_or_default(),
         };
 
         debug!(target: LOG_TARGET, "Sending MetaData response to client");
@@ -1314,6 +1472,76 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(response))
     }
 
+    async fn get_chain_stats(
+        &self,
+        request: Request<tari_rpc::HeightRequest>,
+    ) -> Result<Response<tari_rpc::ChainStatsResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::GetChainStats) {
+            return Err(Status::permission_denied("`GetChainStats` method not made available"));
+        }
+        let report_error_flag = self.report_error_flag();
+        let request = request.into_inner();
+        debug!(
+            target: LOG_TARGET,
+            "Incoming GRPC request for GetChainStats: from_tip: {:?} start_height: {:?} end_height: {:?}",
+            request.from_tip,
+            request.start_height,
+            request.end_height
+        );
+
+        let mut handler = self.node_service.clone();
+        let (start, end) = get_heights(&request, handler.clone()).await?;
+
+        let num_requested = end.saturating_sub(start);
+        if num_requested > BLOCK_TIMING_MAX_BLOCKS {
+            warn!(
+                target: LOG_TARGET,
+                "GetChainStats request for too many blocks. Requested: {}. Max: {}.",
+                num_requested,
+                BLOCK_TIMING_MAX_BLOCKS
+            );
+            return Err(obscure_error_if_true(
+                report_error_flag,
+                Status::invalid_argument(format!(
+                    "Exceeded max blocks request limit of {}",
+                    BLOCK_TIMING_MAX_BLOCKS
+                )),
+            ));
+        }
+
+        let headers = handler.get_headers(start..=end).await.map_err(|err| {
+            obscure_error_if_true(
+                report_error_flag,
+                Status::internal(format!("Could not provide headers:{}", err)),
+            )
+        })?;
+
+        let stats = ChainStats::from_headers(&headers);
+        let algo_stats = |algo: PowAlgorithm, stats: &PowAlgoStats| tari_rpc::PowAlgoStats {
+            pow_algo: Some(tari_rpc::PowAlgo { pow_algo: algo as i32 }),
+            num_blocks: stats.num_blocks,
+            average_block_interval: stats.average_block_interval,
+            first_target_difficulty: stats.first_target_difficulty,
+            last_target_difficulty: stats.last_target_difficulty,
+            min_target_difficulty: stats.min_target_difficulty,
+            max_target_difficulty: stats.max_target_difficulty,
+            average_target_difficulty: stats.average_target_difficulty,
+            estimated_hash_rate: stats.estimated_hash_rate,
+        };
+        let response = tari_rpc::ChainStatsResponse {
+            start_height: stats.start_height,
+            end_height: stats.end_height,
+            num_blocks: stats.num_blocks,
+            average_block_interval: stats.average_block_interval,
+            algo_stats: vec![
+                algo_stats(PowAlgorithm::Sha3x, &stats.sha3x),
+                algo_stats(PowAlgorithm::RandomX, &stats.randomx),
+            ],
+        };
+        debug!(target: LOG_TARGET, "Sending GetChainStats response to client");
+        Ok(Response::new(response))
+    }
+
     async fn get_constants(
         &self,
         request: Request<tari_rpc::BlockHeight>,
@@ -1678,6 +1906,43 @@ impl tari_rpc::base_node_server::BaseNode for BaseNodeGrpcServer {
         Ok(Response::new(resp))
     }
 
+    async fn list_rpc_sessions(
+        &self,
+        _: Request<tari_rpc::Empty>,
+    ) -> Result<Response<tari_rpc::ListRpcSessionsResponse>, Status> {
+        if !self.is_method_enabled(GrpcMethod::ListRpcSessions) {
+            return Err(Status::permission_denied("`ListRpcSessions` method not made available"));
+        }
+        let report_error_flag = self.report_error_flag();
+        let mut rpc_server = self.rpc_server.clone();
+        let peer_manager = self.comms.peer_manager();
+        let active_sessions = rpc_server
+            .get_active_sessions()
+            .await
+            .map_err(|err| obscure_error_if_true(report_error_flag, Status::internal(err.to_string())))?;
+
+        let mut sessions = Vec::with_capacity(active_sessions.len());
+        for session in active_sessions {
+            let user_agent = peer_manager
+                .find_by_node_id(&session.peer_node_id)
+                .await
+                .map_err(|err| obscure_error_if_true(report_error_flag, Status::internal(err.to_string())))?
+                .map(|peer| peer.user_agent)
+                .unwrap_or_default();
+            sessions.push(tari_rpc::RpcSession {
+                session_id: session.session_id,
+                node_id: session.peer_node_id.to_vec(),
+                user_agent,
+                protocol: session.protocol_name(),
+                requests_served: session.requests_served,
+                bytes_out: session.bytes_out,
+                duration_secs: session.duration.as_secs(),
+            });
+        }
+
+        Ok(Response::new(tari_rpc::ListRpcSessionsResponse { sessions }))
+    }
+
     async fn get_mempool_stats(
         &self,
         _: Request<tari_rpc::Empty>,
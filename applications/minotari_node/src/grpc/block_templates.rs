@@ -0,0 +1,282 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cmp, convert::TryInto, time::Duration};
+
+use futures::{channel::mpsc, SinkExt};
+use log::*;
+use minotari_app_grpc::tari_rpc;
+use tari_core::{
+    base_node::{
+        comms_interface::{BlockEvent, CommsInterfaceError},
+        LocalNodeCommsInterface,
+        StateMachineHandle,
+    },
+    consensus::ConsensusManager,
+    mempool::{service::LocalMempoolService, StatsResponse},
+    proof_of_work::PowAlgorithm,
+};
+use tari_p2p::services::liveness::LivenessHandle;
+use tokio::{sync::broadcast::error::RecvError, time, time::MissedTickBehavior};
+use tonic::Status;
+
+use crate::grpc::base_node_grpc_server::obscure_error_if_true;
+
+const LOG_TARGET: &str = "minotari::base_node::grpc::block_templates";
+
+/// How often the mempool is checked for changes when the client does not specify an interval
+pub const DEFAULT_MEMPOOL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Clients may not poll the mempool more often than this
+const MIN_MEMPOOL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Builds block template responses for the gRPC server, either on request or as a stream that is refreshed when the
+/// chain tip or the mempool changes.
+#[derive(Clone)]
+pub struct BlockTemplateSource {
+    pub node_service: LocalNodeCommsInterface,
+    pub mempool_service: LocalMempoolService,
+    pub state_machine_handle: StateMachineHandle,
+    pub liveness: LivenessHandle,
+    pub consensus_rules: ConsensusManager,
+    pub report_error_flag: bool,
+}
+
+impl BlockTemplateSource {
+    /// Block timestamps are taken from the local clock, so refuse to produce blocks while it is skewed beyond the
+    /// future time limit instead of handing out blocks that peers will reject.
+    pub async fn check_clock_skew(&self, height: u64) -> Result<(), Status> {
+        let skew = self
+            .liveness
+            .clone()
+            .get_clock_skew()
+            .await
+            .map_err(|e| obscure_error_if_true(self.report_error_flag, Status::internal(e.to_string())))?;
+        let future_time_limit =
+            Duration::from_secs(self.consensus_rules.consensus_constants(height).future_time_limit());
+        if skew.exceeds(future_time_limit) {
+            warn!(
+                target: LOG_TARGET,
+                "Refusing to create a block at height {} because the local clock offset is {}", height, skew
+            );
+            return Err(Status::failed_precondition(format!(
+                "The local clock offset {} exceeds the future time limit of {}s. Please synchronise the system clock.",
+                skew,
+                future_time_limit.as_secs()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns a new block template response along with its height and total fees
+    pub async fn new_block_template(
+        &self,
+        algo: PowAlgorithm,
+        max_weight: u64,
+    ) -> Result<(tari_rpc::NewBlockTemplateResponse, u64, u64), Status> {
+        let new_template = self
+            .node_service
+            .clone()
+            .get_new_block_template(algo, max_weight)
+            .await
+            .map_err(|e| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not get new block template: {}",
+                    e.to_string()
+                );
+                match e {
+                    CommsInterfaceError::BlockTemplatesHalted(_) => Status::unavailable(e.to_string()),
+                    _ => obscure_error_if_true(self.report_error_flag, Status::internal(e.to_string())),
+                }
+            })?;
+
+        let height = new_template.header.height;
+        self.check_clock_skew(height).await?;
+
+        let total_fees = new_template.total_fees.as_u64();
+        let status_watch = self.state_machine_handle.get_status_info_watch();
+        let response = tari_rpc::NewBlockTemplateResponse {
+            miner_data: Some(tari_rpc::MinerData {
+                reward: new_template.reward.into(),
+                target_difficulty: new_template.target_difficulty.as_u64(),
+                total_fees,
+                algo: Some(tari_rpc::PowAlgo { pow_algo: algo as i32 }),
+            }),
+            new_block_template: Some(
+                new_template
+                    .try_into()
+                    .map_err(|e| obscure_error_if_true(self.report_error_flag, Status::internal(e)))?,
+            ),
+
+            initial_sync_achieved: status_watch.borrow().bootstrapped,
+        };
+        Ok((response, height, total_fees))
+    }
+
+    /// Sends a template immediately, and then a new one whenever the chain tip changes, or whenever the mempool
+    /// changes and the fees of the new template differ from the last one sent (increases smaller than
+    /// `min_fee_increase` are ignored). Runs until the client disconnects.
+    pub async fn stream_templates(
+        self,
+        algo: PowAlgorithm,
+        max_weight: u64,
+        mempool_poll_interval: Duration,
+        min_fee_increase: u64,
+        mut tx: mpsc::Sender<Result<tari_rpc::NewBlockTemplateResponse, Status>>,
+    ) {
+        let mut block_events = self.node_service.get_block_event_stream();
+        let mut mempool = self.mempool_service.clone();
+        let mut interval = time::interval(cmp::max(mempool_poll_interval, MIN_MEMPOOL_POLL_INTERVAL));
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        let mut last_stats = mempool.get_mempool_stats().await.ok();
+        let mut last_sent = None;
+        let mut tip_changed = true;
+        loop {
+            if tip_changed || has_mempool_changed(&mut mempool, &mut last_stats).await {
+                match self.new_block_template(algo, max_weight).await {
+                    Ok((response, height, total_fees)) => {
+                        if tip_changed || is_material_change(last_sent, height, total_fees, min_fee_increase) {
+                            if tx.send(Ok(response)).await.is_err() {
+                                break;
+                            }
+                            last_sent = Some((height, total_fees));
+                        }
+                    },
+                    Err(status) => {
+                        if tx.send(Err(status)).await.is_err() {
+                            break;
+                        }
+                    },
+                }
+            }
+
+            tip_changed = tokio::select! {
+                event = block_events.recv() => match event {
+                    Ok(event) => is_tip_change(&event),
+                    Err(RecvError::Lagged(_)) => true,
+                    Err(RecvError::Closed) => break,
+                },
+                _ = interval.tick() => false,
+            };
+            if tx.is_closed() {
+                break;
+            }
+        }
+        debug!(target: LOG_TARGET, "Block template stream for {} closed", algo);
+    }
+}
+
+fn is_tip_change(event: &BlockEvent) -> bool {
+    match event {
+        BlockEvent::ValidBlockAdded(_, result) => result.was_chain_modified(),
+        BlockEvent::BlockSyncComplete(_, _) | BlockEvent::BlockSyncRewind(_) => true,
+        BlockEvent::AddBlockValidationFailed { .. } | BlockEvent::AddBlockErrored { .. } => false,
+    }
+}
+
+/// A template is worth sending if it is for a new height, or if its fees dropped or rose by at least
+/// `min_fee_increase` compared to the `(height, total_fees)` of the last template sent
+fn is_material_change(last_sent: Option<(u64, u64)>, height: u64, total_fees: u64, min_fee_increase: u64) -> bool {
+    last_sent.map_or(true, |(last_height, last_fees)| {
+        height != last_height ||
+            total_fees < last_fees ||
+            total_fees >= last_fees.saturating_add(cmp::max(min_fee_increase, 1))
+    })
+}
+
+fn have_stats_changed(last_stats: Option<&StatsResponse>, stats: &StatsResponse) -> bool {
+    last_stats.map_or(true, |last| {
+        last.unconfirmed_txs != stats.unconfirmed_txs || last.unconfirmed_weight != stats.unconfirmed_weight
+    })
+}
+
+async fn has_mempool_changed(mempool: &mut LocalMempoolService, last_stats: &mut Option<StatsResponse>) -> bool {
+    match mempool.get_mempool_stats().await {
+        Ok(stats) => {
+            let changed = have_stats_changed(last_stats.as_ref(), &stats);
+            *last_stats = Some(stats);
+            changed
+        },
+        Err(e) => {
+            warn!(target: LOG_TARGET, "Could not get mempool stats: {}", e);
+            false
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use tari_core::{
+        blocks::{Block, BlockHeader},
+        transactions::aggregated_body::AggregateBody,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_only_sends_templates_that_changed_materially() {
+        assert!(is_material_change(None, 10, 0, 100));
+        // A new height is always sent
+        assert!(is_material_change(Some((10, 500)), 11, 0, 100));
+        // Small fee increases are ignored
+        assert!(!is_material_change(Some((10, 500)), 10, 500, 100));
+        assert!(!is_material_change(Some((10, 500)), 10, 599, 100));
+        assert!(is_material_change(Some((10, 500)), 10, 600, 100));
+        // Fee decreases, e.g. after a mempool reorg, are always sent
+        assert!(is_material_change(Some((10, 500)), 10, 499, 100));
+        // Without a minimum increase, any change is sent
+        assert!(!is_material_change(Some((10, 500)), 10, 500, 0));
+        assert!(is_material_change(Some((10, 500)), 10, 501, 0));
+    }
+
+    #[test]
+    fn it_detects_mempool_changes() {
+        let stats = StatsResponse {
+            unconfirmed_txs: 2,
+            reorg_txs: 0,
+            unconfirmed_weight: 1000,
+        };
+        assert!(have_stats_changed(None, &stats));
+        assert!(!have_stats_changed(Some(&stats), &StatsResponse {
+            reorg_txs: 1,
+            ..stats.clone()
+        }));
+        assert!(have_stats_changed(Some(&stats), &StatsResponse {
+            unconfirmed_txs: 3,
+            ..stats.clone()
+        }));
+        assert!(have_stats_changed(Some(&stats), &StatsResponse {
+            unconfirmed_weight: 1200,
+            ..stats.clone()
+        }));
+    }
+
+    #[test]
+    fn it_detects_tip_changes() {
+        assert!(is_tip_change(&BlockEvent::BlockSyncRewind(vec![])));
+        let block = Arc::new(Block::new(BlockHeader::new(0), AggregateBody::empty()));
+        assert!(!is_tip_change(&BlockEvent::AddBlockErrored { block }));
+    }
+}
@@ -22,7 +22,12 @@
 
 use std::cmp;
 
-use tari_core::{base_node::LocalNodeCommsInterface, blocks::HistoricalBlock};
+use minotari_app_grpc::tari_rpc;
+use tari_core::{
+    base_node::LocalNodeCommsInterface,
+    blocks::HistoricalBlock,
+    transactions::transaction_components::{KernelFeatures, OutputType},
+};
 use tonic::Status;
 
 // The maximum number of blocks that can be requested at a time. These will be streamed to the
@@ -82,3 +87,130 @@ pub fn block_fees(block: &HistoricalBlock) -> u64 {
         .iter()
         .sum::<u64>()
 }
+
+/// Selects the parts of each block body returned by `GetBlocks`, so that clients that only need some of the data do
+/// not have to download full blocks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BlockBodyFilter {
+    pub only_coinbases: bool,
+    pub only_kernels: bool,
+    pub exclude_range_proofs: bool,
+}
+
+impl BlockBodyFilter {
+    pub fn apply(&self, block: &mut tari_rpc::HistoricalBlock) {
+        let Some(body) = block.block.as_mut().and_then(|b| b.body.as_mut()) else {
+            return;
+        };
+        if self.only_kernels {
+            body.inputs.clear();
+            body.outputs.clear();
+        }
+        if self.only_coinbases {
+            let coinbase_output = u32::from(OutputType::Coinbase.as_byte());
+            let coinbase_kernel = u32::from(KernelFeatures::COINBASE_KERNEL.bits());
+            body.inputs.clear();
+            body.outputs
+                .retain(|o| o.features.as_ref().map_or(false, |f| f.output_type == coinbase_output));
+            body.kernels.retain(|k| k.features & coinbase_kernel != 0);
+        }
+        if self.exclude_range_proofs {
+            for output in &mut body.outputs {
+                output.range_proof = None;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(output_type: OutputType) -> tari_rpc::TransactionOutput {
+        tari_rpc::TransactionOutput {
+            features: Some(tari_rpc::OutputFeatures {
+                output_type: u32::from(output_type.as_byte()),
+                ..Default::default()
+            }),
+            range_proof: Some(tari_rpc::RangeProof {
+                proof_bytes: vec![1, 2, 3],
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn kernel(features: KernelFeatures) -> tari_rpc::TransactionKernel {
+        tari_rpc::TransactionKernel {
+            features: u32::from(features.bits()),
+            ..Default::default()
+        }
+    }
+
+    fn historical_block() -> tari_rpc::HistoricalBlock {
+        tari_rpc::HistoricalBlock {
+            confirmations: 1,
+            block: Some(tari_rpc::Block {
+                header: Some(tari_rpc::BlockHeader::default()),
+                body: Some(tari_rpc::AggregateBody {
+                    inputs: vec![tari_rpc::TransactionInput::default()],
+                    outputs: vec![output(OutputType::Coinbase), output(OutputType::Standard)],
+                    kernels: vec![kernel(KernelFeatures::COINBASE_KERNEL), kernel(KernelFeatures::empty())],
+                }),
+            }),
+        }
+    }
+
+    fn body(block: &tari_rpc::HistoricalBlock) -> &tari_rpc::AggregateBody {
+        block.block.as_ref().unwrap().body.as_ref().unwrap()
+    }
+
+    #[test]
+    fn it_returns_the_full_block_without_filters() {
+        let mut block = historical_block();
+        BlockBodyFilter::default().apply(&mut block);
+        assert_eq!(block, historical_block());
+    }
+
+    #[test]
+    fn it_keeps_only_the_kernels() {
+        let mut block = historical_block();
+        BlockBodyFilter {
+            only_kernels: true,
+            ..Default::default()
+        }
+        .apply(&mut block);
+        let body = body(&block);
+        assert!(body.inputs.is_empty());
+        assert!(body.outputs.is_empty());
+        assert_eq!(body.kernels.len(), 2);
+    }
+
+    #[test]
+    fn it_keeps_only_the_coinbases() {
+        let mut block = historical_block();
+        BlockBodyFilter {
+            only_coinbases: true,
+            ..Default::default()
+        }
+        .apply(&mut block);
+        let body = body(&block);
+        assert!(body.inputs.is_empty());
+        assert_eq!(body.outputs, vec![output(OutputType::Coinbase)]);
+        assert_eq!(body.kernels, vec![kernel(KernelFeatures::COINBASE_KERNEL)]);
+    }
+
+    #[test]
+    fn it_strips_the_range_proofs() {
+        let mut block = historical_block();
+        BlockBodyFilter {
+            exclude_range_proofs: true,
+            ..Default::default()
+        }
+        .apply(&mut block);
+        let body = body(&block);
+        assert_eq!(body.inputs.len(), 1);
+        assert_eq!(body.outputs.len(), 2);
+        assert!(body.outputs.iter().all(|o| o.range_proof.is_none()));
+        assert_eq!(body.kernels.len(), 2);
+    }
+}
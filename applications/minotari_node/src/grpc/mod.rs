@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod base_node_grpc_server;
+pub mod block_templates;
 pub mod blocks;
 pub mod hash_rate;
 pub mod helpers;
@@ -0,0 +1,284 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Node identity rotation.
+//!
+//! Rotating the node identity happens in two steps. The `rotate-identity` command generates the next identity, signs a
+//! link from the current identity to it and announces that link to the network. Peers record the link and redirect
+//! dials to the current identity to the next one once the current identity can no longer be reached. The node keeps
+//! running under its current identity for the configured rotation window, during which peers learn about the next
+//! identity while the current one remains reachable. The next identity is promoted the first time the node starts
+//! after the window has elapsed.
+//!
+//! When using the Tor transport, the promoted identity is served on a fresh onion address. The onion address of the
+//! replaced identity, which peers have been told the next identity can be reached on, is served alongside it for a
+//! further rotation window before it is retired.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use chrono::{DateTime, Utc};
+use log::*;
+use minotari_app_utilities::identity_management::{load_from_json, save_as_json};
+use rand::rngs::OsRng;
+use tari_common::exit_codes::{ExitCode, ExitError};
+use tari_comms::{
+    multiaddr::Multiaddr,
+    peer_manager::IdentityRotation,
+    tor::{HiddenServiceBuilder, TorIdentity},
+    CommsNode,
+    NodeIdentity,
+};
+use tari_p2p::TorTransportConfig;
+use tari_shutdown::Shutdown;
+use tokio::time;
+
+use crate::BaseNodeConfig;
+
+const LOG_TARGET: &str = "minotari::base_node::identity_rotation";
+
+/// The file that the next node identity is stored in until it is promoted
+pub fn next_identity_file(identity_file: &Path) -> PathBuf {
+    identity_file.with_extension("next.json")
+}
+
+/// The file that the replaced node identity is moved to once the next identity is promoted
+pub fn previous_identity_file(identity_file: &Path) -> PathBuf {
+    identity_file.with_extension("previous.json")
+}
+
+/// The file that stores the signed link between the current and next node identity
+pub fn rotation_file(identity_file: &Path) -> PathBuf {
+    identity_file.with_extension("rotation.json")
+}
+
+/// Generates the identity that will replace `current` and signs the link between them. The next identity and the
+/// rotation are stored alongside the identity file until the next identity is promoted.
+pub fn prepare_identity_rotation(
+    config: &BaseNodeConfig,
+    current: &NodeIdentity,
+) -> Result<(Arc<NodeIdentity>, IdentityRotation), ExitError> {
+    let next_file = next_identity_file(&config.identity_file);
+    if next_file.exists() {
+        return Err(ExitError::new(
+            ExitCode::IdentityError,
+            format!(
+                "An identity rotation is already pending ({}). Restart the node after the rotation window has elapsed \
+                 to complete it.",
+                next_file.display()
+            ),
+        ));
+    }
+
+    let previous_tor_identity_file = previous_identity_file(&config.tor_identity_file);
+    if previous_tor_identity_file.exists() {
+        return Err(ExitError::new(
+            ExitCode::IdentityError,
+            format!(
+                "The onion address of the previous identity is still being served ({}). Wait for the dual-listen \
+                 window of the last rotation to end before rotating again.",
+                previous_tor_identity_file.display()
+            ),
+        ));
+    }
+
+    let next = NodeIdentity::random_multiple_addresses(&mut OsRng, current.public_addresses(), current.features());
+    let rotation = IdentityRotation::sign(current, &next);
+
+    save_as_json(&next_file, &next).map_err(|e| ExitError::new(ExitCode::IdentityError, e))?;
+    save_as_json(rotation_file(&config.identity_file), &rotation)
+        .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?;
+    info!(
+        target: LOG_TARGET,
+        "Identity rotation from {} to {} prepared. The new identity will be used once the node is restarted after {}s.",
+        current.public_key(),
+        next.public_key(),
+        config.identity_rotation_window.as_secs()
+    );
+
+    Ok((Arc::new(next), rotation))
+}
+
+/// Promotes a pending next identity if its rotation window has elapsed. The replaced identity is kept in the previous
+/// identity file. When using the Tor transport, the hidden service identity is moved aside as well so that a fresh
+/// onion address, that cannot be linked to the replaced identity, is created on startup.
+///
+/// Returns true if the identity was rotated.
+pub fn promote_pending_identity(config: &BaseNodeConfig) -> Result<bool, ExitError> {
+    let identity_file = &config.identity_file;
+    let next_file = next_identity_file(identity_file);
+    if !next_file.exists() {
+        return Ok(false);
+    }
+
+    let rotation = load_from_json::<_, IdentityRotation>(rotation_file(identity_file))
+        .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?
+        .ok_or_else(|| {
+            ExitError::new(
+                ExitCode::IdentityError,
+                format!(
+                    "{} exists but the identity rotation record is missing",
+                    next_file.display()
+                ),
+            )
+        })?;
+
+    let window = chrono::Duration::from_std(config.identity_rotation_window)
+        .map_err(|e| ExitError::new(ExitCode::ConfigError, e))?;
+    let promote_at = rotation.rotated_at() + window;
+    if Utc::now() < promote_at {
+        info!(
+            target: LOG_TARGET,
+            "Identity rotation to {} is pending. The new identity will be used after {}",
+            rotation.new_public_key(),
+            promote_at
+        );
+        return Ok(false);
+    }
+
+    fs::rename(identity_file, previous_identity_file(identity_file))?;
+    fs::rename(&next_file, identity_file)?;
+    if config.p2p.transport.is_tor() && config.tor_identity_file.exists() {
+        fs::rename(
+            &config.tor_identity_file,
+            previous_identity_file(&config.tor_identity_file),
+        )?;
+    }
+
+    info!(
+        target: LOG_TARGET,
+        "Node identity rotated from {} to {}",
+        rotation.previous_public_key(),
+        rotation.new_public_key()
+    );
+    Ok(true)
+}
+
+/// Serves the onion address of the replaced identity alongside the onion address of the promoted identity until the
+/// dual-listen window, the rotation window following the promotion, has elapsed. Once it has elapsed, the replaced
+/// onion address is removed from the node's public addresses and the previous Tor identity is deleted.
+pub async fn serve_previous_onion_address(
+    config: &BaseNodeConfig,
+    tor_config: &TorTransportConfig,
+    comms: &CommsNode,
+) -> Result<(), ExitError> {
+    let Some(hidden_service) = comms.hidden_service() else {
+        return Ok(());
+    };
+    let previous_tor_identity_file = previous_identity_file(&config.tor_identity_file);
+    let Some(previous_tor_identity) = load_from_json::<_, TorIdentity>(&previous_tor_identity_file)
+        .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?
+    else {
+        return Ok(());
+    };
+    let previous_onion_address = previous_tor_identity
+        .try_get_onion_address()
+        .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?;
+    let node_identity = comms.node_identity();
+
+    let listen_until = dual_listen_until(config)?;
+    let remaining = listen_until
+        .and_then(|until| (until - Utc::now()).to_std().ok())
+        .filter(|remaining| !remaining.is_zero());
+    let Some(remaining) = remaining else {
+        retire_previous_onion_address(config, &node_identity, &previous_onion_address);
+        return Ok(());
+    };
+
+    let mut window_shutdown = Shutdown::new();
+    let mut hidden_service_ctl = HiddenServiceBuilder::new()
+        .with_port_mapping(tor_config.to_port_mapping().map_err(|e| e.to_exit_error())?)
+        .with_socks_authentication(tor_config.to_socks_auth())
+        .with_control_server_auth(tor_config.to_control_auth().map_err(|e| e.to_exit_error())?)
+        .with_socks_address_override(tor_config.socks_address_override.clone())
+        .with_control_server_address(tor_config.control_address.clone())
+        .with_tor_identity(previous_tor_identity)
+        .with_shutdown_signal(window_shutdown.to_signal())
+        .build()
+        .map_err(|e| ExitError::new(ExitCode::NetworkError, e))?;
+    // The previous onion address is forwarded to the same listener as the current one
+    hidden_service_ctl.set_proxied_addr(hidden_service.proxied_address());
+    let previous_hidden_service = hidden_service_ctl
+        .create_hidden_service()
+        .await
+        .map_err(|e| ExitError::new(ExitCode::NetworkError, e))?;
+    node_identity.add_public_address(previous_onion_address.clone());
+    info!(
+        target: LOG_TARGET,
+        "Serving the onion address {} of the replaced identity for another {}s",
+        previous_onion_address,
+        remaining.as_secs()
+    );
+
+    let config = config.clone();
+    let mut shutdown_signal = comms.shutdown_signal();
+    tokio::spawn(async move {
+        let _previous_hidden_service = previous_hidden_service;
+        tokio::select! {
+            _ = time::sleep(remaining) => {
+                retire_previous_onion_address(&config, &node_identity, &previous_onion_address);
+            },
+            _ = shutdown_signal.wait() => {},
+        }
+        window_shutdown.trigger();
+    });
+
+    Ok(())
+}
+
+/// Returns when the dual-listen window of the last promoted identity rotation ends
+fn dual_listen_until(config: &BaseNodeConfig) -> Result<Option<DateTime<Utc>>, ExitError> {
+    let window = chrono::Duration::from_std(config.identity_rotation_window)
+        .map_err(|e| ExitError::new(ExitCode::ConfigError, e))?;
+    let rotation = load_from_json::<_, IdentityRotation>(rotation_file(&config.identity_file))
+        .map_err(|e| ExitError::new(ExitCode::IdentityError, e))?;
+    Ok(rotation.map(|rotation| rotation.rotated_at() + window + window))
+}
+
+fn retire_previous_onion_address(config: &BaseNodeConfig, node_identity: &NodeIdentity, address: &Multiaddr) {
+    node_identity.set_public_addresses(
+        node_identity
+            .public_addresses()
+            .into_iter()
+            .filter(|a| a != address)
+            .collect(),
+    );
+    if let Err(e) = save_as_json(&config.identity_file, node_identity) {
+        warn!(target: LOG_TARGET, "Failed to save the node identity: {}", e);
+    }
+    for file in [
+        previous_identity_file(&config.tor_identity_file),
+        rotation_file(&config.identity_file),
+    ] {
+        if let Err(e) = fs::remove_file(&file) {
+            warn!(target: LOG_TARGET, "Failed to remove {}: {}", file.display(), e);
+        }
+    }
+    info!(
+        target: LOG_TARGET,
+        "The dual-listen window of the last identity rotation has elapsed. The onion address {} is no longer served.",
+        address
+    );
+}
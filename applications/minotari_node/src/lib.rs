@@ -32,6 +32,7 @@ pub mod cli;
 mod commands;
 pub mod config;
 mod grpc;
+pub mod identity_rotation;
 #[cfg(feature = "metrics")]
 mod metrics;
 mod recovery;
@@ -67,6 +67,7 @@
 /// `get-mempool-stats` - Displays information about the mempool
 /// `get-mempool-state` - Displays state information for the mempool
 /// `whoami` - Displays identity information about this Base Node and it's wallet
+/// `rotate-identity` - Announces a new identity for this Base Node, which is used after the rotation window
 /// `quit` - Exits the Base Node
 /// `exit` - Same as quit
 use std::{process, sync::Arc};
@@ -74,7 +75,7 @@ use std::{process, sync::Arc};
 use clap::Parser;
 use log::*;
 use minotari_app_utilities::{consts, identity_management::setup_node_identity, utilities::setup_runtime};
-use minotari_node::{cli::Cli, run_base_node_with_cli, ApplicationConfig};
+use minotari_node::{cli::Cli, identity_rotation::promote_pending_identity, run_base_node_with_cli, ApplicationConfig};
 use tari_common::{exit_codes::ExitError, initialize_logging, load_configuration};
 use tari_comms::peer_manager::PeerFeatures;
 #[cfg(all(unix, feature = "libtor"))]
@@ -128,6 +129,11 @@ fn main_inner() -> Result<(), ExitError> {
     let config = ApplicationConfig::load_from(&cfg)?;
     debug!(target: LOG_TARGET, "Using base node configuration: {:?}", config);
 
+    // Complete a pending identity rotation before the identity is loaded
+    if promote_pending_identity(&config.base_node)? {
+        info!(target: LOG_TARGET, "Node identity rotation completed");
+    }
+
     // Load or create the Node identity
     let node_identity = setup_node_identity(
         &config.base_node.identity_file,
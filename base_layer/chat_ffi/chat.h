@@ -119,6 +119,26 @@ struct ApplicationConfig *create_chat_config(const char *network_str,
                                              int log_verbosity,
                                              int *error_out);
 
+/**
+ * Sets the liveness privacy settings of an ApplicationConfig
+ *
+ * ## Arguments
+ * `config` - The pointer of an ApplicationConfig
+ * `hide_online_status` - Do not answer liveness pings or share this client's last-seen time with contacts
+ * `allow_outbound_pings` - Keep pinging contacts to learn their online status while `hide_online_status` is set
+ * `error_out` - Pointer to an int which will be modified
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void set_chat_config_liveness_privacy(struct ApplicationConfig *config,
+                                      bool hide_online_status,
+                                      bool allow_outbound_pings,
+                                      int *error_out);
+
 /**
  * Frees memory for an ApplicationConfig
  *
@@ -208,6 +208,39 @@ pub unsafe extern "C" fn create_chat_config(
     Box::into_raw(Box::new(config))
 }
 
+/// Sets the liveness privacy settings of an ApplicationConfig
+///
+/// ## Arguments
+/// `config` - The pointer of an ApplicationConfig
+/// `hide_online_status` - Do not answer liveness pings or share this client's last-seen time with contacts
+/// `allow_outbound_pings` - Keep pinging contacts to learn their online status while `hide_online_status` is set
+/// `error_out` - Pointer to an int which will be modified
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn set_chat_config_liveness_privacy(
+    config: *mut ApplicationConfig,
+    hide_online_status: bool,
+    allow_outbound_pings: bool,
+    error_out: *mut c_int,
+) {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if config.is_null() {
+        error = LibChatError::from(InterfaceError::NullError("config".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return;
+    }
+
+    (*config).chat_client.hide_online_status = hide_online_status;
+    (*config).chat_client.allow_outbound_pings = allow_outbound_pings;
+}
+
 /// Frees memory for an ApplicationConfig
 ///
 /// ## Arguments
@@ -232,7 +265,7 @@ mod test {
     use libc::c_char;
 
     use crate::{
-        application_config::{create_chat_config, destroy_chat_config},
+        application_config::{create_chat_config, destroy_chat_config, set_chat_config_liveness_privacy},
         tansport_config::{create_chat_tor_transport_config, destroy_chat_tor_transport_config},
         *,
     };
@@ -275,6 +308,12 @@ mod test {
 
             assert_eq!(error, 0);
 
+            set_chat_config_liveness_privacy(chat_config, true, false, error_ptr);
+
+            assert_eq!(error, 0);
+            assert!((*chat_config).chat_client.hide_online_status);
+            assert!(!(*chat_config).chat_client.allow_outbound_pings);
+
             destroy_chat_config(chat_config);
             destroy_chat_tor_transport_config(transport_config);
         }
@@ -104,7 +104,9 @@ impl CallbackHandler {
                                     );
                                     self.trigger_contact_status_change(data.deref().clone());
                                 }
-                                ContactsLivenessEvent::NetworkSilence => {},
+                                ContactsLivenessEvent::NetworkSilence |
+                                ContactsLivenessEvent::ContactSaved(_) |
+                                ContactsLivenessEvent::ContactRemoved(_) => {},
                             }
                         },
                         Err(_) => { debug!(target: LOG_TARGET, "FFI Callback monitor had an error with contacts liveness")}
@@ -42,6 +42,12 @@ pub trait Encryptable<C> {
     const KNOWN_ONESIDED_PAYMENT_SCRIPT: &'static [u8] = b"KNOWN_ONESIDED_PAYMENT_SCRIPT";
     const CLIENT_KEY_VALUE: &'static [u8] = b"CLIENT_KEY_VALUE";
     const BURNT_PROOF: &'static [u8] = b"BURNT_PROOF";
+    const OUTBOUND_MESSAGE: &'static [u8] = b"OUTBOUND_MESSAGE";
+    const ATOMIC_SWAP: &'static [u8] = b"ATOMIC_SWAP";
+    const TRANSACTION_MEMO: &'static [u8] = b"TRANSACTION_MEMO";
+    const OFFLINE_TRANSACTION: &'static [u8] = b"OFFLINE_TRANSACTION";
+    const BURN_TRANSACTION: &'static [u8] = b"BURN_TRANSACTION";
+    const SUPERSEDED_TRANSACTION: &'static [u8] = b"SUPERSEDED_TRANSACTION";
 
     fn domain(&self, field_name: &'static str) -> Vec<u8>;
     fn encrypt(self, cipher: &C) -> Result<Self, String>
@@ -56,6 +56,10 @@ pub enum TariAddressError {
     InvalidEmoji,
     #[error("Cannot recover public key")]
     CannotRecoverPublicKey,
+    #[error("Invalid hex encoding")]
+    InvalidHex,
+    #[error("Address is for network {actual}, expected {expected}")]
+    NetworkMismatch { expected: Network, actual: Network },
 }
 
 impl TariAddress {
@@ -171,13 +175,13 @@ impl TariAddress {
 
     /// Construct Tari Address from hex with network
     pub fn from_hex_with_network(hex_str: &str, network: Network) -> Result<TariAddress, TariAddressError> {
-        let buf = from_hex(hex_str).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
+        let buf = from_hex(hex_str).map_err(|_| TariAddressError::InvalidHex)?;
         TariAddress::from_bytes_with_network(buf.as_slice(), network)
     }
 
     /// Construct Tari Address from hex  and try to calculate the network
     pub fn from_hex(hex_str: &str) -> Result<TariAddress, TariAddressError> {
-        let buf = from_hex(hex_str).map_err(|_| TariAddressError::CannotRecoverPublicKey)?;
+        let buf = from_hex(hex_str).map_err(|_| TariAddressError::InvalidHex)?;
         TariAddress::from_bytes(buf.as_slice())
     }
 
@@ -186,6 +190,29 @@ impl TariAddress {
         let buf = self.to_bytes();
         buf.to_hex()
     }
+
+    /// Parse a Tari Address from either its emoji or hex representation, reporting why the address is invalid rather
+    /// than a generic error
+    pub fn parse(address: &str) -> Result<TariAddress, TariAddressError> {
+        let address = address.trim();
+        if !address.is_empty() && address.chars().all(|c| c.is_ascii_hexdigit()) {
+            TariAddress::from_hex(address)
+        } else {
+            TariAddress::from_emoji_string(&address.replace('|', ""))
+        }
+    }
+
+    /// Parse a Tari Address as [TariAddress::parse] does, additionally checking that it is for the expected network
+    pub fn parse_with_network(address: &str, network: Network) -> Result<TariAddress, TariAddressError> {
+        let address = TariAddress::parse(address)?;
+        if address.network() != network {
+            return Err(TariAddressError::NetworkMismatch {
+                expected: network,
+                actual: address.network(),
+            });
+        }
+        Ok(address)
+    }
 }
 
 impl FromStr for TariAddress {
@@ -325,6 +352,47 @@ mod test {
         );
     }
 
+    #[test]
+    /// Test that parsing reports the reason an address is invalid
+    fn parse_reports_reason() {
+        let mut rng = rand::thread_rng();
+        let public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut rng));
+        let address = TariAddress::new(public_key, Network::Esmeralda);
+
+        assert_eq!(TariAddress::parse(&address.to_hex()), Ok(address.clone()));
+        assert_eq!(TariAddress::parse(&address.to_emoji_string()), Ok(address.clone()));
+        assert_eq!(
+            TariAddress::parse(&format!(" {} ", address.to_emoji_string())),
+            Ok(address.clone())
+        );
+
+        // Odd length hex
+        assert_eq!(
+            TariAddress::parse(&address.to_hex()[1..]),
+            Err(TariAddressError::InvalidHex)
+        );
+        // Valid hex, but too short
+        assert_eq!(
+            TariAddress::parse(&address.to_hex()[2..]),
+            Err(TariAddressError::InvalidSize)
+        );
+        // Emoji ID with a character removed
+        let emoji = address.to_emoji_string().chars().skip(1).collect::<String>();
+        assert_eq!(TariAddress::parse(&emoji), Err(TariAddressError::InvalidSize));
+
+        assert_eq!(
+            TariAddress::parse_with_network(&address.to_hex(), Network::MainNet),
+            Err(TariAddressError::NetworkMismatch {
+                expected: Network::MainNet,
+                actual: Network::Esmeralda
+            })
+        );
+        assert_eq!(
+            TariAddress::parse_with_network(&address.to_hex(), Network::Esmeralda),
+            Ok(address)
+        );
+    }
+
     #[test]
     /// Test invalid public key
     fn invalid_public_key() {
@@ -38,13 +38,22 @@ pub enum TransactionStatus {
     FauxConfirmed,
     /// This transaction is still being queued for initial sending
     Queued,
+    /// This is a record of a historical transaction imported from outside the wallet, e.g. an old wallet export. It
+    /// has no outputs or kernel in this wallet and is never validated against the chain.
+    ImportedHistory,
+    /// The recipient has replied, but the transaction needs the signatures of the cosigners of this wallet's keys
+    /// before it can be completed
+    AwaitingCosigners,
 }
 
 impl TransactionStatus {
     pub fn is_faux(&self) -> bool {
         matches!(
             self,
-            TransactionStatus::Imported | TransactionStatus::FauxUnconfirmed | TransactionStatus::FauxConfirmed
+            TransactionStatus::Imported |
+                TransactionStatus::FauxUnconfirmed |
+                TransactionStatus::FauxConfirmed |
+                TransactionStatus::ImportedHistory
         )
     }
 }
@@ -71,6 +80,8 @@ impl TryFrom<i32> for TransactionStatus {
             8 => Ok(TransactionStatus::FauxUnconfirmed),
             9 => Ok(TransactionStatus::FauxConfirmed),
             10 => Ok(TransactionStatus::Queued),
+            11 => Ok(TransactionStatus::ImportedHistory),
+            12 => Ok(TransactionStatus::AwaitingCosigners),
             code => Err(TransactionConversionError { code }),
         }
     }
@@ -91,6 +102,8 @@ impl Display for TransactionStatus {
             TransactionStatus::FauxUnconfirmed => write!(f, "FauxUnconfirmed"),
             TransactionStatus::FauxConfirmed => write!(f, "FauxConfirmed"),
             TransactionStatus::Queued => write!(f, "Queued"),
+            TransactionStatus::ImportedHistory => write!(f, "Imported History"),
+            TransactionStatus::AwaitingCosigners => write!(f, "Awaiting Cosigners"),
         }
     }
 }
@@ -0,0 +1 @@
+ALTER TABLE contacts DROP liveness_policy;
@@ -0,0 +1 @@
+ALTER TABLE contacts ADD liveness_policy INTEGER DEFAULT 0 NOT NULL;
@@ -0,0 +1,5 @@
+DROP INDEX idx_messages_address;
+
+DROP TABLE IF EXISTS messages;
+
+DROP TABLE IF EXISTS contacts;
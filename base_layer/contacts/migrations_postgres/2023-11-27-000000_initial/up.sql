@@ -0,0 +1,22 @@
+CREATE TABLE contacts (
+    address         BYTEA PRIMARY KEY NOT NULL,
+    node_id         BYTEA             NOT NULL UNIQUE,
+    alias           TEXT              NOT NULL,
+    last_seen       TIMESTAMP         NULL,
+    latency         INTEGER           NULL,
+    favourite       INTEGER           NOT NULL DEFAULT 0,
+    liveness_policy INTEGER           NOT NULL DEFAULT 0
+);
+
+CREATE TABLE messages (
+    address                  BYTEA             NOT NULL,
+    message_id               BYTEA PRIMARY KEY NOT NULL,
+    body                     BYTEA             NOT NULL,
+    metadata                 BYTEA             NOT NULL,
+    stored_at                TIMESTAMP         NOT NULL,
+    delivery_confirmation_at TIMESTAMP         NULL,
+    read_confirmation_at     TIMESTAMP         NULL,
+    direction                INTEGER           NOT NULL
+);
+
+CREATE INDEX idx_messages_address ON messages (address);
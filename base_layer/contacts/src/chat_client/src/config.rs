@@ -91,6 +91,14 @@ pub struct ChatClientConfig {
     /// Liveness meta data auto ping interval between peers
     #[serde(with = "serializers::seconds")]
     pub metadata_auto_ping_interval: Duration,
+    /// How long to wait for a message to be sent directly to an online contact before falling back to store and
+    /// forward. Zero always uses store and forward.
+    #[serde(with = "serializers::seconds")]
+    pub message_direct_send_timeout: Duration,
+    /// Do not answer liveness pings or share this client's last-seen time with contacts
+    pub hide_online_status: bool,
+    /// Keep pinging contacts to learn their online status while `hide_online_status` is set
+    pub allow_outbound_pings: bool,
     /// The location of the log path
     pub log_path: Option<PathBuf>,
     /// The log verbosity
@@ -121,6 +129,9 @@ impl Default for ChatClientConfig {
             lmdb_path: PathBuf::from("db"),
             force_sync_peers: StringList::default(),
             metadata_auto_ping_interval: Duration::from_secs(30),
+            message_direct_send_timeout: Duration::from_secs(20),
+            hide_online_status: false,
+            allow_outbound_pings: true,
             log_path: None,
             log_verbosity: Some(2), // Warn
         }
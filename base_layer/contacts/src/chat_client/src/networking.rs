@@ -30,7 +30,11 @@ pub use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
 };
 use tari_comms::{peer_manager::Peer, CommsNode, UnspawnedCommsNode};
-use tari_contacts::contacts_service::{handle::ContactsServiceHandle, ContactsServiceInitializer};
+use tari_contacts::contacts_service::{
+    handle::ContactsServiceHandle,
+    types::{LivenessPrivacyMode, MessageDeliveryStrategy},
+    ContactsServiceInitializer,
+};
 use tari_p2p::{
     comms_connector::pubsub_connector,
     initialization::{spawn_comms_using_transport, P2pInitializer},
@@ -78,12 +82,16 @@ pub async fn start(
             },
             in_msg.clone(),
         ))
-        .add_initializer(ContactsServiceInitializer::new(
-            backend,
-            in_msg,
-            Duration::from_secs(5),
-            2,
-        ))
+        .add_initializer(
+            ContactsServiceInitializer::new(backend, in_msg, Duration::from_secs(5), 2)
+                .with_delivery_strategy(MessageDeliveryStrategy::from_direct_send_timeout(
+                    config.chat_client.message_direct_send_timeout,
+                ))
+                .with_liveness_privacy(LivenessPrivacyMode::from_settings(
+                    config.chat_client.hide_online_status,
+                    config.chat_client.allow_outbound_pings,
+                )),
+        )
         .build();
 
     let mut handles = fut.await.expect("Service initialization failed");
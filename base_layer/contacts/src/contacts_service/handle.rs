@@ -37,7 +37,7 @@ use tower::Service;
 use crate::contacts_service::{
     error::ContactsServiceError,
     service::{ContactMessageType, ContactOnlineStatus},
-    types::{Confirmation, Contact, Message, MessageDispatch},
+    types::{Confirmation, Contact, ContactLivenessPolicy, Message, MessageDispatch},
 };
 
 pub static DEFAULT_MESSAGE_LIMIT: u64 = 35;
@@ -127,6 +127,10 @@ impl Display for ContactsLivenessData {
 pub enum ContactsLivenessEvent {
     StatusUpdated(Box<ContactsLivenessData>),
     NetworkSilence,
+    /// A contact was added or updated
+    ContactSaved(Box<Contact>),
+    /// A contact was removed
+    ContactRemoved(Box<Contact>),
 }
 
 #[derive(Debug)]
@@ -140,6 +144,7 @@ pub enum ContactsServiceRequest {
     GetMessages(TariAddress, i64, i64),
     SendReadConfirmation(TariAddress, Confirmation),
     GetConversationalists,
+    SetLivenessPolicy(Vec<TariAddress>, ContactLivenessPolicy),
 }
 
 #[derive(Debug)]
@@ -150,9 +155,10 @@ pub enum ContactsServiceResponse {
     Contacts(Vec<Contact>),
     OnlineStatus(ContactOnlineStatus),
     Messages(Vec<Message>),
-    MessageSent,
+    MessageSent(DeliveryRoute),
     ReadConfirmationSent,
     Conversationalists(Vec<TariAddress>),
+    LivenessPolicySet,
 }
 
 #[derive(Clone)]
@@ -223,6 +229,40 @@ impl ContactsServiceHandle {
         }
     }
 
+    /// Sets how the liveness of a single contact is monitored
+    pub async fn set_contact_liveness_policy(
+        &mut self,
+        address: TariAddress,
+        liveness_policy: ContactLivenessPolicy,
+    ) -> Result<(), ContactsServiceError> {
+        self.set_contacts_liveness_policy(vec![address], liveness_policy).await
+    }
+
+    /// Sets how the liveness of each of the given contacts is monitored. Unknown addresses are ignored.
+    pub async fn set_contacts_liveness_policy(
+        &mut self,
+        addresses: Vec<TariAddress>,
+        liveness_policy: ContactLivenessPolicy,
+    ) -> Result<(), ContactsServiceError> {
+        match self
+            .request_response_service
+            .call(ContactsServiceRequest::SetLivenessPolicy(addresses, liveness_policy))
+            .await??
+        {
+            ContactsServiceResponse::LivenessPolicySet => Ok(()),
+            _ => Err(ContactsServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sets how the liveness of every contact is monitored
+    pub async fn set_all_contacts_liveness_policy(
+        &mut self,
+        liveness_policy: ContactLivenessPolicy,
+    ) -> Result<(), ContactsServiceError> {
+        let addresses = self.get_contacts().await?.into_iter().map(|c| c.address).collect();
+        self.set_contacts_liveness_policy(addresses, liveness_policy).await
+    }
+
     pub fn get_contacts_liveness_event_stream(&self) -> broadcast::Receiver<Arc<ContactsLivenessEvent>> {
         self.liveness_events.subscribe()
     }
@@ -277,13 +317,14 @@ impl ContactsServiceHandle {
         }
     }
 
-    pub async fn send_message(&mut self, message: Message) -> Result<(), ContactsServiceError> {
+    /// Sends a chat message to the contact in `message.address`, returning the route it was delivered by
+    pub async fn send_message(&mut self, message: Message) -> Result<DeliveryRoute, ContactsServiceError> {
         match self
             .request_response_service
             .call(ContactsServiceRequest::SendMessage(message.address.clone(), message))
             .await??
         {
-            ContactsServiceResponse::MessageSent => Ok(()),
+            ContactsServiceResponse::MessageSent(route) => Ok(route),
             _ => Err(ContactsServiceError::UnexpectedApiResponse),
         }
     }
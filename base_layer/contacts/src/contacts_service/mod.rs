@@ -47,6 +47,7 @@ use crate::contacts_service::{
     handle::ContactsServiceHandle,
     service::ContactsService,
     storage::database::{ContactsBackend, ContactsDatabase},
+    types::{LivenessPrivacyMode, MessageDeliveryStrategy},
 };
 
 const LOG_TARGET: &str = "contacts::contacts_service::initializer";
@@ -57,6 +58,8 @@ where T: ContactsBackend
     backend: Option<T>,
     contacts_auto_ping_interval: Duration,
     contacts_online_ping_window: usize,
+    delivery_strategy: MessageDeliveryStrategy,
+    liveness_privacy: LivenessPrivacyMode,
     subscription_factory: Arc<SubscriptionFactory>,
 }
 
@@ -73,9 +76,23 @@ where T: ContactsBackend
             backend: Some(backend),
             contacts_auto_ping_interval,
             contacts_online_ping_window: online_ping_window,
+            delivery_strategy: MessageDeliveryStrategy::default(),
+            liveness_privacy: LivenessPrivacyMode::default(),
             subscription_factory,
         }
     }
+
+    /// Sets how chat messages are delivered, defaults to a direct send with a store and forward fallback
+    pub fn with_delivery_strategy(mut self, delivery_strategy: MessageDeliveryStrategy) -> Self {
+        self.delivery_strategy = delivery_strategy;
+        self
+    }
+
+    /// Sets how much of this node's online presence is shared with contacts, defaults to sharing it
+    pub fn with_liveness_privacy(mut self, liveness_privacy: LivenessPrivacyMode) -> Self {
+        self.liveness_privacy = liveness_privacy;
+        self
+    }
 }
 
 #[async_trait]
@@ -101,6 +118,8 @@ where T: ContactsBackend + 'static
 
         let contacts_auto_ping_interval = self.contacts_auto_ping_interval;
         let contacts_online_ping_window = self.contacts_online_ping_window;
+        let delivery_strategy = self.delivery_strategy;
+        let liveness_privacy = self.liveness_privacy;
         let subscription_factory = self.subscription_factory.clone();
         context.spawn_when_ready(move |handles| async move {
             let liveness = handles.expect_handle::<LivenessHandle>();
@@ -119,6 +138,8 @@ where T: ContactsBackend + 'static
                 message_publisher,
                 contacts_auto_ping_interval,
                 contacts_online_ping_window,
+                delivery_strategy,
+                liveness_privacy,
             )
             .start();
             futures::pin_mut!(service);
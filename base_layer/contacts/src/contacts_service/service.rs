@@ -36,7 +36,11 @@ use tari_comms::{
     connectivity::{ConnectivityEvent, ConnectivityRequester},
     types::CommsPublicKey,
 };
-use tari_comms_dht::{domain_message::OutboundDomainMessage, outbound::OutboundEncryption, Dht};
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, SendMessageResponse},
+    Dht,
+};
 use tari_p2p::{
     comms_connector::SubscriptionFactory,
     domain_message::DomainMessage,
@@ -56,7 +60,16 @@ use crate::contacts_service::{
     handle::{ContactsLivenessData, ContactsLivenessEvent, ContactsServiceRequest, ContactsServiceResponse},
     proto,
     storage::database::{ContactsBackend, ContactsDatabase},
-    types::{Confirmation, Contact, Message, MessageDispatch},
+    types::{
+        Confirmation,
+        Contact,
+        ContactLivenessPolicy,
+        DeliveryRoute,
+        LivenessPrivacyMode,
+        Message,
+        MessageDeliveryStrategy,
+        MessageDispatch,
+    },
 };
 
 const LOG_TARGET: &str = "contacts::contacts_service";
@@ -137,11 +150,14 @@ where T: ContactsBackend + 'static
     number_of_rounds_no_pings: u16,
     contacts_auto_ping_interval: Duration,
     contacts_online_ping_window: usize,
+    delivery_strategy: MessageDeliveryStrategy,
+    liveness_privacy: LivenessPrivacyMode,
 }
 
 impl<T> ContactsService<T>
 where T: ContactsBackend + 'static
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         db: ContactsDatabase<T>,
         request_stream: reply_channel::Receiver<
@@ -157,6 +173,8 @@ where T: ContactsBackend + 'static
         message_publisher: broadcast::Sender<Arc<MessageDispatch>>,
         contacts_auto_ping_interval: Duration,
         contacts_online_ping_window: usize,
+        delivery_strategy: MessageDeliveryStrategy,
+        liveness_privacy: LivenessPrivacyMode,
     ) -> Self {
         Self {
             db,
@@ -172,6 +190,8 @@ where T: ContactsBackend + 'static
             number_of_rounds_no_pings: 0,
             contacts_auto_ping_interval,
             contacts_online_ping_window,
+            delivery_strategy,
+            liveness_privacy,
         }
     }
 
@@ -207,7 +227,15 @@ where T: ContactsBackend + 'static
         if let Ok(ref contacts) = result {
             self.add_contacts_to_liveness_service(contacts).await?;
         }
-        self.set_liveness_metadata(b"Watching you!".to_vec()).await?;
+        if self.liveness_privacy.shares_online_status() {
+            self.set_liveness_metadata(b"Watching you!".to_vec()).await?;
+        } else {
+            self.liveness.set_respond_to_pings(false).await?;
+            info!(
+                target: LOG_TARGET,
+                "Contacts liveness privacy mode is {:?}, online status is not shared", self.liveness_privacy
+            );
+        }
         debug!(target: LOG_TARGET, "Contacts Service started");
         loop {
             tokio::select! {
@@ -259,17 +287,28 @@ where T: ContactsBackend + 'static
             ContactsServiceRequest::GetContact(pk) => {
                 let result = self.db.get_contact(pk.clone());
                 if let Ok(ref contact) = result {
-                    self.liveness.check_add_monitored_peer(contact.node_id.clone()).await?;
+                    match self.effective_liveness_policy(contact) {
+                        ContactLivenessPolicy::Always => {
+                            self.liveness.check_add_monitored_peer(contact.node_id.clone()).await?
+                        },
+                        ContactLivenessPolicy::OnDemand => self.liveness.send_ping(contact.node_id.clone()).await?,
+                        ContactLivenessPolicy::Never => {},
+                    }
                 };
                 Ok(result.map(ContactsServiceResponse::Contact)?)
             },
             ContactsServiceRequest::UpsertContact(c) => {
                 self.db.upsert_contact(c.clone())?;
-                self.liveness.check_add_monitored_peer(c.node_id.clone()).await?;
+                // The stored policy is kept when an existing contact is updated
+                let contact = self.db.get_contact(c.address.clone())?;
+                self.apply_liveness_policy(&contact).await?;
                 info!(
                     target: LOG_TARGET,
                     "Contact Saved: \nAlias: {}\nAddress: {}\nNodeId: {}", c.alias, c.address, c.node_id
                 );
+                let _size = self
+                    .event_publisher
+                    .send(Arc::new(ContactsLivenessEvent::ContactSaved(Box::new(contact))));
                 Ok(ContactsServiceResponse::ContactSaved)
             },
             ContactsServiceRequest::RemoveContact(pk) => {
@@ -281,6 +320,11 @@ where T: ContactsBackend + 'static
                     target: LOG_TARGET,
                     "Contact Removed: \nAlias: {}\nAddress: {} ", result.alias, result.address
                 );
+                let _size = self
+                    .event_publisher
+                    .send(Arc::new(ContactsLivenessEvent::ContactRemoved(Box::new(
+                        result.clone(),
+                    ))));
                 Ok(ContactsServiceResponse::ContactRemoved(result))
             },
             ContactsServiceRequest::GetContacts => {
@@ -303,9 +347,9 @@ where T: ContactsBackend + 'static
 
                 message.stored_at = Utc::now().naive_utc().timestamp() as u64;
                 self.db.save_message(message)?;
-                self.deliver_message(address, ob_message).await?;
+                let route = self.deliver_message(address, ob_message).await?;
 
-                Ok(ContactsServiceResponse::MessageSent)
+                Ok(ContactsServiceResponse::MessageSent(route))
             },
             ContactsServiceRequest::SendReadConfirmation(address, confirmation) => {
                 let msg = OutboundDomainMessage::from(MessageDispatch::ReadConfirmation(confirmation.clone()));
@@ -322,16 +366,58 @@ where T: ContactsBackend + 'static
                 let result = self.db.get_conversationlists();
                 Ok(result.map(ContactsServiceResponse::Conversationalists)?)
             },
+            ContactsServiceRequest::SetLivenessPolicy(addresses, liveness_policy) => {
+                self.db
+                    .set_contacts_liveness_policy(addresses.clone(), liveness_policy)?;
+                for address in addresses {
+                    if let Ok(contact) = self.db.get_contact(address) {
+                        self.apply_liveness_policy(&contact).await?;
+                    }
+                }
+                debug!(
+                    target: LOG_TARGET,
+                    "Contact liveness policy set to '{}'", liveness_policy
+                );
+                Ok(ContactsServiceResponse::LivenessPolicySet)
+            },
         }
     }
 
     async fn add_contacts_to_liveness_service(&mut self, contacts: &[Contact]) -> Result<(), ContactsServiceError> {
-        for contact in contacts {
+        if !self.liveness_privacy.allows_outbound_pings() {
+            return Ok(());
+        }
+        for contact in contacts
+            .iter()
+            .filter(|c| c.liveness_policy == ContactLivenessPolicy::Always)
+        {
             self.liveness.check_add_monitored_peer(contact.node_id.clone()).await?;
         }
         Ok(())
     }
 
+    /// Only contacts with the `Always` policy are monitored by the liveness service and pinged every round
+    async fn apply_liveness_policy(&mut self, contact: &Contact) -> Result<(), ContactsServiceError> {
+        match self.effective_liveness_policy(contact) {
+            ContactLivenessPolicy::Always => self.liveness.check_add_monitored_peer(contact.node_id.clone()).await?,
+            ContactLivenessPolicy::OnDemand | ContactLivenessPolicy::Never => {
+                self.liveness
+                    .check_remove_monitored_peer(contact.node_id.clone())
+                    .await?
+            },
+        }
+        Ok(())
+    }
+
+    /// Contacts are never pinged when outbound pings are disabled by the liveness privacy mode
+    fn effective_liveness_policy(&self, contact: &Contact) -> ContactLivenessPolicy {
+        if self.liveness_privacy.allows_outbound_pings() {
+            contact.liveness_policy
+        } else {
+            ContactLivenessPolicy::Never
+        }
+    }
+
     /// Tack this node's metadata on to ping/pongs sent by the liveness service
     async fn set_liveness_metadata(&mut self, message: Vec<u8>) -> Result<(), ContactsServiceError> {
         self.liveness
@@ -368,7 +454,10 @@ where T: ContactsBackend + 'static
 
                 // Update offline status
                 if let Ok(contacts) = self.db.get_contacts() {
-                    for contact in contacts {
+                    for contact in contacts
+                        .into_iter()
+                        .filter(|c| c.liveness_policy != ContactLivenessPolicy::Never)
+                    {
                         let online_status = self.get_online_status(&contact).await?;
                         if online_status == ContactOnlineStatus::Online {
                             continue;
@@ -389,6 +478,8 @@ where T: ContactsBackend + 'static
                     }
                 };
             },
+            // Clock skew is reported by the liveness service and does not affect contact liveness
+            LivenessEvent::ClockSkewDetected(_) | LivenessEvent::ClockSkewResolved(_) => {},
         }
 
         Ok(())
@@ -613,36 +704,77 @@ where T: ContactsBackend + 'static
         &mut self,
         address: TariAddress,
         message: OutboundDomainMessage<proto::MessageDispatch>,
-    ) -> Result<(), ContactsServiceError> {
+    ) -> Result<DeliveryRoute, ContactsServiceError> {
         let contact = match self.db.get_contact(address.clone()) {
             Ok(contact) => contact,
             Err(_) => Contact::from(&address),
         };
         let encryption = OutboundEncryption::EncryptFor(Box::new(address.public_key().clone()));
 
-        match self.get_online_status(&contact).await {
-            Ok(ContactOnlineStatus::Online) => {
-                info!(target: LOG_TARGET, "Chat message being sent directed");
-                let mut comms_outbound = self.dht.outbound_requester();
-
-                comms_outbound
-                    .send_direct_encrypted(
-                        address.public_key().clone(),
-                        message,
-                        encryption,
-                        "contact service messaging".to_string(),
-                    )
-                    .await?;
-            },
-            Err(e) => return Err(e),
-            _ => {
-                let mut comms_outbound = self.dht.outbound_requester();
-                comms_outbound
-                    .closest_broadcast(address.public_key().clone(), encryption, vec![], message)
-                    .await?;
-            },
+        if let MessageDeliveryStrategy::DirectWithSafFallback { direct_send_timeout } = self.delivery_strategy {
+            if self.get_online_status(&contact).await? == ContactOnlineStatus::Online {
+                if self
+                    .send_direct(&address, message.clone(), encryption.clone(), direct_send_timeout)
+                    .await?
+                {
+                    debug!(target: LOG_TARGET, "Chat message delivered directly to {}", address);
+                    return Ok(DeliveryRoute::Direct);
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Direct send of chat message to {} did not complete within {:.2?}, falling back to store and \
+                     forward",
+                    address,
+                    direct_send_timeout
+                );
+            }
+        }
+
+        let mut comms_outbound = self.dht.outbound_requester();
+        comms_outbound
+            .closest_broadcast(address.public_key().clone(), encryption, vec![], message)
+            .await?;
+        debug!(target: LOG_TARGET, "Chat message sent to {} via store and forward", address);
+
+        Ok(DeliveryRoute::StoreAndForward)
+    }
+
+    /// Attempts a direct send to `address`, returning true if the message was sent within `timeout`
+    async fn send_direct(
+        &mut self,
+        address: &TariAddress,
+        message: OutboundDomainMessage<proto::MessageDispatch>,
+        encryption: OutboundEncryption,
+        timeout: Duration,
+    ) -> Result<bool, ContactsServiceError> {
+        let mut comms_outbound = self.dht.outbound_requester();
+        let response = comms_outbound
+            .send_direct_encrypted(
+                address.public_key().clone(),
+                message,
+                encryption,
+                "contact service messaging".to_string(),
+            )
+            .await?;
+
+        let send_attempt = async move {
+            let response = match response {
+                SendMessageResponse::PendingDiscovery(rx) => match rx.await {
+                    Ok(response) => response,
+                    Err(_) => return false,
+                },
+                response => response,
+            };
+            match response {
+                SendMessageResponse::Queued(send_states) => send_states.wait_single().await,
+                SendMessageResponse::Failed(err) => {
+                    debug!(target: LOG_TARGET, "Direct send of chat message failed: {}", err);
+                    false
+                },
+                SendMessageResponse::PendingDiscovery(_) => false,
+            }
         };
 
-        Ok(())
+        Ok(tokio::time::timeout(timeout, send_attempt).await.unwrap_or(false))
     }
 }
@@ -33,7 +33,7 @@ use tari_comms::peer_manager::NodeId;
 
 use crate::contacts_service::{
     error::ContactsServiceStorageError,
-    types::{Contact, Message},
+    types::{Contact, ContactLivenessPolicy, Message},
 };
 
 const LOG_TARGET: &str = "contacts::contacts_service::database";
@@ -70,6 +70,7 @@ pub enum DbKeyValuePair {
     Contact(TariAddress, Contact),
     MessageConfirmations(Vec<u8>, Option<NaiveDateTime>, Option<NaiveDateTime>),
     LastSeen(NodeId, NaiveDateTime, Option<i32>),
+    LivenessPolicy(Vec<TariAddress>, ContactLivenessPolicy),
 }
 
 pub enum WriteOperation {
@@ -131,6 +132,19 @@ where T: ContactsBackend + 'static
         Ok(())
     }
 
+    pub fn set_contacts_liveness_policy(
+        &self,
+        addresses: Vec<TariAddress>,
+        liveness_policy: ContactLivenessPolicy,
+    ) -> Result<(), ContactsServiceStorageError> {
+        self.db
+            .write(WriteOperation::Upsert(Box::new(DbKeyValuePair::LivenessPolicy(
+                addresses,
+                liveness_policy,
+            ))))?;
+        Ok(())
+    }
+
     // converting u32 to i32 is okay here as its just the latency which wont reach u32 max.
     #[allow(clippy::cast_possible_wrap)]
     pub fn update_contact_last_seen(
@@ -0,0 +1,303 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use diesel::{
+    pg::PgConnection,
+    prelude::*,
+    r2d2::{ConnectionManager, Pool, PooledConnection},
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tari_common_sqlite::error::SqliteStorageError;
+use tari_common_types::tari_address::TariAddress;
+use tari_utilities::ByteArray;
+
+use crate::{
+    contacts_service::{
+        error::ContactsServiceStorageError,
+        storage::{
+            database::{ContactsBackend, DbKey, DbKeyValuePair, DbValue, WriteOperation},
+            types::{
+                contacts::{ContactSql, UpdateContact},
+                messages::{MessageUpdate, MessagesSql, MessagesSqlInsert},
+            },
+        },
+        types::{Contact, Message},
+    },
+    schema::{contacts, messages},
+};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations_postgres");
+
+type PgConnectionManager = ConnectionManager<PgConnection>;
+
+/// A PostgreSQL backend for the Contacts Service, for deployments that keep their wallets on a database server rather
+/// than in a Sqlite file. The Backend is accessed via a connection pool to the server.
+#[derive(Clone)]
+pub struct ContactsServicePostgresDatabase {
+    pool: Pool<PgConnectionManager>,
+}
+
+impl ContactsServicePostgresDatabase {
+    /// Connect to the database at the given `postgres://` url with a pool of at most `pool_size` connections
+    pub fn connect(database_url: &str, pool_size: u32) -> Result<Self, ContactsServiceStorageError> {
+        let pool = Pool::builder()
+            .max_size(pool_size)
+            .build(PgConnectionManager::new(database_url))
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()))?;
+        Ok(Self { pool })
+    }
+
+    /// Connect to the database and bring its schema up to date
+    pub fn init(database_url: &str, pool_size: u32) -> Result<Self, ContactsServiceStorageError> {
+        let db = Self::connect(database_url, pool_size)?;
+        db.run_migrations()?;
+        Ok(db)
+    }
+
+    fn run_migrations(&self) -> Result<(), ContactsServiceStorageError> {
+        let mut conn = self.get_connection()?;
+        conn.run_pending_migrations(MIGRATIONS)
+            .map_err(|e| ContactsServiceStorageError::DatabaseMigrationError(e.to_string()))?;
+        Ok(())
+    }
+
+    fn get_connection(&self) -> Result<PooledConnection<PgConnectionManager>, ContactsServiceStorageError> {
+        self.pool
+            .get()
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()).into())
+    }
+}
+
+impl ContactsBackend for ContactsServicePostgresDatabase {
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ContactsServiceStorageError> {
+        let mut conn = self.get_connection()?;
+
+        let result = match key {
+            DbKey::Contact(address) => contacts::table
+                .filter(contacts::address.eq(address.to_bytes().to_vec()))
+                .first::<ContactSql>(&mut conn)
+                .optional()?
+                .map(|c| Contact::try_from(c).map(|c| DbValue::Contact(Box::new(c))))
+                .transpose()?,
+            DbKey::ContactId(id) => contacts::table
+                .filter(contacts::node_id.eq(id.to_vec()))
+                .first::<ContactSql>(&mut conn)
+                .optional()?
+                .map(|c| Contact::try_from(c).map(|c| DbValue::Contact(Box::new(c))))
+                .transpose()?,
+            DbKey::Contacts => Some(DbValue::Contacts(
+                contacts::table
+                    .load::<ContactSql>(&mut conn)?
+                    .into_iter()
+                    .map(Contact::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::Messages(address, limit, page) => Some(DbValue::Messages(
+                messages::table
+                    .filter(messages::address.eq(address.to_bytes().to_vec()))
+                    .order(messages::stored_at.desc())
+                    .offset(limit * page)
+                    .limit(*limit)
+                    .load::<MessagesSql>(&mut conn)?
+                    .into_iter()
+                    .map(Message::try_from)
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+            DbKey::Message(id) => messages::table
+                .filter(messages::message_id.eq(id.clone()))
+                .first::<MessagesSql>(&mut conn)
+                .optional()?
+                .map(|m| Message::try_from(m).map(|m| DbValue::Message(Box::new(m))))
+                .transpose()?,
+            DbKey::Conversationalists => Some(DbValue::Conversationalists(
+                messages::table
+                    .select(messages::address)
+                    .distinct()
+                    .load::<Vec<u8>>(&mut conn)?
+                    .iter()
+                    .map(|a| TariAddress::from_bytes(a).map_err(|_e| ContactsServiceStorageError::UnknownError))
+                    .collect::<Result<Vec<_>, _>>()?,
+            )),
+        };
+
+        Ok(result)
+    }
+
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, ContactsServiceStorageError> {
+        let mut conn = self.get_connection()?;
+
+        match op {
+            WriteOperation::Upsert(kvp) => match *kvp {
+                DbKeyValuePair::MessageConfirmations(k, d, r) => {
+                    let num_updated = diesel::update(messages::table.filter(messages::message_id.eq(k)))
+                        .set(MessageUpdate {
+                            delivery_confirmation_at: d,
+                            read_confirmation_at: r,
+                        })
+                        .execute(&mut conn)?;
+                    if num_updated == 0 {
+                        return Err(diesel::result::Error::NotFound.into());
+                    }
+                },
+                DbKeyValuePair::Contact(_k, c) => {
+                    let contact = ContactSql::from(c);
+                    diesel::insert_into(contacts::table)
+                        .values(contact.clone())
+                        .on_conflict(contacts::address)
+                        .do_update()
+                        .set((
+                            contacts::alias.eq(contact.alias.clone()),
+                            contacts::favourite.eq(contact.favourite),
+                        ))
+                        .execute(&mut conn)?;
+                },
+                DbKeyValuePair::LivenessPolicy(addresses, policy) => {
+                    let addresses = addresses.iter().map(|a| a.to_bytes().to_vec()).collect::<Vec<_>>();
+                    diesel::update(contacts::table.filter(contacts::address.eq_any(addresses)))
+                        .set(contacts::liveness_policy.eq(i32::from(policy.as_u8())))
+                        .execute(&mut conn)?;
+                },
+                DbKeyValuePair::LastSeen(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
+            },
+            WriteOperation::UpdateLastSeen(kvp) => match *kvp {
+                DbKeyValuePair::LastSeen(node_id, date_time, latency) => {
+                    let contact = diesel::update(contacts::table.filter(contacts::node_id.eq(node_id.to_vec())))
+                        .set(UpdateContact {
+                            alias: None,
+                            last_seen: Some(Some(date_time)),
+                            latency: Some(latency),
+                            favourite: None,
+                        })
+                        .get_result::<ContactSql>(&mut conn)?;
+                    return Ok(Some(DbValue::TariAddress(Box::new(
+                        TariAddress::from_bytes(&contact.address)
+                            .map_err(|_| ContactsServiceStorageError::ConversionError)?,
+                    ))));
+                },
+                DbKeyValuePair::Contact(..) |
+                DbKeyValuePair::LivenessPolicy(..) |
+                DbKeyValuePair::MessageConfirmations(..) => {
+                    return Err(ContactsServiceStorageError::OperationNotSupported)
+                },
+            },
+            WriteOperation::Remove(k) => {
+                let removed = match k {
+                    DbKey::Contact(k) => {
+                        diesel::delete(contacts::table.filter(contacts::address.eq(k.to_bytes().to_vec())))
+                            .get_result::<ContactSql>(&mut conn)
+                            .optional()?
+                    },
+                    DbKey::ContactId(id) => diesel::delete(contacts::table.filter(contacts::node_id.eq(id.to_vec())))
+                        .get_result::<ContactSql>(&mut conn)
+                        .optional()?,
+                    DbKey::Contacts | DbKey::Messages(..) | DbKey::Message(_) | DbKey::Conversationalists => {
+                        return Err(ContactsServiceStorageError::OperationNotSupported)
+                    },
+                };
+                if let Some(c) = removed {
+                    return Ok(Some(DbValue::Contact(Box::new(Contact::try_from(c)?))));
+                }
+            },
+            WriteOperation::Insert(i) => {
+                if let DbValue::Message(m) = *i {
+                    diesel::insert_into(messages::table)
+                        .values(MessagesSqlInsert::try_from(*m)?)
+                        .execute(&mut conn)?;
+                }
+            },
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use diesel::{Connection, PgConnection, RunQueryDsl};
+    use rand::rngs::OsRng;
+    use tari_common::configuration::Network;
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_test_utils::random::string;
+
+    use super::*;
+    use crate::contacts_service::{
+        storage::database::ContactsDatabase,
+        types::{Contact, ContactLivenessPolicy},
+    };
+
+    /// Creates an empty database on the server given by `TARI_TEST_POSTGRES_URL` and returns its url
+    fn make_database_url() -> String {
+        let server_url = std::env::var("TARI_TEST_POSTGRES_URL")
+            .expect("TARI_TEST_POSTGRES_URL must be set to run the postgres tests");
+        let mut conn = PgConnection::establish(&server_url).unwrap();
+        let db_name = format!("contacts_test_{}", string(12).to_lowercase());
+        diesel::sql_query(format!("CREATE DATABASE {}", db_name))
+            .execute(&mut conn)
+            .unwrap();
+        let (server, _) = server_url.rsplit_once('/').unwrap();
+        format!("{}/{}", server, db_name)
+    }
+
+    #[test]
+    fn test_crud() {
+        let db = ContactsDatabase::new(ContactsServicePostgresDatabase::init(&make_database_url(), 4).unwrap());
+
+        let mut contacts = Vec::new();
+        for name in ["Alice", "Bob", "Carol"] {
+            let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+            let address = TariAddress::new(public_key, Network::default());
+            let contact = Contact::new(name.to_string(), address, None, None, false);
+            db.upsert_contact(contact.clone()).unwrap();
+            contacts.push(contact);
+        }
+
+        let retrieved_contacts = db.get_contacts().unwrap();
+        assert_eq!(retrieved_contacts.len(), 3);
+        for contact in &contacts {
+            assert!(retrieved_contacts.contains(contact));
+        }
+        assert_eq!(db.get_contact(contacts[1].address.clone()).unwrap(), contacts[1]);
+
+        db.remove_contact(contacts[0].address.clone()).unwrap();
+        assert!(db.get_contact(contacts[0].address.clone()).is_err());
+        assert_eq!(db.get_contacts().unwrap().len(), 2);
+
+        let mut updated_contact = contacts[1].clone();
+        updated_contact.alias = "Fred".to_string();
+        updated_contact.favourite = true;
+        db.upsert_contact(updated_contact.clone()).unwrap();
+        let c_updated = db.get_contact(updated_contact.address.clone()).unwrap();
+        assert_eq!(c_updated.alias, "Fred".to_string());
+        assert!(c_updated.favourite);
+
+        db.set_contacts_liveness_policy(
+            vec![contacts[1].address.clone(), contacts[2].address.clone()],
+            ContactLivenessPolicy::Never,
+        )
+        .unwrap();
+        let c_updated = db.get_contact(contacts[2].address.clone()).unwrap();
+        assert_eq!(c_updated.liveness_policy, ContactLivenessPolicy::Never);
+    }
+}
@@ -85,7 +85,7 @@ impl<TContactServiceDbConnection> ContactsBackend for ContactsServiceSqliteDatab
 where TContactServiceDbConnection: PooledDbConnection<Error = SqliteStorageError>
 {
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, ContactsServiceStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
 
         let result = match key {
             DbKey::Contact(address) => match ContactSql::find_by_address(&address.to_bytes(), &mut conn) {
@@ -159,6 +159,10 @@ where TContactServiceDbConnection: PooledDbConnection<Error = SqliteStorageError
                         ContactSql::from(c).commit(&mut conn)?;
                     }
                 },
+                DbKeyValuePair::LivenessPolicy(addresses, policy) => {
+                    let addresses = addresses.iter().map(|a| a.to_bytes().to_vec()).collect::<Vec<_>>();
+                    ContactSql::set_liveness_policy(&mut conn, &addresses, i32::from(policy.as_u8()))?;
+                },
                 DbKeyValuePair::LastSeen(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
             },
             WriteOperation::UpdateLastSeen(kvp) => match *kvp {
@@ -175,7 +179,9 @@ where TContactServiceDbConnection: PooledDbConnection<Error = SqliteStorageError
                             .map_err(|_| ContactsServiceStorageError::ConversionError)?,
                     ))));
                 },
-                DbKeyValuePair::Contact(..) => return Err(ContactsServiceStorageError::OperationNotSupported),
+                DbKeyValuePair::Contact(..) | DbKeyValuePair::LivenessPolicy(..) => {
+                    return Err(ContactsServiceStorageError::OperationNotSupported)
+                },
                 DbKeyValuePair::MessageConfirmations(..) => {
                     return Err(ContactsServiceStorageError::OperationNotSupported)
                 },
@@ -228,7 +234,7 @@ mod test {
     use super::*;
     use crate::contacts_service::{
         storage::types::contacts::{ContactSql, UpdateContact},
-        types::Contact,
+        types::{Contact, ContactLivenessPolicy},
     };
 
     #[test]
@@ -285,6 +291,19 @@ mod test {
             let c_updated = ContactSql::find_by_address(&contacts[1].address.to_bytes(), &mut conn).unwrap();
             assert_eq!(c_updated.alias, "Fred".to_string());
             assert_eq!(c_updated.favourite, i32::from(true));
+
+            let addresses = vec![
+                contacts[1].address.to_bytes().to_vec(),
+                contacts[2].address.to_bytes().to_vec(),
+            ];
+            let num_updated =
+                ContactSql::set_liveness_policy(&mut conn, &addresses, i32::from(ContactLivenessPolicy::Never.as_u8()))
+                    .unwrap();
+            assert_eq!(num_updated, 2);
+            let c_updated =
+                Contact::try_from(ContactSql::find_by_address(&contacts[2].address.to_bytes(), &mut conn).unwrap())
+                    .unwrap();
+            assert_eq!(c_updated.liveness_policy, ContactLivenessPolicy::Never);
         });
     }
 }
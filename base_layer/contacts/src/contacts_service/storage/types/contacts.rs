@@ -30,7 +30,10 @@ use tari_comms::peer_manager::NodeId;
 use tari_utilities::ByteArray;
 
 use crate::{
-    contacts_service::{error::ContactsServiceStorageError, types::Contact},
+    contacts_service::{
+        error::ContactsServiceStorageError,
+        types::{Contact, ContactLivenessPolicy},
+    },
     schema::contacts,
 };
 
@@ -44,6 +47,7 @@ pub struct ContactSql {
     last_seen: Option<NaiveDateTime>,
     latency: Option<i32>,
     pub favourite: i32,
+    pub liveness_policy: i32,
 }
 
 impl ContactSql {
@@ -94,6 +98,19 @@ impl ContactSql {
         ContactSql::find_by_address(address, conn)
     }
 
+    /// Set the liveness policy of all the Contacts with the given addresses, returning the number of affected records
+    pub fn set_liveness_policy(
+        conn: &mut SqliteConnection,
+        addresses: &[Vec<u8>],
+        liveness_policy: i32,
+    ) -> Result<usize, ContactsServiceStorageError> {
+        Ok(
+            diesel::update(contacts::table.filter(contacts::address.eq_any(addresses)))
+                .set(contacts::liveness_policy.eq(liveness_policy))
+                .execute(conn)?,
+        )
+    }
+
     /// Find a particular Contact by their address, and delete it if it exists, returning the affected record
     pub fn find_by_address_and_delete(
         conn: &mut SqliteConnection,
@@ -154,6 +171,10 @@ impl TryFrom<ContactSql> for Contact {
                 1 => true,
                 _ => return Err(ContactsServiceStorageError::ConversionError),
             },
+            liveness_policy: u8::try_from(o.liveness_policy)
+                .ok()
+                .and_then(ContactLivenessPolicy::from_byte)
+                .ok_or(ContactsServiceStorageError::ConversionError)?,
         })
     }
 }
@@ -170,6 +191,7 @@ impl From<Contact> for ContactSql {
             last_seen: o.last_seen,
             latency: o.latency.map(|val| val as i32),
             favourite: i32::from(o.favourite),
+            liveness_policy: i32::from(o.liveness_policy.as_u8()),
         }
     }
 }
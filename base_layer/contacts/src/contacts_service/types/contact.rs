@@ -20,10 +20,53 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::fmt::{Display, Error, Formatter};
+
 use chrono::NaiveDateTime;
 use tari_common_types::tari_address::TariAddress;
 use tari_comms::peer_manager::NodeId;
 
+/// How the contacts service monitors the liveness of a contact
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ContactLivenessPolicy {
+    /// The contact is pinged every liveness round
+    #[default]
+    Always,
+    /// The contact is only pinged when it is explicitly requested
+    OnDemand,
+    /// The contact is never pinged
+    Never,
+}
+
+impl ContactLivenessPolicy {
+    pub fn as_u8(self) -> u8 {
+        match self {
+            Self::Always => 0,
+            Self::OnDemand => 1,
+            Self::Never => 2,
+        }
+    }
+
+    pub fn from_byte(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(Self::Always),
+            1 => Some(Self::OnDemand),
+            2 => Some(Self::Never),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ContactLivenessPolicy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            ContactLivenessPolicy::Always => write!(f, "Always"),
+            ContactLivenessPolicy::OnDemand => write!(f, "OnDemand"),
+            ContactLivenessPolicy::Never => write!(f, "Never"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Contact {
     pub alias: String,
@@ -32,6 +75,7 @@ pub struct Contact {
     pub last_seen: Option<NaiveDateTime>,
     pub latency: Option<u32>,
     pub favourite: bool,
+    pub liveness_policy: ContactLivenessPolicy,
 }
 
 impl Contact {
@@ -49,6 +93,7 @@ impl Contact {
             last_seen,
             latency,
             favourite,
+            liveness_policy: ContactLivenessPolicy::default(),
         }
     }
 }
@@ -62,6 +107,7 @@ impl From<&TariAddress> for Contact {
             last_seen: None,
             latency: None,
             favourite: false,
+            liveness_policy: ContactLivenessPolicy::default(),
         }
     }
 }
@@ -0,0 +1,74 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::{Display, Error, Formatter},
+    time::Duration,
+};
+
+/// The default time allowed for a direct send to an online contact before falling back to store and forward
+pub const DEFAULT_DIRECT_SEND_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Determines how chat messages and confirmations are delivered to a contact
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDeliveryStrategy {
+    /// Send directly to contacts that are online and fall back to store and forward if the direct send has not
+    /// completed within `direct_send_timeout`. Contacts that are not online are sent to via store and forward.
+    DirectWithSafFallback { direct_send_timeout: Duration },
+    /// Always send via store and forward
+    StoreAndForwardOnly,
+}
+
+impl MessageDeliveryStrategy {
+    /// A zero timeout disables direct sends altogether
+    pub fn from_direct_send_timeout(direct_send_timeout: Duration) -> Self {
+        if direct_send_timeout.is_zero() {
+            Self::StoreAndForwardOnly
+        } else {
+            Self::DirectWithSafFallback { direct_send_timeout }
+        }
+    }
+}
+
+impl Default for MessageDeliveryStrategy {
+    fn default() -> Self {
+        Self::DirectWithSafFallback {
+            direct_send_timeout: DEFAULT_DIRECT_SEND_TIMEOUT,
+        }
+    }
+}
+
+/// The route that was used to deliver a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryRoute {
+    Direct,
+    StoreAndForward,
+}
+
+impl Display for DeliveryRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            DeliveryRoute::Direct => write!(f, "Direct"),
+            DeliveryRoute::StoreAndForward => write!(f, "StoreAndForward"),
+        }
+    }
+}
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 mod contact;
-pub use contact::Contact;
+pub use contact::{Contact, ContactLivenessPolicy};
 
 mod message;
 pub use message::{Direction, Message, MessageMetadata, MessageMetadataType};
@@ -34,3 +34,9 @@ pub use message_dispatch::MessageDispatch;
 
 mod confirmation;
 pub use confirmation::Confirmation;
+
+mod delivery;
+pub use delivery::{DeliveryRoute, MessageDeliveryStrategy, DEFAULT_DIRECT_SEND_TIMEOUT};
+
+mod privacy;
+pub use privacy::LivenessPrivacyMode;
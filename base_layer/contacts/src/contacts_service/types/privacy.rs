@@ -0,0 +1,56 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+/// Determines how much of this node's online presence is shared with other nodes through the liveness service
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LivenessPrivacyMode {
+    /// Reply to pings and attach contacts liveness metadata so that contacts can see when this node was last online
+    #[default]
+    Disabled,
+    /// Do not reply to pings or attach contacts liveness metadata, but keep pinging contacts to learn their online
+    /// status. A pinged contact can still see that a ping was received, but it is not recorded as this node's
+    /// last-seen time.
+    HideOnlineStatus,
+    /// Do not reply to pings, attach contacts liveness metadata or ping contacts. The online status of contacts is not
+    /// tracked in this mode.
+    Silent,
+}
+
+impl LivenessPrivacyMode {
+    pub fn from_settings(hide_online_status: bool, allow_outbound_pings: bool) -> Self {
+        match (hide_online_status, allow_outbound_pings) {
+            (false, _) => Self::Disabled,
+            (true, true) => Self::HideOnlineStatus,
+            (true, false) => Self::Silent,
+        }
+    }
+
+    /// Whether pings received from peers are answered and this node's last-seen time is shared
+    pub fn shares_online_status(self) -> bool {
+        self == Self::Disabled
+    }
+
+    /// Whether contacts are pinged to learn their online status
+    pub fn allows_outbound_pings(self) -> bool {
+        self != Self::Silent
+    }
+}
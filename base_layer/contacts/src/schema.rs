@@ -8,6 +8,7 @@ diesel::table! {
         last_seen -> Nullable<Timestamp>,
         latency -> Nullable<Integer>,
         favourite -> Integer,
+        liveness_policy -> Integer,
     }
 }
 
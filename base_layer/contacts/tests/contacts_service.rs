@@ -22,6 +22,7 @@
 
 use std::{convert::TryInto, sync::Arc, time::Duration};
 
+use chrono::Utc;
 use rand::rngs::OsRng;
 use tari_common::configuration::{MultiaddrList, Network, StringList};
 use tari_common_sqlite::connection::{DbConnection, DbConnectionUrl};
@@ -35,7 +36,7 @@ use tari_contacts::contacts_service::{
         database::{ContactsBackend, ContactsDatabase, DbKey},
         sqlite_db::ContactsServiceSqliteDatabase,
     },
-    types::{Contact, MessageBuilder},
+    types::{Contact, ContactLivenessPolicy, DeliveryRoute, MessageBuilder, MessageDeliveryStrategy},
     ContactsServiceInitializer,
 };
 use tari_crypto::keys::PublicKey as PublicKeyTrait;
@@ -58,6 +59,14 @@ use tokio::{runtime::Runtime, sync::broadcast::error::TryRecvError};
 pub fn setup_contacts_service<T: ContactsBackend + 'static>(
     runtime: &mut Runtime,
     backend: T,
+) -> (ContactsServiceHandle, Arc<NodeIdentity>, Shutdown) {
+    setup_contacts_service_with_delivery_strategy(runtime, backend, MessageDeliveryStrategy::default())
+}
+
+pub fn setup_contacts_service_with_delivery_strategy<T: ContactsBackend + 'static>(
+    runtime: &mut Runtime,
+    backend: T,
+    delivery_strategy: MessageDeliveryStrategy,
 ) -> (ContactsServiceHandle, Arc<NodeIdentity>, Shutdown) {
     let _enter = runtime.enter();
     let (publisher, subscription_factory) = pubsub_connector(100);
@@ -92,6 +101,8 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
         },
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: StringList::new(),
+        allowed_peers: StringList::new(),
+        denied_peers: StringList::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-contacts-service".to_string(),
         rpc_max_simultaneous_sessions: 0,
@@ -117,12 +128,10 @@ pub fn setup_contacts_service<T: ContactsBackend + 'static>(
             },
             peer_message_subscription_factory.clone(),
         ))
-        .add_initializer(ContactsServiceInitializer::new(
-            backend,
-            peer_message_subscription_factory,
-            Duration::from_secs(5),
-            2,
-        ))
+        .add_initializer(
+            ContactsServiceInitializer::new(backend, peer_message_subscription_factory, Duration::from_secs(5), 2)
+                .with_delivery_strategy(delivery_strategy),
+        )
         .build();
 
     let handles = runtime.block_on(fut).expect("Service initialization failed");
@@ -305,3 +314,158 @@ pub fn test_message_pagination() {
         assert_eq!(0, messages.len());
     });
 }
+
+#[test]
+pub fn test_contact_liveness_policy() {
+    with_temp_dir(|dir_path| {
+        let mut runtime = Runtime::new().unwrap();
+
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_path = format!("{}/{}", dir_path.to_str().unwrap(), db_name);
+        let url: DbConnectionUrl = db_path.try_into().unwrap();
+
+        let db = DbConnection::connect_url(&url).unwrap();
+        let backend = ContactsServiceSqliteDatabase::init(db);
+
+        let (mut contacts_service, _node_identity, _shutdown) = setup_contacts_service(&mut runtime, backend);
+
+        let mut contacts = Vec::new();
+        for _ in 0..3 {
+            let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+            let address = TariAddress::new(public_key, Network::default());
+            let contact = Contact::new(random::string(8), address, None, None, false);
+            runtime
+                .block_on(contacts_service.upsert_contact(contact.clone()))
+                .unwrap();
+            contacts.push(contact);
+        }
+        let policies = |contacts_service: &mut ContactsServiceHandle, runtime: &mut Runtime| {
+            runtime
+                .block_on(contacts_service.get_contacts())
+                .unwrap()
+                .into_iter()
+                .map(|c| (c.address, c.liveness_policy))
+                .collect::<Vec<_>>()
+        };
+        assert!(policies(&mut contacts_service, &mut runtime)
+            .iter()
+            .all(|(_, p)| *p == ContactLivenessPolicy::Always));
+
+        runtime
+            .block_on(
+                contacts_service
+                    .set_contact_liveness_policy(contacts[0].address.clone(), ContactLivenessPolicy::OnDemand),
+            )
+            .unwrap();
+        let contact = runtime
+            .block_on(contacts_service.get_contact(contacts[0].address.clone()))
+            .unwrap();
+        assert_eq!(contact.liveness_policy, ContactLivenessPolicy::OnDemand);
+
+        // Unknown addresses are ignored
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let unknown = TariAddress::new(public_key, Network::default());
+        runtime
+            .block_on(contacts_service.set_contacts_liveness_policy(
+                vec![contacts[1].address.clone(), contacts[2].address.clone(), unknown],
+                ContactLivenessPolicy::Never,
+            ))
+            .unwrap();
+        let expected = vec![
+            (contacts[0].address.clone(), ContactLivenessPolicy::OnDemand),
+            (contacts[1].address.clone(), ContactLivenessPolicy::Never),
+            (contacts[2].address.clone(), ContactLivenessPolicy::Never),
+        ];
+        let mut got = policies(&mut contacts_service, &mut runtime);
+        got.sort_by_key(|(a, _)| contacts.iter().position(|c| c.address == *a));
+        assert_eq!(got, expected);
+
+        // Updating a contact keeps its stored policy
+        let mut updated_contact = contacts[1].clone();
+        updated_contact.alias = "Fred".to_string();
+        runtime
+            .block_on(contacts_service.upsert_contact(updated_contact.clone()))
+            .unwrap();
+        let contact = runtime
+            .block_on(contacts_service.get_contact(updated_contact.address))
+            .unwrap();
+        assert_eq!(contact.alias, "Fred");
+        assert_eq!(contact.liveness_policy, ContactLivenessPolicy::Never);
+
+        runtime
+            .block_on(contacts_service.set_all_contacts_liveness_policy(ContactLivenessPolicy::Always))
+            .unwrap();
+        assert!(policies(&mut contacts_service, &mut runtime)
+            .iter()
+            .all(|(_, p)| *p == ContactLivenessPolicy::Always));
+    });
+}
+
+#[test]
+pub fn test_message_delivery_falls_back_to_store_and_forward() {
+    with_temp_dir(|dir_path| {
+        let mut runtime = Runtime::new().unwrap();
+
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_path = format!("{}/{}", dir_path.to_str().unwrap(), db_name);
+        let url: DbConnectionUrl = db_path.try_into().unwrap();
+
+        let db = DbConnection::connect_url(&url).unwrap();
+        let backend = ContactsServiceSqliteDatabase::init(db);
+
+        let direct_send_timeout = Duration::from_secs(1);
+        let (mut contacts_service, _node_identity, _shutdown) = setup_contacts_service_with_delivery_strategy(
+            &mut runtime,
+            backend,
+            MessageDeliveryStrategy::from_direct_send_timeout(direct_send_timeout),
+        );
+        assert_eq!(
+            MessageDeliveryStrategy::from_direct_send_timeout(Duration::ZERO),
+            MessageDeliveryStrategy::StoreAndForwardOnly
+        );
+
+        // A contact that has never been seen is sent to via store and forward straight away
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let offline_address = TariAddress::new(public_key, Network::default());
+        let contact = Contact::new(random::string(8), offline_address.clone(), None, None, false);
+        runtime.block_on(contacts_service.upsert_contact(contact)).unwrap();
+
+        let message = MessageBuilder::new()
+            .message("Are you there?".to_string())
+            .address(offline_address.clone())
+            .build();
+        let route = runtime.block_on(contacts_service.send_message(message)).unwrap();
+        assert_eq!(route, DeliveryRoute::StoreAndForward);
+
+        // A contact that was seen recently is tried directly first, which cannot succeed as it is not reachable
+        let (_secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let online_address = TariAddress::new(public_key, Network::default());
+        let contact = Contact::new(
+            random::string(8),
+            online_address.clone(),
+            Some(Utc::now().naive_utc()),
+            None,
+            false,
+        );
+        runtime.block_on(contacts_service.upsert_contact(contact)).unwrap();
+
+        let message = MessageBuilder::new()
+            .message("Hello".to_string())
+            .address(online_address.clone())
+            .build();
+        let route = runtime
+            .block_on(tokio::time::timeout(
+                direct_send_timeout * 10,
+                contacts_service.send_message(message),
+            ))
+            .expect("the direct send should have timed out")
+            .unwrap();
+        assert_eq!(route, DeliveryRoute::StoreAndForward);
+
+        // Sent messages are kept whichever route was used
+        for address in [offline_address, online_address] {
+            let messages = runtime.block_on(contacts_service.get_messages(address, 10, 0)).unwrap();
+            assert_eq!(messages.len(), 1);
+        }
+    });
+}
@@ -177,6 +177,8 @@ impl ChainMetadataService {
                     }
                 }
             },
+            // Clock skew is reported by the liveness service and does not affect chain metadata
+            LivenessEvent::ClockSkewDetected(_) | LivenessEvent::ClockSkewResolved(_) => {},
         }
 
         Ok(())
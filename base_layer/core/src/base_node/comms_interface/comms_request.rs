@@ -60,6 +60,7 @@ pub enum NodeCommsRequest {
     GetShardKey { height: u64, public_key: PublicKey },
     FetchTemplateRegistrations { start_height: u64, end_height: u64 },
     FetchUnspentUtxosInBlock { block_hash: BlockHash },
+    GetPowAlgorithmStatus,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -113,6 +114,7 @@ impl Display for NodeCommsRequest {
             FetchUnspentUtxosInBlock { block_hash } => {
                 write!(f, "FetchUnspentUtxosInBlock ({})", block_hash)
             },
+            GetPowAlgorithmStatus => write!(f, "GetPowAlgorithmStatus"),
         }
     }
 }
@@ -29,11 +29,12 @@ use tari_common_types::{
     chain_metadata::ChainMetadata,
     types::{HashOutput, PrivateKey, PublicKey},
 };
+use tari_utilities::epoch_time::EpochTime;
 
 use crate::{
-    blocks::{Block, ChainHeader, HistoricalBlock, NewBlockTemplate},
+    blocks::{Block, ChainHeader, ChainStats, HistoricalBlock, NewBlockTemplate},
     chain_storage::TemplateRegistrationEntry,
-    proof_of_work::Difficulty,
+    proof_of_work::{Difficulty, PowAlgorithm},
     transactions::transaction_components::{Transaction, TransactionKernel, TransactionOutput},
 };
 
@@ -61,6 +62,7 @@ pub enum NodeCommsResponse {
     FetchValidatorNodesKeysResponse(Vec<(PublicKey, [u8; 32])>),
     GetShardKeyResponse(Option<[u8; 32]>),
     FetchTemplateRegistrationsResponse(Vec<TemplateRegistrationEntry>),
+    PowAlgorithmStatus(Vec<PowAlgorithmStatus>),
 }
 
 impl Display for NodeCommsResponse {
@@ -98,6 +100,7 @@ impl Display for NodeCommsResponse {
             FetchValidatorNodesKeysResponse(_) => write!(f, "FetchValidatorNodesKeysResponse"),
             GetShardKeyResponse(_) => write!(f, "GetShardKeyResponse"),
             FetchTemplateRegistrationsResponse(_) => write!(f, "FetchTemplateRegistrationsResponse"),
+            PowAlgorithmStatus(_) => write!(f, "PowAlgorithmStatus"),
         }
     }
 }
@@ -108,3 +111,104 @@ pub struct FetchMempoolTransactionsResponse {
     pub transactions: Vec<Arc<Transaction>>,
     pub not_found: Vec<PrivateKey>,
 }
+
+/// The mining conditions for a single proof of work algorithm at the current chain tip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowAlgorithmStatus {
+    pub pow_algo: PowAlgorithm,
+    /// The difficulty a block mined with this algorithm on top of the current tip must meet
+    pub target_difficulty: Difficulty,
+    /// The estimated network hash rate (hashes/s) of this algorithm over the recent blocks mined with it
+    pub estimated_hash_rate: u64,
+    /// The target time between blocks mined with this algorithm, in seconds
+    pub target_block_time: u64,
+    /// The average time between the recent blocks mined with this algorithm, in seconds, if at least two were found
+    pub average_block_time: Option<u64>,
+    /// The height of the most recent block mined with this algorithm, if one was found in the recent blocks
+    pub last_block_height: Option<u64>,
+    /// The timestamp of the most recent block mined with this algorithm, if one was found in the recent blocks
+    pub last_block_timestamp: Option<EpochTime>,
+    /// The number of recent blocks that were mined with this algorithm
+    pub num_recent_blocks: u64,
+}
+
+impl PowAlgorithmStatus {
+    /// Builds the status from the statistics of the recent `headers`, so that the block timing and hash rate agree with
+    /// the chain stats reported for the same window
+    pub fn from_chain_stats(
+        pow_algo: PowAlgorithm,
+        target_difficulty: Difficulty,
+        target_block_time: u64,
+        stats: &ChainStats,
+        headers: &[ChainHeader],
+    ) -> Self {
+        let algo_stats = stats.for_algo(pow_algo);
+        let last_block = headers
+            .iter()
+            .filter(|h| h.header().pow.pow_algo == pow_algo)
+            .max_by_key(|h| h.height());
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_sign_loss)]
+        let average_block_time = if algo_stats.num_blocks > 1 {
+            Some(algo_stats.average_block_interval as u64)
+        } else {
+            None
+        };
+
+        Self {
+            pow_algo,
+            target_difficulty,
+            estimated_hash_rate: algo_stats.estimated_hash_rate,
+            target_block_time,
+            average_block_time,
+            last_block_height: last_block.map(|h| h.height()),
+            last_block_timestamp: last_block.map(|h| h.header().timestamp),
+            num_recent_blocks: algo_stats.num_blocks,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blocks::genesis_block::get_esmeralda_genesis_block;
+
+    fn chain_header(height: u64, timestamp: u64, pow_algo: PowAlgorithm, target_difficulty: u64) -> ChainHeader {
+        let genesis = get_esmeralda_genesis_block().to_chain_header();
+        let mut header = genesis.header().clone();
+        header.height = height;
+        header.timestamp = EpochTime::from(timestamp);
+        header.pow.pow_algo = pow_algo;
+        let mut accum = genesis.accumulated_data().clone();
+        accum.hash = header.hash();
+        accum.target_difficulty = Difficulty::from_u64(target_difficulty).unwrap();
+        ChainHeader::try_construct(header, accum).unwrap()
+    }
+
+    #[test]
+    fn it_agrees_with_chain_stats() {
+        let headers = vec![
+            chain_header(10, 1000, PowAlgorithm::Sha3x, 12_000),
+            chain_header(11, 1100, PowAlgorithm::RandomX, 3_000),
+            chain_header(12, 1250, PowAlgorithm::Sha3x, 18_000),
+            chain_header(13, 1300, PowAlgorithm::RandomX, 5_000),
+            chain_header(14, 1500, PowAlgorithm::Sha3x, 15_000),
+        ];
+        let stats = ChainStats::from_headers(&headers);
+
+        for pow_algo in [PowAlgorithm::RandomX, PowAlgorithm::Sha3x] {
+            let status = PowAlgorithmStatus::from_chain_stats(pow_algo, Difficulty::min(), 120, &stats, &headers);
+            let algo_stats = stats.for_algo(pow_algo);
+            assert_eq!(status.estimated_hash_rate, algo_stats.estimated_hash_rate);
+            assert_eq!(status.num_recent_blocks, algo_stats.num_blocks);
+            assert!(status.estimated_hash_rate > 0);
+        }
+
+        let sha3x = PowAlgorithmStatus::from_chain_stats(PowAlgorithm::Sha3x, Difficulty::min(), 120, &stats, &headers);
+        // Average difficulty of 15,000 over an average interval of 250s
+        assert_eq!(sha3x.estimated_hash_rate, 60);
+        assert_eq!(sha3x.average_block_time, Some(250));
+        assert_eq!(sha3x.last_block_height, Some(14));
+        assert_eq!(sha3x.last_block_timestamp, Some(EpochTime::from(1500)));
+    }
+}
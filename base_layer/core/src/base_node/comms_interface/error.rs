@@ -77,6 +77,8 @@ pub enum CommsInterfaceError {
     DifficultyError(#[from] DifficultyError),
     #[error("Transaction error: {0}")]
     TransactionError(#[from] TransactionError),
+    #[error("Block templates are not being served: {0}")]
+    BlockTemplatesHalted(String),
 }
 
 impl CommsInterfaceError {
@@ -107,7 +109,8 @@ impl CommsInterfaceError {
             CommsInterfaceError::InternalError(_) |
             CommsInterfaceError::ApiError(_) |
             CommsInterfaceError::BlockError(_) |
-            CommsInterfaceError::DifficultyError(_) => None,
+            CommsInterfaceError::DifficultyError(_) |
+            CommsInterfaceError::BlockTemplatesHalted(_) => None,
         }
     }
 }
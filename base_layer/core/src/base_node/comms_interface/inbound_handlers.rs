@@ -34,15 +34,28 @@ use tokio::sync::RwLock;
 #[cfg(feature = "metrics")]
 use crate::base_node::metrics;
 use crate::{
-    base_node::comms_interface::{
-        error::CommsInterfaceError,
-        local_interface::BlockEventSender,
-        FetchMempoolTransactionsResponse,
-        NodeCommsRequest,
-        NodeCommsResponse,
-        OutboundNodeCommsInterface,
+    base_node::{
+        comms_interface::{
+            error::CommsInterfaceError,
+            local_interface::BlockEventSender,
+            FetchMempoolTransactionsResponse,
+            NodeCommsRequest,
+            NodeCommsResponse,
+            OutboundNodeCommsInterface,
+            PowAlgorithmStatus,
+        },
+        ReorgAlarm,
+    },
+    blocks::{
+        Block,
+        BlockBuilder,
+        BlockHeader,
+        BlockHeaderValidationError,
+        ChainBlock,
+        ChainStats,
+        NewBlock,
+        NewBlockTemplate,
     },
-    blocks::{Block, BlockBuilder, BlockHeader, BlockHeaderValidationError, ChainBlock, NewBlock, NewBlockTemplate},
     chain_storage::{async_db::AsyncBlockchainDb, BlockAddResult, BlockchainBackend, ChainStorageError},
     consensus::{ConsensusConstants, ConsensusManager},
     mempool::Mempool,
@@ -89,6 +102,7 @@ pub struct InboundNodeCommsHandlers<B> {
     outbound_nci: OutboundNodeCommsInterface,
     connectivity: ConnectivityRequester,
     randomx_factory: RandomXFactory,
+    reorg_alarm: ReorgAlarm,
 }
 
 impl<B> InboundNodeCommsHandlers<B>
@@ -103,6 +117,7 @@ where B: BlockchainBackend + 'static
         outbound_nci: OutboundNodeCommsInterface,
         connectivity: ConnectivityRequester,
         randomx_factory: RandomXFactory,
+        reorg_alarm: ReorgAlarm,
     ) -> Self {
         Self {
             block_event_sender,
@@ -113,6 +128,7 @@ where B: BlockchainBackend + 'static
             outbound_nci,
             connectivity,
             randomx_factory,
+            reorg_alarm,
         }
     }
 
@@ -256,6 +272,17 @@ where B: BlockchainBackend + 'static
                 Ok(NodeCommsResponse::HistoricalBlock(Box::new(block)))
             },
             NodeCommsRequest::GetNewBlockTemplate(request) => {
+                if self.reorg_alarm.is_block_template_serving_halted() {
+                    let depth = self.reorg_alarm.status().map(|s| s.depth).unwrap_or_default();
+                    warn!(
+                        target: LOG_TARGET,
+                        "Refusing to serve a block template while the reorg alarm is raised"
+                    );
+                    return Err(CommsInterfaceError::BlockTemplatesHalted(format!(
+                        "reorg alarm raised by a reorg of {} blocks",
+                        depth
+                    )));
+                }
                 let best_block_header = self.blockchain_db.fetch_tip_header().await?;
                 let mut header = BlockHeader::from_previous(best_block_header.header());
                 let constants = self.consensus_manager.consensus_constants(header.height);
@@ -416,6 +443,9 @@ where B: BlockchainBackend + 'static
                 let utxos = self.blockchain_db.fetch_outputs_in_block(block_hash).await?;
                 Ok(NodeCommsResponse::TransactionOutputs(utxos))
             },
+            NodeCommsRequest::GetPowAlgorithmStatus => Ok(NodeCommsResponse::PowAlgorithmStatus(
+                self.get_pow_algorithm_status().await?,
+            )),
         }
     }
 
@@ -969,6 +999,35 @@ where B: BlockchainBackend + 'static
         debug!(target: LOG_TARGET, "Target difficulty {} for PoW {}", target, pow_algo);
         Ok(target)
     }
+
+    /// Summarises each proof of work algorithm over the last difficulty window of blocks
+    async fn get_pow_algorithm_status(&self) -> Result<Vec<PowAlgorithmStatus>, CommsInterfaceError> {
+        let tip_header = self.blockchain_db.fetch_tip_header().await?;
+        let tip_height = tip_header.height();
+        let constants = self.consensus_manager.consensus_constants(tip_height.saturating_add(1));
+        let start_height = tip_height.saturating_sub(constants.difficulty_block_window().saturating_sub(1));
+        let headers = self
+            .blockchain_db
+            .fetch_chain_headers(start_height..=tip_height)
+            .await?;
+
+        let stats = ChainStats::from_headers(&headers);
+
+        let mut statuses = Vec::with_capacity(2);
+        for pow_algo in [PowAlgorithm::RandomX, PowAlgorithm::Sha3x] {
+            let target_difficulty = self
+                .get_target_difficulty_for_next_block(pow_algo, constants, *tip_header.hash())
+                .await?;
+            statuses.push(PowAlgorithmStatus::from_chain_stats(
+                pow_algo,
+                target_difficulty,
+                constants.pow_target_block_interval(pow_algo),
+                &stats,
+                &headers,
+            ));
+        }
+        Ok(statuses)
+    }
 }
 
 impl<B> Clone for InboundNodeCommsHandlers<B> {
@@ -982,6 +1041,7 @@ impl<B> Clone for InboundNodeCommsHandlers<B> {
             outbound_nci: self.outbound_nci.clone(),
             connectivity: self.connectivity.clone(),
             randomx_factory: self.randomx_factory.clone(),
+            reorg_alarm: self.reorg_alarm.clone(),
         }
     }
 }
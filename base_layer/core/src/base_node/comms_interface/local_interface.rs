@@ -36,6 +36,7 @@ use crate::{
         BlockEvent,
         NodeCommsRequest,
         NodeCommsResponse,
+        PowAlgorithmStatus,
     },
     blocks::{Block, ChainHeader, HistoricalBlock, NewBlockTemplate},
     chain_storage::TemplateRegistrationEntry,
@@ -342,4 +343,16 @@ impl LocalNodeCommsInterface {
             _ => Err(CommsInterfaceError::UnexpectedApiResponse),
         }
     }
+
+    /// Returns the difficulty, estimated hash rate and recent block times of each proof of work algorithm
+    pub async fn get_pow_algorithm_status(&mut self) -> Result<Vec<PowAlgorithmStatus>, CommsInterfaceError> {
+        match self
+            .request_sender
+            .call(NodeCommsRequest::GetPowAlgorithmStatus)
+            .await??
+        {
+            NodeCommsResponse::PowAlgorithmStatus(status) => Ok(status),
+            _ => Err(CommsInterfaceError::UnexpectedApiResponse),
+        }
+    }
 }
@@ -42,9 +42,17 @@ pub use comms_interface::LocalNodeCommsInterface;
 #[cfg(feature = "metrics")]
 mod metrics;
 
+#[cfg(feature = "base_node")]
+pub mod reorg_alarm;
+#[cfg(feature = "base_node")]
+pub use reorg_alarm::{ReorgAlarm, ReorgAlarmStatus};
+
 #[cfg(feature = "base_node")]
 pub mod service;
 
+#[cfg(feature = "base_node")]
+pub mod snapshot;
+
 #[cfg(feature = "base_node")]
 pub mod state_machine_service;
 #[cfg(feature = "base_node")]
@@ -29,3 +29,5 @@ mod request;
 mod response;
 #[cfg(feature = "base_node")]
 mod rpc;
+#[cfg(feature = "base_node")]
+mod snapshot;
@@ -0,0 +1,51 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::{TryFrom, TryInto};
+
+use tari_common_types::types::FixedHash;
+
+use crate::{base_node::snapshot::SnapshotManifest, proto::base_node as proto};
+
+impl TryFrom<proto::SnapshotManifest> for SnapshotManifest {
+    type Error = String;
+
+    fn try_from(manifest: proto::SnapshotManifest) -> Result<Self, Self::Error> {
+        let block_hash: FixedHash = manifest
+            .block_hash
+            .try_into()
+            .map_err(|e| format!("Malformed snapshot block hash: {}", e))?;
+        Ok(Self {
+            height: manifest.height,
+            block_hash,
+        })
+    }
+}
+
+impl From<SnapshotManifest> for proto::SnapshotManifest {
+    fn from(manifest: SnapshotManifest) -> Self {
+        Self {
+            height: manifest.height,
+            block_hash: manifest.block_hash.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,289 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Chain reorg depth alarm.
+//!
+//! A reorg deeper than a configured threshold may indicate an attack or a network split. When one occurs the alarm is
+//! raised, which is visible to subscribers (e.g. the gRPC server) and can optionally halt block template serving so
+//! that miners do not build on a contested chain. The alarm clears once the new chain has been extended by at least as
+//! many blocks as were reorged out.
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use log::*;
+use tari_common_types::types::HashOutput;
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::{epoch_time::EpochTime, hex::Hex};
+use tokio::sync::{broadcast::error::RecvError, watch};
+
+use crate::{
+    base_node::comms_interface::{BlockEvent, BlockEventReceiver},
+    chain_storage::BlockAddResult,
+};
+
+const LOG_TARGET: &str = "c::bn::reorg_alarm";
+
+/// The details of a reorg that raised the alarm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReorgAlarmStatus {
+    /// The number of blocks that were removed from the main chain
+    pub depth: u64,
+    /// The tip height immediately after the reorg. Confirmations needed to clear the alarm are counted from here.
+    pub tip_height: u64,
+    /// The tip hash immediately after the reorg, if known
+    pub tip_hash: Option<HashOutput>,
+    /// When the alarm was raised
+    pub raised_at: EpochTime,
+}
+
+#[derive(Clone)]
+pub struct ReorgAlarm {
+    threshold: u64,
+    halt_block_templates: bool,
+    status: Arc<watch::Sender<Option<ReorgAlarmStatus>>>,
+    /// Set when a block sync rewind raised the alarm and the tip of the new chain is not yet known
+    awaiting_sync_tip: Arc<AtomicBool>,
+}
+
+impl ReorgAlarm {
+    /// Create a new alarm that is raised by reorgs of at least `threshold` blocks. A threshold of zero disables the
+    /// alarm.
+    pub fn new(threshold: u64, halt_block_templates: bool) -> Self {
+        let (status, _) = watch::channel(None);
+        Self {
+            threshold,
+            halt_block_templates,
+            status: Arc::new(status),
+            awaiting_sync_tip: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self::new(0, false)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.threshold > 0
+    }
+
+    /// Returns the details of the reorg that raised the alarm, or None if the alarm is not raised
+    pub fn status(&self) -> Option<ReorgAlarmStatus> {
+        self.status.borrow().clone()
+    }
+
+    pub fn is_raised(&self) -> bool {
+        self.status.borrow().is_some()
+    }
+
+    /// Subscribe to changes in the alarm status
+    pub fn subscribe(&self) -> watch::Receiver<Option<ReorgAlarmStatus>> {
+        self.status.subscribe()
+    }
+
+    /// Returns true if new block templates should not be served
+    pub fn is_block_template_serving_halted(&self) -> bool {
+        self.halt_block_templates && self.is_raised()
+    }
+
+    /// Manually clear the alarm
+    pub fn clear(&self) {
+        self.awaiting_sync_tip.store(false, Ordering::SeqCst);
+        if self.status.send_replace(None).is_some() {
+            info!(target: LOG_TARGET, "Reorg alarm cleared");
+        }
+    }
+
+    /// Update the alarm state from the base node block event stream until shutdown
+    pub async fn run(self, mut block_events: BlockEventReceiver, mut shutdown: ShutdownSignal) {
+        if !self.is_enabled() {
+            debug!(target: LOG_TARGET, "Reorg alarm is disabled");
+            return;
+        }
+        loop {
+            tokio::select! {
+                event = block_events.recv() => match event {
+                    Ok(event) => self.handle_block_event(&event),
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Reorg alarm lagged {} block events", n);
+                    },
+                    Err(RecvError::Closed) => break,
+                },
+                _ = shutdown.wait() => break,
+            }
+        }
+    }
+
+    fn handle_block_event(&self, event: &BlockEvent) {
+        match event {
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::Ok(block)) => self.on_tip_changed(block.height()),
+            BlockEvent::ValidBlockAdded(_, BlockAddResult::ChainReorg { added, removed }) => {
+                if let Some(tip) = added.iter().max_by_key(|b| b.height()) {
+                    self.on_reorg(removed.len() as u64, tip.height(), Some(*tip.hash()));
+                }
+            },
+            BlockEvent::BlockSyncRewind(removed) => {
+                if let Some(fork_height) = removed.iter().map(|b| b.height()).min() {
+                    if self.on_reorg(removed.len() as u64, fork_height.saturating_sub(1), None) {
+                        self.awaiting_sync_tip.store(true, Ordering::SeqCst);
+                    }
+                }
+            },
+            BlockEvent::BlockSyncComplete(tip, _) => self.on_sync_complete(tip.height(), *tip.hash()),
+            _ => {},
+        }
+    }
+
+    /// Raises the alarm if the reorg is deep enough, returning true if it was raised
+    fn on_reorg(&self, depth: u64, tip_height: u64, tip_hash: Option<HashOutput>) -> bool {
+        if !self.is_enabled() || depth < self.threshold {
+            return false;
+        }
+        error!(
+            target: LOG_TARGET,
+            "Reorg alarm raised: chain reorg of {} block(s) to height {} ({}) exceeds the alarm threshold of {}. This \
+             may indicate an attack or a network split.{}",
+            depth,
+            tip_height,
+            tip_hash.map(|h| h.to_hex()).unwrap_or_else(|| "unknown hash".to_string()),
+            self.threshold,
+            if self.halt_block_templates {
+                " Block template serving is halted."
+            } else {
+                ""
+            }
+        );
+        // A deeper reorg while the alarm is raised replaces the existing status
+        let raised = self.status.send_if_modified(|status| match status {
+            Some(s) if s.depth > depth => false,
+            _ => {
+                *status = Some(ReorgAlarmStatus {
+                    depth,
+                    tip_height,
+                    tip_hash,
+                    raised_at: EpochTime::now(),
+                });
+                true
+            },
+        });
+        if raised {
+            self.awaiting_sync_tip.store(false, Ordering::SeqCst);
+        }
+        raised
+    }
+
+    /// A rewind during block sync only tells us the fork height. The new chain is synced in one go, so the alarm is
+    /// anchored to the synced tip rather than cleared by it.
+    fn on_sync_complete(&self, tip_height: u64, tip_hash: HashOutput) {
+        if !self.awaiting_sync_tip.swap(false, Ordering::SeqCst) {
+            self.on_tip_changed(tip_height);
+            return;
+        }
+        self.status.send_if_modified(|status| match status {
+            Some(s) => {
+                s.tip_height = tip_height;
+                s.tip_hash = Some(tip_hash);
+                true
+            },
+            None => false,
+        });
+    }
+
+    fn on_tip_changed(&self, tip_height: u64) {
+        let should_clear = self
+            .status
+            .borrow()
+            .as_ref()
+            .map_or(false, |s| tip_height >= s.tip_height.saturating_add(s.depth));
+        if should_clear {
+            self.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::blocks::{genesis_block::get_esmeralda_genesis_block, ChainBlock};
+
+    #[test]
+    fn it_raises_and_clears() {
+        let alarm = ReorgAlarm::new(3, true);
+        alarm.on_reorg(2, 100, None);
+        assert!(!alarm.is_raised());
+
+        alarm.on_reorg(3, 100, None);
+        assert_eq!(alarm.status().unwrap().depth, 3);
+        assert!(alarm.is_block_template_serving_halted());
+
+        // Reorgs below the threshold are ignored, an equally deep reorg replaces the status
+        alarm.on_reorg(1, 101, None);
+        alarm.on_reorg(3, 101, None);
+        assert_eq!(alarm.status().unwrap().tip_height, 101);
+
+        alarm.on_tip_changed(103);
+        assert!(alarm.is_raised());
+        alarm.on_tip_changed(104);
+        assert!(!alarm.is_raised());
+        assert!(!alarm.is_block_template_serving_halted());
+    }
+
+    fn chain_block_at(height: u64) -> Arc<ChainBlock> {
+        let genesis = get_esmeralda_genesis_block();
+        let mut block = genesis.block().clone();
+        block.header.height = height;
+        let mut accum = genesis.accumulated_data().clone();
+        accum.hash = block.hash();
+        Arc::new(ChainBlock::try_construct(Arc::new(block), accum).unwrap())
+    }
+
+    #[test]
+    fn it_counts_confirmations_from_the_synced_tip_after_a_sync_rewind() {
+        let alarm = ReorgAlarm::new(3, false);
+        let removed = (101..=105).map(chain_block_at).collect();
+        alarm.handle_block_event(&BlockEvent::BlockSyncRewind(removed));
+        let status = alarm.status().unwrap();
+        assert_eq!(status.depth, 5);
+        assert_eq!(status.tip_height, 100);
+
+        // The synced tip is well past the fork height plus the depth, but no confirmations have been received yet
+        let synced_tip = chain_block_at(110);
+        alarm.handle_block_event(&BlockEvent::BlockSyncComplete(synced_tip.clone(), 100));
+        let status = alarm.status().unwrap();
+        assert_eq!(status.tip_height, 110);
+        assert_eq!(status.tip_hash, Some(*synced_tip.hash()));
+
+        alarm.handle_block_event(&BlockEvent::BlockSyncComplete(chain_block_at(114), 110));
+        assert!(alarm.is_raised());
+        alarm.handle_block_event(&BlockEvent::BlockSyncComplete(chain_block_at(115), 114));
+        assert!(!alarm.is_raised());
+    }
+
+    #[test]
+    fn it_does_nothing_when_disabled() {
+        let alarm = ReorgAlarm::disabled();
+        alarm.on_reorg(1000, 100, None);
+        assert!(!alarm.is_raised());
+    }
+}
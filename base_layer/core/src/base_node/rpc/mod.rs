@@ -48,6 +48,8 @@ use crate::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures,
+            SpendingInputsRequest,
+            SpendingInputsResponse,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -61,6 +63,9 @@ use crate::{
     },
 };
 
+/// The maximum number of excess signatures that may be queried in a single `transaction_batch_query` request
+pub const MAX_TX_QUERY_BATCH_SIZE: usize = 1000;
+
 #[tari_rpc(protocol_name = b"t/bnwallet/1", server_struct = BaseNodeWalletRpcServer, client_struct = BaseNodeWalletRpcClient)]
 pub trait BaseNodeWalletService: Send + Sync + 'static {
     #[rpc(method = 1)]
@@ -119,6 +124,13 @@ pub trait BaseNodeWalletService: Send + Sync + 'static {
         &self,
         request: Request<GetMempoolFeePerGramStatsRequest>,
     ) -> Result<Response<GetMempoolFeePerGramStatsResponse>, RpcStatus>;
+
+    /// Returns the inputs that spent the given outputs, e.g. to read the data a counterparty revealed when spending
+    #[rpc(method = 13)]
+    async fn get_spending_inputs(
+        &self,
+        request: Request<SpendingInputsRequest>,
+    ) -> Result<Response<SpendingInputsResponse>, RpcStatus>;
 }
 
 #[cfg(feature = "base_node")]
@@ -31,7 +31,7 @@ use tokio::sync::mpsc;
 
 use crate::{
     base_node::{
-        rpc::{sync_utxos_by_block_task::SyncUtxosByBlockTask, BaseNodeWalletService},
+        rpc::{sync_utxos_by_block_task::SyncUtxosByBlockTask, BaseNodeWalletService, MAX_TX_QUERY_BATCH_SIZE},
         state_machine_service::states::StateInfo,
         StateMachineHandle,
     },
@@ -48,6 +48,9 @@ use crate::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
+            SpendingInput,
+            SpendingInputsRequest,
+            SpendingInputsResponse,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -296,8 +299,14 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
         };
 
         let message = request.into_message();
+        if message.sigs.len() > MAX_TX_QUERY_BATCH_SIZE {
+            return Err(RpcStatus::bad_request(&format!(
+                "Exceeded maximum allowed query size of {}",
+                MAX_TX_QUERY_BATCH_SIZE
+            )));
+        }
 
-        let mut responses: Vec<TxQueryBatchResponse> = Vec::new();
+        let mut responses: Vec<TxQueryBatchResponse> = Vec::with_capacity(message.sigs.len());
 
         let metadata = self
             .db
@@ -526,6 +535,53 @@ impl<B: BlockchainBackend + 'static> BaseNodeWalletService for BaseNodeWalletRpc
         }))
     }
 
+    async fn get_spending_inputs(
+        &self,
+        request: Request<SpendingInputsRequest>,
+    ) -> Result<Response<SpendingInputsResponse>, RpcStatus> {
+        let message = request.into_message();
+        if message.output_hashes.len() > MAX_QUERY_DELETED_HASHES {
+            return Err(RpcStatus::bad_request(
+                &"Received more hashes than we allow".to_string(),
+            ));
+        }
+        let hashes: Vec<FixedHash> = message
+            .output_hashes
+            .into_iter()
+            .map(|hash| hash.try_into())
+            .collect::<Result<_, _>>()
+            .map_err(|_| RpcStatus::bad_request(&"Malformed utxo hash received".to_string()))?;
+        let inputs = self
+            .db
+            .fetch_inputs_mined_info(hashes.clone())
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+        let inputs = hashes
+            .into_iter()
+            .zip(inputs)
+            .filter_map(|(hash, input)| input.map(|input| (hash, input)))
+            .map(|(hash, info)| {
+                Ok(SpendingInput {
+                    output_hash: hash.to_vec(),
+                    input: Some(info.input.try_into().map_err(|e: String| RpcStatus::general(&e))?),
+                    spent_height: info.spent_height,
+                    block_spent_in: info.header_hash.to_vec(),
+                })
+            })
+            .collect::<Result<Vec<_>, RpcStatus>>()?;
+        let metadata = self
+            .db
+            .get_chain_metadata()
+            .await
+            .rpc_status_internal_error(LOG_TARGET)?;
+
+        Ok(Response::new(SpendingInputsResponse {
+            inputs,
+            best_block_height: metadata.height_of_longest_chain(),
+            best_block_hash: metadata.best_block().to_vec(),
+        }))
+    }
+
     async fn get_tip_info(&self, _request: Request<()>) -> Result<Response<TipInfoResponse>, RpcStatus> {
         let state_machine = self.state_machine();
         let status_watch = state_machine.get_status_info_watch();
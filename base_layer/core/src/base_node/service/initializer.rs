@@ -40,13 +40,17 @@ use tari_service_framework::{
     ServiceInitializerContext,
 };
 use thiserror::Error;
-use tokio::sync::{broadcast, mpsc};
+use tokio::{
+    sync::{broadcast, mpsc},
+    task,
+};
 
 use crate::{
     base_node::{
         comms_interface::{InboundNodeCommsHandlers, LocalNodeCommsInterface, OutboundNodeCommsInterface},
         service::service::{BaseNodeService, BaseNodeStreams},
         BaseNodeStateMachineConfig,
+        ReorgAlarm,
         StateMachineHandle,
     },
     blocks::NewBlock,
@@ -175,9 +179,16 @@ where T: BlockchainBackend + 'static
             block_event_sender.clone(),
         );
 
+        let reorg_alarm = ReorgAlarm::new(
+            self.base_node_config.reorg_alarm_depth,
+            self.base_node_config.halt_block_templates_on_reorg_alarm,
+        );
+        let reorg_alarm_block_events = block_event_sender.subscribe();
+
         // Register handle to OutboundNodeCommsInterface before waiting for handles to be ready
         context.register_handle(outbound_nci.clone());
         context.register_handle(local_nci);
+        context.register_handle(reorg_alarm.clone());
 
         let service_request_timeout = self.service_request_timeout;
         let blockchain_db = self.blockchain_db.clone();
@@ -193,6 +204,12 @@ where T: BlockchainBackend + 'static
 
             let state_machine = handles.expect_handle::<StateMachineHandle>();
 
+            task::spawn(
+                reorg_alarm
+                    .clone()
+                    .run(reorg_alarm_block_events, handles.get_shutdown_signal()),
+            );
+
             let inbound_nch = InboundNodeCommsHandlers::new(
                 block_event_sender,
                 blockchain_db,
@@ -201,6 +218,7 @@ where T: BlockchainBackend + 'static
                 outbound_nci.clone(),
                 connectivity.clone(),
                 randomx_factory,
+                reorg_alarm,
             );
 
             let streams = BaseNodeStreams {
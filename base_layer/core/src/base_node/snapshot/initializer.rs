@@ -0,0 +1,97 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{sync::Arc, time::Duration};
+
+use futures::StreamExt;
+use log::*;
+use tari_comms_dht::Dht;
+use tari_p2p::{comms_connector::SubscriptionFactory, services::utils::map_decode, tari_message::TariMessageType};
+use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
+
+use super::{service::SnapshotService, SnapshotRegistry, LOG_TARGET};
+use crate::{
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    proto::base_node as proto,
+};
+
+const SUBSCRIPTION_LABEL: &str = "Snapshot";
+
+/// Initializer for the snapshot advertisement and discovery service. Registers a [SnapshotRegistry] handle.
+pub struct SnapshotServiceInitializer<B> {
+    inbound_message_subscription_factory: Arc<SubscriptionFactory>,
+    db: AsyncBlockchainDb<B>,
+    snapshot_interval: u64,
+    advertisement_period: Duration,
+}
+
+impl<B> SnapshotServiceInitializer<B>
+where B: BlockchainBackend + 'static
+{
+    pub fn new(
+        inbound_message_subscription_factory: Arc<SubscriptionFactory>,
+        db: AsyncBlockchainDb<B>,
+        snapshot_interval: u64,
+        advertisement_period: Duration,
+    ) -> Self {
+        Self {
+            inbound_message_subscription_factory,
+            db,
+            snapshot_interval,
+            advertisement_period,
+        }
+    }
+}
+
+#[async_trait]
+impl<B> ServiceInitializer for SnapshotServiceInitializer<B>
+where B: BlockchainBackend + 'static
+{
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        debug!(target: LOG_TARGET, "Initializing Snapshot Service");
+        let inbound_manifests = self
+            .inbound_message_subscription_factory
+            .get_subscription(TariMessageType::SnapshotManifest, SUBSCRIPTION_LABEL)
+            .map(map_decode::<proto::SnapshotManifest>);
+
+        let registry = SnapshotRegistry::default();
+        context.register_handle(registry.clone());
+
+        let db = self.db.clone();
+        let snapshot_interval = self.snapshot_interval;
+        let advertisement_period = self.advertisement_period;
+        context.spawn_until_shutdown(move |handles| {
+            let dht = handles.expect_handle::<Dht>();
+            SnapshotService::new(
+                db,
+                registry,
+                dht.outbound_requester(),
+                snapshot_interval,
+                advertisement_period,
+            )
+            .run(inbound_manifests)
+        });
+
+        debug!(target: LOG_TARGET, "Snapshot Service initialized");
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::{Display, Formatter};
+
+use tari_common_types::types::BlockHash;
+
+/// A manifest describing the chain state at a block that the advertising node is able to provide
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotManifest {
+    pub height: u64,
+    pub block_hash: BlockHash,
+}
+
+impl SnapshotManifest {
+    /// Returns the height at which a node with the given tip height takes snapshots at the given interval. Snapshot
+    /// heights are aligned to the interval so that archive nodes on the same chain advertise the same snapshot.
+    pub fn snapshot_height(tip_height: u64, interval: u64) -> Option<u64> {
+        if interval == 0 || tip_height < interval {
+            return None;
+        }
+        Some(tip_height - tip_height % interval)
+    }
+}
+
+impl Display for SnapshotManifest {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "snapshot #{} ({})", self.height, self.block_hash)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_aligns_snapshot_heights_to_the_interval() {
+        assert_eq!(SnapshotManifest::snapshot_height(1234, 0), None);
+        assert_eq!(SnapshotManifest::snapshot_height(999, 1000), None);
+        assert_eq!(SnapshotManifest::snapshot_height(1000, 1000), Some(1000));
+        assert_eq!(SnapshotManifest::snapshot_height(2999, 1000), Some(2000));
+    }
+}
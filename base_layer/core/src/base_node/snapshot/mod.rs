@@ -0,0 +1,43 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Chain state snapshot advertisement and discovery.
+//!
+//! Archive nodes periodically advertise a snapshot manifest, i.e. the height and block hash of a recent block at a
+//! fixed interval, to their DHT neighbours. Nodes that are syncing for the first time collect these manifests and,
+//! once their header chain has been synchronised, verify each manifest against it. If bootstrapping from snapshots is
+//! enabled, the sync state machine then downloads the kernels and unspent outputs at the highest verified snapshot from
+//! the peers that advertised it, verifies them against the snapshot block's header and applies them, and block sync
+//! continues from the snapshot. Such a node does not have the blocks below the snapshot.
+
+const LOG_TARGET: &str = "c::bn::snapshot";
+
+mod error;
+mod initializer;
+mod manifest;
+mod registry;
+mod service;
+
+pub use error::SnapshotError;
+pub use initializer::SnapshotServiceInitializer;
+pub use manifest::SnapshotManifest;
+pub use registry::SnapshotRegistry;
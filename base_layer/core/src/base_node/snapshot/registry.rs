@@ -0,0 +1,245 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use log::*;
+use tari_comms::peer_manager::NodeId;
+
+use super::{SnapshotError, SnapshotManifest, LOG_TARGET};
+use crate::chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend};
+
+/// The maximum number of advertising peers that are tracked at any one time
+const MAX_SNAPSHOT_ADVERTISERS: usize = 500;
+
+/// Keeps track of the most recent snapshot manifest advertised by each peer
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotRegistry {
+    manifests: Arc<RwLock<HashMap<NodeId, SnapshotManifest>>>,
+}
+
+impl SnapshotRegistry {
+    /// Record the manifest advertised by a peer, replacing any manifest it previously advertised
+    pub fn insert(&self, node_id: NodeId, manifest: SnapshotManifest) {
+        let mut manifests = self.manifests.write().expect("snapshot registry lock poisoned");
+        if manifests.len() >= MAX_SNAPSHOT_ADVERTISERS && !manifests.contains_key(&node_id) {
+            trace!(
+                target: LOG_TARGET,
+                "Ignoring {} from {} because the snapshot registry is full",
+                manifest,
+                node_id
+            );
+            return;
+        }
+        manifests.insert(node_id, manifest);
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<SnapshotManifest> {
+        self.manifests
+            .read()
+            .expect("snapshot registry lock poisoned")
+            .get(node_id)
+            .copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.manifests.read().expect("snapshot registry lock poisoned").len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Verifies the advertised manifests against the local header chain and returns the snapshots of blocks in that
+    /// chain with the peers that advertised them, ordered from the highest snapshot to the lowest. Manifests that
+    /// reference a block above the local header tip cannot be verified yet and are retained. Manifests that conflict
+    /// with the local header chain are discarded.
+    pub async fn verified_snapshots<B: BlockchainBackend + 'static>(
+        &self,
+        db: &AsyncBlockchainDb<B>,
+    ) -> Result<Vec<(NodeId, SnapshotManifest)>, SnapshotError> {
+        let manifests = self
+            .manifests
+            .read()
+            .expect("snapshot registry lock poisoned")
+            .iter()
+            .map(|(node_id, manifest)| (node_id.clone(), *manifest))
+            .collect::<Vec<_>>();
+        if manifests.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let tip_height = db.fetch_last_header().await?.height;
+        let mut verified = Vec::new();
+        let mut conflicting = Vec::new();
+        for (node_id, manifest) in manifests {
+            if manifest.height > tip_height {
+                continue;
+            }
+            match db.fetch_header(manifest.height).await? {
+                Some(header) if header.hash() == manifest.block_hash => verified.push((node_id, manifest)),
+                _ => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Discarding {} advertised by {} because it is not in the local header chain", manifest, node_id
+                    );
+                    conflicting.push(node_id);
+                },
+            }
+        }
+
+        if !conflicting.is_empty() {
+            let mut manifests = self.manifests.write().expect("snapshot registry lock poisoned");
+            for node_id in conflicting {
+                manifests.remove(&node_id);
+            }
+        }
+
+        verified.sort_by(|(_, a), (_, b)| b.height.cmp(&a.height));
+        Ok(verified)
+    }
+
+    /// The highest verified snapshot and the peers that advertised it, see [SnapshotRegistry::verified_snapshots]
+    pub async fn best_verified_snapshot<B: BlockchainBackend + 'static>(
+        &self,
+        db: &AsyncBlockchainDb<B>,
+    ) -> Result<Option<(SnapshotManifest, Vec<NodeId>)>, SnapshotError> {
+        let verified = self.verified_snapshots(db).await?;
+        let best = match verified.first() {
+            Some((_, manifest)) => *manifest,
+            None => return Ok(None),
+        };
+        let advertisers = verified
+            .into_iter()
+            .filter(|(_, manifest)| *manifest == best)
+            .map(|(node_id, _)| node_id)
+            .collect();
+        Ok(Some((best, advertisers)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{BlockHash, PublicKey};
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+    use crate::test_helpers::blockchain::{create_chained_blocks, create_main_chain, create_new_blockchain};
+
+    fn random_node_id() -> NodeId {
+        NodeId::from_public_key(&PublicKey::random_keypair(&mut OsRng).1)
+    }
+
+    #[tokio::test]
+    async fn it_discards_manifests_that_conflict_with_the_header_chain() {
+        let db = create_new_blockchain();
+        let (_, main_chain) = create_main_chain(&db, &[("A->GB", 1, 120), ("B->A", 1, 120), ("C->B", 1, 120)]).await;
+        let block_a = main_chain.get("A").unwrap();
+        let block_b = main_chain.get("B").unwrap();
+        let (_, fork) = create_chained_blocks(&[("B2->GB", 1, 120)], block_a.clone()).await;
+        let block_b2 = fork.get("B2").unwrap();
+        let db = AsyncBlockchainDb::from(db);
+
+        let registry = SnapshotRegistry::default();
+        let low = random_node_id();
+        let high = random_node_id();
+        let forked = random_node_id();
+        let wrong_height = random_node_id();
+        let ahead = random_node_id();
+        registry.insert(low.clone(), SnapshotManifest {
+            height: 1,
+            block_hash: *block_a.hash(),
+        });
+        registry.insert(high.clone(), SnapshotManifest {
+            height: 2,
+            block_hash: *block_b.hash(),
+        });
+        // A block on another chain at the same height
+        registry.insert(forked.clone(), SnapshotManifest {
+            height: 2,
+            block_hash: *block_b2.hash(),
+        });
+        // A block in the chain, but not at the advertised height
+        registry.insert(wrong_height.clone(), SnapshotManifest {
+            height: 3,
+            block_hash: *block_b.hash(),
+        });
+        // Cannot be verified until the header chain reaches it
+        registry.insert(ahead.clone(), SnapshotManifest {
+            height: 10,
+            block_hash: BlockHash::from([9u8; 32]),
+        });
+
+        let verified = registry.verified_snapshots(&db).await.unwrap();
+        assert_eq!(
+            verified.iter().map(|(node_id, _)| node_id.clone()).collect::<Vec<_>>(),
+            vec![high.clone(), low.clone()]
+        );
+        let (best, advertisers) = registry.best_verified_snapshot(&db).await.unwrap().unwrap();
+        assert_eq!(best.height, 2);
+        assert_eq!(advertisers, vec![high.clone()]);
+        assert_eq!(registry.len(), 3);
+        assert!(registry.get(&forked).is_none());
+        assert!(registry.get(&wrong_height).is_none());
+        assert!(registry.get(&ahead).is_some());
+
+        // A peer that re-advertises a conflicting manifest loses its place
+        registry.insert(high.clone(), SnapshotManifest {
+            height: 2,
+            block_hash: *block_b2.hash(),
+        });
+        let (best, advertisers) = registry.best_verified_snapshot(&db).await.unwrap().unwrap();
+        assert_eq!(best.height, 1);
+        assert_eq!(advertisers, vec![low]);
+        assert!(registry.get(&high).is_none());
+    }
+
+    #[test]
+    fn it_limits_the_number_of_advertisers() {
+        let registry = SnapshotRegistry::default();
+        let manifest = SnapshotManifest {
+            height: 1000,
+            block_hash: BlockHash::from([1u8; 32]),
+        };
+        let first = random_node_id();
+        registry.insert(first.clone(), manifest);
+        for _ in 1..MAX_SNAPSHOT_ADVERTISERS {
+            registry.insert(random_node_id(), manifest);
+        }
+        let ignored = random_node_id();
+        registry.insert(ignored.clone(), manifest);
+        assert_eq!(registry.len(), MAX_SNAPSHOT_ADVERTISERS);
+        assert!(registry.get(&ignored).is_none());
+
+        // Peers already in the registry can still update their manifest
+        let updated = SnapshotManifest {
+            height: 2000,
+            block_hash: BlockHash::from([2u8; 32]),
+        };
+        registry.insert(first.clone(), updated);
+        assert_eq!(registry.get(&first), Some(updated));
+    }
+}
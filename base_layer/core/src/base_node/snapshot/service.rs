@@ -0,0 +1,147 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, time::Duration};
+
+use futures::{Stream, StreamExt};
+use log::*;
+use prost::DecodeError;
+use tari_comms::peer_manager::NodeId;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    envelope::NodeDestination,
+    outbound::{DhtOutboundError, OutboundEncryption, OutboundMessageRequester},
+};
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tokio::time;
+
+use super::{SnapshotError, SnapshotManifest, SnapshotRegistry, LOG_TARGET};
+use crate::{
+    chain_storage::{async_db::AsyncBlockchainDb, BlockchainBackend},
+    proto::base_node as proto,
+};
+
+pub(super) struct SnapshotService<B> {
+    db: AsyncBlockchainDb<B>,
+    registry: SnapshotRegistry,
+    outbound_messaging: OutboundMessageRequester,
+    snapshot_interval: u64,
+    advertisement_period: Duration,
+}
+
+impl<B: BlockchainBackend + 'static> SnapshotService<B> {
+    pub fn new(
+        db: AsyncBlockchainDb<B>,
+        registry: SnapshotRegistry,
+        outbound_messaging: OutboundMessageRequester,
+        snapshot_interval: u64,
+        advertisement_period: Duration,
+    ) -> Self {
+        Self {
+            db,
+            registry,
+            outbound_messaging,
+            snapshot_interval,
+            advertisement_period,
+        }
+    }
+
+    pub async fn run<S>(mut self, inbound_manifests: S)
+    where S: Stream<Item = DomainMessage<Result<proto::SnapshotManifest, DecodeError>>> {
+        futures::pin_mut!(inbound_manifests);
+        let mut advertisement_interval = time::interval(self.advertisement_period);
+        advertisement_interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+
+        loop {
+            tokio::select! {
+                Some(msg) = inbound_manifests.next() => {
+                    if let Err(err) = self.handle_inbound_manifest(msg) {
+                        debug!(target: LOG_TARGET, "Failed to handle inbound snapshot manifest: {}", err);
+                    }
+                },
+                _ = advertisement_interval.tick(), if self.snapshot_interval > 0 => {
+                    if let Err(err) = self.advertise_snapshot().await {
+                        warn!(target: LOG_TARGET, "Failed to advertise snapshot manifest: {}", err);
+                    }
+                },
+                else => break,
+            }
+        }
+    }
+
+    fn handle_inbound_manifest(
+        &mut self,
+        msg: DomainMessage<Result<proto::SnapshotManifest, DecodeError>>,
+    ) -> Result<(), SnapshotError> {
+        let node_id = NodeId::from_public_key(msg.origin_public_key());
+        let manifest = SnapshotManifest::try_from(msg.into_inner()?).map_err(SnapshotError::InvalidManifest)?;
+        if manifest.height == 0 {
+            return Err(SnapshotError::InvalidManifest(
+                "Snapshot of the genesis block advertised".to_string(),
+            ));
+        }
+        debug!(target: LOG_TARGET, "Received {} from {}", manifest, node_id);
+        self.registry.insert(node_id, manifest);
+        Ok(())
+    }
+
+    /// Advertise the latest snapshot to our neighbours. Only archive nodes are able to provide the full chain state
+    /// up to a snapshot, so pruned nodes never advertise.
+    async fn advertise_snapshot(&mut self) -> Result<(), SnapshotError> {
+        let metadata = self.db.get_chain_metadata().await?;
+        if metadata.pruning_horizon() > 0 {
+            return Ok(());
+        }
+        let height = match SnapshotManifest::snapshot_height(metadata.height_of_longest_chain(), self.snapshot_interval)
+        {
+            Some(height) => height,
+            None => return Ok(()),
+        };
+        let header = match self.db.fetch_header(height).await? {
+            Some(header) => header,
+            None => return Ok(()),
+        };
+        let manifest = SnapshotManifest {
+            height,
+            block_hash: header.hash(),
+        };
+
+        debug!(target: LOG_TARGET, "Advertising {}", manifest);
+        let result = self
+            .outbound_messaging
+            .flood(
+                NodeDestination::Unknown,
+                OutboundEncryption::ClearText,
+                vec![],
+                OutboundDomainMessage::new(
+                    &TariMessageType::SnapshotManifest,
+                    proto::SnapshotManifest::from(manifest),
+                ),
+                "Snapshot manifest advertisement".to_string(),
+            )
+            .await;
+        match result {
+            Ok(_) | Err(DhtOutboundError::NoMessagesQueued) => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
@@ -30,6 +30,7 @@ use tokio::sync::{broadcast, watch};
 use crate::{
     base_node::{
         chain_metadata_service::ChainMetadataHandle,
+        snapshot::SnapshotRegistry,
         state_machine_service::{
             handle::StateMachineHandle,
             state_machine::{BaseNodeStateMachine, BaseNodeStateMachineConfig},
@@ -108,6 +109,7 @@ where B: BlockchainBackend + 'static
             let node_local_interface = handles.expect_handle::<LocalNodeCommsInterface>();
             let connectivity = handles.expect_handle::<ConnectivityRequester>();
             let peer_manager = handles.expect_handle::<Arc<PeerManager>>();
+            let snapshot_registry = handles.get_handle::<SnapshotRegistry>().unwrap_or_default();
 
             let sync_validators =
                 SyncValidators::full_consensus(rules.clone(), factories, bypass_range_proof_verification);
@@ -124,6 +126,7 @@ where B: BlockchainBackend + 'static
                 state_event_publisher,
                 randomx_factory,
                 rules,
+                snapshot_registry,
                 handles.get_shutdown_signal(),
             );
 
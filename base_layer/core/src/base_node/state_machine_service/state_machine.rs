@@ -34,6 +34,7 @@ use crate::{
     base_node::{
         chain_metadata_service::ChainMetadataEvent,
         comms_interface::LocalNodeCommsInterface,
+        snapshot::SnapshotRegistry,
         state_machine_service::{
             states,
             states::{BaseNodeState, HeaderSyncState, StateEvent, StateInfo, StatusInfo, SyncStatus},
@@ -62,6 +63,21 @@ pub struct BaseNodeStateMachineConfig {
     /// to always be behind the network
     #[serde(with = "serializers::seconds")]
     pub time_before_considered_lagging: Duration,
+    /// Raise the reorg alarm when a chain reorg removes at least this many blocks from the main chain. A deep reorg
+    /// may indicate an attack or a network split. Set to 0 to disable the alarm.
+    pub reorg_alarm_depth: u64,
+    /// Refuse to serve new block templates while the reorg alarm is raised
+    pub halt_block_templates_on_reorg_alarm: bool,
+    /// Archive nodes advertise a snapshot manifest for the block at every multiple of this height to their
+    /// neighbours. Set to 0 to disable snapshot advertisements.
+    pub snapshot_interval: u64,
+    /// The time between snapshot manifest advertisements
+    #[serde(with = "serializers::seconds")]
+    pub snapshot_advertisement_period: Duration,
+    /// When an archive node syncs for the first time, download the chain state at the highest snapshot that is
+    /// verified against the synced header chain from the peers that advertised it, and only sync the blocks above it.
+    /// The node will not have the blocks below the snapshot.
+    pub bootstrap_from_snapshots: bool,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -71,6 +87,11 @@ impl Default for BaseNodeStateMachineConfig {
             blockchain_sync_config: Default::default(),
             blocks_behind_before_considered_lagging: 1,
             time_before_considered_lagging: Duration::from_secs(10),
+            reorg_alarm_depth: 0,
+            halt_block_templates_on_reorg_alarm: false,
+            snapshot_interval: 1000,
+            snapshot_advertisement_period: Duration::from_secs(30 * 60),
+            bootstrap_from_snapshots: false,
         }
     }
 }
@@ -94,6 +115,7 @@ pub struct BaseNodeStateMachine<B: BlockchainBackend> {
     pub(super) consensus_rules: ConsensusManager,
     pub(super) status_event_sender: Arc<watch::Sender<StatusInfo>>,
     pub(super) randomx_factory: RandomXFactory,
+    pub(super) snapshot_registry: SnapshotRegistry,
     is_bootstrapped: bool,
     event_publisher: broadcast::Sender<Arc<StateEvent>>,
     interrupt_signal: ShutdownSignal,
@@ -114,6 +136,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
         event_publisher: broadcast::Sender<Arc<StateEvent>>,
         randomx_factory: RandomXFactory,
         consensus_rules: ConsensusManager,
+        snapshot_registry: SnapshotRegistry,
         interrupt_signal: ShutdownSignal,
     ) -> Self {
         Self {
@@ -128,6 +151,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             status_event_sender: Arc::new(status_event_sender),
             sync_validators,
             randomx_factory,
+            snapshot_registry,
             is_bootstrapped: false,
             consensus_rules,
             interrupt_signal,
@@ -164,6 +188,9 @@ impl<B: BlockchainBackend + 'static> BaseNodeStateMachine<B> {
             (HeaderSync(s), HeadersSynchronized(..)) => DecideNextSync(s.into()),
 
             (DecideNextSync(_), ProceedToHorizonSync(peers)) => HorizonStateSync(peers.into()),
+            (DecideNextSync(_), ProceedToSnapshotSync(peers, snapshot)) => {
+                HorizonStateSync(states::HorizonStateSync::from_snapshot(peers, snapshot))
+            },
             (DecideNextSync(s), Continue) => {
                 db.clear_disable_add_block_flag();
                 Listening(s.into())
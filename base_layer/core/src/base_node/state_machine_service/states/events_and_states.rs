@@ -26,6 +26,7 @@ use randomx_rs::RandomXFlag;
 use tari_common_types::chain_metadata::ChainMetadata;
 
 use crate::base_node::{
+    snapshot::SnapshotManifest,
     state_machine_service::states::{
         BlockSync,
         DecideNextSync,
@@ -60,6 +61,8 @@ pub enum StateEvent {
     HeadersSynchronized(SyncPeer, AttemptSyncResult),
     HeaderSyncFailed(String),
     ProceedToHorizonSync(Vec<SyncPeer>),
+    /// Sync the chain state at the snapshot from the sync peers that advertised it, then the blocks above it
+    ProceedToSnapshotSync(Vec<SyncPeer>, SnapshotManifest),
     ProceedToBlockSync(Vec<SyncPeer>),
     HorizonStateSynchronized,
     HorizonStateSyncFailure,
@@ -148,6 +151,7 @@ impl Display for StateEvent {
             HeadersSynchronized(peer, result) => write!(f, "Headers Synchronized from peer `{}` ({:?})", peer, result),
             HeaderSyncFailed(err) => write!(f, "Header Synchronization Failed ({})", err),
             ProceedToHorizonSync(_) => write!(f, "Proceed to horizon sync"),
+            ProceedToSnapshotSync(_, snapshot) => write!(f, "Proceed to sync from {}", snapshot),
             ProceedToBlockSync(_) => write!(f, "Proceed to block sync"),
             HorizonStateSynchronized => write!(f, "Horizon State Synchronized"),
             HorizonStateSyncFailure => write!(f, "Horizon State Synchronization Failed"),
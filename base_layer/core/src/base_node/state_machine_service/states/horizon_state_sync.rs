@@ -21,13 +21,14 @@
 //   USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 //! # Horizon state sync
 //!
-//! Horizon state synchronisation module for pruned mode.
+//! Horizon state synchronisation module for pruned mode, also used to bootstrap a node from a snapshot.
 
 use log::*;
 
 use super::{StateEvent, StateInfo};
 use crate::{
     base_node::{
+        snapshot::SnapshotManifest,
         state_machine_service::states::StatusInfo,
         sync::{HorizonStateSynchronization, SyncPeer},
         BaseNodeStateMachine,
@@ -41,9 +42,19 @@ const LOG_TARGET: &str = "c::bn::state_machine_service::states::horizon_state_sy
 #[derive(Clone, Debug)]
 pub struct HorizonStateSync {
     sync_peers: Vec<SyncPeer>,
+    /// Sync the chain state at this snapshot rather than at the pruning horizon
+    snapshot: Option<SnapshotManifest>,
 }
 
 impl HorizonStateSync {
+    /// Syncs the chain state at `snapshot` from the sync peers that advertised it
+    pub fn from_snapshot(sync_peers: Vec<SyncPeer>, snapshot: SnapshotManifest) -> Self {
+        Self {
+            sync_peers,
+            snapshot: Some(snapshot),
+        }
+    }
+
     pub fn into_sync_peers(self) -> Vec<SyncPeer> {
         self.sync_peers
     }
@@ -62,7 +73,23 @@ impl HorizonStateSync {
             Err(err) => return err.into(),
         };
 
-        let horizon_sync_height = local_metadata.horizon_block_height(last_header.height);
+        let horizon_sync_height = match self.snapshot {
+            Some(snapshot) => {
+                // The header chain may have been reorged since the snapshot was verified
+                match shared.db.fetch_header(snapshot.height).await {
+                    Ok(Some(header)) if header.hash() == snapshot.block_hash => snapshot.height,
+                    Ok(_) => {
+                        warn!(
+                            target: LOG_TARGET,
+                            "{} is no longer in the header chain, not syncing from it", snapshot
+                        );
+                        return StateEvent::HorizonStateSyncFailure;
+                    },
+                    Err(err) => return err.into(),
+                }
+            },
+            None => local_metadata.horizon_block_height(last_header.height),
+        };
         if local_metadata.pruned_height() >= horizon_sync_height {
             info!(target: LOG_TARGET, "Horizon state was already synchronized.");
             return StateEvent::HorizonStateSynchronized;
@@ -139,6 +166,9 @@ impl HorizonStateSync {
 
 impl From<Vec<SyncPeer>> for HorizonStateSync {
     fn from(sync_peers: Vec<SyncPeer>) -> Self {
-        Self { sync_peers }
+        Self {
+            sync_peers,
+            snapshot: None,
+        }
     }
 }
@@ -24,6 +24,7 @@ use log::*;
 
 use crate::{
     base_node::{
+        snapshot::SnapshotManifest,
         state_machine_service::{
             states::{HeaderSyncState, StateEvent},
             BaseNodeStateMachine,
@@ -47,7 +48,7 @@ impl DecideNextSync {
     }
 
     pub async fn next_event<B: BlockchainBackend + 'static>(&mut self, shared: &BaseNodeStateMachine<B>) -> StateEvent {
-        use StateEvent::{Continue, FatalError, ProceedToBlockSync, ProceedToHorizonSync};
+        use StateEvent::{Continue, FatalError, ProceedToBlockSync, ProceedToHorizonSync, ProceedToSnapshotSync};
         let local_metadata = match shared.db.get_chain_metadata().await {
             Ok(m) => m,
             Err(e) => {
@@ -61,6 +62,21 @@ impl DecideNextSync {
             self.sync_peers.len()
         );
 
+        if shared.config.bootstrap_from_snapshots &&
+            local_metadata.pruning_horizon() == 0 &&
+            local_metadata.height_of_longest_chain() == 0
+        {
+            if let Some((snapshot, sync_peers)) = self.snapshot_sync_peers(shared).await {
+                info!(
+                    target: LOG_TARGET,
+                    "Proceeding to bootstrap from {} with {} sync peer(s)",
+                    snapshot,
+                    sync_peers.len()
+                );
+                return ProceedToSnapshotSync(sync_peers, snapshot);
+            }
+        }
+
         if local_metadata.pruning_horizon() > 0 {
             let last_header = match shared.db.fetch_last_header().await {
                 Ok(h) => h,
@@ -122,6 +138,43 @@ impl DecideNextSync {
     }
 }
 
+impl DecideNextSync {
+    /// The highest snapshot of a block in our synced header chain and the sync peers that advertised it, or None if no
+    /// sync peer advertised a verified snapshot. The other sync peers are kept for a regular sync.
+    async fn snapshot_sync_peers<B: BlockchainBackend + 'static>(
+        &self,
+        shared: &BaseNodeStateMachine<B>,
+    ) -> Option<(SnapshotManifest, Vec<SyncPeer>)> {
+        if shared.snapshot_registry.is_empty() {
+            return None;
+        }
+        let (snapshot, advertisers) = match shared.snapshot_registry.best_verified_snapshot(&shared.db).await {
+            Ok(Some(best)) => best,
+            Ok(None) => return None,
+            Err(err) => {
+                warn!(target: LOG_TARGET, "Could not verify advertised snapshots: {}", err);
+                return None;
+            },
+        };
+        let sync_peers = self
+            .sync_peers
+            .iter()
+            .filter(|peer| {
+                advertisers.contains(peer.node_id()) && peer.claimed_chain_metadata().pruned_height() <= snapshot.height
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        if sync_peers.is_empty() {
+            debug!(
+                target: LOG_TARGET,
+                "None of the sync peers advertised {}, syncing from genesis", snapshot
+            );
+            return None;
+        }
+        Some((snapshot, sync_peers))
+    }
+}
+
 impl From<HeaderSyncState> for DecideNextSync {
     fn from(sync: HeaderSyncState) -> Self {
         let is_synced = sync.is_synced();
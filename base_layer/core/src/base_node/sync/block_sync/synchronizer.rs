@@ -28,7 +28,13 @@ use std::{
 
 use futures::StreamExt;
 use log::*;
-use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::rpc::RpcClient, PeerConnection};
+use tari_comms::{
+    compression::Compression,
+    connectivity::ConnectivityRequester,
+    peer_manager::NodeId,
+    protocol::rpc::RpcClient,
+    PeerConnection,
+};
 use tari_utilities::hex::Hex;
 use tokio::task;
 
@@ -159,7 +165,8 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
             };
             let config = RpcClient::builder()
                 .with_deadline(self.config.rpc_deadline)
-                .with_deadline_grace_period(Duration::from_secs(5));
+                .with_deadline_grace_period(Duration::from_secs(5))
+                .with_compression(Compression::Zstd);
             let mut client = match conn
                 .connect_rpc_using_builder::<rpc::BaseNodeSyncRpcClient>(config)
                 .await
@@ -31,6 +31,8 @@ pub use service::BaseNodeSyncRpcService;
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "base_node")]
+use tari_comms::peer_manager::NodeId;
 use tari_comms::protocol::rpc::{Request, Response, RpcStatus, Streaming};
 use tari_comms_rpc_macros::tari_rpc;
 
@@ -98,6 +100,7 @@ pub trait BaseNodeSyncService: Send + Sync + 'static {
 pub fn create_base_node_sync_rpc_service<B: BlockchainBackend + 'static>(
     db: AsyncBlockchainDb<B>,
     base_node_service: LocalNodeCommsInterface,
+    sync_allowlist: Vec<NodeId>,
 ) -> BaseNodeSyncRpcServer<BaseNodeSyncRpcService<B>> {
-    BaseNodeSyncRpcServer::new(BaseNodeSyncRpcService::new(db, base_node_service))
+    BaseNodeSyncRpcServer::new(BaseNodeSyncRpcService::new(db, base_node_service).with_sync_allowlist(sync_allowlist))
 }
@@ -71,6 +71,7 @@ pub struct BaseNodeSyncRpcService<B> {
     db: AsyncBlockchainDb<B>,
     active_sessions: Mutex<Vec<Weak<NodeId>>>,
     base_node_service: LocalNodeCommsInterface,
+    sync_allowlist: Vec<NodeId>,
 }
 
 impl<B: BlockchainBackend + 'static> BaseNodeSyncRpcService<B> {
@@ -79,9 +80,27 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncRpcService<B> {
             db,
             active_sessions: Mutex::new(Vec::new()),
             base_node_service,
+            sync_allowlist: Vec::new(),
         }
     }
 
+    /// Only serve block, header, kernel and UTXO sync to these peers. If empty, sync is served to any peer.
+    pub fn with_sync_allowlist(mut self, sync_allowlist: Vec<NodeId>) -> Self {
+        self.sync_allowlist = sync_allowlist;
+        self
+    }
+
+    fn check_sync_allowed(&self, peer: &NodeId) -> Result<(), RpcStatus> {
+        if self.sync_allowlist.is_empty() || self.sync_allowlist.contains(peer) {
+            return Ok(());
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Refusing to serve sync to peer {} that is not in the sync allowlist", peer
+        );
+        Err(RpcStatus::forbidden("This node only serves sync to allowlisted peers"))
+    }
+
     #[inline]
     fn db(&self) -> AsyncBlockchainDb<B> {
         self.db.clone()
@@ -114,6 +133,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
         request: Request<SyncBlocksRequest>,
     ) -> Result<Streaming<proto::base_node::BlockBodyResponse>, RpcStatus> {
         let peer_node_id = request.context().peer_node_id().clone();
+        self.check_sync_allowed(&peer_node_id)?;
         let message = request.into_message();
         let mut block_event_stream = self.base_node_service.get_block_event_stream();
 
@@ -278,6 +298,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
     ) -> Result<Streaming<proto::core::BlockHeader>, RpcStatus> {
         let db = self.db();
         let peer_node_id = request.context().peer_node_id().clone();
+        self.check_sync_allowed(&peer_node_id)?;
         let message = request.into_message();
         let hash = message
             .start_hash
@@ -466,6 +487,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
         request: Request<SyncKernelsRequest>,
     ) -> Result<Streaming<proto::types::TransactionKernel>, RpcStatus> {
         let peer_node_id = request.context().peer_node_id().clone();
+        self.check_sync_allowed(&peer_node_id)?;
         let req = request.into_message();
         let (tx, rx) = mpsc::channel(100);
         let db = self.db();
@@ -590,6 +612,7 @@ impl<B: BlockchainBackend + 'static> BaseNodeSyncService for BaseNodeSyncRpcServ
     async fn sync_utxos(&self, request: Request<SyncUtxosRequest>) -> Result<Streaming<SyncUtxosResponse>, RpcStatus> {
         let req = request.message();
         let peer_node_id = request.context().peer_node_id();
+        self.check_sync_allowed(peer_node_id)?;
         debug!(
             target: LOG_TARGET,
             "Received sync_utxos-{} request from header {} to {}",
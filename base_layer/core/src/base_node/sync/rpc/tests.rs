@@ -21,7 +21,11 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use futures::StreamExt;
-use tari_comms::protocol::rpc::{mock::RpcRequestMock, RpcStatusCode};
+use tari_comms::{
+    peer_manager::NodeId,
+    protocol::rpc::{mock::RpcRequestMock, RpcStatusCode},
+    types::CommsPublicKey,
+};
 use tari_service_framework::reply_channel;
 use tari_test_utils::{streams::convert_mpsc_to_stream, unpack_enum};
 use tempfile::{tempdir, TempDir};
@@ -74,6 +78,25 @@ mod sync_blocks {
         unpack_enum!(RpcStatusCode::NotFound = err.as_status_code());
     }
 
+    #[tokio::test]
+    async fn it_only_serves_allowlisted_peers() {
+        let (service, _, rpc_request_mock, _tmp) = setup();
+        let allowed = NodeId::from_public_key(&CommsPublicKey::default());
+        let service = service.with_sync_allowlist(vec![allowed.clone()]);
+        let msg = SyncBlocksRequest {
+            start_hash: vec![0; 32],
+            end_hash: vec![0; 32],
+        };
+
+        let req = rpc_request_mock.request_with_context(NodeId::default(), msg.clone());
+        let err = service.sync_blocks(req).await.unwrap_err();
+        unpack_enum!(RpcStatusCode::Forbidden = err.as_status_code());
+
+        let req = rpc_request_mock.request_with_context(allowed, msg);
+        let err = service.sync_blocks(req).await.unwrap_err();
+        unpack_enum!(RpcStatusCode::NotFound = err.as_status_code());
+    }
+
     #[tokio::test]
     async fn it_sends_bad_request_on_bad_response() {
         let (service, db, rpc_request_mock, _tmp) = setup();
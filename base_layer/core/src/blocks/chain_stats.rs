@@ -0,0 +1,158 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::{blocks::ChainHeader, proof_of_work::PowAlgorithm};
+
+/// Statistics for the blocks of a single proof of work algorithm within a window of headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PowAlgoStats {
+    pub num_blocks: u64,
+    /// The average number of seconds between consecutive blocks mined with this algorithm
+    pub average_block_interval: f64,
+    /// The target difficulty of the first block in the window mined with this algorithm
+    pub first_target_difficulty: u64,
+    /// The target difficulty of the last block in the window mined with this algorithm
+    pub last_target_difficulty: u64,
+    pub min_target_difficulty: u64,
+    pub max_target_difficulty: u64,
+    pub average_target_difficulty: u64,
+    /// The estimated hash rate, i.e. the average target difficulty divided by the average block interval
+    pub estimated_hash_rate: u64,
+}
+
+/// Block timing and difficulty statistics over a window of chain headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChainStats {
+    pub start_height: u64,
+    pub end_height: u64,
+    pub num_blocks: u64,
+    /// The average number of seconds between consecutive blocks, regardless of algorithm
+    pub average_block_interval: f64,
+    pub sha3x: PowAlgoStats,
+    pub randomx: PowAlgoStats,
+}
+
+impl ChainStats {
+    /// Calculate the statistics for the given headers, which may be in any order
+    pub fn from_headers(headers: &[ChainHeader]) -> Self {
+        if headers.is_empty() {
+            return Self::default();
+        }
+        let mut headers = headers.iter().collect::<Vec<_>>();
+        headers.sort_by_key(|h| h.height());
+
+        let timestamps = headers.iter().map(|h| h.timestamp()).collect::<Vec<_>>();
+        let algo_stats = |algo: PowAlgorithm| {
+            let blocks = headers
+                .iter()
+                .filter(|h| h.header().pow_algo() == algo)
+                .map(|h| (h.timestamp(), h.accumulated_data().target_difficulty.as_u64()))
+                .collect::<Vec<_>>();
+            PowAlgoStats::from_blocks(&blocks)
+        };
+
+        Self {
+            start_height: headers[0].height(),
+            end_height: headers[headers.len() - 1].height(),
+            num_blocks: headers.len() as u64,
+            average_block_interval: average_interval(&timestamps),
+            sha3x: algo_stats(PowAlgorithm::Sha3x),
+            randomx: algo_stats(PowAlgorithm::RandomX),
+        }
+    }
+
+    pub fn for_algo(&self, algo: PowAlgorithm) -> &PowAlgoStats {
+        match algo {
+            PowAlgorithm::Sha3x => &self.sha3x,
+            PowAlgorithm::RandomX => &self.randomx,
+        }
+    }
+}
+
+impl PowAlgoStats {
+    /// `blocks` is a list of (timestamp, target difficulty) pairs ordered by height
+    fn from_blocks(blocks: &[(u64, u64)]) -> Self {
+        if blocks.is_empty() {
+            return Self::default();
+        }
+        let timestamps = blocks.iter().map(|(t, _)| *t).collect::<Vec<_>>();
+        let difficulties = blocks.iter().map(|(_, d)| u128::from(*d)).collect::<Vec<_>>();
+        let average_block_interval = average_interval(&timestamps);
+        #[allow(clippy::cast_possible_truncation)]
+        let average_target_difficulty = (difficulties.iter().sum::<u128>() / difficulties.len() as u128) as u64;
+        #[allow(clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_sign_loss)]
+        let estimated_hash_rate = if average_block_interval > 0.0 {
+            (average_target_difficulty as f64 / average_block_interval) as u64
+        } else {
+            0
+        };
+
+        Self {
+            num_blocks: blocks.len() as u64,
+            average_block_interval,
+            first_target_difficulty: blocks[0].1,
+            last_target_difficulty: blocks[blocks.len() - 1].1,
+            min_target_difficulty: blocks.iter().map(|(_, d)| *d).min().unwrap_or_default(),
+            max_target_difficulty: blocks.iter().map(|(_, d)| *d).max().unwrap_or_default(),
+            average_target_difficulty,
+            estimated_hash_rate,
+        }
+    }
+}
+
+/// The average interval between timestamps ordered by height. Timestamps may not be monotonic, so the interval is taken
+/// between the first and last timestamp.
+fn average_interval(timestamps: &[u64]) -> f64 {
+    if timestamps.len() < 2 {
+        return 0.0;
+    }
+    let elapsed = timestamps[timestamps.len() - 1].saturating_sub(timestamps[0]);
+    elapsed as f64 / (timestamps.len() - 1) as f64
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_calculates_algo_stats() {
+        let stats = PowAlgoStats::from_blocks(&[(100, 1000), (220, 3000), (340, 2000)]);
+        assert_eq!(stats.num_blocks, 3);
+        assert!((stats.average_block_interval - 120.0).abs() < f64::EPSILON);
+        assert_eq!(stats.first_target_difficulty, 1000);
+        assert_eq!(stats.last_target_difficulty, 2000);
+        assert_eq!(stats.min_target_difficulty, 1000);
+        assert_eq!(stats.max_target_difficulty, 3000);
+        assert_eq!(stats.average_target_difficulty, 2000);
+        assert_eq!(stats.estimated_hash_rate, 16);
+    }
+
+    #[test]
+    fn it_handles_too_few_blocks() {
+        assert_eq!(PowAlgoStats::from_blocks(&[]), PowAlgoStats::default());
+        let stats = PowAlgoStats::from_blocks(&[(100, 1000)]);
+        assert_eq!(stats.num_blocks, 1);
+        assert_eq!(stats.estimated_hash_rate, 0);
+        assert_eq!(ChainStats::from_headers(&[]), ChainStats::default());
+    }
+}
@@ -44,6 +44,11 @@ mod block_header;
 #[cfg(any(feature = "base_node", feature = "base_node_proto"))]
 pub use block_header::{BlockHeader, BlockHeaderValidationError};
 
+#[cfg(feature = "base_node")]
+mod chain_stats;
+#[cfg(feature = "base_node")]
+pub use chain_stats::{ChainStats, PowAlgoStats};
+
 #[cfg(feature = "base_node")]
 pub mod genesis_block;
 
@@ -28,7 +28,7 @@ use std::{
     mem,
     ops::{Bound, RangeBounds},
     sync::{atomic, atomic::AtomicBool, Arc, RwLock, RwLockReadGuard, RwLockWriteGuard},
-    time::Instant,
+    time::{Duration, Instant},
 };
 
 use blake2::Blake2b;
@@ -47,6 +47,8 @@ use tari_mmr::{
 use tari_utilities::{epoch_time::EpochTime, hex::Hex, ByteArray};
 
 use super::TemplateRegistrationEntry;
+#[cfg(feature = "metrics")]
+use crate::chain_storage::metrics;
 use crate::{
     blocks::{
         Block,
@@ -108,6 +110,7 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "c::cs::database";
+const SLOW_OPERATION_LOG_TARGET: &str = "c::cs::database::slow";
 
 /// Configuration for the BlockchainDatabase.
 #[derive(Clone, Copy, Debug, Serialize, Deserialize)]
@@ -118,6 +121,15 @@ pub struct BlockchainDatabaseConfig {
     pub pruning_interval: u64,
     pub track_reorgs: bool,
     pub cleanup_orphans_at_startup: bool,
+    /// Database operations that take longer than this many milliseconds are logged, along with the keys they
+    /// touched, to the `c::cs::database::slow` log target. Slow operation logging is disabled if not set.
+    pub slow_operation_threshold_ms: Option<u64>,
+}
+
+impl BlockchainDatabaseConfig {
+    pub fn slow_operation_threshold(&self) -> Option<Duration> {
+        self.slow_operation_threshold_ms.map(Duration::from_millis)
+    }
 }
 
 impl Default for BlockchainDatabaseConfig {
@@ -128,6 +140,7 @@ impl Default for BlockchainDatabaseConfig {
             pruning_interval: BLOCKCHAIN_DATABASE_PRUNED_MODE_PRUNING_INTERVAL,
             track_reorgs: false,
             cleanup_orphans_at_startup: false,
+            slow_operation_threshold_ms: None,
         }
     }
 }
@@ -336,13 +349,62 @@ where B: BlockchainBackend
     }
 
     fn db_write_access(&self) -> Result<RwLockWriteGuard<B>, ChainStorageError> {
-        self.db.write().map_err(|e| {
+        #[cfg(feature = "metrics")]
+        let timer = Instant::now();
+        let db = self.db.write().map_err(|e| {
             error!(
                 target: LOG_TARGET,
                 "An attempt to get a write lock on the blockchain backend failed. {:?}", e
             );
             ChainStorageError::AccessError("Write lock on blockchain backend failed".into())
-        })
+        })?;
+        #[cfg(feature = "metrics")]
+        metrics::write_lock_wait().observe(timer.elapsed().as_secs_f64());
+        Ok(db)
+    }
+
+    /// Runs a database operation, recording how long it took. If the operation exceeds the configured slow operation
+    /// threshold, it is logged along with a description of the keys it touched. `keys` is only evaluated in that case.
+    fn timed<T, F, K>(&self, operation: &'static str, keys: K, f: F) -> Result<T, ChainStorageError>
+    where
+        F: FnOnce() -> Result<T, ChainStorageError>,
+        K: FnOnce() -> String,
+    {
+        let timer = Instant::now();
+        let result = f();
+        let elapsed = timer.elapsed();
+        #[cfg(feature = "metrics")]
+        metrics::operation_duration(operation).observe(elapsed.as_secs_f64());
+        if self
+            .config
+            .slow_operation_threshold()
+            .map_or(false, |threshold| elapsed >= threshold)
+        {
+            #[cfg(feature = "metrics")]
+            metrics::slow_operations(operation).inc();
+            warn!(
+                target: SLOW_OPERATION_LOG_TARGET,
+                "Slow database operation `{}` took {:.2?} ({}). Keys: {}",
+                operation,
+                elapsed,
+                if result.is_ok() { "ok" } else { "failed" },
+                keys()
+            );
+        }
+        result
+    }
+
+    /// Describes the keys touched by a transaction for the slow operation log. This is only done when slow operation
+    /// logging is enabled, because the transaction is consumed by the write.
+    fn describe_transaction_keys(&self, txn: &DbTransaction) -> String {
+        if self.config.slow_operation_threshold().is_none() {
+            return String::new();
+        }
+        txn.operations()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ")
     }
 
     pub(crate) fn is_add_block_disabled(&self) -> bool {
@@ -358,8 +420,15 @@ where B: BlockchainBackend
     }
 
     pub fn write(&self, transaction: DbTransaction) -> Result<(), ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        db.write(transaction)
+        let keys = self.describe_transaction_keys(&transaction);
+        self.timed(
+            "write",
+            || keys,
+            || {
+                let mut db = self.db_write_access()?;
+                db.write(transaction)
+            },
+        )
     }
 
     /// Returns the height of the current longest chain. This method will only fail if there's a fairly serious
@@ -576,8 +645,18 @@ where B: BlockchainBackend
     /// Store the provided headers. This function does not do any validation and assumes the inserted header has already
     /// been validated.
     pub fn insert_valid_headers(&self, headers: Vec<ChainHeader>) -> Result<(), ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        insert_headers(&mut *db, headers)
+        let keys = match (headers.first(), headers.last()) {
+            (Some(first), Some(last)) => format!("headers #{}-#{}", first.height(), last.height()),
+            _ => "no headers".to_string(),
+        };
+        self.timed(
+            "insert_valid_headers",
+            || keys,
+            || {
+                let mut db = self.db_write_access()?;
+                insert_headers(&mut *db, headers)
+            },
+        )
     }
 
     /// Returns the set of block headers between `start` and up to and including `end_inclusive`
@@ -912,6 +991,11 @@ where B: BlockchainBackend
     ///
     /// If an error does occur while writing the new block parts, all changes are reverted before returning.
     pub fn add_block(&self, candidate_block: Arc<Block>) -> Result<BlockAddResult, ChainStorageError> {
+        let keys = format!("block #{} {}", candidate_block.header.height, candidate_block.hash());
+        self.timed("add_block", || keys, || self.add_block_inner(candidate_block))
+    }
+
+    fn add_block_inner(&self, candidate_block: Arc<Block>) -> Result<BlockAddResult, ChainStorageError> {
         let timer = Instant::now();
 
         let block_hash = candidate_block.hash();
@@ -1035,8 +1119,14 @@ where B: BlockchainBackend
 
     /// Prunes the blockchain up to and including the given height
     pub fn prune_to_height(&self, height: u64) -> Result<(), ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        prune_to_height(&mut *db, height)
+        self.timed(
+            "prune_to_height",
+            || format!("height #{}", height),
+            || {
+                let mut db = self.db_write_access()?;
+                prune_to_height(&mut *db, height)
+            },
+        )
     }
 
     /// Fetch a block from the blockchain database.
@@ -1052,8 +1142,14 @@ where B: BlockchainBackend
     /// * The height is beyond the current chain tip.
     /// * The height is lower than the block at the pruning horizon.
     pub fn fetch_block(&self, height: u64, compact: bool) -> Result<HistoricalBlock, ChainStorageError> {
-        let db = self.db_read_access()?;
-        fetch_block(&*db, height, compact)
+        self.timed(
+            "fetch_block",
+            || format!("block #{}", height),
+            || {
+                let db = self.db_read_access()?;
+                fetch_block(&*db, height, compact)
+            },
+        )
     }
 
     /// Returns the set of blocks according to the bounds
@@ -1146,8 +1242,15 @@ where B: BlockchainBackend
 
     /// Atomically commit the provided transaction to the database backend. This function does not update the metadata.
     pub fn commit(&self, txn: DbTransaction) -> Result<(), ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        db.write(txn)
+        let keys = self.describe_transaction_keys(&txn);
+        self.timed(
+            "commit",
+            || keys,
+            || {
+                let mut db = self.db_write_access()?;
+                db.write(txn)
+            },
+        )
     }
 
     /// Rewind the blockchain state to the block height given and return the blocks that were removed and orphaned.
@@ -1155,8 +1258,14 @@ where B: BlockchainBackend
     /// The operation will fail if
     /// * The block height is in the future
     pub fn rewind_to_height(&self, height: u64) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        rewind_to_height(&mut *db, height)
+        self.timed(
+            "rewind_to_height",
+            || format!("height #{}", height),
+            || {
+                let mut db = self.db_write_access()?;
+                rewind_to_height(&mut *db, height)
+            },
+        )
     }
 
     /// Rewind the blockchain state to the block hash making the block at that hash the new tip.
@@ -1166,8 +1275,14 @@ where B: BlockchainBackend
     /// * The block hash does not exist
     /// * The block hash is before the horizon block height determined by the pruning horizon
     pub fn rewind_to_hash(&self, hash: BlockHash) -> Result<Vec<Arc<ChainBlock>>, ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        rewind_to_hash(&mut *db, hash)
+        self.timed(
+            "rewind_to_hash",
+            || format!("block {}", hash),
+            || {
+                let mut db = self.db_write_access()?;
+                rewind_to_hash(&mut *db, hash)
+            },
+        )
     }
 
     /// This method will compare all chain tips the node currently knows about. This includes
@@ -1176,14 +1291,16 @@ where B: BlockchainBackend
     /// This is typically used when an attempted sync failed to sync to the expected height and
     /// we are not sure if the new chain is higher than the old one.
     pub fn swap_to_highest_pow_chain(&self) -> Result<(), ChainStorageError> {
-        let mut db = self.db_write_access()?;
-        swap_to_highest_pow_chain(
-            &mut *db,
-            &self.config,
-            &*self.validators.block,
-            self.consensus_manager.chain_strength_comparer(),
-        )?;
-        Ok(())
+        self.timed("swap_to_highest_pow_chain", String::new, || {
+            let mut db = self.db_write_access()?;
+            swap_to_highest_pow_chain(
+                &mut *db,
+                &self.config,
+                &*self.validators.block,
+                self.consensus_manager.chain_strength_comparer(),
+            )?;
+            Ok(())
+        })
     }
 
     pub fn fetch_horizon_data(&self) -> Result<HorizonData, ChainStorageError> {
@@ -2516,6 +2633,76 @@ mod test {
         }
     }
 
+    mod slow_operation_log {
+        use std::cell::Cell;
+
+        use super::*;
+        use crate::test_helpers::blockchain::create_store_with_consensus_and_validators_and_config;
+
+        fn create_db(slow_operation_threshold_ms: Option<u64>) -> BlockchainDatabase<TempDatabase> {
+            let validators = Validators::new(
+                MockValidator::new(true),
+                MockValidator::new(true),
+                MockValidator::new(true),
+            );
+            create_store_with_consensus_and_validators_and_config(
+                create_consensus_rules(),
+                validators,
+                BlockchainDatabaseConfig {
+                    slow_operation_threshold_ms,
+                    ..Default::default()
+                },
+            )
+        }
+
+        #[test]
+        fn it_only_describes_keys_of_slow_operations() {
+            let keys_described = Cell::new(false);
+            let describe_keys = || {
+                keys_described.set(true);
+                "keys".to_string()
+            };
+
+            let db = create_db(None);
+            assert_eq!(db.timed("test", describe_keys, || Ok(1)).unwrap(), 1);
+            assert!(!keys_described.get());
+
+            let db = create_db(Some(60_000));
+            assert_eq!(db.timed("test", describe_keys, || Ok(1)).unwrap(), 1);
+            assert!(!keys_described.get());
+
+            // Every operation exceeds a zero threshold, and failures are passed through
+            let db = create_db(Some(0));
+            let err = db
+                .timed("test", describe_keys, || -> Result<(), _> {
+                    Err(ChainStorageError::AccessError("test".to_string()))
+                })
+                .unwrap_err();
+            assert!(matches!(err, ChainStorageError::AccessError(_)));
+            assert!(keys_described.get());
+        }
+
+        #[test]
+        fn it_only_describes_transactions_when_enabled() {
+            let mut txn = DbTransaction::new();
+            txn.insert_monero_seed_height(b"seed".to_vec(), 5);
+
+            assert!(create_db(None).describe_transaction_keys(&txn).is_empty());
+            assert!(!create_db(Some(0)).describe_transaction_keys(&txn).is_empty());
+
+            // Writes are still applied when they are logged as slow
+            let db = create_db(Some(0));
+            db.write(txn).unwrap();
+            assert_eq!(
+                db.db_read_access()
+                    .unwrap()
+                    .fetch_monero_seed_first_seen_height(b"seed")
+                    .unwrap(),
+                5
+            );
+        }
+    }
+
     mod get_orphan_link_main_chain {
         use super::*;
 
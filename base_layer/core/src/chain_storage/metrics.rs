@@ -0,0 +1,62 @@
+//  Copyright 2022, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use once_cell::sync::Lazy;
+use tari_metrics::{Histogram, HistogramVec, IntCounter, IntCounterVec};
+
+pub fn operation_duration(operation: &str) -> Histogram {
+    static METER: Lazy<HistogramVec> = Lazy::new(|| {
+        tari_metrics::register_histogram_vec(
+            "base_node::blockchain_database::operation_duration",
+            "Time taken in seconds by blockchain database operations, including waiting for the database lock",
+            &["operation"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[operation])
+}
+
+pub fn write_lock_wait() -> Histogram {
+    static METER: Lazy<Histogram> = Lazy::new(|| {
+        tari_metrics::register_histogram(
+            "base_node::blockchain_database::write_lock_wait",
+            "Time spent in seconds waiting to acquire the blockchain database write lock",
+        )
+        .unwrap()
+    });
+
+    METER.clone()
+}
+
+pub fn slow_operations(operation: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "base_node::blockchain_database::slow_operations",
+            "Number of blockchain database operations that exceeded the slow operation threshold",
+            &["operation"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[operation])
+}
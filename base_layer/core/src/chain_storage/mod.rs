@@ -58,6 +58,9 @@ mod consts;
 mod db_transaction;
 pub use db_transaction::{DbKey, DbTransaction, DbValue, WriteOperation};
 
+#[cfg(feature = "metrics")]
+mod metrics;
+
 mod mmr_tree;
 pub use mmr_tree::MmrTree;
 
@@ -79,8 +79,13 @@ pub fn diffie_hellman_stealth_domain_hasher(
     private_key: &PrivateKey,
     public_key: &PublicKey,
 ) -> DomainSeparatedHash<Blake2b<U64>> {
+    shared_secret_to_stealth_domain_hasher(&CommsDHKE::new(private_key, public_key))
+}
+
+/// Stealth address domain separated hasher using an already computed Diffie-Hellman shared secret
+pub fn shared_secret_to_stealth_domain_hasher(shared_secret: &CommsDHKE) -> DomainSeparatedHash<Blake2b<U64>> {
     WalletHasher::new_with_label("stealth_address")
-        .chain(CommsDHKE::new(private_key, public_key).as_bytes())
+        .chain(shared_secret.as_bytes())
         .finalize()
 }
 
@@ -200,6 +200,11 @@ impl ConsensusConstants {
         Utc::now().add(Duration::seconds(self.future_time_limit as i64))
     }
 
+    /// The number of seconds a block timestamp may be ahead of the local clock before the block is rejected.
+    pub fn future_time_limit(&self) -> u64 {
+        self.future_time_limit
+    }
+
     /// When doing difficulty adjustments and FTL calculations this is the amount of blocks we look at.
     pub fn difficulty_block_window(&self) -> u64 {
         self.difficulty_block_window
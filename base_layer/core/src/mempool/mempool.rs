@@ -35,6 +35,7 @@ use crate::{
         MempoolConfig,
         StateResponse,
         StatsResponse,
+        TxAcceptanceResponse,
         TxStorageResponse,
     },
     transactions::transaction_components::Transaction,
@@ -67,6 +68,16 @@ impl Mempool {
         .await
     }
 
+    /// Validates a transaction against the mempool and reports whether it would be accepted, without inserting it.
+    pub async fn test_insert(&self, tx: Arc<Transaction>) -> Result<TxAcceptanceResponse, MempoolError> {
+        self.with_read_access(|storage| {
+            storage
+                .test_insert(tx)
+                .map_err(|e| MempoolError::InternalError(e.to_string()))
+        })
+        .await
+    }
+
     /// Inserts all transactions into the mempool.
     pub async fn insert_all(&self, transactions: Vec<Arc<Transaction>>) -> Result<(), MempoolError> {
         self.with_write_access(|storage| {
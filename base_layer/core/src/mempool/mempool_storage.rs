@@ -23,7 +23,7 @@
 use std::{sync::Arc, time::Instant};
 
 use log::*;
-use tari_common_types::types::{PrivateKey, Signature};
+use tari_common_types::types::{HashOutput, PrivateKey, Signature};
 use tari_utilities::hex::Hex;
 
 use crate::{
@@ -37,9 +37,11 @@ use crate::{
         MempoolConfig,
         StateResponse,
         StatsResponse,
+        TxAcceptanceResponse,
         TxStorageResponse,
     },
     transactions::{
+        tari_amount::MicroMinotari,
         transaction_components::{Transaction, TransactionError},
         weight::TransactionWeight,
     },
@@ -81,20 +83,8 @@ impl MempoolStorage {
             .unwrap_or_else(|| "None?!".into());
         let timer = Instant::now();
         debug!(target: LOG_TARGET, "Inserting tx into mempool: {}", tx_id);
-        let tx_fee = match tx.body.get_total_fee() {
-            Ok(fee) => fee,
-            Err(e) => {
-                warn!(target: LOG_TARGET, "Invalid transaction: {}", e);
-                return Ok(TxStorageResponse::NotStoredConsensus);
-            },
-        };
-        // This check is almost free, so lets check this before we do any expensive validation.
-        if tx_fee.as_u64() < self.unconfirmed_pool.config.min_fee {
-            debug!(target: LOG_TARGET, "Tx: ({}) fee too low, rejecting",tx_id);
-            return Ok(TxStorageResponse::NotStoredFeeTooLow);
-        }
-        match self.validator.validate(&tx) {
-            Ok(()) => {
+        match self.check_admission(&tx) {
+            TxAdmission::Accepted { dependent_outputs } => {
                 debug!(
                     target: LOG_TARGET,
                     "Transaction {} is VALID ({:.2?}), inserting in unconfirmed pool in",
@@ -103,7 +93,7 @@ impl MempoolStorage {
                 );
                 let timer = Instant::now();
                 let weight = self.get_transaction_weighting();
-                self.unconfirmed_pool.insert(tx, None, &weight)?;
+                self.unconfirmed_pool.insert(tx, dependent_outputs, &weight)?;
                 debug!(
                     target: LOG_TARGET,
                     "Transaction {} inserted in {:.2?}",
@@ -112,39 +102,107 @@ impl MempoolStorage {
                 );
                 Ok(TxStorageResponse::UnconfirmedPool)
             },
+            TxAdmission::Rejected { response, .. } => Ok(response),
+        }
+    }
+
+    /// Runs the same checks as [insert](Self::insert) and reports whether the transaction would be accepted, without
+    /// modifying the mempool.
+    pub fn test_insert(&self, tx: Arc<Transaction>) -> Result<TxAcceptanceResponse, UnconfirmedPoolError> {
+        let weighting = self.get_transaction_weighting();
+        let fee_per_gram = match (tx.body.get_total_fee(), tx.calculate_weight(&weighting)) {
+            (Ok(fee), Ok(weight)) if weight > 0 => MicroMinotari::from(fee.as_u64() / weight),
+            _ => MicroMinotari::zero(),
+        };
+        let response = match self.check_admission(&tx) {
+            TxAdmission::Accepted { .. } => {
+                if self.unconfirmed_pool.has_capacity_for(tx, &weighting)? {
+                    TxAcceptanceResponse {
+                        storage: TxStorageResponse::UnconfirmedPool,
+                        fee_per_gram,
+                        rejection_reason: None,
+                    }
+                } else {
+                    TxAcceptanceResponse {
+                        storage: TxStorageResponse::NotStored,
+                        fee_per_gram,
+                        rejection_reason: Some(
+                            "The mempool is full and the transaction has a lower priority than every transaction in it"
+                                .to_string(),
+                        ),
+                    }
+                }
+            },
+            TxAdmission::Rejected { response, reason } => TxAcceptanceResponse {
+                storage: response,
+                fee_per_gram,
+                rejection_reason: Some(reason),
+            },
+        };
+        Ok(response)
+    }
+
+    /// Validates the transaction and checks that it pays at least the minimum fee
+    fn check_admission(&self, tx: &Transaction) -> TxAdmission {
+        let tx_fee = match tx.body.get_total_fee() {
+            Ok(fee) => fee,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Invalid transaction: {}", e);
+                return TxAdmission::rejected(TxStorageResponse::NotStoredConsensus, e);
+            },
+        };
+        // This check is almost free, so lets check this before we do any expensive validation.
+        if tx_fee.as_u64() < self.unconfirmed_pool.config.min_fee {
+            debug!(target: LOG_TARGET, "Tx fee ({}) too low, rejecting", tx_fee);
+            return TxAdmission::rejected(
+                TxStorageResponse::NotStoredFeeTooLow,
+                format!(
+                    "Transaction fee {} is below the minimum of {}",
+                    tx_fee,
+                    MicroMinotari::from(self.unconfirmed_pool.config.min_fee)
+                ),
+            );
+        }
+        match self.validator.validate(tx) {
+            Ok(()) => TxAdmission::Accepted {
+                dependent_outputs: None,
+            },
             Err(ValidationError::UnknownInputs(dependent_outputs)) => {
                 if self.unconfirmed_pool.contains_all_outputs(&dependent_outputs) {
-                    let weight = self.get_transaction_weighting();
-                    self.unconfirmed_pool.insert(tx, Some(dependent_outputs), &weight)?;
-                    Ok(TxStorageResponse::UnconfirmedPool)
+                    TxAdmission::Accepted {
+                        dependent_outputs: Some(dependent_outputs),
+                    }
                 } else {
                     warn!(target: LOG_TARGET, "Validation failed due to unknown inputs");
-                    Ok(TxStorageResponse::NotStoredOrphan)
+                    TxAdmission::rejected(
+                        TxStorageResponse::NotStoredOrphan,
+                        "The transaction spends inputs that are unknown to this node",
+                    )
                 }
             },
             Err(ValidationError::ContainsSTxO) => {
                 warn!(target: LOG_TARGET, "Validation failed due to already spent input");
-                Ok(TxStorageResponse::NotStoredAlreadySpent)
+                TxAdmission::rejected(TxStorageResponse::NotStoredAlreadySpent, ValidationError::ContainsSTxO)
             },
             Err(ValidationError::MaturityError) => {
                 warn!(target: LOG_TARGET, "Validation failed due to maturity error");
-                Ok(TxStorageResponse::NotStoredTimeLocked)
+                TxAdmission::rejected(TxStorageResponse::NotStoredTimeLocked, ValidationError::MaturityError)
             },
             Err(ValidationError::ConsensusError(msg)) => {
                 warn!(target: LOG_TARGET, "Validation failed due to consensus rule: {}", msg);
-                Ok(TxStorageResponse::NotStoredConsensus)
+                TxAdmission::rejected(TxStorageResponse::NotStoredConsensus, msg)
             },
             Err(ValidationError::DuplicateKernelError(msg)) => {
                 debug!(
                     target: LOG_TARGET,
                     "Validation failed due to already mined kernel: {}", msg
                 );
-                Ok(TxStorageResponse::NotStoredAlreadyMined)
+                TxAdmission::rejected(TxStorageResponse::NotStoredAlreadyMined, msg)
             },
             Err(e) => {
                 eprintln!("Validation failed due to error: {}", e);
                 warn!(target: LOG_TARGET, "Validation failed due to error: {}", e);
-                Ok(TxStorageResponse::NotStored)
+                TxAdmission::rejected(TxStorageResponse::NotStored, e)
             },
         }
     }
@@ -385,3 +443,23 @@ impl MempoolStorage {
         Ok(stats)
     }
 }
+
+/// The outcome of checking whether a transaction may enter the unconfirmed pool
+enum TxAdmission {
+    Accepted {
+        dependent_outputs: Option<Vec<HashOutput>>,
+    },
+    Rejected {
+        response: TxStorageResponse,
+        reason: String,
+    },
+}
+
+impl TxAdmission {
+    fn rejected<T: ToString>(response: TxStorageResponse, reason: T) -> Self {
+        TxAdmission::Rejected {
+            response,
+            reason: reason.to_string(),
+        }
+    }
+}
@@ -140,6 +140,23 @@ impl Display for TxStorageResponse {
     }
 }
 
+/// The outcome of validating a transaction against the mempool without inserting it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxAcceptanceResponse {
+    /// Where the transaction would be stored if it were submitted
+    pub storage: TxStorageResponse,
+    /// The fee per gram paid by the transaction
+    pub fee_per_gram: MicroMinotari,
+    /// The reason the transaction would not be stored, if any
+    pub rejection_reason: Option<String>,
+}
+
+impl TxAcceptanceResponse {
+    pub fn is_accepted(&self) -> bool {
+        self.storage.is_stored()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FeePerGramStat {
     pub order: u64,
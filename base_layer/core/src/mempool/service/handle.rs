@@ -30,6 +30,7 @@ use crate::{
         MempoolServiceError,
         StateResponse,
         StatsResponse,
+        TxAcceptanceResponse,
         TxStorageResponse,
     },
     transactions::transaction_components::Transaction,
@@ -83,6 +84,21 @@ impl MempoolHandle {
         }
     }
 
+    /// Validates the transaction against the mempool without inserting it
+    pub async fn test_submit_transaction(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<TxAcceptanceResponse, MempoolServiceError> {
+        match self
+            .inner
+            .call(MempoolRequest::TestSubmitTransaction(transaction))
+            .await??
+        {
+            MempoolResponse::TxAcceptance(response) => Ok(response),
+            _ => panic!("Incorrect response"),
+        }
+    }
+
     pub async fn get_fee_per_gram_stats(
         &mut self,
         count: usize,
@@ -36,6 +36,7 @@ pub enum MempoolRequest {
     GetState,
     GetTxStateByExcessSig(Signature),
     SubmitTransaction(Transaction),
+    TestSubmitTransaction(Transaction),
     GetFeePerGramStats { count: usize, tip_height: u64 },
 }
 
@@ -52,6 +53,11 @@ impl Display for MempoolRequest {
                 "SubmitTransaction ({})",
                 tx.body.kernels()[0].excess_sig.get_signature().to_hex()
             ),
+            MempoolRequest::TestSubmitTransaction(tx) => write!(
+                f,
+                "TestSubmitTransaction ({})",
+                tx.body.kernels()[0].excess_sig.get_signature().to_hex()
+            ),
             MempoolRequest::GetFeePerGramStats { count, tip_height } => {
                 write!(f, "GetFeePerGramStats(count: {}, tip_height: {})", *count, *tip_height)
             },
@@ -24,7 +24,7 @@ use std::{fmt, fmt::Formatter};
 
 use tari_common_types::waiting_requests::RequestKey;
 
-use crate::mempool::{FeePerGramStat, StateResponse, StatsResponse, TxStorageResponse};
+use crate::mempool::{FeePerGramStat, StateResponse, StatsResponse, TxAcceptanceResponse, TxStorageResponse};
 
 /// API Response enum for Mempool responses.
 #[derive(Clone, Debug)]
@@ -32,16 +32,18 @@ pub enum MempoolResponse {
     Stats(StatsResponse),
     State(StateResponse),
     TxStorage(TxStorageResponse),
+    TxAcceptance(TxAcceptanceResponse),
     FeePerGramStats { response: Vec<FeePerGramStat> },
 }
 
 impl fmt::Display for MempoolResponse {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        use MempoolResponse::{FeePerGramStats, State, Stats, TxStorage};
+        use MempoolResponse::{FeePerGramStats, State, Stats, TxAcceptance, TxStorage};
         match &self {
             Stats(_) => write!(f, "Stats"),
             State(_) => write!(f, "State"),
             TxStorage(_) => write!(f, "TxStorage"),
+            TxAcceptance(_) => write!(f, "TxAcceptance"),
             FeePerGramStats { response } => write!(f, "FeePerGramStats({} item(s))", response.len()),
         }
     }
@@ -34,6 +34,7 @@ use crate::mempool::{
     MempoolServiceError,
     StateResponse,
     StatsResponse,
+    TxAcceptanceResponse,
     TxStorageResponse,
 };
 
@@ -51,6 +52,7 @@ pub struct MempoolMockState {
     get_state: Arc<Mutex<StateResponse>>,
     get_tx_state_by_excess_sig: Arc<Mutex<TxStorageResponse>>,
     submit_transaction: Arc<Mutex<TxStorageResponse>>,
+    test_submit_transaction: Arc<Mutex<TxAcceptanceResponse>>,
     calls: Arc<AtomicUsize>,
 }
 
@@ -68,6 +70,11 @@ impl Default for MempoolMockState {
             })),
             get_tx_state_by_excess_sig: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
             submit_transaction: Arc::new(Mutex::new(TxStorageResponse::NotStored)),
+            test_submit_transaction: Arc::new(Mutex::new(TxAcceptanceResponse {
+                storage: TxStorageResponse::NotStored,
+                fee_per_gram: 0.into(),
+                rejection_reason: None,
+            })),
             calls: Arc::new(Default::default()),
         }
     }
@@ -90,6 +97,10 @@ impl MempoolMockState {
         *self.submit_transaction.lock().await = resp;
     }
 
+    pub async fn set_test_submit_transaction_response(&self, resp: TxAcceptanceResponse) {
+        *self.test_submit_transaction.lock().await = resp;
+    }
+
     fn inc_call_count(&self) {
         self.calls.fetch_add(1, Ordering::SeqCst);
     }
@@ -124,7 +135,14 @@ impl MempoolServiceMock {
     }
 
     async fn handle_request(&self, req: MempoolRequest) -> Result<MempoolResponse, MempoolServiceError> {
-        use MempoolRequest::{GetFeePerGramStats, GetState, GetStats, GetTxStateByExcessSig, SubmitTransaction};
+        use MempoolRequest::{
+            GetFeePerGramStats,
+            GetState,
+            GetStats,
+            GetTxStateByExcessSig,
+            SubmitTransaction,
+            TestSubmitTransaction,
+        };
 
         self.state.inc_call_count();
         match req {
@@ -136,9 +154,37 @@ impl MempoolServiceMock {
             SubmitTransaction(_) => Ok(MempoolResponse::TxStorage(
                 self.state.submit_transaction.lock().await.clone(),
             )),
+            TestSubmitTransaction(_) => Ok(MempoolResponse::TxAcceptance(
+                self.state.test_submit_transaction.lock().await.clone(),
+            )),
             GetFeePerGramStats { .. } => {
                 unimplemented!()
             },
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::PrivateKey;
+
+    use super::*;
+    use crate::transactions::transaction_components::Transaction;
+
+    #[tokio::test]
+    async fn it_responds_to_test_submissions_without_storing() {
+        let (mut handle, state) = create_mempool_service_mock();
+        let expected = TxAcceptanceResponse {
+            storage: TxStorageResponse::UnconfirmedPool,
+            fee_per_gram: 25.into(),
+            rejection_reason: None,
+        };
+        state.set_test_submit_transaction_response(expected.clone()).await;
+
+        let transaction = Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default());
+        let response = handle.test_submit_transaction(transaction).await.unwrap();
+        assert_eq!(response, expected);
+        assert!(response.is_accepted());
+        assert_eq!(state.get_call_count(), 1);
+    }
+}
@@ -88,6 +88,7 @@ pub struct UnconfirmedPool {
     txs_by_signature: HashMap<PrivateKey, Vec<TransactionKey>>,
     tx_by_priority: BTreeMap<FeePriority, TransactionKey>,
     txs_by_output: HashMap<HashOutput, Vec<TransactionKey>>,
+    txs_by_dependent_output: HashMap<HashOutput, Vec<TransactionKey>>,
     txs_by_unique_id: HashMap<[u8; 32], Vec<TransactionKey>>,
 }
 
@@ -109,6 +110,7 @@ impl UnconfirmedPool {
             txs_by_signature: HashMap::new(),
             tx_by_priority: BTreeMap::new(),
             txs_by_output: HashMap::new(),
+            txs_by_dependent_output: HashMap::new(),
             txs_by_unique_id: HashMap::new(),
         }
     }
@@ -144,6 +146,12 @@ impl UnconfirmedPool {
         for output in prioritized_tx.transaction.body.outputs() {
             self.txs_by_output.entry(output.hash()).or_default().push(new_key);
         }
+        for output_hash in &prioritized_tx.dependent_output_hashes {
+            self.txs_by_dependent_output
+                .entry(*output_hash)
+                .or_default()
+                .push(new_key);
+        }
         for kernel in prioritized_tx.transaction.body.kernels() {
             let sig = kernel.excess_sig.get_signature();
             self.txs_by_signature.entry(sig.clone()).or_default().push(new_key);
@@ -176,6 +184,20 @@ impl UnconfirmedPool {
         Ok(())
     }
 
+    /// Returns false if the pool is full and the transaction has a lower priority than every transaction in it, in
+    /// which case [insert](Self::insert) would discard it.
+    pub fn has_capacity_for(
+        &self,
+        tx: Arc<Transaction>,
+        transaction_weighting: &TransactionWeight,
+    ) -> Result<bool, UnconfirmedPoolError> {
+        if self.tx_by_key.len() < self.config.storage_capacity {
+            return Ok(true);
+        }
+        let prioritized_tx = PrioritizedTransaction::new(0, transaction_weighting, tx, None)?;
+        Ok(prioritized_tx.priority >= *self.lowest_priority()?)
+    }
+
     /// Check if a transaction is available in the UnconfirmedPool
     pub fn has_tx_with_excess_sig(&self, excess_sig: &Signature) -> bool {
         self.txs_by_signature.contains_key(excess_sig.get_signature())
@@ -555,15 +577,54 @@ impl UnconfirmedPool {
     }
 
     fn lowest_priority(&self) -> Result<&FeePriority, UnconfirmedPoolError> {
-        self.tx_by_priority
-            .keys()
-            .next()
+        self.lowest_priority_evictable()
+            .map(|(priority, _)| priority)
             .ok_or(UnconfirmedPoolError::StorageOutofSync)
     }
 
     fn remove_lowest_priority_tx(&mut self) -> Result<(), UnconfirmedPoolError> {
-        if let Some(tx_key) = self.tx_by_priority.values().next().copied() {
-            self.remove_transaction(tx_key)?;
+        if let Some((_, tx_key)) = self.lowest_priority_evictable() {
+            self.remove_transaction_with_dependants(tx_key)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the lowest priority transaction that no other transaction in the pool spends from. A low fee parent
+    /// is kept while a child spending its outputs is in the pool, as the child pays for both of them.
+    fn lowest_priority_evictable(&self) -> Option<(&FeePriority, TransactionKey)> {
+        self.tx_by_priority
+            .iter()
+            .find(|(_, key)| !self.has_dependants(**key))
+            .map(|(priority, key)| (priority, *key))
+    }
+
+    /// Returns true if a transaction in the pool spends one of the outputs of the transaction
+    fn has_dependants(&self, tx_key: TransactionKey) -> bool {
+        self.tx_by_key.get(&tx_key).map_or(false, |tx| {
+            tx.transaction
+                .body
+                .outputs()
+                .iter()
+                .any(|o| self.txs_by_dependent_output.contains_key(&o.hash()))
+        })
+    }
+
+    /// Removes a transaction together with the transactions spending its outputs, which cannot be mined without it.
+    /// An output that another transaction in the pool also creates is still available, so its spenders are kept.
+    fn remove_transaction_with_dependants(&mut self, tx_key: TransactionKey) -> Result<(), UnconfirmedPoolError> {
+        let mut to_remove = vec![tx_key];
+        while let Some(key) = to_remove.pop() {
+            if let Some(transaction) = self.remove_transaction(key)? {
+                for output in transaction.body.outputs() {
+                    let output_hash = output.hash();
+                    if self.txs_by_output.contains_key(&output_hash) {
+                        continue;
+                    }
+                    if let Some(keys) = self.txs_by_dependent_output.get(&output_hash) {
+                        to_remove.extend(keys.iter().copied());
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -573,6 +634,7 @@ impl UnconfirmedPool {
         self.txs_by_signature.clear();
         self.tx_by_priority.clear();
         self.txs_by_output.clear();
+        self.txs_by_dependent_output.clear();
         self.tx_by_key.drain().map(|(_, val)| val.transaction).collect()
     }
 
@@ -739,6 +801,17 @@ impl UnconfirmedPool {
             }
         }
 
+        for output_hash in &prioritized_transaction.dependent_output_hashes {
+            if let Some(keys) = self.txs_by_dependent_output.get_mut(output_hash) {
+                if let Some(pos) = keys.iter().position(|k| *k == tx_key) {
+                    keys.remove(pos);
+                }
+                if keys.is_empty() {
+                    self.txs_by_dependent_output.remove(output_hash);
+                }
+            }
+        }
+
         trace!(
             target: LOG_TARGET,
             "Deleted transaction: {}",
@@ -839,6 +912,9 @@ impl UnconfirmedPool {
             self.txs_by_output
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
+            self.txs_by_dependent_output
+                .values()
+                .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key))) &&
             self.txs_by_unique_id
                 .values()
                 .all(|tx_keys| tx_keys.iter().all(|tx_key| self.tx_by_key.contains_key(tx_key)))
@@ -856,6 +932,7 @@ impl UnconfirmedPool {
         let (old, new) = shrink_hashmap(&mut self.tx_by_key);
         shrink_hashmap(&mut self.txs_by_signature);
         shrink_hashmap(&mut self.txs_by_output);
+        shrink_hashmap(&mut self.txs_by_dependent_output);
         shrink_hashmap(&mut self.txs_by_unique_id);
 
         if old > new {
@@ -983,6 +1060,106 @@ mod test {
         assert!(unconfirmed_pool.check_data_consistency());
     }
 
+    #[tokio::test]
+    async fn test_parent_is_not_evicted_while_child_pays_for_it() {
+        let key_manager = create_test_core_key_manager_with_memory_db();
+        let parent = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(2), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let child = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let other = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 2,
+            weight_tx_skip_count: 3,
+            min_fee: 0,
+        });
+
+        let tx_weight = TransactionWeight::latest();
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight).unwrap();
+        unconfirmed_pool
+            .insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]), &tx_weight)
+            .unwrap();
+        unconfirmed_pool.insert(other.clone(), None, &tx_weight).unwrap();
+
+        // The parent has the lowest fee, but the child spending its output outbids the new transaction
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&other.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
+    #[tokio::test]
+    async fn test_evicting_a_parent_evicts_its_dependants() {
+        let key_manager = create_test_core_key_manager_with_memory_db();
+        let parent = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let child = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let grandchild = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(20), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+        let other = Arc::new(
+            tx!(MicroMinotari(5_000), fee: MicroMinotari(5), inputs: 1, outputs: 1, &key_manager)
+                .expect("Failed to get tx")
+                .0,
+        );
+
+        let mut unconfirmed_pool = UnconfirmedPool::new(UnconfirmedPoolConfig {
+            storage_capacity: 10,
+            weight_tx_skip_count: 3,
+            min_fee: 0,
+        });
+
+        let tx_weight = TransactionWeight::latest();
+        unconfirmed_pool.insert(parent.clone(), None, &tx_weight).unwrap();
+        unconfirmed_pool
+            .insert(child.clone(), Some(vec![parent.body.outputs()[0].hash()]), &tx_weight)
+            .unwrap();
+        unconfirmed_pool
+            .insert(
+                grandchild.clone(),
+                Some(vec![child.body.outputs()[0].hash()]),
+                &tx_weight,
+            )
+            .unwrap();
+        unconfirmed_pool.insert(other.clone(), None, &tx_weight).unwrap();
+
+        // Only transactions that nothing spends from are eviction candidates, so the lowest fee one is evicted first
+        let (_, lowest) = unconfirmed_pool.lowest_priority_evictable().unwrap();
+        assert_eq!(
+            unconfirmed_pool.tx_by_key[&lowest].transaction.body.kernels()[0].excess_sig,
+            other.body.kernels()[0].excess_sig
+        );
+
+        let parent_key = unconfirmed_pool.txs_by_signature[parent.body.kernels()[0].excess_sig.get_signature()][0];
+        unconfirmed_pool.remove_transaction_with_dependants(parent_key).unwrap();
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&parent.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&child.body.kernels()[0].excess_sig));
+        assert!(!unconfirmed_pool.has_tx_with_excess_sig(&grandchild.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.has_tx_with_excess_sig(&other.body.kernels()[0].excess_sig));
+        assert!(unconfirmed_pool.txs_by_dependent_output.is_empty());
+        assert!(unconfirmed_pool.check_data_consistency());
+    }
+
     #[tokio::test]
     async fn test_double_spend_inputs() {
         let key_manager = create_test_core_key_manager_with_memory_db();
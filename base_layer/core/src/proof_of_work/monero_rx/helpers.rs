@@ -202,6 +202,16 @@ pub fn create_blockhashing_blob_from_block(block: &monero::Block) -> Result<Stri
     Ok(hex::encode(blob))
 }
 
+/// Calculates the Monero block hash (block id), i.e. the hash of the length-prefixed blockhashing blob
+pub fn calculate_monero_block_hash(block: &monero::Block) -> Result<monero::Hash, MergeMineError> {
+    let tx_hashes = create_ordered_transaction_hashes_from_block(block);
+    let root = tree_hash(&tx_hashes)?;
+    let blob = create_block_hashing_blob(&block.header, &root, tx_hashes.len() as u64);
+    let mut data = consensus::serialize(&VarInt(blob.len() as u64));
+    data.extend_from_slice(&blob);
+    Ok(monero::Hash::new(data))
+}
+
 /// Create a set of ordered transaction hashes from a Monero block
 pub fn create_ordered_transaction_hashes_from_block(block: &monero::Block) -> Vec<monero::Hash> {
     iter::once(block.miner_tx.hash())
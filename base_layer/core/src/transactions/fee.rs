@@ -22,9 +22,23 @@
 
 use std::cmp::max;
 
-use super::{tari_amount::MicroMinotari, weight::TransactionWeight};
+use super::{
+    tari_amount::MicroMinotari,
+    weight::{TransactionWeight, WeightBreakdown},
+};
 use crate::transactions::aggregated_body::AggregateBody;
 
+/// The fee for a transaction together with the consensus weight it was derived from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub weight: WeightBreakdown,
+    pub fee_per_gram: MicroMinotari,
+    /// The fee as calculated from the weight, before normalisation to the minimum fee
+    pub fee: MicroMinotari,
+    /// The fee that must be paid, i.e. `fee` raised to the minimum transaction fee if necessary
+    pub normalized_fee: MicroMinotari,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct Fee(TransactionWeight);
 
@@ -62,6 +76,41 @@ impl Fee {
         Ok(MicroMinotari::from(weight) * fee_per_gram)
     }
 
+    /// Computes the fee and weight breakdown for the given number of kernels, inputs, outputs and rounded up
+    /// features_and_scripts size. The fee is identical to the one returned by `calculate`.
+    pub fn breakdown(
+        &self,
+        fee_per_gram: MicroMinotari,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+        rounded_features_and_scripts_byte_size: usize,
+    ) -> FeeBreakdown {
+        let weight = self.weighting().breakdown(
+            num_kernels,
+            num_inputs,
+            num_outputs,
+            rounded_features_and_scripts_byte_size,
+        );
+        Self::fee_breakdown_from_weight(fee_per_gram, weight)
+    }
+
+    /// Computes the fee and weight breakdown of an aggregate body
+    pub fn breakdown_body(&self, fee_per_gram: MicroMinotari, body: &AggregateBody) -> std::io::Result<FeeBreakdown> {
+        let weight = self.weighting().breakdown_body(body)?;
+        Ok(Self::fee_breakdown_from_weight(fee_per_gram, weight))
+    }
+
+    fn fee_breakdown_from_weight(fee_per_gram: MicroMinotari, weight: WeightBreakdown) -> FeeBreakdown {
+        let fee = MicroMinotari::from(weight.total().saturating_mul(fee_per_gram.0));
+        FeeBreakdown {
+            weight,
+            fee_per_gram,
+            fee,
+            normalized_fee: Self::normalize(fee),
+        }
+    }
+
     /// Normalizes the given fee returning a fee that is equal to or above the minimum fee
     pub fn normalize(fee: MicroMinotari) -> MicroMinotari {
         max(Self::MINIMUM_TRANSACTION_FEE, fee)
@@ -125,6 +174,9 @@ mod test {
             fee.calculate_body(100.into(), &aggregate_body)
                 .unwrap_or_else(|e| panic!("Failed with error: {}", e)),
             fee.calculate(100.into(), 0, 1, 0, 0)
-        )
+        );
+        let breakdown = fee.breakdown_body(100.into(), &aggregate_body).unwrap();
+        assert_eq!(breakdown.fee, fee.calculate(100.into(), 0, 1, 0, 0));
+        assert_eq!(breakdown.weight.num_inputs, 1);
     }
 }
@@ -0,0 +1,705 @@
+// Copyright 2023 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+//! FROST threshold Schnorr signatures. The participants of a FROST session share one group key, fixed when the session
+//! is set up, and any `threshold` of them can sign for it. The resulting signature is an ordinary Schnorr signature, so
+//! it cannot be told apart from the signature of a single key.
+//!
+//! The group key is generated without a dealer. Every participant `i` picks a secret polynomial `f_i` of degree
+//! `threshold - 1`, publishes commitments to its coefficients with a proof of possession of the constant term and sends
+//! `f_i(j)` privately to every other participant `j`. The signing share of `j` is the sum of the shares it received,
+//! the group key is the sum of the constant term commitments.
+//!
+//! Signing takes two rounds: every signer publishes a hiding and a binding nonce commitment, and once all commitments
+//! are in, returns its signature share. The binding factors tie every nonce to the signing target and to the full set
+//! of commitments, so that the nonces can be published without being committed to first.
+//!
+//! Outputs are locked to the group with script keys derived from the group key by a public tweak, so that outputs do
+//! not share a script key. A signing of a script key signs the script key part of the input's script signature, the
+//! tweak is added when the shares are aggregated.
+
+use blake2::Blake2b;
+use digest::consts::{U32, U64};
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey, Signature};
+use tari_crypto::{
+    hash_domain,
+    hashing::DomainSeparatedHasher,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
+use tari_utilities::ByteArray;
+use thiserror::Error;
+
+use crate::transactions::transaction_components::{TransactionInput, TransactionInputVersion};
+
+hash_domain!(FrostHashDomain, "com.tari.base_layer.core.transactions.frost", 0);
+
+/// The order of the Ristretto group minus two, little-endian. Raising a scalar to this power inverts it.
+const GROUP_ORDER_MINUS_TWO: [u8; 32] = [
+    0xeb, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum FrostError {
+    #[error("Participant indices start at 1")]
+    ZeroParticipantIndex,
+    #[error("Participant {0} is listed more than once")]
+    DuplicateParticipant(u16),
+    #[error("Participant {0} is not one of the signers")]
+    NotASigner(u16),
+    #[error("A threshold of 0 is not possible")]
+    ZeroThreshold,
+    #[error("Could not derive a key: {0}")]
+    KeyDerivation(String),
+}
+
+/// A participant's index is its position in the participant list of the session, counted from 1
+pub fn frost_participant_scalar(participant: u16) -> PrivateKey {
+    PrivateKey::from(u64::from(participant))
+}
+
+/// A secret polynomial of degree `threshold - 1` of the key generation
+pub struct FrostPolynomial {
+    coefficients: Vec<PrivateKey>,
+}
+
+impl FrostPolynomial {
+    /// Derives the polynomial from its constant term, so that a wallet only has to keep the constant term to answer for
+    /// its polynomial later
+    pub fn derive(secret: &PrivateKey, threshold: u8) -> Result<Self, FrostError> {
+        if threshold == 0 {
+            return Err(FrostError::ZeroThreshold);
+        }
+        let mut coefficients = vec![secret.clone()];
+        for degree in 1..threshold {
+            let hasher = DomainSeparatedHasher::<Blake2b<U64>, FrostHashDomain>::new_with_label("coefficient")
+                .chain(secret.as_bytes())
+                .chain([degree]);
+            let coefficient = PrivateKey::from_uniform_bytes(digest::Digest::finalize(hasher).as_ref())
+                .map_err(|e| FrostError::KeyDerivation(e.to_string()))?;
+            coefficients.push(coefficient);
+        }
+        Ok(Self { coefficients })
+    }
+
+    /// The commitments to the coefficients that every participant verifies its share against
+    pub fn commitments(&self) -> Vec<PublicKey> {
+        self.coefficients.iter().map(PublicKey::from_secret_key).collect()
+    }
+
+    /// The share of `participant`
+    pub fn evaluate(&self, participant: u16) -> PrivateKey {
+        let x = frost_participant_scalar(participant);
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(PrivateKey::default(), |acc, coefficient| &(&acc * &x) + coefficient)
+    }
+}
+
+/// The challenge of the proof of possession of a polynomial's constant term, which stops a participant from choosing
+/// its polynomial as a function of the others' and taking control of the group key
+pub fn frost_dkg_proof_challenge(
+    participant: u16,
+    session_id: u64,
+    secret_commitment: &PublicKey,
+    public_nonce: &PublicKey,
+) -> [u8; 64] {
+    let hasher = DomainSeparatedHasher::<Blake2b<U64>, FrostHashDomain>::new_with_label("dkg_proof")
+        .chain(participant.to_le_bytes())
+        .chain(session_id.to_le_bytes())
+        .chain(secret_commitment.as_bytes())
+        .chain(public_nonce.as_bytes());
+    digest::Digest::finalize(hasher).into()
+}
+
+pub fn verify_frost_dkg_proof(participant: u16, session_id: u64, commitments: &[PublicKey], proof: &Signature) -> bool {
+    match commitments.first() {
+        Some(secret_commitment) => {
+            let challenge =
+                frost_dkg_proof_challenge(participant, session_id, secret_commitment, proof.get_public_nonce());
+            proof.verify_raw_uniform(secret_commitment, &challenge)
+        },
+        None => false,
+    }
+}
+
+/// The public key of the share `participant` gets from the polynomial behind `commitments`
+pub fn evaluate_frost_commitments(commitments: &[PublicKey], participant: u16) -> PublicKey {
+    let x = frost_participant_scalar(participant);
+    commitments
+        .iter()
+        .rev()
+        .fold(PublicKey::default(), |acc, commitment| &(&x * &acc) + commitment)
+}
+
+/// Checks a received share, given as its public key, against the sender's coefficient commitments
+pub fn verify_frost_share(share_public_key: &PublicKey, commitments: &[PublicKey], participant: u16) -> bool {
+    !commitments.is_empty() && &evaluate_frost_commitments(commitments, participant) == share_public_key
+}
+
+/// The group key: the sum of the constant term commitments of all participants
+pub fn frost_group_public_key<'a, I: IntoIterator<Item = &'a [PublicKey]>>(commitments: I) -> PublicKey {
+    commitments
+        .into_iter()
+        .filter_map(|c| c.first())
+        .fold(PublicKey::default(), |acc, key| &acc + key)
+}
+
+/// The public key of the signing share of `participant`, which its signature shares are verified against
+pub fn frost_verification_share<'a, I: IntoIterator<Item = &'a [PublicKey]>>(
+    commitments: I,
+    participant: u16,
+) -> PublicKey {
+    commitments.into_iter().fold(PublicKey::default(), |acc, c| {
+        &acc + &evaluate_frost_commitments(c, participant)
+    })
+}
+
+/// The Lagrange coefficient that weighs the signing share of `participant` when `signers` sign together
+pub fn frost_lagrange_coefficient(participant: u16, signers: &[u16]) -> Result<PrivateKey, FrostError> {
+    validate_participants(signers)?;
+    if !signers.contains(&participant) {
+        return Err(FrostError::NotASigner(participant));
+    }
+    let x_i = frost_participant_scalar(participant);
+    let mut numerator = frost_participant_scalar(1);
+    let mut denominator = frost_participant_scalar(1);
+    for signer in signers.iter().filter(|s| **s != participant) {
+        let x_j = frost_participant_scalar(*signer);
+        numerator = &numerator * &x_j;
+        denominator = &denominator * &(&x_j - &x_i);
+    }
+    Ok(&numerator * &invert_scalar(&denominator))
+}
+
+/// The hiding and binding nonce commitments of a signer
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrostNonceCommitment {
+    pub participant: u16,
+    pub hiding: PublicKey,
+    pub binding: PublicKey,
+}
+
+/// What a group signature signs. Either a plain message, or the script key part of the script signature of a
+/// transaction input, so that an output can be locked to the group key.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrostSigningTarget {
+    /// A Schnorr signature of the message by the group key
+    Message([u8; 32]),
+    /// The script key part of the script signature of an input whose script key is the group key tweaked by
+    /// `key_tweak`, see [frost_script_key_tweak]. The host adds the commitment part.
+    ScriptSignature {
+        key_tweak: PrivateKey,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: Commitment,
+        commitment: Commitment,
+        script_message: [u8; 32],
+    },
+}
+
+impl FrostSigningTarget {
+    /// The public key the signature verifies against
+    pub fn signing_key(&self, group_public_key: &PublicKey) -> PublicKey {
+        match self {
+            FrostSigningTarget::Message(_) => group_public_key.clone(),
+            FrostSigningTarget::ScriptSignature { key_tweak, .. } => {
+                group_public_key + &PublicKey::from_secret_key(key_tweak)
+            },
+        }
+    }
+
+    /// The part of the signature made by the tweak rather than by the signing shares
+    fn key_tweak(&self) -> Option<&PrivateKey> {
+        match self {
+            FrostSigningTarget::Message(_) => None,
+            FrostSigningTarget::ScriptSignature { key_tweak, .. } => Some(key_tweak),
+        }
+    }
+
+    /// Commits to everything that is signed, the binding factors are bound to it
+    fn binding_message(&self) -> [u8; 32] {
+        let hasher = match self {
+            FrostSigningTarget::Message(message) => {
+                DomainSeparatedHasher::<Blake2b<U32>, FrostHashDomain>::new_with_label("message_target").chain(message)
+            },
+            FrostSigningTarget::ScriptSignature {
+                key_tweak,
+                txi_version,
+                ephemeral_commitment,
+                commitment,
+                script_message,
+            } => DomainSeparatedHasher::<Blake2b<U32>, FrostHashDomain>::new_with_label("script_signature_target")
+                .chain(key_tweak.as_bytes())
+                .chain([txi_version.as_u8()])
+                .chain(ephemeral_commitment.as_bytes())
+                .chain(commitment.as_bytes())
+                .chain(script_message),
+        };
+        digest::Digest::finalize(hasher).into()
+    }
+
+    /// The challenge of the signature, which for a script signature is the challenge of the input's script signature
+    pub fn challenge(&self, group_nonce: &PublicKey, group_public_key: &PublicKey) -> Result<PrivateKey, FrostError> {
+        match self {
+            FrostSigningTarget::Message(message) => {
+                frost_signature_challenge(group_nonce, group_public_key, message)
+            },
+            FrostSigningTarget::ScriptSignature {
+                txi_version,
+                ephemeral_commitment,
+                commitment,
+                script_message,
+                ..
+            } => {
+                let challenge = TransactionInput::finalize_script_signature_challenge(
+                    txi_version,
+                    ephemeral_commitment,
+                    group_nonce,
+                    &self.signing_key(group_public_key),
+                    commitment,
+                    script_message,
+                );
+                PrivateKey::from_uniform_bytes(&challenge).map_err(|e| FrostError::KeyDerivation(e.to_string()))
+            },
+        }
+    }
+}
+
+/// The tweak that turns the group key into the script key at `index` of a key branch. Every output locked to the group
+/// gets its own script key, while any `threshold` participants can still sign for it.
+pub fn frost_script_key_tweak(group_public_key: &PublicKey, branch: &str, index: u64) -> Result<PrivateKey, FrostError> {
+    let hasher = DomainSeparatedHasher::<Blake2b<U64>, FrostHashDomain>::new_with_label("script_key_tweak")
+        .chain(group_public_key.as_bytes())
+        .chain((branch.len() as u64).to_le_bytes())
+        .chain(branch.as_bytes())
+        .chain(index.to_le_bytes());
+    PrivateKey::from_uniform_bytes(digest::Digest::finalize(hasher).as_ref())
+        .map_err(|e| FrostError::KeyDerivation(e.to_string()))
+}
+
+/// A hash of everything the key generation of a session agreed on. Participants compare it once they have the group
+/// key, so that a participant that sent different commitments to different participants is caught before the group
+/// key is used.
+pub fn frost_dkg_transcript_hash<'a, I: IntoIterator<Item = &'a [PublicKey]>>(
+    session_id: u64,
+    threshold: u8,
+    commitments: I,
+) -> [u8; 32] {
+    let mut hasher = DomainSeparatedHasher::<Blake2b<U32>, FrostHashDomain>::new_with_label("dkg_transcript")
+        .chain(session_id.to_le_bytes())
+        .chain([threshold]);
+    for (participant, participant_commitments) in commitments.into_iter().enumerate() {
+        hasher = hasher
+            .chain((participant as u64).to_le_bytes())
+            .chain((participant_commitments.len() as u64).to_le_bytes());
+        for commitment in participant_commitments {
+            hasher = hasher.chain(commitment.as_bytes());
+        }
+    }
+    digest::Digest::finalize(hasher).into()
+}
+
+/// The binding factor of `participant`, which commits its binding nonce to the signing target and to every signer's
+/// nonce commitments
+pub fn frost_binding_factor(
+    participant: u16,
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+) -> Result<PrivateKey, FrostError> {
+    let mut hasher = DomainSeparatedHasher::<Blake2b<U64>, FrostHashDomain>::new_with_label("binding_factor")
+        .chain(participant.to_le_bytes())
+        .chain(group_public_key.as_bytes())
+        .chain(target.binding_message());
+    for commitment in sorted_commitments(commitments)? {
+        hasher = hasher
+            .chain(commitment.participant.to_le_bytes())
+            .chain(commitment.hiding.as_bytes())
+            .chain(commitment.binding.as_bytes());
+    }
+    PrivateKey::from_uniform_bytes(digest::Digest::finalize(hasher).as_ref())
+        .map_err(|e| FrostError::KeyDerivation(e.to_string()))
+}
+
+/// The public nonce of the signature
+pub fn frost_group_nonce(
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+) -> Result<PublicKey, FrostError> {
+    let mut group_nonce = PublicKey::default();
+    for commitment in commitments {
+        let binding_factor = frost_binding_factor(commitment.participant, group_public_key, target, commitments)?;
+        group_nonce = &(&group_nonce + &commitment.hiding) + &(&binding_factor * &commitment.binding);
+    }
+    Ok(group_nonce)
+}
+
+/// The challenge of a plain Schnorr signature of `message`, so that the group signature verifies like any other
+pub fn frost_signature_challenge(
+    group_nonce: &PublicKey,
+    group_public_key: &PublicKey,
+    message: &[u8; 32],
+) -> Result<PrivateKey, FrostError> {
+    let challenge =
+        Signature::construct_domain_separated_challenge::<_, Blake2b<U64>>(group_nonce, group_public_key, message);
+    PrivateKey::from_uniform_bytes(challenge.as_ref()).map_err(|e| FrostError::KeyDerivation(e.to_string()))
+}
+
+/// The signature share of `participant`: `d + e * rho + lambda * s * c`
+pub fn frost_signature_share(
+    signing_share: &PrivateKey,
+    hiding_nonce: &PrivateKey,
+    binding_nonce: &PrivateKey,
+    participant: u16,
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+) -> Result<PrivateKey, FrostError> {
+    let (binding_factor, lagrange_coefficient, challenge) =
+        share_factors(participant, group_public_key, target, commitments)?;
+    Ok(&(hiding_nonce + &(binding_nonce * &binding_factor)) + &(&(&lagrange_coefficient * signing_share) * &challenge))
+}
+
+/// Checks a single signer's share against its verification share, so that a bad share can be attributed to its signer
+pub fn verify_frost_signature_share(
+    signature_share: &PrivateKey,
+    verification_share: &PublicKey,
+    participant: u16,
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+) -> Result<bool, FrostError> {
+    let commitment = commitments
+        .iter()
+        .find(|c| c.participant == participant)
+        .ok_or(FrostError::NotASigner(participant))?;
+    let (binding_factor, lagrange_coefficient, challenge) =
+        share_factors(participant, group_public_key, target, commitments)?;
+    let expected = &(&commitment.hiding + &(&binding_factor * &commitment.binding)) +
+        &(&(&lagrange_coefficient * &challenge) * verification_share);
+    Ok(PublicKey::from_secret_key(signature_share) == expected)
+}
+
+/// Sums the signature shares of all signers into the group signature. For a script signature, the returned signature
+/// is the script key part: its public nonce and `u_y`.
+pub fn aggregate_frost_signature<'a, I: IntoIterator<Item = &'a PrivateKey>>(
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+    signature_shares: I,
+) -> Result<Signature, FrostError> {
+    let group_nonce = frost_group_nonce(group_public_key, target, commitments)?;
+    let mut signature = signature_shares
+        .into_iter()
+        .fold(PrivateKey::default(), |acc, share| &acc + share);
+    if let Some(key_tweak) = target.key_tweak() {
+        let challenge = target.challenge(&group_nonce, group_public_key)?;
+        signature = &signature + &(&challenge * key_tweak);
+    }
+    Ok(Signature::new(group_nonce, signature))
+}
+
+pub fn verify_frost_signature(
+    signature: &Signature,
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+) -> Result<bool, FrostError> {
+    let challenge = target.challenge(signature.get_public_nonce(), group_public_key)?;
+    let expected = signature.get_public_nonce() + &(&challenge * &target.signing_key(group_public_key));
+    Ok(PublicKey::from_secret_key(signature.get_signature()) == expected)
+}
+
+/// The share of `participant` of the script offset of a transaction spending `script_key_count` inputs locked to the
+/// group, minus the participant's parts of the joint sender offset keys. The shares of all signers add up to the group
+/// part of the offset, without the tweaks, which the host adds. A participant must never answer twice for the same
+/// sender offset key parts, as the difference of two answers would reveal its signing share.
+pub fn frost_script_offset_share<'a, I: IntoIterator<Item = &'a PrivateKey>>(
+    signing_share: &PrivateKey,
+    participant: u16,
+    signers: &[u16],
+    script_key_count: u64,
+    sender_offset_key_parts: I,
+) -> Result<PrivateKey, FrostError> {
+    let lagrange_coefficient = frost_lagrange_coefficient(participant, signers)?;
+    let script_share = &(&lagrange_coefficient * signing_share) * &PrivateKey::from(script_key_count);
+    Ok(sender_offset_key_parts
+        .into_iter()
+        .fold(script_share, |acc, part| &acc - part))
+}
+
+fn share_factors(
+    participant: u16,
+    group_public_key: &PublicKey,
+    target: &FrostSigningTarget,
+    commitments: &[FrostNonceCommitment],
+) -> Result<(PrivateKey, PrivateKey, PrivateKey), FrostError> {
+    let signers = commitments.iter().map(|c| c.participant).collect::<Vec<_>>();
+    let binding_factor = frost_binding_factor(participant, group_public_key, target, commitments)?;
+    let lagrange_coefficient = frost_lagrange_coefficient(participant, &signers)?;
+    let group_nonce = frost_group_nonce(group_public_key, target, commitments)?;
+    let challenge = target.challenge(&group_nonce, group_public_key)?;
+    Ok((binding_factor, lagrange_coefficient, challenge))
+}
+
+fn sorted_commitments(commitments: &[FrostNonceCommitment]) -> Result<Vec<&FrostNonceCommitment>, FrostError> {
+    validate_participants(&commitments.iter().map(|c| c.participant).collect::<Vec<_>>())?;
+    let mut sorted = commitments.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|c| c.participant);
+    Ok(sorted)
+}
+
+fn validate_participants(participants: &[u16]) -> Result<(), FrostError> {
+    for (i, participant) in participants.iter().enumerate() {
+        if *participant == 0 {
+            return Err(FrostError::ZeroParticipantIndex);
+        }
+        if participants[..i].contains(participant) {
+            return Err(FrostError::DuplicateParticipant(*participant));
+        }
+    }
+    Ok(())
+}
+
+/// Inverts a non-zero scalar by raising it to the power of the group order minus two
+fn invert_scalar(scalar: &PrivateKey) -> PrivateKey {
+    let mut result = frost_participant_scalar(1);
+    for byte in GROUP_ORDER_MINUS_TWO.iter().rev() {
+        for bit in (0..8).rev() {
+            result = &result * &result;
+            if (byte >> bit) & 1 == 1 {
+                result = &result * scalar;
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_common_types::types::CommitmentFactory;
+    use tari_crypto::commitment::HomomorphicCommitmentFactory;
+
+    use super::*;
+
+    /// Runs the key generation of a 2-of-3 session, returning the signing shares, the coefficient commitments and the
+    /// group key
+    fn two_of_three_key_generation() -> (Vec<PrivateKey>, Vec<Vec<PublicKey>>, PublicKey) {
+        let session_id = 42;
+        let polynomials = (0..3)
+            .map(|_| FrostPolynomial::derive(&PrivateKey::random(&mut OsRng), 2).unwrap())
+            .collect::<Vec<_>>();
+        let commitments = polynomials.iter().map(|p| p.commitments()).collect::<Vec<_>>();
+        for (i, polynomial) in polynomials.iter().enumerate() {
+            let participant = i as u16 + 1;
+            let secret = &polynomial.coefficients[0];
+            let (nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+            let challenge = frost_dkg_proof_challenge(participant, session_id, &commitments[i][0], &public_nonce);
+            let proof = Signature::sign_raw_uniform(secret, nonce, &challenge).unwrap();
+            assert!(verify_frost_dkg_proof(participant, session_id, &commitments[i], &proof));
+            assert!(!verify_frost_dkg_proof(
+                participant + 1,
+                session_id,
+                &commitments[i],
+                &proof
+            ));
+        }
+
+        let signing_shares = (1..=3u16)
+            .map(|participant| {
+                polynomials
+                    .iter()
+                    .zip(&commitments)
+                    .fold(PrivateKey::default(), |acc, (p, c)| {
+                        let share = p.evaluate(participant);
+                        assert!(verify_frost_share(&PublicKey::from_secret_key(&share), c, participant));
+                        assert!(!verify_frost_share(
+                            &PublicKey::from_secret_key(&share),
+                            c,
+                            participant % 3 + 1
+                        ));
+                        &acc + &share
+                    })
+            })
+            .collect::<Vec<_>>();
+        let group_public_key = frost_group_public_key(commitments.iter().map(|c| c.as_slice()));
+        (signing_shares, commitments, group_public_key)
+    }
+
+    /// Signs the target with the given signers, checking every share, and returns the nonce commitments and shares
+    fn sign(
+        signers: &[u16],
+        signing_shares: &[PrivateKey],
+        commitments: &[Vec<PublicKey>],
+        group_public_key: &PublicKey,
+        target: &FrostSigningTarget,
+    ) -> (Vec<FrostNonceCommitment>, Vec<PrivateKey>) {
+        let nonces = signers
+            .iter()
+            .map(|_| (PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng)))
+            .collect::<Vec<_>>();
+        let nonce_commitments = signers
+            .iter()
+            .zip(&nonces)
+            .map(|(participant, (hiding, binding))| FrostNonceCommitment {
+                participant: *participant,
+                hiding: PublicKey::from_secret_key(hiding),
+                binding: PublicKey::from_secret_key(binding),
+            })
+            .collect::<Vec<_>>();
+        let shares = signers
+            .iter()
+            .zip(&nonces)
+            .map(|(participant, (hiding, binding))| {
+                let share = frost_signature_share(
+                    &signing_shares[usize::from(*participant) - 1],
+                    hiding,
+                    binding,
+                    *participant,
+                    group_public_key,
+                    target,
+                    &nonce_commitments,
+                )
+                .unwrap();
+                let verification_share =
+                    frost_verification_share(commitments.iter().map(|c| c.as_slice()), *participant);
+                assert!(verify_frost_signature_share(
+                    &share,
+                    &verification_share,
+                    *participant,
+                    group_public_key,
+                    target,
+                    &nonce_commitments
+                )
+                .unwrap());
+                share
+            })
+            .collect::<Vec<_>>();
+        (nonce_commitments, shares)
+    }
+
+    #[test]
+    fn any_two_of_three_participants_sign_for_the_group_key() {
+        let (signing_shares, commitments, group_public_key) = two_of_three_key_generation();
+
+        let message = [7u8; 32];
+        let target = FrostSigningTarget::Message(message);
+        for signers in [[1u16, 2], [1, 3], [3, 2]] {
+            let (nonce_commitments, shares) =
+                sign(&signers, &signing_shares, &commitments, &group_public_key, &target);
+            let signature = aggregate_frost_signature(&group_public_key, &target, &nonce_commitments, &shares).unwrap();
+            assert!(verify_frost_signature(&signature, &group_public_key, &target).unwrap());
+            assert!(signature.verify(&group_public_key, &message));
+            assert!(!verify_frost_signature(&signature, &group_public_key, &FrostSigningTarget::Message([8u8; 32])).unwrap());
+            let incomplete =
+                aggregate_frost_signature(&group_public_key, &target, &nonce_commitments, &shares[..1]).unwrap();
+            assert!(!verify_frost_signature(&incomplete, &group_public_key, &target).unwrap());
+        }
+    }
+
+    #[test]
+    fn two_of_three_participants_sign_for_a_script_key_of_the_group() {
+        let (signing_shares, commitments, group_public_key) = two_of_three_key_generation();
+        let key_tweak = frost_script_key_tweak(&group_public_key, "script key", 5).unwrap();
+        assert_ne!(
+            key_tweak,
+            frost_script_key_tweak(&group_public_key, "script key", 6).unwrap()
+        );
+
+        let factory = CommitmentFactory::default();
+        let spend_key = PrivateKey::random(&mut OsRng);
+        let value = PrivateKey::from(1000u64);
+        let (r_a, r_x) = (PrivateKey::random(&mut OsRng), PrivateKey::random(&mut OsRng));
+        let target = FrostSigningTarget::ScriptSignature {
+            key_tweak: key_tweak.clone(),
+            txi_version: TransactionInputVersion::get_current_version(),
+            ephemeral_commitment: factory.commit(&r_x, &r_a),
+            commitment: factory.commit(&spend_key, &value),
+            script_message: [3u8; 32],
+        };
+        let (nonce_commitments, shares) = sign(&[2, 3], &signing_shares, &commitments, &group_public_key, &target);
+        let signature = aggregate_frost_signature(&group_public_key, &target, &nonce_commitments, &shares).unwrap();
+        assert!(verify_frost_signature(&signature, &group_public_key, &target).unwrap());
+
+        // The group signature is the script key part of a script signature for the tweaked key
+        let script_private_key = &(&(&signing_shares[0] * &PrivateKey::from(2u64)) - &signing_shares[1]) + &key_tweak;
+        assert_eq!(
+            PublicKey::from_secret_key(&script_private_key),
+            target.signing_key(&group_public_key)
+        );
+        let challenge = target.challenge(signature.get_public_nonce(), &group_public_key).unwrap();
+        assert_eq!(
+            PublicKey::from_secret_key(signature.get_signature()),
+            signature.get_public_nonce() + &(&challenge * &target.signing_key(&group_public_key))
+        );
+    }
+
+    #[test]
+    fn script_offset_shares_add_up_to_the_group_part_of_the_offset() {
+        let (signing_shares, _, group_public_key) = two_of_three_key_generation();
+        let signers = [1u16, 3];
+        let offset_parts = signers
+            .iter()
+            .map(|_| PrivateKey::random(&mut OsRng))
+            .collect::<Vec<_>>();
+        let offset = signers
+            .iter()
+            .zip(&offset_parts)
+            .map(|(participant, part)| {
+                frost_script_offset_share(
+                    &signing_shares[usize::from(*participant) - 1],
+                    *participant,
+                    &signers,
+                    3,
+                    [part],
+                )
+                .unwrap()
+            })
+            .fold(PrivateKey::default(), |acc, share| &acc + &share);
+        let joint_offset_key = offset_parts
+            .iter()
+            .fold(PublicKey::default(), |acc, part| &acc + &PublicKey::from_secret_key(part));
+        assert_eq!(
+            PublicKey::from_secret_key(&offset),
+            &(&PrivateKey::from(3u64) * &group_public_key) - &joint_offset_key
+        );
+    }
+
+    #[test]
+    fn transcript_hash_depends_on_every_commitment() {
+        let (_, commitments, _) = two_of_three_key_generation();
+        let hash = frost_dkg_transcript_hash(1, 2, commitments.iter().map(|c| c.as_slice()));
+        assert_eq!(
+            hash,
+            frost_dkg_transcript_hash(1, 2, commitments.iter().map(|c| c.as_slice()))
+        );
+        assert_ne!(
+            hash,
+            frost_dkg_transcript_hash(2, 2, commitments.iter().map(|c| c.as_slice()))
+        );
+        let mut equivocated = commitments.clone();
+        equivocated[1][1] = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        assert_ne!(
+            hash,
+            frost_dkg_transcript_hash(1, 2, equivocated.iter().map(|c| c.as_slice()))
+        );
+    }
+
+    #[test]
+    fn lagrange_coefficients_reject_bad_signer_sets() {
+        assert_eq!(
+            frost_lagrange_coefficient(1, &[1, 1]),
+            Err(FrostError::DuplicateParticipant(1))
+        );
+        assert_eq!(
+            frost_lagrange_coefficient(1, &[0, 1]),
+            Err(FrostError::ZeroParticipantIndex)
+        );
+        assert_eq!(frost_lagrange_coefficient(3, &[1, 2]), Err(FrostError::NotASigner(3)));
+        let inverse = invert_scalar(&frost_participant_scalar(7));
+        assert_eq!(&inverse * &frost_participant_scalar(7), frost_participant_scalar(1));
+    }
+}
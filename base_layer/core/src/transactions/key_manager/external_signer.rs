@@ -0,0 +1,1120 @@
+//  Copyright 2023, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use rand::rngs::OsRng;
+use tari_common_types::types::{
+    ComAndPubSignature,
+    Commitment,
+    CommitmentFactory,
+    PrivateKey,
+    PublicKey,
+    RangeProof,
+    Signature,
+};
+use tari_comms::types::CommsDHKE;
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    hashing::DomainSeparatedHash,
+    keys::SecretKey,
+    ristretto::RistrettoComSig,
+};
+use tari_key_manager::key_manager_service::{AddResult, KeyManagerInterface, KeyManagerServiceError};
+
+use crate::{
+    common::one_sided::shared_secret_to_stealth_domain_hasher,
+    transactions::{
+        frost::{FrostNonceCommitment, FrostSigningTarget},
+        key_manager::{
+            interface::{SecretTransactionKeyManagerInterface, TxoStage},
+            TariKeyId,
+            TransactionKeyManagerBranch,
+            TransactionKeyManagerInterface,
+            ViewKeyExport,
+        },
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            EncryptedData,
+            KernelFeatures,
+            RangeProofType,
+            TransactionError,
+            TransactionInput,
+            TransactionInputVersion,
+            TransactionKernelVersion,
+            TransactionOutput,
+            TransactionOutputVersion,
+        },
+    },
+};
+
+/// A signer outside the host, such as a hardware wallet or a signing daemon, that holds the keys of some key branches.
+/// It only ever returns public keys, Diffie-Hellman shared secrets and signatures made with its keys.
+#[async_trait::async_trait]
+pub trait ExternalSigner: Clone + Send + Sync + 'static {
+    /// How the signer identifies one of its keys
+    type Key: Send + Sync + 'static;
+
+    /// Describes the signer in error messages, e.g. "the Ledger device"
+    fn description(&self) -> &'static str;
+
+    /// Whether the keys on the branch are held by the signer
+    fn holds_branch(&self, branch: &str) -> bool;
+
+    /// The signer's key for the key id, or None if the key is held by the host
+    fn signer_key(&self, key_id: &TariKeyId) -> Option<Self::Key>;
+
+    /// Whether the signer waits for other parties before it answers, rather than asking a local device or user
+    fn awaits_cosigners(&self) -> bool {
+        false
+    }
+
+    async fn get_public_key(&self, key: Self::Key) -> Result<PublicKey, KeyManagerServiceError>;
+
+    /// The Diffie-Hellman shared secret of the signer's key and the given public key, as a point
+    async fn get_diffie_hellman_shared_secret(
+        &self,
+        key: Self::Key,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, KeyManagerServiceError>;
+
+    /// Signs for the script key of an input being spent. Returns the public script key, the signer's public nonce and
+    /// its part of the script signature; the host adds the commitment part.
+    async fn get_script_signature(
+        &self,
+        script_key: Self::Key,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), KeyManagerServiceError>;
+
+    /// The sum of the given script keys minus the sum of the given sender offset keys. Signers must not return a
+    /// combination of keys chosen by the host, which could reveal a script key: they only answer if at least one of
+    /// the sender offset keys they generated for the session is subtracted, and forget those keys once they have.
+    async fn get_script_offset(
+        &self,
+        script_keys: Vec<Self::Key>,
+        sender_offset_keys: Vec<Self::Key>,
+    ) -> Result<PrivateKey, KeyManagerServiceError>;
+
+    /// Makes the sender's part of an output's metadata signature. Returns the public nonce and the signature scalar.
+    async fn get_sender_metadata_signature(
+        &self,
+        nonce: Self::Key,
+        sender_offset_key: Self::Key,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), KeyManagerServiceError>;
+}
+
+/// A transaction key manager that sends every operation on the keys held by an external signer to the signer, and
+/// delegates everything else, including the commitment masks, to the host key manager it wraps. Operations that need
+/// the private value of a key held by the signer fail rather than fall back to the host key of the same id.
+#[derive(Clone)]
+pub struct ExternalSignerKeyManager<KM, S> {
+    inner: KM,
+    signer: S,
+}
+
+impl<KM, S> ExternalSignerKeyManager<KM, S>
+where
+    KM: SecretTransactionKeyManagerInterface,
+    S: ExternalSigner,
+{
+    pub fn new(inner: KM, signer: S) -> Self {
+        Self { inner, signer }
+    }
+
+    /// Fails if the key is held by the signer, for operations the host can only do with the private key
+    fn ensure_host_key(&self, key_id: &TariKeyId) -> Result<(), KeyManagerServiceError> {
+        if self.signer.signer_key(key_id).is_some() {
+            return Err(KeyManagerServiceError::SigningDeviceError(format!(
+                "Key `{}` is held by {} and cannot be used by the host",
+                key_id,
+                self.signer.description()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Splits key ids into the signer keys of those held by the signer, and those held by the host
+    fn partition_keys(&self, key_ids: &[TariKeyId]) -> (Vec<S::Key>, Vec<TariKeyId>) {
+        let mut signer_keys = Vec::new();
+        let mut host_keys = Vec::new();
+        for key_id in key_ids {
+            match self.signer.signer_key(key_id) {
+                Some(key) => signer_keys.push(key),
+                None => host_keys.push(key_id.clone()),
+            }
+        }
+        (signer_keys, host_keys)
+    }
+
+    async fn signer_shared_secret(
+        &self,
+        key: S::Key,
+        public_key: &PublicKey,
+    ) -> Result<CommsDHKE, KeyManagerServiceError> {
+        let shared_secret = self.signer.get_diffie_hellman_shared_secret(key, public_key).await?;
+        // The signer returns the shared point itself, which is the shared secret of the unit scalar and that point
+        Ok(CommsDHKE::new(&PrivateKey::from(1u64), &shared_secret))
+    }
+}
+
+#[async_trait::async_trait]
+impl<KM, S> KeyManagerInterface<PublicKey> for ExternalSignerKeyManager<KM, S>
+where
+    KM: SecretTransactionKeyManagerInterface,
+    S: ExternalSigner,
+{
+    async fn add_new_branch<T: Into<String> + Send>(&self, branch: T) -> Result<AddResult, KeyManagerServiceError> {
+        self.inner.add_new_branch(branch).await
+    }
+
+    async fn get_next_key<T: Into<String> + Send>(
+        &self,
+        branch: T,
+    ) -> Result<(TariKeyId, PublicKey), KeyManagerServiceError> {
+        // The host key manager still tracks the index of the signer's branches
+        let (key_id, public_key) = self.inner.get_next_key(branch).await?;
+        match self.signer.signer_key(&key_id) {
+            Some(key) => Ok((key_id, self.signer.get_public_key(key).await?)),
+            None => Ok((key_id, public_key)),
+        }
+    }
+
+    async fn get_static_key<T: Into<String> + Send>(&self, branch: T) -> Result<TariKeyId, KeyManagerServiceError> {
+        self.inner.get_static_key(branch).await
+    }
+
+    async fn get_public_key_at_key_id(&self, key_id: &TariKeyId) -> Result<PublicKey, KeyManagerServiceError> {
+        match self.signer.signer_key(key_id) {
+            Some(key) => self.signer.get_public_key(key).await,
+            None => self.inner.get_public_key_at_key_id(key_id).await,
+        }
+    }
+
+    async fn find_key_index<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        key: &PublicKey,
+    ) -> Result<u64, KeyManagerServiceError> {
+        let branch = branch.into();
+        if self.signer.holds_branch(&branch) {
+            // Searching would take a round trip to the signer per index
+            return Err(KeyManagerServiceError::KeyNotFoundInKeyChain);
+        }
+        self.inner.find_key_index(branch, key).await
+    }
+
+    async fn update_current_key_index_if_higher<T: Into<String> + Send>(
+        &self,
+        branch: T,
+        index: u64,
+    ) -> Result<(), KeyManagerServiceError> {
+        self.inner.update_current_key_index_if_higher(branch, index).await
+    }
+
+    async fn import_key(&self, private_key: PrivateKey) -> Result<TariKeyId, KeyManagerServiceError> {
+        self.inner.import_key(private_key).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<KM, S> TransactionKeyManagerInterface for ExternalSignerKeyManager<KM, S>
+where
+    KM: SecretTransactionKeyManagerInterface,
+    S: ExternalSigner,
+{
+    async fn get_commitment(
+        &self,
+        spend_key_id: &TariKeyId,
+        value: &PrivateKey,
+    ) -> Result<Commitment, KeyManagerServiceError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.inner.get_commitment(spend_key_id, value).await
+    }
+
+    async fn verify_mask(
+        &self,
+        commitment: &Commitment,
+        spend_key_id: &TariKeyId,
+        value: u64,
+    ) -> Result<bool, KeyManagerServiceError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.inner.verify_mask(commitment, spend_key_id, value).await
+    }
+
+    async fn get_recovery_key_id(&self) -> Result<TariKeyId, KeyManagerServiceError> {
+        self.inner.get_recovery_key_id().await
+    }
+
+    async fn get_next_spend_and_script_key_ids(
+        &self,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError> {
+        let (spend_key_id, spend_public_key, script_key_id, _) = self.inner.get_next_spend_and_script_key_ids().await?;
+        let script_public_key = self.get_public_key_at_key_id(&script_key_id).await?;
+        Ok((spend_key_id, spend_public_key, script_key_id, script_public_key))
+    }
+
+    async fn get_next_spend_and_script_key_ids_for_account(
+        &self,
+        account: u32,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError> {
+        let (spend_key_id, spend_public_key, script_key_id, _) = self
+            .inner
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await?;
+        let script_public_key = self.get_public_key_at_key_id(&script_key_id).await?;
+        Ok((spend_key_id, spend_public_key, script_key_id, script_public_key))
+    }
+
+    async fn get_branch_key_indices(&self) -> Result<Vec<(String, u64)>, KeyManagerServiceError> {
+        self.inner.get_branch_key_indices().await
+    }
+
+    async fn find_script_key_id_from_spend_key_id(
+        &self,
+        spend_key_id: &TariKeyId,
+        public_script_key: Option<&PublicKey>,
+    ) -> Result<Option<TariKeyId>, KeyManagerServiceError> {
+        let script_key_id = match self
+            .inner
+            .find_script_key_id_from_spend_key_id(spend_key_id, None)
+            .await?
+        {
+            Some(key_id) => key_id,
+            None => return Ok(None),
+        };
+        if let Some(key) = public_script_key {
+            if self.get_public_key_at_key_id(&script_key_id).await? != *key {
+                return Ok(None);
+            }
+        }
+        Ok(Some(script_key_id))
+    }
+
+    async fn get_diffie_hellman_shared_secret(
+        &self,
+        secret_key_id: &TariKeyId,
+        public_key: &PublicKey,
+    ) -> Result<CommsDHKE, TransactionError> {
+        match self.signer.signer_key(secret_key_id) {
+            Some(key) => Ok(self.signer_shared_secret(key, public_key).await?),
+            None => {
+                self.inner
+                    .get_diffie_hellman_shared_secret(secret_key_id, public_key)
+                    .await
+            },
+        }
+    }
+
+    async fn get_diffie_hellman_stealth_domain_hasher(
+        &self,
+        secret_key_id: &TariKeyId,
+        public_key: &PublicKey,
+    ) -> Result<DomainSeparatedHash<Blake2b<U64>>, TransactionError> {
+        match self.signer.signer_key(secret_key_id) {
+            Some(key) => {
+                let shared_secret = self.signer_shared_secret(key, public_key).await?;
+                Ok(shared_secret_to_stealth_domain_hasher(&shared_secret))
+            },
+            None => {
+                self.inner
+                    .get_diffie_hellman_stealth_domain_hasher(secret_key_id, public_key)
+                    .await
+            },
+        }
+    }
+
+    async fn import_add_offset_to_private_key(
+        &self,
+        secret_key_id: &TariKeyId,
+        offset: PrivateKey,
+    ) -> Result<TariKeyId, KeyManagerServiceError> {
+        self.ensure_host_key(secret_key_id)?;
+        self.inner.import_add_offset_to_private_key(secret_key_id, offset).await
+    }
+
+    async fn get_spending_key_id(&self, public_spending_key: &PublicKey) -> Result<TariKeyId, TransactionError> {
+        self.inner.get_spending_key_id(public_spending_key).await
+    }
+
+    async fn construct_range_proof(
+        &self,
+        spend_key_id: &TariKeyId,
+        value: u64,
+        min_value: u64,
+    ) -> Result<RangeProof, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.inner.construct_range_proof(spend_key_id, value, min_value).await
+    }
+
+    async fn get_script_signature(
+        &self,
+        script_key_id: &TariKeyId,
+        spend_key_id: &TariKeyId,
+        value: &PrivateKey,
+        txi_version: &TransactionInputVersion,
+        script_message: &[u8; 32],
+    ) -> Result<ComAndPubSignature, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        let script_key = match self.signer.signer_key(script_key_id) {
+            Some(key) => key,
+            None => {
+                return self
+                    .inner
+                    .get_script_signature(script_key_id, spend_key_id, value, txi_version, script_message)
+                    .await
+            },
+        };
+
+        let factory = CommitmentFactory::default();
+        let r_a = PrivateKey::random(&mut OsRng);
+        let r_x = PrivateKey::random(&mut OsRng);
+        let ephemeral_commitment = factory.commit(&r_x, &r_a);
+        let commitment = self.inner.get_commitment(spend_key_id, value).await?;
+        let (script_public_key, ephemeral_pubkey, u_y) = self
+            .signer
+            .get_script_signature(
+                script_key,
+                *txi_version,
+                &ephemeral_commitment,
+                &commitment,
+                script_message,
+            )
+            .await?;
+
+        let challenge = TransactionInput::finalize_script_signature_challenge(
+            txi_version,
+            &ephemeral_commitment,
+            &ephemeral_pubkey,
+            &script_public_key,
+            &commitment,
+            script_message,
+        );
+        // The commitment part of the signature, the signer has signed for the script key
+        let spend_private_key = self.inner.get_private_key(spend_key_id).await?;
+        let commitment_signature = ComAndPubSignature::sign(
+            value,
+            &spend_private_key,
+            &PrivateKey::default(),
+            &r_a,
+            &r_x,
+            &PrivateKey::default(),
+            &challenge,
+            &factory,
+        )?;
+        Ok(ComAndPubSignature::new(
+            ephemeral_commitment,
+            ephemeral_pubkey,
+            commitment_signature.u_a().clone(),
+            commitment_signature.u_x().clone(),
+            u_y,
+        ))
+    }
+
+    async fn get_partial_txo_kernel_signature(
+        &self,
+        spend_key_id: &TariKeyId,
+        nonce_id: &TariKeyId,
+        total_nonce: &PublicKey,
+        total_excess: &PublicKey,
+        kernel_version: &TransactionKernelVersion,
+        kernel_message: &[u8; 32],
+        kernel_features: &KernelFeatures,
+        txo_type: TxoStage,
+    ) -> Result<Signature, TransactionError> {
+        // Kernel nonces come from their own branch, which stays on the host
+        self.ensure_host_key(spend_key_id)?;
+        self.ensure_host_key(nonce_id)?;
+        self.inner
+            .get_partial_txo_kernel_signature(
+                spend_key_id,
+                nonce_id,
+                total_nonce,
+                total_excess,
+                kernel_version,
+                kernel_message,
+                kernel_features,
+                txo_type,
+            )
+            .await
+    }
+
+    async fn get_txo_kernel_signature_excess_with_offset(
+        &self,
+        spend_key_id: &TariKeyId,
+        nonce: &TariKeyId,
+    ) -> Result<PublicKey, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.ensure_host_key(nonce)?;
+        self.inner
+            .get_txo_kernel_signature_excess_with_offset(spend_key_id, nonce)
+            .await
+    }
+
+    async fn get_txo_private_kernel_offset(
+        &self,
+        spend_key_id: &TariKeyId,
+        nonce_id: &TariKeyId,
+    ) -> Result<PrivateKey, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.ensure_host_key(nonce_id)?;
+        self.inner.get_txo_private_kernel_offset(spend_key_id, nonce_id).await
+    }
+
+    async fn encrypt_data_for_recovery(
+        &self,
+        spend_key_id: &TariKeyId,
+        custom_recovery_key_id: Option<&TariKeyId>,
+        value: u64,
+    ) -> Result<EncryptedData, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        if let Some(key_id) = custom_recovery_key_id {
+            self.ensure_host_key(key_id)?;
+        }
+        self.inner
+            .encrypt_data_for_recovery(spend_key_id, custom_recovery_key_id, value)
+            .await
+    }
+
+    async fn try_output_key_recovery(
+        &self,
+        output: &TransactionOutput,
+        custom_recovery_key_id: Option<&TariKeyId>,
+    ) -> Result<(TariKeyId, MicroMinotari), TransactionError> {
+        if let Some(key_id) = custom_recovery_key_id {
+            self.ensure_host_key(key_id)?;
+        }
+        self.inner.try_output_key_recovery(output, custom_recovery_key_id).await
+    }
+
+    async fn get_script_offset(
+        &self,
+        script_key_ids: &[TariKeyId],
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, TransactionError> {
+        let (signer_script_keys, host_script_keys) = self.partition_keys(script_key_ids);
+        let (signer_sender_offset_keys, host_sender_offset_keys) = self.partition_keys(sender_offset_key_ids);
+        let host_offset = self
+            .inner
+            .get_script_offset(&host_script_keys, &host_sender_offset_keys)
+            .await?;
+        if signer_script_keys.is_empty() && signer_sender_offset_keys.is_empty() {
+            return Ok(host_offset);
+        }
+        let signer_offset = self
+            .signer
+            .get_script_offset(signer_script_keys, signer_sender_offset_keys)
+            .await?;
+        Ok(host_offset + signer_offset)
+    }
+
+    fn awaits_cosigners(&self) -> bool {
+        self.signer.awaits_cosigners()
+    }
+
+    async fn get_metadata_signature_ephemeral_commitment(
+        &self,
+        nonce_id: &TariKeyId,
+        range_proof_type: RangeProofType,
+    ) -> Result<Commitment, TransactionError> {
+        self.ensure_host_key(nonce_id)?;
+        self.inner
+            .get_metadata_signature_ephemeral_commitment(nonce_id, range_proof_type)
+            .await
+    }
+
+    async fn get_metadata_signature(
+        &self,
+        spending_key_id: &TariKeyId,
+        value_as_private_key: &PrivateKey,
+        sender_offset_key_id: &TariKeyId,
+        txo_version: &TransactionOutputVersion,
+        metadata_signature_message: &[u8; 32],
+        range_proof_type: RangeProofType,
+    ) -> Result<ComAndPubSignature, TransactionError> {
+        self.ensure_host_key(spending_key_id)?;
+        if self.signer.signer_key(sender_offset_key_id).is_none() {
+            return self
+                .inner
+                .get_metadata_signature(
+                    spending_key_id,
+                    value_as_private_key,
+                    sender_offset_key_id,
+                    txo_version,
+                    metadata_signature_message,
+                    range_proof_type,
+                )
+                .await;
+        }
+        // The host key manager would sign with its own sender offset key, so the receiver and sender halves are made
+        // separately, the latter by the signer
+        let sender_offset_public_key = self.get_public_key_at_key_id(sender_offset_key_id).await?;
+        let (ephemeral_private_nonce_id, ephemeral_pubkey) = self
+            .get_next_key(TransactionKeyManagerBranch::Nonce.get_branch_key())
+            .await?;
+        let receiver_partial_metadata_signature = self
+            .inner
+            .get_receiver_partial_metadata_signature(
+                spending_key_id,
+                value_as_private_key,
+                &sender_offset_public_key,
+                &ephemeral_pubkey,
+                txo_version,
+                metadata_signature_message,
+                range_proof_type,
+            )
+            .await?;
+        let commitment = self.get_commitment(spending_key_id, value_as_private_key).await?;
+        let sender_partial_metadata_signature = self
+            .get_sender_partial_metadata_signature(
+                &ephemeral_private_nonce_id,
+                sender_offset_key_id,
+                &commitment,
+                receiver_partial_metadata_signature.ephemeral_commitment(),
+                txo_version,
+                metadata_signature_message,
+            )
+            .await?;
+        Ok(&receiver_partial_metadata_signature + &sender_partial_metadata_signature)
+    }
+
+    async fn get_receiver_partial_metadata_signature(
+        &self,
+        spend_key_id: &TariKeyId,
+        value: &PrivateKey,
+        sender_offset_public_key: &PublicKey,
+        ephemeral_pubkey: &PublicKey,
+        txo_version: &TransactionOutputVersion,
+        metadata_signature_message: &[u8; 32],
+        range_proof_type: RangeProofType,
+    ) -> Result<ComAndPubSignature, TransactionError> {
+        self.ensure_host_key(spend_key_id)?;
+        self.inner
+            .get_receiver_partial_metadata_signature(
+                spend_key_id,
+                value,
+                sender_offset_public_key,
+                ephemeral_pubkey,
+                txo_version,
+                metadata_signature_message,
+                range_proof_type,
+            )
+            .await
+    }
+
+    async fn get_sender_partial_metadata_signature(
+        &self,
+        ephemeral_private_nonce_id: &TariKeyId,
+        sender_offset_key_id: &TariKeyId,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        txo_version: &TransactionOutputVersion,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<ComAndPubSignature, TransactionError> {
+        let (nonce, sender_offset_key) = match (
+            self.signer.signer_key(ephemeral_private_nonce_id),
+            self.signer.signer_key(sender_offset_key_id),
+        ) {
+            (Some(nonce), Some(sender_offset_key)) => (nonce, sender_offset_key),
+            (None, None) => {
+                return self
+                    .inner
+                    .get_sender_partial_metadata_signature(
+                        ephemeral_private_nonce_id,
+                        sender_offset_key_id,
+                        commitment,
+                        ephemeral_commitment,
+                        txo_version,
+                        metadata_signature_message,
+                    )
+                    .await
+            },
+            _ => {
+                return Err(TransactionError::KeyManagerError(format!(
+                    "The sender offset key and its nonce must both be held by {}",
+                    self.signer.description()
+                )))
+            },
+        };
+
+        let (ephemeral_pubkey, u_y) = self
+            .signer
+            .get_sender_metadata_signature(
+                nonce,
+                sender_offset_key,
+                *txo_version,
+                commitment,
+                ephemeral_commitment,
+                metadata_signature_message,
+            )
+            .await?;
+        // Matches the sender partial signature made by the host key manager, which has no commitment part
+        let factory = CommitmentFactory::default();
+        Ok(ComAndPubSignature::new(
+            factory.commit(&PrivateKey::default(), &PrivateKey::default()),
+            ephemeral_pubkey,
+            PrivateKey::default(),
+            PrivateKey::default(),
+            u_y,
+        ))
+    }
+
+    async fn generate_burn_proof(
+        &self,
+        spending_key: &TariKeyId,
+        amount: &PrivateKey,
+        claim_public_key: &PublicKey,
+    ) -> Result<RistrettoComSig, TransactionError> {
+        self.ensure_host_key(spending_key)?;
+        self.inner
+            .generate_burn_proof(spending_key, amount, claim_public_key)
+            .await
+    }
+
+    async fn get_frost_dkg_commitments(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        session_id: u64,
+    ) -> Result<(Vec<PublicKey>, Signature), TransactionError> {
+        self.ensure_host_key(polynomial_key_id)?;
+        self.inner
+            .get_frost_dkg_commitments(polynomial_key_id, threshold, participant, session_id)
+            .await
+    }
+
+    async fn get_frost_dkg_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        recipient: u16,
+    ) -> Result<PrivateKey, TransactionError> {
+        self.ensure_host_key(polynomial_key_id)?;
+        self.inner
+            .get_frost_dkg_share(polynomial_key_id, threshold, recipient)
+            .await
+    }
+
+    async fn import_frost_signing_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        received_share_ids: &[TariKeyId],
+    ) -> Result<TariKeyId, TransactionError> {
+        self.ensure_host_key(polynomial_key_id)?;
+        self.inner
+            .import_frost_signing_share(polynomial_key_id, threshold, participant, received_share_ids)
+            .await
+    }
+
+    async fn get_frost_signature_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        hiding_nonce_id: &TariKeyId,
+        binding_nonce_id: &TariKeyId,
+        participant: u16,
+        group_public_key: &PublicKey,
+        target: &FrostSigningTarget,
+        commitments: &[FrostNonceCommitment],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.ensure_host_key(signing_share_id)?;
+        self.ensure_host_key(hiding_nonce_id)?;
+        self.ensure_host_key(binding_nonce_id)?;
+        self.inner
+            .get_frost_signature_share(
+                signing_share_id,
+                hiding_nonce_id,
+                binding_nonce_id,
+                participant,
+                group_public_key,
+                target,
+                commitments,
+            )
+            .await
+    }
+
+    async fn get_frost_script_offset_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        participant: u16,
+        signers: &[u16],
+        script_key_count: u64,
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.ensure_host_key(signing_share_id)?;
+        for key_id in sender_offset_key_ids {
+            self.ensure_host_key(key_id)?;
+        }
+        self.inner
+            .get_frost_script_offset_share(
+                signing_share_id,
+                participant,
+                signers,
+                script_key_count,
+                sender_offset_key_ids,
+            )
+            .await
+    }
+
+    async fn get_joint_sender_metadata_signature_share(
+        &self,
+        nonce_id: &TariKeyId,
+        sender_offset_key_id: &TariKeyId,
+        joint_nonce: &PublicKey,
+        joint_sender_offset_key: &PublicKey,
+        txo_version: &TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.ensure_host_key(nonce_id)?;
+        self.ensure_host_key(sender_offset_key_id)?;
+        self.inner
+            .get_joint_sender_metadata_signature_share(
+                nonce_id,
+                sender_offset_key_id,
+                joint_nonce,
+                joint_sender_offset_key,
+                txo_version,
+                commitment,
+                ephemeral_commitment,
+                metadata_signature_message,
+            )
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<KM, S> SecretTransactionKeyManagerInterface for ExternalSignerKeyManager<KM, S>
+where
+    KM: SecretTransactionKeyManagerInterface,
+    S: ExternalSigner,
+{
+    async fn get_private_key(&self, key_id: &TariKeyId) -> Result<PrivateKey, KeyManagerServiceError> {
+        self.ensure_host_key(key_id)?;
+        self.inner.get_private_key(key_id).await
+    }
+
+    async fn export_view_key(
+        &self,
+        view_key_id: &TariKeyId,
+        spend_public_key: &PublicKey,
+        script_key_ids: &[TariKeyId],
+    ) -> Result<ViewKeyExport, KeyManagerServiceError> {
+        self.ensure_host_key(view_key_id)?;
+        let mut export = self.inner.export_view_key(view_key_id, spend_public_key, &[]).await?;
+        // The host key manager only knows its own script keys
+        for key_id in script_key_ids {
+            let public_key = self.get_public_key_at_key_id(key_id).await?;
+            if !export.script_public_keys.contains(&public_key) {
+                export.script_public_keys.push(public_key);
+            }
+        }
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_key_manager::key_manager_service::KeyId;
+    use tari_script::{one_sided_payment_script, ExecutionStack};
+
+    use super::*;
+    use crate::{
+        common::one_sided::{shared_secret_to_output_encryption_key, shared_secret_to_output_spending_key},
+        transactions::{
+            test_helpers::{create_test_core_key_manager_with_memory_db, TestKeyManager},
+            transaction_components::{WalletOutput, WalletOutputBuilder},
+        },
+    };
+
+    const SIGNER_BRANCHES: [TransactionKeyManagerBranch; 4] = [
+        TransactionKeyManagerBranch::ScriptKey,
+        TransactionKeyManagerBranch::CoinbaseScript,
+        TransactionKeyManagerBranch::SenderOffset,
+        TransactionKeyManagerBranch::Nonce,
+    ];
+
+    /// A signer holding its keys in an in-memory key manager with its own seed
+    #[derive(Clone)]
+    struct SoftwareSigner {
+        key_manager: TestKeyManager,
+    }
+
+    fn signer_error(err: TransactionError) -> KeyManagerServiceError {
+        KeyManagerServiceError::SigningDeviceError(err.to_string())
+    }
+
+    #[async_trait::async_trait]
+    impl ExternalSigner for SoftwareSigner {
+        type Key = TariKeyId;
+
+        fn description(&self) -> &'static str {
+            "the software signer"
+        }
+
+        fn holds_branch(&self, branch: &str) -> bool {
+            SIGNER_BRANCHES.iter().any(|b| b.get_branch_key() == branch)
+        }
+
+        fn signer_key(&self, key_id: &TariKeyId) -> Option<TariKeyId> {
+            match key_id {
+                KeyId::Managed { branch, .. } if self.holds_branch(branch) => Some(key_id.clone()),
+                _ => None,
+            }
+        }
+
+        async fn get_public_key(&self, key: TariKeyId) -> Result<PublicKey, KeyManagerServiceError> {
+            self.key_manager.get_public_key_at_key_id(&key).await
+        }
+
+        async fn get_diffie_hellman_shared_secret(
+            &self,
+            key: TariKeyId,
+            public_key: &PublicKey,
+        ) -> Result<PublicKey, KeyManagerServiceError> {
+            let private_key = self.key_manager.get_private_key(&key).await?;
+            Ok(&private_key * public_key)
+        }
+
+        async fn get_script_signature(
+            &self,
+            script_key: TariKeyId,
+            txi_version: TransactionInputVersion,
+            ephemeral_commitment: &Commitment,
+            commitment: &Commitment,
+            script_message: &[u8; 32],
+        ) -> Result<(PublicKey, PublicKey, PrivateKey), KeyManagerServiceError> {
+            use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+            let script_private_key = self.key_manager.get_private_key(&script_key).await?;
+            let script_public_key = PublicKey::from_secret_key(&script_private_key);
+            let r_y = PrivateKey::random(&mut OsRng);
+            let ephemeral_pubkey = PublicKey::from_secret_key(&r_y);
+            let challenge = TransactionInput::finalize_script_signature_challenge(
+                &txi_version,
+                ephemeral_commitment,
+                &ephemeral_pubkey,
+                &script_public_key,
+                commitment,
+                script_message,
+            );
+            let e = PrivateKey::from_uniform_bytes(&challenge)
+                .map_err(|e| KeyManagerServiceError::SigningDeviceError(e.to_string()))?;
+            Ok((script_public_key, ephemeral_pubkey, &r_y + &(&e * &script_private_key)))
+        }
+
+        async fn get_script_offset(
+            &self,
+            script_keys: Vec<TariKeyId>,
+            sender_offset_keys: Vec<TariKeyId>,
+        ) -> Result<PrivateKey, KeyManagerServiceError> {
+            self.key_manager
+                .get_script_offset(&script_keys, &sender_offset_keys)
+                .await
+                .map_err(signer_error)
+        }
+
+        async fn get_sender_metadata_signature(
+            &self,
+            nonce: TariKeyId,
+            sender_offset_key: TariKeyId,
+            txo_version: TransactionOutputVersion,
+            commitment: &Commitment,
+            ephemeral_commitment: &Commitment,
+            metadata_signature_message: &[u8; 32],
+        ) -> Result<(PublicKey, PrivateKey), KeyManagerServiceError> {
+            let signature = self
+                .key_manager
+                .get_sender_partial_metadata_signature(
+                    &nonce,
+                    &sender_offset_key,
+                    commitment,
+                    ephemeral_commitment,
+                    &txo_version,
+                    metadata_signature_message,
+                )
+                .await
+                .map_err(signer_error)?;
+            Ok((signature.ephemeral_pubkey().clone(), signature.u_y().clone()))
+        }
+    }
+
+    fn key_manager_with_signer() -> (ExternalSignerKeyManager<TestKeyManager, SoftwareSigner>, SoftwareSigner) {
+        let signer = SoftwareSigner {
+            key_manager: create_test_core_key_manager_with_memory_db(),
+        };
+        (
+            ExternalSignerKeyManager::new(create_test_core_key_manager_with_memory_db(), signer.clone()),
+            signer,
+        )
+    }
+
+    #[tokio::test]
+    async fn it_sends_one_sided_outputs_the_recipient_can_spend() {
+        let (sender, signer) = key_manager_with_signer();
+        let recipient = create_test_core_key_manager_with_memory_db();
+        let (recipient_key_id, recipient_public_key) = recipient
+            .get_next_key(TransactionKeyManagerBranch::ScriptKey.get_branch_key())
+            .await
+            .unwrap();
+
+        // Built the way the wallet builds a one-sided payment, with the sender offset key held by the signer
+        let (sender_offset_key_id, sender_offset_public_key) = sender
+            .get_next_key(TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+            .await
+            .unwrap();
+        assert_eq!(
+            sender_offset_public_key,
+            signer
+                .key_manager
+                .get_public_key_at_key_id(&sender_offset_key_id)
+                .await
+                .unwrap()
+        );
+        let shared_secret = sender
+            .get_diffie_hellman_shared_secret(&sender_offset_key_id, &recipient_public_key)
+            .await
+            .unwrap();
+        let spending_key_id = sender
+            .import_key(shared_secret_to_output_spending_key(&shared_secret).unwrap())
+            .await
+            .unwrap();
+        let encryption_key_id = sender
+            .import_key(shared_secret_to_output_encryption_key(&shared_secret).unwrap())
+            .await
+            .unwrap();
+        let amount = MicroMinotari::from(5000);
+        let output = WalletOutputBuilder::new(amount, spending_key_id)
+            .with_script(one_sided_payment_script(&recipient_public_key))
+            .encrypt_data_for_recovery(&sender, Some(&encryption_key_id))
+            .await
+            .unwrap()
+            .with_input_data(ExecutionStack::default())
+            .with_sender_offset_public_key(sender_offset_public_key)
+            .with_script_key(TariKeyId::Zero)
+            .sign_as_sender_and_receiver(&sender, &sender_offset_key_id)
+            .await
+            .unwrap()
+            .try_build(&sender)
+            .await
+            .unwrap();
+        let transaction_output = output.to_transaction_output(&sender).await.unwrap();
+        transaction_output.verify_metadata_signature().unwrap();
+
+        // The recipient recognises the output from its sender offset public key
+        let shared_secret = recipient
+            .get_diffie_hellman_shared_secret(&recipient_key_id, &transaction_output.sender_offset_public_key)
+            .await
+            .unwrap();
+        let encryption_key_id = recipient
+            .import_key(shared_secret_to_output_encryption_key(&shared_secret).unwrap())
+            .await
+            .unwrap();
+        let (spending_key_id, value) = recipient
+            .try_output_key_recovery(&transaction_output, Some(&encryption_key_id))
+            .await
+            .unwrap();
+        assert_eq!(value, amount);
+
+        // and can spend it with its own script key
+        let received = WalletOutput::new_with_rangeproof(
+            output.version,
+            value,
+            spending_key_id,
+            output.features.clone(),
+            output.script.clone(),
+            ExecutionStack::default(),
+            recipient_key_id,
+            output.sender_offset_public_key.clone(),
+            output.metadata_signature.clone(),
+            0,
+            output.covenant.clone(),
+            output.encrypted_data,
+            output.minimum_value_promise,
+            output.rangeproof.clone(),
+        );
+        let input = received.to_transaction_input(&recipient).await.unwrap();
+        assert_eq!(input.commitment().unwrap(), &transaction_output.commitment);
+        assert_eq!(
+            input
+                .run_and_verify_script(&CommitmentFactory::default(), None)
+                .unwrap(),
+            recipient_public_key
+        );
+    }
+
+    #[tokio::test]
+    async fn it_refuses_host_operations_on_signer_keys() {
+        let (key_manager, _) = key_manager_with_signer();
+        let (nonce_id, nonce) = key_manager
+            .get_next_key(TransactionKeyManagerBranch::Nonce.get_branch_key())
+            .await
+            .unwrap();
+        let (spend_key_id, spend_public_key) = key_manager
+            .get_next_key(TransactionKeyManagerBranch::CommitmentMask.get_branch_key())
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            key_manager
+                .get_partial_txo_kernel_signature(
+                    &spend_key_id,
+                    &nonce_id,
+                    &nonce,
+                    &spend_public_key,
+                    &TransactionKernelVersion::get_current_version(),
+                    &[0u8; 32],
+                    &KernelFeatures::default(),
+                    TxoStage::Output,
+                )
+                .await,
+            Err(TransactionError::KeyManagerError(_))
+        ));
+        assert!(key_manager
+            .get_txo_private_kernel_offset(&spend_key_id, &nonce_id)
+            .await
+            .is_err());
+        assert!(key_manager
+            .get_metadata_signature_ephemeral_commitment(&nonce_id, RangeProofType::BulletProofPlus)
+            .await
+            .is_err());
+        assert!(key_manager
+            .import_add_offset_to_private_key(&nonce_id, PrivateKey::default())
+            .await
+            .is_err());
+        assert!(matches!(
+            key_manager.get_private_key(&nonce_id).await,
+            Err(KeyManagerServiceError::SigningDeviceError(_))
+        ));
+        // Host keys are unaffected
+        assert!(key_manager.get_private_key(&spend_key_id).await.is_ok());
+    }
+}
@@ -68,9 +68,18 @@ const KEY_MANAGER_MAX_SEARCH_DEPTH: u64 = 1_000_000;
 use crate::{
     common::ConfidentialOutputHasher,
     transactions::{
+        frost::{
+            frost_dkg_proof_challenge,
+            frost_script_offset_share,
+            frost_signature_share,
+            FrostNonceCommitment,
+            FrostPolynomial,
+            FrostSigningTarget,
+        },
         key_manager::{
             interface::{TransactionKeyManagerBranch, TxoStage},
             TariKeyId,
+            ViewKeyExport,
         },
         tari_amount::MicroMinotari,
         transaction_components::{
@@ -201,17 +210,24 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
 
     pub async fn get_next_spend_and_script_key_ids(
         &self,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError> {
+        self.get_next_spend_and_script_key_ids_for_account(0).await
+    }
+
+    pub async fn get_next_spend_and_script_key_ids_for_account(
+        &self,
+        account: u32,
     ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError> {
         let (spend_key_id, spend_public_key) = self
-            .get_next_key(&TransactionKeyManagerBranch::CommitmentMask.get_branch_key())
+            .get_next_key(&TransactionKeyManagerBranch::CommitmentMask.get_account_branch_key(account))
             .await?;
         let index = spend_key_id
             .managed_index()
             .ok_or(KeyManagerServiceError::KyeIdWithoutIndex)?;
-        self.db
-            .set_key_index(&TransactionKeyManagerBranch::ScriptKey.get_branch_key(), index)?;
+        let script_branch = TransactionKeyManagerBranch::ScriptKey.get_account_branch_key(account);
+        self.db.set_key_index(&script_branch, index)?;
         let script_key_id = KeyId::Managed {
-            branch: TransactionKeyManagerBranch::ScriptKey.get_branch_key(),
+            branch: script_branch,
             index,
         };
         let script_public_key = self.get_public_key_at_key_id(&script_key_id).await?;
@@ -225,13 +241,14 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         spend_key_id: &TariKeyId,
         public_script_key: Option<&PublicKey>,
     ) -> Result<Option<TariKeyId>, KeyManagerServiceError> {
-        let index = match spend_key_id {
-            KeyId::Managed { index, .. } => *index,
+        let (branch, index) = match spend_key_id {
+            KeyId::Managed { branch, index } => (branch, *index),
             KeyId::Imported { .. } => return Ok(None),
             KeyId::Zero => return Ok(None),
         };
+        let account = TransactionKeyManagerBranch::account_of_branch_key(branch);
         let script_key_id = KeyId::Managed {
-            branch: TransactionKeyManagerBranch::ScriptKey.get_branch_key(),
+            branch: TransactionKeyManagerBranch::ScriptKey.get_account_branch_key(account),
             index,
         };
 
@@ -319,6 +336,36 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         Ok(key_id)
     }
 
+    pub async fn get_branch_key_indices(&self) -> Result<Vec<(String, u64)>, KeyManagerServiceError> {
+        let mut indices = Vec::with_capacity(self.key_managers.len());
+        for (branch, km) in &self.key_managers {
+            indices.push((branch.clone(), km.read().await.key_index()));
+        }
+        indices.sort();
+        Ok(indices)
+    }
+
+    pub async fn export_view_key(
+        &self,
+        view_key_id: &TariKeyId,
+        spend_public_key: &PublicKey,
+        script_key_ids: &[TariKeyId],
+    ) -> Result<ViewKeyExport, KeyManagerServiceError> {
+        let view_key = self.get_private_key(view_key_id).await?;
+        let mut script_public_keys = Vec::with_capacity(script_key_ids.len());
+        for key_id in script_key_ids {
+            let public_key = self.get_public_key_at_key_id(key_id).await?;
+            if !script_public_keys.contains(&public_key) {
+                script_public_keys.push(public_key);
+            }
+        }
+        Ok(ViewKeyExport::new(
+            view_key,
+            spend_public_key.clone(),
+            script_public_keys,
+        ))
+    }
+
     pub(crate) async fn get_private_key(&self, key_id: &TariKeyId) -> Result<PrivateKey, KeyManagerServiceError> {
         match key_id {
             KeyId::Managed { branch, index } => {
@@ -425,6 +472,124 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         .map_err(|e| TransactionError::InvalidSignatureError(e.to_string()))
     }
 
+    // -----------------------------------------------------------------------------------------------------------------
+    // FROST section (transactions > frost)
+    // -----------------------------------------------------------------------------------------------------------------
+
+    pub async fn get_frost_dkg_commitments(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        session_id: u64,
+    ) -> Result<(Vec<PublicKey>, Signature), TransactionError> {
+        let secret = self.get_private_key(polynomial_key_id).await?;
+        let commitments = FrostPolynomial::derive(&secret, threshold)?.commitments();
+        let (private_nonce, public_nonce) = PublicKey::random_keypair(&mut OsRng);
+        let challenge = frost_dkg_proof_challenge(participant, session_id, &commitments[0], &public_nonce);
+        let proof = Signature::sign_raw_uniform(&secret, private_nonce, &challenge)?;
+        Ok((commitments, proof))
+    }
+
+    pub async fn get_frost_dkg_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        recipient: u16,
+    ) -> Result<PrivateKey, TransactionError> {
+        let secret = self.get_private_key(polynomial_key_id).await?;
+        Ok(FrostPolynomial::derive(&secret, threshold)?.evaluate(recipient))
+    }
+
+    pub async fn import_frost_signing_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        received_share_ids: &[TariKeyId],
+    ) -> Result<TariKeyId, TransactionError> {
+        let mut signing_share = self
+            .get_frost_dkg_share(polynomial_key_id, threshold, participant)
+            .await?;
+        for share_id in received_share_ids {
+            signing_share = &signing_share + &self.get_private_key(share_id).await?;
+        }
+        Ok(self.import_key(signing_share).await?)
+    }
+
+    pub async fn get_frost_signature_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        hiding_nonce_id: &TariKeyId,
+        binding_nonce_id: &TariKeyId,
+        participant: u16,
+        group_public_key: &PublicKey,
+        target: &FrostSigningTarget,
+        commitments: &[FrostNonceCommitment],
+    ) -> Result<PrivateKey, TransactionError> {
+        let signing_share = self.get_private_key(signing_share_id).await?;
+        let hiding_nonce = self.get_private_key(hiding_nonce_id).await?;
+        let binding_nonce = self.get_private_key(binding_nonce_id).await?;
+        Ok(frost_signature_share(
+            &signing_share,
+            &hiding_nonce,
+            &binding_nonce,
+            participant,
+            group_public_key,
+            target,
+            commitments,
+        )?)
+    }
+
+    pub async fn get_frost_script_offset_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        participant: u16,
+        signers: &[u16],
+        script_key_count: u64,
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, TransactionError> {
+        let signing_share = self.get_private_key(signing_share_id).await?;
+        let mut sender_offset_keys = Vec::with_capacity(sender_offset_key_ids.len());
+        for key_id in sender_offset_key_ids {
+            sender_offset_keys.push(self.get_private_key(key_id).await?);
+        }
+        Ok(frost_script_offset_share(
+            &signing_share,
+            participant,
+            signers,
+            script_key_count,
+            &sender_offset_keys,
+        )?)
+    }
+
+    pub async fn get_joint_sender_metadata_signature_share(
+        &self,
+        nonce_id: &TariKeyId,
+        sender_offset_key_id: &TariKeyId,
+        joint_nonce: &PublicKey,
+        joint_sender_offset_key: &PublicKey,
+        txo_version: &TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<PrivateKey, TransactionError> {
+        let nonce = self.get_private_key(nonce_id).await?;
+        let sender_offset_private_key = self.get_private_key(sender_offset_key_id).await?;
+        let challenge = TransactionOutput::finalize_metadata_signature_challenge(
+            txo_version,
+            joint_sender_offset_key,
+            ephemeral_commitment,
+            joint_nonce,
+            commitment,
+            metadata_signature_message,
+        );
+        let challenge = PrivateKey::from_uniform_bytes(&challenge).map_err(|_| {
+            TransactionError::KeyManagerError("Invalid challenge for joint metadata signature".to_string())
+        })?;
+        Ok(&nonce + &(&challenge * &sender_offset_private_key))
+    }
+
     // -----------------------------------------------------------------------------------------------------------------
     // Transaction input section (transactions > transaction_components > transaction_input)
     // -----------------------------------------------------------------------------------------------------------------
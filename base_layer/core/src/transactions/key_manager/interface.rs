@@ -29,6 +29,8 @@ use tari_crypto::{hashing::DomainSeparatedHash, ristretto::RistrettoComSig};
 use tari_key_manager::key_manager_service::{KeyId, KeyManagerInterface, KeyManagerServiceError};
 
 use crate::transactions::{
+    frost::{FrostNonceCommitment, FrostSigningTarget},
+    key_manager::ViewKeyExport,
     tari_amount::MicroMinotari,
     transaction_components::{
         EncryptedData,
@@ -60,6 +62,8 @@ pub enum TransactionKeyManagerBranch {
     KernelNonce,
     ScriptKey,
     SenderOffset,
+    Subaddress,
+    FrostPolynomial,
 }
 
 impl TransactionKeyManagerBranch {
@@ -75,8 +79,28 @@ impl TransactionKeyManagerBranch {
             TransactionKeyManagerBranch::KernelNonce => "kernel nonce".to_string(),
             TransactionKeyManagerBranch::ScriptKey => "script key".to_string(),
             TransactionKeyManagerBranch::SenderOffset => "sender offset".to_string(),
+            TransactionKeyManagerBranch::Subaddress => "subaddress".to_string(),
+            TransactionKeyManagerBranch::FrostPolynomial => "frost polynomial".to_string(),
         }
     }
+
+    /// The key of this branch for a wallet account. The default account 0 uses the plain branch key, so that existing
+    /// wallets keep their keys.
+    pub fn get_account_branch_key(self, account: u32) -> String {
+        if account == 0 {
+            self.get_branch_key()
+        } else {
+            format!("{} account {}", self.get_branch_key(), account)
+        }
+    }
+
+    /// The wallet account a branch key belongs to, see [TransactionKeyManagerBranch::get_account_branch_key]
+    pub fn account_of_branch_key(branch: &str) -> u32 {
+        branch
+            .rsplit_once(" account ")
+            .and_then(|(_, account)| account.parse().ok())
+            .unwrap_or(0)
+    }
 }
 
 #[async_trait::async_trait]
@@ -101,6 +125,15 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         &self,
     ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError>;
 
+    /// Gets the next spend and script keys of a wallet account, the branches of the account must have been added
+    async fn get_next_spend_and_script_key_ids_for_account(
+        &self,
+        account: u32,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError>;
+
+    /// Gets the current key index of every branch tracked by the key manager, sorted by branch
+    async fn get_branch_key_indices(&self) -> Result<Vec<(String, u64)>, KeyManagerServiceError>;
+
     async fn find_script_key_id_from_spend_key_id(
         &self,
         spend_key_id: &TariKeyId,
@@ -231,10 +264,92 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         amount: &PrivateKey,
         claim_public_key: &PublicKey,
     ) -> Result<RistrettoComSig, TransactionError>;
+
+    /// Whether signing with this key manager waits for cosigners of the wallet's keys, so that a transaction can sit
+    /// between the recipient's reply and its completion for a while
+    fn awaits_cosigners(&self) -> bool {
+        false
+    }
+
+    /// Returns the coefficient commitments of this wallet's FROST key generation polynomial, whose constant term is the
+    /// key at `polynomial_key_id`, with a proof of possession of the constant term
+    async fn get_frost_dkg_commitments(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        session_id: u64,
+    ) -> Result<(Vec<PublicKey>, Signature), TransactionError>;
+
+    /// The share of this wallet's FROST key generation polynomial for `recipient`, which must only ever be sent to
+    /// `recipient`, encrypted
+    async fn get_frost_dkg_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        recipient: u16,
+    ) -> Result<PrivateKey, TransactionError>;
+
+    /// Adds this wallet's own share to the shares received from the other participants, given as imported keys, and
+    /// imports the sum as this wallet's FROST signing share
+    async fn import_frost_signing_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        received_share_ids: &[TariKeyId],
+    ) -> Result<TariKeyId, TransactionError>;
+
+    /// Creates this wallet's share of a FROST signature of `target`. `commitments` holds the nonce commitments of all
+    /// signers, including this wallet's commitments to the hiding and binding nonces.
+    async fn get_frost_signature_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        hiding_nonce_id: &TariKeyId,
+        binding_nonce_id: &TariKeyId,
+        participant: u16,
+        group_public_key: &PublicKey,
+        target: &FrostSigningTarget,
+        commitments: &[FrostNonceCommitment],
+    ) -> Result<PrivateKey, TransactionError>;
+
+    /// This wallet's share of the script offset of `script_key_count` inputs locked to a FROST group key, minus this
+    /// wallet's parts of the joint sender offset keys. Callers must never ask twice with the same sender offset keys.
+    async fn get_frost_script_offset_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        participant: u16,
+        signers: &[u16],
+        script_key_count: u64,
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, TransactionError>;
+
+    /// This wallet's part `r + e * o` of the sender half of a metadata signature whose nonce and sender offset key are
+    /// held jointly by several wallets, `joint_nonce` and `joint_sender_offset_key` being the sums of their parts
+    async fn get_joint_sender_metadata_signature_share(
+        &self,
+        nonce_id: &TariKeyId,
+        sender_offset_key_id: &TariKeyId,
+        joint_nonce: &PublicKey,
+        joint_sender_offset_key: &PublicKey,
+        txo_version: &TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<PrivateKey, TransactionError>;
 }
 
 #[async_trait::async_trait]
 pub trait SecretTransactionKeyManagerInterface: TransactionKeyManagerInterface {
     /// Gets the pedersen commitment for the specified index
     async fn get_private_key(&self, key_id: &TariKeyId) -> Result<PrivateKey, KeyManagerServiceError>;
+
+    /// Exports the view key and the public keys of the given one-sided payment script keys, so that an external
+    /// scanner can detect one-sided payments to `spend_public_key` without being able to recover the wallet
+    async fn export_view_key(
+        &self,
+        view_key_id: &TariKeyId,
+        spend_public_key: &PublicKey,
+        script_key_ids: &[TariKeyId],
+    ) -> Result<ViewKeyExport, KeyManagerServiceError>;
 }
@@ -0,0 +1,602 @@
+//  Copyright 2023, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, sync::Arc};
+
+use ledger_transport::{APDUAnswer, APDUCommand};
+use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
+use log::*;
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_key_manager::key_manager_service::{KeyId, KeyManagerServiceError};
+use tari_utilities::ByteArray;
+
+use crate::transactions::{
+    key_manager::{TariKeyId, TransactionKeyManagerBranch},
+    transaction_components::{TransactionInputVersion, TransactionOutputVersion},
+};
+
+const LOG_TARGET: &str = "c::transactions::key_manager::ledger";
+
+const CLA: u8 = 0x80;
+const INS_GET_VERSION: u8 = 0x01;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_GET_SCRIPT_SIGNATURE: u8 = 0x03;
+const INS_GET_SCRIPT_OFFSET: u8 = 0x04;
+const INS_GET_SENDER_METADATA_SIGNATURE: u8 = 0x05;
+const INS_GET_DH_SHARED_SECRET: u8 = 0x06;
+
+const SW_OK: u16 = 0x9000;
+const SW_USER_REJECTED: u16 = 0x6985;
+const SW_INS_NOT_SUPPORTED: u16 = 0x6d00;
+const SW_CLA_NOT_SUPPORTED: u16 = 0x6e00;
+
+/// P1 of a script offset chunk that is followed by more chunks
+const P1_MORE_CHUNKS: u8 = 0x00;
+/// P1 of the last chunk of a script offset request, which the device answers with the offset
+const P1_LAST_CHUNK: u8 = 0x01;
+
+/// The number of keys sent in a single script offset request, which keeps the APDU data below 255 bytes
+const SCRIPT_OFFSET_KEYS_PER_REQUEST: usize = 25;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LedgerDeviceError {
+    #[error("HID error: `{0}`")]
+    Hid(String),
+    #[error("No Ledger device was found")]
+    DeviceNotFound,
+    #[error("The Minotari app is not open on the Ledger device")]
+    AppNotOpen,
+    #[error("The request was rejected on the Ledger device")]
+    UserRejected,
+    #[error("The Ledger device returned status 0x{0:04x}")]
+    Status(u16),
+    #[error("Invalid response from the Ledger device: `{0}`")]
+    InvalidResponse(String),
+    #[error("Invalid request to the Ledger device: `{0}`")]
+    InvalidRequest(String),
+}
+
+impl From<LedgerDeviceError> for KeyManagerServiceError {
+    fn from(err: LedgerDeviceError) -> Self {
+        KeyManagerServiceError::SigningDeviceError(err.to_string())
+    }
+}
+
+/// The key branches that are held by the Ledger device instead of the host. Keys on these branches never leave the
+/// device; it only ever returns their public keys and signatures made with them.
+///
+/// Script keys are derived from the seed on the device. Sender offset keys and sender nonces are not: the device
+/// generates them from its own randomness for each app session, and forgets each one once it has been used, so the
+/// host cannot choose them or ask for them twice. See [LedgerDevice::get_script_offset].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedgerKeyBranch {
+    ScriptKey = 1,
+    CoinbaseScript = 2,
+    SenderOffset = 3,
+    Nonce = 4,
+}
+
+impl LedgerKeyBranch {
+    pub fn from_branch_key(branch: &str) -> Option<Self> {
+        [
+            (TransactionKeyManagerBranch::ScriptKey, LedgerKeyBranch::ScriptKey),
+            (
+                TransactionKeyManagerBranch::CoinbaseScript,
+                LedgerKeyBranch::CoinbaseScript,
+            ),
+            (TransactionKeyManagerBranch::SenderOffset, LedgerKeyBranch::SenderOffset),
+            (TransactionKeyManagerBranch::Nonce, LedgerKeyBranch::Nonce),
+        ]
+        .iter()
+        .find(|(b, _)| b.get_branch_key() == branch)
+        .map(|(_, ledger_branch)| *ledger_branch)
+    }
+
+    /// The device branch and index of the key, or None if the key is held by the host
+    pub fn of_key_id(key_id: &TariKeyId) -> Option<(Self, u64)> {
+        match key_id {
+            KeyId::Managed { branch, index } => Self::from_branch_key(branch).map(|b| (b, *index)),
+            KeyId::Imported { .. } | KeyId::Zero => None,
+        }
+    }
+}
+
+/// A Ledger device found on the host
+#[derive(Debug, Clone)]
+pub struct LedgerDeviceInfo {
+    pub product: String,
+    pub serial_number: String,
+    pub path: String,
+}
+
+/// Exchanges APDUs with a Ledger device
+pub(crate) trait LedgerTransport: Send + Sync {
+    fn exchange(&self, command: &APDUCommand<Vec<u8>>) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError>;
+}
+
+impl LedgerTransport for TransportNativeHID {
+    fn exchange(&self, command: &APDUCommand<Vec<u8>>) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        TransportNativeHID::exchange(self, command).map_err(|e| LedgerDeviceError::Hid(e.to_string()))
+    }
+}
+
+/// A connection to the Minotari app on a Ledger hardware wallet over USB HID. Requests block until the device
+/// answers, which for signing requests includes the time the user takes to confirm them on the device.
+#[derive(Clone)]
+pub struct LedgerDevice {
+    transport: Arc<dyn LedgerTransport>,
+}
+
+impl LedgerDevice {
+    /// Lists the Ledger devices connected to the host
+    pub fn list() -> Result<Vec<LedgerDeviceInfo>, LedgerDeviceError> {
+        let api = HidApi::new().map_err(|e| LedgerDeviceError::Hid(e.to_string()))?;
+        Ok(TransportNativeHID::list_ledgers(&api)
+            .map(|device| LedgerDeviceInfo {
+                product: device.product_string().unwrap_or_default().to_string(),
+                serial_number: device.serial_number().unwrap_or_default().to_string(),
+                path: device.path().to_string_lossy().to_string(),
+            })
+            .collect())
+    }
+
+    /// Connects to the first Ledger device found and checks that the Minotari app is open on it
+    pub fn connect() -> Result<Self, LedgerDeviceError> {
+        let api = HidApi::new().map_err(|e| LedgerDeviceError::Hid(e.to_string()))?;
+        if TransportNativeHID::list_ledgers(&api).next().is_none() {
+            return Err(LedgerDeviceError::DeviceNotFound);
+        }
+        let transport = TransportNativeHID::new(&api).map_err(|e| LedgerDeviceError::Hid(e.to_string()))?;
+        let device = Self::with_transport(Arc::new(transport));
+        let version = device.app_version()?;
+        info!(target: LOG_TARGET, "Connected to Ledger device running Minotari app v{}", version);
+        Ok(device)
+    }
+
+    pub(crate) fn with_transport(transport: Arc<dyn LedgerTransport>) -> Self {
+        Self { transport }
+    }
+
+    /// The version of the Minotari app running on the device
+    pub fn app_version(&self) -> Result<String, LedgerDeviceError> {
+        let data = self.exchange(INS_GET_VERSION, &[])?;
+        String::from_utf8(data).map_err(|e| LedgerDeviceError::InvalidResponse(e.to_string()))
+    }
+
+    pub fn get_public_key(&self, branch: LedgerKeyBranch, index: u64) -> Result<PublicKey, LedgerDeviceError> {
+        let data = self.exchange(INS_GET_PUBLIC_KEY, &encode_key(branch, index))?;
+        let [public_key] = parse_response::<1>(&data)?;
+        parse_public_key(public_key)
+    }
+
+    /// The Diffie-Hellman shared secret of a device key and the given public key, as a point. The shared secret of a
+    /// sender offset key is only used to derive the keys of the one output it belongs to.
+    pub fn get_diffie_hellman_shared_secret(
+        &self,
+        branch: LedgerKeyBranch,
+        index: u64,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, LedgerDeviceError> {
+        let mut request = encode_key(branch, index);
+        request.extend_from_slice(public_key.as_bytes());
+        let data = self.exchange(INS_GET_DH_SHARED_SECRET, &request)?;
+        let [shared_secret] = parse_response::<1>(&data)?;
+        parse_public_key(shared_secret)
+    }
+
+    /// Has the device sign for the script key of an input being spent, after the user confirms the spend on the
+    /// device. The device picks its own nonce and returns the public script key, its public nonce and its part of the
+    /// script signature; the host adds the commitment part.
+    pub fn get_script_signature(
+        &self,
+        branch: LedgerKeyBranch,
+        index: u64,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), LedgerDeviceError> {
+        let mut request = encode_key(branch, index);
+        request.push(txi_version.as_u8());
+        request.extend_from_slice(ephemeral_commitment.as_bytes());
+        request.extend_from_slice(commitment.as_bytes());
+        request.extend_from_slice(script_message);
+        let data = self.exchange(INS_GET_SCRIPT_SIGNATURE, &request)?;
+        let [script_public_key, ephemeral_pubkey, u_y] = parse_response::<3>(&data)?;
+        Ok((
+            parse_public_key(script_public_key)?,
+            parse_public_key(ephemeral_pubkey)?,
+            parse_private_key(u_y)?,
+        ))
+    }
+
+    /// The sum of the given script keys minus the sum of the given sender offset keys.
+    ///
+    /// A linear combination of keys chosen by the host would let a compromised host extract a script key, so the
+    /// device only answers requests that subtract at least one of its session sender offset keys, and forgets those
+    /// keys once it has answered. The offset is then masked by keys the host never learns and can never use again.
+    /// Requests with many keys are sent in chunks, and the device only answers the last one.
+    pub fn get_script_offset(
+        &self,
+        script_keys: &[(LedgerKeyBranch, u64)],
+        sender_offset_keys: &[(LedgerKeyBranch, u64)],
+    ) -> Result<PrivateKey, LedgerDeviceError> {
+        if sender_offset_keys.is_empty() {
+            return Err(LedgerDeviceError::InvalidRequest(
+                "a script offset must subtract at least one sender offset key".to_string(),
+            ));
+        }
+        let entries = script_keys
+            .iter()
+            .map(|key| (0u8, key))
+            .chain(sender_offset_keys.iter().map(|key| (1u8, key)))
+            .collect::<Vec<_>>();
+        let chunks = entries.chunks(SCRIPT_OFFSET_KEYS_PER_REQUEST).collect::<Vec<_>>();
+        let mut data = Vec::new();
+        for (i, chunk) in chunks.iter().enumerate() {
+            let mut request = Vec::with_capacity(chunk.len() * 10);
+            for (subtract, (branch, index)) in chunk.iter() {
+                request.push(*subtract);
+                request.extend(encode_key(*branch, *index));
+            }
+            let p1 = if i + 1 == chunks.len() {
+                P1_LAST_CHUNK
+            } else {
+                P1_MORE_CHUNKS
+            };
+            data = self.exchange_with_p1(INS_GET_SCRIPT_OFFSET, p1, &request)?;
+        }
+        let [script_offset] = parse_response::<1>(&data)?;
+        parse_private_key(script_offset)
+    }
+
+    /// Has the device make the sender's part of an output's metadata signature with the sender offset key, using the
+    /// nonce at `nonce_index` that was shared with the receiver. Returns the public nonce and the signature scalar.
+    pub fn get_sender_metadata_signature(
+        &self,
+        nonce_index: u64,
+        sender_offset_index: u64,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), LedgerDeviceError> {
+        let mut request = Vec::with_capacity(113);
+        request.extend_from_slice(&nonce_index.to_le_bytes());
+        request.extend_from_slice(&sender_offset_index.to_le_bytes());
+        request.push(txo_version.as_u8());
+        request.extend_from_slice(commitment.as_bytes());
+        request.extend_from_slice(ephemeral_commitment.as_bytes());
+        request.extend_from_slice(metadata_signature_message);
+        let data = self.exchange(INS_GET_SENDER_METADATA_SIGNATURE, &request)?;
+        let [ephemeral_pubkey, u_y] = parse_response::<2>(&data)?;
+        Ok((parse_public_key(ephemeral_pubkey)?, parse_private_key(u_y)?))
+    }
+
+    fn exchange(&self, ins: u8, data: &[u8]) -> Result<Vec<u8>, LedgerDeviceError> {
+        self.exchange_with_p1(ins, 0, data)
+    }
+
+    fn exchange_with_p1(&self, ins: u8, p1: u8, data: &[u8]) -> Result<Vec<u8>, LedgerDeviceError> {
+        let command = APDUCommand {
+            cla: CLA,
+            ins,
+            p1,
+            p2: 0,
+            data: data.to_vec(),
+        };
+        let answer = self.transport.exchange(&command)?;
+        match answer.retcode() {
+            SW_OK => Ok(answer.data().to_vec()),
+            SW_USER_REJECTED => Err(LedgerDeviceError::UserRejected),
+            SW_INS_NOT_SUPPORTED | SW_CLA_NOT_SUPPORTED => Err(LedgerDeviceError::AppNotOpen),
+            code => Err(LedgerDeviceError::Status(code)),
+        }
+    }
+}
+
+fn encode_key(branch: LedgerKeyBranch, index: u64) -> Vec<u8> {
+    let mut data = Vec::with_capacity(9);
+    data.push(branch as u8);
+    data.extend_from_slice(&index.to_le_bytes());
+    data
+}
+
+/// Splits a response into `N` 32 byte values
+fn parse_response<const N: usize>(data: &[u8]) -> Result<[&[u8]; N], LedgerDeviceError> {
+    if data.len() != N * 32 {
+        return Err(LedgerDeviceError::InvalidResponse(format!(
+            "expected {} bytes, received {}",
+            N * 32,
+            data.len()
+        )));
+    }
+    let values = data.chunks(32).collect::<Vec<_>>();
+    <[&[u8]; N]>::try_from(values).map_err(|_| LedgerDeviceError::InvalidResponse("malformed response".to_string()))
+}
+
+fn parse_public_key(bytes: &[u8]) -> Result<PublicKey, LedgerDeviceError> {
+    PublicKey::from_canonical_bytes(bytes).map_err(|e| LedgerDeviceError::InvalidResponse(e.to_string()))
+}
+
+fn parse_private_key(bytes: &[u8]) -> Result<PrivateKey, LedgerDeviceError> {
+    PrivateKey::from_canonical_bytes(bytes).map_err(|e| LedgerDeviceError::InvalidResponse(e.to_string()))
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::{collections::VecDeque, sync::Mutex};
+
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    /// A transport that records the commands it is sent and answers them with queued responses
+    #[derive(Default)]
+    pub(crate) struct MockTransport {
+        commands: Mutex<Vec<(u8, u8, Vec<u8>)>>,
+        p1s: Mutex<Vec<u8>>,
+        answers: Mutex<VecDeque<Result<Vec<u8>, LedgerDeviceError>>>,
+    }
+
+    impl MockTransport {
+        /// Queues an answer with the given data and status word
+        pub fn answer(&self, data: &[u8], status: u16) {
+            let mut answer = data.to_vec();
+            answer.extend_from_slice(&status.to_be_bytes());
+            self.answers.lock().unwrap().push_back(Ok(answer));
+        }
+
+        pub fn fail(&self, error: LedgerDeviceError) {
+            self.answers.lock().unwrap().push_back(Err(error));
+        }
+
+        /// The class, instruction and data of each command sent so far
+        pub fn commands(&self) -> Vec<(u8, u8, Vec<u8>)> {
+            self.commands.lock().unwrap().clone()
+        }
+
+        /// The P1 parameter of each command sent so far
+        pub fn p1s(&self) -> Vec<u8> {
+            self.p1s.lock().unwrap().clone()
+        }
+    }
+
+    impl LedgerTransport for MockTransport {
+        fn exchange(&self, command: &APDUCommand<Vec<u8>>) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+            self.commands
+                .lock()
+                .unwrap()
+                .push((command.cla, command.ins, command.data.clone()));
+            self.p1s.lock().unwrap().push(command.p1);
+            let answer = self
+                .answers
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("Unexpected command sent to the mock Ledger device")?;
+            Ok(APDUAnswer::from_answer(answer).unwrap())
+        }
+    }
+
+    pub(crate) fn mock_device() -> (LedgerDevice, Arc<MockTransport>) {
+        let transport = Arc::new(MockTransport::default());
+        (LedgerDevice::with_transport(transport.clone()), transport)
+    }
+
+    fn random_keypair() -> (PrivateKey, PublicKey) {
+        PublicKey::random_keypair(&mut OsRng)
+    }
+
+    fn random_commitment() -> Commitment {
+        Commitment::from_public_key(&random_keypair().1)
+    }
+
+    #[test]
+    fn it_encodes_the_branch_and_index_of_a_public_key_request() {
+        let (device, transport) = mock_device();
+        let (_, public_key) = random_keypair();
+        transport.answer(public_key.as_bytes(), SW_OK);
+
+        let received = device.get_public_key(LedgerKeyBranch::SenderOffset, 0x0102).unwrap();
+
+        assert_eq!(received, public_key);
+        assert_eq!(transport.commands(), vec![(CLA, INS_GET_PUBLIC_KEY, vec![
+            3, 0x02, 0x01, 0, 0, 0, 0, 0, 0
+        ])]);
+    }
+
+    #[test]
+    fn it_encodes_signing_requests_and_decodes_their_responses() {
+        let (device, transport) = mock_device();
+        let ephemeral_commitment = random_commitment();
+        let commitment = random_commitment();
+        let message = [7u8; 32];
+        let (_, script_public_key) = random_keypair();
+        let (_, ephemeral_pubkey) = random_keypair();
+        let (u_y, _) = random_keypair();
+        let mut answer = script_public_key.as_bytes().to_vec();
+        answer.extend_from_slice(ephemeral_pubkey.as_bytes());
+        answer.extend_from_slice(u_y.as_bytes());
+        transport.answer(&answer, SW_OK);
+
+        let signature = device
+            .get_script_signature(
+                LedgerKeyBranch::ScriptKey,
+                5,
+                TransactionInputVersion::get_current_version(),
+                &ephemeral_commitment,
+                &commitment,
+                &message,
+            )
+            .unwrap();
+        assert_eq!(signature, (script_public_key, ephemeral_pubkey.clone(), u_y.clone()));
+
+        let mut expected = encode_key(LedgerKeyBranch::ScriptKey, 5);
+        expected.push(TransactionInputVersion::get_current_version().as_u8());
+        expected.extend_from_slice(ephemeral_commitment.as_bytes());
+        expected.extend_from_slice(commitment.as_bytes());
+        expected.extend_from_slice(&message);
+        assert_eq!(transport.commands()[0], (CLA, INS_GET_SCRIPT_SIGNATURE, expected));
+
+        let mut answer = ephemeral_pubkey.as_bytes().to_vec();
+        answer.extend_from_slice(u_y.as_bytes());
+        transport.answer(&answer, SW_OK);
+        let signature = device
+            .get_sender_metadata_signature(
+                3,
+                4,
+                TransactionOutputVersion::get_current_version(),
+                &commitment,
+                &ephemeral_commitment,
+                &message,
+            )
+            .unwrap();
+        assert_eq!(signature, (ephemeral_pubkey, u_y));
+
+        let mut expected = 3u64.to_le_bytes().to_vec();
+        expected.extend_from_slice(&4u64.to_le_bytes());
+        expected.push(TransactionOutputVersion::get_current_version().as_u8());
+        expected.extend_from_slice(commitment.as_bytes());
+        expected.extend_from_slice(ephemeral_commitment.as_bytes());
+        expected.extend_from_slice(&message);
+        assert_eq!(expected.len(), 113);
+        assert_eq!(
+            transport.commands()[1],
+            (CLA, INS_GET_SENDER_METADATA_SIGNATURE, expected)
+        );
+    }
+
+    #[test]
+    fn it_splits_script_offset_requests_and_only_reads_the_offset_from_the_last_chunk() {
+        let (device, transport) = mock_device();
+        let script_keys = (0..30).map(|i| (LedgerKeyBranch::ScriptKey, i)).collect::<Vec<_>>();
+        let sender_offset_keys = (0..5).map(|i| (LedgerKeyBranch::SenderOffset, i)).collect::<Vec<_>>();
+        let (script_offset, _) = random_keypair();
+        transport.answer(&[], SW_OK);
+        transport.answer(script_offset.as_bytes(), SW_OK);
+
+        let offset = device.get_script_offset(&script_keys, &sender_offset_keys).unwrap();
+
+        assert_eq!(offset, script_offset);
+        assert_eq!(transport.p1s(), vec![P1_MORE_CHUNKS, P1_LAST_CHUNK]);
+        let commands = transport.commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].2.len(), SCRIPT_OFFSET_KEYS_PER_REQUEST * 10);
+        assert!(commands[0].2.len() <= 255);
+        assert_eq!(commands[1].2.len(), 10 * 10);
+        // Script keys are added and sender offset keys are subtracted
+        assert_eq!(&commands[0].2[..10], &[0, 1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&commands[1].2[..10], &[0, 1, 25, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&commands[1].2[50..60], &[1, 3, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn it_does_not_ask_for_script_offsets_without_a_sender_offset_key() {
+        let (device, transport) = mock_device();
+
+        let result = device.get_script_offset(&[(LedgerKeyBranch::ScriptKey, 1)], &[]);
+
+        assert!(matches!(result, Err(LedgerDeviceError::InvalidRequest(_))));
+        assert!(transport.commands().is_empty());
+    }
+
+    #[test]
+    fn it_maps_status_words_to_errors() {
+        let (device, transport) = mock_device();
+        transport.answer(&[], SW_USER_REJECTED);
+        transport.answer(&[], SW_INS_NOT_SUPPORTED);
+        transport.answer(&[], SW_CLA_NOT_SUPPORTED);
+        transport.answer(&[], 0x6a80);
+        transport.fail(LedgerDeviceError::Hid("device unplugged".to_string()));
+
+        assert!(matches!(
+            device.get_public_key(LedgerKeyBranch::Nonce, 1),
+            Err(LedgerDeviceError::UserRejected)
+        ));
+        assert!(matches!(device.app_version(), Err(LedgerDeviceError::AppNotOpen)));
+        assert!(matches!(device.app_version(), Err(LedgerDeviceError::AppNotOpen)));
+        assert!(matches!(device.app_version(), Err(LedgerDeviceError::Status(0x6a80))));
+        assert!(matches!(device.app_version(), Err(LedgerDeviceError::Hid(_))));
+    }
+
+    #[test]
+    fn it_rejects_malformed_responses() {
+        let (device, transport) = mock_device();
+        transport.answer(&[1u8; 31], SW_OK);
+        transport.answer(&[0xffu8; 32], SW_OK);
+        transport.answer(&[0xffu8; 64], SW_OK);
+        transport.answer(&[0xc3, 0x28], SW_OK);
+
+        assert!(matches!(
+            device.get_public_key(LedgerKeyBranch::Nonce, 1),
+            Err(LedgerDeviceError::InvalidResponse(_))
+        ));
+        // Not a canonical encoding of a public key or scalar
+        assert!(matches!(
+            device.get_public_key(LedgerKeyBranch::Nonce, 1),
+            Err(LedgerDeviceError::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            device.get_sender_metadata_signature(
+                1,
+                1,
+                TransactionOutputVersion::get_current_version(),
+                &random_commitment(),
+                &random_commitment(),
+                &[0u8; 32]
+            ),
+            Err(LedgerDeviceError::InvalidResponse(_))
+        ));
+        assert!(matches!(
+            device.app_version(),
+            Err(LedgerDeviceError::InvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn it_only_maps_device_branches_to_the_device() {
+        let key_id = |branch: TransactionKeyManagerBranch| TariKeyId::Managed {
+            branch: branch.get_branch_key(),
+            index: 9,
+        };
+        assert_eq!(
+            LedgerKeyBranch::of_key_id(&key_id(TransactionKeyManagerBranch::ScriptKey)),
+            Some((LedgerKeyBranch::ScriptKey, 9))
+        );
+        assert_eq!(
+            LedgerKeyBranch::of_key_id(&key_id(TransactionKeyManagerBranch::Nonce)),
+            Some((LedgerKeyBranch::Nonce, 9))
+        );
+        assert_eq!(
+            LedgerKeyBranch::of_key_id(&key_id(TransactionKeyManagerBranch::CommitmentMask)),
+            None
+        );
+        assert_eq!(
+            LedgerKeyBranch::of_key_id(&TariKeyId::Imported {
+                key: random_keypair().1
+            }),
+            None
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//  Copyright 2023, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
+
+use crate::transactions::{
+    key_manager::{
+        external_signer::{ExternalSigner, ExternalSignerKeyManager},
+        ledger_device::{LedgerDevice, LedgerDeviceError, LedgerKeyBranch},
+        TariKeyId,
+    },
+    transaction_components::{TransactionInputVersion, TransactionOutputVersion},
+};
+
+/// A transaction key manager that keeps the script, sender offset and sender nonce keys on a Ledger device. Everything
+/// else, including the commitment masks, is delegated to the host key manager it wraps.
+///
+/// Without the script key an output cannot be spent, so funds can only leave the wallet once the user has confirmed
+/// the spend on the device.
+pub type LedgerTransactionKeyManager<KM> = ExternalSignerKeyManager<KM, LedgerDevice>;
+
+impl LedgerDevice {
+    /// Runs a device request on the blocking thread pool, as the device only answers once the user has responded to
+    /// any confirmation prompt
+    async fn run_blocking<F, T>(&self, request: F) -> Result<T, KeyManagerServiceError>
+    where
+        F: FnOnce(&LedgerDevice) -> Result<T, LedgerDeviceError> + Send + 'static,
+        T: Send + 'static,
+    {
+        let device = self.clone();
+        Ok(tokio::task::spawn_blocking(move || request(&device))
+            .await
+            .map_err(|e| LedgerDeviceError::Hid(e.to_string()))??)
+    }
+}
+
+#[async_trait::async_trait]
+impl ExternalSigner for LedgerDevice {
+    type Key = (LedgerKeyBranch, u64);
+
+    fn description(&self) -> &'static str {
+        "the Ledger device"
+    }
+
+    fn holds_branch(&self, branch: &str) -> bool {
+        LedgerKeyBranch::from_branch_key(branch).is_some()
+    }
+
+    fn signer_key(&self, key_id: &TariKeyId) -> Option<Self::Key> {
+        LedgerKeyBranch::of_key_id(key_id)
+    }
+
+    async fn get_public_key(&self, (branch, index): Self::Key) -> Result<PublicKey, KeyManagerServiceError> {
+        self.run_blocking(move |device| device.get_public_key(branch, index))
+            .await
+    }
+
+    async fn get_diffie_hellman_shared_secret(
+        &self,
+        (branch, index): Self::Key,
+        public_key: &PublicKey,
+    ) -> Result<PublicKey, KeyManagerServiceError> {
+        let public_key = public_key.clone();
+        self.run_blocking(move |device| device.get_diffie_hellman_shared_secret(branch, index, &public_key))
+            .await
+    }
+
+    async fn get_script_signature(
+        &self,
+        (branch, index): Self::Key,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), KeyManagerServiceError> {
+        let (ephemeral_commitment, commitment, script_message) =
+            (ephemeral_commitment.clone(), commitment.clone(), *script_message);
+        self.run_blocking(move |device| {
+            device.get_script_signature(
+                branch,
+                index,
+                txi_version,
+                &ephemeral_commitment,
+                &commitment,
+                &script_message,
+            )
+        })
+        .await
+    }
+
+    async fn get_script_offset(
+        &self,
+        script_keys: Vec<Self::Key>,
+        sender_offset_keys: Vec<Self::Key>,
+    ) -> Result<PrivateKey, KeyManagerServiceError> {
+        self.run_blocking(move |device| device.get_script_offset(&script_keys, &sender_offset_keys))
+            .await
+    }
+
+    async fn get_sender_metadata_signature(
+        &self,
+        nonce: Self::Key,
+        sender_offset_key: Self::Key,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), KeyManagerServiceError> {
+        let (nonce_index, sender_offset_index) = match (nonce, sender_offset_key) {
+            ((LedgerKeyBranch::Nonce, nonce_index), (LedgerKeyBranch::SenderOffset, sender_offset_index)) => {
+                (nonce_index, sender_offset_index)
+            },
+            _ => {
+                return Err(KeyManagerServiceError::SigningDeviceError(
+                    "The Ledger device only signs metadata with a sender offset key and a sender nonce".to_string(),
+                ))
+            },
+        };
+        let (commitment, ephemeral_commitment, metadata_signature_message) = (
+            commitment.clone(),
+            ephemeral_commitment.clone(),
+            *metadata_signature_message,
+        );
+        self.run_blocking(move |device| {
+            device.get_sender_metadata_signature(
+                nonce_index,
+                sender_offset_index,
+                txo_version,
+                &commitment,
+                &ephemeral_commitment,
+                &metadata_signature_message,
+            )
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+    use tari_key_manager::key_manager_service::KeyManagerInterface;
+    use tari_utilities::ByteArray;
+
+    use super::*;
+    use crate::transactions::{
+        key_manager::{ledger_device::test::mock_device, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+        test_helpers::create_test_core_key_manager_with_memory_db,
+    };
+
+    const SW_OK: u16 = 0x9000;
+
+    #[tokio::test]
+    async fn it_asks_the_device_for_keys_on_device_branches_only() {
+        let (device, transport) = mock_device();
+        let key_manager = LedgerTransactionKeyManager::new(create_test_core_key_manager_with_memory_db(), device);
+        let (_, device_public_key) = PublicKey::random_keypair(&mut OsRng);
+        transport.answer(device_public_key.as_bytes(), SW_OK);
+
+        let (key_id, public_key) = key_manager
+            .get_next_key(TransactionKeyManagerBranch::ScriptKey.get_branch_key())
+            .await
+            .unwrap();
+        assert_eq!(public_key, device_public_key);
+        let Some((LedgerKeyBranch::ScriptKey, index)) = LedgerKeyBranch::of_key_id(&key_id) else {
+            panic!("Expected a script key held by the device, got {}", key_id);
+        };
+        let mut expected = vec![LedgerKeyBranch::ScriptKey as u8];
+        expected.extend_from_slice(&index.to_le_bytes());
+        assert_eq!(transport.commands(), vec![(0x80, 0x02, expected)]);
+
+        // Commitment masks are held by the host
+        let (mask_id, mask_public_key) = key_manager
+            .get_next_key(TransactionKeyManagerBranch::CommitmentMask.get_branch_key())
+            .await
+            .unwrap();
+        assert_eq!(
+            key_manager.get_public_key_at_key_id(&mask_id).await.unwrap(),
+            mask_public_key
+        );
+        assert_eq!(transport.commands().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn it_asks_the_device_for_shared_secrets_of_device_keys() {
+        let (device, transport) = mock_device();
+        let key_manager = LedgerTransactionKeyManager::new(create_test_core_key_manager_with_memory_db(), device);
+        let (_, recipient_public_key) = PublicKey::random_keypair(&mut OsRng);
+        let (device_private_key, _) = PublicKey::random_keypair(&mut OsRng);
+        let shared_point = &device_private_key * &recipient_public_key;
+        transport.answer(shared_point.as_bytes(), SW_OK);
+
+        let sender_offset_key_id = TariKeyId::Managed {
+            branch: TransactionKeyManagerBranch::SenderOffset.get_branch_key(),
+            index: 7,
+        };
+        let shared_secret = key_manager
+            .get_diffie_hellman_shared_secret(&sender_offset_key_id, &recipient_public_key)
+            .await
+            .unwrap();
+
+        assert_eq!(shared_secret.as_bytes(), shared_point.as_bytes());
+        let mut expected = vec![LedgerKeyBranch::SenderOffset as u8];
+        expected.extend_from_slice(&7u64.to_le_bytes());
+        expected.extend_from_slice(recipient_public_key.as_bytes());
+        assert_eq!(transport.commands(), vec![(0x80, 0x06, expected)]);
+    }
+
+    #[tokio::test]
+    async fn it_reports_device_errors_as_signing_device_errors() {
+        let (device, transport) = mock_device();
+        let key_manager = LedgerTransactionKeyManager::new(create_test_core_key_manager_with_memory_db(), device);
+        transport.answer(&[], 0x6985);
+
+        let key_id = TariKeyId::Managed {
+            branch: TransactionKeyManagerBranch::SenderOffset.get_branch_key(),
+            index: 1,
+        };
+        match key_manager.get_public_key_at_key_id(&key_id).await {
+            Err(KeyManagerServiceError::SigningDeviceError(reason)) => {
+                assert_eq!(reason, LedgerDeviceError::UserRejected.to_string())
+            },
+            r => panic!("Expected a signing device error, got {:?}", r),
+        }
+    }
+}
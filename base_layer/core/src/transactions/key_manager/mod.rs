@@ -32,8 +32,24 @@ pub use interface::{
     TxoStage,
 };
 
+mod view_key;
+pub use view_key::{ViewKeyExport, ViewKeyExportError, VIEW_KEY_EXPORT_VERSION};
+
 mod initializer;
 pub use initializer::TransactionKeyManagerInitializer;
 
 mod inner;
 pub use inner::TransactionKeyManagerInner;
+
+mod external_signer;
+pub use external_signer::{ExternalSigner, ExternalSignerKeyManager};
+
+#[cfg(feature = "ledger")]
+mod ledger_device;
+#[cfg(feature = "ledger")]
+pub use ledger_device::{LedgerDevice, LedgerDeviceError, LedgerDeviceInfo, LedgerKeyBranch};
+
+#[cfg(feature = "ledger")]
+mod ledger_key_manager;
+#[cfg(feature = "ledger")]
+pub use ledger_key_manager::LedgerTransactionKeyManager;
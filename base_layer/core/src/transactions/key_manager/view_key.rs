@@ -0,0 +1,198 @@
+// Copyright 2023 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! The view key export handed to external scanners. It carries what is needed to detect one-sided payments to a
+//! wallet: the private view key and watched spend public key for stealth one-sided outputs, and the public keys of the
+//! wallet's known one-sided payment scripts for simple one-sided outputs. It contains no seed material.
+//!
+//! The encoding is `version || view key || spend public key || script key count || script public keys || checksum`
+//! with a DammSum checksum, usually carried as hex.
+
+use std::{
+    convert::TryFrom,
+    fmt::{Debug, Display, Formatter},
+    str::FromStr,
+};
+
+use tari_common_types::{
+    dammsum::{compute_checksum, validate_checksum},
+    types::{PrivateKey, PublicKey},
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    ByteArray,
+};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// The current version of the view key export encoding
+pub const VIEW_KEY_EXPORT_VERSION: u8 = 1;
+const KEY_SIZE: usize = 32;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ViewKeyExportError {
+    #[error("Invalid size")]
+    InvalidSize,
+    #[error("Unsupported view key export version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Invalid checksum")]
+    InvalidChecksum,
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    #[error("Too many script keys: {0}")]
+    TooManyScriptKeys(usize),
+    #[error("Invalid hex encoding")]
+    InvalidHex,
+}
+
+/// The keys needed to detect one-sided payments to a wallet. The view key also decrypts the value and commitment mask
+/// of the outputs it detects, so an export must be handled as a secret even though it cannot recover the wallet.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ViewKeyExport {
+    pub view_key: PrivateKey,
+    pub spend_public_key: PublicKey,
+    pub script_public_keys: Vec<PublicKey>,
+}
+
+impl ViewKeyExport {
+    pub fn new(view_key: PrivateKey, spend_public_key: PublicKey, script_public_keys: Vec<PublicKey>) -> Self {
+        Self {
+            view_key,
+            spend_public_key,
+            script_public_keys,
+        }
+    }
+
+    pub fn view_public_key(&self) -> PublicKey {
+        PublicKey::from_secret_key(&self.view_key)
+    }
+
+    pub fn to_bytes(&self) -> Result<Zeroizing<Vec<u8>>, ViewKeyExportError> {
+        let count = u8::try_from(self.script_public_keys.len())
+            .map_err(|_| ViewKeyExportError::TooManyScriptKeys(self.script_public_keys.len()))?;
+        let mut buf = Zeroizing::new(Vec::with_capacity(
+            2 * KEY_SIZE + 3 + self.script_public_keys.len() * KEY_SIZE,
+        ));
+        buf.push(VIEW_KEY_EXPORT_VERSION);
+        buf.extend_from_slice(self.view_key.as_bytes());
+        buf.extend_from_slice(self.spend_public_key.as_bytes());
+        buf.push(count);
+        for key in &self.script_public_keys {
+            buf.extend_from_slice(key.as_bytes());
+        }
+        let checksum = compute_checksum(&buf);
+        buf.push(checksum);
+        Ok(buf)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ViewKeyExportError> {
+        if bytes.len() < 2 * KEY_SIZE + 3 {
+            return Err(ViewKeyExportError::InvalidSize);
+        }
+        if bytes[0] != VIEW_KEY_EXPORT_VERSION {
+            return Err(ViewKeyExportError::UnsupportedVersion(bytes[0]));
+        }
+        let count = bytes[2 * KEY_SIZE + 1] as usize;
+        if bytes.len() != 2 * KEY_SIZE + 3 + count * KEY_SIZE {
+            return Err(ViewKeyExportError::InvalidSize);
+        }
+        validate_checksum(&bytes.to_vec()).map_err(|_| ViewKeyExportError::InvalidChecksum)?;
+
+        let view_key = PrivateKey::from_canonical_bytes(&bytes[1..=KEY_SIZE])
+            .map_err(|e| ViewKeyExportError::InvalidKey(e.to_string()))?;
+        let spend_public_key = PublicKey::from_canonical_bytes(&bytes[KEY_SIZE + 1..=2 * KEY_SIZE])
+            .map_err(|e| ViewKeyExportError::InvalidKey(e.to_string()))?;
+        let script_public_keys = bytes[2 * KEY_SIZE + 2..bytes.len() - 1]
+            .chunks(KEY_SIZE)
+            .map(|chunk| {
+                PublicKey::from_canonical_bytes(chunk).map_err(|e| ViewKeyExportError::InvalidKey(e.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            view_key,
+            spend_public_key,
+            script_public_keys,
+        })
+    }
+
+    pub fn to_hex(&self) -> Result<Zeroizing<String>, ViewKeyExportError> {
+        Ok(Zeroizing::new(self.to_bytes()?.to_hex()))
+    }
+
+    pub fn from_hex(hex_str: &str) -> Result<Self, ViewKeyExportError> {
+        let bytes = Zeroizing::new(from_hex(hex_str.trim()).map_err(|_| ViewKeyExportError::InvalidHex)?);
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl FromStr for ViewKeyExport {
+    type Err = ViewKeyExportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_hex(s)
+    }
+}
+
+impl Debug for ViewKeyExport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ViewKeyExport")
+            .field("view_key", &"<hidden>")
+            .field("spend_public_key", &self.spend_public_key.to_hex())
+            .field("script_public_keys", &self.script_public_keys.len())
+            .finish()
+    }
+}
+
+impl Display for ViewKeyExport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "view key export for {}", self.spend_public_key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_crypto::keys::SecretKey;
+
+    use super::*;
+
+    #[test]
+    fn it_round_trips_and_validates_the_checksum() {
+        let view_key = PrivateKey::random(&mut OsRng);
+        let export = ViewKeyExport::new(view_key.clone(), PublicKey::from_secret_key(&view_key), vec![
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        ]);
+        let hex = export.to_hex().unwrap();
+        assert_eq!(ViewKeyExport::from_hex(&hex).unwrap(), export);
+
+        let mut bytes = export.to_bytes().unwrap();
+        bytes[40] ^= 0x01;
+        assert!(ViewKeyExport::from_bytes(&bytes).is_err());
+        let bytes = export.to_bytes().unwrap();
+        assert_eq!(
+            ViewKeyExport::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ViewKeyExportError::InvalidSize)
+        );
+    }
+}
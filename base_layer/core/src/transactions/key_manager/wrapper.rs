@@ -39,12 +39,14 @@ use tari_key_manager::{
 use tokio::sync::RwLock;
 
 use crate::transactions::{
+    frost::{FrostNonceCommitment, FrostSigningTarget},
     key_manager::{
         interface::{SecretTransactionKeyManagerInterface, TxoStage},
         TariKeyId,
         TransactionKeyManagerBranch,
         TransactionKeyManagerInner,
         TransactionKeyManagerInterface,
+        ViewKeyExport,
     },
     tari_amount::MicroMinotari,
     transaction_components::{
@@ -206,6 +208,25 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .await
     }
 
+    async fn get_next_spend_and_script_key_ids_for_account(
+        &self,
+        account: u32,
+    ) -> Result<(TariKeyId, PublicKey, TariKeyId, PublicKey), KeyManagerServiceError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await
+    }
+
+    async fn get_branch_key_indices(&self) -> Result<Vec<(String, u64)>, KeyManagerServiceError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_branch_key_indices()
+            .await
+    }
+
     async fn find_script_key_id_from_spend_key_id(
         &self,
         spend_key_id: &TariKeyId,
@@ -473,6 +494,120 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .generate_burn_proof(spending_key, amount, claim_public_key)
             .await
     }
+
+    async fn get_frost_dkg_commitments(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        session_id: u64,
+    ) -> Result<(Vec<PublicKey>, Signature), TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_frost_dkg_commitments(polynomial_key_id, threshold, participant, session_id)
+            .await
+    }
+
+    async fn get_frost_dkg_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        recipient: u16,
+    ) -> Result<PrivateKey, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_frost_dkg_share(polynomial_key_id, threshold, recipient)
+            .await
+    }
+
+    async fn import_frost_signing_share(
+        &self,
+        polynomial_key_id: &TariKeyId,
+        threshold: u8,
+        participant: u16,
+        received_share_ids: &[TariKeyId],
+    ) -> Result<TariKeyId, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .import_frost_signing_share(polynomial_key_id, threshold, participant, received_share_ids)
+            .await
+    }
+
+    async fn get_frost_signature_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        hiding_nonce_id: &TariKeyId,
+        binding_nonce_id: &TariKeyId,
+        participant: u16,
+        group_public_key: &PublicKey,
+        target: &FrostSigningTarget,
+        commitments: &[FrostNonceCommitment],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_frost_signature_share(
+                signing_share_id,
+                hiding_nonce_id,
+                binding_nonce_id,
+                participant,
+                group_public_key,
+                target,
+                commitments,
+            )
+            .await
+    }
+
+    async fn get_frost_script_offset_share(
+        &self,
+        signing_share_id: &TariKeyId,
+        participant: u16,
+        signers: &[u16],
+        script_key_count: u64,
+        sender_offset_key_ids: &[TariKeyId],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_frost_script_offset_share(
+                signing_share_id,
+                participant,
+                signers,
+                script_key_count,
+                sender_offset_key_ids,
+            )
+            .await
+    }
+
+    async fn get_joint_sender_metadata_signature_share(
+        &self,
+        nonce_id: &TariKeyId,
+        sender_offset_key_id: &TariKeyId,
+        joint_nonce: &PublicKey,
+        joint_sender_offset_key: &PublicKey,
+        txo_version: &TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<PrivateKey, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_joint_sender_metadata_signature_share(
+                nonce_id,
+                sender_offset_key_id,
+                joint_nonce,
+                joint_sender_offset_key,
+                txo_version,
+                commitment,
+                ephemeral_commitment,
+                metadata_signature_message,
+            )
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -486,4 +621,17 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .get_private_key(key_id)
             .await
     }
+
+    async fn export_view_key(
+        &self,
+        view_key_id: &TariKeyId,
+        spend_public_key: &PublicKey,
+        script_key_ids: &[TariKeyId],
+    ) -> Result<ViewKeyExport, KeyManagerServiceError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .export_view_key(view_key_id, spend_public_key, script_key_ids)
+            .await
+    }
 }
@@ -32,7 +32,7 @@ use tari_key_manager::key_manager_service::KeyManagerServiceError;
 use tari_script::ScriptError;
 use thiserror::Error;
 
-use crate::transactions::transaction_components::EncryptedDataError;
+use crate::transactions::{frost::FrostError, transaction_components::EncryptedDataError};
 
 //----------------------------------------     TransactionError   ----------------------------------------------------//
 #[derive(Clone, Debug, PartialEq, Error, Deserialize, Serialize, Eq)]
@@ -61,6 +61,10 @@ pub enum TransactionError {
     ScriptError(#[from] ScriptError),
     #[error("The script offset in body does not balance")]
     ScriptOffset,
+    #[error("The inputs, outputs, fees and kernel excesses in body do not balance")]
+    InvalidAccountingBalance,
+    #[error("The body contains duplicated inputs or outputs")]
+    DuplicateInputsOrOutputs,
     #[error("Error executing script: {0}")]
     ScriptExecutionError(String),
     #[error("Compact TransactionInput is missing {0}")]
@@ -73,6 +77,8 @@ pub enum TransactionError {
     KeyManagerError(String),
     #[error("EncryptedData error: {0}")]
     EncryptedDataError(String),
+    #[error("FROST error: {0}")]
+    FrostError(String),
 }
 
 impl From<KeyManagerServiceError> for TransactionError {
@@ -87,6 +93,12 @@ impl From<EncryptedDataError> for TransactionError {
     }
 }
 
+impl From<FrostError> for TransactionError {
+    fn from(err: FrostError) -> Self {
+        TransactionError::FrostError(err.to_string())
+    }
+}
+
 impl From<RangeProofError> for TransactionError {
     fn from(e: RangeProofError) -> Self {
         TransactionError::RangeProofError(e.to_string())
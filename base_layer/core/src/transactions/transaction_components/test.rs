@@ -393,6 +393,30 @@ async fn test_validate_internal_consistency() {
     assert!(validator.validate(&tx, None, None, u64::MAX).is_ok());
 }
 
+#[tokio::test]
+async fn transaction_validate_internal_consistency() {
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let (tx, _, _) = test_helpers::create_tx(5000.into(), 3.into(), 1, 2, 1, 4, Default::default(), &key_manager)
+        .await
+        .expect("Failed to create tx");
+    let factories = CryptoFactories::default();
+    tx.validate_internal_consistency(&factories, None, u64::MAX).unwrap();
+
+    let mut unbalanced = tx.clone();
+    unbalanced.offset = PrivateKey::random(&mut OsRng);
+    assert_eq!(
+        unbalanced.validate_internal_consistency(&factories, None, u64::MAX),
+        Err(TransactionError::InvalidAccountingBalance)
+    );
+
+    let mut wrong_script_offset = tx;
+    wrong_script_offset.script_offset = PrivateKey::random(&mut OsRng);
+    assert_eq!(
+        wrong_script_offset.validate_internal_consistency(&factories, None, u64::MAX),
+        Err(TransactionError::ScriptOffset)
+    );
+}
+
 #[tokio::test]
 #[allow(clippy::identity_op)]
 async fn check_cut_through() {
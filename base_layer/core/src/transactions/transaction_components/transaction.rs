@@ -24,18 +24,30 @@
 // Version 2.0, available at http://www.apache.org/licenses/LICENSE-2.0.
 
 use std::{
+    convert::TryInto,
     fmt::{Display, Formatter},
     ops::Add,
 };
 
 use serde::{Deserialize, Serialize};
-use tari_common_types::types::{PrivateKey, Signature};
+use tari_common_types::types::{Commitment, HashOutput, PrivateKey, PublicKey, Signature};
+use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::PublicKey as PublicKeyTrait};
+use tari_script::ScriptContext;
 use tari_utilities::hex::Hex;
 
 use crate::transactions::{
     aggregated_body::AggregateBody,
-    transaction_components::{TransactionError, TransactionInput, TransactionKernel, TransactionOutput},
+    fee::{Fee, FeeBreakdown},
+    tari_amount::MicroMinotari,
+    transaction_components::{
+        transaction_output::batch_verify_range_proofs,
+        TransactionError,
+        TransactionInput,
+        TransactionKernel,
+        TransactionOutput,
+    },
     weight::TransactionWeight,
+    CryptoFactories,
 };
 
 /// A transaction which consists of a kernel offset and an aggregate body made up of inputs, outputs and kernels.
@@ -80,6 +92,18 @@ impl Transaction {
         self.body.calculate_weight(transaction_weight)
     }
 
+    /// Returns the consensus weight of this transaction broken down by component, along with the fee it would pay at
+    /// the given fee per gram
+    pub fn calculate_fee_breakdown(
+        &self,
+        transaction_weight: &TransactionWeight,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<FeeBreakdown, TransactionError> {
+        Fee::new(*transaction_weight)
+            .breakdown_body(fee_per_gram, &self.body)
+            .map_err(|e| TransactionError::SerializationError(e.to_string()))
+    }
+
     /// Returns the maximum maturity of the input UTXOs
     pub fn max_input_maturity(&self) -> Result<u64, TransactionError> {
         self.body.max_input_maturity()
@@ -99,6 +123,67 @@ impl Transaction {
     pub fn first_kernel_excess_sig(&self) -> Option<&Signature> {
         Some(&self.body.kernels().first()?.excess_sig)
     }
+
+    /// Validates this transaction on its own by checking the following:
+    /// 1. No input or output is duplicated
+    /// 1. The kernel signatures and output metadata signatures are valid
+    /// 1. The range proofs of the outputs are valid
+    /// 1. The sum of inputs, outputs and fees equal the (public excess value + offset)
+    /// 1. The input scripts run and the script offset balances
+    ///
+    /// This does NOT check that the inputs come from the UTXO set, nor any consensus rules that need the chain. The
+    /// inputs must carry the data of the outputs they spend.
+    pub fn validate_internal_consistency(
+        &self,
+        factories: &CryptoFactories,
+        prev_header: Option<HashOutput>,
+        height: u64,
+    ) -> Result<(), TransactionError> {
+        if self.body.contains_duplicated_inputs() || self.body.contains_duplicated_outputs() {
+            return Err(TransactionError::DuplicateInputsOrOutputs);
+        }
+        self.body.verify_kernel_signatures()?;
+        for output in self.body.outputs() {
+            output.verify_metadata_signature()?;
+        }
+        batch_verify_range_proofs(&factories.range_proof, &self.body.outputs().iter().collect::<Vec<_>>())?;
+
+        let sum_inputs = self
+            .body
+            .inputs()
+            .iter()
+            .map(|i| i.commitment())
+            .collect::<Result<Vec<&Commitment>, _>>()?
+            .into_iter()
+            .sum::<Commitment>();
+        let sum_outputs = self.body.outputs().iter().map(|o| &o.commitment).sum::<Commitment>();
+        let fees = self.body.get_total_fee()?;
+        let mut excess = factories.commitment.commit_value(&self.offset, 0);
+        for kernel in self.body.kernels() {
+            excess = &excess + &kernel.excess;
+        }
+        let fees = factories.commitment.commit_value(&PrivateKey::default(), fees.into());
+        if excess != &(&sum_outputs - &sum_inputs) + &fees {
+            return Err(TransactionError::InvalidAccountingBalance);
+        }
+
+        let prev_hash: [u8; 32] = prev_header.unwrap_or_default().as_slice().try_into().unwrap_or([0; 32]);
+        let mut input_keys = PublicKey::default();
+        for input in self.body.inputs() {
+            let context = ScriptContext::new(height, &prev_hash, input.commitment()?);
+            input_keys = input_keys + input.run_and_verify_script(&factories.commitment, Some(context))?;
+        }
+        let output_keys = self
+            .body
+            .outputs()
+            .iter()
+            .filter(|o| !o.is_coinbase())
+            .fold(PublicKey::default(), |acc, o| acc + o.sender_offset_public_key.clone());
+        if input_keys - output_keys != PublicKey::from_secret_key(&self.script_offset) {
+            return Err(TransactionError::ScriptOffset);
+        }
+        Ok(())
+    }
 }
 
 impl Add for Transaction {
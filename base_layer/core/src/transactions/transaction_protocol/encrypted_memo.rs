@@ -0,0 +1,194 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Payment memos encrypted to the recipient's public key.
+//!
+//! The sender generates an ephemeral key pair and derives an AEAD key from the Diffie-Hellman shared secret between the
+//! ephemeral secret key and the recipient's public key. Only the ephemeral public key and the ciphertext are sent, so
+//! the memo is never visible in plaintext to relaying nodes.
+
+use std::mem::size_of;
+
+use blake2::Blake2b;
+use chacha20poly1305::{
+    aead::{AeadCore, AeadInPlace, OsRng},
+    KeyInit,
+    Tag,
+    XChaCha20Poly1305,
+    XNonce,
+};
+use digest::{consts::U32, generic_array::GenericArray, FixedOutput};
+use serde::{Deserialize, Serialize};
+use tari_common_types::types::{PrivateKey, PublicKey};
+use tari_comms::types::CommsDHKE;
+use tari_crypto::{
+    hashing::DomainSeparatedHasher,
+    keys::{PublicKey as PublicKeyTrait, SecretKey},
+};
+use tari_utilities::{ByteArray, ByteArrayError};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+use crate::transactions::TransactionSecureNonceKdfDomain;
+
+/// The maximum length, in bytes, of a memo
+pub const MAX_MEMO_LENGTH: usize = 512;
+
+const SIZE_KEY: usize = PublicKey::KEY_LEN;
+const SIZE_NONCE: usize = size_of::<XNonce>();
+const SIZE_TAG: usize = size_of::<Tag>();
+
+/// AEAD associated data
+const ENCRYPTED_MEMO_AAD: &[u8] = b"TARI_AAD_PAYMENT_MEMO";
+
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+pub enum EncryptedMemoError {
+    #[error("Memo is {0} bytes, the maximum is {MAX_MEMO_LENGTH}")]
+    MemoTooLong(usize),
+    #[error("Encrypted memo has an invalid length of {0} bytes")]
+    IncorrectLength(usize),
+    #[error("Invalid ephemeral public key: {0}")]
+    InvalidPublicKey(#[from] ByteArrayError),
+    #[error("Encryption failed")]
+    EncryptionFailed,
+    #[error("Decryption failed")]
+    DecryptionFailed,
+    #[error("Decrypted memo is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+/// A memo that can only be read by the holder of the recipient's secret key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedMemo {
+    ephemeral_public_key: PublicKey,
+    data: Vec<u8>, // nonce, ciphertext, tag
+}
+
+impl EncryptedMemo {
+    /// Encrypts `memo` so that only the owner of `recipient` can decrypt it
+    pub fn encrypt(recipient: &PublicKey, memo: &str) -> Result<Self, EncryptedMemoError> {
+        if memo.len() > MAX_MEMO_LENGTH {
+            return Err(EncryptedMemoError::MemoTooLong(memo.len()));
+        }
+        let ephemeral_secret_key = PrivateKey::random(&mut OsRng);
+        let ephemeral_public_key = PublicKey::from_secret_key(&ephemeral_secret_key);
+        let shared_secret = CommsDHKE::new(&ephemeral_secret_key, recipient);
+
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let aead_key = kdf_aead(&shared_secret, &ephemeral_public_key);
+        let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(aead_key.as_slice()));
+
+        let mut bytes = memo.as_bytes().to_vec();
+        let tag = cipher
+            .encrypt_in_place_detached(&nonce, ENCRYPTED_MEMO_AAD, bytes.as_mut_slice())
+            .map_err(|_| EncryptedMemoError::EncryptionFailed)?;
+
+        let mut data = Vec::with_capacity(SIZE_NONCE + bytes.len() + SIZE_TAG);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&bytes);
+        data.extend_from_slice(&tag);
+        Ok(Self {
+            ephemeral_public_key,
+            data,
+        })
+    }
+
+    /// The public key the recipient uses, with their secret key, to compute the shared secret for
+    /// [decrypt](Self::decrypt)
+    pub fn ephemeral_public_key(&self) -> &PublicKey {
+        &self.ephemeral_public_key
+    }
+
+    /// Authenticates and decrypts the memo using the Diffie-Hellman shared secret between the recipient's secret key
+    /// and the ephemeral public key
+    pub fn decrypt(&self, shared_secret: &CommsDHKE) -> Result<String, EncryptedMemoError> {
+        let nonce = XNonce::from_slice(&self.data[..SIZE_NONCE]);
+        let tag = Tag::from_slice(&self.data[self.data.len() - SIZE_TAG..]);
+        let mut bytes = Zeroizing::new(self.data[SIZE_NONCE..self.data.len() - SIZE_TAG].to_vec());
+
+        let aead_key = kdf_aead(shared_secret, &self.ephemeral_public_key);
+        let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(aead_key.as_slice()));
+        cipher
+            .decrypt_in_place_detached(nonce, ENCRYPTED_MEMO_AAD, bytes.as_mut_slice(), tag)
+            .map_err(|_| EncryptedMemoError::DecryptionFailed)?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| EncryptedMemoError::InvalidUtf8)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(SIZE_KEY + self.data.len());
+        bytes.extend_from_slice(self.ephemeral_public_key.as_bytes());
+        bytes.extend_from_slice(&self.data);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EncryptedMemoError> {
+        if bytes.len() < SIZE_KEY + SIZE_NONCE + SIZE_TAG ||
+            bytes.len() > SIZE_KEY + SIZE_NONCE + MAX_MEMO_LENGTH + SIZE_TAG
+        {
+            return Err(EncryptedMemoError::IncorrectLength(bytes.len()));
+        }
+        Ok(Self {
+            ephemeral_public_key: PublicKey::from_canonical_bytes(&bytes[..SIZE_KEY])?,
+            data: bytes[SIZE_KEY..].to_vec(),
+        })
+    }
+}
+
+fn kdf_aead(shared_secret: &CommsDHKE, ephemeral_public_key: &PublicKey) -> Zeroizing<[u8; 32]> {
+    let mut aead_key = Zeroizing::new([0u8; 32]);
+    DomainSeparatedHasher::<Blake2b<U32>, TransactionSecureNonceKdfDomain>::new_with_label("encrypted_payment_memo")
+        .chain(shared_secret.as_bytes())
+        .chain(ephemeral_public_key.as_bytes())
+        .finalize_into(GenericArray::from_mut_slice(aead_key.as_mut_slice()));
+    aead_key
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_encrypts_and_decrypts_for_the_recipient_only() {
+        let (recipient_sk, recipient_pk) = PublicKey::random_keypair(&mut OsRng);
+        let (other_sk, _) = PublicKey::random_keypair(&mut OsRng);
+
+        let memo = EncryptedMemo::encrypt(&recipient_pk, "Invoice #42").unwrap();
+        let memo = EncryptedMemo::from_bytes(&memo.to_bytes()).unwrap();
+
+        let shared_secret = CommsDHKE::new(&recipient_sk, memo.ephemeral_public_key());
+        assert_eq!(memo.decrypt(&shared_secret).unwrap(), "Invoice #42");
+
+        let wrong_secret = CommsDHKE::new(&other_sk, memo.ephemeral_public_key());
+        assert_eq!(memo.decrypt(&wrong_secret), Err(EncryptedMemoError::DecryptionFailed));
+    }
+
+    #[test]
+    fn it_rejects_long_memos() {
+        let (_, recipient_pk) = PublicKey::random_keypair(&mut OsRng);
+        let memo = "x".repeat(MAX_MEMO_LENGTH + 1);
+        assert_eq!(
+            EncryptedMemo::encrypt(&recipient_pk, &memo),
+            Err(EncryptedMemoError::MemoTooLong(MAX_MEMO_LENGTH + 1))
+        );
+    }
+}
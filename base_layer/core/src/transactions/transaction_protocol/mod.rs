@@ -95,6 +95,9 @@ use thiserror::Error;
 
 use crate::transactions::{tari_amount::*, transaction_components::TransactionError};
 
+mod encrypted_memo;
+pub use encrypted_memo::{EncryptedMemo, EncryptedMemoError, MAX_MEMO_LENGTH};
+
 pub mod proto;
 pub mod recipient;
 pub mod sender;
@@ -29,7 +29,10 @@ use tari_script::TariScript;
 use tari_utilities::ByteArray;
 
 use super::{protocol as proto, protocol::transaction_sender_message::Message as ProtoTransactionSenderMessage};
-use crate::transactions::transaction_protocol::sender::{SingleRoundSenderData, TransactionSenderMessage};
+use crate::transactions::transaction_protocol::{
+    sender::{SingleRoundSenderData, TransactionSenderMessage},
+    EncryptedMemo,
+};
 
 impl proto::TransactionSenderMessage {
     pub fn none() -> Self {
@@ -100,6 +103,11 @@ impl TryFrom<proto::SingleRoundSenderData> for SingleRoundSenderData {
             .map(TryInto::try_into)
             .ok_or_else(|| "Transaction metadata not provided".to_string())??;
         let message = data.message;
+        let encrypted_memo = if data.encrypted_memo.is_empty() {
+            None
+        } else {
+            Some(EncryptedMemo::from_bytes(&data.encrypted_memo).map_err(|err| err.to_string())?)
+        };
         let ephemeral_public_nonce =
             PublicKey::from_canonical_bytes(&data.ephemeral_public_nonce).map_err(|err| err.to_string())?;
         let features = data
@@ -124,6 +132,7 @@ impl TryFrom<proto::SingleRoundSenderData> for SingleRoundSenderData {
             public_nonce,
             metadata,
             message,
+            encrypted_memo,
             features,
             script: TariScript::from_bytes(&data.script).map_err(|err| err.to_string())?,
             sender_offset_public_key,
@@ -151,6 +160,11 @@ impl TryFrom<SingleRoundSenderData> for proto::SingleRoundSenderData {
             public_nonce: sender_data.public_nonce.to_vec(),
             metadata: Some(sender_data.metadata.into()),
             message: sender_data.message,
+            encrypted_memo: sender_data
+                .encrypted_memo
+                .as_ref()
+                .map(EncryptedMemo::to_bytes)
+                .unwrap_or_default(),
             features: Some(sender_data.features.into()),
             script: sender_data.script.to_bytes(),
             sender_offset_public_key: sender_data.sender_offset_public_key.to_vec(),
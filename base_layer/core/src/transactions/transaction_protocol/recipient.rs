@@ -227,6 +227,7 @@ mod test {
             public_nonce: sender_test_params.public_nonce_key_pk, // any random key will do
             metadata: m.clone(),
             message: "".to_string(),
+            encrypted_memo: None,
             features,
             script,
             sender_offset_public_key: sender_test_params.sender_offset_key_pk,
@@ -55,6 +55,7 @@ use crate::{
         transaction_protocol::{
             recipient::RecipientSignedMessage,
             transaction_initializer::{RecipientDetails, SenderTransactionInitializer},
+            EncryptedMemo,
             TransactionMetadata,
             TransactionProtocolError as TPE,
         },
@@ -103,6 +104,9 @@ pub(super) struct RawTransactionInfo {
     pub metadata: TransactionMetadata,
     /// A user message sent to the receiver
     pub text_message: String,
+    /// A memo encrypted to the receiver's public key
+    #[serde(default)]
+    pub encrypted_memo: Option<EncryptedMemo>,
 }
 
 impl RawTransactionInfo {
@@ -133,6 +137,8 @@ pub struct SingleRoundSenderData {
     pub metadata: TransactionMetadata,
     /// Plain text message to receiver
     pub message: String,
+    /// A memo encrypted to the receiver's public key
+    pub encrypted_memo: Option<EncryptedMemo>,
     /// The output's features
     pub features: OutputFeatures,
     /// Script
@@ -357,6 +363,18 @@ impl SenderTransactionProtocol {
         }
     }
 
+    /// Attach a memo, encrypted to the recipient's public key, to the single round message. This must be done before
+    /// the message is sent to the recipient.
+    pub fn set_encrypted_memo(&mut self, memo: EncryptedMemo) -> Result<(), TPE> {
+        match &mut self.state {
+            SenderState::Initializing(info) | SenderState::SingleRoundMessageReady(info) => {
+                info.encrypted_memo = Some(memo);
+                Ok(())
+            },
+            _ => Err(TPE::InvalidStateError),
+        }
+    }
+
     /// Build the sender's message for the single-round protocol (one recipient) and move to next State
     pub async fn build_single_round_message<KM: TransactionKeyManagerInterface>(
         &mut self,
@@ -425,6 +443,7 @@ impl SenderTransactionProtocol {
                     public_excess,
                     metadata: info.metadata.clone(),
                     message: info.text_message.clone(),
+                    encrypted_memo: info.encrypted_memo.clone(),
                     features: recipient_output_features,
                     script: recipient_script,
                     sender_offset_public_key,
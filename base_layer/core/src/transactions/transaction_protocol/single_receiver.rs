@@ -279,6 +279,7 @@ mod test {
             public_nonce: pub_rs.clone(),
             metadata: m.clone(),
             message: "".to_string(),
+            encrypted_memo: None,
             features: OutputFeatures::default(),
             script: script.clone(),
             sender_offset_public_key,
@@ -49,6 +49,7 @@ use crate::{
         },
         transaction_protocol::{
             sender::{calculate_tx_id, OutputPair, RawTransactionInfo, SenderState, SenderTransactionProtocol},
+            EncryptedMemo,
             KernelFeatures,
             TransactionMetadata,
         },
@@ -94,6 +95,7 @@ pub struct SenderTransactionInitializer<KM> {
     change: Option<ChangeDetails>,
     recipient: Option<RecipientDetails>,
     recipient_text_message: Option<String>,
+    recipient_encrypted_memo: Option<EncryptedMemo>,
     prevent_fee_gt_amount: bool,
     tx_id: Option<TxId>,
     kernel_features: KernelFeatures,
@@ -125,6 +127,7 @@ where KM: TransactionKeyManagerInterface
             sender_custom_outputs: Vec::new(),
             change: None,
             recipient_text_message: None,
+            recipient_encrypted_memo: None,
             prevent_fee_gt_amount: true,
             recipient: None,
             kernel_features: KernelFeatures::empty(),
@@ -239,6 +242,12 @@ where KM: TransactionKeyManagerInterface
         self
     }
 
+    /// Provide a memo for the receiver that has been encrypted to their public key
+    pub fn with_encrypted_memo(&mut self, memo: EncryptedMemo) -> &mut Self {
+        self.recipient_encrypted_memo = Some(memo);
+        self
+    }
+
     /// This will select the desired kernel features to be signed by the receiver
     pub fn with_kernel_features(&mut self, features: KernelFeatures) -> &mut Self {
         self.kernel_features = features;
@@ -579,6 +588,7 @@ where KM: TransactionKeyManagerInterface
             inputs: self.inputs,
             outputs: self.sender_custom_outputs,
             text_message: self.recipient_text_message.unwrap_or_default(),
+            encrypted_memo: self.recipient_encrypted_memo,
         };
 
         let state = SenderState::Initializing(Box::new(sender_info));
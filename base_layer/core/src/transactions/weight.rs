@@ -48,6 +48,31 @@ impl WeightParams {
     }
 }
 
+/// The per-component weight (in grams) of a transaction, as used by consensus. The sum of the components is exactly
+/// the value returned by [TransactionWeight::calculate].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WeightBreakdown {
+    pub num_kernels: usize,
+    pub num_inputs: usize,
+    pub num_outputs: usize,
+    /// The sum of each output's features and scripts size, each rounded up to the per-gram boundary
+    pub rounded_features_and_scripts_byte_size: usize,
+    pub kernels_weight: u64,
+    pub inputs_weight: u64,
+    pub outputs_weight: u64,
+    pub features_and_scripts_weight: u64,
+}
+
+impl WeightBreakdown {
+    /// The total weight in grams
+    pub fn total(&self) -> u64 {
+        self.kernels_weight
+            .saturating_add(self.inputs_weight)
+            .saturating_add(self.outputs_weight)
+            .saturating_add(self.features_and_scripts_weight)
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TransactionWeight(WeightParams);
 
@@ -84,6 +109,41 @@ impl TransactionWeight {
             rounded_up_features_and_scripts_byte_size as u64 / params.features_and_scripts_bytes_per_gram.get()
     }
 
+    /// Calculates the same weight as `calculate`, broken down into its kernel, input, output and features_and_scripts
+    /// components.
+    pub fn breakdown(
+        &self,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+        rounded_up_features_and_scripts_byte_size: usize,
+    ) -> WeightBreakdown {
+        let params = self.params();
+        WeightBreakdown {
+            num_kernels,
+            num_inputs,
+            num_outputs,
+            rounded_features_and_scripts_byte_size: rounded_up_features_and_scripts_byte_size,
+            kernels_weight: params.kernel_weight * num_kernels as u64,
+            inputs_weight: params.input_weight * num_inputs as u64,
+            outputs_weight: params.output_weight * num_outputs as u64,
+            features_and_scripts_weight: rounded_up_features_and_scripts_byte_size as u64 /
+                params.features_and_scripts_bytes_per_gram.get(),
+        }
+    }
+
+    /// Calculates the weight breakdown of an aggregate body, using the same normalisation as `calculate_body`.
+    pub fn breakdown_body(&self, body: &AggregateBody) -> std::io::Result<WeightBreakdown> {
+        let rounded_up_features_and_scripts_bytes_size =
+            self.calculate_normalised_total_features_and_scripts_size(body)?;
+        Ok(self.breakdown(
+            body.kernels().len(),
+            body.inputs().len(),
+            body.outputs().len(),
+            rounded_up_features_and_scripts_bytes_size,
+        ))
+    }
+
     pub fn calculate_body(&self, body: &AggregateBody) -> std::io::Result<u64> {
         let rounded_up_features_and_scripts_bytes_size =
             self.calculate_normalised_total_features_and_scripts_size(body)?;
@@ -163,4 +223,15 @@ mod test {
             );
         }
     }
+
+    #[test]
+    fn breakdown_sums_to_calculated_weight() {
+        let weighting = TransactionWeight::latest();
+        let breakdown = weighting.breakdown(1, 3, 2, 96);
+        assert_eq!(breakdown.total(), weighting.calculate(1, 3, 2, 96));
+        assert_eq!(breakdown.kernels_weight, 10);
+        assert_eq!(breakdown.inputs_weight, 24);
+        assert_eq!(breakdown.outputs_weight, 106);
+        assert_eq!(breakdown.features_and_scripts_weight, 6);
+    }
 }
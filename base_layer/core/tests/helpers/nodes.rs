@@ -444,6 +444,7 @@ async fn setup_base_node_services(
     let rpc_server = rpc_server.add_service(base_node::create_base_node_sync_rpc_service(
         blockchain_db.clone().into(),
         base_node_service,
+        Vec::new(),
     ));
     let comms = comms
         .add_protocol_extension(rpc_server)
@@ -28,6 +28,7 @@ use tari_comms::peer_manager::NodeId;
 use tari_core::{
     base_node::{
         chain_metadata_service::PeerChainMetadata,
+        snapshot::SnapshotRegistry,
         state_machine_service::states::{BlockSync, HeaderSyncState, StateEvent, StatusInfo},
         sync::SyncPeer,
         BaseNodeStateMachine,
@@ -139,6 +140,7 @@ pub async fn create_network_with_local_and_peer_nodes() -> (
         state_change_event_publisher,
         RandomXFactory::default(),
         consensus_manager.clone(),
+        SnapshotRegistry::default(),
         shutdown.to_signal(),
     );
 
@@ -259,6 +259,46 @@ async fn test_insert_and_process_published_block() {
     assert_eq!(stats.unconfirmed_weight, 0);
 }
 
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn test_test_insert_does_not_modify_mempool() {
+    let network = Network::LocalNet;
+    let (mut store, mut blocks, mut outputs, consensus_manager, key_manager) = create_new_blockchain(network).await;
+    let mempool_validator = TransactionChainLinkedValidator::new(store.clone(), consensus_manager.clone());
+    let mempool = Mempool::new(
+        MempoolConfig::default(),
+        consensus_manager.clone(),
+        Box::new(mempool_validator),
+    );
+    let txs = vec![txn_schema!(
+        from: vec![outputs[0][0].clone()],
+        to: vec![2 * T, 2 * T],fee: 5.into(), lock: 0, features: OutputFeatures::default()
+    )];
+    generate_new_block(
+        &mut store,
+        &mut blocks,
+        &mut outputs,
+        txs,
+        &consensus_manager,
+        &key_manager,
+    )
+    .await
+    .unwrap();
+
+    let tx = txn_schema!(from: vec![outputs[1][0].clone()], to: vec![1*T], fee: 20*uT, lock: 0, features: OutputFeatures::default());
+    let tx = Arc::new(spend_utxos(tx, &key_manager).await.0);
+    let response = mempool.test_insert(tx.clone()).await.unwrap();
+    assert!(response.is_accepted());
+    assert!(response.fee_per_gram > MicroMinotari::zero());
+    assert!(response.rejection_reason.is_none());
+    assert_eq!(mempool.stats().await.unwrap().unconfirmed_txs, 0);
+
+    let (orphan, _, _) = tx!(1*T, fee: 100*uT, &key_manager).expect("Failed to get tx");
+    let response = mempool.test_insert(Arc::new(orphan)).await.unwrap();
+    assert_eq!(response.storage, TxStorageResponse::NotStoredOrphan);
+    assert!(response.rejection_reason.is_some());
+}
+
 #[tokio::test]
 #[allow(clippy::identity_op)]
 async fn test_time_locked() {
@@ -23,17 +23,15 @@
 use tari_common::configuration::Network;
 use tari_comms::test_utils::mocks::create_connectivity_mock;
 use tari_core::{
-    base_node::comms_interface::{
-        InboundNodeCommsHandlers,
-        NodeCommsRequest,
-        NodeCommsResponse,
-        OutboundNodeCommsInterface,
+    base_node::{
+        comms_interface::{InboundNodeCommsHandlers, NodeCommsRequest, NodeCommsResponse, OutboundNodeCommsInterface},
+        ReorgAlarm,
     },
     chain_storage::{BlockchainDatabaseConfig, Validators},
     consensus::ConsensusManager,
     covenants::Covenant,
     mempool::{Mempool, MempoolConfig},
-    proof_of_work::{randomx_factory::RandomXFactory, Difficulty},
+    proof_of_work::{randomx_factory::RandomXFactory, Difficulty, PowAlgorithm},
     test_helpers::{
         blockchain::{create_store_with_consensus_and_validators_and_config, create_test_blockchain_db},
         create_consensus_rules,
@@ -95,6 +93,7 @@ async fn inbound_get_metadata() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
     let block = store.fetch_block(0, true).unwrap().block().clone();
 
@@ -130,6 +129,7 @@ async fn inbound_fetch_kernel_by_excess_sig() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
     let block = store.fetch_block(0, true).unwrap().block().clone();
     let sig = block.body.kernels()[0].excess_sig.clone();
@@ -145,6 +145,53 @@ async fn inbound_fetch_kernel_by_excess_sig() {
     }
 }
 
+#[tokio::test]
+async fn inbound_get_pow_algorithm_status() {
+    let store = create_test_blockchain_db();
+    let mempool = new_mempool();
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build().unwrap();
+    let (block_event_sender, _) = broadcast::channel(50);
+    let (request_sender, _) = reply_channel::unbounded();
+    let (block_sender, _) = mpsc::unbounded_channel();
+    let outbound_nci = OutboundNodeCommsInterface::new(request_sender, block_sender);
+    let (connectivity, _) = create_connectivity_mock();
+    let randomx_factory = RandomXFactory::new(2);
+    let inbound_nch = InboundNodeCommsHandlers::new(
+        block_event_sender,
+        store.clone().into(),
+        mempool,
+        consensus_manager.clone(),
+        outbound_nci,
+        connectivity,
+        randomx_factory,
+        ReorgAlarm::disabled(),
+    );
+
+    if let Ok(NodeCommsResponse::PowAlgorithmStatus(statuses)) = inbound_nch
+        .handle_request(NodeCommsRequest::GetPowAlgorithmStatus)
+        .await
+    {
+        let constants = consensus_manager.consensus_constants(1);
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].pow_algo, PowAlgorithm::RandomX);
+        assert_eq!(statuses[1].pow_algo, PowAlgorithm::Sha3x);
+        for status in &statuses {
+            assert!(status.target_difficulty >= constants.min_pow_difficulty(status.pow_algo));
+            assert_eq!(
+                status.target_block_time,
+                constants.pow_target_block_interval(status.pow_algo)
+            );
+            // A single block can't have an average block time
+            assert!(status.average_block_time.is_none());
+        }
+        // Only the genesis block has been mined
+        assert_eq!(statuses.iter().map(|s| s.num_recent_blocks).sum::<u64>(), 1);
+    } else {
+        panic!();
+    }
+}
+
 #[tokio::test]
 async fn inbound_fetch_headers() {
     let store = create_test_blockchain_db();
@@ -165,6 +212,7 @@ async fn inbound_fetch_headers() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
     let header = store.fetch_block(0, true).unwrap().header().clone();
 
@@ -198,6 +246,7 @@ async fn inbound_fetch_utxos() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
     let block = store.fetch_block(0, true).unwrap().block().clone();
     let utxo_1 = block.body.outputs()[0].clone();
@@ -247,6 +296,7 @@ async fn inbound_fetch_blocks() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
     let block = store.fetch_block(0, true).unwrap().block().clone();
 
@@ -464,6 +514,7 @@ async fn inbound_fetch_blocks_before_horizon_height() {
         outbound_nci,
         connectivity,
         randomx_factory,
+        ReorgAlarm::disabled(),
     );
 
     let block1 = append_block(
@@ -29,6 +29,7 @@ use tari_common_types::chain_metadata::ChainMetadata;
 use tari_core::{
     base_node::{
         chain_metadata_service::PeerChainMetadata,
+        snapshot::SnapshotRegistry,
         state_machine_service::{
             states::{Listening, StateEvent, StatusInfo, SyncStatus::Lagging},
             BaseNodeStateMachine,
@@ -107,6 +108,7 @@ async fn test_listening_lagging() {
         state_change_event_publisher,
         RandomXFactory::default(),
         consensus_manager.clone(),
+        SnapshotRegistry::default(),
         shutdown.to_signal(),
     );
     wait_until_online(&[&alice_node, &bob_node]).await;
@@ -229,6 +231,7 @@ async fn test_listening_initial_fallen_behind() {
         state_change_event_publisher,
         RandomXFactory::default(),
         consensus_manager.clone(),
+        SnapshotRegistry::default(),
         shutdown.to_signal(),
     );
 
@@ -276,6 +279,7 @@ async fn test_event_channel() {
         state_change_event_publisher,
         RandomXFactory::default(),
         consensus_manager,
+        SnapshotRegistry::default(),
         shutdown.to_signal(),
     );
 
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS imported_keys;
+
+DROP TABLE IF EXISTS key_manager_states;
@@ -0,0 +1,13 @@
+CREATE TABLE key_manager_states (
+    id                SERIAL PRIMARY KEY NOT NULL,
+    branch_seed       TEXT UNIQUE        NOT NULL,
+    primary_key_index BYTEA              NOT NULL,
+    timestamp         TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE imported_keys (
+    id                SERIAL PRIMARY KEY NOT NULL,
+    private_key       BYTEA UNIQUE       NOT NULL,
+    public_key        TEXT               NOT NULL,
+    timestamp         TIMESTAMP          NOT NULL
+);
@@ -48,6 +48,7 @@ use crate::{
     LABEL_ARGON_ENCODING,
     LABEL_CHACHA20_ENCODING,
     LABEL_MAC_GENERATION,
+    LABEL_SEED_PASSPHRASE,
 };
 
 // The version should be incremented for any breaking change to the format
@@ -327,6 +328,36 @@ impl CipherSeed {
         self.birthday
     }
 
+    /// Derive the seed of the wallet protected by an additional seed passphrase, similar to the BIP39 "25th word".
+    /// Unlike the passphrase used to encipher the seed, any seed passphrase is valid and each one yields a different
+    /// wallet. The derived seed keeps the birthday of this seed, but it is not meant to be shown as seed words, those
+    /// of this seed together with the seed passphrase are what recovers the wallet.
+    pub fn derive_with_seed_passphrase(&self, seed_passphrase: &SafePassword) -> Result<CipherSeed, KeyManagerError> {
+        // The Argon2 salt is derived from the seed so that the same passphrase on different seeds cannot be attacked
+        // at once
+        let argon2_salt = mac_domain_hasher::<Blake2b<U32>>(LABEL_SEED_PASSPHRASE)
+            .chain(self.entropy.as_ref())
+            .chain(self.salt.as_ref())
+            .finalize();
+        let argon2_salt = &argon2_salt.as_ref()[..ARGON2_SALT_BYTES];
+
+        // We use the same OWASP parameters as for the cipher seed keys
+        let params = argon2::Params::new(46 * 1024, 1, 1, Some(CIPHER_SEED_ENTROPY_BYTES))
+            .map_err(|_| KeyManagerError::CryptographicError("Problem generating Argon2 parameters".to_string()))?;
+        let mut entropy = Box::new([0u8; CIPHER_SEED_ENTROPY_BYTES]);
+        let hasher = argon2::Argon2::new(argon2::Algorithm::Argon2d, argon2::Version::V0x13, params);
+        hasher
+            .hash_password_into(seed_passphrase.reveal(), argon2_salt, entropy.as_mut())
+            .map_err(|_| KeyManagerError::CryptographicError("Problem generating Argon2 password hash".to_string()))?;
+
+        Ok(Self {
+            version: self.version,
+            birthday: self.birthday,
+            entropy,
+            salt: self.salt.clone(),
+        })
+    }
+
     /// Generate a MAC using Blake2b
     fn generate_mac(
         birthday: &[u8],
@@ -629,6 +660,26 @@ mod test {
         );
     }
 
+    #[test]
+    fn seed_passphrase_derives_a_different_seed() {
+        let seed = CipherSeed::new();
+        let derived = seed
+            .derive_with_seed_passphrase(&SafePassword::from_str("25th word").unwrap())
+            .unwrap();
+        assert_ne!(derived.entropy(), seed.entropy());
+        assert_eq!(derived.birthday(), seed.birthday());
+
+        // The derivation is deterministic and depends on the passphrase
+        let same = seed
+            .derive_with_seed_passphrase(&SafePassword::from_str("25th word").unwrap())
+            .unwrap();
+        assert_eq!(derived, same);
+        let other = seed
+            .derive_with_seed_passphrase(&SafePassword::from_str("26th word").unwrap())
+            .unwrap();
+        assert_ne!(derived, other);
+    }
+
     #[test]
     fn birthday_from_unix_epoch_works_for_zero_duration() {
         let birthday = 0u16;
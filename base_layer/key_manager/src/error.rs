@@ -47,6 +47,12 @@ pub enum KeyManagerError {
     SliceError(String),
     #[error("Key ID not valid")]
     InvalidKeyID,
+    #[error("Cannot split a seed into {share_count} shares with a threshold of {threshold}")]
+    InvalidShareThreshold { threshold: u8, share_count: u8 },
+    #[error("At least {0} distinct seed shares are needed to recover the seed")]
+    InsufficientSeedShares(u8),
+    #[error("The seed shares are not all from the same split")]
+    SeedShareMismatch,
 }
 
 impl From<ByteArrayError> for KeyManagerError {
@@ -47,6 +47,8 @@ pub enum KeyManagerServiceError {
     RangeProofError(String),
     #[error("Tari Key Manager error: `{0}`")]
     TariKeyManagerError(#[from] KMError),
+    #[error("Signing device error: `{0}`")]
+    SigningDeviceError(String),
 }
 
 impl From<RangeProofError> for KeyManagerServiceError {
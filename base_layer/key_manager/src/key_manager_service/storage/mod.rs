@@ -21,4 +21,6 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod database;
+#[cfg(feature = "postgres")]
+pub mod postgres_db;
 pub mod sqlite_db;
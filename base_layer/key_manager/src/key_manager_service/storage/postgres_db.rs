@@ -0,0 +1,234 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    sync::{Arc, RwLock},
+};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use diesel::{
+    pg::PgConnection,
+    prelude::*,
+    r2d2::{ConnectionManager, Pool, PooledConnection},
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tari_common_sqlite::{error::SqliteStorageError, util::diesel_ext::ExpectedRowsExtension};
+use tari_common_types::encryption::Encryptable;
+use tari_crypto::keys::PublicKey;
+use tari_utilities::{acquire_read_lock, hex::Hex};
+
+use crate::{
+    key_manager_service::{
+        error::KeyManagerStorageError,
+        storage::{
+            database::{ImportedKey, KeyManagerBackend, KeyManagerState},
+            sqlite_db::{reencrypt, ImportedKeySql, KeyManagerStateSql, NewImportedKeySql, NewKeyManagerStateSql},
+        },
+    },
+    schema::{imported_keys, key_manager_states},
+};
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations_postgres");
+
+type PgConnectionManager = ConnectionManager<PgConnection>;
+
+/// A PostgreSQL backend for the Key Manager Service, kept in the same database as the rest of a wallet stored on a
+/// database server. Rows are stored and encrypted exactly as in
+/// [KeyManagerSqliteDatabase](super::sqlite_db::KeyManagerSqliteDatabase).
+#[derive(Clone)]
+pub struct KeyManagerPostgresDatabase {
+    pool: Pool<PgConnectionManager>,
+    cipher: Arc<RwLock<XChaCha20Poly1305>>,
+}
+
+impl KeyManagerPostgresDatabase {
+    /// Connect to the database at the given `postgres://` url and bring its schema up to date
+    pub fn init(database_url: &str, pool_size: u32, cipher: XChaCha20Poly1305) -> Result<Self, KeyManagerStorageError> {
+        Self::init_with_shared_cipher(database_url, pool_size, Arc::new(RwLock::new(cipher)))
+    }
+
+    /// As [KeyManagerPostgresDatabase::init], but the cipher is shared with the owner of the database encryption key so
+    /// that a key rotation is picked up without reopening the backend
+    pub fn init_with_shared_cipher(
+        database_url: &str,
+        pool_size: u32,
+        cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    ) -> Result<Self, KeyManagerStorageError> {
+        let pool = Pool::builder()
+            .max_size(pool_size)
+            .build(PgConnectionManager::new(database_url))
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()))?;
+        let db = Self { pool, cipher };
+        db.get_connection()?
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|e| KeyManagerStorageError::DatabaseMigrationError(e.to_string()))?;
+        Ok(db)
+    }
+
+    fn get_connection(&self) -> Result<PooledConnection<PgConnectionManager>, KeyManagerStorageError> {
+        self.pool
+            .get()
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()).into())
+    }
+
+    fn find_state(branch: &str, conn: &mut PgConnection) -> Result<KeyManagerStateSql, KeyManagerStorageError> {
+        key_manager_states::table
+            .filter(key_manager_states::branch_seed.eq(branch))
+            .first::<KeyManagerStateSql>(conn)
+            .optional()?
+            .ok_or(KeyManagerStorageError::KeyManagerNotInitialized)
+    }
+
+    /// Encrypt and store the given index for the branch
+    fn write_index(&self, branch: &str, index: u64, conn: &mut PgConnection) -> Result<(), KeyManagerStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        let mut km = Self::find_state(branch, conn)?;
+        km.primary_key_index = index.to_le_bytes().to_vec();
+        let km = km
+            .encrypt(&cipher)
+            .map_err(|e| KeyManagerStorageError::AeadError(format!("Encryption Error: {}", e)))?;
+        diesel::update(key_manager_states::table.filter(key_manager_states::id.eq(km.id)))
+            .set(key_manager_states::primary_key_index.eq(&km.primary_key_index))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn read_state(&self, branch: &str, conn: &mut PgConnection) -> Result<KeyManagerState, KeyManagerStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        let km = Self::find_state(branch, conn)?
+            .decrypt(&cipher)
+            .map_err(|e| KeyManagerStorageError::AeadError(format!("Decryption Error: {}", e)))?;
+        KeyManagerState::try_from(km)
+    }
+}
+
+/// Re-encrypt the index of every key manager branch, moving it from the `old` to the `new` cipher. Returns the number
+/// of rows that were updated.
+pub fn reencrypt_key_manager_states(
+    conn: &mut PgConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, KeyManagerStorageError> {
+    let states = key_manager_states::table.load::<KeyManagerStateSql>(conn)?;
+    for state in &states {
+        let state = reencrypt(state.clone(), old, new)?;
+        diesel::update(key_manager_states::table.filter(key_manager_states::id.eq(state.id)))
+            .set(key_manager_states::primary_key_index.eq(&state.primary_key_index))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+    }
+    Ok(states.len())
+}
+
+/// Re-encrypt the private key of every imported key, moving it from the `old` to the `new` cipher. Returns the number
+/// of rows that were updated.
+pub fn reencrypt_imported_keys(
+    conn: &mut PgConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, KeyManagerStorageError> {
+    let keys = imported_keys::table.load::<ImportedKeySql>(conn)?;
+    for key in &keys {
+        let key = reencrypt(key.clone(), old, new)?;
+        diesel::update(imported_keys::table.filter(imported_keys::id.eq(key.id)))
+            .set(imported_keys::private_key.eq(&key.private_key))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+    }
+    Ok(keys.len())
+}
+
+impl<PK> KeyManagerBackend<PK> for KeyManagerPostgresDatabase
+where PK: PublicKey
+{
+    fn get_key_manager(&self, branch: &str) -> Result<Option<KeyManagerState>, KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        match self.read_state(branch, &mut conn) {
+            Ok(state) => Ok(Some(state)),
+            Err(KeyManagerStorageError::KeyManagerNotInitialized) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn add_key_manager(&self, key_manager: KeyManagerState) -> Result<(), KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let km_sql = NewKeyManagerStateSql::from(key_manager)
+            .encrypt(&cipher)
+            .map_err(|e| KeyManagerStorageError::AeadError(format!("Encryption Error: {}", e)))?;
+        diesel::insert_into(key_manager_states::table)
+            .values(km_sql)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn increment_key_index(&self, branch: &str) -> Result<(), KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        conn.transaction::<_, KeyManagerStorageError, _>(|conn| {
+            let index = self.read_state(branch, conn)?.primary_key_index + 1;
+            self.write_index(branch, index, conn)
+        })
+    }
+
+    fn set_key_index(&self, branch: &str, index: u64) -> Result<(), KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        self.write_index(branch, index, &mut conn)
+    }
+
+    fn insert_imported_key(&self, public_key: PK, private_key: PK::K) -> Result<(), KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        if find_imported_key(&public_key, &mut conn)?.is_some() {
+            // we already have the key so we dont have to add it in
+            return Ok(());
+        }
+        let cipher = acquire_read_lock!(self.cipher);
+        let key = ImportedKey {
+            public_key,
+            private_key,
+        };
+        let encrypted_key = NewImportedKeySql::new_from_imported_key(key, &cipher)?;
+        diesel::insert_into(imported_keys::table)
+            .values(encrypted_key)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn get_imported_key(&self, public_key: &PK) -> Result<PK::K, KeyManagerStorageError> {
+        let mut conn = self.get_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let key = find_imported_key(public_key, &mut conn)?.ok_or(KeyManagerStorageError::KeyManagerNotInitialized)?;
+        Ok(key.to_imported_key::<PK>(&cipher)?.private_key)
+    }
+}
+
+fn find_imported_key<PK: PublicKey>(
+    public_key: &PK,
+    conn: &mut PgConnection,
+) -> Result<Option<ImportedKeySql>, KeyManagerStorageError> {
+    Ok(imported_keys::table
+        .filter(imported_keys::public_key.eq(public_key.to_hex()))
+        .first::<ImportedKeySql>(conn)
+        .optional()?)
+}
@@ -22,6 +22,7 @@
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
 use diesel::{prelude::*, SqliteConnection};
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
 use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
 use tari_crypto::keys::PublicKey;
 use tari_utilities::{hex::Hex, ByteArray, Hidden};
@@ -35,7 +36,7 @@ use crate::{
             sqlite_db::{imported_keys, Encryptable},
         },
     },
-    schema::imported_keys::{private_key, public_key, table, timestamp},
+    schema::imported_keys::{id, private_key, public_key, table, timestamp},
 };
 
 /// Represents a row in the imported keys table.
@@ -119,6 +120,15 @@ impl ImportedKeySql {
             .first::<ImportedKeySql>(conn)
             .map_err(|_| KeyManagerStorageError::KeyManagerNotInitialized)
     }
+
+    /// Writes the (re-)encrypted private key of this instance back to the database.
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), KeyManagerStorageError> {
+        diesel::update(imported_keys::table.filter(imported_keys::id.eq(self.id)))
+            .set(imported_keys::private_key.eq(&self.private_key))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
 }
 
 impl Encryptable<XChaCha20Poly1305> for ImportedKeySql {
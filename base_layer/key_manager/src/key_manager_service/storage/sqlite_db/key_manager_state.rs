@@ -143,6 +143,11 @@ impl KeyManagerStateSql {
             .num_rows_affected_or_not_found(1)?;
         Ok(())
     }
+
+    /// Writes the (re-)encrypted key index of this instance back to the database.
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), KeyManagerStorageError> {
+        KeyManagerStateSql::set_index(self.id, self.primary_key_index.clone(), conn)
+    }
 }
 
 impl Encryptable<XChaCha20Poly1305> for KeyManagerStateSql {
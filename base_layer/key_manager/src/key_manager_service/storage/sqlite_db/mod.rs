@@ -26,7 +26,9 @@ use std::{
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
+use diesel::SqliteConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+pub use imported_keys::{ImportedKeySql, NewImportedKeySql};
 pub use key_manager_state::{KeyManagerStateSql, NewKeyManagerStateSql};
 use log::*;
 use tari_common_sqlite::{error::SqliteStorageError, sqlite_connection_pool::PooledDbConnection};
@@ -37,10 +39,7 @@ use tokio::time::Instant;
 
 use crate::key_manager_service::{
     error::KeyManagerStorageError,
-    storage::{
-        database::{ImportedKey, KeyManagerBackend, KeyManagerState},
-        sqlite_db::imported_keys::{ImportedKeySql, NewImportedKeySql},
-    },
+    storage::database::{ImportedKey, KeyManagerBackend, KeyManagerState},
 };
 
 mod imported_keys;
@@ -75,6 +74,20 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + C
         db
     }
 
+    /// As [KeyManagerSqliteDatabase::init], but the cipher is shared with the owner of the database encryption key so
+    /// that a key rotation is picked up without reopening the backend
+    pub fn init_with_shared_cipher(
+        database_connection: TKeyManagerDbConnection,
+        cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    ) -> Self {
+        let db = Self {
+            database_connection: Arc::new(database_connection),
+            cipher,
+        };
+        db.run_migrations().expect("Migrations to run");
+        db
+    }
+
     fn run_migrations(&self) -> Result<Vec<String>, SqliteStorageError> {
         let mut conn = self.database_connection.get_pooled_connection()?;
         conn.run_pending_migrations(MIGRATIONS)
@@ -93,6 +106,46 @@ impl<TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + C
     }
 }
 
+/// Re-encrypt the index of every key manager branch, moving it from the `old` to the `new` cipher. Returns the number
+/// of rows that were updated.
+pub fn reencrypt_key_manager_states(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, KeyManagerStorageError> {
+    let states = KeyManagerStateSql::index(conn)?;
+    for state in &states {
+        reencrypt(state.clone(), old, new)?.update_encryption(conn)?;
+    }
+    Ok(states.len())
+}
+
+/// Re-encrypt the private key of every imported key, moving it from the `old` to the `new` cipher. Returns the number
+/// of rows that were updated.
+pub fn reencrypt_imported_keys(
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, KeyManagerStorageError> {
+    let keys = ImportedKeySql::index(conn)?;
+    for key in &keys {
+        reencrypt(key.clone(), old, new)?.update_encryption(conn)?;
+    }
+    Ok(keys.len())
+}
+
+pub(crate) fn reencrypt<T: Encryptable<XChaCha20Poly1305>>(
+    value: T,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<T, KeyManagerStorageError> {
+    value
+        .decrypt(old)
+        .map_err(|e| KeyManagerStorageError::AeadError(format!("Decryption Error: {}", e)))?
+        .encrypt(new)
+        .map_err(|e| KeyManagerStorageError::AeadError(format!("Encryption Error: {}", e)))
+}
+
 impl<TKeyManagerDbConnection, PK> KeyManagerBackend<PK> for KeyManagerSqliteDatabase<TKeyManagerDbConnection>
 where
     TKeyManagerDbConnection: PooledDbConnection<Error = SqliteStorageError> + Send + Sync + Clone,
@@ -100,7 +153,7 @@ where
 {
     fn get_key_manager(&self, branch: &str) -> Result<Option<KeyManagerState>, KeyManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = match KeyManagerStateSql::get_state(branch, &mut conn).ok() {
@@ -238,7 +291,7 @@ where
 
     fn get_imported_key(&self, public_key: &PK) -> Result<PK::K, KeyManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
         let key = ImportedKeySql::get_key(public_key, &mut conn)?;
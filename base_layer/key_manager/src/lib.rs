@@ -27,6 +27,7 @@ pub mod mnemonic;
 pub mod mnemonic_wordlists;
 #[cfg(feature = "key_manager_service")]
 pub mod schema;
+pub mod seed_shares;
 
 hash_domain!(KeyManagerDomain, "com.tari.base_layer.key_manager", 1);
 
@@ -34,6 +35,7 @@ const LABEL_ARGON_ENCODING: &str = "argon2_encoding";
 const LABEL_CHACHA20_ENCODING: &str = "chacha20_encoding";
 const LABEL_MAC_GENERATION: &str = "mac_generation";
 const LABEL_DERIVE_KEY: &str = "derive_key";
+const LABEL_SEED_PASSPHRASE: &str = "seed_passphrase";
 
 pub(crate) fn mac_domain_hasher<D: Digest + LengthExtensionAttackResistant>(
     label: &'static str,
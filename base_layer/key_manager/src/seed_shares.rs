@@ -0,0 +1,344 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Shamir secret sharing of a cipher seed, so that a seed backup can be split into `n` shares of which any `k`
+//! recover it, while fewer than `k` reveal nothing about it.
+//!
+//! The shared secret is the enciphered seed, i.e. the bytes the 24 seed words encode, so a seed protected by a
+//! passphrase still needs that passphrase once it has been recovered from its shares. Each byte of the enciphered seed
+//! is shared separately with a random polynomial of degree `k - 1` over GF(2^8).
+//!
+//! A share is encoded as follows and shown to the user as 31 seed words:
+//! version     1 byte
+//! split id    2 bytes
+//! threshold   1 byte
+//! index       1 byte
+//! share data  33 bytes
+//! checksum    4 bytes
+//!
+//! The split id is random for every split, it allows shares of different splits of the same seed to be told apart.
+
+use crc32fast::Hasher as CrcHasher;
+use rand::{rngs::OsRng, RngCore};
+use tari_utilities::{hidden::Hidden, SafePassword};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    cipher_seed::{
+        CipherSeed,
+        CIPHER_SEED_BIRTHDAY_BYTES,
+        CIPHER_SEED_CHECKSUM_BYTES,
+        CIPHER_SEED_ENTROPY_BYTES,
+        CIPHER_SEED_MAC_BYTES,
+        CIPHER_SEED_MAIN_SALT_BYTES,
+    },
+    error::KeyManagerError,
+    mnemonic::{from_bytes, to_bytes, MnemonicLanguage},
+    SeedWords,
+};
+
+// The version should be incremented for any breaking change to the format
+const SEED_SHARE_VERSION: u8 = 1u8;
+
+pub const ENCIPHERED_SEED_BYTES: usize = 1 +
+    CIPHER_SEED_BIRTHDAY_BYTES +
+    CIPHER_SEED_ENTROPY_BYTES +
+    CIPHER_SEED_MAC_BYTES +
+    CIPHER_SEED_MAIN_SALT_BYTES +
+    CIPHER_SEED_CHECKSUM_BYTES;
+const SEED_SHARE_CHECKSUM_BYTES: usize = 4;
+pub const SEED_SHARE_BYTES: usize = 5 + ENCIPHERED_SEED_BYTES + SEED_SHARE_CHECKSUM_BYTES;
+/// The number of seed words a share is encoded as
+pub const SEED_SHARE_WORD_COUNT: usize = (SEED_SHARE_BYTES * 8 + 10) / 11;
+
+/// One share of a cipher seed split with [CipherSeed::split_into_shares]
+#[derive(Clone, Debug, PartialEq, Eq, Zeroize)]
+#[zeroize(drop)]
+pub struct SeedShare {
+    split_id: u16,
+    threshold: u8,
+    index: u8,
+    data: Box<[u8; ENCIPHERED_SEED_BYTES]>,
+}
+
+impl SeedShare {
+    /// The index of this share, from 1 up to the number of shares in the split
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The number of shares needed to recover the seed
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(SEED_SHARE_BYTES));
+        bytes.push(SEED_SHARE_VERSION);
+        bytes.extend(self.split_id.to_le_bytes());
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend(self.data.iter());
+        let mut crc_hasher = CrcHasher::new();
+        crc_hasher.update(bytes.as_slice());
+        bytes.extend(crc_hasher.finalize().to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, KeyManagerError> {
+        if bytes.len() != SEED_SHARE_BYTES {
+            return Err(KeyManagerError::InvalidData);
+        }
+        if bytes[0] != SEED_SHARE_VERSION {
+            return Err(KeyManagerError::VersionMismatch);
+        }
+        let (payload, checksum) = bytes.split_at(SEED_SHARE_BYTES - SEED_SHARE_CHECKSUM_BYTES);
+        let mut crc_hasher = CrcHasher::new();
+        crc_hasher.update(payload);
+        if checksum != crc_hasher.finalize().to_le_bytes() {
+            return Err(KeyManagerError::CrcError);
+        }
+        let threshold = payload[3];
+        let index = payload[4];
+        if threshold == 0 || index == 0 {
+            return Err(KeyManagerError::InvalidData);
+        }
+        let mut data = Box::new([0u8; ENCIPHERED_SEED_BYTES]);
+        data.copy_from_slice(&payload[5..]);
+        Ok(Self {
+            split_id: u16::from_le_bytes([payload[1], payload[2]]),
+            threshold,
+            index,
+            data,
+        })
+    }
+
+    pub fn to_mnemonic(&self, language: MnemonicLanguage) -> Result<SeedWords, KeyManagerError> {
+        Ok(from_bytes(&self.to_bytes(), language)?)
+    }
+
+    /// Parses a share from its seed words, the language of the words is autodetected
+    pub fn from_mnemonic(words: &SeedWords) -> Result<Self, KeyManagerError> {
+        if words.len() != SEED_SHARE_WORD_COUNT {
+            return Err(KeyManagerError::InvalidData);
+        }
+        let bytes = to_bytes(words)?;
+        // The words carry a few bits of padding which must be zero
+        if bytes.reveal().len() < SEED_SHARE_BYTES || bytes.reveal()[SEED_SHARE_BYTES..].iter().any(|b| *b != 0) {
+            return Err(KeyManagerError::InvalidData);
+        }
+        Self::from_bytes(&bytes.reveal()[..SEED_SHARE_BYTES])
+    }
+}
+
+impl CipherSeed {
+    /// Splits the enciphered seed into `share_count` shares of which any `threshold` recover the seed
+    pub fn split_into_shares(
+        &self,
+        threshold: u8,
+        share_count: u8,
+        passphrase: Option<SafePassword>,
+    ) -> Result<Vec<SeedShare>, KeyManagerError> {
+        if threshold == 0 || threshold > share_count {
+            return Err(KeyManagerError::InvalidShareThreshold { threshold, share_count });
+        }
+        let secret = Zeroizing::new(self.encipher(passphrase)?);
+        let mut split_id = [0u8; 2];
+        OsRng.fill_bytes(&mut split_id);
+        let split_id = u16::from_le_bytes(split_id);
+
+        let mut shares = (1..=share_count)
+            .map(|index| SeedShare {
+                split_id,
+                threshold,
+                index,
+                data: Box::new([0u8; ENCIPHERED_SEED_BYTES]),
+            })
+            .collect::<Vec<_>>();
+        // The constant term of each byte's polynomial is the secret byte, the other coefficients are random
+        let mut coefficients = Zeroizing::new(vec![0u8; usize::from(threshold)]);
+        for (i, secret_byte) in secret.iter().enumerate() {
+            coefficients[0] = *secret_byte;
+            OsRng.fill_bytes(&mut coefficients[1..]);
+            for share in &mut shares {
+                share.data[i] = gf256_evaluate(&coefficients, share.index);
+            }
+        }
+        Ok(shares)
+    }
+
+    /// Recovers a seed from at least `threshold` of the shares it was split into
+    pub fn from_shares(shares: &[SeedShare], passphrase: Option<SafePassword>) -> Result<Self, KeyManagerError> {
+        let enciphered_seed = combine_seed_shares(shares)?;
+        CipherSeed::from_enciphered_bytes(enciphered_seed.reveal(), passphrase)
+    }
+}
+
+/// Recovers the enciphered seed from at least `threshold` of the shares it was split into. This is what the seed words
+/// of the original seed encode.
+pub fn combine_seed_shares(shares: &[SeedShare]) -> Result<Hidden<Vec<u8>>, KeyManagerError> {
+    let first = shares.first().ok_or(KeyManagerError::InsufficientSeedShares(1))?;
+    if shares
+        .iter()
+        .any(|s| s.split_id != first.split_id || s.threshold != first.threshold)
+    {
+        return Err(KeyManagerError::SeedShareMismatch);
+    }
+    let mut indices = shares.iter().map(|s| s.index).collect::<Vec<_>>();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() < usize::from(first.threshold) {
+        return Err(KeyManagerError::InsufficientSeedShares(first.threshold));
+    }
+    // Any `threshold` distinct shares define the polynomials, extra shares add nothing
+    let mut used = Vec::with_capacity(usize::from(first.threshold));
+    for share in shares {
+        if used.len() < usize::from(first.threshold) && !used.iter().any(|s: &&SeedShare| s.index == share.index) {
+            used.push(share);
+        }
+    }
+    let lagrange_coefficients = used
+        .iter()
+        .map(|share| {
+            used.iter()
+                .filter(|other| other.index != share.index)
+                .fold(1u8, |acc, other| {
+                    // The basis polynomial at zero: the product of x_j / (x_j - x_i), subtraction is xor in GF(2^8)
+                    gf256_mul(acc, gf256_mul(other.index, gf256_inverse(other.index ^ share.index)))
+                })
+        })
+        .collect::<Vec<_>>();
+
+    let mut secret = Hidden::hide(vec![0u8; ENCIPHERED_SEED_BYTES]);
+    for (i, byte) in secret.reveal_mut().iter_mut().enumerate() {
+        *byte = used
+            .iter()
+            .zip(&lagrange_coefficients)
+            .fold(0u8, |acc, (share, coefficient)| {
+                acc ^ gf256_mul(share.data[i], *coefficient)
+            });
+    }
+    Ok(secret)
+}
+
+/// Converts the seed words of enough shares into the seed words of the seed they were split from, in the language of
+/// the shares
+pub fn combine_seed_share_words(share_words: &[SeedWords]) -> Result<SeedWords, KeyManagerError> {
+    let first = share_words.first().ok_or(KeyManagerError::InsufficientSeedShares(1))?;
+    let language = MnemonicLanguage::detect_language(first)?;
+    let shares = share_words
+        .iter()
+        .map(SeedShare::from_mnemonic)
+        .collect::<Result<Vec<_>, _>>()?;
+    let enciphered_seed = combine_seed_shares(&shares)?;
+    Ok(from_bytes(enciphered_seed.reveal(), language)?)
+}
+
+/// Multiplication in GF(2^8) with the AES reduction polynomial x^8 + x^4 + x^3 + x + 1
+fn gf256_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    for _ in 0..8 {
+        // Branchless, so the timing does not depend on the secret bytes
+        product ^= a & 0u8.wrapping_sub(b & 1);
+        let carry = 0u8.wrapping_sub(a >> 7);
+        a = (a << 1) ^ (carry & 0x1b);
+        b >>= 1;
+    }
+    product
+}
+
+/// The multiplicative inverse in GF(2^8), a^254
+fn gf256_inverse(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exponent = 254u8;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = gf256_mul(result, base);
+        }
+        base = gf256_mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+/// Evaluates the polynomial with the given coefficients, lowest degree first, at `x` using Horner's method
+fn gf256_evaluate(coefficients: &[u8], x: u8) -> u8 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(0u8, |acc, coefficient| gf256_mul(acc, x) ^ coefficient)
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use tari_utilities::SafePassword;
+
+    use super::*;
+
+    #[test]
+    fn it_recovers_the_seed_from_any_threshold_of_shares() {
+        let passphrase = Some(SafePassword::from_str("Passphrase").unwrap());
+        let seed = CipherSeed::new();
+        let shares = seed.split_into_shares(3, 5, passphrase.clone()).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for (a, b, c) in [(0, 1, 2), (0, 2, 4), (4, 3, 1), (1, 2, 3)] {
+            let subset = vec![shares[a].clone(), shares[b].clone(), shares[c].clone()];
+            assert_eq!(CipherSeed::from_shares(&subset, passphrase.clone()).unwrap(), seed);
+        }
+        assert_eq!(
+            CipherSeed::from_shares(&shares[..2], passphrase.clone()),
+            Err(KeyManagerError::InsufficientSeedShares(3))
+        );
+        // The same share twice does not count towards the threshold
+        let repeated = vec![shares[0].clone(), shares[0].clone(), shares[1].clone()];
+        assert_eq!(
+            CipherSeed::from_shares(&repeated, passphrase.clone()),
+            Err(KeyManagerError::InsufficientSeedShares(3))
+        );
+        // Shares of another split cannot be mixed in
+        let other = seed.split_into_shares(3, 5, passphrase.clone()).unwrap();
+        let mixed = vec![shares[0].clone(), shares[1].clone(), other[2].clone()];
+        assert!(CipherSeed::from_shares(&mixed, passphrase).is_err());
+    }
+
+    #[test]
+    fn it_encodes_shares_as_seed_words() {
+        let seed = CipherSeed::new();
+        let shares = seed.split_into_shares(2, 3, None).unwrap();
+        let words = shares
+            .iter()
+            .map(|s| s.to_mnemonic(MnemonicLanguage::Spanish).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(words[0].len(), SEED_SHARE_WORD_COUNT);
+        assert_eq!(SeedShare::from_mnemonic(&words[1]).unwrap(), shares[1]);
+
+        let seed_words = combine_seed_share_words(&words[1..]).unwrap();
+        assert_eq!(
+            seed_words,
+            crate::mnemonic::Mnemonic::to_mnemonic(&seed, MnemonicLanguage::Spanish, None).unwrap()
+        );
+    }
+}
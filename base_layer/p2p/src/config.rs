@@ -115,6 +115,12 @@ pub struct P2pConfig {
     pub listener_liveness_check_interval: Option<Duration>,
     /// CIDR for addresses allowed to enter into liveness check mode on the listener.
     pub listener_liveness_allowlist_cidrs: StringList,
+    /// Peers that are permitted to connect to or be dialed by this node. Entries may be node IDs, public keys (hex) or
+    /// CIDR ranges for clearnet addresses. If empty, all peers that are not denied are permitted.
+    pub allowed_peers: StringList,
+    /// Peers that may never connect to or be dialed by this node. Entries may be node IDs, public keys (hex) or CIDR
+    /// ranges for clearnet addresses. The deny list takes precedence over the allow list.
+    pub denied_peers: StringList,
     /// User agent string for this node
     pub user_agent: String,
     /// The address to bind on using the TCP transport _in addition to_ the primary transport. This is typically useful
@@ -148,6 +154,8 @@ impl Default for P2pConfig {
             listener_liveness_max_sessions: 0,
             listener_liveness_check_interval: None,
             listener_liveness_allowlist_cidrs: StringList::default(),
+            allowed_peers: StringList::default(),
+            denied_peers: StringList::default(),
             user_agent: String::new(),
             auxiliary_tcp_listener_address: None,
             rpc_max_simultaneous_sessions: 100,
@@ -41,6 +41,7 @@ use tari_common::{
 };
 use tari_comms::{
     backoff::ConstantBackoff,
+    connection_manager::{PeerAccessConfig, PeerAccessList},
     multiaddr::multiaddr,
     peer_manager::{NodeIdentity, Peer, PeerFeatures, PeerFlags, PeerManagerError},
     pipeline,
@@ -52,7 +53,14 @@ use tari_comms::{
     },
     tor,
     tor::HiddenServiceControllerError,
-    transports::{predicate::FalsePredicate, MemoryTransport, SocksConfig, SocksTransport, TcpWithTorTransport},
+    transports::{
+        predicate::{is_onion_address, FalsePredicate},
+        MemoryTransport,
+        OnionOnlyTransport,
+        SocksConfig,
+        SocksTransport,
+        TcpWithTorTransport,
+    },
     utils::cidr::parse_cidrs,
     CommsBuilder,
     CommsBuilderError,
@@ -97,6 +105,8 @@ pub enum CommsInitializationError {
     HiddenServiceBuilderError(#[from] tor::HiddenServiceBuilderError),
     #[error("Invalid liveness CIDRs error: `{0}`")]
     InvalidLivenessCidrs(String),
+    #[error("Invalid peer access list: `{0}`")]
+    InvalidPeerAccessList(String),
     #[error("Could not add seed peers to comms layer: `{0}`")]
     FailedToAddSeedPeer(#[from] PeerManagerError),
     #[error("Cannot acquire exclusive file lock, another instance of the application is already running")]
@@ -105,6 +115,8 @@ pub enum CommsInitializationError {
     InvalidTorForwardAddress(std::io::Error),
     #[error("IO Error: `{0}`")]
     IoError(#[from] std::io::Error),
+    #[error("Onion-only mode: {0}")]
+    OnionOnlyViolation(String),
 }
 
 impl CommsInitializationError {
@@ -213,6 +225,7 @@ pub async fn spawn_comms_using_transport(
     comms: UnspawnedCommsNode,
     transport_config: TransportConfig,
 ) -> Result<CommsNode, CommsInitializationError> {
+    transport_config.check_onion_only()?;
     let comms = match transport_config.transport_type {
         TransportType::Memory => {
             debug!(target: LOG_TARGET, "Building in-memory comms stack");
@@ -260,13 +273,17 @@ pub async fn spawn_comms_using_transport(
                 listener_address_override,
             );
 
-            comms
+            let comms = comms
                 .with_listener_address(
                     listener_address_override.unwrap_or_else(|| multiaddr![Ip4([127, 0, 0, 1]), Tcp(0u16)]),
                 )
-                .with_hidden_service_controller(hidden_service_ctl)
-                .spawn_with_transport(transport)
-                .await?
+                .with_hidden_service_controller(hidden_service_ctl);
+            if transport_config.onion_only {
+                info!(target: LOG_TARGET, "Onion-only mode: only onion addresses will be dialed");
+                comms.spawn_with_transport(OnionOnlyTransport::new(transport)).await?
+            } else {
+                comms.spawn_with_transport(transport).await?
+            }
         },
         TransportType::Socks5 => {
             debug!(target: LOG_TARGET, "Building SOCKS5 comms stack");
@@ -304,6 +321,24 @@ fn initialize_hidden_service(
     Ok(hidden_svc_ctl)
 }
 
+/// Checks that no part of the p2p configuration can reach the clearnet when the transport is onion-only
+fn check_onion_only(config: &P2pConfig) -> Result<(), CommsInitializationError> {
+    config.transport.check_onion_only()?;
+    if let Some(ref addr) = config.auxiliary_tcp_listener_address {
+        return Err(CommsInitializationError::OnionOnlyViolation(format!(
+            "onion_only cannot be combined with the auxiliary TCP listener '{}'",
+            addr
+        )));
+    }
+    if let Some(addr) = config.public_addresses.iter().find(|addr| !is_onion_address(addr)) {
+        return Err(CommsInitializationError::OnionOnlyViolation(format!(
+            "onion_only requires onion public addresses, not '{}'",
+            addr
+        )));
+    }
+    Ok(())
+}
+
 async fn configure_comms_and_dht(
     builder: CommsBuilder,
     config: &P2pConfig,
@@ -324,10 +359,15 @@ async fn configure_comms_and_dht(
 
     let listener_liveness_allowlist_cidrs = parse_cidrs(&config.listener_liveness_allowlist_cidrs)
         .map_err(CommsInitializationError::InvalidLivenessCidrs)?;
+    let peer_access = PeerAccessConfig {
+        allow: PeerAccessList::parse(&config.allowed_peers).map_err(CommsInitializationError::InvalidPeerAccessList)?,
+        deny: PeerAccessList::parse(&config.denied_peers).map_err(CommsInitializationError::InvalidPeerAccessList)?,
+    };
 
     let builder = builder
         .with_listener_liveness_max_sessions(config.listener_liveness_max_sessions)
         .with_listener_liveness_allowlist_cidrs(listener_liveness_allowlist_cidrs)
+        .with_peer_access_config(peer_access)
         .with_dial_backoff(ConstantBackoff::new(Duration::from_millis(500)))
         .with_peer_storage(peer_database, Some(file_lock));
 
@@ -566,17 +606,27 @@ impl ServiceInitializer for P2pInitializer {
             config.dht.peer_validator_config = builder.peer_validator_config().clone();
         }
 
+        if config.transport.onion_only {
+            check_onion_only(&config)?;
+        }
+
         let (comms, dht) = configure_comms_and_dht(builder, &config, connector).await?;
 
         let peer_manager = comms.peer_manager();
         let node_identity = comms.node_identity();
 
-        let peers = match Self::try_resolve_dns_seeds(&self.seed_config).await {
-            Ok(peers) => peers,
-            Err(err) => {
-                warn!(target: LOG_TARGET, "Failed to resolve DNS seeds: {}", err);
-                Vec::new()
-            },
+        let peers = if config.transport.onion_only {
+            // DNS seed lookups do not go through tor
+            debug!(target: LOG_TARGET, "Onion-only mode: not resolving DNS seeds");
+            Vec::new()
+        } else {
+            match Self::try_resolve_dns_seeds(&self.seed_config).await {
+                Ok(peers) => peers,
+                Err(err) => {
+                    warn!(target: LOG_TARGET, "Failed to resolve DNS seeds: {}", err);
+                    Vec::new()
+                },
+            }
         };
         add_seed_peers(&peer_manager, &node_identity, peers).await?;
 
@@ -592,3 +642,58 @@ impl ServiceInitializer for P2pInitializer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn onion_only_config() -> P2pConfig {
+        P2pConfig {
+            transport: TransportConfig {
+                onion_only: true,
+                ..TransportConfig::new_tor(TorTransportConfig::default())
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_allows_onion_public_addresses() {
+        let mut config = onion_only_config();
+        config.public_addresses = vec!["/onion3/vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd:18141"
+            .parse()
+            .unwrap()]
+        .into();
+        check_onion_only(&config).unwrap();
+    }
+
+    #[test]
+    fn it_rejects_clearnet_public_addresses() {
+        let mut config = onion_only_config();
+        config.public_addresses = vec!["/ip4/1.2.3.4/tcp/18141".parse().unwrap()].into();
+        assert!(matches!(
+            check_onion_only(&config),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_the_auxiliary_tcp_listener() {
+        let mut config = onion_only_config();
+        config.auxiliary_tcp_listener_address = Some("/ip4/127.0.0.1/tcp/18188".parse().unwrap());
+        assert!(matches!(
+            check_onion_only(&config),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn it_checks_the_transport() {
+        let mut config = onion_only_config();
+        config.transport.tor.proxy_bypass_for_outbound_tcp = true;
+        assert!(matches!(
+            check_onion_only(&config),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt::{Display, Formatter},
+    io,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use log::*;
+use tokio::{net::UdpSocket, time};
+
+use super::LOG_TARGET;
+
+/// Seconds between the NTP epoch (1900-01-01) and the unix epoch (1970-01-01)
+const NTP_UNIX_EPOCH_DELTA_SECS: u64 = 2_208_988_800;
+const NTP_PACKET_SIZE: usize = 48;
+const NTP_QUERY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The estimated offset of the local clock. Offsets are `remote - local` in milliseconds, so a positive value means
+/// the local clock is behind.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ClockSkew {
+    /// Median offset reported by recently ponged peers, if enough samples are available
+    pub peer_offset_ms: Option<i64>,
+    /// The number of peer samples used to calculate `peer_offset_ms`
+    pub num_peer_samples: usize,
+    /// Offset obtained from the last successful NTP query, if NTP servers are configured
+    pub ntp_offset_ms: Option<i64>,
+}
+
+impl ClockSkew {
+    /// The best available estimate of the local clock offset. NTP is preferred over peer samples.
+    pub fn offset_ms(&self) -> Option<i64> {
+        self.ntp_offset_ms.or(self.peer_offset_ms)
+    }
+
+    /// Returns true if the estimated offset is known and greater than `tolerance`
+    pub fn exceeds(&self, tolerance: Duration) -> bool {
+        self.offset_ms()
+            .map(|offset| u128::from(offset.unsigned_abs()) > tolerance.as_millis())
+            .unwrap_or(false)
+    }
+}
+
+impl Display for ClockSkew {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.offset_ms() {
+            Some(offset) => write!(f, "{}ms", offset)?,
+            None => write!(f, "unknown")?,
+        }
+        write!(
+            f,
+            " (peers: {} from {} sample(s), ntp: {})",
+            self.peer_offset_ms
+                .map(|o| format!("{}ms", o))
+                .unwrap_or_else(|| "n/a".to_string()),
+            self.num_peer_samples,
+            self.ntp_offset_ms
+                .map(|o| format!("{}ms", o))
+                .unwrap_or_else(|| "n/a".to_string()),
+        )
+    }
+}
+
+/// The current unix time of the local clock in milliseconds
+pub fn unix_timestamp_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| u64::try_from(d.as_millis()).unwrap_or(u64::MAX))
+        .unwrap_or(0)
+}
+
+/// Queries each NTP server in turn and returns the clock offset from the first one that responds
+pub async fn query_ntp_servers(servers: &[String]) -> Option<i64> {
+    for server in servers {
+        match query_ntp_offset(server).await {
+            Ok(offset) => {
+                trace!(target: LOG_TARGET, "NTP server {} reports clock offset {}ms", server, offset);
+                return Some(offset);
+            },
+            Err(err) => {
+                debug!(target: LOG_TARGET, "NTP query to {} failed: {}", server, err);
+            },
+        }
+    }
+    None
+}
+
+/// Performs a single SNTP (RFC 4330) query against `server` (`host:port`) and returns the local clock offset in
+/// milliseconds.
+pub async fn query_ntp_offset(server: &str) -> io::Result<i64> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.connect(server).await?;
+
+    let mut request = [0u8; NTP_PACKET_SIZE];
+    // LI = 0 (no warning), VN = 3, Mode = 3 (client)
+    request[0] = 0x1b;
+    let t1 = unix_timestamp_millis();
+    socket.send(&request).await?;
+
+    let mut response = [0u8; NTP_PACKET_SIZE];
+    let n = time::timeout(NTP_QUERY_TIMEOUT, socket.recv(&mut response))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "NTP query timed out"))??;
+    let t4 = unix_timestamp_millis();
+    if n < NTP_PACKET_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "NTP response too short"));
+    }
+
+    let t2 = ntp_timestamp_to_unix_millis(&response[32..40])?;
+    let t3 = ntp_timestamp_to_unix_millis(&response[40..48])?;
+
+    let (t1, t2, t3, t4) = (i128::from(t1), i128::from(t2), i128::from(t3), i128::from(t4));
+    let offset = ((t2 - t1) + (t3 - t4)) / 2;
+    i64::try_from(offset).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "NTP offset out of range"))
+}
+
+fn ntp_timestamp_to_unix_millis(bytes: &[u8]) -> io::Result<u64> {
+    let mut secs = [0u8; 4];
+    let mut fraction = [0u8; 4];
+    secs.copy_from_slice(&bytes[0..4]);
+    fraction.copy_from_slice(&bytes[4..8]);
+    let secs = u64::from(u32::from_be_bytes(secs));
+    let fraction = u64::from(u32::from_be_bytes(fraction));
+    let unix_secs = secs
+        .checked_sub(NTP_UNIX_EPOCH_DELTA_SECS)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "NTP timestamp before unix epoch"))?;
+    Ok(unix_secs * 1000 + ((fraction * 1000) >> 32))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_converts_ntp_timestamps() {
+        let mut bytes = [0u8; 8];
+        bytes[0..4].copy_from_slice(&u32::try_from(NTP_UNIX_EPOCH_DELTA_SECS + 10).unwrap().to_be_bytes());
+        bytes[4..8].copy_from_slice(&(u32::MAX / 2 + 1).to_be_bytes());
+        assert_eq!(ntp_timestamp_to_unix_millis(&bytes).unwrap(), 10_500);
+    }
+
+    #[test]
+    fn it_prefers_ntp_offset() {
+        let skew = ClockSkew {
+            peer_offset_ms: Some(-2_000),
+            num_peer_samples: 5,
+            ntp_offset_ms: Some(100),
+        };
+        assert_eq!(skew.offset_ms(), Some(100));
+        assert!(!skew.exceeds(Duration::from_secs(1)));
+        let skew = ClockSkew {
+            ntp_offset_ms: None,
+            ..skew
+        };
+        assert!(skew.exceeds(Duration::from_secs(1)));
+        assert!(!ClockSkew::default().exceeds(Duration::ZERO));
+    }
+}
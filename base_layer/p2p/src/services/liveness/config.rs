@@ -35,6 +35,20 @@ pub struct LivenessConfig {
     pub monitored_peers: Vec<NodeId>,
     /// Number of ping failures to tolerate before disconnecting the peer. A value of zero disables this feature.
     pub max_allowed_ping_failures: usize,
+    /// The maximum tolerated offset between the local clock and the network before a
+    /// [ClockSkewDetected](super::LivenessEvent::ClockSkewDetected) event is raised, or None to disable clock skew
+    /// checks (default: None (disabled))
+    pub max_clock_skew: Option<Duration>,
+    /// NTP servers (`host:port`) queried every `ntp_query_interval` to estimate clock skew. NTP queries are sent in
+    /// the clear over UDP, so this should be left empty on privacy sensitive nodes, in which case clock skew is
+    /// estimated from peer timestamps only (Default: <empty>)
+    pub ntp_servers: Vec<String>,
+    /// The interval between NTP queries, the last offset obtained is used for clock skew checks in between (Default:
+    /// 10 minutes)
+    pub ntp_query_interval: Duration,
+    /// Reply to pings received from peers. Disabling this stops peers from learning that this node is online by
+    /// pinging it, outbound pings are unaffected (Default: true)
+    pub respond_to_pings: bool,
 }
 
 impl Default for LivenessConfig {
@@ -44,6 +58,10 @@ impl Default for LivenessConfig {
             num_peers_per_round: 8,
             monitored_peers: Default::default(),
             max_allowed_ping_failures: 2,
+            max_clock_skew: None,
+            ntp_servers: Vec::new(),
+            ntp_query_interval: Duration::from_secs(10 * 60),
+            respond_to_pings: true,
         }
     }
 }
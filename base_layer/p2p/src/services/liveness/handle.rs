@@ -27,7 +27,7 @@ use tari_service_framework::reply_channel::SenderService;
 use tokio::sync::broadcast;
 use tower::Service;
 
-use super::{error::LivenessError, state::Metadata};
+use super::{clock_skew::ClockSkew, error::LivenessError, state::Metadata};
 use crate::proto::liveness::MetadataKey;
 
 /// Request types made through the `LivenessHandle` and are handled by the `LivenessService`
@@ -49,6 +49,10 @@ pub enum LivenessRequest {
     AddMonitoredPeer(NodeId),
     /// Remove a monitored peer from the basic config
     RemoveMonitoredPeer(NodeId),
+    /// Get the current estimate of the local clock skew
+    GetClockSkew,
+    /// Enable or disable replying to received pings
+    SetRespondToPings(bool),
 }
 
 /// Response type for `LivenessService`
@@ -62,6 +66,8 @@ pub enum LivenessResponse {
     AvgLatency(Option<Duration>),
     /// The number of active neighbouring peers
     NumActiveNeighbours(usize),
+    /// Response for GetClockSkew
+    ClockSkew(ClockSkew),
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -73,6 +79,10 @@ pub enum LivenessEvent {
     ReceivedPong(Box<PingPongEvent>),
     /// A round of pings was broadcast to random and monitored peers
     PingRoundBroadcast(usize),
+    /// The local clock offset exceeded the configured maximum clock skew
+    ClockSkewDetected(ClockSkew),
+    /// The local clock offset returned to within the configured maximum clock skew
+    ClockSkewResolved(ClockSkew),
 }
 
 /// Represents a ping or pong event
@@ -192,4 +202,20 @@ impl LivenessHandle {
             _ => Err(LivenessError::UnexpectedApiResponse),
         }
     }
+
+    /// Retrieve the current estimate of the local clock skew
+    pub async fn get_clock_skew(&mut self) -> Result<ClockSkew, LivenessError> {
+        match self.handle.call(LivenessRequest::GetClockSkew).await?? {
+            LivenessResponse::ClockSkew(skew) => Ok(skew),
+            _ => Err(LivenessError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Enable or disable replying to pings received from peers. Received pings are still counted and published.
+    pub async fn set_respond_to_pings(&mut self, respond: bool) -> Result<(), LivenessError> {
+        match self.handle.call(LivenessRequest::SetRespondToPings(respond)).await?? {
+            LivenessResponse::Ok => Ok(()),
+            _ => Err(LivenessError::UnexpectedApiResponse),
+        }
+    }
 }
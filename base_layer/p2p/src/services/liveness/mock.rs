@@ -148,6 +148,12 @@ impl LivenessMock {
             RemoveMonitoredPeer(_) => {
                 reply.send(Ok(LivenessResponse::Ok)).unwrap();
             },
+            GetClockSkew => {
+                reply.send(Ok(LivenessResponse::ClockSkew(Default::default()))).unwrap();
+            },
+            SetRespondToPings(_) => {
+                reply.send(Ok(LivenessResponse::Ok)).unwrap();
+            },
         }
     }
 }
@@ -35,6 +35,9 @@
 //! [LivenessRequest]: ./messages/enum.LivenessRequets.html
 //! [PingPong]: ./messages/enum.PingPong.html
 
+mod clock_skew;
+pub use clock_skew::ClockSkew;
+
 pub mod config;
 pub use self::config::LivenessConfig;
 
@@ -20,7 +20,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{iter, sync::Arc, time::Instant};
+use std::{
+    iter,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use futures::{future::Either, pin_mut, stream::StreamExt, Stream};
 use log::*;
@@ -35,21 +39,26 @@ use tari_comms_dht::{
 };
 use tari_service_framework::reply_channel::RequestContext;
 use tari_shutdown::ShutdownSignal;
-use tokio::{sync::RwLock, time, time::MissedTickBehavior};
+use tokio::{
+    sync::{mpsc, RwLock},
+    time,
+    time::MissedTickBehavior,
+};
 use tokio_stream::wrappers;
 
 use super::{
+    clock_skew::{self, ClockSkew},
     config::LivenessConfig,
     error::LivenessError,
     message::{PingPong, PingPongMessage},
-    state::LivenessState,
+    state::{LivenessState, Metadata},
     LivenessRequest,
     LivenessResponse,
     LOG_TARGET,
 };
 use crate::{
     domain_message::DomainMessage,
-    services::liveness::{handle::LivenessEventSender, LivenessEvent, PingPongEvent},
+    services::liveness::{handle::LivenessEventSender, LivenessEvent, MetadataKey, PingPongEvent},
     tari_message::TariMessageType,
 };
 
@@ -64,6 +73,8 @@ pub struct LivenessService<THandleStream, TPingStream> {
     event_publisher: LivenessEventSender,
     shutdown_signal: ShutdownSignal,
     monitored_peers: Arc<RwLock<Vec<NodeId>>>,
+    ntp_offset_ms: Option<i64>,
+    is_clock_skewed: bool,
 }
 
 impl<TRequestStream, TPingStream> LivenessService<TRequestStream, TPingStream>
@@ -91,6 +102,8 @@ where
             shutdown_signal,
             config: config.clone(),
             monitored_peers: Arc::new(RwLock::new(config.monitored_peers)),
+            ntp_offset_ms: None,
+            is_clock_skewed: false,
         }
     }
 
@@ -112,6 +125,8 @@ where
             None => Either::Right(futures::stream::iter(iter::empty())),
         };
 
+        let mut ntp_offsets = self.spawn_ntp_queries();
+
         loop {
             tokio::select! {
                 // Requests from the handle
@@ -130,6 +145,13 @@ where
                             error!(target: LOG_TARGET, "Error occurred while disconnecting failed peers: {}", err);
                         }
                     }
+                    self.check_clock_skew();
+                },
+
+                // NTP offsets are queried in their own task so that slow NTP servers do not hold up this service
+                Some(ntp_offset_ms) = ntp_offsets.recv() => {
+                    self.ntp_offset_ms = ntp_offset_ms;
+                    self.check_clock_skew();
                 },
 
                 // Incoming messages from the Comms layer
@@ -176,8 +198,16 @@ where
         match ping_pong_msg.kind().ok_or(LivenessError::InvalidPingPongType)? {
             PingPong::Ping => {
                 self.state.inc_pings_received();
-                self.send_pong(ping_pong_msg.nonce, public_key).await?;
-                self.state.inc_pongs_sent();
+                if self.config.respond_to_pings {
+                    self.send_pong(ping_pong_msg.nonce, public_key).await?;
+                    self.state.inc_pongs_sent();
+                } else {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Not replying to ping from peer '{}' because pong replies are disabled",
+                        node_id.short_str()
+                    );
+                }
 
                 debug!(
                     target: LOG_TARGET,
@@ -203,6 +233,10 @@ where
                 }
 
                 let maybe_latency = self.state.record_pong(ping_pong_msg.nonce, &node_id);
+                let metadata = Metadata::from(ping_pong_msg.metadata);
+                if let Some(latency) = maybe_latency {
+                    self.record_peer_clock_offset(&node_id, &metadata, latency);
+                }
                 debug!(
                     target: LOG_TARGET,
                     "Received pong from peer '{}' with useragent '{}'. {} (Trace: {})",
@@ -214,7 +248,7 @@ where
                     message_tag,
                 );
 
-                let pong_event = PingPongEvent::new(node_id, maybe_latency, ping_pong_msg.metadata.into());
+                let pong_event = PingPongEvent::new(node_id, maybe_latency, metadata);
                 self.publish_event(LivenessEvent::ReceivedPong(Box::new(pong_event)));
             },
         }
@@ -222,7 +256,7 @@ where
     }
 
     async fn send_ping(&mut self, node_id: NodeId) -> Result<(), LivenessError> {
-        let msg = PingPongMessage::ping_with_metadata(self.state.metadata().clone());
+        let msg = PingPongMessage::ping_with_metadata(self.outbound_metadata());
         self.state.add_inflight_ping(msg.nonce, node_id.clone());
         debug!(target: LOG_TARGET, "Sending ping to peer '{}'", node_id.short_str(),);
 
@@ -239,7 +273,7 @@ where
     }
 
     async fn send_pong(&mut self, nonce: u64, dest: CommsPublicKey) -> Result<(), LivenessError> {
-        let msg = PingPongMessage::pong_with_metadata(nonce, self.state.metadata().clone());
+        let msg = PingPongMessage::pong_with_metadata(nonce, self.outbound_metadata());
         self.outbound_messaging
             .send_direct_unencrypted(
                 dest,
@@ -294,6 +328,11 @@ where
                 }
                 Ok(LivenessResponse::Ok)
             },
+            GetClockSkew => Ok(LivenessResponse::ClockSkew(self.clock_skew())),
+            SetRespondToPings(respond) => {
+                self.config.respond_to_pings = respond;
+                Ok(LivenessResponse::Ok)
+            },
         }
     }
 
@@ -321,7 +360,7 @@ where
         let len_peers = selected_peers.len();
 
         for peer in selected_peers {
-            let msg = PingPongMessage::ping_with_metadata(self.state.metadata().clone());
+            let msg = PingPongMessage::ping_with_metadata(self.outbound_metadata());
             self.state.add_inflight_ping(msg.nonce, peer.clone());
             self.outbound_messaging
                 .send_direct_node_id(
@@ -357,6 +396,101 @@ where
         Ok(())
     }
 
+    /// Local metadata with the current local time attached, allowing the receiver to estimate clock skew
+    fn outbound_metadata(&self) -> Metadata {
+        let mut metadata = self.state.metadata().clone();
+        metadata.insert(
+            MetadataKey::LocalTime,
+            clock_skew::unix_timestamp_millis().to_le_bytes().to_vec(),
+        );
+        metadata
+    }
+
+    /// Estimates the clock offset of a peer from the local time it attached to a pong, assuming the pong was sent
+    /// halfway through the round trip.
+    fn record_peer_clock_offset(&mut self, node_id: &NodeId, metadata: &Metadata, latency: Duration) {
+        let peer_time = match metadata
+            .get(MetadataKey::LocalTime)
+            .and_then(|bytes| <[u8; 8]>::try_from(bytes.as_slice()).ok())
+        {
+            Some(bytes) => i128::from(u64::from_le_bytes(bytes)),
+            None => return,
+        };
+        let local_time = i128::from(clock_skew::unix_timestamp_millis()) -
+            i128::try_from(latency.as_millis() / 2).unwrap_or_default();
+        match i64::try_from(peer_time - local_time) {
+            Ok(offset) => self.state.record_peer_clock_offset(node_id.clone(), offset),
+            Err(_) => debug!(
+                target: LOG_TARGET,
+                "Peer '{}' reported an out of range local time. Ignoring it.",
+                node_id.short_str()
+            ),
+        }
+    }
+
+    fn clock_skew(&self) -> ClockSkew {
+        let (peer_offset_ms, num_peer_samples) = self.state.get_peer_clock_offset();
+        ClockSkew {
+            peer_offset_ms,
+            num_peer_samples,
+            ntp_offset_ms: self.ntp_offset_ms,
+        }
+    }
+
+    /// Queries the NTP servers every `ntp_query_interval` in a separate task, sending each result to the returned
+    /// receiver. The receiver is closed straight away if no NTP servers or clock skew checks are configured.
+    fn spawn_ntp_queries(&self) -> mpsc::Receiver<Option<i64>> {
+        let (tx, rx) = mpsc::channel(1);
+        if self.config.max_clock_skew.is_none() || self.config.ntp_servers.is_empty() {
+            return rx;
+        }
+
+        let ntp_servers = self.config.ntp_servers.clone();
+        let mut interval = time::interval(self.config.ntp_query_interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut shutdown_signal = self.shutdown_signal.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {
+                        let offset = clock_skew::query_ntp_servers(&ntp_servers).await;
+                        if tx.send(offset).await.is_err() {
+                            break;
+                        }
+                    },
+                    _ = shutdown_signal.wait() => break,
+                }
+            }
+        });
+        rx
+    }
+
+    fn check_clock_skew(&mut self) {
+        let max_clock_skew = match self.config.max_clock_skew {
+            Some(max) => max,
+            None => return,
+        };
+
+        let skew = self.clock_skew();
+        if skew.offset_ms().is_none() {
+            return;
+        }
+        let exceeded = skew.exceeds(max_clock_skew);
+        if exceeded && !self.is_clock_skewed {
+            warn!(
+                target: LOG_TARGET,
+                "Local clock offset {} exceeds the maximum of {:.2?}. Blocks timestamped by this node may be rejected.",
+                skew,
+                max_clock_skew
+            );
+            self.publish_event(LivenessEvent::ClockSkewDetected(skew));
+        } else if !exceeded && self.is_clock_skewed {
+            info!(target: LOG_TARGET, "Local clock offset {} is within tolerance again", skew);
+            self.publish_event(LivenessEvent::ClockSkewResolved(skew));
+        }
+        self.is_clock_skewed = exceeded;
+    }
+
     fn publish_event(&mut self, event: LivenessEvent) {
         let _ = self.event_publisher.send(Arc::new(event)).map_err(|_| {
             trace!(
@@ -561,6 +695,48 @@ mod test {
         unwrap_oms_send_msg!(outbound_rx.recv().await.unwrap());
     }
 
+    #[tokio::test]
+    async fn handle_message_ping_without_responding() {
+        let state = LivenessState::new();
+
+        let (connectivity, mock) = create_connectivity_mock();
+        mock.spawn();
+        let (outbound_tx, mut outbound_rx) = mpsc::channel(10);
+        let outbound_messaging = OutboundMessageRequester::new(outbound_tx);
+
+        let msg = create_dummy_message(PingPongMessage::ping_with_metadata(Metadata::new()));
+        let pingpong_stream = stream::iter(std::iter::once(msg));
+
+        let (publisher, _) = broadcast::channel(200);
+        let mut subscriber = publisher.subscribe();
+        let shutdown = Shutdown::new();
+        let service = LivenessService::new(
+            LivenessConfig {
+                respond_to_pings: false,
+                ..Default::default()
+            },
+            stream::empty(),
+            pingpong_stream,
+            state,
+            connectivity,
+            outbound_messaging,
+            publisher,
+            shutdown.to_signal(),
+        );
+
+        task::spawn(service.run());
+
+        // The ping is still received and published, but no pong is sent
+        let event = time::timeout(Duration::from_secs(10), subscriber.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(&*event, LivenessEvent::ReceivedPing(_)));
+        assert!(time::timeout(Duration::from_millis(100), outbound_rx.recv())
+            .await
+            .is_err());
+    }
+
     #[tokio::test]
     async fn handle_message_pong() {
         let mut state = LivenessState::new();
@@ -34,6 +34,10 @@ use crate::proto::liveness::MetadataKey;
 
 const LATENCY_SAMPLE_WINDOW_SIZE: usize = 25;
 const MAX_INFLIGHT_TTL: Duration = Duration::from_secs(40);
+/// Peer clock offset samples older than this are not used to estimate clock skew
+const CLOCK_OFFSET_SAMPLE_TTL: Duration = Duration::from_secs(10 * 60);
+/// The minimum number of peer samples required before a peer clock offset is reported
+const MIN_CLOCK_OFFSET_SAMPLES: usize = 3;
 
 /// Represents metadata in a ping/pong message.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -77,6 +81,7 @@ pub struct LivenessState {
     inflight_pings: HashMap<u64, (NodeId, Instant)>,
     peer_latency: HashMap<NodeId, AverageLatency>,
     failed_pings: HashMap<NodeId, usize>,
+    peer_clock_offsets: HashMap<NodeId, (i64, Instant)>,
 
     pings_received: usize,
     pongs_received: usize,
@@ -217,6 +222,36 @@ impl LivenessState {
             .map(|latency| Duration::from_millis(u64::try_from(latency.as_millis()).unwrap() / num_peers as u64))
     }
 
+    /// Records the offset (in milliseconds) between the clock of the given peer and the local clock
+    pub fn record_peer_clock_offset(&mut self, node_id: NodeId, offset_ms: i64) {
+        self.peer_clock_offsets
+            .retain(|_, (_, recorded_at)| recorded_at.elapsed() <= CLOCK_OFFSET_SAMPLE_TTL);
+        self.peer_clock_offsets.insert(node_id, (offset_ms, Instant::now()));
+    }
+
+    /// Returns the median clock offset of recently sampled peers along with the number of samples. The offset is None
+    /// if fewer than [MIN_CLOCK_OFFSET_SAMPLES](self::MIN_CLOCK_OFFSET_SAMPLES) samples are available.
+    pub fn get_peer_clock_offset(&self) -> (Option<i64>, usize) {
+        let mut offsets = self
+            .peer_clock_offsets
+            .values()
+            .filter(|(_, recorded_at)| recorded_at.elapsed() <= CLOCK_OFFSET_SAMPLE_TTL)
+            .map(|(offset, _)| *offset)
+            .collect::<Vec<_>>();
+        let num_samples = offsets.len();
+        if num_samples < MIN_CLOCK_OFFSET_SAMPLES {
+            return (None, num_samples);
+        }
+        offsets.sort_unstable();
+        let mid = num_samples / 2;
+        let median = if num_samples % 2 == 0 {
+            (offsets[mid - 1] + offsets[mid]) / 2
+        } else {
+            offsets[mid]
+        };
+        (Some(median), num_samples)
+    }
+
     pub fn failed_pings_iter(&self) -> impl Iterator<Item = (&NodeId, &usize)> {
         self.failed_pings.iter()
     }
@@ -264,6 +299,10 @@ impl AverageLatency {
 
 #[cfg(test)]
 mod test {
+    use rand::rngs::OsRng;
+    use tari_comms::types::CommsPublicKey;
+    use tari_crypto::keys::PublicKey;
+
     use super::*;
 
     #[test]
@@ -365,4 +404,20 @@ mod test {
         assert_eq!(*n, 1);
         assert!(state.failed_pings.get(&peer2).is_none());
     }
+
+    #[test]
+    fn get_peer_clock_offset() {
+        let mut state = LivenessState::new();
+        let random_node_id = || NodeId::from_public_key(&CommsPublicKey::random_keypair(&mut OsRng).1);
+
+        state.record_peer_clock_offset(random_node_id(), 100);
+        state.record_peer_clock_offset(random_node_id(), -50);
+        assert_eq!(state.get_peer_clock_offset(), (None, 2));
+
+        state.record_peer_clock_offset(random_node_id(), 10_000);
+        assert_eq!(state.get_peer_clock_offset(), (Some(100), 3));
+
+        state.record_peer_clock_offset(random_node_id(), 0);
+        assert_eq!(state.get_peer_clock_offset(), (Some(50), 4));
+    }
 }
@@ -42,6 +42,9 @@ pub struct TransportConfig {
     pub tor: TorTransportConfig,
     pub socks: Socks5TransportConfig,
     pub memory: MemoryTransportConfig,
+    /// Refuse to dial or accept anything but onion addresses, so that no traffic leaves this node outside of tor.
+    /// Requires the tor transport.
+    pub onion_only: bool,
 }
 
 impl TransportConfig {
@@ -84,6 +87,36 @@ impl TransportConfig {
     pub fn is_tor(&self) -> bool {
         matches!(self.transport_type, TransportType::Tor)
     }
+
+    /// Checks that this configuration cannot reach the clearnet when `onion_only` is set
+    pub fn check_onion_only(&self) -> Result<(), CommsInitializationError> {
+        if !self.onion_only {
+            return Ok(());
+        }
+        if !self.is_tor() {
+            return Err(CommsInitializationError::OnionOnlyViolation(format!(
+                "onion_only requires the tor transport, not {:?}",
+                self.transport_type
+            )));
+        }
+        if self.tor.proxy_bypass_for_outbound_tcp || !self.tor.proxy_bypass_addresses.is_empty() {
+            return Err(CommsInitializationError::OnionOnlyViolation(
+                "onion_only cannot be combined with tor proxy bypass settings".to_string(),
+            ));
+        }
+        for addr in [&self.tor.forward_address, &self.tor.listener_address_override]
+            .into_iter()
+            .flatten()
+        {
+            if !multiaddr_to_socketaddr(addr).map_or(false, |addr| addr.ip().is_loopback()) {
+                return Err(CommsInitializationError::OnionOnlyViolation(format!(
+                    "onion_only requires the tor forward and listener addresses to be loopback addresses, not '{}'",
+                    addr
+                )));
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
@@ -243,3 +276,81 @@ impl Default for MemoryTransportConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn onion_only_tor() -> TransportConfig {
+        TransportConfig {
+            onion_only: true,
+            ..TransportConfig::new_tor(TorTransportConfig::default())
+        }
+    }
+
+    #[test]
+    fn it_allows_any_transport_when_not_onion_only() {
+        TransportConfig::new_tcp(TcpTransportConfig::default())
+            .check_onion_only()
+            .unwrap();
+        TransportConfig::new_tor(TorTransportConfig::default())
+            .check_onion_only()
+            .unwrap();
+    }
+
+    #[test]
+    fn it_allows_the_default_tor_transport() {
+        onion_only_tor().check_onion_only().unwrap();
+
+        let mut config = onion_only_tor();
+        config.tor.forward_address = Some("/ip4/127.0.0.1/tcp/18189".parse().unwrap());
+        config.tor.listener_address_override = Some("/ip6/::1/tcp/18189".parse().unwrap());
+        config.check_onion_only().unwrap();
+    }
+
+    #[test]
+    fn it_rejects_transports_other_than_tor() {
+        let config = TransportConfig {
+            onion_only: true,
+            ..TransportConfig::new_tcp(TcpTransportConfig::default())
+        };
+        assert!(matches!(
+            config.check_onion_only(),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_proxy_bypass() {
+        let mut config = onion_only_tor();
+        config.tor.proxy_bypass_for_outbound_tcp = true;
+        assert!(matches!(
+            config.check_onion_only(),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+
+        let mut config = onion_only_tor();
+        config.tor.proxy_bypass_addresses = vec!["/ip4/10.0.0.1/tcp/18189".parse().unwrap()];
+        assert!(matches!(
+            config.check_onion_only(),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+
+    #[test]
+    fn it_rejects_non_loopback_forward_and_listener_addresses() {
+        let mut config = onion_only_tor();
+        config.tor.forward_address = Some("/ip4/0.0.0.0/tcp/18189".parse().unwrap());
+        assert!(matches!(
+            config.check_onion_only(),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+
+        let mut config = onion_only_tor();
+        config.tor.listener_address_override = Some("/dns4/example.com/tcp/18189".parse().unwrap());
+        assert!(matches!(
+            config.check_onion_only(),
+            Err(CommsInitializationError::OnionOnlyViolation(_))
+        ));
+    }
+}
@@ -0,0 +1 @@
+DROP TABLE outbound_message_queue;
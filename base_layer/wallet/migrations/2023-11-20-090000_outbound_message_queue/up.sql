@@ -0,0 +1,12 @@
+CREATE TABLE outbound_message_queue
+(
+    tx_id               BIGINT            NOT NULL,
+    message_type        INTEGER           NOT NULL,
+    destination_address BLOB              NOT NULL,
+    payload             BLOB              NOT NULL,
+    status              INTEGER DEFAULT 0 NOT NULL,
+    attempts            INTEGER DEFAULT 0 NOT NULL,
+    created_at          DATETIME          NOT NULL,
+    last_attempt_at     DATETIME          NULL,
+    PRIMARY KEY (tx_id, message_type)
+);
@@ -0,0 +1,5 @@
+CREATE TABLE transaction_counterparty_aliases
+(
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    alias TEXT               NOT NULL
+);
@@ -0,0 +1 @@
+DROP TABLE batched_payments;
@@ -0,0 +1,11 @@
+CREATE TABLE batched_payments
+(
+    id                  INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    tx_id               BIGINT  NOT NULL,
+    destination_address BLOB    NOT NULL,
+    amount              BIGINT  NOT NULL,
+    message             TEXT    NOT NULL,
+    output_hash         BLOB    NOT NULL
+);
+
+CREATE INDEX idx_batched_payments_tx_id ON batched_payments (tx_id);
@@ -0,0 +1,19 @@
+CREATE TABLE scheduled_transactions
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    destination_address BLOB     NOT NULL,
+    amount              BIGINT   NOT NULL,
+    fee_per_gram        BIGINT   NOT NULL,
+    message             TEXT     NOT NULL,
+    one_sided           INTEGER  NOT NULL,
+    execute_at_height   BIGINT   NULL,
+    execute_at_time     DATETIME NULL,
+    expires_at_height   BIGINT   NULL,
+    expires_at_time     DATETIME NULL,
+    status              INTEGER  NOT NULL,
+    tx_id               BIGINT   NULL,
+    failure_reason      TEXT     NULL,
+    created_at          DATETIME NOT NULL
+);
+
+CREATE INDEX idx_scheduled_transactions_status ON scheduled_transactions (status);
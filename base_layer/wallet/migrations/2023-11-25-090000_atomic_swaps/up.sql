@@ -0,0 +1,18 @@
+CREATE TABLE atomic_swaps
+(
+    swap_id                  BIGINT PRIMARY KEY NOT NULL,
+    role                     INTEGER  NOT NULL,
+    state                    INTEGER  NOT NULL,
+    counterparty_address     BLOB     NOT NULL,
+    amount                   BIGINT   NOT NULL,
+    hash_lock                BLOB     NOT NULL,
+    pre_image                BLOB     NULL,
+    lock_height              BIGINT   NOT NULL,
+    lock_tx_id               BIGINT   NULL,
+    lock_output_hash         BLOB     NULL,
+    counterparty_output_hash BLOB     NULL,
+    settle_tx_id             BIGINT   NULL,
+    message                  TEXT     NOT NULL,
+    created_at               DATETIME NOT NULL,
+    updated_at               DATETIME NOT NULL
+);
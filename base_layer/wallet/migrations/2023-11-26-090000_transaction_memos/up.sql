@@ -0,0 +1,5 @@
+CREATE TABLE transaction_memos
+(
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    memo  BLOB               NOT NULL
+);
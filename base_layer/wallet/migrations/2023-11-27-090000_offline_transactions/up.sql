@@ -0,0 +1,9 @@
+CREATE TABLE offline_transactions
+(
+    tx_id                BIGINT PRIMARY KEY NOT NULL,
+    unsigned_transaction BLOB               NOT NULL,
+    status               INTEGER            NOT NULL,
+    created_at           DATETIME           NOT NULL
+);
+
+CREATE INDEX idx_offline_transactions_status ON offline_transactions (status);
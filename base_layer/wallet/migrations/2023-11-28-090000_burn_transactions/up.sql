@@ -0,0 +1,15 @@
+CREATE TABLE burn_transactions
+(
+    tx_id                       BIGINT PRIMARY KEY NOT NULL,
+    amount                      BIGINT             NOT NULL,
+    claim_public_key            BLOB               NULL,
+    reciprocal_claim_public_key BLOB               NOT NULL,
+    commitment                  BLOB               NOT NULL,
+    ownership_proof             BLOB               NULL,
+    range_proof                 BLOB               NOT NULL,
+    status                      INTEGER            NOT NULL,
+    burned_at                   DATETIME           NOT NULL,
+    claimed_at                  DATETIME           NULL
+);
+
+CREATE INDEX idx_burn_transactions_status ON burn_transactions (status);
@@ -0,0 +1,2 @@
+DROP TABLE multisig_signings;
+DROP TABLE multisig_sessions;
@@ -0,0 +1,19 @@
+CREATE TABLE recurring_payments
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    destination_address BLOB     NOT NULL,
+    amount              BIGINT   NOT NULL,
+    fee_per_gram        BIGINT   NOT NULL,
+    message             TEXT     NOT NULL,
+    one_sided           INTEGER  NOT NULL,
+    interval_secs       BIGINT   NOT NULL,
+    next_payment_at     DATETIME NOT NULL,
+    end_at              DATETIME NULL,
+    status              INTEGER  NOT NULL,
+    payments_made       BIGINT   NOT NULL DEFAULT 0,
+    last_tx_id          BIGINT   NULL,
+    failure_reason      TEXT     NULL,
+    created_at          DATETIME NOT NULL
+);
+
+CREATE INDEX idx_recurring_payments_status ON recurring_payments (status);
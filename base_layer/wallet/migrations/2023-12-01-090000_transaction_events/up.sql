@@ -0,0 +1,6 @@
+CREATE TABLE transaction_events
+(
+    id         INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    event      TEXT     NOT NULL,
+    created_at DATETIME NOT NULL
+);
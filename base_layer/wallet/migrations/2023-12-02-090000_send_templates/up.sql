@@ -0,0 +1,12 @@
+CREATE TABLE send_templates
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    name                TEXT     NOT NULL UNIQUE,
+    destination_address BLOB     NOT NULL,
+    default_amount      BIGINT   NULL,
+    message             TEXT     NOT NULL,
+    fee_priority        INTEGER  NOT NULL,
+    one_sided           INTEGER  NOT NULL,
+    created_at          DATETIME NOT NULL,
+    updated_at          DATETIME NOT NULL
+);
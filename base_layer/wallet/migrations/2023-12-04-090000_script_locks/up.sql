@@ -0,0 +1 @@
+ALTER TABLE outputs ADD hash_lock BLOB NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE outputs DROP COLUMN metadata_json;
+ALTER TABLE outputs DROP COLUMN label;
@@ -0,0 +1,2 @@
+ALTER TABLE outputs ADD label TEXT NULL;
+ALTER TABLE outputs ADD metadata_json TEXT NULL;
@@ -0,0 +1,5 @@
+CREATE TABLE archived_outputs (
+    hash BLOB PRIMARY KEY NOT NULL,
+    spent_height BIGINT NOT NULL,
+    data BLOB NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE accounts
+(
+    id         INTEGER PRIMARY KEY NOT NULL,
+    name       TEXT     NOT NULL UNIQUE,
+    receiving  INTEGER  NOT NULL DEFAULT 0,
+    created_at DATETIME NOT NULL
+);
+
+ALTER TABLE outputs ADD account_id INTEGER NOT NULL DEFAULT 0;
+CREATE INDEX idx_outputs_account_id ON outputs (account_id);
@@ -0,0 +1,9 @@
+CREATE TABLE subaddresses
+(
+    subaddress_index BIGINT PRIMARY KEY NOT NULL,
+    public_key       BLOB     NOT NULL UNIQUE,
+    label            TEXT     NULL,
+    created_at       DATETIME NOT NULL
+);
+
+ALTER TABLE outputs ADD subaddress_index BIGINT NULL;
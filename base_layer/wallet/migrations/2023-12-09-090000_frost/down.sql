@@ -0,0 +1,2 @@
+DROP TABLE frost_signings;
+DROP TABLE frost_sessions;
@@ -0,0 +1,29 @@
+CREATE TABLE frost_sessions
+(
+    session_id            BIGINT PRIMARY KEY NOT NULL,
+    threshold             INTEGER  NOT NULL,
+    participants_json     TEXT     NOT NULL,
+    polynomial_key_id     TEXT     NOT NULL,
+    signing_share_key_id  TEXT     NULL,
+    group_public_key      BLOB     NULL,
+    label                 TEXT     NOT NULL,
+    state                 INTEGER  NOT NULL,
+    created_at            DATETIME NOT NULL,
+    updated_at            DATETIME NOT NULL
+);
+
+CREATE TABLE frost_signings
+(
+    signing_id            BIGINT PRIMARY KEY NOT NULL,
+    session_id            BIGINT   NOT NULL,
+    message               BLOB     NOT NULL,
+    signers_json          TEXT     NOT NULL,
+    hiding_nonce_key_id   TEXT     NULL,
+    binding_nonce_key_id  TEXT     NULL,
+    description           TEXT     NOT NULL,
+    state                 INTEGER  NOT NULL,
+    signature_nonce       BLOB     NULL,
+    signature_key         BLOB     NULL,
+    created_at            DATETIME NOT NULL,
+    updated_at            DATETIME NOT NULL
+);
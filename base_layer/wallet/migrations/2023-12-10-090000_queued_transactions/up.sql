@@ -0,0 +1,15 @@
+CREATE TABLE queued_transactions
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    destination_address BLOB     NOT NULL,
+    amount              BIGINT   NOT NULL,
+    fee_per_gram        BIGINT   NOT NULL,
+    message             TEXT     NOT NULL,
+    one_sided           INTEGER  NOT NULL,
+    status              INTEGER  NOT NULL,
+    tx_id               BIGINT   NULL,
+    failure_reason      TEXT     NULL,
+    queued_at           DATETIME NOT NULL
+);
+
+CREATE INDEX idx_queued_transactions_status ON queued_transactions (status);
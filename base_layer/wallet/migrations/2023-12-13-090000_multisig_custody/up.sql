@@ -0,0 +1,8 @@
+-- FROST signing is done by the multisig sessions, the separate FROST tables are not used anymore
+DROP TABLE frost_signings;
+DROP TABLE frost_sessions;
+
+ALTER TABLE multisig_signings ADD COLUMN kind_json TEXT DEFAULT '"Message"' NOT NULL;
+ALTER TABLE multisig_signings ADD COLUMN secret_key_id TEXT NULL;
+ALTER TABLE multisig_signings ADD COLUMN joint_public_key BLOB NULL;
+ALTER TABLE multisig_signings ADD COLUMN script_offset BLOB NULL;
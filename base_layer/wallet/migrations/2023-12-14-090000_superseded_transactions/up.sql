@@ -0,0 +1,11 @@
+CREATE TABLE superseded_transactions
+(
+    id                   BIGINT PRIMARY KEY NOT NULL,
+    tx_id                BIGINT   NOT NULL,
+    fee                  BIGINT   NOT NULL,
+    transaction_protocol BLOB     NOT NULL,
+    batched_payments     TEXT     NOT NULL,
+    superseded_at        DATETIME NOT NULL
+);
+
+CREATE INDEX idx_superseded_transactions_tx_id ON superseded_transactions (tx_id);
@@ -0,0 +1,28 @@
+DROP TABLE IF EXISTS send_templates;
+DROP TABLE IF EXISTS transaction_events;
+DROP TABLE IF EXISTS recurring_payments;
+DROP TABLE IF EXISTS frost_signings;
+DROP TABLE IF EXISTS frost_sessions;
+DROP TABLE IF EXISTS multisig_signings;
+DROP TABLE IF EXISTS multisig_sessions;
+DROP TABLE IF EXISTS burn_transactions;
+DROP TABLE IF EXISTS offline_transactions;
+DROP TABLE IF EXISTS transaction_memos;
+DROP TABLE IF EXISTS atomic_swaps;
+DROP TABLE IF EXISTS transaction_tags;
+DROP TABLE IF EXISTS scheduled_transactions;
+DROP TABLE IF EXISTS batched_payments;
+DROP TABLE IF EXISTS transaction_counterparty_aliases;
+DROP TABLE IF EXISTS outbound_message_queue;
+DROP TABLE IF EXISTS outbound_transactions;
+DROP TABLE IF EXISTS inbound_transactions;
+DROP TABLE IF EXISTS completed_transactions;
+DROP TABLE IF EXISTS subaddresses;
+DROP TABLE IF EXISTS accounts;
+DROP TABLE IF EXISTS archived_outputs;
+DROP TABLE IF EXISTS outputs;
+DROP TABLE IF EXISTS known_one_sided_payment_scripts;
+DROP TABLE IF EXISTS burnt_proofs;
+DROP TABLE IF EXISTS scanned_blocks;
+DROP TABLE IF EXISTS wallet_settings;
+DROP TABLE IF EXISTS client_key_values;
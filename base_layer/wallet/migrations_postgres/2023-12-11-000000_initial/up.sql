@@ -0,0 +1,371 @@
+-- The PostgreSQL schema of the wallet, equivalent to the schema built up by the Sqlite migrations in `migrations`
+
+CREATE TABLE client_key_values
+(
+    key   TEXT PRIMARY KEY NOT NULL,
+    value TEXT             NOT NULL
+);
+
+CREATE TABLE wallet_settings
+(
+    key   TEXT PRIMARY KEY NOT NULL,
+    value TEXT             NOT NULL
+);
+
+CREATE TABLE scanned_blocks
+(
+    header_hash BYTEA PRIMARY KEY NOT NULL,
+    height      BIGINT            NOT NULL,
+    num_outputs BIGINT            NULL,
+    amount      BIGINT            NULL,
+    timestamp   TIMESTAMP         NOT NULL
+);
+
+CREATE TABLE burnt_proofs
+(
+    id                          INTEGER PRIMARY KEY NOT NULL,
+    reciprocal_claim_public_key TEXT                NOT NULL,
+    payload                     TEXT                NOT NULL,
+    burned_at                   TIMESTAMP           NOT NULL
+);
+
+CREATE TABLE known_one_sided_payment_scripts
+(
+    script_hash        BYTEA PRIMARY KEY NOT NULL,
+    private_key        TEXT              NOT NULL,
+    script             BYTEA             NOT NULL,
+    input              BYTEA             NOT NULL,
+    script_lock_height BIGINT            NOT NULL DEFAULT 0
+);
+
+CREATE TABLE outputs
+(
+    id                                      SERIAL PRIMARY KEY NOT NULL,
+    commitment                              BYTEA              NOT NULL,
+    rangeproof                              BYTEA              NULL,
+    spending_key                            TEXT               NOT NULL,
+    value                                   BIGINT             NOT NULL,
+    output_type                             INTEGER            NOT NULL,
+    maturity                                BIGINT             NOT NULL,
+    status                                  INTEGER            NOT NULL,
+    hash                                    BYTEA              NOT NULL,
+    script                                  BYTEA              NOT NULL,
+    input_data                              BYTEA              NOT NULL,
+    script_private_key                      TEXT               NOT NULL,
+    script_lock_height                      BIGINT             NOT NULL DEFAULT 0,
+    sender_offset_public_key                BYTEA              NOT NULL,
+    metadata_signature_ephemeral_commitment BYTEA              NOT NULL,
+    metadata_signature_ephemeral_pubkey     BYTEA              NOT NULL,
+    metadata_signature_u_a                  BYTEA              NOT NULL,
+    metadata_signature_u_x                  BYTEA              NOT NULL,
+    metadata_signature_u_y                  BYTEA              NOT NULL,
+    mined_height                            BIGINT             NULL,
+    mined_in_block                          BYTEA              NULL,
+    marked_deleted_at_height                BIGINT             NULL,
+    marked_deleted_in_block                 BYTEA              NULL,
+    received_in_tx_id                       BIGINT             NULL,
+    spent_in_tx_id                          BIGINT             NULL,
+    coinbase_block_height                   BIGINT             NULL,
+    coinbase_extra                          BYTEA              NULL,
+    features_json                           TEXT               NOT NULL DEFAULT '{}',
+    spending_priority                       INTEGER            NOT NULL DEFAULT 500,
+    covenant                                BYTEA              NOT NULL,
+    mined_timestamp                         TIMESTAMP          NULL,
+    encrypted_data                          BYTEA              NOT NULL,
+    minimum_value_promise                   BIGINT             NOT NULL,
+    source                                  INTEGER            NOT NULL DEFAULT 0,
+    last_validation_timestamp               TIMESTAMP          NULL,
+    frozen                                  INTEGER            NOT NULL DEFAULT 0,
+    hash_lock                               BYTEA              NULL,
+    label                                   TEXT               NULL,
+    metadata_json                           TEXT               NULL,
+    account_id                              INTEGER            NOT NULL DEFAULT 0,
+    subaddress_index                        BIGINT             NULL,
+    CONSTRAINT unique_commitment UNIQUE (commitment)
+);
+
+CREATE INDEX idx_outputs_account_id ON outputs (account_id);
+
+CREATE TABLE archived_outputs
+(
+    hash         BYTEA PRIMARY KEY NOT NULL,
+    spent_height BIGINT            NOT NULL,
+    data         BYTEA             NOT NULL
+);
+
+CREATE TABLE accounts
+(
+    id         INTEGER PRIMARY KEY NOT NULL,
+    name       TEXT                NOT NULL UNIQUE,
+    receiving  INTEGER             NOT NULL DEFAULT 0,
+    created_at TIMESTAMP           NOT NULL
+);
+
+CREATE TABLE subaddresses
+(
+    subaddress_index BIGINT PRIMARY KEY NOT NULL,
+    public_key       BYTEA              NOT NULL UNIQUE,
+    label            TEXT               NULL,
+    created_at       TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE completed_transactions
+(
+    tx_id                       BIGINT PRIMARY KEY NOT NULL,
+    source_address              BYTEA              NOT NULL,
+    destination_address         BYTEA              NOT NULL,
+    amount                      BIGINT             NOT NULL,
+    fee                         BIGINT             NOT NULL,
+    transaction_protocol        BYTEA              NOT NULL,
+    status                      INTEGER            NOT NULL,
+    message                     TEXT               NOT NULL,
+    timestamp                   TIMESTAMP          NOT NULL,
+    cancelled                   INTEGER            NULL,
+    direction                   INTEGER            NULL,
+    coinbase_block_height       BIGINT             NULL,
+    send_count                  INTEGER DEFAULT 0  NOT NULL,
+    last_send_timestamp         TIMESTAMP          NULL,
+    confirmations               BIGINT             NULL,
+    mined_height                BIGINT             NULL,
+    mined_in_block              BYTEA              NULL,
+    mined_timestamp             TIMESTAMP          NULL,
+    transaction_signature_nonce BYTEA              NOT NULL,
+    transaction_signature_key   BYTEA              NOT NULL
+);
+
+CREATE TABLE inbound_transactions
+(
+    tx_id               BIGINT PRIMARY KEY NOT NULL,
+    source_address      BYTEA              NOT NULL,
+    amount              BIGINT             NOT NULL,
+    receiver_protocol   BYTEA              NOT NULL,
+    message             TEXT               NOT NULL,
+    timestamp           TIMESTAMP          NOT NULL,
+    cancelled           INTEGER            NOT NULL,
+    direct_send_success INTEGER DEFAULT 0  NOT NULL,
+    send_count          INTEGER DEFAULT 0  NOT NULL,
+    last_send_timestamp TIMESTAMP          NULL
+);
+
+CREATE TABLE outbound_transactions
+(
+    tx_id               BIGINT PRIMARY KEY NOT NULL,
+    destination_address BYTEA              NOT NULL,
+    amount              BIGINT             NOT NULL,
+    fee                 BIGINT             NOT NULL,
+    sender_protocol     BYTEA              NOT NULL,
+    message             TEXT               NOT NULL,
+    timestamp           TIMESTAMP          NOT NULL,
+    cancelled           INTEGER DEFAULT 0  NOT NULL,
+    direct_send_success INTEGER DEFAULT 0  NOT NULL,
+    send_count          INTEGER DEFAULT 0  NOT NULL,
+    last_send_timestamp TIMESTAMP          NULL
+);
+
+CREATE TABLE outbound_message_queue
+(
+    tx_id               BIGINT            NOT NULL,
+    message_type        INTEGER           NOT NULL,
+    destination_address BYTEA             NOT NULL,
+    payload             BYTEA             NOT NULL,
+    status              INTEGER DEFAULT 0 NOT NULL,
+    attempts            INTEGER DEFAULT 0 NOT NULL,
+    created_at          TIMESTAMP         NOT NULL,
+    last_attempt_at     TIMESTAMP         NULL,
+    PRIMARY KEY (tx_id, message_type)
+);
+
+CREATE TABLE transaction_counterparty_aliases
+(
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    alias TEXT               NOT NULL
+);
+
+CREATE TABLE batched_payments
+(
+    id                  SERIAL PRIMARY KEY NOT NULL,
+    tx_id               BIGINT             NOT NULL,
+    destination_address BYTEA              NOT NULL,
+    amount              BIGINT             NOT NULL,
+    message             TEXT               NOT NULL,
+    output_hash         BYTEA              NOT NULL
+);
+
+CREATE INDEX idx_batched_payments_tx_id ON batched_payments (tx_id);
+
+CREATE TABLE scheduled_transactions
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    destination_address BYTEA              NOT NULL,
+    amount              BIGINT             NOT NULL,
+    fee_per_gram        BIGINT             NOT NULL,
+    message             TEXT               NOT NULL,
+    one_sided           INTEGER            NOT NULL,
+    execute_at_height   BIGINT             NULL,
+    execute_at_time     TIMESTAMP          NULL,
+    expires_at_height   BIGINT             NULL,
+    expires_at_time     TIMESTAMP          NULL,
+    status              INTEGER            NOT NULL,
+    tx_id               BIGINT             NULL,
+    failure_reason      TEXT               NULL,
+    created_at          TIMESTAMP          NOT NULL
+);
+
+CREATE INDEX idx_scheduled_transactions_status ON scheduled_transactions (status);
+
+CREATE TABLE transaction_tags
+(
+    tx_id BIGINT NOT NULL,
+    tag   TEXT   NOT NULL,
+    PRIMARY KEY (tx_id, tag)
+);
+
+CREATE INDEX idx_transaction_tags_tag ON transaction_tags (tag);
+
+CREATE TABLE atomic_swaps
+(
+    swap_id                  BIGINT PRIMARY KEY NOT NULL,
+    role                     INTEGER            NOT NULL,
+    state                    INTEGER            NOT NULL,
+    counterparty_address     BYTEA              NOT NULL,
+    amount                   BIGINT             NOT NULL,
+    hash_lock                BYTEA              NOT NULL,
+    pre_image                BYTEA              NULL,
+    lock_height              BIGINT             NOT NULL,
+    lock_tx_id               BIGINT             NULL,
+    lock_output_hash         BYTEA              NULL,
+    counterparty_output_hash BYTEA              NULL,
+    settle_tx_id             BIGINT             NULL,
+    message                  TEXT               NOT NULL,
+    created_at               TIMESTAMP          NOT NULL,
+    updated_at               TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE transaction_memos
+(
+    tx_id BIGINT PRIMARY KEY NOT NULL,
+    memo  BYTEA              NOT NULL
+);
+
+CREATE TABLE offline_transactions
+(
+    tx_id                BIGINT PRIMARY KEY NOT NULL,
+    unsigned_transaction BYTEA              NOT NULL,
+    status               INTEGER            NOT NULL,
+    created_at           TIMESTAMP          NOT NULL
+);
+
+CREATE INDEX idx_offline_transactions_status ON offline_transactions (status);
+
+CREATE TABLE burn_transactions
+(
+    tx_id                       BIGINT PRIMARY KEY NOT NULL,
+    amount                      BIGINT             NOT NULL,
+    claim_public_key            BYTEA              NULL,
+    reciprocal_claim_public_key BYTEA              NOT NULL,
+    commitment                  BYTEA              NOT NULL,
+    ownership_proof             BYTEA              NULL,
+    range_proof                 BYTEA              NOT NULL,
+    status                      INTEGER            NOT NULL,
+    burned_at                   TIMESTAMP          NOT NULL,
+    claimed_at                  TIMESTAMP          NULL
+);
+
+CREATE INDEX idx_burn_transactions_status ON burn_transactions (status);
+
+CREATE TABLE multisig_sessions
+(
+    session_id        BIGINT PRIMARY KEY NOT NULL,
+    threshold         INTEGER            NOT NULL,
+    participants_json TEXT               NOT NULL,
+    key_share_key_id  TEXT               NOT NULL,
+    label             TEXT               NOT NULL,
+    state             INTEGER            NOT NULL,
+    created_at        TIMESTAMP          NOT NULL,
+    updated_at        TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE multisig_signings
+(
+    signing_id      BIGINT PRIMARY KEY NOT NULL,
+    session_id      BIGINT             NOT NULL,
+    message         BYTEA              NOT NULL,
+    signers_json    TEXT               NOT NULL,
+    nonce_key_id    TEXT               NULL,
+    description     TEXT               NOT NULL,
+    state           INTEGER            NOT NULL,
+    signature_nonce BYTEA              NULL,
+    signature_key   BYTEA              NULL,
+    created_at      TIMESTAMP          NOT NULL,
+    updated_at      TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE frost_sessions
+(
+    session_id           BIGINT PRIMARY KEY NOT NULL,
+    threshold            INTEGER            NOT NULL,
+    participants_json    TEXT               NOT NULL,
+    polynomial_key_id    TEXT               NOT NULL,
+    signing_share_key_id TEXT               NULL,
+    group_public_key     BYTEA              NULL,
+    label                TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE frost_signings
+(
+    signing_id           BIGINT PRIMARY KEY NOT NULL,
+    session_id           BIGINT             NOT NULL,
+    message              BYTEA              NOT NULL,
+    signers_json         TEXT               NOT NULL,
+    hiding_nonce_key_id  TEXT               NULL,
+    binding_nonce_key_id TEXT               NULL,
+    description          TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    signature_nonce      BYTEA              NULL,
+    signature_key        BYTEA              NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE recurring_payments
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    destination_address BYTEA              NOT NULL,
+    amount              BIGINT             NOT NULL,
+    fee_per_gram        BIGINT             NOT NULL,
+    message             TEXT               NOT NULL,
+    one_sided           INTEGER            NOT NULL,
+    interval_secs       BIGINT             NOT NULL,
+    next_payment_at     TIMESTAMP          NOT NULL,
+    end_at              TIMESTAMP          NULL,
+    status              INTEGER            NOT NULL,
+    payments_made       BIGINT             NOT NULL DEFAULT 0,
+    last_tx_id          BIGINT             NULL,
+    failure_reason      TEXT               NULL,
+    created_at          TIMESTAMP          NOT NULL
+);
+
+CREATE INDEX idx_recurring_payments_status ON recurring_payments (status);
+
+CREATE TABLE transaction_events
+(
+    id         BIGSERIAL PRIMARY KEY NOT NULL,
+    event      TEXT                  NOT NULL,
+    created_at TIMESTAMP             NOT NULL
+);
+
+CREATE TABLE send_templates
+(
+    id                  BIGINT PRIMARY KEY NOT NULL,
+    name                TEXT               NOT NULL UNIQUE,
+    destination_address BYTEA              NOT NULL,
+    default_amount      BIGINT             NULL,
+    message             TEXT               NOT NULL,
+    fee_priority        INTEGER            NOT NULL,
+    one_sided           INTEGER            NOT NULL,
+    created_at          TIMESTAMP          NOT NULL,
+    updated_at          TIMESTAMP          NOT NULL
+);
@@ -0,0 +1,31 @@
+ALTER TABLE outbound_transactions DROP COLUMN awaiting_cosigners;
+
+DROP TABLE multisig_signings;
+DROP TABLE multisig_sessions;
+
+CREATE TABLE multisig_sessions
+(
+    session_id        BIGINT PRIMARY KEY NOT NULL,
+    threshold         INTEGER            NOT NULL,
+    participants_json TEXT               NOT NULL,
+    key_share_key_id  TEXT               NOT NULL,
+    label             TEXT               NOT NULL,
+    state             INTEGER            NOT NULL,
+    created_at        TIMESTAMP          NOT NULL,
+    updated_at        TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE multisig_signings
+(
+    signing_id      BIGINT PRIMARY KEY NOT NULL,
+    session_id      BIGINT             NOT NULL,
+    message         BYTEA              NOT NULL,
+    signers_json    TEXT               NOT NULL,
+    nonce_key_id    TEXT               NULL,
+    description     TEXT               NOT NULL,
+    state           INTEGER            NOT NULL,
+    signature_nonce BYTEA              NULL,
+    signature_key   BYTEA              NULL,
+    created_at      TIMESTAMP          NOT NULL,
+    updated_at      TIMESTAMP          NOT NULL
+);
@@ -0,0 +1,35 @@
+-- Sessions of the aggregate key scheme have no group key and cannot be carried over, they have to be set up again
+DROP TABLE multisig_signings;
+DROP TABLE multisig_sessions;
+
+CREATE TABLE multisig_sessions
+(
+    session_id           BIGINT PRIMARY KEY NOT NULL,
+    threshold            INTEGER            NOT NULL,
+    participants_json    TEXT               NOT NULL,
+    polynomial_key_id    TEXT               NOT NULL,
+    signing_share_key_id TEXT               NULL,
+    group_public_key     BYTEA              NULL,
+    label                TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE multisig_signings
+(
+    signing_id           BIGINT PRIMARY KEY NOT NULL,
+    session_id           BIGINT             NOT NULL,
+    message              BYTEA              NOT NULL,
+    signers_json         TEXT               NOT NULL,
+    hiding_nonce_key_id  TEXT               NULL,
+    binding_nonce_key_id TEXT               NULL,
+    description          TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    signature_nonce      BYTEA              NULL,
+    signature_key        BYTEA              NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
+
+ALTER TABLE outbound_transactions ADD COLUMN awaiting_cosigners INTEGER DEFAULT 0 NOT NULL;
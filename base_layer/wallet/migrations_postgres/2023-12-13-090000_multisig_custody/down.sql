@@ -0,0 +1,34 @@
+ALTER TABLE multisig_signings DROP COLUMN script_offset;
+ALTER TABLE multisig_signings DROP COLUMN joint_public_key;
+ALTER TABLE multisig_signings DROP COLUMN secret_key_id;
+ALTER TABLE multisig_signings DROP COLUMN kind_json;
+
+CREATE TABLE frost_sessions
+(
+    session_id           BIGINT PRIMARY KEY NOT NULL,
+    threshold            INTEGER            NOT NULL,
+    participants_json    TEXT               NOT NULL,
+    polynomial_key_id    TEXT               NOT NULL,
+    signing_share_key_id TEXT               NULL,
+    group_public_key     BYTEA              NULL,
+    label                TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
+
+CREATE TABLE frost_signings
+(
+    signing_id           BIGINT PRIMARY KEY NOT NULL,
+    session_id           BIGINT             NOT NULL,
+    message              BYTEA              NOT NULL,
+    signers_json         TEXT               NOT NULL,
+    hiding_nonce_key_id  TEXT               NULL,
+    binding_nonce_key_id TEXT               NULL,
+    description          TEXT               NOT NULL,
+    state                INTEGER            NOT NULL,
+    signature_nonce      BYTEA              NULL,
+    signature_key        BYTEA              NULL,
+    created_at           TIMESTAMP          NOT NULL,
+    updated_at           TIMESTAMP          NOT NULL
+);
@@ -0,0 +1,93 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Periodically writes encrypted snapshots of the wallet database to a backup directory, keeping a bounded number of
+//! the most recent ones. See [crate::storage::sqlite_db::snapshot] for the snapshot format and for restoring one.
+
+pub mod config;
+pub mod service;
+
+use log::*;
+use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
+
+use crate::{
+    backup_service::{config::BackupConfig, service::BackupService},
+    error::WalletStorageError,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::backup_service";
+
+pub struct BackupServiceInitializer<T> {
+    config: BackupConfig,
+    db: WalletDatabase<T>,
+}
+
+impl<T> BackupServiceInitializer<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(config: BackupConfig, db: WalletDatabase<T>) -> Self {
+        Self { config, db }
+    }
+}
+
+#[async_trait]
+impl<T> ServiceInitializer for BackupServiceInitializer<T>
+where T: WalletBackend + 'static
+{
+    async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
+        let Some(directory) = self.config.directory.clone() else {
+            return Ok(());
+        };
+        if !self.db.supports_snapshots() {
+            return Err(ServiceInitializationError::msg(
+                WalletStorageError::SnapshotsNotSupported.to_string(),
+            ));
+        }
+        // Fail at startup rather than at the first snapshot if the backup directory is unusable
+        std::fs::create_dir_all(&directory).map_err(|e| {
+            ServiceInitializationError::msg(format!(
+                "Could not create wallet backup directory '{}': {}",
+                directory.display(),
+                e
+            ))
+        })?;
+        info!(
+            target: LOG_TARGET,
+            "Wallet backup service initializing, writing snapshots to '{}' every {:.0?}",
+            directory.display(),
+            self.config.interval
+        );
+
+        let config = self.config.clone();
+        let db = self.db.clone();
+        context.spawn_when_ready(move |handles| async move {
+            BackupService::new(directory, config, db, handles.get_shutdown_signal())
+                .start()
+                .await;
+
+            info!(target: LOG_TARGET, "Wallet backup service shutdown");
+        });
+
+        Ok(())
+    }
+}
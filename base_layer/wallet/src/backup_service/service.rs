@@ -0,0 +1,170 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Utc;
+use log::*;
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{interval_at, Instant, MissedTickBehavior};
+
+use crate::{
+    backup_service::config::BackupConfig,
+    error::WalletStorageError,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::backup_service::service";
+
+/// Snapshot files are named `wallet-snapshot-<UTC timestamp>.snapshot`, so that they sort by age
+const SNAPSHOT_PREFIX: &str = "wallet-snapshot-";
+const SNAPSHOT_EXTENSION: &str = "snapshot";
+
+pub struct BackupService<T> {
+    directory: PathBuf,
+    config: BackupConfig,
+    db: WalletDatabase<T>,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl<T> BackupService<T>
+where T: WalletBackend + 'static
+{
+    pub fn new(
+        directory: PathBuf,
+        config: BackupConfig,
+        db: WalletDatabase<T>,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            directory,
+            config,
+            db,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn start(self) {
+        let mut interval = interval_at(Instant::now() + self.config.interval, self.config.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut shutdown_signal = self.shutdown_signal.clone();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {
+                    self.take_snapshot().await;
+                },
+                _ = shutdown_signal.wait() => {
+                    info!(target: LOG_TARGET, "Backup service shutting down because it received the shutdown signal");
+                    break;
+                },
+            }
+        }
+    }
+
+    async fn take_snapshot(&self) {
+        let path = self.directory.join(format!(
+            "{}{}.{}",
+            SNAPSHOT_PREFIX,
+            Utc::now().format("%Y%m%dT%H%M%SZ"),
+            SNAPSHOT_EXTENSION
+        ));
+        let db = self.db.clone();
+        let snapshot_path = path.clone();
+        match tokio::task::spawn_blocking(move || db.create_snapshot(&snapshot_path)).await {
+            Ok(Ok(())) => info!(target: LOG_TARGET, "Wallet snapshot written to '{}'", path.display()),
+            Ok(Err(e)) => {
+                error!(target: LOG_TARGET, "Could not write wallet snapshot '{}': {}", path.display(), e);
+                return;
+            },
+            Err(e) => {
+                error!(target: LOG_TARGET, "Wallet snapshot task failed: {}", e);
+                return;
+            },
+        }
+
+        if let Err(e) = prune_snapshots(&self.directory, self.config.max_snapshots) {
+            warn!(target: LOG_TARGET, "Could not remove old wallet snapshots: {}", e);
+        }
+    }
+}
+
+/// Remove the oldest snapshots in `directory` so that at most `max_snapshots` remain
+fn prune_snapshots(directory: &Path, max_snapshots: usize) -> Result<(), WalletStorageError> {
+    let mut snapshots = Vec::new();
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_snapshot = path.extension().map_or(false, |ext| ext == SNAPSHOT_EXTENSION) &&
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .map_or(false, |name| name.starts_with(SNAPSHOT_PREFIX));
+        if is_snapshot {
+            snapshots.push(path);
+        }
+    }
+    snapshots.sort();
+
+    let num_to_remove = snapshots.len().saturating_sub(max_snapshots.max(1));
+    for path in snapshots.into_iter().take(num_to_remove) {
+        debug!(target: LOG_TARGET, "Removing old wallet snapshot '{}'", path.display());
+        fs::remove_file(&path)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_keeps_the_newest_snapshots() {
+        let dir = tempdir().unwrap();
+        for name in [
+            "wallet-snapshot-20231201T000000Z.snapshot",
+            "wallet-snapshot-20231202T000000Z.snapshot",
+            "wallet-snapshot-20231203T000000Z.snapshot",
+            "unrelated.snapshot",
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+
+        prune_snapshots(dir.path(), 2).unwrap();
+
+        let mut remaining = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        remaining.sort();
+        assert_eq!(remaining, vec![
+            "unrelated.snapshot",
+            "wallet-snapshot-20231202T000000Z.snapshot",
+            "wallet-snapshot-20231203T000000Z.snapshot",
+        ]);
+    }
+}
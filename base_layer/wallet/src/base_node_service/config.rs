@@ -35,6 +35,12 @@ pub struct BaseNodeServiceConfig {
     pub base_node_rpc_pool_size: usize,
     /// This is the size of the event channel used to communicate base node events to the wallet
     pub event_channel_size: usize,
+    /// How long the wallet stays on a fallback base node before trying the preferred base node again
+    #[serde(with = "serializers::seconds")]
+    pub base_node_failback_interval: Duration,
+    /// Fail over to the next base node if the chain tip reported by the current one has not advanced for this long
+    #[serde(with = "serializers::seconds")]
+    pub base_node_stale_tip_timeout: Duration,
 }
 
 impl Default for BaseNodeServiceConfig {
@@ -43,6 +49,8 @@ impl Default for BaseNodeServiceConfig {
             base_node_monitor_max_refresh_interval: Duration::from_secs(90),
             base_node_rpc_pool_size: 10,
             event_channel_size: 250,
+            base_node_failback_interval: Duration::from_secs(10 * 60),
+            base_node_stale_tip_timeout: Duration::from_secs(30 * 60),
         }
     }
 }
@@ -44,10 +44,31 @@ pub enum BaseNodeServiceResponse {
     ChainMetadata(Option<ChainMetadata>),
     Latency(Option<Duration>),
 }
+/// The best block of the chain at a point in time
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct ChainTip {
+    pub height: u64,
+    pub hash: BlockHash,
+}
+
+impl fmt::Display for ChainTip {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.height, self.hash.to_hex())
+    }
+}
+
 #[derive(Clone, Debug, Hash, PartialEq, Eq)]
 pub enum BaseNodeEvent {
     BaseNodeStateChanged(BaseNodeState),
     NewBlockDetected(BlockHash, u64),
+    /// The base node's tip changed without the chain growing, so the last `depth` blocks the wallet saw were replaced.
+    /// Anything mined above `old_tip.height - depth` must be revalidated. This is always followed by a
+    /// `NewBlockDetected` event for the new tip.
+    Reorged {
+        depth: u64,
+        old_tip: ChainTip,
+        new_tip: ChainTip,
+    },
 }
 
 impl fmt::Display for BaseNodeEvent {
@@ -59,6 +80,13 @@ impl fmt::Display for BaseNodeEvent {
             BaseNodeEvent::NewBlockDetected(hash, height) => {
                 write!(f, "NewBlockDetected: {} ({})", height, hash.to_hex())
             },
+            BaseNodeEvent::Reorged {
+                depth,
+                old_tip,
+                new_tip,
+            } => {
+                write!(f, "Reorged: depth {}, tip {} -> {}", depth, old_tip, new_tip)
+            },
         }
     }
 }
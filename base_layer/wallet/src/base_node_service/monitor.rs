@@ -22,6 +22,7 @@
 
 use std::{
     cmp,
+    collections::VecDeque,
     convert::TryFrom,
     future::Future,
     sync::Arc,
@@ -34,13 +35,15 @@ use log::*;
 use tari_common_types::{chain_metadata::ChainMetadata, types::BlockHash as BlockHashType};
 use tari_comms::{
     backoff::{Backoff, ExponentialBackoff},
+    peer_manager::NodeId,
     protocol::rpc::RpcError,
 };
+use tari_core::{base_node::rpc::BaseNodeWalletRpcClient, blocks::BlockHeader};
 use tokio::{sync::RwLock, time};
 
 use crate::{
     base_node_service::{
-        handle::{BaseNodeEvent, BaseNodeEventSender},
+        handle::{BaseNodeEvent, BaseNodeEventSender, ChainTip},
         service::BaseNodeState,
     },
     connectivity_service::WalletConnectivityInterface,
@@ -49,6 +52,8 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::base_node_service::chain_metadata_monitor";
+/// The number of most recent tips kept to find where the chain forked when a reorg is detected
+const MAX_TRACKED_TIPS: usize = 50;
 
 pub struct BaseNodeMonitor<TBackend, TWalletConnectivity> {
     max_interval: Duration,
@@ -58,6 +63,8 @@ pub struct BaseNodeMonitor<TBackend, TWalletConnectivity> {
     db: WalletDatabase<TBackend>,
     wallet_connectivity: TWalletConnectivity,
     event_publisher: BaseNodeEventSender,
+    recent_tips: VecDeque<ChainTip>,
+    recent_tips_node: Option<NodeId>,
 }
 
 impl<TBackend, TWalletConnectivity> BaseNodeMonitor<TBackend, TWalletConnectivity>
@@ -80,6 +87,8 @@ where
             db,
             wallet_connectivity,
             event_publisher,
+            recent_tips: VecDeque::with_capacity(MAX_TRACKED_TIPS),
+            recent_tips_node: None,
         }
     }
 
@@ -172,6 +181,11 @@ where
                 timer.elapsed().as_millis()
             );
 
+            if let Some(reorg) = self.detect_reorg(&base_node_id, &chain_metadata, &mut client).await? {
+                warn!(target: LOG_TARGET, "Base node {} {}", base_node_id, reorg);
+                self.publish_event(reorg);
+            }
+
             self.db.set_chain_metadata(chain_metadata.clone())?;
 
             let is_synced = tip_info.is_synced;
@@ -216,6 +230,69 @@ where
         Ok(())
     }
 
+    /// Records the new tip and returns a `Reorged` event if the tip changed without the chain growing
+    async fn detect_reorg(
+        &mut self,
+        base_node_id: &NodeId,
+        chain_metadata: &ChainMetadata,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<Option<BaseNodeEvent>, BaseNodeMonitorError> {
+        let new_tip = ChainTip {
+            height: chain_metadata.height_of_longest_chain(),
+            hash: *chain_metadata.best_block(),
+        };
+        // Tips reported by different base nodes cannot be compared, a node that is behind is not a reorg
+        if self.recent_tips_node.as_ref() != Some(base_node_id) {
+            self.recent_tips.clear();
+            self.recent_tips_node = Some(base_node_id.clone());
+        }
+        let old_tip = match self.recent_tips.back() {
+            Some(tip) if tip.hash == new_tip.hash => return Ok(None),
+            Some(tip) if tip.height < new_tip.height => {
+                self.track_tip(new_tip);
+                return Ok(None);
+            },
+            Some(tip) => *tip,
+            None => {
+                self.track_tip(new_tip);
+                return Ok(None);
+            },
+        };
+
+        // Find the most recent tip we saw that is still on the base node's chain
+        let mut fork_height = None;
+        for tip in self.recent_tips.iter().rev().filter(|tip| tip.height <= new_tip.height) {
+            let header = BlockHeader::try_from(client.get_header_by_height(tip.height).await?)
+                .map_err(BaseNodeMonitorError::InvalidBaseNodeResponse)?;
+            if header.hash() == tip.hash {
+                fork_height = Some(tip.height);
+                break;
+            }
+        }
+        // If none of them are, the reorg is at least as deep as the tips we have tracked
+        let fork_height = fork_height.unwrap_or_else(|| {
+            self.recent_tips
+                .front()
+                .map_or(new_tip.height, |oldest| cmp::min(oldest.height, new_tip.height))
+                .saturating_sub(1)
+        });
+        self.recent_tips.retain(|tip| tip.height <= fork_height);
+        self.track_tip(new_tip);
+
+        Ok(Some(BaseNodeEvent::Reorged {
+            depth: old_tip.height.saturating_sub(fork_height),
+            old_tip,
+            new_tip,
+        }))
+    }
+
+    fn track_tip(&mut self, tip: ChainTip) {
+        if self.recent_tips.len() >= MAX_TRACKED_TIPS {
+            self.recent_tips.pop_front();
+        }
+        self.recent_tips.push_back(tip);
+    }
+
     // returns true if a new block, otherwise false
     async fn update_state(&self, new_state: BaseNodeState) -> bool {
         let mut lock = self.state.write().await;
@@ -38,14 +38,16 @@ use tari_p2p::P2pConfig;
 use tari_utilities::SafePassword;
 
 use crate::{
+    backup_service::config::BackupConfig,
     base_node_service::config::BaseNodeServiceConfig,
     output_manager_service::config::OutputManagerServiceConfig,
     transaction_service::config::TransactionServiceConfig,
+    webhook_service::config::WebhookConfig,
 };
 
 pub const KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY: &str = "comms";
 
-fn deserialize_safe_password_option<'de, D>(deserializer: D) -> Result<Option<SafePassword>, D::Error>
+pub(crate) fn deserialize_safe_password_option<'de, D>(deserializer: D) -> Result<Option<SafePassword>, D::Error>
 where D: serde::Deserializer<'de> {
     let password: Option<String> = Deserialize::deserialize(deserializer)?;
     Ok(password.map(SafePassword::from))
@@ -53,6 +55,7 @@ where D: serde::Deserializer<'de> {
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct WalletConfig {
     pub override_from: Option<String>,
     /// The p2p config settings
@@ -84,6 +87,29 @@ pub struct WalletConfig {
     pub contacts_auto_ping_interval: Duration,
     /// How long a contact may be not seen before being determined to be offline
     pub contacts_online_ping_window: usize,
+    /// How long to wait for a chat message to be sent directly to an online contact before falling back to store and
+    /// forward. Zero always uses store and forward.
+    #[serde(with = "serializers::seconds")]
+    pub contacts_direct_send_timeout: Duration,
+    /// Do not answer liveness pings or share this wallet's last-seen time with contacts
+    pub contacts_hide_online_status: bool,
+    /// Keep pinging contacts to learn their online status while `contacts_hide_online_status` is set
+    pub contacts_allow_outbound_pings: bool,
+    /// Require the wallet passphrase to be supplied again for sends that bring the total sent within
+    /// `reauthentication_send_window` to at least this many uT, and for spends whose amount is not known up front.
+    /// Unset disables the check.
+    pub reauthentication_send_threshold: Option<u64>,
+    /// The period over which sends are added up for `reauthentication_send_threshold`
+    #[serde(with = "serializers::seconds")]
+    pub reauthentication_send_window: Duration,
+    /// Require the wallet passphrase to be supplied again to export the seed words
+    pub reauthentication_for_seed_export: bool,
+    /// Require the wallet passphrase to be supplied again to create a wallet backup
+    pub reauthentication_for_backups: bool,
+    /// Run a consistency pass over the wallet database on startup and report any inconsistencies found
+    pub storage_integrity_check: bool,
+    /// Automatically repair inconsistencies found by the startup integrity check
+    pub storage_integrity_repair: bool,
     /// When running the console wallet in command mode, how long to wait for sent transactions.
     #[serde(with = "serializers::seconds")]
     pub command_send_wait_timeout: Duration,
@@ -114,6 +140,10 @@ pub struct WalletConfig {
     pub use_libtor: bool,
     /// A path to the file that stores the base node identity and secret key
     pub identity_file: Option<PathBuf>,
+    /// The webhook config settings for pushing transaction events to HTTP endpoints
+    pub webhooks: WebhookConfig,
+    /// The config settings for automatic encrypted snapshots of the wallet database
+    pub backups: BackupConfig,
 }
 
 impl Default for WalletConfig {
@@ -137,6 +167,15 @@ impl Default for WalletConfig {
             password: None,
             contacts_auto_ping_interval: Duration::from_secs(30),
             contacts_online_ping_window: 30,
+            contacts_direct_send_timeout: Duration::from_secs(20),
+            contacts_hide_online_status: false,
+            contacts_allow_outbound_pings: true,
+            reauthentication_send_threshold: None,
+            reauthentication_send_window: Duration::from_secs(24 * 60 * 60),
+            reauthentication_for_seed_export: false,
+            reauthentication_for_backups: false,
+            storage_integrity_check: true,
+            storage_integrity_repair: false,
             command_send_wait_stage: TransactionStage::Broadcast,
             command_send_wait_timeout: Duration::from_secs(300),
             notify_file: None,
@@ -150,6 +189,8 @@ impl Default for WalletConfig {
             num_required_confirmations: 3,
             use_libtor: false,
             identity_file: None,
+            webhooks: WebhookConfig::default(),
+            backups: BackupConfig::default(),
         }
     }
 }
@@ -168,6 +209,11 @@ impl WalletConfig {
         if !self.db_file.is_absolute() {
             self.db_file = self.data_dir.join(self.db_file.as_path());
         }
+        if let Some(directory) = self.backups.directory.as_mut() {
+            if !directory.is_absolute() {
+                *directory = self.data_dir.join(directory.as_path());
+            }
+        }
         self.p2p.set_base_path(base_path);
     }
 }
@@ -21,7 +21,9 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use futures::channel::{mpsc, oneshot};
-use tari_comms::connectivity::ConnectivityError;
+use tari_comms::{connectivity::ConnectivityError, protocol::rpc::RpcError};
+
+use crate::error::{ErrorCategory, ErrorHint};
 
 #[derive(Debug, thiserror::Error)]
 pub enum WalletConnectivityError {
@@ -29,8 +31,24 @@ pub enum WalletConnectivityError {
     BaseNodeNotSet,
     #[error("Connectivity error: {0}")]
     ConnectivityError(#[from] ConnectivityError),
+    #[error("RPC error: {0}")]
+    RpcError(#[from] RpcError),
     #[error("Service is terminated and can no longer response to requests")]
     ServiceTerminated,
+    #[error("Probing the base node timed out")]
+    ProbeTimedOut,
+}
+
+impl ErrorHint for WalletConnectivityError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WalletConnectivityError::BaseNodeNotSet => ErrorCategory::NotReady,
+            WalletConnectivityError::ConnectivityError(_) |
+            WalletConnectivityError::RpcError(_) |
+            WalletConnectivityError::ProbeTimedOut => ErrorCategory::Connectivity,
+            WalletConnectivityError::ServiceTerminated => ErrorCategory::Shutdown,
+        }
+    }
 }
 
 impl From<mpsc::SendError> for WalletConnectivityError {
@@ -28,7 +28,10 @@ use tari_comms::{
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
 use tokio::sync::{mpsc, oneshot, watch};
 
-use super::service::OnlineStatus;
+use super::{
+    scoring::BaseNodeScore,
+    service::{OnlineStatus, WalletConnectivityEventReceiver, WalletConnectivityEventSender},
+};
 use crate::{connectivity_service::WalletConnectivityInterface, util::watch::Watch};
 
 pub enum WalletConnectivityRequest {
@@ -40,19 +43,28 @@ pub enum WalletConnectivityRequest {
 pub struct WalletConnectivityHandle {
     sender: mpsc::Sender<WalletConnectivityRequest>,
     base_node_watch: Watch<Option<Peer>>,
+    base_node_peers: Watch<Vec<Peer>>,
     online_status_rx: watch::Receiver<OnlineStatus>,
+    event_publisher: WalletConnectivityEventSender,
+    base_node_scores: watch::Receiver<Vec<BaseNodeScore>>,
 }
 
 impl WalletConnectivityHandle {
     pub(super) fn new(
         sender: mpsc::Sender<WalletConnectivityRequest>,
         base_node_watch: Watch<Option<Peer>>,
+        base_node_peers: Watch<Vec<Peer>>,
         online_status_rx: watch::Receiver<OnlineStatus>,
+        event_publisher: WalletConnectivityEventSender,
+        base_node_scores: watch::Receiver<Vec<BaseNodeScore>>,
     ) -> Self {
         Self {
             sender,
             base_node_watch,
+            base_node_peers,
             online_status_rx,
+            event_publisher,
+            base_node_scores,
         }
     }
 }
@@ -60,12 +72,32 @@ impl WalletConnectivityHandle {
 #[async_trait::async_trait]
 impl WalletConnectivityInterface for WalletConnectivityHandle {
     fn set_base_node(&mut self, base_node_peer: Peer) {
+        self.set_base_node_peers(vec![base_node_peer]);
+    }
+
+    fn set_base_node_peers(&mut self, base_node_peers: Vec<Peer>) {
+        let Some(preferred) = base_node_peers.first().cloned() else {
+            return;
+        };
+        self.base_node_peers.send(base_node_peers);
         if let Some(peer) = self.base_node_watch.borrow().as_ref() {
-            if peer.public_key == base_node_peer.public_key {
+            if peer.public_key == preferred.public_key {
                 return;
             }
         }
-        self.base_node_watch.send(Some(base_node_peer));
+        self.base_node_watch.send(Some(preferred));
+    }
+
+    fn get_base_node_peers(&self) -> Vec<Peer> {
+        self.base_node_peers.borrow().clone()
+    }
+
+    fn get_event_stream(&self) -> WalletConnectivityEventReceiver {
+        self.event_publisher.subscribe()
+    }
+
+    fn get_base_node_scores(&self) -> Vec<BaseNodeScore> {
+        self.base_node_scores.borrow().clone()
     }
 
     fn get_current_base_node_watcher(&self) -> watch::Receiver<Option<Peer>> {
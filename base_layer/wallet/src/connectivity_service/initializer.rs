@@ -28,8 +28,11 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::sync::Arc;
+
+use tari_comms::PeerManager;
 use tari_service_framework::{async_trait, ServiceInitializationError, ServiceInitializer, ServiceInitializerContext};
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc};
 
 use super::{handle::WalletConnectivityHandle, service::WalletConnectivityService};
 use crate::{
@@ -53,23 +56,34 @@ impl ServiceInitializer for WalletConnectivityInitializer {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = mpsc::channel(5);
         let base_node_watch = Watch::new(None);
+        let base_node_peers = Watch::new(Vec::new());
         let online_status_watch = Watch::new(OnlineStatus::Offline);
+        let (event_publisher, _) = broadcast::channel(self.config.event_channel_size);
+        let base_node_scores = Watch::new(Vec::new());
         context.register_handle(WalletConnectivityHandle::new(
             sender,
             base_node_watch.clone(),
+            base_node_peers.clone(),
             online_status_watch.get_receiver(),
+            event_publisher.clone(),
+            base_node_scores.get_receiver(),
         ));
 
         let config = self.config.clone();
 
         context.spawn_until_shutdown(move |handles| {
             let connectivity = handles.expect_handle();
+            let peer_manager = handles.expect_handle::<Arc<PeerManager>>();
             let service = WalletConnectivityService::new(
                 config,
                 receiver,
-                base_node_watch.get_receiver(),
+                base_node_watch,
+                base_node_peers.get_receiver(),
                 online_status_watch,
                 connectivity,
+                peer_manager,
+                event_publisher,
+                base_node_scores,
             );
             service.start()
         });
@@ -30,12 +30,28 @@ use tari_comms::{
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
 use tokio::sync::watch;
 
-use crate::connectivity_service::OnlineStatus;
+use crate::connectivity_service::{BaseNodeScore, OnlineStatus, WalletConnectivityEventReceiver};
 
 #[async_trait::async_trait]
 pub trait WalletConnectivityInterface: Clone + Send + Sync + 'static {
+    /// Use a single base node, replacing any configured fallback base nodes
     fn set_base_node(&mut self, base_node_peer: Peer);
 
+    /// Use the first of `base_node_peers`, failing over to the next one in order if the active base node becomes
+    /// unreachable, fails RPC requests or reports a stale chain tip. The wallet fails back to the first base node once
+    /// the failback interval has passed.
+    fn set_base_node_peers(&mut self, base_node_peers: Vec<Peer>);
+
+    /// The configured base nodes, in order of preference
+    fn get_base_node_peers(&self) -> Vec<Peer>;
+
+    /// Subscribe to changes of the active base node made by the connectivity service
+    fn get_event_stream(&self) -> WalletConnectivityEventReceiver;
+
+    /// The scores of the configured base nodes, in order of preference. The connectivity service moves to the best
+    /// scoring base node when it scores clearly better than the current one.
+    fn get_base_node_scores(&self) -> Vec<BaseNodeScore>;
+
     fn get_current_base_node_watcher(&self) -> watch::Receiver<Option<Peer>>;
 
     /// Obtain a BaseNodeWalletRpcClient.
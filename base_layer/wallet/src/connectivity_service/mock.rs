@@ -26,10 +26,16 @@ use tari_comms::{
     types::CommsPublicKey,
 };
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
-use tokio::sync::watch::Receiver;
+use tokio::sync::{broadcast, watch::Receiver};
 
 use crate::{
-    connectivity_service::{OnlineStatus, WalletConnectivityInterface},
+    connectivity_service::{
+        BaseNodeScore,
+        OnlineStatus,
+        WalletConnectivityEventReceiver,
+        WalletConnectivityEventSender,
+        WalletConnectivityInterface,
+    },
     util::watch::Watch,
 };
 
@@ -41,6 +47,8 @@ pub fn create() -> WalletConnectivityMock {
 pub struct WalletConnectivityMock {
     online_status_watch: Watch<OnlineStatus>,
     base_node_watch: Watch<Option<Peer>>,
+    base_node_peers: Watch<Vec<Peer>>,
+    event_publisher: WalletConnectivityEventSender,
     base_node_wallet_rpc_client: Watch<Option<RpcClientLease<BaseNodeWalletRpcClient>>>,
     base_node_sync_rpc_client: Watch<Option<RpcClientLease<BaseNodeSyncRpcClient>>>,
 }
@@ -50,6 +58,8 @@ impl WalletConnectivityMock {
         Self {
             online_status_watch: Watch::new(OnlineStatus::Offline),
             base_node_watch: Watch::new(None),
+            base_node_peers: Watch::new(Vec::new()),
+            event_publisher: broadcast::channel(10).0,
             base_node_wallet_rpc_client: Watch::new(None),
             base_node_sync_rpc_client: Watch::new(None),
         }
@@ -83,7 +93,26 @@ impl WalletConnectivityMock {
 #[async_trait::async_trait]
 impl WalletConnectivityInterface for WalletConnectivityMock {
     fn set_base_node(&mut self, base_node_peer: Peer) {
-        self.notify_base_node_set(base_node_peer);
+        self.set_base_node_peers(vec![base_node_peer]);
+    }
+
+    fn set_base_node_peers(&mut self, base_node_peers: Vec<Peer>) {
+        if let Some(preferred) = base_node_peers.first().cloned() {
+            self.base_node_peers.send(base_node_peers);
+            self.notify_base_node_set(preferred);
+        }
+    }
+
+    fn get_base_node_peers(&self) -> Vec<Peer> {
+        self.base_node_peers.borrow().clone()
+    }
+
+    fn get_event_stream(&self) -> WalletConnectivityEventReceiver {
+        self.event_publisher.subscribe()
+    }
+
+    fn get_base_node_scores(&self) -> Vec<BaseNodeScore> {
+        Vec::new()
     }
 
     fn get_current_base_node_watcher(&self) -> Receiver<Option<Peer>> {
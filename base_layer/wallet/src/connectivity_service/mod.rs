@@ -29,8 +29,17 @@ pub use handle::WalletConnectivityHandle;
 mod initializer;
 pub use initializer::WalletConnectivityInitializer;
 
+mod scoring;
+pub use scoring::BaseNodeScore;
+
 mod service;
-pub use service::OnlineStatus;
+pub use service::{
+    BaseNodeChangeReason,
+    OnlineStatus,
+    WalletConnectivityEvent,
+    WalletConnectivityEventReceiver,
+    WalletConnectivityEventSender,
+};
 
 #[cfg(test)]
 mod test;
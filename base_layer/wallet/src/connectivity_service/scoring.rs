@@ -0,0 +1,208 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use tari_comms::{
+    peer_manager::{NodeId, Peer},
+    types::CommsPublicKey,
+};
+
+/// Latency at or above which a base node gets no latency points
+const MAX_SCORED_LATENCY: Duration = Duration::from_secs(2);
+/// Number of blocks behind the best known tip at which a base node gets no tip freshness points
+const MAX_SCORED_TIP_LAG: u64 = 10;
+
+const RELIABILITY_POINTS: u64 = 40;
+const LATENCY_POINTS: u64 = 30;
+const TIP_FRESHNESS_POINTS: u64 = 30;
+
+/// The quality of a candidate base node as observed by the wallet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BaseNodeScore {
+    pub node_id: NodeId,
+    pub public_key: CommsPublicKey,
+    /// Smoothed RPC round trip latency
+    pub latency: Option<Duration>,
+    /// The last chain tip height reported by the base node
+    pub tip_height: Option<u64>,
+    /// Number of successful RPC requests
+    pub successes: u64,
+    /// Number of failed dials and RPC requests
+    pub failures: u64,
+    /// The overall score out of 100, combining the error rate, latency and how far the tip lags the best known tip
+    pub score: u64,
+    /// Whether this is the base node the wallet is currently using
+    pub is_active: bool,
+}
+
+impl BaseNodeScore {
+    fn new(peer: &Peer) -> Self {
+        Self {
+            node_id: peer.node_id.clone(),
+            public_key: peer.public_key.clone(),
+            latency: None,
+            tip_height: None,
+            successes: 0,
+            failures: 0,
+            score: 0,
+            is_active: false,
+        }
+    }
+
+    fn calculate_score(&self, best_tip_height: u64) -> u64 {
+        let reliability = RELIABILITY_POINTS * (self.successes + 1) / (self.successes + self.failures + 2);
+
+        let latency = match self.latency {
+            Some(latency) if latency >= MAX_SCORED_LATENCY => 0,
+            Some(latency) => {
+                let max = MAX_SCORED_LATENCY.as_millis();
+                let points = u128::from(LATENCY_POINTS) * (max - latency.as_millis()) / max;
+                u64::try_from(points).unwrap_or(0)
+            },
+            // Unknown latency is neither rewarded nor penalised
+            None => LATENCY_POINTS / 2,
+        };
+
+        let tip_freshness = match self.tip_height {
+            Some(height) => {
+                let lag = best_tip_height.saturating_sub(height);
+                TIP_FRESHNESS_POINTS * MAX_SCORED_TIP_LAG.saturating_sub(lag) / MAX_SCORED_TIP_LAG
+            },
+            None => 0,
+        };
+
+        reliability + latency + tip_freshness
+    }
+}
+
+/// Keeps the scores of the candidate base nodes of the connectivity service
+#[derive(Debug, Default)]
+pub(super) struct BaseNodeScorer {
+    scores: Vec<BaseNodeScore>,
+}
+
+impl BaseNodeScorer {
+    /// Track exactly the given candidates, keeping what is known about the ones that were already tracked
+    pub fn set_candidates(&mut self, candidates: &[Peer]) {
+        let mut scores = Vec::with_capacity(candidates.len());
+        for peer in candidates {
+            let score = self
+                .scores
+                .iter()
+                .find(|s| s.node_id == peer.node_id)
+                .cloned()
+                .unwrap_or_else(|| BaseNodeScore::new(peer));
+            scores.push(score);
+        }
+        self.scores = scores;
+        self.update_scores();
+    }
+
+    pub fn record_success(&mut self, node_id: &NodeId, latency: Option<Duration>, tip_height: Option<u64>) {
+        if let Some(score) = self.get_mut(node_id) {
+            score.successes += 1;
+            if let Some(sample) = latency {
+                score.latency = Some(score.latency.map_or(sample, |latency| (latency * 3 + sample) / 4));
+            }
+            if tip_height.is_some() {
+                score.tip_height = tip_height;
+            }
+        }
+        self.update_scores();
+    }
+
+    pub fn record_failure(&mut self, node_id: &NodeId) {
+        if let Some(score) = self.get_mut(node_id) {
+            score.failures += 1;
+        }
+        self.update_scores();
+    }
+
+    pub fn set_active(&mut self, node_id: Option<&NodeId>) {
+        for score in &mut self.scores {
+            score.is_active = Some(&score.node_id) == node_id;
+        }
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<&BaseNodeScore> {
+        self.scores.iter().find(|s| &s.node_id == node_id)
+    }
+
+    /// The candidate with the highest score. Candidates with equal scores are preferred in the order they were given.
+    pub fn best(&self) -> Option<&BaseNodeScore> {
+        self.scores.iter().rev().max_by_key(|s| s.score)
+    }
+
+    pub fn scores(&self) -> Vec<BaseNodeScore> {
+        self.scores.clone()
+    }
+
+    fn get_mut(&mut self, node_id: &NodeId) -> Option<&mut BaseNodeScore> {
+        self.scores.iter_mut().find(|s| &s.node_id == node_id)
+    }
+
+    fn update_scores(&mut self) {
+        let best_tip_height = self
+            .scores
+            .iter()
+            .filter_map(|s| s.tip_height)
+            .max()
+            .unwrap_or_default();
+        for score in &mut self.scores {
+            score.score = score.calculate_score(best_tip_height);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_comms::{peer_manager::PeerFeatures, test_utils::node_identity::build_node_identity};
+
+    use super::*;
+
+    #[test]
+    fn it_prefers_reliable_fast_and_up_to_date_base_nodes() {
+        let peers = (0..3)
+            .map(|_| build_node_identity(PeerFeatures::COMMUNICATION_NODE).to_peer())
+            .collect::<Vec<_>>();
+        let mut scorer = BaseNodeScorer::default();
+        scorer.set_candidates(&peers);
+        // Nothing is known yet, so the first candidate is preferred
+        assert_eq!(scorer.best().unwrap().node_id, peers[0].node_id);
+
+        scorer.record_failure(&peers[0].node_id);
+        scorer.record_success(&peers[1].node_id, Some(Duration::from_millis(1500)), Some(100));
+        scorer.record_success(&peers[2].node_id, Some(Duration::from_millis(100)), Some(100));
+        assert_eq!(scorer.best().unwrap().node_id, peers[2].node_id);
+
+        // Falling behind the best known tip costs points
+        scorer.record_success(&peers[1].node_id, None, Some(110));
+        assert_eq!(scorer.best().unwrap().node_id, peers[1].node_id);
+        assert!(scorer.get(&peers[0].node_id).unwrap().score < scorer.get(&peers[2].node_id).unwrap().score);
+
+        // Known scores survive a change of candidates
+        scorer.set_candidates(&peers[1..]);
+        assert_eq!(scorer.scores().len(), 2);
+        assert_eq!(scorer.get(&peers[1].node_id).unwrap().successes, 2);
+    }
+}
@@ -20,7 +20,11 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{mem, time::Duration};
+use std::{
+    mem,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use log::*;
 use tari_comms::{
@@ -28,22 +32,35 @@ use tari_comms::{
     peer_manager::{NodeId, Peer},
     protocol::rpc::{RpcClientLease, RpcClientPool},
     PeerConnection,
+    PeerManager,
 };
 use tari_core::base_node::{rpc::BaseNodeWalletRpcClient, sync::rpc::BaseNodeSyncRpcClient};
 use tokio::{
-    sync::{mpsc, oneshot, watch},
+    sync::{broadcast, mpsc, oneshot, watch},
     time,
     time::MissedTickBehavior,
 };
 
 use crate::{
     base_node_service::config::BaseNodeServiceConfig,
-    connectivity_service::{error::WalletConnectivityError, handle::WalletConnectivityRequest},
+    connectivity_service::{
+        error::WalletConnectivityError,
+        handle::WalletConnectivityRequest,
+        scoring::{BaseNodeScore, BaseNodeScorer},
+    },
     util::watch::Watch,
 };
 
 const LOG_TARGET: &str = "wallet::connectivity";
 
+/// How many points out of 100 another base node must score above the current one before the wallet moves to it
+const SCORE_SWITCH_MARGIN: u64 = 15;
+/// How long a probe of a base node that is not in use may take before it counts as failed
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The latency and chain tip height measured by probing a base node
+type ProbeResult = (NodeId, Result<(Option<Duration>, u64), WalletConnectivityError>);
+
 /// Connection status of the Base Node
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum OnlineStatus {
@@ -52,14 +69,60 @@ pub enum OnlineStatus {
     Offline,
 }
 
+/// Why the connectivity service moved the wallet to another base node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BaseNodeChangeReason {
+    /// The base node could not be dialed
+    Unreachable,
+    /// An RPC session with the base node could not be obtained or failed
+    RpcError,
+    /// The chain tip reported by the base node has not advanced within the stale tip timeout
+    StaleTip,
+    /// The failback interval has passed, so the preferred base node is tried again
+    FailBack,
+    /// Another base node scores clearly better than the current one
+    BetterScore,
+    /// The base node rotated its identity, so the wallet follows it to its new identity
+    IdentityRotated,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WalletConnectivityEvent {
+    /// The connectivity service switched the active base node to another of the configured base nodes
+    ActiveBaseNodeChanged {
+        previous: Option<NodeId>,
+        current: NodeId,
+        reason: BaseNodeChangeReason,
+    },
+}
+
+pub type WalletConnectivityEventSender = broadcast::Sender<Arc<WalletConnectivityEvent>>;
+pub type WalletConnectivityEventReceiver = broadcast::Receiver<Arc<WalletConnectivityEvent>>;
+
 pub struct WalletConnectivityService {
     config: BaseNodeServiceConfig,
     request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
     connectivity: ConnectivityRequester,
-    base_node_watch: watch::Receiver<Option<Peer>>,
+    peer_manager: Arc<PeerManager>,
+    base_node_watch: Watch<Option<Peer>>,
+    base_node_receiver: watch::Receiver<Option<Peer>>,
+    base_node_peers: watch::Receiver<Vec<Peer>>,
     pools: Option<ClientPoolContainer>,
     online_status_watch: Watch<OnlineStatus>,
     pending_requests: Vec<ReplyOneshot>,
+    event_publisher: WalletConnectivityEventSender,
+    /// When the current base node became the active one
+    active_since: Instant,
+    /// The last chain tip height reported by the current base node and when it was first seen
+    last_tip: Option<(u64, Instant)>,
+    last_tip_check: Option<Instant>,
+    scorer: BaseNodeScorer,
+    base_node_scores: Watch<Vec<BaseNodeScore>>,
+    /// Which of the base nodes that are not in use is probed next
+    probe_index: usize,
+    probe_in_flight: bool,
+    probe_result_sender: mpsc::Sender<ProbeResult>,
+    probe_results: mpsc::Receiver<ProbeResult>,
 }
 
 struct ClientPoolContainer {
@@ -71,18 +134,36 @@ impl WalletConnectivityService {
     pub(super) fn new(
         config: BaseNodeServiceConfig,
         request_receiver: mpsc::Receiver<WalletConnectivityRequest>,
-        base_node_watch: watch::Receiver<Option<Peer>>,
+        base_node_watch: Watch<Option<Peer>>,
+        base_node_peers: watch::Receiver<Vec<Peer>>,
         online_status_watch: Watch<OnlineStatus>,
         connectivity: ConnectivityRequester,
+        peer_manager: Arc<PeerManager>,
+        event_publisher: WalletConnectivityEventSender,
+        base_node_scores: Watch<Vec<BaseNodeScore>>,
     ) -> Self {
+        let (probe_result_sender, probe_results) = mpsc::channel(1);
         Self {
             config,
             request_receiver,
             connectivity,
+            peer_manager,
+            base_node_receiver: base_node_watch.get_receiver(),
             base_node_watch,
+            base_node_peers,
             pools: None,
             pending_requests: Vec::new(),
             online_status_watch,
+            event_publisher,
+            active_since: Instant::now(),
+            last_tip: None,
+            last_tip_check: None,
+            scorer: BaseNodeScorer::default(),
+            base_node_scores,
+            probe_index: 0,
+            probe_in_flight: false,
+            probe_result_sender,
+            probe_results,
         }
     }
 
@@ -97,8 +178,11 @@ impl WalletConnectivityService {
                 // BIASED: select branches are in order of priority
                 biased;
 
-                Ok(_) = self.base_node_watch.changed() => {
-                    if self.base_node_watch.borrow().is_some() {
+                Ok(_) = self.base_node_receiver.changed() => {
+                    self.active_since = Instant::now();
+                    self.last_tip = None;
+                    self.scorer.set_candidates(&self.base_node_peers.borrow());
+                    if self.base_node_receiver.borrow().is_some() {
                         // This will block the rest until the connection is established. This is what we want.
                         self.setup_base_node_connection().await;
                     }
@@ -108,6 +192,10 @@ impl WalletConnectivityService {
                     self.handle_request(req).await;
                 },
 
+                Some((node_id, result)) = self.probe_results.recv() => {
+                    self.handle_probe_result(node_id, result);
+                },
+
                 _ = check_connection.tick() => {
                     self.check_connection().await;
                 }
@@ -122,6 +210,8 @@ impl WalletConnectivityService {
                     debug!(target: LOG_TARGET, "Peer connection lost. Attempting to reconnect...");
                     self.set_online_status(OnlineStatus::Offline);
                     self.setup_base_node_connection().await;
+                } else {
+                    self.check_base_node_health().await;
                 }
             },
             None => {
@@ -167,14 +257,16 @@ impl WalletConnectivityService {
                         "Base node connection failed: {}. Reconnecting...", e
                     );
                     if let Some(node_id) = self.current_base_node() {
+                        self.scorer.record_failure(&node_id);
                         self.disconnect_base_node(node_id).await;
                     };
+                    self.fail_over(BaseNodeChangeReason::RpcError);
                     self.pending_requests.push(reply.into());
                 },
             },
             None => {
                 self.pending_requests.push(reply.into());
-                if self.base_node_watch.borrow().is_none() {
+                if self.base_node_receiver.borrow().is_none() {
                     warn!(
                         target: LOG_TARGET,
                         "{} requests are waiting for base node to be set",
@@ -200,14 +292,16 @@ impl WalletConnectivityService {
                         "Base node connection failed: {}. Reconnecting...", e
                     );
                     if let Some(node_id) = self.current_base_node() {
+                        self.scorer.record_failure(&node_id);
                         self.disconnect_base_node(node_id).await;
                     };
+                    self.fail_over(BaseNodeChangeReason::RpcError);
                     self.pending_requests.push(reply.into());
                 },
             },
             None => {
                 self.pending_requests.push(reply.into());
-                if self.base_node_watch.borrow().is_none() {
+                if self.base_node_receiver.borrow().is_none() {
                     warn!(
                         target: LOG_TARGET,
                         "{} requests are waiting for base node to be set",
@@ -219,7 +313,215 @@ impl WalletConnectivityService {
     }
 
     fn current_base_node(&self) -> Option<NodeId> {
-        self.base_node_watch.borrow().as_ref().map(|p| p.node_id.clone())
+        self.base_node_receiver.borrow().as_ref().map(|p| p.node_id.clone())
+    }
+
+    fn is_preferred_base_node(&self, node_id: &NodeId) -> bool {
+        self.base_node_peers
+            .borrow()
+            .first()
+            .map_or(true, |preferred| &preferred.node_id == node_id)
+    }
+
+    /// Keep the scores of the configured base nodes up to date, fail back to the preferred base node once the failback
+    /// interval has passed and move to another base node when the current one has gone stale or another one scores
+    /// clearly better.
+    async fn check_base_node_health(&mut self) {
+        let Some(node_id) = self.current_base_node() else {
+            return;
+        };
+        self.scorer.set_candidates(&self.base_node_peers.borrow());
+        let has_fallbacks = self.base_node_peers.borrow().len() > 1;
+
+        if has_fallbacks &&
+            !self.is_preferred_base_node(&node_id) &&
+            self.active_since.elapsed() >= self.config.base_node_failback_interval
+        {
+            let preferred = self.base_node_peers.borrow().first().cloned();
+            if let Some(preferred) = preferred {
+                if !self.scores_better(&node_id, &preferred.node_id) {
+                    self.switch_base_node(preferred, BaseNodeChangeReason::FailBack);
+                    return;
+                }
+            }
+        }
+
+        if self.last_tip_check.map_or(false, |checked| {
+            checked.elapsed() < self.config.base_node_monitor_max_refresh_interval
+        }) {
+            return;
+        }
+        self.last_tip_check = Some(Instant::now());
+
+        let Some(pools) = self.pools.as_ref() else {
+            return;
+        };
+        let tip_info = match pools.base_node_wallet_rpc_client.get().await {
+            Ok(mut client) => client
+                .get_tip_info()
+                .await
+                .map(|tip_info| (tip_info, client.get_last_request_latency()))
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        };
+        match tip_info {
+            Ok((tip_info, latency)) => {
+                let height = tip_info.metadata.map(|m| m.height_of_longest_chain).unwrap_or_default();
+                self.scorer.record_success(&node_id, latency, Some(height));
+                match self.last_tip {
+                    Some((last_height, since)) if height <= last_height => {
+                        if has_fallbacks && since.elapsed() >= self.config.base_node_stale_tip_timeout {
+                            warn!(
+                                target: LOG_TARGET,
+                                "Base node {} has been stuck at height {} for {}s",
+                                node_id,
+                                height,
+                                since.elapsed().as_secs()
+                            );
+                            self.fail_over(BaseNodeChangeReason::StaleTip);
+                            return;
+                        }
+                    },
+                    _ => self.last_tip = Some((height, Instant::now())),
+                }
+            },
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not get the chain tip from base node {}: {}", node_id, e);
+                self.scorer.record_failure(&node_id);
+                self.disconnect_base_node(node_id).await;
+                self.fail_over(BaseNodeChangeReason::RpcError);
+                return;
+            },
+        }
+
+        if has_fallbacks {
+            self.probe_next_candidate(&node_id);
+            self.select_best_base_node(&node_id);
+        }
+        self.publish_scores();
+    }
+
+    /// Measure the latency and chain tip of one of the base nodes that are not in use, taking turns between them. The
+    /// probe runs in its own task so that a slow or unreachable candidate cannot hold up requests for RPC clients, its
+    /// result is fed back over `probe_results`.
+    fn probe_next_candidate(&mut self, active: &NodeId) {
+        if self.probe_in_flight {
+            return;
+        }
+        let candidate = {
+            let candidates = self.base_node_peers.borrow();
+            let others = candidates.iter().filter(|p| &p.node_id != active).collect::<Vec<_>>();
+            if others.is_empty() {
+                return;
+            }
+            self.probe_index = (self.probe_index + 1) % others.len();
+            others[self.probe_index].node_id.clone()
+        };
+        self.probe_in_flight = true;
+        let connectivity = self.connectivity.clone();
+        let probe_result_sender = self.probe_result_sender.clone();
+        tokio::spawn(async move {
+            let result = time::timeout(PROBE_TIMEOUT, probe_base_node(connectivity, candidate.clone()))
+                .await
+                .unwrap_or(Err(WalletConnectivityError::ProbeTimedOut));
+            // The service may have shut down while the probe was running
+            let _result = probe_result_sender.send((candidate, result)).await;
+        });
+    }
+
+    fn handle_probe_result(
+        &mut self,
+        node_id: NodeId,
+        result: Result<(Option<Duration>, u64), WalletConnectivityError>,
+    ) {
+        self.probe_in_flight = false;
+        match result {
+            Ok((latency, height)) => self.scorer.record_success(&node_id, latency, Some(height)),
+            Err(e) => {
+                debug!(target: LOG_TARGET, "Probing base node {} failed: {}", node_id, e);
+                self.scorer.record_failure(&node_id);
+            },
+        }
+        self.publish_scores();
+    }
+
+    /// Move to the best scoring base node if it scores clearly better than the current one
+    fn select_best_base_node(&mut self, current: &NodeId) {
+        let Some(best) = self.scorer.best().map(|s| s.node_id.clone()) else {
+            return;
+        };
+        if &best == current || !self.scores_better(&best, current) {
+            return;
+        }
+        let peer = self
+            .base_node_peers
+            .borrow()
+            .iter()
+            .find(|p| p.node_id == best)
+            .cloned();
+        if let Some(peer) = peer {
+            self.switch_base_node(peer, BaseNodeChangeReason::BetterScore);
+        }
+    }
+
+    /// Whether base node `a` scores clearly better than base node `b`
+    fn scores_better(&self, a: &NodeId, b: &NodeId) -> bool {
+        match (self.scorer.get(a), self.scorer.get(b)) {
+            (Some(a), Some(b)) => a.score >= b.score + SCORE_SWITCH_MARGIN,
+            _ => false,
+        }
+    }
+
+    fn publish_scores(&mut self) {
+        self.scorer.set_active(self.current_base_node().as_ref());
+        self.base_node_scores.send(self.scorer.scores());
+    }
+
+    /// Switch to the next configured base node after the current one. Returns false if there is no other base node to
+    /// switch to.
+    fn fail_over(&mut self, reason: BaseNodeChangeReason) -> bool {
+        let current = self.current_base_node();
+        let next = {
+            let candidates = self.base_node_peers.borrow();
+            if candidates.is_empty() {
+                return false;
+            }
+            let index = current
+                .as_ref()
+                .and_then(|node_id| candidates.iter().position(|p| &p.node_id == node_id))
+                .map_or(0, |i| (i + 1) % candidates.len());
+            candidates[index].clone()
+        };
+        if current.as_ref() == Some(&next.node_id) {
+            return false;
+        }
+        self.switch_base_node(next, reason);
+        true
+    }
+
+    fn switch_base_node(&mut self, peer: Peer, reason: BaseNodeChangeReason) {
+        let previous = self.current_base_node();
+        let current = peer.node_id.clone();
+        info!(
+            target: LOG_TARGET,
+            "Switching base node from {} to {} ({:?})",
+            previous.as_ref().map_or_else(|| "none".to_string(), |n| n.to_string()),
+            current,
+            reason
+        );
+        self.pools = None;
+        self.active_since = Instant::now();
+        self.last_tip = None;
+        self.base_node_watch.send(Some(peer));
+        // Nobody may be listening, which is fine
+        let _size = self
+            .event_publisher
+            .send(Arc::new(WalletConnectivityEvent::ActiveBaseNodeChanged {
+                previous,
+                current,
+                reason,
+            }));
+        self.publish_scores();
     }
 
     async fn disconnect_base_node(&mut self, node_id: NodeId) {
@@ -276,9 +578,19 @@ impl WalletConnectivityService {
                 Err(e) => {
                     warn!(target: LOG_TARGET, "{}", e);
                     if self.current_base_node().as_ref() == Some(&node_id) {
+                        self.scorer.record_failure(&node_id);
                         self.disconnect_base_node(node_id).await;
                         self.set_online_status(OnlineStatus::Offline);
-                        time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                        let switched = self.fail_over(BaseNodeChangeReason::Unreachable);
+                        // The switch is handled here rather than by the main loop
+                        self.base_node_receiver.borrow_and_update();
+                        // Back off once every configured base node has been tried
+                        let cycled = self
+                            .current_base_node()
+                            .map_or(true, |node_id| self.is_preferred_base_node(&node_id));
+                        if !switched || cycled {
+                            time::sleep(self.config.base_node_monitor_max_refresh_interval).await;
+                        }
                     }
                     continue;
                 },
@@ -303,6 +615,9 @@ impl WalletConnectivityService {
             "Successfully established peer connection to base node {}",
             conn.peer_node_id()
         );
+        if conn.peer_node_id() != &peer {
+            self.follow_identity_rotation(&peer, conn.peer_node_id().clone()).await;
+        }
         self.pools = Some(ClientPoolContainer {
             base_node_sync_rpc_client: conn.create_rpc_client_pool(1, Default::default()),
             base_node_wallet_rpc_client: conn
@@ -313,11 +628,40 @@ impl WalletConnectivityService {
         Ok(true)
     }
 
+    /// Makes the new identity of a base node that has rotated its identity the active base node. The connectivity
+    /// manager redirects dials to the previous identity to its successor once the previous identity is unreachable.
+    async fn follow_identity_rotation(&mut self, previous: &NodeId, successor: NodeId) {
+        let peer = match self.peer_manager.find_by_node_id(&successor).await {
+            Ok(Some(peer)) => peer,
+            Ok(None) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Base node {} was redirected to unknown peer {}", previous, successor
+                );
+                return;
+            },
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Failed to look up base node peer {}: {}", successor, e);
+                return;
+            },
+        };
+        info!(
+            target: LOG_TARGET,
+            "Base node {} has rotated its identity to {}", previous, successor
+        );
+        if let Err(e) = self.connectivity.add_peer_to_allow_list(successor).await {
+            warn!(target: LOG_TARGET, "Failed to add base node to the allow list: {}", e);
+        }
+        self.switch_base_node(peer, BaseNodeChangeReason::IdentityRotated);
+        // The switch is handled here rather than by the main loop
+        self.base_node_receiver.borrow_and_update();
+    }
+
     async fn try_dial_peer(&mut self, peer: NodeId) -> Result<Option<PeerConnection>, WalletConnectivityError> {
         tokio::select! {
             biased;
 
-            _ = self.base_node_watch.changed() => {
+            _ = self.base_node_receiver.changed() => {
                 Ok(None)
             }
             result = self.connectivity.dial_peer(peer) => {
@@ -339,6 +683,17 @@ impl WalletConnectivityService {
     }
 }
 
+async fn probe_base_node(
+    connectivity: ConnectivityRequester,
+    node_id: NodeId,
+) -> Result<(Option<Duration>, u64), WalletConnectivityError> {
+    let mut conn = connectivity.dial_peer(node_id).await?;
+    let mut client = conn.connect_rpc::<BaseNodeWalletRpcClient>().await?;
+    let tip_info = client.get_tip_info().await?;
+    let height = tip_info.metadata.map(|m| m.height_of_longest_chain).unwrap_or_default();
+    Ok((client.get_last_request_latency(), height))
+}
+
 enum ReplyOneshot {
     WalletRpc(oneshot::Sender<RpcClientLease<BaseNodeWalletRpcClient>>),
     SyncRpc(oneshot::Sender<RpcClientLease<BaseNodeSyncRpcClient>>),
@@ -31,20 +31,28 @@ use tari_comms::{
         RpcPoolClient,
     },
     test_utils::{
+        build_peer_manager,
         mocks::{create_connectivity_mock, ConnectivityManagerMockState},
         node_identity::build_node_identity,
     },
+    PeerManager,
 };
 use tari_shutdown::Shutdown;
 use tari_test_utils::runtime::spawn_until_shutdown;
 use tokio::{
-    sync::{mpsc, Barrier},
+    sync::{broadcast, mpsc, Barrier},
     task,
 };
 
 use super::service::WalletConnectivityService;
 use crate::{
-    connectivity_service::{OnlineStatus, WalletConnectivityHandle, WalletConnectivityInterface},
+    connectivity_service::{
+        BaseNodeChangeReason,
+        OnlineStatus,
+        WalletConnectivityEvent,
+        WalletConnectivityHandle,
+        WalletConnectivityInterface,
+    },
     util::watch::Watch,
 };
 
@@ -53,20 +61,44 @@ async fn setup() -> (
     MockRpcServer<MockRpcImpl>,
     ConnectivityManagerMockState,
     Shutdown,
+) {
+    setup_with_peer_manager(build_peer_manager()).await
+}
+
+async fn setup_with_peer_manager(
+    peer_manager: Arc<PeerManager>,
+) -> (
+    WalletConnectivityHandle,
+    MockRpcServer<MockRpcImpl>,
+    ConnectivityManagerMockState,
+    Shutdown,
 ) {
     let (tx, rx) = mpsc::channel(1);
     let base_node_watch = Watch::new(None);
+    let base_node_peers = Watch::new(Vec::new());
     let online_status_watch = Watch::new(OnlineStatus::Offline);
-    let handle = WalletConnectivityHandle::new(tx, base_node_watch.clone(), online_status_watch.get_receiver());
+    let (event_publisher, _) = broadcast::channel(10);
+    let base_node_scores = Watch::new(Vec::new());
+    let handle = WalletConnectivityHandle::new(
+        tx,
+        base_node_watch.clone(),
+        base_node_peers.clone(),
+        online_status_watch.get_receiver(),
+        event_publisher.clone(),
+        base_node_scores.get_receiver(),
+    );
     let (connectivity, mock) = create_connectivity_mock();
     let mock_state = mock.spawn();
-    // let peer_manager = create_peer_manager(tempdir().unwrap());
     let service = WalletConnectivityService::new(
         Default::default(),
         rx,
-        base_node_watch.get_receiver(),
+        base_node_watch,
+        base_node_peers.get_receiver(),
         online_status_watch,
         connectivity,
+        peer_manager,
+        event_publisher,
+        base_node_scores,
     );
     let shutdown = spawn_until_shutdown(service.start());
 
@@ -161,6 +193,67 @@ async fn it_changes_to_a_new_base_node() {
     assert!(rpc_client.is_connected());
 }
 
+#[tokio::test]
+async fn it_fails_over_to_the_next_base_node() {
+    let (mut handle, mock_server, mock_state, _shutdown) = setup().await;
+    let base_node_peer1 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let base_node_peer2 = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let conn2 = mock_server.create_mockimpl_connection(base_node_peer2.to_peer()).await;
+
+    // Only the second base node is reachable
+    mock_state.add_active_connection(conn2).await;
+    let mut events = handle.get_event_stream();
+    handle.set_base_node_peers(vec![base_node_peer1.to_peer(), base_node_peer2.to_peer()]);
+
+    let event = events.recv().await.unwrap();
+    assert_eq!(*event, WalletConnectivityEvent::ActiveBaseNodeChanged {
+        previous: Some(base_node_peer1.node_id().clone()),
+        current: base_node_peer2.node_id().clone(),
+        reason: BaseNodeChangeReason::Unreachable,
+    });
+
+    let rpc_client = handle.obtain_base_node_wallet_rpc_client().await.unwrap();
+    assert!(rpc_client.is_connected());
+    assert_eq!(
+        handle.get_current_base_node_id().as_ref(),
+        Some(base_node_peer2.node_id())
+    );
+    assert_eq!(handle.get_base_node_peers().len(), 2);
+}
+
+#[tokio::test]
+async fn it_follows_a_base_node_that_rotated_its_identity() {
+    let peer_manager = build_peer_manager();
+    let (mut handle, mock_server, mock_state, _shutdown) = setup_with_peer_manager(peer_manager.clone()).await;
+    let previous = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let successor = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    peer_manager.add_peer(previous.to_peer()).await.unwrap();
+    peer_manager.add_peer(successor.to_peer()).await.unwrap();
+    peer_manager
+        .set_peer_successor(previous.node_id(), successor.public_key())
+        .await
+        .unwrap();
+
+    // The connectivity manager answers dials to the previous identity with a connection to its successor
+    let conn = mock_server.create_mockimpl_connection(successor.to_peer()).await;
+    mock_state
+        .add_redirected_connection(previous.node_id().clone(), conn)
+        .await;
+    let mut events = handle.get_event_stream();
+    handle.set_base_node(previous.to_peer());
+
+    let event = events.recv().await.unwrap();
+    assert_eq!(*event, WalletConnectivityEvent::ActiveBaseNodeChanged {
+        previous: Some(previous.node_id().clone()),
+        current: successor.node_id().clone(),
+        reason: BaseNodeChangeReason::IdentityRotated,
+    });
+
+    let rpc_client = handle.obtain_base_node_wallet_rpc_client().await.unwrap();
+    assert!(rpc_client.is_connected());
+    assert_eq!(handle.get_current_base_node_id().as_ref(), Some(successor.node_id()));
+}
+
 #[tokio::test]
 async fn it_gracefully_handles_connect_fail_reconnect() {
     let (mut handle, mock_server, mock_state, _shutdown) = setup().await;
@@ -0,0 +1,240 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A machine readable description of how the wallet derives its keys, for third-party recovery tools.
+//!
+//! A [DerivationExport] does not contain any secret material. Together with the wallet's seed words it describes
+//! everything a recovery tool needs to rebuild the wallet's keys and recognise its outputs on chain:
+//!
+//! * `key_derivation` - every managed key is a Ristretto scalar built from the 64 uniform bytes of a Blake2b-512 MAC,
+//!   domain separated with `domain` (version `domain_version`) and `label`, over the inputs listed in `inputs`. The
+//!   entropy is the 16 byte entropy of the cipher seed the seed words decode to.
+//! * `branches` - the branch seeds in use and the highest index handed out on each, so a tool knows how far to scan.
+//!   Keys are used from index 0 (static keys) and from index 1 up to `highest_index` (all other keys).
+//! * `script_types` - the scripts the wallet's outputs are locked with and how the spending, script and recovery keys
+//!   are found for each of them.
+//!
+//! The JSON layout is versioned with `version` and only changes with a version bump.
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tari_common::configuration::Network;
+use tari_common_types::types::PublicKey;
+use tari_core::transactions::key_manager::TransactionKeyManagerBranch;
+
+use crate::config::KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY;
+
+/// The serialization version of [DerivationExport]
+pub const DERIVATION_EXPORT_VERSION: u8 = 0;
+
+/// The key derivation metadata of a wallet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationExport {
+    pub version: u8,
+    pub network: String,
+    /// The wallet birthday, in days since 2022-01-01, from which the chain needs to be scanned
+    pub birthday: u16,
+    /// The public key of the wallet, used in one-sided payment scripts
+    pub wallet_public_key: PublicKey,
+    pub key_derivation: KeyDerivationScheme,
+    pub branches: Vec<DerivationBranch>,
+    pub script_types: Vec<ScriptType>,
+}
+
+/// How a managed key is derived from the seed entropy, a branch seed and an index
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyDerivationScheme {
+    pub hasher: String,
+    pub domain: String,
+    pub domain_version: u8,
+    pub label: String,
+    pub inputs: Vec<String>,
+    pub output: String,
+}
+
+impl Default for KeyDerivationScheme {
+    fn default() -> Self {
+        Self {
+            hasher: "Blake2b-512".to_string(),
+            domain: "com.tari.base_layer.key_manager".to_string(),
+            domain_version: 1,
+            label: "derive_key".to_string(),
+            inputs: vec![
+                "cipher seed entropy (16 bytes)".to_string(),
+                "branch seed (UTF-8 bytes)".to_string(),
+                "index (u64, little endian)".to_string(),
+            ],
+            output: "Ristretto scalar from 64 uniform bytes".to_string(),
+        }
+    }
+}
+
+/// A key manager branch and the highest index used on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivationBranch {
+    pub branch_seed: String,
+    pub purpose: String,
+    pub highest_index: u64,
+}
+
+/// A script the wallet locks its outputs with
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScriptType {
+    pub name: String,
+    pub script: String,
+    pub spending_key: String,
+    pub script_key: String,
+    pub recovery: String,
+}
+
+impl DerivationExport {
+    pub fn new(
+        network: Network,
+        birthday: u16,
+        wallet_public_key: PublicKey,
+        branch_indices: Vec<(String, u64)>,
+    ) -> Self {
+        let mut branches = vec![DerivationBranch {
+            branch_seed: KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY.to_string(),
+            purpose: branch_purpose(KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY).to_string(),
+            highest_index: 0,
+        }];
+        branches.extend(
+            branch_indices
+                .into_iter()
+                .map(|(branch_seed, highest_index)| DerivationBranch {
+                    purpose: branch_purpose(&branch_seed).to_string(),
+                    branch_seed,
+                    highest_index,
+                }),
+        );
+        Self {
+            version: DERIVATION_EXPORT_VERSION,
+            network: network.to_string(),
+            birthday,
+            wallet_public_key,
+            key_derivation: KeyDerivationScheme::default(),
+            branches,
+            script_types: script_types(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+fn branch_purpose(branch_seed: &str) -> &'static str {
+    if branch_seed == KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY {
+        return "Wallet (comms) secret key at index 0, the private key of wallet_public_key";
+    }
+    let branch = TransactionKeyManagerBranch::iter().find(|b| b.get_branch_key() == branch_seed);
+    match branch {
+        Some(TransactionKeyManagerBranch::DataEncryption) => {
+            "Key at index 0 decrypts the encrypted data (value and spending key) of interactive and coinbase outputs"
+        },
+        Some(TransactionKeyManagerBranch::Coinbase) => "Spending keys of coinbase outputs",
+        Some(TransactionKeyManagerBranch::CoinbaseScript) => "Script keys of coinbase outputs",
+        Some(TransactionKeyManagerBranch::CommitmentMask) => "Spending keys (commitment masks) of received outputs",
+        Some(TransactionKeyManagerBranch::ScriptKey) => {
+            "Script keys of received outputs, at the same index as the commitment mask"
+        },
+        Some(TransactionKeyManagerBranch::FrostPolynomial) => {
+            "Secrets of multisig and FROST key generations. The signing shares of these sessions are imported keys and \
+             cannot be recovered from the seed"
+        },
+        Some(TransactionKeyManagerBranch::Subaddress) => {
+            "Subaddress keys, used in place of the wallet secret key for one-sided and stealth outputs paid to \
+             subaddress i"
+        },
+        Some(TransactionKeyManagerBranch::Nonce) |
+        Some(TransactionKeyManagerBranch::KernelNonce) |
+        Some(TransactionKeyManagerBranch::SenderOffset) => "Signing nonces and offsets, not needed for recovery",
+        None => "Wallet specific branch",
+    }
+}
+
+fn script_types() -> Vec<ScriptType> {
+    vec![
+        ScriptType {
+            name: "interactive".to_string(),
+            script: "Nop".to_string(),
+            spending_key: "commitment mask branch at index i".to_string(),
+            script_key: "script key branch at index i".to_string(),
+            recovery: "Decrypt the output's encrypted data with the data encryption key, then find i by deriving \
+                       commitment mask keys until one matches the decrypted spending key"
+                .to_string(),
+        },
+        ScriptType {
+            name: "coinbase".to_string(),
+            script: "Nop".to_string(),
+            spending_key: "coinbase branch at index i".to_string(),
+            script_key: "coinbase script branch at index i".to_string(),
+            recovery: "As for interactive outputs, searching the coinbase branch".to_string(),
+        },
+        ScriptType {
+            name: "one_sided".to_string(),
+            script: "PushPubKey(wallet_public_key)".to_string(),
+            spending_key: "Ristretto scalar from the com.tari.base_layer.wallet.output_spending_keys (v1) Blake2b-512 \
+                           hash of the Diffie-Hellman secret of the wallet secret key and the output's sender offset \
+                           public key"
+                .to_string(),
+            script_key: "wallet secret key (comms branch at index 0)".to_string(),
+            recovery: "Decrypt the output's encrypted data with the com.tari.base_layer.wallet.output_encryption_keys \
+                       (v1) key from the same Diffie-Hellman secret"
+                .to_string(),
+        },
+        ScriptType {
+            name: "stealth".to_string(),
+            script: "PushPubKey(R) Drop PushPubKey(K_S)".to_string(),
+            spending_key: "As for one-sided outputs".to_string(),
+            script_key: "c + wallet secret key, with c the Ristretto scalar from the com.tari.base_layer.wallet (v1) \
+                         Blake2b-512 hash, label stealth_address, of the Diffie-Hellman secret of the wallet secret \
+                         key and R. K_S is the matching public key."
+                .to_string(),
+            recovery: "As for one-sided outputs".to_string(),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_lists_the_comms_branch_and_describes_known_branches() {
+        let export = DerivationExport::new(Network::LocalNet, 100, PublicKey::default(), vec![
+            (TransactionKeyManagerBranch::CommitmentMask.get_branch_key(), 12),
+            ("custom".to_string(), 3),
+        ]);
+        assert_eq!(export.version, DERIVATION_EXPORT_VERSION);
+        assert_eq!(export.branches.len(), 3);
+        assert_eq!(export.branches[0].branch_seed, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY);
+        assert_eq!(export.branches[1].highest_index, 12);
+        assert!(export.branches[1].purpose.starts_with("Spending keys"));
+        assert_eq!(export.branches[2].purpose, "Wallet specific branch");
+
+        let json = export.to_json().unwrap();
+        let parsed: DerivationExport = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, export);
+    }
+}
@@ -20,6 +20,8 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{fmt, fmt::Formatter};
+
 use diesel::result::Error as DieselError;
 use log::SetLoggerError;
 use serde_json::Error as SerdeJsonError;
@@ -42,8 +44,10 @@ use thiserror::Error;
 use crate::{
     base_node_service::error::BaseNodeServiceError,
     output_manager_service::error::OutputManagerError,
+    spent_output_proof::SpentOutputProofError,
     storage::database::DbKey,
     transaction_service::error::TransactionServiceError,
+    util::reauthentication::ReauthenticationError,
     utxo_scanner_service::error::UtxoScannerError,
 };
 
@@ -85,6 +89,10 @@ pub enum WalletError {
     NodeIdError(#[from] NodeIdError),
     #[error("Error performing wallet recovery: '{0}'")]
     WalletRecoveryError(String),
+    #[error("Error initializing watch-only wallet: '{0}'")]
+    WatchOnlyInitializationError(String),
+    #[error("The seed passphrase does not match the one the wallet was created with")]
+    SeedPassphraseMismatch,
     #[error("Shutdown Signal Received")]
     Shutdown,
     #[error("Transaction Error: {0}")]
@@ -103,9 +111,44 @@ pub enum WalletError {
     UnexpectedApiResponse { method: String, api: String },
     #[error("Public address not set for this wallet")]
     PublicAddressNotSet,
+    #[error("Re-authentication error: `{0}`")]
+    ReauthenticationError(#[from] ReauthenticationError),
+    #[error("Spent output proof error: `{0}`")]
+    SpentOutputProofError(#[from] SpentOutputProofError),
+    #[error("A wallet with id `{0}` is already registered")]
+    DuplicateWalletId(String),
+    #[error("No wallet with id `{0}` is registered")]
+    UnknownWalletId(String),
+    #[error("Wallet `{id}` uses the same comms identity as wallet `{other}`")]
+    DuplicateWalletIdentity { id: String, other: String },
 }
 
 pub const LOG_TARGET: &str = "minotari::application";
+
+impl ErrorHint for WalletError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            WalletError::ArgumentError { .. } |
+            WalletError::DuplicateWalletId(_) |
+            WalletError::DuplicateWalletIdentity { .. } => ErrorCategory::InvalidRequest,
+            WalletError::UnknownWalletId(_) => ErrorCategory::NotFound,
+            WalletError::OutputManagerError(e) => e.category(),
+            WalletError::TransactionServiceError(e) => e.category(),
+            WalletError::ConnectivityError(_) | WalletError::BaseNodeServiceError(_) => ErrorCategory::Connectivity,
+            WalletError::WalletStorageError(_) => ErrorCategory::Storage,
+            WalletError::Shutdown | WalletError::TransportChannelError(_) => ErrorCategory::Shutdown,
+            _ => ErrorCategory::Internal,
+        }
+    }
+
+    fn retry_hint(&self) -> RetryHint {
+        match self {
+            WalletError::OutputManagerError(e) => e.retry_hint(),
+            WalletError::TransactionServiceError(e) => e.retry_hint(),
+            e => e.category().retry_hint(),
+        }
+    }
+}
 impl From<ByteArrayError> for WalletError {
     fn from(err: ByteArrayError) -> Self {
         Self::ByteArrayError(err.to_string())
@@ -183,6 +226,21 @@ pub enum WalletStorageError {
     RecoverySeedError(String),
     #[error("Bad encryption version: `{0}`")]
     BadEncryptionVersion(String),
+    #[error("Database key provider `{provider}` error: {details}")]
+    KeyProviderError { provider: &'static str, details: String },
+    #[error(
+        "Wallet database belongs to network `{bound}` but was opened for network `{requested}`; clone the wallet for \
+         the new network instead"
+    )]
+    NetworkMismatch { bound: String, requested: String },
+    #[error("Could not re-encrypt table `{table}`: {details}")]
+    ReencryptionError { table: &'static str, details: String },
+    #[error("Invalid database key rotation state: {0}")]
+    InvalidKeyRotationState(String),
+    #[error("An interrupted database encryption key rotation must be completed before the wallet is used")]
+    KeyRotationPending,
+    #[error("Snapshots are not supported by this wallet database, back it up with the database server's own tooling")]
+    SnapshotsNotSupported,
 }
 
 impl From<HexError> for WalletStorageError {
@@ -206,3 +264,104 @@ impl From<WalletStorageError> for ExitError {
         }
     }
 }
+
+/// A broad classification of wallet service errors, so that integrators can decide how to react to an error without
+/// matching on its message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    /// The request is invalid and will fail again unless it is changed
+    InvalidRequest,
+    /// The wallet does not hold enough spendable funds
+    InsufficientFunds,
+    /// The requested item does not exist
+    NotFound,
+    /// A peer or the base node could not be reached
+    Connectivity,
+    /// The wallet or its base node is busy or not ready yet, e.g. syncing, validating or recovering
+    NotReady,
+    /// The wallet database returned an error
+    Storage,
+    /// The service is shutting down
+    Shutdown,
+    /// An unexpected internal error
+    Internal,
+}
+
+impl ErrorCategory {
+    /// A stable numeric code for the category
+    pub fn code(self) -> u32 {
+        match self {
+            ErrorCategory::InvalidRequest => 1,
+            ErrorCategory::InsufficientFunds => 2,
+            ErrorCategory::NotFound => 3,
+            ErrorCategory::Connectivity => 4,
+            ErrorCategory::NotReady => 5,
+            ErrorCategory::Storage => 6,
+            ErrorCategory::Shutdown => 7,
+            ErrorCategory::Internal => 8,
+        }
+    }
+
+    /// Only connectivity and readiness errors are considered transient
+    pub fn retry_hint(self) -> RetryHint {
+        match self {
+            ErrorCategory::Connectivity | ErrorCategory::NotReady => RetryHint::RetryLater,
+            _ => RetryHint::DoNotRetry,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ErrorCategory::InvalidRequest => write!(f, "invalid_request"),
+            ErrorCategory::InsufficientFunds => write!(f, "insufficient_funds"),
+            ErrorCategory::NotFound => write!(f, "not_found"),
+            ErrorCategory::Connectivity => write!(f, "connectivity"),
+            ErrorCategory::NotReady => write!(f, "not_ready"),
+            ErrorCategory::Storage => write!(f, "storage"),
+            ErrorCategory::Shutdown => write!(f, "shutdown"),
+            ErrorCategory::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+/// Whether a failed request is worth retrying unchanged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryHint {
+    /// Retrying the same request will fail again
+    DoNotRetry,
+    /// The request may succeed if it is retried after a delay
+    RetryLater,
+    /// The request may succeed once the chain reaches the given height
+    RetryAtHeight(u64),
+}
+
+impl RetryHint {
+    pub fn is_retryable(&self) -> bool {
+        !matches!(self, RetryHint::DoNotRetry)
+    }
+}
+
+impl fmt::Display for RetryHint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RetryHint::DoNotRetry => write!(f, "do_not_retry"),
+            RetryHint::RetryLater => write!(f, "retry_later"),
+            RetryHint::RetryAtHeight(height) => write!(f, "retry_at_height:{}", height),
+        }
+    }
+}
+
+/// Implemented by the wallet service errors to expose their category, error code and retry hint
+pub trait ErrorHint {
+    fn category(&self) -> ErrorCategory;
+
+    fn retry_hint(&self) -> RetryHint {
+        self.category().retry_hint()
+    }
+
+    fn error_code(&self) -> u32 {
+        self.category().code()
+    }
+}
@@ -9,19 +9,25 @@
 
 #[macro_use]
 mod macros;
+pub mod backup_service;
 pub mod base_node_service;
 pub mod connectivity_service;
+pub mod derivation_export;
 pub mod error;
 mod operation_id;
 pub mod output_manager_service;
+pub mod payment_request;
 pub mod storage;
 pub mod test_utils;
+pub mod test_vectors;
 pub mod transaction_service;
 pub mod types;
 
 pub use tari_common_types::types::WalletHasher;
 pub mod util;
 pub mod wallet;
+pub mod wallet_manager;
+pub mod webhook_service;
 
 pub use operation_id::OperationId;
 
@@ -32,6 +38,7 @@ extern crate diesel_migrations;
 
 mod config;
 pub mod schema;
+pub mod spent_output_proof;
 pub mod utxo_scanner_service;
 pub use config::{TransactionStage, WalletConfig};
 use tari_contacts::contacts_service::storage::sqlite_db::ContactsServiceSqliteDatabase;
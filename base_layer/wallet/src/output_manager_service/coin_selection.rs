@@ -0,0 +1,331 @@
+//  Copyright 2022. The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{
+    cmp::Reverse,
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+};
+
+use rand::{rngs::OsRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use tari_core::transactions::{fee::Fee, tari_amount::MicroMinotari};
+
+use crate::output_manager_service::storage::models::DbWalletOutput;
+
+/// The maximum number of search steps taken by the branch-and-bound strategy before it gives up on finding a
+/// changeless selection
+const BRANCH_AND_BOUND_MAX_TRIES: usize = 100_000;
+
+/// The coin selection strategies the output manager can be configured with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoinSelectionMethod {
+    /// Accumulates inputs in the order given by the selection criteria ordering
+    #[default]
+    Default,
+    /// Spends the largest outputs first, keeping the number of inputs (and so the fee) as low as possible
+    LargestFirst,
+    /// Searches for a set of inputs that covers the amount and fee without needing a change output, falling back to
+    /// the default selection if no such set is found
+    BranchAndBound,
+    /// Accumulates inputs in a random order, so that the choice of inputs does not reveal anything about the wallet
+    Random,
+}
+
+impl CoinSelectionMethod {
+    pub fn strategy(self) -> Box<dyn CoinSelectionStrategy> {
+        match self {
+            CoinSelectionMethod::Default => Box::new(AccumulativeSelection),
+            CoinSelectionMethod::LargestFirst => Box::new(LargestFirstSelection),
+            CoinSelectionMethod::BranchAndBound => Box::new(BranchAndBoundSelection {
+                max_tries: BRANCH_AND_BOUND_MAX_TRIES,
+            }),
+            CoinSelectionMethod::Random => Box::new(RandomSelection),
+        }
+    }
+}
+
+impl Display for CoinSelectionMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CoinSelectionMethod::Default => write!(f, "default"),
+            CoinSelectionMethod::LargestFirst => write!(f, "largest_first"),
+            CoinSelectionMethod::BranchAndBound => write!(f, "branch_and_bound"),
+            CoinSelectionMethod::Random => write!(f, "random"),
+        }
+    }
+}
+
+impl FromStr for CoinSelectionMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('-', "_").as_str() {
+            "default" => Ok(CoinSelectionMethod::Default),
+            "largest_first" => Ok(CoinSelectionMethod::LargestFirst),
+            "branch_and_bound" | "bnb" => Ok(CoinSelectionMethod::BranchAndBound),
+            "random" => Ok(CoinSelectionMethod::Random),
+            _ => Err(format!("Unknown coin selection method '{}'", s)),
+        }
+    }
+}
+
+/// The amount a selection has to cover, along with what is needed to work out the fee for a given number of inputs
+pub struct SelectionTarget<'a> {
+    pub fee_calc: &'a Fee,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub num_outputs: usize,
+    pub features_and_scripts_byte_size: usize,
+    pub change_features_and_scripts_byte_size: usize,
+}
+
+impl SelectionTarget<'_> {
+    pub fn fee_without_change(&self, num_inputs: usize) -> MicroMinotari {
+        self.fee_calc.calculate(
+            self.fee_per_gram,
+            1,
+            num_inputs,
+            self.num_outputs,
+            self.features_and_scripts_byte_size,
+        )
+    }
+
+    pub fn fee_with_change(&self, num_inputs: usize) -> MicroMinotari {
+        self.fee_calc.calculate(
+            self.fee_per_gram,
+            1,
+            num_inputs,
+            self.num_outputs + 1,
+            self.features_and_scripts_byte_size + self.change_features_and_scripts_byte_size,
+        )
+    }
+}
+
+/// Chooses which of the spendable outputs are used as inputs to a transaction. A strategy may return a selection
+/// that does not cover the target if the candidates are insufficient, the caller reports the shortfall.
+pub trait CoinSelectionStrategy: Send + Sync {
+    fn select(&self, candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection;
+}
+
+/// Adds candidates in the order they are given until the amount and fee are covered
+pub struct AccumulativeSelection;
+
+impl CoinSelectionStrategy for AccumulativeSelection {
+    fn select(&self, candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        accumulate(candidates, target)
+    }
+}
+
+/// Adds the largest candidates first until the amount and fee are covered
+pub struct LargestFirstSelection;
+
+impl CoinSelectionStrategy for LargestFirstSelection {
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        candidates.sort_by_key(|o| {
+            (
+                Reverse(u32::from(o.spending_priority.clone())),
+                Reverse(o.wallet_output.value),
+            )
+        });
+        accumulate(candidates, target)
+    }
+}
+
+/// Adds candidates in a random order until the amount and fee are covered. Outputs that must be spent first are
+/// still selected before any others.
+pub struct RandomSelection;
+
+impl CoinSelectionStrategy for RandomSelection {
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        candidates.shuffle(&mut OsRng);
+        candidates.sort_by_key(|o| Reverse(u32::from(o.spending_priority.clone())));
+        accumulate(candidates, target)
+    }
+}
+
+/// Searches for a set of candidates whose value lies between the amount plus the fee without change and the amount
+/// plus the fee with change, so that no change output is created and the small excess is paid as fee. If no such set
+/// is found within `max_tries` steps, the largest-first selection is used instead.
+pub struct BranchAndBoundSelection {
+    pub max_tries: usize,
+}
+
+impl CoinSelectionStrategy for BranchAndBoundSelection {
+    fn select(&self, mut candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+        candidates.sort_by_key(|o| Reverse(o.wallet_output.value));
+        // Outputs worth less than the fee to spend them can never help reach the target. As the candidates are sorted
+        // by value these are all at the end, so the search indices still line up with the candidates.
+        let input_fee = target.fee_without_change(2) - target.fee_without_change(1);
+        let values = candidates
+            .iter()
+            .map(|o| o.wallet_output.value)
+            .filter(|v| *v > input_fee)
+            .collect::<Vec<_>>();
+        let mut remaining = vec![MicroMinotari::zero(); values.len() + 1];
+        for (i, value) in values.iter().enumerate().rev() {
+            remaining[i] = remaining[i + 1] + *value;
+        }
+
+        let mut search = BranchAndBoundSearch {
+            values: &values,
+            remaining: &remaining,
+            target,
+            tries: self.max_tries,
+            selected: Vec::new(),
+        };
+        if !search.search(0, MicroMinotari::zero()) {
+            return LargestFirstSelection.select(candidates, target);
+        }
+
+        let selected = search.selected;
+        let utxos = candidates
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| selected.contains(i))
+            .map(|(_, o)| o)
+            .collect::<Vec<_>>();
+        let total_value = utxos.iter().map(|o| o.wallet_output.value).sum::<MicroMinotari>();
+        UtxoSelection {
+            requires_change_output: false,
+            total_value,
+            // The excess over the minimum fee is too small to pay for a change output, so it is paid as fee
+            fee_without_change: total_value - target.amount,
+            fee_with_change: target.fee_with_change(utxos.len()),
+            utxos,
+        }
+    }
+}
+
+struct BranchAndBoundSearch<'a> {
+    values: &'a [MicroMinotari],
+    remaining: &'a [MicroMinotari],
+    target: &'a SelectionTarget<'a>,
+    tries: usize,
+    selected: Vec<usize>,
+}
+
+impl BranchAndBoundSearch<'_> {
+    fn search(&mut self, index: usize, total: MicroMinotari) -> bool {
+        if self.tries == 0 {
+            return false;
+        }
+        self.tries -= 1;
+
+        let num_inputs = self.selected.len();
+        if num_inputs > 0 {
+            if total >= self.target.amount + self.target.fee_without_change(num_inputs) &&
+                total <= self.target.amount + self.target.fee_with_change(num_inputs)
+            {
+                return true;
+            }
+            // Every remaining candidate is worth more than the fee to spend it, so adding inputs only overshoots
+            // further
+            if total > self.target.amount + self.target.fee_with_change(num_inputs) {
+                return false;
+            }
+        }
+        if index == self.values.len() ||
+            total + self.remaining[index] < self.target.amount + self.target.fee_without_change(num_inputs + 1)
+        {
+            return false;
+        }
+
+        self.selected.push(index);
+        if self.search(index + 1, total + self.values[index]) {
+            return true;
+        }
+        self.selected.pop();
+        self.search(index + 1, total)
+    }
+}
+
+fn accumulate(candidates: Vec<DbWalletOutput>, target: &SelectionTarget<'_>) -> UtxoSelection {
+    let mut utxos = Vec::new();
+    let mut requires_change_output = false;
+    let mut total_value = MicroMinotari::from(0);
+    let mut fee_without_change = MicroMinotari::from(0);
+    let mut fee_with_change = MicroMinotari::from(0);
+    for o in candidates {
+        total_value += o.wallet_output.value;
+        utxos.push(o);
+        // The assumption here is that the only output will be the payment output and change if required
+        fee_without_change = target.fee_without_change(utxos.len());
+        if total_value == target.amount + fee_without_change {
+            break;
+        }
+        fee_with_change = target.fee_with_change(utxos.len());
+        if total_value > target.amount + fee_with_change {
+            requires_change_output = true;
+            break;
+        }
+    }
+
+    UtxoSelection {
+        utxos,
+        requires_change_output,
+        total_value,
+        fee_without_change,
+        fee_with_change,
+    }
+}
+
+/// The inputs chosen for a transaction and the fees that apply to them
+#[derive(Debug, Clone)]
+pub struct UtxoSelection {
+    pub(crate) utxos: Vec<DbWalletOutput>,
+    pub(crate) requires_change_output: bool,
+    pub(crate) total_value: MicroMinotari,
+    pub(crate) fee_without_change: MicroMinotari,
+    pub(crate) fee_with_change: MicroMinotari,
+}
+
+impl UtxoSelection {
+    pub fn as_final_fee(&self) -> MicroMinotari {
+        if self.requires_change_output {
+            return self.fee_with_change;
+        }
+        self.fee_without_change
+    }
+
+    pub fn requires_change_output(&self) -> bool {
+        self.requires_change_output
+    }
+
+    /// Total value of the selected inputs
+    pub fn total_value(&self) -> MicroMinotari {
+        self.total_value
+    }
+
+    pub fn num_selected(&self) -> usize {
+        self.utxos.len()
+    }
+
+    pub fn into_selected(self) -> Vec<DbWalletOutput> {
+        self.utxos
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &DbWalletOutput> + '_ {
+        self.utxos.iter()
+    }
+}
@@ -20,7 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
+use tari_common::configuration::serializers;
+
+use crate::output_manager_service::coin_selection::CoinSelectionMethod;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -45,6 +50,32 @@ pub struct OutputManagerServiceConfig {
     pub autoignore_onesided_utxos: bool,
     /// The number of seconds that have to pass for the wallet to run revalidation of invalid UTXOs on startup.
     pub num_of_seconds_to_revalidate_invalid_utxos: u64,
+    /// How inputs are chosen when spending: `default`, `largest_first`, `branch_and_bound` (avoids change outputs
+    /// where possible) or `random`. This can be overridden for individual transactions.
+    pub coin_selection_method: CoinSelectionMethod,
+    /// If set to `true`, the wallet periodically joins small outputs into a single output while it has no
+    /// transactions in flight. This can also be toggled at runtime through the output manager handle.
+    pub auto_consolidation_enabled: bool,
+    /// How often the wallet checks whether its outputs need consolidating
+    #[serde(with = "serializers::seconds")]
+    pub auto_consolidation_interval: Duration,
+    /// Outputs below this value, in micro MinoTari, are counted as fragments
+    pub auto_consolidation_threshold: u64,
+    /// The number of fragments the wallet must hold before a consolidation transaction is created
+    pub auto_consolidation_min_outputs: usize,
+    /// The maximum number of fragments joined by a single consolidation transaction
+    pub auto_consolidation_max_inputs: usize,
+    /// The fee per gram, in micro MinoTari, paid by consolidation transactions
+    pub auto_consolidation_fee_per_gram: u64,
+    /// If set to `true`, the wallet periodically moves spent outputs into a compressed archive table to keep the
+    /// outputs table small. Archived outputs are kept for audits and exports, but no longer count towards historical
+    /// balances.
+    pub spent_output_archiving_enabled: bool,
+    /// How often the wallet archives its spent outputs
+    #[serde(with = "serializers::seconds")]
+    pub spent_output_archiving_interval: Duration,
+    /// The number of confirmations the spending of an output must have before the output is archived
+    pub spent_output_archiving_min_confirmations: u64,
 }
 
 impl Default for OutputManagerServiceConfig {
@@ -57,6 +88,16 @@ impl Default for OutputManagerServiceConfig {
             tx_validator_batch_size: 100,
             autoignore_onesided_utxos: false,
             num_of_seconds_to_revalidate_invalid_utxos: 60 * 60 * 24 * 3,
+            coin_selection_method: CoinSelectionMethod::Default,
+            auto_consolidation_enabled: false,
+            auto_consolidation_interval: Duration::from_secs(60 * 60 * 6),
+            auto_consolidation_threshold: 1_000_000,
+            auto_consolidation_min_outputs: 50,
+            auto_consolidation_max_inputs: 100,
+            auto_consolidation_fee_per_gram: 5,
+            spent_output_archiving_enabled: false,
+            spent_output_archiving_interval: Duration::from_secs(60 * 60 * 24),
+            spent_output_archiving_min_confirmations: 1000,
         }
     }
 }
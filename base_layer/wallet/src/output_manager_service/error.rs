@@ -26,6 +26,7 @@ use tari_common_sqlite::error::SqliteStorageError;
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    tari_amount::MicroMinotari,
     transaction_components::{EncryptedDataError, TransactionError},
     transaction_protocol::TransactionProtocolError,
     CoinbaseBuildError,
@@ -42,8 +43,9 @@ use thiserror::Error;
 
 use crate::{
     base_node_service::error::BaseNodeServiceError,
-    error::WalletStorageError,
+    error::{ErrorCategory, ErrorHint, RetryHint, WalletStorageError},
     output_manager_service::UtxoSelectionCriteria,
+    util::reauthentication::ReauthenticationError,
 };
 
 #[derive(Debug, Error)]
@@ -76,6 +78,8 @@ pub enum OutputManagerError {
     NotEnoughFunds,
     #[error("Funds are still pending. Unable to fulfil transaction right now.")]
     FundsPending,
+    #[error("Funds are time locked until block height {0}. Unable to fulfil transaction right now.")]
+    FundsTimeLocked(u64),
     #[error("Output already exists")]
     DuplicateOutput,
     #[error("Error sending a message to the public API")]
@@ -132,6 +136,17 @@ pub enum OutputManagerError {
     KeyNotFoundInKeyChain,
     #[error("No UTXOs selected as inputs for {criteria}")]
     NoUtxosSelected { criteria: UtxoSelectionCriteria },
+    #[error("This is a watch-only wallet and cannot create or sign transactions")]
+    WatchOnlyWallet,
+    #[error("Re-authentication error: `{0}`")]
+    ReauthenticationError(#[from] ReauthenticationError),
+    #[error("The selected outputs are not available to spend: {0}")]
+    SelectedOutputsUnavailable(String),
+    #[error("The selected outputs total {available}, which does not cover the amount and fee of {required}")]
+    SelectedOutputsInsufficient {
+        available: MicroMinotari,
+        required: MicroMinotari,
+    },
     #[error("Connectivity error: {source}")]
     ConnectivityError {
         #[from]
@@ -153,6 +168,51 @@ pub enum OutputManagerError {
     RangeProofError(String),
 }
 
+impl ErrorHint for OutputManagerError {
+    fn category(&self) -> ErrorCategory {
+        #[allow(clippy::enum_glob_use)]
+        use OutputManagerError::*;
+        match self {
+            InvalidArgument(_) |
+            InvalidConfig |
+            NoCommitmentsProvided |
+            NoUtxosSelected { .. } |
+            SelectedOutputsUnavailable(_) |
+            SelectedOutputsInsufficient { .. } |
+            InvalidScriptHash |
+            InvalidCovenant |
+            InvalidOutputFeatures |
+            InvalidKernelFeatures |
+            InvalidLockHeight |
+            InvalidSenderMessage |
+            InvalidMessageError(_) |
+            DuplicateOutput |
+            WatchOnlyWallet => ErrorCategory::InvalidRequest,
+            NotEnoughFunds | FundsPending | FundsTimeLocked(_) => ErrorCategory::InsufficientFunds,
+            ConnectivityError { .. } |
+            RpcError(_) |
+            DhtOutboundError(_) |
+            BaseNodeServiceError(_) |
+            BaseNodeChanged |
+            NoBaseNodeKeysProvided |
+            MaximumAttemptsExceeded => ErrorCategory::Connectivity,
+            BaseNodeNotSynced | ValidationInProgress | Cancellation => ErrorCategory::NotReady,
+            OutputManagerStorageError(e) => e.category(),
+            Shutdown | ApiSendFailed | ApiReceiveFailed | TransportChannelError(_) => ErrorCategory::Shutdown,
+            _ => ErrorCategory::Internal,
+        }
+    }
+
+    fn retry_hint(&self) -> RetryHint {
+        match self {
+            // Pending funds become spendable once the transactions they are received in are mined
+            OutputManagerError::FundsPending => RetryHint::RetryLater,
+            OutputManagerError::FundsTimeLocked(height) => RetryHint::RetryAtHeight(*height),
+            e => e.category().retry_hint(),
+        }
+    }
+}
+
 impl From<RangeProofError> for OutputManagerError {
     fn from(e: RangeProofError) -> Self {
         OutputManagerError::RangeProofError(e.to_string())
@@ -223,6 +283,22 @@ pub enum OutputManagerStorageError {
     EncryptedOpeningsError(#[from] EncryptedDataError),
 }
 
+impl ErrorHint for OutputManagerStorageError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            OutputManagerStorageError::ValueNotFound |
+            OutputManagerStorageError::ValuesNotFound |
+            OutputManagerStorageError::PendingTransactionNotFound => ErrorCategory::NotFound,
+            OutputManagerStorageError::DuplicateOutput |
+            OutputManagerStorageError::DuplicateTransaction |
+            OutputManagerStorageError::DuplicateScript |
+            OutputManagerStorageError::OutputAlreadySpent |
+            OutputManagerStorageError::OutputAlreadyEncumbered => ErrorCategory::InvalidRequest,
+            _ => ErrorCategory::Storage,
+        }
+    }
+}
+
 impl From<HexError> for OutputManagerStorageError {
     fn from(err: HexError) -> Self {
         OutputManagerStorageError::HexError(err.to_string())
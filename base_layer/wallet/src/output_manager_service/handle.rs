@@ -20,7 +20,7 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{fmt, fmt::Formatter, sync::Arc};
+use std::{collections::BTreeMap, fmt, fmt::Formatter, sync::Arc};
 
 use tari_common_types::{
     transaction::TxId,
@@ -29,6 +29,8 @@ use tari_common_types::{
 use tari_core::{
     covenants::Covenant,
     transactions::{
+        fee::FeeBreakdown,
+        key_manager::{TariKeyId, ViewKeyExport},
         tari_amount::MicroMinotari,
         transaction_components::{OutputFeatures, Transaction, TransactionOutput, WalletOutput, WalletOutputBuilder},
         transaction_protocol::{sender::TransactionSenderMessage, TransactionMetadata},
@@ -38,24 +40,59 @@ use tari_core::{
 };
 use tari_script::TariScript;
 use tari_service_framework::reply_channel::SenderService;
-use tari_utilities::hex::Hex;
+use tari_utilities::{hex::Hex, SafePassword};
 use tokio::sync::broadcast;
 use tower::Service;
 
-use crate::output_manager_service::{
-    error::OutputManagerError,
-    service::{Balance, OutputStatusesByTxId},
-    storage::{
-        database::OutputBackendQuery,
-        models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
+use crate::{
+    output_manager_service::{
+        error::OutputManagerError,
+        script_lock::TimeLock,
+        service::{
+            Balance,
+            BalanceByOrigin,
+            BalanceCutoff,
+            DustSweep,
+            HistoricalBalance,
+            MaturityScheduleEntry,
+            OutputStatusesByTxId,
+            ScriptLockedOutput,
+            TransactionPreview,
+        },
+        storage::{
+            database::OutputBackendQuery,
+            models::{
+                ArchivedOutput,
+                DbWalletOutput,
+                KnownOneSidedPaymentScript,
+                SpendingPriority,
+                Subaddress,
+                WalletAccount,
+            },
+        },
+        UtxoSelectionCriteria,
     },
-    UtxoSelectionCriteria,
+    util::reauthentication::{ReauthenticationGuard, SensitiveOperation},
 };
 
 /// API Request enum
 #[allow(clippy::large_enum_variant)]
 pub enum OutputManagerRequest {
     GetBalance,
+    GetBalanceAt(BalanceCutoff),
+    GetBalanceByOrigin(Option<u64>),
+    ArchiveSpentOutputs {
+        min_confirmations: u64,
+    },
+    GetArchivedOutputs,
+    GetMaturitySchedule,
+    CreateAccount(String),
+    GetAccounts,
+    SetReceivingAccount(u32),
+    GetAccountBalance(u32),
+    CreateSubaddress(Option<String>),
+    GetSubaddresses,
+    ExportViewKey,
     AddOutput((Box<WalletOutput>, Option<SpendingPriority>)),
     AddOutputWithTxId((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
     AddUnvalidatedOutput((TxId, Box<WalletOutput>, Option<SpendingPriority>)),
@@ -80,6 +117,9 @@ pub enum OutputManagerRequest {
         script: TariScript,
         covenant: Covenant,
         minimum_value_promise: MicroMinotari,
+        /// Already signed one-sided outputs to other wallets, paired with their sender offset keys, that are paid
+        /// alongside the interactive recipient
+        additional_outputs: Vec<(WalletOutput, TariKeyId)>,
     },
     CreatePayToSelfTransaction {
         tx_id: TxId,
@@ -94,6 +134,29 @@ pub enum OutputManagerRequest {
         fee_per_gram: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
     },
+    /// Build a single transaction with one kernel that pays the given, already signed, recipient outputs. Each
+    /// output is paired with the sender offset key it was signed with.
+    CreateBatchTransaction {
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    },
+    /// Select and encumber the inputs of a transaction that an offline wallet will build and sign. This needs no
+    /// spending keys, so a watch-only wallet can prepare transactions for the wallet it watches.
+    EncumberUnsignedTransactionInputs {
+        tx_id: TxId,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    },
+    /// Rebuild the pending transaction `tx_id` from the same inputs at a higher fee, paying the given, already signed,
+    /// recipient outputs. The original change output is kept as cancelled and replaced.
+    CreateFeeBumpTransaction {
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        fee_per_gram: MicroMinotari,
+    },
     CancelTransaction(TxId),
     GetSpentOutputs,
     GetUnspentOutputs,
@@ -103,12 +166,24 @@ pub enum OutputManagerRequest {
     RevalidateTxos,
     CreateCoinSplit((Vec<Commitment>, MicroMinotari, usize, MicroMinotari)),
     CreateCoinSplitEven((Vec<Commitment>, usize, MicroMinotari)),
+    CreateCoinSplitWithDenominations {
+        denominations: Vec<MicroMinotari>,
+        fee_per_gram: MicroMinotari,
+    },
     PreviewCoinJoin((Vec<Commitment>, MicroMinotari)),
     PreviewCoinSplitEven((Vec<Commitment>, usize, MicroMinotari)),
     CreateCoinJoin {
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
     },
+    /// Spend the unconfirmed outputs received in `parent_tx_id` at a fee that lifts the parent and child package to
+    /// `fee_per_gram`.
+    CreateChildPaysForParentTransaction {
+        parent_tx_id: TxId,
+        parent_fee: MicroMinotari,
+        parent_weight: u64,
+        fee_per_gram: MicroMinotari,
+    },
     FeeEstimate {
         amount: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
@@ -116,6 +191,17 @@ pub enum OutputManagerRequest {
         num_kernels: usize,
         num_outputs: usize,
     },
+    PreviewTransaction {
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    },
+    FeeBreakdown {
+        fee_per_gram: MicroMinotari,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+    },
 
     ScanForRecoverableOutputs(Vec<TransactionOutput>),
     ScanOutputs(Vec<TransactionOutput>),
@@ -124,12 +210,49 @@ pub enum OutputManagerRequest {
         value: MicroMinotari,
         features: Box<OutputFeatures>,
     },
+    CreateTimeLockedOutput {
+        value: MicroMinotari,
+        lock: TimeLock,
+    },
+    GetScriptLockedOutputs,
 
     ReinstateCancelledInboundTx(TxId),
+    /// Make the outputs with the given commitments the pending change of `tx_id` again, cancelling its other change,
+    /// e.g. when an earlier version of a fee bumped transaction is the one that gets mined
+    ReinstateTransactionOutputs {
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    },
     SetCoinbaseAbandoned(TxId, bool),
     CreateClaimShaAtomicSwapTransaction(HashOutput, PublicKey, MicroMinotari),
     CreateHtlcRefundTransaction(HashOutput, MicroMinotari),
     GetOutputStatusesByTxId(TxId),
+    SetAutoConsolidation(bool),
+    SetOutputsFrozen {
+        commitments: Vec<Commitment>,
+        frozen: bool,
+    },
+    ExportOutputs {
+        commitments: Vec<Commitment>,
+        passphrase: SafePassword,
+    },
+    ImportOutputs {
+        blob: Vec<u8>,
+        passphrase: SafePassword,
+    },
+    RescanRange {
+        start_height: u64,
+        end_height: u64,
+    },
+    SetOutputLabel {
+        commitment: Commitment,
+        label: Option<String>,
+        metadata: BTreeMap<String, String>,
+    },
+    SweepDust {
+        threshold: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+    },
 }
 
 impl fmt::Display for OutputManagerRequest {
@@ -139,6 +262,18 @@ impl fmt::Display for OutputManagerRequest {
         use OutputManagerRequest::*;
         match self {
             GetBalance => write!(f, "GetBalance"),
+            GetBalanceAt(cutoff) => write!(f, "GetBalanceAt({})", cutoff),
+            GetBalanceByOrigin(mined_since) => write!(f, "GetBalanceByOrigin({:?})", mined_since),
+            ArchiveSpentOutputs { min_confirmations } => write!(f, "ArchiveSpentOutputs({})", min_confirmations),
+            GetArchivedOutputs => write!(f, "GetArchivedOutputs"),
+            GetMaturitySchedule => write!(f, "GetMaturitySchedule"),
+            CreateAccount(name) => write!(f, "CreateAccount({})", name),
+            GetAccounts => write!(f, "GetAccounts"),
+            SetReceivingAccount(account) => write!(f, "SetReceivingAccount({})", account),
+            GetAccountBalance(account) => write!(f, "GetAccountBalance({})", account),
+            CreateSubaddress(label) => write!(f, "CreateSubaddress({:?})", label),
+            GetSubaddresses => write!(f, "GetSubaddresses"),
+            ExportViewKey => write!(f, "ExportViewKey"),
             AddOutput((v, _)) => write!(f, "AddOutput ({})", v.value),
             AddOutputWithTxId((t, v, _)) => write!(f, "AddOutputWithTxId ({}: {})", t, v.value),
             AddUnvalidatedOutput((t, v, _)) => {
@@ -176,6 +311,14 @@ impl fmt::Display for OutputManagerRequest {
             ),
             CreateCoinSplit(v) => write!(f, "CreateCoinSplit ({:?})", v.0),
             CreateCoinSplitEven(v) => write!(f, "CreateCoinSplitEven ({:?})", v.0),
+            CreateCoinSplitWithDenominations {
+                denominations,
+                fee_per_gram,
+            } => write!(
+                f,
+                "CreateCoinSplitWithDenominations (denominations={:?}, fee_per_gram={})",
+                denominations, fee_per_gram
+            ),
             CreateCoinJoin {
                 commitments,
                 fee_per_gram,
@@ -184,6 +327,15 @@ impl fmt::Display for OutputManagerRequest {
                 "CreateCoinJoin: commitments={:#?}, fee_per_gram={}",
                 commitments, fee_per_gram,
             ),
+            CreateChildPaysForParentTransaction {
+                parent_tx_id,
+                fee_per_gram,
+                ..
+            } => write!(
+                f,
+                "CreateChildPaysForParentTransaction(parent: {}, fee_per_gram: {})",
+                parent_tx_id, fee_per_gram
+            ),
             GetCoinbaseTransaction { .. } => write!(f, "GetCoinbaseTransaction"),
             FeeEstimate {
                 amount,
@@ -196,14 +348,74 @@ impl fmt::Display for OutputManagerRequest {
                 "FeeEstimate(amount: {}, fee_per_gram: {}, num_kernels: {}, num_outputs: {}, selection_criteria: {:?})",
                 amount, fee_per_gram, num_kernels, num_outputs, selection_criteria
             ),
+            PreviewTransaction {
+                amount,
+                fee_per_gram,
+                selection_criteria,
+            } => write!(
+                f,
+                "PreviewTransaction(amount: {}, fee_per_gram: {}, selection_criteria: {})",
+                amount, fee_per_gram, selection_criteria
+            ),
+            FeeBreakdown {
+                fee_per_gram,
+                num_kernels,
+                num_inputs,
+                num_outputs,
+            } => write!(
+                f,
+                "FeeBreakdown(fee_per_gram: {}, num_kernels: {}, num_inputs: {}, num_outputs: {})",
+                fee_per_gram, num_kernels, num_inputs, num_outputs
+            ),
             ScanForRecoverableOutputs(_) => write!(f, "ScanForRecoverableOutputs"),
             ScanOutputs(_) => write!(f, "ScanOutputs"),
             AddKnownOneSidedPaymentScript(_) => write!(f, "AddKnownOneSidedPaymentScript"),
             CreateOutputWithFeatures { value, features } => {
                 write!(f, "CreateOutputWithFeatures({}, {})", value, features,)
             },
+            CreateTimeLockedOutput { value, lock } => write!(f, "CreateTimeLockedOutput({}, {})", value, lock),
+            GetScriptLockedOutputs => write!(f, "GetScriptLockedOutputs"),
             CreatePayToSelfWithOutputs { .. } => write!(f, "CreatePayToSelfWithOutputs"),
+            CreateBatchTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+                ..
+            } => write!(
+                f,
+                "CreateBatchTransaction({}, recipients: {}, fee_per_gram: {})",
+                tx_id,
+                recipient_outputs.len(),
+                fee_per_gram
+            ),
+            EncumberUnsignedTransactionInputs {
+                tx_id,
+                amount,
+                fee_per_gram,
+                ..
+            } => write!(
+                f,
+                "EncumberUnsignedTransactionInputs({}, amount: {}, fee_per_gram: {})",
+                tx_id, amount, fee_per_gram
+            ),
+            CreateFeeBumpTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+            } => write!(
+                f,
+                "CreateFeeBumpTransaction({}, recipients: {}, fee_per_gram: {})",
+                tx_id,
+                recipient_outputs.len(),
+                fee_per_gram
+            ),
             ReinstateCancelledInboundTx(_) => write!(f, "ReinstateCancelledInboundTx"),
+            ReinstateTransactionOutputs { tx_id, commitments } => write!(
+                f,
+                "ReinstateTransactionOutputs({}, outputs: {})",
+                tx_id,
+                commitments.len()
+            ),
             SetCoinbaseAbandoned(_, _) => write!(f, "SetCoinbaseAbandoned"),
             CreateClaimShaAtomicSwapTransaction(output, pre_image, fee_per_gram) => write!(
                 f,
@@ -220,14 +432,80 @@ impl fmt::Display for OutputManagerRequest {
             ),
 
             GetOutputStatusesByTxId(t) => write!(f, "GetOutputStatusesByTxId: {}", t),
+            SetAutoConsolidation(enabled) => write!(f, "SetAutoConsolidation({})", enabled),
+            SetOutputsFrozen { commitments, frozen } => write!(
+                f,
+                "SetOutputsFrozen(commitments={}, frozen={})",
+                commitments.len(),
+                frozen
+            ),
+            ExportOutputs { commitments, .. } => write!(f, "ExportOutputs(commitments={})", commitments.len()),
+            ImportOutputs { blob, .. } => write!(f, "ImportOutputs(blob_len={})", blob.len()),
+            RescanRange {
+                start_height,
+                end_height,
+            } => write!(f, "RescanRange({}..={})", start_height, end_height),
+            SetOutputLabel {
+                commitment,
+                label,
+                metadata,
+            } => write!(
+                f,
+                "SetOutputLabel(commitment={}, label={:?}, metadata_entries={})",
+                commitment.to_hex(),
+                label,
+                metadata.len()
+            ),
+            SweepDust {
+                threshold,
+                fee_per_gram,
+            } => write!(f, "SweepDust(threshold={}, fee_per_gram={})", threshold, fee_per_gram),
         }
     }
 }
 
+impl OutputManagerRequest {
+    /// Whether servicing this request requires the wallet's spending keys. A watch-only wallet rejects these.
+    pub fn needs_spending_keys(&self) -> bool {
+        #[allow(clippy::enum_glob_use)]
+        use OutputManagerRequest::*;
+        matches!(
+            self,
+            GetRecipientTransaction(_) |
+                GetCoinbaseTransaction { .. } |
+                PrepareToSendTransaction { .. } |
+                CreatePayToSelfTransaction { .. } |
+                CreatePayToSelfWithOutputs { .. } |
+                CreateBatchTransaction { .. } |
+                CreateFeeBumpTransaction { .. } |
+                CreateCoinSplit(_) |
+                CreateCoinSplitEven(_) |
+                CreateCoinSplitWithDenominations { .. } |
+                CreateCoinJoin { .. } |
+                CreateChildPaysForParentTransaction { .. } |
+                CreateClaimShaAtomicSwapTransaction(..) |
+                CreateHtlcRefundTransaction(..) |
+                ExportOutputs { .. } |
+                SweepDust { .. }
+        )
+    }
+}
+
 /// API Reply enum
 #[derive(Debug, Clone)]
 pub enum OutputManagerResponse {
     Balance(Balance),
+    HistoricalBalance(HistoricalBalance),
+    BalanceByOrigin(BalanceByOrigin),
+    SpentOutputsArchived(usize),
+    ArchivedOutputs(Vec<ArchivedOutput>),
+    MaturitySchedule(Vec<MaturityScheduleEntry>),
+    AccountCreated(WalletAccount),
+    Accounts(Vec<WalletAccount>),
+    ReceivingAccountSet,
+    ViewKeyExported(Box<ViewKeyExport>),
+    SubaddressCreated(Subaddress),
+    Subaddresses(Vec<Subaddress>),
     OutputAdded,
     ConvertedToTransactionOutput(Box<TransactionOutput>),
     OutputMetadataSignatureUpdated,
@@ -236,6 +514,8 @@ pub enum OutputManagerResponse {
     OutputConfirmed,
     PendingTransactionConfirmed,
     PayToSelfTransaction((MicroMinotari, Transaction)),
+    BatchTransaction((MicroMinotari, Transaction)),
+    UnsignedTransactionInputs((Vec<TransactionOutput>, MicroMinotari)),
     TransactionToSend(SenderTransactionProtocol),
     TransactionCancelled,
     SpentOutputs(Vec<DbWalletOutput>),
@@ -248,16 +528,28 @@ pub enum OutputManagerResponse {
     PublicRewindKeys(Box<PublicRewindKeys>),
     RecoveryByte(u8),
     FeeEstimate(MicroMinotari),
+    TransactionPreview(Box<TransactionPreview>),
+    FeeBreakdown(FeeBreakdown),
     RewoundOutputs(Vec<RecoveredOutput>),
     ScanOutputs(Vec<RecoveredOutput>),
     AddKnownOneSidedPaymentScript,
     CreateOutputWithFeatures { output: Box<WalletOutputBuilder> },
+    TimeLockedOutputCreated { output: Box<WalletOutputBuilder> },
+    ScriptLockedOutputs(Vec<ScriptLockedOutput>),
     CreatePayToSelfWithOutputs { transaction: Box<Transaction>, tx_id: TxId },
     ReinstatedCancelledInboundTx,
+    TransactionOutputsReinstated,
     CoinbaseAbandonedSet,
     ClaimHtlcTransaction((TxId, MicroMinotari, MicroMinotari, Transaction)),
     OutputStatusesByTxId(OutputStatusesByTxId),
     CoinPreview((Vec<MicroMinotari>, MicroMinotari)),
+    AutoConsolidationSet,
+    OutputsFrozenSet,
+    OutputsExported(Vec<u8>),
+    OutputsImported(Vec<Commitment>),
+    RangeRescanned(Vec<RecoveredOutput>),
+    OutputLabelSet,
+    DustSwept(Option<Box<DustSweep>>),
 }
 
 pub type OutputManagerEventSender = broadcast::Sender<Arc<OutputManagerEvent>>;
@@ -270,6 +562,28 @@ pub enum OutputManagerEvent {
     TxoValidationInternalFailure(u64),
     TxoValidationCommunicationFailure(u64),
     TxoValidationAlreadyBusy(u64),
+    /// Outputs have been encumbered to the transaction with this TxId
+    OutputsEncumbered(TxId),
+    /// The outputs encumbered to the transaction with this TxId have been released back into the unspent pool
+    OutputsReleased(TxId),
+    /// An output was found to be invalid during validation
+    OutputInvalidated(HashOutput),
+    /// An output was imported into the wallet, optionally as part of a transaction
+    OutputImported {
+        hash: HashOutput,
+        tx_id: Option<TxId>,
+    },
+    /// A background consolidation transaction joining `num_inputs` small outputs into one has been created and must
+    /// be submitted for broadcast
+    ConsolidationTransactionCreated {
+        tx_id: TxId,
+        transaction: Box<Transaction>,
+        amount: MicroMinotari,
+        fee: MicroMinotari,
+        num_inputs: usize,
+    },
+    /// A background consolidation was due but the transaction could not be created
+    ConsolidationFailed(String),
 }
 
 impl fmt::Display for OutputManagerEvent {
@@ -287,6 +601,29 @@ impl fmt::Display for OutputManagerEvent {
             OutputManagerEvent::TxoValidationAlreadyBusy(tx) => {
                 write!(f, "Txo is already running, stopping {}", tx)
             },
+            OutputManagerEvent::OutputsEncumbered(tx_id) => {
+                write!(f, "OutputsEncumbered for {}", tx_id)
+            },
+            OutputManagerEvent::OutputsReleased(tx_id) => {
+                write!(f, "OutputsReleased for {}", tx_id)
+            },
+            OutputManagerEvent::OutputInvalidated(hash) => {
+                write!(f, "OutputInvalidated {}", hash)
+            },
+            OutputManagerEvent::OutputImported { hash, tx_id } => match tx_id {
+                Some(tx_id) => write!(f, "OutputImported {} for {}", hash, tx_id),
+                None => write!(f, "OutputImported {}", hash),
+            },
+            OutputManagerEvent::ConsolidationTransactionCreated { tx_id, num_inputs, .. } => {
+                write!(
+                    f,
+                    "ConsolidationTransactionCreated {} joining {} outputs",
+                    tx_id, num_inputs
+                )
+            },
+            OutputManagerEvent::ConsolidationFailed(reason) => {
+                write!(f, "ConsolidationFailed: {}", reason)
+            },
         }
     }
 }
@@ -300,12 +637,16 @@ pub struct PublicRewindKeys {
 pub struct RecoveredOutput {
     pub tx_id: TxId,
     pub output: WalletOutput,
+    /// The subaddress a one-sided payment was paid to, `None` for payments to the wallet address
+    pub subaddress: Option<Subaddress>,
 }
 
 #[derive(Clone)]
 pub struct OutputManagerHandle {
     handle: SenderService<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
     event_stream_sender: OutputManagerEventSender,
+    reauthentication: Option<ReauthenticationGuard>,
+    passphrase: Option<SafePassword>,
 }
 
 impl OutputManagerHandle {
@@ -316,6 +657,32 @@ impl OutputManagerHandle {
         OutputManagerHandle {
             handle,
             event_stream_sender,
+            reauthentication: None,
+            passphrase: None,
+        }
+    }
+
+    /// Enforce the given re-authentication policy on spends made through this handle and its clones
+    pub fn with_reauthentication_guard(mut self, guard: ReauthenticationGuard) -> Self {
+        self.reauthentication = Some(guard);
+        self
+    }
+
+    /// Returns a handle that supplies the wallet passphrase for spends that require re-authentication. The passphrase
+    /// is only checked when an operation requires it.
+    pub fn with_passphrase(&self, passphrase: SafePassword) -> Self {
+        let mut handle = self.clone();
+        handle.passphrase = Some(passphrase);
+        handle
+    }
+
+    async fn authorize(&self, operation: SensitiveOperation) -> Result<(), OutputManagerError> {
+        match self.reauthentication.as_ref() {
+            Some(guard) => guard
+                .authorize_async(operation, self.passphrase.clone())
+                .await
+                .map_err(Into::into),
+            None => Ok(()),
         }
     }
 
@@ -396,6 +763,33 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Prepares an output paying to this wallet whose script prevents it from being spent before `lock` expires. A
+    /// relative lock is resolved against the current chain tip. The output can be funded with
+    /// `create_pay_to_self_with_outputs`.
+    pub async fn create_time_locked_output(
+        &mut self,
+        value: MicroMinotari,
+        lock: TimeLock,
+    ) -> Result<WalletOutputBuilder, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateTimeLockedOutput { value, lock })
+            .await??
+        {
+            OutputManagerResponse::TimeLockedOutputCreated { output } => Ok(*output),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the unspent outputs locked by a script height lock or hash lock, with the height at which each becomes
+    /// claimable
+    pub async fn get_script_locked_outputs(&mut self) -> Result<Vec<ScriptLockedOutput>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetScriptLockedOutputs).await?? {
+            OutputManagerResponse::ScriptLockedOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn update_output_metadata_signature(
         &mut self,
         output: TransactionOutput,
@@ -417,6 +811,141 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Reconstructs the confirmed balance at the end of the block at `height`
+    pub async fn get_balance_at_height(&mut self, height: u64) -> Result<HistoricalBalance, OutputManagerError> {
+        self.get_balance_at(BalanceCutoff::Height(height)).await
+    }
+
+    /// Reconstructs the confirmed balance at the given unix timestamp (seconds)
+    pub async fn get_balance_at_timestamp(&mut self, timestamp: u64) -> Result<HistoricalBalance, OutputManagerError> {
+        self.get_balance_at(BalanceCutoff::Timestamp(timestamp)).await
+    }
+
+    async fn get_balance_at(&mut self, cutoff: BalanceCutoff) -> Result<HistoricalBalance, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetBalanceAt(cutoff)).await?? {
+            OutputManagerResponse::HistoricalBalance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Breaks the available balance down by where the outputs came from. If `mined_since` (unix timestamp in seconds)
+    /// is given, the breakdown instead covers everything received in blocks mined since then, whether spent or not,
+    /// e.g. the mining income for the current month.
+    pub async fn get_balance_by_origin(
+        &mut self,
+        mined_since: Option<u64>,
+    ) -> Result<BalanceByOrigin, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetBalanceByOrigin(mined_since))
+            .await??
+        {
+            OutputManagerResponse::BalanceByOrigin(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Moves outputs whose spending has at least `min_confirmations` confirmations into the spent output archive,
+    /// returning the number of outputs archived
+    pub async fn archive_spent_outputs(&mut self, min_confirmations: u64) -> Result<usize, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ArchiveSpentOutputs { min_confirmations })
+            .await??
+        {
+            OutputManagerResponse::SpentOutputsArchived(n) => Ok(n),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the records of all archived spent outputs, ordered by the height they were spent at
+    pub async fn get_archived_outputs(&mut self) -> Result<Vec<ArchivedOutput>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetArchivedOutputs).await?? {
+            OutputManagerResponse::ArchivedOutputs(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a new wallet account with the given unique name. Outputs received into the account are only ever spent
+    /// by transactions sent from that account.
+    pub async fn create_account(&mut self, name: String) -> Result<WalletAccount, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::CreateAccount(name)).await?? {
+            OutputManagerResponse::AccountCreated(account) => Ok(account),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the created wallet accounts, the default account 0 is implicit and not included
+    pub async fn get_accounts(&mut self) -> Result<Vec<WalletAccount>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetAccounts).await?? {
+            OutputManagerResponse::Accounts(accounts) => Ok(accounts),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sets the account that interactive payments are received into, 0 selects the default account
+    pub async fn set_receiving_account(&mut self, account: u32) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetReceivingAccount(account))
+            .await??
+        {
+            OutputManagerResponse::ReceivingAccountSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the balance of a single wallet account
+    pub async fn get_account_balance(&mut self, account: u32) -> Result<Balance, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::GetAccountBalance(account))
+            .await??
+        {
+            OutputManagerResponse::Balance(b) => Ok(b),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Derives a new subaddress from the wallet seed. One-sided payments to the subaddress are received by this wallet
+    /// and recorded against the subaddress index.
+    pub async fn create_subaddress(&mut self, label: Option<String>) -> Result<Subaddress, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateSubaddress(label))
+            .await??
+        {
+            OutputManagerResponse::SubaddressCreated(subaddress) => Ok(subaddress),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns all subaddresses derived so far, ordered by index
+    pub async fn get_subaddresses(&mut self) -> Result<Vec<Subaddress>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetSubaddresses).await?? {
+            OutputManagerResponse::Subaddresses(subaddresses) => Ok(subaddresses),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Exports the key material an external scanner needs to detect one-sided payments to this wallet, without the
+    /// ability to recover the wallet from it
+    pub async fn export_view_key(&mut self) -> Result<ViewKeyExport, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::ExportViewKey).await?? {
+            OutputManagerResponse::ViewKeyExported(export) => Ok(*export),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns when the currently time-locked funds (immature coinbases and outputs with a script lock height) become
+    /// spendable, ordered by height
+    pub async fn get_maturity_schedule(&mut self) -> Result<Vec<MaturityScheduleEntry>, OutputManagerError> {
+        match self.handle.call(OutputManagerRequest::GetMaturitySchedule).await?? {
+            OutputManagerResponse::MaturitySchedule(s) => Ok(s),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn revalidate_all_outputs(&mut self) -> Result<u64, OutputManagerError> {
         match self.handle.call(OutputManagerRequest::RevalidateTxos).await?? {
             OutputManagerResponse::TxoValidationStarted(request_key) => Ok(request_key),
@@ -475,6 +1004,7 @@ impl OutputManagerHandle {
         covenant: Covenant,
         minimum_value_promise: MicroMinotari,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.authorize(SensitiveOperation::Send(amount)).await?;
         match self
             .handle
             .call(OutputManagerRequest::PrepareToSendTransaction {
@@ -488,6 +1018,43 @@ impl OutputManagerHandle {
                 script,
                 covenant,
                 minimum_value_promise,
+                additional_outputs: vec![],
+            })
+            .await??
+        {
+            OutputManagerResponse::TransactionToSend(stp) => Ok(stp),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Prepare a Sender Transaction Protocol that pays `amount` to the interactive recipient and also pays the given,
+    /// already signed, one-sided outputs. The one-sided outputs are paired with the sender offset key they were signed
+    /// with and, as they belong to other wallets, are not added to our database.
+    pub async fn prepare_transaction_to_send_with_outputs(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroMinotari,
+        additional_outputs: Vec<(WalletOutput, TariKeyId)>,
+        utxo_selection: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        tx_meta: TransactionMetadata,
+        message: String,
+    ) -> Result<SenderTransactionProtocol, OutputManagerError> {
+        self.authorize(SensitiveOperation::Send(amount)).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::PrepareToSendTransaction {
+                tx_id,
+                amount,
+                selection_criteria: utxo_selection,
+                output_features: Box::new(OutputFeatures::default()),
+                fee_per_gram,
+                tx_meta,
+                message,
+                script: TariScript::default(),
+                covenant: Covenant::default(),
+                minimum_value_promise: MicroMinotari::zero(),
+                additional_outputs,
             })
             .await??
         {
@@ -522,6 +1089,52 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Runs coin selection for sending `amount` without locking any outputs, returning the inputs that would be spent,
+    /// the exact fee and the resulting change. Used to show a confirmation screen before sending.
+    pub async fn preview_transaction(
+        &mut self,
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    ) -> Result<TransactionPreview, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::PreviewTransaction {
+                amount,
+                fee_per_gram,
+                selection_criteria,
+            })
+            .await??
+        {
+            OutputManagerResponse::TransactionPreview(preview) => Ok(*preview),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Get the exact consensus weight and fee breakdown for a transaction with the given number of kernels, inputs and
+    /// outputs, assuming default output features and scripts. Unlike `fee_estimate`, no UTXOs are selected.
+    pub async fn fee_breakdown(
+        &mut self,
+        fee_per_gram: MicroMinotari,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+    ) -> Result<FeeBreakdown, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::FeeBreakdown {
+                fee_per_gram,
+                num_kernels,
+                num_inputs,
+                num_outputs,
+            })
+            .await??
+        {
+            OutputManagerResponse::FeeBreakdown(breakdown) => Ok(breakdown),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn confirm_pending_transaction(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -617,6 +1230,7 @@ impl OutputManagerHandle {
         split_count: usize,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreateCoinSplit((
@@ -632,12 +1246,35 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Create a coin split transaction that produces one output of exactly each of the given denominations from the
+    /// wallet's available outputs. Any excess is returned as change.
+    /// Returns (tx_id, tx, utxos_total_value).
+    pub async fn create_coin_split_with_denominations(
+        &mut self,
+        denominations: Vec<MicroMinotari>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateCoinSplitWithDenominations {
+                denominations,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::Transaction(ct) => Ok(ct),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_coin_split_even(
         &mut self,
         commitments: Vec<Commitment>,
         split_count: usize,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreateCoinSplitEven((
@@ -657,6 +1294,7 @@ impl OutputManagerHandle {
         commitments: Vec<Commitment>,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreateCoinJoin {
@@ -670,11 +1308,38 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Builds a child transaction spending the unconfirmed outputs of the broadcast transaction `parent_tx_id`, with a
+    /// fee high enough that the parent and child together pay `fee_per_gram`. Returns the child's tx id, the finalized
+    /// transaction and the value it returns to this wallet.
+    pub async fn create_child_pays_for_parent_transaction(
+        &mut self,
+        parent_tx_id: TxId,
+        parent_fee: MicroMinotari,
+        parent_weight: u64,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateChildPaysForParentTransaction {
+                parent_tx_id,
+                parent_fee,
+                parent_weight,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::Transaction(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_htlc_refund_transaction(
         &mut self,
         output: HashOutput,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreateHtlcRefundTransaction(output, fee_per_gram))
@@ -691,6 +1356,7 @@ impl OutputManagerHandle {
         pre_image: PublicKey,
         fee_per_gram: MicroMinotari,
     ) -> Result<(TxId, MicroMinotari, MicroMinotari, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreateClaimShaAtomicSwapTransaction(
@@ -746,6 +1412,7 @@ impl OutputManagerHandle {
         fee_per_gram: MicroMinotari,
         input_selection: UtxoSelectionCriteria,
     ) -> Result<(TxId, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreatePayToSelfWithOutputs {
@@ -760,6 +1427,81 @@ impl OutputManagerHandle {
         }
     }
 
+    /// Selects and encumbers to `tx_id` the inputs of a one-sided payment of `amount` that an offline wallet will build
+    /// and sign, returning the outputs being spent and their total value. The recipient and change outputs are both
+    /// created by the signer.
+    pub async fn encumber_unsigned_transaction_inputs(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(Vec<TransactionOutput>, MicroMinotari), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::EncumberUnsignedTransactionInputs {
+                tx_id,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::UnsignedTransactionInputs(inputs) => Ok(inputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Creates a transaction with a single kernel paying all of the given recipient outputs, returning the fee and
+    /// the finalized transaction. The selected inputs and any change output are encumbered to `tx_id`.
+    pub async fn create_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateBatchTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+                selection_criteria,
+            })
+            .await??
+        {
+            OutputManagerResponse::BatchTransaction(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replaces the pending, not yet mined, transaction `tx_id` with one that spends the same inputs to the given
+    /// recipient outputs at the new fee per gram, returning the fee and the finalized replacement transaction. The
+    /// replacement keeps `tx_id` and its new change output is encumbered in place of the original one, which is kept
+    /// as cancelled.
+    pub async fn create_fee_bump_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::CreateFeeBumpTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::BatchTransaction(result) => Ok(result),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -769,6 +1511,7 @@ impl OutputManagerHandle {
         fee_per_gram: MicroMinotari,
         lock_height: Option<u64>,
     ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(OutputManagerRequest::CreatePayToSelfTransaction {
@@ -800,6 +1543,21 @@ impl OutputManagerHandle {
         }
     }
 
+    pub async fn reinstate_transaction_outputs(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ReinstateTransactionOutputs { tx_id, commitments })
+            .await??
+        {
+            OutputManagerResponse::TransactionOutputsReinstated => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn set_coinbase_abandoned(&mut self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerError> {
         match self
             .handle
@@ -824,4 +1582,149 @@ impl OutputManagerHandle {
             _ => Err(OutputManagerError::UnexpectedApiResponse),
         }
     }
+
+    /// Enables or disables the automatic background consolidation of small outputs, overriding the configured setting
+    /// until the wallet restarts
+    pub async fn set_auto_consolidation(&mut self, enabled: bool) -> Result<(), OutputManagerError> {
+        if enabled {
+            self.authorize(SensitiveOperation::Spend).await?;
+        }
+        match self
+            .handle
+            .call(OutputManagerRequest::SetAutoConsolidation(enabled))
+            .await??
+        {
+            OutputManagerResponse::AutoConsolidationSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Freezes the given unspent outputs so that they are never selected as inputs until they are unfrozen
+    pub async fn freeze_outputs(&mut self, commitments: Vec<Commitment>) -> Result<(), OutputManagerError> {
+        self.set_outputs_frozen(commitments, true).await
+    }
+
+    /// Makes previously frozen outputs available for input selection again
+    pub async fn unfreeze_outputs(&mut self, commitments: Vec<Commitment>) -> Result<(), OutputManagerError> {
+        self.set_outputs_frozen(commitments, false).await
+    }
+
+    async fn set_outputs_frozen(
+        &mut self,
+        commitments: Vec<Commitment>,
+        frozen: bool,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputsFrozen { commitments, frozen })
+            .await??
+        {
+            OutputManagerResponse::OutputsFrozenSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Exports the given unspent outputs, including their private keys and recovery data, as a blob encrypted with
+    /// `passphrase`. The outputs remain spendable by this wallet, so they should be frozen or spent from only one of
+    /// the two wallets once imported elsewhere.
+    pub async fn export_outputs(
+        &mut self,
+        commitments: Vec<Commitment>,
+        passphrase: SafePassword,
+    ) -> Result<Vec<u8>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ExportOutputs {
+                commitments,
+                passphrase,
+            })
+            .await??
+        {
+            OutputManagerResponse::OutputsExported(blob) => Ok(blob),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Imports the outputs contained in a blob produced by `export_outputs`. The imported outputs are added as
+    /// unvalidated and only become spendable once validation has confirmed them on chain. Returns the commitments of
+    /// the imported outputs.
+    pub async fn import_outputs(
+        &mut self,
+        blob: Vec<u8>,
+        passphrase: SafePassword,
+    ) -> Result<Vec<Commitment>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::ImportOutputs { blob, passphrase })
+            .await??
+        {
+            OutputManagerResponse::OutputsImported(commitments) => Ok(commitments),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Revalidates the outputs mined or spent between `start_height` and `end_height` (inclusive) and scans the blocks
+    /// in that range for outputs belonging to this wallet that were missed. This is much cheaper than a full wallet
+    /// recovery when a payment is suspected to be missing from a known window. Returns the newly discovered outputs.
+    pub async fn rescan_range(
+        &mut self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::RescanRange {
+                start_height,
+                end_height,
+            })
+            .await??
+        {
+            OutputManagerResponse::RangeRescanned(outputs) => Ok(outputs),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Attaches a label and key/value metadata to an output, replacing any that were set before. These are returned
+    /// with the output, e.g. by `get_unspent_outputs`, so that coin control can show where funds came from.
+    pub async fn set_output_label(
+        &mut self,
+        commitment: Commitment,
+        label: Option<String>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), OutputManagerError> {
+        match self
+            .handle
+            .call(OutputManagerRequest::SetOutputLabel {
+                commitment,
+                label,
+                metadata,
+            })
+            .await??
+        {
+            OutputManagerResponse::OutputLabelSet => Ok(()),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Builds a transaction joining all spendable outputs worth less than `threshold` into a single output paying to
+    /// this wallet. Returns `None` when sweeping is not worth it, i.e. when fewer than two outputs are worth more than
+    /// the fee to spend them or the fee would consume the dust. The transaction still has to be broadcast.
+    pub async fn sweep_dust(
+        &mut self,
+        threshold: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<Option<DustSweep>, OutputManagerError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(OutputManagerRequest::SweepDust {
+                threshold,
+                fee_per_gram,
+            })
+            .await??
+        {
+            OutputManagerResponse::DustSwept(sweep) => Ok(sweep.map(|s| *s)),
+            _ => Err(OutputManagerError::UnexpectedApiResponse),
+        }
+    }
 }
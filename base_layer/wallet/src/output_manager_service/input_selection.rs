@@ -25,7 +25,9 @@ use std::{
     fmt::{Display, Formatter},
 };
 
-use tari_common_types::types::Commitment;
+use tari_common_types::{transaction::TxId, types::Commitment};
+
+use crate::output_manager_service::coin_selection::CoinSelectionMethod;
 
 #[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
 pub enum UtxoSelectionMode {
@@ -42,6 +44,11 @@ pub struct UtxoSelectionCriteria {
     pub excluding: Vec<Commitment>,
     pub min_dust: u64,
     pub excluding_onesided: bool,
+    /// Overrides the configured coin selection method for this selection
+    pub coin_selection: Option<CoinSelectionMethod>,
+    /// The wallet account to spend from, outputs of other accounts are never selected and change is returned to this
+    /// account
+    pub account: u32,
 }
 
 impl UtxoSelectionCriteria {
@@ -71,11 +78,39 @@ impl UtxoSelectionCriteria {
             ..Default::default()
         }
     }
+
+    /// Selects the outputs received in the broadcast but not yet mined transaction `parent_tx_id`, so that a child
+    /// transaction can spend them at an elevated fee and pull its parent through.
+    pub fn child_pays_for_parent(parent_tx_id: TxId) -> Self {
+        Self {
+            filter: UtxoSelectionFilter::UnconfirmedOutputsOf { tx_id: parent_tx_id },
+            ordering: UtxoSelectionOrdering::LargestFirst,
+            min_dust: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_coin_selection(mut self, method: CoinSelectionMethod) -> Self {
+        self.coin_selection = Some(method);
+        self
+    }
+
+    pub fn with_account(mut self, account: u32) -> Self {
+        self.account = account;
+        self
+    }
 }
 
 impl Display for UtxoSelectionCriteria {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "filter: {}, ordering: {}", self.filter, self.ordering)
+        write!(f, "filter: {}, ordering: {}", self.filter, self.ordering)?;
+        if let Some(method) = self.coin_selection {
+            write!(f, ", coin selection: {}", method)?;
+        }
+        if self.account != 0 {
+            write!(f, ", account: {}", self.account)?;
+        }
+        Ok(())
     }
 }
 
@@ -109,13 +144,21 @@ pub enum UtxoSelectionFilter {
     /// Select OutputType::Standard or OutputType::Coinbase outputs only
     #[default]
     Standard,
-    /// Selects specific outputs. All outputs must be exist and be spendable.
+    /// Selects specific outputs. All outputs must exist and be spendable, and are spent together without any other
+    /// outputs being added, so they must cover the amount and fee.
     SpecificOutputs { commitments: Vec<Commitment> },
+    /// Selects the unconfirmed outputs received in the given transaction. These are only ever spent by a
+    /// child-pays-for-parent transaction.
+    UnconfirmedOutputsOf { tx_id: TxId },
 }
 impl UtxoSelectionFilter {
     pub fn is_standard(&self) -> bool {
         matches!(self, UtxoSelectionFilter::Standard)
     }
+
+    pub fn is_unconfirmed(&self) -> bool {
+        matches!(self, UtxoSelectionFilter::UnconfirmedOutputsOf { .. })
+    }
 }
 
 impl Display for UtxoSelectionFilter {
@@ -127,6 +170,9 @@ impl Display for UtxoSelectionFilter {
             UtxoSelectionFilter::SpecificOutputs { commitments: outputs } => {
                 write!(f, "Specific({} output(s))", outputs.len())
             },
+            UtxoSelectionFilter::UnconfirmedOutputsOf { tx_id } => {
+                write!(f, "UnconfirmedOutputsOf({})", tx_id)
+            },
         }
     }
 }
@@ -20,15 +20,18 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+pub mod coin_selection;
 pub mod config;
 pub mod error;
 pub mod handle;
+pub mod output_export;
 
 mod input_selection;
 pub use input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionOrdering};
 
 mod recovery;
 pub mod resources;
+pub mod script_lock;
 pub mod service;
 pub mod storage;
 mod tasks;
@@ -39,7 +42,7 @@ use futures::future;
 use log::*;
 use tari_core::{
     consensus::NetworkConsensus,
-    transactions::{key_manager::TransactionKeyManagerInterface, CryptoFactories},
+    transactions::{key_manager::SecretTransactionKeyManagerInterface, CryptoFactories},
 };
 use tari_service_framework::{
     async_trait,
@@ -100,7 +103,7 @@ where T: OutputManagerBackend + 'static
 impl<T, TKeyManagerInterface> ServiceInitializer for OutputManagerServiceInitializer<T, TKeyManagerInterface>
 where
     T: OutputManagerBackend + 'static,
-    TKeyManagerInterface: TransactionKeyManagerInterface,
+    TKeyManagerInterface: SecretTransactionKeyManagerInterface,
 {
     async fn initialize(&mut self, context: ServiceInitializerContext) -> Result<(), ServiceInitializationError> {
         let (sender, receiver) = reply_channel::unbounded();
@@ -0,0 +1,111 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! The encrypted format used to move spendable outputs between wallet instances without an on-chain transaction.
+//!
+//! An export blob is laid out as `version || salt || nonce || ciphertext || tag`. The encryption key is derived from a
+//! user supplied passphrase with Argon2id, so the blob can be handed to a wallet that does not share this wallet's
+//! seed.
+
+use std::mem::size_of;
+
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
+use tari_core::transactions::transaction_components::UnblindedOutput;
+use tari_utilities::{Hidden, SafePassword};
+use zeroize::Zeroizing;
+
+use crate::output_manager_service::error::OutputManagerError;
+
+/// The current version of the export format
+pub const OUTPUT_EXPORT_VERSION: u8 = 1;
+const OUTPUT_EXPORT_DOMAIN: &[u8] = b"OUTPUT_EXPORT";
+const OUTPUT_EXPORT_SALT_LENGTH: usize = 32;
+
+/// The plaintext contents of an export blob
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutputExport {
+    pub outputs: Vec<UnblindedOutput>,
+}
+
+/// Serialize and encrypt the given outputs with a key derived from `passphrase`
+pub fn encrypt_output_export(export: &OutputExport, passphrase: &SafePassword) -> Result<Vec<u8>, OutputManagerError> {
+    let plaintext =
+        Hidden::hide(serde_json::to_vec(export).map_err(|e| OutputManagerError::ConversionError(e.to_string()))?);
+
+    let mut salt = [0u8; OUTPUT_EXPORT_SALT_LENGTH];
+    OsRng.fill_bytes(&mut salt);
+    let cipher = export_cipher(passphrase, &salt)?;
+    let ciphertext = encrypt_bytes_integral_nonce(&cipher, export_domain(OUTPUT_EXPORT_VERSION), plaintext)
+        .map_err(OutputManagerError::ServiceError)?;
+
+    let mut blob = Vec::with_capacity(1 + salt.len() + ciphertext.len());
+    blob.push(OUTPUT_EXPORT_VERSION);
+    blob.extend_from_slice(&salt);
+    blob.extend_from_slice(&ciphertext);
+    Ok(blob)
+}
+
+/// Authenticate, decrypt and deserialize an export blob produced by [encrypt_output_export]
+pub fn decrypt_output_export(blob: &[u8], passphrase: &SafePassword) -> Result<OutputExport, OutputManagerError> {
+    let (version, rest) = blob
+        .split_first()
+        .ok_or_else(|| OutputManagerError::InvalidArgument("Output export is empty".to_string()))?;
+    if *version != OUTPUT_EXPORT_VERSION {
+        return Err(OutputManagerError::InvalidArgument(format!(
+            "Unsupported output export version {}",
+            version
+        )));
+    }
+    if rest.len() < OUTPUT_EXPORT_SALT_LENGTH {
+        return Err(OutputManagerError::InvalidArgument(
+            "Output export is too short".to_string(),
+        ));
+    }
+    let (salt, ciphertext) = rest.split_at(OUTPUT_EXPORT_SALT_LENGTH);
+
+    let cipher = export_cipher(passphrase, salt)?;
+    let plaintext = Zeroizing::new(
+        decrypt_bytes_integral_nonce(&cipher, export_domain(*version), ciphertext).map_err(|_| {
+            OutputManagerError::InvalidArgument("Output export could not be decrypted with this passphrase".to_string())
+        })?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|e| OutputManagerError::ConversionError(e.to_string()))
+}
+
+fn export_domain(version: u8) -> Vec<u8> {
+    let mut domain = OUTPUT_EXPORT_DOMAIN.to_vec();
+    domain.push(version);
+    domain
+}
+
+fn export_cipher(passphrase: &SafePassword, salt: &[u8]) -> Result<XChaCha20Poly1305, OutputManagerError> {
+    // These match the parameters used to derive the wallet's own database encryption key
+    let params = argon2::Params::new(46 * 1024, 1, 1, Some(size_of::<Key>()))
+        .map_err(|e| OutputManagerError::ServiceError(e.to_string()))?;
+    let mut key = Zeroizing::new([0u8; size_of::<Key>()]);
+    argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params)
+        .hash_password_into(passphrase.reveal(), salt, key.as_mut())
+        .map_err(|e| OutputManagerError::ServiceError(e.to_string()))?;
+    Ok(XChaCha20Poly1305::new(Key::from_slice(key.as_ref())))
+}
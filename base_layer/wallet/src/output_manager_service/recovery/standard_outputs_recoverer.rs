@@ -153,6 +153,7 @@ where
             rewound_outputs_with_tx_id.push(RecoveredOutput {
                 output: output.clone(),
                 tx_id,
+                subaddress: None,
             });
             self.update_outputs_script_private_key_and_update_key_manager_index(output)
                 .await?;
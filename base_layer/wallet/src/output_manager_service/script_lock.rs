@@ -0,0 +1,134 @@
+// Copyright 2019. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Recognises and builds outputs whose TariScript encumbers them with a hash preimage and/or a block height lock.
+
+use std::fmt;
+
+use tari_common_types::types::{FixedHash, PublicKey};
+use tari_script::{script, Opcode, TariScript};
+
+/// A block height lock, either at an absolute height or a number of blocks after the current tip
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeLock {
+    Absolute(u64),
+    Relative(u64),
+}
+
+impl TimeLock {
+    /// The absolute height this lock expires at when applied at `tip_height`
+    pub fn lock_height(&self, tip_height: u64) -> u64 {
+        match self {
+            TimeLock::Absolute(height) => *height,
+            TimeLock::Relative(blocks) => tip_height.saturating_add(*blocks),
+        }
+    }
+}
+
+impl fmt::Display for TimeLock {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeLock::Absolute(height) => write!(f, "at height {}", height),
+            TimeLock::Relative(blocks) => write!(f, "{} blocks from the tip", blocks),
+        }
+    }
+}
+
+/// The spending conditions a script places on an output, beyond the usual script key signature
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptLock {
+    /// The highest height checked by the script. For a hash time-locked contract this is the height from which the
+    /// refund path can be taken.
+    pub lock_height: Option<u64>,
+    /// The hash whose preimage must be provided to take the hash-locked path of the script
+    pub hash_lock: Option<FixedHash>,
+}
+
+impl ScriptLock {
+    pub fn from_script(script: &TariScript) -> Self {
+        let opcodes = script.as_slice();
+        let lock_height = opcodes
+            .iter()
+            .filter_map(|op| match op {
+                Opcode::CheckHeightVerify(height) => Some(*height),
+                _ => None,
+            })
+            .max();
+        let hash_lock = opcodes.windows(2).find_map(|ops| match ops {
+            [Opcode::HashSha256 | Opcode::HashSha3 | Opcode::HashBlake256, Opcode::PushHash(hash)] => {
+                Some(FixedHash::from(**hash))
+            },
+            _ => None,
+        });
+        Self { lock_height, hash_lock }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.lock_height.is_some() || self.hash_lock.is_some()
+    }
+}
+
+/// A script that can only be spent by the owner of `script_public_key` once the chain has reached `lock_height`
+pub fn time_locked_script(lock_height: u64, script_public_key: PublicKey) -> TariScript {
+    script!(CheckHeightVerify(lock_height) PushPubKey(Box::new(script_public_key)))
+}
+
+/// A hash time-locked contract script. The owner of `claim_public_key` can spend the output by revealing the SHA256
+/// preimage of `hash`, otherwise the owner of `refund_public_key` can spend it from `refund_height` onwards.
+pub fn htlc_script(
+    hash: FixedHash,
+    claim_public_key: PublicKey,
+    refund_public_key: PublicKey,
+    refund_height: u64,
+) -> TariScript {
+    let hash: [u8; 32] = *hash;
+    script!(
+        HashSha256 PushHash(Box::new(hash)) Equal IfThen
+            PushPubKey(Box::new(claim_public_key))
+        Else
+            CheckHeightVerify(refund_height) PushPubKey(Box::new(refund_public_key))
+        EndIf
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    #[test]
+    fn it_recognises_htlc_scripts() {
+        let (_, claim_key) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        let (_, refund_key) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        let hash = FixedHash::from([7u8; 32]);
+
+        let lock = ScriptLock::from_script(&htlc_script(hash, claim_key.clone(), refund_key, 120));
+        assert_eq!(lock.hash_lock, Some(hash));
+        assert_eq!(lock.lock_height, Some(120));
+
+        let lock = ScriptLock::from_script(&time_locked_script(TimeLock::Relative(10).lock_height(50), claim_key));
+        assert_eq!(lock.hash_lock, None);
+        assert_eq!(lock.lock_height, Some(60));
+
+        assert!(!ScriptLock::from_script(&TariScript::default()).is_locked());
+    }
+}
@@ -20,35 +20,45 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryInto, fmt, sync::Arc};
+use std::{
+    convert::{TryFrom, TryInto},
+    fmt,
+    sync::Arc,
+};
 
+use blake2::Blake2b;
+use chrono::Utc;
 use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use digest::consts::U32;
 use futures::{pin_mut, StreamExt};
 use log::*;
 use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
     transaction::TxId,
-    types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey},
+    types::{BlockHash, Commitment, FixedHash, HashOutput, PrivateKey, PublicKey},
 };
-use tari_comms::types::CommsDHKE;
+use tari_comms::{protocol::rpc::RpcError, types::CommsDHKE};
 use tari_core::{
+    blocks::BlockHeader,
     borsh::SerializedSize,
     consensus::ConsensusConstants,
     covenants::Covenant,
     one_sided::{shared_secret_to_output_encryption_key, stealth_address_script_spending_key},
-    proto::base_node::FetchMatchingUtxos,
+    proto::base_node::{FetchMatchingUtxos, SyncUtxosByBlockRequest},
     transactions::{
-        fee::Fee,
-        key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+        fee::{Fee, FeeBreakdown},
+        key_manager::{SecretTransactionKeyManagerInterface, TariKeyId, TransactionKeyManagerBranch, ViewKeyExport},
         tari_amount::MicroMinotari,
         transaction_components::{
             EncryptedData,
             KernelFeatures,
             OutputFeatures,
+            OutputType,
             Transaction,
             TransactionError,
             TransactionOutput,
             TransactionOutputVersion,
+            UnblindedOutput,
             WalletOutput,
             WalletOutputBuilder,
         },
@@ -60,16 +70,20 @@ use tari_core::{
     },
 };
 use tari_crypto::keys::SecretKey;
-use tari_script::{inputs, script, ExecutionStack, Opcode, TariScript};
+use tari_script::{inputs, one_sided_payment_script, script, ExecutionStack, Opcode, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::ShutdownSignal;
-use tari_utilities::{hex::Hex, ByteArray};
-use tokio::sync::Mutex;
+use tari_utilities::{hex::Hex, ByteArray, SafePassword};
+use tokio::{
+    sync::Mutex,
+    time::{self, MissedTickBehavior},
+};
 
 use crate::{
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::{
+        coin_selection::{SelectionTarget, UtxoSelection},
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerProtocolError, OutputManagerStorageError},
         handle::{
@@ -79,12 +93,14 @@ use crate::{
             OutputManagerResponse,
             RecoveredOutput,
         },
-        input_selection::UtxoSelectionCriteria,
+        input_selection::{UtxoSelectionCriteria, UtxoSelectionFilter, UtxoSelectionMode},
+        output_export::{decrypt_output_export, encrypt_output_export, OutputExport},
         recovery::StandardUtxoRecoverer,
         resources::OutputManagerResources,
+        script_lock::{time_locked_script, ScriptLock, TimeLock},
         storage::{
             database::{OutputBackendQuery, OutputManagerBackend, OutputManagerDatabase},
-            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority},
+            models::{DbWalletOutput, KnownOneSidedPaymentScript, SpendingPriority, Subaddress, WalletAccount},
             OutputSource,
             OutputStatus,
         },
@@ -94,6 +110,8 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::output_manager_service";
+const MAX_OUTPUT_LABEL_LENGTH: usize = 256;
+const MAX_DUST_SWEEP_INPUTS: usize = 500;
 
 /// This service will manage a wallet's available outputs and the key manager that produces the keys for these outputs.
 /// The service will assemble transactions to be sent from the wallets available outputs and provide keys to receive
@@ -106,6 +124,7 @@ pub struct OutputManagerService<TBackend, TWalletConnectivity, TKeyManagerInterf
     base_node_service: BaseNodeServiceHandle,
     last_seen_tip_height: Option<u64>,
     validation_in_progress: Arc<Mutex<()>>,
+    auto_consolidation_enabled: bool,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -113,7 +132,7 @@ impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
 where
     TBackend: OutputManagerBackend + 'static,
     TWalletConnectivity: WalletConnectivityInterface,
-    TKeyManagerInterface: TransactionKeyManagerInterface,
+    TKeyManagerInterface: SecretTransactionKeyManagerInterface,
 {
     pub async fn new(
         config: OutputManagerServiceConfig,
@@ -131,6 +150,7 @@ where
         wallet_identity: WalletIdentity,
         key_manager: TKeyManagerInterface,
     ) -> Result<Self, OutputManagerError> {
+        let auto_consolidation_enabled = config.auto_consolidation_enabled;
         let resources = OutputManagerResources {
             config,
             db,
@@ -149,6 +169,7 @@ where
             base_node_service,
             last_seen_tip_height: None,
             validation_in_progress: Arc::new(Mutex::new(())),
+            auto_consolidation_enabled,
         })
     }
 
@@ -159,6 +180,10 @@ where
             .key_manager
             .import_key(self.resources.wallet_identity.node_identity.secret_key().clone())
             .await?;
+        // The key manager does not remember which branches are in use, so the account branches are added on startup
+        for account in self.resources.db.fetch_accounts()? {
+            self.add_account_key_branches(account.id).await?;
+        }
 
         let request_stream = self
             .request_stream
@@ -171,9 +196,19 @@ where
 
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
 
+        let mut consolidation_interval = time::interval(self.resources.config.auto_consolidation_interval);
+        consolidation_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately, the wallet is rarely idle straight after startup
+        consolidation_interval.tick().await;
+        let mut archiving_interval = time::interval(self.resources.config.spent_output_archiving_interval);
+        archiving_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        archiving_interval.tick().await;
+
         debug!(target: LOG_TARGET, "Output Manager Service started");
         loop {
             tokio::select! {
+                _ = consolidation_interval.tick() => self.run_auto_consolidation().await,
+                _ = archiving_interval.tick() => self.run_spent_output_archiving().await,
                 event = base_node_service_event_stream.recv() => {
                     match event {
                         Ok(msg) => self.handle_base_node_service_event(msg),
@@ -209,6 +244,9 @@ where
         request: OutputManagerRequest,
     ) -> Result<OutputManagerResponse, OutputManagerError> {
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if self.resources.wallet_identity.is_watch_only() && request.needs_spending_keys() {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
         match request {
             OutputManagerRequest::AddOutput((uo, spend_priority)) => self
                 .add_output(None, *uo, spend_priority)
@@ -233,6 +271,55 @@ where
                 self.get_balance(current_tip_for_time_lock_calculation)
                     .map(OutputManagerResponse::Balance)
             },
+            OutputManagerRequest::GetBalanceAt(cutoff) => Ok(OutputManagerResponse::HistoricalBalance(
+                self.resources.db.get_balance_at(cutoff)?,
+            )),
+            OutputManagerRequest::GetBalanceByOrigin(mined_since) => Ok(OutputManagerResponse::BalanceByOrigin(
+                self.resources.db.get_balance_by_origin(mined_since)?,
+            )),
+            OutputManagerRequest::ArchiveSpentOutputs { min_confirmations } => self
+                .archive_spent_outputs(min_confirmations)
+                .await
+                .map(OutputManagerResponse::SpentOutputsArchived),
+            OutputManagerRequest::GetArchivedOutputs => Ok(OutputManagerResponse::ArchivedOutputs(
+                self.resources.db.fetch_archived_outputs()?,
+            )),
+            OutputManagerRequest::GetMaturitySchedule => self
+                .get_maturity_schedule()
+                .await
+                .map(OutputManagerResponse::MaturitySchedule),
+            OutputManagerRequest::CreateAccount(name) => self
+                .create_account(name)
+                .await
+                .map(OutputManagerResponse::AccountCreated),
+            OutputManagerRequest::GetAccounts => {
+                Ok(OutputManagerResponse::Accounts(self.resources.db.fetch_accounts()?))
+            },
+            OutputManagerRequest::SetReceivingAccount(account) => self
+                .set_receiving_account(account)
+                .map(|_| OutputManagerResponse::ReceivingAccountSet),
+            OutputManagerRequest::CreateSubaddress(label) => self
+                .create_subaddress(label)
+                .await
+                .map(OutputManagerResponse::SubaddressCreated),
+            OutputManagerRequest::GetSubaddresses => Ok(OutputManagerResponse::Subaddresses(
+                self.resources.db.fetch_subaddresses()?,
+            )),
+            OutputManagerRequest::ExportViewKey => self
+                .export_view_key()
+                .await
+                .map(|export| OutputManagerResponse::ViewKeyExported(Box::new(export))),
+            OutputManagerRequest::GetAccountBalance(account) => {
+                let current_tip_for_time_lock_calculation = match self.base_node_service.get_chain_metadata().await {
+                    Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                    Err(_) => None,
+                };
+                Ok(OutputManagerResponse::Balance(
+                    self.resources
+                        .db
+                        .get_account_balance(account, current_tip_for_time_lock_calculation)?,
+                ))
+            },
             OutputManagerRequest::GetRecipientTransaction(tsm) => self
                 .get_default_recipient_transaction(tsm)
                 .await
@@ -258,6 +345,7 @@ where
                 script,
                 covenant,
                 minimum_value_promise,
+                additional_outputs,
             } => self
                 .prepare_transaction_to_send(
                     tx_id,
@@ -270,6 +358,7 @@ where
                     script,
                     covenant,
                     minimum_value_promise,
+                    additional_outputs,
                 )
                 .await
                 .map(OutputManagerResponse::TransactionToSend),
@@ -301,6 +390,22 @@ where
                 .fee_estimate(amount, selection_criteria, fee_per_gram, num_kernels, num_outputs)
                 .await
                 .map(OutputManagerResponse::FeeEstimate),
+            OutputManagerRequest::PreviewTransaction {
+                amount,
+                fee_per_gram,
+                selection_criteria,
+            } => self
+                .preview_transaction(amount, fee_per_gram, selection_criteria)
+                .await
+                .map(|preview| OutputManagerResponse::TransactionPreview(Box::new(preview))),
+            OutputManagerRequest::FeeBreakdown {
+                fee_per_gram,
+                num_kernels,
+                num_inputs,
+                num_outputs,
+            } => self
+                .fee_breakdown(fee_per_gram, num_kernels, num_inputs, num_outputs)
+                .map(OutputManagerResponse::FeeBreakdown),
             OutputManagerRequest::ConfirmPendingTransaction(tx_id) => self
                 .confirm_encumberance(tx_id)
                 .map(|_| OutputManagerResponse::PendingTransactionConfirmed),
@@ -357,6 +462,13 @@ where
                     .map(OutputManagerResponse::Transaction)
                 }
             },
+            OutputManagerRequest::CreateCoinSplitWithDenominations {
+                denominations,
+                fee_per_gram,
+            } => self
+                .create_coin_split_with_denominations_auto(denominations, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::Transaction),
             OutputManagerRequest::CreateCoinSplitEven((commitments, split_count, fee_per_gram)) => {
                 if commitments.is_empty() {
                     self.create_coin_split_auto(None, split_count, fee_per_gram)
@@ -376,6 +488,16 @@ where
                 .await
                 .map(OutputManagerResponse::Transaction),
 
+            OutputManagerRequest::CreateChildPaysForParentTransaction {
+                parent_tx_id,
+                parent_fee,
+                parent_weight,
+                fee_per_gram,
+            } => self
+                .create_child_pays_for_parent_transaction(parent_tx_id, parent_fee, parent_weight, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::Transaction),
+
             OutputManagerRequest::ScanForRecoverableOutputs(outputs) => {
                 StandardUtxoRecoverer::new(self.resources.key_manager.clone(), self.resources.db.clone())
                     .scan_and_recover_outputs(outputs)
@@ -392,12 +514,24 @@ where
             OutputManagerRequest::ReinstateCancelledInboundTx(tx_id) => self
                 .reinstate_cancelled_inbound_transaction_outputs(tx_id)
                 .map(|_| OutputManagerResponse::ReinstatedCancelledInboundTx),
+            OutputManagerRequest::ReinstateTransactionOutputs { tx_id, commitments } => self
+                .reinstate_transaction_outputs(tx_id, commitments)
+                .map(|_| OutputManagerResponse::TransactionOutputsReinstated),
             OutputManagerRequest::CreateOutputWithFeatures { value, features } => {
                 let wallet_output = self.create_output_with_features(value, *features).await?;
                 Ok(OutputManagerResponse::CreateOutputWithFeatures {
                     output: Box::new(wallet_output),
                 })
             },
+            OutputManagerRequest::CreateTimeLockedOutput { value, lock } => {
+                let wallet_output = self.create_time_locked_output(value, lock).await?;
+                Ok(OutputManagerResponse::TimeLockedOutputCreated {
+                    output: Box::new(wallet_output),
+                })
+            },
+            OutputManagerRequest::GetScriptLockedOutputs => Ok(OutputManagerResponse::ScriptLockedOutputs(
+                self.resources.db.get_script_locked_outputs()?,
+            )),
             OutputManagerRequest::CreatePayToSelfWithOutputs {
                 outputs,
                 fee_per_gram,
@@ -411,6 +545,32 @@ where
                     tx_id,
                 })
             },
+            OutputManagerRequest::CreateBatchTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+                selection_criteria,
+            } => self
+                .create_batch_transaction(tx_id, recipient_outputs, selection_criteria, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::BatchTransaction),
+            OutputManagerRequest::EncumberUnsignedTransactionInputs {
+                tx_id,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+            } => self
+                .encumber_unsigned_transaction_inputs(tx_id, amount, selection_criteria, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::UnsignedTransactionInputs),
+            OutputManagerRequest::CreateFeeBumpTransaction {
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+            } => self
+                .create_fee_bump_transaction(tx_id, recipient_outputs, fee_per_gram)
+                .await
+                .map(OutputManagerResponse::BatchTransaction),
             OutputManagerRequest::SetCoinbaseAbandoned(tx_id, abandoned) => self
                 .set_coinbase_abandoned(tx_id, abandoned)
                 .map(|_| OutputManagerResponse::CoinbaseAbandonedSet),
@@ -426,6 +586,67 @@ where
                 let output_statuses_by_tx_id = self.get_output_status_by_tx_id(tx_id)?;
                 Ok(OutputManagerResponse::OutputStatusesByTxId(output_statuses_by_tx_id))
             },
+            OutputManagerRequest::SetAutoConsolidation(enabled) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Automatic consolidation {}",
+                    if enabled { "enabled" } else { "disabled" }
+                );
+                self.auto_consolidation_enabled = enabled;
+                Ok(OutputManagerResponse::AutoConsolidationSet)
+            },
+            OutputManagerRequest::SetOutputsFrozen { commitments, frozen } => {
+                if commitments.is_empty() {
+                    return Err(OutputManagerError::NoCommitmentsProvided);
+                }
+                self.resources.db.set_outputs_frozen(&commitments, frozen)?;
+                info!(
+                    target: LOG_TARGET,
+                    "{} {} output(s)",
+                    if frozen { "Froze" } else { "Unfroze" },
+                    commitments.len()
+                );
+                Ok(OutputManagerResponse::OutputsFrozenSet)
+            },
+            OutputManagerRequest::ExportOutputs {
+                commitments,
+                passphrase,
+            } => self
+                .export_outputs(commitments, &passphrase)
+                .await
+                .map(OutputManagerResponse::OutputsExported),
+            OutputManagerRequest::ImportOutputs { blob, passphrase } => self
+                .import_outputs(&blob, &passphrase)
+                .await
+                .map(OutputManagerResponse::OutputsImported),
+            OutputManagerRequest::RescanRange {
+                start_height,
+                end_height,
+            } => self
+                .rescan_range(start_height, end_height)
+                .await
+                .map(OutputManagerResponse::RangeRescanned),
+            OutputManagerRequest::SetOutputLabel {
+                commitment,
+                label,
+                metadata,
+            } => {
+                if label.as_ref().map_or(0, |l| l.len()) > MAX_OUTPUT_LABEL_LENGTH {
+                    return Err(OutputManagerError::InvalidArgument(format!(
+                        "Output labels may not be longer than {} bytes",
+                        MAX_OUTPUT_LABEL_LENGTH
+                    )));
+                }
+                self.resources.db.set_output_label(&commitment, label, &metadata)?;
+                Ok(OutputManagerResponse::OutputLabelSet)
+            },
+            OutputManagerRequest::SweepDust {
+                threshold,
+                fee_per_gram,
+            } => self
+                .sweep_dust(threshold, fee_per_gram)
+                .await
+                .map(|sweep| OutputManagerResponse::DustSwept(sweep.map(Box::new))),
         }
     }
 
@@ -483,6 +704,20 @@ where
                     e
                 });
             },
+            BaseNodeEvent::Reorged { depth, old_tip, .. } => {
+                // The validation started by the NewBlockDetected event that follows will look these up again
+                let fork_height = old_tip.height.saturating_sub(depth);
+                if let Err(e) = self
+                    .resources
+                    .db
+                    .set_outputs_in_height_range_to_be_revalidated(fork_height + 1, old_tip.height)
+                {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not reset outputs affected by the reorg at height {}: {}", fork_height, e
+                    );
+                }
+            },
         }
     }
 
@@ -590,6 +825,75 @@ where
         self.validate_outputs()
     }
 
+    /// Revalidates the outputs mined or spent in the given height range and scans the blocks in that range for
+    /// outputs belonging to this wallet
+    async fn rescan_range(
+        &mut self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
+        if start_height > end_height {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "Rescan start height {} is above the end height {}",
+                start_height, end_height
+            )));
+        }
+        self.resources
+            .db
+            .set_outputs_in_height_range_to_be_revalidated(start_height, end_height)?;
+
+        let mut client = self
+            .resources
+            .connectivity
+            .obtain_base_node_wallet_rpc_client()
+            .await
+            .ok_or_else(|| {
+                OutputManagerError::InvalidResponseError("Could not connect to base node rpc client".to_string())
+            })?;
+        let start_header = BlockHeader::try_from(client.get_header_by_height(start_height).await?)
+            .map_err(OutputManagerError::ConversionError)?;
+        let end_header = BlockHeader::try_from(client.get_header_by_height(end_height).await?)
+            .map_err(OutputManagerError::ConversionError)?;
+        let request = SyncUtxosByBlockRequest {
+            start_header_hash: start_header.hash().to_vec(),
+            end_header_hash: end_header.hash().to_vec(),
+        };
+        let mut utxo_stream = client.sync_utxos_by_block(request).await?;
+
+        let mut recovered_outputs = Vec::new();
+        let mut num_scanned = 0usize;
+        while let Some(response) = utxo_stream.next().await {
+            let outputs = response
+                .map_err(RpcError::from)?
+                .outputs
+                .into_iter()
+                .map(TransactionOutput::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(OutputManagerError::ConversionError)?;
+            num_scanned += outputs.len();
+
+            if !self.resources.wallet_identity.is_watch_only() {
+                recovered_outputs.append(
+                    &mut StandardUtxoRecoverer::new(self.resources.key_manager.clone(), self.resources.db.clone())
+                        .scan_and_recover_outputs(outputs.clone())
+                        .await?,
+                );
+            }
+            recovered_outputs.append(&mut self.scan_outputs_for_one_sided_payments(outputs).await?);
+        }
+        info!(
+            target: LOG_TARGET,
+            "Rescanned blocks {} to {}: found {} new output(s) in {} scanned",
+            start_height,
+            end_height,
+            recovered_outputs.len(),
+            num_scanned
+        );
+
+        self.validate_outputs()?;
+        Ok(recovered_outputs)
+    }
+
     /// Add a key manager recoverable output to the outputs table and mark it as `Unspent`.
     pub async fn add_output(
         &mut self,
@@ -616,10 +920,12 @@ where
             "saving output of hash {} to Output Manager",
             output.hash.to_hex()
         );
+        let hash = output.hash;
         match tx_id {
             None => self.resources.db.add_unspent_output(output)?,
             Some(t) => self.resources.db.add_unspent_output_with_tx_id(t, output)?,
         }
+        self.publish_event(OutputManagerEvent::OutputImported { hash, tx_id });
         Ok(())
     }
 
@@ -651,6 +957,88 @@ where
         Ok(())
     }
 
+    /// Exports the given unspent outputs together with their private keys as a blob encrypted with `passphrase`
+    async fn export_outputs(
+        &mut self,
+        commitments: Vec<Commitment>,
+        passphrase: &SafePassword,
+    ) -> Result<Vec<u8>, OutputManagerError> {
+        if commitments.is_empty() {
+            return Err(OutputManagerError::NoCommitmentsProvided);
+        }
+        let mut outputs = Vec::with_capacity(commitments.len());
+        let mut unavailable = Vec::new();
+        for commitment in commitments {
+            let output = self.resources.db.fetch_by_commitment(commitment.clone())?;
+            if matches!(
+                output.status,
+                OutputStatus::Unspent | OutputStatus::UnspentMinedUnconfirmed
+            ) {
+                outputs.push(output);
+            } else {
+                unavailable.push(commitment.to_hex());
+            }
+        }
+        if !unavailable.is_empty() {
+            return Err(OutputManagerError::SelectedOutputsUnavailable(unavailable.join(", ")));
+        }
+
+        let mut export = OutputExport {
+            outputs: Vec::with_capacity(outputs.len()),
+        };
+        for output in outputs {
+            export
+                .outputs
+                .push(UnblindedOutput::from_wallet_output(output.wallet_output, &self.resources.key_manager).await?);
+        }
+        let blob = encrypt_output_export(&export, passphrase)?;
+        info!(
+            target: LOG_TARGET,
+            "Exported {} output(s) in an encrypted blob of {} bytes",
+            export.outputs.len(),
+            blob.len()
+        );
+        Ok(blob)
+    }
+
+    /// Imports the outputs in an export blob as unvalidated outputs and triggers their validation
+    async fn import_outputs(
+        &mut self,
+        blob: &[u8],
+        passphrase: &SafePassword,
+    ) -> Result<Vec<Commitment>, OutputManagerError> {
+        let export = decrypt_output_export(blob, passphrase)?;
+        if export.outputs.is_empty() {
+            return Err(OutputManagerError::NoCommitmentsProvided);
+        }
+
+        let mut commitments = Vec::with_capacity(export.outputs.len());
+        for unblinded_output in export.outputs {
+            let wallet_output = unblinded_output.to_wallet_output(&self.resources.key_manager).await?;
+            let tx_id = TxId::new_random();
+            let output = DbWalletOutput::from_wallet_output(
+                wallet_output,
+                &self.resources.key_manager,
+                None,
+                OutputSource::default(),
+                Some(tx_id),
+                None,
+            )
+            .await?;
+            let commitment = output.commitment.clone();
+            self.resources.db.add_unvalidated_output(tx_id, output)?;
+            commitments.push(commitment);
+        }
+        info!(
+            target: LOG_TARGET,
+            "Imported {} output(s) from an output export",
+            commitments.len()
+        );
+
+        self.validate_outputs()?;
+        Ok(commitments)
+    }
+
     /// Update an output's metadata signature, akin to 'finalize output'
     pub fn update_output_metadata_signature(&mut self, output: TransactionOutput) -> Result<(), OutputManagerError> {
         self.resources.db.update_output_metadata_signature(output)?;
@@ -674,12 +1062,163 @@ where
             .with_script_key(script_key_id))
     }
 
+    async fn get_maturity_schedule(&mut self) -> Result<Vec<MaturityScheduleEntry>, OutputManagerError> {
+        let tip = self.current_tip_height().await?;
+        Ok(self.resources.db.get_maturity_schedule(tip)?)
+    }
+
+    async fn current_tip_height(&mut self) -> Result<u64, OutputManagerError> {
+        match self.base_node_service.get_chain_metadata().await? {
+            Some(metadata) => Ok(metadata.height_of_longest_chain()),
+            None => self.last_seen_tip_height.ok_or(OutputManagerError::BaseNodeNotSynced),
+        }
+    }
+
+    /// Prepares an output paying to this wallet that cannot be spent before the given time lock expires
+    async fn create_time_locked_output(
+        &mut self,
+        value: MicroMinotari,
+        lock: TimeLock,
+    ) -> Result<WalletOutputBuilder, OutputManagerError> {
+        let lock_height = match lock {
+            TimeLock::Absolute(height) => height,
+            TimeLock::Relative(_) => lock.lock_height(self.current_tip_height().await?),
+        };
+        let (spending_key_id, _spending_key_id, script_key_id, script_public_key) =
+            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+
+        Ok(WalletOutputBuilder::new(value, spending_key_id)
+            .with_script(time_locked_script(lock_height, script_public_key))
+            .with_input_data(ExecutionStack::default())
+            .with_script_key(script_key_id))
+    }
+
+    /// Returns the first height at which enough currently time-locked funds have matured to cover `shortfall`, if any
+    fn height_when_funds_mature(&self, tip: u64, shortfall: MicroMinotari) -> Result<Option<u64>, OutputManagerError> {
+        let mut matured = MicroMinotari::zero();
+        for entry in self.resources.db.get_maturity_schedule(tip)? {
+            matured += entry.amount;
+            if matured >= shortfall {
+                return Ok(Some(entry.spendable_at_height));
+            }
+        }
+        Ok(None)
+    }
+
     fn get_balance(&self, current_tip_for_time_lock_calculation: Option<u64>) -> Result<Balance, OutputManagerError> {
         let balance = self.resources.db.get_balance(current_tip_for_time_lock_calculation)?;
         trace!(target: LOG_TARGET, "Balance: {:?}", balance);
         Ok(balance)
     }
 
+    async fn create_account(&mut self, name: String) -> Result<WalletAccount, OutputManagerError> {
+        let name = name.trim().to_string();
+        if name.is_empty() {
+            return Err(OutputManagerError::InvalidArgument(
+                "Account name cannot be empty".to_string(),
+            ));
+        }
+        if self.resources.db.fetch_accounts()?.iter().any(|a| a.name == name) {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "An account named '{}' already exists",
+                name
+            )));
+        }
+        let account = self.resources.db.create_account(name)?;
+        self.add_account_key_branches(account.id).await?;
+        info!(target: LOG_TARGET, "Created wallet account {} ({})", account.id, account.name);
+        Ok(account)
+    }
+
+    fn set_receiving_account(&mut self, account: u32) -> Result<(), OutputManagerError> {
+        if account != 0 && !self.resources.db.fetch_accounts()?.iter().any(|a| a.id == account) {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "Account {} does not exist",
+                account
+            )));
+        }
+        self.resources.db.set_receiving_account(account)?;
+        Ok(())
+    }
+
+    /// Derives the next subaddress from the wallet seed. Its one-sided payment script is stored with the known scripts,
+    /// so payments to the subaddress are found by the same scan as payments to the wallet address.
+    async fn create_subaddress(&mut self, label: Option<String>) -> Result<Subaddress, OutputManagerError> {
+        if self.resources.wallet_identity.is_watch_only() {
+            return Err(OutputManagerError::WatchOnlyWallet);
+        }
+        if label.as_ref().map_or(0, |l| l.len()) > MAX_OUTPUT_LABEL_LENGTH {
+            return Err(OutputManagerError::InvalidArgument(format!(
+                "Subaddress labels are limited to {} bytes",
+                MAX_OUTPUT_LABEL_LENGTH
+            )));
+        }
+        let (script_key_id, public_key) = self
+            .resources
+            .key_manager
+            .get_next_key(TransactionKeyManagerBranch::Subaddress.get_branch_key())
+            .await?;
+        let index = match &script_key_id {
+            TariKeyId::Managed { index, .. } => *index,
+            _ => {
+                return Err(OutputManagerError::ServiceError(
+                    "Subaddress key is not a managed key".to_string(),
+                ))
+            },
+        };
+
+        let script = one_sided_payment_script(&public_key);
+        self.add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script
+                .as_hash::<Blake2b<U32>>()
+                .map_err(OutputManagerError::ScriptError)?
+                .to_vec(),
+            script_key_id,
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })?;
+
+        let subaddress = Subaddress {
+            index,
+            public_key,
+            label,
+            created_at: Utc::now().naive_utc(),
+        };
+        self.resources.db.add_subaddress(subaddress.clone())?;
+        info!(target: LOG_TARGET, "Created subaddress {}", subaddress.index);
+        Ok(subaddress)
+    }
+
+    async fn export_view_key(&self) -> Result<ViewKeyExport, OutputManagerError> {
+        let (view_key_id, spend_public_key) = self.resources.wallet_identity.one_sided_scanning_keys();
+        let script_key_ids = self
+            .resources
+            .db
+            .get_all_known_one_sided_payment_scripts()?
+            .into_iter()
+            .map(|s| s.script_key_id)
+            .collect::<Vec<_>>();
+        Ok(self
+            .resources
+            .key_manager
+            .export_view_key(&view_key_id, &spend_public_key, &script_key_ids)
+            .await?)
+    }
+
+    async fn add_account_key_branches(&self, account: u32) -> Result<(), OutputManagerError> {
+        for branch in [
+            TransactionKeyManagerBranch::CommitmentMask,
+            TransactionKeyManagerBranch::ScriptKey,
+        ] {
+            self.resources
+                .key_manager
+                .add_new_branch(branch.get_account_branch_key(account))
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Request a receiver transaction be generated from the supplied Sender Message
     async fn get_default_recipient_transaction(
         &mut self,
@@ -710,8 +1249,12 @@ where
             return Err(OutputManagerError::InvalidKernelFeatures);
         }
 
-        let (spending_key_id, _, script_key_id, script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+        let account = self.resources.db.get_receiving_account()?;
+        let (spending_key_id, _, script_key_id, script_public_key) = self
+            .resources
+            .key_manager
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await?;
 
         // Confirm script hash is for the expected script, at the moment assuming Nop or Push_pubkey
         // if the script is Push_pubkey(default_key) we know we have to fill it in.
@@ -841,7 +1384,11 @@ where
             .await
         {
             Ok(v) => Ok(v),
-            Err(OutputManagerError::FundsPending | OutputManagerError::NotEnoughFunds) => {
+            Err(
+                OutputManagerError::FundsPending |
+                OutputManagerError::FundsTimeLocked(_) |
+                OutputManagerError::NotEnoughFunds,
+            ) => {
                 debug!(
                     target: LOG_TARGET,
                     "We dont have enough funds available to make a fee estimate, so we estimate 1 input, no change"
@@ -874,8 +1421,86 @@ where
         Ok(fee)
     }
 
+    /// Runs coin selection for sending `amount` to a single recipient without encumbering any outputs, so the caller
+    /// can show the inputs, fee and change before committing to the transaction. As with `fee_estimate`, default
+    /// output features and script are assumed for the recipient output.
+    async fn preview_transaction(
+        &mut self,
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+    ) -> Result<TransactionPreview, OutputManagerError> {
+        let features_and_scripts_byte_size = self
+            .resources
+            .consensus_constants
+            .transaction_weight_params()
+            .round_up_features_and_scripts_size(
+                OutputFeatures::default()
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    TariScript::default()
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                    Covenant::new()
+                        .get_serialized_size()
+                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            );
+        let selection = self
+            .select_utxos(
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                1,
+                features_and_scripts_byte_size,
+            )
+            .await?;
+
+        let fee = selection.as_final_fee();
+        let change = if selection.requires_change_output() {
+            selection.total_value() - amount - fee
+        } else {
+            MicroMinotari::zero()
+        };
+        Ok(TransactionPreview {
+            total_input_value: selection.total_value(),
+            inputs: selection.into_selected(),
+            fee,
+            change,
+        })
+    }
+
+    /// Get the consensus weight and fee breakdown for the given number of kernels, inputs and outputs. We assume that
+    /// default OutputFeatures and PushPubKey TariScript is used for every output.
+    fn fee_breakdown(
+        &self,
+        fee_per_gram: MicroMinotari,
+        num_kernels: usize,
+        num_inputs: usize,
+        num_outputs: usize,
+    ) -> Result<FeeBreakdown, OutputManagerError> {
+        let fee_calc = self.get_fee_calc();
+        let features_and_scripts_byte_size = fee_calc.weighting().round_up_features_and_scripts_size(
+            OutputFeatures::default()
+                .get_serialized_size()
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                TariScript::default()
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                Covenant::new()
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+        );
+        Ok(fee_calc.breakdown(
+            fee_per_gram,
+            num_kernels,
+            num_inputs,
+            num_outputs,
+            features_and_scripts_byte_size * num_outputs,
+        ))
+    }
+
     /// Prepare a Sender Transaction Protocol for the amount and fee_per_gram specified. If required a change output
-    /// will be produced.
+    /// will be produced. Any additional, already signed, outputs to other wallets are included in the same transaction.
     #[allow(clippy::too_many_lines)]
     pub async fn prepare_transaction_to_send(
         &mut self,
@@ -889,36 +1514,45 @@ where
         recipient_script: TariScript,
         recipient_covenant: Covenant,
         recipient_minimum_value_promise: MicroMinotari,
+        additional_outputs: Vec<(WalletOutput, TariKeyId)>,
     ) -> Result<SenderTransactionProtocol, OutputManagerError> {
         debug!(
             target: LOG_TARGET,
-            "Preparing to send transaction. Amount: {}. UTXO Selection: {}. Fee per gram: {}. ",
+            "Preparing to send transaction. Amount: {}. Additional outputs: {}. UTXO Selection: {}. Fee per gram: {}. ",
             amount,
+            additional_outputs.len(),
             selection_criteria,
             fee_per_gram,
         );
-        let features_and_scripts_byte_size = self
-            .resources
-            .consensus_constants
-            .transaction_weight_params()
-            .round_up_features_and_scripts_size(
-                recipient_output_features
+        let weighting = self.resources.consensus_constants.transaction_weight_params();
+        let mut features_and_scripts_byte_size = weighting.round_up_features_and_scripts_size(
+            recipient_output_features
+                .get_serialized_size()
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                recipient_script
                     .get_serialized_size()
                     .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
-                    recipient_script
-                        .get_serialized_size()
-                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
-                    recipient_covenant
-                        .get_serialized_size()
-                        .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+                recipient_covenant
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+        );
+        let mut total_value = amount;
+        for (output, _) in &additional_outputs {
+            total_value += output.value;
+            features_and_scripts_byte_size += weighting.round_up_features_and_scripts_size(
+                output
+                    .features_and_scripts_byte_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
             );
+        }
 
+        let account = selection_criteria.account;
         let input_selection = self
             .select_utxos(
-                amount,
+                total_value,
                 selection_criteria,
                 fee_per_gram,
-                1,
+                1 + additional_outputs.len(),
                 features_and_scripts_byte_size,
             )
             .await?;
@@ -946,6 +1580,12 @@ where
         for uo in input_selection.iter() {
             builder.with_input(uo.wallet_output.clone()).await?;
         }
+        for (output, sender_offset_key_id) in additional_outputs {
+            builder
+                .with_output(output, sender_offset_key_id)
+                .await
+                .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
+        }
         debug!(
             target: LOG_TARGET,
             "Calculating fee for tx with: Fee per gram: {}. Num selected inputs: {}",
@@ -953,8 +1593,11 @@ where
             input_selection.num_selected()
         );
 
-        let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+        let (change_spending_key_id, _, change_script_key_id, change_script_public_key) = self
+            .resources
+            .key_manager
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await?;
         builder.with_change_data(
             script!(PushPubKey(Box::new(change_script_public_key.clone()))),
             ExecutionStack::default(),
@@ -981,7 +1624,7 @@ where
                     wallet_output,
                     &self.resources.key_manager,
                     None,
-                    OutputSource::default(),
+                    OutputSource::Change,
                     Some(tx_id),
                     None,
                 )
@@ -991,9 +1634,7 @@ where
 
         // The Transaction Protocol built successfully so we will pull the unspent outputs out of the unspent list and
         // store them until the transaction times out OR is confirmed
-        self.resources
-            .db
-            .encumber_outputs(tx_id, input_selection.into_selected(), change_output)?;
+        self.encumber_outputs(tx_id, input_selection.into_selected(), change_output)?;
 
         debug!(target: LOG_TARGET, "Prepared transaction (TxId: {}) to send", tx_id);
 
@@ -1095,6 +1736,7 @@ where
             features_and_scripts_byte_size += weighting.round_up_features_and_scripts_size(features + covenant + script)
         }
 
+        let account = selection_criteria.account;
         let input_selection = self
             .select_utxos(
                 total_value,
@@ -1121,8 +1763,11 @@ where
         }
 
         if input_selection.requires_change_output() {
-            let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
-                self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+            let (change_spending_key_id, _, change_script_key_id, change_script_public_key) = self
+                .resources
+                .key_manager
+                .get_next_spend_and_script_key_ids_for_account(account)
+                .await?;
             builder.with_change_data(
                 script!(PushPubKey(Box::new(change_script_public_key))),
                 ExecutionStack::default(),
@@ -1172,7 +1817,7 @@ where
                     wallet_output,
                     &self.resources.key_manager,
                     None,
-                    OutputSource::default(),
+                    OutputSource::Change,
                     Some(tx_id),
                     None,
                 )
@@ -1180,14 +1825,279 @@ where
             );
         }
 
-        self.resources
-            .db
-            .encumber_outputs(tx_id, input_selection.into_selected(), db_outputs)?;
+        self.encumber_outputs(tx_id, input_selection.into_selected(), db_outputs)?;
         stp.finalize(&self.resources.key_manager).await?;
 
         Ok((tx_id, stp.into_transaction()?))
     }
 
+    async fn create_batch_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        if recipient_outputs.is_empty() {
+            return Err(OutputManagerError::BuildError(
+                "A batch transaction needs at least one recipient".to_string(),
+            ));
+        }
+        let total_value = recipient_outputs.iter().map(|(o, _)| o.value).sum();
+        let weighting = self.resources.consensus_constants.transaction_weight_params();
+        let mut features_and_scripts_byte_size = 0;
+        for (output, _) in &recipient_outputs {
+            features_and_scripts_byte_size += weighting.round_up_features_and_scripts_size(
+                output
+                    .features_and_scripts_byte_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+            );
+        }
+
+        let account = selection_criteria.account;
+        let input_selection = self
+            .select_utxos(
+                total_value,
+                selection_criteria,
+                fee_per_gram,
+                recipient_outputs.len(),
+                features_and_scripts_byte_size,
+            )
+            .await?;
+
+        let mut builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for uo in input_selection.iter() {
+            builder.with_input(uo.wallet_output.clone()).await?;
+        }
+
+        // The recipient outputs belong to other wallets, so unlike a pay-to-self they are not added to our database
+        for (output, sender_offset_key_id) in recipient_outputs {
+            builder
+                .with_output(output, sender_offset_key_id)
+                .await
+                .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
+        }
+
+        if input_selection.requires_change_output() {
+            let (change_spending_key_id, _, change_script_key_id, change_script_public_key) = self
+                .resources
+                .key_manager
+                .get_next_spend_and_script_key_ids_for_account(account)
+                .await?;
+            builder.with_change_data(
+                script!(PushPubKey(Box::new(change_script_public_key))),
+                ExecutionStack::default(),
+                change_script_key_id,
+                change_spending_key_id,
+                Covenant::default(),
+            );
+        }
+
+        let mut stp = builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let mut db_outputs = vec![];
+        if let Some(wallet_output) = stp.get_change_output()? {
+            db_outputs.push(
+                DbWalletOutput::from_wallet_output(
+                    wallet_output,
+                    &self.resources.key_manager,
+                    None,
+                    OutputSource::Change,
+                    Some(tx_id),
+                    None,
+                )
+                .await?,
+            );
+        }
+
+        trace!(target: LOG_TARGET, "Encumber batch transaction ({}) outputs.", tx_id);
+        self.encumber_outputs(tx_id, input_selection.into_selected(), db_outputs)?;
+        self.confirm_encumberance(tx_id)?;
+        let fee = stp.get_fee_amount()?;
+        stp.finalize(&self.resources.key_manager).await?;
+        let tx = stp.into_transaction()?;
+
+        Ok((fee, tx))
+    }
+
+    /// Selects and encumbers the inputs of a one-sided payment that an offline wallet will build and sign. The
+    /// signer pays both the recipient and the change as one-sided outputs, so inputs are selected for two one-sided
+    /// outputs and no outputs are encumbered to be received. The change is found by scanning like any other one-sided
+    /// payment to this wallet.
+    async fn encumber_unsigned_transaction_inputs(
+        &mut self,
+        tx_id: TxId,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(Vec<TransactionOutput>, MicroMinotari), OutputManagerError> {
+        let weighting = self.resources.consensus_constants.transaction_weight_params();
+        let one_sided_output_size = weighting.round_up_features_and_scripts_size(
+            OutputFeatures::default()
+                .get_serialized_size()
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                one_sided_payment_script(&PublicKey::default())
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? +
+                Covenant::default()
+                    .get_serialized_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+        );
+        let input_selection = self
+            .select_utxos(amount, selection_criteria, fee_per_gram, 2, 2 * one_sided_output_size)
+            .await?;
+
+        let mut inputs = Vec::with_capacity(input_selection.num_selected());
+        let mut input_total = MicroMinotari::zero();
+        for uo in input_selection.iter() {
+            inputs.push(
+                uo.wallet_output
+                    .to_transaction_output(&self.resources.key_manager)
+                    .await?,
+            );
+            input_total += uo.wallet_output.value;
+        }
+        self.encumber_outputs(tx_id, input_selection.into_selected(), vec![])?;
+        debug!(
+            target: LOG_TARGET,
+            "Encumbered {} inputs for unsigned transaction (TxId: {})",
+            inputs.len(),
+            tx_id
+        );
+
+        Ok((inputs, input_total))
+    }
+
+    async fn create_fee_bump_transaction(
+        &mut self,
+        tx_id: TxId,
+        recipient_outputs: Vec<(WalletOutput, TariKeyId)>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(MicroMinotari, Transaction), OutputManagerError> {
+        let inputs = self
+            .resources
+            .db
+            .fetch_outputs_by_tx_id(tx_id)?
+            .into_iter()
+            .filter(|o| o.spent_in_tx_id == Some(tx_id))
+            .collect::<Vec<_>>();
+        if inputs.is_empty() {
+            return Err(OutputManagerError::BuildError(format!(
+                "Transaction {} does not spend any of our outputs",
+                tx_id
+            )));
+        }
+        // Once an input is spent on chain the original transaction has been mined and can no longer be replaced
+        if let Some(input) = inputs.iter().find(|o| o.status != OutputStatus::EncumberedToBeSpent) {
+            return Err(OutputManagerError::BuildError(format!(
+                "Input {} of transaction {} is {} and cannot be spent by a replacement",
+                input.commitment.to_hex(),
+                tx_id,
+                input.status
+            )));
+        }
+
+        let mut builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        builder
+            .with_lock_height(0)
+            .with_fee_per_gram(fee_per_gram)
+            .with_prevent_fee_gt_amount(self.resources.config.prevent_fee_gt_amount)
+            .with_kernel_features(KernelFeatures::empty())
+            .with_tx_id(tx_id);
+
+        for input in &inputs {
+            builder.with_input(input.wallet_output.clone()).await?;
+        }
+        for (output, sender_offset_key_id) in recipient_outputs {
+            builder
+                .with_output(output, sender_offset_key_id)
+                .await
+                .map_err(|e| OutputManagerError::BuildError(e.to_string()))?;
+        }
+
+        // The replacement returns its change to the account the original inputs were spent from
+        let account = inputs[0].account();
+        let (change_spending_key_id, _, change_script_key_id, change_script_public_key) = self
+            .resources
+            .key_manager
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await?;
+        builder.with_change_data(
+            script!(PushPubKey(Box::new(change_script_public_key))),
+            ExecutionStack::default(),
+            change_script_key_id,
+            change_spending_key_id,
+            Covenant::default(),
+        );
+
+        let mut stp = builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+        let fee = stp.get_fee_amount()?;
+        let change_output = stp.get_change_output()?;
+        stp.finalize(&self.resources.key_manager).await?;
+        let tx = stp.into_transaction()?;
+
+        let mut db_outputs = vec![];
+        if let Some(wallet_output) = change_output {
+            db_outputs.push(
+                DbWalletOutput::from_wallet_output(
+                    wallet_output,
+                    &self.resources.key_manager,
+                    None,
+                    OutputSource::Change,
+                    Some(tx_id),
+                    None,
+                )
+                .await?,
+            );
+        }
+
+        // Nothing is changed in the database until the replacement has been finalized. The inputs stay encumbered to
+        // the transaction and the original change output is kept as cancelled, as the original may still be mined.
+        trace!(target: LOG_TARGET, "Replace change output of fee bumped transaction ({}).", tx_id);
+        self.resources
+            .db
+            .replace_pending_transaction_outputs(tx_id, db_outputs)?;
+
+        Ok((fee, tx))
+    }
+
+    /// Make the outputs with the given commitments the pending outputs to be received in `tx_id` again, cancelling its
+    /// other ones. This switches the change output back to that of an earlier version of a fee bumped transaction.
+    fn reinstate_transaction_outputs(
+        &mut self,
+        tx_id: TxId,
+        commitments: Vec<Commitment>,
+    ) -> Result<(), OutputManagerError> {
+        let outputs = self
+            .resources
+            .db
+            .fetch_outputs_by_tx_id(tx_id)?
+            .into_iter()
+            .filter(|o| o.received_in_tx_id == Some(tx_id) && commitments.contains(&o.commitment))
+            .collect();
+        self.resources.db.replace_pending_transaction_outputs(tx_id, outputs)?;
+        Ok(())
+    }
+
     async fn create_pay_to_self_transaction(
         &mut self,
         tx_id: TxId,
@@ -1215,6 +2125,7 @@ where
                         .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
             );
 
+        let account = selection_criteria.account;
         let input_selection = self
             .select_utxos(
                 amount,
@@ -1250,8 +2161,11 @@ where
 
         let mut outputs = vec![output];
 
-        let (change_spending_key_id, _spend_public_key, change_script_key_id, change_script_public_key) =
-            self.resources.key_manager.get_next_spend_and_script_key_ids().await?;
+        let (change_spending_key_id, _spend_public_key, change_script_key_id, change_script_public_key) = self
+            .resources
+            .key_manager
+            .get_next_spend_and_script_key_ids_for_account(account)
+            .await?;
         builder.with_change_data(
             script!(PushPubKey(Box::new(change_script_public_key.clone()))),
             ExecutionStack::default(),
@@ -1275,7 +2189,7 @@ where
                 wallet_output,
                 &self.resources.key_manager,
                 None,
-                OutputSource::default(),
+                OutputSource::Change,
                 Some(tx_id),
                 None,
             )
@@ -1288,9 +2202,7 @@ where
             "Encumber send to self transaction ({}) outputs.",
             tx_id
         );
-        self.resources
-            .db
-            .encumber_outputs(tx_id, input_selection.into_selected(), outputs)?;
+        self.encumber_outputs(tx_id, input_selection.into_selected(), outputs)?;
         self.confirm_encumberance(tx_id)?;
         let fee = stp.get_fee_amount()?;
         trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
@@ -1300,6 +2212,20 @@ where
         Ok((fee, tx))
     }
 
+    /// Encumber the outputs to the transaction and notify subscribers
+    fn encumber_outputs(
+        &self,
+        tx_id: TxId,
+        outputs_to_send: Vec<DbWalletOutput>,
+        outputs_to_receive: Vec<DbWalletOutput>,
+    ) -> Result<(), OutputManagerError> {
+        self.resources
+            .db
+            .encumber_outputs(tx_id, outputs_to_send, outputs_to_receive)?;
+        self.publish_event(OutputManagerEvent::OutputsEncumbered(tx_id));
+        Ok(())
+    }
+
     /// Confirm that a transaction has finished being negotiated between parties so the short-term encumberance can be
     /// made official
     fn confirm_encumberance(&mut self, tx_id: TxId) -> Result<(), OutputManagerError> {
@@ -1314,7 +2240,9 @@ where
             target: LOG_TARGET,
             "Cancelling pending transaction outputs for TxId: {}", tx_id
         );
-        Ok(self.resources.db.cancel_pending_transaction_outputs(tx_id)?)
+        self.resources.db.cancel_pending_transaction_outputs(tx_id)?;
+        self.publish_event(OutputManagerEvent::OutputsReleased(tx_id));
+        Ok(())
     }
 
     /// Restore the pending transaction encumberance and output for an inbound transaction that was previously
@@ -1346,8 +2274,6 @@ where
             total_output_features_and_scripts_byte_size,
             selection_criteria
         );
-        let mut utxos = Vec::new();
-
         let fee_calc = self.get_fee_calc();
 
         // Attempt to get the chain tip height
@@ -1391,41 +2317,45 @@ where
 
         trace!(target: LOG_TARGET, "We found {} UTXOs to select from", uo.len());
 
-        let mut requires_change_output = false;
-        let mut utxos_total_value = MicroMinotari::from(0);
-        let mut fee_without_change = MicroMinotari::from(0);
-        let mut fee_with_change = MicroMinotari::from(0);
-        for o in uo {
-            utxos_total_value += o.wallet_output.value;
-
-            trace!(target: LOG_TARGET, "-- utxos_total_value = {:?}", utxos_total_value);
-            utxos.push(o);
-            // The assumption here is that the only output will be the payment output and change if required
-            fee_without_change = fee_calc.calculate(
-                fee_per_gram,
-                1,
-                utxos.len(),
-                num_outputs,
-                total_output_features_and_scripts_byte_size,
-            );
-            if utxos_total_value == amount + fee_without_change {
-                break;
-            }
-            fee_with_change = fee_calc.calculate(
-                fee_per_gram,
-                1,
-                utxos.len(),
-                num_outputs + 1,
-                total_output_features_and_scripts_byte_size + default_features_and_scripts_size,
-            );
-
-            trace!(target: LOG_TARGET, "-- amt+fee = {:?} {}", amount, fee_with_change);
-            if utxos_total_value > amount + fee_with_change {
-                requires_change_output = true;
-                break;
+        if let UtxoSelectionFilter::SpecificOutputs { commitments } = &selection_criteria.filter {
+            if !commitments.is_empty() {
+                return Self::select_specific_utxos(
+                    &fee_calc,
+                    commitments,
+                    uo,
+                    amount,
+                    fee_per_gram,
+                    num_outputs,
+                    total_output_features_and_scripts_byte_size,
+                    default_features_and_scripts_size,
+                );
             }
         }
 
+        let method = selection_criteria
+            .coin_selection
+            .unwrap_or(self.resources.config.coin_selection_method);
+        trace!(target: LOG_TARGET, "Selecting inputs using the {} coin selection method", method);
+        let target = SelectionTarget {
+            fee_calc: &fee_calc,
+            amount,
+            fee_per_gram,
+            num_outputs,
+            features_and_scripts_byte_size: total_output_features_and_scripts_byte_size,
+            change_features_and_scripts_byte_size: default_features_and_scripts_size,
+        };
+        let selection = method.strategy().select(uo, &target);
+        let utxos_total_value = selection.total_value;
+        let fee_without_change = selection.fee_without_change;
+        let fee_with_change = selection.fee_with_change;
+        trace!(
+            target: LOG_TARGET,
+            "-- utxos_total_value = {:?}, amt+fee = {:?} {}",
+            utxos_total_value,
+            amount,
+            fee_with_change
+        );
+
         let perfect_utxo_selection = utxos_total_value == amount + fee_without_change;
         let enough_spendable = utxos_total_value > amount + fee_with_change;
 
@@ -1435,15 +2365,74 @@ where
             let pending_incoming = balance.pending_incoming_balance;
             if utxos_total_value + pending_incoming >= amount + fee_with_change {
                 return Err(OutputManagerError::FundsPending);
-            } else {
-                return Err(OutputManagerError::NotEnoughFunds);
             }
+            // Time-locked outputs are only excluded from selection in safe mode
+            if let Some(tip) = current_tip_for_time_lock_calculation {
+                if selection_criteria.mode == UtxoSelectionMode::Safe {
+                    let shortfall = amount + fee_with_change - utxos_total_value;
+                    if let Some(height) = self.height_when_funds_mature(tip, shortfall)? {
+                        return Err(OutputManagerError::FundsTimeLocked(height));
+                    }
+                }
+            }
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+
+        Ok(selection)
+    }
+
+    /// Uses exactly the outputs chosen by the caller as inputs. Every chosen output must be spendable, and together
+    /// they must cover the amount and fee, as no other outputs are added to make up a shortfall.
+    fn select_specific_utxos(
+        fee_calc: &Fee,
+        commitments: &[Commitment],
+        utxos: Vec<DbWalletOutput>,
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        num_outputs: usize,
+        total_output_features_and_scripts_byte_size: usize,
+        default_features_and_scripts_size: usize,
+    ) -> Result<UtxoSelection, OutputManagerError> {
+        let unavailable = commitments
+            .iter()
+            .filter(|commitment| !utxos.iter().any(|o| &o.commitment == *commitment))
+            .map(|commitment| commitment.to_hex())
+            .collect::<Vec<_>>();
+        if !unavailable.is_empty() {
+            return Err(OutputManagerError::SelectedOutputsUnavailable(unavailable.join(", ")));
         }
 
+        let total_value = utxos.iter().map(|o| o.wallet_output.value).sum::<MicroMinotari>();
+        let fee_without_change = fee_calc.calculate(
+            fee_per_gram,
+            1,
+            utxos.len(),
+            num_outputs,
+            total_output_features_and_scripts_byte_size,
+        );
+        let fee_with_change = fee_calc.calculate(
+            fee_per_gram,
+            1,
+            utxos.len(),
+            num_outputs + 1,
+            total_output_features_and_scripts_byte_size + default_features_and_scripts_size,
+        );
+
+        let requires_change_output = if total_value == amount + fee_without_change {
+            false
+        } else if total_value > amount + fee_with_change {
+            true
+        } else {
+            return Err(OutputManagerError::SelectedOutputsInsufficient {
+                available: total_value,
+                required: amount + fee_with_change,
+            });
+        };
+
         Ok(UtxoSelection {
             utxos,
             requires_change_output,
-            total_value: utxos_total_value,
+            total_value,
             fee_without_change,
             fee_with_change,
         })
@@ -1718,9 +2707,7 @@ where
         );
 
         // encumbering transaction
-        self.resources
-            .db
-            .encumber_outputs(tx_id, src_outputs.clone(), dest_outputs)?;
+        self.encumber_outputs(tx_id, src_outputs.clone(), dest_outputs)?;
         self.confirm_encumberance(tx_id)?;
 
         trace!(
@@ -1735,7 +2722,6 @@ where
         Ok((tx_id, stp.into_transaction()?, accumulated_amount + fee))
     }
 
-    #[allow(clippy::too_many_lines)]
     async fn create_coin_split(
         &mut self,
         src_outputs: Vec<DbWalletOutput>,
@@ -1755,11 +2741,48 @@ where
             ));
         }
 
+        self.create_coin_split_with_denominations(src_outputs, vec![amount_per_split; number_of_splits], fee_per_gram)
+            .await
+    }
+
+    async fn create_coin_split_with_denominations_auto(
+        &mut self,
+        denominations: Vec<MicroMinotari>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        let total_split_amount = total_of_denominations(&denominations)?;
+        let number_of_splits = denominations.len();
+        let selection = self
+            .select_utxos(
+                total_split_amount,
+                UtxoSelectionCriteria::largest_first(self.resources.config.dust_ignore_value),
+                fee_per_gram,
+                number_of_splits,
+                self.default_features_and_scripts_size()
+                    .map_err(|e| OutputManagerError::ConversionError(e.to_string()))? *
+                    number_of_splits,
+            )
+            .await?;
+
+        self.create_coin_split_with_denominations(selection.utxos, denominations, fee_per_gram)
+            .await
+    }
+
+    /// Splits the source outputs into outputs of exactly the given denominations, with any excess returned as change.
+    #[allow(clippy::too_many_lines)]
+    async fn create_coin_split_with_denominations(
+        &mut self,
+        src_outputs: Vec<DbWalletOutput>,
+        denominations: Vec<MicroMinotari>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        let total_split_amount = total_of_denominations(&denominations)?;
+        let number_of_splits = denominations.len();
+
         let default_features_and_scripts_size = self
             .default_features_and_scripts_size()
             .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?;
         let mut dest_outputs = Vec::with_capacity(number_of_splits + 1);
-        let total_split_amount = MicroMinotari::from(amount_per_split.as_u64() * number_of_splits as u64);
 
         // accumulated value amount from given source outputs
         let accumulated_amount = src_outputs
@@ -1844,9 +2867,9 @@ where
         // ----------------------------------------------------------------------------
         // initializing primary outputs
 
-        for _ in 0..number_of_splits {
+        for amount in denominations {
             let (output, sender_offset_key_id) = self
-                .output_to_self(OutputFeatures::default(), amount_per_split, Covenant::default())
+                .output_to_self(OutputFeatures::default(), amount, Covenant::default())
                 .await?;
 
             tx_builder
@@ -1902,7 +2925,7 @@ where
                     wallet_output_for_change,
                     &self.resources.key_manager,
                     None,
-                    OutputSource::default(),
+                    OutputSource::Change,
                     Some(tx_id),
                     None,
                 )
@@ -1911,9 +2934,7 @@ where
         }
 
         // encumbering transaction
-        self.resources
-            .db
-            .encumber_outputs(tx_id, src_outputs.clone(), dest_outputs)?;
+        self.encumber_outputs(tx_id, src_outputs.clone(), dest_outputs)?;
         self.confirm_encumberance(tx_id)?;
 
         trace!(
@@ -2089,9 +3110,7 @@ where
         );
 
         // encumbering transaction
-        self.resources
-            .db
-            .encumber_outputs(tx_id, src_outputs.clone(), vec![output])?;
+        self.encumber_outputs(tx_id, src_outputs.clone(), vec![output])?;
         self.confirm_encumberance(tx_id)?;
 
         trace!(
@@ -2106,6 +3125,108 @@ where
         Ok((tx_id, stp.into_transaction()?, accumulated_amount + fee))
     }
 
+    /// Spends the unconfirmed outputs received in `parent_tx_id` back to this wallet, paying enough fee that the
+    /// parent and child together pay `fee_per_gram` over their combined weight.
+    pub async fn create_child_pays_for_parent_transaction(
+        &mut self,
+        parent_tx_id: TxId,
+        parent_fee: MicroMinotari,
+        parent_weight: u64,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<(TxId, Transaction, MicroMinotari), OutputManagerError> {
+        let default_features_and_scripts_size = self
+            .default_features_and_scripts_size()
+            .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?;
+
+        let selection_criteria = UtxoSelectionCriteria::child_pays_for_parent(parent_tx_id);
+        let src_outputs =
+            self.resources
+                .db
+                .fetch_unspent_outputs_for_spending(&selection_criteria, MicroMinotari::zero(), None)?;
+        if src_outputs.is_empty() {
+            return Err(OutputManagerError::NoUtxosSelected {
+                criteria: selection_criteria,
+            });
+        }
+
+        let accumulated_amount_with_fee = src_outputs
+            .iter()
+            .fold(MicroMinotari::zero(), |acc, x| acc + x.wallet_output.value);
+
+        // The child pays its own weight at the requested rate plus whatever the parent is short of it
+        let child_weight =
+            self.get_fee_calc()
+                .weighting()
+                .calculate(1, src_outputs.len(), 1, default_features_and_scripts_size);
+        let package_fee = parent_weight
+            .saturating_add(child_weight)
+            .saturating_mul(fee_per_gram.as_u64());
+        let child_fee = package_fee
+            .saturating_sub(parent_fee.as_u64())
+            .max(child_weight.saturating_mul(fee_per_gram.as_u64()));
+        let child_weight = child_weight.max(1);
+        let child_fee_per_gram = MicroMinotari::from((child_fee + child_weight - 1) / child_weight);
+        let fee = self.get_fee_calc().calculate(
+            child_fee_per_gram,
+            1,
+            src_outputs.len(),
+            1,
+            default_features_and_scripts_size,
+        );
+
+        let accumulated_amount = accumulated_amount_with_fee.saturating_sub(fee);
+        if accumulated_amount == MicroMinotari::zero() {
+            error!(
+                target: LOG_TARGET,
+                "Unconfirmed outputs of TxId: {} cannot cover a child fee of {}", parent_tx_id, fee
+            );
+            return Err(OutputManagerError::NotEnoughFunds);
+        }
+
+        let mut tx_builder = SenderTransactionProtocol::builder(
+            self.resources.consensus_constants.clone(),
+            self.resources.key_manager.clone(),
+        );
+        tx_builder
+            .with_lock_height(0)
+            .with_fee_per_gram(child_fee_per_gram)
+            .with_kernel_features(KernelFeatures::empty());
+
+        for input in &src_outputs {
+            tx_builder.with_input(input.wallet_output.clone()).await?;
+        }
+
+        let (output, sender_offset_key_id) = self
+            .output_to_self(OutputFeatures::default(), accumulated_amount, Covenant::default())
+            .await?;
+
+        tx_builder
+            .with_output(output.wallet_output.clone(), sender_offset_key_id)
+            .await?;
+
+        let mut stp = tx_builder
+            .build()
+            .await
+            .map_err(|e| OutputManagerError::BuildError(e.message))?;
+
+        let tx_id = stp.get_tx_id()?;
+        info!(
+            target: LOG_TARGET,
+            "Spending {} unconfirmed output(s) of TxId: {} in child TxId: {} with a fee of {}",
+            src_outputs.len(),
+            parent_tx_id,
+            tx_id,
+            fee
+        );
+
+        self.encumber_outputs(tx_id, src_outputs, vec![output])?;
+        self.confirm_encumberance(tx_id)?;
+
+        stp.finalize(&self.resources.key_manager).await?;
+
+        Ok((tx_id, stp.into_transaction()?, accumulated_amount))
+    }
+
     async fn fetch_outputs_from_node(
         &mut self,
         hashes: Vec<HashOutput>,
@@ -2227,7 +3348,7 @@ where
                 outputs.push(change_output);
 
                 trace!(target: LOG_TARGET, "Claiming HTLC with transaction ({}).", tx_id);
-                self.resources.db.encumber_outputs(tx_id, Vec::new(), outputs)?;
+                self.encumber_outputs(tx_id, Vec::new(), outputs)?;
                 self.confirm_encumberance(tx_id)?;
                 let fee = stp.get_fee_amount()?;
                 trace!(target: LOG_TARGET, "Finalize send-to-self transaction ({}).", tx_id);
@@ -2314,7 +3435,7 @@ where
 
         let tx = stp.into_transaction()?;
 
-        self.resources.db.encumber_outputs(tx_id, Vec::new(), outputs)?;
+        self.encumber_outputs(tx_id, Vec::new(), outputs)?;
         self.confirm_encumberance(tx_id)?;
         Ok((tx_id, fee, amount - fee, tx))
     }
@@ -2357,8 +3478,28 @@ where
             ));
         }
 
-        let wallet_sk = self.resources.wallet_identity.wallet_node_key_id.clone();
-        let wallet_pk = self.resources.key_manager.get_public_key_at_key_id(&wallet_sk).await?;
+        let (wallet_sk, wallet_pk) = self.resources.wallet_identity.one_sided_scanning_keys();
+        // Payments to a subaddress use its key in place of the wallet key, so stealth outputs are checked against the
+        // wallet key and then every subaddress key
+        let subaddresses = self
+            .resources
+            .db
+            .fetch_subaddresses()?
+            .into_iter()
+            .map(|s| {
+                let key_id = TariKeyId::Managed {
+                    branch: TransactionKeyManagerBranch::Subaddress.get_branch_key(),
+                    index: s.index,
+                };
+                (key_id, s)
+            })
+            .collect::<Vec<_>>();
+        let mut stealth_keys = vec![(wallet_sk, wallet_pk, None)];
+        stealth_keys.extend(
+            subaddresses
+                .iter()
+                .map(|(key_id, s)| (key_id.clone(), s.public_key.clone(), Some(s.clone()))),
+        );
 
         let mut scanned_outputs = vec![];
 
@@ -2378,11 +3519,16 @@ where
                                 .key_manager
                                 .get_diffie_hellman_shared_secret(&matched_key.1, &output.sender_offset_public_key)
                                 .await?;
+                            let subaddress = subaddresses
+                                .iter()
+                                .find(|(key_id, _)| key_id == &matched_key.1)
+                                .map(|(_, s)| s.clone());
                             scanned_outputs.push((
                                 output.clone(),
                                 OutputSource::OneSided,
                                 matched_key.1.clone(),
                                 shared_secret,
+                                subaddress,
                             ));
                         },
                     }
@@ -2393,37 +3539,41 @@ where
                 // NOTE: Extracting the nonce R and a spending (public aka scan_key) key from the script
                 // NOTE: [RFC 203 on Stealth Addresses](https://rfc.tari.com/RFC-0203_StealthAddresses.html)
                 [Opcode::PushPubKey(nonce), Opcode::Drop, Opcode::PushPubKey(scanned_pk)] => {
-                    // matching spending (public) keys
-                    let stealth_address_hasher = self
-                        .resources
-                        .key_manager
-                        .get_diffie_hellman_stealth_domain_hasher(&wallet_sk, nonce.as_ref())
-                        .await?;
-                    let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, &wallet_pk);
-                    if &script_spending_key != scanned_pk.as_ref() {
-                        continue;
-                    }
+                    for (scan_key_id, scan_pk, subaddress) in &stealth_keys {
+                        // matching spending (public) keys
+                        let stealth_address_hasher = self
+                            .resources
+                            .key_manager
+                            .get_diffie_hellman_stealth_domain_hasher(scan_key_id, nonce.as_ref())
+                            .await?;
+                        let script_spending_key = stealth_address_script_spending_key(&stealth_address_hasher, scan_pk);
+                        if &script_spending_key != scanned_pk.as_ref() {
+                            continue;
+                        }
 
-                    // Compute the stealth address offset
-                    let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
-                        .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
-                    let stealth_key = self
-                        .resources
-                        .key_manager
-                        .import_add_offset_to_private_key(&wallet_sk, stealth_address_offset)
-                        .await?;
-
-                    let shared_secret = self
-                        .resources
-                        .key_manager
-                        .get_diffie_hellman_shared_secret(&wallet_sk, &output.sender_offset_public_key)
-                        .await?;
-                    scanned_outputs.push((
-                        output.clone(),
-                        OutputSource::StealthOneSided,
-                        stealth_key,
-                        shared_secret,
-                    ));
+                        // Compute the stealth address offset
+                        let stealth_address_offset = PrivateKey::from_uniform_bytes(stealth_address_hasher.as_ref())
+                            .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size");
+                        let stealth_key = self
+                            .resources
+                            .key_manager
+                            .import_add_offset_to_private_key(scan_key_id, stealth_address_offset)
+                            .await?;
+
+                        let shared_secret = self
+                            .resources
+                            .key_manager
+                            .get_diffie_hellman_shared_secret(scan_key_id, &output.sender_offset_public_key)
+                            .await?;
+                        scanned_outputs.push((
+                            output.clone(),
+                            OutputSource::StealthOneSided,
+                            stealth_key,
+                            shared_secret,
+                            subaddress.clone(),
+                        ));
+                        break;
+                    }
                 },
 
                 _ => {},
@@ -2436,11 +3586,17 @@ where
     // Import scanned outputs into the wallet
     async fn import_onesided_outputs(
         &self,
-        scanned_outputs: Vec<(TransactionOutput, OutputSource, TariKeyId, CommsDHKE)>,
+        scanned_outputs: Vec<(
+            TransactionOutput,
+            OutputSource,
+            TariKeyId,
+            CommsDHKE,
+            Option<Subaddress>,
+        )>,
     ) -> Result<Vec<RecoveredOutput>, OutputManagerError> {
         let mut rewound_outputs = Vec::with_capacity(scanned_outputs.len());
 
-        for (output, output_source, script_private_key, shared_secret) in scanned_outputs {
+        for (output, output_source, script_private_key, shared_secret, subaddress) in scanned_outputs {
             let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
             if let Ok((committed_value, spending_key)) =
                 EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data)
@@ -2469,7 +3625,7 @@ where
                     );
 
                     let tx_id = TxId::new_random();
-                    let db_output = DbWalletOutput::from_wallet_output(
+                    let mut db_output = DbWalletOutput::from_wallet_output(
                         rewound_output.clone(),
                         &self.resources.key_manager,
                         None,
@@ -2478,9 +3634,15 @@ where
                         None,
                     )
                     .await?;
+                    db_output.subaddress_index = subaddress.as_ref().map(|s| s.index);
 
+                    let hash = db_output.hash;
                     match self.resources.db.add_unspent_output_with_tx_id(tx_id, db_output) {
                         Ok(_) => {
+                            self.publish_event(OutputManagerEvent::OutputImported {
+                                hash,
+                                tx_id: Some(tx_id),
+                            });
                             trace!(
                                 target: LOG_TARGET,
                                 "One-sided payment Output {} with value {} recovered",
@@ -2491,6 +3653,7 @@ where
                             rewound_outputs.push(RecoveredOutput {
                                 output: rewound_output,
                                 tx_id,
+                                subaddress,
                             })
                         },
                         Err(OutputManagerStorageError::DuplicateOutput) => {
@@ -2511,6 +3674,181 @@ where
         Ok(rewound_outputs)
     }
 
+    /// Joins small outputs into one when the wallet holds at least `auto_consolidation_min_outputs` of them and has no
+    /// transactions in flight. The transaction is handed to the transaction service for broadcast through a
+    /// [OutputManagerEvent::ConsolidationTransactionCreated] event.
+    async fn run_auto_consolidation(&mut self) {
+        if !self.auto_consolidation_enabled || self.resources.wallet_identity.is_watch_only() {
+            return;
+        }
+        match self.create_consolidation_transaction().await {
+            Ok(Some(event)) => self.publish_event(event),
+            Ok(None) => {},
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Automatic consolidation failed: {}", e);
+                self.publish_event(OutputManagerEvent::ConsolidationFailed(e.to_string()));
+            },
+        }
+    }
+
+    async fn run_spent_output_archiving(&mut self) {
+        if !self.resources.config.spent_output_archiving_enabled {
+            return;
+        }
+        let min_confirmations = self.resources.config.spent_output_archiving_min_confirmations;
+        if let Err(e) = self.archive_spent_outputs(min_confirmations).await {
+            warn!(target: LOG_TARGET, "Archiving spent outputs failed: {}", e);
+        }
+    }
+
+    /// Moves the outputs that were spent at least `min_confirmations` blocks below the tip into the archive
+    async fn archive_spent_outputs(&mut self, min_confirmations: u64) -> Result<usize, OutputManagerError> {
+        let tip = self.current_tip_height().await?;
+        let spent_height = match tip.checked_sub(min_confirmations) {
+            Some(height) => height,
+            None => return Ok(0),
+        };
+        let num_archived = self.resources.db.archive_spent_outputs(spent_height)?;
+        if num_archived > 0 {
+            info!(
+                target: LOG_TARGET,
+                "Archived {} outputs spent at or below height {}", num_archived, spent_height
+            );
+        }
+        Ok(num_archived)
+    }
+
+    async fn create_consolidation_transaction(&mut self) -> Result<Option<OutputManagerEvent>, OutputManagerError> {
+        // Only consolidate while idle so that the outputs are not locked up while other transactions need them
+        let balance = self.get_balance(None)?;
+        if balance.pending_incoming_balance > MicroMinotari::zero() ||
+            balance.pending_outgoing_balance > MicroMinotari::zero() ||
+            self.validation_in_progress.try_lock().is_err()
+        {
+            trace!(target: LOG_TARGET, "Wallet is busy, skipping automatic consolidation");
+            return Ok(None);
+        }
+
+        let config = &self.resources.config;
+        let threshold = MicroMinotari::from(config.auto_consolidation_threshold);
+        let fee_per_gram = MicroMinotari::from(config.auto_consolidation_fee_per_gram);
+        let min_outputs = config.auto_consolidation_min_outputs.max(2);
+        let max_inputs = config.auto_consolidation_max_inputs;
+        // Outputs worth less than the fee to spend them would only shrink the consolidated output
+        let input_fee = self.get_fee_calc().calculate(fee_per_gram, 0, 1, 0, 0);
+        let mut fragments = self
+            .resources
+            .db
+            .fetch_mined_unspent_outputs()?
+            .into_iter()
+            .filter(|o| {
+                !o.frozen &&
+                    o.wallet_output.features.output_type == OutputType::Standard &&
+                    o.wallet_output.value < threshold &&
+                    o.wallet_output.value > input_fee
+            })
+            .collect::<Vec<_>>();
+        if fragments.len() < min_outputs {
+            return Ok(None);
+        }
+        fragments.sort_by_key(|o| o.wallet_output.value);
+        fragments.truncate(max_inputs);
+
+        let num_inputs = fragments.len();
+        let commitments = fragments.into_iter().map(|o| o.commitment).collect();
+        let (tx_id, transaction, total_value) = self.create_coin_join(commitments, fee_per_gram).await?;
+        let fee = transaction.body.get_total_fee()?;
+        info!(
+            target: LOG_TARGET,
+            "Created consolidation transaction {} joining {} outputs", tx_id, num_inputs
+        );
+        Ok(Some(OutputManagerEvent::ConsolidationTransactionCreated {
+            tx_id,
+            transaction: Box::new(transaction),
+            amount: total_value.saturating_sub(fee),
+            fee,
+            num_inputs,
+        }))
+    }
+
+    /// Joins the unspent outputs worth less than `threshold` into a single output paying to this wallet. Only outputs
+    /// worth more than the fee to spend them are swept, and nothing is built unless at least two of them together
+    /// cover the fee of the whole transaction.
+    async fn sweep_dust(
+        &mut self,
+        threshold: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<Option<DustSweep>, OutputManagerError> {
+        let fee_calc = self.get_fee_calc();
+        let input_fee = fee_calc.calculate(fee_per_gram, 0, 1, 0, 0);
+        let mut dust = self
+            .resources
+            .db
+            .fetch_mined_unspent_outputs()?
+            .into_iter()
+            .filter(|o| {
+                o.status == OutputStatus::Unspent &&
+                    !o.frozen &&
+                    o.wallet_output.features.output_type == OutputType::Standard &&
+                    !ScriptLock::from_script(&o.wallet_output.script).is_locked() &&
+                    o.wallet_output.value < threshold &&
+                    o.wallet_output.value > input_fee
+            })
+            .collect::<Vec<_>>();
+        // Prefer the largest dust so that as much value as possible is reclaimed if there is more than fits
+        dust.sort_by(|a, b| b.wallet_output.value.cmp(&a.wallet_output.value));
+        dust.truncate(MAX_DUST_SWEEP_INPUTS);
+
+        let amount: MicroMinotari = dust.iter().map(|o| o.wallet_output.value).sum();
+        let fee = fee_calc.calculate(
+            fee_per_gram,
+            1,
+            dust.len(),
+            1,
+            self.default_features_and_scripts_size()
+                .map_err(|e| OutputManagerError::ConversionError(e.to_string()))?,
+        );
+        if dust.len() < 2 || amount <= fee {
+            debug!(
+                target: LOG_TARGET,
+                "Not sweeping {} dust output(s) worth {} at a fee of {}",
+                dust.len(),
+                amount,
+                fee
+            );
+            return Ok(None);
+        }
+
+        let num_outputs_swept = dust.len();
+        let commitments = dust.into_iter().map(|o| o.commitment).collect();
+        let (tx_id, transaction, amount_swept) = self.create_coin_join(commitments, fee_per_gram).await?;
+        let fee = transaction.body.get_total_fee()?;
+        info!(
+            target: LOG_TARGET,
+            "Created dust sweep transaction {} joining {} outputs worth {} at a cost of {}",
+            tx_id,
+            num_outputs_swept,
+            amount_swept,
+            fee
+        );
+        Ok(Some(DustSweep {
+            tx_id,
+            transaction,
+            num_outputs_swept,
+            amount_swept,
+            fee,
+        }))
+    }
+
+    fn publish_event(&self, event: OutputManagerEvent) {
+        if let Err(e) = self.resources.event_publisher.send(Arc::new(event)) {
+            trace!(
+                target: LOG_TARGET,
+                "Error sending event because there are no subscribers: {:?}", e
+            );
+        }
+    }
+
     fn get_fee_calc(&self) -> Fee {
         Fee::new(*self.resources.consensus_constants.transaction_weight_params())
     }
@@ -2529,6 +3867,121 @@ pub struct Balance {
     pub pending_outgoing_balance: MicroMinotari,
 }
 
+/// The point in the chain's history at which a [HistoricalBalance] is reconstructed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalanceCutoff {
+    /// At the end of the block at this height
+    Height(u64),
+    /// At this unix timestamp (seconds), based on the mined timestamps of the wallet's outputs and transactions
+    Timestamp(u64),
+}
+
+impl fmt::Display for BalanceCutoff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BalanceCutoff::Height(height) => write!(f, "height {}", height),
+            BalanceCutoff::Timestamp(timestamp) => write!(f, "timestamp {}", timestamp),
+        }
+    }
+}
+
+/// The confirmed balance of the wallet at a point in the past
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoricalBalance {
+    /// The height the balance was reconstructed at, None if the wallet had no activity before the cutoff
+    pub height: Option<u64>,
+    /// The sum of the outputs that were mined and unspent at `height`
+    pub balance: MicroMinotari,
+    /// The number of outputs that were mined and unspent at `height`
+    pub num_outputs: u64,
+}
+
+/// Funds broken down by where the outputs holding them came from
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BalanceByOrigin {
+    /// Coinbase outputs, i.e. mining income
+    pub coinbase: MicroMinotari,
+    /// Outputs received in interactive transactions
+    pub interactive: MicroMinotari,
+    /// Outputs received in one-sided and stealth one-sided transactions
+    pub one_sided: MicroMinotari,
+    /// Change returned to the wallet by its own transactions
+    pub change: MicroMinotari,
+    /// Outputs of any other origin, such as refunds, atomic swaps and unrecognised recovered outputs
+    pub other: MicroMinotari,
+}
+
+impl BalanceByOrigin {
+    pub(crate) fn add(&mut self, source: OutputSource, amount: MicroMinotari) {
+        let bucket = match source {
+            OutputSource::Coinbase => &mut self.coinbase,
+            OutputSource::Standard => &mut self.interactive,
+            OutputSource::OneSided | OutputSource::StealthOneSided => &mut self.one_sided,
+            OutputSource::Change => &mut self.change,
+            OutputSource::Unknown |
+            OutputSource::RecoveredButUnrecognized |
+            OutputSource::Refund |
+            OutputSource::AtomicSwap => &mut self.other,
+        };
+        *bucket += amount;
+    }
+
+    pub fn total(&self) -> MicroMinotari {
+        self.coinbase + self.interactive + self.one_sided + self.change + self.other
+    }
+}
+
+/// An amount of currently time-locked funds that become spendable at a given height
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MaturityScheduleEntry {
+    /// The block height at which these funds become spendable
+    pub spendable_at_height: u64,
+    /// The total value of the outputs that become spendable at this height
+    pub amount: MicroMinotari,
+    /// The part of `amount` that comes from coinbase outputs
+    pub coinbase_amount: MicroMinotari,
+    /// The number of outputs that become spendable at this height
+    pub num_outputs: u64,
+}
+
+/// The outcome of coin selection for a prospective transaction, before any outputs are encumbered
+#[derive(Debug, Clone)]
+pub struct TransactionPreview {
+    /// The outputs that would be spent
+    pub inputs: Vec<DbWalletOutput>,
+    /// The total value of `inputs`
+    pub total_input_value: MicroMinotari,
+    /// The exact fee the transaction would pay
+    pub fee: MicroMinotari,
+    /// The value of the change output returned to the wallet, zero if no change output is needed
+    pub change: MicroMinotari,
+}
+
+/// A transaction joining a wallet's dust outputs into one, ready to be broadcast
+#[derive(Debug, Clone)]
+pub struct DustSweep {
+    pub tx_id: TxId,
+    pub transaction: Transaction,
+    /// The number of dust outputs spent
+    pub num_outputs_swept: usize,
+    /// The total value of the dust outputs spent
+    pub amount_swept: MicroMinotari,
+    /// The fee paid, which is the net cost of the sweep to the wallet
+    pub fee: MicroMinotari,
+}
+
+/// An unspent output that is encumbered by a script height lock and/or hash lock
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptLockedOutput {
+    pub commitment: Commitment,
+    pub value: MicroMinotari,
+    /// The first block height at which this output can be spent. For a hash time-locked contract this is when the
+    /// refund path opens; the hash-locked path can be taken at any height by revealing the preimage.
+    pub claimable_at_height: u64,
+    /// The hash whose preimage unlocks the output, if any
+    pub hash_lock: Option<FixedHash>,
+}
+
 impl Balance {
     pub fn zero() -> Self {
         Self {
@@ -2552,44 +4005,22 @@ impl fmt::Display for Balance {
     }
 }
 
-#[derive(Debug, Clone)]
-struct UtxoSelection {
-    utxos: Vec<DbWalletOutput>,
-    requires_change_output: bool,
-    total_value: MicroMinotari,
-    fee_without_change: MicroMinotari,
-    fee_with_change: MicroMinotari,
-}
-
-#[allow(dead_code)]
-impl UtxoSelection {
-    pub fn as_final_fee(&self) -> MicroMinotari {
-        if self.requires_change_output {
-            return self.fee_with_change;
-        }
-        self.fee_without_change
-    }
-
-    pub fn requires_change_output(&self) -> bool {
-        self.requires_change_output
-    }
-
-    /// Total value of the selected inputs
-    pub fn total_value(&self) -> MicroMinotari {
-        self.total_value
-    }
-
-    pub fn num_selected(&self) -> usize {
-        self.utxos.len()
-    }
-
-    pub fn into_selected(self) -> Vec<DbWalletOutput> {
-        self.utxos
+/// Returns the sum of the requested coin split denominations, checking that the request is valid
+fn total_of_denominations(denominations: &[MicroMinotari]) -> Result<MicroMinotari, OutputManagerError> {
+    if denominations.is_empty() {
+        return Err(OutputManagerError::InvalidArgument(
+            "at least one denomination is required".to_string(),
+        ));
     }
-
-    pub fn iter(&self) -> impl Iterator<Item = &DbWalletOutput> + '_ {
-        self.utxos.iter()
+    if denominations.iter().any(|d| *d == MicroMinotari::zero()) {
+        return Err(OutputManagerError::InvalidArgument(
+            "denominations must be greater than 0".to_string(),
+        ));
     }
+    denominations
+        .iter()
+        .try_fold(MicroMinotari::zero(), |total, d| total.checked_add(d))
+        .ok_or_else(|| OutputManagerError::InvalidArgument("the sum of the denominations is too large".to_string()))
 }
 
 #[derive(Debug, Clone)]
@@ -1,6 +1,8 @@
 // Copyright 2022 The Tari Project
 // SPDX-License-Identifier: BSD-3-Clause
 
+use std::collections::BTreeMap;
+
 use tari_common_types::{
     transaction::TxId,
     types::{Commitment, FixedHash},
@@ -10,10 +12,10 @@ use tari_core::transactions::transaction_components::{OutputType, TransactionOut
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     input_selection::UtxoSelectionCriteria,
-    service::Balance,
+    service::{Balance, BalanceByOrigin, BalanceCutoff, HistoricalBalance},
     storage::{
         database::{DbKey, DbValue, OutputBackendQuery, WriteOperation},
-        models::DbWalletOutput,
+        models::{ArchivedOutput, DbWalletOutput, Subaddress, WalletAccount},
     },
 };
 
@@ -50,6 +52,13 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn set_output_to_unmined_and_invalid(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError>;
     fn update_last_validation_timestamp(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError>;
     fn set_outputs_to_be_revalidated(&self) -> Result<(), OutputManagerStorageError>;
+    /// Resets the mined and spent state of outputs that were mined or spent between `start_height` and `end_height`
+    /// (inclusive) so that they are revalidated
+    fn set_outputs_in_height_range_to_be_revalidated(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), OutputManagerStorageError>;
 
     fn mark_output_as_spent(
         &self,
@@ -83,6 +92,28 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn update_output_metadata_signature(&self, output: &TransactionOutput) -> Result<(), OutputManagerStorageError>;
     /// If an invalid output is found to be valid this function will turn it back into an unspent output
     fn revalidate_unspent_output(&self, spending_key: &Commitment) -> Result<(), OutputManagerStorageError>;
+    /// Freeze or unfreeze the outputs with the given commitments. Frozen outputs are never selected as inputs.
+    fn set_outputs_frozen(&self, commitments: &[Commitment], frozen: bool) -> Result<(), OutputManagerStorageError>;
+    /// Replace the label and metadata attached to the output with the given commitment
+    fn set_output_label(
+        &self,
+        commitment: &Commitment,
+        label: Option<String>,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), OutputManagerStorageError>;
+    /// Move the outputs that were spent at or below `spent_height` out of the outputs table and into the archive,
+    /// returning the number of outputs archived
+    fn archive_spent_outputs(&self, spent_height: u64) -> Result<usize, OutputManagerStorageError>;
+    /// Fetch the records of all archived outputs, ordered by the height they were spent at
+    fn fetch_archived_outputs(&self) -> Result<Vec<ArchivedOutput>, OutputManagerStorageError>;
+    /// Create a new wallet account with the given unique name
+    fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerStorageError>;
+    /// Fetch all stored wallet accounts, the default account 0 is not stored
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError>;
+    /// Set the account that interactive payments are received into
+    fn set_receiving_account(&self, account: u32) -> Result<(), OutputManagerStorageError>;
+    /// Get the account that interactive payments are received into
+    fn get_receiving_account(&self) -> Result<u32, OutputManagerStorageError>;
 
     /// Get the output that was most recently mined, ordered descending by mined height
     fn get_last_mined_output(&self) -> Result<Option<DbWalletOutput>, OutputManagerStorageError>;
@@ -92,8 +123,27 @@ pub trait OutputManagerBackend: Send + Sync + Clone {
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError>;
     /// Reinstate a cancelled inbound output
     fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
+    /// Make `outputs` the pending outputs to be received in transaction `tx_id`, such as the change output of a fee
+    /// bumped version of it. Its other pending outputs to be received are marked as cancelled inbound, and any of
+    /// `outputs` that was cancelled before is reinstated rather than added again.
+    fn replace_pending_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        outputs: Vec<DbWalletOutput>,
+    ) -> Result<(), OutputManagerStorageError>;
     /// Return the available, time locked, pending incoming and pending outgoing balance
     fn get_balance(&self, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    /// Reconstruct the confirmed balance at a point in the past from the output and transaction history
+    fn get_balance_at(&self, cutoff: BalanceCutoff) -> Result<HistoricalBalance, OutputManagerStorageError>;
+    /// Break down the available balance by output origin or, if `mined_since` (unix timestamp in seconds) is given,
+    /// the value of all outputs received in blocks mined since then, whether spent or not
+    fn get_balance_by_origin(&self, mined_since: Option<u64>) -> Result<BalanceByOrigin, OutputManagerStorageError>;
+    /// Return the balance of a single wallet account
+    fn get_account_balance(&self, account: u32, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError>;
+    /// Store a newly derived subaddress
+    fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError>;
+    /// Fetch all derived subaddresses, ordered by index
+    fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError>;
     /// Import unvalidated output
     fn add_unvalidated_output(&self, output: DbWalletOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError>;
     fn fetch_unspent_outputs_for_spending(
@@ -22,6 +22,7 @@
 
 mod backend;
 use std::{
+    collections::BTreeMap,
     fmt::{Debug, Display, Error, Formatter},
     sync::Arc,
 };
@@ -41,9 +42,11 @@ use tari_utilities::hex::Hex;
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
     input_selection::UtxoSelectionCriteria,
-    service::Balance,
+    script_lock::ScriptLock,
+    service::{Balance, BalanceByOrigin, BalanceCutoff, HistoricalBalance, MaturityScheduleEntry, ScriptLockedOutput},
     storage::{
-        models::{DbWalletOutput, KnownOneSidedPaymentScript},
+        models::{ArchivedOutput, DbWalletOutput, KnownOneSidedPaymentScript, Subaddress, WalletAccount},
+        OutputSource,
         OutputStatus,
     },
 };
@@ -199,6 +202,67 @@ where T: OutputManagerBackend + 'static
         self.db.get_balance(current_tip_for_time_lock_calculation)
     }
 
+    pub fn get_balance_at(&self, cutoff: BalanceCutoff) -> Result<HistoricalBalance, OutputManagerStorageError> {
+        self.db.get_balance_at(cutoff)
+    }
+
+    pub fn get_balance_by_origin(
+        &self,
+        mined_since: Option<u64>,
+    ) -> Result<BalanceByOrigin, OutputManagerStorageError> {
+        self.db.get_balance_by_origin(mined_since)
+    }
+
+    pub fn get_account_balance(
+        &self,
+        account: u32,
+        current_tip_for_time_lock_calculation: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        self.db
+            .get_account_balance(account, current_tip_for_time_lock_calculation)
+    }
+
+    /// Groups the unspent outputs that cannot be spent at `tip` by the height at which they become spendable, ordered
+    /// by that height.
+    pub fn get_maturity_schedule(&self, tip: u64) -> Result<Vec<MaturityScheduleEntry>, OutputManagerStorageError> {
+        let mut schedule = BTreeMap::<u64, MaturityScheduleEntry>::new();
+        for output in self.get_timelocked_outputs(tip)? {
+            let spendable_at_height = output.spendable_at_height();
+            let entry = schedule
+                .entry(spendable_at_height)
+                .or_insert_with(|| MaturityScheduleEntry {
+                    spendable_at_height,
+                    ..Default::default()
+                });
+            entry.amount += output.wallet_output.value;
+            entry.num_outputs += 1;
+            if output.source == OutputSource::Coinbase {
+                entry.coinbase_amount += output.wallet_output.value;
+            }
+        }
+        Ok(schedule.into_values().collect())
+    }
+
+    /// Returns the unspent outputs whose script carries a height lock and/or hash lock, ordered by the height at which
+    /// they become claimable
+    pub fn get_script_locked_outputs(&self) -> Result<Vec<ScriptLockedOutput>, OutputManagerStorageError> {
+        let mut outputs = self
+            .fetch_all_unspent_outputs()?
+            .into_iter()
+            .filter_map(|output| {
+                let script_lock = ScriptLock::from_script(&output.wallet_output.script);
+                script_lock.is_locked().then(|| ScriptLockedOutput {
+                    claimable_at_height: output.spendable_at_height(),
+                    hash_lock: script_lock.hash_lock,
+                    value: output.wallet_output.value,
+                    commitment: output.commitment,
+                })
+            })
+            .collect::<Vec<_>>();
+        outputs.sort_by_key(|o| o.claimable_at_height);
+        Ok(outputs)
+    }
+
     /// This method is called when a transaction is built to be sent. It will encumber unspent outputs against a pending
     /// transaction in the short term.
     pub fn encumber_outputs(
@@ -332,10 +396,67 @@ where T: OutputManagerBackend + 'static
         self.db.revalidate_unspent_output(&commitment)
     }
 
+    pub fn set_outputs_frozen(
+        &self,
+        commitments: &[Commitment],
+        frozen: bool,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.set_outputs_frozen(commitments, frozen)
+    }
+
+    pub fn set_output_label(
+        &self,
+        commitment: &Commitment,
+        label: Option<String>,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.set_output_label(commitment, label, metadata)
+    }
+
+    pub fn archive_spent_outputs(&self, spent_height: u64) -> Result<usize, OutputManagerStorageError> {
+        self.db.archive_spent_outputs(spent_height)
+    }
+
+    pub fn fetch_archived_outputs(&self) -> Result<Vec<ArchivedOutput>, OutputManagerStorageError> {
+        self.db.fetch_archived_outputs()
+    }
+
+    pub fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerStorageError> {
+        self.db.create_account(name)
+    }
+
+    pub fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError> {
+        self.db.fetch_accounts()
+    }
+
+    pub fn set_receiving_account(&self, account: u32) -> Result<(), OutputManagerStorageError> {
+        self.db.set_receiving_account(account)
+    }
+
+    pub fn get_receiving_account(&self) -> Result<u32, OutputManagerStorageError> {
+        self.db.get_receiving_account()
+    }
+
+    pub fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError> {
+        self.db.add_subaddress(subaddress)
+    }
+
+    pub fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError> {
+        self.db.fetch_subaddresses()
+    }
+
     pub fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         self.db.reinstate_cancelled_inbound_output(tx_id)
     }
 
+    pub fn replace_pending_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        outputs: Vec<DbWalletOutput>,
+    ) -> Result<(), OutputManagerStorageError> {
+        self.db.replace_pending_transaction_outputs(tx_id, outputs)
+    }
+
     pub fn get_all_known_one_sided_payment_scripts(
         &self,
     ) -> Result<Vec<KnownOneSidedPaymentScript>, OutputManagerStorageError> {
@@ -421,6 +542,16 @@ where T: OutputManagerBackend + 'static
         Ok(())
     }
 
+    pub fn set_outputs_in_height_range_to_be_revalidated(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), OutputManagerStorageError> {
+        let db = self.db.clone();
+        db.set_outputs_in_height_range_to_be_revalidated(start_height, end_height)?;
+        Ok(())
+    }
+
     pub fn update_last_validation_timestamp(&self, hash: HashOutput) -> Result<(), OutputManagerStorageError> {
         let db = self.db.clone();
         db.update_last_validation_timestamp(hash)?;
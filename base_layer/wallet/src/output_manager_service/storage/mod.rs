@@ -29,5 +29,10 @@ pub mod output_status;
 #[allow(clippy::cast_possible_wrap)]
 pub mod sqlite_db;
 
+// converting between unsigned and signed is okay here as we do it both ways
+#[cfg(feature = "postgres")]
+#[allow(clippy::cast_possible_wrap)]
+pub mod postgres_db;
+
 pub use output_source::OutputSource;
 pub use output_status::OutputStatus;
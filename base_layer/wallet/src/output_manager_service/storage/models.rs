@@ -20,22 +20,25 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::cmp::Ordering;
+use std::{cmp::Ordering, collections::BTreeMap};
 
 use chrono::NaiveDateTime;
 use derivative::Derivative;
+use serde::{Deserialize, Serialize};
 use tari_common_types::{
     transaction::TxId,
-    types::{BlockHash, Commitment, HashOutput},
+    types::{BlockHash, Commitment, HashOutput, PublicKey},
 };
 use tari_core::transactions::{
-    key_manager::{TariKeyId, TransactionKeyManagerInterface},
+    key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+    tari_amount::MicroMinotari,
     transaction_components::WalletOutput,
 };
 use tari_script::{ExecutionStack, TariScript};
 
 use crate::output_manager_service::{
     error::OutputManagerStorageError,
+    script_lock::ScriptLock,
     storage::{OutputSource, OutputStatus},
 };
 
@@ -56,6 +59,14 @@ pub struct DbWalletOutput {
     pub source: OutputSource,
     pub received_in_tx_id: Option<TxId>,
     pub spent_in_tx_id: Option<TxId>,
+    /// Frozen outputs are never selected as inputs until they are unfrozen
+    pub frozen: bool,
+    /// A user supplied description of where this output came from, e.g. "mining income"
+    pub label: Option<String>,
+    /// Arbitrary user supplied key/value data attached to this output
+    pub metadata: BTreeMap<String, String>,
+    /// The index of the subaddress this output was paid to, if it was a one-sided payment to a subaddress
+    pub subaddress_index: Option<u64>,
 }
 
 impl DbWalletOutput {
@@ -82,8 +93,35 @@ impl DbWalletOutput {
             source,
             received_in_tx_id,
             spent_in_tx_id,
+            frozen: false,
+            label: None,
+            metadata: BTreeMap::new(),
+            subaddress_index: None,
         })
     }
+
+    /// The first block height at which this output may be spent, taking both the output maturity (which includes the
+    /// coinbase lock height) and the script lock height into account
+    pub fn spendable_at_height(&self) -> u64 {
+        self.wallet_output.features.maturity.max(self.script_lock_height())
+    }
+
+    /// The script lock height of this output, including any height checked by its script
+    pub fn script_lock_height(&self) -> u64 {
+        ScriptLock::from_script(&self.wallet_output.script)
+            .lock_height
+            .unwrap_or_default()
+            .max(self.wallet_output.script_lock_height)
+    }
+
+    /// The wallet account this output belongs to, which follows from the key branch of its spending key. Outputs with
+    /// imported spending keys belong to the default account 0.
+    pub fn account(&self) -> u32 {
+        match &self.wallet_output.spending_key_id {
+            TariKeyId::Managed { branch, .. } => TransactionKeyManagerBranch::account_of_branch_key(branch),
+            _ => 0,
+        }
+    }
 }
 
 impl From<DbWalletOutput> for WalletOutput {
@@ -112,6 +150,69 @@ impl Ord for DbWalletOutput {
 
 impl Eq for DbWalletOutput {}
 
+/// The record kept of a spent output once it has been archived out of the outputs table. The spending keys are
+/// dropped, but enough is kept to reproduce the wallet's history for audits and exports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedOutput {
+    pub commitment: Commitment,
+    pub hash: HashOutput,
+    pub value: MicroMinotari,
+    pub source: OutputSource,
+    pub received_in_tx_id: Option<TxId>,
+    pub spent_in_tx_id: Option<TxId>,
+    pub mined_height: Option<u64>,
+    pub mined_in_block: Option<BlockHash>,
+    pub mined_timestamp: Option<NaiveDateTime>,
+    pub spent_height: Option<u64>,
+    pub spent_in_block: Option<BlockHash>,
+    pub label: Option<String>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl From<DbWalletOutput> for ArchivedOutput {
+    fn from(output: DbWalletOutput) -> Self {
+        Self {
+            commitment: output.commitment,
+            hash: output.hash,
+            value: output.wallet_output.value,
+            source: output.source,
+            received_in_tx_id: output.received_in_tx_id,
+            spent_in_tx_id: output.spent_in_tx_id,
+            mined_height: output.mined_height,
+            mined_in_block: output.mined_in_block,
+            mined_timestamp: output.mined_timestamp,
+            spent_height: output.marked_deleted_at_height,
+            spent_in_block: output.marked_deleted_in_block,
+            label: output.label,
+            metadata: output.metadata,
+        }
+    }
+}
+
+/// A separate account within the wallet. Each account has its own key branches and balance, and its outputs are only
+/// ever spent together with outputs of the same account. The default account 0 always exists and is not stored.
+/// Accounts share the wallet address, interactive payments are received into the account flagged as `receiving`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletAccount {
+    pub id: u32,
+    pub name: String,
+    /// Whether interactive payments received by the wallet are paid into this account
+    pub receiving: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// A receive address derived from the wallet seed at `index` of the subaddress key branch. One-sided payments to any
+/// subaddress are detected by the wallet and recorded against the index that was paid. The same index always gives the
+/// same address, so recreating subaddresses in order restores them after a recovery from seed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Subaddress {
+    pub index: u64,
+    pub public_key: PublicKey,
+    /// A user supplied description, e.g. the invoice the subaddress was handed out for
+    pub label: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone)]
@@ -27,12 +27,13 @@ use core::{
     },
 };
 
+use serde::{Deserialize, Serialize};
 use strum_macros::Display;
 
 use crate::output_manager_service::error::OutputManagerStorageError;
 
 // The source of where the output came from
-#[derive(Copy, Clone, Debug, PartialEq, Display, Default)]
+#[derive(Copy, Clone, Debug, PartialEq, Display, Default, Serialize, Deserialize)]
 pub enum OutputSource {
     Unknown,
     Coinbase,
@@ -43,6 +44,7 @@ pub enum OutputSource {
     StealthOneSided,
     Refund,
     AtomicSwap,
+    Change,
 }
 
 impl TryFrom<i32> for OutputSource {
@@ -58,9 +60,10 @@ impl TryFrom<i32> for OutputSource {
             5 => OutputSource::StealthOneSided,
             6 => OutputSource::Refund,
             7 => OutputSource::AtomicSwap,
+            8 => OutputSource::Change,
             _ => {
                 return Err(OutputManagerStorageError::ConversionError {
-                    reason: "Was expecting value between 0 and 7 for OutputSource".to_string(),
+                    reason: "Was expecting value between 0 and 8 for OutputSource".to_string(),
                 })
             },
         })
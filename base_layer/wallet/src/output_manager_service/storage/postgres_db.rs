@@ -0,0 +1,1464 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::BTreeMap, convert::TryFrom};
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::{pg::PgConnection, prelude::*, sql_query};
+use log::*;
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
+use tari_common_types::{
+    transaction::TxId,
+    types::{Commitment, FixedHash},
+};
+use tari_core::transactions::{
+    tari_amount::MicroMinotari,
+    transaction_components::{OutputType, TransactionOutput},
+};
+use tari_crypto::tari_utilities::{hex::Hex, ByteArray};
+
+use crate::{
+    output_manager_service::{
+        error::OutputManagerStorageError,
+        input_selection::UtxoSelectionMode,
+        service::{Balance, BalanceByOrigin, BalanceCutoff, HistoricalBalance},
+        storage::{
+            database::{
+                DbKey,
+                DbKeyValuePair,
+                DbValue,
+                OutputBackendQuery,
+                OutputManagerBackend,
+                SortDirection,
+                WriteOperation,
+            },
+            models::{ArchivedOutput, DbWalletOutput, Subaddress, WalletAccount},
+            sqlite_db::{
+                ArchivedOutputSql,
+                KnownOneSidedPaymentScriptSql,
+                NewOutputSql,
+                OutputSql,
+                SubaddressSql,
+                UpdateOutput,
+                UpdateOutputSql,
+                WalletAccountSql,
+            },
+            OutputSource,
+            OutputStatus,
+        },
+        UtxoSelectionCriteria,
+        UtxoSelectionFilter,
+        UtxoSelectionOrdering,
+    },
+    schema::{accounts, archived_outputs, known_one_sided_payment_scripts, outputs, subaddresses},
+    storage::postgres_utilities::WalletPostgresConnection,
+};
+
+const LOG_TARGET: &str = "wallet::output_manager_service::database::postgres";
+
+/// A PostgreSQL backend for the Output Manager Service. Rows are stored exactly as in
+/// [OutputManagerSqliteDatabase](super::sqlite_db::OutputManagerSqliteDatabase); only the queries differ.
+#[derive(Clone)]
+pub struct OutputManagerPostgresDatabase {
+    database_connection: WalletPostgresConnection,
+}
+
+impl OutputManagerPostgresDatabase {
+    pub fn new(database_connection: WalletPostgresConnection) -> Self {
+        Self { database_connection }
+    }
+
+    fn insert(&self, key_value_pair: DbKeyValuePair, conn: &mut PgConnection) -> Result<(), OutputManagerStorageError> {
+        let new_output = match key_value_pair {
+            DbKeyValuePair::UnspentOutput(c, o) => {
+                if find_by_commitment(&c.to_vec(), Some(false), conn)?.is_some() {
+                    return Err(OutputManagerStorageError::DuplicateOutput);
+                }
+                NewOutputSql::new(*o, OutputStatus::Unspent, None, None)?
+            },
+            DbKeyValuePair::UnspentOutputWithTxId(c, (tx_id, o)) => {
+                if find_by_commitment(&c.to_vec(), Some(false), conn)?.is_some() {
+                    return Err(OutputManagerStorageError::DuplicateOutput);
+                }
+                NewOutputSql::new(*o, OutputStatus::Unspent, Some(tx_id), None)?
+            },
+            DbKeyValuePair::OutputToBeReceived(c, (tx_id, o, coinbase_block_height)) => {
+                if find_by_commitment(&c.to_vec(), Some(false), conn)?.is_some() {
+                    return Err(OutputManagerStorageError::DuplicateOutput);
+                }
+                NewOutputSql::new(
+                    *o,
+                    OutputStatus::EncumberedToBeReceived,
+                    Some(tx_id),
+                    coinbase_block_height,
+                )?
+            },
+            DbKeyValuePair::KnownOneSidedPaymentScripts(script) => {
+                let script_sql = KnownOneSidedPaymentScriptSql::from_known_one_sided_payment_script(script)?;
+                let exists = known_one_sided_payment_scripts::table
+                    .filter(known_one_sided_payment_scripts::script_hash.eq(&script_sql.script_hash))
+                    .count()
+                    .get_result::<i64>(conn)? >
+                    0;
+                if exists {
+                    return Err(OutputManagerStorageError::DuplicateScript);
+                }
+                diesel::insert_into(known_one_sided_payment_scripts::table)
+                    .values(script_sql)
+                    .execute(conn)?;
+                return Ok(());
+            },
+        };
+        diesel::insert_into(outputs::table).values(new_output).execute(conn)?;
+        Ok(())
+    }
+}
+
+impl OutputManagerBackend for OutputManagerPostgresDatabase {
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        let result = match key {
+            DbKey::SpentOutput(k) => find_by_spending_key(k, OutputStatus::Spent, &mut conn)?
+                .map(|o| o.to_db_wallet_output())
+                .transpose()?
+                .map(|o| DbValue::SpentOutput(Box::new(o))),
+            DbKey::UnspentOutput(k) => find_by_spending_key(k, OutputStatus::Unspent, &mut conn)?
+                .map(|o| o.to_db_wallet_output())
+                .transpose()?
+                .map(|o| DbValue::UnspentOutput(Box::new(o))),
+            DbKey::UnspentOutputHash(hash) => outputs::table
+                .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                .filter(outputs::hash.eq(hash.as_slice()))
+                .first::<OutputSql>(&mut conn)
+                .optional()?
+                .map(|o| o.to_db_wallet_output())
+                .transpose()?
+                .map(|o| DbValue::UnspentOutput(Box::new(o))),
+            DbKey::AnyOutputByCommitment(commitment) => find_by_commitment(&commitment.to_vec(), None, &mut conn)?
+                .map(|o| o.to_db_wallet_output())
+                .transpose()?
+                .map(|o| DbValue::AnyOutput(Box::new(o))),
+            DbKey::OutputsByTxIdAndStatus(tx_id, status) => {
+                let outputs = outputs::table
+                    .filter(
+                        outputs::received_in_tx_id
+                            .eq(tx_id.as_i64_wrapped())
+                            .or(outputs::spent_in_tx_id.eq(tx_id.as_i64_wrapped())),
+                    )
+                    .filter(outputs::status.eq(*status as i32))
+                    .load::<OutputSql>(&mut conn)?;
+                Some(DbValue::AnyOutputs(to_db_wallet_outputs(outputs)?))
+            },
+            DbKey::UnspentOutputs => {
+                let outputs = index_status(
+                    vec![OutputStatus::Unspent, OutputStatus::UnspentMinedUnconfirmed],
+                    &mut conn,
+                )?;
+                Some(DbValue::UnspentOutputs(to_db_wallet_outputs(outputs)?))
+            },
+            DbKey::SpentOutputs => {
+                let outputs = index_status(vec![OutputStatus::Spent], &mut conn)?;
+                Some(DbValue::SpentOutputs(to_db_wallet_outputs(outputs)?))
+            },
+            DbKey::TimeLockedUnspentOutputs(tip) => {
+                let tip = *tip as i64;
+                let outputs = outputs::table
+                    .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                    .filter(outputs::maturity.gt(tip).or(outputs::script_lock_height.gt(tip)))
+                    .load::<OutputSql>(&mut conn)?;
+                Some(DbValue::UnspentOutputs(to_db_wallet_outputs(outputs)?))
+            },
+            DbKey::InvalidOutputs => {
+                let outputs = index_status(vec![OutputStatus::Invalid], &mut conn)?;
+                Some(DbValue::InvalidOutputs(to_db_wallet_outputs(outputs)?))
+            },
+            DbKey::KnownOneSidedPaymentScripts => {
+                let scripts =
+                    known_one_sided_payment_scripts::table.load::<KnownOneSidedPaymentScriptSql>(&mut conn)?;
+                Some(DbValue::KnownOneSidedPaymentScripts(
+                    scripts
+                        .into_iter()
+                        .map(KnownOneSidedPaymentScriptSql::to_known_one_sided_payment_script)
+                        .collect::<Result<Vec<_>, _>>()?,
+                ))
+            },
+        };
+
+        Ok(result)
+    }
+
+    fn fetch_with_features(&self, output_type: OutputType) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = sql_query("SELECT * FROM outputs WHERE output_type & $1 = $1 ORDER BY id")
+            .bind::<diesel::sql_types::Integer, _>(i32::from(output_type.as_byte()))
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn fetch_sorted_unspent_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = outputs::table
+            .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+            .order(outputs::id.asc())
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn fetch_mined_unspent_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = outputs::table
+            // Return outputs not marked as deleted or confirmed
+            .filter(
+                outputs::marked_deleted_in_block
+                    .is_null()
+                    .or(outputs::status.eq(OutputStatus::SpentMinedUnconfirmed as i32)),
+            )
+            // Only return mined
+            .filter(outputs::mined_in_block.is_not_null().and(outputs::mined_height.is_not_null()))
+            .order(outputs::id.asc())
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn fetch_invalid_outputs(&self, timestamp: i64) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let timestamp =
+            NaiveDateTime::from_timestamp_opt(timestamp, 0).ok_or(OutputManagerStorageError::ConversionError {
+                reason: format!("Could not create timestamp from: {}", timestamp),
+            })?;
+        let outputs = outputs::table
+            .filter(outputs::status.eq_any(vec![
+                OutputStatus::Invalid as i32,
+                OutputStatus::CancelledInbound as i32,
+            ]))
+            .filter(
+                outputs::last_validation_timestamp
+                    .le(timestamp)
+                    .or(outputs::last_validation_timestamp.is_null()),
+            )
+            .order(outputs::id.asc())
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn fetch_unspent_mined_unconfirmed_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = outputs::table
+            .filter(
+                outputs::status
+                    .eq(OutputStatus::UnspentMinedUnconfirmed as i32)
+                    .or(outputs::mined_in_block.is_null())
+                    .or(outputs::mined_height.is_null()),
+            )
+            .order(outputs::id.asc())
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        match op {
+            WriteOperation::Insert(kvp) => {
+                self.insert(kvp, &mut conn)?;
+                Ok(None)
+            },
+            WriteOperation::Remove(DbKey::AnyOutputByCommitment(commitment)) => {
+                // Used by coinbase when mining.
+                conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+                    match find_by_commitment(&commitment.to_vec(), None, conn)? {
+                        Some(o) => {
+                            diesel::delete(outputs::table.filter(outputs::id.eq(o.id)))
+                                .execute(conn)
+                                .num_rows_affected_or_not_found(1)?;
+                            Ok(Some(DbValue::AnyOutput(Box::new(o.to_db_wallet_output()?))))
+                        },
+                        None => Ok(None),
+                    }
+                })
+            },
+            WriteOperation::Remove(
+                DbKey::SpentOutput(_) |
+                DbKey::UnspentOutputHash(_) |
+                DbKey::UnspentOutput(_) |
+                DbKey::UnspentOutputs |
+                DbKey::SpentOutputs |
+                DbKey::InvalidOutputs |
+                DbKey::TimeLockedUnspentOutputs(_) |
+                DbKey::KnownOneSidedPaymentScripts |
+                DbKey::OutputsByTxIdAndStatus(_, _),
+            ) => Err(OutputManagerStorageError::OperationNotSupported),
+        }
+    }
+
+    fn fetch_pending_incoming_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = index_status(
+            vec![
+                OutputStatus::EncumberedToBeReceived,
+                OutputStatus::UnspentMinedUnconfirmed,
+                OutputStatus::ShortTermEncumberedToBeReceived,
+            ],
+            &mut conn,
+        )?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn set_received_output_mined_height_and_status(
+        &self,
+        hash: FixedHash,
+        mined_height: u64,
+        mined_in_block: FixedHash,
+        confirmed: bool,
+        mined_timestamp: u64,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let status = if confirmed {
+            OutputStatus::Unspent as i32
+        } else {
+            OutputStatus::UnspentMinedUnconfirmed as i32
+        };
+        let hash = hash.to_vec();
+        let mined_in_block = mined_in_block.to_vec();
+        let timestamp = NaiveDateTime::from_timestamp_opt(mined_timestamp as i64, 0).ok_or(
+            OutputManagerStorageError::ConversionError {
+                reason: format!("Could not create timestamp mined_timestamp: {}", mined_timestamp),
+            },
+        )?;
+        // An output that is already being spent by a child-pays-for-parent transaction keeps its spending status when
+        // its parent is mined
+        let is_pending_spend = outputs::table
+            .filter(outputs::hash.eq(&hash))
+            .filter(outputs::status.eq_any(vec![
+                OutputStatus::ShortTermEncumberedToBeSpent as i32,
+                OutputStatus::EncumberedToBeSpent as i32,
+            ]))
+            .count()
+            .get_result::<i64>(&mut conn)? >
+            0;
+        if is_pending_spend {
+            diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
+                .set((
+                    outputs::mined_height.eq(mined_height as i64),
+                    outputs::mined_in_block.eq(mined_in_block),
+                    outputs::mined_timestamp.eq(timestamp),
+                    outputs::last_validation_timestamp.eq::<Option<NaiveDateTime>>(None),
+                ))
+                .execute(&mut conn)
+                .num_rows_affected_or_not_found(1)?;
+        } else {
+            diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
+                .set((
+                    outputs::mined_height.eq(mined_height as i64),
+                    outputs::mined_in_block.eq(mined_in_block),
+                    outputs::status.eq(status),
+                    outputs::mined_timestamp.eq(timestamp),
+                    outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                    outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+                    outputs::last_validation_timestamp.eq::<Option<NaiveDateTime>>(None),
+                ))
+                .execute(&mut conn)
+                .num_rows_affected_or_not_found(1)?;
+        }
+
+        Ok(())
+    }
+
+    fn set_output_to_unmined_and_invalid(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(outputs::table.filter(outputs::hash.eq(hash.to_vec())))
+            .set((
+                outputs::mined_height.eq::<Option<i64>>(None),
+                outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
+                outputs::status.eq(OutputStatus::Invalid as i32),
+                outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
+                outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+            ))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn update_last_validation_timestamp(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(outputs::table.filter(outputs::hash.eq(hash.to_vec())))
+            .set(outputs::last_validation_timestamp.eq(NaiveDateTime::from_timestamp_opt(Utc::now().timestamp(), 0)))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn set_outputs_to_be_revalidated(&self) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let result = diesel::update(outputs::table)
+            .set((
+                outputs::mined_height.eq::<Option<i64>>(None),
+                outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
+                outputs::status.eq(OutputStatus::Invalid as i32),
+                outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
+                outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+            ))
+            .execute(&mut conn)?;
+        trace!(target: LOG_TARGET, "rows updated: {:?}", result);
+        Ok(())
+    }
+
+    fn set_outputs_in_height_range_to_be_revalidated(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let (start_height, end_height) = (start_height as i64, end_height as i64);
+        let result = diesel::update(
+            outputs::table.filter(
+                outputs::mined_height
+                    .between(start_height, end_height)
+                    .or(outputs::marked_deleted_at_height.between(start_height, end_height)),
+            ),
+        )
+        .set((
+            outputs::mined_height.eq::<Option<i64>>(None),
+            outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
+            outputs::status.eq(OutputStatus::Invalid as i32),
+            outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
+            outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+            outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+        ))
+        .execute(&mut conn)?;
+        trace!(target: LOG_TARGET, "rows updated: {:?}", result);
+        Ok(())
+    }
+
+    fn mark_output_as_spent(
+        &self,
+        hash: FixedHash,
+        mark_deleted_at_height: u64,
+        mark_deleted_in_block: FixedHash,
+        confirmed: bool,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let status = if confirmed {
+            OutputStatus::Spent as i32
+        } else {
+            OutputStatus::SpentMinedUnconfirmed as i32
+        };
+        diesel::update(outputs::table.filter(outputs::hash.eq(hash.to_vec())))
+            .set((
+                outputs::marked_deleted_at_height.eq(mark_deleted_at_height as i64),
+                outputs::marked_deleted_in_block.eq(mark_deleted_in_block.to_vec()),
+                outputs::status.eq(status),
+            ))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn mark_output_as_unspent(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let hash = hash.to_vec();
+        debug!(target: LOG_TARGET, "mark_output_as_unspent({})", hash.to_hex());
+        diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
+            .set((
+                outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+                outputs::status.eq(OutputStatus::Unspent as i32),
+            ))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn short_term_encumber_outputs(
+        &self,
+        tx_id: TxId,
+        outputs_to_send: &[DbWalletOutput],
+        outputs_to_receive: &[DbWalletOutput],
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let commitments = outputs_to_send
+            .iter()
+            .map(|o| o.commitment.to_vec())
+            .collect::<Vec<_>>();
+
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            // Any output in the list without the `Unspent` status will invalidate the encumberance, unless it is a not
+            // yet mined output that a child-pays-for-parent transaction explicitly selected
+            let not_unspent = outputs::table
+                .filter(outputs::commitment.eq_any(&commitments))
+                .filter(outputs::status.ne(OutputStatus::Unspent as i32))
+                .load::<OutputSql>(conn)?;
+            for output in not_unspent {
+                let selected_as_unconfirmed = outputs_to_send.iter().any(|o| {
+                    o.commitment.as_bytes() == output.commitment.as_slice() &&
+                        o.status == OutputStatus::EncumberedToBeReceived
+                });
+                let is_unconfirmed_and_unspent = output.status == OutputStatus::EncumberedToBeReceived as i32 &&
+                    output.received_in_tx_id.is_some() &&
+                    output.spent_in_tx_id.is_none() &&
+                    output.mined_in_block.is_none();
+                if !(selected_as_unconfirmed && is_unconfirmed_and_unspent) {
+                    return Err(OutputManagerStorageError::OutputAlreadySpent);
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Encumbering unconfirmed output with Commitment: {} from TxId: {} as a child-pays-for-parent \
+                     spend in TxId: {}",
+                    output.commitment.to_hex(),
+                    TxId::from(output.received_in_tx_id.unwrap_or_default() as u64),
+                    tx_id
+                );
+            }
+
+            let count = diesel::update(outputs::table.filter(outputs::commitment.eq_any(&commitments)))
+                .set(UpdateOutputSql::from(UpdateOutput {
+                    status: Some(OutputStatus::ShortTermEncumberedToBeSpent),
+                    spent_in_tx_id: Some(Some(tx_id)),
+                    ..Default::default()
+                }))
+                .execute(conn)?;
+            if count != outputs_to_send.len() {
+                let msg = format!(
+                    "Inconsistent short term encumbering! Lengths do not match - {} vs {}",
+                    count,
+                    outputs_to_send.len()
+                );
+                error!(target: LOG_TARGET, "{}", msg);
+                return Err(OutputManagerStorageError::UnexpectedResult(msg));
+            }
+
+            let new_outputs = outputs_to_receive
+                .iter()
+                .map(|o| {
+                    NewOutputSql::new(
+                        o.clone(),
+                        OutputStatus::ShortTermEncumberedToBeReceived,
+                        Some(tx_id),
+                        None,
+                    )
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            diesel::insert_into(outputs::table).values(new_outputs).execute(conn)?;
+
+            Ok(())
+        })
+    }
+
+    fn confirm_encumbered_outputs(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            update_outputs_with_tx_id_and_status_to_new_status(
+                tx_id,
+                OutputStatus::ShortTermEncumberedToBeReceived,
+                OutputStatus::EncumberedToBeReceived,
+                conn,
+            )?;
+            update_outputs_with_tx_id_and_status_to_new_status(
+                tx_id,
+                OutputStatus::ShortTermEncumberedToBeSpent,
+                OutputStatus::EncumberedToBeSpent,
+                conn,
+            )?;
+            Ok(())
+        })
+    }
+
+    fn clear_short_term_encumberances(&self) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            diesel::update(
+                outputs::table.filter(outputs::status.eq(OutputStatus::ShortTermEncumberedToBeReceived as i32)),
+            )
+            .set((
+                outputs::status.eq(OutputStatus::CancelledInbound as i32),
+                outputs::last_validation_timestamp.eq(NaiveDateTime::from_timestamp_opt(Utc::now().timestamp(), 0)),
+            ))
+            .execute(conn)?;
+
+            diesel::update(
+                outputs::table.filter(outputs::status.eq(OutputStatus::ShortTermEncumberedToBeSpent as i32)),
+            )
+            .set(outputs::status.eq(OutputStatus::Unspent as i32))
+            .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let outputs = outputs::table
+                .filter(
+                    outputs::received_in_tx_id
+                        .eq(tx_id.as_i64_wrapped())
+                        .or(outputs::spent_in_tx_id.eq(tx_id.as_i64_wrapped())),
+                )
+                .filter(outputs::status.eq_any(vec![
+                    OutputStatus::EncumberedToBeReceived as i32,
+                    OutputStatus::EncumberedToBeSpent as i32,
+                    OutputStatus::ShortTermEncumberedToBeReceived as i32,
+                    OutputStatus::ShortTermEncumberedToBeSpent as i32,
+                ]))
+                .load::<OutputSql>(conn)?;
+
+            if outputs.is_empty() {
+                return Err(OutputManagerStorageError::ValueNotFound);
+            }
+
+            for output in &outputs {
+                if output.received_in_tx_id == Some(tx_id.as_i64_wrapped()) {
+                    info!(
+                        target: LOG_TARGET,
+                        "Cancelling pending inbound output with Commitment: {} - from TxId: {}",
+                        output.commitment.to_hex(),
+                        tx_id
+                    );
+                    update_output(
+                        output.id,
+                        UpdateOutput {
+                            status: Some(OutputStatus::CancelledInbound),
+                            last_validation_timestamp: Some(NaiveDateTime::from_timestamp_opt(
+                                Utc::now().timestamp(),
+                                0,
+                            )),
+                            ..Default::default()
+                        },
+                        conn,
+                    )?;
+                } else if output.spent_in_tx_id == Some(tx_id.as_i64_wrapped()) {
+                    info!(
+                        target: LOG_TARGET,
+                        "Cancelling pending outbound output with Commitment: {} - from TxId: {}",
+                        output.commitment.to_hex(),
+                        tx_id
+                    );
+                    // An output that was spent before it was mined goes back to waiting for its parent
+                    let status = if output.received_in_tx_id.is_some() && output.mined_in_block.is_none() {
+                        OutputStatus::EncumberedToBeReceived
+                    } else {
+                        OutputStatus::Unspent
+                    };
+                    update_output(
+                        output.id,
+                        UpdateOutput {
+                            status: Some(status),
+                            spent_in_tx_id: Some(None),
+                            // We clear these so that the output will be revalidated the next time a validation is done.
+                            mined_height: Some(None),
+                            mined_in_block: Some(None),
+                            ..Default::default()
+                        },
+                        conn,
+                    )?;
+                } else {
+                    // can only be one of the two
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    // This is typically used by a receiver after the finalized transaction has been broadcast/returned by the sender
+    // as the sender has to finalize the signature that was partially constructed by the receiver
+    fn update_output_metadata_signature(&self, output: &TransactionOutput) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let db_output = find_by_commitment(&output.commitment.to_vec(), Some(false), conn)?
+                .ok_or(OutputManagerStorageError::ValueNotFound)?;
+            update_output(
+                db_output.id,
+                // Note: Only the `ephemeral_pubkey` and `u_y` portion needs to be updated at this time as the rest was
+                // already correct
+                UpdateOutput {
+                    metadata_signature_ephemeral_pubkey: Some(output.metadata_signature.ephemeral_pubkey().to_vec()),
+                    metadata_signature_u_y: Some(output.metadata_signature.u_y().to_vec()),
+                    hash: Some(output.hash().to_vec()),
+                    ..Default::default()
+                },
+                conn,
+            )
+        })
+    }
+
+    fn revalidate_unspent_output(&self, commitment: &Commitment) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let output = find_by_commitment(&commitment.to_vec(), Some(false), conn)?
+                .ok_or(OutputManagerStorageError::ValueNotFound)?;
+            if OutputStatus::try_from(output.status)? != OutputStatus::Invalid {
+                return Err(OutputManagerStorageError::ValuesNotFound);
+            }
+            update_output(
+                output.id,
+                UpdateOutput {
+                    status: Some(OutputStatus::Unspent),
+                    ..Default::default()
+                },
+                conn,
+            )
+        })
+    }
+
+    fn set_outputs_frozen(&self, commitments: &[Commitment], frozen: bool) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            for commitment in commitments {
+                let mut query = diesel::update(outputs::table)
+                    .filter(outputs::commitment.eq(commitment.to_vec()))
+                    .into_boxed();
+                // Only outputs that could still be selected as inputs can be frozen
+                if frozen {
+                    query = query.filter(outputs::status.eq_any(vec![
+                        OutputStatus::Unspent as i32,
+                        OutputStatus::UnspentMinedUnconfirmed as i32,
+                        OutputStatus::EncumberedToBeReceived as i32,
+                    ]));
+                }
+                query
+                    .set(outputs::frozen.eq(i32::from(frozen)))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn set_output_label(
+        &self,
+        commitment: &Commitment,
+        label: Option<String>,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let metadata_json = if metadata.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(metadata).map_err(|e| OutputManagerStorageError::ConversionError {
+                    reason: format!("Could not serialize output metadata: {}", e),
+                })?,
+            )
+        };
+        diesel::update(outputs::table.filter(outputs::commitment.eq(commitment.to_vec())))
+            .set((outputs::label.eq(label), outputs::metadata_json.eq(metadata_json)))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn archive_spent_outputs(&self, spent_height: u64) -> Result<usize, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let spent_outputs = outputs::table
+                .filter(outputs::status.eq(OutputStatus::Spent as i32))
+                .filter(outputs::marked_deleted_at_height.le(spent_height as i64))
+                .load::<OutputSql>(conn)?;
+            let hashes = spent_outputs.iter().map(|o| o.hash.clone()).collect::<Vec<_>>();
+            let archived = spent_outputs
+                .into_iter()
+                .map(|o| ArchivedOutputSql::try_from(ArchivedOutput::from(o.to_db_wallet_output()?)))
+                .collect::<Result<Vec<_>, _>>()?;
+            diesel::insert_into(archived_outputs::table)
+                .values(archived)
+                .execute(conn)?;
+            diesel::delete(outputs::table.filter(outputs::hash.eq_any(&hashes))).execute(conn)?;
+            Ok(hashes.len())
+        })
+    }
+
+    fn fetch_archived_outputs(&self) -> Result<Vec<ArchivedOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        archived_outputs::table
+            .order(archived_outputs::spent_height.asc())
+            .load::<ArchivedOutputSql>(&mut conn)?
+            .iter()
+            .map(ArchivedOutputSql::to_archived_output)
+            .collect()
+    }
+
+    fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            // Ids are never reused and start at 1 as 0 is the default account
+            let max: Option<i32> = accounts::table.select(diesel::dsl::max(accounts::id)).first(conn)?;
+            let account = WalletAccountSql {
+                id: max.unwrap_or_default() + 1,
+                name,
+                receiving: 0,
+                created_at: Utc::now().naive_utc(),
+            };
+            diesel::insert_into(accounts::table)
+                .values(account.clone())
+                .execute(conn)?;
+            WalletAccount::try_from(account)
+        })
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        accounts::table
+            .order(accounts::id.asc())
+            .load::<WalletAccountSql>(&mut conn)?
+            .into_iter()
+            .map(WalletAccount::try_from)
+            .collect()
+    }
+
+    fn set_receiving_account(&self, account: u32) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let id = i32::try_from(account).map_err(|_| OutputManagerStorageError::ValueNotFound)?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            // The default account 0 clears the flag on all stored accounts
+            diesel::update(accounts::table)
+                .set(accounts::receiving.eq(0))
+                .execute(conn)?;
+            if id != 0 {
+                diesel::update(accounts::table.filter(accounts::id.eq(id)))
+                    .set(accounts::receiving.eq(1))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn get_receiving_account(&self) -> Result<u32, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let id = accounts::table
+            .filter(accounts::receiving.eq(1))
+            .select(accounts::id)
+            .first::<i32>(&mut conn)
+            .optional()?
+            .unwrap_or_default();
+        u32::try_from(id).map_err(|_| OutputManagerStorageError::ConversionError {
+            reason: format!("Invalid account id: {}", id),
+        })
+    }
+
+    fn get_last_mined_output(&self) -> Result<Option<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        outputs::table
+            .filter(outputs::mined_height.is_not_null())
+            .order(outputs::mined_height.desc())
+            .first::<OutputSql>(&mut conn)
+            .optional()?
+            .map(OutputSql::to_db_wallet_output)
+            .transpose()
+    }
+
+    fn get_last_spent_output(&self) -> Result<Option<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        outputs::table
+            .filter(outputs::marked_deleted_at_height.is_not_null())
+            .order(outputs::marked_deleted_at_height.desc())
+            .first::<OutputSql>(&mut conn)
+            .optional()?
+            .map(OutputSql::to_db_wallet_output)
+            .transpose()
+    }
+
+    fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        if abandoned {
+            debug!(
+                target: LOG_TARGET,
+                "set_coinbase_abandoned(TxID: {}) as {}", tx_id, abandoned
+            );
+            diesel::update(
+                outputs::table.filter(
+                    outputs::received_in_tx_id
+                        .eq(tx_id.as_i64_wrapped())
+                        .and(outputs::coinbase_block_height.is_not_null()),
+                ),
+            )
+            .set(outputs::status.eq(OutputStatus::AbandonedCoinbase as i32))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        } else {
+            update_outputs_with_tx_id_and_status_to_new_status(
+                tx_id,
+                OutputStatus::AbandonedCoinbase,
+                OutputStatus::EncumberedToBeReceived,
+                &mut conn,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn reinstate_cancelled_inbound_output(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        update_outputs_with_tx_id_and_status_to_new_status(
+            tx_id,
+            OutputStatus::CancelledInbound,
+            OutputStatus::EncumberedToBeReceived,
+            &mut conn,
+        )
+    }
+
+    fn replace_pending_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        outputs: Vec<DbWalletOutput>,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let commitments = outputs.iter().map(|o| o.commitment.to_vec()).collect::<Vec<_>>();
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            diesel::update(
+                outputs::table
+                    .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                    .filter(outputs::status.eq(OutputStatus::EncumberedToBeReceived as i32))
+                    .filter(outputs::commitment.ne_all(&commitments)),
+            )
+            .set(outputs::status.eq(OutputStatus::CancelledInbound as i32))
+            .execute(conn)?;
+
+            for output in outputs {
+                let commitment = output.commitment.to_vec();
+                if find_by_commitment(&commitment, None, conn)?.is_some() {
+                    diesel::update(
+                        outputs::table
+                            .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                            .filter(outputs::commitment.eq(&commitment))
+                            .filter(outputs::status.eq(OutputStatus::CancelledInbound as i32)),
+                    )
+                    .set(outputs::status.eq(OutputStatus::EncumberedToBeReceived as i32))
+                    .execute(conn)?;
+                } else {
+                    diesel::insert_into(outputs::table)
+                        .values(NewOutputSql::new(
+                            output,
+                            OutputStatus::EncumberedToBeReceived,
+                            Some(tx_id),
+                            None,
+                        )?)
+                        .execute(conn)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    fn get_balance(
+        &self,
+        current_tip_for_time_lock_calculation: Option<u64>,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        get_balance(current_tip_for_time_lock_calculation, &mut conn)
+    }
+
+    fn get_balance_at(&self, cutoff: BalanceCutoff) -> Result<HistoricalBalance, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let height = match cutoff {
+            BalanceCutoff::Height(height) => Some(height),
+            BalanceCutoff::Timestamp(timestamp) => {
+                find_last_activity_height_at_or_before(to_naive_date_time(timestamp)?, &mut conn)?
+            },
+        };
+        match height {
+            Some(height) => get_balance_at_height(height, &mut conn),
+            None => Ok(HistoricalBalance {
+                height: None,
+                balance: MicroMinotari::zero(),
+                num_outputs: 0,
+            }),
+        }
+    }
+
+    fn get_balance_by_origin(&self, mined_since: Option<u64>) -> Result<BalanceByOrigin, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mined_since = mined_since.map(to_naive_date_time).transpose()?;
+        get_balance_by_origin(mined_since, &mut conn)
+    }
+
+    fn get_account_balance(&self, account: u32, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = outputs::table
+            .filter(outputs::account_id.eq(account as i32))
+            .select((
+                outputs::status,
+                outputs::source,
+                outputs::value,
+                outputs::maturity,
+                outputs::script_lock_height,
+            ))
+            .load::<(i32, i32, i64, i64, i64)>(&mut conn)?;
+
+        let mut balance = Balance {
+            available_balance: MicroMinotari::zero(),
+            time_locked_balance: tip.map(|_| MicroMinotari::zero()),
+            pending_incoming_balance: MicroMinotari::zero(),
+            pending_outgoing_balance: MicroMinotari::zero(),
+        };
+        for (status, source, value, maturity, script_lock_height) in outputs {
+            let value = MicroMinotari::from(value as u64);
+            match OutputStatus::try_from(status)? {
+                OutputStatus::Unspent => {
+                    balance.available_balance += value;
+                    if let (Some(tip), Some(time_locked)) = (tip, balance.time_locked_balance.as_mut()) {
+                        if maturity > tip as i64 || script_lock_height > tip as i64 {
+                            *time_locked += value;
+                        }
+                    }
+                },
+                OutputStatus::EncumberedToBeReceived if source == OutputSource::Coinbase as i32 => {},
+                OutputStatus::EncumberedToBeReceived |
+                OutputStatus::ShortTermEncumberedToBeReceived |
+                OutputStatus::UnspentMinedUnconfirmed => balance.pending_incoming_balance += value,
+                OutputStatus::EncumberedToBeSpent |
+                OutputStatus::ShortTermEncumberedToBeSpent |
+                OutputStatus::SpentMinedUnconfirmed => balance.pending_outgoing_balance += value,
+                _ => {},
+            }
+        }
+        Ok(balance)
+    }
+
+    fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(subaddresses::table)
+            .values(SubaddressSql::try_from(subaddress)?)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        subaddresses::table
+            .order(subaddresses::subaddress_index.asc())
+            .load::<SubaddressSql>(&mut conn)?
+            .into_iter()
+            .map(Subaddress::try_from)
+            .collect()
+    }
+
+    fn add_unvalidated_output(&self, output: DbWalletOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        if find_by_commitment(&output.commitment.to_vec(), Some(false), &mut conn)?.is_some() {
+            return Err(OutputManagerStorageError::DuplicateOutput);
+        }
+        diesel::insert_into(outputs::table)
+            .values(NewOutputSql::new(
+                output,
+                OutputStatus::EncumberedToBeReceived,
+                Some(tx_id),
+                None,
+            )?)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    /// Retrieves UTXOs than can be spent, sorted by priority, then value from smallest to largest.
+    fn fetch_unspent_outputs_for_spending(
+        &self,
+        selection_criteria: &UtxoSelectionCriteria,
+        amount: u64,
+        tip_height: Option<u64>,
+    ) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let i64_tip_height = tip_height.and_then(|h| i64::try_from(h).ok()).unwrap_or(i64::MAX);
+        let i64_value = i64::try_from(selection_criteria.min_dust).unwrap_or(i64::MAX);
+
+        // Unconfirmed outputs are only selected when explicitly asked for by a child-pays-for-parent spend
+        let status = if selection_criteria.filter.is_unconfirmed() {
+            OutputStatus::EncumberedToBeReceived
+        } else {
+            OutputStatus::Unspent
+        };
+        let mut query = outputs::table
+            .into_boxed()
+            .filter(outputs::status.eq(status as i32))
+            .filter(outputs::frozen.eq(0))
+            .filter(outputs::value.gt(i64_value))
+            .filter(outputs::account_id.eq(selection_criteria.account as i32))
+            .order_by(outputs::spending_priority.desc());
+
+        // NOTE: Safe mode presets `script_lock_height` and `maturity` filters for all queries
+        if selection_criteria.mode == UtxoSelectionMode::Safe {
+            query = query
+                .filter(outputs::script_lock_height.le(i64_tip_height))
+                .filter(outputs::maturity.le(i64_tip_height));
+        };
+
+        match &selection_criteria.filter {
+            UtxoSelectionFilter::Standard => {
+                query = query.filter(
+                    outputs::output_type
+                        .eq(i32::from(OutputType::Standard.as_byte()))
+                        .or(outputs::output_type.eq(i32::from(OutputType::Coinbase.as_byte()))),
+                );
+                if selection_criteria.excluding_onesided {
+                    query = query.filter(outputs::source.ne(OutputSource::OneSided as i32));
+                }
+                // Hash-locked outputs need their preimage as script input, so they are only spent explicitly
+                query = query.filter(outputs::hash_lock.is_null());
+            },
+            UtxoSelectionFilter::SpecificOutputs { commitments } => {
+                if !commitments.is_empty() {
+                    query = query
+                        .filter(outputs::commitment.eq_any(commitments.iter().map(|c| c.to_vec()).collect::<Vec<_>>()));
+                }
+            },
+            UtxoSelectionFilter::UnconfirmedOutputsOf { tx_id } => {
+                query = query
+                    .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                    .filter(outputs::spent_in_tx_id.is_null())
+                    .filter(outputs::mined_in_block.is_null());
+            },
+        }
+
+        for exclude in &selection_criteria.excluding {
+            query = query.filter(outputs::commitment.ne(exclude.to_vec()));
+        }
+
+        query = match selection_criteria.ordering {
+            UtxoSelectionOrdering::SmallestFirst => query.then_order_by(outputs::value.asc()),
+            UtxoSelectionOrdering::LargestFirst => query.then_order_by(outputs::value.desc()),
+            UtxoSelectionOrdering::Default => {
+                let max: Option<i64> = outputs::table
+                    .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+                    .filter(outputs::script_lock_height.le(i64_tip_height))
+                    .filter(outputs::maturity.le(i64_tip_height))
+                    .order(outputs::value.desc())
+                    .select(outputs::value)
+                    .first(&mut conn)
+                    .optional()?;
+                match max {
+                    // Want to reduce the number of inputs to reduce fees
+                    Some(max) if amount > max as u64 => query.then_order_by(outputs::value.desc()),
+                    // Use the smaller utxos to make up this transaction.
+                    _ => query.then_order_by(outputs::value.asc()),
+                }
+            },
+        };
+
+        to_db_wallet_outputs(query.load::<OutputSql>(&mut conn)?)
+    }
+
+    fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let outputs = outputs::table
+            .filter(
+                outputs::received_in_tx_id
+                    .eq(tx_id.as_i64_wrapped())
+                    .or(outputs::spent_in_tx_id.eq(tx_id.as_i64_wrapped())),
+            )
+            .load::<OutputSql>(&mut conn)?;
+        to_db_wallet_outputs(outputs)
+    }
+
+    fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = outputs::table
+            .into_boxed()
+            .filter(outputs::script_lock_height.le(q.tip_height))
+            .filter(outputs::maturity.le(q.tip_height));
+
+        if let Some((offset, limit)) = q.pagination {
+            query = query.offset(offset).limit(limit);
+        }
+        if !q.status.is_empty() {
+            query = query.filter(outputs::status.eq_any(q.status.into_iter().map(|s| s as i32).collect::<Vec<_>>()));
+        }
+        if !q.commitments.is_empty() {
+            query = query
+                .filter(outputs::commitment.eq_any(q.commitments.into_iter().map(|c| c.to_vec()).collect::<Vec<_>>()));
+        }
+        if let Some((min, is_inclusive)) = q.value_min {
+            query = if is_inclusive {
+                query.filter(outputs::value.ge(min))
+            } else {
+                query.filter(outputs::value.gt(min))
+            };
+        }
+        if let Some((max, is_inclusive)) = q.value_max {
+            query = if is_inclusive {
+                query.filter(outputs::value.le(max))
+            } else {
+                query.filter(outputs::value.lt(max))
+            };
+        }
+
+        use SortDirection::{Asc, Desc};
+        let query = q.sorting.into_iter().fold(query, |query, s| match s {
+            ("value", Asc) => query.then_order_by(outputs::value.asc()),
+            ("value", Desc) => query.then_order_by(outputs::value.desc()),
+            ("mined_height", Asc) => query.then_order_by(outputs::mined_height.asc()),
+            ("mined_height", Desc) => query.then_order_by(outputs::mined_height.desc()),
+            _ => query,
+        });
+
+        Ok(query
+            .load::<OutputSql>(&mut conn)?
+            .into_iter()
+            .filter_map(|x| {
+                x.to_db_wallet_output()
+                    .map_err(|e| {
+                        error!(
+                            target: LOG_TARGET,
+                            "failed to convert `OutputSql` to `DbWalletOutput`: {:#?}", e
+                        );
+                        e
+                    })
+                    .ok()
+            })
+            .collect())
+    }
+}
+
+fn to_db_wallet_outputs(outputs: Vec<OutputSql>) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
+    outputs.into_iter().map(OutputSql::to_db_wallet_output).collect()
+}
+
+fn to_naive_date_time(timestamp: u64) -> Result<NaiveDateTime, OutputManagerStorageError> {
+    i64::try_from(timestamp)
+        .ok()
+        .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0))
+        .ok_or(OutputManagerStorageError::ConversionError {
+            reason: format!("Could not create timestamp from: {}", timestamp),
+        })
+}
+
+fn index_status(
+    statuses: Vec<OutputStatus>,
+    conn: &mut PgConnection,
+) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
+    Ok(outputs::table
+        .filter(outputs::status.eq_any(statuses.into_iter().map(|s| s as i32).collect::<Vec<_>>()))
+        .load::<OutputSql>(conn)?)
+}
+
+fn find_by_spending_key(
+    spending_key: &str,
+    status: OutputStatus,
+    conn: &mut PgConnection,
+) -> Result<Option<OutputSql>, OutputManagerStorageError> {
+    Ok(outputs::table
+        .filter(outputs::status.eq(status as i32))
+        .filter(outputs::spending_key.eq(spending_key))
+        .first::<OutputSql>(conn)
+        .optional()?)
+}
+
+/// Find the output with the given commitment, optionally only if it is (or is not) a cancelled inbound output
+fn find_by_commitment(
+    commitment: &[u8],
+    cancelled: Option<bool>,
+    conn: &mut PgConnection,
+) -> Result<Option<OutputSql>, OutputManagerStorageError> {
+    let mut query = outputs::table.filter(outputs::commitment.eq(commitment)).into_boxed();
+    match cancelled {
+        Some(true) => query = query.filter(outputs::status.eq(OutputStatus::CancelledInbound as i32)),
+        Some(false) => query = query.filter(outputs::status.ne(OutputStatus::CancelledInbound as i32)),
+        None => {},
+    }
+    Ok(query.first::<OutputSql>(conn).optional()?)
+}
+
+fn update_output(id: i32, update: UpdateOutput, conn: &mut PgConnection) -> Result<(), OutputManagerStorageError> {
+    diesel::update(outputs::table.filter(outputs::id.eq(id)))
+        .set(UpdateOutputSql::from(update))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+    Ok(())
+}
+
+fn update_outputs_with_tx_id_and_status_to_new_status(
+    tx_id: TxId,
+    from_status: OutputStatus,
+    to_status: OutputStatus,
+    conn: &mut PgConnection,
+) -> Result<(), OutputManagerStorageError> {
+    diesel::update(
+        outputs::table
+            .filter(
+                outputs::received_in_tx_id
+                    .eq(tx_id.as_i64_wrapped())
+                    .or(outputs::spent_in_tx_id.eq(tx_id.as_i64_wrapped())),
+            )
+            .filter(outputs::status.eq(from_status as i32)),
+    )
+    .set(outputs::status.eq(to_status as i32))
+    .execute(conn)?;
+    Ok(())
+}
+
+#[derive(QueryableByName)]
+struct AmountByCategory {
+    #[diesel(sql_type = diesel::sql_types::BigInt)]
+    amount: i64,
+    #[diesel(sql_type = diesel::sql_types::Text)]
+    category: String,
+}
+
+/// Return the available, time locked, pending incoming and pending outgoing balance. Postgres sums a `BIGINT` column
+/// into a `NUMERIC`, hence the casts.
+fn get_balance(
+    current_tip_for_time_lock_calculation: Option<u64>,
+    conn: &mut PgConnection,
+) -> Result<Balance, OutputManagerStorageError> {
+    const AVAILABLE: &str = "SELECT CAST(coalesce(sum(value), 0) AS BIGINT) AS amount, 'available_balance' AS \
+                             category FROM outputs WHERE status = $1";
+    const PENDING: &str = "SELECT CAST(coalesce(sum(value), 0) AS BIGINT) AS amount, 'pending_incoming_balance' AS \
+                           category FROM outputs WHERE source != $2 AND status = $3 OR status = $4 OR status = $5 \
+                           UNION ALL SELECT CAST(coalesce(sum(value), 0) AS BIGINT) AS amount, \
+                           'pending_outgoing_balance' AS category FROM outputs WHERE status = $6 OR status = $7 OR \
+                           status = $8";
+    let query = match current_tip_for_time_lock_calculation {
+        Some(_) => format!(
+            "{} UNION ALL {} UNION ALL SELECT CAST(coalesce(sum(value), 0) AS BIGINT) AS amount, \
+             'time_locked_balance' AS category FROM outputs WHERE status = $1 AND (maturity > $9 OR \
+             script_lock_height > $9)",
+            AVAILABLE, PENDING
+        ),
+        None => format!("{} UNION ALL {}", AVAILABLE, PENDING),
+    };
+    let query = sql_query(query)
+        .into_boxed::<diesel::pg::Pg>()
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputSource::Coinbase as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::EncumberedToBeReceived as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::ShortTermEncumberedToBeReceived as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::UnspentMinedUnconfirmed as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::EncumberedToBeSpent as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::ShortTermEncumberedToBeSpent as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::SpentMinedUnconfirmed as i32);
+    let query = match current_tip_for_time_lock_calculation {
+        Some(tip) => query.bind::<diesel::sql_types::BigInt, _>(tip as i64),
+        None => query,
+    };
+
+    let mut balance = Balance {
+        available_balance: MicroMinotari::zero(),
+        time_locked_balance: None,
+        pending_incoming_balance: MicroMinotari::zero(),
+        pending_outgoing_balance: MicroMinotari::zero(),
+    };
+    for row in query.load::<AmountByCategory>(conn)? {
+        let amount = MicroMinotari::from(row.amount as u64);
+        match row.category.as_str() {
+            "available_balance" => balance.available_balance = amount,
+            "time_locked_balance" => balance.time_locked_balance = Some(amount),
+            "pending_incoming_balance" => balance.pending_incoming_balance = amount,
+            "pending_outgoing_balance" => balance.pending_outgoing_balance = amount,
+            _ => {
+                return Err(OutputManagerStorageError::UnexpectedResult(
+                    "Unexpected category in balance query".to_string(),
+                ))
+            },
+        }
+    }
+    Ok(balance)
+}
+
+/// Returns the height of the most recent block mined at or before `timestamp` that contains one of our outputs or
+/// one of our transactions. The balance can only change at these heights.
+fn find_last_activity_height_at_or_before(
+    timestamp: NaiveDateTime,
+    conn: &mut PgConnection,
+) -> Result<Option<u64>, OutputManagerStorageError> {
+    #[derive(QueryableByName)]
+    struct HeightQueryResult {
+        #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+        height: Option<i64>,
+    }
+    let result = sql_query(
+        "SELECT max(height) AS height FROM (SELECT mined_height AS height FROM outputs WHERE mined_height IS NOT NULL \
+         AND mined_timestamp <= $1 UNION ALL SELECT mined_height AS height FROM completed_transactions WHERE \
+         mined_height IS NOT NULL AND mined_timestamp <= $1) AS activity",
+    )
+    .bind::<diesel::sql_types::Timestamp, _>(timestamp)
+    .get_result::<HeightQueryResult>(conn)?;
+
+    Ok(result.height.map(|h| h as u64))
+}
+
+/// Reconstructs the confirmed balance as it was at the end of the block at `height`
+fn get_balance_at_height(height: u64, conn: &mut PgConnection) -> Result<HistoricalBalance, OutputManagerStorageError> {
+    #[derive(QueryableByName)]
+    struct HistoricalBalanceQueryResult {
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        amount: i64,
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        num_outputs: i64,
+    }
+    let result = sql_query(
+        "SELECT CAST(coalesce(sum(value), 0) AS BIGINT) AS amount, count(*) AS num_outputs FROM outputs WHERE \
+         mined_height IS NOT NULL AND mined_height <= $1 AND (marked_deleted_at_height IS NULL OR \
+         marked_deleted_at_height > $1) AND status NOT IN ($2, $3, $4)",
+    )
+    .bind::<diesel::sql_types::BigInt, _>(height as i64)
+    .bind::<diesel::sql_types::Integer, _>(OutputStatus::Invalid as i32)
+    .bind::<diesel::sql_types::Integer, _>(OutputStatus::CancelledInbound as i32)
+    .bind::<diesel::sql_types::Integer, _>(OutputStatus::AbandonedCoinbase as i32)
+    .get_result::<HistoricalBalanceQueryResult>(conn)?;
+
+    Ok(HistoricalBalance {
+        height: Some(height),
+        balance: MicroMinotari::from(result.amount as u64),
+        num_outputs: result.num_outputs as u64,
+    })
+}
+
+/// Sums the value of outputs by their source, see [OutputManagerBackend::get_balance_by_origin]
+fn get_balance_by_origin(
+    mined_since: Option<NaiveDateTime>,
+    conn: &mut PgConnection,
+) -> Result<BalanceByOrigin, OutputManagerStorageError> {
+    #[derive(QueryableByName)]
+    struct OriginQueryResult {
+        #[diesel(sql_type = diesel::sql_types::Integer)]
+        source: i32,
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        amount: i64,
+    }
+    let results = match mined_since {
+        Some(mined_since) => sql_query(
+            "SELECT source, CAST(coalesce(sum(value), 0) AS BIGINT) AS amount FROM outputs WHERE mined_height IS NOT \
+             NULL AND mined_timestamp >= $1 AND status NOT IN ($2, $3, $4) GROUP BY source",
+        )
+        .bind::<diesel::sql_types::Timestamp, _>(mined_since)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::Invalid as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::CancelledInbound as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::AbandonedCoinbase as i32)
+        .load::<OriginQueryResult>(conn)?,
+        None => sql_query(
+            "SELECT source, CAST(coalesce(sum(value), 0) AS BIGINT) AS amount FROM outputs WHERE status = $1 GROUP BY \
+             source",
+        )
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
+        .load::<OriginQueryResult>(conn)?,
+    };
+
+    let mut balance = BalanceByOrigin::default();
+    for result in results {
+        balance.add(
+            OutputSource::try_from(result.source)?,
+            MicroMinotari::from(result.amount as u64),
+        );
+    }
+    Ok(balance)
+}
@@ -0,0 +1,103 @@
+//  Copyright 2021. The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, SqliteConnection};
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
+
+use crate::{
+    output_manager_service::{error::OutputManagerStorageError, storage::models::WalletAccount},
+    schema::accounts,
+};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = accounts)]
+pub struct WalletAccountSql {
+    pub id: i32,
+    pub name: String,
+    pub receiving: i32,
+    pub created_at: NaiveDateTime,
+}
+
+impl WalletAccountSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(accounts::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all accounts, ordered by id
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<WalletAccountSql>, OutputManagerStorageError> {
+        Ok(accounts::table
+            .order(accounts::id.asc())
+            .load::<WalletAccountSql>(conn)?)
+    }
+
+    /// The id for a new account, ids are never reused and start at 1 as 0 is the default account
+    pub fn next_id(conn: &mut SqliteConnection) -> Result<i32, OutputManagerStorageError> {
+        let max: Option<i32> = accounts::table.select(diesel::dsl::max(accounts::id)).first(conn)?;
+        Ok(max.unwrap_or_default() + 1)
+    }
+
+    /// Makes the given account the receiving account, the default account 0 clears the flag on all stored accounts
+    pub fn set_receiving(id: i32, conn: &mut SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::update(accounts::table)
+            .set(accounts::receiving.eq(0))
+            .execute(conn)?;
+        if id != 0 {
+            diesel::update(accounts::table.filter(accounts::id.eq(id)))
+                .set(accounts::receiving.eq(1))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+        }
+        Ok(())
+    }
+
+    /// The account receiving interactive payments, 0 if no stored account is flagged
+    pub fn find_receiving(conn: &mut SqliteConnection) -> Result<i32, OutputManagerStorageError> {
+        Ok(accounts::table
+            .filter(accounts::receiving.eq(1))
+            .select(accounts::id)
+            .first::<i32>(conn)
+            .optional()?
+            .unwrap_or_default())
+    }
+}
+
+impl TryFrom<WalletAccountSql> for WalletAccount {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(account: WalletAccountSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: u32::try_from(account.id).map_err(|_| OutputManagerStorageError::ConversionError {
+                reason: format!("Invalid account id: {}", account.id),
+            })?,
+            name: account.name,
+            receiving: account.receiving != 0,
+            created_at: account.created_at,
+        })
+    }
+}
@@ -0,0 +1,96 @@
+//  Copyright 2021. The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    io::{Read, Write},
+};
+
+use diesel::{prelude::*, SqliteConnection};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+use crate::{
+    output_manager_service::{error::OutputManagerStorageError, storage::models::ArchivedOutput},
+    schema::archived_outputs,
+};
+
+/// An archived output as stored in the database. The record itself is kept as gzip compressed JSON, as archived
+/// outputs are only ever read back in bulk for audits and exports.
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = archived_outputs)]
+pub struct ArchivedOutputSql {
+    pub hash: Vec<u8>,
+    pub spent_height: i64,
+    pub data: Vec<u8>,
+}
+
+impl ArchivedOutputSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(archived_outputs::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all archived outputs, ordered by the height they were spent at
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<ArchivedOutputSql>, OutputManagerStorageError> {
+        Ok(archived_outputs::table
+            .order(archived_outputs::spent_height.asc())
+            .load::<ArchivedOutputSql>(conn)?)
+    }
+
+    pub fn to_archived_output(&self) -> Result<ArchivedOutput, OutputManagerStorageError> {
+        let mut json = Vec::new();
+        GzDecoder::new(self.data.as_slice())
+            .read_to_end(&mut json)
+            .map_err(|e| OutputManagerStorageError::ConversionError {
+                reason: format!("Could not decompress archived output: {}", e),
+            })?;
+        serde_json::from_slice(&json).map_err(|e| OutputManagerStorageError::ConversionError {
+            reason: format!("Could not deserialize archived output: {}", e),
+        })
+    }
+}
+
+impl TryFrom<ArchivedOutput> for ArchivedOutputSql {
+    type Error = OutputManagerStorageError;
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn try_from(output: ArchivedOutput) -> Result<Self, Self::Error> {
+        let json = serde_json::to_vec(&output).map_err(|e| OutputManagerStorageError::ConversionError {
+            reason: format!("Could not serialize archived output: {}", e),
+        })?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        let data = encoder.write_all(&json).and_then(|_| encoder.finish()).map_err(|e| {
+            OutputManagerStorageError::ConversionError {
+                reason: format!("Could not compress archived output: {}", e),
+            }
+        })?;
+
+        Ok(Self {
+            hash: output.hash.to_vec(),
+            spent_height: output.spent_height.unwrap_or_default() as i64,
+            data,
+        })
+    }
+}
@@ -20,8 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryFrom, str::FromStr};
+use std::{collections::BTreeMap, convert::TryFrom, str::FromStr};
 
+pub use account_sql::WalletAccountSql;
+pub use archived_output_sql::ArchivedOutputSql;
 use chrono::{NaiveDateTime, Utc};
 use derivative::Derivative;
 use diesel::{
@@ -33,6 +35,7 @@ use diesel::{
 use log::*;
 pub use new_output_sql::NewOutputSql;
 pub use output_sql::OutputSql;
+pub use subaddress_sql::SubaddressSql;
 use tari_common_sqlite::{sqlite_connection_pool::PooledDbConnection, util::diesel_ext::ExpectedRowsExtension};
 use tari_common_types::{
     transaction::TxId,
@@ -40,6 +43,7 @@ use tari_common_types::{
 };
 use tari_core::transactions::{
     key_manager::TariKeyId,
+    tari_amount::MicroMinotari,
     transaction_components::{OutputType, TransactionOutput},
 };
 use tari_crypto::tari_utilities::{hex::Hex, ByteArray};
@@ -49,10 +53,10 @@ use tokio::time::Instant;
 use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
-        service::Balance,
+        service::{Balance, BalanceByOrigin, BalanceCutoff, HistoricalBalance},
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, OutputBackendQuery, OutputManagerBackend, WriteOperation},
-            models::{DbWalletOutput, KnownOneSidedPaymentScript},
+            models::{ArchivedOutput, DbWalletOutput, KnownOneSidedPaymentScript, Subaddress, WalletAccount},
             OutputStatus,
         },
         UtxoSelectionCriteria,
@@ -60,8 +64,11 @@ use crate::{
     schema::{known_one_sided_payment_scripts, outputs},
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
 };
+mod account_sql;
+mod archived_output_sql;
 mod new_output_sql;
 mod output_sql;
+mod subaddress_sql;
 const LOG_TARGET: &str = "wallet::output_manager_service::database::wallet";
 
 /// A Sqlite backend for the Output Manager Service. The Backend is accessed via a connection pool to the Sqlite file.
@@ -125,7 +132,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     #[allow(clippy::too_many_lines)]
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = match key {
@@ -252,7 +259,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     }
 
     fn fetch_with_features(&self, output_type: OutputType) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let outputs = OutputSql::index_by_output_type(output_type, &mut conn)?;
 
         outputs
@@ -262,7 +269,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     }
 
     fn fetch_sorted_unspent_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let outputs = OutputSql::index_unspent(&mut conn)?;
 
         outputs
@@ -273,7 +280,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn fetch_mined_unspent_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_marked_deleted_in_block_is_null(&mut conn)?;
 
@@ -295,7 +302,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn fetch_invalid_outputs(&self, timestamp: i64) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_invalid(&NaiveDateTime::from_timestamp_opt(timestamp, 0).unwrap(), &mut conn)?;
 
@@ -317,7 +324,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn fetch_unspent_mined_unconfirmed_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let outputs = OutputSql::index_unconfirmed(&mut conn)?;
 
@@ -393,7 +400,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn fetch_pending_incoming_outputs(&self) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let outputs = OutputSql::index_status(
@@ -447,18 +454,41 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                 reason: format!("Could not create timestamp mined_timestamp: {}", mined_timestamp),
             },
         )?;
-        diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
-            .set((
-                outputs::mined_height.eq(mined_height as i64),
-                outputs::mined_in_block.eq(mined_in_block),
-                outputs::status.eq(status),
-                outputs::mined_timestamp.eq(timestamp),
-                outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
-                outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
-                outputs::last_validation_timestamp.eq::<Option<NaiveDateTime>>(None),
-            ))
-            .execute(&mut conn)
-            .num_rows_affected_or_not_found(1)?;
+        // An output that is already being spent by a child-pays-for-parent transaction keeps its spending status when
+        // its parent is mined
+        let is_pending_spend = outputs::table
+            .filter(outputs::hash.eq(&hash))
+            .filter(outputs::status.eq_any([
+                OutputStatus::ShortTermEncumberedToBeSpent as i32,
+                OutputStatus::EncumberedToBeSpent as i32,
+            ]))
+            .count()
+            .get_result::<i64>(&mut conn)? >
+            0;
+        if is_pending_spend {
+            diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
+                .set((
+                    outputs::mined_height.eq(mined_height as i64),
+                    outputs::mined_in_block.eq(mined_in_block),
+                    outputs::mined_timestamp.eq(timestamp),
+                    outputs::last_validation_timestamp.eq::<Option<NaiveDateTime>>(None),
+                ))
+                .execute(&mut conn)
+                .num_rows_affected_or_not_found(1)?;
+        } else {
+            diesel::update(outputs::table.filter(outputs::hash.eq(hash)))
+                .set((
+                    outputs::mined_height.eq(mined_height as i64),
+                    outputs::mined_in_block.eq(mined_in_block),
+                    outputs::status.eq(status),
+                    outputs::mined_timestamp.eq(timestamp),
+                    outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+                    outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+                    outputs::last_validation_timestamp.eq::<Option<NaiveDateTime>>(None),
+                ))
+                .execute(&mut conn)
+                .num_rows_affected_or_not_found(1)?;
+        }
         if start.elapsed().as_millis() > 0 {
             trace!(
                 target: LOG_TARGET,
@@ -530,6 +560,46 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn set_outputs_in_height_range_to_be_revalidated(
+        &self,
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let (start_height, end_height) = (start_height as i64, end_height as i64);
+        let result = diesel::update(
+            outputs::table.filter(
+                outputs::mined_height
+                    .between(start_height, end_height)
+                    .or(outputs::marked_deleted_at_height.between(start_height, end_height)),
+            ),
+        )
+        .set((
+            outputs::mined_height.eq::<Option<i64>>(None),
+            outputs::mined_in_block.eq::<Option<Vec<u8>>>(None),
+            outputs::status.eq(OutputStatus::Invalid as i32),
+            outputs::mined_timestamp.eq::<Option<NaiveDateTime>>(None),
+            outputs::marked_deleted_at_height.eq::<Option<i64>>(None),
+            outputs::marked_deleted_in_block.eq::<Option<Vec<u8>>>(None),
+        ))
+        .execute(&mut conn)?;
+
+        trace!(target: LOG_TARGET, "rows updated: {:?}", result);
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - set_outputs_in_height_range_to_be_revalidated: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+
+        Ok(())
+    }
+
     fn update_last_validation_timestamp(&self, hash: FixedHash) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -633,12 +703,31 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
             commitments.push(output.commitment.as_bytes());
         }
         conn.transaction::<_, _, _>(|conn| {
-            // Any output in the list without the `Unspent` status will invalidate the encumberance
-            if !OutputSql::find_by_commitments_excluding_status(commitments.clone(), OutputStatus::Unspent, conn)?
-                .is_empty()
+            // Any output in the list without the `Unspent` status will invalidate the encumberance, unless it is a not
+            // yet mined output that a child-pays-for-parent transaction explicitly selected
+            for output in
+                OutputSql::find_by_commitments_excluding_status(commitments.clone(), OutputStatus::Unspent, conn)?
             {
-                return Err(OutputManagerStorageError::OutputAlreadySpent);
-            };
+                let selected_as_unconfirmed = outputs_to_send.iter().any(|o| {
+                    o.commitment.as_bytes() == output.commitment.as_slice() &&
+                        o.status == OutputStatus::EncumberedToBeReceived
+                });
+                let is_unconfirmed_and_unspent = output.status == OutputStatus::EncumberedToBeReceived as i32 &&
+                    output.received_in_tx_id.is_some() &&
+                    output.spent_in_tx_id.is_none() &&
+                    output.mined_in_block.is_none();
+                if !(selected_as_unconfirmed && is_unconfirmed_and_unspent) {
+                    return Err(OutputManagerStorageError::OutputAlreadySpent);
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "Encumbering unconfirmed output with Commitment: {} from TxId: {} as a child-pays-for-parent \
+                     spend in TxId: {}",
+                    output.commitment.to_hex(),
+                    TxId::from(output.received_in_tx_id.unwrap_or_default() as u64),
+                    tx_id
+                );
+            }
 
             let count = OutputSql::update_by_commitments(
                 commitments,
@@ -756,7 +845,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn get_last_mined_output(&self) -> Result<Option<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let output = OutputSql::first_by_mined_height_desc(&mut conn)?;
@@ -777,7 +866,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
 
     fn get_last_spent_output(&self) -> Result<Option<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let output = OutputSql::first_by_marked_deleted_height_desc(&mut conn)?;
@@ -801,7 +890,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         current_tip_for_time_lock_calculation: Option<u64>,
     ) -> Result<Balance, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = OutputSql::get_balance(current_tip_for_time_lock_calculation, &mut conn);
@@ -817,6 +906,88 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         result
     }
 
+    fn get_balance_at(&self, cutoff: BalanceCutoff) -> Result<HistoricalBalance, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let height = match cutoff {
+            BalanceCutoff::Height(height) => Some(height),
+            BalanceCutoff::Timestamp(timestamp) => {
+                let timestamp = i64::try_from(timestamp)
+                    .ok()
+                    .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0))
+                    .ok_or(OutputManagerStorageError::ConversionError {
+                        reason: format!("Could not create timestamp from: {}", timestamp),
+                    })?;
+                OutputSql::find_last_activity_height_at_or_before(timestamp, &mut conn)?
+            },
+        };
+        let result = match height {
+            Some(height) => OutputSql::get_balance_at_height(height, &mut conn)?,
+            None => HistoricalBalance {
+                height: None,
+                balance: MicroMinotari::zero(),
+                num_outputs: 0,
+            },
+        };
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - get_balance_at: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(result)
+    }
+
+    fn get_balance_by_origin(&self, mined_since: Option<u64>) -> Result<BalanceByOrigin, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let mined_since = mined_since
+            .map(|timestamp| {
+                i64::try_from(timestamp)
+                    .ok()
+                    .and_then(|t| NaiveDateTime::from_timestamp_opt(t, 0))
+                    .ok_or(OutputManagerStorageError::ConversionError {
+                        reason: format!("Could not create timestamp from: {}", timestamp),
+                    })
+            })
+            .transpose()?;
+        let result = OutputSql::get_balance_by_origin(mined_since, &mut conn);
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - get_balance_by_origin: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        result
+    }
+
+    fn get_account_balance(&self, account: u32, tip: Option<u64>) -> Result<Balance, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+        let result = OutputSql::get_account_balance(account, tip, &mut conn);
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - get_account_balance: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        result
+    }
+
     fn cancel_pending_transaction(&self, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -854,9 +1025,15 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
                         output.commitment.to_hex(),
                         tx_id
                     );
+                    // An output that was spent before it was mined goes back to waiting for its parent
+                    let status = if output.received_in_tx_id.is_some() && output.mined_in_block.is_none() {
+                        OutputStatus::EncumberedToBeReceived
+                    } else {
+                        OutputStatus::Unspent
+                    };
                     output.update(
                         UpdateOutput {
-                            status: Some(OutputStatus::Unspent),
+                            status: Some(status),
                             spent_in_tx_id: Some(None),
                             // We clear these so that the output will be revalidated the next time a validation is done.
                             mined_height: Some(None),
@@ -955,6 +1132,157 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn set_outputs_frozen(&self, commitments: &[Commitment], frozen: bool) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        conn.transaction::<_, _, _>(|conn| {
+            for commitment in commitments {
+                let mut query = diesel::update(outputs::table)
+                    .filter(outputs::commitment.eq(commitment.to_vec()))
+                    .into_boxed();
+                // Only outputs that could still be selected as inputs can be frozen
+                if frozen {
+                    query = query.filter(outputs::status.eq_any(vec![
+                        OutputStatus::Unspent as i32,
+                        OutputStatus::UnspentMinedUnconfirmed as i32,
+                        OutputStatus::EncumberedToBeReceived as i32,
+                    ]));
+                }
+                query
+                    .set(outputs::frozen.eq(i32::from(frozen)))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn set_output_label(
+        &self,
+        commitment: &Commitment,
+        label: Option<String>,
+        metadata: &BTreeMap<String, String>,
+    ) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let metadata_json = if metadata.is_empty() {
+            None
+        } else {
+            Some(
+                serde_json::to_string(metadata).map_err(|e| OutputManagerStorageError::ConversionError {
+                    reason: format!("Could not serialize output metadata: {}", e),
+                })?,
+            )
+        };
+        diesel::update(outputs::table.filter(outputs::commitment.eq(commitment.to_vec())))
+            .set((outputs::label.eq(label), outputs::metadata_json.eq(metadata_json)))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    fn archive_spent_outputs(&self, spent_height: u64) -> Result<usize, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let num_archived = conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let spent_outputs = outputs::table
+                .filter(outputs::status.eq(OutputStatus::Spent as i32))
+                .filter(outputs::marked_deleted_at_height.le(spent_height as i64))
+                .load::<OutputSql>(conn)?;
+            let hashes = spent_outputs.iter().map(|o| o.hash.clone()).collect::<Vec<_>>();
+            for output in spent_outputs {
+                ArchivedOutputSql::try_from(ArchivedOutput::from(output.to_db_wallet_output()?))?.commit(conn)?;
+            }
+            // Keep well below the maximum number of SQLite host parameters
+            for chunk in hashes.chunks(500) {
+                diesel::delete(outputs::table.filter(outputs::hash.eq_any(chunk))).execute(conn)?;
+            }
+            Ok(hashes.len())
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - archive_spent_outputs: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(num_archived)
+    }
+
+    fn fetch_archived_outputs(&self) -> Result<Vec<ArchivedOutput>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        ArchivedOutputSql::index(&mut conn)?
+            .iter()
+            .map(ArchivedOutputSql::to_archived_output)
+            .collect()
+    }
+
+    fn create_account(&self, name: String) -> Result<WalletAccount, OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let account = conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            let account = WalletAccountSql {
+                id: WalletAccountSql::next_id(conn)?,
+                name,
+                receiving: 0,
+                created_at: Utc::now().naive_utc(),
+            };
+            account.commit(conn)?;
+            WalletAccount::try_from(account)
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - create_account: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(account)
+    }
+
+    fn fetch_accounts(&self) -> Result<Vec<WalletAccount>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        WalletAccountSql::index(&mut conn)?
+            .into_iter()
+            .map(WalletAccount::try_from)
+            .collect()
+    }
+
+    fn set_receiving_account(&self, account: u32) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let id = i32::try_from(account).map_err(|_| OutputManagerStorageError::ValueNotFound)?;
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| WalletAccountSql::set_receiving(id, conn))
+    }
+
+    fn get_receiving_account(&self) -> Result<u32, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let id = WalletAccountSql::find_receiving(&mut conn)?;
+        u32::try_from(id).map_err(|_| OutputManagerStorageError::ConversionError {
+            reason: format!("Invalid account id: {}", id),
+        })
+    }
+
+    fn add_subaddress(&self, subaddress: Subaddress) -> Result<(), OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SubaddressSql::try_from(subaddress)?.commit(&mut conn)
+    }
+
+    fn fetch_subaddresses(&self) -> Result<Vec<Subaddress>, OutputManagerStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        SubaddressSql::index(&mut conn)?
+            .into_iter()
+            .map(Subaddress::try_from)
+            .collect()
+    }
+
     fn set_coinbase_abandoned(&self, tx_id: TxId, abandoned: bool) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -1020,6 +1348,56 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         Ok(())
     }
 
+    fn replace_pending_transaction_outputs(
+        &self,
+        tx_id: TxId,
+        outputs: Vec<DbWalletOutput>,
+    ) -> Result<(), OutputManagerStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let commitments = outputs.iter().map(|o| o.commitment.to_vec()).collect::<Vec<_>>();
+        conn.transaction::<_, OutputManagerStorageError, _>(|conn| {
+            diesel::update(
+                outputs::table
+                    .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                    .filter(outputs::status.eq(OutputStatus::EncumberedToBeReceived as i32))
+                    .filter(outputs::commitment.ne_all(&commitments)),
+            )
+            .set(outputs::status.eq(OutputStatus::CancelledInbound as i32))
+            .execute(conn)?;
+
+            for output in outputs {
+                let commitment = output.commitment.to_vec();
+                if OutputSql::find_by_commitment(&commitment, conn).is_ok() {
+                    diesel::update(
+                        outputs::table
+                            .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                            .filter(outputs::commitment.eq(&commitment))
+                            .filter(outputs::status.eq(OutputStatus::CancelledInbound as i32)),
+                    )
+                    .set(outputs::status.eq(OutputStatus::EncumberedToBeReceived as i32))
+                    .execute(conn)?;
+                } else {
+                    NewOutputSql::new(output, OutputStatus::EncumberedToBeReceived, Some(tx_id), None)?.commit(conn)?;
+                }
+            }
+            Ok(())
+        })?;
+
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - replace_pending_transaction_outputs: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
+
     fn add_unvalidated_output(&self, output: DbWalletOutput, tx_id: TxId) -> Result<(), OutputManagerStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -1051,7 +1429,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
         tip_height: Option<u64>,
     ) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let outputs = OutputSql::fetch_unspent_outputs_for_spending(selection_criteria, amount, tip_height, &mut conn)?;
@@ -1070,7 +1448,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     }
 
     fn fetch_outputs_by_tx_id(&self, tx_id: TxId) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let outputs = OutputSql::find_by_tx_id(tx_id, &mut conn)?;
 
         outputs
@@ -1080,7 +1458,7 @@ impl OutputManagerBackend for OutputManagerSqliteDatabase {
     }
 
     fn fetch_outputs_by(&self, q: OutputBackendQuery) -> Result<Vec<DbWalletOutput>, OutputManagerStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         Ok(OutputSql::fetch_outputs_by(q, &mut conn)?
             .into_iter()
             .filter_map(|x| {
@@ -1121,18 +1499,18 @@ fn update_outputs_with_tx_id_and_status_to_new_status(
 /// These are the fields that can be updated for an Output
 #[derive(Clone, Default)]
 pub struct UpdateOutput {
-    status: Option<OutputStatus>,
-    hash: Option<Vec<u8>>,
-    received_in_tx_id: Option<Option<TxId>>,
-    spent_in_tx_id: Option<Option<TxId>>,
-    metadata_signature_ephemeral_commitment: Option<Vec<u8>>,
-    metadata_signature_ephemeral_pubkey: Option<Vec<u8>>,
-    metadata_signature_u_a: Option<Vec<u8>>,
-    metadata_signature_u_x: Option<Vec<u8>>,
-    metadata_signature_u_y: Option<Vec<u8>>,
-    mined_height: Option<Option<u64>>,
-    mined_in_block: Option<Option<Vec<u8>>>,
-    last_validation_timestamp: Option<Option<NaiveDateTime>>,
+    pub(crate) status: Option<OutputStatus>,
+    pub(crate) hash: Option<Vec<u8>>,
+    pub(crate) received_in_tx_id: Option<Option<TxId>>,
+    pub(crate) spent_in_tx_id: Option<Option<TxId>>,
+    pub(crate) metadata_signature_ephemeral_commitment: Option<Vec<u8>>,
+    pub(crate) metadata_signature_ephemeral_pubkey: Option<Vec<u8>>,
+    pub(crate) metadata_signature_u_a: Option<Vec<u8>>,
+    pub(crate) metadata_signature_u_x: Option<Vec<u8>>,
+    pub(crate) metadata_signature_u_y: Option<Vec<u8>>,
+    pub(crate) mined_height: Option<Option<u64>>,
+    pub(crate) mined_in_block: Option<Option<Vec<u8>>>,
+    pub(crate) last_validation_timestamp: Option<Option<NaiveDateTime>>,
 }
 
 #[derive(AsChangeset)]
@@ -29,6 +29,7 @@ use tari_utilities::ByteArray;
 use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
+        script_lock::ScriptLock,
         storage::{models::DbWalletOutput, OutputStatus},
     },
     schema::outputs,
@@ -51,6 +52,7 @@ pub struct NewOutputSql {
     pub script: Vec<u8>,
     pub input_data: Vec<u8>,
     pub script_private_key: String,
+    pub script_lock_height: i64,
     pub coinbase_extra: Option<Vec<u8>>,
     pub sender_offset_public_key: Vec<u8>,
     pub metadata_signature_ephemeral_commitment: Vec<u8>,
@@ -65,6 +67,9 @@ pub struct NewOutputSql {
     pub encrypted_data: Vec<u8>,
     pub minimum_value_promise: i64,
     pub source: i32,
+    pub hash_lock: Option<Vec<u8>>,
+    pub account_id: i32,
+    pub subaddress_index: Option<i64>,
 }
 
 impl NewOutputSql {
@@ -77,6 +82,8 @@ impl NewOutputSql {
     ) -> Result<Self, OutputManagerStorageError> {
         let mut covenant = Vec::new();
         BorshSerialize::serialize(&output.wallet_output.covenant, &mut covenant)?;
+        let script_lock = ScriptLock::from_script(&output.wallet_output.script);
+        let account_id = output.account() as i32;
 
         let output = Self {
             commitment: output.commitment.to_vec(),
@@ -91,6 +98,7 @@ impl NewOutputSql {
             script: output.wallet_output.script.to_bytes(),
             input_data: output.wallet_output.input_data.to_bytes(),
             script_private_key: output.wallet_output.script_key_id.to_string(),
+            script_lock_height: output.script_lock_height() as i64,
             coinbase_extra: Some(output.wallet_output.features.coinbase_extra.clone()),
             sender_offset_public_key: output.wallet_output.sender_offset_public_key.to_vec(),
             metadata_signature_ephemeral_commitment: output
@@ -112,6 +120,9 @@ impl NewOutputSql {
             encrypted_data: output.wallet_output.encrypted_data.to_byte_vec(),
             minimum_value_promise: output.wallet_output.minimum_value_promise.as_u64() as i64,
             source: output.source as i32,
+            hash_lock: script_lock.hash_lock.map(|hash| hash.to_vec()),
+            account_id,
+            subaddress_index: output.subaddress_index.map(|i| i as i64),
         };
 
         Ok(output)
@@ -21,6 +21,7 @@
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
+    collections::BTreeMap,
     convert::{TryFrom, TryInto},
     str::FromStr,
 };
@@ -47,7 +48,7 @@ use crate::{
     output_manager_service::{
         error::OutputManagerStorageError,
         input_selection::{UtxoSelectionCriteria, UtxoSelectionMode},
-        service::Balance,
+        service::{Balance, BalanceByOrigin, HistoricalBalance},
         storage::{
             database::{OutputBackendQuery, SortDirection},
             models::DbWalletOutput,
@@ -101,6 +102,12 @@ pub struct OutputSql {
     pub minimum_value_promise: i64,
     pub source: i32,
     pub last_validation_timestamp: Option<NaiveDateTime>,
+    pub frozen: i32,
+    pub hash_lock: Option<Vec<u8>>,
+    pub label: Option<String>,
+    pub metadata_json: Option<String>,
+    pub account_id: i32,
+    pub subaddress_index: Option<i64>,
 }
 
 impl OutputSql {
@@ -198,10 +205,18 @@ impl OutputSql {
         let i64_tip_height = tip_height.and_then(|h| i64::try_from(h).ok()).unwrap_or(i64::MAX);
         let i64_value = i64::try_from(selection_criteria.min_dust).unwrap_or(i64::MAX);
 
+        // Unconfirmed outputs are only selected when explicitly asked for by a child-pays-for-parent spend
+        let status = if selection_criteria.filter.is_unconfirmed() {
+            OutputStatus::EncumberedToBeReceived
+        } else {
+            OutputStatus::Unspent
+        };
         let mut query = outputs::table
             .into_boxed()
-            .filter(outputs::status.eq(OutputStatus::Unspent as i32))
+            .filter(outputs::status.eq(status as i32))
+            .filter(outputs::frozen.eq(0))
             .filter(outputs::value.gt(i64_value))
+            .filter(outputs::account_id.eq(selection_criteria.account as i32))
             .order_by(outputs::spending_priority.desc());
 
         // NOTE: Safe mode presets `script_lock_height` and `maturity` filters for all queries
@@ -222,6 +237,9 @@ impl OutputSql {
                 if selection_criteria.excluding_onesided {
                     query = query.filter(outputs::source.ne(OutputSource::OneSided as i32));
                 }
+
+                // Hash-locked outputs need their preimage as script input, so they are only spent explicitly
+                query = query.filter(outputs::hash_lock.is_null());
             },
 
             UtxoSelectionFilter::SpecificOutputs { commitments } => {
@@ -233,6 +251,13 @@ impl OutputSql {
                     ),
                 };
             },
+
+            UtxoSelectionFilter::UnconfirmedOutputsOf { tx_id } => {
+                query = query
+                    .filter(outputs::received_in_tx_id.eq(tx_id.as_i64_wrapped()))
+                    .filter(outputs::spent_in_tx_id.is_null())
+                    .filter(outputs::mined_in_block.is_null());
+            },
         }
 
         for exclude in &selection_criteria.excluding {
@@ -273,7 +298,7 @@ impl OutputSql {
         Ok(query.load(conn)?)
     }
 
-    /// Return all unspent outputs that have a maturity above the provided chain tip
+    /// Return all unspent outputs that have a maturity or script lock height above the provided chain tip
     #[allow(clippy::cast_possible_wrap)]
     pub fn index_time_locked(
         tip: u64,
@@ -281,7 +306,11 @@ impl OutputSql {
     ) -> Result<Vec<OutputSql>, OutputManagerStorageError> {
         Ok(outputs::table
             .filter(outputs::status.eq(OutputStatus::Unspent as i32))
-            .filter(outputs::maturity.gt(tip as i64))
+            .filter(
+                outputs::maturity
+                    .gt(tip as i64)
+                    .or(outputs::script_lock_height.gt(tip as i64)),
+            )
             .load(conn)?)
     }
 
@@ -404,7 +433,7 @@ impl OutputSql {
                  FROM outputs WHERE status = ? \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'time_locked_balance' as category \
-                 FROM outputs WHERE status = ? AND maturity > ? OR script_lock_height > ? \
+                 FROM outputs WHERE status = ? AND (maturity > ? OR script_lock_height > ?) \
                  UNION ALL \
                  SELECT coalesce(sum(value), 0) as amount, 'pending_incoming_balance' as category \
                  FROM outputs WHERE source != ? AND status = ? OR status = ? OR status = ? \
@@ -494,6 +523,158 @@ impl OutputSql {
         })
     }
 
+    /// Returns the height of the most recent block mined at or before `timestamp` that contains one of our outputs or
+    /// one of our transactions. The balance can only change at these heights.
+    pub fn find_last_activity_height_at_or_before(
+        timestamp: NaiveDateTime,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<u64>, OutputManagerStorageError> {
+        #[derive(QueryableByName, Clone)]
+        struct HeightQueryResult {
+            #[diesel(sql_type = diesel::sql_types::Nullable<diesel::sql_types::BigInt>)]
+            height: Option<i64>,
+        }
+        let result = sql_query(
+            "SELECT max(height) as height FROM ( SELECT mined_height as height FROM outputs WHERE mined_height IS NOT \
+             NULL AND mined_timestamp <= ? UNION ALL SELECT mined_height as height FROM completed_transactions WHERE \
+             mined_height IS NOT NULL AND mined_timestamp <= ?)",
+        )
+        .bind::<diesel::sql_types::Timestamp, _>(timestamp)
+        .bind::<diesel::sql_types::Timestamp, _>(timestamp)
+        .get_result::<HeightQueryResult>(conn)?;
+
+        #[allow(clippy::cast_sign_loss)]
+        Ok(result.height.map(|h| h as u64))
+    }
+
+    /// Reconstructs the confirmed balance as it was at the end of the block at `height`, i.e. the sum of all outputs
+    /// mined at or before `height` that had not been spent at that height.
+    /// The balance of a single wallet account, calculated like [OutputSql::get_balance] but over the outputs of the
+    /// account only
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn get_account_balance(
+        account: u32,
+        current_tip_for_time_lock_calculation: Option<u64>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Balance, OutputManagerStorageError> {
+        let outputs = outputs::table
+            .filter(outputs::account_id.eq(account as i32))
+            .select((
+                outputs::status,
+                outputs::source,
+                outputs::value,
+                outputs::maturity,
+                outputs::script_lock_height,
+            ))
+            .load::<(i32, i32, i64, i64, i64)>(conn)?;
+
+        let mut balance = Balance {
+            available_balance: MicroMinotari::zero(),
+            time_locked_balance: current_tip_for_time_lock_calculation.map(|_| MicroMinotari::zero()),
+            pending_incoming_balance: MicroMinotari::zero(),
+            pending_outgoing_balance: MicroMinotari::zero(),
+        };
+        for (status, source, value, maturity, script_lock_height) in outputs {
+            let value = MicroMinotari::from(value as u64);
+            let status = OutputStatus::try_from(status)?;
+            match status {
+                OutputStatus::Unspent => {
+                    balance.available_balance += value;
+                    if let (Some(tip), Some(time_locked)) = (
+                        current_tip_for_time_lock_calculation,
+                        balance.time_locked_balance.as_mut(),
+                    ) {
+                        if maturity > tip as i64 || script_lock_height > tip as i64 {
+                            *time_locked += value;
+                        }
+                    }
+                },
+                OutputStatus::EncumberedToBeReceived if source == OutputSource::Coinbase as i32 => {},
+                OutputStatus::EncumberedToBeReceived |
+                OutputStatus::ShortTermEncumberedToBeReceived |
+                OutputStatus::UnspentMinedUnconfirmed => balance.pending_incoming_balance += value,
+                OutputStatus::EncumberedToBeSpent |
+                OutputStatus::ShortTermEncumberedToBeSpent |
+                OutputStatus::SpentMinedUnconfirmed => balance.pending_outgoing_balance += value,
+                _ => {},
+            }
+        }
+        Ok(balance)
+    }
+
+    #[allow(clippy::cast_possible_wrap)]
+    #[allow(clippy::cast_sign_loss)]
+    pub fn get_balance_at_height(
+        height: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<HistoricalBalance, OutputManagerStorageError> {
+        #[derive(QueryableByName, Clone)]
+        struct HistoricalBalanceQueryResult {
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            amount: i64,
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            num_outputs: i64,
+        }
+        let result = sql_query(
+            "SELECT coalesce(sum(value), 0) as amount, count(*) as num_outputs FROM outputs WHERE mined_height IS NOT \
+             NULL AND mined_height <= ? AND (marked_deleted_at_height IS NULL OR marked_deleted_at_height > ?) AND \
+             status NOT IN (?, ?, ?)",
+        )
+        .bind::<diesel::sql_types::BigInt, _>(height as i64)
+        .bind::<diesel::sql_types::BigInt, _>(height as i64)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::Invalid as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::CancelledInbound as i32)
+        .bind::<diesel::sql_types::Integer, _>(OutputStatus::AbandonedCoinbase as i32)
+        .get_result::<HistoricalBalanceQueryResult>(conn)?;
+
+        Ok(HistoricalBalance {
+            height: Some(height),
+            balance: MicroMinotari::from(result.amount as u64),
+            num_outputs: result.num_outputs as u64,
+        })
+    }
+
+    /// Sums the value of outputs by their source. Without `mined_since` this covers the available (unspent) outputs,
+    /// otherwise it covers every valid output mined at or after `mined_since`, including those since spent.
+    #[allow(clippy::cast_sign_loss)]
+    pub fn get_balance_by_origin(
+        mined_since: Option<NaiveDateTime>,
+        conn: &mut SqliteConnection,
+    ) -> Result<BalanceByOrigin, OutputManagerStorageError> {
+        #[derive(QueryableByName, Clone)]
+        struct OriginQueryResult {
+            #[diesel(sql_type = diesel::sql_types::Integer)]
+            source: i32,
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            amount: i64,
+        }
+        let results = if let Some(mined_since) = mined_since {
+            sql_query(
+                "SELECT source, coalesce(sum(value), 0) as amount FROM outputs WHERE mined_height IS NOT NULL AND \
+                 mined_timestamp >= ? AND status NOT IN (?, ?, ?) GROUP BY source",
+            )
+            .bind::<diesel::sql_types::Timestamp, _>(mined_since)
+            .bind::<diesel::sql_types::Integer, _>(OutputStatus::Invalid as i32)
+            .bind::<diesel::sql_types::Integer, _>(OutputStatus::CancelledInbound as i32)
+            .bind::<diesel::sql_types::Integer, _>(OutputStatus::AbandonedCoinbase as i32)
+            .load::<OriginQueryResult>(conn)?
+        } else {
+            sql_query("SELECT source, coalesce(sum(value), 0) as amount FROM outputs WHERE status = ? GROUP BY source")
+                .bind::<diesel::sql_types::Integer, _>(OutputStatus::Unspent as i32)
+                .load::<OriginQueryResult>(conn)?
+        };
+
+        let mut balance = BalanceByOrigin::default();
+        for result in results {
+            balance.add(
+                OutputSource::try_from(result.source)?,
+                MicroMinotari::from(result.amount as u64),
+            );
+        }
+        Ok(balance)
+    }
+
     pub fn find_by_commitment(
         commitment: &[u8],
         conn: &mut SqliteConnection,
@@ -771,6 +952,12 @@ impl OutputSql {
             },
             None => None,
         };
+        let metadata = match self.metadata_json {
+            Some(json) => serde_json::from_str(&json).map_err(|e| OutputManagerStorageError::ConversionError {
+                reason: format!("Could not parse output metadata from JSON: {}", e),
+            })?,
+            None => BTreeMap::new(),
+        };
         Ok(DbWalletOutput {
             commitment,
             wallet_output,
@@ -785,6 +972,10 @@ impl OutputSql {
             source: self.source.try_into()?,
             received_in_tx_id: self.received_in_tx_id.map(|d| (d as u64).into()),
             spent_in_tx_id: self.spent_in_tx_id.map(|d| (d as u64).into()),
+            frozen: self.frozen != 0,
+            label: self.label,
+            metadata,
+            subaddress_index: self.subaddress_index.map(|i| i as u64),
         })
     }
 }
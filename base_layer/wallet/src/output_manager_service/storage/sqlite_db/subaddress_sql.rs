@@ -0,0 +1,97 @@
+//  Copyright 2021. The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use diesel::{prelude::*, SqliteConnection};
+use tari_common_types::types::PublicKey;
+use tari_utilities::ByteArray;
+
+use crate::{
+    output_manager_service::{error::OutputManagerStorageError, storage::models::Subaddress},
+    schema::subaddresses,
+};
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = subaddresses)]
+pub struct SubaddressSql {
+    pub subaddress_index: i64,
+    pub public_key: Vec<u8>,
+    pub label: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl SubaddressSql {
+    /// Write this struct to the database
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), OutputManagerStorageError> {
+        diesel::insert_into(subaddresses::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Return all subaddresses, ordered by index
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<SubaddressSql>, OutputManagerStorageError> {
+        Ok(subaddresses::table
+            .order(subaddresses::subaddress_index.asc())
+            .load::<SubaddressSql>(conn)?)
+    }
+}
+
+impl TryFrom<Subaddress> for SubaddressSql {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(subaddress: Subaddress) -> Result<Self, Self::Error> {
+        Ok(Self {
+            subaddress_index: i64::try_from(subaddress.index).map_err(|_| {
+                OutputManagerStorageError::ConversionError {
+                    reason: format!("Invalid subaddress index: {}", subaddress.index),
+                }
+            })?,
+            public_key: subaddress.public_key.to_vec(),
+            label: subaddress.label,
+            created_at: subaddress.created_at,
+        })
+    }
+}
+
+impl TryFrom<SubaddressSql> for Subaddress {
+    type Error = OutputManagerStorageError;
+
+    fn try_from(subaddress: SubaddressSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            index: u64::try_from(subaddress.subaddress_index).map_err(|_| {
+                OutputManagerStorageError::ConversionError {
+                    reason: format!("Invalid subaddress index: {}", subaddress.subaddress_index),
+                }
+            })?,
+            public_key: PublicKey::from_canonical_bytes(&subaddress.public_key).map_err(|e| {
+                OutputManagerStorageError::ConversionError {
+                    reason: format!("Invalid subaddress public key: {}", e),
+                }
+            })?,
+            label: subaddress.label,
+            created_at: subaddress.created_at,
+        })
+    }
+}
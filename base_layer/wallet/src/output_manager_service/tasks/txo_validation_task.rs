@@ -233,6 +233,7 @@ where
                     self.db
                         .set_output_to_unmined_and_invalid(output.hash)
                         .for_protocol(self.operation_id)?;
+                    self.publish_event(OutputManagerEvent::OutputInvalidated(output.hash));
                     continue;
                 };
                 if data.height_deleted_at == 0 && output.marked_deleted_at_height.is_some() {
@@ -352,6 +353,7 @@ where
                 self.db
                     .set_output_to_unmined_and_invalid(last_spent_output.hash)
                     .for_protocol(self.operation_id)?;
+                self.publish_event(OutputManagerEvent::OutputInvalidated(last_spent_output.hash));
                 continue;
             };
             let mined_in_block_hash = if let Some(hash) = last_spent_output.marked_deleted_in_block {
@@ -367,6 +369,7 @@ where
                 self.db
                     .set_output_to_unmined_and_invalid(last_spent_output.hash)
                     .for_protocol(self.operation_id)?;
+                self.publish_event(OutputManagerEvent::OutputInvalidated(last_spent_output.hash));
                 continue;
             };
             let block_at_height = self
@@ -408,6 +411,7 @@ where
                 self.db
                     .set_output_to_unmined_and_invalid(last_mined_output.hash)
                     .for_protocol(self.operation_id)?;
+                self.publish_event(OutputManagerEvent::OutputInvalidated(last_mined_output.hash));
                 continue;
             }
             let mined_height = last_mined_output.mined_height.unwrap();
@@ -428,6 +432,7 @@ where
                 self.db
                     .set_output_to_unmined_and_invalid(last_mined_output.hash)
                     .for_protocol(self.operation_id)?;
+                self.publish_event(OutputManagerEvent::OutputInvalidated(last_mined_output.hash));
             } else {
                 debug!(
                     target: LOG_TARGET,
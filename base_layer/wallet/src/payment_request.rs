@@ -0,0 +1,270 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Payment request URIs.
+//!
+//! A payment request is a `tari://` URI that merchants and wallets can exchange (e.g. as a QR code) to describe a
+//! payment. It extends the deep link the console wallet already displays with an optional amount, message and expiry:
+//!
+//! `tari://{network}/transactions/send?tariAddress={hex}&amount={micro_minotari}&message={text}&expires={unix_secs}`
+
+use std::{fmt, str::FromStr};
+
+use chrono::{NaiveDateTime, Utc};
+use tari_common::configuration::Network;
+use tari_common_types::tari_address::TariAddress;
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+use url::Url;
+
+pub const PAYMENT_REQUEST_SCHEME: &str = "tari";
+const PAYMENT_REQUEST_PATH: &str = "/transactions/send";
+
+const ADDRESS_PARAM: &str = "tariAddress";
+const AMOUNT_PARAM: &str = "amount";
+const MESSAGE_PARAM: &str = "message";
+const EXPIRES_PARAM: &str = "expires";
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PaymentRequestError {
+    #[error("Invalid payment request URI: {0}")]
+    InvalidUri(String),
+    #[error("Unsupported URI scheme `{0}`")]
+    UnsupportedScheme(String),
+    #[error("Unsupported payment request path `{0}`")]
+    UnsupportedPath(String),
+    #[error("Invalid network `{0}`")]
+    InvalidNetwork(String),
+    #[error("Missing required parameter `{0}`")]
+    MissingParameter(&'static str),
+    #[error("Invalid value for parameter `{param}`: {reason}")]
+    InvalidParameter { param: &'static str, reason: String },
+    #[error("Address network {address} does not match request network {request}")]
+    NetworkMismatch { address: Network, request: Network },
+}
+
+/// A request for payment to a Tari address, optionally for a fixed amount and with an expiry time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PaymentRequest {
+    pub address: TariAddress,
+    pub amount: Option<MicroMinotari>,
+    pub message: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+impl PaymentRequest {
+    pub fn new(address: TariAddress) -> Self {
+        Self {
+            address,
+            amount: None,
+            message: None,
+            expires_at: None,
+        }
+    }
+
+    pub fn with_amount(mut self, amount: MicroMinotari) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+
+    pub fn with_message<T: Into<String>>(mut self, message: T) -> Self {
+        self.message = Some(message.into());
+        self
+    }
+
+    pub fn with_expiry(mut self, expires_at: NaiveDateTime) -> Self {
+        self.expires_at = Some(expires_at);
+        self
+    }
+
+    pub fn network(&self) -> Network {
+        self.address.network()
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|expires_at| expires_at <= Utc::now().naive_utc())
+            .unwrap_or(false)
+    }
+
+    /// Encodes this request as a `tari://` URI.
+    pub fn to_uri(&self) -> String {
+        let mut url = Url::parse(&format!(
+            "{}://{}{}",
+            PAYMENT_REQUEST_SCHEME,
+            self.network(),
+            PAYMENT_REQUEST_PATH
+        ))
+        .expect("payment request base URI is always valid");
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair(ADDRESS_PARAM, &self.address.to_hex());
+            if let Some(amount) = self.amount {
+                query.append_pair(AMOUNT_PARAM, &amount.as_u64().to_string());
+            }
+            if let Some(message) = self.message.as_ref() {
+                query.append_pair(MESSAGE_PARAM, message);
+            }
+            if let Some(expires_at) = self.expires_at {
+                query.append_pair(EXPIRES_PARAM, &expires_at.timestamp().to_string());
+            }
+        }
+        url.into()
+    }
+
+    /// Parses a `tari://` payment request URI. Unknown query parameters are ignored so that the format can be
+    /// extended without breaking older wallets.
+    pub fn parse(uri: &str) -> Result<Self, PaymentRequestError> {
+        let url = Url::parse(uri.trim()).map_err(|e| PaymentRequestError::InvalidUri(e.to_string()))?;
+        if url.scheme() != PAYMENT_REQUEST_SCHEME {
+            return Err(PaymentRequestError::UnsupportedScheme(url.scheme().to_string()));
+        }
+        if url.path() != PAYMENT_REQUEST_PATH {
+            return Err(PaymentRequestError::UnsupportedPath(url.path().to_string()));
+        }
+        let host = url.host_str().unwrap_or_default();
+        let network = Network::from_str(host).map_err(|_| PaymentRequestError::InvalidNetwork(host.to_string()))?;
+
+        let mut address = None;
+        let mut amount = None;
+        let mut message = None;
+        let mut expires_at = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                ADDRESS_PARAM => {
+                    let parsed = TariAddress::from_str(&value).map_err(|e| PaymentRequestError::InvalidParameter {
+                        param: ADDRESS_PARAM,
+                        reason: e.to_string(),
+                    })?;
+                    address = Some(parsed);
+                },
+                AMOUNT_PARAM => {
+                    let parsed = value
+                        .parse::<u64>()
+                        .map_err(|e| PaymentRequestError::InvalidParameter {
+                            param: AMOUNT_PARAM,
+                            reason: e.to_string(),
+                        })?;
+                    amount = Some(MicroMinotari::from(parsed));
+                },
+                MESSAGE_PARAM => message = Some(value.into_owned()),
+                EXPIRES_PARAM => {
+                    let timestamp = value
+                        .parse::<i64>()
+                        .map_err(|e| PaymentRequestError::InvalidParameter {
+                            param: EXPIRES_PARAM,
+                            reason: e.to_string(),
+                        })?;
+                    let parsed = NaiveDateTime::from_timestamp_opt(timestamp, 0).ok_or_else(|| {
+                        PaymentRequestError::InvalidParameter {
+                            param: EXPIRES_PARAM,
+                            reason: "timestamp out of range".to_string(),
+                        }
+                    })?;
+                    expires_at = Some(parsed);
+                },
+                _ => {},
+            }
+        }
+
+        let address = address.ok_or(PaymentRequestError::MissingParameter(ADDRESS_PARAM))?;
+        if address.network() != network {
+            return Err(PaymentRequestError::NetworkMismatch {
+                address: address.network(),
+                request: network,
+            });
+        }
+
+        Ok(Self {
+            address,
+            amount,
+            message,
+            expires_at,
+        })
+    }
+}
+
+impl FromStr for PaymentRequest {
+    type Err = PaymentRequestError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for PaymentRequest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::PublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    fn random_address(network: Network) -> TariAddress {
+        let (_, public_key) = PublicKey::random_keypair(&mut rand::rngs::OsRng);
+        TariAddress::new(public_key, network)
+    }
+
+    #[test]
+    fn it_round_trips_a_full_request() {
+        let request = PaymentRequest::new(random_address(Network::Esmeralda))
+            .with_amount(MicroMinotari::from(1_250_000))
+            .with_message("Invoice #42 & change?")
+            .with_expiry(NaiveDateTime::from_timestamp_opt(1_900_000_000, 0).unwrap());
+        let uri = request.to_uri();
+        assert!(uri.starts_with("tari://esmeralda/transactions/send?tariAddress="));
+        assert_eq!(PaymentRequest::parse(&uri).unwrap(), request);
+        assert!(!request.is_expired());
+    }
+
+    #[test]
+    fn it_parses_the_plain_address_link() {
+        let address = random_address(Network::Esmeralda);
+        let uri = format!("tari://esmeralda/transactions/send?tariAddress={}", address.to_hex());
+        let request = PaymentRequest::parse(&uri).unwrap();
+        assert_eq!(request, PaymentRequest::new(address));
+    }
+
+    #[test]
+    fn it_rejects_invalid_requests() {
+        let address = random_address(Network::Esmeralda);
+        let uri = format!("tari://igor/transactions/send?tariAddress={}", address.to_hex());
+        assert!(matches!(
+            PaymentRequest::parse(&uri),
+            Err(PaymentRequestError::NetworkMismatch { .. })
+        ));
+        assert_eq!(
+            PaymentRequest::parse("tari://esmeralda/transactions/send?amount=10"),
+            Err(PaymentRequestError::MissingParameter(ADDRESS_PARAM))
+        );
+        assert!(matches!(
+            PaymentRequest::parse("tari://esmeralda/base_nodes/add?name=x"),
+            Err(PaymentRequestError::UnsupportedPath(_))
+        ));
+    }
+}
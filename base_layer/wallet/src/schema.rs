@@ -1,5 +1,68 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    accounts (id) {
+        id -> Integer,
+        name -> Text,
+        receiving -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    archived_outputs (hash) {
+        hash -> Binary,
+        spent_height -> BigInt,
+        data -> Binary,
+    }
+}
+
+diesel::table! {
+    atomic_swaps (swap_id) {
+        swap_id -> BigInt,
+        role -> Integer,
+        state -> Integer,
+        counterparty_address -> Binary,
+        amount -> BigInt,
+        hash_lock -> Binary,
+        pre_image -> Nullable<Binary>,
+        lock_height -> BigInt,
+        lock_tx_id -> Nullable<BigInt>,
+        lock_output_hash -> Nullable<Binary>,
+        counterparty_output_hash -> Nullable<Binary>,
+        settle_tx_id -> Nullable<BigInt>,
+        message -> Text,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    batched_payments (id) {
+        id -> Integer,
+        tx_id -> BigInt,
+        destination_address -> Binary,
+        amount -> BigInt,
+        message -> Text,
+        output_hash -> Binary,
+    }
+}
+
+diesel::table! {
+    burn_transactions (tx_id) {
+        tx_id -> BigInt,
+        amount -> BigInt,
+        claim_public_key -> Nullable<Binary>,
+        reciprocal_claim_public_key -> Binary,
+        commitment -> Binary,
+        ownership_proof -> Nullable<Binary>,
+        range_proof -> Binary,
+        status -> Integer,
+        burned_at -> Timestamp,
+        claimed_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     burnt_proofs (id) {
         id -> Integer,
@@ -66,6 +129,64 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    multisig_sessions (session_id) {
+        session_id -> BigInt,
+        threshold -> Integer,
+        participants_json -> Text,
+        polynomial_key_id -> Text,
+        signing_share_key_id -> Nullable<Text>,
+        group_public_key -> Nullable<Binary>,
+        label -> Text,
+        state -> Integer,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    multisig_signings (signing_id) {
+        signing_id -> BigInt,
+        session_id -> BigInt,
+        message -> Binary,
+        signers_json -> Text,
+        hiding_nonce_key_id -> Nullable<Text>,
+        binding_nonce_key_id -> Nullable<Text>,
+        description -> Text,
+        state -> Integer,
+        signature_nonce -> Nullable<Binary>,
+        signature_key -> Nullable<Binary>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        kind_json -> Text,
+        secret_key_id -> Nullable<Text>,
+        joint_public_key -> Nullable<Binary>,
+        script_offset -> Nullable<Binary>,
+    }
+}
+
+diesel::table! {
+    offline_transactions (tx_id) {
+        tx_id -> BigInt,
+        unsigned_transaction -> Binary,
+        status -> Integer,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    outbound_message_queue (tx_id, message_type) {
+        tx_id -> BigInt,
+        message_type -> Integer,
+        destination_address -> Binary,
+        payload -> Binary,
+        status -> Integer,
+        attempts -> Integer,
+        created_at -> Timestamp,
+        last_attempt_at -> Nullable<Timestamp>,
+    }
+}
+
 diesel::table! {
     outbound_transactions (tx_id) {
         tx_id -> BigInt,
@@ -79,6 +200,7 @@ diesel::table! {
         direct_send_success -> Integer,
         send_count -> Integer,
         last_send_timestamp -> Nullable<Timestamp>,
+        awaiting_cosigners -> Integer,
     }
 }
 
@@ -119,6 +241,46 @@ diesel::table! {
         minimum_value_promise -> BigInt,
         source -> Integer,
         last_validation_timestamp -> Nullable<Timestamp>,
+        frozen -> Integer,
+        hash_lock -> Nullable<Binary>,
+        label -> Nullable<Text>,
+        metadata_json -> Nullable<Text>,
+        account_id -> Integer,
+        subaddress_index -> Nullable<BigInt>,
+    }
+}
+
+diesel::table! {
+    queued_transactions (id) {
+        id -> BigInt,
+        destination_address -> Binary,
+        amount -> BigInt,
+        fee_per_gram -> BigInt,
+        message -> Text,
+        one_sided -> Integer,
+        status -> Integer,
+        tx_id -> Nullable<BigInt>,
+        failure_reason -> Nullable<Text>,
+        queued_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    recurring_payments (id) {
+        id -> BigInt,
+        destination_address -> Binary,
+        amount -> BigInt,
+        fee_per_gram -> BigInt,
+        message -> Text,
+        one_sided -> Integer,
+        interval_secs -> BigInt,
+        next_payment_at -> Timestamp,
+        end_at -> Nullable<Timestamp>,
+        status -> Integer,
+        payments_made -> BigInt,
+        last_tx_id -> Nullable<BigInt>,
+        failure_reason -> Nullable<Text>,
+        created_at -> Timestamp,
     }
 }
 
@@ -132,6 +294,88 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    scheduled_transactions (id) {
+        id -> BigInt,
+        destination_address -> Binary,
+        amount -> BigInt,
+        fee_per_gram -> BigInt,
+        message -> Text,
+        one_sided -> Integer,
+        execute_at_height -> Nullable<BigInt>,
+        execute_at_time -> Nullable<Timestamp>,
+        expires_at_height -> Nullable<BigInt>,
+        expires_at_time -> Nullable<Timestamp>,
+        status -> Integer,
+        tx_id -> Nullable<BigInt>,
+        failure_reason -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    send_templates (id) {
+        id -> BigInt,
+        name -> Text,
+        destination_address -> Binary,
+        default_amount -> Nullable<BigInt>,
+        message -> Text,
+        fee_priority -> Integer,
+        one_sided -> Integer,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    subaddresses (subaddress_index) {
+        subaddress_index -> BigInt,
+        public_key -> Binary,
+        label -> Nullable<Text>,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    superseded_transactions (id) {
+        id -> BigInt,
+        tx_id -> BigInt,
+        fee -> BigInt,
+        transaction_protocol -> Binary,
+        batched_payments -> Text,
+        superseded_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_counterparty_aliases (tx_id) {
+        tx_id -> BigInt,
+        alias -> Text,
+    }
+}
+
+diesel::table! {
+    transaction_events (id) {
+        id -> BigInt,
+        event -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    transaction_memos (tx_id) {
+        tx_id -> BigInt,
+        memo -> Binary,
+    }
+}
+
+diesel::table! {
+    transaction_tags (tx_id, tag) {
+        tx_id -> BigInt,
+        tag -> Text,
+    }
+}
+
 diesel::table! {
     wallet_settings (key) {
         key -> Text,
@@ -140,13 +384,32 @@ diesel::table! {
 }
 
 diesel::allow_tables_to_appear_in_same_query!(
+    accounts,
+    archived_outputs,
+    atomic_swaps,
+    batched_payments,
+    burn_transactions,
     burnt_proofs,
     client_key_values,
     completed_transactions,
     inbound_transactions,
     known_one_sided_payment_scripts,
+    multisig_sessions,
+    multisig_signings,
+    offline_transactions,
+    outbound_message_queue,
     outbound_transactions,
     outputs,
+    queued_transactions,
+    recurring_payments,
     scanned_blocks,
+    scheduled_transactions,
+    send_templates,
+    subaddresses,
+    superseded_transactions,
+    transaction_counterparty_aliases,
+    transaction_events,
+    transaction_memos,
+    transaction_tags,
     wallet_settings,
 );
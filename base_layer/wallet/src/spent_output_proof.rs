@@ -0,0 +1,182 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Signed statements of the outputs a wallet spent, for proof-of-reserves style audits.
+//!
+//! A [SpentOutputProof] lists every output the wallet controlled and spent in a date range together with the block it
+//! was spent in and the kernel of the spending transaction. The statement is signed with the wallet's public key, so an
+//! auditor that knows the wallet address can check that it was issued by the wallet with [SpentOutputProof::verify],
+//! and then check each record against the chain: the output was spent as an input in `spent_in_block` at
+//! `spent_at_height`, and the kernel with `kernel_excess_sig` was mined in the same block.
+
+use chrono::NaiveDateTime;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_common_types::{
+    transaction::TxId,
+    types::{BlockHash, Commitment, HashOutput, PrivateKey, PublicKey, Signature, SignatureWithDomain},
+};
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tari_crypto::hash_domain;
+use thiserror::Error;
+
+/// The serialization version of [SpentOutputStatement]
+pub const SPENT_OUTPUT_PROOF_VERSION: u8 = 0;
+
+hash_domain!(
+    SpentOutputProofDomain,
+    "com.tari.base_layer.wallet.spent_output_proof",
+    1
+);
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SpentOutputProofError {
+    #[error("Unsupported spent output proof version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Could not serialize spent output proof: {0}")]
+    Serialization(String),
+    #[error("Could not deserialize spent output proof: {0}")]
+    Deserialization(String),
+    #[error("Could not sign spent output proof: {0}")]
+    Signing(String),
+}
+
+/// An output spent by the wallet
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpentOutputRecord {
+    pub commitment: Commitment,
+    pub output_hash: HashOutput,
+    /// The value the wallet claims for the output. It cannot be checked against the chain without the opening.
+    pub value: MicroMinotari,
+    pub spent_in_tx_id: TxId,
+    pub spent_at_height: u64,
+    pub spent_in_block: BlockHash,
+    pub spent_timestamp: NaiveDateTime,
+    /// The kernel of the spending transaction
+    pub kernel_excess: Commitment,
+    pub kernel_excess_sig: Signature,
+}
+
+/// The signed part of a [SpentOutputProof]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpentOutputStatement {
+    pub version: u8,
+    pub wallet_public_key: PublicKey,
+    pub from: NaiveDateTime,
+    pub to: NaiveDateTime,
+    pub created_at: NaiveDateTime,
+    pub outputs: Vec<SpentOutputRecord>,
+}
+
+impl SpentOutputStatement {
+    pub fn new(
+        wallet_public_key: PublicKey,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+        created_at: NaiveDateTime,
+        outputs: Vec<SpentOutputRecord>,
+    ) -> Self {
+        Self {
+            version: SPENT_OUTPUT_PROOF_VERSION,
+            wallet_public_key,
+            from,
+            to,
+            created_at,
+            outputs,
+        }
+    }
+
+    /// Signs the statement with the wallet's secret key
+    pub fn sign(self, wallet_secret_key: &PrivateKey) -> Result<SpentOutputProof, SpentOutputProofError> {
+        let message = self.to_bytes()?;
+        let signature = SignatureWithDomain::<SpentOutputProofDomain>::sign(wallet_secret_key, message, &mut OsRng)
+            .map_err(|e| SpentOutputProofError::Signing(e.to_string()))?;
+        Ok(SpentOutputProof {
+            statement: self,
+            public_nonce: signature.get_public_nonce().clone(),
+            signature: signature.get_signature().clone(),
+        })
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, SpentOutputProofError> {
+        bincode::serialize(self).map_err(|e| SpentOutputProofError::Serialization(e.to_string()))
+    }
+}
+
+/// A [SpentOutputStatement] with the wallet's signature over it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpentOutputProof {
+    pub statement: SpentOutputStatement,
+    pub public_nonce: PublicKey,
+    pub signature: PrivateKey,
+}
+
+impl SpentOutputProof {
+    /// Returns true if the statement was signed by the secret key of its `wallet_public_key`
+    pub fn verify(&self) -> bool {
+        let message = match self.statement.to_bytes() {
+            Ok(message) => message,
+            Err(_) => return false,
+        };
+        SignatureWithDomain::<SpentOutputProofDomain>::new(self.public_nonce.clone(), self.signature.clone())
+            .verify(&self.statement.wallet_public_key, message)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SpentOutputProofError> {
+        bincode::serialize(self).map_err(|e| SpentOutputProofError::Serialization(e.to_string()))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SpentOutputProofError> {
+        let proof: Self =
+            bincode::deserialize(bytes).map_err(|e| SpentOutputProofError::Deserialization(e.to_string()))?;
+        if proof.statement.version != SPENT_OUTPUT_PROOF_VERSION {
+            return Err(SpentOutputProofError::UnsupportedVersion(proof.statement.version));
+        }
+        Ok(proof)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+
+    use super::*;
+
+    #[test]
+    fn it_detects_a_tampered_statement() {
+        let (secret_key, public_key) = PublicKey::random_keypair(&mut OsRng);
+        let now = chrono::Utc::now().naive_utc();
+        let statement = SpentOutputStatement::new(public_key, now, now, now, vec![]);
+        let proof = statement.sign(&secret_key).unwrap();
+        assert!(proof.verify());
+        let proof = SpentOutputProof::from_bytes(&proof.to_bytes().unwrap()).unwrap();
+        assert!(proof.verify());
+
+        let mut tampered = proof.clone();
+        tampered.statement.to = tampered.statement.from - chrono::Duration::days(1);
+        assert!(!tampered.verify());
+
+        let mut other_signer = proof;
+        other_signer.statement.wallet_public_key = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        assert!(!other_signer.verify());
+    }
+}
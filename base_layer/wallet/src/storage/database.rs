@@ -22,6 +22,7 @@
 
 use std::{
     fmt::{Display, Error, Formatter},
+    path::Path,
     sync::Arc,
 };
 
@@ -36,7 +37,12 @@ use tari_comms::{
 use tari_key_manager::cipher_seed::CipherSeed;
 use tari_utilities::SafePassword;
 
-use crate::{error::WalletStorageError, utxo_scanner_service::service::ScannedBlock};
+use crate::{
+    error::WalletStorageError,
+    storage::integrity::StorageIntegrityReport,
+    util::wallet_identity::WatchOnlyKeys,
+    utxo_scanner_service::service::ScannedBlock,
+};
 
 const LOG_TARGET: &str = "wallet::database";
 
@@ -62,6 +68,23 @@ pub trait WalletBackend: Send + Sync + Clone {
 
     /// Change the passphrase used to encrypt the database
     fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError>;
+    /// Check whether the given passphrase is the one used to encrypt the database
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError>;
+    /// Replace the main database encryption key with a fresh one protected by the new passphrase, re-encrypting every
+    /// encrypted column. The rotation is committed one table at a time and resumes where it stopped if it is called
+    /// again with the same passphrases after being interrupted.
+    fn rotate_encryption_key(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        progress: &mut dyn FnMut(KeyRotationProgress),
+    ) -> Result<(), WalletStorageError>;
+    /// Check whether a database encryption key rotation was started but not finished
+    fn is_key_rotation_pending(&self) -> Result<bool, WalletStorageError>;
+    /// Whether [WalletBackend::create_snapshot] is available for this backend
+    fn supports_snapshots(&self) -> bool;
+    /// Write an encrypted, transactionally consistent snapshot of the database to the given file
+    fn create_snapshot(&self, path: &Path) -> Result<(), WalletStorageError>;
 
     fn create_burnt_proof(
         &self,
@@ -72,6 +95,8 @@ pub trait WalletBackend: Send + Sync + Clone {
     fn fetch_burnt_proof(&self, id: u32) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError>;
     fn fetch_burnt_proofs(&self) -> Result<Vec<(u32, String, String, NaiveDateTime)>, WalletStorageError>;
     fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError>;
+    /// Check the database for inconsistencies between tables, repairing them if `repair` is set
+    fn check_storage_integrity(&self, repair: bool) -> Result<StorageIntegrityReport, WalletStorageError>;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -83,19 +108,24 @@ pub enum DbKey {
     BaseNodeChainMetadata,
     ClientKey(String),
     MasterSeed,
-    EncryptedMainKey,    // the database encryption key, itself encrypted with the secondary key
-    SecondaryKeySalt,    // the salt used (with the user's passphrase) to derive the secondary derivation key
+    PassphraseSeed, // the master seed combined with the seed passphrase, if the wallet was created with one
+    EncryptedMainKey, // the database encryption key, itself encrypted with the secondary key
+    SecondaryKeySalt, // the salt used (with the user's passphrase) to derive the secondary derivation key
     SecondaryKeyVersion, // the parameter version for the secondary derivation key
-    SecondaryKeyHash,    // a hash commitment to the secondary derivation key
+    SecondaryKeyHash, // a hash commitment to the secondary derivation key
     WalletBirthday,
     LastAccessedNetwork,
     LastAccessedVersion,
+    WatchOnlyKeys,
+    WalletNetwork,
+    KeyRotationState, // the progress of an interrupted database encryption key rotation
 }
 
 impl DbKey {
     pub fn to_key_string(&self) -> String {
         match self {
             DbKey::MasterSeed => "MasterSeed".to_string(),
+            DbKey::PassphraseSeed => "PassphraseSeed".to_string(),
             DbKey::CommsAddress => "CommsAddress".to_string(),
             DbKey::CommsFeatures => "NodeFeatures".to_string(),
             DbKey::TorId => "TorId".to_string(),
@@ -109,6 +139,9 @@ impl DbKey {
             DbKey::CommsIdentitySignature => "CommsIdentitySignature".to_string(),
             DbKey::LastAccessedNetwork => "LastAccessedNetwork".to_string(),
             DbKey::LastAccessedVersion => "LastAccessedVersion".to_string(),
+            DbKey::WatchOnlyKeys => "WatchOnlyKeys".to_string(),
+            DbKey::WalletNetwork => "WalletNetwork".to_string(),
+            DbKey::KeyRotationState => "KeyRotationState".to_string(),
         }
     }
 }
@@ -122,6 +155,7 @@ pub enum DbValue {
     ValueCleared,
     BaseNodeChainMetadata(ChainMetadata),
     MasterSeed(CipherSeed),
+    PassphraseSeed(CipherSeed),
     EncryptedMainKey(String),
     SecondaryKeySalt(String),
     SecondaryKeyVersion(String),
@@ -129,6 +163,9 @@ pub enum DbValue {
     WalletBirthday(String),
     LastAccessedNetwork(String),
     LastAccessedVersion(String),
+    WatchOnlyKeys(Box<WatchOnlyKeys>),
+    WalletNetwork(String),
+    KeyRotationState(String),
 }
 
 #[derive(Clone)]
@@ -137,10 +174,26 @@ pub enum DbKeyValuePair {
     TorId(TorIdentity),
     BaseNodeChainMetadata(ChainMetadata),
     MasterSeed(CipherSeed),
+    PassphraseSeed(CipherSeed),
+    MasterAndPassphraseSeed(CipherSeed, CipherSeed),
     CommsAddress(Multiaddr),
     CommsFeatures(PeerFeatures),
     CommsIdentitySignature(Box<IdentitySignature>),
     NetworkAndVersion((String, String)),
+    WatchOnlyKeys(Box<WatchOnlyKeys>),
+    WalletNetwork(String),
+}
+
+/// Progress of a database encryption key rotation, reported after each table has been re-encrypted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyRotationProgress {
+    /// The table that was just re-encrypted
+    pub table: &'static str,
+    /// The number of rows that were re-encrypted in the table
+    pub rows: usize,
+    /// The number of tables that have been re-encrypted so far, including those from an interrupted run
+    pub tables_completed: usize,
+    pub total_tables: usize,
 }
 
 pub enum WriteOperation {
@@ -165,6 +218,36 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    pub fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+        self.db.verify_passphrase(passphrase)
+    }
+
+    /// Re-encrypt the whole database under a new main key protected by `new`, reporting progress after each table.
+    /// Other database users wait for the rotation to finish and then carry on with the new key.
+    pub fn rotate_encryption_key<F>(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        mut progress: F,
+    ) -> Result<(), WalletStorageError>
+    where
+        F: FnMut(KeyRotationProgress),
+    {
+        self.db.rotate_encryption_key(existing, new, &mut progress)
+    }
+
+    pub fn is_key_rotation_pending(&self) -> Result<bool, WalletStorageError> {
+        self.db.is_key_rotation_pending()
+    }
+
+    pub fn supports_snapshots(&self) -> bool {
+        self.db.supports_snapshots()
+    }
+
+    pub fn create_snapshot(&self, path: &Path) -> Result<(), WalletStorageError> {
+        self.db.create_snapshot(path)
+    }
+
     pub fn get_master_seed(&self) -> Result<Option<CipherSeed>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::MasterSeed) {
             Ok(None) => Ok(None),
@@ -186,6 +269,56 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    /// Returns the seed the wallet keys are derived from if the wallet was created with a seed passphrase, see
+    /// [CipherSeed::derive_with_seed_passphrase]
+    pub fn get_passphrase_seed(&self) -> Result<Option<CipherSeed>, WalletStorageError> {
+        let c = match self.db.fetch(&DbKey::PassphraseSeed) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::PassphraseSeed(k))) => Ok(Some(k)),
+            Ok(Some(other)) => unexpected_result(DbKey::PassphraseSeed, other),
+            Err(e) => log_error(DbKey::PassphraseSeed, e),
+        }?;
+        Ok(c)
+    }
+
+    pub fn set_passphrase_seed(&self, seed: CipherSeed) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::PassphraseSeed(seed)))?;
+        Ok(())
+    }
+
+    /// Stores the master seed of a new wallet together with the seed derived from it with a seed passphrase, so that
+    /// either both are stored or neither is
+    pub fn set_master_and_passphrase_seed(
+        &self,
+        master_seed: CipherSeed,
+        passphrase_seed: CipherSeed,
+    ) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::MasterAndPassphraseSeed(
+                master_seed,
+                passphrase_seed,
+            )))?;
+        Ok(())
+    }
+
+    /// Returns the keys this wallet watches if it is a watch-only wallet
+    pub fn get_watch_only_keys(&self) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        let c = match self.db.fetch(&DbKey::WatchOnlyKeys) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WatchOnlyKeys(k))) => Ok(Some(*k)),
+            Ok(Some(other)) => unexpected_result(DbKey::WatchOnlyKeys, other),
+            Err(e) => log_error(DbKey::WatchOnlyKeys, e),
+        }?;
+        Ok(c)
+    }
+
+    pub fn set_watch_only_keys(&self, keys: WatchOnlyKeys) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::WatchOnlyKeys(Box::new(keys))))?;
+        Ok(())
+    }
+
     pub fn get_tor_id(&self) -> Result<Option<TorIdentity>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::TorId) {
             Ok(None) => Ok(None),
@@ -281,6 +414,62 @@ where T: WalletBackend + 'static
         Ok(())
     }
 
+    /// Returns the network this wallet database is bound to, if it has been bound
+    pub fn get_wallet_network(&self) -> Result<Option<String>, WalletStorageError> {
+        let c = match self.db.fetch(&DbKey::WalletNetwork) {
+            Ok(None) => Ok(None),
+            Ok(Some(DbValue::WalletNetwork(n))) => Ok(Some(n)),
+            Ok(Some(other)) => unexpected_result(DbKey::WalletNetwork, other),
+            Err(e) => log_error(DbKey::WalletNetwork, e),
+        }?;
+        Ok(c)
+    }
+
+    /// Checks that this wallet database belongs to `network`, binding it to `network` if it has never been bound.
+    /// Databases created before the binding existed are bound to the network they were last opened on, so a wallet
+    /// that was last used on another network is refused rather than silently mixed with the wrong chain.
+    pub fn ensure_network(&self, network: &str) -> Result<(), WalletStorageError> {
+        let bound = match self.get_wallet_network()? {
+            Some(bound) => bound,
+            None => {
+                let last_accessed = match self.db.fetch(&DbKey::LastAccessedNetwork)? {
+                    Some(DbValue::LastAccessedNetwork(n)) => n,
+                    _ => network.to_string(),
+                };
+                self.set_wallet_network(last_accessed.clone())?;
+                last_accessed
+            },
+        };
+        if bound != network {
+            return Err(WalletStorageError::NetworkMismatch {
+                bound,
+                requested: network.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Rebinds this wallet database to `network`. This is the explicit migration path for a wallet whose stored
+    /// state is known to be valid on the new network; in most cases [clone_wallet_for_network] should be used
+    /// instead.
+    ///
+    /// [clone_wallet_for_network]: crate::storage::sqlite_utilities::clone_wallet_for_network
+    pub fn rebind_network(&self, network: &str) -> Result<(), WalletStorageError> {
+        if let Some(bound) = self.get_wallet_network()? {
+            warn!(
+                target: LOG_TARGET,
+                "Rebinding wallet database from network {} to {}", bound, network
+            );
+        }
+        self.set_wallet_network(network.to_string())
+    }
+
+    fn set_wallet_network(&self, network: String) -> Result<(), WalletStorageError> {
+        self.db
+            .write(WriteOperation::Insert(DbKeyValuePair::WalletNetwork(network)))?;
+        Ok(())
+    }
+
     pub fn get_client_key_value(&self, key: String) -> Result<Option<String>, WalletStorageError> {
         let c = match self.db.fetch(&DbKey::ClientKey(key.clone())) {
             Ok(None) => Ok(None),
@@ -384,12 +573,17 @@ where T: WalletBackend + 'static
     pub fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError> {
         self.db.delete_burnt_proof(id)
     }
+
+    pub fn check_storage_integrity(&self, repair: bool) -> Result<StorageIntegrityReport, WalletStorageError> {
+        self.db.check_storage_integrity(repair)
+    }
 }
 
 impl Display for DbValue {
     fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
         match self {
             DbValue::MasterSeed(k) => f.write_str(&format!("MasterSeed: {:?}", k)),
+            DbValue::PassphraseSeed(k) => f.write_str(&format!("PassphraseSeed: {:?}", k)),
             DbValue::ClientValue(v) => f.write_str(&format!("ClientValue: {:?}", v)),
             DbValue::ValueCleared => f.write_str("ValueCleared"),
             DbValue::CommsFeatures(_) => f.write_str("Node features"),
@@ -403,7 +597,10 @@ impl Display for DbValue {
             DbValue::WalletBirthday(b) => f.write_str(&format!("WalletBirthday: {}", b)),
             DbValue::CommsIdentitySignature(_) => f.write_str("CommsIdentitySignature"),
             DbValue::LastAccessedNetwork(network) => f.write_str(&format!("LastAccessedNetwork: {}", network)),
+            DbValue::WalletNetwork(network) => f.write_str(&format!("WalletNetwork: {}", network)),
             DbValue::LastAccessedVersion(version) => f.write_str(&format!("LastAccessedVersion: {}", version)),
+            DbValue::WatchOnlyKeys(keys) => f.write_str(&format!("WatchOnlyKeys: {}", keys.spend_public_key)),
+            DbValue::KeyRotationState(_) => f.write_str("KeyRotationState"),
         }
     }
 }
@@ -436,7 +633,7 @@ mod test {
     use tempfile::tempdir;
 
     use crate::storage::{
-        database::WalletDatabase,
+        database::{WalletBackend, WalletDatabase},
         sqlite_db::wallet::WalletSqliteDatabase,
         sqlite_utilities::run_migration_and_create_sqlite_connection,
     };
@@ -448,8 +645,69 @@ mod test {
         let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
 
         let passphrase = SafePassword::from("my secret lovely passphrase");
-        let db = WalletDatabase::new(WalletSqliteDatabase::new(connection, passphrase).unwrap());
+        check_database_crud(WalletDatabase::new(
+            WalletSqliteDatabase::new(connection, passphrase).unwrap(),
+        ));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_postgres_database_crud() {
+        use crate::{
+            storage::{
+                postgres_db::WalletPostgresDatabase,
+                postgres_utilities::run_migration_and_create_postgres_connection,
+            },
+            test_utils::make_postgres_database_url,
+        };
+
+        let connection = run_migration_and_create_postgres_connection(&make_postgres_database_url(), 4).unwrap();
+
+        let passphrase = SafePassword::from("my secret lovely passphrase");
+        check_database_crud(WalletDatabase::new(
+            WalletPostgresDatabase::new(connection, passphrase).unwrap(),
+        ));
+    }
 
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_postgres_key_rotation_and_integrity_check() {
+        use crate::{
+            storage::postgres_utilities::initialize_postgres_database_backends,
+            test_utils::make_postgres_database_url,
+        };
+
+        let database_url = make_postgres_database_url();
+        let (wallet_backend, _, _, _, _key_manager_backend) =
+            initialize_postgres_database_backends(&database_url, "passphrase".to_string().into(), 4).unwrap();
+        let db = WalletDatabase::new(wallet_backend);
+        assert!(!db.supports_snapshots());
+        assert!(db.check_storage_integrity(true).unwrap().is_clean());
+
+        let seed = CipherSeed::new();
+        db.set_master_seed(seed.clone()).unwrap();
+        db.set_client_key_value("key".to_string(), "value".to_string()).unwrap();
+
+        let mut progress = Vec::new();
+        db.rotate_encryption_key(
+            &"passphrase".to_string().into(),
+            &"new passphrase".to_string().into(),
+            |p| progress.push(p),
+        )
+        .unwrap();
+        let last = progress.last().unwrap();
+        assert_eq!(last.tables_completed, last.total_tables);
+        assert_eq!(progress.iter().map(|p| p.rows).sum::<usize>(), 2);
+        assert!(!db.is_key_rotation_pending().unwrap());
+        assert_eq!(db.get_master_seed().unwrap().unwrap().entropy(), seed.entropy());
+        assert_eq!(db.get_client_key_value("key".to_string()).unwrap().unwrap(), "value");
+
+        // Only the new passphrase opens the database
+        assert!(initialize_postgres_database_backends(&database_url, "passphrase".to_string().into(), 4).is_err());
+        assert!(initialize_postgres_database_backends(&database_url, "new passphrase".to_string().into(), 4).is_ok());
+    }
+
+    fn check_database_crud<T: WalletBackend + 'static>(db: WalletDatabase<T>) {
         // Test wallet settings
         assert!(db.get_master_seed().unwrap().is_none());
         let seed = CipherSeed::new();
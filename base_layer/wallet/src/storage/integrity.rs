@@ -0,0 +1,100 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::fmt::{Display, Error, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// The kind of inconsistency found by a storage integrity check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IntegrityIssueKind {
+    /// Rows that refer to a transaction that is not in the database
+    OrphanedRows,
+    /// An output encumbered to a transaction that is not in the database
+    EncumberedOutputWithoutTransaction,
+    /// An output whose status disagrees with its spending transaction or with the chain, so that it is counted in the
+    /// wrong balance
+    BalanceMismatch,
+}
+
+impl Display for IntegrityIssueKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            IntegrityIssueKind::OrphanedRows => write!(f, "Orphaned rows"),
+            IntegrityIssueKind::EncumberedOutputWithoutTransaction => {
+                write!(f, "Encumbered output without transaction")
+            },
+            IntegrityIssueKind::BalanceMismatch => write!(f, "Balance mismatch"),
+        }
+    }
+}
+
+/// An inconsistency found by a storage integrity check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageIntegrityIssue {
+    pub kind: IntegrityIssueKind,
+    pub table: String,
+    pub description: String,
+    /// The number of rows affected
+    pub count: u64,
+}
+
+impl Display for StorageIntegrityIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(
+            f,
+            "{} in '{}' ({} row(s)): {}",
+            self.kind, self.table, self.count, self.description
+        )
+    }
+}
+
+/// The result of a storage integrity check. When `repaired` is set the issues listed have been fixed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StorageIntegrityReport {
+    pub issues: Vec<StorageIntegrityIssue>,
+    pub repaired: bool,
+}
+
+impl StorageIntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl Display for StorageIntegrityReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        if self.is_clean() {
+            return write!(f, "No storage integrity issues found");
+        }
+        write!(
+            f,
+            "{} storage integrity issue(s) found{}",
+            self.issues.len(),
+            if self.repaired { " and repaired" } else { "" }
+        )?;
+        for issue in &self.issues {
+            write!(f, "\n  {}", issue)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Sources for the secret that protects the wallet database encryption key.
+//!
+//! The wallet database is encrypted with a random main key, which is itself encrypted with a secondary key derived
+//! from a secret using `Argon2`. By default that secret is the user's passphrase, but platforms that offer a hardware
+//! backed keystore (Android Keystore, iOS Secure Enclave, a TPM) can instead supply a secret that never leaves the
+//! keystore unprotected by implementing [DbKeyProvider].
+
+use tari_utilities::SafePassword;
+
+use crate::error::WalletStorageError;
+
+/// A provider of the secret used to unlock the wallet database encryption key
+pub trait DbKeyProvider: Send + Sync {
+    /// A short, human-readable name for this provider, used in logs
+    fn name(&self) -> &'static str;
+
+    /// Return the secret used to derive the key that protects the database main key. This is called once when the
+    /// database is opened, implementations may prompt the user (e.g. for biometric authentication) at this point.
+    fn fetch_secret(&self) -> Result<SafePassword, WalletStorageError>;
+}
+
+/// The default provider, which uses the user's passphrase as the secret
+pub struct PassphraseKeyProvider {
+    passphrase: SafePassword,
+}
+
+impl PassphraseKeyProvider {
+    pub fn new(passphrase: SafePassword) -> Self {
+        Self { passphrase }
+    }
+}
+
+impl DbKeyProvider for PassphraseKeyProvider {
+    fn name(&self) -> &'static str {
+        "passphrase"
+    }
+
+    fn fetch_secret(&self) -> Result<SafePassword, WalletStorageError> {
+        Ok(self.passphrase.clone())
+    }
+}
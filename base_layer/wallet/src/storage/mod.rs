@@ -31,5 +31,11 @@
 //     any unwanted changes)
 
 pub mod database;
+pub mod integrity;
+pub mod key_provider;
+#[cfg(feature = "postgres")]
+pub mod postgres_db;
+#[cfg(feature = "postgres")]
+pub mod postgres_utilities;
 pub mod sqlite_db;
 pub mod sqlite_utilities;
@@ -0,0 +1,784 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    convert::TryFrom,
+    mem::size_of,
+    path::Path,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use chrono::NaiveDateTime;
+use diesel::{pg::PgConnection, prelude::*};
+use log::*;
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+    types::{PrivateKey, PublicKey},
+};
+use tari_comms::{
+    multiaddr::Multiaddr,
+    peer_manager::{IdentitySignature, PeerFeatures},
+    tor::TorIdentity,
+};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    key_manager_service::storage::postgres_db::{reencrypt_imported_keys, reencrypt_key_manager_states},
+};
+use tari_utilities::{
+    hex::{from_hex, Hex},
+    ByteArray,
+    Hidden,
+    SafePassword,
+};
+
+use crate::{
+    error::WalletStorageError,
+    schema::{burnt_proofs, client_key_values, scanned_blocks, wallet_settings},
+    storage::{
+        database::{DbKey, DbKeyValuePair, DbValue, KeyRotationProgress, WalletBackend, WriteOperation},
+        integrity::StorageIntegrityReport,
+        key_provider::{DbKeyProvider, PassphraseKeyProvider},
+        postgres_utilities::WalletPostgresConnection,
+        sqlite_db::{
+            integrity_check::check_postgres_storage_integrity,
+            scanned_blocks::ScannedBlockSql,
+            wallet::{
+                reencrypt_value,
+                BurntProofSql,
+                ClientKeyValueSql,
+                DatabaseEncryptionFields,
+                KeyRotationState,
+                KeyRotationStep,
+                WalletMainEncryptionKey,
+                ENCRYPTED_WALLET_SETTINGS,
+            },
+        },
+    },
+    transaction_service::storage::postgres_db::reencrypt_table as reencrypt_transaction_table,
+    util::wallet_identity::WatchOnlyKeys,
+    utxo_scanner_service::service::ScannedBlock,
+};
+
+const LOG_TARGET: &str = "wallet::storage::postgres_db";
+
+/// A PostgreSQL backend for the wallet settings, for deployments that keep their wallets on a database server rather
+/// than in a Sqlite file. Values are encrypted exactly as in
+/// [WalletSqliteDatabase](super::sqlite_db::wallet::WalletSqliteDatabase).
+#[derive(Clone)]
+pub struct WalletPostgresDatabase {
+    database_connection: WalletPostgresConnection,
+    cipher: Arc<RwLock<XChaCha20Poly1305>>,
+}
+
+impl WalletPostgresDatabase {
+    pub fn new(
+        database_connection: WalletPostgresConnection,
+        passphrase: SafePassword,
+    ) -> Result<Self, WalletStorageError> {
+        Self::new_with_key_provider(database_connection, &PassphraseKeyProvider::new(passphrase))
+    }
+
+    /// Open the wallet database, unlocking the database encryption key with the secret from the given provider
+    pub fn new_with_key_provider(
+        database_connection: WalletPostgresConnection,
+        key_provider: &dyn DbKeyProvider,
+    ) -> Result<Self, WalletStorageError> {
+        debug!(
+            target: LOG_TARGET,
+            "Unlocking wallet database using the '{}' key provider",
+            key_provider.name()
+        );
+        let secret = key_provider.fetch_secret()?;
+        let mut conn = database_connection.get_pooled_connection()?;
+        let cipher = get_db_cipher(&mut conn, &secret)?;
+
+        Ok(Self {
+            database_connection,
+            cipher: Arc::new(RwLock::new(cipher)),
+        })
+    }
+
+    pub fn cipher(&self) -> XChaCha20Poly1305 {
+        let cipher = acquire_read_lock!(self.cipher);
+        (*cipher).clone()
+    }
+
+    /// The cipher shared with the other wallet backends
+    pub fn shared_cipher(&self) -> Arc<RwLock<XChaCha20Poly1305>> {
+        self.cipher.clone()
+    }
+
+    fn set_encrypted_setting(
+        &self,
+        key: DbKey,
+        domain: &[u8],
+        plaintext: Hidden<Vec<u8>>,
+        conn: &mut PgConnection,
+    ) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(&cipher, domain.to_vec(), plaintext)
+            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+        set_setting(&key, ciphertext_integral_nonce.to_hex(), conn)
+    }
+
+    fn get_decrypted_setting(
+        &self,
+        key: &DbKey,
+        domain: &[u8],
+        conn: &mut PgConnection,
+    ) -> Result<Option<Hidden<Vec<u8>>>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        get_setting(key, conn)?
+            .map(|value| {
+                decrypt_bytes_integral_nonce(&cipher, domain.to_vec(), &from_hex(&value)?)
+                    .map(Hidden::hide)
+                    .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))
+            })
+            .transpose()
+    }
+
+    fn set_master_seed(&self, seed: &CipherSeed, conn: &mut PgConnection) -> Result<(), WalletStorageError> {
+        if get_setting(&DbKey::WalletBirthday, conn)?.is_none() {
+            set_setting(&DbKey::WalletBirthday, seed.birthday().to_string(), conn)?;
+        }
+        self.set_encrypted_setting(
+            DbKey::MasterSeed,
+            b"wallet_setting_master_seed",
+            Hidden::hide(seed.encipher(None)?),
+            conn,
+        )
+    }
+
+    fn get_cipher_seed(
+        &self,
+        key: &DbKey,
+        domain: &[u8],
+        conn: &mut PgConnection,
+    ) -> Result<Option<CipherSeed>, WalletStorageError> {
+        self.get_decrypted_setting(key, domain, conn)?
+            .map(|bytes| CipherSeed::from_enciphered_bytes(bytes.reveal(), None).map_err(Into::into))
+            .transpose()
+    }
+
+    fn set_watch_only_keys(&self, keys: &WatchOnlyKeys, conn: &mut PgConnection) -> Result<(), WalletStorageError> {
+        let mut key_bytes = keys.view_key.as_bytes().to_vec();
+        key_bytes.extend_from_slice(keys.spend_public_key.as_bytes());
+        self.set_encrypted_setting(
+            DbKey::WatchOnlyKeys,
+            b"wallet_setting_watch_only_keys",
+            Hidden::hide(key_bytes),
+            conn,
+        )
+    }
+
+    fn get_watch_only_keys(&self, conn: &mut PgConnection) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        self.get_decrypted_setting(&DbKey::WatchOnlyKeys, b"wallet_setting_watch_only_keys", conn)?
+            .map(|bytes| {
+                let (view_key, spend_public_key) = bytes.reveal().split_at(PrivateKey::key_length());
+                Ok(WatchOnlyKeys {
+                    view_key: PrivateKey::from_bytes(view_key)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+                    spend_public_key: PublicKey::from_bytes(spend_public_key)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+                })
+            })
+            .transpose()
+    }
+
+    fn set_tor_id(&self, tor: TorIdentity, conn: &mut PgConnection) -> Result<(), WalletStorageError> {
+        let bytes =
+            Hidden::hide(bincode::serialize(&tor).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?);
+        self.set_encrypted_setting(DbKey::TorId, b"wallet_setting_tor_id", bytes, conn)
+    }
+
+    fn get_tor_id(&self, conn: &mut PgConnection) -> Result<Option<TorIdentity>, WalletStorageError> {
+        self.get_decrypted_setting(&DbKey::TorId, b"wallet_setting_tor_id", conn)?
+            .map(|bytes| {
+                bincode::deserialize(bytes.reveal()).map_err(|e| WalletStorageError::ConversionError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn get_chain_metadata(&self, conn: &mut PgConnection) -> Result<Option<ChainMetadata>, WalletStorageError> {
+        get_setting(&DbKey::BaseNodeChainMetadata, conn)?
+            .map(|value| {
+                bincode::deserialize(&from_hex(&value)?).map_err(|e| WalletStorageError::ConversionError(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn decrypt_value<T: Encryptable<XChaCha20Poly1305>>(&self, o: T) -> Result<T, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        o.decrypt(&cipher)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))
+    }
+
+    fn insert_key_value_pair(&self, kvp: DbKeyValuePair) -> Result<Option<DbValue>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        match kvp {
+            DbKeyValuePair::MasterSeed(seed) => self.set_master_seed(&seed, &mut conn)?,
+            DbKeyValuePair::PassphraseSeed(seed) => self.set_encrypted_setting(
+                DbKey::PassphraseSeed,
+                b"wallet_setting_passphrase_seed",
+                Hidden::hide(seed.encipher(None)?),
+                &mut conn,
+            )?,
+            DbKeyValuePair::MasterAndPassphraseSeed(master_seed, passphrase_seed) => conn
+                .transaction::<_, WalletStorageError, _>(|conn| {
+                    self.set_master_seed(&master_seed, conn)?;
+                    self.set_encrypted_setting(
+                        DbKey::PassphraseSeed,
+                        b"wallet_setting_passphrase_seed",
+                        Hidden::hide(passphrase_seed.encipher(None)?),
+                        conn,
+                    )
+                })?,
+            DbKeyValuePair::TorId(node_id) => self.set_tor_id(node_id, &mut conn)?,
+            DbKeyValuePair::BaseNodeChainMetadata(metadata) => {
+                let bytes =
+                    bincode::serialize(&metadata).map_err(|e| WalletStorageError::ConversionError(e.to_string()))?;
+                set_setting(&DbKey::BaseNodeChainMetadata, bytes.to_hex(), &mut conn)?;
+            },
+            DbKeyValuePair::ClientKeyValue(k, v) => {
+                // First see if we will overwrite a value so we can return the old value
+                let value_to_return = client_key_values::table
+                    .filter(client_key_values::key.eq(&k))
+                    .first::<ClientKeyValueSql>(&mut conn)
+                    .optional()?
+                    .map(|found_value| self.decrypt_value(found_value))
+                    .transpose()?;
+
+                let client_key_value = {
+                    let cipher = acquire_read_lock!(self.cipher);
+                    ClientKeyValueSql::new(k, v, &cipher)?
+                };
+                diesel::insert_into(client_key_values::table)
+                    .values(&client_key_value)
+                    .on_conflict(client_key_values::key)
+                    .do_update()
+                    .set(client_key_values::value.eq(&client_key_value.value))
+                    .execute(&mut conn)?;
+
+                return Ok(value_to_return.map(|v| DbValue::ClientValue(v.value)));
+            },
+            DbKeyValuePair::CommsAddress(ca) => set_setting(&DbKey::CommsAddress, ca.to_string(), &mut conn)?,
+            DbKeyValuePair::CommsFeatures(cf) => set_setting(&DbKey::CommsFeatures, cf.bits().to_string(), &mut conn)?,
+            DbKeyValuePair::CommsIdentitySignature(identity_sig) => set_setting(
+                &DbKey::CommsIdentitySignature,
+                identity_sig.to_bytes().to_hex(),
+                &mut conn,
+            )?,
+            DbKeyValuePair::WatchOnlyKeys(keys) => self.set_watch_only_keys(&keys, &mut conn)?,
+            DbKeyValuePair::NetworkAndVersion((network, version)) => {
+                set_setting(&DbKey::LastAccessedNetwork, network, &mut conn)?;
+                set_setting(&DbKey::LastAccessedVersion, version, &mut conn)?;
+            },
+            DbKeyValuePair::WalletNetwork(network) => set_setting(&DbKey::WalletNetwork, network, &mut conn)?,
+        }
+
+        Ok(None)
+    }
+
+    fn remove_key(&self, k: DbKey) -> Result<Option<DbValue>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        match k {
+            DbKey::MasterSeed | DbKey::PassphraseSeed | DbKey::TorId => {
+                clear_setting(&k, &mut conn)?;
+            },
+            DbKey::ClientKey(ref k) => {
+                let num_deleted =
+                    diesel::delete(client_key_values::table.filter(client_key_values::key.eq(k))).execute(&mut conn)?;
+                if num_deleted > 0 {
+                    return Ok(Some(DbValue::ValueCleared));
+                }
+            },
+            DbKey::CommsFeatures |
+            DbKey::CommsAddress |
+            DbKey::BaseNodeChainMetadata |
+            DbKey::EncryptedMainKey |
+            DbKey::SecondaryKeyVersion |
+            DbKey::SecondaryKeySalt |
+            DbKey::SecondaryKeyHash |
+            DbKey::WalletBirthday |
+            DbKey::CommsIdentitySignature |
+            DbKey::LastAccessedNetwork |
+            DbKey::LastAccessedVersion |
+            DbKey::WatchOnlyKeys |
+            DbKey::WalletNetwork |
+            DbKey::KeyRotationState => {
+                return Err(WalletStorageError::OperationNotSupported);
+            },
+        };
+        Ok(None)
+    }
+
+    fn burnt_proof_tuple(
+        &self,
+        entry: BurntProofSql,
+    ) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError> {
+        let id = entry.id;
+        let decrypted = self.decrypt_value(entry).map_err(|e| {
+            error!(target: LOG_TARGET, "Failed to decrypt burnt proof: id={}: {}", id, e);
+            e
+        })?;
+        Ok((
+            decrypted.id as u32,
+            decrypted.reciprocal_claim_public_key,
+            decrypted.payload,
+            decrypted.burned_at,
+        ))
+    }
+}
+
+impl WalletBackend for WalletPostgresDatabase {
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        let result = match key {
+            DbKey::MasterSeed => self
+                .get_cipher_seed(key, b"wallet_setting_master_seed", &mut conn)?
+                .map(DbValue::MasterSeed),
+            DbKey::PassphraseSeed => self
+                .get_cipher_seed(key, b"wallet_setting_passphrase_seed", &mut conn)?
+                .map(DbValue::PassphraseSeed),
+            DbKey::ClientKey(k) => client_key_values::table
+                .filter(client_key_values::key.eq(k))
+                .first::<ClientKeyValueSql>(&mut conn)
+                .optional()?
+                .map(|v| self.decrypt_value(v).map(|v| DbValue::ClientValue(v.value)))
+                .transpose()?,
+            DbKey::CommsAddress => get_setting(key, &mut conn)?
+                .map(|value| {
+                    Multiaddr::from_str(&value)
+                        .map(DbValue::CommsAddress)
+                        .map_err(|e| WalletStorageError::ConversionError(e.to_string()))
+                })
+                .transpose()?,
+            DbKey::TorId => self.get_tor_id(&mut conn)?.map(DbValue::TorId),
+            DbKey::CommsFeatures => get_setting(key, &mut conn)?
+                .map(|value| u32::from_str(&value).map_err(|e| WalletStorageError::ConversionError(e.to_string())))
+                .transpose()?
+                .and_then(PeerFeatures::from_bits)
+                .map(DbValue::CommsFeatures),
+            DbKey::BaseNodeChainMetadata => self.get_chain_metadata(&mut conn)?.map(DbValue::BaseNodeChainMetadata),
+            DbKey::EncryptedMainKey => get_setting(key, &mut conn)?.map(DbValue::EncryptedMainKey),
+            DbKey::SecondaryKeyVersion => get_setting(key, &mut conn)?.map(DbValue::SecondaryKeyVersion),
+            DbKey::SecondaryKeySalt => get_setting(key, &mut conn)?.map(DbValue::SecondaryKeySalt),
+            DbKey::SecondaryKeyHash => get_setting(key, &mut conn)?.map(DbValue::SecondaryKeyHash),
+            DbKey::WalletBirthday => get_setting(key, &mut conn)?.map(DbValue::WalletBirthday),
+            DbKey::LastAccessedNetwork => get_setting(key, &mut conn)?.map(DbValue::LastAccessedNetwork),
+            DbKey::LastAccessedVersion => get_setting(key, &mut conn)?.map(DbValue::LastAccessedVersion),
+            DbKey::WalletNetwork => get_setting(key, &mut conn)?.map(DbValue::WalletNetwork),
+            DbKey::KeyRotationState => get_setting(key, &mut conn)?.map(DbValue::KeyRotationState),
+            DbKey::WatchOnlyKeys => self
+                .get_watch_only_keys(&mut conn)?
+                .map(|keys| DbValue::WatchOnlyKeys(Box::new(keys))),
+            DbKey::CommsIdentitySignature => get_setting(key, &mut conn)?
+                .and_then(|s| from_hex(&s).ok())
+                .and_then(|bytes| IdentitySignature::from_bytes(&bytes).ok())
+                .map(Box::new)
+                .map(DbValue::CommsIdentitySignature),
+        };
+
+        Ok(result)
+    }
+
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, WalletStorageError> {
+        match op {
+            WriteOperation::Insert(kvp) => self.insert_key_value_pair(kvp),
+            WriteOperation::Remove(k) => self.remove_key(k),
+        }
+    }
+
+    fn get_scanned_blocks(&self) -> Result<Vec<ScannedBlock>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        scanned_blocks::table
+            .order(scanned_blocks::height.desc())
+            .load::<ScannedBlockSql>(&mut conn)?
+            .into_iter()
+            .map(ScannedBlock::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(WalletStorageError::ConversionError)
+    }
+
+    fn save_scanned_block(&self, scanned_block: ScannedBlock) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(scanned_blocks::table)
+            .values(ScannedBlockSql::from(scanned_block))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn clear_scanned_blocks(&self) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(scanned_blocks::table).execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn clear_scanned_blocks_from_and_higher(&self, height: u64) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(scanned_blocks::table.filter(scanned_blocks::height.ge(height as i64))).execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn clear_scanned_blocks_before_height(
+        &self,
+        height: u64,
+        exclude_recovered: bool,
+    ) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = diesel::delete(scanned_blocks::table)
+            .into_boxed()
+            .filter(scanned_blocks::height.lt(height as i64));
+        if exclude_recovered {
+            query = query.filter(
+                scanned_blocks::num_outputs
+                    .is_null()
+                    .or(scanned_blocks::num_outputs.eq(0)),
+            );
+        }
+        query.execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn change_passphrase(&self, existing: &SafePassword, new: &SafePassword) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let data = read_encryption_fields(&mut conn)?.ok_or_else(|| {
+            WalletStorageError::UnexpectedResult("Unable to get valid key-related data from database".into())
+        })?;
+        let main_key = data.unseal(existing)?;
+        write_encryption_fields(&DatabaseEncryptionFields::seal(&main_key, new)?, &mut conn)
+    }
+
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        match read_encryption_fields(&mut conn)? {
+            Some(data) => data.verify(passphrase),
+            None => Err(WalletStorageError::UnexpectedResult(
+                "Unable to get valid key-related data from database".into(),
+            )),
+        }
+    }
+
+    fn rotate_encryption_key(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        progress: &mut dyn FnMut(KeyRotationProgress),
+    ) -> Result<(), WalletStorageError> {
+        // Take the connection before the cipher, so that backends waiting on the cipher cannot exhaust the pool
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut cipher = acquire_write_lock!(self.cipher);
+
+        let current_fields = read_encryption_fields(&mut conn)?.ok_or_else(|| {
+            WalletStorageError::UnexpectedResult("Unable to get valid key-related data from database".into())
+        })?;
+        let old_cipher = XChaCha20Poly1305::new(Key::from_slice(current_fields.unseal(existing)?.reveal()));
+
+        let mut state = match read_key_rotation_state(&mut conn)? {
+            Some(state) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Resuming database encryption key rotation after {} tables", state.tables_completed
+                );
+                state
+            },
+            None => {
+                let mut main_key = WalletMainEncryptionKey::from(vec![0u8; size_of::<Key>()]);
+                OsRng.fill_bytes(main_key.reveal_mut());
+                let state = KeyRotationState::new(&DatabaseEncryptionFields::seal(&main_key, new)?);
+                set_setting(&DbKey::KeyRotationState, state.to_setting()?, &mut conn)?;
+                state
+            },
+        };
+        // When resuming, this also ensures the rotation continues with the passphrase it was started with
+        let new_fields = state.encryption_fields()?;
+        let new_cipher = XChaCha20Poly1305::new(Key::from_slice(new_fields.unseal(new)?.reveal()));
+
+        let steps = KeyRotationStep::all();
+        let total_tables = steps.len();
+        for step in steps.into_iter().skip(state.tables_completed) {
+            let mut next_state = state.clone();
+            next_state.tables_completed += 1;
+            let rows = conn.transaction::<_, WalletStorageError, _>(|conn| {
+                let rows = reencrypt_step(step, conn, &old_cipher, &new_cipher)?;
+                set_setting(&DbKey::KeyRotationState, next_state.to_setting()?, conn)?;
+                Ok(rows)
+            })?;
+            state = next_state;
+            debug!(
+                target: LOG_TARGET,
+                "Re-encrypted {} rows of `{}` ({}/{})",
+                rows,
+                step.table_name(),
+                state.tables_completed,
+                total_tables
+            );
+            progress(KeyRotationProgress {
+                table: step.table_name(),
+                rows,
+                tables_completed: state.tables_completed,
+                total_tables,
+            });
+        }
+
+        // Switch over to the new key fields and finish the rotation atomically
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            for (key, value) in new_fields.encode() {
+                set_setting(&key, value, conn)?;
+            }
+            clear_setting(&DbKey::KeyRotationState, conn)?;
+            Ok(())
+        })?;
+        *cipher = new_cipher;
+        info!(target: LOG_TARGET, "Database encryption key rotation completed");
+
+        Ok(())
+    }
+
+    fn is_key_rotation_pending(&self) -> Result<bool, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        Ok(read_key_rotation_state(&mut conn)?.is_some())
+    }
+
+    /// Snapshots are written from a Sqlite copy of the database file, so a database kept on a PostgreSQL server is
+    /// backed up with the server's own tooling instead
+    fn supports_snapshots(&self) -> bool {
+        false
+    }
+
+    fn create_snapshot(&self, _path: &Path) -> Result<(), WalletStorageError> {
+        Err(WalletStorageError::SnapshotsNotSupported)
+    }
+
+    fn create_burnt_proof(
+        &self,
+        id: u32,
+        reciprocal_claim_public_key: String,
+        payload: String,
+    ) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let proof = {
+            let cipher = acquire_read_lock!(self.cipher);
+            BurntProofSql::new(
+                id,
+                reciprocal_claim_public_key,
+                payload,
+                chrono::Utc::now().naive_utc(),
+                &cipher,
+            )?
+        };
+        diesel::insert_into(burnt_proofs::table)
+            .values(&proof)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_burnt_proof(&self, id: u32) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let entry = burnt_proofs::table
+            .filter(burnt_proofs::id.eq(id as i32))
+            .first::<BurntProofSql>(&mut conn)
+            .optional()
+            .map_err(|e| {
+                error!(target: LOG_TARGET, "Failed to fetch burnt proof: id={}: {}", id, e);
+                WalletStorageError::BurntProofNotFound(id)
+            })?
+            .ok_or(WalletStorageError::BurntProofNotFound(id))?;
+        self.burnt_proof_tuple(entry)
+            .map_err(|e| WalletStorageError::AeadError(e.to_string()))
+    }
+
+    fn fetch_burnt_proofs(&self) -> Result<Vec<(u32, String, String, NaiveDateTime)>, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        Ok(burnt_proofs::table
+            .load::<BurntProofSql>(&mut conn)?
+            .into_iter()
+            .filter_map(|entry| self.burnt_proof_tuple(entry).ok())
+            .collect())
+    }
+
+    fn delete_burnt_proof(&self, id: u32) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(burnt_proofs::table.filter(burnt_proofs::id.eq(id as i32))).execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn check_storage_integrity(&self, repair: bool) -> Result<StorageIntegrityReport, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        check_postgres_storage_integrity(&mut conn, repair)
+    }
+}
+
+fn get_setting(key: &DbKey, conn: &mut PgConnection) -> Result<Option<String>, WalletStorageError> {
+    Ok(wallet_settings::table
+        .filter(wallet_settings::key.eq(key.to_key_string()))
+        .select(wallet_settings::value)
+        .first::<String>(conn)
+        .optional()?)
+}
+
+fn set_setting(key: &DbKey, value: String, conn: &mut PgConnection) -> Result<(), WalletStorageError> {
+    diesel::insert_into(wallet_settings::table)
+        .values((
+            wallet_settings::key.eq(key.to_key_string()),
+            wallet_settings::value.eq(&value),
+        ))
+        .on_conflict(wallet_settings::key)
+        .do_update()
+        .set(wallet_settings::value.eq(&value))
+        .execute(conn)?;
+    Ok(())
+}
+
+fn read_key_rotation_state(conn: &mut PgConnection) -> Result<Option<KeyRotationState>, WalletStorageError> {
+    get_setting(&DbKey::KeyRotationState, conn)?
+        .map(|value| KeyRotationState::from_setting(&value))
+        .transpose()
+}
+
+/// Re-encrypt every encrypted value in the table of the given key rotation step from the `old` to the `new` cipher,
+/// returning the number of rows that were updated
+fn reencrypt_step(
+    step: KeyRotationStep,
+    conn: &mut PgConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, WalletStorageError> {
+    match step {
+        KeyRotationStep::WalletSettings => {
+            let mut rows = 0;
+            for (key, domain) in &ENCRYPTED_WALLET_SETTINGS {
+                if let Some(value) = get_setting(key, conn)? {
+                    let plaintext = Hidden::hide(
+                        decrypt_bytes_integral_nonce(old, domain.to_vec(), &from_hex(&value)?)
+                            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
+                    );
+                    let ciphertext = encrypt_bytes_integral_nonce(new, domain.to_vec(), plaintext)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+                    set_setting(key, ciphertext.to_hex(), conn)?;
+                    rows += 1;
+                }
+            }
+            Ok(rows)
+        },
+        KeyRotationStep::ClientKeyValues => {
+            let values = client_key_values::table.load::<ClientKeyValueSql>(conn)?;
+            for value in &values {
+                let value = reencrypt_value(value.clone(), old, new)?;
+                diesel::update(client_key_values::table.filter(client_key_values::key.eq(&value.key)))
+                    .set(client_key_values::value.eq(&value.value))
+                    .execute(conn)?;
+            }
+            Ok(values.len())
+        },
+        KeyRotationStep::BurntProofs => {
+            let proofs = burnt_proofs::table.load::<BurntProofSql>(conn)?;
+            for proof in &proofs {
+                let proof = reencrypt_value(proof.clone(), old, new)?;
+                diesel::update(burnt_proofs::table.filter(burnt_proofs::id.eq(proof.id)))
+                    .set(burnt_proofs::payload.eq(&proof.payload))
+                    .execute(conn)?;
+            }
+            Ok(proofs.len())
+        },
+        KeyRotationStep::Transactions(table) => {
+            reencrypt_transaction_table(table, conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                table: step.table_name(),
+                details: e.to_string(),
+            })
+        },
+        KeyRotationStep::KeyManagerStates => {
+            reencrypt_key_manager_states(conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                table: step.table_name(),
+                details: e.to_string(),
+            })
+        },
+        KeyRotationStep::ImportedKeys => {
+            reencrypt_imported_keys(conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                table: step.table_name(),
+                details: e.to_string(),
+            })
+        },
+    }
+}
+
+fn clear_setting(key: &DbKey, conn: &mut PgConnection) -> Result<bool, WalletStorageError> {
+    let num_deleted =
+        diesel::delete(wallet_settings::table.filter(wallet_settings::key.eq(key.to_key_string()))).execute(conn)?;
+    Ok(num_deleted > 0)
+}
+
+/// Read and parse the encryption field data atomically
+fn read_encryption_fields(conn: &mut PgConnection) -> Result<Option<DatabaseEncryptionFields>, WalletStorageError> {
+    let (secondary_key_version, secondary_key_salt, secondary_key_hash, encrypted_main_key) = conn
+        .transaction::<_, WalletStorageError, _>(|conn| {
+            Ok((
+                get_setting(&DbKey::SecondaryKeyVersion, conn)?,
+                get_setting(&DbKey::SecondaryKeySalt, conn)?,
+                get_setting(&DbKey::SecondaryKeyHash, conn)?,
+                get_setting(&DbKey::EncryptedMainKey, conn)?,
+            ))
+        })
+        .map_err(|_| WalletStorageError::UnexpectedResult("Unable to read key fields from database".into()))?;
+    DatabaseEncryptionFields::parse(
+        secondary_key_version,
+        secondary_key_salt,
+        secondary_key_hash,
+        encrypted_main_key,
+    )
+}
+
+/// Encode and write the encryption field data atomically
+fn write_encryption_fields(
+    fields: &DatabaseEncryptionFields,
+    conn: &mut PgConnection,
+) -> Result<(), WalletStorageError> {
+    conn.transaction::<_, WalletStorageError, _>(|conn| {
+        for (key, value) in fields.encode() {
+            set_setting(&key, value, conn)?;
+        }
+        Ok(())
+    })
+    .map_err(|_| WalletStorageError::UnexpectedResult("Unable to write key fields into database".into()))
+}
+
+/// Prepare the database encryption cipher, setting up a new main key if the database does not have one yet
+fn get_db_cipher(conn: &mut PgConnection, passphrase: &SafePassword) -> Result<XChaCha20Poly1305, WalletStorageError> {
+    let fields = read_encryption_fields(conn)
+        .map_err(|_| WalletStorageError::UnexpectedResult("Unable to parse key fields from database".into()))?;
+    let main_key = match fields {
+        Some(data) => data.unseal(passphrase)?,
+        None => {
+            let mut main_key = WalletMainEncryptionKey::from(vec![0u8; size_of::<Key>()]);
+            OsRng.fill_bytes(main_key.reveal_mut());
+            write_encryption_fields(&DatabaseEncryptionFields::seal(&main_key, passphrase)?, conn)?;
+            main_key
+        },
+    };
+
+    Ok(XChaCha20Poly1305::new(Key::from_slice(main_key.reveal())))
+}
@@ -0,0 +1,156 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use diesel::{
+    pg::PgConnection,
+    r2d2::{ConnectionManager, Pool, PooledConnection},
+};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use log::*;
+use tari_common_sqlite::error::SqliteStorageError;
+use tari_contacts::contacts_service::storage::postgres_db::ContactsServicePostgresDatabase;
+use tari_key_manager::key_manager_service::storage::postgres_db::KeyManagerPostgresDatabase;
+use tari_utilities::SafePassword;
+
+use crate::{
+    error::WalletStorageError,
+    output_manager_service::storage::postgres_db::OutputManagerPostgresDatabase,
+    storage::{
+        key_provider::{DbKeyProvider, PassphraseKeyProvider},
+        postgres_db::WalletPostgresDatabase,
+    },
+    transaction_service::storage::postgres_db::TransactionServicePostgresDatabase,
+};
+
+const LOG_TARGET: &str = "wallet::storage:postgres_utilities";
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations_postgres");
+
+pub type PgConnectionManager = ConnectionManager<PgConnection>;
+
+/// The wallet database connection for wallets kept on a PostgreSQL server. The server arbitrates concurrent writers
+/// itself, so unlike [WalletDbConnection](crate::storage::sqlite_utilities::WalletDbConnection) reads and writes share
+/// a single pool.
+#[derive(Clone)]
+pub struct WalletPostgresConnection {
+    pool: Pool<PgConnectionManager>,
+}
+
+impl WalletPostgresConnection {
+    /// Connect to the database at the given `postgres://` url with a pool of at most `pool_size` connections
+    pub fn connect(database_url: &str, pool_size: u32) -> Result<Self, WalletStorageError> {
+        let pool = Pool::builder()
+            .max_size(pool_size)
+            .build(PgConnectionManager::new(database_url))
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()))?;
+        Ok(Self { pool })
+    }
+
+    pub fn get_pooled_connection(&self) -> Result<PooledConnection<PgConnectionManager>, SqliteStorageError> {
+        self.pool
+            .get()
+            .map_err(|e| SqliteStorageError::DieselR2d2Error(e.to_string()))
+    }
+}
+
+/// Connects to the wallet database at `database_url` and brings its schema up to date
+pub fn run_migration_and_create_postgres_connection(
+    database_url: &str,
+    pool_size: u32,
+) -> Result<WalletPostgresConnection, WalletStorageError> {
+    let connection = WalletPostgresConnection::connect(database_url, pool_size)?;
+    connection
+        .get_pooled_connection()?
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|e| WalletStorageError::DatabaseMigrationError(e.to_string()))?;
+    Ok(connection)
+}
+
+/// The PostgreSQL counterpart of
+/// [initialize_sqlite_database_backends](crate::storage::sqlite_utilities::initialize_sqlite_database_backends). Every
+/// backend, the key manager included, is kept in the one database so that a key rotation covers all of them.
+#[allow(clippy::type_complexity)]
+pub fn initialize_postgres_database_backends(
+    database_url: &str,
+    passphrase: SafePassword,
+    pool_size: u32,
+) -> Result<
+    (
+        WalletPostgresDatabase,
+        TransactionServicePostgresDatabase,
+        OutputManagerPostgresDatabase,
+        ContactsServicePostgresDatabase,
+        KeyManagerPostgresDatabase,
+    ),
+    WalletStorageError,
+> {
+    initialize_postgres_database_backends_with_key_provider(
+        database_url,
+        &PassphraseKeyProvider::new(passphrase),
+        pool_size,
+    )
+}
+
+/// As [initialize_postgres_database_backends], but the database encryption key is unlocked with the secret supplied
+/// by `key_provider`
+#[allow(clippy::type_complexity)]
+pub fn initialize_postgres_database_backends_with_key_provider(
+    database_url: &str,
+    key_provider: &dyn DbKeyProvider,
+    pool_size: u32,
+) -> Result<
+    (
+        WalletPostgresDatabase,
+        TransactionServicePostgresDatabase,
+        OutputManagerPostgresDatabase,
+        ContactsServicePostgresDatabase,
+        KeyManagerPostgresDatabase,
+    ),
+    WalletStorageError,
+> {
+    let connection = run_migration_and_create_postgres_connection(database_url, pool_size).map_err(|e| {
+        error!(
+            target: LOG_TARGET,
+            "Error creating Postgres Connection in Wallet: {:?}", e
+        );
+        e
+    })?;
+
+    let wallet_backend = WalletPostgresDatabase::new_with_key_provider(connection.clone(), key_provider)?;
+    let transaction_backend =
+        TransactionServicePostgresDatabase::new_with_shared_cipher(connection.clone(), wallet_backend.shared_cipher());
+    let output_manager_backend = OutputManagerPostgresDatabase::new(connection);
+    let contacts_backend = ContactsServicePostgresDatabase::init(database_url, pool_size)
+        .map_err(|e| WalletStorageError::UnexpectedResult(format!("Could not open the contacts database: {}", e)))?;
+    let key_manager_backend =
+        KeyManagerPostgresDatabase::init_with_shared_cipher(database_url, pool_size, wallet_backend.shared_cipher())
+            .map_err(|e| {
+                WalletStorageError::UnexpectedResult(format!("Could not open the key manager database: {}", e))
+            })?;
+    Ok((
+        wallet_backend,
+        transaction_backend,
+        output_manager_backend,
+        contacts_backend,
+        key_manager_backend,
+    ))
+}
@@ -0,0 +1,344 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Startup integrity checks over the wallet database. Every check is a read-only query, the matching repair is only
+//! run when requested and all repairs are applied in a single transaction. The queries are plain SQL understood by
+//! both Sqlite and PostgreSQL.
+
+#[cfg(feature = "postgres")]
+use diesel::pg::PgConnection;
+use diesel::{
+    query_builder::SqlQuery,
+    query_dsl::{methods::ExecuteDsl, LoadQuery},
+    sql_query,
+    sql_types::{BigInt, Binary, Integer, Nullable},
+    Connection,
+    QueryableByName,
+    RunQueryDsl,
+    SqliteConnection,
+};
+use log::*;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    error::WalletStorageError,
+    output_manager_service::storage::OutputStatus,
+    storage::integrity::{IntegrityIssueKind, StorageIntegrityIssue, StorageIntegrityReport},
+};
+
+const LOG_TARGET: &str = "wallet::storage::sqlite_db::integrity_check";
+
+const KNOWN_TX_IDS: &str = "SELECT tx_id FROM completed_transactions UNION SELECT tx_id FROM inbound_transactions \
+                            UNION SELECT tx_id FROM outbound_transactions";
+
+/// Tables with a `tx_id` column whose rows only have meaning while the transaction exists
+const TX_DEPENDENT_TABLES: &[&str] = &[
+    "batched_payments",
+    "burn_transactions",
+    "outbound_message_queue",
+    "superseded_transactions",
+    "transaction_counterparty_aliases",
+    "transaction_memos",
+    "transaction_tags",
+];
+
+#[derive(QueryableByName)]
+struct CountResult {
+    #[diesel(sql_type = BigInt)]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct OutputResult {
+    #[diesel(sql_type = Binary)]
+    commitment: Vec<u8>,
+    #[diesel(sql_type = Integer)]
+    status: i32,
+    #[diesel(sql_type = Nullable<BigInt>)]
+    tx_id: Option<i64>,
+}
+
+/// An output check: the query selecting inconsistent outputs with the status to check and the update that repairs them
+struct OutputCheck {
+    kind: IntegrityIssueKind,
+    status: OutputStatus,
+    description: &'static str,
+    select: String,
+    repair: String,
+}
+
+fn output_checks() -> Vec<OutputCheck> {
+    let received_without_tx = |status: OutputStatus| {
+        format!(
+            "status = {} AND (received_in_tx_id IS NULL OR received_in_tx_id NOT IN ({}))",
+            status as i32, KNOWN_TX_IDS
+        )
+    };
+    let spent_without_tx = |status: OutputStatus| {
+        format!(
+            "status = {} AND (spent_in_tx_id IS NULL OR spent_in_tx_id NOT IN ({}))",
+            status as i32, KNOWN_TX_IDS
+        )
+    };
+    let spent_in_cancelled_tx = |status: OutputStatus| {
+        format!(
+            "status = {} AND marked_deleted_at_height IS NULL AND spent_in_tx_id IN (SELECT tx_id FROM \
+             completed_transactions WHERE cancelled IS NOT NULL)",
+            status as i32
+        )
+    };
+    let unspent_but_deleted =
+        |status: OutputStatus| format!("status = {} AND marked_deleted_at_height IS NOT NULL", status as i32);
+
+    let select = |condition: &str, tx_id_column: &str| {
+        format!(
+            "SELECT commitment, status, {} AS tx_id FROM outputs WHERE {}",
+            tx_id_column, condition
+        )
+    };
+    let update = |set: String, condition: &str| format!("UPDATE outputs SET {} WHERE {}", set, condition);
+    let release_spend = format!(
+        "status = {}, spent_in_tx_id = NULL, last_validation_timestamp = NULL",
+        OutputStatus::Unspent as i32
+    );
+
+    vec![
+        OutputCheck {
+            kind: IntegrityIssueKind::EncumberedOutputWithoutTransaction,
+            status: OutputStatus::EncumberedToBeReceived,
+            description: "Outputs expected from a transaction that does not exist, repaired by cancelling them",
+            select: select(
+                &received_without_tx(OutputStatus::EncumberedToBeReceived),
+                "received_in_tx_id",
+            ),
+            repair: update(
+                format!("status = {}", OutputStatus::CancelledInbound as i32),
+                &received_without_tx(OutputStatus::EncumberedToBeReceived),
+            ),
+        },
+        OutputCheck {
+            kind: IntegrityIssueKind::EncumberedOutputWithoutTransaction,
+            status: OutputStatus::EncumberedToBeSpent,
+            description: "Outputs locked by a transaction that does not exist, repaired by releasing them",
+            select: select(&spent_without_tx(OutputStatus::EncumberedToBeSpent), "spent_in_tx_id"),
+            repair: update(
+                release_spend.clone(),
+                &spent_without_tx(OutputStatus::EncumberedToBeSpent),
+            ),
+        },
+        OutputCheck {
+            kind: IntegrityIssueKind::BalanceMismatch,
+            status: OutputStatus::EncumberedToBeSpent,
+            description: "Outputs locked by a cancelled transaction, repaired by releasing them",
+            select: select(
+                &spent_in_cancelled_tx(OutputStatus::EncumberedToBeSpent),
+                "spent_in_tx_id",
+            ),
+            repair: update(
+                release_spend.clone(),
+                &spent_in_cancelled_tx(OutputStatus::EncumberedToBeSpent),
+            ),
+        },
+        OutputCheck {
+            kind: IntegrityIssueKind::BalanceMismatch,
+            status: OutputStatus::SpentMinedUnconfirmed,
+            description: "Outputs spent by a cancelled transaction that were not seen spent on chain, repaired by \
+                          releasing them for revalidation",
+            select: select(
+                &spent_in_cancelled_tx(OutputStatus::SpentMinedUnconfirmed),
+                "spent_in_tx_id",
+            ),
+            repair: update(
+                release_spend,
+                &spent_in_cancelled_tx(OutputStatus::SpentMinedUnconfirmed),
+            ),
+        },
+        OutputCheck {
+            kind: IntegrityIssueKind::BalanceMismatch,
+            status: OutputStatus::Unspent,
+            description: "Unspent outputs that were seen spent on chain, repaired by clearing the spent height for \
+                          revalidation",
+            select: select(&unspent_but_deleted(OutputStatus::Unspent), "spent_in_tx_id"),
+            repair: update(
+                "marked_deleted_at_height = NULL, marked_deleted_in_block = NULL, last_validation_timestamp = NULL"
+                    .to_string(),
+                &unspent_but_deleted(OutputStatus::Unspent),
+            ),
+        },
+    ]
+}
+
+/// Checks the wallet database for orphaned rows, encumbered outputs without a transaction and outputs that are
+/// counted in the wrong balance. The inconsistencies found are repaired if `repair` is set.
+pub fn check_storage_integrity(
+    conn: &mut SqliteConnection,
+    repair: bool,
+) -> Result<StorageIntegrityReport, WalletStorageError> {
+    run_integrity_checks(conn, repair)
+}
+
+/// As [check_storage_integrity], for a wallet database kept on a PostgreSQL server
+#[cfg(feature = "postgres")]
+pub fn check_postgres_storage_integrity(
+    conn: &mut PgConnection,
+    repair: bool,
+) -> Result<StorageIntegrityReport, WalletStorageError> {
+    run_integrity_checks(conn, repair)
+}
+
+fn run_integrity_checks<C>(conn: &mut C, repair: bool) -> Result<StorageIntegrityReport, WalletStorageError>
+where
+    C: Connection,
+    SqlQuery: for<'a> LoadQuery<'a, C, CountResult> + for<'a> LoadQuery<'a, C, OutputResult> + ExecuteDsl<C>,
+{
+    conn.transaction::<_, WalletStorageError, _>(|conn| {
+        let mut issues = Vec::new();
+
+        for table in TX_DEPENDENT_TABLES {
+            let condition = format!("tx_id NOT IN ({})", KNOWN_TX_IDS);
+            let count = sql_query(format!("SELECT COUNT(*) AS count FROM {} WHERE {}", table, condition))
+                .get_result::<CountResult>(conn)?
+                .count;
+            if count == 0 {
+                continue;
+            }
+            issues.push(StorageIntegrityIssue {
+                kind: IntegrityIssueKind::OrphanedRows,
+                table: (*table).to_string(),
+                description: "Rows for a transaction that does not exist, repaired by deleting them".to_string(),
+                count: count.unsigned_abs(),
+            });
+            if repair {
+                sql_query(format!("DELETE FROM {} WHERE {}", table, condition)).execute(conn)?;
+            }
+        }
+
+        for check in output_checks() {
+            let outputs = sql_query(&check.select).load::<OutputResult>(conn)?;
+            if outputs.is_empty() {
+                continue;
+            }
+            for output in &outputs {
+                debug!(
+                    target: LOG_TARGET,
+                    "{}: output {} with status {} and transaction {:?}",
+                    check.kind,
+                    output.commitment.to_hex(),
+                    output.status,
+                    output.tx_id
+                );
+            }
+            issues.push(StorageIntegrityIssue {
+                kind: check.kind,
+                table: "outputs".to_string(),
+                description: format!("{} (status {})", check.description, check.status),
+                count: outputs.len() as u64,
+            });
+            if repair {
+                sql_query(&check.repair).execute(conn)?;
+            }
+        }
+
+        Ok(StorageIntegrityReport {
+            repaired: repair && !issues.is_empty(),
+            issues,
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::sqlite_utilities::run_migration_and_create_sqlite_connection;
+
+    fn insert_output(conn: &mut SqliteConnection, id: u8, status: OutputStatus, spent_in_tx_id: Option<i64>) {
+        sql_query(
+            "INSERT INTO outputs (commitment, spending_key, value, output_type, maturity, status, hash, script, \
+             input_data, script_private_key, script_lock_height, sender_offset_public_key, \
+             metadata_signature_ephemeral_commitment, metadata_signature_ephemeral_pubkey, metadata_signature_u_a, \
+             metadata_signature_u_x, metadata_signature_u_y, features_json, spending_priority, covenant, \
+             encrypted_data, minimum_value_promise, source, frozen, account_id, spent_in_tx_id) VALUES (?, '', 1000, \
+             0, 0, ?, ?, x'', x'', '', 0, x'', x'', x'', x'', x'', x'', '{}', 0, x'', x'', 0, 0, 0, 0, ?)",
+        )
+        .bind::<diesel::sql_types::Binary, _>(vec![id; 32])
+        .bind::<Integer, _>(status as i32)
+        .bind::<diesel::sql_types::Binary, _>(vec![id; 32])
+        .bind::<Nullable<BigInt>, _>(spent_in_tx_id)
+        .execute(conn)
+        .unwrap();
+    }
+
+    fn output_status(conn: &mut SqliteConnection, id: u8) -> i32 {
+        #[derive(QueryableByName)]
+        struct StatusResult {
+            #[diesel(sql_type = Integer)]
+            status: i32,
+        }
+        sql_query("SELECT status FROM outputs WHERE commitment = ?")
+            .bind::<diesel::sql_types::Binary, _>(vec![id; 32])
+            .get_result::<StatusResult>(conn)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn it_reports_and_repairs_inconsistencies() {
+        let db_tempdir = tempdir().unwrap();
+        let connection =
+            run_migration_and_create_sqlite_connection(db_tempdir.path().join("wallet.sqlite3"), 1).unwrap();
+        let mut conn = connection.get_pooled_connection().unwrap();
+
+        let report = check_storage_integrity(&mut conn, true).unwrap();
+        assert!(report.is_clean());
+        assert!(!report.repaired);
+
+        sql_query("INSERT INTO transaction_tags (tx_id, tag) VALUES (1, 'orphan'), (2, 'orphan')")
+            .execute(&mut conn)
+            .unwrap();
+        insert_output(&mut conn, 1, OutputStatus::EncumberedToBeSpent, Some(3));
+        insert_output(&mut conn, 2, OutputStatus::Unspent, None);
+
+        // Without repairing, the issues are only reported
+        let report = check_storage_integrity(&mut conn, false).unwrap();
+        assert!(!report.repaired);
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(report.issues[0].kind, IntegrityIssueKind::OrphanedRows);
+        assert_eq!(report.issues[0].table, "transaction_tags");
+        assert_eq!(report.issues[0].count, 2);
+        assert_eq!(
+            report.issues[1].kind,
+            IntegrityIssueKind::EncumberedOutputWithoutTransaction
+        );
+        assert_eq!(report.issues[1].count, 1);
+        assert_eq!(check_storage_integrity(&mut conn, false).unwrap(), report);
+
+        let report = check_storage_integrity(&mut conn, true).unwrap();
+        assert!(report.repaired);
+        assert_eq!(report.issues.len(), 2);
+        assert_eq!(output_status(&mut conn, 1), OutputStatus::Unspent as i32);
+        assert_eq!(output_status(&mut conn, 2), OutputStatus::Unspent as i32);
+        assert!(check_storage_integrity(&mut conn, false).unwrap().is_clean());
+    }
+}
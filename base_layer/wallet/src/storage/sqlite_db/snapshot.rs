@@ -0,0 +1,366 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Encrypted snapshots of the wallet database, used for automatic backups.
+//!
+//! A snapshot is a transactionally consistent copy of the database produced with the sqlite online backup API,
+//! encrypted with the database main key. The main key fields of the database are stored in the clear in the snapshot
+//! header, so a snapshot can be restored with nothing but the wallet passphrase it was taken under.
+
+use std::{
+    convert::TryFrom,
+    ffi::{CStr, CString, OsString},
+    fs,
+    io,
+    path::{Path, PathBuf},
+    ptr,
+};
+
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use diesel::{sql_query, sql_types::Text, Connection, RunQueryDsl, SqliteConnection};
+use libsqlite3_sys as ffi;
+use log::*;
+use tari_common_types::encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce};
+use tari_utilities::{Hidden, SafePassword};
+
+use crate::{error::WalletStorageError, storage::sqlite_db::wallet::DatabaseEncryptionFields};
+
+const LOG_TARGET: &str = "wallet::storage::snapshot";
+
+// Identifies a wallet snapshot file and its format version
+const SNAPSHOT_MAGIC: &[u8; 8] = b"TWSNAP01";
+// Authenticated data for the snapshot body
+const SNAPSHOT_AAD: &[u8] = b"wallet_database_snapshot_v1";
+// Suffix of the unencrypted copy of the database made while a snapshot is taken
+const COPY_SUFFIX: &str = ".snapshot";
+
+#[derive(QueryableByName)]
+struct DatabaseFileSql {
+    #[diesel(sql_type = Text)]
+    file: String,
+}
+
+#[derive(QueryableByName)]
+struct IntegrityCheckSql {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+/// Write an encrypted snapshot of the database behind `conn` to `path`. The snapshot only appears at `path` once it
+/// has been written completely.
+pub(crate) fn write_snapshot(
+    conn: &mut SqliteConnection,
+    cipher: &XChaCha20Poly1305,
+    path: &Path,
+) -> Result<(), WalletStorageError> {
+    let fields = DatabaseEncryptionFields::read(conn)?.ok_or_else(|| {
+        WalletStorageError::UnexpectedResult("Unable to get valid key-related data from database".into())
+    })?;
+
+    // The unencrypted copy is kept next to the database itself rather than in the backup directory, in a file only we
+    // can read that is removed as soon as it has been read back
+    let database_file = sql_query("SELECT file FROM pragma_database_list WHERE name = 'main'")
+        .get_result::<DatabaseFileSql>(conn)?
+        .file;
+    let database_file = Path::new(&database_file);
+    let copy_path = with_suffix(database_file, COPY_SUFFIX);
+    remove_if_exists(&copy_path)?;
+    create_private_file(&copy_path)?;
+    let plaintext =
+        backup_database(database_file, &copy_path).and_then(|_| fs::read(&copy_path).map_err(WalletStorageError::from));
+    remove_if_exists(&copy_path)?;
+    let plaintext = Hidden::hide(plaintext?);
+
+    let header = serde_json::to_vec(&fields)?;
+    let header_len = u32::try_from(header.len())
+        .map_err(|_| WalletStorageError::ConversionError("Snapshot header too large".into()))?;
+    let body = encrypt_bytes_integral_nonce(cipher, SNAPSHOT_AAD.to_vec(), plaintext)
+        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+
+    let mut snapshot = Vec::with_capacity(SNAPSHOT_MAGIC.len() + 4 + header.len() + body.len());
+    snapshot.extend_from_slice(SNAPSHOT_MAGIC);
+    snapshot.extend_from_slice(&header_len.to_le_bytes());
+    snapshot.extend_from_slice(&header);
+    snapshot.extend_from_slice(&body);
+
+    let partial_path = with_suffix(path, ".partial");
+    fs::write(&partial_path, snapshot)?;
+    fs::rename(&partial_path, path)?;
+    debug!(target: LOG_TARGET, "Wrote wallet snapshot '{}'", path.display());
+
+    Ok(())
+}
+
+/// Remove the unencrypted copy of the database at `db_path` that a snapshot interrupted by a crash may have left behind
+pub(crate) fn remove_leftover_copy(db_path: &Path) -> Result<(), WalletStorageError> {
+    let copy_path = with_suffix(db_path, COPY_SUFFIX);
+    if copy_path.exists() {
+        warn!(
+            target: LOG_TARGET,
+            "Removing unencrypted database copy '{}' left by an interrupted snapshot",
+            copy_path.display()
+        );
+    }
+    remove_if_exists(&copy_path)
+}
+
+/// Restore the wallet database at `db_path` from a snapshot. The snapshot is decrypted with `passphrase` and checked
+/// for integrity before the existing database is touched. Any existing database is kept alongside with a
+/// `.pre-restore` suffix.
+pub fn restore_snapshot(
+    snapshot_path: &Path,
+    db_path: &Path,
+    passphrase: &SafePassword,
+) -> Result<(), WalletStorageError> {
+    let (fields, plaintext) = open_snapshot(snapshot_path, passphrase)?;
+
+    // Validate the restored database in a scratch file first
+    let restore_path = with_suffix(db_path, ".restore");
+    fs::write(&restore_path, plaintext.reveal())?;
+    if let Err(e) = validate_restored_database(&restore_path, &fields) {
+        remove_if_exists(&restore_path)?;
+        return Err(e);
+    }
+
+    // Move the existing database, along with its write-ahead log, out of the way
+    if db_path.exists() {
+        for suffix in ["", "-wal", "-shm"] {
+            let file = with_suffix(db_path, suffix);
+            if file.exists() {
+                fs::rename(&file, with_suffix(&file, ".pre-restore"))?;
+            }
+        }
+    }
+    fs::rename(&restore_path, db_path)?;
+    info!(
+        target: LOG_TARGET,
+        "Restored wallet database '{}' from snapshot '{}'",
+        db_path.display(),
+        snapshot_path.display()
+    );
+
+    Ok(())
+}
+
+/// Check that a snapshot can be decrypted with `passphrase`, without restoring it
+pub fn verify_snapshot(snapshot_path: &Path, passphrase: &SafePassword) -> Result<(), WalletStorageError> {
+    open_snapshot(snapshot_path, passphrase).map(|_| ())
+}
+
+fn open_snapshot(
+    snapshot_path: &Path,
+    passphrase: &SafePassword,
+) -> Result<(DatabaseEncryptionFields, Hidden<Vec<u8>>), WalletStorageError> {
+    let snapshot = fs::read(snapshot_path)?;
+    let invalid =
+        || WalletStorageError::UnexpectedResult(format!("'{}' is not a wallet snapshot", snapshot_path.display()));
+
+    let rest = snapshot.strip_prefix(SNAPSHOT_MAGIC.as_slice()).ok_or_else(invalid)?;
+    if rest.len() < 4 {
+        return Err(invalid());
+    }
+    let (header_len, rest) = rest.split_at(4);
+    let header_len = usize::try_from(u32::from_le_bytes([
+        header_len[0],
+        header_len[1],
+        header_len[2],
+        header_len[3],
+    ]))
+    .map_err(|_| invalid())?;
+    if rest.len() < header_len {
+        return Err(invalid());
+    }
+    let (header, body) = rest.split_at(header_len);
+    let fields: DatabaseEncryptionFields = serde_json::from_slice(header)?;
+
+    let main_key = fields.unseal(passphrase)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(main_key.reveal()));
+    let plaintext = Hidden::hide(
+        decrypt_bytes_integral_nonce(&cipher, SNAPSHOT_AAD.to_vec(), body)
+            .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
+    );
+
+    Ok((fields, plaintext))
+}
+
+fn validate_restored_database(path: &Path, fields: &DatabaseEncryptionFields) -> Result<(), WalletStorageError> {
+    let mut conn = SqliteConnection::establish(path_to_str(path)?)?;
+
+    let results = sql_query("PRAGMA integrity_check").load::<IntegrityCheckSql>(&mut conn)?;
+    if results.len() != 1 || results[0].integrity_check != "ok" {
+        let problems = results.into_iter().map(|r| r.integrity_check).collect::<Vec<_>>();
+        return Err(WalletStorageError::UnexpectedResult(format!(
+            "Snapshot failed the integrity check: {}",
+            problems.join(", ")
+        )));
+    }
+
+    // The snapshot header must describe the key of the database it contains
+    match DatabaseEncryptionFields::read(&mut conn)? {
+        Some(ref restored) if restored == fields => Ok(()),
+        _ => Err(WalletStorageError::UnexpectedResult(
+            "Snapshot header does not match the database it contains".into(),
+        )),
+    }
+}
+
+/// Create an empty file at `path` that only the current user can access. Fails if anything, including a symlink,
+/// already exists at `path`.
+fn create_private_file(path: &Path) -> Result<(), WalletStorageError> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?;
+    Ok(())
+}
+
+/// Copy the committed state of the database at `source` into the empty database file at `destination` with the
+/// sqlite online backup API. The source is read in a single read transaction, so the copy is consistent while the
+/// wallet keeps writing to it.
+fn backup_database(source: &Path, destination: &Path) -> Result<(), WalletStorageError> {
+    let source = RawConnection::open(source, ffi::SQLITE_OPEN_READONLY)?;
+    let destination = RawConnection::open(destination, ffi::SQLITE_OPEN_READWRITE)?;
+    let main = b"main\0".as_ptr().cast();
+    // Safety: both connections are open until they are dropped at the end of this function, and the backup is
+    // finished before that
+    unsafe {
+        ffi::sqlite3_busy_timeout(source.0, 60_000);
+        let backup = ffi::sqlite3_backup_init(destination.0, main, source.0, main);
+        if backup.is_null() {
+            return Err(destination.error(ffi::sqlite3_errcode(destination.0)));
+        }
+        let step = ffi::sqlite3_backup_step(backup, -1);
+        let finish = ffi::sqlite3_backup_finish(backup);
+        if step != ffi::SQLITE_DONE {
+            return Err(destination.error(step));
+        }
+        if finish != ffi::SQLITE_OK {
+            return Err(destination.error(finish));
+        }
+    }
+    Ok(())
+}
+
+/// A connection made directly through the sqlite C API, for the calls diesel does not expose
+struct RawConnection(*mut ffi::sqlite3);
+
+impl RawConnection {
+    fn open(path: &Path, flags: i32) -> Result<Self, WalletStorageError> {
+        let path = CString::new(path_to_str(path)?)
+            .map_err(|_| WalletStorageError::UnexpectedResult("Database path contains a nul byte".into()))?;
+        let mut handle = ptr::null_mut();
+        // Safety: `path` is a valid C string and sqlite sets `handle`, which is closed on drop even if opening fails
+        let code = unsafe { ffi::sqlite3_open_v2(path.as_ptr(), &mut handle, flags, ptr::null()) };
+        let conn = Self(handle);
+        if code != ffi::SQLITE_OK {
+            return Err(conn.error(code));
+        }
+        Ok(conn)
+    }
+
+    fn error(&self, code: i32) -> WalletStorageError {
+        // Safety: sqlite returns static or connection owned nul terminated strings, which are copied straight away
+        let message = unsafe {
+            let message = if self.0.is_null() {
+                ffi::sqlite3_errstr(code)
+            } else {
+                ffi::sqlite3_errmsg(self.0)
+            };
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        };
+        WalletStorageError::UnexpectedResult(format!("Unable to copy the wallet database: {}", message))
+    }
+}
+
+impl Drop for RawConnection {
+    fn drop(&mut self) {
+        // Safety: the handle came from sqlite3_open_v2 and is not used after this; closing a null handle is a no-op
+        unsafe {
+            ffi::sqlite3_close(self.0);
+        }
+    }
+}
+
+/// Append a suffix to the full file name, e.g. `console_wallet.db` becomes `console_wallet.db-wal`
+pub(crate) fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+pub(crate) fn path_to_str(path: &Path) -> Result<&str, WalletStorageError> {
+    path.to_str().ok_or(WalletStorageError::InvalidUnicodePath)
+}
+
+pub(crate) fn remove_if_exists(path: &Path) -> Result<(), WalletStorageError> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_key_manager::cipher_seed::CipherSeed;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::{
+        database::WalletDatabase,
+        sqlite_db::wallet::WalletSqliteDatabase,
+        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    };
+
+    #[test]
+    fn it_restores_a_snapshot() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.sqlite3");
+        let snapshot_path = dir.path().join("wallet.snapshot");
+        let passphrase = SafePassword::from("passphrase".to_string());
+        let seed = CipherSeed::new();
+
+        // A copy left by an interrupted snapshot is removed when the database is opened
+        fs::write(with_suffix(&db_path, COPY_SUFFIX), b"plaintext").unwrap();
+        let connection = run_migration_and_create_sqlite_connection(&db_path, 16).unwrap();
+        assert!(!with_suffix(&db_path, COPY_SUFFIX).exists());
+        let db = WalletDatabase::new(WalletSqliteDatabase::new(connection, passphrase.clone()).unwrap());
+        db.set_master_seed(seed.clone()).unwrap();
+        db.create_snapshot(&snapshot_path).unwrap();
+        drop(db);
+        assert!(!with_suffix(&db_path, COPY_SUFFIX).exists());
+
+        assert!(matches!(
+            verify_snapshot(&snapshot_path, &SafePassword::from("evil passphrase".to_string())),
+            Err(WalletStorageError::InvalidPassphrase)
+        ));
+
+        let restored_path = dir.path().join("restored.sqlite3");
+        restore_snapshot(&snapshot_path, &restored_path, &passphrase).unwrap();
+        let connection = run_migration_and_create_sqlite_connection(&restored_path, 16).unwrap();
+        let db = WalletDatabase::new(WalletSqliteDatabase::new(connection, passphrase).unwrap());
+        assert_eq!(db.get_master_seed().unwrap().unwrap().entropy(), seed.entropy());
+    }
+}
@@ -23,6 +23,7 @@
 use std::{
     convert::TryFrom,
     mem::size_of,
+    path::Path,
     str::{from_utf8, FromStr},
     sync::{Arc, RwLock},
 };
@@ -38,18 +39,23 @@ use diesel::{prelude::*, result::Error, SqliteConnection};
 use digest::{consts::U32, generic_array::GenericArray, FixedOutput};
 use itertools::Itertools;
 use log::*;
+use serde::{Deserialize, Serialize};
 use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
 use tari_common_types::{
     chain_metadata::ChainMetadata,
     encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
+    types::{PrivateKey, PublicKey},
 };
 use tari_comms::{
     multiaddr::Multiaddr,
     peer_manager::{IdentitySignature, PeerFeatures},
     tor::TorIdentity,
 };
-use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher};
-use tari_key_manager::cipher_seed::CipherSeed;
+use tari_crypto::{hash_domain, hashing::DomainSeparatedHasher, keys::SecretKey as SecretKeyTrait};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    key_manager_service::storage::sqlite_db::{reencrypt_imported_keys, reencrypt_key_manager_states},
+};
 use tari_utilities::{
     hex::{from_hex, Hex},
     hidden_type,
@@ -65,10 +71,21 @@ use crate::{
     error::WalletStorageError,
     schema::{burnt_proofs, client_key_values, wallet_settings},
     storage::{
-        database::{DbKey, DbKeyValuePair, DbValue, WalletBackend, WriteOperation},
-        sqlite_db::scanned_blocks::ScannedBlockSql,
+        database::{DbKey, DbKeyValuePair, DbValue, KeyRotationProgress, WalletBackend, WriteOperation},
+        integrity::StorageIntegrityReport,
+        key_provider::{DbKeyProvider, PassphraseKeyProvider},
+        sqlite_db::{
+            integrity_check::check_storage_integrity,
+            scanned_blocks::ScannedBlockSql,
+            snapshot::write_snapshot,
+        },
         sqlite_utilities::wallet_db_connection::WalletDbConnection,
     },
+    transaction_service::storage::sqlite_db::{
+        reencrypt_table as reencrypt_transaction_table,
+        TransactionEncryptedTable,
+    },
+    util::wallet_identity::WatchOnlyKeys,
     utxo_scanner_service::service::ScannedBlock,
 };
 
@@ -125,6 +142,7 @@ impl Argon2Parameters {
 }
 
 /// A structure to hold encryption-related database field data, to make atomic operations cleaner
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct DatabaseEncryptionFields {
     secondary_key_version: u8,   // the encryption parameter version
     secondary_key_salt: String,  // the high-entropy salt used to derive the secondary derivation key
@@ -155,7 +173,21 @@ impl DatabaseEncryptionFields {
             })
             .map_err(|_| WalletStorageError::UnexpectedResult("Unable to read key fields from database".into()))?;
 
-        // Parse the fields
+        Self::parse(
+            secondary_key_version,
+            secondary_key_salt,
+            secondary_key_hash,
+            encrypted_main_key,
+        )
+    }
+
+    /// Parse field data as read from the wallet settings
+    pub(crate) fn parse(
+        secondary_key_version: Option<String>,
+        secondary_key_salt: Option<String>,
+        secondary_key_hash: Option<String>,
+        encrypted_main_key: Option<String>,
+    ) -> Result<Option<Self>, WalletStorageError> {
         match (
             secondary_key_version,
             secondary_key_salt,
@@ -199,18 +231,11 @@ impl DatabaseEncryptionFields {
         // Because the encoding can't fail, just do it inside the write transaction
         connection
             .transaction::<_, Error, _>(|connection| {
-                WalletSettingSql::new(DbKey::SecondaryKeyVersion, self.secondary_key_version.to_string())
-                    .set(connection)
-                    .map_err(|_| Error::RollbackTransaction)?;
-                WalletSettingSql::new(DbKey::SecondaryKeySalt, self.secondary_key_salt.to_string())
-                    .set(connection)
-                    .map_err(|_| Error::RollbackTransaction)?;
-                WalletSettingSql::new(DbKey::SecondaryKeyHash, self.secondary_key_hash.to_hex())
-                    .set(connection)
-                    .map_err(|_| Error::RollbackTransaction)?;
-                WalletSettingSql::new(DbKey::EncryptedMainKey, self.encrypted_main_key.to_hex())
-                    .set(connection)
-                    .map_err(|_| Error::RollbackTransaction)?;
+                for (key, value) in self.encode() {
+                    WalletSettingSql::new(key, value)
+                        .set(connection)
+                        .map_err(|_| Error::RollbackTransaction)?;
+                }
 
                 Ok(())
             })
@@ -218,6 +243,224 @@ impl DatabaseEncryptionFields {
 
         Ok(())
     }
+
+    /// Encode the fields as wallet settings
+    pub(crate) fn encode(&self) -> [(DbKey, String); 4] {
+        [
+            (DbKey::SecondaryKeyVersion, self.secondary_key_version.to_string()),
+            (DbKey::SecondaryKeySalt, self.secondary_key_salt.to_string()),
+            (DbKey::SecondaryKeyHash, self.secondary_key_hash.to_hex()),
+            (DbKey::EncryptedMainKey, self.encrypted_main_key.to_hex()),
+        ]
+    }
+
+    /// Check whether the passphrase matches the one the main key was protected with
+    pub(crate) fn verify(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+        let argon2_params = Argon2Parameters::from_version(Some(self.secondary_key_version))?;
+        let (_, secondary_key_hash) = derive_secondary_key(passphrase, argon2_params, &self.secondary_key_salt)?;
+        Ok(self.secondary_key_hash == secondary_key_hash)
+    }
+
+    /// Protect a main key with a secondary key derived from the passphrase, a fresh salt and the most recent
+    /// `Argon2` parameters
+    pub(crate) fn seal(
+        main_key: &WalletMainEncryptionKey,
+        passphrase: &SafePassword,
+    ) -> Result<Self, WalletStorageError> {
+        let argon2_params = Argon2Parameters::from_version(None)?;
+        let secondary_key_salt = SaltString::generate(&mut OsRng).to_string();
+        let (secondary_key, secondary_key_hash) =
+            derive_secondary_key(passphrase, argon2_params.clone(), &secondary_key_salt)?;
+        let encrypted_main_key = encrypt_main_key(&secondary_key, main_key, argon2_params.id)?;
+
+        Ok(Self {
+            secondary_key_version: argon2_params.id,
+            secondary_key_salt,
+            secondary_key_hash,
+            encrypted_main_key,
+        })
+    }
+
+    /// Recover the main key protected by these fields, failing if the passphrase does not match
+    pub(crate) fn unseal(&self, passphrase: &SafePassword) -> Result<WalletMainEncryptionKey, WalletStorageError> {
+        let argon2_params = Argon2Parameters::from_version(Some(self.secondary_key_version))?;
+        let (secondary_key, secondary_key_hash) =
+            derive_secondary_key(passphrase, argon2_params, &self.secondary_key_salt)?;
+        if self.secondary_key_hash != secondary_key_hash {
+            return Err(WalletStorageError::InvalidPassphrase);
+        }
+        decrypt_main_key(&secondary_key, &self.encrypted_main_key, self.secondary_key_version)
+    }
+}
+
+/// The persisted state of a database encryption key rotation. The replacement key fields are kept here until every
+/// table has been re-encrypted, so that an interrupted rotation can be resumed with the same key.
+#[derive(Clone, Serialize, Deserialize)]
+pub(crate) struct KeyRotationState {
+    secondary_key_version: u8,
+    secondary_key_salt: String,
+    secondary_key_hash: String,
+    encrypted_main_key: String,
+    pub(crate) tables_completed: usize,
+}
+
+impl KeyRotationState {
+    pub(crate) fn new(fields: &DatabaseEncryptionFields) -> Self {
+        Self {
+            secondary_key_version: fields.secondary_key_version,
+            secondary_key_salt: fields.secondary_key_salt.clone(),
+            secondary_key_hash: fields.secondary_key_hash.to_hex(),
+            encrypted_main_key: fields.encrypted_main_key.to_hex(),
+            tables_completed: 0,
+        }
+    }
+
+    pub(crate) fn encryption_fields(&self) -> Result<DatabaseEncryptionFields, WalletStorageError> {
+        Ok(DatabaseEncryptionFields {
+            secondary_key_version: self.secondary_key_version,
+            secondary_key_salt: self.secondary_key_salt.clone(),
+            secondary_key_hash: from_hex(&self.secondary_key_hash)?,
+            encrypted_main_key: from_hex(&self.encrypted_main_key)?,
+        })
+    }
+
+    /// Parse the state from its wallet setting value
+    pub(crate) fn from_setting(value: &str) -> Result<Self, WalletStorageError> {
+        serde_json::from_str(value).map_err(|e| WalletStorageError::InvalidKeyRotationState(e.to_string()))
+    }
+
+    /// Encode the state as a wallet setting value
+    pub(crate) fn to_setting(&self) -> Result<String, WalletStorageError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    fn read(conn: &mut SqliteConnection) -> Result<Option<Self>, WalletStorageError> {
+        WalletSettingSql::get(&DbKey::KeyRotationState, conn)?
+            .map(|value| Self::from_setting(&value))
+            .transpose()
+    }
+
+    fn write(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        WalletSettingSql::new(DbKey::KeyRotationState, self.to_setting()?).set(conn)
+    }
+}
+
+/// The wallet settings that are stored encrypted, with the authenticated data used for each
+pub(crate) const ENCRYPTED_WALLET_SETTINGS: [(DbKey, &[u8]); 4] = [
+    (DbKey::MasterSeed, b"wallet_setting_master_seed"),
+    (DbKey::PassphraseSeed, b"wallet_setting_passphrase_seed"),
+    (DbKey::WatchOnlyKeys, b"wallet_setting_watch_only_keys"),
+    (DbKey::TorId, b"wallet_setting_tor_id"),
+];
+
+/// A table holding encrypted columns, re-encrypted as a single step of a key rotation
+#[derive(Clone, Copy)]
+pub(crate) enum KeyRotationStep {
+    WalletSettings,
+    ClientKeyValues,
+    BurntProofs,
+    Transactions(TransactionEncryptedTable),
+    KeyManagerStates,
+    ImportedKeys,
+}
+
+impl KeyRotationStep {
+    /// Every step of a key rotation, in order. New steps must only ever be appended, as the persisted rotation state
+    /// refers to steps by their position.
+    pub(crate) fn all() -> Vec<Self> {
+        let mut steps = vec![
+            KeyRotationStep::WalletSettings,
+            KeyRotationStep::ClientKeyValues,
+            KeyRotationStep::BurntProofs,
+        ];
+        steps.extend(TransactionEncryptedTable::ALL.map(KeyRotationStep::Transactions));
+        steps.push(KeyRotationStep::KeyManagerStates);
+        steps.push(KeyRotationStep::ImportedKeys);
+        steps
+    }
+
+    pub(crate) fn table_name(self) -> &'static str {
+        match self {
+            KeyRotationStep::WalletSettings => "wallet_settings",
+            KeyRotationStep::ClientKeyValues => "client_key_values",
+            KeyRotationStep::BurntProofs => "burnt_proofs",
+            KeyRotationStep::Transactions(table) => table.table_name(),
+            KeyRotationStep::KeyManagerStates => "key_manager_states",
+            KeyRotationStep::ImportedKeys => "imported_keys",
+        }
+    }
+
+    /// Re-encrypt every encrypted value in the table from the `old` to the `new` cipher, returning the number of rows
+    /// that were updated
+    fn reencrypt(
+        self,
+        conn: &mut SqliteConnection,
+        old: &XChaCha20Poly1305,
+        new: &XChaCha20Poly1305,
+    ) -> Result<usize, WalletStorageError> {
+        match self {
+            KeyRotationStep::WalletSettings => {
+                let mut rows = 0;
+                for (key, domain) in &ENCRYPTED_WALLET_SETTINGS {
+                    if let Some(value) = WalletSettingSql::get(key, conn)? {
+                        let plaintext = Hidden::hide(
+                            decrypt_bytes_integral_nonce(old, domain.to_vec(), &from_hex(&value)?)
+                                .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
+                        );
+                        let ciphertext = encrypt_bytes_integral_nonce(new, domain.to_vec(), plaintext)
+                            .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+                        WalletSettingSql::new(key.clone(), ciphertext.to_hex()).set(conn)?;
+                        rows += 1;
+                    }
+                }
+                Ok(rows)
+            },
+            KeyRotationStep::ClientKeyValues => {
+                let values = ClientKeyValueSql::index(conn)?;
+                for value in &values {
+                    reencrypt_value(value.clone(), old, new)?.set(conn)?;
+                }
+                Ok(values.len())
+            },
+            KeyRotationStep::BurntProofs => {
+                let proofs = BurntProofSql::index(conn)?;
+                for proof in &proofs {
+                    reencrypt_value(proof.clone(), old, new)?.update_encryption(conn)?;
+                }
+                Ok(proofs.len())
+            },
+            KeyRotationStep::Transactions(table) => {
+                reencrypt_transaction_table(table, conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                    table: self.table_name(),
+                    details: e.to_string(),
+                })
+            },
+            KeyRotationStep::KeyManagerStates => {
+                reencrypt_key_manager_states(conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                    table: self.table_name(),
+                    details: e.to_string(),
+                })
+            },
+            KeyRotationStep::ImportedKeys => {
+                reencrypt_imported_keys(conn, old, new).map_err(|e| WalletStorageError::ReencryptionError {
+                    table: self.table_name(),
+                    details: e.to_string(),
+                })
+            },
+        }
+    }
+}
+
+pub(crate) fn reencrypt_value<T: Encryptable<XChaCha20Poly1305>>(
+    value: T,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<T, WalletStorageError> {
+    value
+        .decrypt(old)
+        .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?
+        .encrypt(new)
+        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))
 }
 
 /// A Sqlite backend for the Output Manager Service. The Backend is accessed via a connection pool to the Sqlite file.
@@ -228,7 +471,47 @@ pub struct WalletSqliteDatabase {
 }
 impl WalletSqliteDatabase {
     pub fn new(database_connection: WalletDbConnection, passphrase: SafePassword) -> Result<Self, WalletStorageError> {
-        let cipher = get_db_cipher(&database_connection, &passphrase)?;
+        Self::new_with_key_provider(database_connection, &PassphraseKeyProvider::new(passphrase))
+    }
+
+    /// Open the wallet database, unlocking the database encryption key with the secret from the given provider. Fails
+    /// with [WalletStorageError::KeyRotationPending] if an interrupted key rotation has to be completed first.
+    pub fn new_with_key_provider(
+        database_connection: WalletDbConnection,
+        key_provider: &dyn DbKeyProvider,
+    ) -> Result<Self, WalletStorageError> {
+        let db = Self::open(database_connection, key_provider)?;
+        if KeyRotationState::read(&mut *db.database_connection.get_pooled_connection()?)?.is_some() {
+            warn!(
+                target: LOG_TARGET,
+                "An interrupted database encryption key rotation must be completed before the wallet is used"
+            );
+            return Err(WalletStorageError::KeyRotationPending);
+        }
+        Ok(db)
+    }
+
+    /// Open the wallet database to start or resume a key rotation, see
+    /// [WalletBackend::rotate_encryption_key](crate::storage::database::WalletBackend::rotate_encryption_key). No other
+    /// backend may use the database until the rotation has completed.
+    pub fn open_for_key_rotation(
+        database_connection: WalletDbConnection,
+        passphrase: SafePassword,
+    ) -> Result<Self, WalletStorageError> {
+        Self::open(database_connection, &PassphraseKeyProvider::new(passphrase))
+    }
+
+    fn open(
+        database_connection: WalletDbConnection,
+        key_provider: &dyn DbKeyProvider,
+    ) -> Result<Self, WalletStorageError> {
+        debug!(
+            target: LOG_TARGET,
+            "Unlocking wallet database using the '{}' key provider",
+            key_provider.name()
+        );
+        let secret = key_provider.fetch_secret()?;
+        let cipher = get_db_cipher(&database_connection, &secret)?;
 
         Ok(Self {
             database_connection,
@@ -253,18 +536,38 @@ impl WalletSqliteDatabase {
     }
 
     fn get_master_seed(&self, conn: &mut SqliteConnection) -> Result<Option<CipherSeed>, WalletStorageError> {
+        self.get_cipher_seed(&DbKey::MasterSeed, b"wallet_setting_master_seed", conn)
+    }
+
+    fn set_passphrase_seed(&self, seed: &CipherSeed, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
-        if let Some(seed_str) = WalletSettingSql::get(&DbKey::MasterSeed, conn)? {
+        let seed_bytes = Hidden::hide(seed.encipher(None)?);
+        let ciphertext_integral_nonce =
+            encrypt_bytes_integral_nonce(&cipher, b"wallet_setting_passphrase_seed".to_vec(), seed_bytes)
+                .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+        WalletSettingSql::new(DbKey::PassphraseSeed, ciphertext_integral_nonce.to_hex()).set(conn)?;
+
+        Ok(())
+    }
+
+    fn get_passphrase_seed(&self, conn: &mut SqliteConnection) -> Result<Option<CipherSeed>, WalletStorageError> {
+        self.get_cipher_seed(&DbKey::PassphraseSeed, b"wallet_setting_passphrase_seed", conn)
+    }
+
+    fn get_cipher_seed(
+        &self,
+        key: &DbKey,
+        domain: &[u8],
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<CipherSeed>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(seed_str) = WalletSettingSql::get(key, conn)? {
             let seed = {
                 // Decrypted_key_bytes contains sensitive data regarding decrypted
                 // seed words. For this reason, we should zeroize the underlying data buffer
                 let decrypted_key_bytes = Hidden::hide(
-                    decrypt_bytes_integral_nonce(
-                        &cipher,
-                        b"wallet_setting_master_seed".to_vec(),
-                        &from_hex(seed_str.as_str())?,
-                    )
-                    .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
+                    decrypt_bytes_integral_nonce(&cipher, domain.to_vec(), &from_hex(seed_str.as_str())?)
+                        .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
                 );
                 CipherSeed::from_enciphered_bytes(decrypted_key_bytes.reveal(), None)?
             };
@@ -275,6 +578,44 @@ impl WalletSqliteDatabase {
         }
     }
 
+    fn set_watch_only_keys(&self, keys: &WatchOnlyKeys, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        let mut key_bytes = keys.view_key.as_bytes().to_vec();
+        key_bytes.extend_from_slice(keys.spend_public_key.as_bytes());
+        let ciphertext_integral_nonce = encrypt_bytes_integral_nonce(
+            &cipher,
+            b"wallet_setting_watch_only_keys".to_vec(),
+            Hidden::hide(key_bytes),
+        )
+        .map_err(|e| WalletStorageError::AeadError(format!("Encryption Error:{}", e)))?;
+        WalletSettingSql::new(DbKey::WatchOnlyKeys, ciphertext_integral_nonce.to_hex()).set(conn)?;
+
+        Ok(())
+    }
+
+    fn get_watch_only_keys(&self, conn: &mut SqliteConnection) -> Result<Option<WatchOnlyKeys>, WalletStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        if let Some(keys_str) = WalletSettingSql::get(&DbKey::WatchOnlyKeys, conn)? {
+            let decrypted_key_bytes = Hidden::hide(
+                decrypt_bytes_integral_nonce(
+                    &cipher,
+                    b"wallet_setting_watch_only_keys".to_vec(),
+                    &from_hex(keys_str.as_str())?,
+                )
+                .map_err(|e| WalletStorageError::AeadError(format!("Decryption Error:{}", e)))?,
+            );
+            let (view_key, spend_public_key) = decrypted_key_bytes.reveal().split_at(PrivateKey::key_length());
+            Ok(Some(WatchOnlyKeys {
+                view_key: PrivateKey::from_bytes(view_key)
+                    .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+                spend_public_key: PublicKey::from_bytes(spend_public_key)
+                    .map_err(|e| WalletStorageError::ConversionError(e.to_string()))?,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
     fn decrypt_value<T: Encryptable<XChaCha20Poly1305>>(&self, o: T) -> Result<T, WalletStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
         let o = o
@@ -371,6 +712,17 @@ impl WalletSqliteDatabase {
                 kvp_text = "MasterSeed";
                 self.set_master_seed(&seed, &mut conn)?;
             },
+            DbKeyValuePair::PassphraseSeed(seed) => {
+                kvp_text = "PassphraseSeed";
+                self.set_passphrase_seed(&seed, &mut conn)?;
+            },
+            DbKeyValuePair::MasterAndPassphraseSeed(master_seed, passphrase_seed) => {
+                kvp_text = "MasterAndPassphraseSeed";
+                conn.transaction::<_, WalletStorageError, _>(|conn| {
+                    self.set_master_seed(&master_seed, conn)?;
+                    self.set_passphrase_seed(&passphrase_seed, conn)
+                })?;
+            },
             DbKeyValuePair::TorId(node_id) => {
                 kvp_text = "TorId";
                 self.set_tor_id(node_id, &mut conn)?;
@@ -416,12 +768,20 @@ impl WalletSqliteDatabase {
                 WalletSettingSql::new(DbKey::CommsIdentitySignature, identity_sig.to_bytes().to_hex())
                     .set(&mut conn)?;
             },
+            DbKeyValuePair::WatchOnlyKeys(keys) => {
+                kvp_text = "WatchOnlyKeys";
+                self.set_watch_only_keys(&keys, &mut conn)?;
+            },
             DbKeyValuePair::NetworkAndVersion((network, version)) => {
                 kvp_text = "NetworkAndVersion";
 
                 WalletSettingSql::new(DbKey::LastAccessedNetwork, network).set(&mut conn)?;
                 WalletSettingSql::new(DbKey::LastAccessedVersion, version).set(&mut conn)?;
             },
+            DbKeyValuePair::WalletNetwork(network) => {
+                kvp_text = "WalletNetwork";
+                WalletSettingSql::new(DbKey::WalletNetwork, network).set(&mut conn)?;
+            },
         }
 
         if start.elapsed().as_millis() > 0 {
@@ -445,6 +805,9 @@ impl WalletSqliteDatabase {
             DbKey::MasterSeed => {
                 let _ = WalletSettingSql::clear(&DbKey::MasterSeed, &mut conn)?;
             },
+            DbKey::PassphraseSeed => {
+                let _ = WalletSettingSql::clear(&DbKey::PassphraseSeed, &mut conn)?;
+            },
             DbKey::ClientKey(ref k) => {
                 if ClientKeyValueSql::clear(k, &mut conn)? {
                     return Ok(Some(DbValue::ValueCleared));
@@ -463,7 +826,10 @@ impl WalletSqliteDatabase {
             DbKey::WalletBirthday |
             DbKey::CommsIdentitySignature |
             DbKey::LastAccessedNetwork |
-            DbKey::LastAccessedVersion => {
+            DbKey::LastAccessedVersion |
+            DbKey::WatchOnlyKeys |
+            DbKey::WalletNetwork |
+            DbKey::KeyRotationState => {
                 return Err(WalletStorageError::OperationNotSupported);
             },
         };
@@ -484,16 +850,22 @@ impl WalletSqliteDatabase {
         let cipher = acquire_read_lock!(self.cipher);
         (*cipher).clone()
     }
+
+    /// The cipher shared with the other wallet backends, which is replaced when the database encryption key is rotated
+    pub fn shared_cipher(&self) -> Arc<RwLock<XChaCha20Poly1305>> {
+        self.cipher.clone()
+    }
 }
 
 impl WalletBackend for WalletSqliteDatabase {
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, WalletStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = match key {
             DbKey::MasterSeed => self.get_master_seed(&mut conn)?.map(DbValue::MasterSeed),
+            DbKey::PassphraseSeed => self.get_passphrase_seed(&mut conn)?.map(DbValue::PassphraseSeed),
             DbKey::ClientKey(k) => match ClientKeyValueSql::get(k, &mut conn)? {
                 None => None,
                 Some(v) => {
@@ -512,6 +884,11 @@ impl WalletBackend for WalletSqliteDatabase {
             DbKey::WalletBirthday => WalletSettingSql::get(key, &mut conn)?.map(DbValue::WalletBirthday),
             DbKey::LastAccessedNetwork => WalletSettingSql::get(key, &mut conn)?.map(DbValue::LastAccessedNetwork),
             DbKey::LastAccessedVersion => WalletSettingSql::get(key, &mut conn)?.map(DbValue::LastAccessedVersion),
+            DbKey::WalletNetwork => WalletSettingSql::get(key, &mut conn)?.map(DbValue::WalletNetwork),
+            DbKey::KeyRotationState => WalletSettingSql::get(key, &mut conn)?.map(DbValue::KeyRotationState),
+            DbKey::WatchOnlyKeys => self
+                .get_watch_only_keys(&mut conn)?
+                .map(|keys| DbValue::WatchOnlyKeys(Box::new(keys))),
             DbKey::CommsIdentitySignature => WalletSettingSql::get(key, &mut conn)?
                 .and_then(|s| from_hex(&s).ok())
                 .and_then(|bytes| IdentitySignature::from_bytes(&bytes).ok())
@@ -540,7 +917,7 @@ impl WalletBackend for WalletSqliteDatabase {
     }
 
     fn get_scanned_blocks(&self) -> Result<Vec<ScannedBlock>, WalletStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let sql_blocks = ScannedBlockSql::index(&mut conn)?;
         sql_blocks
             .into_iter()
@@ -580,38 +957,11 @@ impl WalletBackend for WalletSqliteDatabase {
         match DatabaseEncryptionFields::read(&mut conn) {
             // Key-related data was present and valid
             Ok(Some(data)) => {
-                // Use the given version if it is valid
-                let argon2_params = Argon2Parameters::from_version(Some(data.secondary_key_version))?;
-
-                // Derive a secondary key from the existing passphrase and salt
-                let (secondary_key, secondary_key_hash) =
-                    derive_secondary_key(existing, argon2_params.clone(), &data.secondary_key_salt)?;
-
-                // Attempt to decrypt the encrypted main key
-                if data.secondary_key_hash != secondary_key_hash {
-                    return Err(WalletStorageError::InvalidPassphrase);
-                }
-                let main_key = decrypt_main_key(&secondary_key, &data.encrypted_main_key, argon2_params.id)?;
+                // Attempt to decrypt the main key with the existing passphrase
+                let main_key = data.unseal(existing)?;
 
-                // Now use the most recent version
-                let new_argon2_params = Argon2Parameters::from_version(None)?;
-
-                // Derive a new secondary key from the new passphrase and a fresh salt
-                let new_secondary_key_salt = SaltString::generate(&mut OsRng).to_string();
-                let (new_secondary_key, new_secondary_key_hash) =
-                    derive_secondary_key(new, new_argon2_params.clone(), &new_secondary_key_salt)?;
-
-                // Encrypt the main key with the new secondary key
-                let new_encrypted_main_key = encrypt_main_key(&new_secondary_key, &main_key, new_argon2_params.id)?;
-
-                // Store the new key-related fields
-                DatabaseEncryptionFields {
-                    secondary_key_version: new_argon2_params.id,
-                    secondary_key_salt: new_secondary_key_salt,
-                    secondary_key_hash: new_secondary_key_hash,
-                    encrypted_main_key: new_encrypted_main_key,
-                }
-                .write(&mut conn)?;
+                // Protect the main key with the new passphrase and store the new key-related fields
+                DatabaseEncryptionFields::seal(&main_key, new)?.write(&mut conn)?;
             },
 
             // If any key-related is not present, this is an invalid state
@@ -625,6 +975,115 @@ impl WalletBackend for WalletSqliteDatabase {
         Ok(())
     }
 
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        match DatabaseEncryptionFields::read(&mut conn)? {
+            Some(data) => data.verify(passphrase),
+            None => Err(WalletStorageError::UnexpectedResult(
+                "Unable to get valid key-related data from database".into(),
+            )),
+        }
+    }
+
+    fn rotate_encryption_key(
+        &self,
+        existing: &SafePassword,
+        new: &SafePassword,
+        progress: &mut dyn FnMut(KeyRotationProgress),
+    ) -> Result<(), WalletStorageError> {
+        // Take the connection before the cipher, so that backends waiting on the cipher cannot exhaust the pool
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut cipher = acquire_write_lock!(self.cipher);
+
+        let current_fields = DatabaseEncryptionFields::read(&mut conn)?.ok_or_else(|| {
+            WalletStorageError::UnexpectedResult("Unable to get valid key-related data from database".into())
+        })?;
+        let old_cipher = XChaCha20Poly1305::new(Key::from_slice(current_fields.unseal(existing)?.reveal()));
+
+        let mut state = match KeyRotationState::read(&mut conn)? {
+            Some(state) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Resuming database encryption key rotation after {} tables", state.tables_completed
+                );
+                state
+            },
+            None => {
+                let mut main_key = WalletMainEncryptionKey::from(vec![0u8; size_of::<Key>()]);
+                OsRng.fill_bytes(main_key.reveal_mut());
+                let state = KeyRotationState::new(&DatabaseEncryptionFields::seal(&main_key, new)?);
+                state.write(&mut conn)?;
+                state
+            },
+        };
+        // When resuming, this also ensures the rotation continues with the passphrase it was started with
+        let new_fields = state.encryption_fields()?;
+        let new_cipher = XChaCha20Poly1305::new(Key::from_slice(new_fields.unseal(new)?.reveal()));
+
+        let steps = KeyRotationStep::all();
+        let total_tables = steps.len();
+        for step in steps.into_iter().skip(state.tables_completed) {
+            let tables_completed = state.tables_completed + 1;
+            let rows = conn.transaction::<_, WalletStorageError, _>(|conn| {
+                let rows = step.reencrypt(conn, &old_cipher, &new_cipher)?;
+                KeyRotationState {
+                    tables_completed,
+                    ..state.clone()
+                }
+                .write(conn)?;
+                Ok(rows)
+            })?;
+            state.tables_completed = tables_completed;
+            debug!(
+                target: LOG_TARGET,
+                "Re-encrypted {} rows of `{}` ({}/{})",
+                rows,
+                step.table_name(),
+                tables_completed,
+                total_tables
+            );
+            progress(KeyRotationProgress {
+                table: step.table_name(),
+                rows,
+                tables_completed,
+                total_tables,
+            });
+        }
+
+        // Switch over to the new key fields and finish the rotation atomically
+        conn.transaction::<_, WalletStorageError, _>(|conn| {
+            new_fields.write(conn)?;
+            WalletSettingSql::clear(&DbKey::KeyRotationState, conn)?;
+            Ok(())
+        })?;
+        *cipher = new_cipher;
+        info!(target: LOG_TARGET, "Database encryption key rotation completed");
+
+        Ok(())
+    }
+
+    fn is_key_rotation_pending(&self) -> Result<bool, WalletStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        Ok(KeyRotationState::read(&mut conn)?.is_some())
+    }
+
+    fn supports_snapshots(&self) -> bool {
+        true
+    }
+
+    fn create_snapshot(&self, path: &Path) -> Result<(), WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        // Holding the cipher keeps a key rotation from starting while the snapshot is taken
+        let cipher = acquire_read_lock!(self.cipher);
+        if KeyRotationState::read(&mut conn)?.is_some() {
+            return Err(WalletStorageError::UnexpectedResult(
+                "Cannot take a snapshot while a database encryption key rotation is pending".into(),
+            ));
+        }
+        write_snapshot(&mut conn, &cipher, path)
+    }
+
     fn create_burnt_proof(
         &self,
         id: u32,
@@ -645,7 +1104,7 @@ impl WalletBackend for WalletSqliteDatabase {
     }
 
     fn fetch_burnt_proof(&self, id: u32) -> Result<(u32, String, String, NaiveDateTime), WalletStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
 
         match BurntProofSql::get(id, &mut conn) {
             Ok(None) => Err(WalletStorageError::BurntProofNotFound(id)),
@@ -682,7 +1141,7 @@ impl WalletBackend for WalletSqliteDatabase {
     }
 
     fn fetch_burnt_proofs(&self) -> Result<Vec<(u32, String, String, NaiveDateTime)>, WalletStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let proofs = BurntProofSql::index(&mut conn)?;
 
         Ok(proofs
@@ -717,6 +1176,11 @@ impl WalletBackend for WalletSqliteDatabase {
         BurntProofSql::delete(id, &mut conn)?;
         Ok(())
     }
+
+    fn check_storage_integrity(&self, repair: bool) -> Result<StorageIntegrityReport, WalletStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        check_storage_integrity(&mut conn, repair)
+    }
 }
 
 /// Derive a secondary database key and associated commitment
@@ -804,45 +1268,15 @@ fn get_db_cipher(
             let mut rng = OsRng;
             rng.fill_bytes(main_key.reveal_mut());
 
-            // Use the most recent `Argon2` parameters
-            let argon2_params = Argon2Parameters::from_version(None)?;
-
-            // Derive the secondary key from the user's passphrase and a high-entropy salt
-            let secondary_key_salt = SaltString::generate(&mut rng).to_string();
-            let (secondary_key, secondary_key_hash) =
-                derive_secondary_key(passphrase, argon2_params.clone(), &secondary_key_salt)?;
-
-            // Use the secondary key to encrypt the main key
-            let encrypted_main_key = encrypt_main_key(&secondary_key, &main_key, argon2_params.id)?;
-
-            // Store the key-related fields
-            DatabaseEncryptionFields {
-                secondary_key_version: argon2_params.id,
-                secondary_key_salt,
-                secondary_key_hash,
-                encrypted_main_key,
-            }
-            .write(&mut conn)?;
+            // Protect the main key with the user's passphrase and store the key-related fields
+            DatabaseEncryptionFields::seal(&main_key, passphrase)?.write(&mut conn)?;
 
             // Return the unencrypted main key
             main_key
         },
 
         // Encryption has already been set up
-        Ok(Some(data)) => {
-            // Use the given version if it is valid
-            let argon2_params = Argon2Parameters::from_version(Some(data.secondary_key_version))?;
-
-            // Derive the secondary key from the user's passphrase and salt
-            let (secondary_key, secondary_key_hash) =
-                derive_secondary_key(passphrase, argon2_params, &data.secondary_key_salt)?;
-
-            // Attempt to decrypt and return the encrypted main key
-            if data.secondary_key_hash != secondary_key_hash {
-                return Err(WalletStorageError::InvalidPassphrase);
-            }
-            decrypt_main_key(&secondary_key, &data.encrypted_main_key, data.secondary_key_version)?
-        },
+        Ok(Some(data)) => data.unseal(passphrase)?,
 
         // We couldn't get valid key-related data
         Err(_) => {
@@ -900,9 +1334,9 @@ impl WalletSettingSql {
 /// A Sql version of the wallet setting key-value table
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
 #[diesel(table_name = client_key_values)]
-struct ClientKeyValueSql {
-    key: String,
-    value: String,
+pub(crate) struct ClientKeyValueSql {
+    pub(crate) key: String,
+    pub(crate) value: String,
 }
 
 impl ClientKeyValueSql {
@@ -911,7 +1345,6 @@ impl ClientKeyValueSql {
         client_kv.encrypt(cipher).map_err(WalletStorageError::AeadError)
     }
 
-    #[allow(dead_code)]
     pub fn index(conn: &mut SqliteConnection) -> Result<Vec<Self>, WalletStorageError> {
         Ok(client_key_values::table.load::<ClientKeyValueSql>(conn)?)
     }
@@ -983,11 +1416,11 @@ impl Encryptable<XChaCha20Poly1305> for ClientKeyValueSql {
 
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
 #[diesel(table_name = burnt_proofs)]
-struct BurntProofSql {
-    id: i32,
-    reciprocal_claim_public_key: String,
-    payload: String,
-    burned_at: NaiveDateTime,
+pub(crate) struct BurntProofSql {
+    pub(crate) id: i32,
+    pub(crate) reciprocal_claim_public_key: String,
+    pub(crate) payload: String,
+    pub(crate) burned_at: NaiveDateTime,
 }
 
 impl BurntProofSql {
@@ -1031,6 +1464,13 @@ impl BurntProofSql {
         let num_deleted = diesel::delete(burnt_proofs::table.filter(burnt_proofs::id.eq(id as i32))).execute(conn)?;
         Ok(num_deleted > 0)
     }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+        diesel::update(burnt_proofs::table.filter(burnt_proofs::id.eq(self.id)))
+            .set(burnt_proofs::payload.eq(&self.payload))
+            .execute(conn)?;
+        Ok(())
+    }
 }
 
 impl Encryptable<XChaCha20Poly1305> for BurntProofSql {
@@ -1077,9 +1517,20 @@ impl Encryptable<XChaCha20Poly1305> for BurntProofSql {
 
 #[cfg(test)]
 mod test {
+    use std::mem::size_of;
+
+    use chacha20poly1305::Key;
+    use rand::{rngs::OsRng, RngCore};
     use tari_common_sqlite::sqlite_connection_pool::PooledDbConnection;
-    use tari_common_types::encryption::{decrypt_bytes_integral_nonce, Encryptable};
-    use tari_key_manager::cipher_seed::CipherSeed;
+    use tari_common_types::{
+        encryption::{decrypt_bytes_integral_nonce, Encryptable},
+        types::{PrivateKey, PublicKey},
+    };
+    use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey as SecretKeyTrait};
+    use tari_key_manager::{
+        cipher_seed::CipherSeed,
+        key_manager_service::storage::sqlite_db::KeyManagerSqliteDatabase,
+    };
     use tari_test_utils::random::string;
     use tari_utilities::{
         hex::{from_hex, Hex},
@@ -1088,11 +1539,76 @@ mod test {
     };
     use tempfile::tempdir;
 
-    use crate::storage::{
-        database::{DbKey, DbValue, WalletBackend},
-        sqlite_db::wallet::{ClientKeyValueSql, WalletSettingSql, WalletSqliteDatabase},
-        sqlite_utilities::run_migration_and_create_sqlite_connection,
+    use crate::{
+        error::WalletStorageError,
+        storage::{
+            database::{DbKey, DbValue, WalletBackend, WalletDatabase},
+            key_provider::DbKeyProvider,
+            sqlite_db::wallet::{
+                ClientKeyValueSql,
+                DatabaseEncryptionFields,
+                KeyRotationState,
+                WalletMainEncryptionKey,
+                WalletSettingSql,
+                WalletSqliteDatabase,
+            },
+            sqlite_utilities::run_migration_and_create_sqlite_connection,
+        },
+        util::wallet_identity::WatchOnlyKeys,
     };
+    struct TestKeystoreProvider {
+        secret: Option<String>,
+    }
+
+    impl DbKeyProvider for TestKeystoreProvider {
+        fn name(&self) -> &'static str {
+            "test keystore"
+        }
+
+        fn fetch_secret(&self) -> Result<SafePassword, WalletStorageError> {
+            self.secret
+                .clone()
+                .map(SafePassword::from)
+                .ok_or_else(|| WalletStorageError::KeyProviderError {
+                    provider: self.name(),
+                    details: "Key not found".to_string(),
+                })
+        }
+    }
+
+    #[test]
+    fn test_key_provider() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let db_path = format!("{}/{}", db_folder, db_name);
+        let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+        let provider = TestKeystoreProvider {
+            secret: Some("keystore secret".to_string()),
+        };
+        WalletSqliteDatabase::new_with_key_provider(connection.clone(), &provider).unwrap();
+
+        // The same keystore secret unlocks the database again
+        assert!(WalletSqliteDatabase::new_with_key_provider(connection.clone(), &provider).is_ok());
+
+        // A different secret does not
+        let other = TestKeystoreProvider {
+            secret: Some("other keystore secret".to_string()),
+        };
+        assert!(matches!(
+            WalletSqliteDatabase::new_with_key_provider(connection.clone(), &other),
+            Err(WalletStorageError::InvalidPassphrase)
+        ));
+
+        // Provider failures are surfaced to the caller
+        let missing = TestKeystoreProvider { secret: None };
+        assert!(matches!(
+            WalletSqliteDatabase::new_with_key_provider(connection, &missing),
+            Err(WalletStorageError::KeyProviderError { .. })
+        ));
+    }
+
     #[test]
     fn test_passphrase() {
         // Set up a database
@@ -1111,6 +1627,10 @@ mod test {
         // Try to load with the wrong passphrase
         assert!(WalletSqliteDatabase::new(connection.clone(), "evil passphrase".to_string().into()).is_err());
 
+        // Verify passphrases without loading
+        assert!(db.verify_passphrase(&"passphrase".to_string().into()).unwrap());
+        assert!(!db.verify_passphrase(&"evil passphrase".to_string().into()).unwrap());
+
         // Try to change the passphrase, but fail
         assert!(db
             .change_passphrase(
@@ -1132,11 +1652,112 @@ mod test {
 
         // The existing passphrase no longer works
         assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_err());
+        assert!(!db.verify_passphrase(&"passphrase".to_string().into()).unwrap());
 
         // The new passphrase does
         assert!(WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).is_ok());
     }
 
+    #[test]
+    fn test_rotate_encryption_key() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let backend = WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).unwrap();
+        let _key_manager = KeyManagerSqliteDatabase::init(connection.clone(), backend.cipher());
+        let old_cipher = backend.cipher();
+        let db = WalletDatabase::new(backend);
+
+        let seed = CipherSeed::new();
+        db.set_master_seed(seed.clone()).unwrap();
+        db.set_client_key_value("key".to_string(), "value".to_string()).unwrap();
+
+        // The existing passphrase must be correct
+        assert!(matches!(
+            db.rotate_encryption_key(
+                &"evil passphrase".to_string().into(),
+                &"new passphrase".to_string().into(),
+                |_| {}
+            ),
+            Err(WalletStorageError::InvalidPassphrase)
+        ));
+
+        let mut progress = Vec::new();
+        db.rotate_encryption_key(
+            &"passphrase".to_string().into(),
+            &"new passphrase".to_string().into(),
+            |p| progress.push(p),
+        )
+        .unwrap();
+        let last = progress.last().unwrap();
+        assert_eq!(last.tables_completed, last.total_tables);
+        assert_eq!(progress.iter().map(|p| p.rows).sum::<usize>(), 2);
+        assert!(!db.is_key_rotation_pending().unwrap());
+
+        // The open database carries on with the new key
+        assert_eq!(db.get_master_seed().unwrap().unwrap().entropy(), seed.entropy());
+        assert_eq!(db.get_client_key_value("key".to_string()).unwrap().unwrap(), "value");
+
+        // The data is no longer readable with the old key. The connection is released again as the writer pool only
+        // holds one.
+        {
+            let mut conn = connection.get_pooled_connection().unwrap();
+            let ckv = ClientKeyValueSql::get("key", &mut conn).unwrap().unwrap();
+            assert!(ckv.decrypt(&old_cipher).is_err());
+        }
+
+        // Only the new passphrase opens the database
+        assert!(WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).is_err());
+        let reopened = WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).unwrap();
+        let reopened = WalletDatabase::new(reopened);
+        assert_eq!(reopened.get_master_seed().unwrap().unwrap().entropy(), seed.entropy());
+    }
+
+    #[test]
+    fn test_pending_key_rotation_must_be_completed() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let db = WalletDatabase::new(
+            WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()).unwrap(),
+        );
+        let seed = CipherSeed::new();
+        db.set_master_seed(seed.clone()).unwrap();
+
+        // Leave a rotation to a new passphrase behind, as if it had been interrupted before re-encrypting any table
+        {
+            let mut main_key = WalletMainEncryptionKey::from(vec![0u8; size_of::<Key>()]);
+            OsRng.fill_bytes(main_key.reveal_mut());
+            let fields = DatabaseEncryptionFields::seal(&main_key, &"new passphrase".to_string().into()).unwrap();
+            KeyRotationState::new(&fields)
+                .write(&mut connection.get_pooled_connection().unwrap())
+                .unwrap();
+        }
+        assert!(matches!(
+            WalletSqliteDatabase::new(connection.clone(), "passphrase".to_string().into()),
+            Err(WalletStorageError::KeyRotationPending)
+        ));
+
+        let db = WalletDatabase::new(
+            WalletSqliteDatabase::open_for_key_rotation(connection.clone(), "passphrase".to_string().into()).unwrap(),
+        );
+        assert!(db.is_key_rotation_pending().unwrap());
+        db.rotate_encryption_key(
+            &"passphrase".to_string().into(),
+            &"new passphrase".to_string().into(),
+            |_| {},
+        )
+        .unwrap();
+
+        let reopened = WalletSqliteDatabase::new(connection, "new passphrase".to_string().into()).unwrap();
+        let reopened = WalletDatabase::new(reopened);
+        assert_eq!(reopened.get_master_seed().unwrap().unwrap().entropy(), seed.entropy());
+    }
+
     #[test]
     #[allow(unused_must_use)]
     fn test_malleated_secondary_key_hash() {
@@ -1251,7 +1872,6 @@ mod test {
         let db_tempdir = tempdir().unwrap();
         let db_folder = db_tempdir.path().to_str().unwrap().to_string();
         let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
-        let mut conn = connection.get_pooled_connection().unwrap();
 
         let key1 = "key1".to_string();
         let value1 = "value1".to_string();
@@ -1259,8 +1879,9 @@ mod test {
         let value2 = "value2".to_string();
 
         let passphrase = "a very very secret key example.".to_string().into();
-        let db = WalletSqliteDatabase::new(connection, passphrase).unwrap();
+        let db = WalletSqliteDatabase::new(connection.clone(), passphrase).unwrap();
         let cipher = db.cipher();
+        let mut conn = connection.get_pooled_connection().unwrap();
 
         ClientKeyValueSql::new(key1.clone(), value1.clone(), &cipher)
             .unwrap()
@@ -1327,4 +1948,71 @@ mod test {
 
         assert_eq!(decrypted_db_seed, seed_bytes);
     }
+
+    #[test]
+    fn test_set_master_and_passphrase_seed() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let db = WalletDatabase::new(WalletSqliteDatabase::new(connection, passphrase).unwrap());
+
+        let master_seed = CipherSeed::new();
+        let passphrase_seed = master_seed
+            .derive_with_seed_passphrase(&SafePassword::from("seed passphrase".to_string()))
+            .unwrap();
+        db.set_master_and_passphrase_seed(master_seed.clone(), passphrase_seed.clone())
+            .unwrap();
+
+        assert_eq!(db.get_master_seed().unwrap().unwrap(), master_seed);
+        assert_eq!(db.get_passphrase_seed().unwrap().unwrap(), passphrase_seed);
+    }
+
+    #[test]
+    fn test_watch_only_keys_round_trip() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let wallet = WalletSqliteDatabase::new(connection.clone(), passphrase).unwrap();
+        let mut conn = connection.get_pooled_connection().unwrap();
+        assert!(wallet.get_watch_only_keys(&mut conn).unwrap().is_none());
+
+        let (_, spend_public_key) = PublicKey::random_keypair(&mut OsRng);
+        let keys = WatchOnlyKeys {
+            view_key: PrivateKey::random(&mut OsRng),
+            spend_public_key,
+        };
+        wallet.set_watch_only_keys(&keys, &mut conn).unwrap();
+
+        let stored = wallet.get_watch_only_keys(&mut conn).unwrap().unwrap();
+        assert_eq!(stored.view_key, keys.view_key);
+        assert_eq!(stored.spend_public_key, keys.spend_public_key);
+    }
+
+    #[test]
+    fn test_network_binding() {
+        let db_name = format!("{}.sqlite3", string(8).as_str());
+        let db_tempdir = tempdir().unwrap();
+        let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+        let connection = run_migration_and_create_sqlite_connection(format!("{}{}", db_folder, db_name), 16).unwrap();
+
+        let passphrase = SafePassword::from("an example very very secret key.".to_string());
+        let db = WalletDatabase::new(WalletSqliteDatabase::new(connection, passphrase).unwrap());
+        assert!(db.get_wallet_network().unwrap().is_none());
+
+        db.ensure_network("esmeralda").unwrap();
+        assert_eq!(db.get_wallet_network().unwrap().unwrap(), "esmeralda");
+        db.ensure_network("esmeralda").unwrap();
+
+        let err = db.ensure_network("mainnet").unwrap_err();
+        assert!(matches!(err, WalletStorageError::NetworkMismatch { .. }));
+
+        db.rebind_network("mainnet").unwrap();
+        db.ensure_network("mainnet").unwrap();
+    }
 }
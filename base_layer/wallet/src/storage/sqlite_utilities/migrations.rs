@@ -0,0 +1,346 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Versioned schema migrations for the wallet sqlite database.
+//!
+//! The wallet schema is described by the diesel migrations embedded from `./migrations`, each identified by its version
+//! (the timestamp prefix of the migration directory). This module reports which of them are pending on a database and
+//! applies them:
+//! - All pending migrations are applied in a single transaction, so a failed migration leaves the schema untouched.
+//! - Before an existing database is migrated, a copy of it is written next to it with a `.pre-migration-<version>`
+//!   suffix, where `<version>` is the last migration that was applied to it.
+//! - A dry run applies the pending migrations to a scratch copy of the database and checks the result, without touching
+//!   the database itself.
+//! - Applied migrations can be rolled back to an earlier version with their `down.sql` scripts. The early migrations
+//!   have no usable down scripts, so rolling back is limited to [OLDEST_ROLLBACK_VERSION] and later.
+
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use diesel::{
+    migration::{Migration, MigrationSource},
+    sql_query,
+    sql_types::Text,
+    sqlite::Sqlite,
+    Connection,
+    RunQueryDsl,
+    SqliteConnection,
+};
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+use log::*;
+
+use crate::{
+    error::WalletStorageError,
+    storage::sqlite_db::snapshot::{path_to_str, remove_if_exists, with_suffix},
+};
+
+const LOG_TARGET: &str = "wallet::storage::migrations";
+
+/// The wallet schema migrations
+pub const WALLET_MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
+
+/// The oldest version the schema can be rolled back to. This and all earlier migrations cannot be reverted.
+pub const OLDEST_ROLLBACK_VERSION: &str = "2023-11-13-082000";
+
+#[derive(QueryableByName)]
+struct IntegrityCheckSql {
+    #[diesel(sql_type = Text)]
+    integrity_check: String,
+}
+
+/// The migration state of a wallet database
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationStatus {
+    /// The names of the migrations that have been applied, oldest first
+    pub applied: Vec<String>,
+    /// The names of the migrations that have not been applied yet, in the order they will be applied
+    pub pending: Vec<String>,
+}
+
+impl MigrationStatus {
+    pub fn is_up_to_date(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// The version of the last applied migration, if any
+    pub fn current_version(&self) -> Option<&str> {
+        self.applied.last().map(|name| migration_version(name))
+    }
+}
+
+/// How pending migrations are applied by [migrate]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MigrationOptions {
+    /// Apply the migrations to a scratch copy of the database only, leaving the database itself untouched
+    pub dry_run: bool,
+    /// Copy the database before migrating it. Databases that have not been migrated before are never copied.
+    pub backup: bool,
+}
+
+impl Default for MigrationOptions {
+    fn default() -> Self {
+        Self {
+            dry_run: false,
+            backup: true,
+        }
+    }
+}
+
+/// The outcome of [migrate]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// The migrations that were applied, or that would be applied in a dry run
+    pub applied: Vec<String>,
+    /// The copy of the database taken before the migrations were applied
+    pub backup_path: Option<PathBuf>,
+    pub dry_run: bool,
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.applied.is_empty() {
+            return write!(f, "The wallet database is up to date");
+        }
+        let verb = if self.dry_run { "can be applied" } else { "applied" };
+        write!(
+            f,
+            "{} migration(s) {}: {}",
+            self.applied.len(),
+            verb,
+            self.applied.join(", ")
+        )?;
+        if let Some(ref path) = self.backup_path {
+            write!(f, " (backup at '{}')", path.display())?;
+        }
+        Ok(())
+    }
+}
+
+/// Report the applied and pending migrations of the database behind `conn`
+pub fn migration_status(conn: &mut SqliteConnection) -> Result<MigrationStatus, WalletStorageError> {
+    let applied = applied_versions(conn)?;
+    let (applied, pending) = all_migrations()?
+        .into_iter()
+        .map(|m| m.name().to_string())
+        .partition(|name| applied.contains(migration_version(name)));
+    Ok(MigrationStatus { applied, pending })
+}
+
+/// Apply the pending migrations to the database at `db_path`, which `conn` is connected to
+pub fn migrate(
+    conn: &mut SqliteConnection,
+    db_path: &Path,
+    options: MigrationOptions,
+) -> Result<MigrationReport, WalletStorageError> {
+    let status = migration_status(conn)?;
+    let mut report = MigrationReport {
+        applied: status.pending.clone(),
+        backup_path: None,
+        dry_run: options.dry_run,
+    };
+    if status.is_up_to_date() {
+        return Ok(report);
+    }
+
+    if options.dry_run {
+        dry_run(conn, db_path)?;
+        info!(target: LOG_TARGET, "{}", report);
+        return Ok(report);
+    }
+
+    if options.backup {
+        if let Some(version) = status.current_version() {
+            let backup_path = with_suffix(db_path, &format!(".pre-migration-{}", version));
+            copy_database(conn, &backup_path)?;
+            report.backup_path = Some(backup_path);
+        }
+    }
+
+    apply_pending(conn)?;
+    info!(target: LOG_TARGET, "{}", report);
+    Ok(report)
+}
+
+/// Roll the database behind `conn` back to the migration `version`, by reverting every applied migration that is newer
+/// than it, newest first. All reverts happen in a single transaction. Returns the names of the reverted migrations.
+pub fn rollback_to(conn: &mut SqliteConnection, version: &str) -> Result<Vec<String>, WalletStorageError> {
+    let migrations = all_migrations()?;
+    if !migrations
+        .iter()
+        .any(|m| migration_version(&m.name().to_string()) == version)
+    {
+        return Err(WalletStorageError::DatabaseMigrationError(format!(
+            "Unknown migration version '{}'",
+            version
+        )));
+    }
+    if version < OLDEST_ROLLBACK_VERSION {
+        return Err(WalletStorageError::DatabaseMigrationError(format!(
+            "Cannot roll back to '{}', the oldest version that can be rolled back to is '{}'",
+            version, OLDEST_ROLLBACK_VERSION
+        )));
+    }
+
+    let applied = applied_versions(conn)?;
+    let mut to_revert = migrations
+        .iter()
+        .filter(|m| {
+            let name = m.name().to_string();
+            let migration = migration_version(&name);
+            applied.contains(migration) && migration > version
+        })
+        .collect::<Vec<_>>();
+    to_revert.reverse();
+
+    conn.transaction::<_, WalletStorageError, _>(|conn| {
+        for migration in &to_revert {
+            conn.revert_migration(migration.as_ref()).map_err(|e| {
+                WalletStorageError::DatabaseMigrationError(format!("Reverting '{}' failed: {}", migration.name(), e))
+            })?;
+        }
+        Ok(())
+    })?;
+
+    let reverted = to_revert.iter().map(|m| m.name().to_string()).collect::<Vec<_>>();
+    info!(
+        target: LOG_TARGET,
+        "Rolled the wallet database back to '{}', reverting: {}",
+        version,
+        reverted.join(", ")
+    );
+    Ok(reverted)
+}
+
+/// Apply all pending migrations in a single transaction
+fn apply_pending(conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+    conn.transaction::<_, WalletStorageError, _>(|conn| {
+        conn.run_pending_migrations(WALLET_MIGRATIONS)
+            .map_err(|e| WalletStorageError::DatabaseMigrationError(format!("Database migration failed {}", e)))?;
+        Ok(())
+    })
+}
+
+/// Apply the pending migrations to a scratch copy of the database and check that the result is sound
+fn dry_run(conn: &mut SqliteConnection, db_path: &Path) -> Result<(), WalletStorageError> {
+    let copy_path = with_suffix(db_path, ".migration-dry-run");
+    copy_database(conn, &copy_path)?;
+    let result = SqliteConnection::establish(path_to_str(&copy_path)?)
+        .map_err(WalletStorageError::from)
+        .and_then(|mut copy| {
+            apply_pending(&mut copy)?;
+            integrity_check(&mut copy)
+        });
+    remove_if_exists(&copy_path)?;
+    result
+}
+
+fn integrity_check(conn: &mut SqliteConnection) -> Result<(), WalletStorageError> {
+    let results = sql_query("PRAGMA integrity_check").load::<IntegrityCheckSql>(conn)?;
+    if results.len() != 1 || results[0].integrity_check != "ok" {
+        let problems = results.into_iter().map(|r| r.integrity_check).collect::<Vec<_>>();
+        return Err(WalletStorageError::DatabaseMigrationError(format!(
+            "The migrated database failed the integrity check: {}",
+            problems.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Write a transactionally consistent copy of the database behind `conn` to `path`, replacing any existing file
+fn copy_database(conn: &mut SqliteConnection, path: &Path) -> Result<(), WalletStorageError> {
+    remove_if_exists(path)?;
+    sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(path_to_str(path)?)
+        .execute(conn)?;
+    debug!(target: LOG_TARGET, "Copied the wallet database to '{}'", path.display());
+    Ok(())
+}
+
+fn all_migrations() -> Result<Vec<Box<dyn Migration<Sqlite>>>, WalletStorageError> {
+    MigrationSource::<Sqlite>::migrations(&WALLET_MIGRATIONS)
+        .map_err(|e| WalletStorageError::DatabaseMigrationError(format!("Could not load migrations: {}", e)))
+}
+
+fn applied_versions(conn: &mut SqliteConnection) -> Result<HashSet<String>, WalletStorageError> {
+    Ok(conn
+        .applied_migrations()
+        .map_err(|e| WalletStorageError::DatabaseMigrationError(format!("Could not read applied migrations: {}", e)))?
+        .into_iter()
+        .map(|v| v.to_string())
+        .collect())
+}
+
+/// The version of a migration is the timestamp prefix of its name, e.g. `2023-12-09-090000` for
+/// `2023-12-09-090000_frost`
+fn migration_version(name: &str) -> &str {
+    name.split_once('_').map_or(name, |(version, _)| version)
+}
+
+#[cfg(test)]
+mod test {
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn it_migrates_with_backup_dry_run_and_rollback() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.sqlite3");
+        let mut conn = SqliteConnection::establish(db_path.to_str().unwrap()).unwrap();
+
+        let status = migration_status(&mut conn).unwrap();
+        assert!(status.applied.is_empty());
+        assert!(status.pending.len() > 2);
+        let previous_version = migration_version(&status.pending[status.pending.len() - 2]).to_string();
+
+        // A dry run leaves the database untouched
+        let report = migrate(&mut conn, &db_path, MigrationOptions {
+            dry_run: true,
+            backup: true,
+        })
+        .unwrap();
+        assert_eq!(report.applied, status.pending);
+        assert!(migration_status(&mut conn).unwrap().applied.is_empty());
+        assert!(!with_suffix(&db_path, ".migration-dry-run").exists());
+
+        // A fresh database is not backed up
+        let report = migrate(&mut conn, &db_path, MigrationOptions::default()).unwrap();
+        assert_eq!(report.applied, status.pending);
+        assert!(report.backup_path.is_none());
+        assert!(migration_status(&mut conn).unwrap().is_up_to_date());
+
+        assert!(rollback_to(&mut conn, migration_version(&status.pending[0])).is_err());
+        let reverted = rollback_to(&mut conn, &previous_version).unwrap();
+        assert_eq!(reverted, vec![status.pending.last().unwrap().clone()]);
+        assert_eq!(migration_status(&mut conn).unwrap().pending, reverted);
+
+        // Migrating again backs up the partially migrated database
+        let report = migrate(&mut conn, &db_path, MigrationOptions::default()).unwrap();
+        let backup_path = report.backup_path.unwrap();
+        assert!(backup_path.exists());
+        assert!(backup_path.to_str().unwrap().ends_with(&previous_version));
+        assert!(migration_status(&mut conn).unwrap().is_up_to_date());
+    }
+}
@@ -22,7 +22,7 @@
 
 use std::{fs::File, ops::DerefMut, path::Path, time::Duration};
 
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness};
+use diesel::{Connection, SqliteConnection};
 use fs2::FileExt;
 use log::*;
 use tari_common_sqlite::sqlite_connection_pool::SqliteConnectionPool;
@@ -35,43 +35,85 @@ use crate::{
     error::WalletStorageError,
     output_manager_service::storage::sqlite_db::OutputManagerSqliteDatabase,
     storage::{
-        database::DbKey,
-        sqlite_db::wallet::{WalletSettingSql, WalletSqliteDatabase},
+        database::{DbKey, WalletDatabase},
+        key_provider::{DbKeyProvider, PassphraseKeyProvider},
+        sqlite_db::{
+            snapshot,
+            wallet::{WalletSettingSql, WalletSqliteDatabase},
+        },
+        sqlite_utilities::migrations::{MigrationOptions, MigrationReport},
     },
     transaction_service::storage::sqlite_db::TransactionServiceSqliteDatabase,
 };
 
+pub mod migrations;
 pub(crate) mod wallet_db_connection;
 
 const LOG_TARGET: &str = "wallet::storage:sqlite_utilities";
 
+/// The number of connections in the writer pool. SQLite only allows a single writer at a time, so writes are
+/// serialised in-process rather than contending on the database lock.
+const WRITER_POOL_SIZE: usize = 1;
+
+/// Runs the wallet migrations and returns a connection with a single writer connection and `sqlite_pool_size`
+/// read-only connections, all in WAL mode.
 pub fn run_migration_and_create_sqlite_connection<P: AsRef<Path>>(
     db_path: P,
     sqlite_pool_size: usize,
 ) -> Result<WalletDbConnection, WalletStorageError> {
     let file_lock = acquire_exclusive_file_lock(db_path.as_ref())?;
+    // Holding the lock, no snapshot of this database can be in progress
+    snapshot::remove_leftover_copy(db_path.as_ref())?;
 
     let path_str = db_path
         .as_ref()
         .to_str()
         .ok_or(WalletStorageError::InvalidUnicodePath)?;
 
-    let mut pool = SqliteConnectionPool::new(
+    let mut write_pool = SqliteConnectionPool::new(
         String::from(path_str),
-        sqlite_pool_size,
+        WRITER_POOL_SIZE,
         true,
         true,
         Duration::from_secs(60),
     );
-    pool.create_pool()?;
-    let mut connection = pool.get_pooled_connection()?;
+    write_pool.create_pool()?;
+    {
+        let mut connection = write_pool.get_pooled_connection()?;
+        migrations::migrate(&mut connection, db_path.as_ref(), MigrationOptions::default())?;
+    }
 
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./migrations");
-    connection
-        .run_pending_migrations(MIGRATIONS)
-        .map_err(|err| WalletStorageError::DatabaseMigrationError(format!("Database migration failed {}", err)))?;
+    // The writer has switched the database to WAL mode, so readers can proceed concurrently with it
+    let mut read_pool = SqliteConnectionPool::new(
+        String::from(path_str),
+        sqlite_pool_size.max(1),
+        true,
+        true,
+        Duration::from_secs(60),
+    )
+    .with_read_only();
+    read_pool.create_pool()?;
+
+    Ok(WalletDbConnection::new_with_read_pool(
+        write_pool,
+        read_pool,
+        Some(file_lock),
+    ))
+}
 
-    Ok(WalletDbConnection::new(pool, Some(file_lock)))
+/// Validates the pending migrations of the wallet database at `db_path` against a scratch copy of it, leaving the
+/// database itself untouched
+pub fn dry_run_migrations<P: AsRef<Path>>(db_path: P) -> Result<MigrationReport, WalletStorageError> {
+    let _file_lock = acquire_exclusive_file_lock(db_path.as_ref())?;
+    let path_str = db_path
+        .as_ref()
+        .to_str()
+        .ok_or(WalletStorageError::InvalidUnicodePath)?;
+    let mut connection = SqliteConnection::establish(path_str)?;
+    migrations::migrate(&mut connection, db_path.as_ref(), MigrationOptions {
+        dry_run: true,
+        backup: false,
+    })
 }
 
 pub fn acquire_exclusive_file_lock(db_path: &Path) -> Result<File, WalletStorageError> {
@@ -120,6 +162,30 @@ pub fn initialize_sqlite_database_backends<P: AsRef<Path>>(
         KeyManagerSqliteDatabase<WalletDbConnection>,
     ),
     WalletStorageError,
+> {
+    initialize_sqlite_database_backends_with_key_provider(
+        db_path,
+        &PassphraseKeyProvider::new(passphrase),
+        sqlite_pool_size,
+    )
+}
+
+/// As [initialize_sqlite_database_backends], but the database encryption key is unlocked with the secret supplied by
+/// `key_provider`, e.g. one backed by a platform keystore.
+#[allow(clippy::type_complexity)]
+pub fn initialize_sqlite_database_backends_with_key_provider<P: AsRef<Path>>(
+    db_path: P,
+    key_provider: &dyn DbKeyProvider,
+    sqlite_pool_size: usize,
+) -> Result<
+    (
+        WalletSqliteDatabase,
+        TransactionServiceSqliteDatabase,
+        OutputManagerSqliteDatabase,
+        ContactsServiceSqliteDatabase<WalletDbConnection>,
+        KeyManagerSqliteDatabase<WalletDbConnection>,
+    ),
+    WalletStorageError,
 > {
     let connection = run_migration_and_create_sqlite_connection(db_path, sqlite_pool_size).map_err(|e| {
         error!(
@@ -129,11 +195,13 @@ pub fn initialize_sqlite_database_backends<P: AsRef<Path>>(
         e
     })?;
 
-    let wallet_backend = WalletSqliteDatabase::new(connection.clone(), passphrase)?;
-    let transaction_backend = TransactionServiceSqliteDatabase::new(connection.clone(), wallet_backend.cipher());
+    let wallet_backend = WalletSqliteDatabase::new_with_key_provider(connection.clone(), key_provider)?;
+    let transaction_backend =
+        TransactionServiceSqliteDatabase::new_with_shared_cipher(connection.clone(), wallet_backend.shared_cipher());
     let output_manager_backend = OutputManagerSqliteDatabase::new(connection.clone());
     let contacts_backend = ContactsServiceSqliteDatabase::init(connection.clone());
-    let key_manager_backend = KeyManagerSqliteDatabase::init(connection, wallet_backend.cipher());
+    let key_manager_backend =
+        KeyManagerSqliteDatabase::init_with_shared_cipher(connection, wallet_backend.shared_cipher());
     Ok((
         wallet_backend,
         transaction_backend,
@@ -166,3 +234,46 @@ pub fn get_last_network<P: AsRef<Path>>(db_path: P) -> Result<Option<String>, Wa
 
     WalletSettingSql::get(&DbKey::LastAccessedNetwork, pool.get_pooled_connection()?.deref_mut())
 }
+
+/// Creates a fresh wallet database at `dest_db_path`, bound to `network`, that holds the same master seed (or
+/// watch-only keys) as the wallet at `source_db_path`. No outputs, transactions or chain state are copied; the new
+/// wallet recovers its funds for the new network by scanning. The source wallet must not be running and the destination
+/// must not exist yet.
+pub fn clone_wallet_for_network<P: AsRef<Path>>(
+    source_db_path: P,
+    dest_db_path: P,
+    passphrase: SafePassword,
+    network: &str,
+) -> Result<(), WalletStorageError> {
+    if dest_db_path.as_ref().exists() {
+        return Err(WalletStorageError::FileError(format!(
+            "Destination wallet database {} already exists",
+            dest_db_path.as_ref().display()
+        )));
+    }
+
+    let (source_backend, _, _, _, _) = initialize_sqlite_database_backends(source_db_path, passphrase.clone(), 1)?;
+    let source = WalletDatabase::new(source_backend);
+    let master_seed = source.get_master_seed()?;
+    let passphrase_seed = source.get_passphrase_seed()?;
+    let watch_only_keys = source.get_watch_only_keys()?;
+    if master_seed.is_none() && watch_only_keys.is_none() {
+        return Err(WalletStorageError::ValueNotFound(DbKey::MasterSeed));
+    }
+
+    let (dest_backend, _, _, _, _) = initialize_sqlite_database_backends(dest_db_path, passphrase, 1)?;
+    let dest = WalletDatabase::new(dest_backend);
+    if let Some(seed) = master_seed {
+        dest.set_master_seed(seed)?;
+    }
+    if let Some(seed) = passphrase_seed {
+        dest.set_passphrase_seed(seed)?;
+    }
+    if let Some(keys) = watch_only_keys {
+        dest.set_watch_only_keys(keys)?;
+    }
+    dest.rebind_network(network)?;
+    info!(target: LOG_TARGET, "Cloned wallet keys into a new database for network {}", network);
+
+    Ok(())
+}
@@ -31,9 +31,12 @@ use tari_common_sqlite::{
     sqlite_connection_pool::{PooledDbConnection, SqliteConnectionPool},
 };
 
+/// The wallet database connection. Writes go through `pool`; if a `read_pool` of read-only connections is configured,
+/// reads are served from it so that long reads in WAL mode do not block writers.
 #[derive(Clone)]
 pub struct WalletDbConnection {
     pool: SqliteConnectionPool,
+    read_pool: Option<SqliteConnectionPool>,
     _file_lock: Arc<Option<File>>,
 }
 
@@ -41,6 +44,19 @@ impl WalletDbConnection {
     pub fn new(pool: SqliteConnectionPool, file_lock: Option<File>) -> Self {
         Self {
             pool,
+            read_pool: None,
+            _file_lock: Arc::new(file_lock),
+        }
+    }
+
+    pub fn new_with_read_pool(
+        write_pool: SqliteConnectionPool,
+        read_pool: SqliteConnectionPool,
+        file_lock: Option<File>,
+    ) -> Self {
+        Self {
+            pool: write_pool,
+            read_pool: Some(read_pool),
             _file_lock: Arc::new(file_lock),
         }
     }
@@ -53,4 +69,61 @@ impl PooledDbConnection for WalletDbConnection {
         let conn = self.pool.get_pooled_connection()?;
         Ok(conn)
     }
+
+    fn get_read_connection(&self) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, Self::Error> {
+        match self.read_pool {
+            Some(ref read_pool) => read_pool.get_pooled_connection(),
+            None => self.pool.get_pooled_connection(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use diesel::connection::SimpleConnection;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::storage::sqlite_utilities::run_migration_and_create_sqlite_connection;
+
+    #[test]
+    fn it_falls_back_to_the_writer_pool_without_a_read_pool() {
+        let dir = tempdir().unwrap();
+        let db_path = dir.path().join("wallet.sqlite3");
+        let mut pool = SqliteConnectionPool::new(
+            db_path.to_str().unwrap().to_string(),
+            1,
+            true,
+            true,
+            Duration::from_secs(60),
+        );
+        pool.create_pool().unwrap();
+        let connection = WalletDbConnection::new(pool, None);
+
+        let mut conn = connection.get_read_connection().unwrap();
+        conn.batch_execute("CREATE TABLE test (value INTEGER); INSERT INTO test VALUES (1);")
+            .unwrap();
+    }
+
+    #[test]
+    fn it_serves_reads_from_the_read_only_pool() {
+        let dir = tempdir().unwrap();
+        let connection = run_migration_and_create_sqlite_connection(dir.path().join("wallet.sqlite3"), 2).unwrap();
+
+        let mut writer = connection.get_pooled_connection().unwrap();
+        writer
+            .batch_execute("CREATE TABLE test (value INTEGER); INSERT INTO test VALUES (1);")
+            .unwrap();
+
+        let mut reader = connection.get_read_connection().unwrap();
+        reader.batch_execute("SELECT value FROM test;").unwrap();
+        assert!(reader.batch_execute("INSERT INTO test VALUES (2);").is_err());
+
+        // A read in progress does not stop the writer
+        reader.batch_execute("BEGIN; SELECT value FROM test;").unwrap();
+        writer.batch_execute("INSERT INTO test VALUES (3);").unwrap();
+        reader.batch_execute("COMMIT;").unwrap();
+    }
 }
@@ -0,0 +1,506 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Deterministic wallet test vectors.
+//!
+//! Given a fixed master seed, [generate_test_vectors] derives the wallet's addresses and transaction keys and plays a
+//! scripted sequence of coinbases and one-sided payments into a simple in-memory chain. The result is versioned so that
+//! downstream SDKs can validate their key derivation, address encoding and output construction against it.
+//!
+//! Key derivation, addresses, output values, scripts and commitments are fully determined by the seed and the script.
+//! Range proofs, signatures and encrypted data are randomised, so the serialized transactions are included for
+//! validation (e.g. proof verification and output recovery) rather than byte-for-byte comparison.
+
+use std::mem::size_of;
+
+use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
+use serde::Serialize;
+use tari_common::configuration::Network;
+use tari_common_sqlite::connection::{DbConnection, DbConnectionUrl};
+use tari_common_types::{tari_address::TariAddress, transaction::TxId, types::PublicKey};
+use tari_comms::peer_manager::{NodeIdentity, PeerFeatures};
+use tari_core::{
+    consensus::{ConsensusConstants, ConsensusManager},
+    covenants::Covenant,
+    transactions::{
+        key_manager::{
+            TariKeyId,
+            TransactionKeyManagerBranch,
+            TransactionKeyManagerInterface,
+            TransactionKeyManagerWrapper,
+        },
+        tari_amount::MicroMinotari,
+        transaction_components::{KernelFeatures, Transaction, TransactionError, WalletOutput},
+        CoinbaseBuildError,
+        CoinbaseBuilder,
+        CryptoFactories,
+        SenderTransactionProtocol,
+    },
+};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    key_manager::KeyManager,
+    key_manager_service::{
+        storage::{database::KeyManagerDatabase, sqlite_db::KeyManagerSqliteDatabase},
+        KeyDigest,
+        KeyManagerInterface,
+        KeyManagerServiceError,
+    },
+};
+use tari_script::{script, ExecutionStack};
+use tari_utilities::hex::Hex;
+use thiserror::Error;
+
+use crate::{
+    config::KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY,
+    test_utils::random_string,
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::BatchPayment,
+        protocols::transaction_batch_send_protocol::build_one_sided_recipient_output,
+    },
+    util::wallet_identity::WalletIdentity,
+};
+
+/// The version of the test vector format. This must be incremented whenever the generated vectors change for the same
+/// seed and script.
+pub const TEST_VECTORS_VERSION: u32 = 1;
+
+type TestVectorKeyManager = TransactionKeyManagerWrapper<KeyManagerSqliteDatabase<DbConnection>>;
+
+#[derive(Debug, Error)]
+pub enum TestVectorError {
+    #[error("Key manager service error: `{0}`")]
+    KeyManagerServiceError(#[from] KeyManagerServiceError),
+    #[error("Transaction error: `{0}`")]
+    TransactionError(#[from] TransactionError),
+    #[error("Coinbase build error: `{0}`")]
+    CoinbaseBuildError(#[from] CoinbaseBuildError),
+    #[error("Transaction service error: `{0}`")]
+    TransactionServiceError(#[from] TransactionServiceError),
+    #[error("Could not build transaction: `{0}`")]
+    BuildError(String),
+    #[error("Could not set up the in-memory key manager: `{0}`")]
+    StorageError(String),
+    #[error("Invalid script step {step}: {reason}")]
+    InvalidStep { step: usize, reason: String },
+}
+
+/// A single step of the script played into the in-memory chain. Each step produces one block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestVectorStep {
+    /// Mine a block with a coinbase paid to the wallet
+    MineCoinbase,
+    /// Mine a block containing a one-sided payment of `amount` to the wallet address at `address_index`. All of the
+    /// wallet's unspent outputs are spent, with the remainder returned as change.
+    SendOneSided {
+        address_index: usize,
+        amount: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+    },
+}
+
+/// Parameters for [generate_test_vectors]
+#[derive(Debug, Clone)]
+pub struct TestVectorConfig {
+    pub network: Network,
+    /// The number of addresses to derive from the seed
+    pub num_addresses: usize,
+    /// The number of keys to derive on each key manager branch
+    pub num_keys_per_branch: u64,
+    pub script: Vec<TestVectorStep>,
+}
+
+impl Default for TestVectorConfig {
+    fn default() -> Self {
+        Self {
+            network: Network::LocalNet,
+            num_addresses: 3,
+            num_keys_per_branch: 3,
+            script: vec![
+                TestVectorStep::MineCoinbase,
+                TestVectorStep::MineCoinbase,
+                TestVectorStep::SendOneSided {
+                    address_index: 1,
+                    amount: MicroMinotari::from(1_000_000),
+                    fee_per_gram: MicroMinotari::from(5),
+                },
+                TestVectorStep::SendOneSided {
+                    address_index: 2,
+                    amount: MicroMinotari::from(250_000),
+                    fee_per_gram: MicroMinotari::from(25),
+                },
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TestVectors {
+    pub version: u32,
+    pub network: Network,
+    pub addresses: Vec<AddressVector>,
+    pub keys: Vec<KeyVector>,
+    pub blocks: Vec<BlockVector>,
+}
+
+impl TestVectors {
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(self)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AddressVector {
+    pub index: u64,
+    pub public_key: String,
+    pub hex: String,
+    pub emoji: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyVector {
+    pub branch: String,
+    pub index: u64,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockVector {
+    pub height: u64,
+    pub transactions: Vec<TransactionVector>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputKind {
+    Coinbase,
+    OneSided,
+    Change,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputVector {
+    pub kind: OutputKind,
+    pub value: MicroMinotari,
+    pub commitment: String,
+    pub script: String,
+    /// The index of the address that the output was paid to, for one-sided outputs
+    pub address_index: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactionVector {
+    pub tx_id: TxId,
+    pub fee: MicroMinotari,
+    pub outputs: Vec<OutputVector>,
+    pub transaction: Transaction,
+}
+
+/// Generates the test vectors for `seed` by deriving the configured keys and addresses and playing `config.script`
+/// into an in-memory chain starting at height 1.
+pub async fn generate_test_vectors(
+    seed: CipherSeed,
+    config: &TestVectorConfig,
+) -> Result<TestVectors, TestVectorError> {
+    let key_manager = create_key_manager(seed.clone())?;
+    let consensus_manager = ConsensusManager::builder(config.network)
+        .build()
+        .map_err(|e| TestVectorError::BuildError(e.to_string()))?;
+
+    let comms_key_manager =
+        KeyManager::<PublicKey, KeyDigest>::from(seed, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY.to_string(), 0);
+    let mut identities = Vec::with_capacity(config.num_addresses);
+    let mut addresses = Vec::with_capacity(config.num_addresses);
+    for index in 0..config.num_addresses as u64 {
+        let secret_key = comms_key_manager
+            .derive_key(index)
+            .map_err(KeyManagerServiceError::from)?
+            .key;
+        let node_identity = NodeIdentity::new(secret_key, vec![], PeerFeatures::COMMUNICATION_CLIENT);
+        let identity = WalletIdentity::new(node_identity.into(), config.network);
+        addresses.push(AddressVector {
+            index,
+            public_key: identity.address.public_key().to_hex(),
+            hex: identity.address.to_hex(),
+            emoji: identity.address.to_emoji_string(),
+        });
+        identities.push(identity);
+    }
+    let wallet_identity = identities.first().ok_or_else(|| TestVectorError::InvalidStep {
+        step: 0,
+        reason: "At least one address is required".to_string(),
+    })?;
+
+    let mut keys = Vec::new();
+    for branch in [
+        TransactionKeyManagerBranch::Coinbase,
+        TransactionKeyManagerBranch::CoinbaseScript,
+        TransactionKeyManagerBranch::CommitmentMask,
+        TransactionKeyManagerBranch::ScriptKey,
+        TransactionKeyManagerBranch::SenderOffset,
+    ] {
+        for index in 0..config.num_keys_per_branch {
+            let key_id = TariKeyId::Managed {
+                branch: branch.get_branch_key(),
+                index,
+            };
+            keys.push(KeyVector {
+                branch: branch.get_branch_key(),
+                index,
+                public_key: key_manager.get_public_key_at_key_id(&key_id).await?.to_hex(),
+            });
+        }
+    }
+
+    let mut unspent: Vec<WalletOutput> = Vec::new();
+    let mut blocks = Vec::with_capacity(config.script.len());
+    for (step_index, step) in config.script.iter().enumerate() {
+        let height = step_index as u64 + 1;
+        let tx_id = TxId::from(height);
+        let constants = consensus_manager.consensus_constants(height);
+        let transaction = match step {
+            TestVectorStep::MineCoinbase => {
+                let (spend_key_id, _) = key_manager
+                    .get_next_key(TransactionKeyManagerBranch::Coinbase.get_branch_key())
+                    .await?;
+                let (script_key_id, _) = key_manager
+                    .get_next_key(TransactionKeyManagerBranch::CoinbaseScript.get_branch_key())
+                    .await?;
+                let (tx, output) = CoinbaseBuilder::new(key_manager.clone())
+                    .with_block_height(height)
+                    .with_fees(MicroMinotari::zero())
+                    .with_spend_key_id(spend_key_id)
+                    .with_script_key_id(script_key_id)
+                    .with_script(script!(Nop))
+                    .build_with_reward(constants, consensus_manager.get_block_reward_at(height))
+                    .await?;
+                let outputs = vec![output_vector(&key_manager, &output, OutputKind::Coinbase, None).await?];
+                unspent.push(output);
+                TransactionVector {
+                    tx_id,
+                    fee: MicroMinotari::zero(),
+                    outputs,
+                    transaction: tx,
+                }
+            },
+            TestVectorStep::SendOneSided {
+                address_index,
+                amount,
+                fee_per_gram,
+            } => {
+                let destination = identities
+                    .get(*address_index)
+                    .ok_or_else(|| TestVectorError::InvalidStep {
+                        step: step_index,
+                        reason: format!("Address index {} has not been derived", address_index),
+                    })?
+                    .address
+                    .clone();
+                if unspent.is_empty() {
+                    return Err(TestVectorError::InvalidStep {
+                        step: step_index,
+                        reason: "There are no outputs to spend".to_string(),
+                    });
+                }
+                send_one_sided(
+                    &key_manager,
+                    wallet_identity,
+                    constants.clone(),
+                    tx_id,
+                    &mut unspent,
+                    destination,
+                    *address_index,
+                    *amount,
+                    *fee_per_gram,
+                )
+                .await?
+            },
+        };
+        blocks.push(BlockVector {
+            height,
+            transactions: vec![transaction],
+        });
+    }
+
+    Ok(TestVectors {
+        version: TEST_VECTORS_VERSION,
+        network: config.network,
+        addresses,
+        keys,
+        blocks,
+    })
+}
+
+async fn send_one_sided(
+    key_manager: &TestVectorKeyManager,
+    wallet_identity: &WalletIdentity,
+    constants: ConsensusConstants,
+    tx_id: TxId,
+    unspent: &mut Vec<WalletOutput>,
+    destination: TariAddress,
+    address_index: usize,
+    amount: MicroMinotari,
+    fee_per_gram: MicroMinotari,
+) -> Result<TransactionVector, TestVectorError> {
+    let payment = BatchPayment {
+        destination,
+        amount,
+        message: String::new(),
+    };
+    let (recipient_output, sender_offset_key_id) =
+        build_one_sided_recipient_output(key_manager, wallet_identity, &payment).await?;
+    let mut outputs = vec![
+        output_vector(
+            key_manager,
+            &recipient_output,
+            OutputKind::OneSided,
+            Some(address_index),
+        )
+        .await?,
+    ];
+
+    let mut builder = SenderTransactionProtocol::builder(constants, key_manager.clone());
+    builder
+        .with_lock_height(0)
+        .with_fee_per_gram(fee_per_gram)
+        .with_kernel_features(KernelFeatures::empty())
+        .with_tx_id(tx_id);
+    for input in unspent.drain(..) {
+        builder.with_input(input).await?;
+    }
+    builder.with_output(recipient_output, sender_offset_key_id).await?;
+
+    let (change_spending_key_id, _, change_script_key_id, change_script_public_key) =
+        key_manager.get_next_spend_and_script_key_ids().await?;
+    builder.with_change_data(
+        script!(PushPubKey(Box::new(change_script_public_key))),
+        ExecutionStack::default(),
+        change_script_key_id,
+        change_spending_key_id,
+        Covenant::default(),
+    );
+
+    let mut stp = builder
+        .build()
+        .await
+        .map_err(|e| TestVectorError::BuildError(e.message))?;
+    let fee = stp
+        .get_fee_amount()
+        .map_err(|e| TestVectorError::BuildError(e.to_string()))?;
+    if let Some(change) = stp
+        .get_change_output()
+        .map_err(|e| TestVectorError::BuildError(e.to_string()))?
+    {
+        outputs.push(output_vector(key_manager, &change, OutputKind::Change, None).await?);
+        unspent.push(change);
+    }
+    stp.finalize(key_manager)
+        .await
+        .map_err(|e| TestVectorError::BuildError(e.to_string()))?;
+    let transaction = stp
+        .into_transaction()
+        .map_err(|e| TestVectorError::BuildError(e.to_string()))?;
+
+    Ok(TransactionVector {
+        tx_id,
+        fee,
+        outputs,
+        transaction,
+    })
+}
+
+async fn output_vector(
+    key_manager: &TestVectorKeyManager,
+    output: &WalletOutput,
+    kind: OutputKind,
+    address_index: Option<usize>,
+) -> Result<OutputVector, TestVectorError> {
+    let commitment = output.commitment(key_manager).await?;
+    Ok(OutputVector {
+        kind,
+        value: output.value,
+        commitment: commitment.to_hex(),
+        script: output.script.to_hex(),
+        address_index,
+    })
+}
+
+/// Creates a transaction key manager for `seed` backed by a private in-memory database
+fn create_key_manager(seed: CipherSeed) -> Result<TestVectorKeyManager, TestVectorError> {
+    let connection = DbConnection::connect_url(&DbConnectionUrl::MemoryShared(random_string(8)))
+        .map_err(|e| TestVectorError::StorageError(e.to_string()))?;
+    // The database only lives for the duration of the generation, so the cipher key does not need to be secret
+    let key = [0u8; size_of::<Key>()];
+    let db_cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let key_manager = TransactionKeyManagerWrapper::new(
+        seed,
+        KeyManagerDatabase::new(KeyManagerSqliteDatabase::init(connection, db_cipher)),
+        CryptoFactories::default(),
+    )?;
+    Ok(key_manager)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_generates_the_same_deterministic_vectors_for_a_seed() {
+        let seed = CipherSeed::new();
+        let config = TestVectorConfig::default();
+        let a = generate_test_vectors(seed.clone(), &config).await.unwrap();
+        let b = generate_test_vectors(seed, &config).await.unwrap();
+
+        assert_eq!(a.version, TEST_VECTORS_VERSION);
+        assert_eq!(a.addresses.len(), config.num_addresses);
+        assert_eq!(a.blocks.len(), config.script.len());
+        assert_eq!(
+            serde_json::to_value(&a.addresses).unwrap(),
+            serde_json::to_value(&b.addresses).unwrap()
+        );
+        assert_eq!(
+            serde_json::to_value(&a.keys).unwrap(),
+            serde_json::to_value(&b.keys).unwrap()
+        );
+        for (block_a, block_b) in a.blocks.iter().zip(b.blocks.iter()) {
+            let outputs_a = serde_json::to_value(&block_a.transactions[0].outputs).unwrap();
+            let outputs_b = serde_json::to_value(&block_b.transactions[0].outputs).unwrap();
+            assert_eq!(outputs_a, outputs_b);
+        }
+        a.to_json().unwrap();
+    }
+
+    #[tokio::test]
+    async fn it_rejects_unknown_addresses() {
+        let config = TestVectorConfig {
+            script: vec![TestVectorStep::MineCoinbase, TestVectorStep::SendOneSided {
+                address_index: 10,
+                amount: MicroMinotari::from(1_000),
+                fee_per_gram: MicroMinotari::from(5),
+            }],
+            ..Default::default()
+        };
+        let err = generate_test_vectors(CipherSeed::new(), &config).await.unwrap_err();
+        assert!(matches!(err, TestVectorError::InvalidStep { step: 1, .. }));
+    }
+}
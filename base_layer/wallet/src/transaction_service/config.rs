@@ -58,7 +58,8 @@ pub struct TransactionServiceConfig {
     /// This is the number of block confirmations required for a transaction to be considered completely mined and
     /// confirmed
     pub num_confirmations_required: u64,
-    /// The number of batches the unconfirmed transactions will be divided into before being queried from the base node
+    /// The maximum number of unconfirmed transactions that will be queried from the base node in a single request.
+    /// This is capped at the base node's limit of 1000.
     pub max_tx_query_batch_size: usize,
     /// This option specifies the transaction routing mechanism as being directly between wallets, making use of store
     /// and forward or using any combination of these.
@@ -69,6 +70,42 @@ pub struct TransactionServiceConfig {
     /// This is the timeout period that will be used to re-submit transactions not found in the mempool
     #[serde(with = "serializers::seconds")]
     pub transaction_mempool_resubmission_window: Duration,
+    /// The maximum number of delivery attempts for a queued transaction protocol message before it is marked as failed
+    /// and no longer resent. Attempts are persisted, so this also bounds the protocols' periodic resends across
+    /// restarts.
+    pub max_outbound_message_attempts: u32,
+    /// How often scheduled transactions are checked to see whether they are due or have expired. They are also
+    /// checked whenever a new block is detected.
+    #[serde(with = "serializers::seconds")]
+    pub scheduled_transaction_check_interval: Duration,
+    /// Accept sends made while the wallet has no base node connectivity into a queue and send them once connectivity
+    /// returns, rather than attempting them while offline
+    pub queue_offline_transactions: bool,
+    /// How long a transaction queued while offline waits for connectivity to return before it is expired
+    #[serde(with = "serializers::seconds")]
+    pub max_queued_transaction_age: Duration,
+    /// How often queued transactions are checked for expiry while the wallet is offline. They are also checked
+    /// whenever the connectivity status changes.
+    #[serde(with = "serializers::seconds")]
+    pub queued_transaction_check_interval: Duration,
+    /// How often recurring payment plans are checked to see whether a payment is due
+    #[serde(with = "serializers::seconds")]
+    pub recurring_payment_check_interval: Duration,
+    /// The number of recent blocks whose mempool fee statistics are used to estimate fees per gram
+    pub fee_estimation_sample_blocks: usize,
+    /// How often the mempool of the connected base node is queried for the state of broadcast transactions
+    #[serde(with = "serializers::seconds")]
+    pub mempool_state_refresh_interval: Duration,
+    /// The maximum number of inbound transaction protocols that run at the same time. Further inbound transactions
+    /// are queued until a running protocol completes.
+    pub max_concurrent_receive_protocols: usize,
+    /// The maximum number of inbound transaction protocols waiting to run. Inbound transactions received while the
+    /// queue is full are dropped, to be picked up again when the sender resends them. Protocols restarted for pending
+    /// inbound transactions are always queued.
+    pub receive_protocol_queue_size: usize,
+    /// The number of most recent transaction events kept in the event journal, from which consumers that missed events
+    /// while not subscribed can catch up
+    pub max_journaled_events: usize,
 }
 
 impl Default for TransactionServiceConfig {
@@ -83,10 +120,21 @@ impl Default for TransactionServiceConfig {
             resend_response_cooldown: Duration::from_secs(300),
             pending_transaction_cancellation_timeout: Duration::from_secs(259_200), // 3 Days
             num_confirmations_required: 3,
-            max_tx_query_batch_size: 20,
+            max_tx_query_batch_size: 500,
             transaction_routing_mechanism: TransactionRoutingMechanism::default(),
             transaction_event_channel_size: 1000,
             transaction_mempool_resubmission_window: Duration::from_secs(600),
+            max_outbound_message_attempts: 10,
+            scheduled_transaction_check_interval: Duration::from_secs(60),
+            queue_offline_transactions: true,
+            max_queued_transaction_age: Duration::from_secs(86_400), // 1 Day
+            queued_transaction_check_interval: Duration::from_secs(60),
+            recurring_payment_check_interval: Duration::from_secs(60),
+            fee_estimation_sample_blocks: 10,
+            mempool_state_refresh_interval: Duration::from_secs(60),
+            max_concurrent_receive_protocols: 100,
+            receive_protocol_queue_size: 10_000,
+            max_journaled_events: 10_000,
         }
     }
 }
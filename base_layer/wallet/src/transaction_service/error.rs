@@ -32,8 +32,9 @@ use tari_common_types::{
 use tari_comms::{connectivity::ConnectivityError, peer_manager::node_id::NodeIdError, protocol::rpc::RpcError};
 use tari_comms_dht::outbound::DhtOutboundError;
 use tari_core::transactions::{
+    frost::FrostError,
     transaction_components::{EncryptedDataError, TransactionError},
-    transaction_protocol::TransactionProtocolError,
+    transaction_protocol::{EncryptedMemoError, TransactionProtocolError},
 };
 use tari_crypto::{errors::RangeProofError, signatures::CommitmentSignatureError};
 use tari_key_manager::key_manager_service::KeyManagerServiceError;
@@ -44,12 +45,15 @@ use thiserror::Error;
 use tokio::sync::broadcast::error::RecvError;
 
 use crate::{
-    error::WalletStorageError,
+    error::{ErrorCategory, ErrorHint, RetryHint, WalletStorageError},
     output_manager_service::error::OutputManagerError,
+    payment_request::PaymentRequestError,
     transaction_service::{
-        storage::{database::DbKey, sqlite_db::CompletedTransactionConversionError},
+        offline_signing::OfflineSigningError,
+        storage::{database::DbKey, models::BurnStatus, sqlite_db::CompletedTransactionConversionError},
         utc::NegativeDurationError,
     },
+    util::reauthentication::ReauthenticationError,
 };
 
 #[derive(Debug, Error)]
@@ -60,8 +64,60 @@ pub enum TransactionServiceError {
     InvalidNetwork,
     #[error("One-sided transaction error: `{0}`")]
     OneSidedTransactionError(String),
+    #[error("Batch transaction error: `{0}`")]
+    BatchTransactionError(String),
+    #[error("Scheduled transaction error: `{0}`")]
+    ScheduledTransactionError(String),
+    #[error("Queued transaction error: `{0}`")]
+    QueuedTransactionError(String),
+    #[error("Recurring payment error: `{0}`")]
+    RecurringPaymentError(String),
+    #[error("Send template error: `{0}`")]
+    SendTemplateError(String),
+    #[error("Fee bump error: `{0}`")]
+    FeeBumpError(String),
+    #[error("Child-pays-for-parent error: `{0}`")]
+    ChildPaysForParentError(String),
+    #[error("The receive protocol queue is full, inbound transaction {0} was dropped")]
+    ReceiveProtocolQueueFull(TxId),
+    #[error("Invalid transaction tag: `{0}`")]
+    InvalidTransactionTag(String),
+    #[error("Transaction history export error: `{0}`")]
+    HistoryExportError(String),
+    #[error("Transaction history import error: `{0}`")]
+    HistoryImportError(String),
+    #[error("Atomic swap error: `{0}`")]
+    AtomicSwapError(String),
+    #[error("Payment request error: `{0}`")]
+    PaymentRequestError(#[from] PaymentRequestError),
+    #[error("Re-authentication error: `{0}`")]
+    ReauthenticationError(#[from] ReauthenticationError),
+    #[error("Payment request has expired")]
+    PaymentRequestExpired,
+    #[error("Payment request does not specify an amount")]
+    PaymentRequestMissingAmount,
+    #[error("Atomic swap `{0}` not found")]
+    AtomicSwapNotFound(u64),
+    #[error("Multisig error: `{0}`")]
+    MultisigError(String),
+    #[error("Multisig session `{0}` not found")]
+    MultisigSessionNotFound(u64),
+    #[error("Multisig signing `{0}` not found")]
+    MultisigSigningNotFound(u64),
     #[error("Transaction Protocol Error: `{0}`")]
     TransactionProtocolError(#[from] TransactionProtocolError),
+    #[error("Encrypted memo error: `{0}`")]
+    EncryptedMemoError(#[from] EncryptedMemoError),
+    #[error("Offline signing error: `{0}`")]
+    OfflineSigningError(#[from] OfflineSigningError),
+    #[error("No transaction awaiting an offline signature with TxId `{0}`")]
+    OfflineTransactionNotFound(TxId),
+    #[error("Offline transaction error: `{0}`")]
+    OfflineTransactionError(String),
+    #[error("No burn made by this wallet with TxId `{0}`")]
+    BurnNotFound(TxId),
+    #[error("Burn `{tx_id}` cannot be claimed while it is `{status}`")]
+    BurnNotClaimable { tx_id: TxId, status: BurnStatus },
     #[error("The message being processed is not recognized by the Transaction Manager")]
     InvalidMessageTypeError,
     #[error("A message for a specific tx_id has been repeated")]
@@ -94,6 +150,8 @@ pub enum TransactionServiceError {
     OutboundSendDiscoveryInProgress(TxId),
     #[error("Discovery process failed to return a result: TxId `{0}`")]
     DiscoveryProcessFailed(TxId),
+    #[error("The transaction could not be delivered directly to the recipient and was cancelled: TxId `{0}`")]
+    DirectSendFailed(TxId),
     #[error("Invalid Completed Transaction provided")]
     InvalidCompletedTransaction,
     #[error("Attempted to broadcast a coinbase transaction. TxId `{0}`")]
@@ -168,6 +226,8 @@ pub enum TransactionServiceError {
     ServiceError(String),
     #[error("Wallet Recovery in progress so Transaction Service Messaging Requests ignored")]
     WalletRecoveryInProgress,
+    #[error("This is a watch-only wallet and cannot send or sign transactions")]
+    WatchOnlyWallet,
     #[error("Wallet Transaction Validation already in progress, request ignored")]
     TransactionValidationInProgress,
     #[error("Connectivity error: {source}")]
@@ -191,6 +251,65 @@ pub enum TransactionServiceError {
     KeyManagerServiceError(#[from] KeyManagerServiceError),
 }
 
+impl ErrorHint for TransactionServiceError {
+    fn category(&self) -> ErrorCategory {
+        #[allow(clippy::enum_glob_use)]
+        use TransactionServiceError::*;
+        match self {
+            InvalidNetwork |
+            InvalidTransactionTag(_) |
+            PaymentRequestError(_) |
+            PaymentRequestExpired |
+            PaymentRequestMissingAmount |
+            BurnNotClaimable { .. } |
+            AttemptedToBroadcastCoinbaseTransaction(_) |
+            InvalidCompletedTransaction |
+            InvalidTransaction |
+            MempoolRejectionDoubleSpend |
+            MempoolRejectionInvalidTransaction |
+            WatchOnlyWallet => ErrorCategory::InvalidRequest,
+            TransactionDoesNotExistError |
+            AtomicSwapNotFound(_) |
+            MultisigSessionNotFound(_) |
+            MultisigSigningNotFound(_) |
+            OfflineTransactionNotFound(_) |
+            BurnNotFound(_) => ErrorCategory::NotFound,
+            OutboundSendFailure |
+            OutboundSendDiscoveryInProgress(_) |
+            DiscoveryProcessFailed(_) |
+            DirectSendFailed(_) |
+            NoBaseNodeKeysProvided |
+            BaseNodeChanged { .. } |
+            DhtOutboundError(_) |
+            RpcError(_) |
+            ConnectivityError { .. } |
+            LivenessError(_) |
+            Timeout |
+            MaximumAttemptsExceeded => ErrorCategory::Connectivity,
+            BaseNodeNotSynced |
+            WalletRecoveryInProgress |
+            TransactionValidationInProgress |
+            ReceiveProtocolQueueFull(_) |
+            MempoolRejectionTimeLocked |
+            MempoolRejectionOrphan => ErrorCategory::NotReady,
+            OutputManagerError(e) => e.category(),
+            TransactionStorageError(e) => e.category(),
+            WalletStorageError(_) => ErrorCategory::Storage,
+            Shutdown | ApiSendFailed | ApiReceiveFailed | TransportChannelError(_) | OneshotCancelled(_) => {
+                ErrorCategory::Shutdown
+            },
+            _ => ErrorCategory::Internal,
+        }
+    }
+
+    fn retry_hint(&self) -> RetryHint {
+        match self {
+            TransactionServiceError::OutputManagerError(e) => e.retry_hint(),
+            e => e.category().retry_hint(),
+        }
+    }
+}
+
 impl From<RangeProofError> for TransactionServiceError {
     fn from(e: RangeProofError) -> Self {
         TransactionServiceError::RangeProofError(e.to_string())
@@ -209,6 +328,12 @@ impl From<ByteArrayError> for TransactionServiceError {
     }
 }
 
+impl From<FrostError> for TransactionServiceError {
+    fn from(err: FrostError) -> Self {
+        TransactionServiceError::MultisigError(err.to_string())
+    }
+}
+
 #[derive(Debug, Error)]
 pub enum TransactionKeyError {
     #[error("Invalid source address")]
@@ -277,6 +402,20 @@ pub enum TransactionStorageError {
     SqliteStorageError(#[from] SqliteStorageError),
 }
 
+impl ErrorHint for TransactionStorageError {
+    fn category(&self) -> ErrorCategory {
+        match self {
+            TransactionStorageError::ValueNotFound(_) |
+            TransactionStorageError::ValuesNotFound |
+            TransactionStorageError::TransactionNotMined(_) => ErrorCategory::NotFound,
+            TransactionStorageError::DuplicateOutput |
+            TransactionStorageError::TransactionAlreadyExists |
+            TransactionStorageError::NotCoinbase => ErrorCategory::InvalidRequest,
+            _ => ErrorCategory::Storage,
+        }
+    }
+}
+
 impl From<ByteArrayError> for TransactionStorageError {
     fn from(e: ByteArrayError) -> Self {
         TransactionStorageError::ByteArrayError(e.to_string())
@@ -22,17 +22,21 @@
 
 use std::{
     collections::HashMap,
+    convert::TryFrom,
     fmt,
     fmt::{Display, Formatter},
+    path::PathBuf,
     sync::Arc,
+    time::Duration,
 };
 
 use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
-    transaction::{ImportStatus, TxId},
-    types::{PublicKey, Signature},
+    transaction::{ImportStatus, TransactionConversionError, TxId},
+    types::{BlockHash, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
 use tari_core::{
@@ -49,24 +53,51 @@ use tari_core::{
             Transaction,
             TransactionOutput,
         },
+        transaction_protocol::{EncryptedMemoError, MAX_MEMO_LENGTH},
     },
 };
 use tari_service_framework::reply_channel::SenderService;
+use tari_utilities::{hex::Hex, SafePassword};
 use tokio::sync::broadcast;
 use tower::Service;
 
 use crate::{
     output_manager_service::UtxoSelectionCriteria,
+    payment_request::PaymentRequest,
     transaction_service::{
+        config::TransactionRoutingMechanism,
         error::TransactionServiceError,
+        offline_signing::{SignedTransaction, UnsignedTransaction},
         storage::models::{
+            AtomicSwap,
+            AtomicSwapState,
+            BatchedPayment,
+            BurnRecord,
+            BurnStatus,
             CompletedTransaction,
+            HeightOrTime,
             InboundTransaction,
+            JournaledTransactionEvent,
+            MultisigSession,
+            MultisigSessionState,
+            MultisigSigning,
+            MultisigSigningState,
+            OfflineTransaction,
+            OfflineTransactionStatus,
             OutboundTransaction,
+            QueuedTransaction,
+            RecurringPayment,
+            ScheduledTransaction,
+            SendTemplate,
             TxCancellationReason,
             WalletTransaction,
         },
+        tasks::{
+            export_history::{HistoryDateRange, HistoryExportFormat},
+            import_history::HistoricalTransaction,
+        },
     },
+    util::reauthentication::{ReauthenticationGuard, SensitiveOperation},
     OperationId,
 };
 
@@ -89,6 +120,8 @@ pub enum TransactionServiceRequest {
         output_features: Box<OutputFeatures>,
         fee_per_gram: MicroMinotari,
         message: String,
+        memo: Option<String>,
+        routing_mechanism: Option<TransactionRoutingMechanism>,
     },
     BurnTari {
         amount: MicroMinotari,
@@ -137,6 +170,7 @@ pub enum TransactionServiceRequest {
     ImportUtxoWithStatus {
         amount: MicroMinotari,
         source_address: TariAddress,
+        destination_address: Option<TariAddress>,
         message: String,
         maturity: Option<u64>,
         import_status: ImportStatus,
@@ -159,10 +193,197 @@ pub enum TransactionServiceRequest {
     SetNumConfirmationsRequired(u64),
     ValidateTransactions,
     ReValidateTransactions,
+    /// Validates at most the given number of unconfirmed transactions, continuing from the previous batch
+    ValidateTransactionBatch(usize),
+    /// Resends at most the given number of queued outbound transaction protocol messages
+    ResendQueuedOutboundMessages(usize),
     /// Returns the fee per gram estimates for the next {count} blocks.
     GetFeePerGramStatsPerBlock {
         count: usize,
     },
+    /// Returns the address book alias of the counterparty of each of the given transactions, where known.
+    GetCounterpartyAliases(Vec<TxId>),
+    SendBatchTransaction {
+        payments: Vec<BatchPayment>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    GetBatchedPayments(TxId),
+    /// Returns the decrypted payment memo attached to the given transaction, if any.
+    GetTransactionMemo(TxId),
+    SendMultiRecipientTransaction {
+        destination: TariAddress,
+        amount: MicroMinotari,
+        one_sided_payments: Vec<BatchPayment>,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    ScheduleTransaction {
+        payment: ScheduledPayment,
+        execute_at: HeightOrTime,
+        expires_at: Option<HeightOrTime>,
+    },
+    GetScheduledTransactions,
+    CancelScheduledTransaction(u64),
+    SendOrQueueTransaction(ScheduledPayment),
+    GetQueuedTransactions,
+    CancelQueuedTransaction(u64),
+    GetFeeEstimates,
+    GetMempoolStates,
+    BumpFee {
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    },
+    ChildPaysForParent {
+        parent_tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    },
+    AddTransactionTags {
+        tx_id: TxId,
+        tags: Vec<String>,
+    },
+    RemoveTransactionTags {
+        tx_id: TxId,
+        tags: Vec<String>,
+    },
+    GetTransactionTags(Vec<TxId>),
+    GetTransactionsByTag(String),
+    /// Free-text search over transaction tags and payment messages
+    SearchTransactions(String),
+    ExportHistory {
+        format: HistoryExportFormat,
+        date_range: HistoryDateRange,
+        path: PathBuf,
+    },
+    ImportHistory(Vec<HistoricalTransaction>),
+    InitiateAtomicSwap {
+        counterparty: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    ParticipateAtomicSwap {
+        swap_id: u64,
+        counterparty: TariAddress,
+        amount: MicroMinotari,
+        hash_lock: FixedHash,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    RedeemAtomicSwap {
+        swap_id: u64,
+        pre_image: Option<PublicKey>,
+        fee_per_gram: MicroMinotari,
+    },
+    RefundAtomicSwap {
+        swap_id: u64,
+        fee_per_gram: MicroMinotari,
+    },
+    GetAtomicSwaps,
+    CreateUnsignedTransaction {
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    SignUnsignedTransaction(Box<UnsignedTransaction>),
+    ImportSignedTransaction(Box<SignedTransaction>),
+    CancelOfflineTransaction(TxId),
+    GetOfflineTransactions(Option<OfflineTransactionStatus>),
+    BurnFunds {
+        amount: MicroMinotari,
+        claim_public_key: PublicKey,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    },
+    GetBurnProof(TxId),
+    GetBurnProofs(Option<BurnStatus>),
+    MarkBurnClaimed(TxId),
+    CreateMultisigSession {
+        threshold: u8,
+        cosigners: Vec<TariAddress>,
+        label: String,
+    },
+    GetMultisigSessions,
+    RequestMultisigSignature {
+        session_id: u64,
+        cosigners: Vec<TariAddress>,
+        message: FixedHash,
+        description: String,
+    },
+    ApproveMultisigSigning(u64),
+    CancelMultisigSigning(u64),
+    GetMultisigSignings(Option<u64>),
+    CreateRecurringPayment {
+        payment: ScheduledPayment,
+        interval: Duration,
+        start_at: Option<NaiveDateTime>,
+        end_at: Option<NaiveDateTime>,
+    },
+    GetRecurringPayments,
+    PauseRecurringPayment(u64),
+    ResumeRecurringPayment(u64),
+    CancelRecurringPayment(u64),
+    GetEventsSince(u64),
+    CreateSendTemplate {
+        name: String,
+        destination: TariAddress,
+        default_amount: Option<MicroMinotari>,
+        message: String,
+        fee_priority: FeePriority,
+        one_sided: bool,
+    },
+    UpdateSendTemplate {
+        id: u64,
+        name: String,
+        destination: TariAddress,
+        default_amount: Option<MicroMinotari>,
+        message: String,
+        fee_priority: FeePriority,
+        one_sided: bool,
+    },
+    GetSendTemplate(u64),
+    GetSendTemplates,
+    DeleteSendTemplate(u64),
+}
+
+impl TransactionServiceRequest {
+    /// Whether this request spends the wallet's funds or signs on its behalf. A watch-only wallet rejects these.
+    pub fn is_spend(&self) -> bool {
+        #[allow(clippy::enum_glob_use)]
+        use TransactionServiceRequest::*;
+        matches!(
+            self,
+            SendTransaction { .. } |
+                BurnTari { .. } |
+                RegisterValidatorNode { .. } |
+                RegisterCodeTemplate { .. } |
+                SendOneSidedTransaction { .. } |
+                SendOneSidedToStealthAddressTransaction { .. } |
+                SendShaAtomicSwapTransaction(..) |
+                GenerateCoinbaseTransaction { .. } |
+                SendBatchTransaction { .. } |
+                SendMultiRecipientTransaction { .. } |
+                ScheduleTransaction { .. } |
+                SendOrQueueTransaction(_) |
+                BumpFee { .. } |
+                ChildPaysForParent { .. } |
+                InitiateAtomicSwap { .. } |
+                ParticipateAtomicSwap { .. } |
+                RedeemAtomicSwap { .. } |
+                RefundAtomicSwap { .. } |
+                SignUnsignedTransaction(_) |
+                BurnFunds { .. } |
+                RequestMultisigSignature { .. } |
+                ApproveMultisigSigning(_) |
+                CreateRecurringPayment { .. }
+        )
+    }
 }
 
 impl fmt::Display for TransactionServiceRequest {
@@ -214,6 +435,7 @@ impl fmt::Display for TransactionServiceRequest {
             Self::ImportUtxoWithStatus {
                 amount,
                 source_address,
+                destination_address: _,
                 message,
                 maturity,
                 import_status,
@@ -245,12 +467,149 @@ impl fmt::Display for TransactionServiceRequest {
             Self::GetAnyTransaction(t) => write!(f, "GetAnyTransaction({})", t),
             Self::ValidateTransactions => write!(f, "ValidateTransactions"),
             Self::ReValidateTransactions => write!(f, "ReValidateTransactions"),
+            Self::ValidateTransactionBatch(max_transactions) => {
+                write!(f, "ValidateTransactionBatch({})", max_transactions)
+            },
+            Self::ResendQueuedOutboundMessages(max_messages) => {
+                write!(f, "ResendQueuedOutboundMessages({})", max_messages)
+            },
             Self::GetFeePerGramStatsPerBlock { count } => {
                 write!(f, "GetFeePerGramEstimatesPerBlock(count: {})", count,)
             },
             TransactionServiceRequest::RegisterCodeTemplate { template_name, .. } => {
                 write!(f, "RegisterCodeTemplate: {}", template_name)
             },
+            Self::GetCounterpartyAliases(tx_ids) => write!(f, "GetCounterpartyAliases({} txs)", tx_ids.len()),
+            Self::SendBatchTransaction { payments, message, .. } => {
+                write!(f, "SendBatchTransaction ({} payments, {})", payments.len(), message)
+            },
+            Self::GetBatchedPayments(tx_id) => write!(f, "GetBatchedPayments({})", tx_id),
+            Self::GetTransactionMemo(tx_id) => write!(f, "GetTransactionMemo({})", tx_id),
+            Self::SendMultiRecipientTransaction {
+                destination,
+                amount,
+                one_sided_payments,
+                message,
+                ..
+            } => write!(
+                f,
+                "SendMultiRecipientTransaction (to {}, {}, {} one-sided payments, {})",
+                destination,
+                amount,
+                one_sided_payments.len(),
+                message
+            ),
+            Self::ScheduleTransaction {
+                payment, execute_at, ..
+            } => write!(
+                f,
+                "ScheduleTransaction (to {}, {}, at {})",
+                payment.destination, payment.amount, execute_at
+            ),
+            Self::GetScheduledTransactions => write!(f, "GetScheduledTransactions"),
+            Self::CancelScheduledTransaction(id) => write!(f, "CancelScheduledTransaction({})", id),
+            Self::SendOrQueueTransaction(payment) => write!(
+                f,
+                "SendOrQueueTransaction (to {}, {})",
+                payment.destination, payment.amount
+            ),
+            Self::GetQueuedTransactions => write!(f, "GetQueuedTransactions"),
+            Self::CancelQueuedTransaction(id) => write!(f, "CancelQueuedTransaction({})", id),
+            Self::GetFeeEstimates => write!(f, "GetFeeEstimates"),
+            Self::GetMempoolStates => write!(f, "GetMempoolStates"),
+            Self::BumpFee { tx_id, fee_per_gram } => write!(f, "BumpFee ({}, {})", tx_id, fee_per_gram),
+            Self::ChildPaysForParent {
+                parent_tx_id,
+                fee_per_gram,
+            } => write!(f, "ChildPaysForParent ({}, {})", parent_tx_id, fee_per_gram),
+            Self::AddTransactionTags { tx_id, tags } => write!(f, "AddTransactionTags ({}, {:?})", tx_id, tags),
+            Self::RemoveTransactionTags { tx_id, tags } => write!(f, "RemoveTransactionTags ({}, {:?})", tx_id, tags),
+            Self::GetTransactionTags(tx_ids) => write!(f, "GetTransactionTags({} txs)", tx_ids.len()),
+            Self::GetTransactionsByTag(tag) => write!(f, "GetTransactionsByTag({})", tag),
+            Self::SearchTransactions(query) => write!(f, "SearchTransactions({})", query),
+            Self::ExportHistory {
+                format,
+                date_range,
+                path,
+            } => write!(
+                f,
+                "ExportHistory (format: {}, date_range: {}, path: {})",
+                format,
+                date_range,
+                path.display()
+            ),
+            Self::ImportHistory(transactions) => write!(f, "ImportHistory({} txs)", transactions.len()),
+            Self::InitiateAtomicSwap {
+                counterparty, amount, ..
+            } => write!(f, "InitiateAtomicSwap (to {}, {})", counterparty, amount),
+            Self::ParticipateAtomicSwap {
+                swap_id,
+                counterparty,
+                amount,
+                hash_lock,
+                ..
+            } => write!(
+                f,
+                "ParticipateAtomicSwap ({}, to {}, {}, hash lock {})",
+                swap_id, counterparty, amount, hash_lock
+            ),
+            Self::RedeemAtomicSwap {
+                swap_id, fee_per_gram, ..
+            } => write!(f, "RedeemAtomicSwap ({}, {})", swap_id, fee_per_gram),
+            Self::RefundAtomicSwap { swap_id, fee_per_gram } => {
+                write!(f, "RefundAtomicSwap ({}, {})", swap_id, fee_per_gram)
+            },
+            Self::GetAtomicSwaps => write!(f, "GetAtomicSwaps"),
+            Self::CreateUnsignedTransaction {
+                destination, amount, ..
+            } => write!(f, "CreateUnsignedTransaction (to {}, {})", destination, amount),
+            Self::SignUnsignedTransaction(unsigned) => write!(f, "SignUnsignedTransaction({})", unsigned.tx_id),
+            Self::ImportSignedTransaction(signed) => write!(f, "ImportSignedTransaction({})", signed.tx_id),
+            Self::CancelOfflineTransaction(tx_id) => write!(f, "CancelOfflineTransaction({})", tx_id),
+            Self::GetOfflineTransactions(status) => write!(f, "GetOfflineTransactions({:?})", status),
+            Self::BurnFunds { amount, message, .. } => write!(f, "BurnFunds ({}, {})", amount, message),
+            Self::GetBurnProof(tx_id) => write!(f, "GetBurnProof({})", tx_id),
+            Self::GetBurnProofs(status) => write!(f, "GetBurnProofs({:?})", status),
+            Self::MarkBurnClaimed(tx_id) => write!(f, "MarkBurnClaimed({})", tx_id),
+            Self::CreateMultisigSession {
+                threshold,
+                cosigners,
+                label,
+            } => write!(
+                f,
+                "CreateMultisigSession ({}-of-{}, {})",
+                threshold,
+                cosigners.len() + 1,
+                label
+            ),
+            Self::GetMultisigSessions => write!(f, "GetMultisigSessions"),
+            Self::RequestMultisigSignature {
+                session_id,
+                description,
+                ..
+            } => write!(f, "RequestMultisigSignature ({}, {})", session_id, description),
+            Self::ApproveMultisigSigning(signing_id) => write!(f, "ApproveMultisigSigning({})", signing_id),
+            Self::CancelMultisigSigning(signing_id) => write!(f, "CancelMultisigSigning({})", signing_id),
+            Self::GetMultisigSignings(session_id) => write!(f, "GetMultisigSignings({:?})", session_id),
+            Self::CreateRecurringPayment { payment, interval, .. } => write!(
+                f,
+                "CreateRecurringPayment (to {}, {} every {}s)",
+                payment.destination,
+                payment.amount,
+                interval.as_secs()
+            ),
+            Self::GetRecurringPayments => write!(f, "GetRecurringPayments"),
+            Self::PauseRecurringPayment(id) => write!(f, "PauseRecurringPayment({})", id),
+            Self::ResumeRecurringPayment(id) => write!(f, "ResumeRecurringPayment({})", id),
+            Self::CancelRecurringPayment(id) => write!(f, "CancelRecurringPayment({})", id),
+            Self::GetEventsSince(cursor) => write!(f, "GetEventsSince({})", cursor),
+            Self::CreateSendTemplate { name, destination, .. } => {
+                write!(f, "CreateSendTemplate ({} to {})", name, destination)
+            },
+            Self::UpdateSendTemplate { id, name, .. } => write!(f, "UpdateSendTemplate({}, {})", id, name),
+            Self::GetSendTemplate(id) => write!(f, "GetSendTemplate({})", id),
+            Self::GetSendTemplates => write!(f, "GetSendTemplates"),
+            Self::DeleteSendTemplate(id) => write!(f, "DeleteSendTemplate({})", id),
         }
     }
 }
@@ -283,12 +642,169 @@ pub enum TransactionServiceResponse {
     NumConfirmationsRequired(u64),
     NumConfirmationsSet,
     ValidationStarted(OperationId),
+    BatchValidationStarted(TransactionBatchValidation),
+    QueuedOutboundMessagesResent {
+        resent: usize,
+        remaining: usize,
+    },
     CompletedTransactionValidityChanged,
     ShaAtomicSwapTransactionSent(Box<(TxId, PublicKey, TransactionOutput)>),
     FeePerGramStatsPerBlock(FeePerGramStatsResponse),
+    CounterpartyAliases(HashMap<TxId, String>),
+    BatchedPayments(Vec<BatchedPayment>),
+    TransactionMemo(Option<String>),
+    TransactionScheduled(u64),
+    ScheduledTransactions(Vec<ScheduledTransaction>),
+    ScheduledTransactionCancelled,
+    TransactionQueued(u64),
+    QueuedTransactions(Vec<QueuedTransaction>),
+    QueuedTransactionCancelled,
+    FeeEstimates(FeeEstimates),
+    MempoolStates(HashMap<TxId, MempoolTransactionState>),
+    FeeBumped(MicroMinotari),
+    TransactionTagsUpdated,
+    TransactionTags(HashMap<TxId, Vec<String>>),
+    Transactions(Vec<WalletTransaction>),
+    HistoryExported(usize),
+    HistoryImported(Vec<TxId>),
+    AtomicSwap(Box<AtomicSwap>),
+    AtomicSwaps(Vec<AtomicSwap>),
+    UnsignedTransaction(Box<UnsignedTransaction>),
+    SignedTransaction(Box<SignedTransaction>),
+    OfflineTransactions(Vec<OfflineTransaction>),
+    BurnProof(Box<Option<BurnRecord>>),
+    BurnProofs(Vec<BurnRecord>),
+    BurnMarkedClaimed,
+    MultisigSession(Box<MultisigSession>),
+    MultisigSessions(Vec<MultisigSession>),
+    MultisigSigning(Box<MultisigSigning>),
+    MultisigSignings(Vec<MultisigSigning>),
+    RecurringPaymentCreated(u64),
+    RecurringPayments(Vec<RecurringPayment>),
+    RecurringPaymentUpdated,
+    Events(Vec<JournaledTransactionEvent>),
+    SendTemplateCreated(u64),
+    SendTemplate(Box<Option<SendTemplate>>),
+    SendTemplates(Vec<SendTemplate>),
+    SendTemplateUpdated,
+}
+
+/// A single payout to be included in a batch transaction, see [TransactionServiceHandle::send_batch_transaction]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchPayment {
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub message: String,
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq, Default)]
+/// A payment to be sent at a later block height or time, see [TransactionServiceHandle::schedule_transaction]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScheduledPayment {
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub message: String,
+    /// Send the payment one-sided rather than interactively
+    pub one_sided: bool,
+}
+
+/// The outcome of [TransactionServiceHandle::send_or_queue_transaction]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendOrQueueResult {
+    /// The wallet was online and the payment was sent as the given transaction
+    Sent(TxId),
+    /// The wallet was offline and the payment was queued under the given id, to be sent once connectivity returns
+    Queued(u64),
+}
+
+/// How soon a transaction should be mined, see [TransactionServiceHandle::estimate_fee_per_gram]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FeePriority {
+    /// Mined once the current mempool backlog has cleared
+    Low,
+    /// Mined in the next block unless the mempool grows
+    Medium,
+    /// Mined in the next block even if the mempool grows
+    High,
+}
+
+impl TryFrom<i32> for FeePriority {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(FeePriority::Low),
+            1 => Ok(FeePriority::Medium),
+            2 => Ok(FeePriority::High),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<FeePriority> for i32 {
+    fn from(value: FeePriority) -> Self {
+        match value {
+            FeePriority::Low => 0,
+            FeePriority::Medium => 1,
+            FeePriority::High => 2,
+        }
+    }
+}
+
+impl Display for FeePriority {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            FeePriority::Low => write!(fmt, "Low"),
+            FeePriority::Medium => write!(fmt, "Medium"),
+            FeePriority::High => write!(fmt, "High"),
+        }
+    }
+}
+
+/// Fee per gram recommendations derived from the mempool of the connected base node over recent blocks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeeEstimates {
+    pub low: MicroMinotari,
+    pub medium: MicroMinotari,
+    pub high: MicroMinotari,
+    /// The chain tip when the most recent sample was taken
+    pub tip_height: u64,
+    /// The number of blocks that were sampled
+    pub num_samples: usize,
+}
+
+impl FeeEstimates {
+    pub fn for_priority(&self, priority: FeePriority) -> MicroMinotari {
+        match priority {
+            FeePriority::Low => self.low,
+            FeePriority::Medium => self.medium,
+            FeePriority::High => self.high,
+        }
+    }
+}
+
+/// A validation of a batch of the unconfirmed transactions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransactionBatchValidation {
+    pub operation_id: OperationId,
+    /// The number of transactions in the batch
+    pub num_transactions: usize,
+    /// The number of unconfirmed transactions left out of the batch
+    pub remaining: usize,
+}
+
+/// The state of a broadcast transaction in the mempool of the connected base node, as of the last mempool query
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MempoolTransactionState {
+    pub in_mempool: bool,
+    pub fee_per_gram: MicroMinotari,
+    /// Where the fee per gram of the transaction ranks amongst the transactions in the mempool, from 0 (cheapest) to
+    /// 100 (most expensive). Only known while the transaction is in the mempool.
+    pub feerate_percentile: Option<u8>,
+    pub checked_at: NaiveDateTime,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct TransactionSendStatus {
     pub direct_send_result: bool,
     pub store_and_forward_send_result: bool,
@@ -305,18 +821,56 @@ impl Display for TransactionSendStatus {
     }
 }
 
+/// An input of a pending outbound transaction that the base node reports as already spent on-chain by a different
+/// transaction
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpentInputConflict {
+    pub output_hash: HashOutput,
+    pub spent_at_height: u64,
+    pub spent_in_block: BlockHash,
+    /// The wallet transaction that spent the input, if the conflicting spend is known to this wallet
+    pub conflicting_tx_id: Option<TxId>,
+    /// The kernel excess signature of the conflicting spend, if it is known to this wallet
+    pub conflicting_kernel_excess_sig: Option<Signature>,
+}
+
+impl Display for SpentInputConflict {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(
+            fmt,
+            "output {} spent at height {} in block {}",
+            self.output_hash, self.spent_at_height, self.spent_in_block
+        )?;
+        if let Some(tx_id) = self.conflicting_tx_id {
+            write!(fmt, " by tx {tx_id}")?;
+        }
+        if let Some(sig) = &self.conflicting_kernel_excess_sig {
+            write!(fmt, " (kernel sig nonce {})", sig.get_public_nonce().to_hex())?;
+        }
+        Ok(())
+    }
+}
+
 /// Events that can be published on the Text Message Service Event Stream
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransactionEvent {
     MempoolBroadcastTimedOut(TxId),
     NewBlockMined(TxId),
     ReceivedTransaction(TxId),
     ReceivedTransactionReply(TxId),
+    /// The recipient replied to the transaction and the wallet is waiting for the cosigners of its keys to sign it
+    TransactionAwaitingCosigners(TxId),
     ReceivedFinalizedTransaction(TxId),
     TransactionDiscoveryInProgress(TxId),
     TransactionSendResult(TxId, TransactionSendStatus),
     TransactionCompletedImmediately(TxId),
     TransactionCancelled(TxId, TxCancellationReason),
+    /// A pending outbound transaction was cancelled because one or more of its inputs were spent on-chain by a
+    /// different transaction
+    InputsSpentElsewhere {
+        tx_id: TxId,
+        conflicts: Vec<SpentInputConflict>,
+    },
     TransactionBroadcast(TxId),
     TransactionImported(TxId),
     FauxTransactionUnconfirmed {
@@ -341,6 +895,76 @@ pub enum TransactionEvent {
     TransactionValidationStateChanged(OperationId),
     TransactionValidationCompleted(OperationId),
     TransactionValidationFailed(OperationId, u64),
+    /// A scheduled transaction became due and is being sent
+    ScheduledTransactionTriggered(u64),
+    /// A scheduled transaction was sent as the given transaction
+    ScheduledTransactionSucceeded {
+        schedule_id: u64,
+        tx_id: TxId,
+    },
+    /// A scheduled transaction became due but could not be sent
+    ScheduledTransactionFailed {
+        schedule_id: u64,
+        reason: String,
+    },
+    /// A scheduled transaction reached its expiry before it could be sent
+    ScheduledTransactionExpired(u64),
+    /// A send was accepted into the offline queue because the wallet has no base node connectivity
+    TransactionQueued(u64),
+    /// Connectivity returned and a queued transaction was sent as the given transaction
+    QueuedTransactionSent {
+        queue_id: u64,
+        tx_id: TxId,
+    },
+    /// Connectivity returned but a queued transaction could not be sent
+    QueuedTransactionFailed {
+        queue_id: u64,
+        reason: String,
+    },
+    /// A queued transaction reached the maximum queue age before connectivity returned
+    QueuedTransactionExpired(u64),
+    /// A pending transaction was replaced by a version paying the given, higher, fee
+    TransactionFeeBumped {
+        tx_id: TxId,
+        fee: MicroMinotari,
+    },
+    /// An atomic swap moved to a new state or the counterparty reported progress on it
+    AtomicSwapUpdated {
+        swap_id: u64,
+        state: AtomicSwapState,
+    },
+    /// A multisig session was created or moved to its next key generation round
+    MultisigSessionUpdated {
+        session_id: u64,
+        state: MultisigSessionState,
+    },
+    /// A multisig signing was requested or moved to its next round
+    MultisigSigningUpdated {
+        signing_id: u64,
+        state: MultisigSigningState,
+    },
+    /// The receive protocol queue was full and the inbound transaction was dropped
+    ReceiveProtocolQueueOverflow(TxId),
+    /// A recurring payment was sent as the given transaction
+    RecurringPaymentSucceeded {
+        payment_id: u64,
+        tx_id: TxId,
+    },
+    /// A recurring payment was due but could not be sent. The plan stays active and the next payment is attempted at
+    /// the following interval.
+    RecurringPaymentFailed {
+        payment_id: u64,
+        reason: String,
+    },
+    /// A recurring payment plan was paused because the wallet did not have enough funds for the payment that was due
+    RecurringPaymentPaused {
+        payment_id: u64,
+        reason: String,
+    },
+    /// A recurring payment plan reached its end date
+    RecurringPaymentCompleted(u64),
+    /// Historical transactions were added to the transaction history by a bulk import
+    TransactionHistoryImported(usize),
     Error(String),
 }
 
@@ -356,6 +980,9 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::ReceivedTransactionReply(tx) => {
                 write!(f, "ReceivedTransactionReply for {tx}")
             },
+            TransactionEvent::TransactionAwaitingCosigners(tx) => {
+                write!(f, "TransactionAwaitingCosigners for {tx}")
+            },
             TransactionEvent::ReceivedFinalizedTransaction(tx) => {
                 write!(f, "ReceivedFinalizedTransaction for {tx}")
             },
@@ -371,6 +998,16 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::TransactionCancelled(tx, rejection) => {
                 write!(f, "TransactionCancelled for {tx}:{:?}", rejection)
             },
+            TransactionEvent::InputsSpentElsewhere { tx_id, conflicts } => {
+                write!(f, "InputsSpentElsewhere for {tx_id}: ")?;
+                for (i, conflict) in conflicts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{conflict}")?;
+                }
+                Ok(())
+            },
             TransactionEvent::TransactionBroadcast(tx) => {
                 write!(f, "TransactionBroadcast for {tx}")
             },
@@ -423,6 +1060,60 @@ impl fmt::Display for TransactionEvent {
             TransactionEvent::NewBlockMined(tx_id) => {
                 write!(f, "New block mined {tx_id}")
             },
+            TransactionEvent::ScheduledTransactionTriggered(schedule_id) => {
+                write!(f, "ScheduledTransactionTriggered for schedule {schedule_id}")
+            },
+            TransactionEvent::ScheduledTransactionSucceeded { schedule_id, tx_id } => {
+                write!(f, "ScheduledTransactionSucceeded for schedule {schedule_id}: {tx_id}")
+            },
+            TransactionEvent::ScheduledTransactionFailed { schedule_id, reason } => {
+                write!(f, "ScheduledTransactionFailed for schedule {schedule_id}: {reason}")
+            },
+            TransactionEvent::ScheduledTransactionExpired(schedule_id) => {
+                write!(f, "ScheduledTransactionExpired for schedule {schedule_id}")
+            },
+            TransactionEvent::TransactionQueued(queue_id) => {
+                write!(f, "TransactionQueued as {queue_id}")
+            },
+            TransactionEvent::QueuedTransactionSent { queue_id, tx_id } => {
+                write!(f, "QueuedTransactionSent for queued transaction {queue_id}: {tx_id}")
+            },
+            TransactionEvent::QueuedTransactionFailed { queue_id, reason } => {
+                write!(f, "QueuedTransactionFailed for queued transaction {queue_id}: {reason}")
+            },
+            TransactionEvent::QueuedTransactionExpired(queue_id) => {
+                write!(f, "QueuedTransactionExpired for queued transaction {queue_id}")
+            },
+            TransactionEvent::TransactionFeeBumped { tx_id, fee } => {
+                write!(f, "TransactionFeeBumped for tx:{tx_id} to {fee}")
+            },
+            TransactionEvent::AtomicSwapUpdated { swap_id, state } => {
+                write!(f, "AtomicSwapUpdated for swap {swap_id}: {state}")
+            },
+            TransactionEvent::MultisigSessionUpdated { session_id, state } => {
+                write!(f, "MultisigSessionUpdated for session {session_id}: {state}")
+            },
+            TransactionEvent::MultisigSigningUpdated { signing_id, state } => {
+                write!(f, "MultisigSigningUpdated for signing {signing_id}: {state}")
+            },
+            TransactionEvent::ReceiveProtocolQueueOverflow(tx_id) => {
+                write!(f, "ReceiveProtocolQueueOverflow for tx:{tx_id}")
+            },
+            TransactionEvent::RecurringPaymentSucceeded { payment_id, tx_id } => {
+                write!(f, "RecurringPaymentSucceeded for plan {payment_id}: {tx_id}")
+            },
+            TransactionEvent::RecurringPaymentFailed { payment_id, reason } => {
+                write!(f, "RecurringPaymentFailed for plan {payment_id}: {reason}")
+            },
+            TransactionEvent::RecurringPaymentPaused { payment_id, reason } => {
+                write!(f, "RecurringPaymentPaused for plan {payment_id}: {reason}")
+            },
+            TransactionEvent::RecurringPaymentCompleted(payment_id) => {
+                write!(f, "RecurringPaymentCompleted for plan {payment_id}")
+            },
+            TransactionEvent::TransactionHistoryImported(count) => {
+                write!(f, "TransactionHistoryImported {count} transactions")
+            },
         }
     }
 }
@@ -449,6 +1140,8 @@ impl From<proto::base_node::GetMempoolFeePerGramStatsResponse> for FeePerGramSta
 pub struct TransactionServiceHandle {
     handle: SenderService<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
     event_stream_sender: TransactionEventSender,
+    reauthentication: Option<ReauthenticationGuard>,
+    passphrase: Option<SafePassword>,
 }
 
 impl TransactionServiceHandle {
@@ -459,9 +1152,39 @@ impl TransactionServiceHandle {
         Self {
             handle,
             event_stream_sender,
+            reauthentication: None,
+            passphrase: None,
+        }
+    }
+
+    /// Enforce the given re-authentication policy on sends made through this handle and its clones
+    pub fn with_reauthentication_guard(mut self, guard: ReauthenticationGuard) -> Self {
+        self.reauthentication = Some(guard);
+        self
+    }
+
+    /// Returns a handle that supplies the wallet passphrase for sends that require re-authentication. The passphrase
+    /// is only checked when an operation requires it.
+    pub fn with_passphrase(&self, passphrase: SafePassword) -> Self {
+        let mut handle = self.clone();
+        handle.passphrase = Some(passphrase);
+        handle
+    }
+
+    async fn authorize(&self, operation: SensitiveOperation) -> Result<(), TransactionServiceError> {
+        match self.reauthentication.as_ref() {
+            Some(guard) => guard
+                .authorize_async(operation, self.passphrase.clone())
+                .await
+                .map_err(Into::into),
+            None => Ok(()),
         }
     }
 
+    async fn authorize_send(&self, amount: MicroMinotari) -> Result<(), TransactionServiceError> {
+        self.authorize(SensitiveOperation::Send(amount)).await
+    }
+
     pub fn get_event_stream(&self) -> TransactionEventReceiver {
         self.event_stream_sender.subscribe()
     }
@@ -475,6 +1198,7 @@ impl TransactionServiceHandle {
         fee_per_gram: MicroMinotari,
         message: String,
     ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount).await?;
         match self
             .handle
             .call(TransactionServiceRequest::SendTransaction {
@@ -484,6 +1208,76 @@ impl TransactionServiceHandle {
                 output_features: Box::new(output_features),
                 fee_per_gram,
                 message,
+                memo: None,
+                routing_mechanism: None,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a transaction to an interactive recipient with a private memo attached. The memo is encrypted to the
+    /// recipient's public key, so it never appears in plaintext in the DHT message. Both parties can read it back with
+    /// [get_transaction_memo](Self::get_transaction_memo).
+    pub async fn send_transaction_with_memo(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        memo: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        if memo.len() > MAX_MEMO_LENGTH {
+            return Err(EncryptedMemoError::MemoTooLong(memo.len()).into());
+        }
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                output_features: Box::default(),
+                fee_per_gram,
+                message,
+                memo: Some(memo),
+                routing_mechanism: None,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a transaction to an interactive recipient using the given routing mechanism instead of the one configured
+    /// for the wallet. With `DirectOnly` the call only returns once the direct send has been attempted, and it returns
+    /// [DirectSendFailed](TransactionServiceError::DirectSendFailed) if the recipient could not be reached. With
+    /// `StoreAndForwardOnly` the direct send is skipped and the transaction goes straight to store-and-forward.
+    pub async fn send_transaction_with_routing(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        routing_mechanism: TransactionRoutingMechanism,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                output_features: Box::default(),
+                fee_per_gram,
+                message,
+                memo: None,
+                routing_mechanism: Some(routing_mechanism),
             })
             .await??
         {
@@ -492,6 +1286,31 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Pays a `tari://` payment request URI. Requests that have expired or do not specify an amount are rejected.
+    pub async fn pay_request(
+        &mut self,
+        uri: &str,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<TxId, TransactionServiceError> {
+        let request = PaymentRequest::parse(uri)?;
+        if request.is_expired() {
+            return Err(TransactionServiceError::PaymentRequestExpired);
+        }
+        let amount = request
+            .amount
+            .ok_or(TransactionServiceError::PaymentRequestMissingAmount)?;
+        self.send_transaction(
+            request.address,
+            amount,
+            selection_criteria,
+            OutputFeatures::default(),
+            fee_per_gram,
+            request.message.unwrap_or_default(),
+        )
+        .await
+    }
+
     pub async fn register_validator_node(
         &mut self,
         amount: MicroMinotari,
@@ -501,6 +1320,7 @@ impl TransactionServiceHandle {
         fee_per_gram: MicroMinotari,
         message: String,
     ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount).await?;
         match self
             .handle
             .call(TransactionServiceRequest::RegisterValidatorNode {
@@ -530,6 +1350,7 @@ impl TransactionServiceHandle {
         binary_url: MaxSizeString<255>,
         fee_per_gram: MicroMinotari,
     ) -> Result<TxId, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
             .call(TransactionServiceRequest::RegisterCodeTemplate {
@@ -559,6 +1380,7 @@ impl TransactionServiceHandle {
         fee_per_gram: MicroMinotari,
         message: String,
     ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount).await?;
         match self
             .handle
             .call(TransactionServiceRequest::SendOneSidedTransaction {
@@ -576,47 +1398,52 @@ impl TransactionServiceHandle {
         }
     }
 
-    /// Burns the given amount of Tari from the wallet
-    pub async fn burn_tari(
+    /// Sends several payments in a single transaction with one kernel, which costs less in fees and chain weight than
+    /// sending each payment on its own. Every payment is made one-sided, so the recipients do not need to be online.
+    /// The child payments of the batch can be retrieved with [get_batched_payments](Self::get_batched_payments).
+    pub async fn send_batch_transaction(
         &mut self,
-        amount: MicroMinotari,
+        payments: Vec<BatchPayment>,
         selection_criteria: UtxoSelectionCriteria,
         fee_per_gram: MicroMinotari,
         message: String,
-        claim_public_key: Option<PublicKey>,
-    ) -> Result<(TxId, BurntProof), TransactionServiceError> {
+    ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(payments.iter().map(|p| p.amount).sum()).await?;
         match self
             .handle
-            .call(TransactionServiceRequest::BurnTari {
-                amount,
+            .call(TransactionServiceRequest::SendBatchTransaction {
+                payments,
                 selection_criteria,
                 fee_per_gram,
                 message,
-                claim_public_key,
             })
             .await??
         {
-            TransactionServiceResponse::BurntTransactionSent { tx_id, proof } => Ok((tx_id, *proof)),
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn send_one_sided_to_stealth_address_transaction(
+    /// Sends a single transaction that pays `amount` to `destination` interactively and also pays each of the
+    /// `one_sided_payments`, so that several recipients can be paid out with one kernel. The transaction completes
+    /// once the interactive recipient replies. The amount of the transaction is the interactive payment, the one-sided
+    /// payments are recorded separately and can be retrieved with [get_batched_payments](Self::get_batched_payments).
+    pub async fn send_multi_recipient_transaction(
         &mut self,
         destination: TariAddress,
         amount: MicroMinotari,
-        selection_criteria: UtxoSelectionCriteria,
-        output_features: OutputFeatures,
+        one_sided_payments: Vec<BatchPayment>,
         fee_per_gram: MicroMinotari,
         message: String,
     ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount + one_sided_payments.iter().map(|p| p.amount).sum::<MicroMinotari>())
+            .await?;
         match self
             .handle
-            .call(TransactionServiceRequest::SendOneSidedToStealthAddressTransaction {
+            .call(TransactionServiceRequest::SendMultiRecipientTransaction {
                 destination,
                 amount,
-                selection_criteria,
-                output_features: Box::new(output_features),
+                one_sided_payments,
                 fee_per_gram,
                 message,
             })
@@ -627,127 +1454,1097 @@ impl TransactionServiceHandle {
         }
     }
 
-    pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+    /// Returns the child payments of a batch transaction, or an empty list if the transaction is not a batch
+    pub async fn get_batched_payments(&mut self, tx_id: TxId) -> Result<Vec<BatchedPayment>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetBatchedPayments(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::BatchedPayments(payments) => Ok(payments),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the decrypted payment memo attached to the given transaction, or `None` if it has no memo
+    pub async fn get_transaction_memo(&mut self, tx_id: TxId) -> Result<Option<String>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionMemo(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionMemo(memo) => Ok(memo),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Queues a payment to be sent once the chain tip reaches the given height, or once the given UTC time has passed.
+    /// If `expires_at` is reached before the payment could be sent, it is abandoned. Returns the id of the schedule,
+    /// which can be used to cancel it with [cancel_scheduled_transaction](Self::cancel_scheduled_transaction).
+    pub async fn schedule_transaction(
+        &mut self,
+        payment: ScheduledPayment,
+        execute_at: HeightOrTime,
+        expires_at: Option<HeightOrTime>,
+    ) -> Result<u64, TransactionServiceError> {
+        self.authorize_send(payment.amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::ScheduleTransaction {
+                payment,
+                execute_at,
+                expires_at,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionScheduled(id) => Ok(id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the recommended fee per gram for the given priority, based on the mempool of the connected base node
+    /// over recent blocks
+    pub async fn estimate_fee_per_gram(
+        &mut self,
+        priority: FeePriority,
+    ) -> Result<MicroMinotari, TransactionServiceError> {
+        Ok(self.get_fee_estimates().await?.for_priority(priority))
+    }
+
+    /// Returns the low, medium and high fee per gram recommendations
+    pub async fn get_fee_estimates(&mut self) -> Result<FeeEstimates, TransactionServiceError> {
+        match self.handle.call(TransactionServiceRequest::GetFeeEstimates).await?? {
+            TransactionServiceResponse::FeeEstimates(estimates) => Ok(estimates),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the last known mempool state of each broadcast transaction
+    pub async fn get_mempool_states(
+        &mut self,
+    ) -> Result<HashMap<TxId, MempoolTransactionState>, TransactionServiceError> {
+        match self.handle.call(TransactionServiceRequest::GetMempoolStates).await?? {
+            TransactionServiceResponse::MempoolStates(states) => Ok(states),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns all scheduled transactions, oldest first, regardless of their status
+    pub async fn get_scheduled_transactions(&mut self) -> Result<Vec<ScheduledTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetScheduledTransactions)
+            .await??
+        {
+            TransactionServiceResponse::ScheduledTransactions(scheduled) => Ok(scheduled),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancels a scheduled transaction that has not been triggered yet
+    pub async fn cancel_scheduled_transaction(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelScheduledTransaction(id))
+            .await??
+        {
+            TransactionServiceResponse::ScheduledTransactionCancelled => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends the payment straight away if the wallet is connected to a base node. Otherwise, if
+    /// `queue_offline_transactions` is enabled, the payment is queued and sent automatically once connectivity
+    /// returns, unless it has been waiting longer than `max_queued_transaction_age` by then.
+    pub async fn send_or_queue_transaction(
+        &mut self,
+        payment: ScheduledPayment,
+    ) -> Result<SendOrQueueResult, TransactionServiceError> {
+        self.authorize_send(payment.amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOrQueueTransaction(payment))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(SendOrQueueResult::Sent(tx_id)),
+            TransactionServiceResponse::TransactionQueued(id) => Ok(SendOrQueueResult::Queued(id)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns all transactions queued while offline, oldest first, regardless of their status
+    pub async fn get_queued_transactions(&mut self) -> Result<Vec<QueuedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetQueuedTransactions)
+            .await??
+        {
+            TransactionServiceResponse::QueuedTransactions(queued) => Ok(queued),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancels a queued transaction that has not been sent yet
+    pub async fn cancel_queued_transaction(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelQueuedTransaction(id))
+            .await??
+        {
+            TransactionServiceResponse::QueuedTransactionCancelled => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replaces a pending transaction that has not been mined yet with one that spends the same inputs at the higher
+    /// `fee_per_gram`, and re-broadcasts it under the same `tx_id`. Only transactions whose recipients were all paid
+    /// one-sided can be rebuilt, as an interactive recipient would have to sign again. Returns the new fee.
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<MicroMinotari, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::BumpFee { tx_id, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::FeeBumped(fee) => Ok(fee),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Speeds up a broadcast transaction that has not been mined yet by spending its unconfirmed outputs back to this
+    /// wallet in a child transaction, paying enough fee that parent and child together pay `fee_per_gram`. Unlike
+    /// [bump_fee](Self::bump_fee) the parent is left as is, so this also works for interactive and inbound
+    /// transactions. Returns the tx id of the child.
+    pub async fn child_pays_for_parent(
+        &mut self,
+        parent_tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::ChildPaysForParent {
+                parent_tx_id,
+                fee_per_gram,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Burns the given amount of Tari from the wallet
+    pub async fn burn_tari(
+        &mut self,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        claim_public_key: Option<PublicKey>,
+    ) -> Result<(TxId, BurntProof), TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::BurnTari {
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                message,
+                claim_public_key,
+            })
+            .await??
+        {
+            TransactionServiceResponse::BurntTransactionSent { tx_id, proof } => Ok((tx_id, *proof)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn send_one_sided_to_stealth_address_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<TxId, TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::SendOneSidedToStealthAddressTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                output_features: Box::new(output_features),
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn cancel_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
         match self
             .handle
             .call(TransactionServiceRequest::CancelTransaction(tx_id))
             .await??
         {
-            TransactionServiceResponse::TransactionCancelled => Ok(()),
+            TransactionServiceResponse::TransactionCancelled => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_pending_inbound_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetPendingInboundTransactions)
+            .await??
+        {
+            TransactionServiceResponse::PendingInboundTransactions(p) => Ok(p),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_cancelled_pending_inbound_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCancelledPendingInboundTransactions)
+            .await??
+        {
+            TransactionServiceResponse::PendingInboundTransactions(p) => Ok(p),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_pending_outbound_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, OutboundTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetPendingOutboundTransactions)
+            .await??
+        {
+            TransactionServiceResponse::PendingOutboundTransactions(p) => Ok(p),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_cancelled_pending_outbound_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, OutboundTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCancelledPendingOutboundTransactions)
+            .await??
+        {
+            TransactionServiceResponse::PendingOutboundTransactions(p) => Ok(p),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_completed_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCompletedTransactions)
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_cancelled_completed_transactions(
+        &mut self,
+    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCancelledCompletedTransactions)
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_completed_transaction(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<CompletedTransaction, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCompletedTransaction(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::CompletedTransaction(t) => Ok(*t),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_any_transaction(
+        &mut self,
+        tx_id: TxId,
+    ) -> Result<Option<WalletTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetAnyTransaction(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::AnyTransaction(t) => Ok(*t),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the address book alias of the counterparty for each of the given transactions, using the contact's
+    /// current alias where it is still a contact and the alias it had when it was removed otherwise. Transactions whose
+    /// counterparty has never been a contact are omitted from the result.
+    pub async fn get_counterparty_aliases(
+        &mut self,
+        tx_ids: Vec<TxId>,
+    ) -> Result<HashMap<TxId, String>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetCounterpartyAliases(tx_ids))
+            .await??
+        {
+            TransactionServiceResponse::CounterpartyAliases(aliases) => Ok(aliases),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_counterparty_alias(&mut self, tx_id: TxId) -> Result<Option<String>, TransactionServiceError> {
+        let mut aliases = self.get_counterparty_aliases(vec![tx_id]).await?;
+        Ok(aliases.remove(&tx_id))
+    }
+
+    /// Attaches the given tags to a transaction. Tags are trimmed and must not be empty.
+    pub async fn add_transaction_tags(
+        &mut self,
+        tx_id: TxId,
+        tags: Vec<String>,
+    ) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::AddTransactionTags { tx_id, tags })
+            .await??
+        {
+            TransactionServiceResponse::TransactionTagsUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn remove_transaction_tags(
+        &mut self,
+        tx_id: TxId,
+        tags: Vec<String>,
+    ) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::RemoveTransactionTags { tx_id, tags })
+            .await??
+        {
+            TransactionServiceResponse::TransactionTagsUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the tags of each of the given transactions. Transactions without tags are omitted.
+    pub async fn get_transaction_tags(
+        &mut self,
+        tx_ids: Vec<TxId>,
+    ) -> Result<HashMap<TxId, Vec<String>>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionTags(tx_ids))
+            .await??
+        {
+            TransactionServiceResponse::TransactionTags(tags) => Ok(tags),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_transactions_by_tag(
+        &mut self,
+        tag: String,
+    ) -> Result<Vec<WalletTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetTransactionsByTag(tag))
+            .await??
+        {
+            TransactionServiceResponse::Transactions(txs) => Ok(txs),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the transactions with a tag or payment message containing `query`, ignoring ASCII case
+    pub async fn search_transactions(
+        &mut self,
+        query: String,
+    ) -> Result<Vec<WalletTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::SearchTransactions(query))
+            .await??
+        {
+            TransactionServiceResponse::Transactions(txs) => Ok(txs),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Writes the completed, pending and cancelled transactions in `date_range` to `path` as CSV or JSON. Returns
+    /// the number of transactions exported.
+    pub async fn export_history(
+        &mut self,
+        format: HistoryExportFormat,
+        date_range: HistoryDateRange,
+        path: PathBuf,
+    ) -> Result<usize, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ExportHistory {
+                format,
+                date_range,
+                path,
+            })
+            .await??
+        {
+            TransactionServiceResponse::HistoryExported(count) => Ok(count),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Records externally known transactions, e.g. from an old wallet's history export or an exchange statement, as
+    /// imported history so that it remains visible after migrating. Transactions whose id is already known are
+    /// skipped. Returns the ids of the transactions that were added.
+    pub async fn import_history(
+        &mut self,
+        transactions: Vec<HistoricalTransaction>,
+    ) -> Result<Vec<TxId>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportHistory(transactions))
+            .await??
+        {
+            TransactionServiceResponse::HistoryImported(tx_ids) => Ok(tx_ids),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Starts an atomic swap by generating a secret pre-image and locking `amount` in an HTLC output that
+    /// `counterparty` can claim with it. The funds can be refunded after `INITIATOR_LOCK_BLOCKS` blocks. The
+    /// counterparty is told about the lock and the new swap, which holds the hash lock to share, is returned.
+    pub async fn initiate_atomic_swap(
+        &mut self,
+        counterparty: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::InitiateAtomicSwap {
+                counterparty,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::AtomicSwap(swap) => Ok(*swap),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Takes part in the atomic swap `swap_id` started by `counterparty`, by locking `amount` in an HTLC output that
+    /// the counterparty can claim with the pre-image of `hash_lock`. The funds can be refunded after
+    /// `PARTICIPANT_LOCK_BLOCKS` blocks. The swap must have been proposed by the counterparty, and is refused unless
+    /// the initiator's lock expires at least `LOCK_HEIGHT_SAFETY_MARGIN` blocks after ours.
+    pub async fn participate_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        counterparty: TariAddress,
+        amount: MicroMinotari,
+        hash_lock: FixedHash,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::ParticipateAtomicSwap {
+                swap_id,
+                counterparty,
+                amount,
+                hash_lock,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::AtomicSwap(swap) => Ok(*swap),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Claims the HTLC output the counterparty locked for this wallet. `pre_image` may be omitted if the wallet
+    /// already knows it, either because it initiated the swap or because the counterparty revealed it.
+    pub async fn redeem_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        pre_image: Option<PublicKey>,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::RedeemAtomicSwap {
+                swap_id,
+                pre_image,
+                fee_per_gram,
+            })
+            .await??
+        {
+            TransactionServiceResponse::AtomicSwap(swap) => Ok(*swap),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Reclaims the HTLC output this wallet locked, once the lock height has been reached
+    pub async fn refund_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        fee_per_gram: MicroMinotari,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::RefundAtomicSwap { swap_id, fee_per_gram })
+            .await??
+        {
+            TransactionServiceResponse::AtomicSwap(swap) => Ok(*swap),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_pending_inbound_transactions(
+    pub async fn get_atomic_swaps(&mut self) -> Result<Vec<AtomicSwap>, TransactionServiceError> {
+        match self.handle.call(TransactionServiceRequest::GetAtomicSwaps).await?? {
+            TransactionServiceResponse::AtomicSwaps(swaps) => Ok(swaps),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Proposes a `threshold`-of-n multisig session between this wallet and `cosigners`. The participants run a FROST
+    /// key generation and the session is `Ready` once it has a group key, which stays the same whichever participants
+    /// sign for it.
+    pub async fn create_multisig_session(
         &mut self,
-    ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
+        threshold: u8,
+        cosigners: Vec<TariAddress>,
+        label: String,
+    ) -> Result<MultisigSession, TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::GetPendingInboundTransactions)
+            .call(TransactionServiceRequest::CreateMultisigSession {
+                threshold,
+                cosigners,
+                label,
+            })
             .await??
         {
-            TransactionServiceResponse::PendingInboundTransactions(p) => Ok(p),
+            TransactionServiceResponse::MultisigSession(session) => Ok(*session),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_cancelled_pending_inbound_transactions(
+    pub async fn get_multisig_sessions(&mut self) -> Result<Vec<MultisigSession>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetMultisigSessions)
+            .await??
+        {
+            TransactionServiceResponse::MultisigSessions(sessions) => Ok(sessions),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Asks `cosigners` to sign `message` with this wallet for the group key of the multisig session. Together with
+    /// this wallet the cosigners must be exactly `threshold` participants. The signing waits for every cosigner to
+    /// approve it and completes with a Schnorr signature that verifies against the group key.
+    pub async fn request_multisig_signature(
         &mut self,
-    ) -> Result<HashMap<TxId, InboundTransaction>, TransactionServiceError> {
+        session_id: u64,
+        cosigners: Vec<TariAddress>,
+        message: FixedHash,
+        description: String,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
-            .call(TransactionServiceRequest::GetCancelledPendingInboundTransactions)
+            .call(TransactionServiceRequest::RequestMultisigSignature {
+                session_id,
+                cosigners,
+                message,
+                description,
+            })
             .await??
         {
-            TransactionServiceResponse::PendingInboundTransactions(p) => Ok(p),
+            TransactionServiceResponse::MultisigSigning(signing) => Ok(*signing),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_pending_outbound_transactions(
+    /// Takes part in a signing a cosigner requested, which is `PendingApproval` until then
+    pub async fn approve_multisig_signing(
         &mut self,
-    ) -> Result<HashMap<TxId, OutboundTransaction>, TransactionServiceError> {
+        signing_id: u64,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
-            .call(TransactionServiceRequest::GetPendingOutboundTransactions)
+            .call(TransactionServiceRequest::ApproveMultisigSigning(signing_id))
             .await??
         {
-            TransactionServiceResponse::PendingOutboundTransactions(p) => Ok(p),
+            TransactionServiceResponse::MultisigSigning(signing) => Ok(*signing),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_cancelled_pending_outbound_transactions(
+    /// Declines or abandons a signing. The cosigners are not notified.
+    pub async fn cancel_multisig_signing(
         &mut self,
-    ) -> Result<HashMap<TxId, OutboundTransaction>, TransactionServiceError> {
+        signing_id: u64,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::GetCancelledPendingOutboundTransactions)
+            .call(TransactionServiceRequest::CancelMultisigSigning(signing_id))
             .await??
         {
-            TransactionServiceResponse::PendingOutboundTransactions(p) => Ok(p),
+            TransactionServiceResponse::MultisigSigning(signing) => Ok(*signing),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_completed_transactions(
+    /// Returns the signings of a multisig session, or of all sessions if `session_id` is `None`
+    pub async fn get_multisig_signings(
         &mut self,
-    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        session_id: Option<u64>,
+    ) -> Result<Vec<MultisigSigning>, TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::GetCompletedTransactions)
+            .call(TransactionServiceRequest::GetMultisigSignings(session_id))
             .await??
         {
-            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            TransactionServiceResponse::MultisigSignings(signings) => Ok(signings),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_cancelled_completed_transactions(
+    /// Creates a payment plan that sends the payment every `interval`, starting at `start_at` (or immediately) until
+    /// `end_at` is reached. Plans are paused when the wallet does not have enough funds for a payment and can be
+    /// resumed with [resume_recurring_payment](Self::resume_recurring_payment). Returns the id of the plan.
+    pub async fn create_recurring_payment(
         &mut self,
-    ) -> Result<HashMap<TxId, CompletedTransaction>, TransactionServiceError> {
+        payment: ScheduledPayment,
+        interval: Duration,
+        start_at: Option<NaiveDateTime>,
+        end_at: Option<NaiveDateTime>,
+    ) -> Result<u64, TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
         match self
             .handle
-            .call(TransactionServiceRequest::GetCancelledCompletedTransactions)
+            .call(TransactionServiceRequest::CreateRecurringPayment {
+                payment,
+                interval,
+                start_at,
+                end_at,
+            })
             .await??
         {
-            TransactionServiceResponse::CompletedTransactions(c) => Ok(c),
+            TransactionServiceResponse::RecurringPaymentCreated(id) => Ok(id),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_completed_transaction(
+    /// Returns all recurring payment plans, oldest first, regardless of their status
+    pub async fn get_recurring_payments(&mut self) -> Result<Vec<RecurringPayment>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetRecurringPayments)
+            .await??
+        {
+            TransactionServiceResponse::RecurringPayments(payments) => Ok(payments),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Stops an active recurring payment plan from sending payments until it is resumed
+    pub async fn pause_recurring_payment(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::PauseRecurringPayment(id))
+            .await??
+        {
+            TransactionServiceResponse::RecurringPaymentUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Resumes a paused recurring payment plan. If a payment was missed while the plan was paused, it is sent straight
+    /// away.
+    pub async fn resume_recurring_payment(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        self.authorize(SensitiveOperation::Spend).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::ResumeRecurringPayment(id))
+            .await??
+        {
+            TransactionServiceResponse::RecurringPaymentUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancels an active or paused recurring payment plan
+    pub async fn cancel_recurring_payment(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelRecurringPayment(id))
+            .await??
+        {
+            TransactionServiceResponse::RecurringPaymentUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the journaled events published after the event with the given cursor, oldest first. Unlike the event
+    /// stream, this also returns events published while the caller was not subscribed, e.g. before the wallet was
+    /// restarted after a crash. Pass a cursor of 0 to fetch every event still in the journal.
+    pub async fn get_events_since(
         &mut self,
-        tx_id: TxId,
-    ) -> Result<CompletedTransaction, TransactionServiceError> {
+        cursor: u64,
+    ) -> Result<Vec<JournaledTransactionEvent>, TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::GetCompletedTransaction(tx_id))
+            .call(TransactionServiceRequest::GetEventsSince(cursor))
             .await??
         {
-            TransactionServiceResponse::CompletedTransaction(t) => Ok(*t),
+            TransactionServiceResponse::Events(events) => Ok(events),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
-    pub async fn get_any_transaction(
+    /// Saves the payment details of a repeat payee under a unique name. Returns the id of the template.
+    pub async fn create_send_template(
         &mut self,
-        tx_id: TxId,
-    ) -> Result<Option<WalletTransaction>, TransactionServiceError> {
+        name: String,
+        destination: TariAddress,
+        default_amount: Option<MicroMinotari>,
+        message: String,
+        fee_priority: FeePriority,
+        one_sided: bool,
+    ) -> Result<u64, TransactionServiceError> {
         match self
             .handle
-            .call(TransactionServiceRequest::GetAnyTransaction(tx_id))
+            .call(TransactionServiceRequest::CreateSendTemplate {
+                name,
+                destination,
+                default_amount,
+                message,
+                fee_priority,
+                one_sided,
+            })
             .await??
         {
-            TransactionServiceResponse::AnyTransaction(t) => Ok(*t),
+            TransactionServiceResponse::SendTemplateCreated(id) => Ok(id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Replaces the payment details of an existing send template
+    pub async fn update_send_template(
+        &mut self,
+        id: u64,
+        name: String,
+        destination: TariAddress,
+        default_amount: Option<MicroMinotari>,
+        message: String,
+        fee_priority: FeePriority,
+        one_sided: bool,
+    ) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::UpdateSendTemplate {
+                id,
+                name,
+                destination,
+                default_amount,
+                message,
+                fee_priority,
+                one_sided,
+            })
+            .await??
+        {
+            TransactionServiceResponse::SendTemplateUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn get_send_template(&mut self, id: u64) -> Result<Option<SendTemplate>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetSendTemplate(id))
+            .await??
+        {
+            TransactionServiceResponse::SendTemplate(template) => Ok(*template),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns all send templates ordered by name
+    pub async fn get_send_templates(&mut self) -> Result<Vec<SendTemplate>, TransactionServiceError> {
+        match self.handle.call(TransactionServiceRequest::GetSendTemplates).await?? {
+            TransactionServiceResponse::SendTemplates(templates) => Ok(templates),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    pub async fn delete_send_template(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::DeleteSendTemplate(id))
+            .await??
+        {
+            TransactionServiceResponse::SendTemplateUpdated => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Sends a payment using the details saved in a send template. The template's default amount is sent unless an
+    /// amount is given, and the fee per gram is estimated for the template's fee priority.
+    pub async fn send_from_template(
+        &mut self,
+        id: u64,
+        amount: Option<MicroMinotari>,
+    ) -> Result<TxId, TransactionServiceError> {
+        let template = self
+            .get_send_template(id)
+            .await?
+            .ok_or_else(|| TransactionServiceError::SendTemplateError(format!("No send template with id {}", id)))?;
+        let amount = amount.or(template.default_amount).ok_or_else(|| {
+            TransactionServiceError::SendTemplateError(format!(
+                "Send template '{}' has no default amount, an amount must be given",
+                template.name
+            ))
+        })?;
+        let fee_per_gram = self.estimate_fee_per_gram(template.fee_priority).await?;
+        if template.one_sided {
+            self.send_one_sided_transaction(
+                template.destination,
+                amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                fee_per_gram,
+                template.message,
+            )
+            .await
+        } else {
+            self.send_transaction(
+                template.destination,
+                amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                fee_per_gram,
+                template.message,
+            )
+            .await
+        }
+    }
+
+    /// Prepares a one-sided transaction to be built and signed by an offline wallet. This only selects the inputs, so a
+    /// watch-only wallet of the offline wallet can call it. The selected inputs stay encumbered until the signed
+    /// transaction is imported with
+    /// [import_signed_transaction](Self::import_signed_transaction) or the export is cancelled with
+    /// [cancel_offline_transaction](Self::cancel_offline_transaction). Use
+    /// [UnsignedTransaction::to_bytes] to move it to the offline wallet.
+    pub async fn create_unsigned_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<UnsignedTransaction, TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::CreateUnsignedTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::UnsignedTransaction(unsigned) => Ok(*unsigned),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Builds and signs a transaction created by [create_unsigned_transaction](Self::create_unsigned_transaction) on
+    /// the online wallet, paying the change back to the online wallet. This does not need a network connection.
+    pub async fn sign_unsigned_transaction(
+        &mut self,
+        unsigned_transaction: UnsignedTransaction,
+    ) -> Result<SignedTransaction, TransactionServiceError> {
+        self.authorize_send(unsigned_transaction.amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::SignUnsignedTransaction(Box::new(
+                unsigned_transaction,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::SignedTransaction(signed) => Ok(*signed),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Checks a transaction signed by the offline wallet against the unsigned transaction and broadcasts it
+    pub async fn import_signed_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+    ) -> Result<TxId, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ImportSignedTransaction(Box::new(
+                signed_transaction,
+            )))
+            .await??
+        {
+            TransactionServiceResponse::TransactionSent(tx_id) => Ok(tx_id),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Cancels a transaction that is awaiting an offline signature and releases its inputs
+    pub async fn cancel_offline_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::CancelOfflineTransaction(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::TransactionCancelled => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the transactions exported for offline signing, optionally only those with the given status
+    pub async fn get_offline_transactions(
+        &mut self,
+        status: Option<OfflineTransactionStatus>,
+    ) -> Result<Vec<OfflineTransaction>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetOfflineTransactions(status))
+            .await??
+        {
+            TransactionServiceResponse::OfflineTransactions(transactions) => Ok(transactions),
             _ => Err(TransactionServiceError::UnexpectedApiResponse),
         }
     }
 
+    /// Burns the given amount so that it can be claimed on the second layer with `claim_public_key`. The burn proof
+    /// is stored and can be fetched again with [get_burn_proof](Self::get_burn_proof).
+    pub async fn burn_funds(
+        &mut self,
+        amount: MicroMinotari,
+        claim_public_key: PublicKey,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<(TxId, BurntProof), TransactionServiceError> {
+        self.authorize_send(amount).await?;
+        match self
+            .handle
+            .call(TransactionServiceRequest::BurnFunds {
+                amount,
+                claim_public_key,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            })
+            .await??
+        {
+            TransactionServiceResponse::BurntTransactionSent { tx_id, proof } => Ok((tx_id, *proof)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the burn made in the given transaction, along with its claim proof
+    pub async fn get_burn_proof(&mut self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetBurnProof(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::BurnProof(record) => Ok(*record),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Returns the burns made by this wallet, optionally only those with the given status
+    pub async fn get_burn_proofs(
+        &mut self,
+        status: Option<BurnStatus>,
+    ) -> Result<Vec<BurnRecord>, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::GetBurnProofs(status))
+            .await??
+        {
+            TransactionServiceResponse::BurnProofs(records) => Ok(records),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Records that the funds burnt in the given transaction have been claimed on the second layer
+    pub async fn mark_burn_claimed(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::MarkBurnClaimed(tx_id))
+            .await??
+        {
+            TransactionServiceResponse::BurnMarkedClaimed => Ok(()),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Records an imported or scanned UTXO as a completed transaction. The destination defaults to the wallet address,
+    /// payments received on a subaddress record the subaddress instead.
     pub async fn import_utxo_with_status(
         &mut self,
         amount: MicroMinotari,
         source_address: TariAddress,
+        destination_address: Option<TariAddress>,
         message: String,
         maturity: Option<u64>,
         import_status: ImportStatus,
@@ -760,6 +2557,7 @@ impl TransactionServiceHandle {
             .call(TransactionServiceRequest::ImportUtxoWithStatus {
                 amount,
                 source_address,
+                destination_address,
                 message,
                 maturity,
                 import_status,
@@ -900,6 +2698,38 @@ impl TransactionServiceHandle {
         }
     }
 
+    /// Validates at most `max_transactions` of the unconfirmed transactions, picking up after the batch validated by
+    /// the previous call.
+    pub async fn validate_transaction_batch(
+        &mut self,
+        max_transactions: usize,
+    ) -> Result<TransactionBatchValidation, TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ValidateTransactionBatch(max_transactions))
+            .await??
+        {
+            TransactionServiceResponse::BatchValidationStarted(validation) => Ok(validation),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
+    /// Resends at most `max_messages` of the queued outbound transaction protocol messages. Returns the number of
+    /// messages resent and the number still queued.
+    pub async fn resend_queued_outbound_messages(
+        &mut self,
+        max_messages: usize,
+    ) -> Result<(usize, usize), TransactionServiceError> {
+        match self
+            .handle
+            .call(TransactionServiceRequest::ResendQueuedOutboundMessages(max_messages))
+            .await??
+        {
+            TransactionServiceResponse::QueuedOutboundMessagesResent { resent, remaining } => Ok((resent, remaining)),
+            _ => Err(TransactionServiceError::UnexpectedApiResponse),
+        }
+    }
+
     pub async fn send_sha_atomic_swap_transaction(
         &mut self,
         destination: TariAddress,
@@ -908,6 +2738,7 @@ impl TransactionServiceHandle {
         fee_per_gram: MicroMinotari,
         message: String,
     ) -> Result<(TxId, PublicKey, TransactionOutput), TransactionServiceError> {
+        self.authorize_send(amount).await?;
         match self
             .handle
             .call(TransactionServiceRequest::SendShaAtomicSwapTransaction(
@@ -0,0 +1,235 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, sync::Arc, time::Instant};
+
+use once_cell::sync::Lazy;
+use tari_common_types::transaction::TxId;
+use tari_comms::peer_manager::NodeId;
+use tari_metrics::{Histogram, HistogramVec, IntCounter, IntCounterVec};
+use tari_shutdown::ShutdownSignal;
+use tokio::sync::broadcast;
+
+use crate::transaction_service::{
+    handle::{TransactionEvent, TransactionServiceRequest},
+    storage::models::TxCancellationReason,
+};
+
+// Every metric is labelled with the node id of the wallet, so that wallets running in the same process are counted
+// separately
+
+pub fn sends_initiated(wallet: &NodeId, kind: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "wallet::transaction_service::sends_initiated",
+            "Number of transaction sends initiated by kind",
+            &["wallet", "kind"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[&wallet.to_string(), kind])
+}
+
+pub fn protocol_retries(wallet: &NodeId, protocol: &str) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "wallet::transaction_service::protocol_retries",
+            "Number of transaction protocol messages resent to the counterparty",
+            &["wallet", "protocol"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[&wallet.to_string(), protocol])
+}
+
+pub fn negotiation_latency(wallet: &NodeId) -> Histogram {
+    static METER: Lazy<HistogramVec> = Lazy::new(|| {
+        tari_metrics::register_histogram_vec(
+            "wallet::transaction_service::negotiation_latency",
+            "Seconds from sending a transaction to receiving the recipient's reply",
+            &["wallet"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[&wallet.to_string()])
+}
+
+pub fn broadcast_to_mined_time(wallet: &NodeId) -> Histogram {
+    static METER: Lazy<HistogramVec> = Lazy::new(|| {
+        tari_metrics::register_histogram_vec(
+            "wallet::transaction_service::broadcast_to_mined_time",
+            "Seconds from a transaction being broadcast to it being detected in a block",
+            &["wallet"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[&wallet.to_string()])
+}
+
+pub fn cancelled_transactions(wallet: &NodeId, reason: &TxCancellationReason) -> IntCounter {
+    static METER: Lazy<IntCounterVec> = Lazy::new(|| {
+        tari_metrics::register_int_counter_vec(
+            "wallet::transaction_service::cancelled_transactions",
+            "Number of cancelled transactions by reason",
+            &["wallet", "reason"],
+        )
+        .unwrap()
+    });
+
+    METER.with_label_values(&[&wallet.to_string(), reason.to_string().as_str()])
+}
+
+/// The kind label of a request that initiates a send, if any
+pub fn send_kind(request: &TransactionServiceRequest) -> Option<&'static str> {
+    #[allow(clippy::enum_glob_use)]
+    use TransactionServiceRequest::*;
+    match request {
+        SendTransaction { .. } => Some("interactive"),
+        SendOneSidedTransaction { .. } => Some("one_sided"),
+        SendOneSidedToStealthAddressTransaction { .. } => Some("stealth"),
+        SendShaAtomicSwapTransaction(..) | InitiateAtomicSwap { .. } => Some("atomic_swap"),
+        SendBatchTransaction { .. } => Some("batch"),
+        SendMultiRecipientTransaction { .. } => Some("multi_recipient"),
+        BurnTari { .. } | BurnFunds { .. } => Some("burn"),
+        RegisterValidatorNode { .. } | RegisterCodeTemplate { .. } => Some("registration"),
+        _ => None,
+    }
+}
+
+/// Records cancellations and broadcast-to-mined times from the transaction event stream. Only broadcasts observed by
+/// this process are timed.
+pub async fn record_transaction_events(
+    wallet: NodeId,
+    mut event_stream: broadcast::Receiver<Arc<TransactionEvent>>,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    let mut broadcast_at = HashMap::<TxId, Instant>::new();
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                match event {
+                    Ok(event) => record_event(&wallet, &event, &mut broadcast_at),
+                    Err(broadcast::error::RecvError::Lagged(_)) => {},
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            },
+            _ = shutdown_signal.wait() => break,
+        }
+    }
+}
+
+fn record_event(wallet: &NodeId, event: &TransactionEvent, broadcast_at: &mut HashMap<TxId, Instant>) {
+    match event {
+        TransactionEvent::TransactionBroadcast(tx_id) => {
+            broadcast_at.entry(*tx_id).or_insert_with(Instant::now);
+        },
+        TransactionEvent::TransactionMined { tx_id, .. } |
+        TransactionEvent::TransactionMinedUnconfirmed { tx_id, .. } => {
+            if let Some(at) = broadcast_at.remove(tx_id) {
+                broadcast_to_mined_time(wallet).observe(at.elapsed().as_secs_f64());
+            }
+        },
+        TransactionEvent::TransactionCancelled(tx_id, reason) => {
+            broadcast_at.remove(tx_id);
+            cancelled_transactions(wallet, reason).inc();
+        },
+        _ => {},
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+    use tari_comms::types::CommsPublicKey;
+    use tari_crypto::keys::PublicKey as PublicKeyTrait;
+
+    use super::*;
+
+    #[test]
+    fn it_records_broadcast_to_mined_times_and_cancellations() {
+        let wallet = NodeId::from_key(&CommsPublicKey::random_keypair(&mut OsRng).1);
+        let other_wallet = NodeId::from_key(&CommsPublicKey::random_keypair(&mut OsRng).1);
+        let mut broadcast_at = HashMap::new();
+        let mined = broadcast_to_mined_time(&wallet).get_sample_count();
+        let cancelled = cancelled_transactions(&wallet, &TxCancellationReason::Orphan).get();
+
+        let (tx_id1, tx_id2) = (TxId::from(1u64), TxId::from(2u64));
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionBroadcast(tx_id1),
+            &mut broadcast_at,
+        );
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionBroadcast(tx_id2),
+            &mut broadcast_at,
+        );
+        // Only the first broadcast of a transaction is timed
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionBroadcast(tx_id1),
+            &mut broadcast_at,
+        );
+        assert_eq!(broadcast_at.len(), 2);
+
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionMinedUnconfirmed {
+                tx_id: tx_id1,
+                num_confirmations: 1,
+                is_valid: true,
+            },
+            &mut broadcast_at,
+        );
+        // Later confirmations are not timed again
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionMined {
+                tx_id: tx_id1,
+                is_valid: true,
+            },
+            &mut broadcast_at,
+        );
+        assert_eq!(broadcast_to_mined_time(&wallet).get_sample_count(), mined + 1);
+
+        record_event(
+            &wallet,
+            &TransactionEvent::TransactionCancelled(tx_id2, TxCancellationReason::Orphan),
+            &mut broadcast_at,
+        );
+        assert!(broadcast_at.is_empty());
+        assert_eq!(
+            cancelled_transactions(&wallet, &TxCancellationReason::Orphan).get(),
+            cancelled + 1
+        );
+        // Another wallet in the same process is counted separately
+        assert_eq!(broadcast_to_mined_time(&other_wallet).get_sample_count(), 0);
+        assert_eq!(
+            cancelled_transactions(&other_wallet, &TxCancellationReason::Orphan).get(),
+            0
+        );
+    }
+}
@@ -25,6 +25,7 @@ use std::{marker::PhantomData, sync::Arc};
 use futures::{Stream, StreamExt};
 use log::*;
 use tari_comms_dht::Dht;
+use tari_contacts::contacts_service::handle::ContactsServiceHandle;
 use tari_core::{
     consensus::ConsensusManager,
     proto::base_node as base_node_proto,
@@ -51,14 +52,20 @@ use tokio::sync::broadcast;
 
 use crate::{
     base_node_service::handle::BaseNodeServiceHandle,
-    connectivity_service::WalletConnectivityHandle,
+    connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
     output_manager_service::handle::OutputManagerHandle,
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
         config::TransactionServiceConfig,
         handle::TransactionServiceHandle,
+        protocols::atomic_swap_protocol::run_atomic_swap_message_handler,
         service::TransactionService,
         storage::database::{TransactionBackend, TransactionDatabase},
+        tasks::{
+            queued_transactions::run_queued_transactions,
+            recurring_payments::run_recurring_payments,
+            scheduled_transactions::run_scheduled_transactions,
+        },
     },
     util::wallet_identity::WalletIdentity,
 };
@@ -66,6 +73,10 @@ use crate::{
 pub mod config;
 pub mod error;
 pub mod handle;
+#[cfg(feature = "metrics")]
+mod metrics;
+pub mod multisig_custody;
+pub mod offline_signing;
 pub mod protocols;
 pub mod service;
 pub mod storage;
@@ -188,6 +199,48 @@ where
             .get_subscription(TariMessageType::TransactionCancelled, SUBSCRIPTION_LABEL)
             .map(map_decode::<proto::TransactionCancelledMessage>)
     }
+
+    fn atomic_swap_stream(
+        &self,
+    ) -> impl Stream<Item = DomainMessage<Result<proto::AtomicSwapMessage, prost::DecodeError>>> {
+        trace!(
+            target: LOG_TARGET,
+            "Subscription '{}' for topic '{:?}' created.",
+            SUBSCRIPTION_LABEL,
+            TariMessageType::AtomicSwap
+        );
+        self.subscription_factory
+            .get_subscription(TariMessageType::AtomicSwap, SUBSCRIPTION_LABEL)
+            .map(map_decode::<proto::AtomicSwapMessage>)
+    }
+
+    fn multisig_key_share_stream(
+        &self,
+    ) -> impl Stream<Item = DomainMessage<Result<proto::MultisigKeyShareMessage, prost::DecodeError>>> {
+        trace!(
+            target: LOG_TARGET,
+            "Subscription '{}' for topic '{:?}' created.",
+            SUBSCRIPTION_LABEL,
+            TariMessageType::MultisigKeyShare
+        );
+        self.subscription_factory
+            .get_subscription(TariMessageType::MultisigKeyShare, SUBSCRIPTION_LABEL)
+            .map(map_decode::<proto::MultisigKeyShareMessage>)
+    }
+
+    fn multisig_signing_stream(
+        &self,
+    ) -> impl Stream<Item = DomainMessage<Result<proto::MultisigSigningMessage, prost::DecodeError>>> {
+        trace!(
+            target: LOG_TARGET,
+            "Subscription '{}' for topic '{:?}' created.",
+            SUBSCRIPTION_LABEL,
+            TariMessageType::MultisigSigning
+        );
+        self.subscription_factory
+            .get_subscription(TariMessageType::MultisigSigning, SUBSCRIPTION_LABEL)
+            .map(map_decode::<proto::MultisigSigningMessage>)
+    }
 }
 
 #[async_trait]
@@ -204,6 +257,9 @@ where
         let transaction_finalized_stream = self.transaction_finalized_stream();
         let base_node_response_stream = self.base_node_response_stream();
         let transaction_cancelled_stream = self.transaction_cancelled_stream();
+        let atomic_swap_stream = self.atomic_swap_stream();
+        let multisig_key_share_stream = self.multisig_key_share_stream();
+        let multisig_signing_stream = self.multisig_signing_stream();
 
         let (publisher, _) = broadcast::channel(self.config.transaction_event_channel_size);
 
@@ -233,10 +289,48 @@ where
             let core_key_manager_service = handles.expect_handle::<TKeyManagerInterface>();
             let connectivity = handles.expect_handle::<WalletConnectivityHandle>();
             let base_node_service_handle = handles.expect_handle::<BaseNodeServiceHandle>();
+            let contacts_service = handles.get_handle::<ContactsServiceHandle>();
+            let transaction_service_handle = handles.expect_handle::<TransactionServiceHandle>();
+            let db = TransactionDatabase::new(tx_backend);
+
+            tokio::spawn(run_scheduled_transactions(
+                db.clone(),
+                transaction_service_handle.clone(),
+                base_node_service_handle.clone(),
+                publisher.clone(),
+                config.scheduled_transaction_check_interval,
+                handles.get_shutdown_signal(),
+            ));
+
+            tokio::spawn(run_queued_transactions(
+                db.clone(),
+                transaction_service_handle.clone(),
+                connectivity.get_connectivity_status_watch(),
+                publisher.clone(),
+                config.max_queued_transaction_age,
+                config.queued_transaction_check_interval,
+                handles.get_shutdown_signal(),
+            ));
+
+            tokio::spawn(run_recurring_payments(
+                db.clone(),
+                transaction_service_handle,
+                publisher.clone(),
+                config.recurring_payment_check_interval,
+                handles.get_shutdown_signal(),
+            ));
+
+            tokio::spawn(run_atomic_swap_message_handler(
+                db.clone(),
+                atomic_swap_stream,
+                wallet_identity.network,
+                publisher.clone(),
+                handles.get_shutdown_signal(),
+            ));
 
             let result = TransactionService::new(
                 config,
-                TransactionDatabase::new(tx_backend),
+                db,
                 wallet_database,
                 receiver,
                 transaction_stream,
@@ -244,6 +338,8 @@ where
                 transaction_finalized_stream,
                 base_node_response_stream,
                 transaction_cancelled_stream,
+                multisig_key_share_stream,
+                multisig_signing_stream,
                 output_manager_service,
                 core_key_manager_service,
                 outbound_message_service,
@@ -254,6 +350,7 @@ where
                 factories,
                 handles.get_shutdown_signal(),
                 base_node_service_handle,
+                contacts_service,
             )
             .start()
             .await;
@@ -0,0 +1,366 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! A transaction key manager for a wallet whose funds are held in custody by a multisig session, such as 2-of-3
+//! custody. Outputs are locked to script keys of the session's group key and any `threshold` participants of the
+//! session can spend them, over the same multisig transport as the session's other signings.
+
+use std::{sync::Arc, time::Duration};
+
+use tari_common_types::types::{Commitment, PrivateKey, PublicKey};
+use tari_comms::types::CommsPublicKey;
+use tari_comms_dht::outbound::OutboundMessageRequester;
+use tari_core::transactions::{
+    frost::frost_script_key_tweak,
+    key_manager::{ExternalSigner, ExternalSignerKeyManager, TariKeyId, TransactionKeyManagerInterface},
+    transaction_components::{TransactionInputVersion, TransactionOutputVersion},
+};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
+use tari_key_manager::key_manager_service::KeyManagerServiceError;
+use tokio::time::{sleep, Instant};
+
+use crate::transaction_service::{
+    error::TransactionServiceError,
+    handle::TransactionEventSender,
+    protocols::multisig_protocol::{MultisigProtocol, MULTISIG_JOINT_KEY_BRANCHES, MULTISIG_SCRIPT_KEY_BRANCHES},
+    storage::{
+        database::{TransactionBackend, TransactionDatabase},
+        models::{MultisigKey, MultisigSessionState, MultisigSigning, MultisigSigningKind, MultisigSigningState},
+    },
+};
+
+/// How often a pending signing is checked for the cosigners' contributions
+const SIGNING_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A transaction key manager whose script keys are held by a multisig session, and whose sender offset keys and
+/// metadata signature nonces are held jointly by this wallet and its cosigners. The commitment masks, kernel nonces
+/// and everything else stay with the host key manager, so the wallet can still see and rewind its outputs, while no
+/// participant can spend them on its own.
+pub type FrostTransactionKeyManager<KM, TBackend> = ExternalSignerKeyManager<KM, FrostCustodySigner<TBackend, KM>>;
+
+/// Signs for the keys of a wallet whose funds are held by a multisig session, together with `cosigners`. Script
+/// signatures wait for the cosigners to approve the signing in their wallets, for up to `timeout`.
+pub struct FrostCustodySigner<TBackend, KM> {
+    protocol: Arc<MultisigProtocol<TBackend, KM>>,
+    db: TransactionDatabase<TBackend>,
+    session_id: u64,
+    group_public_key: PublicKey,
+    cosigners: Vec<CommsPublicKey>,
+    timeout: Duration,
+}
+
+impl<TBackend, KM> Clone for FrostCustodySigner<TBackend, KM> {
+    fn clone(&self) -> Self {
+        Self {
+            protocol: self.protocol.clone(),
+            db: self.db.clone(),
+            session_id: self.session_id,
+            group_public_key: self.group_public_key.clone(),
+            cosigners: self.cosigners.clone(),
+            timeout: self.timeout,
+        }
+    }
+}
+
+impl<TBackend, KM> FrostCustodySigner<TBackend, KM>
+where
+    TBackend: TransactionBackend + 'static,
+    KM: TransactionKeyManagerInterface,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        db: TransactionDatabase<TBackend>,
+        key_manager: KM,
+        outbound_message_service: OutboundMessageRequester,
+        own_public_key: CommsPublicKey,
+        event_publisher: TransactionEventSender,
+        session_id: u64,
+        cosigners: Vec<CommsPublicKey>,
+        timeout: Duration,
+    ) -> Result<Self, TransactionServiceError> {
+        let session = db
+            .fetch_multisig_session(session_id)?
+            .ok_or(TransactionServiceError::MultisigSessionNotFound(session_id))?;
+        let group_public_key = match (session.state, session.group_public_key) {
+            (MultisigSessionState::Ready, Some(group_public_key)) => group_public_key,
+            _ => {
+                return Err(TransactionServiceError::MultisigError(format!(
+                    "Session {} is still generating its key",
+                    session_id
+                )))
+            },
+        };
+        let protocol = MultisigProtocol::new(
+            db.clone(),
+            key_manager,
+            outbound_message_service,
+            own_public_key,
+            event_publisher,
+        );
+        Ok(Self {
+            protocol: Arc::new(protocol),
+            db,
+            session_id,
+            group_public_key,
+            cosigners,
+            timeout,
+        })
+    }
+
+    /// The group key of the session, tweaked for the script key `key`
+    fn script_key_tweak(&self, key: &MultisigKey) -> Result<PrivateKey, KeyManagerServiceError> {
+        frost_script_key_tweak(&self.group_public_key, &key.branch, key.index).map_err(signer_error)
+    }
+
+    /// The joint key `key`, made with the cosigners the first time it is asked for
+    async fn joint_key(&self, key: MultisigKey) -> Result<PublicKey, KeyManagerServiceError> {
+        let existing = self
+            .protocol
+            .find_joint_key(self.session_id, self.protocol.own_public_key(), &key)
+            .map_err(signer_error)?;
+        let signing = match existing {
+            Some(signing) => signing,
+            None => {
+                self.request(
+                    MultisigSigningKind::JointKey { key: key.clone() },
+                    format!("Joint key {}", key),
+                )
+                .await?
+            },
+        };
+        let signing = self.wait_for(signing.signing_id).await?;
+        signing
+            .joint_public_key
+            .ok_or_else(|| KeyManagerServiceError::SigningDeviceError(format!("Joint key {} is missing", key)))
+    }
+
+    async fn request(
+        &self,
+        kind: MultisigSigningKind,
+        description: String,
+    ) -> Result<MultisigSigning, KeyManagerServiceError> {
+        self.protocol
+            .request_custody_signing(self.session_id, self.cosigners.clone(), kind, description)
+            .await
+            .map_err(signer_error)
+    }
+
+    /// Waits until the cosigners made their contributions to the signing. A signing that is not complete after the
+    /// timeout is cancelled.
+    async fn wait_for(&self, signing_id: u64) -> Result<MultisigSigning, KeyManagerServiceError> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            let signing = self
+                .db
+                .fetch_multisig_signing(signing_id)
+                .map_err(signer_error)?
+                .ok_or_else(|| signer_error(TransactionServiceError::MultisigSigningNotFound(signing_id)))?;
+            match signing.state {
+                MultisigSigningState::Complete | MultisigSigningState::Used => return Ok(signing),
+                MultisigSigningState::Cancelled => {
+                    return Err(KeyManagerServiceError::SigningDeviceError(format!(
+                        "Multisig signing {} was cancelled",
+                        signing_id
+                    )))
+                },
+                _ if Instant::now() >= deadline => {
+                    self.protocol.cancel_signing(signing_id).map_err(signer_error)?;
+                    return Err(KeyManagerServiceError::SigningDeviceError(format!(
+                        "The cosigners did not answer multisig signing {} in time",
+                        signing_id
+                    )));
+                },
+                _ => sleep(SIGNING_POLL_INTERVAL).await,
+            }
+        }
+    }
+
+    /// Asks the cosigners for a signing and waits for it
+    async fn sign(
+        &self,
+        kind: MultisigSigningKind,
+        description: String,
+    ) -> Result<MultisigSigning, KeyManagerServiceError> {
+        let signing = self.request(kind, description).await?;
+        self.wait_for(signing.signing_id).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<TBackend, KM> ExternalSigner for FrostCustodySigner<TBackend, KM>
+where
+    TBackend: TransactionBackend + 'static,
+    KM: TransactionKeyManagerInterface,
+{
+    type Key = MultisigKey;
+
+    fn description(&self) -> &'static str {
+        "the multisig session"
+    }
+
+    fn holds_branch(&self, branch: &str) -> bool {
+        MULTISIG_SCRIPT_KEY_BRANCHES
+            .iter()
+            .chain(MULTISIG_JOINT_KEY_BRANCHES.iter())
+            .any(|b| b.get_branch_key() == branch)
+    }
+
+    fn signer_key(&self, key_id: &TariKeyId) -> Option<MultisigKey> {
+        match key_id {
+            TariKeyId::Managed { branch, index } if self.holds_branch(branch) => Some(MultisigKey {
+                branch: branch.clone(),
+                index: *index,
+            }),
+            _ => None,
+        }
+    }
+
+    fn awaits_cosigners(&self) -> bool {
+        true
+    }
+
+    async fn get_public_key(&self, key: MultisigKey) -> Result<PublicKey, KeyManagerServiceError> {
+        if is_script_key(&key) {
+            let tweak = self.script_key_tweak(&key)?;
+            Ok(&self.group_public_key + &PublicKey::from_secret_key(&tweak))
+        } else {
+            self.joint_key(key).await
+        }
+    }
+
+    async fn get_diffie_hellman_shared_secret(
+        &self,
+        key: MultisigKey,
+        _public_key: &PublicKey,
+    ) -> Result<PublicKey, KeyManagerServiceError> {
+        Err(KeyManagerServiceError::SigningDeviceError(format!(
+            "Key {} is held by the multisig session, which does not make shared secrets",
+            key
+        )))
+    }
+
+    async fn get_script_signature(
+        &self,
+        script_key: MultisigKey,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<(PublicKey, PublicKey, PrivateKey), KeyManagerServiceError> {
+        let public_script_key = self.get_public_key(script_key.clone()).await?;
+        let description = format!("Spend the output locked to script key {}", script_key);
+        let signing = self
+            .sign(
+                MultisigSigningKind::ScriptSignature {
+                    script_key,
+                    txi_version,
+                    ephemeral_commitment: ephemeral_commitment.clone(),
+                    commitment: commitment.clone(),
+                    script_message: (*script_message).into(),
+                },
+                description,
+            )
+            .await?;
+        let signature = signing
+            .signature
+            .ok_or_else(|| KeyManagerServiceError::SigningDeviceError("The script signature is missing".to_string()))?;
+        Ok((
+            public_script_key,
+            signature.get_public_nonce().clone(),
+            signature.get_signature().clone(),
+        ))
+    }
+
+    async fn get_script_offset(
+        &self,
+        script_keys: Vec<MultisigKey>,
+        sender_offset_keys: Vec<MultisigKey>,
+    ) -> Result<PrivateKey, KeyManagerServiceError> {
+        if !script_keys.iter().all(is_script_key) || sender_offset_keys.iter().any(is_script_key) {
+            return Err(KeyManagerServiceError::SigningDeviceError(
+                "The multisig session only subtracts joint sender offset keys from its script keys".to_string(),
+            ));
+        }
+        // The signers make the part of the group key, the tweaks are public
+        let mut tweaks = PrivateKey::default();
+        for key in &script_keys {
+            tweaks = &tweaks + &self.script_key_tweak(key)?;
+        }
+        let description = format!("Script offset of {} inputs", script_keys.len());
+        let signing = self
+            .sign(
+                MultisigSigningKind::ScriptOffset {
+                    script_keys,
+                    sender_offset_keys,
+                },
+                description,
+            )
+            .await?;
+        let script_offset = signing
+            .script_offset
+            .ok_or_else(|| KeyManagerServiceError::SigningDeviceError("The script offset is missing".to_string()))?;
+        Ok(&script_offset + &tweaks)
+    }
+
+    async fn get_sender_metadata_signature(
+        &self,
+        nonce: MultisigKey,
+        sender_offset_key: MultisigKey,
+        txo_version: TransactionOutputVersion,
+        commitment: &Commitment,
+        ephemeral_commitment: &Commitment,
+        metadata_signature_message: &[u8; 32],
+    ) -> Result<(PublicKey, PrivateKey), KeyManagerServiceError> {
+        let joint_nonce = self.joint_key(nonce.clone()).await?;
+        let joint_sender_offset_key = self.joint_key(sender_offset_key.clone()).await?;
+        let description = format!("Metadata signature with nonce {}", nonce);
+        let signing = self
+            .sign(
+                MultisigSigningKind::MetadataSignature {
+                    nonce,
+                    sender_offset_key,
+                    txo_version,
+                    commitment: commitment.clone(),
+                    ephemeral_commitment: ephemeral_commitment.clone(),
+                    metadata_signature_message: (*metadata_signature_message).into(),
+                    joint_nonce,
+                    joint_sender_offset_key,
+                },
+                description,
+            )
+            .await?;
+        let signature = signing.signature.ok_or_else(|| {
+            KeyManagerServiceError::SigningDeviceError("The metadata signature is missing".to_string())
+        })?;
+        Ok((signature.get_public_nonce().clone(), signature.get_signature().clone()))
+    }
+}
+
+fn is_script_key(key: &MultisigKey) -> bool {
+    MULTISIG_SCRIPT_KEY_BRANCHES
+        .iter()
+        .any(|b| b.get_branch_key() == key.branch)
+}
+
+fn signer_error<E: ToString>(e: E) -> KeyManagerServiceError {
+    KeyManagerServiceError::SigningDeviceError(e.to_string())
+}
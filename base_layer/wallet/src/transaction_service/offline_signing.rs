@@ -0,0 +1,155 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Offline (cold) signing of one-sided transactions.
+//!
+//! The workflow is split between an online wallet, usually a watch-only wallet of the offline wallet's view key, and
+//! the offline wallet that holds the spending keys:
+//! 1. The online wallet selects and encumbers the inputs, see
+//!    [create_unsigned_transaction](super::handle::TransactionServiceHandle::create_unsigned_transaction). The
+//!    [UnsignedTransaction] holds the outputs being spent as they are on chain, so it carries no secrets.
+//! 2. The offline wallet recognises the inputs as its own and builds and signs the transaction, paying the recipient
+//!    and the change back to the online wallet's address as one-sided outputs, producing a [SignedTransaction], see
+//!    [sign_unsigned_transaction](super::handle::TransactionServiceHandle::sign_unsigned_transaction).
+//! 3. The online wallet checks that the signed transaction spends exactly the encumbered inputs and pays exactly the
+//!    requested amount to the destination and the rest, less the fee, back to itself, then broadcasts it, see
+//!    [import_signed_transaction](super::handle::TransactionServiceHandle::import_signed_transaction).
+//!
+//! Both types are moved between the wallets as bytes, for example in a file.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tari_common_types::{tari_address::TariAddress, transaction::TxId};
+use tari_core::transactions::{
+    tari_amount::MicroMinotari,
+    transaction_components::{Transaction, TransactionOutput},
+};
+use thiserror::Error;
+
+/// The serialization version of [UnsignedTransaction] and [SignedTransaction]
+pub const OFFLINE_TRANSACTION_VERSION: u8 = 1;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OfflineSigningError {
+    #[error("Unsupported offline transaction version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Could not serialize offline transaction: {0}")]
+    Serialization(String),
+    #[error("Could not deserialize offline transaction: {0}")]
+    Deserialization(String),
+}
+
+/// A one-sided transaction whose inputs have been selected and encumbered by the online wallet, waiting to be built
+/// and signed by the offline wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsignedTransaction {
+    pub version: u8,
+    pub tx_id: TxId,
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub message: String,
+    /// The address of the online wallet, which the change is paid to as a one-sided output
+    pub change_address: TariAddress,
+    /// The outputs being spent
+    pub inputs: Vec<TransactionOutput>,
+    /// The total value of the inputs according to the online wallet
+    pub input_total: MicroMinotari,
+    /// The chain tip height seen by the online wallet, which selects the consensus constants used for signing
+    pub tip_height: u64,
+}
+
+impl UnsignedTransaction {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OfflineSigningError> {
+        to_bytes(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OfflineSigningError> {
+        let unsigned: Self = from_bytes(bytes)?;
+        check_version(unsigned.version)?;
+        Ok(unsigned)
+    }
+}
+
+/// A transaction signed by the offline wallet, ready to be imported and broadcast by the online wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignedTransaction {
+    pub version: u8,
+    pub tx_id: TxId,
+    pub transaction: Transaction,
+}
+
+impl SignedTransaction {
+    pub fn to_bytes(&self) -> Result<Vec<u8>, OfflineSigningError> {
+        to_bytes(self)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, OfflineSigningError> {
+        let signed: Self = from_bytes(bytes)?;
+        check_version(signed.version)?;
+        Ok(signed)
+    }
+}
+
+fn to_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>, OfflineSigningError> {
+    bincode::serialize(value).map_err(|e| OfflineSigningError::Serialization(e.to_string()))
+}
+
+fn from_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, OfflineSigningError> {
+    bincode::deserialize(bytes).map_err(|e| OfflineSigningError::Deserialization(e.to_string()))
+}
+
+fn check_version(version: u8) -> Result<(), OfflineSigningError> {
+    if version == OFFLINE_TRANSACTION_VERSION {
+        Ok(())
+    } else {
+        Err(OfflineSigningError::UnsupportedVersion(version))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common_types::types::PrivateKey;
+
+    use super::*;
+
+    #[test]
+    fn it_rejects_unknown_versions() {
+        let signed = SignedTransaction {
+            version: OFFLINE_TRANSACTION_VERSION + 1,
+            tx_id: TxId::from(1u64),
+            transaction: Transaction::new(vec![], vec![], vec![], PrivateKey::default(), PrivateKey::default()),
+        };
+        let bytes = signed.to_bytes().unwrap();
+        assert_eq!(
+            SignedTransaction::from_bytes(&bytes).unwrap_err(),
+            OfflineSigningError::UnsupportedVersion(OFFLINE_TRANSACTION_VERSION + 1)
+        );
+
+        let signed = SignedTransaction {
+            version: OFFLINE_TRANSACTION_VERSION,
+            ..signed
+        };
+        let decoded = SignedTransaction::from_bytes(&signed.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.tx_id, signed.tx_id);
+        assert!(SignedTransaction::from_bytes(&[1, 2, 3]).is_err());
+    }
+}
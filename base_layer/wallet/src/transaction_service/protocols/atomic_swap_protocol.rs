@@ -0,0 +1,393 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{convert::TryFrom, sync::Arc};
+
+use chrono::Utc;
+use digest::Digest;
+use futures::{pin_mut, Stream, StreamExt};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use sha2::Sha256;
+use tari_common::configuration::Network;
+use tari_common_types::{
+    tari_address::TariAddress,
+    types::{FixedHash, PublicKey},
+};
+use tari_comms::types::CommsPublicKey;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester},
+};
+use tari_core::{
+    base_node::proto::wallet_rpc::SpendingInputsRequest,
+    transactions::{
+        tari_amount::MicroMinotari,
+        transaction_components::TransactionInput,
+        transaction_protocol::proto::protocol as proto,
+    },
+};
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_script::StackItem;
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::ByteArray;
+
+use crate::{
+    connectivity_service::WalletConnectivityInterface,
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionEvent, TransactionEventSender},
+        storage::{
+            database::{TransactionBackend, TransactionDatabase},
+            models::{AtomicSwap, AtomicSwapRole, AtomicSwapState},
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::protocols::atomic_swap";
+
+/// The number of blocks the initiator's HTLC output stays locked for, roughly a day of 2 minute blocks
+pub const INITIATOR_LOCK_BLOCKS: u64 = 24 * 30;
+/// The number of blocks a participant's HTLC output stays locked for. This must expire well before the initiator's
+/// lock so that the participant can still redeem once the initiator reveals the pre-image.
+pub const PARTICIPANT_LOCK_BLOCKS: u64 = 12 * 30;
+/// The number of blocks a participant's lock must expire before the initiator's. After the initiator redeems with the
+/// pre-image, the participant needs this long to see the pre-image on chain and redeem the initiator's output before
+/// the initiator can refund it.
+pub const LOCK_HEIGHT_SAFETY_MARGIN: u64 = 4 * 30;
+
+/// Returns the SHA256 hash lock for `pre_image`, as checked by the HTLC script
+pub fn hash_lock_for(pre_image: &PublicKey) -> FixedHash {
+    let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+    hash.into()
+}
+
+/// Checks that a participant locking until `participant_lock_height` can still redeem the initiator's output, which is
+/// locked until `initiator_lock_height`
+pub fn check_participant_lock_height(
+    participant_lock_height: u64,
+    initiator_lock_height: u64,
+) -> Result<(), TransactionServiceError> {
+    if participant_lock_height + LOCK_HEIGHT_SAFETY_MARGIN >= initiator_lock_height {
+        return Err(TransactionServiceError::AtomicSwapError(format!(
+            "The initiator's funds are only locked until height {}, which is too soon for a lock until height {}. \
+             The initiator's lock must expire at least {} blocks after ours.",
+            initiator_lock_height, participant_lock_height, LOCK_HEIGHT_SAFETY_MARGIN
+        )));
+    }
+    Ok(())
+}
+
+/// The pre-image for `hash_lock` in the input data of an input that redeemed an HTLC output
+pub fn pre_image_from_input(input: &TransactionInput, hash_lock: &FixedHash) -> Option<PublicKey> {
+    let mut input_data = input.input_data.clone();
+    while let Some(item) = input_data.pop() {
+        if let StackItem::PublicKey(pre_image) = item {
+            if hash_lock_for(&pre_image) == *hash_lock {
+                return Some(pre_image);
+            }
+        }
+    }
+    None
+}
+
+/// Looks up whether the HTLC outputs this wallet locked as a participant have been spent on chain, and reads the
+/// pre-image from the inputs that redeemed them. The initiator reveals the pre-image on chain when redeeming, so the
+/// participant does not have to rely on the initiator's `Redeemed` message to redeem in turn.
+pub async fn find_redeemed_pre_images<TBackend, TWalletConnectivity>(
+    db: TransactionDatabase<TBackend>,
+    mut connectivity: TWalletConnectivity,
+    event_publisher: TransactionEventSender,
+) -> Result<(), TransactionServiceError>
+where
+    TBackend: TransactionBackend + 'static,
+    TWalletConnectivity: WalletConnectivityInterface,
+{
+    let swaps = db
+        .fetch_atomic_swaps()?
+        .into_iter()
+        .filter(|swap| {
+            swap.role == AtomicSwapRole::Participant &&
+                swap.state == AtomicSwapState::Locked &&
+                swap.pre_image.is_none()
+        })
+        .filter_map(|swap| swap.lock_output_hash.map(|hash| (hash, swap)))
+        .collect::<Vec<_>>();
+    if swaps.is_empty() {
+        return Ok(());
+    }
+
+    let mut client = connectivity
+        .obtain_base_node_wallet_rpc_client()
+        .await
+        .ok_or(TransactionServiceError::Shutdown)?;
+    let response = client
+        .get_spending_inputs(SpendingInputsRequest {
+            output_hashes: swaps.iter().map(|(hash, _)| hash.to_vec()).collect(),
+        })
+        .await?;
+    for spending_input in response.inputs {
+        let output_hash = FixedHash::try_from(spending_input.output_hash.as_slice())
+            .map_err(|e| TransactionServiceError::AtomicSwapError(e.to_string()))?;
+        let swap = match swaps.iter().find(|(hash, _)| *hash == output_hash) {
+            Some((_, swap)) => swap,
+            None => continue,
+        };
+        let input = spending_input
+            .input
+            .map(TransactionInput::try_from)
+            .transpose()
+            .map_err(TransactionServiceError::AtomicSwapError)?;
+        let pre_image = match input.and_then(|input| pre_image_from_input(&input, &swap.hash_lock)) {
+            Some(pre_image) => pre_image,
+            None => {
+                warn!(
+                    target: LOG_TARGET,
+                    "The HTLC output of swap {} was spent at height {} without revealing the pre-image",
+                    swap.swap_id,
+                    spending_input.spent_height
+                );
+                continue;
+            },
+        };
+        info!(
+            target: LOG_TARGET,
+            "The initiator of swap {} revealed the pre-image on chain at height {}",
+            swap.swap_id,
+            spending_input.spent_height
+        );
+        let mut swap = swap.clone();
+        swap.pre_image = Some(pre_image);
+        swap.state = AtomicSwapState::CounterpartyRedeemed;
+        swap.updated_at = Utc::now().naive_utc();
+        db.upsert_atomic_swap(swap.clone())?;
+        let _size = event_publisher.send(Arc::new(TransactionEvent::AtomicSwapUpdated {
+            swap_id: swap.swap_id,
+            state: swap.state,
+        }));
+    }
+    Ok(())
+}
+
+pub fn new_swap_id() -> u64 {
+    OsRng.next_u64()
+}
+
+/// Tells the counterparty that this wallet locked its side of the swap in the given HTLC output
+pub fn locked_message(swap: &AtomicSwap) -> Option<proto::AtomicSwapMessage> {
+    let output_hash = swap.lock_output_hash?;
+    Some(proto::AtomicSwapMessage {
+        swap_id: swap.swap_id,
+        message: Some(proto::atomic_swap_message::Message::Locked(proto::AtomicSwapLocked {
+            hash_lock: swap.hash_lock.to_vec(),
+            amount: swap.amount.as_u64(),
+            lock_height: swap.lock_height,
+            output_hash: output_hash.to_vec(),
+            message: swap.message.clone(),
+        })),
+    })
+}
+
+/// Tells the counterparty the pre-image this wallet revealed when redeeming
+pub fn redeemed_message(swap_id: u64, pre_image: &PublicKey) -> proto::AtomicSwapMessage {
+    proto::AtomicSwapMessage {
+        swap_id,
+        message: Some(proto::atomic_swap_message::Message::Redeemed(
+            proto::AtomicSwapRedeemed {
+                pre_image: pre_image.to_vec(),
+            },
+        )),
+    }
+}
+
+pub fn refunded_message(swap_id: u64) -> proto::AtomicSwapMessage {
+    proto::AtomicSwapMessage {
+        swap_id,
+        message: Some(proto::atomic_swap_message::Message::Refunded(
+            proto::AtomicSwapRefunded {},
+        )),
+    }
+}
+
+/// Sends an atomic swap message to the counterparty both directly and via store and forward. The messages only let the
+/// counterparty react sooner, the swap itself is enforced on chain, so delivery is not monitored.
+pub async fn send_atomic_swap_message(
+    destination_public_key: CommsPublicKey,
+    message: proto::AtomicSwapMessage,
+    mut outbound_message_service: OutboundMessageRequester,
+) -> Result<(), TransactionServiceError> {
+    let _send_message_response = outbound_message_service
+        .send_direct_encrypted(
+            destination_public_key.clone(),
+            OutboundDomainMessage::new(&TariMessageType::AtomicSwap, message.clone()),
+            OutboundEncryption::encrypt_for(destination_public_key.clone()),
+            "atomic swap".to_string(),
+        )
+        .await?;
+
+    let _message_send_state = outbound_message_service
+        .closest_broadcast(
+            destination_public_key.clone(),
+            OutboundEncryption::encrypt_for(destination_public_key),
+            vec![],
+            OutboundDomainMessage::new(&TariMessageType::AtomicSwap, message),
+        )
+        .await?;
+    Ok(())
+}
+
+/// Records the atomic swap messages received from counterparties and publishes an `AtomicSwapUpdated` event for every
+/// swap that changed
+pub async fn run_atomic_swap_message_handler<TBackend, TStream>(
+    db: TransactionDatabase<TBackend>,
+    messages: TStream,
+    network: Network,
+    event_publisher: TransactionEventSender,
+    mut shutdown_signal: ShutdownSignal,
+) where
+    TBackend: TransactionBackend + 'static,
+    TStream: Stream<Item = DomainMessage<Result<proto::AtomicSwapMessage, prost::DecodeError>>>,
+{
+    pin_mut!(messages);
+    loop {
+        tokio::select! {
+            Some(msg) = messages.next() => {
+                let message_tag = msg.dht_header.message_tag;
+                match handle_atomic_swap_message(&db, network, msg) {
+                    Ok(swap) => {
+                        let _size = event_publisher.send(Arc::new(TransactionEvent::AtomicSwapUpdated {
+                            swap_id: swap.swap_id,
+                            state: swap.state,
+                        }));
+                    },
+                    Err(e) => warn!(
+                        target: LOG_TARGET,
+                        "Error handling atomic swap message (Trace: {}): {}", message_tag, e
+                    ),
+                }
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Atomic swap message handler shutting down");
+                break;
+            },
+        }
+    }
+}
+
+fn handle_atomic_swap_message<TBackend: TransactionBackend + 'static>(
+    db: &TransactionDatabase<TBackend>,
+    network: Network,
+    msg: DomainMessage<Result<proto::AtomicSwapMessage, prost::DecodeError>>,
+) -> Result<AtomicSwap, TransactionServiceError> {
+    let (origin_public_key, message) = msg.into_origin_and_inner();
+    let message = message.map_err(|e| {
+        TransactionServiceError::InvalidMessageError(format!("Could not decode AtomicSwapMessage: {:?}", e))
+    })?;
+    let source = TariAddress::new(origin_public_key, network);
+    let swap_id = message.swap_id;
+    let existing = db.fetch_atomic_swap(swap_id)?;
+    if let Some(swap) = existing.as_ref() {
+        if swap.counterparty.public_key() != source.public_key() {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Swap {} does not belong to {}",
+                swap_id, source
+            )));
+        }
+    }
+    let now = Utc::now().naive_utc();
+
+    let swap = match message.message {
+        Some(proto::atomic_swap_message::Message::Locked(locked)) => {
+            let hash_lock = FixedHash::try_from(locked.hash_lock.as_slice())
+                .map_err(|e| TransactionServiceError::AtomicSwapError(e.to_string()))?;
+            let output_hash = FixedHash::try_from(locked.output_hash.as_slice())
+                .map_err(|e| TransactionServiceError::AtomicSwapError(e.to_string()))?;
+            match existing {
+                Some(mut swap) => {
+                    if swap.hash_lock != hash_lock {
+                        return Err(TransactionServiceError::AtomicSwapError(format!(
+                            "Counterparty locked swap {} with a different hash lock",
+                            swap_id
+                        )));
+                    }
+                    swap.counterparty_output_hash = Some(output_hash);
+                    swap.updated_at = now;
+                    swap
+                },
+                None => AtomicSwap {
+                    swap_id,
+                    role: AtomicSwapRole::Participant,
+                    state: AtomicSwapState::Proposed,
+                    counterparty: source,
+                    amount: MicroMinotari::from(locked.amount),
+                    hash_lock,
+                    pre_image: None,
+                    lock_height: locked.lock_height,
+                    lock_tx_id: None,
+                    lock_output_hash: None,
+                    counterparty_output_hash: Some(output_hash),
+                    settle_tx_id: None,
+                    message: locked.message,
+                    created_at: now,
+                    updated_at: now,
+                },
+            }
+        },
+        Some(proto::atomic_swap_message::Message::Redeemed(redeemed)) => {
+            let mut swap = existing.ok_or(TransactionServiceError::AtomicSwapNotFound(swap_id))?;
+            let pre_image = PublicKey::from_canonical_bytes(&redeemed.pre_image)
+                .map_err(|e| TransactionServiceError::AtomicSwapError(e.to_string()))?;
+            if hash_lock_for(&pre_image) != swap.hash_lock {
+                return Err(TransactionServiceError::AtomicSwapError(format!(
+                    "Pre-image for swap {} does not match the hash lock",
+                    swap_id
+                )));
+            }
+            swap.pre_image = Some(pre_image);
+            if swap.state == AtomicSwapState::Locked {
+                swap.state = AtomicSwapState::CounterpartyRedeemed;
+            }
+            swap.updated_at = now;
+            swap
+        },
+        Some(proto::atomic_swap_message::Message::Refunded(_)) => {
+            let mut swap = existing.ok_or(TransactionServiceError::AtomicSwapNotFound(swap_id))?;
+            if swap.state == AtomicSwapState::Proposed {
+                swap.state = AtomicSwapState::CounterpartyRefunded;
+            }
+            swap.updated_at = now;
+            swap
+        },
+        None => {
+            return Err(TransactionServiceError::InvalidMessageError(
+                "AtomicSwapMessage has no content".to_string(),
+            ))
+        },
+    };
+
+    debug!(
+        target: LOG_TARGET,
+        "Atomic swap {} with {} is now {}", swap.swap_id, swap.counterparty, swap.state
+    );
+    db.upsert_atomic_swap(swap.clone())?;
+    Ok(swap)
+}
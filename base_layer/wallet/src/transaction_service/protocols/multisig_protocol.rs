@@ -0,0 +1,1765 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, convert::TryFrom, sync::Arc};
+
+use chrono::Utc;
+use futures::{pin_mut, Stream, StreamExt};
+use log::*;
+use rand::{rngs::OsRng, RngCore};
+use tari_common_types::types::{Commitment, FixedHash, PrivateKey, PublicKey, Signature};
+use tari_comms::types::CommsPublicKey;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester},
+};
+use tari_core::transactions::{
+    frost::{
+        aggregate_frost_signature,
+        frost_dkg_transcript_hash,
+        frost_group_public_key,
+        frost_lagrange_coefficient,
+        frost_script_key_tweak,
+        verify_frost_dkg_proof,
+        verify_frost_share,
+        verify_frost_signature,
+        verify_frost_signature_share,
+        FrostSigningTarget,
+    },
+    key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+    transaction_components::{TransactionInputVersion, TransactionOutput, TransactionOutputVersion},
+    transaction_protocol::proto::protocol as proto,
+};
+use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+use tari_p2p::{domain_message::DomainMessage, tari_message::TariMessageType};
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::ByteArray;
+use tokio::sync::Mutex;
+
+use crate::transaction_service::{
+    error::TransactionServiceError,
+    handle::{TransactionEvent, TransactionEventSender},
+    storage::{
+        database::{TransactionBackend, TransactionDatabase},
+        models::{
+            MultisigKey,
+            MultisigParticipant,
+            MultisigSession,
+            MultisigSessionState,
+            MultisigSigner,
+            MultisigSigning,
+            MultisigSigningKind,
+            MultisigSigningState,
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::protocols::multisig";
+
+/// The largest number of participants of a session, which bounds the size of the key generation messages
+pub const MAX_MULTISIG_PARTICIPANTS: usize = 32;
+
+/// The key branches whose keys are the group key of a session, tweaked for every key
+pub const MULTISIG_SCRIPT_KEY_BRANCHES: [TransactionKeyManagerBranch; 2] = [
+    TransactionKeyManagerBranch::ScriptKey,
+    TransactionKeyManagerBranch::CoinbaseScript,
+];
+
+/// The key branches whose keys are joint keys of the signers of a session
+pub const MULTISIG_JOINT_KEY_BRANCHES: [TransactionKeyManagerBranch; 2] = [
+    TransactionKeyManagerBranch::SenderOffset,
+    TransactionKeyManagerBranch::Nonce,
+];
+
+/// Runs the multisig sessions and signings of this wallet. A session generates one group key without a dealer: every
+/// participant publishes the commitments to a secret polynomial derived from the `FrostPolynomial` key manager branch
+/// and, once all commitments are in, sends every other participant its share of the polynomial. Once a participant has
+/// the group key, it confirms the transcript of the key generation to the others, and the session is only ready once
+/// all participants confirmed the same transcript. The group key is then fixed, so it can be used as the key of an
+/// address or a script. Any `threshold` participants can then sign for it in two rounds, nonce commitments and
+/// signature shares, and the result is a plain Schnorr signature of the group key, or the script key part of the script
+/// signature of an input locked to a script key of the group. The signing shares never leave the key manager. The
+/// nonces of a signing are random and only ever used for that signing.
+///
+/// The signers of a session also hold the sender offset keys and metadata signature nonces of a wallet whose keys are
+/// held by the session as joint keys, made up of a random part of every signer, and answer for them in a single round.
+/// The transaction service serializes the operations on the sessions of a wallet.
+pub struct MultisigProtocol<TBackend, TKeyManagerInterface> {
+    db: TransactionDatabase<TBackend>,
+    key_manager: TKeyManagerInterface,
+    outbound_message_service: OutboundMessageRequester,
+    own_public_key: CommsPublicKey,
+    event_publisher: TransactionEventSender,
+}
+
+impl<TBackend, TKeyManagerInterface> MultisigProtocol<TBackend, TKeyManagerInterface>
+where
+    TBackend: TransactionBackend + 'static,
+    TKeyManagerInterface: TransactionKeyManagerInterface,
+{
+    pub fn new(
+        db: TransactionDatabase<TBackend>,
+        key_manager: TKeyManagerInterface,
+        outbound_message_service: OutboundMessageRequester,
+        own_public_key: CommsPublicKey,
+        event_publisher: TransactionEventSender,
+    ) -> Self {
+        Self {
+            db,
+            key_manager,
+            outbound_message_service,
+            own_public_key,
+            event_publisher,
+        }
+    }
+
+    pub fn own_public_key(&self) -> &CommsPublicKey {
+        &self.own_public_key
+    }
+
+    /// Proposes a `threshold`-of-n session between this wallet and `cosigners`, publishing the commitments to this
+    /// wallet's key generation polynomial to them
+    pub async fn create_session(
+        &self,
+        threshold: u8,
+        cosigners: Vec<CommsPublicKey>,
+        label: String,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        let mut participants = vec![self.own_public_key.clone()];
+        for cosigner in cosigners {
+            if !participants.contains(&cosigner) {
+                participants.push(cosigner);
+            }
+        }
+        self.join_session(OsRng.next_u64(), threshold, participants, label)
+            .await
+    }
+
+    async fn join_session(
+        &self,
+        session_id: u64,
+        threshold: u8,
+        participants: Vec<CommsPublicKey>,
+        label: String,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        if participants.is_empty() || participants.len() > MAX_MULTISIG_PARTICIPANTS {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "A session needs between 1 and {} participants",
+                MAX_MULTISIG_PARTICIPANTS
+            )));
+        }
+        if threshold == 0 || usize::from(threshold) > participants.len() {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Threshold {} is not possible with {} participants",
+                threshold,
+                participants.len()
+            )));
+        }
+        if participants.iter().collect::<HashSet<_>>().len() != participants.len() {
+            return Err(TransactionServiceError::MultisigError(
+                "Duplicate participants".to_string(),
+            ));
+        }
+
+        let (polynomial_key_id, _) = self
+            .key_manager
+            .get_next_key(TransactionKeyManagerBranch::FrostPolynomial.get_branch_key())
+            .await?;
+        let now = Utc::now().naive_utc();
+        let mut session = MultisigSession {
+            session_id,
+            threshold,
+            participants: participants
+                .iter()
+                .map(|public_key| MultisigParticipant {
+                    public_key: public_key.clone(),
+                    coefficient_commitments: None,
+                    share_id: None,
+                    confirmation: None,
+                })
+                .collect(),
+            polynomial_key_id,
+            signing_share_id: None,
+            group_public_key: None,
+            label,
+            state: MultisigSessionState::AwaitingCommitments,
+            created_at: now,
+            updated_at: now,
+        };
+        let own_index = self.own_index(&session)?;
+        let (coefficient_commitments, proof) = self
+            .key_manager
+            .get_frost_dkg_commitments(&session.polynomial_key_id, threshold, own_index, session_id)
+            .await?;
+        let own_public_key = self.own_public_key.clone();
+        if let Some(own) = session.participant_mut(&own_public_key) {
+            own.coefficient_commitments = Some(coefficient_commitments.clone());
+        }
+        self.db.upsert_multisig_session(session.clone())?;
+
+        let message = proto::multisig_key_share_message::Message::Commitments(proto::MultisigDkgCommitments {
+            coefficient_commitments: coefficient_commitments.iter().map(|c| c.to_vec()).collect(),
+            proof: Some(proof.into()),
+        });
+        for participant in &participants {
+            if participant != &self.own_public_key {
+                self.send_key_share_message(&session, participant, message.clone());
+            }
+        }
+        self.advance_session(session).await
+    }
+
+    /// Handles a key generation round of a session. Sessions hold no funds, so a session this wallet is invited to is
+    /// joined straight away; every signing with it still needs this wallet's approval.
+    pub async fn handle_key_share_message(
+        &self,
+        source: CommsPublicKey,
+        message: proto::MultisigKeyShareMessage,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        let session_id = message.session_id;
+        let threshold = u8::try_from(message.threshold)
+            .map_err(|_| TransactionServiceError::MultisigError(format!("Invalid threshold {}", message.threshold)))?;
+        let participants = message
+            .participants
+            .iter()
+            .map(|p| PublicKey::from_canonical_bytes(p))
+            .collect::<Result<Vec<_>, _>>()?;
+        if !participants.contains(&source) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "{} is not a participant of session {}",
+                source, session_id
+            )));
+        }
+        match message.message {
+            Some(proto::multisig_key_share_message::Message::Commitments(commitments)) => {
+                self.handle_dkg_commitments(source, session_id, threshold, participants, message.label, commitments)
+                    .await
+            },
+            Some(proto::multisig_key_share_message::Message::Share(share)) => {
+                self.handle_dkg_share(source, session_id, threshold, participants, share)
+                    .await
+            },
+            Some(proto::multisig_key_share_message::Message::Confirmation(confirmation)) => {
+                self.handle_dkg_confirmation(source, session_id, threshold, participants, confirmation)
+                    .await
+            },
+            None => Err(TransactionServiceError::InvalidMessageError(
+                "MultisigKeyShareMessage has no content".into(),
+            )),
+        }
+    }
+
+    /// Records the polynomial commitments of a participant
+    async fn handle_dkg_commitments(
+        &self,
+        source: CommsPublicKey,
+        session_id: u64,
+        threshold: u8,
+        participants: Vec<CommsPublicKey>,
+        label: String,
+        message: proto::MultisigDkgCommitments,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        let source_index = participants
+            .iter()
+            .position(|p| p == &source)
+            .and_then(|i| u16::try_from(i + 1).ok())
+            .ok_or_else(|| {
+                TransactionServiceError::MultisigError(format!(
+                    "{} is not a participant of session {}",
+                    source, session_id
+                ))
+            })?;
+        let coefficient_commitments = message
+            .coefficient_commitments
+            .iter()
+            .map(|c| PublicKey::from_canonical_bytes(c))
+            .collect::<Result<Vec<_>, _>>()?;
+        if coefficient_commitments.len() != usize::from(threshold) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "{} sent {} commitments for a threshold of {}",
+                source,
+                coefficient_commitments.len(),
+                threshold
+            )));
+        }
+        let proof = message
+            .proof
+            .ok_or_else(|| TransactionServiceError::MultisigError("Commitments have no proof".to_string()))
+            .and_then(|proof| Signature::try_from(proof).map_err(TransactionServiceError::MultisigError))?;
+        if !verify_frost_dkg_proof(source_index, session_id, &coefficient_commitments, &proof) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Invalid proof of possession from {} for session {}",
+                source, session_id
+            )));
+        }
+
+        let mut session = match self.db.fetch_multisig_session(session_id)? {
+            Some(session) => {
+                check_session_parameters(&session, &source, threshold, &participants)?;
+                session
+            },
+            None => self.join_session(session_id, threshold, participants, label).await?,
+        };
+
+        let participant = session.participant_mut(&source).ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!("{} is not a participant of session {}", source, session_id))
+        })?;
+        match participant.coefficient_commitments.as_ref() {
+            Some(known) if known != &coefficient_commitments => {
+                return Err(TransactionServiceError::MultisigError(format!(
+                    "{} changed its commitments for session {}",
+                    source, session_id
+                )));
+            },
+            Some(_) => return Ok(session),
+            None => participant.coefficient_commitments = Some(coefficient_commitments),
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Multisig session {} received the commitments of {}", session_id, source
+        );
+        self.db.upsert_multisig_session(session.clone())?;
+        self.advance_session(session).await
+    }
+
+    /// Records the share of a participant's polynomial for this wallet, imported into the key manager
+    async fn handle_dkg_share(
+        &self,
+        source: CommsPublicKey,
+        session_id: u64,
+        threshold: u8,
+        participants: Vec<CommsPublicKey>,
+        message: proto::MultisigDkgShare,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        let mut session = self
+            .db
+            .fetch_multisig_session(session_id)?
+            .ok_or(TransactionServiceError::MultisigSessionNotFound(session_id))?;
+        check_session_parameters(&session, &source, threshold, &participants)?;
+        if source == self.own_public_key {
+            return Err(TransactionServiceError::MultisigError(
+                "A wallet does not send shares to itself".to_string(),
+            ));
+        }
+        let own_index = self.own_index(&session)?;
+        let share = PrivateKey::from_canonical_bytes(&message.share)?;
+        let participant = session.participant_mut(&source).ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!("{} is not a participant of session {}", source, session_id))
+        })?;
+        if participant.share_id.is_some() {
+            return Ok(session);
+        }
+        if let Some(commitments) = participant.coefficient_commitments.as_ref() {
+            if !verify_frost_share(&PublicKey::from_secret_key(&share), commitments, own_index) {
+                return Err(TransactionServiceError::MultisigError(format!(
+                    "The share of {} for session {} does not match its commitments",
+                    source, session_id
+                )));
+            }
+        }
+        participant.share_id = Some(self.key_manager.import_key(share).await?);
+        debug!(
+            target: LOG_TARGET,
+            "Multisig session {} received the share of {}", session_id, source
+        );
+        self.db.upsert_multisig_session(session.clone())?;
+        self.advance_session(session).await
+    }
+
+    /// Records the transcript hash a participant confirmed
+    async fn handle_dkg_confirmation(
+        &self,
+        source: CommsPublicKey,
+        session_id: u64,
+        threshold: u8,
+        participants: Vec<CommsPublicKey>,
+        message: proto::MultisigDkgConfirmation,
+    ) -> Result<MultisigSession, TransactionServiceError> {
+        let mut session = self
+            .db
+            .fetch_multisig_session(session_id)?
+            .ok_or(TransactionServiceError::MultisigSessionNotFound(session_id))?;
+        check_session_parameters(&session, &source, threshold, &participants)?;
+        let transcript_hash = hash_from_bytes(&message.transcript_hash)?;
+        let participant = session.participant_mut(&source).ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!("{} is not a participant of session {}", source, session_id))
+        })?;
+        match participant.confirmation.as_ref() {
+            Some(known) if known != &transcript_hash => {
+                return Err(TransactionServiceError::MultisigError(format!(
+                    "{} confirmed two different transcripts for session {}",
+                    source, session_id
+                )));
+            },
+            Some(_) => return Ok(session),
+            None => participant.confirmation = Some(transcript_hash),
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Multisig session {} received the confirmation of {}", session_id, source
+        );
+        self.db.upsert_multisig_session(session.clone())?;
+        self.advance_session(session).await
+    }
+
+    /// Moves the key generation through as many rounds as the messages received so far allow
+    async fn advance_session(&self, mut session: MultisigSession) -> Result<MultisigSession, TransactionServiceError> {
+        let own_index = self.own_index(&session)?;
+        loop {
+            match session.state {
+                MultisigSessionState::AwaitingCommitments
+                    if session.participants.iter().all(|p| p.coefficient_commitments.is_some()) =>
+                {
+                    for (i, participant) in session.participants.iter().enumerate() {
+                        if participant.public_key == self.own_public_key {
+                            continue;
+                        }
+                        let recipient = u16::try_from(i + 1)
+                            .map_err(|_| TransactionServiceError::MultisigError("Too many participants".to_string()))?;
+                        let share = self
+                            .key_manager
+                            .get_frost_dkg_share(&session.polynomial_key_id, session.threshold, recipient)
+                            .await?;
+                        self.send_key_share_message(
+                            &session,
+                            &participant.public_key,
+                            proto::multisig_key_share_message::Message::Share(proto::MultisigDkgShare {
+                                share: share.to_vec(),
+                            }),
+                        );
+                    }
+                    session.state = MultisigSessionState::AwaitingShares;
+                },
+                MultisigSessionState::AwaitingShares
+                    if session
+                        .participants
+                        .iter()
+                        .all(|p| p.public_key == self.own_public_key || p.share_id.is_some()) =>
+                {
+                    // Shares that arrived before the sender's commitments have not been checked yet
+                    let mut share_ids = Vec::with_capacity(session.participants.len());
+                    let mut invalid_sender = None;
+                    for participant in &session.participants {
+                        if let (Some(share_id), Some(commitments)) = (
+                            participant.share_id.as_ref(),
+                            participant.coefficient_commitments.as_ref(),
+                        ) {
+                            let share_public_key = self.key_manager.get_public_key_at_key_id(share_id).await?;
+                            if !verify_frost_share(&share_public_key, commitments, own_index) {
+                                invalid_sender = Some(participant.public_key.clone());
+                                break;
+                            }
+                            share_ids.push(share_id.clone());
+                        }
+                    }
+                    if let Some(invalid_sender) = invalid_sender {
+                        if let Some(participant) = session.participant_mut(&invalid_sender) {
+                            participant.share_id = None;
+                        }
+                        self.db.upsert_multisig_session(session.clone())?;
+                        return Err(TransactionServiceError::MultisigError(format!(
+                            "The share of {} for session {} does not match its commitments",
+                            invalid_sender, session.session_id
+                        )));
+                    }
+                    let signing_share_id = self
+                        .key_manager
+                        .import_frost_signing_share(
+                            &session.polynomial_key_id,
+                            session.threshold,
+                            own_index,
+                            &share_ids,
+                        )
+                        .await?;
+                    let group_public_key = frost_group_public_key(
+                        session
+                            .participants
+                            .iter()
+                            .filter_map(|p| p.coefficient_commitments.as_deref()),
+                    );
+                    let signing_share_public_key = self.key_manager.get_public_key_at_key_id(&signing_share_id).await?;
+                    if session.verification_share(&self.own_public_key).as_ref() != Some(&signing_share_public_key) {
+                        return Err(TransactionServiceError::MultisigError(format!(
+                            "The signing share of session {} does not match the commitments",
+                            session.session_id
+                        )));
+                    }
+                    session.signing_share_id = Some(signing_share_id);
+                    session.group_public_key = Some(group_public_key);
+
+                    let transcript_hash = transcript_hash(&session)?;
+                    let own_public_key = self.own_public_key.clone();
+                    if let Some(own) = session.participant_mut(&own_public_key) {
+                        own.confirmation = Some(transcript_hash);
+                    }
+                    for participant in &session.participants {
+                        if participant.public_key != self.own_public_key {
+                            self.send_key_share_message(
+                                &session,
+                                &participant.public_key,
+                                proto::multisig_key_share_message::Message::Confirmation(
+                                    proto::MultisigDkgConfirmation {
+                                        transcript_hash: transcript_hash.to_vec(),
+                                    },
+                                ),
+                            );
+                        }
+                    }
+                    session.state = MultisigSessionState::AwaitingConfirmations;
+                },
+                MultisigSessionState::AwaitingConfirmations
+                    if session.participants.iter().all(|p| p.confirmation.is_some()) =>
+                {
+                    let own_confirmation = session
+                        .participant(&self.own_public_key)
+                        .and_then(|p| p.confirmation.clone());
+                    if let Some(participant) = session.participants.iter().find(|p| p.confirmation != own_confirmation)
+                    {
+                        // A participant sent different commitments to different participants, so the participants do
+                        // not agree on the group key
+                        let error = TransactionServiceError::MultisigError(format!(
+                            "{} confirmed a different key generation transcript for session {}, the session cannot be \
+                             used",
+                            participant.public_key, session.session_id
+                        ));
+                        session.updated_at = Utc::now().naive_utc();
+                        self.db.upsert_multisig_session(session.clone())?;
+                        return Err(error);
+                    }
+                    session.state = MultisigSessionState::Ready;
+                    info!(
+                        target: LOG_TARGET,
+                        "Multisig session {} is ready", session.session_id
+                    );
+                },
+                _ => break,
+            }
+        }
+        session.updated_at = Utc::now().naive_utc();
+        self.db.upsert_multisig_session(session.clone())?;
+        self.publish_session(&session);
+        Ok(session)
+    }
+
+    /// Asks `cosigners` to sign `message` for the group key together with this wallet. With this wallet they must be
+    /// exactly `threshold` participants of the session.
+    pub async fn request_signing(
+        &self,
+        session_id: u64,
+        cosigners: Vec<CommsPublicKey>,
+        message: FixedHash,
+        description: String,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        self.request(
+            session_id,
+            cosigners,
+            MultisigSigningKind::Message,
+            message,
+            description,
+        )
+        .await
+    }
+
+    /// Asks `cosigners` to take part in an operation on a key of a wallet whose keys are held by the session. Script
+    /// signatures wait for the cosigners' approval, the other kinds are answered straight away.
+    pub async fn request_custody_signing(
+        &self,
+        session_id: u64,
+        cosigners: Vec<CommsPublicKey>,
+        kind: MultisigSigningKind,
+        description: String,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        self.request(session_id, cosigners, kind, FixedHash::zero(), description)
+            .await
+    }
+
+    async fn request(
+        &self,
+        session_id: u64,
+        cosigners: Vec<CommsPublicKey>,
+        kind: MultisigSigningKind,
+        message: FixedHash,
+        description: String,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        let mut signers = vec![self.own_public_key.clone()];
+        for cosigner in cosigners {
+            if !signers.contains(&cosigner) {
+                signers.push(cosigner);
+            }
+        }
+        let session = self.fetch_ready_session(session_id)?;
+        check_kind(&kind)?;
+        let signers = signers_of(&session, signers)?;
+
+        let now = Utc::now().naive_utc();
+        let mut signing = MultisigSigning {
+            signing_id: OsRng.next_u64(),
+            session_id,
+            kind,
+            message,
+            signers,
+            hiding_nonce_id: None,
+            binding_nonce_id: None,
+            secret_key_id: None,
+            description,
+            state: MultisigSigningState::PendingApproval,
+            signature: None,
+            joint_public_key: None,
+            script_offset: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let nonce_commitments = if signing.kind.is_group_signature() {
+            Some(self.commit_own_nonces(&mut signing).await?)
+        } else {
+            // The requester keeps its own contribution
+            self.contribute(&session, &mut signing).await?;
+            signing.state = MultisigSigningState::AwaitingCosignerShares;
+            None
+        };
+        // The signing is stored before the cosigners are asked, so that their answers always find it
+        let signing = self.advance_signing(signing).await?;
+
+        let request = proto::MultisigSigningRequest {
+            message: signing.message.to_vec(),
+            signers: signing.signers.iter().map(|s| s.public_key.to_vec()).collect(),
+            description: signing.description.clone(),
+            nonce_commitments,
+            kind: kind_to_proto(&signing.kind),
+        };
+        self.send_to_cosigners(&signing, proto::multisig_signing_message::Message::Request(request));
+        Ok(signing)
+    }
+
+    /// Takes part in a signing requested by a cosigner
+    pub async fn approve_signing(&self, signing_id: u64) -> Result<MultisigSigning, TransactionServiceError> {
+        let mut signing = self
+            .db
+            .fetch_multisig_signing(signing_id)?
+            .ok_or(TransactionServiceError::MultisigSigningNotFound(signing_id))?;
+        if signing.state != MultisigSigningState::PendingApproval {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Signing {} is {} and cannot be approved",
+                signing_id, signing.state
+            )));
+        }
+        let nonce_commitments = self.commit_own_nonces(&mut signing).await?;
+        let signing = self.advance_signing(signing).await?;
+        self.send_to_cosigners(
+            &signing,
+            proto::multisig_signing_message::Message::NonceCommitments(nonce_commitments),
+        );
+        Ok(signing)
+    }
+
+    /// Stops this wallet from taking part in a signing. The cosigners are not told, they will keep waiting for this
+    /// wallet.
+    pub fn cancel_signing(&self, signing_id: u64) -> Result<MultisigSigning, TransactionServiceError> {
+        let mut signing = self
+            .db
+            .fetch_multisig_signing(signing_id)?
+            .ok_or(TransactionServiceError::MultisigSigningNotFound(signing_id))?;
+        if matches!(
+            signing.state,
+            MultisigSigningState::Complete | MultisigSigningState::Used
+        ) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Signing {} is already complete",
+                signing_id
+            )));
+        }
+        signing.state = MultisigSigningState::Cancelled;
+        signing.updated_at = Utc::now().naive_utc();
+        self.db.upsert_multisig_signing(signing.clone())?;
+        self.publish_signing(&signing);
+        Ok(signing)
+    }
+
+    /// The joint key `key` of `requester`, unless its signing was cancelled
+    pub fn find_joint_key(
+        &self,
+        session_id: u64,
+        requester: &CommsPublicKey,
+        key: &MultisigKey,
+    ) -> Result<Option<MultisigSigning>, TransactionServiceError> {
+        Ok(self
+            .db
+            .fetch_multisig_signings(Some(session_id))?
+            .into_iter()
+            .find(|s| {
+                s.requester() == Some(requester) &&
+                    s.state != MultisigSigningState::Cancelled &&
+                    matches!(&s.kind, MultisigSigningKind::JointKey { key: k } if k == key)
+            }))
+    }
+
+    pub async fn handle_signing_message(
+        &self,
+        source: CommsPublicKey,
+        message: proto::MultisigSigningMessage,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        let signing_id = message.signing_id;
+        let content = message.message.ok_or_else(|| {
+            TransactionServiceError::InvalidMessageError("MultisigSigningMessage has no content".into())
+        })?;
+        if let proto::multisig_signing_message::Message::Request(request) = content {
+            return self
+                .handle_signing_request(source, message.session_id, signing_id, request)
+                .await;
+        }
+
+        let mut signing = self
+            .db
+            .fetch_multisig_signing(signing_id)?
+            .filter(|s| s.session_id == message.session_id)
+            .ok_or(TransactionServiceError::MultisigSigningNotFound(signing_id))?;
+        if matches!(
+            signing.state,
+            MultisigSigningState::Complete | MultisigSigningState::Cancelled | MultisigSigningState::Used
+        ) {
+            return Ok(signing);
+        }
+        let signer = signing.signer_mut(&source).ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!("{} is not a signer of signing {}", source, signing_id))
+        })?;
+        match content {
+            proto::multisig_signing_message::Message::NonceCommitments(commitments) => {
+                if signer.hiding_nonce.is_some() {
+                    return Err(TransactionServiceError::MultisigError(format!(
+                        "{} already published its nonce commitments for signing {}",
+                        source, signing_id
+                    )));
+                }
+                set_nonce_commitments(signer, &commitments)?;
+            },
+            proto::multisig_signing_message::Message::SignatureShare(share) => {
+                if signer.signature_share.is_some() {
+                    return Err(TransactionServiceError::MultisigError(format!(
+                        "{} already sent its signature share for signing {}",
+                        source, signing_id
+                    )));
+                }
+                signer.signature_share = Some(PrivateKey::from_canonical_bytes(&share.signature_share)?);
+            },
+            proto::multisig_signing_message::Message::JointKeyPart(part) => {
+                if signer.joint_key_part.is_some() {
+                    return Err(TransactionServiceError::MultisigError(format!(
+                        "{} already sent its joint key part for signing {}",
+                        source, signing_id
+                    )));
+                }
+                let public_key = PublicKey::from_canonical_bytes(&part.public_key)?;
+                let proof = part
+                    .proof
+                    .ok_or_else(|| TransactionServiceError::MultisigError("Joint key part has no proof".to_string()))
+                    .and_then(|proof| Signature::try_from(proof).map_err(TransactionServiceError::MultisigError))?;
+                // Stops a signer from choosing its part as a function of the others' and taking control of the key
+                if !verify_frost_dkg_proof(signer.participant, signing_id, &[public_key.clone()], &proof) {
+                    return Err(TransactionServiceError::MultisigError(format!(
+                        "Invalid proof of possession from {} for signing {}",
+                        source, signing_id
+                    )));
+                }
+                signer.joint_key_part = Some(public_key);
+            },
+            proto::multisig_signing_message::Message::Request(_) => unreachable!("requests are handled above"),
+        }
+        self.db.upsert_multisig_signing(signing.clone())?;
+        self.advance_signing(signing).await
+    }
+
+    async fn handle_signing_request(
+        &self,
+        source: CommsPublicKey,
+        session_id: u64,
+        signing_id: u64,
+        request: proto::MultisigSigningRequest,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        if self.db.fetch_multisig_signing(signing_id)?.is_some() {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Signing {} already exists",
+                signing_id
+            )));
+        }
+        let session = self.fetch_ready_session(session_id)?;
+        let signers = request
+            .signers
+            .iter()
+            .map(|s| PublicKey::from_canonical_bytes(s))
+            .collect::<Result<Vec<_>, _>>()?;
+        if signers.first() != Some(&source) || !signers.contains(&self.own_public_key) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Signing {} from {} must list the requester first and include this wallet",
+                signing_id, source
+            )));
+        }
+        let signers = signers_of(&session, signers)?;
+        let kind = kind_from_proto(request.kind)?;
+        check_kind(&kind)?;
+        let message = hash_from_bytes(&request.message)?;
+
+        let now = Utc::now().naive_utc();
+        let mut signing = MultisigSigning {
+            signing_id,
+            session_id,
+            kind,
+            message,
+            signers,
+            hiding_nonce_id: None,
+            binding_nonce_id: None,
+            secret_key_id: None,
+            description: request.description,
+            state: MultisigSigningState::PendingApproval,
+            signature: None,
+            joint_public_key: None,
+            script_offset: None,
+            created_at: now,
+            updated_at: now,
+        };
+        if !signing.kind.needs_approval() {
+            if let MultisigSigningKind::JointKey { key } = &signing.kind {
+                // The requester asks again when it gave up waiting for a signer. A key that was used up is never
+                // made again.
+                if let Some(mut previous) = self.find_joint_key(session_id, &source, key)? {
+                    if previous.state == MultisigSigningState::Used {
+                        return Err(TransactionServiceError::MultisigError(format!(
+                            "{} already used joint key {}",
+                            source, key
+                        )));
+                    }
+                    previous.state = MultisigSigningState::Cancelled;
+                    previous.updated_at = Utc::now().naive_utc();
+                    self.db.upsert_multisig_signing(previous.clone())?;
+                    self.publish_signing(&previous);
+                }
+            }
+            let contribution = self.contribute(&session, &mut signing).await?;
+            // Only the requester needs the result
+            signing.state = MultisigSigningState::Complete;
+            self.db.upsert_multisig_signing(signing.clone())?;
+            self.publish_signing(&signing);
+            self.send(
+                &source,
+                TariMessageType::MultisigSigning,
+                proto::MultisigSigningMessage {
+                    session_id,
+                    signing_id,
+                    message: Some(contribution),
+                },
+            );
+            debug!(
+                target: LOG_TARGET,
+                "Answered {} signing {} of {} with multisig session {}", signing.kind, signing_id, source, session_id
+            );
+            return Ok(signing);
+        }
+
+        let nonce_commitments = request
+            .nonce_commitments
+            .ok_or_else(|| TransactionServiceError::MultisigError("Request has no nonce commitments".to_string()))?;
+        if let Some(requester) = signing.signer_mut(&source) {
+            set_nonce_commitments(requester, &nonce_commitments)?;
+        }
+        info!(
+            target: LOG_TARGET,
+            "{} requested {} signing {} with multisig session {}, awaiting approval",
+            source,
+            signing.kind,
+            signing_id,
+            session_id
+        );
+        self.db.upsert_multisig_signing(signing.clone())?;
+        self.publish_signing(&signing);
+        Ok(signing)
+    }
+
+    /// Picks this wallet's hiding and binding nonces for the signing and returns the commitments to send to the
+    /// cosigners. The nonces are random rather than derived from the seed, so that a wallet restored from its seed
+    /// can never use a nonce twice.
+    async fn commit_own_nonces(
+        &self,
+        signing: &mut MultisigSigning,
+    ) -> Result<proto::MultisigNonceCommitments, TransactionServiceError> {
+        let hiding_nonce = PrivateKey::random(&mut OsRng);
+        let binding_nonce = PrivateKey::random(&mut OsRng);
+        let hiding_commitment = PublicKey::from_secret_key(&hiding_nonce);
+        let binding_commitment = PublicKey::from_secret_key(&binding_nonce);
+        let hiding_nonce_id = self.key_manager.import_key(hiding_nonce).await?;
+        let binding_nonce_id = self.key_manager.import_key(binding_nonce).await?;
+        let commitments = proto::MultisigNonceCommitments {
+            hiding: hiding_commitment.to_vec(),
+            binding: binding_commitment.to_vec(),
+        };
+        let own_public_key = self.own_public_key.clone();
+        let signer = signing
+            .signer_mut(&own_public_key)
+            .ok_or_else(|| TransactionServiceError::MultisigError("This wallet is not a signer".to_string()))?;
+        signer.hiding_nonce = Some(hiding_commitment);
+        signer.binding_nonce = Some(binding_commitment);
+        signing.hiding_nonce_id = Some(hiding_nonce_id);
+        signing.binding_nonce_id = Some(binding_nonce_id);
+        signing.state = MultisigSigningState::AwaitingCosignerCommitments;
+        Ok(commitments)
+    }
+
+    /// Makes this wallet's contribution to a single round signing, using up the joint keys it is made with
+    async fn contribute(
+        &self,
+        session: &MultisigSession,
+        signing: &mut MultisigSigning,
+    ) -> Result<proto::multisig_signing_message::Message, TransactionServiceError> {
+        let own_index = self.own_index(session)?;
+        let own_public_key = self.own_public_key.clone();
+        let requester = signing
+            .requester()
+            .cloned()
+            .ok_or_else(|| TransactionServiceError::MultisigError("A signing has no signers".to_string()))?;
+        match signing.kind.clone() {
+            MultisigSigningKind::JointKey { .. } => {
+                // A random part rather than one derived from the seed, so that a restored wallet never reuses a part
+                let key_id = self.key_manager.import_key(PrivateKey::random(&mut OsRng)).await?;
+                let (commitments, proof) = self
+                    .key_manager
+                    .get_frost_dkg_commitments(&key_id, 1, own_index, signing.signing_id)
+                    .await?;
+                let public_key = commitments.into_iter().next().ok_or_else(|| {
+                    TransactionServiceError::MultisigError("The joint key part has no commitment".to_string())
+                })?;
+                signing.secret_key_id = Some(key_id);
+                if let Some(signer) = signing.signer_mut(&own_public_key) {
+                    signer.joint_key_part = Some(public_key.clone());
+                }
+                Ok(proto::multisig_signing_message::Message::JointKeyPart(
+                    proto::MultisigJointKeyPart {
+                        public_key: public_key.to_vec(),
+                        proof: Some(proof.into()),
+                    },
+                ))
+            },
+            MultisigSigningKind::MetadataSignature {
+                nonce,
+                sender_offset_key,
+                txo_version,
+                commitment,
+                ephemeral_commitment,
+                metadata_signature_message,
+                joint_nonce,
+                joint_sender_offset_key,
+            } => {
+                // A sender offset key may already have been used for the script offset, a nonce is only used once
+                let nonce_key = self.usable_joint_key(signing, &requester, &nonce, false)?;
+                let sender_offset = self.usable_joint_key(signing, &requester, &sender_offset_key, true)?;
+                let share = self
+                    .key_manager
+                    .get_joint_sender_metadata_signature_share(
+                        &secret_key_id(&nonce_key)?,
+                        &secret_key_id(&sender_offset)?,
+                        &joint_nonce,
+                        &joint_sender_offset_key,
+                        &txo_version,
+                        &commitment,
+                        &ephemeral_commitment,
+                        &metadata_signature_message,
+                    )
+                    .await?;
+                self.use_up(nonce_key)?;
+                Ok(self.own_signature_share(signing, share))
+            },
+            MultisigSigningKind::ScriptOffset {
+                script_keys,
+                sender_offset_keys,
+            } => {
+                let mut joint_keys = Vec::with_capacity(sender_offset_keys.len());
+                for key in &sender_offset_keys {
+                    joint_keys.push(self.usable_joint_key(signing, &requester, key, false)?);
+                }
+                let secret_key_ids = joint_keys.iter().map(secret_key_id).collect::<Result<Vec<_>, _>>()?;
+                let (signing_share_id, _) = ready_keys(session)?;
+                let share = self
+                    .key_manager
+                    .get_frost_script_offset_share(
+                        &signing_share_id,
+                        own_index,
+                        &signing.signer_indices(),
+                        script_keys.len() as u64,
+                        &secret_key_ids,
+                    )
+                    .await?;
+                // Another offset with the same sender offset keys would reveal this wallet's signing share
+                for joint_key in joint_keys {
+                    self.use_up(joint_key)?;
+                }
+                Ok(self.own_signature_share(signing, share))
+            },
+            kind => Err(TransactionServiceError::MultisigError(format!(
+                "{} signings take two rounds",
+                kind
+            ))),
+        }
+    }
+
+    fn own_signature_share(
+        &self,
+        signing: &mut MultisigSigning,
+        share: PrivateKey,
+    ) -> proto::multisig_signing_message::Message {
+        let own_public_key = self.own_public_key.clone();
+        let message = proto::MultisigSignatureShare {
+            signature_share: share.to_vec(),
+        };
+        if let Some(signer) = signing.signer_mut(&own_public_key) {
+            signer.signature_share = Some(share);
+        }
+        proto::multisig_signing_message::Message::SignatureShare(message)
+    }
+
+    /// The joint key of `requester` that a signing is made with. It must have been made by the same signers, and must
+    /// not be used up unless `allow_used` is set.
+    fn usable_joint_key(
+        &self,
+        signing: &MultisigSigning,
+        requester: &CommsPublicKey,
+        key: &MultisigKey,
+        allow_used: bool,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        let joint_key = self
+            .find_joint_key(signing.session_id, requester, key)?
+            .ok_or_else(|| TransactionServiceError::MultisigError(format!("{} has no joint key {}", requester, key)))?;
+        let usable = match joint_key.state {
+            MultisigSigningState::Complete => true,
+            MultisigSigningState::Used => allow_used,
+            _ => false,
+        };
+        if !usable {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Joint key {} of {} is {}",
+                key, requester, joint_key.state
+            )));
+        }
+        let key_signers = joint_key.signers.iter().map(|s| &s.public_key);
+        if !key_signers.eq(signing.signers.iter().map(|s| &s.public_key)) {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Joint key {} of {} was made by other signers",
+                key, requester
+            )));
+        }
+        Ok(joint_key)
+    }
+
+    fn use_up(&self, mut joint_key: MultisigSigning) -> Result<(), TransactionServiceError> {
+        joint_key.state = MultisigSigningState::Used;
+        joint_key.updated_at = Utc::now().naive_utc();
+        self.db.upsert_multisig_signing(joint_key.clone())?;
+        self.publish_signing(&joint_key);
+        Ok(())
+    }
+
+    /// Moves the signing through as many rounds as the contributions received so far allow
+    async fn advance_signing(&self, mut signing: MultisigSigning) -> Result<MultisigSigning, TransactionServiceError> {
+        let own_public_key = self.own_public_key.clone();
+        loop {
+            match (signing.state, signing.nonce_commitments()) {
+                (MultisigSigningState::AwaitingCosignerCommitments, Some(commitments)) => {
+                    let session = self.fetch_ready_session(signing.session_id)?;
+                    let (signing_share_id, group_public_key) = ready_keys(&session)?;
+                    let (hiding_nonce_id, binding_nonce_id) =
+                        match (signing.hiding_nonce_id.clone(), signing.binding_nonce_id.clone()) {
+                            (Some(hiding), Some(binding)) => (hiding, binding),
+                            _ => {
+                                return Err(TransactionServiceError::MultisigError(
+                                    "Own nonces are missing".to_string(),
+                                ))
+                            },
+                        };
+                    let own_index = self.own_index(&session)?;
+                    let target = signing_target(&signing, &group_public_key)?;
+                    let signature_share = self
+                        .key_manager
+                        .get_frost_signature_share(
+                            &signing_share_id,
+                            &hiding_nonce_id,
+                            &binding_nonce_id,
+                            own_index,
+                            &group_public_key,
+                            &target,
+                            &commitments,
+                        )
+                        .await?;
+                    if let Some(signer) = signing.signer_mut(&own_public_key) {
+                        signer.signature_share = Some(signature_share.clone());
+                    }
+                    self.send_to_cosigners(
+                        &signing,
+                        proto::multisig_signing_message::Message::SignatureShare(proto::MultisigSignatureShare {
+                            signature_share: signature_share.to_vec(),
+                        }),
+                    );
+                    signing.state = MultisigSigningState::AwaitingCosignerShares;
+                },
+                (MultisigSigningState::AwaitingCosignerShares, Some(commitments))
+                    if signing.signers.iter().all(|s| s.signature_share.is_some()) =>
+                {
+                    let session = self.fetch_ready_session(signing.session_id)?;
+                    let (_, group_public_key) = ready_keys(&session)?;
+                    let target = signing_target(&signing, &group_public_key)?;
+                    let mut invalid_signer = None;
+                    for signer in &signing.signers {
+                        let valid = match (
+                            session.verification_share(&signer.public_key),
+                            signer.signature_share.as_ref(),
+                        ) {
+                            (Some(verification_share), Some(signature_share)) => verify_frost_signature_share(
+                                signature_share,
+                                &verification_share,
+                                signer.participant,
+                                &group_public_key,
+                                &target,
+                                &commitments,
+                            )?,
+                            _ => false,
+                        };
+                        if !valid {
+                            invalid_signer = Some(signer.public_key.clone());
+                            break;
+                        }
+                    }
+                    if let Some(invalid_signer) = invalid_signer {
+                        return self.fail_signing(signing, &invalid_signer);
+                    }
+                    let signature = aggregate_frost_signature(
+                        &group_public_key,
+                        &target,
+                        &commitments,
+                        signing.signers.iter().filter_map(|s| s.signature_share.as_ref()),
+                    )?;
+                    if !verify_frost_signature(&signature, &group_public_key, &target)? {
+                        return Err(TransactionServiceError::MultisigError(format!(
+                            "Group signature for signing {} is invalid",
+                            signing.signing_id
+                        )));
+                    }
+                    signing.signature = Some(signature);
+                    signing.state = MultisigSigningState::Complete;
+                },
+                (MultisigSigningState::AwaitingCosignerShares, None)
+                    if !signing.kind.is_group_signature() &&
+                        signing
+                            .signers
+                            .iter()
+                            .all(|s| s.signature_share.is_some() || s.joint_key_part.is_some()) =>
+                {
+                    if let Some(invalid_signer) = self.combine_contributions(&mut signing)? {
+                        return self.fail_signing(signing, &invalid_signer);
+                    }
+                    signing.state = MultisigSigningState::Complete;
+                },
+                _ => break,
+            }
+        }
+        signing.updated_at = Utc::now().naive_utc();
+        debug!(
+            target: LOG_TARGET,
+            "Multisig signing {} is {}", signing.signing_id, signing.state
+        );
+        self.db.upsert_multisig_signing(signing.clone())?;
+        self.publish_signing(&signing);
+        Ok(signing)
+    }
+
+    /// Checks the contributions of all signers to a single round signing this wallet requested and adds them up.
+    /// Returns the first signer whose contribution is invalid.
+    fn combine_contributions(
+        &self,
+        signing: &mut MultisigSigning,
+    ) -> Result<Option<CommsPublicKey>, TransactionServiceError> {
+        let session = self.fetch_ready_session(signing.session_id)?;
+        match signing.kind.clone() {
+            MultisigSigningKind::JointKey { .. } => {
+                // The parts were checked against their proofs when they arrived
+                let parts = signing
+                    .signers
+                    .iter()
+                    .map(|s| s.joint_key_part.clone())
+                    .collect::<Option<Vec<_>>>();
+                match parts {
+                    Some(parts) => {
+                        signing.joint_public_key =
+                            Some(parts.iter().fold(PublicKey::default(), |acc, part| &acc + part));
+                        Ok(None)
+                    },
+                    None => Err(TransactionServiceError::MultisigError(format!(
+                        "Joint key parts are missing for signing {}",
+                        signing.signing_id
+                    ))),
+                }
+            },
+            MultisigSigningKind::MetadataSignature {
+                nonce,
+                sender_offset_key,
+                txo_version,
+                commitment,
+                ephemeral_commitment,
+                metadata_signature_message,
+                joint_nonce,
+                joint_sender_offset_key,
+            } => {
+                let nonce_key = self.own_joint_key(signing.session_id, &nonce)?;
+                let sender_offset = self.own_joint_key(signing.session_id, &sender_offset_key)?;
+                let challenge = TransactionOutput::finalize_metadata_signature_challenge(
+                    &txo_version,
+                    &joint_sender_offset_key,
+                    &ephemeral_commitment,
+                    &joint_nonce,
+                    &commitment,
+                    &metadata_signature_message,
+                );
+                let challenge = PrivateKey::from_uniform_bytes(&challenge)
+                    .map_err(|e| TransactionServiceError::MultisigError(e.to_string()))?;
+                let mut total = PrivateKey::default();
+                for signer in &signing.signers {
+                    let expected = match (
+                        joint_key_part(&nonce_key, &signer.public_key),
+                        joint_key_part(&sender_offset, &signer.public_key),
+                    ) {
+                        (Some(nonce_part), Some(sender_offset_part)) => {
+                            Some(&nonce_part + &(&challenge * &sender_offset_part))
+                        },
+                        _ => None,
+                    };
+                    match (expected, signer.signature_share.as_ref()) {
+                        (Some(expected), Some(share)) if PublicKey::from_secret_key(share) == expected => {
+                            total = &total + share;
+                        },
+                        _ => return Ok(Some(signer.public_key.clone())),
+                    }
+                }
+                signing.signature = Some(Signature::new(joint_nonce, total));
+                Ok(None)
+            },
+            MultisigSigningKind::ScriptOffset {
+                script_keys,
+                sender_offset_keys,
+            } => {
+                let mut joint_keys = Vec::with_capacity(sender_offset_keys.len());
+                for key in &sender_offset_keys {
+                    joint_keys.push(self.own_joint_key(signing.session_id, key)?);
+                }
+                let script_key_count = PrivateKey::from(script_keys.len() as u64);
+                let signer_indices = signing.signer_indices();
+                let mut total = PrivateKey::default();
+                for signer in &signing.signers {
+                    let lagrange_coefficient = frost_lagrange_coefficient(signer.participant, &signer_indices)?;
+                    let expected = session
+                        .verification_share(&signer.public_key)
+                        .and_then(|verification_share| {
+                            joint_keys.iter().try_fold(
+                                &(&lagrange_coefficient * &script_key_count) * &verification_share,
+                                |acc, joint_key| joint_key_part(joint_key, &signer.public_key).map(|part| &acc - &part),
+                            )
+                        });
+                    match (expected, signer.signature_share.as_ref()) {
+                        (Some(expected), Some(share)) if PublicKey::from_secret_key(share) == expected => {
+                            total = &total + share;
+                        },
+                        _ => return Ok(Some(signer.public_key.clone())),
+                    }
+                }
+                signing.script_offset = Some(total);
+                Ok(None)
+            },
+            kind => Err(TransactionServiceError::MultisigError(format!(
+                "{} signings take two rounds",
+                kind
+            ))),
+        }
+    }
+
+    /// A joint key this wallet requested, used up or not
+    fn own_joint_key(&self, session_id: u64, key: &MultisigKey) -> Result<MultisigSigning, TransactionServiceError> {
+        self.find_joint_key(session_id, &self.own_public_key, key)?
+            .filter(|s| s.joint_public_key.is_some())
+            .ok_or_else(|| TransactionServiceError::MultisigError(format!("This wallet has no joint key {}", key)))
+    }
+
+    fn fail_signing(
+        &self,
+        mut signing: MultisigSigning,
+        invalid_signer: &CommsPublicKey,
+    ) -> Result<MultisigSigning, TransactionServiceError> {
+        let error = TransactionServiceError::MultisigError(format!(
+            "Invalid contribution from {} to signing {}",
+            invalid_signer, signing.signing_id
+        ));
+        signing.state = MultisigSigningState::Cancelled;
+        signing.updated_at = Utc::now().naive_utc();
+        self.db.upsert_multisig_signing(signing.clone())?;
+        self.publish_signing(&signing);
+        Err(error)
+    }
+
+    fn own_index(&self, session: &MultisigSession) -> Result<u16, TransactionServiceError> {
+        session.participant_index(&self.own_public_key).ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!(
+                "This wallet is not a participant of session {}",
+                session.session_id
+            ))
+        })
+    }
+
+    fn fetch_ready_session(&self, session_id: u64) -> Result<MultisigSession, TransactionServiceError> {
+        let session = self
+            .db
+            .fetch_multisig_session(session_id)?
+            .ok_or(TransactionServiceError::MultisigSessionNotFound(session_id))?;
+        if session.state != MultisigSessionState::Ready {
+            return Err(TransactionServiceError::MultisigError(format!(
+                "Session {} is still generating its key",
+                session_id
+            )));
+        }
+        Ok(session)
+    }
+
+    fn send_key_share_message(
+        &self,
+        session: &MultisigSession,
+        destination: &CommsPublicKey,
+        message: proto::multisig_key_share_message::Message,
+    ) {
+        let message = proto::MultisigKeyShareMessage {
+            session_id: session.session_id,
+            threshold: u32::from(session.threshold),
+            participants: session.participants.iter().map(|p| p.public_key.to_vec()).collect(),
+            label: session.label.clone(),
+            message: Some(message),
+        };
+        self.send(destination, TariMessageType::MultisigKeyShare, message);
+    }
+
+    fn send_to_cosigners(&self, signing: &MultisigSigning, message: proto::multisig_signing_message::Message) {
+        let message = proto::MultisigSigningMessage {
+            session_id: signing.session_id,
+            signing_id: signing.signing_id,
+            message: Some(message),
+        };
+        for signer in &signing.signers {
+            if signer.public_key != self.own_public_key {
+                self.send(&signer.public_key, TariMessageType::MultisigSigning, message.clone());
+            }
+        }
+    }
+
+    /// Sends a message to a participant both directly and via store and forward, without waiting for delivery. Every
+    /// message is encrypted for its recipient, which keeps the key generation shares private.
+    fn send<T: prost::Message + Send + 'static>(
+        &self,
+        destination: &CommsPublicKey,
+        message_type: TariMessageType,
+        message: T,
+    ) {
+        let mut outbound_message_service = self.outbound_message_service.clone();
+        let destination = destination.clone();
+        tokio::spawn(async move {
+            let message = OutboundDomainMessage::new(&message_type, message);
+            if let Err(e) = outbound_message_service
+                .send_direct_encrypted(
+                    destination.clone(),
+                    message.clone(),
+                    OutboundEncryption::encrypt_for(destination.clone()),
+                    "multisig".to_string(),
+                )
+                .await
+            {
+                warn!(target: LOG_TARGET, "Direct multisig message to {} failed: {}", destination, e);
+            }
+            if let Err(e) = outbound_message_service
+                .closest_broadcast(
+                    destination.clone(),
+                    OutboundEncryption::encrypt_for(destination.clone()),
+                    vec![],
+                    message,
+                )
+                .await
+            {
+                warn!(
+                    target: LOG_TARGET,
+                    "Store and forward multisig message to {} failed: {}", destination, e
+                );
+            }
+        });
+    }
+
+    fn publish_session(&self, session: &MultisigSession) {
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::MultisigSessionUpdated {
+                session_id: session.session_id,
+                state: session.state,
+            }));
+    }
+
+    fn publish_signing(&self, signing: &MultisigSigning) {
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::MultisigSigningUpdated {
+                signing_id: signing.signing_id,
+                state: signing.state,
+            }));
+    }
+}
+
+/// Handles the multisig messages of the wallet's cosigners outside the transaction service's event loop, so that a
+/// key manager whose keys are held by a multisig session can wait for its cosigners while the event loop waits for the
+/// key manager. `multisig_lock` serializes the handling with the multisig requests of the transaction service.
+pub async fn run_multisig_message_handler<TBackend, TKeyManagerInterface, TKeyShareStream, TSigningStream>(
+    protocol: MultisigProtocol<TBackend, TKeyManagerInterface>,
+    multisig_lock: Arc<Mutex<()>>,
+    key_share_stream: TKeyShareStream,
+    signing_stream: TSigningStream,
+    mut shutdown_signal: ShutdownSignal,
+) where
+    TBackend: TransactionBackend + 'static,
+    TKeyManagerInterface: TransactionKeyManagerInterface,
+    TKeyShareStream: Stream<Item = DomainMessage<Result<proto::MultisigKeyShareMessage, prost::DecodeError>>>,
+    TSigningStream: Stream<Item = DomainMessage<Result<proto::MultisigSigningMessage, prost::DecodeError>>>,
+{
+    let key_share_stream = key_share_stream.fuse();
+    pin_mut!(key_share_stream);
+    let signing_stream = signing_stream.fuse();
+    pin_mut!(signing_stream);
+    loop {
+        tokio::select! {
+            Some(msg) = key_share_stream.next() => {
+                let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
+                trace!(target: LOG_TARGET, "Handling Multisig Key Share message, Trace: {}", msg.dht_header.message_tag);
+                let _lock = multisig_lock.lock().await;
+                let result = match inner_msg {
+                    Ok(message) => protocol.handle_key_share_message(origin_public_key, message).await.map(|_| ()),
+                    Err(e) => Err(TransactionServiceError::InvalidMessageError(format!(
+                        "Could not decode MultisigKeyShareMessage: {:?}",
+                        e
+                    ))),
+                };
+                if let Err(e) = result {
+                    warn!(target: LOG_TARGET, "Error handling Multisig Key Share message: {:?}", e);
+                }
+            },
+            Some(msg) = signing_stream.next() => {
+                let (origin_public_key, inner_msg) = msg.clone().into_origin_and_inner();
+                trace!(target: LOG_TARGET, "Handling Multisig Signing message, Trace: {}", msg.dht_header.message_tag);
+                let _lock = multisig_lock.lock().await;
+                let result = match inner_msg {
+                    Ok(message) => protocol.handle_signing_message(origin_public_key, message).await.map(|_| ()),
+                    Err(e) => Err(TransactionServiceError::InvalidMessageError(format!(
+                        "Could not decode MultisigSigningMessage: {:?}",
+                        e
+                    ))),
+                };
+                if let Err(e) = result {
+                    warn!(target: LOG_TARGET, "Error handling Multisig Signing message: {:?}", e);
+                }
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Multisig message handler shutting down because it received the shutdown signal");
+                break;
+            },
+        }
+    }
+}
+
+/// Checks that a message about a known session agrees with the session's parameters
+fn check_session_parameters(
+    session: &MultisigSession,
+    source: &CommsPublicKey,
+    threshold: u8,
+    participants: &[CommsPublicKey],
+) -> Result<(), TransactionServiceError> {
+    let known_participants = session.participants.iter().map(|p| &p.public_key);
+    if session.threshold != threshold || !known_participants.eq(participants.iter()) {
+        return Err(TransactionServiceError::MultisigError(format!(
+            "{} sent different parameters for session {}",
+            source, session.session_id
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that `signers` are `threshold` distinct participants of the session and returns them with their indices
+fn signers_of(
+    session: &MultisigSession,
+    signers: Vec<CommsPublicKey>,
+) -> Result<Vec<MultisigSigner>, TransactionServiceError> {
+    if signers.len() != usize::from(session.threshold) {
+        return Err(TransactionServiceError::MultisigError(format!(
+            "Session {} needs {} signers, got {}",
+            session.session_id,
+            session.threshold,
+            signers.len()
+        )));
+    }
+    if signers.iter().collect::<HashSet<_>>().len() != signers.len() {
+        return Err(TransactionServiceError::MultisigError("Duplicate signers".to_string()));
+    }
+    signers
+        .into_iter()
+        .map(|signer| match session.participant_index(&signer) {
+            Some(index) => Ok(MultisigSigner::new(signer, index)),
+            None => Err(TransactionServiceError::MultisigError(format!(
+                "{} is not a participant of session {}",
+                signer, session.session_id
+            ))),
+        })
+        .collect()
+}
+
+fn set_nonce_commitments(
+    signer: &mut MultisigSigner,
+    commitments: &proto::MultisigNonceCommitments,
+) -> Result<(), TransactionServiceError> {
+    signer.hiding_nonce = Some(PublicKey::from_canonical_bytes(&commitments.hiding)?);
+    signer.binding_nonce = Some(PublicKey::from_canonical_bytes(&commitments.binding)?);
+    Ok(())
+}
+
+/// Returns this wallet's signing share and the group key of a ready session
+fn ready_keys(session: &MultisigSession) -> Result<(TariKeyId, PublicKey), TransactionServiceError> {
+    match (session.signing_share_id.clone(), session.group_public_key.clone()) {
+        (Some(signing_share_id), Some(group_public_key)) => Ok((signing_share_id, group_public_key)),
+        _ => Err(TransactionServiceError::MultisigError(format!(
+            "Session {} has no signing share",
+            session.session_id
+        ))),
+    }
+}
+
+/// The hash of the key generation transcript of a session, once all commitments are in
+fn transcript_hash(session: &MultisigSession) -> Result<FixedHash, TransactionServiceError> {
+    let commitments = session
+        .participants
+        .iter()
+        .map(|p| p.coefficient_commitments.as_deref())
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| {
+            TransactionServiceError::MultisigError(format!("Session {} is missing commitments", session.session_id))
+        })?;
+    Ok(FixedHash::from(frost_dkg_transcript_hash(
+        session.session_id,
+        session.threshold,
+        commitments,
+    )))
+}
+
+fn hash_from_bytes(bytes: &[u8]) -> Result<FixedHash, TransactionServiceError> {
+    FixedHash::try_from(bytes).map_err(|e| TransactionServiceError::MultisigError(e.to_string()))
+}
+
+/// This wallet's part of a joint key
+fn secret_key_id(joint_key: &MultisigSigning) -> Result<TariKeyId, TransactionServiceError> {
+    joint_key.secret_key_id.clone().ok_or_else(|| {
+        TransactionServiceError::MultisigError(format!("This wallet has no part of joint key {}", joint_key.kind))
+    })
+}
+
+/// The part of `signer` of a joint key this wallet requested
+fn joint_key_part(joint_key: &MultisigSigning, signer: &CommsPublicKey) -> Option<PublicKey> {
+    joint_key.signer(signer).and_then(|s| s.joint_key_part.clone())
+}
+
+/// What the signers of a group signature sign
+fn signing_target(
+    signing: &MultisigSigning,
+    group_public_key: &PublicKey,
+) -> Result<FrostSigningTarget, TransactionServiceError> {
+    match &signing.kind {
+        MultisigSigningKind::Message => Ok(FrostSigningTarget::Message(*signing.message)),
+        MultisigSigningKind::ScriptSignature {
+            script_key,
+            txi_version,
+            ephemeral_commitment,
+            commitment,
+            script_message,
+        } => Ok(FrostSigningTarget::ScriptSignature {
+            key_tweak: frost_script_key_tweak(group_public_key, &script_key.branch, script_key.index)?,
+            txi_version: *txi_version,
+            ephemeral_commitment: ephemeral_commitment.clone(),
+            commitment: commitment.clone(),
+            script_message: **script_message,
+        }),
+        kind => Err(TransactionServiceError::MultisigError(format!(
+            "{} signings are not signed by the group key",
+            kind
+        ))),
+    }
+}
+
+fn is_branch_of(key: &MultisigKey, branches: &[TransactionKeyManagerBranch]) -> bool {
+    branches.iter().any(|b| b.get_branch_key() == key.branch)
+}
+
+/// Checks that the keys of a signing are of the branches the session holds for the kind
+fn check_kind(kind: &MultisigSigningKind) -> Result<(), TransactionServiceError> {
+    let valid = match kind {
+        MultisigSigningKind::Message => true,
+        MultisigSigningKind::ScriptSignature { script_key, .. } => {
+            is_branch_of(script_key, &MULTISIG_SCRIPT_KEY_BRANCHES)
+        },
+        MultisigSigningKind::JointKey { key } => is_branch_of(key, &MULTISIG_JOINT_KEY_BRANCHES),
+        MultisigSigningKind::MetadataSignature {
+            nonce,
+            sender_offset_key,
+            ..
+        } => {
+            is_branch_of(nonce, &[TransactionKeyManagerBranch::Nonce]) &&
+                is_branch_of(sender_offset_key, &[TransactionKeyManagerBranch::SenderOffset])
+        },
+        MultisigSigningKind::ScriptOffset {
+            script_keys,
+            sender_offset_keys,
+        } => {
+            !sender_offset_keys.is_empty() &&
+                script_keys
+                    .iter()
+                    .all(|k| is_branch_of(k, &MULTISIG_SCRIPT_KEY_BRANCHES)) &&
+                sender_offset_keys
+                    .iter()
+                    .all(|k| is_branch_of(k, &[TransactionKeyManagerBranch::SenderOffset]))
+        },
+    };
+    if !valid {
+        return Err(TransactionServiceError::MultisigError(format!(
+            "The session does not hold the keys of a {} signing",
+            kind
+        )));
+    }
+    Ok(())
+}
+
+fn key_to_proto(key: &MultisigKey) -> proto::MultisigKey {
+    proto::MultisigKey {
+        branch: key.branch.clone(),
+        index: key.index,
+    }
+}
+
+fn key_from_proto(key: Option<proto::MultisigKey>) -> Result<MultisigKey, TransactionServiceError> {
+    key.map(|key| MultisigKey {
+        branch: key.branch,
+        index: key.index,
+    })
+    .ok_or_else(|| TransactionServiceError::MultisigError("Signing request has no key".to_string()))
+}
+
+fn kind_to_proto(kind: &MultisigSigningKind) -> Option<proto::multisig_signing_request::Kind> {
+    use proto::multisig_signing_request::Kind;
+    match kind {
+        MultisigSigningKind::Message => None,
+        MultisigSigningKind::ScriptSignature {
+            script_key,
+            txi_version,
+            ephemeral_commitment,
+            commitment,
+            script_message,
+        } => Some(Kind::ScriptSignature(proto::MultisigScriptSignature {
+            script_key: Some(key_to_proto(script_key)),
+            txi_version: u32::from(txi_version.as_u8()),
+            ephemeral_commitment: ephemeral_commitment.to_vec(),
+            commitment: commitment.to_vec(),
+            script_message: script_message.to_vec(),
+        })),
+        MultisigSigningKind::JointKey { key } => Some(Kind::JointKey(proto::MultisigJointKey {
+            key: Some(key_to_proto(key)),
+        })),
+        MultisigSigningKind::MetadataSignature {
+            nonce,
+            sender_offset_key,
+            txo_version,
+            commitment,
+            ephemeral_commitment,
+            metadata_signature_message,
+            joint_nonce,
+            joint_sender_offset_key,
+        } => Some(Kind::MetadataSignature(proto::MultisigMetadataSignature {
+            nonce: Some(key_to_proto(nonce)),
+            sender_offset_key: Some(key_to_proto(sender_offset_key)),
+            txo_version: u32::from(txo_version.as_u8()),
+            commitment: commitment.to_vec(),
+            ephemeral_commitment: ephemeral_commitment.to_vec(),
+            metadata_signature_message: metadata_signature_message.to_vec(),
+            joint_nonce: joint_nonce.to_vec(),
+            joint_sender_offset_key: joint_sender_offset_key.to_vec(),
+        })),
+        MultisigSigningKind::ScriptOffset {
+            script_keys,
+            sender_offset_keys,
+        } => Some(Kind::ScriptOffset(proto::MultisigScriptOffset {
+            script_keys: script_keys.iter().map(key_to_proto).collect(),
+            sender_offset_keys: sender_offset_keys.iter().map(key_to_proto).collect(),
+        })),
+    }
+}
+
+fn kind_from_proto(
+    kind: Option<proto::multisig_signing_request::Kind>,
+) -> Result<MultisigSigningKind, TransactionServiceError> {
+    use proto::multisig_signing_request::Kind;
+    let version = |version: u32| {
+        u8::try_from(version)
+            .map_err(|_| TransactionServiceError::MultisigError(format!("Invalid version {}", version)))
+    };
+    Ok(match kind {
+        None => MultisigSigningKind::Message,
+        Some(Kind::ScriptSignature(signature)) => MultisigSigningKind::ScriptSignature {
+            script_key: key_from_proto(signature.script_key)?,
+            txi_version: TransactionInputVersion::try_from(version(signature.txi_version)?)
+                .map_err(TransactionServiceError::MultisigError)?,
+            ephemeral_commitment: Commitment::from_canonical_bytes(&signature.ephemeral_commitment)?,
+            commitment: Commitment::from_canonical_bytes(&signature.commitment)?,
+            script_message: hash_from_bytes(&signature.script_message)?,
+        },
+        Some(Kind::JointKey(joint_key)) => MultisigSigningKind::JointKey {
+            key: key_from_proto(joint_key.key)?,
+        },
+        Some(Kind::MetadataSignature(signature)) => MultisigSigningKind::MetadataSignature {
+            nonce: key_from_proto(signature.nonce)?,
+            sender_offset_key: key_from_proto(signature.sender_offset_key)?,
+            txo_version: TransactionOutputVersion::try_from(version(signature.txo_version)?)
+                .map_err(TransactionServiceError::MultisigError)?,
+            commitment: Commitment::from_canonical_bytes(&signature.commitment)?,
+            ephemeral_commitment: Commitment::from_canonical_bytes(&signature.ephemeral_commitment)?,
+            metadata_signature_message: hash_from_bytes(&signature.metadata_signature_message)?,
+            joint_nonce: PublicKey::from_canonical_bytes(&signature.joint_nonce)?,
+            joint_sender_offset_key: PublicKey::from_canonical_bytes(&signature.joint_sender_offset_key)?,
+        },
+        Some(Kind::ScriptOffset(offset)) => MultisigSigningKind::ScriptOffset {
+            script_keys: offset
+                .script_keys
+                .into_iter()
+                .map(|k| key_from_proto(Some(k)))
+                .collect::<Result<_, _>>()?,
+            sender_offset_keys: offset
+                .sender_offset_keys
+                .into_iter()
+                .map(|k| key_from_proto(Some(k)))
+                .collect::<Result<_, _>>()?,
+        },
+    })
+}
@@ -0,0 +1,246 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use chrono::Utc;
+use log::*;
+use tari_common_types::{
+    transaction::{TransactionDirection, TransactionStatus, TxId},
+    types::PublicKey,
+};
+use tari_core::{
+    one_sided::{shared_secret_to_output_encryption_key, shared_secret_to_output_spending_key},
+    transactions::{
+        key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+        tari_amount::MicroMinotari,
+        transaction_components::{OutputFeatures, WalletOutput, WalletOutputBuilder},
+    },
+};
+use tari_crypto::keys::PublicKey as PKtrait;
+use tari_script::{inputs, one_sided_payment_script};
+
+use crate::{
+    connectivity_service::WalletConnectivityInterface,
+    output_manager_service::UtxoSelectionCriteria,
+    transaction_service::{
+        error::{TransactionServiceError, TransactionServiceProtocolError},
+        handle::BatchPayment,
+        service::TransactionServiceResources,
+        storage::{
+            database::TransactionBackend,
+            models::{BatchedPayment, CompletedTransaction},
+        },
+    },
+    util::wallet_identity::WalletIdentity,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::protocols::batch_send_protocol";
+
+/// Builds a single transaction with one kernel that pays out several recipients at once. Each recipient is paid
+/// one-sided, so no negotiation with the recipients is needed and the batch can be completed immediately. The
+/// returned completed transaction still needs to be submitted for broadcast by the service.
+pub struct TransactionBatchSendProtocol<TBackend, TWalletConnectivity, TKeyManagerInterface> {
+    tx_id: TxId,
+    payments: Vec<BatchPayment>,
+    selection_criteria: UtxoSelectionCriteria,
+    fee_per_gram: MicroMinotari,
+    message: String,
+    resources: TransactionServiceResources<TBackend, TWalletConnectivity, TKeyManagerInterface>,
+}
+
+impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
+    TransactionBatchSendProtocol<TBackend, TWalletConnectivity, TKeyManagerInterface>
+where
+    TBackend: TransactionBackend + 'static,
+    TWalletConnectivity: WalletConnectivityInterface,
+    TKeyManagerInterface: TransactionKeyManagerInterface,
+{
+    pub fn new(
+        tx_id: TxId,
+        resources: TransactionServiceResources<TBackend, TWalletConnectivity, TKeyManagerInterface>,
+        payments: Vec<BatchPayment>,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Self {
+        Self {
+            tx_id,
+            payments,
+            selection_criteria,
+            fee_per_gram,
+            message,
+            resources,
+        }
+    }
+
+    pub async fn execute(mut self) -> Result<CompletedTransaction, TransactionServiceProtocolError<TxId>> {
+        let tx_id = self.tx_id;
+        self.validate_payments()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e))?;
+
+        let mut recipient_outputs = Vec::with_capacity(self.payments.len());
+        let mut batched_payments = Vec::with_capacity(self.payments.len());
+        for payment in &self.payments {
+            let (output, sender_offset_key_id) = build_one_sided_recipient_output(
+                &self.resources.transaction_key_manager_service,
+                &self.resources.wallet_identity,
+                payment,
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e))?;
+            let output_hash = output
+                .hash(&self.resources.transaction_key_manager_service)
+                .await
+                .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+            batched_payments.push(BatchedPayment {
+                tx_id,
+                destination_address: payment.destination.clone(),
+                amount: payment.amount,
+                message: payment.message.clone(),
+                output_hash,
+            });
+            recipient_outputs.push((output, sender_offset_key_id));
+        }
+
+        let (fee, transaction) = self
+            .resources
+            .output_manager_service
+            .create_batch_transaction(
+                tx_id,
+                recipient_outputs,
+                self.fee_per_gram,
+                self.selection_criteria.clone(),
+            )
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        if let Err(e) = self.resources.db.insert_batched_payments(batched_payments) {
+            // Release the encumbered inputs again, the batch will not be broadcast without its payment records
+            if let Err(e) = self.resources.output_manager_service.cancel_transaction(tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to cancel outputs for batch TxId: {} with error {:?}", tx_id, e
+                );
+            }
+            return Err(TransactionServiceProtocolError::new(tx_id, e.into()));
+        }
+        info!(
+            target: LOG_TARGET,
+            "Finalized batch transaction TxId: {} paying {} recipients",
+            tx_id,
+            self.payments.len()
+        );
+
+        let amount = self.payments.iter().map(|p| p.amount).sum();
+        // A batch has no single counterparty, it is recorded against its first recipient and the batched payments list
+        // every recipient
+        let destination = self.payments[0].destination.clone();
+        Ok(CompletedTransaction::new(
+            tx_id,
+            self.resources.wallet_identity.address.clone(),
+            destination,
+            amount,
+            fee,
+            transaction,
+            TransactionStatus::Completed,
+            self.message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+            None,
+        ))
+    }
+
+    fn validate_payments(&self) -> Result<(), TransactionServiceError> {
+        if self.payments.is_empty() {
+            return Err(TransactionServiceError::BatchTransactionError(
+                "A batch transaction needs at least one payment".to_string(),
+            ));
+        }
+        for payment in &self.payments {
+            validate_batch_payment(payment, &self.resources.wallet_identity)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a single payment of a batch can be paid one-sided from this wallet
+pub(crate) fn validate_batch_payment(
+    payment: &BatchPayment,
+    wallet_identity: &WalletIdentity,
+) -> Result<(), TransactionServiceError> {
+    if payment.destination.network() != wallet_identity.network {
+        return Err(TransactionServiceError::InvalidNetwork);
+    }
+    if payment.destination.public_key() == wallet_identity.node_identity.public_key() {
+        return Err(TransactionServiceError::BatchTransactionError(
+            "Batch payments to self are not supported".to_string(),
+        ));
+    }
+    if payment.amount == MicroMinotari::zero() {
+        return Err(TransactionServiceError::BatchTransactionError(format!(
+            "Payment to {} has a zero amount",
+            payment.destination
+        )));
+    }
+    Ok(())
+}
+
+/// Creates and signs the one-sided output for a single payment of a batch, returning it along with the sender offset
+/// key it was signed with.
+pub(crate) async fn build_one_sided_recipient_output<TKeyManagerInterface: TransactionKeyManagerInterface>(
+    key_manager: &TKeyManagerInterface,
+    wallet_identity: &WalletIdentity,
+    payment: &BatchPayment,
+) -> Result<(WalletOutput, TariKeyId), TransactionServiceError> {
+    let (sender_offset_key_id, sender_offset_public_key) = key_manager
+        .get_next_key(&TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+        .await?;
+
+    // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is fed into
+    // KDFs to produce the spending, rewind, and encryption keys
+    let shared_secret = key_manager
+        .get_diffie_hellman_shared_secret(&sender_offset_key_id, payment.destination.public_key())
+        .await?;
+    let spending_key = shared_secret_to_output_spending_key(&shared_secret)?;
+    let encryption_private_key = shared_secret_to_output_encryption_key(&shared_secret)?;
+    let encryption_key = key_manager.import_key(encryption_private_key).await?;
+    let spending_key_id = key_manager.import_key(spending_key).await?;
+
+    let output = WalletOutputBuilder::new(payment.amount, spending_key_id)
+        .with_features(OutputFeatures::default())
+        .with_script(one_sided_payment_script(payment.destination.public_key()))
+        .encrypt_data_for_recovery(key_manager, Some(&encryption_key))
+        .await?
+        .with_input_data(inputs!(PublicKey::from_secret_key(
+            wallet_identity.node_identity.secret_key()
+        )))
+        .with_sender_offset_public_key(sender_offset_public_key)
+        .with_script_key(wallet_identity.wallet_node_key_id.clone())
+        .with_minimum_value_promise(MicroMinotari::zero())
+        .sign_as_sender_and_receiver(key_manager, &sender_offset_key_id)
+        .await?
+        .try_build(key_manager)
+        .await?;
+
+    Ok((output, sender_offset_key_id))
+}
@@ -63,6 +63,7 @@ pub struct TransactionBroadcastProtocol<TBackend, TWalletConnectivity, TKeyManag
     resources: TransactionServiceResources<TBackend, TWalletConnectivity, TKeyManagerInterface>,
     timeout_update_receiver: watch::Receiver<Duration>,
     last_rejection: Option<Instant>,
+    kernel_signature: Option<Signature>,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -83,6 +84,7 @@ where
             resources,
             timeout_update_receiver,
             last_rejection: None,
+            kernel_signature: None,
         }
     }
 
@@ -128,6 +130,19 @@ where
                 return Ok(self.tx_id);
             }
 
+            // The transaction may have been replaced, e.g. by a fee bump, since it was last submitted. The replacement
+            // supersedes the earlier version, so it has to be submitted afresh rather than queried for.
+            let kernel_signature = completed_tx.transaction.first_kernel_excess_sig().cloned();
+            if self.kernel_signature.is_some() && self.kernel_signature != kernel_signature {
+                info!(
+                    target: LOG_TARGET,
+                    "Transaction (TxId: {}) has been superseded by a new version, submitting the replacement", self.tx_id
+                );
+                self.mode = TxBroadcastMode::TransactionSubmission;
+                self.last_rejection = None;
+            }
+            self.kernel_signature = kernel_signature;
+
             loop {
                 tokio::select! {
                     _ = current_base_node_watcher.changed() => {
@@ -20,11 +20,12 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::Arc;
+use std::{convert::TryInto, sync::Arc};
 
 use chrono::Utc;
 use futures::future::FutureExt;
 use log::*;
+use prost::Message;
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::{TransactionDirection, TransactionStatus, TxId},
@@ -32,13 +33,20 @@ use tari_common_types::{
 use tari_core::transactions::{
     key_manager::TransactionKeyManagerInterface,
     transaction_components::Transaction,
-    transaction_protocol::{recipient::RecipientState, sender::TransactionSenderMessage},
+    transaction_protocol::{
+        proto::protocol as proto,
+        recipient::RecipientState,
+        sender::TransactionSenderMessage,
+        EncryptedMemo,
+    },
 };
 use tokio::{
     sync::{mpsc, oneshot},
     time::sleep,
 };
 
+#[cfg(feature = "metrics")]
+use crate::transaction_service::metrics;
 use crate::{
     connectivity_service::WalletConnectivityInterface,
     transaction_service::{
@@ -47,9 +55,15 @@ use crate::{
         service::TransactionServiceResources,
         storage::{
             database::TransactionBackend,
-            models::{CompletedTransaction, InboundTransaction, TxCancellationReason},
+            models::{
+                CompletedTransaction,
+                InboundTransaction,
+                OutboundMessageType,
+                QueuedOutboundMessage,
+                TxCancellationReason,
+            },
         },
-        tasks::send_transaction_reply::send_transaction_reply,
+        tasks::send_queued_outbound_message::deliver_queued_outbound_message,
         utc::utc_duration_since,
     },
 };
@@ -164,14 +178,23 @@ where
                 .add_pending_inbound_transaction(inbound_transaction.tx_id, inbound_transaction.clone())
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
-            let send_result = send_transaction_reply(
-                inbound_transaction,
-                self.resources.outbound_message_service.clone(),
-                self.resources.config.direct_send_timeout,
-                self.resources.config.transaction_routing_mechanism,
-            )
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
+            if let Some(encrypted_memo) = &data.encrypted_memo {
+                // A memo that cannot be read must not stop us from receiving the funds
+                if let Err(e) = self.store_memo(data.tx_id, encrypted_memo).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Could not decrypt the memo of Transaction (TxId: {}): {}", data.tx_id, e
+                    );
+                }
+            }
+
+            // Persist the reply before attempting delivery so that it can be redelivered if the wallet stops before
+            // the sender receives it
+            self.queue_reply(&inbound_transaction)?;
+            let send_result = self
+                .deliver_reply()
+                .await
+                .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
 
             self.resources
                 .db
@@ -217,6 +240,20 @@ where
         }
     }
 
+    async fn store_memo(&self, tx_id: TxId, encrypted_memo: &EncryptedMemo) -> Result<(), TransactionServiceError> {
+        let shared_secret = self
+            .resources
+            .transaction_key_manager_service
+            .get_diffie_hellman_shared_secret(
+                &self.resources.wallet_identity.wallet_node_key_id,
+                encrypted_memo.ephemeral_public_key(),
+            )
+            .await?;
+        let memo = encrypted_memo.decrypt(&shared_secret)?;
+        self.resources.db.set_transaction_memo(tx_id, memo)?;
+        Ok(())
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn wait_for_finalization(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         let mut receiver = self
@@ -272,23 +309,7 @@ where
         };
 
         if resend {
-            if let Err(e) = send_transaction_reply(
-                inbound_tx.clone(),
-                self.resources.outbound_message_service.clone(),
-                self.resources.config.direct_send_timeout,
-                self.resources.config.transaction_routing_mechanism,
-            )
-            .await
-            {
-                warn!(
-                    target: LOG_TARGET,
-                    "Error resending Transaction Reply (TxId: {}): {:?}", self.id, e
-                );
-            }
-            self.resources
-                .db
-                .increment_send_count(self.id)
-                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+            self.resend_reply(&inbound_tx).await?;
         }
 
         let mut shutdown = self.resources.shutdown_signal.clone();
@@ -321,22 +342,9 @@ where
                         ));
                     },
                     _ = resend_timeout => {
-                        match send_transaction_reply(
-                            inbound_tx.clone(),
-                            self.resources.outbound_message_service.clone(),
-                            self.resources.config.direct_send_timeout,
-                            self.resources.config.transaction_routing_mechanism,
-                        )
-                        .await {
-                            Ok(_) => self.resources
-                                        .db
-                                        .increment_send_count(self.id)
-                                        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?,
-                            Err(e) => warn!(
-                                            target: LOG_TARGET,
-                                            "Error resending Transaction Reply (TxId: {}): {:?}", self.id, e
-                                        ),
-                        }
+                        #[cfg(feature = "metrics")]
+                        metrics::protocol_retries(self.resources.wallet_identity.node_identity.node_id(), "receive").inc();
+                        self.resend_reply(&inbound_tx).await?;
                     },
                     _ = &mut timeout_delay => {
                         return self.timeout_transaction().await;
@@ -442,6 +450,11 @@ where
                 .db
                 .complete_inbound_transaction(self.id, completed_transaction)
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+            // The finalized transaction has arrived, so any queued reply is no longer needed
+            self.resources
+                .db
+                .remove_outbound_messages(self.id)
+                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
             info!(
                 target: LOG_TARGET,
@@ -463,6 +476,65 @@ where
         Ok(())
     }
 
+    fn queue_reply(
+        &self,
+        inbound_transaction: &InboundTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        let recipient_reply: proto::RecipientSignedMessage = inbound_transaction
+            .receiver_protocol
+            .get_signed_data()
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?
+            .clone()
+            .try_into()
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::ServiceError(e)))?;
+        self.resources
+            .db
+            .queue_outbound_message(QueuedOutboundMessage::new(
+                self.id,
+                OutboundMessageType::ReceiverPartialTransactionReply,
+                self.source_address.clone(),
+                recipient_reply.encode_to_vec(),
+                Utc::now().naive_utc(),
+            ))
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))
+    }
+
+    async fn deliver_reply(&self) -> Result<bool, TransactionServiceError> {
+        deliver_queued_outbound_message(
+            self.id,
+            OutboundMessageType::ReceiverPartialTransactionReply,
+            self.resources.db.clone(),
+            self.resources.outbound_message_service.clone(),
+            self.resources.config.direct_send_timeout,
+            self.resources.config.transaction_routing_mechanism,
+            self.resources.config.max_outbound_message_attempts,
+        )
+        .await
+    }
+
+    /// Resend the reply while waiting for the finalized transaction. This goes through the outbound message queue so
+    /// that the persisted attempt count bounds the resends across restarts.
+    async fn resend_reply(
+        &self,
+        inbound_transaction: &InboundTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        // The reply is only queued here if it was not already, e.g. for a transaction received before the queue existed
+        self.queue_reply(inbound_transaction)?;
+        match self.deliver_reply().await {
+            Ok(true) => self
+                .resources
+                .db
+                .increment_send_count(self.id)
+                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?,
+            Ok(false) => {},
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Error resending Transaction Reply (TxId: {}): {:?}", self.id, e
+            ),
+        }
+        Ok(())
+    }
+
     async fn timeout_transaction(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         info!(
             target: LOG_TARGET,
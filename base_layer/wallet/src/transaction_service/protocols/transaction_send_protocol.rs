@@ -25,6 +25,7 @@ use std::{convert::TryInto, sync::Arc};
 use chrono::Utc;
 use futures::FutureExt;
 use log::*;
+use prost::Message;
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::{TransactionDirection, TransactionStatus, TxId},
@@ -44,6 +45,7 @@ use tari_core::{
             proto::protocol as proto,
             recipient::RecipientSignedMessage,
             sender::SingleRoundSenderData,
+            EncryptedMemo,
             TransactionMetadata,
         },
         SenderTransactionProtocol,
@@ -56,20 +58,31 @@ use tokio::{
     time::sleep,
 };
 
+#[cfg(feature = "metrics")]
+use crate::transaction_service::metrics;
 use crate::{
     connectivity_service::WalletConnectivityInterface,
     output_manager_service::UtxoSelectionCriteria,
     transaction_service::{
         config::TransactionRoutingMechanism,
         error::{TransactionServiceError, TransactionServiceProtocolError},
-        handle::{TransactionEvent, TransactionSendStatus, TransactionServiceResponse},
+        handle::{BatchPayment, TransactionEvent, TransactionSendStatus, TransactionServiceResponse},
+        protocols::transaction_batch_send_protocol::{build_one_sided_recipient_output, validate_batch_payment},
         service::{TransactionSendResult, TransactionServiceResources},
         storage::{
             database::TransactionBackend,
-            models::{CompletedTransaction, OutboundTransaction, TxCancellationReason},
+            models::{
+                BatchedPayment,
+                CompletedTransaction,
+                OutboundMessageStatus,
+                OutboundMessageType,
+                OutboundTransaction,
+                QueuedOutboundMessage,
+                TxCancellationReason,
+            },
         },
         tasks::{
-            send_finalized_transaction::send_finalized_transaction_message,
+            send_queued_outbound_message::deliver_queued_outbound_message,
             send_transaction_cancelled::send_transaction_cancelled_message,
             wait_on_dial::wait_on_dial,
         },
@@ -99,6 +112,10 @@ pub struct TransactionSendProtocol<TBackend, TWalletConnectivity, TKeyManagerInt
     cancellation_receiver: Option<oneshot::Receiver<()>>,
     tx_meta: TransactionMetadata,
     sender_protocol: Option<SenderTransactionProtocol>,
+    additional_payments: Vec<BatchPayment>,
+    memo: Option<String>,
+    routing_mechanism: TransactionRoutingMechanism,
+    fail_fast: bool,
 }
 
 impl<TBackend, TWalletConnectivity, TKeyManagerInterface>
@@ -126,6 +143,8 @@ where
     ) -> Self {
         Self {
             id,
+            routing_mechanism: resources.config.transaction_routing_mechanism,
+            fail_fast: false,
             resources,
             transaction_reply_receiver: Some(transaction_reply_receiver),
             cancellation_receiver: Some(cancellation_receiver),
@@ -137,9 +156,37 @@ where
             stage,
             tx_meta,
             sender_protocol,
+            additional_payments: Vec::new(),
+            memo: None,
         }
     }
 
+    /// Pays the given recipients one-sided in the same transaction as the interactive recipient. Their payments are
+    /// recorded as the batched payments of the transaction, see
+    /// [get_batched_payments](crate::transaction_service::handle::TransactionServiceHandle::get_batched_payments).
+    pub fn with_additional_payments(mut self, payments: Vec<BatchPayment>) -> Self {
+        self.additional_payments = payments;
+        self
+    }
+
+    /// Attaches a private memo to the transaction. It is encrypted to the recipient's public key before being sent and
+    /// stored in plaintext in the local database only.
+    pub fn with_memo(mut self, memo: Option<String>) -> Self {
+        self.memo = memo;
+        self
+    }
+
+    /// Overrides the configured routing mechanism for this transaction only. A `DirectOnly` override fails fast: if the
+    /// recipient cannot be reached directly the transaction is cancelled and the error is returned to the caller,
+    /// rather than being queued for a later retry.
+    pub fn with_routing_mechanism(mut self, routing_mechanism: Option<TransactionRoutingMechanism>) -> Self {
+        if let Some(routing_mechanism) = routing_mechanism {
+            self.routing_mechanism = routing_mechanism;
+            self.fail_fast = routing_mechanism == TransactionRoutingMechanism::DirectOnly;
+        }
+        self
+    }
+
     /// Execute the Transaction Send Protocol as an async task.
     pub async fn execute(
         mut self,
@@ -213,23 +260,12 @@ where
             },
         };
 
-        match self
-            .resources
-            .output_manager_service
-            .prepare_transaction_to_send(
-                self.id,
-                self.amount,
-                UtxoSelectionCriteria::default(),
-                OutputFeatures::default(),
-                self.fee_per_gram,
-                self.tx_meta.clone(),
-                self.message.clone(),
-                TariScript::default(),
-                Covenant::default(),
-                MicroMinotari::zero(),
-            )
-            .await
-        {
+        match self.prepare_sender_protocol().await {
+            Ok(sp) if self.fail_fast => {
+                // The caller is only told the outcome once the direct send has been attempted
+                self.service_request_reply_channel = Some(service_reply_channel);
+                Ok(sp)
+            },
             Ok(sp) => {
                 let _result = service_reply_channel
                     .send(Ok(TransactionServiceResponse::TransactionSent(self.id)))
@@ -241,12 +277,10 @@ where
             },
             Err(e) => {
                 let error_string = e.to_string();
-                let _size = service_reply_channel
-                    .send(Err(TransactionServiceError::from(e)))
-                    .map_err(|e| {
-                        warn!(target: LOG_TARGET, "Failed to send service reply");
-                        e
-                    });
+                let _size = service_reply_channel.send(Err(e)).map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
                 Err(TransactionServiceProtocolError::new(
                     self.id,
                     TransactionServiceError::ServiceError(error_string),
@@ -255,6 +289,101 @@ where
         }
     }
 
+    async fn prepare_sender_protocol(&self) -> Result<SenderTransactionProtocol, TransactionServiceError> {
+        let mut sender_protocol = self.build_sender_protocol().await?;
+        if let Some(memo) = &self.memo {
+            if let Err(e) = self.attach_memo(&mut sender_protocol, memo) {
+                if let Err(e) = self.resources.output_manager_service.cancel_transaction(self.id).await {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Failed to cancel outputs for TxId: {} with error {:?}", self.id, e
+                    );
+                }
+                return Err(e);
+            }
+        }
+        Ok(sender_protocol)
+    }
+
+    fn attach_memo(
+        &self,
+        sender_protocol: &mut SenderTransactionProtocol,
+        memo: &str,
+    ) -> Result<(), TransactionServiceError> {
+        let encrypted_memo = EncryptedMemo::encrypt(self.dest_address.public_key(), memo)?;
+        sender_protocol.set_encrypted_memo(encrypted_memo)?;
+        self.resources.db.set_transaction_memo(self.id, memo.to_string())?;
+        Ok(())
+    }
+
+    async fn build_sender_protocol(&self) -> Result<SenderTransactionProtocol, TransactionServiceError> {
+        if self.additional_payments.is_empty() {
+            return Ok(self
+                .resources
+                .output_manager_service
+                .prepare_transaction_to_send(
+                    self.id,
+                    self.amount,
+                    UtxoSelectionCriteria::default(),
+                    OutputFeatures::default(),
+                    self.fee_per_gram,
+                    self.tx_meta.clone(),
+                    self.message.clone(),
+                    TariScript::default(),
+                    Covenant::default(),
+                    MicroMinotari::zero(),
+                )
+                .await?);
+        }
+
+        let mut additional_outputs = Vec::with_capacity(self.additional_payments.len());
+        let mut batched_payments = Vec::with_capacity(self.additional_payments.len());
+        for payment in &self.additional_payments {
+            validate_batch_payment(payment, &self.resources.wallet_identity)?;
+            let (output, sender_offset_key_id) = build_one_sided_recipient_output(
+                &self.resources.transaction_key_manager_service,
+                &self.resources.wallet_identity,
+                payment,
+            )
+            .await?;
+            let output_hash = output.hash(&self.resources.transaction_key_manager_service).await?;
+            batched_payments.push(BatchedPayment {
+                tx_id: self.id,
+                destination_address: payment.destination.clone(),
+                amount: payment.amount,
+                message: payment.message.clone(),
+                output_hash,
+            });
+            additional_outputs.push((output, sender_offset_key_id));
+        }
+
+        let sender_protocol = self
+            .resources
+            .output_manager_service
+            .prepare_transaction_to_send_with_outputs(
+                self.id,
+                self.amount,
+                additional_outputs,
+                UtxoSelectionCriteria::default(),
+                self.fee_per_gram,
+                self.tx_meta.clone(),
+                self.message.clone(),
+            )
+            .await?;
+
+        if let Err(e) = self.resources.db.insert_batched_payments(batched_payments) {
+            // Release the encumbered inputs again, the one-sided payments must be on record before anything is sent
+            if let Err(e) = self.resources.output_manager_service.cancel_transaction(self.id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to cancel outputs for TxId: {} with error {:?}", self.id, e
+                );
+            }
+            return Err(e.into());
+        }
+        Ok(sender_protocol)
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn initial_send_transaction(
         &mut self,
@@ -301,6 +430,20 @@ where
             },
         };
 
+        if self.fail_fast {
+            if !direct_send_result {
+                return Err(self.fail_direct_send().await);
+            }
+            if let Some(reply_channel) = self.service_request_reply_channel.take() {
+                let _result = reply_channel
+                    .send(Ok(TransactionServiceResponse::TransactionSent(self.id)))
+                    .map_err(|e| {
+                        warn!(target: LOG_TARGET, "Failed to send service reply");
+                        e
+                    });
+            }
+        }
+
         // Confirm pending transaction (confirm encumbered outputs)
         if transaction_status == TransactionStatus::Pending {
             self.resources
@@ -320,6 +463,8 @@ where
             let fee = sender_protocol
                 .get_fee_amount()
                 .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+            // The amount is what the interactive recipient is paid, any one-sided payments are recorded separately as
+            // the batched payments of the transaction
             let outbound_tx = OutboundTransaction::new(
                 tx_id,
                 self.dest_address.clone(),
@@ -432,29 +577,7 @@ where
         };
 
         if resend {
-            match self
-                .send_transaction(
-                    outbound_tx
-                        .sender_protocol
-                        .get_single_round_message(&self.resources.transaction_key_manager_service)
-                        .await
-                        .map_err(|e| TransactionServiceProtocolError::new(self.id, e.into()))?,
-                )
-                .await
-            {
-                Ok(val) => {
-                    if val.transaction_status == TransactionStatus::Pending {
-                        self.resources
-                            .db
-                            .increment_send_count(self.id)
-                            .map_err(|e| TransactionServiceProtocolError::new(self.id, e.into()))?
-                    }
-                },
-                Err(e) => warn!(
-                    target: LOG_TARGET,
-                    "Error resending Transaction (TxId: {}): {:?}", self.id, e
-                ),
-            };
+            self.resend_transaction(&outbound_tx).await?;
         }
 
         let mut shutdown = self.resources.shutdown_signal.clone();
@@ -503,26 +626,9 @@ where
                     }
                 },
                 () = resend_timeout => {
-                    match self.send_transaction(
-                        outbound_tx
-                        .sender_protocol
-                        .get_single_round_message(&self.resources.transaction_key_manager_service).await
-                        .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?
-                    ).await
-                    {
-                        Ok(val) => if val.transaction_status == TransactionStatus::Pending {
-                             self.resources
-                                .db
-                                .increment_send_count(self.id)
-                                .map_err(|e| TransactionServiceProtocolError::new(
-                                    self.id, TransactionServiceError::from(e))
-                                )?
-                        },
-                        Err(e) => warn!(
-                            target: LOG_TARGET,
-                            "Error resending Transaction (TxId: {}): {:?}", self.id, e
-                        ),
-                    };
+                    #[cfg(feature = "metrics")]
+                    metrics::protocol_retries(self.resources.wallet_identity.node_identity.node_id(), "send").inc();
+                    self.resend_transaction(&outbound_tx).await?;
                 },
                 () = &mut timeout_delay => {
                     return self.timeout_transaction().await;
@@ -541,6 +647,11 @@ where
         let recipient_reply = reply.ok_or_else(|| {
             TransactionServiceProtocolError::new(self.id, TransactionServiceError::TransactionCancelled)
         })?;
+        #[cfg(feature = "metrics")]
+        if let Ok(latency) = utc_duration_since(&outbound_tx.timestamp) {
+            metrics::negotiation_latency(self.resources.wallet_identity.node_identity.node_id())
+                .observe(latency.as_secs_f64());
+        }
 
         outbound_tx
             .sender_protocol
@@ -548,6 +659,25 @@ where
             .await
             .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
 
+        if self.resources.transaction_key_manager_service.awaits_cosigners() {
+            self.resources
+                .db
+                .mark_awaiting_cosigners(tx_id)
+                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+            let _size = self
+                .resources
+                .event_publisher
+                .send(Arc::new(TransactionEvent::TransactionAwaitingCosigners(tx_id)))
+                .map_err(|e| {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Error sending event, usually because there are no subscribers: {:?}",
+                        e
+                    );
+                    e
+                });
+        }
+
         outbound_tx
             .sender_protocol
             .finalize(&self.resources.transaction_key_manager_service)
@@ -590,16 +720,48 @@ where
             "Transaction Recipient Reply for TX_ID = {} received", tx_id,
         );
 
-        send_finalized_transaction_message(
+        // The reply has been received so the queued sender message is no longer needed
+        self.resources
+            .db
+            .remove_outbound_messages(tx_id)
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        // Persist the finalized message before attempting delivery so that it can be redelivered if the wallet stops
+        // before the recipient receives it
+        let finalized_transaction_message = proto::TransactionFinalizedMessage {
+            tx_id: tx_id.into(),
+            transaction: Some(tx.clone().try_into().map_err(|e| {
+                TransactionServiceProtocolError::new(self.id, TransactionServiceError::InvalidMessageError(e))
+            })?),
+        };
+        self.resources
+            .db
+            .queue_outbound_message(QueuedOutboundMessage::new(
+                tx_id,
+                OutboundMessageType::TransactionFinalized,
+                self.dest_address.clone(),
+                finalized_transaction_message.encode_to_vec(),
+                Utc::now().naive_utc(),
+            ))
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
+        let sent = deliver_queued_outbound_message(
             tx_id,
-            tx.clone(),
-            self.dest_address.public_key().clone(),
+            OutboundMessageType::TransactionFinalized,
+            self.resources.db.clone(),
             self.resources.outbound_message_service.clone(),
             self.resources.config.direct_send_timeout,
             self.resources.config.transaction_routing_mechanism,
+            self.resources.config.max_outbound_message_attempts,
         )
         .await
         .map_err(|e| TransactionServiceProtocolError::new(self.id, e))?;
+        if !sent {
+            return Err(TransactionServiceProtocolError::new(
+                self.id,
+                TransactionServiceError::OutboundSendFailure,
+            ));
+        }
 
         self.resources
             .db
@@ -636,7 +798,10 @@ where
             transaction_status: TransactionStatus::Queued,
         };
 
-        match self.resources.config.transaction_routing_mechanism {
+        // Persist the sender message so that it survives a restart until the recipient has received it
+        self.queue_sender_message(msg.clone())?;
+
+        match self.routing_mechanism {
             TransactionRoutingMechanism::DirectOnly | TransactionRoutingMechanism::DirectAndStoreAndForward => {
                 result = self.send_transaction_direct(msg.clone()).await?;
             },
@@ -648,9 +813,75 @@ where
             },
         };
 
+        // A message accepted for store-and-forward may never arrive, so it is only delivered once sent directly
+        let delivery_status = if result.direct_send_result {
+            OutboundMessageStatus::Delivered
+        } else {
+            OutboundMessageStatus::Pending
+        };
+        self.resources
+            .db
+            .record_outbound_message_attempt(self.id, OutboundMessageType::SenderPartialTransaction, delivery_status)
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+
         Ok(result)
     }
 
+    /// Resend the sender message while waiting for the recipient's reply. This goes through the outbound message queue
+    /// so that the persisted attempt count bounds the resends across restarts.
+    async fn resend_transaction(
+        &mut self,
+        outbound_tx: &OutboundTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        // The message is only queued here if it was not already, e.g. for a transaction sent before the queue existed
+        let msg = outbound_tx
+            .sender_protocol
+            .get_single_round_message(&self.resources.transaction_key_manager_service)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?;
+        self.queue_sender_message(msg)?;
+
+        match deliver_queued_outbound_message(
+            self.id,
+            OutboundMessageType::SenderPartialTransaction,
+            self.resources.db.clone(),
+            self.resources.outbound_message_service.clone(),
+            self.resources.config.direct_send_timeout,
+            self.routing_mechanism,
+            self.resources.config.max_outbound_message_attempts,
+        )
+        .await
+        {
+            Ok(true) => self
+                .resources
+                .db
+                .increment_send_count(self.id)
+                .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))?,
+            Ok(false) => {},
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Error resending Transaction (TxId: {}): {:?}", self.id, e
+            ),
+        }
+        Ok(())
+    }
+
+    fn queue_sender_message(&self, msg: SingleRoundSenderData) -> Result<(), TransactionServiceProtocolError<TxId>> {
+        let proto_message = proto::TransactionSenderMessage::single(msg.clone().try_into().map_err(|err| {
+            TransactionServiceProtocolError::new(msg.tx_id, TransactionServiceError::ServiceError(err))
+        })?);
+        self.resources
+            .db
+            .queue_outbound_message(QueuedOutboundMessage::new(
+                self.id,
+                OutboundMessageType::SenderPartialTransaction,
+                self.dest_address.clone(),
+                proto_message.encode_to_vec(),
+                Utc::now().naive_utc(),
+            ))
+            .map_err(|e| TransactionServiceProtocolError::new(self.id, TransactionServiceError::from(e)))
+    }
+
     /// Attempt to send the transaction to the recipient both directly and via Store-and-forward. If both fail to send
     /// the transaction will be cancelled.
     /// # Argumentswallet_sync_with_base_node
@@ -811,7 +1042,7 @@ where
         &mut self,
         msg: SingleRoundSenderData,
     ) -> Result<bool, TransactionServiceProtocolError<TxId>> {
-        if self.resources.config.transaction_routing_mechanism == TransactionRoutingMechanism::DirectOnly {
+        if self.routing_mechanism == TransactionRoutingMechanism::DirectOnly {
             return Ok(false);
         }
         let proto_message = proto::TransactionSenderMessage::single(msg.clone().try_into().map_err(|err| {
@@ -878,6 +1109,37 @@ where
         }
     }
 
+    /// Cancels a transaction whose fail-fast direct send did not reach the recipient. The encumbered outputs are
+    /// released, the queued message is discarded so that it is not retried, and the caller is sent the error.
+    async fn fail_direct_send(&mut self) -> TransactionServiceProtocolError<TxId> {
+        warn!(
+            target: LOG_TARGET,
+            "Direct send for TxId: {} to {} failed. Cancelling transaction.", self.id, self.dest_address
+        );
+        if let Err(e) = self.resources.db.remove_outbound_messages(self.id) {
+            warn!(
+                target: LOG_TARGET,
+                "Could not remove queued messages for TxId: {}: {}", self.id, e
+            );
+        }
+        if let Err(e) = self.resources.output_manager_service.cancel_transaction(self.id).await {
+            warn!(
+                target: LOG_TARGET,
+                "Could not release outputs for TxId: {}: {}", self.id, e
+            );
+        }
+        if let Some(reply_channel) = self.service_request_reply_channel.take() {
+            let _result = reply_channel
+                .send(Err(TransactionServiceError::DirectSendFailed(self.id)))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+        }
+
+        TransactionServiceProtocolError::new(self.id, TransactionServiceError::DirectSendFailed(self.id))
+    }
+
     async fn timeout_transaction(&mut self) -> Result<(), TransactionServiceProtocolError<TxId>> {
         info!(
             target: LOG_TARGET,
@@ -28,17 +28,20 @@ use std::{
 
 use log::*;
 use tari_common_types::{
-    transaction::{TransactionStatus, TxId},
-    types::{BlockHash, Signature},
+    transaction::{TransactionDirection, TransactionStatus, TxId},
+    types::{BlockHash, FixedHash, Signature},
 };
 use tari_comms::protocol::rpc::{RpcError::RequestFailed, RpcStatusCode::NotFound};
 use tari_core::{
     base_node::{
         proto::wallet_rpc::{TxLocation, TxQueryBatchResponse},
-        rpc::BaseNodeWalletRpcClient,
+        rpc::{BaseNodeWalletRpcClient, MAX_TX_QUERY_BATCH_SIZE},
     },
     blocks::BlockHeader,
-    proto::{base_node::Signatures as SignaturesProto, types::Signature as SignatureProto},
+    proto::{
+        base_node::{QueryDeletedRequest, Signatures as SignaturesProto},
+        types::Signature as SignatureProto,
+    },
 };
 use tari_utilities::hex::Hex;
 
@@ -48,10 +51,10 @@ use crate::{
     transaction_service::{
         config::TransactionServiceConfig,
         error::{TransactionServiceError, TransactionServiceProtocolError, TransactionServiceProtocolErrorExt},
-        handle::{TransactionEvent, TransactionEventSender},
+        handle::{SpentInputConflict, TransactionEvent, TransactionEventSender},
         storage::{
             database::{TransactionBackend, TransactionDatabase},
-            models::TxCancellationReason,
+            models::{SupersededTransaction, TxCancellationReason},
             sqlite_db::UnconfirmedTransactionInfo,
         },
     },
@@ -60,6 +63,10 @@ use crate::{
 
 const LOG_TARGET: &str = "wallet::transaction_service::protocols::validation_protocol";
 
+/// A transaction the base node reported as mined, with the height, block hash, confirmations and timestamp it was
+/// mined at
+type MinedTransactionInfo = (UnconfirmedTransactionInfo, u64, BlockHash, u64, u64);
+
 pub struct TransactionValidationProtocol<TTransactionBackend, TWalletConnectivity> {
     operation_id: OperationId,
     db: TransactionDatabase<TTransactionBackend>,
@@ -67,6 +74,7 @@ pub struct TransactionValidationProtocol<TTransactionBackend, TWalletConnectivit
     config: TransactionServiceConfig,
     event_publisher: TransactionEventSender,
     output_manager_handle: OutputManagerHandle,
+    tx_ids: Option<Vec<TxId>>,
 }
 
 #[allow(unused_variables)]
@@ -90,9 +98,16 @@ where
             config,
             event_publisher,
             output_manager_handle,
+            tx_ids: None,
         }
     }
 
+    /// Only validates the given transactions rather than all of the unconfirmed transactions in the wallet
+    pub fn with_transactions(mut self, tx_ids: Vec<TxId>) -> Self {
+        self.tx_ids = Some(tx_ids);
+        self
+    }
+
     pub async fn execute(mut self) -> Result<OperationId, TransactionServiceProtocolError<OperationId>> {
         let mut base_node_wallet_client = self
             .connectivity
@@ -107,16 +122,44 @@ where
             "Checking if transactions have been mined since last we checked (Operation ID: {})", self.operation_id
         );
         // Fetch completed but unconfirmed transactions that were not imported
-        let unconfirmed_transactions = self
+        let mut unconfirmed_transactions = self
             .db
             .fetch_unconfirmed_transactions_info()
             .for_protocol(self.operation_id)
             .unwrap();
+        if let Some(tx_ids) = &self.tx_ids {
+            unconfirmed_transactions.retain(|tx| tx_ids.contains(&tx.tx_id));
+        }
+        // A fee bumped transaction may still be mined in one of the versions it replaced
+        let superseded = self
+            .db
+            .fetch_superseded_transactions()
+            .for_protocol(self.operation_id)?;
+
+        // The base node rejects queries larger than its limit, so a larger configured batch size is capped to it
+        let batch_size = self.config.max_tx_query_batch_size.clamp(1, MAX_TX_QUERY_BATCH_SIZE);
+        if batch_size != self.config.max_tx_query_batch_size {
+            warn!(
+                target: LOG_TARGET,
+                "max_tx_query_batch_size of {} is out of range, using {} instead (Operation ID: {})",
+                self.config.max_tx_query_batch_size,
+                batch_size,
+                self.operation_id
+            );
+        }
+        debug!(
+            target: LOG_TARGET,
+            "Querying {} unconfirmed transactions in batches of up to {} (Operation ID: {})",
+            unconfirmed_transactions.len(),
+            batch_size,
+            self.operation_id
+        );
 
+        // Each batch is applied as soon as its response arrives, so the progress made is kept if a later query fails
         let mut state_changed = false;
-        for batch in unconfirmed_transactions.chunks(self.config.max_tx_query_batch_size) {
+        for batch in unconfirmed_transactions.chunks(batch_size) {
             let (mined, unmined, tip_info) = self
-                .query_base_node_for_transactions(batch, &mut base_node_wallet_client)
+                .query_base_node_for_transactions(batch, &superseded, &mut base_node_wallet_client)
                 .await
                 .for_protocol(self.operation_id)?;
             debug!(
@@ -134,6 +177,12 @@ where
                     *num_confirmations >= self.config.num_confirmations_required,
                     self.operation_id
                 );
+                if let Some(version) = superseded
+                    .iter()
+                    .find(|v| v.tx_id == mined_tx.tx_id && v.excess_sig() == Some(&mined_tx.signature))
+                {
+                    self.reinstate_superseded_version(version).await?;
+                }
                 self.update_transaction_as_mined(
                     mined_tx.tx_id,
                     &mined_tx.status,
@@ -146,6 +195,16 @@ where
                 state_changed = true;
             }
             if let Some((tip_height, tip_block, tip_mined_timestamp)) = tip_info {
+                let mut spent_elsewhere = self
+                    .find_inputs_spent_elsewhere(
+                        &unmined,
+                        &superseded,
+                        tip_height,
+                        &tip_block,
+                        &mut base_node_wallet_client,
+                    )
+                    .await
+                    .for_protocol(self.operation_id)?;
                 for unmined_tx in &unmined {
                     // Treat coinbases separately
                     if unmined_tx.is_coinbase() {
@@ -176,6 +235,12 @@ where
                             );
                         }
                     } else {
+                        if let Some(conflicts) = spent_elsewhere.remove(&unmined_tx.tx_id) {
+                            self.cancel_transaction_spent_elsewhere(unmined_tx.tx_id, conflicts)
+                                .await?;
+                            state_changed = true;
+                            continue;
+                        }
                         debug!(
                             target: LOG_TARGET,
                             "Updated transaction {} as unmined (Operation ID: {})", unmined_tx.tx_id, self.operation_id
@@ -271,10 +336,11 @@ where
     async fn query_base_node_for_transactions(
         &self,
         batch: &[UnconfirmedTransactionInfo],
+        superseded: &[SupersededTransaction],
         base_node_client: &mut BaseNodeWalletRpcClient,
     ) -> Result<
         (
-            Vec<(UnconfirmedTransactionInfo, u64, BlockHash, u64, u64)>,
+            Vec<MinedTransactionInfo>,
             Vec<UnconfirmedTransactionInfo>,
             Option<(u64, BlockHash, u64)>,
         ),
@@ -343,6 +409,10 @@ where
         }
 
         let tip = batch_response.tip_hash.try_into()?;
+        let (superseded_mined, unmined) = self
+            .query_superseded_versions(unmined, superseded, base_node_client)
+            .await?;
+        mined.extend(superseded_mined);
 
         Ok((
             mined,
@@ -355,6 +425,290 @@ where
         ))
     }
 
+    /// Looks up the kernels of the versions that the unmined transactions superseded. A transaction with a mined
+    /// version is returned as mined, with the signature of that version.
+    async fn query_superseded_versions(
+        &self,
+        unmined: Vec<UnconfirmedTransactionInfo>,
+        superseded: &[SupersededTransaction],
+        base_node_client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<(Vec<MinedTransactionInfo>, Vec<UnconfirmedTransactionInfo>), TransactionServiceError> {
+        let mut mined = Vec::new();
+        #[allow(clippy::mutable_key_type)]
+        let mut signatures = HashMap::new();
+        for tx_info in &unmined {
+            for version in superseded.iter().filter(|v| v.tx_id == tx_info.tx_id) {
+                if let Some(sig) = version.excess_sig() {
+                    signatures.insert(sig.clone(), tx_info);
+                }
+            }
+        }
+        let sigs = signatures.keys().cloned().collect::<Vec<_>>();
+        for batch in sigs.chunks(MAX_TX_QUERY_BATCH_SIZE) {
+            let batch_response = base_node_client
+                .transaction_batch_query(SignaturesProto {
+                    sigs: batch.iter().map(|s| SignatureProto::from(s.clone())).collect(),
+                })
+                .await?;
+            for response_proto in batch_response.responses {
+                let response = TxQueryBatchResponse::try_from(response_proto)
+                    .map_err(TransactionServiceError::ProtobufConversionError)?;
+                let (Some(tx_info), Some(block_hash)) = (signatures.get(&response.signature), response.block_hash)
+                else {
+                    continue;
+                };
+                if response.location != TxLocation::Mined {
+                    continue;
+                }
+                info!(
+                    target: LOG_TARGET,
+                    "A superseded version of transaction {} was mined (Operation ID: {})",
+                    tx_info.tx_id,
+                    self.operation_id
+                );
+                let mut tx_info = (*tx_info).clone();
+                tx_info.signature = response.signature;
+                mined.push((
+                    tx_info,
+                    response.block_height,
+                    block_hash,
+                    response.confirmations,
+                    response.mined_timestamp.unwrap_or_default(),
+                ));
+            }
+        }
+
+        let unmined = unmined
+            .into_iter()
+            .filter(|tx| !mined.iter().any(|(m, ..)| m.tx_id == tx.tx_id))
+            .collect();
+        Ok((mined, unmined))
+    }
+
+    /// Makes a superseded version that was mined the current version of its transaction again, switching the change
+    /// output back to the one of that version first, so that a failure is retried on the next validation round.
+    async fn reinstate_superseded_version(
+        &mut self,
+        version: &SupersededTransaction,
+    ) -> Result<(), TransactionServiceProtocolError<OperationId>> {
+        info!(
+            target: LOG_TARGET,
+            "Reinstating the mined version of transaction {} paying a fee of {} (Operation ID: {})",
+            version.tx_id,
+            version.fee,
+            self.operation_id
+        );
+        let commitments = version
+            .transaction
+            .body
+            .outputs()
+            .iter()
+            .map(|o| o.commitment.clone())
+            .collect();
+        self.output_manager_handle
+            .reinstate_transaction_outputs(version.tx_id, commitments)
+            .await
+            .for_protocol(self.operation_id)?;
+        self.db
+            .reinstate_superseded_transaction(version.id)
+            .for_protocol(self.operation_id)?;
+        Ok(())
+    }
+
+    /// Checks whether any input of the unmined outbound transactions has already been spent on-chain, which can only
+    /// have happened in a different transaction. Inbound, imported and coinbase transactions are never checked.
+    ///
+    /// Spends are only looked up on the chain that the kernel query saw, i.e. the chain must still include
+    /// `tip_block`, and spends above `tip_height` are ignored. Before reporting a conflict, the kernel of the
+    /// transaction is queried again so that a transaction that was mined in the meantime is not cancelled.
+    async fn find_inputs_spent_elsewhere(
+        &self,
+        unmined: &[UnconfirmedTransactionInfo],
+        superseded: &[SupersededTransaction],
+        tip_height: u64,
+        tip_block: &BlockHash,
+        client: &mut BaseNodeWalletRpcClient,
+    ) -> Result<HashMap<TxId, Vec<SpentInputConflict>>, TransactionServiceError> {
+        let mut spent_inputs = Vec::new();
+        #[allow(clippy::mutable_key_type)]
+        let mut signatures = HashMap::new();
+        for unmined_tx in unmined {
+            if unmined_tx.is_coinbase() ||
+                !matches!(
+                    unmined_tx.status,
+                    TransactionStatus::Completed | TransactionStatus::Broadcast | TransactionStatus::MinedUnconfirmed
+                )
+            {
+                continue;
+            }
+            let completed_tx = self.db.get_completed_transaction(unmined_tx.tx_id)?;
+            if completed_tx.direction != TransactionDirection::Outbound {
+                continue;
+            }
+            for input in completed_tx.transaction.body.inputs() {
+                spent_inputs.push((unmined_tx.tx_id, input.output_hash()));
+            }
+            signatures.insert(unmined_tx.signature.clone(), unmined_tx.tx_id);
+            // Any version of the transaction being mined means the recipient was paid
+            for version in superseded.iter().filter(|v| v.tx_id == unmined_tx.tx_id) {
+                if let Some(sig) = version.excess_sig() {
+                    signatures.insert(sig.clone(), unmined_tx.tx_id);
+                }
+            }
+        }
+        if spent_inputs.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let mut conflicts: HashMap<TxId, Vec<SpentInputConflict>> = HashMap::new();
+        for batch in spent_inputs.chunks(MAX_TX_QUERY_BATCH_SIZE) {
+            let response = match client
+                .query_deleted(QueryDeletedRequest {
+                    chain_must_include_header: tip_block.to_vec(),
+                    hashes: batch.iter().map(|(_, hash)| hash.to_vec()).collect(),
+                })
+                .await
+            {
+                Ok(response) => response,
+                Err(RequestFailed(status)) if status.as_status_code() == NotFound => {
+                    // The chain the kernels were looked up on has since been reorged, try again on the next round
+                    debug!(
+                        target: LOG_TARGET,
+                        "Tip {} was reorged out while looking for spent inputs (Operation ID: {})",
+                        tip_block,
+                        self.operation_id
+                    );
+                    return Ok(HashMap::new());
+                },
+                Err(e) => return Err(e.into()),
+            };
+            if response.data.len() != batch.len() {
+                // We only cancel when the base node is unambiguous, so try again on the next validation round
+                warn!(
+                    target: LOG_TARGET,
+                    "Base node did not send back spent information for all {} inputs (Operation ID: {})",
+                    batch.len(),
+                    self.operation_id
+                );
+                return Ok(HashMap::new());
+            }
+            for ((tx_id, output_hash), data) in batch.iter().zip(response.data.iter()) {
+                if data.height_deleted_at == 0 || data.height_deleted_at > tip_height {
+                    continue;
+                }
+                let spent_in_block = FixedHash::try_from(data.block_deleted_in.clone()).map_err(|_| {
+                    TransactionServiceError::InvalidMessageError("Base node sent malformed block hash".to_string())
+                })?;
+                conflicts.entry(*tx_id).or_default().push(SpentInputConflict {
+                    output_hash: *output_hash,
+                    spent_at_height: data.height_deleted_at,
+                    spent_in_block,
+                    conflicting_tx_id: None,
+                    conflicting_kernel_excess_sig: None,
+                });
+            }
+        }
+        if conflicts.is_empty() {
+            return Ok(conflicts);
+        }
+
+        // The kernel may have been mined since it was queried, in which case it is the transaction that spent the
+        // inputs and will be marked as mined on the next round
+        signatures.retain(|_, tx_id| conflicts.contains_key(tx_id));
+        let response = client
+            .transaction_batch_query(SignaturesProto {
+                sigs: signatures.keys().map(|s| SignatureProto::from(s.clone())).collect(),
+            })
+            .await?;
+        let mut rechecked = 0;
+        for response_proto in response.responses {
+            let response = TxQueryBatchResponse::try_from(response_proto)
+                .map_err(TransactionServiceError::ProtobufConversionError)?;
+            if let Some(tx_id) = signatures.get(&response.signature) {
+                rechecked += 1;
+                if response.location == TxLocation::Mined {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Transaction {} was mined while checking its inputs (Operation ID: {})",
+                        tx_id,
+                        self.operation_id
+                    );
+                    conflicts.remove(tx_id);
+                }
+            }
+        }
+        if rechecked != signatures.len() {
+            warn!(
+                target: LOG_TARGET,
+                "Base node did not send back the location of all {} transactions with spent inputs (Operation ID: {})",
+                signatures.len(),
+                self.operation_id
+            );
+            return Ok(HashMap::new());
+        }
+
+        // The conflicting spend may be another transaction made by this wallet (e.g. a restored wallet or a second
+        // instance using the same seed), in which case we can report exactly which kernel won.
+        if !conflicts.is_empty() {
+            for (tx_id, other_tx) in self.db.get_completed_transactions()? {
+                for (_, tx_conflicts) in conflicts.iter_mut().filter(|(id, _)| **id != tx_id) {
+                    for conflict in tx_conflicts.iter_mut().filter(|c| c.conflicting_tx_id.is_none()) {
+                        if other_tx
+                            .transaction
+                            .body
+                            .inputs()
+                            .iter()
+                            .any(|i| i.output_hash() == conflict.output_hash)
+                        {
+                            conflict.conflicting_tx_id = Some(tx_id);
+                            conflict.conflicting_kernel_excess_sig =
+                                other_tx.transaction.first_kernel_excess_sig().cloned();
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    async fn cancel_transaction_spent_elsewhere(
+        &mut self,
+        tx_id: TxId,
+        conflicts: Vec<SpentInputConflict>,
+    ) -> Result<(), TransactionServiceProtocolError<OperationId>> {
+        warn!(
+            target: LOG_TARGET,
+            "Transaction {} can never be mined, {} of its inputs were spent elsewhere; cancelling it (Operation ID: \
+             {})",
+            tx_id,
+            conflicts.len(),
+            self.operation_id
+        );
+        // Release the outputs in the OMS first, the spent inputs will be picked up as spent by TXO validation
+        self.output_manager_handle
+            .cancel_transaction(tx_id)
+            .await
+            .map_err(|e| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not cancel outputs for TxId: {}: {} (Operation ID: {})", tx_id, e, self.operation_id
+                );
+                e
+            })
+            .for_protocol(self.operation_id)?;
+        self.db
+            .reject_completed_transaction(tx_id, TxCancellationReason::DoubleSpend)
+            .for_protocol(self.operation_id)?;
+
+        self.publish_event(TransactionEvent::InputsSpentElsewhere { tx_id, conflicts });
+        self.publish_event(TransactionEvent::TransactionCancelled(
+            tx_id,
+            TxCancellationReason::DoubleSpend,
+        ));
+        Ok(())
+    }
+
     async fn get_base_node_block_at_height(
         &mut self,
         height: u64,
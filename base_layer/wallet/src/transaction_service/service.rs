@@ -21,26 +21,27 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    collections::{HashMap, HashSet},
+    cmp,
+    collections::{HashMap, HashSet, VecDeque},
     convert::TryInto,
+    path::PathBuf,
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use chrono::{NaiveDateTime, Utc};
-use digest::Digest;
 use futures::{pin_mut, stream::FuturesUnordered, Stream, StreamExt};
 use log::*;
-use rand::rngs::OsRng;
-use sha2::Sha256;
+use rand::{rngs::OsRng, RngCore};
 use tari_common_types::{
     burnt_proof::BurntProof,
     tari_address::TariAddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{PrivateKey, PublicKey, Signature},
+    types::{FixedHash, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::types::CommsPublicKey;
 use tari_comms_dht::outbound::OutboundMessageRequester;
+use tari_contacts::contacts_service::handle::{ContactsLivenessEvent, ContactsServiceHandle};
 use tari_core::{
     consensus::ConsensusManager,
     covenants::Covenant,
@@ -53,10 +54,12 @@ use tari_core::{
     },
     proto::base_node as base_node_proto,
     transactions::{
+        fee::Fee,
         key_manager::TransactionKeyManagerInterface,
         tari_amount::MicroMinotari,
         transaction_components::{
             CodeTemplateRegistration,
+            EncryptedData,
             KernelFeatures,
             OutputFeatures,
             Transaction,
@@ -71,42 +74,62 @@ use tari_core::{
         },
         CryptoFactories,
         ReceiverTransactionProtocol,
+        SenderTransactionProtocol,
     },
 };
-use tari_crypto::{
-    keys::{PublicKey as PKtrait, SecretKey},
-    tari_utilities::ByteArray,
-};
+use tari_crypto::keys::{PublicKey as PKtrait, SecretKey};
 use tari_key_manager::key_manager_service::KeyId;
 use tari_p2p::domain_message::DomainMessage;
 use tari_script::{inputs, one_sided_payment_script, script, stealth_payment_script, TariScript};
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::Hex;
 use tokio::{
-    sync::{mpsc, mpsc::Sender, oneshot, Mutex},
+    sync::{broadcast, mpsc, mpsc::Sender, oneshot, Mutex, RwLock},
     task::JoinHandle,
 };
 
+#[cfg(feature = "metrics")]
+use crate::transaction_service::metrics;
 use crate::{
     base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
-    connectivity_service::WalletConnectivityInterface,
+    connectivity_service::{OnlineStatus, WalletConnectivityInterface},
     output_manager_service::{
         handle::{OutputManagerEvent, OutputManagerHandle},
+        script_lock::htlc_script,
         storage::models::SpendingPriority,
         UtxoSelectionCriteria,
     },
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::{
-        config::TransactionServiceConfig,
-        error::{TransactionServiceError, TransactionServiceProtocolError},
+        config::{TransactionRoutingMechanism, TransactionServiceConfig},
+        error::{TransactionServiceError, TransactionServiceProtocolError, TransactionStorageError},
         handle::{
+            BatchPayment,
             FeePerGramStatsResponse,
+            ScheduledPayment,
+            TransactionBatchValidation,
             TransactionEvent,
             TransactionEventSender,
             TransactionServiceRequest,
             TransactionServiceResponse,
         },
+        offline_signing::{SignedTransaction, UnsignedTransaction, OFFLINE_TRANSACTION_VERSION},
         protocols::{
+            atomic_swap_protocol::{
+                check_participant_lock_height,
+                find_redeemed_pre_images,
+                hash_lock_for,
+                locked_message,
+                new_swap_id,
+                redeemed_message,
+                refunded_message,
+                send_atomic_swap_message,
+                INITIATOR_LOCK_BLOCKS,
+                PARTICIPANT_LOCK_BLOCKS,
+            },
+            multisig_protocol::{run_multisig_message_handler, MultisigProtocol},
+            transaction_batch_send_protocol::{build_one_sided_recipient_output, TransactionBatchSendProtocol},
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_receive_protocol::{TransactionReceiveProtocol, TransactionReceiveProtocolStage},
             transaction_send_protocol::{TransactionSendProtocol, TransactionSendProtocolStage},
@@ -114,11 +137,39 @@ use crate::{
         },
         storage::{
             database::{TransactionBackend, TransactionDatabase},
-            models::{CompletedTransaction, TxCancellationReason},
+            models::{
+                AtomicSwap,
+                AtomicSwapRole,
+                AtomicSwapState,
+                BatchedPayment,
+                BurnRecord,
+                BurnStatus,
+                CompletedTransaction,
+                HeightOrTime,
+                OfflineTransaction,
+                OfflineTransactionStatus,
+                OutboundMessageType,
+                QueuedTransaction,
+                QueuedTransactionStatus,
+                RecurringPayment,
+                RecurringPaymentStatus,
+                ScheduledTransaction,
+                ScheduledTransactionStatus,
+                SendTemplate,
+                TxCancellationReason,
+                WalletTransaction,
+            },
         },
         tasks::{
             check_faux_transaction_status::check_faux_transactions,
+            counterparty_aliases::{persist_removed_contact_alias, resolve_counterparty_aliases, ContactAliasCache},
+            event_journal::run_event_journal,
+            export_history::{export_transaction_history, HistoryDateRange, HistoryExportFormat},
+            fee_estimation::{fetch_mempool_fee_stats, run_fee_estimation, FeeHistory, FeeHistoryCache},
+            import_history::{import_transaction_history, HistoricalTransaction},
+            mempool_state::{run_mempool_state_monitor, MempoolStateCache},
             send_finalized_transaction::send_finalized_transaction_message,
+            send_queued_outbound_message::send_queued_outbound_message,
             send_transaction_cancelled::send_transaction_cancelled_message,
             send_transaction_reply::send_transaction_reply,
         },
@@ -130,6 +181,8 @@ use crate::{
 };
 
 const LOG_TARGET: &str = "wallet::transaction_service::service";
+/// The maximum length of a transaction tag, in characters
+const MAX_TRANSACTION_TAG_LENGTH: usize = 64;
 
 /// TransactionService allows for the management of multiple inbound and outbound transaction protocols
 /// which are uniquely identified by a tx_id. The TransactionService generates and accepts the various protocol
@@ -151,6 +204,8 @@ pub struct TransactionService<
     BNResponseStream,
     TBackend,
     TTxCancelledStream,
+    TMultisigKeyShareStream,
+    TMultisigSigningStream,
     TWalletBackend,
     TWalletConnectivity,
     TKeyManagerInterface,
@@ -162,6 +217,8 @@ pub struct TransactionService<
     transaction_finalized_stream: Option<TTxFinalizedStream>,
     base_node_response_stream: Option<BNResponseStream>,
     transaction_cancelled_stream: Option<TTxCancelledStream>,
+    multisig_key_share_stream: Option<TMultisigKeyShareStream>,
+    multisig_signing_stream: Option<TMultisigSigningStream>,
     request_stream: Option<
         reply_channel::Receiver<TransactionServiceRequest, Result<TransactionServiceResponse, TransactionServiceError>>,
     >,
@@ -178,7 +235,19 @@ pub struct TransactionService<
     base_node_service: BaseNodeServiceHandle,
     last_seen_tip_height: Option<u64>,
     validation_in_progress: Arc<Mutex<()>>,
+    /// Serializes the multisig requests with the handling of the cosigners' multisig messages
+    multisig_lock: Arc<Mutex<()>>,
+    /// The last transaction validated by a bounded validation, the next batch starts after it
+    validation_batch_cursor: Option<TxId>,
     consensus_manager: ConsensusManager,
+    contacts_service: Option<ContactsServiceHandle>,
+    contact_aliases: ContactAliasCache,
+    fee_history: FeeHistoryCache,
+    mempool_states: MempoolStateCache,
+    queued_receive_protocols: VecDeque<(
+        TxId,
+        TransactionReceiveProtocol<TBackend, TWalletConnectivity, TKeyManagerInterface>,
+    )>,
 }
 
 impl<
@@ -188,6 +257,8 @@ impl<
         BNResponseStream,
         TBackend,
         TTxCancelledStream,
+        TMultisigKeyShareStream,
+        TMultisigSigningStream,
         TWalletBackend,
         TWalletConnectivity,
         TKeyManagerInterface,
@@ -199,6 +270,8 @@ impl<
         BNResponseStream,
         TBackend,
         TTxCancelledStream,
+        TMultisigKeyShareStream,
+        TMultisigSigningStream,
         TWalletBackend,
         TWalletConnectivity,
         TKeyManagerInterface,
@@ -210,6 +283,10 @@ where
     BNResponseStream:
         Stream<Item = DomainMessage<Result<base_node_proto::BaseNodeServiceResponse, prost::DecodeError>>>,
     TTxCancelledStream: Stream<Item = DomainMessage<Result<proto::TransactionCancelledMessage, prost::DecodeError>>>,
+    TMultisigKeyShareStream:
+        Stream<Item = DomainMessage<Result<proto::MultisigKeyShareMessage, prost::DecodeError>>> + Send + 'static,
+    TMultisigSigningStream:
+        Stream<Item = DomainMessage<Result<proto::MultisigSigningMessage, prost::DecodeError>>> + Send + 'static,
     TBackend: TransactionBackend + 'static,
     TWalletBackend: WalletBackend + 'static,
     TWalletConnectivity: WalletConnectivityInterface,
@@ -228,6 +305,8 @@ where
         transaction_finalized_stream: TTxFinalizedStream,
         base_node_response_stream: BNResponseStream,
         transaction_cancelled_stream: TTxCancelledStream,
+        multisig_key_share_stream: TMultisigKeyShareStream,
+        multisig_signing_stream: TMultisigSigningStream,
         output_manager_service: OutputManagerHandle,
         core_key_manager_service: TKeyManagerInterface,
         outbound_message_service: OutboundMessageRequester,
@@ -238,6 +317,7 @@ where
         factories: CryptoFactories,
         shutdown_signal: ShutdownSignal,
         base_node_service: BaseNodeServiceHandle,
+        contacts_service: Option<ContactsServiceHandle>,
     ) -> Self {
         // Collect the resources that all protocols will need so that they can be neatly cloned as the protocols are
         // spawned.
@@ -260,6 +340,7 @@ where
             PowerMode::Normal => config.broadcast_monitoring_timeout,
         };
         let timeout_update_watch = Watch::new(timeout);
+        let fee_history = Arc::new(RwLock::new(FeeHistory::new(config.fee_estimation_sample_blocks)));
 
         Self {
             config,
@@ -269,6 +350,8 @@ where
             transaction_finalized_stream: Some(transaction_finalized_stream),
             base_node_response_stream: Some(base_node_response_stream),
             transaction_cancelled_stream: Some(transaction_cancelled_stream),
+            multisig_key_share_stream: Some(multisig_key_share_stream),
+            multisig_signing_stream: Some(multisig_signing_stream),
             request_stream: Some(request_stream),
             event_publisher,
             resources,
@@ -283,7 +366,14 @@ where
             wallet_db,
             last_seen_tip_height: None,
             validation_in_progress: Arc::new(Mutex::new(())),
+            multisig_lock: Arc::new(Mutex::new(())),
+            validation_batch_cursor: None,
             consensus_manager,
+            contacts_service,
+            contact_aliases: Arc::new(RwLock::new(Default::default())),
+            fee_history,
+            mempool_states: Arc::new(RwLock::new(HashMap::new())),
+            queued_receive_protocols: VecDeque::new(),
         }
     }
 
@@ -332,6 +422,14 @@ where
             .expect("Transaction Service initialized without transaction_cancelled_stream")
             .fuse();
         pin_mut!(transaction_cancelled_stream);
+        let multisig_key_share_stream = self
+            .multisig_key_share_stream
+            .take()
+            .expect("Transaction Service initialized without multisig_key_share_stream");
+        let multisig_signing_stream = self
+            .multisig_signing_stream
+            .take()
+            .expect("Transaction Service initialized without multisig_signing_stream");
 
         let mut shutdown = self.resources.shutdown_signal.clone();
 
@@ -351,15 +449,67 @@ where
             JoinHandle<Result<OperationId, TransactionServiceProtocolError<OperationId>>>,
         > = FuturesUnordered::new();
 
+        tokio::spawn(run_fee_estimation(
+            self.fee_history.clone(),
+            self.resources.connectivity.clone(),
+            self.base_node_service.clone(),
+            self.resources.shutdown_signal.clone(),
+        ));
+        tokio::spawn(run_event_journal(
+            self.event_publisher.subscribe(),
+            self.db.clone(),
+            self.config.max_journaled_events,
+            self.resources.shutdown_signal.clone(),
+        ));
+        tokio::spawn(run_mempool_state_monitor(
+            self.mempool_states.clone(),
+            self.db.clone(),
+            self.resources.connectivity.clone(),
+            self.resources.consensus_manager.clone(),
+            self.config.mempool_state_refresh_interval,
+            self.resources.shutdown_signal.clone(),
+        ));
+        tokio::spawn(run_multisig_message_handler(
+            self.multisig_protocol(),
+            self.multisig_lock.clone(),
+            multisig_key_share_stream,
+            multisig_signing_stream,
+            self.resources.shutdown_signal.clone(),
+        ));
+        #[cfg(feature = "metrics")]
+        tokio::spawn(metrics::record_transaction_events(
+            self.resources.wallet_identity.node_identity.node_id().clone(),
+            self.event_publisher.subscribe(),
+            self.resources.shutdown_signal.clone(),
+        ));
+
         let mut base_node_service_event_stream = self.base_node_service.get_event_stream();
         let mut output_manager_event_stream = self.resources.output_manager_service.get_event_stream();
+        let mut contacts_event_stream = self
+            .contacts_service
+            .as_ref()
+            .map(|contacts_service| contacts_service.get_contacts_liveness_event_stream());
 
         debug!(target: LOG_TARGET, "Transaction Service started");
         loop {
             tokio::select! {
+                // Contacts Service event
+                event = next_contacts_event(&mut contacts_event_stream) => {
+                    match event {
+                        Ok(event) => self.handle_contacts_event(&event).await,
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            debug!(target: LOG_TARGET, "Lagging read on contacts event broadcast channel by {} events", n);
+                            self.contact_aliases.write().await.invalidate();
+                        },
+                        Err(broadcast::error::RecvError::Closed) => {
+                            debug!(target: LOG_TARGET, "Contacts event broadcast channel closed");
+                            contacts_event_stream = None;
+                        },
+                    }
+                },
                 event = output_manager_event_stream.recv() => {
                     match event {
-                        Ok(msg) => self.handle_output_manager_service_event(msg).await,
+                        Ok(msg) => self.handle_output_manager_service_event(msg, &mut transaction_broadcast_protocol_handles).await,
                         Err(e) => debug!(target: LOG_TARGET, "Lagging read on base node event broadcast channel: {}", e),
                     };
                 },
@@ -535,6 +685,7 @@ where
                         ),
                         Err(e) => error!(target: LOG_TARGET, "Error resolving Send Transaction Protocol: {:?}", e),
                     };
+                    self.start_queued_receive_protocols(&mut receive_transaction_protocol_handles);
                 }
                 Some(join_result) = transaction_broadcast_protocol_handles.next() => {
                     trace!(target: LOG_TARGET, "Transaction Broadcast protocol has ended with result {:?}", join_result);
@@ -585,6 +736,16 @@ where
         let mut reply_channel = Some(reply_channel);
 
         trace!(target: LOG_TARGET, "Handling Service Request: {}", request);
+        if self.resources.wallet_identity.is_watch_only() && request.is_spend() {
+            if let Some(rp) = reply_channel.take() {
+                let _result = rp.send(Err(TransactionServiceError::WatchOnlyWallet));
+            }
+            return Ok(());
+        }
+        #[cfg(feature = "metrics")]
+        if let Some(kind) = metrics::send_kind(&request) {
+            metrics::sends_initiated(self.resources.wallet_identity.node_identity.node_id(), kind).inc();
+        }
         let response = match request {
             TransactionServiceRequest::SendTransaction {
                 destination,
@@ -593,6 +754,8 @@ where
                 output_features,
                 fee_per_gram,
                 message,
+                memo,
+                routing_mechanism,
             } => {
                 let rp = reply_channel.take().expect("Cannot be missing");
                 self.send_transaction(
@@ -602,6 +765,8 @@ where
                     *output_features,
                     fee_per_gram,
                     message,
+                    memo,
+                    routing_mechanism,
                     TransactionMetadata::default(),
                     send_transaction_join_handles,
                     transaction_broadcast_join_handles,
@@ -777,6 +942,7 @@ where
             TransactionServiceRequest::ImportUtxoWithStatus {
                 amount,
                 source_address,
+                destination_address,
                 message,
                 maturity,
                 import_status,
@@ -787,6 +953,7 @@ where
                 .add_utxo_import_transaction_with_status(
                     amount,
                     source_address,
+                    destination_address,
                     message,
                     maturity,
                     import_status,
@@ -834,18 +1001,485 @@ where
                 Ok(TransactionServiceResponse::NumConfirmationsSet)
             },
             TransactionServiceRequest::ValidateTransactions => self
-                .start_transaction_validation_protocol(transaction_validation_join_handles)
+                .start_transaction_validation_protocol(transaction_validation_join_handles, None)
                 .await
                 .map(TransactionServiceResponse::ValidationStarted),
             TransactionServiceRequest::ReValidateTransactions => self
                 .start_transaction_revalidation(transaction_validation_join_handles)
                 .await
                 .map(TransactionServiceResponse::ValidationStarted),
+            TransactionServiceRequest::ValidateTransactionBatch(max_transactions) => self
+                .start_transaction_batch_validation(max_transactions, transaction_validation_join_handles)
+                .await
+                .map(TransactionServiceResponse::BatchValidationStarted),
+            TransactionServiceRequest::ResendQueuedOutboundMessages(max_messages) => self
+                .resend_queued_outbound_messages(Some(max_messages))
+                .map(
+                    |(resent, remaining)| TransactionServiceResponse::QueuedOutboundMessagesResent {
+                        resent,
+                        remaining,
+                    },
+                ),
             TransactionServiceRequest::GetFeePerGramStatsPerBlock { count } => {
                 let reply_channel = reply_channel.take().expect("reply_channel is Some");
                 self.handle_get_fee_per_gram_stats_per_block_request(count, reply_channel);
                 return Ok(());
             },
+            TransactionServiceRequest::GetFeeEstimates => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_get_fee_estimates_request(reply_channel);
+                return Ok(());
+            },
+            TransactionServiceRequest::GetMempoolStates => Ok(TransactionServiceResponse::MempoolStates(
+                self.mempool_states.read().await.clone(),
+            )),
+            TransactionServiceRequest::GetCounterpartyAliases(tx_ids) => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_get_counterparty_aliases_request(tx_ids, reply_channel);
+                return Ok(());
+            },
+            TransactionServiceRequest::SendBatchTransaction {
+                payments,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .send_batch_transaction(
+                    payments,
+                    selection_criteria,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::SendMultiRecipientTransaction {
+                destination,
+                amount,
+                one_sided_payments,
+                fee_per_gram,
+                message,
+            } => {
+                let rp = reply_channel.take().expect("Cannot be missing");
+                self.send_multi_recipient_transaction(
+                    destination,
+                    amount,
+                    one_sided_payments,
+                    fee_per_gram,
+                    message,
+                    send_transaction_join_handles,
+                    rp,
+                )?;
+                return Ok(());
+            },
+            TransactionServiceRequest::GetBatchedPayments(tx_id) => self
+                .db
+                .fetch_batched_payments(tx_id)
+                .map(TransactionServiceResponse::BatchedPayments)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::GetTransactionMemo(tx_id) => self
+                .db
+                .fetch_transaction_memo(tx_id)
+                .map(TransactionServiceResponse::TransactionMemo)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::ScheduleTransaction {
+                payment,
+                execute_at,
+                expires_at,
+            } => self
+                .schedule_transaction(payment, execute_at, expires_at)
+                .map(TransactionServiceResponse::TransactionScheduled),
+            TransactionServiceRequest::GetScheduledTransactions => self
+                .db
+                .fetch_scheduled_transactions(None)
+                .map(TransactionServiceResponse::ScheduledTransactions)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::CancelScheduledTransaction(id) => self
+                .db
+                .update_scheduled_transaction_status(
+                    id,
+                    ScheduledTransactionStatus::Pending,
+                    ScheduledTransactionStatus::Cancelled,
+                    None,
+                    None,
+                )
+                .map(|_| TransactionServiceResponse::ScheduledTransactionCancelled)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::SendOrQueueTransaction(payment) => {
+                if self.should_queue_send() {
+                    self.queue_transaction(payment)
+                        .map(TransactionServiceResponse::TransactionQueued)
+                } else if payment.one_sided {
+                    self.send_one_sided_transaction(
+                        payment.destination,
+                        payment.amount,
+                        UtxoSelectionCriteria::default(),
+                        OutputFeatures::default(),
+                        payment.fee_per_gram,
+                        payment.message,
+                        transaction_broadcast_join_handles,
+                    )
+                    .await
+                    .map(TransactionServiceResponse::TransactionSent)
+                } else {
+                    let rp = reply_channel.take().expect("Cannot be missing");
+                    self.send_transaction(
+                        payment.destination,
+                        payment.amount,
+                        UtxoSelectionCriteria::default(),
+                        OutputFeatures::default(),
+                        payment.fee_per_gram,
+                        payment.message,
+                        None,
+                        None,
+                        TransactionMetadata::default(),
+                        send_transaction_join_handles,
+                        transaction_broadcast_join_handles,
+                        rp,
+                    )
+                    .await?;
+                    return Ok(());
+                }
+            },
+            TransactionServiceRequest::GetQueuedTransactions => self
+                .db
+                .fetch_queued_transactions(None)
+                .map(TransactionServiceResponse::QueuedTransactions)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::CancelQueuedTransaction(id) => self
+                .db
+                .update_queued_transaction_status(
+                    id,
+                    QueuedTransactionStatus::Queued,
+                    QueuedTransactionStatus::Cancelled,
+                    None,
+                    None,
+                )
+                .map(|_| TransactionServiceResponse::QueuedTransactionCancelled)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::BumpFee { tx_id, fee_per_gram } => self
+                .bump_fee(tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::FeeBumped),
+            TransactionServiceRequest::ChildPaysForParent {
+                parent_tx_id,
+                fee_per_gram,
+            } => self
+                .child_pays_for_parent(parent_tx_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::AddTransactionTags { tx_id, tags } => self
+                .add_transaction_tags(tx_id, tags)
+                .map(|_| TransactionServiceResponse::TransactionTagsUpdated),
+            TransactionServiceRequest::RemoveTransactionTags { tx_id, tags } => self
+                .db
+                .remove_transaction_tags(tx_id, tags.into_iter().map(|t| t.trim().to_string()).collect())
+                .map(|_| TransactionServiceResponse::TransactionTagsUpdated)
+                .map_err(Into::into),
+            TransactionServiceRequest::GetTransactionTags(tx_ids) => self
+                .db
+                .fetch_transaction_tags(&tx_ids)
+                .map(TransactionServiceResponse::TransactionTags)
+                .map_err(Into::into),
+            TransactionServiceRequest::GetTransactionsByTag(tag) => self
+                .db
+                .fetch_tx_ids_by_tag(tag.trim())
+                .map_err(Into::into)
+                .and_then(|tx_ids| self.fetch_wallet_transactions(tx_ids))
+                .map(TransactionServiceResponse::Transactions),
+            TransactionServiceRequest::SearchTransactions(query) => self
+                .db
+                .search_transactions(query.trim())
+                .map_err(Into::into)
+                .and_then(|tx_ids| self.fetch_wallet_transactions(tx_ids))
+                .map(TransactionServiceResponse::Transactions),
+            TransactionServiceRequest::InitiateAtomicSwap {
+                counterparty,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .initiate_atomic_swap(
+                    counterparty,
+                    amount,
+                    selection_criteria,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(|swap| TransactionServiceResponse::AtomicSwap(Box::new(swap))),
+            TransactionServiceRequest::ParticipateAtomicSwap {
+                swap_id,
+                counterparty,
+                amount,
+                hash_lock,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .participate_atomic_swap(
+                    swap_id,
+                    counterparty,
+                    amount,
+                    hash_lock,
+                    selection_criteria,
+                    fee_per_gram,
+                    message,
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(|swap| TransactionServiceResponse::AtomicSwap(Box::new(swap))),
+            TransactionServiceRequest::RedeemAtomicSwap {
+                swap_id,
+                pre_image,
+                fee_per_gram,
+            } => self
+                .redeem_atomic_swap(swap_id, pre_image, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(|swap| TransactionServiceResponse::AtomicSwap(Box::new(swap))),
+            TransactionServiceRequest::RefundAtomicSwap { swap_id, fee_per_gram } => self
+                .refund_atomic_swap(swap_id, fee_per_gram, transaction_broadcast_join_handles)
+                .await
+                .map(|swap| TransactionServiceResponse::AtomicSwap(Box::new(swap))),
+            TransactionServiceRequest::GetAtomicSwaps => self
+                .db
+                .fetch_atomic_swaps()
+                .map(TransactionServiceResponse::AtomicSwaps)
+                .map_err(Into::into),
+            TransactionServiceRequest::CreateMultisigSession {
+                threshold,
+                cosigners,
+                label,
+            } => {
+                let _lock = self.multisig_lock.lock().await;
+                self.multisig_protocol()
+                    .create_session(
+                        threshold,
+                        cosigners.into_iter().map(|a| a.public_key().clone()).collect(),
+                        label,
+                    )
+                    .await
+                    .map(|session| TransactionServiceResponse::MultisigSession(Box::new(session)))
+            },
+            TransactionServiceRequest::GetMultisigSessions => self
+                .db
+                .fetch_multisig_sessions()
+                .map(TransactionServiceResponse::MultisigSessions)
+                .map_err(Into::into),
+            TransactionServiceRequest::RequestMultisigSignature {
+                session_id,
+                cosigners,
+                message,
+                description,
+            } => {
+                let _lock = self.multisig_lock.lock().await;
+                self.multisig_protocol()
+                    .request_signing(
+                        session_id,
+                        cosigners.into_iter().map(|a| a.public_key().clone()).collect(),
+                        message,
+                        description,
+                    )
+                    .await
+                    .map(|signing| TransactionServiceResponse::MultisigSigning(Box::new(signing)))
+            },
+            TransactionServiceRequest::ApproveMultisigSigning(signing_id) => {
+                let _lock = self.multisig_lock.lock().await;
+                self.multisig_protocol()
+                    .approve_signing(signing_id)
+                    .await
+                    .map(|signing| TransactionServiceResponse::MultisigSigning(Box::new(signing)))
+            },
+            TransactionServiceRequest::CancelMultisigSigning(signing_id) => {
+                let _lock = self.multisig_lock.lock().await;
+                self.multisig_protocol()
+                    .cancel_signing(signing_id)
+                    .map(|signing| TransactionServiceResponse::MultisigSigning(Box::new(signing)))
+            },
+            TransactionServiceRequest::GetMultisigSignings(session_id) => self
+                .db
+                .fetch_multisig_signings(session_id)
+                .map(TransactionServiceResponse::MultisigSignings)
+                .map_err(Into::into),
+            TransactionServiceRequest::CreateRecurringPayment {
+                payment,
+                interval,
+                start_at,
+                end_at,
+            } => self
+                .create_recurring_payment(payment, interval, start_at, end_at)
+                .map(TransactionServiceResponse::RecurringPaymentCreated),
+            TransactionServiceRequest::GetRecurringPayments => self
+                .db
+                .fetch_recurring_payments(None)
+                .map(TransactionServiceResponse::RecurringPayments)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::PauseRecurringPayment(id) => self
+                .db
+                .update_recurring_payment_status(
+                    id,
+                    &[RecurringPaymentStatus::Active],
+                    RecurringPaymentStatus::Paused,
+                    None,
+                    None,
+                )
+                .map(|_| TransactionServiceResponse::RecurringPaymentUpdated)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::ResumeRecurringPayment(id) => self
+                .resume_recurring_payment(id)
+                .map(|_| TransactionServiceResponse::RecurringPaymentUpdated),
+            TransactionServiceRequest::CancelRecurringPayment(id) => self
+                .db
+                .update_recurring_payment_status(
+                    id,
+                    &[RecurringPaymentStatus::Active, RecurringPaymentStatus::Paused],
+                    RecurringPaymentStatus::Cancelled,
+                    None,
+                    None,
+                )
+                .map(|_| TransactionServiceResponse::RecurringPaymentUpdated)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::GetEventsSince(cursor) => self
+                .db
+                .fetch_transaction_events_since(cursor)
+                .map(TransactionServiceResponse::Events)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::CreateSendTemplate {
+                name,
+                destination,
+                default_amount,
+                message,
+                fee_priority,
+                one_sided,
+            } => {
+                let now = Utc::now().naive_utc();
+                let template = SendTemplate {
+                    id: OsRng.next_u64(),
+                    name,
+                    destination,
+                    default_amount,
+                    message,
+                    fee_priority,
+                    one_sided,
+                    created_at: now,
+                    updated_at: now,
+                };
+                self.save_send_template(template, false)
+                    .map(TransactionServiceResponse::SendTemplateCreated)
+            },
+            TransactionServiceRequest::UpdateSendTemplate {
+                id,
+                name,
+                destination,
+                default_amount,
+                message,
+                fee_priority,
+                one_sided,
+            } => {
+                let existing = self.db.fetch_send_template(id)?.ok_or_else(|| {
+                    TransactionServiceError::SendTemplateError(format!("No send template with id {}", id))
+                })?;
+                let template = SendTemplate {
+                    id,
+                    name,
+                    destination,
+                    default_amount,
+                    message,
+                    fee_priority,
+                    one_sided,
+                    created_at: existing.created_at,
+                    updated_at: Utc::now().naive_utc(),
+                };
+                self.save_send_template(template, true)
+                    .map(|_| TransactionServiceResponse::SendTemplateUpdated)
+            },
+            TransactionServiceRequest::GetSendTemplate(id) => self
+                .db
+                .fetch_send_template(id)
+                .map(|template| TransactionServiceResponse::SendTemplate(Box::new(template)))
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::GetSendTemplates => self
+                .db
+                .fetch_send_templates()
+                .map(TransactionServiceResponse::SendTemplates)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::DeleteSendTemplate(id) => self
+                .db
+                .delete_send_template(id)
+                .map(|_| TransactionServiceResponse::SendTemplateUpdated)
+                .map_err(TransactionServiceError::from),
+            TransactionServiceRequest::CreateUnsignedTransaction {
+                destination,
+                amount,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .create_unsigned_transaction(destination, amount, selection_criteria, fee_per_gram, message)
+                .await
+                .map(|unsigned| TransactionServiceResponse::UnsignedTransaction(Box::new(unsigned))),
+            TransactionServiceRequest::SignUnsignedTransaction(unsigned) => self
+                .sign_unsigned_transaction(*unsigned)
+                .await
+                .map(|signed| TransactionServiceResponse::SignedTransaction(Box::new(signed))),
+            TransactionServiceRequest::ImportSignedTransaction(signed) => self
+                .import_signed_transaction(*signed, transaction_broadcast_join_handles)
+                .await
+                .map(TransactionServiceResponse::TransactionSent),
+            TransactionServiceRequest::CancelOfflineTransaction(tx_id) => self
+                .cancel_offline_transaction(tx_id)
+                .await
+                .map(|_| TransactionServiceResponse::TransactionCancelled),
+            TransactionServiceRequest::GetOfflineTransactions(status) => self
+                .db
+                .fetch_offline_transactions(status)
+                .map(TransactionServiceResponse::OfflineTransactions)
+                .map_err(Into::into),
+            TransactionServiceRequest::BurnFunds {
+                amount,
+                claim_public_key,
+                selection_criteria,
+                fee_per_gram,
+                message,
+            } => self
+                .burn_tari(
+                    amount,
+                    selection_criteria,
+                    fee_per_gram,
+                    message,
+                    Some(claim_public_key),
+                    transaction_broadcast_join_handles,
+                )
+                .await
+                .map(|(tx_id, proof)| TransactionServiceResponse::BurntTransactionSent {
+                    tx_id,
+                    proof: Box::new(proof),
+                }),
+            TransactionServiceRequest::GetBurnProof(tx_id) => self
+                .get_burn_record(tx_id)
+                .map(|record| TransactionServiceResponse::BurnProof(Box::new(record))),
+            TransactionServiceRequest::GetBurnProofs(status) => self
+                .get_burn_records(status)
+                .map(TransactionServiceResponse::BurnProofs),
+            TransactionServiceRequest::MarkBurnClaimed(tx_id) => self
+                .mark_burn_claimed(tx_id)
+                .map(|_| TransactionServiceResponse::BurnMarkedClaimed),
+            TransactionServiceRequest::ExportHistory {
+                format,
+                date_range,
+                path,
+            } => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_export_history_request(format, date_range, path, reply_channel);
+                return Ok(());
+            },
+            TransactionServiceRequest::ImportHistory(transactions) => {
+                let reply_channel = reply_channel.take().expect("reply_channel is Some");
+                self.handle_import_history_request(transactions, reply_channel);
+                return Ok(());
+            },
         };
 
         // If the individual handlers did not already send the API response then do it here.
@@ -858,6 +1492,155 @@ where
         Ok(())
     }
 
+    fn add_transaction_tags(&mut self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionServiceError> {
+        let tags = tags.into_iter().map(|t| t.trim().to_string()).collect::<Vec<_>>();
+        if let Some(tag) = tags
+            .iter()
+            .find(|t| t.is_empty() || t.chars().count() > MAX_TRANSACTION_TAG_LENGTH)
+        {
+            return Err(TransactionServiceError::InvalidTransactionTag(format!(
+                "'{}' must be between 1 and {} characters long",
+                tag, MAX_TRANSACTION_TAG_LENGTH
+            )));
+        }
+        if self.db.get_any_transaction(tx_id)?.is_none() {
+            return Err(TransactionServiceError::TransactionDoesNotExistError);
+        }
+        self.db.add_transaction_tags(tx_id, tags)?;
+        Ok(())
+    }
+
+    /// Loads the given transactions, skipping any that no longer exist
+    fn fetch_wallet_transactions(&self, tx_ids: Vec<TxId>) -> Result<Vec<WalletTransaction>, TransactionServiceError> {
+        let mut transactions = Vec::with_capacity(tx_ids.len());
+        for tx_id in tx_ids {
+            if let Some(tx) = self.db.get_any_transaction(tx_id)? {
+                transactions.push(tx);
+            }
+        }
+        Ok(transactions)
+    }
+
+    /// Replies with the address book alias of the counterparty of each of the given transactions. Aliases are
+    /// resolved from the cached contact aliases so that renamed contacts are reflected, falling back to the alias that
+    /// was persisted when the contact was removed.
+    fn handle_get_counterparty_aliases_request(
+        &self,
+        tx_ids: Vec<TxId>,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let db = self.db.clone();
+        let contacts_service = self.contacts_service.clone();
+        let contact_aliases = self.contact_aliases.clone();
+
+        tokio::spawn(async move {
+            let resp = resolve_counterparty_aliases(db, contacts_service, contact_aliases, tx_ids)
+                .await
+                .map(TransactionServiceResponse::CounterpartyAliases);
+            if reply_channel.send(resp).is_err() {
+                warn!(target: LOG_TARGET, "Failed to send service reply for counterparty aliases request");
+            }
+        });
+    }
+
+    /// Drops the cached contact aliases when a contact changes. The alias of a removed contact is persisted against
+    /// its transactions so that it can still be shown.
+    async fn handle_contacts_event(&mut self, event: &ContactsLivenessEvent) {
+        match event {
+            ContactsLivenessEvent::ContactSaved(_) => self.contact_aliases.write().await.invalidate(),
+            ContactsLivenessEvent::ContactRemoved(contact) => {
+                self.contact_aliases.write().await.invalidate();
+                match persist_removed_contact_alias(&self.db, &contact.address, &contact.alias) {
+                    Ok(n) => trace!(
+                        target: LOG_TARGET,
+                        "Persisted alias of removed contact against {} transactions",
+                        n
+                    ),
+                    Err(e) => warn!(target: LOG_TARGET, "Could not persist alias of removed contact: {}", e),
+                }
+            },
+            _ => {},
+        }
+    }
+
+    /// Replies with the cached fee estimates, sampling the mempool of the base node first if no block has been sampled
+    /// since startup
+    fn handle_get_fee_estimates_request(
+        &self,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let mut connectivity = self.resources.connectivity.clone();
+        let fee_history = self.fee_history.clone();
+        let tip_height = self.last_seen_tip_height.unwrap_or_default();
+
+        tokio::spawn(async move {
+            let cached = fee_history.read().await.estimates();
+            let resp = match cached {
+                Some(estimates) => Ok(TransactionServiceResponse::FeeEstimates(estimates)),
+                None => match fetch_mempool_fee_stats(&mut connectivity).await {
+                    Ok(stats) => {
+                        let mut history = fee_history.write().await;
+                        history.record(tip_height, &stats);
+                        history
+                            .estimates()
+                            .map(TransactionServiceResponse::FeeEstimates)
+                            .ok_or(TransactionServiceError::UnexpectedApiResponse)
+                    },
+                    Err(e) => Err(e),
+                },
+            };
+            if reply_channel.send(resp).is_err() {
+                warn!(target: LOG_TARGET, "Failed to send service reply for fee estimates request");
+            }
+        });
+    }
+
+    fn handle_export_history_request(
+        &self,
+        format: HistoryExportFormat,
+        date_range: HistoryDateRange,
+        path: PathBuf,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let db = self.db.clone();
+        let own_address = self.resources.wallet_identity.address.clone();
+
+        // The export touches the database and the file system, neither of which should block the service loop
+        tokio::task::spawn_blocking(move || {
+            let resp = export_transaction_history(&db, &own_address, format, &date_range, &path)
+                .map(TransactionServiceResponse::HistoryExported);
+            if reply_channel.send(resp).is_err() {
+                warn!(target: LOG_TARGET, "Failed to send service reply for export history request");
+            }
+        });
+    }
+
+    fn handle_import_history_request(
+        &self,
+        transactions: Vec<HistoricalTransaction>,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) {
+        let db = self.db.clone();
+        let event_publisher = self.event_publisher.clone();
+
+        // A bulk import can be large, so the inserts are kept off the service loop
+        tokio::task::spawn_blocking(move || {
+            let resp = import_transaction_history(&db, transactions);
+            if let Ok(tx_ids) = &resp {
+                if !tx_ids.is_empty() {
+                    let _size =
+                        event_publisher.send(Arc::new(TransactionEvent::TransactionHistoryImported(tx_ids.len())));
+                }
+            }
+            if reply_channel
+                .send(resp.map(TransactionServiceResponse::HistoryImported))
+                .is_err()
+            {
+                warn!(target: LOG_TARGET, "Failed to send service reply for import history request");
+            }
+        });
+    }
+
     fn handle_get_fee_per_gram_stats_per_block_request(
         &self,
         count: usize,
@@ -913,7 +1696,7 @@ where
             },
             BaseNodeEvent::NewBlockDetected(_hash, height) => {
                 let _operation_id = self
-                    .start_transaction_validation_protocol(transaction_validation_join_handles)
+                    .start_transaction_validation_protocol(transaction_validation_join_handles, None)
                     .await
                     .map_err(|e| {
                         warn!(target: LOG_TARGET, "Error validating  txos: {:?}", e);
@@ -921,11 +1704,76 @@ where
                     });
 
                 self.last_seen_tip_height = Some(height);
+
+                let db = self.db.clone();
+                let connectivity = self.resources.connectivity.clone();
+                let event_publisher = self.event_publisher.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = find_redeemed_pre_images(db, connectivity, event_publisher).await {
+                        warn!(target: LOG_TARGET, "Could not look up redeemed atomic swaps: {}", e);
+                    }
+                });
+            },
+            BaseNodeEvent::Reorged {
+                depth,
+                old_tip,
+                new_tip,
+            } => {
+                // The validation started by the NewBlockDetected event that follows will look these up again
+                let fork_height = old_tip.height.saturating_sub(depth);
+                match self.db.set_transactions_mined_above_height_as_unmined(fork_height) {
+                    Ok(tx_ids) => info!(
+                        target: LOG_TARGET,
+                        "Reorg of depth {} from {} to {}, revalidating {} transaction(s) mined above height {}",
+                        depth,
+                        old_tip,
+                        new_tip,
+                        tx_ids.len(),
+                        fork_height
+                    ),
+                    Err(e) => warn!(
+                        target: LOG_TARGET,
+                        "Could not reset transactions affected by the reorg at height {}: {}", fork_height, e
+                    ),
+                }
             },
         }
     }
 
-    async fn handle_output_manager_service_event(&mut self, event: Arc<OutputManagerEvent>) {
+    async fn handle_output_manager_service_event(
+        &mut self,
+        event: Arc<OutputManagerEvent>,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) {
+        if let OutputManagerEvent::ConsolidationTransactionCreated {
+            tx_id,
+            transaction,
+            amount,
+            fee,
+            num_inputs,
+        } = (*event).clone()
+        {
+            if let Err(e) = self.submit_transaction_to_self(
+                transaction_broadcast_join_handles,
+                tx_id,
+                *transaction,
+                fee,
+                amount,
+                format!("Automatic consolidation of {} outputs", num_inputs),
+            ) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not submit consolidation transaction {}: {}", tx_id, e
+                );
+                // Release the joined outputs so that they can be spent again
+                if let Err(e) = self.resources.output_manager_service.cancel_transaction(tx_id).await {
+                    error!(target: LOG_TARGET, "Could not release consolidation outputs: {}", e);
+                }
+            }
+            return;
+        }
         if let OutputManagerEvent::TxoValidationSuccess(_) = (*event).clone() {
             let db = self.db.clone();
             let output_manager_handle = self.resources.output_manager_service.clone();
@@ -943,6 +1791,7 @@ where
                 db,
                 event_publisher,
                 tip_height,
+                self.resources.config.num_confirmations_required,
             ));
         }
     }
@@ -952,6 +1801,8 @@ where
     /// 'dest_pubkey': The Comms pubkey of the recipient node
     /// 'amount': The amount of Tari to send to the recipient
     /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    /// 'memo': An optional private note that is encrypted to the recipient's public key
+    /// 'routing_mechanism': Overrides the configured routing mechanism for this transaction if provided
     pub async fn send_transaction(
         &mut self,
         destination: TariAddress,
@@ -960,6 +1811,8 @@ where
         output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
+        memo: Option<String>,
+        routing_mechanism: Option<TransactionRoutingMechanism>,
         tx_meta: TransactionMetadata,
         join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
@@ -1016,6 +1869,9 @@ where
                     None,
                 ),
             )?;
+            if let Some(memo) = memo {
+                self.db.set_transaction_memo(tx_id, memo)?;
+            }
 
             let _result = reply_channel
                 .send(Ok(TransactionServiceResponse::TransactionSent(tx_id)))
@@ -1046,13 +1902,285 @@ where
             Some(reply_channel),
             TransactionSendProtocolStage::Initial,
             None,
-        );
+        )
+        .with_memo(memo)
+        .with_routing_mechanism(routing_mechanism);
+        let join_handle = tokio::spawn(protocol.execute());
+        join_handles.push(join_handle);
+
+        Ok(())
+    }
+
+    /// Sends a new transaction to an interactive recipient that also pays the given recipients one-sided. The
+    /// one-sided outputs are built and recorded by the send protocol before the interactive negotiation starts.
+    pub fn send_multi_recipient_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        one_sided_payments: Vec<BatchPayment>,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TransactionSendResult, TransactionServiceProtocolError<TxId>>>,
+        >,
+        reply_channel: oneshot::Sender<Result<TransactionServiceResponse, TransactionServiceError>>,
+    ) -> Result<(), TransactionServiceError> {
+        if destination.network() != self.resources.wallet_identity.network {
+            let _result = reply_channel
+                .send(Err(TransactionServiceError::InvalidNetwork))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if self.resources.wallet_identity.address.public_key() == destination.public_key() {
+            let reason = "The interactive recipient of a multi-recipient transaction cannot be this wallet";
+            let _result = reply_channel
+                .send(Err(TransactionServiceError::BatchTransactionError(reason.to_string())))
+                .map_err(|e| {
+                    warn!(target: LOG_TARGET, "Failed to send service reply");
+                    e
+                });
+            return Err(TransactionServiceError::BatchTransactionError(reason.to_string()));
+        }
+
+        let tx_id = TxId::new_random();
+        let (tx_reply_sender, tx_reply_receiver) = mpsc::channel(100);
+        let (cancellation_sender, cancellation_receiver) = oneshot::channel();
+        self.pending_transaction_reply_senders.insert(tx_id, tx_reply_sender);
+        self.send_transaction_cancellation_senders
+            .insert(tx_id, cancellation_sender);
+
+        let protocol = TransactionSendProtocol::new(
+            tx_id,
+            self.resources.clone(),
+            tx_reply_receiver,
+            cancellation_receiver,
+            destination,
+            amount,
+            fee_per_gram,
+            message,
+            TransactionMetadata::default(),
+            Some(reply_channel),
+            TransactionSendProtocolStage::Initial,
+            None,
+        )
+        .with_additional_payments(one_sided_payments);
         let join_handle = tokio::spawn(protocol.execute());
         join_handles.push(join_handle);
 
         Ok(())
     }
 
+    /// Rebuilds a pending, not yet mined, transaction at a higher fee per gram from the same inputs and broadcasts the
+    /// replacement in its place. The recipients are paid again with newly built one-sided outputs, so transactions with
+    /// an interactive recipient cannot be bumped.
+    pub async fn bump_fee(
+        &mut self,
+        tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<MicroMinotari, TransactionServiceError> {
+        let completed_tx = self.db.get_completed_transaction(tx_id)?;
+        if !(completed_tx.status == TransactionStatus::Completed || completed_tx.status == TransactionStatus::Broadcast)
+        {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Transaction {} is {} and can no longer be replaced",
+                tx_id, completed_tx.status
+            )));
+        }
+        if completed_tx.direction != TransactionDirection::Outbound {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "Transaction {} was not sent by this wallet",
+                tx_id
+            )));
+        }
+
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let weight = completed_tx.transaction.calculate_weight(
+            self.consensus_manager
+                .consensus_constants(tip_height)
+                .transaction_weight_params(),
+        )?;
+        let current_fee_per_gram = MicroMinotari::from(u64::from(completed_tx.fee) / weight.max(1));
+        if fee_per_gram <= current_fee_per_gram {
+            return Err(TransactionServiceError::FeeBumpError(format!(
+                "The new fee per gram of {} must be higher than the current {}",
+                fee_per_gram, current_fee_per_gram
+            )));
+        }
+
+        // A batch lists each of its recipients and a multi-recipient transaction lists its one-sided recipients, which
+        // is in addition to the interactive payment to its destination. Any other transaction only pays its
+        // destination.
+        let batched_payments = self.db.fetch_batched_payments(tx_id)?;
+        let batched_total = batched_payments.iter().map(|p| p.amount).sum::<MicroMinotari>();
+        let pays_destination = batched_payments.is_empty() ||
+            (completed_tx.destination_address != TariAddress::default() &&
+                (completed_tx.amount != batched_total ||
+                    !batched_payments
+                        .iter()
+                        .any(|p| p.destination_address == completed_tx.destination_address)));
+        let mut payments = batched_payments
+            .iter()
+            .map(|p| BatchPayment {
+                destination: p.destination_address.clone(),
+                amount: p.amount,
+                message: p.message.clone(),
+            })
+            .collect::<Vec<_>>();
+        if pays_destination {
+            payments.push(BatchPayment {
+                destination: completed_tx.destination_address.clone(),
+                amount: completed_tx.amount,
+                message: completed_tx.message.clone(),
+            });
+        }
+        let outputs = completed_tx.transaction.body.outputs();
+        for payment in &payments {
+            let script = one_sided_payment_script(payment.destination.public_key());
+            if !outputs.iter().any(|o| o.script == script) {
+                return Err(TransactionServiceError::FeeBumpError(format!(
+                    "The payment to {} is not one-sided and cannot be rebuilt without the recipient",
+                    payment.destination
+                )));
+            }
+        }
+
+        let mut recipient_outputs = Vec::with_capacity(payments.len());
+        let mut replacement_payments = Vec::with_capacity(batched_payments.len());
+        for payment in &payments {
+            let (output, sender_offset_key_id) = build_one_sided_recipient_output(
+                &self.resources.transaction_key_manager_service,
+                &self.resources.wallet_identity,
+                payment,
+            )
+            .await?;
+            if !batched_payments.is_empty() {
+                replacement_payments.push(BatchedPayment {
+                    tx_id,
+                    destination_address: payment.destination.clone(),
+                    amount: payment.amount,
+                    message: payment.message.clone(),
+                    output_hash: output.hash(&self.resources.transaction_key_manager_service).await?,
+                });
+            }
+            recipient_outputs.push((output, sender_offset_key_id));
+        }
+
+        let (fee, transaction) = self
+            .resources
+            .output_manager_service
+            .create_fee_bump_transaction(tx_id, recipient_outputs, fee_per_gram)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Replacing transaction TxId: {} paying a fee of {} with one paying {}", tx_id, completed_tx.fee, fee
+        );
+
+        let replacement = CompletedTransaction::new(
+            tx_id,
+            completed_tx.source_address,
+            completed_tx.destination_address,
+            completed_tx.amount,
+            fee,
+            transaction,
+            TransactionStatus::Completed,
+            completed_tx.message,
+            Utc::now().naive_utc(),
+            TransactionDirection::Outbound,
+            None,
+            None,
+            None,
+        );
+        if let Err(e) = self
+            .db
+            .supersede_completed_transaction(replacement.clone(), replacement_payments)
+        {
+            // The original is still the current version, e.g. because it was mined in the meantime, so its change
+            // output must be the pending one again
+            warn!(
+                target: LOG_TARGET,
+                "Could not store the replacement of transaction TxId: {}, restoring its original change: {}", tx_id, e
+            );
+            let commitments = outputs.iter().map(|o| o.commitment.clone()).collect();
+            self.resources
+                .output_manager_service
+                .reinstate_transaction_outputs(tx_id, commitments)
+                .await?;
+            return Err(e.into());
+        }
+
+        // A broadcast protocol that is still running for this transaction picks up the replacement on its next
+        // attempt, otherwise a new one is started
+        self.broadcast_completed_transaction(replacement, transaction_broadcast_join_handles)?;
+
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionFeeBumped { tx_id, fee }));
+
+        Ok(fee)
+    }
+
+    /// Builds and broadcasts a child transaction that spends the unconfirmed outputs of a broadcast, not yet mined,
+    /// transaction back to this wallet. The child's fee is set so that the package of parent and child pays
+    /// `fee_per_gram`; the mempool accepts the child as it depends on outputs already in its unconfirmed pool.
+    pub async fn child_pays_for_parent(
+        &mut self,
+        parent_tx_id: TxId,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let parent = self.db.get_completed_transaction(parent_tx_id)?;
+        if !(parent.status == TransactionStatus::Completed || parent.status == TransactionStatus::Broadcast) {
+            return Err(TransactionServiceError::ChildPaysForParentError(format!(
+                "Transaction {} is {} and has no unconfirmed outputs to spend",
+                parent_tx_id, parent.status
+            )));
+        }
+
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        let parent_weight = parent.transaction.calculate_weight(
+            self.consensus_manager
+                .consensus_constants(tip_height)
+                .transaction_weight_params(),
+        )?;
+        let parent_fee_per_gram = MicroMinotari::from(u64::from(parent.fee) / parent_weight.max(1));
+        if fee_per_gram <= parent_fee_per_gram {
+            return Err(TransactionServiceError::ChildPaysForParentError(format!(
+                "The fee per gram of {} must be higher than the parent's {}",
+                fee_per_gram, parent_fee_per_gram
+            )));
+        }
+
+        let (tx_id, transaction, amount) = self
+            .resources
+            .output_manager_service
+            .create_child_pays_for_parent_transaction(parent_tx_id, parent.fee, parent_weight, fee_per_gram)
+            .await?;
+        let fee = transaction.body.get_total_fee()?;
+        info!(
+            target: LOG_TARGET,
+            "Child TxId: {} pays a fee of {} for parent TxId: {}", tx_id, fee, parent_tx_id
+        );
+
+        self.submit_transaction_to_self(
+            transaction_broadcast_join_handles,
+            tx_id,
+            transaction,
+            fee,
+            amount,
+            format!("Child-pays-for-parent of {}", parent_tx_id),
+        )?;
+
+        Ok(tx_id)
+    }
+
     /// broadcasts a SHA-XTR atomic swap transaction
     /// # Arguments
     /// 'dest_pubkey': The Comms pubkey of the recipient node
@@ -1070,23 +2198,56 @@ where
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<Box<(TxId, PublicKey, TransactionOutput)>, TransactionServiceError> {
-        let dest_pubkey = destination.public_key();
-        let tx_id = TxId::new_random();
         // this can be anything, so lets generate a random private key
         let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
-        let hash: [u8; 32] = Sha256::digest(pre_image.as_bytes()).into();
+        let hash = hash_lock_for(&pre_image);
 
-        // lets make the unlock height a day from now, 2 min blocks which gives us 30 blocks per hour * 24 hours
+        // lets make the unlock height a day from now
+        let height = self.last_seen_tip_height.unwrap_or(0) + INITIATOR_LOCK_BLOCKS;
+
+        let (tx_id, tx_output) = self
+            .send_htlc_transaction(
+                destination,
+                amount,
+                hash,
+                height,
+                selection_criteria,
+                fee_per_gram,
+                message,
+                transaction_broadcast_join_handles,
+            )
+            .await?;
+
+        Ok(Box::new((tx_id, pre_image, tx_output)))
+    }
+
+    /// Builds and broadcasts a transaction paying `amount` into an HTLC output that `destination` can claim with the
+    /// pre-image of `hash`, or that this wallet can reclaim once `height` is reached. Returns the transaction id and
+    /// the HTLC output.
+    #[allow(clippy::too_many_lines)]
+    async fn send_htlc_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        hash: FixedHash,
+        height: u64,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<(TxId, TransactionOutput), TransactionServiceError> {
+        let dest_pubkey = destination.public_key();
+        let tx_id = TxId::new_random();
         let tip_height = self.last_seen_tip_height.unwrap_or(0);
-        let height = tip_height + (24 * 30);
 
         // lets create the HTLC script
-        let script = script!(
-            HashSha256 PushHash(Box::new(hash)) Equal IfThen
-                PushPubKey(Box::new(dest_pubkey.clone()))
-            Else
-                CheckHeightVerify(height) PushPubKey(Box::new(self.resources.wallet_identity.node_identity.public_key().clone()))
-            EndIf
+        let script = htlc_script(
+            hash,
+            dest_pubkey.clone(),
+            self.resources.wallet_identity.node_identity.public_key().clone(),
+            height,
         );
 
         // Empty covenant
@@ -1271,72 +2432,317 @@ where
             .to_transaction_output(&self.resources.transaction_key_manager_service)
             .await?;
 
-        Ok(Box::new((tx_id, pre_image, tx_output)))
+        Ok((tx_id, tx_output))
     }
 
-    #[allow(clippy::too_many_lines)]
-    async fn send_one_sided_or_stealth(
+    /// Locks `amount` for `counterparty` behind a newly generated pre-image, see
+    /// [TransactionServiceHandle::initiate_atomic_swap]
+    async fn initiate_atomic_swap(
         &mut self,
-        dest_address: TariAddress,
+        counterparty: TariAddress,
         amount: MicroMinotari,
         selection_criteria: UtxoSelectionCriteria,
-        output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
-        script: TariScript,
-    ) -> Result<TxId, TransactionServiceError> {
-        let tx_id = TxId::new_random();
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+        let hash_lock = hash_lock_for(&pre_image);
+        let lock_height = self.atomic_swap_tip_height()? + INITIATOR_LOCK_BLOCKS;
 
-        // Prepare sender part of the transaction
-        let mut stp = self
-            .resources
-            .output_manager_service
-            .prepare_transaction_to_send(
-                tx_id,
+        let (tx_id, output) = self
+            .send_htlc_transaction(
+                counterparty.clone(),
                 amount,
+                hash_lock,
+                lock_height,
                 selection_criteria,
-                output_features,
                 fee_per_gram,
-                TransactionMetadata::default(),
                 message.clone(),
-                script.clone(),
-                Covenant::default(),
-                MicroMinotari::zero(),
+                transaction_broadcast_join_handles,
             )
             .await?;
 
-        // This call is needed to advance the state from `SingleRoundMessageReady` to `SingleRoundMessageReady`,
-        // but the returned value is not used
-        let _single_round_sender_data = stp
-            .build_single_round_message(&self.resources.transaction_key_manager_service)
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let now = Utc::now().naive_utc();
+        let swap = AtomicSwap {
+            swap_id: new_swap_id(),
+            role: AtomicSwapRole::Initiator,
+            state: AtomicSwapState::Locked,
+            counterparty,
+            amount,
+            hash_lock,
+            pre_image: Some(pre_image),
+            lock_height,
+            lock_tx_id: Some(tx_id),
+            lock_output_hash: Some(output.hash()),
+            counterparty_output_hash: None,
+            settle_tx_id: None,
+            message,
+            created_at: now,
+            updated_at: now,
+        };
+        let notification = locked_message(&swap);
+        self.update_atomic_swap(swap, notification)
+    }
 
-        self.resources
-            .output_manager_service
-            .confirm_pending_transaction(tx_id)
-            .await
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+    /// Locks `amount` for `counterparty` behind their hash lock, see
+    /// [TransactionServiceHandle::participate_atomic_swap]
+    async fn participate_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        counterparty: TariAddress,
+        amount: MicroMinotari,
+        hash_lock: FixedHash,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        let existing = self.db.fetch_atomic_swap(swap_id)?.ok_or_else(|| {
+            TransactionServiceError::AtomicSwapError(format!(
+                "Swap {} has not been proposed to us, so the initiator's lock height is not known",
+                swap_id
+            ))
+        })?;
+        if existing.lock_tx_id.is_some() {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Funds have already been locked for swap {}",
+                swap_id
+            )));
+        }
+        if existing.counterparty.public_key() != counterparty.public_key() || existing.hash_lock != hash_lock {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Swap {} was proposed with a different counterparty or hash lock",
+                swap_id
+            )));
+        }
+        let lock_height = self.atomic_swap_tip_height()? + PARTICIPANT_LOCK_BLOCKS;
+        check_participant_lock_height(lock_height, existing.lock_height)?;
 
-        // Prepare receiver part of the transaction
+        let (tx_id, output) = self
+            .send_htlc_transaction(
+                counterparty.clone(),
+                amount,
+                hash_lock,
+                lock_height,
+                selection_criteria,
+                fee_per_gram,
+                message.clone(),
+                transaction_broadcast_join_handles,
+            )
+            .await?;
 
-        // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is fed into
-        // KDFs to produce the spending, rewind, and encryption keys
-        let sender_offset_private_key = stp
-            .get_recipient_sender_offset_private_key()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?
-            .ok_or(TransactionServiceProtocolError::new(
-                tx_id,
-                TransactionServiceError::InvalidKeyId("Missing sender offset keyid".to_string()),
-            ))?;
+        let now = Utc::now().naive_utc();
+        let swap = AtomicSwap {
+            swap_id,
+            role: AtomicSwapRole::Participant,
+            state: AtomicSwapState::Locked,
+            counterparty,
+            amount,
+            hash_lock,
+            pre_image: None,
+            lock_height,
+            lock_tx_id: Some(tx_id),
+            lock_output_hash: Some(output.hash()),
+            counterparty_output_hash: existing.counterparty_output_hash,
+            settle_tx_id: None,
+            message,
+            created_at: existing.created_at,
+            updated_at: now,
+        };
+        let notification = locked_message(&swap);
+        self.update_atomic_swap(swap, notification)
+    }
 
-        let shared_secret = self
+    /// Claims the counterparty's HTLC output, see [TransactionServiceHandle::redeem_atomic_swap]
+    async fn redeem_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        pre_image: Option<PublicKey>,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        let mut swap = self
+            .db
+            .fetch_atomic_swap(swap_id)?
+            .ok_or(TransactionServiceError::AtomicSwapNotFound(swap_id))?;
+        if swap.state.is_final() {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Swap {} is already {}",
+                swap_id, swap.state
+            )));
+        }
+        let output_hash = swap.counterparty_output_hash.ok_or_else(|| {
+            TransactionServiceError::AtomicSwapError(format!(
+                "The counterparty has not locked any funds on chain for swap {}",
+                swap_id
+            ))
+        })?;
+        let pre_image = pre_image.or_else(|| swap.pre_image.clone()).ok_or_else(|| {
+            TransactionServiceError::AtomicSwapError(format!("The pre-image for swap {} is not known", swap_id))
+        })?;
+        if hash_lock_for(&pre_image) != swap.hash_lock {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Pre-image does not match the hash lock of swap {}",
+                swap_id
+            )));
+        }
+
+        let (tx_id, fee, amount, tx) = self
             .resources
-            .transaction_key_manager_service
-            .get_diffie_hellman_shared_secret(&sender_offset_private_key, dest_address.public_key())
+            .output_manager_service
+            .create_claim_sha_atomic_swap_transaction(output_hash, pre_image.clone(), fee_per_gram)
+            .await?;
+        self.submit_transaction_to_self(
+            transaction_broadcast_join_handles,
+            tx_id,
+            tx,
+            fee,
+            amount,
+            format!("Redeem atomic swap {}", swap_id),
+        )?;
+
+        swap.state = AtomicSwapState::Redeemed;
+        swap.pre_image = Some(pre_image.clone());
+        swap.settle_tx_id = Some(tx_id);
+        swap.updated_at = Utc::now().naive_utc();
+        self.update_atomic_swap(swap, Some(redeemed_message(swap_id, &pre_image)))
+    }
+
+    /// Reclaims this wallet's HTLC output once the lock height is reached, see
+    /// [TransactionServiceHandle::refund_atomic_swap]
+    async fn refund_atomic_swap(
+        &mut self,
+        swap_id: u64,
+        fee_per_gram: MicroMinotari,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        let mut swap = self
+            .db
+            .fetch_atomic_swap(swap_id)?
+            .ok_or(TransactionServiceError::AtomicSwapNotFound(swap_id))?;
+        let output_hash = match (swap.state, swap.lock_output_hash) {
+            (AtomicSwapState::Locked, Some(output_hash)) => output_hash,
+            _ => {
+                return Err(TransactionServiceError::AtomicSwapError(format!(
+                    "Swap {} has no locked funds to refund, it is {}",
+                    swap_id, swap.state
+                )))
+            },
+        };
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        if tip_height < swap.lock_height {
+            return Err(TransactionServiceError::AtomicSwapError(format!(
+                "Swap {} can only be refunded from height {}, the chain is at {}",
+                swap_id, swap.lock_height, tip_height
+            )));
+        }
+
+        let (tx_id, fee, amount, tx) = self
+            .resources
+            .output_manager_service
+            .create_htlc_refund_transaction(output_hash, fee_per_gram)
+            .await?;
+        self.submit_transaction_to_self(
+            transaction_broadcast_join_handles,
+            tx_id,
+            tx,
+            fee,
+            amount,
+            format!("Refund atomic swap {}", swap_id),
+        )?;
+
+        swap.state = AtomicSwapState::Refunded;
+        swap.settle_tx_id = Some(tx_id);
+        swap.updated_at = Utc::now().naive_utc();
+        self.update_atomic_swap(swap, Some(refunded_message(swap_id)))
+    }
+
+    /// The tip height that swap lock heights are based on. Swaps are refused until the tip is known, since a lock
+    /// height based on an unknown tip would already have expired.
+    fn atomic_swap_tip_height(&self) -> Result<u64, TransactionServiceError> {
+        self.last_seen_tip_height.ok_or_else(|| {
+            TransactionServiceError::AtomicSwapError(
+                "The chain tip is not known yet, wait for the wallet to connect to a base node".to_string(),
+            )
+        })
+    }
+
+    /// Persists the swap, publishes an `AtomicSwapUpdated` event and sends `notification` to the counterparty
+    fn update_atomic_swap(
+        &mut self,
+        swap: AtomicSwap,
+        notification: Option<proto::AtomicSwapMessage>,
+    ) -> Result<AtomicSwap, TransactionServiceError> {
+        self.db.upsert_atomic_swap(swap.clone())?;
+        let _size = self.event_publisher.send(Arc::new(TransactionEvent::AtomicSwapUpdated {
+            swap_id: swap.swap_id,
+            state: swap.state,
+        }));
+        if let Some(notification) = notification {
+            tokio::spawn(send_atomic_swap_message(
+                swap.counterparty.public_key().clone(),
+                notification,
+                self.resources.outbound_message_service.clone(),
+            ));
+        }
+        Ok(swap)
+    }
+
+    fn multisig_protocol(&self) -> MultisigProtocol<TBackend, TKeyManagerInterface> {
+        MultisigProtocol::new(
+            self.db.clone(),
+            self.resources.transaction_key_manager_service.clone(),
+            self.resources.outbound_message_service.clone(),
+            self.resources.wallet_identity.node_identity.public_key().clone(),
+            self.event_publisher.clone(),
+        )
+    }
+
+    /// Completes a one-sided transaction prepared by the output manager: builds and signs the recipient's output as
+    /// both sender and receiver and finalizes the transaction. Only the key manager is used, so this can also be done
+    /// by an offline wallet. `tip_height` selects the consensus constants to build the output with.
+    #[allow(clippy::too_many_lines)]
+    async fn sign_one_sided_transaction(
+        &self,
+        tx_id: TxId,
+        stp: &mut SenderTransactionProtocol,
+        dest_address: &TariAddress,
+        amount: MicroMinotari,
+        script: TariScript,
+        tip_height: u64,
+    ) -> Result<(), TransactionServiceError> {
+        // This call is needed to advance the state from `SingleRoundMessageReady` to `SingleRoundMessageReady`,
+        // but the returned value is not used
+        let _single_round_sender_data = stp
+            .build_single_round_message(&self.resources.transaction_key_manager_service)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        // Prepare receiver part of the transaction
+
+        // Diffie-Hellman shared secret `k_Ob * K_Sb = K_Ob * k_Sb` results in a public key, which is fed into
+        // KDFs to produce the spending, rewind, and encryption keys
+        let sender_offset_private_key = stp
+            .get_recipient_sender_offset_private_key()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?
+            .ok_or(TransactionServiceProtocolError::new(
+                tx_id,
+                TransactionServiceError::InvalidKeyId("Missing sender offset keyid".to_string()),
+            ))?;
+
+        let shared_secret = self
+            .resources
+            .transaction_key_manager_service
+            .get_diffie_hellman_shared_secret(&sender_offset_private_key, dest_address.public_key())
             .await?;
         let spending_key = shared_secret_to_output_spending_key(&shared_secret)
             .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
@@ -1394,7 +2800,6 @@ where
             .try_build(&self.resources.transaction_key_manager_service)
             .await?;
 
-        let tip_height = self.last_seen_tip_height.unwrap_or(0);
         let consensus_constants = self.consensus_manager.consensus_constants(tip_height);
         let rtp = ReceiverTransactionProtocol::new(
             sender_message,
@@ -1421,33 +2826,425 @@ where
                 );
                 TransactionServiceProtocolError::new(tx_id, e.into())
             })?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn send_one_sided_or_stealth(
+        &mut self,
+        dest_address: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+        script: TariScript,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = TxId::new_random();
+
+        // Prepare sender part of the transaction
+        let mut stp = self
+            .resources
+            .output_manager_service
+            .prepare_transaction_to_send(
+                tx_id,
+                amount,
+                selection_criteria,
+                output_features,
+                fee_per_gram,
+                TransactionMetadata::default(),
+                message.clone(),
+                script.clone(),
+                Covenant::default(),
+                MicroMinotari::zero(),
+            )
+            .await?;
+
+        self.resources
+            .output_manager_service
+            .confirm_pending_transaction(tx_id)
+            .await
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+
+        let tip_height = self.last_seen_tip_height.unwrap_or(0);
+        self.sign_one_sided_transaction(tx_id, &mut stp, &dest_address, amount, script, tip_height)
+            .await?;
         info!(target: LOG_TARGET, "Finalized one-side transaction TxId: {}", tx_id);
 
-        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
-        // there are no subscribers.
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        // Broadcast one-sided transaction
+
+        let tx = stp
+            .get_transaction()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        let fee = stp
+            .get_fee_amount()
+            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
+        self.submit_transaction(
+            transaction_broadcast_join_handles,
+            CompletedTransaction::new(
+                tx_id,
+                self.resources.wallet_identity.address.clone(),
+                dest_address,
+                amount,
+                fee,
+                tx.clone(),
+                TransactionStatus::Completed,
+                message.clone(),
+                Utc::now().naive_utc(),
+                TransactionDirection::Outbound,
+                None,
+                None,
+                None,
+            ),
+        )?;
+
+        Ok(tx_id)
+    }
+
+    /// Sends a one side payment transaction to a recipient
+    /// # Arguments
+    /// 'dest_pubkey': The Comms pubkey of the recipient node
+    /// 'amount': The amount of Tari to send to the recipient
+    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
+    pub async fn send_one_sided_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        output_features: OutputFeatures,
+        fee_per_gram: MicroMinotari,
+        message: String,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        if destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if self.resources.wallet_identity.node_identity.public_key() == destination.public_key() {
+            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+        let dest_pubkey = destination.public_key().clone();
+        self.send_one_sided_or_stealth(
+            destination,
+            amount,
+            selection_criteria,
+            output_features,
+            fee_per_gram,
+            message,
+            transaction_broadcast_join_handles,
+            one_sided_payment_script(&dest_pubkey),
+        )
+        .await
+    }
+
+    /// Prepares a one-sided transaction for an offline wallet to build and sign. Only the inputs are selected and
+    /// encumbered here, which needs no spending keys, so this works in a watch-only wallet of the offline wallet. The
+    /// inputs stay encumbered until the signed transaction is imported or the export is cancelled.
+    pub async fn create_unsigned_transaction(
+        &mut self,
+        destination: TariAddress,
+        amount: MicroMinotari,
+        selection_criteria: UtxoSelectionCriteria,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<UnsignedTransaction, TransactionServiceError> {
+        if destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if self.resources.wallet_identity.address.public_key() == destination.public_key() {
+            return Err(TransactionServiceError::OneSidedTransactionError(
+                "One-sided spend-to-self transactions not supported".to_string(),
+            ));
+        }
+        let tip_height = self.last_seen_tip_height.ok_or_else(|| {
+            TransactionServiceError::OfflineTransactionError(
+                "The chain tip is not known yet, wait for the wallet to connect to a base node".to_string(),
+            )
+        })?;
+        let tx_id = TxId::new_random();
+        let (inputs, input_total) = self
+            .resources
+            .output_manager_service
+            .encumber_unsigned_transaction_inputs(tx_id, amount, selection_criteria, fee_per_gram)
+            .await?;
+        let unsigned_transaction = UnsignedTransaction {
+            version: OFFLINE_TRANSACTION_VERSION,
+            tx_id,
+            destination,
+            amount,
+            fee_per_gram,
+            message,
+            change_address: self.resources.wallet_identity.address.clone(),
+            inputs,
+            input_total,
+            tip_height,
+        };
+
+        let result = self.db.insert_offline_transaction(OfflineTransaction {
+            unsigned_transaction: unsigned_transaction.clone(),
+            status: OfflineTransactionStatus::AwaitingSignature,
+            created_at: Utc::now().naive_utc(),
+        });
+        if let Err(e) = result {
+            if let Err(e) = self.resources.output_manager_service.cancel_transaction(tx_id).await {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to cancel outputs for TxId: {} with error {:?}", tx_id, e
+                );
+            }
+            return Err(e.into());
+        }
+        self.resources
+            .output_manager_service
+            .confirm_pending_transaction(tx_id)
+            .await?;
+        info!(
+            target: LOG_TARGET,
+            "Created unsigned transaction (TxId: {}) of {} to {} for offline signing",
+            tx_id,
+            amount,
+            unsigned_transaction.destination
+        );
+
+        Ok(unsigned_transaction)
+    }
+
+    /// Builds and signs a transaction exported by an online wallet. The inputs must be outputs this wallet can spend;
+    /// they are recognised by scanning them like any other one-sided payment. The destination and the change are both
+    /// paid as one-sided outputs, the change to the online wallet's address. Nothing is sent, so this can be done by a
+    /// wallet without a network connection.
+    pub async fn sign_unsigned_transaction(
+        &mut self,
+        unsigned_transaction: UnsignedTransaction,
+    ) -> Result<SignedTransaction, TransactionServiceError> {
+        let UnsignedTransaction {
+            tx_id,
+            destination,
+            amount,
+            fee_per_gram,
+            message,
+            change_address,
+            inputs,
+            input_total,
+            tip_height,
+            ..
+        } = unsigned_transaction;
+        if change_address.public_key() != self.resources.wallet_identity.address.public_key() {
+            return Err(TransactionServiceError::OfflineTransactionError(
+                "The change of an offline transaction must be paid to this wallet".to_string(),
+            ));
+        }
+
+        // Outputs this wallet already knows are skipped by the scan, so the inputs are looked up afterwards
+        let commitments = inputs.iter().map(|o| o.commitment.clone()).collect::<Vec<_>>();
+        let _recovered = self
+            .resources
+            .output_manager_service
+            .scan_outputs_for_one_sided_payments(inputs)
+            .await?;
+        let unspent_outputs = self.resources.output_manager_service.get_unspent_outputs().await?;
+        let mut spendable_total = MicroMinotari::zero();
+        for commitment in &commitments {
+            let output = unspent_outputs
+                .iter()
+                .find(|o| &o.commitment == commitment)
+                .ok_or_else(|| {
+                    TransactionServiceError::OfflineTransactionError(format!(
+                        "Input {} is not an unspent output of this wallet",
+                        commitment.to_hex()
+                    ))
+                })?;
+            spendable_total += output.wallet_output.value;
+        }
+        if spendable_total != input_total {
+            return Err(TransactionServiceError::OfflineTransactionError(format!(
+                "The inputs are worth {} but the online wallet expected {}",
+                spendable_total, input_total
+            )));
+        }
+
+        let key_manager = &self.resources.transaction_key_manager_service;
+        let wallet_identity = &self.resources.wallet_identity;
+        let recipient_output = build_one_sided_recipient_output(key_manager, wallet_identity, &BatchPayment {
+            destination,
+            amount,
+            message: message.clone(),
+        })
+        .await?;
+        let weighting = self
+            .resources
+            .consensus_manager
+            .consensus_constants(tip_height)
+            .transaction_weight_params();
+        let output_size = weighting.round_up_features_and_scripts_size(
+            recipient_output
+                .0
+                .features_and_scripts_byte_size()
+                .map_err(|e| TransactionServiceError::OfflineTransactionError(e.to_string()))?,
+        );
+        let fee_calc = Fee::new(*weighting);
+        let mut recipient_outputs = vec![recipient_output];
+        if input_total != amount + fee_calc.calculate(fee_per_gram, 1, commitments.len(), 1, output_size) {
+            // Both outputs are one-sided payments with the same script size, so the fee is that of two recipient
+            // outputs and the inputs are spent in full
+            let fee = fee_calc.calculate(fee_per_gram, 1, commitments.len(), 2, 2 * output_size);
+            let change = input_total
+                .checked_sub(amount + fee)
+                .filter(|change| *change > MicroMinotari::zero())
+                .ok_or_else(|| {
+                    TransactionServiceError::OfflineTransactionError(format!(
+                        "The inputs worth {} cannot pay {} and a fee of {}",
+                        input_total, amount, fee
+                    ))
+                })?;
+            recipient_outputs.push(
+                build_one_sided_recipient_output(key_manager, wallet_identity, &BatchPayment {
+                    destination: change_address,
+                    amount: change,
+                    message,
+                })
+                .await?,
+            );
+        }
+
+        let (_fee, transaction) = self
+            .resources
+            .output_manager_service
+            .create_batch_transaction(
+                tx_id,
+                recipient_outputs,
+                fee_per_gram,
+                UtxoSelectionCriteria::specific(commitments),
+            )
+            .await?;
+        info!(target: LOG_TARGET, "Signed offline transaction TxId: {}", tx_id);
+
+        Ok(SignedTransaction {
+            version: OFFLINE_TRANSACTION_VERSION,
+            tx_id,
+            transaction,
+        })
+    }
+
+    /// Imports a transaction signed by the offline wallet and broadcasts it. It must spend exactly the encumbered
+    /// inputs, pay the destination, return the rest less the fee to this wallet and be internally consistent.
+    pub async fn import_signed_transaction(
+        &mut self,
+        signed_transaction: SignedTransaction,
+        transaction_broadcast_join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
+        >,
+    ) -> Result<TxId, TransactionServiceError> {
+        let tx_id = signed_transaction.tx_id;
+        let offline_transaction = self
+            .db
+            .fetch_offline_transaction(tx_id)?
+            .ok_or(TransactionServiceError::OfflineTransactionNotFound(tx_id))?;
+        if offline_transaction.status != OfflineTransactionStatus::AwaitingSignature {
+            return Err(TransactionServiceError::OfflineTransactionError(format!(
+                "Transaction {} is {}, not awaiting a signature",
+                tx_id, offline_transaction.status
+            )));
+        }
+        let unsigned_transaction = offline_transaction.unsigned_transaction;
+        let transaction = &signed_transaction.transaction;
+
+        let mut expected_inputs = unsigned_transaction.inputs.iter().map(|o| o.hash()).collect::<Vec<_>>();
+        let mut inputs = transaction
+            .body
+            .inputs()
+            .iter()
+            .map(|i| i.output_hash())
+            .collect::<Vec<_>>();
+        expected_inputs.sort();
+        inputs.sort();
+        if inputs != expected_inputs {
+            return Err(TransactionServiceError::OfflineTransactionError(
+                "Signed transaction does not spend exactly the encumbered inputs".to_string(),
+            ));
+        }
+
+        let destination_script = one_sided_payment_script(unsigned_transaction.destination.public_key());
+        let change_script = one_sided_payment_script(self.resources.wallet_identity.address.public_key());
+        let (destination_outputs, change_outputs): (Vec<_>, Vec<_>) = transaction
+            .body
+            .outputs()
+            .iter()
+            .cloned()
+            .partition(|o| o.script == destination_script);
+        if destination_outputs.len() != 1 ||
+            change_outputs.len() > 1 ||
+            change_outputs.iter().any(|o| o.script != change_script)
+        {
+            return Err(TransactionServiceError::OfflineTransactionError(
+                "Signed transaction must have one output to the destination and at most one change output to this \
+                 wallet"
+                    .to_string(),
+            ));
+        }
+
+        transaction
+            .validate_internal_consistency(&self.resources.factories, None, unsigned_transaction.tip_height)
+            .map_err(|e| {
+                TransactionServiceError::OfflineTransactionError(format!(
+                    "Signed transaction is not internally consistent: {}",
+                    e
+                ))
+            })?;
+
+        let fee = transaction.body.get_total_fee()?;
+        let mut change = MicroMinotari::zero();
+        for output in &change_outputs {
+            change += self.read_one_sided_output_value(output).await?;
+        }
+        if unsigned_transaction.input_total != unsigned_transaction.amount + change + fee {
+            return Err(TransactionServiceError::OfflineTransactionError(format!(
+                "Signed transaction pays {} to the destination, expected {}",
+                unsigned_transaction
+                    .input_total
+                    .checked_sub(change + fee)
+                    .unwrap_or_else(MicroMinotari::zero),
+                unsigned_transaction.amount
+            )));
+        }
+
+        self.db.update_offline_transaction_status(
+            tx_id,
+            OfflineTransactionStatus::AwaitingSignature,
+            OfflineTransactionStatus::Imported,
+        )?;
+        // The change is only imported once the whole transaction has been checked
+        self.resources
+            .output_manager_service
+            .scan_outputs_for_one_sided_payments(change_outputs)
+            .await?;
         let _result = self
             .event_publisher
             .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
-
-        // Broadcast one-sided transaction
-
-        let tx = stp
-            .get_transaction()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
-        let fee = stp
-            .get_fee_amount()
-            .map_err(|e| TransactionServiceProtocolError::new(tx_id, e.into()))?;
         self.submit_transaction(
             transaction_broadcast_join_handles,
             CompletedTransaction::new(
                 tx_id,
                 self.resources.wallet_identity.address.clone(),
-                dest_address,
-                amount,
+                unsigned_transaction.destination,
+                unsigned_transaction.amount,
                 fee,
-                tx.clone(),
+                signed_transaction.transaction,
                 TransactionStatus::Completed,
-                message.clone(),
+                unsigned_transaction.message,
                 Utc::now().naive_utc(),
                 TransactionDirection::Outbound,
                 None,
@@ -1455,48 +3252,306 @@ where
                 None,
             ),
         )?;
+        info!(target: LOG_TARGET, "Imported signed offline transaction TxId: {}", tx_id);
 
         Ok(tx_id)
     }
 
-    /// Sends a one side payment transaction to a recipient
-    /// # Arguments
-    /// 'dest_pubkey': The Comms pubkey of the recipient node
-    /// 'amount': The amount of Tari to send to the recipient
-    /// 'fee_per_gram': The amount of fee per transaction gram to be included in transaction
-    pub async fn send_one_sided_transaction(
+    /// Reads the value of a one-sided output paid to this wallet's address without importing it
+    async fn read_one_sided_output_value(
+        &self,
+        output: &TransactionOutput,
+    ) -> Result<MicroMinotari, TransactionServiceError> {
+        let unreadable = || {
+            TransactionServiceError::OfflineTransactionError(
+                "The change output of the signed transaction cannot be read by this wallet".to_string(),
+            )
+        };
+        let (scanning_key_id, _) = self.resources.wallet_identity.one_sided_scanning_keys();
+        let shared_secret = self
+            .resources
+            .transaction_key_manager_service
+            .get_diffie_hellman_shared_secret(&scanning_key_id, &output.sender_offset_public_key)
+            .await?;
+        let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
+        let (value, mask) = EncryptedData::decrypt_data(&encryption_key, &output.commitment, &output.encrypted_data)
+            .map_err(|_| unreadable())?;
+        if !output.verify_mask(&self.resources.factories.range_proof, &mask, value.as_u64())? {
+            return Err(unreadable());
+        }
+        Ok(value)
+    }
+
+    /// Cancels a transaction that is awaiting an offline signature and releases its inputs
+    pub async fn cancel_offline_transaction(&mut self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        match self.db.update_offline_transaction_status(
+            tx_id,
+            OfflineTransactionStatus::AwaitingSignature,
+            OfflineTransactionStatus::Cancelled,
+        ) {
+            Err(TransactionStorageError::ValuesNotFound) => {
+                return Err(TransactionServiceError::OfflineTransactionNotFound(tx_id))
+            },
+            Err(e) => return Err(e.into()),
+            Ok(()) => {},
+        }
+        self.resources.output_manager_service.cancel_transaction(tx_id).await?;
+        Ok(())
+    }
+
+    /// Sends several one-sided payments in a single transaction with one kernel
+    pub async fn send_batch_transaction(
         &mut self,
-        destination: TariAddress,
-        amount: MicroMinotari,
+        payments: Vec<BatchPayment>,
         selection_criteria: UtxoSelectionCriteria,
-        output_features: OutputFeatures,
         fee_per_gram: MicroMinotari,
         message: String,
         transaction_broadcast_join_handles: &mut FuturesUnordered<
             JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>,
         >,
     ) -> Result<TxId, TransactionServiceError> {
-        if destination.network() != self.resources.wallet_identity.network {
+        let tx_id = TxId::new_random();
+        let protocol = TransactionBatchSendProtocol::new(
+            tx_id,
+            self.resources.clone(),
+            payments,
+            selection_criteria,
+            fee_per_gram,
+            message,
+        );
+        let completed_transaction = protocol.execute().await.map_err(|e| {
+            error!(
+                target: LOG_TARGET,
+                "Batch transaction (TxId: {}) could not be created: {:?}", tx_id, e.error
+            );
+            e.error
+        })?;
+
+        // This event being sent is important, but not critical to the protocol being successful. Send only fails if
+        // there are no subscribers.
+        let _result = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionCompletedImmediately(tx_id)));
+
+        self.submit_transaction(transaction_broadcast_join_handles, completed_transaction)?;
+
+        Ok(tx_id)
+    }
+
+    /// Persists a payment to be sent by the scheduled transactions task once `execute_at` is reached
+    fn schedule_transaction(
+        &mut self,
+        payment: ScheduledPayment,
+        execute_at: HeightOrTime,
+        expires_at: Option<HeightOrTime>,
+    ) -> Result<u64, TransactionServiceError> {
+        if payment.destination.network() != self.resources.wallet_identity.network {
             return Err(TransactionServiceError::InvalidNetwork);
         }
-        if self.resources.wallet_identity.node_identity.public_key() == destination.public_key() {
-            warn!(target: LOG_TARGET, "One-sided spend-to-self transactions not supported");
-            return Err(TransactionServiceError::OneSidedTransactionError(
-                "One-sided spend-to-self transactions not supported".to_string(),
+        if payment.amount == MicroMinotari::zero() {
+            return Err(TransactionServiceError::ScheduledTransactionError(
+                "Scheduled amount must be greater than zero".to_string(),
             ));
         }
-        let dest_pubkey = destination.public_key().clone();
-        self.send_one_sided_or_stealth(
-            destination,
-            amount,
-            selection_criteria,
-            output_features,
-            fee_per_gram,
-            message,
-            transaction_broadcast_join_handles,
-            one_sided_payment_script(&dest_pubkey),
-        )
-        .await
+        let expires_first = match (&execute_at, &expires_at) {
+            (HeightOrTime::Height(execute), Some(HeightOrTime::Height(expires))) => expires <= execute,
+            (HeightOrTime::Time(execute), Some(HeightOrTime::Time(expires))) => expires <= execute,
+            _ => false,
+        };
+        if expires_first {
+            return Err(TransactionServiceError::ScheduledTransactionError(format!(
+                "Expiry ({}) must be after the execution point ({})",
+                expires_at.as_ref().map(ToString::to_string).unwrap_or_default(),
+                execute_at
+            )));
+        }
+
+        let id = OsRng.next_u64();
+        info!(
+            target: LOG_TARGET,
+            "Scheduling transaction {} of {} to {} at {}", id, payment.amount, payment.destination, execute_at
+        );
+        self.db.insert_scheduled_transaction(ScheduledTransaction {
+            id,
+            destination: payment.destination,
+            amount: payment.amount,
+            fee_per_gram: payment.fee_per_gram,
+            message: payment.message,
+            one_sided: payment.one_sided,
+            execute_at,
+            expires_at,
+            status: ScheduledTransactionStatus::Pending,
+            tx_id: None,
+            failure_reason: None,
+            created_at: Utc::now().naive_utc(),
+        })?;
+        Ok(id)
+    }
+
+    /// Returns true if sends should be queued rather than attempted, because the wallet has no base node or is not
+    /// connected to it
+    fn should_queue_send(&self) -> bool {
+        self.resources.config.queue_offline_transactions &&
+            (!self.connectivity().is_base_node_set() ||
+                *self.connectivity().get_connectivity_status_watch().borrow() != OnlineStatus::Online)
+    }
+
+    /// Persists a payment to be sent by the queued transactions task once base node connectivity returns
+    fn queue_transaction(&mut self, payment: ScheduledPayment) -> Result<u64, TransactionServiceError> {
+        if payment.destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if payment.amount == MicroMinotari::zero() {
+            return Err(TransactionServiceError::QueuedTransactionError(
+                "Queued amount must be greater than zero".to_string(),
+            ));
+        }
+
+        let id = OsRng.next_u64();
+        info!(
+            target: LOG_TARGET,
+            "Wallet is offline, queueing transaction {} of {} to {} until connectivity returns",
+            id,
+            payment.amount,
+            payment.destination
+        );
+        self.db.insert_queued_transaction(QueuedTransaction {
+            id,
+            destination: payment.destination,
+            amount: payment.amount,
+            fee_per_gram: payment.fee_per_gram,
+            message: payment.message,
+            one_sided: payment.one_sided,
+            status: QueuedTransactionStatus::Queued,
+            tx_id: None,
+            failure_reason: None,
+            queued_at: Utc::now().naive_utc(),
+        })?;
+        // Send only fails if there are no subscribers
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::TransactionQueued(id)));
+        Ok(id)
+    }
+
+    /// Persists a payment plan to be sent by the recurring payments task every `interval`
+    fn create_recurring_payment(
+        &mut self,
+        payment: ScheduledPayment,
+        interval: Duration,
+        start_at: Option<NaiveDateTime>,
+        end_at: Option<NaiveDateTime>,
+    ) -> Result<u64, TransactionServiceError> {
+        if payment.destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if payment.amount == MicroMinotari::zero() {
+            return Err(TransactionServiceError::RecurringPaymentError(
+                "Recurring amount must be greater than zero".to_string(),
+            ));
+        }
+        if interval.as_secs() == 0 {
+            return Err(TransactionServiceError::RecurringPaymentError(
+                "Recurring interval must be at least one second".to_string(),
+            ));
+        }
+        let now = Utc::now().naive_utc();
+        let next_payment_at = start_at.unwrap_or(now);
+        if let Some(end_at) = end_at {
+            if end_at <= next_payment_at {
+                return Err(TransactionServiceError::RecurringPaymentError(format!(
+                    "End date ({}) must be after the first payment ({})",
+                    end_at, next_payment_at
+                )));
+            }
+        }
+
+        let id = OsRng.next_u64();
+        info!(
+            target: LOG_TARGET,
+            "Creating recurring payment {} of {} to {} every {}s from {}",
+            id,
+            payment.amount,
+            payment.destination,
+            interval.as_secs(),
+            next_payment_at
+        );
+        self.db.insert_recurring_payment(RecurringPayment {
+            id,
+            destination: payment.destination,
+            amount: payment.amount,
+            fee_per_gram: payment.fee_per_gram,
+            message: payment.message,
+            one_sided: payment.one_sided,
+            interval,
+            next_payment_at,
+            end_at,
+            status: RecurringPaymentStatus::Active,
+            payments_made: 0,
+            last_tx_id: None,
+            failure_reason: None,
+            created_at: now,
+        })?;
+        Ok(id)
+    }
+
+    /// Validates and persists a new or updated send template, returning its id. Template names are trimmed and must be
+    /// unique.
+    fn save_send_template(&mut self, mut template: SendTemplate, update: bool) -> Result<u64, TransactionServiceError> {
+        template.name = template.name.trim().to_string();
+        if template.name.is_empty() {
+            return Err(TransactionServiceError::SendTemplateError(
+                "Template name must not be empty".to_string(),
+            ));
+        }
+        if template.destination.network() != self.resources.wallet_identity.network {
+            return Err(TransactionServiceError::InvalidNetwork);
+        }
+        if template.default_amount == Some(MicroMinotari::zero()) {
+            return Err(TransactionServiceError::SendTemplateError(
+                "Default amount must be greater than zero".to_string(),
+            ));
+        }
+        if self
+            .db
+            .fetch_send_templates()?
+            .iter()
+            .any(|t| t.id != template.id && t.name == template.name)
+        {
+            return Err(TransactionServiceError::SendTemplateError(format!(
+                "A send template named '{}' already exists",
+                template.name
+            )));
+        }
+
+        let id = template.id;
+        if update {
+            self.db.update_send_template(template)?;
+        } else {
+            self.db.insert_send_template(template)?;
+        }
+        Ok(id)
+    }
+
+    /// Reactivates a paused payment plan. A payment that fell due while the plan was paused is sent straight away.
+    fn resume_recurring_payment(&mut self, id: u64) -> Result<(), TransactionServiceError> {
+        let payment = self
+            .db
+            .fetch_recurring_payments(Some(RecurringPaymentStatus::Paused))?
+            .into_iter()
+            .find(|p| p.id == id)
+            .ok_or_else(|| {
+                TransactionServiceError::RecurringPaymentError(format!("No paused recurring payment with id {}", id))
+            })?;
+        let next_payment_at = cmp::min(payment.next_payment_at, Utc::now().naive_utc());
+        self.db.update_recurring_payment_status(
+            id,
+            &[RecurringPaymentStatus::Paused],
+            RecurringPaymentStatus::Active,
+            Some(next_payment_at),
+            None,
+        )?;
+        Ok(())
     }
 
     /// Creates a transaction to burn some Minotari. The optional _claim public key_ parameter is used in the challenge
@@ -1661,11 +3716,11 @@ where
         let mut ownership_proof = None;
         let commitment = recipient_reply.output.commitment.clone();
 
-        if let Some(claim_public_key) = claim_public_key {
+        if let Some(ref claim_public_key) = claim_public_key {
             ownership_proof = Some(
                 self.resources
                     .transaction_key_manager_service
-                    .generate_burn_proof(&spend_key_id, &amount.into(), &claim_public_key)
+                    .generate_burn_proof(&spend_key_id, &amount.into(), claim_public_key)
                     .await?,
             );
         }
@@ -1719,13 +3774,91 @@ where
         )?;
         info!(target: LOG_TARGET, "Submitted burning transaction - TxId: {}", tx_id);
 
-        Ok((tx_id, BurntProof {
+        let proof = BurntProof {
             // Key used to claim the burn on L2
             reciprocal_claim_public_key: public_spend_key,
             commitment,
             ownership_proof,
             range_proof,
-        }))
+        };
+        // The burn has already been submitted, so failing to keep a copy of the proof must not fail the request; the
+        // proof is still returned to the caller
+        if let Err(e) = self.db.insert_burn_record(BurnRecord {
+            tx_id,
+            amount,
+            claim_public_key,
+            proof: proof.clone(),
+            status: BurnStatus::Pending,
+            burned_at: Utc::now().naive_utc(),
+            claimed_at: None,
+        }) {
+            error!(
+                target: LOG_TARGET,
+                "Could not store the burn proof for burning transaction {}: {}", tx_id, e
+            );
+        }
+
+        Ok((tx_id, proof))
+    }
+
+    /// Returns the burn made in `tx_id`, with its status brought up to date with the burn transaction
+    fn get_burn_record(&self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionServiceError> {
+        self.db
+            .fetch_burn_record(tx_id)?
+            .map(|record| self.refresh_burn_status(record))
+            .transpose()
+    }
+
+    fn get_burn_records(&self, status: Option<BurnStatus>) -> Result<Vec<BurnRecord>, TransactionServiceError> {
+        // Stored statuses can be stale, so they are refreshed before filtering
+        let records = self
+            .db
+            .fetch_burn_records(None)?
+            .into_iter()
+            .map(|record| self.refresh_burn_status(record))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(records
+            .into_iter()
+            .filter(|record| status.map_or(true, |s| record.status == s))
+            .collect())
+    }
+
+    /// A pending burn becomes claimable once its transaction is mined and confirmed, or cancelled if its transaction
+    /// was cancelled
+    fn refresh_burn_status(&self, mut record: BurnRecord) -> Result<BurnRecord, TransactionServiceError> {
+        if record.status != BurnStatus::Pending {
+            return Ok(record);
+        }
+        let transaction = match self.db.get_completed_transaction_cancelled_or_not(record.tx_id) {
+            Ok(transaction) => transaction,
+            Err(TransactionStorageError::ValueNotFound(_)) => return Ok(record),
+            Err(e) => return Err(e.into()),
+        };
+        let status = if transaction.cancelled.is_some() {
+            BurnStatus::Cancelled
+        } else if transaction.status == TransactionStatus::MinedConfirmed {
+            BurnStatus::Claimable
+        } else {
+            return Ok(record);
+        };
+        self.db.update_burn_record_status(record.tx_id, status, None)?;
+        record.status = status;
+        Ok(record)
+    }
+
+    fn mark_burn_claimed(&self, tx_id: TxId) -> Result<(), TransactionServiceError> {
+        let record = self
+            .get_burn_record(tx_id)?
+            .ok_or(TransactionServiceError::BurnNotFound(tx_id))?;
+        if record.status != BurnStatus::Claimable {
+            return Err(TransactionServiceError::BurnNotClaimable {
+                tx_id,
+                status: record.status,
+            });
+        }
+        self.db
+            .update_burn_record_status(tx_id, BurnStatus::Claimed, Some(Utc::now().naive_utc()))?;
+        Ok(())
     }
 
     pub async fn register_validator_node(
@@ -1753,6 +3886,8 @@ where
             output_features,
             fee_per_gram,
             message,
+            None,
+            None,
             TransactionMetadata::default(),
             join_handles,
             transaction_broadcast_join_handles,
@@ -1782,6 +3917,8 @@ where
             OutputFeatures::for_template_registration(template_registration),
             fee_per_gram,
             message,
+            None,
+            None,
             TransactionMetadata::default(),
             join_handles,
             transaction_broadcast_join_handles,
@@ -2049,6 +4186,7 @@ where
         })?;
 
         self.resources.output_manager_service.cancel_transaction(tx_id).await?;
+        self.db.remove_outbound_messages(tx_id)?;
 
         if let Some(cancellation_sender) = self.send_transaction_cancellation_senders.remove(&tx_id) {
             let _result = cancellation_sender.send(());
@@ -2194,6 +4332,10 @@ where
     ) -> Result<(), TransactionServiceError> {
         // Check if a wallet recovery is in progress, if it is we will ignore this request
         self.check_recovery_status()?;
+        // A watch-only wallet cannot sign the receiver's half of an interactive transaction
+        if self.resources.wallet_identity.is_watch_only() {
+            return Err(TransactionServiceError::WatchOnlyWallet);
+        }
 
         if let Err(e) = sender_message {
             return Err(TransactionServiceError::InvalidMessageError(format!(
@@ -2309,9 +4451,7 @@ where
                 cancellation_receiver,
             );
 
-            let join_handle = tokio::spawn(protocol.execute());
-            join_handles.push(join_handle);
-            Ok(())
+            self.start_receive_protocol(data.tx_id, protocol, join_handles)
         } else {
             Err(TransactionServiceError::InvalidStateError)
         }
@@ -2502,8 +4642,73 @@ where
                 cancellation_receiver,
             );
 
-            let join_handle = tokio::spawn(protocol.execute());
-            join_handles.push(join_handle);
+            // A restarted protocol belongs to a pending inbound transaction that is already in the database, so it is
+            // never dropped when the queue is full
+            if join_handles.len() < self.config.max_concurrent_receive_protocols {
+                join_handles.push(tokio::spawn(protocol.execute()));
+            } else {
+                trace!(
+                    target: LOG_TARGET,
+                    "Queueing restarted Receive Transaction Protocol for TxId: {} ({} already queued)",
+                    tx_id,
+                    self.queued_receive_protocols.len()
+                );
+                self.queued_receive_protocols.push_back((tx_id, protocol));
+            }
+        }
+    }
+
+    /// Starts a receive protocol if fewer than `max_concurrent_receive_protocols` are running, otherwise queues it
+    /// until a running one completes. When the queue is full as well the protocol is dropped and a
+    /// `ReceiveProtocolQueueOverflow` event is published.
+    fn start_receive_protocol(
+        &mut self,
+        tx_id: TxId,
+        protocol: TransactionReceiveProtocol<TBackend, TWalletConnectivity, TKeyManagerInterface>,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
+    ) -> Result<(), TransactionServiceError> {
+        if join_handles.len() < self.config.max_concurrent_receive_protocols {
+            join_handles.push(tokio::spawn(protocol.execute()));
+            return Ok(());
+        }
+        if self.queued_receive_protocols.len() < self.config.receive_protocol_queue_size {
+            trace!(
+                target: LOG_TARGET,
+                "Queueing Receive Transaction Protocol for TxId: {} ({} already queued)",
+                tx_id,
+                self.queued_receive_protocols.len()
+            );
+            self.queued_receive_protocols.push_back((tx_id, protocol));
+            return Ok(());
+        }
+
+        warn!(
+            target: LOG_TARGET,
+            "Receive protocol queue is full, dropping inbound transaction TxId: {}", tx_id
+        );
+        self.finalized_transaction_senders.remove(&tx_id);
+        self.receiver_transaction_cancellation_senders.remove(&tx_id);
+        let _size = self
+            .event_publisher
+            .send(Arc::new(TransactionEvent::ReceiveProtocolQueueOverflow(tx_id)));
+        Err(TransactionServiceError::ReceiveProtocolQueueFull(tx_id))
+    }
+
+    fn start_queued_receive_protocols(
+        &mut self,
+        join_handles: &mut FuturesUnordered<JoinHandle<Result<TxId, TransactionServiceProtocolError<TxId>>>>,
+    ) {
+        while join_handles.len() < self.config.max_concurrent_receive_protocols {
+            match self.queued_receive_protocols.pop_front() {
+                Some((tx_id, protocol)) => {
+                    trace!(
+                        target: LOG_TARGET,
+                        "Starting queued Receive Transaction Protocol for TxId: {}", tx_id
+                    );
+                    join_handles.push(tokio::spawn(protocol.execute()));
+                },
+                None => break,
+            }
         }
     }
 
@@ -2535,9 +4740,71 @@ where
                 resp
             })?;
 
+        self.resend_queued_outbound_messages(None).map_err(|resp| {
+            error!(
+                target: LOG_TARGET,
+                "Error resending queued outbound transaction messages: {:?}", resp
+            );
+            resp
+        })?;
+
         Ok(())
     }
 
+    /// Redeliver any transaction protocol messages that were persisted but not yet delivered, e.g. because the wallet
+    /// was shut down part way through a negotiation. Messages belonging to a running send or receive protocol are left
+    /// to that protocol's retry timer so that each message has a single delivery path. At most `max_messages` are
+    /// resent if given. Returns the number of messages resent and the number still waiting to be resent.
+    ///
+    /// Messages that are no longer needed are purged first: those delivered or given up on for transactions that are
+    /// no longer being negotiated, and those queued longer ago than pending transactions are kept for.
+    fn resend_queued_outbound_messages(
+        &mut self,
+        max_messages: Option<usize>,
+    ) -> Result<(usize, usize), TransactionServiceError> {
+        let max_age = chrono::Duration::from_std(self.resources.config.pending_transaction_cancellation_timeout)
+            .unwrap_or_else(|_| chrono::Duration::max_value());
+        let expired_before = Utc::now()
+            .naive_utc()
+            .checked_sub_signed(max_age)
+            .unwrap_or(NaiveDateTime::MIN);
+        let purged = self.db.purge_outbound_messages(expired_before)?;
+        if purged > 0 {
+            debug!(target: LOG_TARGET, "Purged {} queued outbound messages", purged);
+        }
+        let queued_messages = self
+            .db
+            .fetch_pending_outbound_messages()?
+            .into_iter()
+            .filter(|message| match message.message_type {
+                OutboundMessageType::SenderPartialTransaction => {
+                    !self.pending_transaction_reply_senders.contains_key(&message.tx_id)
+                },
+                OutboundMessageType::ReceiverPartialTransactionReply => {
+                    !self.finalized_transaction_senders.contains_key(&message.tx_id)
+                },
+                OutboundMessageType::TransactionFinalized => true,
+            })
+            .collect::<Vec<_>>();
+        let total = queued_messages.len();
+        let resent = max_messages.unwrap_or(total).min(total);
+        for message in queued_messages.into_iter().take(resent) {
+            debug!(
+                target: LOG_TARGET,
+                "Resending queued {} message (TxId: {}, attempts: {})", message.message_type, message.tx_id, message.attempts
+            );
+            tokio::spawn(send_queued_outbound_message(
+                message,
+                self.db.clone(),
+                self.resources.outbound_message_service.clone(),
+                self.resources.config.direct_send_timeout,
+                self.resources.config.transaction_routing_mechanism,
+                self.resources.config.max_outbound_message_attempts,
+            ));
+        }
+        Ok((resent, total - resent))
+    }
+
     async fn start_transaction_revalidation(
         &mut self,
         join_handles: &mut FuturesUnordered<
@@ -2545,7 +4812,41 @@ where
         >,
     ) -> Result<OperationId, TransactionServiceError> {
         self.resources.db.mark_all_transactions_as_unvalidated()?;
-        self.start_transaction_validation_protocol(join_handles).await
+        self.start_transaction_validation_protocol(join_handles, None).await
+    }
+
+    /// Validates at most `max_transactions` of the unconfirmed transactions, continuing from where the previous batch
+    /// left off so that repeated calls cycle through all of them.
+    async fn start_transaction_batch_validation(
+        &mut self,
+        max_transactions: usize,
+        join_handles: &mut FuturesUnordered<
+            JoinHandle<Result<OperationId, TransactionServiceProtocolError<OperationId>>>,
+        >,
+    ) -> Result<TransactionBatchValidation, TransactionServiceError> {
+        let unconfirmed_transactions = self.db.fetch_unconfirmed_transactions_info()?;
+        let start = self
+            .validation_batch_cursor
+            .and_then(|cursor| unconfirmed_transactions.iter().position(|tx| tx.tx_id == cursor))
+            .map_or(0, |i| i + 1);
+        let batch = unconfirmed_transactions
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(max_transactions.min(unconfirmed_transactions.len()))
+            .map(|tx| tx.tx_id)
+            .collect::<Vec<_>>();
+        let num_transactions = batch.len();
+        self.validation_batch_cursor = batch.last().copied();
+
+        let operation_id = self
+            .start_transaction_validation_protocol(join_handles, Some(batch))
+            .await?;
+        Ok(TransactionBatchValidation {
+            operation_id,
+            num_transactions,
+            remaining: unconfirmed_transactions.len() - num_transactions,
+        })
     }
 
     async fn start_transaction_validation_protocol(
@@ -2553,6 +4854,7 @@ where
         join_handles: &mut FuturesUnordered<
             JoinHandle<Result<OperationId, TransactionServiceProtocolError<OperationId>>>,
         >,
+        tx_ids: Option<Vec<TxId>>,
     ) -> Result<OperationId, TransactionServiceError> {
         let current_base_node = self
             .resources
@@ -2563,7 +4865,7 @@ where
         trace!(target: LOG_TARGET, "Starting transaction validation protocol");
         let id = OperationId::new_random();
 
-        let protocol = TransactionValidationProtocol::new(
+        let mut protocol = TransactionValidationProtocol::new(
             id,
             self.resources.db.clone(),
             self.resources.connectivity.clone(),
@@ -2571,6 +4873,9 @@ where
             self.event_publisher.clone(),
             self.resources.output_manager_service.clone(),
         );
+        if let Some(tx_ids) = tx_ids {
+            protocol = protocol.with_transactions(tx_ids);
+        }
 
         let mut base_node_watch = self.connectivity().get_current_base_node_watcher();
         let validation_in_progress = self.validation_in_progress.clone();
@@ -2812,6 +5117,7 @@ where
         &mut self,
         value: MicroMinotari,
         source_address: TariAddress,
+        destination_address: Option<TariAddress>,
         message: String,
         maturity: Option<u64>,
         import_status: ImportStatus,
@@ -2827,7 +5133,7 @@ where
             tx_id,
             value,
             source_address,
-            self.resources.wallet_identity.address.clone(),
+            destination_address.unwrap_or_else(|| self.resources.wallet_identity.address.clone()),
             message,
             maturity,
             import_status.clone(),
@@ -2854,7 +5160,7 @@ where
             e
         });
         // Because we added new transactions, let try to trigger a validation for them
-        self.start_transaction_validation_protocol(transaction_validation_join_handles)
+        self.start_transaction_validation_protocol(transaction_validation_join_handles, None)
             .await?;
         Ok(tx_id)
     }
@@ -3006,6 +5312,16 @@ where
     }
 }
 
+/// Waits for the next contacts event, or forever when the wallet has no contacts service
+async fn next_contacts_event(
+    events: &mut Option<broadcast::Receiver<Arc<ContactsLivenessEvent>>>,
+) -> Result<Arc<ContactsLivenessEvent>, broadcast::error::RecvError> {
+    match events {
+        Some(events) => events.recv().await,
+        None => futures::future::pending().await,
+    }
+}
+
 /// This struct is a collection of the common resources that a protocol in the service requires.
 #[derive(Clone)]
 pub struct TransactionServiceResources<TBackend, TWalletConnectivity, TKeyManagerInterface> {
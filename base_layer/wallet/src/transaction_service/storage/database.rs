@@ -39,11 +39,32 @@ use tari_core::transactions::{tari_amount::MicroMinotari, transaction_components
 
 use crate::transaction_service::{
     error::TransactionStorageError,
+    handle::TransactionEvent,
     storage::{
         models::{
+            AtomicSwap,
+            BatchedPayment,
+            BurnRecord,
+            BurnStatus,
             CompletedTransaction,
             InboundTransaction,
+            JournaledTransactionEvent,
+            MultisigSession,
+            MultisigSigning,
+            OfflineTransaction,
+            OfflineTransactionStatus,
+            OutboundMessageStatus,
+            OutboundMessageType,
             OutboundTransaction,
+            QueuedOutboundMessage,
+            QueuedTransaction,
+            QueuedTransactionStatus,
+            RecurringPayment,
+            RecurringPaymentStatus,
+            ScheduledTransaction,
+            ScheduledTransactionStatus,
+            SendTemplate,
+            SupersededTransaction,
             TxCancellationReason,
             WalletTransaction,
         },
@@ -115,6 +136,8 @@ pub trait TransactionBackend: Send + Sync + Clone {
     ) -> Result<TariAddress, TransactionStorageError>;
     /// Mark a pending transaction direct send attempt as a success
     fn mark_direct_send_success(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Mark a pending outbound transaction as waiting for the cosigners of the wallet's keys to sign it
+    fn mark_awaiting_cosigners(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
     /// Cancel coinbase transactions at a specific block height
     fn cancel_coinbase_transactions_at_block_height(&self, block_height: u64) -> Result<(), TransactionStorageError>;
     /// Find coinbase transaction at a specific block height for a given amount
@@ -142,6 +165,10 @@ pub trait TransactionBackend: Send + Sync + Clone {
     ) -> Result<(), TransactionStorageError>;
     /// Clears the mined block and height of a transaction
     fn set_transaction_as_unmined(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Clears the mined block and height of every transaction mined above `height`, such as after a reorg, returning
+    /// the ids of the transactions that were changed
+    fn set_transactions_mined_above_height_as_unmined(&self, height: u64)
+        -> Result<Vec<TxId>, TransactionStorageError>;
     /// Reset optional 'mined height' and 'mined in block' fields to nothing
     fn mark_all_transactions_as_unvalidated(&self) -> Result<(), TransactionStorageError>;
     /// Light weight method to retrieve pertinent transaction sender info for all pending inbound transactions
@@ -155,6 +182,212 @@ pub trait TransactionBackend: Send + Sync + Clone {
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError>;
     fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Persist a transaction protocol message so that it can be redelivered after a restart. If a message of the same
+    /// type is already queued for this transaction it is kept as is, so its attempt count survives being re-queued.
+    fn queue_outbound_message(&self, message: QueuedOutboundMessage) -> Result<(), TransactionStorageError>;
+    /// Retrieve the queued outbound message of the given type for a transaction, if there is one
+    fn fetch_outbound_message(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+    ) -> Result<Option<QueuedOutboundMessage>, TransactionStorageError>;
+    /// Retrieve all queued outbound messages that have not yet been delivered or given up on
+    fn fetch_pending_outbound_messages(&self) -> Result<Vec<QueuedOutboundMessage>, TransactionStorageError>;
+    /// Record a delivery attempt for a queued outbound message, incrementing its attempt counter and updating its
+    /// status
+    fn record_outbound_message_attempt(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        status: OutboundMessageStatus,
+    ) -> Result<(), TransactionStorageError>;
+    /// Remove all queued outbound messages for a transaction
+    fn remove_outbound_messages(&self, tx_id: TxId) -> Result<(), TransactionStorageError>;
+    /// Remove queued outbound messages that are no longer needed: those that were delivered or given up on for
+    /// transactions that are no longer pending, and any queued before `expired_before`. Returns the number removed.
+    fn purge_outbound_messages(&self, expired_before: NaiveDateTime) -> Result<usize, TransactionStorageError>;
+    /// Persist the address book alias of a transaction's counterparty, replacing any previously stored alias
+    fn set_counterparty_alias(&self, tx_id: TxId, alias: String) -> Result<(), TransactionStorageError>;
+    /// Retrieve the stored counterparty aliases for the given transactions. Transactions without a stored alias are
+    /// omitted.
+    fn fetch_counterparty_aliases(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, String>, TransactionStorageError>;
+    /// Persist the decrypted payment memo of a transaction, replacing any previously stored memo. The memo is
+    /// encrypted at rest.
+    fn set_transaction_memo(&self, tx_id: TxId, memo: String) -> Result<(), TransactionStorageError>;
+    /// Retrieve the payment memo of a transaction, if it has one
+    fn fetch_transaction_memo(&self, tx_id: TxId) -> Result<Option<String>, TransactionStorageError>;
+    /// Persist a transaction that has been exported for signing by an offline wallet
+    fn insert_offline_transaction(&self, transaction: OfflineTransaction) -> Result<(), TransactionStorageError>;
+    /// Retrieve a transaction that was exported for offline signing
+    fn fetch_offline_transaction(&self, tx_id: TxId) -> Result<Option<OfflineTransaction>, TransactionStorageError>;
+    /// Retrieve transactions exported for offline signing, optionally only those with the given status, oldest first
+    fn fetch_offline_transactions(
+        &self,
+        status: Option<OfflineTransactionStatus>,
+    ) -> Result<Vec<OfflineTransaction>, TransactionStorageError>;
+    /// Move a transaction exported for offline signing from the `from` status to the `to` status. Returns
+    /// `ValuesNotFound` if the transaction does not exist or is no longer in the `from` status.
+    fn update_offline_transaction_status(
+        &self,
+        tx_id: TxId,
+        from: OfflineTransactionStatus,
+        to: OfflineTransactionStatus,
+    ) -> Result<(), TransactionStorageError>;
+    /// Persist a burn made by this wallet together with its claim proof
+    fn insert_burn_record(&self, record: BurnRecord) -> Result<(), TransactionStorageError>;
+    /// Retrieve the burn made in the given transaction
+    fn fetch_burn_record(&self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionStorageError>;
+    /// Retrieve the burns made by this wallet, optionally only those with the given status, oldest first
+    fn fetch_burn_records(&self, status: Option<BurnStatus>) -> Result<Vec<BurnRecord>, TransactionStorageError>;
+    /// Set the status of a burn. `claimed_at` is only stored when moving to `Claimed`.
+    fn update_burn_record_status(
+        &self,
+        tx_id: TxId,
+        status: BurnStatus,
+        claimed_at: Option<NaiveDateTime>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Attach the given tags to a transaction. Tags that are already attached are ignored.
+    fn add_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError>;
+    /// Detach the given tags from a transaction. Tags that are not attached are ignored.
+    fn remove_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError>;
+    /// Retrieve the tags of the given transactions, sorted alphabetically. Transactions without tags are omitted.
+    fn fetch_transaction_tags(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError>;
+    /// Retrieve the ids of all transactions with the given tag
+    fn fetch_tx_ids_by_tag(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError>;
+    /// Retrieve the ids of all transactions with a tag or payment message containing `query`, ignoring ASCII case
+    fn search_transactions(&self, query: &str) -> Result<Vec<TxId>, TransactionStorageError>;
+    /// Persist the child payments of a batch transaction
+    fn insert_batched_payments(&self, payments: Vec<BatchedPayment>) -> Result<(), TransactionStorageError>;
+    /// Retrieve the child payments of a batch transaction, in the order they were added to the batch. Returns an
+    /// empty list for transactions that are not batches.
+    fn fetch_batched_payments(&self, tx_id: TxId) -> Result<Vec<BatchedPayment>, TransactionStorageError>;
+    /// Persist a new scheduled transaction
+    fn insert_scheduled_transaction(&self, scheduled: ScheduledTransaction) -> Result<(), TransactionStorageError>;
+    /// Retrieve scheduled transactions, optionally only those with the given status, oldest first
+    fn fetch_scheduled_transactions(
+        &self,
+        status: Option<ScheduledTransactionStatus>,
+    ) -> Result<Vec<ScheduledTransaction>, TransactionStorageError>;
+    /// Move a scheduled transaction from the `from` status to the `to` status, recording the resulting transaction or
+    /// failure reason. Returns `ValuesNotFound` if the scheduled transaction does not exist or is no longer in the
+    /// `from` status.
+    fn update_scheduled_transaction_status(
+        &self,
+        id: u64,
+        from: ScheduledTransactionStatus,
+        to: ScheduledTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Persist a new transaction queued while the wallet was offline
+    fn insert_queued_transaction(&self, queued: QueuedTransaction) -> Result<(), TransactionStorageError>;
+    /// Retrieve queued transactions, optionally only those with the given status, oldest first
+    fn fetch_queued_transactions(
+        &self,
+        status: Option<QueuedTransactionStatus>,
+    ) -> Result<Vec<QueuedTransaction>, TransactionStorageError>;
+    /// Move a queued transaction from the `from` status to the `to` status, recording the resulting transaction or
+    /// failure reason. Returns `ValuesNotFound` if the queued transaction does not exist or is no longer in the `from`
+    /// status.
+    fn update_queued_transaction_status(
+        &self,
+        id: u64,
+        from: QueuedTransactionStatus,
+        to: QueuedTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Replace a completed transaction that has not been mined yet with a new version of it, such as one paying a
+    /// higher fee, along with its batched payments. The replaced version is kept as a superseded transaction, as it
+    /// may still be mined. Returns `ValuesNotFound` if the transaction does not exist or is no longer waiting to be
+    /// mined.
+    fn supersede_completed_transaction(
+        &self,
+        replacement: CompletedTransaction,
+        batched_payments: Vec<BatchedPayment>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Retrieve all versions of completed transactions that were superseded, oldest first
+    fn fetch_superseded_transactions(&self) -> Result<Vec<SupersededTransaction>, TransactionStorageError>;
+    /// Make the superseded version with the given id the current version of its completed transaction again, e.g.
+    /// because it is the version that was mined. The version it replaces is kept as a superseded transaction in turn.
+    fn reinstate_superseded_transaction(&self, id: u64) -> Result<(), TransactionStorageError>;
+    /// Insert an atomic swap or replace the stored state of an existing one
+    fn upsert_atomic_swap(&self, swap: AtomicSwap) -> Result<(), TransactionStorageError>;
+    /// Retrieve an atomic swap by its swap id
+    fn fetch_atomic_swap(&self, swap_id: u64) -> Result<Option<AtomicSwap>, TransactionStorageError>;
+    /// Retrieve all atomic swaps, oldest first
+    fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, TransactionStorageError>;
+    /// Insert a multisig session or replace the stored state of an existing one
+    fn upsert_multisig_session(&self, session: MultisigSession) -> Result<(), TransactionStorageError>;
+    fn fetch_multisig_session(&self, session_id: u64) -> Result<Option<MultisigSession>, TransactionStorageError>;
+    /// Retrieve all multisig sessions, oldest first
+    fn fetch_multisig_sessions(&self) -> Result<Vec<MultisigSession>, TransactionStorageError>;
+    /// Insert a multisig signing or replace the stored state of an existing one
+    fn upsert_multisig_signing(&self, signing: MultisigSigning) -> Result<(), TransactionStorageError>;
+    fn fetch_multisig_signing(&self, signing_id: u64) -> Result<Option<MultisigSigning>, TransactionStorageError>;
+    /// Retrieve the signings of a multisig session, or of all sessions if `session_id` is `None`, oldest first
+    fn fetch_multisig_signings(&self, session_id: Option<u64>)
+        -> Result<Vec<MultisigSigning>, TransactionStorageError>;
+    /// Persist a new recurring payment plan
+    fn insert_recurring_payment(&self, payment: RecurringPayment) -> Result<(), TransactionStorageError>;
+    /// Retrieve recurring payment plans, optionally only those with the given status, oldest first
+    fn fetch_recurring_payments(
+        &self,
+        status: Option<RecurringPaymentStatus>,
+    ) -> Result<Vec<RecurringPayment>, TransactionStorageError>;
+    /// Move a recurring payment plan from one of the `from` statuses to the `to` status, recording the failure reason
+    /// and optionally rescheduling the next payment. Returns `ValuesNotFound` if the plan does not exist or is not in
+    /// one of the `from` statuses.
+    fn update_recurring_payment_status(
+        &self,
+        id: u64,
+        from: &[RecurringPaymentStatus],
+        to: RecurringPaymentStatus,
+        next_payment_at: Option<NaiveDateTime>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Claim the payment of an active plan that was due at `due_at` by moving its next payment to `next_payment_at`.
+    /// Returns `ValuesNotFound` if the plan is no longer active or the payment was already claimed.
+    fn advance_recurring_payment(
+        &self,
+        id: u64,
+        due_at: NaiveDateTime,
+        next_payment_at: NaiveDateTime,
+    ) -> Result<(), TransactionStorageError>;
+    /// Record the outcome of a payment: the transaction it was sent as, or the reason it failed
+    fn record_recurring_payment_result(
+        &self,
+        id: u64,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError>;
+    /// Append an event to the transaction event journal, keeping only the `max_events` most recent events
+    fn journal_transaction_event(
+        &self,
+        event: &TransactionEvent,
+        max_events: usize,
+    ) -> Result<(), TransactionStorageError>;
+    /// Retrieve the journaled events that follow `cursor`, oldest first
+    fn fetch_transaction_events_since(
+        &self,
+        cursor: u64,
+    ) -> Result<Vec<JournaledTransactionEvent>, TransactionStorageError>;
+    /// Persist a new send template
+    fn insert_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError>;
+    /// Replace the details of an existing send template. Returns `ValuesNotFound` if it does not exist.
+    fn update_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError>;
+    fn fetch_send_template(&self, id: u64) -> Result<Option<SendTemplate>, TransactionStorageError>;
+    /// Retrieve all send templates ordered by name
+    fn fetch_send_templates(&self) -> Result<Vec<SendTemplate>, TransactionStorageError>;
+    /// Remove a send template. Returns `ValuesNotFound` if it does not exist.
+    fn delete_send_template(&self, id: u64) -> Result<(), TransactionStorageError>;
+    /// Persist imported historical transactions in a single database transaction. Transactions whose id is already
+    /// known are skipped so that the same history can be imported more than once; the ids that were inserted are
+    /// returned.
+    fn insert_imported_history(
+        &self,
+        transactions: Vec<CompletedTransaction>,
+    ) -> Result<Vec<TxId>, TransactionStorageError>;
 }
 
 #[derive(Clone, PartialEq)]
@@ -636,6 +869,10 @@ where T: TransactionBackend + 'static
         self.db.mark_direct_send_success(tx_id)
     }
 
+    pub fn mark_awaiting_cosigners(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        self.db.mark_awaiting_cosigners(tx_id)
+    }
+
     /// Indicated that the specified completed transaction has been broadcast into the mempool
     pub fn broadcast_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.broadcast_completed_transaction(tx_id)
@@ -707,6 +944,13 @@ where T: TransactionBackend + 'static
         self.db.set_transaction_as_unmined(tx_id)
     }
 
+    pub fn set_transactions_mined_above_height_as_unmined(
+        &self,
+        height: u64,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.set_transactions_mined_above_height_as_unmined(height)
+    }
+
     pub fn mark_all_transactions_as_unvalidated(&self) -> Result<(), TransactionStorageError> {
         self.db.mark_all_transactions_as_unvalidated()
     }
@@ -745,6 +989,321 @@ where T: TransactionBackend + 'static
     pub fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
         self.db.abandon_coinbase_transaction(tx_id)
     }
+
+    pub fn queue_outbound_message(&self, message: QueuedOutboundMessage) -> Result<(), TransactionStorageError> {
+        self.db.queue_outbound_message(message)
+    }
+
+    pub fn fetch_outbound_message(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+    ) -> Result<Option<QueuedOutboundMessage>, TransactionStorageError> {
+        self.db.fetch_outbound_message(tx_id, message_type)
+    }
+
+    pub fn fetch_pending_outbound_messages(&self) -> Result<Vec<QueuedOutboundMessage>, TransactionStorageError> {
+        self.db.fetch_pending_outbound_messages()
+    }
+
+    pub fn record_outbound_message_attempt(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        status: OutboundMessageStatus,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.record_outbound_message_attempt(tx_id, message_type, status)
+    }
+
+    pub fn remove_outbound_messages(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        self.db.remove_outbound_messages(tx_id)
+    }
+
+    pub fn purge_outbound_messages(&self, expired_before: NaiveDateTime) -> Result<usize, TransactionStorageError> {
+        self.db.purge_outbound_messages(expired_before)
+    }
+
+    pub fn set_counterparty_alias(&self, tx_id: TxId, alias: String) -> Result<(), TransactionStorageError> {
+        self.db.set_counterparty_alias(tx_id, alias)
+    }
+
+    pub fn set_transaction_memo(&self, tx_id: TxId, memo: String) -> Result<(), TransactionStorageError> {
+        self.db.set_transaction_memo(tx_id, memo)
+    }
+
+    pub fn fetch_transaction_memo(&self, tx_id: TxId) -> Result<Option<String>, TransactionStorageError> {
+        self.db.fetch_transaction_memo(tx_id)
+    }
+
+    pub fn insert_offline_transaction(&self, transaction: OfflineTransaction) -> Result<(), TransactionStorageError> {
+        self.db.insert_offline_transaction(transaction)
+    }
+
+    pub fn fetch_offline_transaction(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Option<OfflineTransaction>, TransactionStorageError> {
+        self.db.fetch_offline_transaction(tx_id)
+    }
+
+    pub fn fetch_offline_transactions(
+        &self,
+        status: Option<OfflineTransactionStatus>,
+    ) -> Result<Vec<OfflineTransaction>, TransactionStorageError> {
+        self.db.fetch_offline_transactions(status)
+    }
+
+    pub fn update_offline_transaction_status(
+        &self,
+        tx_id: TxId,
+        from: OfflineTransactionStatus,
+        to: OfflineTransactionStatus,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.update_offline_transaction_status(tx_id, from, to)
+    }
+
+    pub fn insert_burn_record(&self, record: BurnRecord) -> Result<(), TransactionStorageError> {
+        self.db.insert_burn_record(record)
+    }
+
+    pub fn fetch_burn_record(&self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionStorageError> {
+        self.db.fetch_burn_record(tx_id)
+    }
+
+    pub fn fetch_burn_records(&self, status: Option<BurnStatus>) -> Result<Vec<BurnRecord>, TransactionStorageError> {
+        self.db.fetch_burn_records(status)
+    }
+
+    pub fn update_burn_record_status(
+        &self,
+        tx_id: TxId,
+        status: BurnStatus,
+        claimed_at: Option<NaiveDateTime>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.update_burn_record_status(tx_id, status, claimed_at)
+    }
+
+    pub fn fetch_counterparty_aliases(
+        &self,
+        tx_ids: &[TxId],
+    ) -> Result<HashMap<TxId, String>, TransactionStorageError> {
+        self.db.fetch_counterparty_aliases(tx_ids)
+    }
+
+    pub fn add_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        self.db.add_transaction_tags(tx_id, tags)
+    }
+
+    pub fn remove_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        self.db.remove_transaction_tags(tx_id, tags)
+    }
+
+    pub fn fetch_transaction_tags(
+        &self,
+        tx_ids: &[TxId],
+    ) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError> {
+        self.db.fetch_transaction_tags(tx_ids)
+    }
+
+    pub fn fetch_tx_ids_by_tag(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.fetch_tx_ids_by_tag(tag)
+    }
+
+    pub fn search_transactions(&self, query: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.search_transactions(query)
+    }
+
+    pub fn insert_batched_payments(&self, payments: Vec<BatchedPayment>) -> Result<(), TransactionStorageError> {
+        self.db.insert_batched_payments(payments)
+    }
+
+    pub fn fetch_batched_payments(&self, tx_id: TxId) -> Result<Vec<BatchedPayment>, TransactionStorageError> {
+        self.db.fetch_batched_payments(tx_id)
+    }
+
+    pub fn insert_scheduled_transaction(&self, scheduled: ScheduledTransaction) -> Result<(), TransactionStorageError> {
+        self.db.insert_scheduled_transaction(scheduled)
+    }
+
+    pub fn fetch_scheduled_transactions(
+        &self,
+        status: Option<ScheduledTransactionStatus>,
+    ) -> Result<Vec<ScheduledTransaction>, TransactionStorageError> {
+        self.db.fetch_scheduled_transactions(status)
+    }
+
+    pub fn update_scheduled_transaction_status(
+        &self,
+        id: u64,
+        from: ScheduledTransactionStatus,
+        to: ScheduledTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db
+            .update_scheduled_transaction_status(id, from, to, tx_id, failure_reason)
+    }
+
+    pub fn insert_queued_transaction(&self, queued: QueuedTransaction) -> Result<(), TransactionStorageError> {
+        self.db.insert_queued_transaction(queued)
+    }
+
+    pub fn fetch_queued_transactions(
+        &self,
+        status: Option<QueuedTransactionStatus>,
+    ) -> Result<Vec<QueuedTransaction>, TransactionStorageError> {
+        self.db.fetch_queued_transactions(status)
+    }
+
+    pub fn update_queued_transaction_status(
+        &self,
+        id: u64,
+        from: QueuedTransactionStatus,
+        to: QueuedTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db
+            .update_queued_transaction_status(id, from, to, tx_id, failure_reason)
+    }
+
+    pub fn supersede_completed_transaction(
+        &self,
+        replacement: CompletedTransaction,
+        batched_payments: Vec<BatchedPayment>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.supersede_completed_transaction(replacement, batched_payments)
+    }
+
+    pub fn fetch_superseded_transactions(&self) -> Result<Vec<SupersededTransaction>, TransactionStorageError> {
+        self.db.fetch_superseded_transactions()
+    }
+
+    pub fn reinstate_superseded_transaction(&self, id: u64) -> Result<(), TransactionStorageError> {
+        self.db.reinstate_superseded_transaction(id)
+    }
+
+    pub fn upsert_atomic_swap(&self, swap: AtomicSwap) -> Result<(), TransactionStorageError> {
+        self.db.upsert_atomic_swap(swap)
+    }
+
+    pub fn fetch_atomic_swap(&self, swap_id: u64) -> Result<Option<AtomicSwap>, TransactionStorageError> {
+        self.db.fetch_atomic_swap(swap_id)
+    }
+
+    pub fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, TransactionStorageError> {
+        self.db.fetch_atomic_swaps()
+    }
+
+    pub fn upsert_multisig_session(&self, session: MultisigSession) -> Result<(), TransactionStorageError> {
+        self.db.upsert_multisig_session(session)
+    }
+
+    pub fn fetch_multisig_session(&self, session_id: u64) -> Result<Option<MultisigSession>, TransactionStorageError> {
+        self.db.fetch_multisig_session(session_id)
+    }
+
+    pub fn fetch_multisig_sessions(&self) -> Result<Vec<MultisigSession>, TransactionStorageError> {
+        self.db.fetch_multisig_sessions()
+    }
+
+    pub fn upsert_multisig_signing(&self, signing: MultisigSigning) -> Result<(), TransactionStorageError> {
+        self.db.upsert_multisig_signing(signing)
+    }
+
+    pub fn fetch_multisig_signing(&self, signing_id: u64) -> Result<Option<MultisigSigning>, TransactionStorageError> {
+        self.db.fetch_multisig_signing(signing_id)
+    }
+
+    pub fn fetch_multisig_signings(
+        &self,
+        session_id: Option<u64>,
+    ) -> Result<Vec<MultisigSigning>, TransactionStorageError> {
+        self.db.fetch_multisig_signings(session_id)
+    }
+
+    pub fn insert_recurring_payment(&self, payment: RecurringPayment) -> Result<(), TransactionStorageError> {
+        self.db.insert_recurring_payment(payment)
+    }
+
+    pub fn fetch_recurring_payments(
+        &self,
+        status: Option<RecurringPaymentStatus>,
+    ) -> Result<Vec<RecurringPayment>, TransactionStorageError> {
+        self.db.fetch_recurring_payments(status)
+    }
+
+    pub fn update_recurring_payment_status(
+        &self,
+        id: u64,
+        from: &[RecurringPaymentStatus],
+        to: RecurringPaymentStatus,
+        next_payment_at: Option<NaiveDateTime>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db
+            .update_recurring_payment_status(id, from, to, next_payment_at, failure_reason)
+    }
+
+    pub fn advance_recurring_payment(
+        &self,
+        id: u64,
+        due_at: NaiveDateTime,
+        next_payment_at: NaiveDateTime,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.advance_recurring_payment(id, due_at, next_payment_at)
+    }
+
+    pub fn record_recurring_payment_result(
+        &self,
+        id: u64,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.record_recurring_payment_result(id, tx_id, failure_reason)
+    }
+
+    pub fn journal_transaction_event(
+        &self,
+        event: &TransactionEvent,
+        max_events: usize,
+    ) -> Result<(), TransactionStorageError> {
+        self.db.journal_transaction_event(event, max_events)
+    }
+
+    pub fn fetch_transaction_events_since(
+        &self,
+        cursor: u64,
+    ) -> Result<Vec<JournaledTransactionEvent>, TransactionStorageError> {
+        self.db.fetch_transaction_events_since(cursor)
+    }
+
+    pub fn insert_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        self.db.insert_send_template(template)
+    }
+
+    pub fn update_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        self.db.update_send_template(template)
+    }
+
+    pub fn fetch_send_template(&self, id: u64) -> Result<Option<SendTemplate>, TransactionStorageError> {
+        self.db.fetch_send_template(id)
+    }
+
+    pub fn fetch_send_templates(&self) -> Result<Vec<SendTemplate>, TransactionStorageError> {
+        self.db.fetch_send_templates()
+    }
+
+    pub fn delete_send_template(&self, id: u64) -> Result<(), TransactionStorageError> {
+        self.db.delete_send_template(id)
+    }
+
+    pub fn insert_imported_history(
+        &self,
+        transactions: Vec<CompletedTransaction>,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        self.db.insert_imported_history(transactions)
+    }
 }
 
 impl Display for DbKey {
@@ -27,3 +27,7 @@ pub mod models;
 // converting between unsigned and signed is okay here as we do it both ways
 #[allow(clippy::cast_possible_wrap)]
 pub mod sqlite_db;
+// converting between unsigned and signed is okay here as we do it both ways
+#[cfg(feature = "postgres")]
+#[allow(clippy::cast_possible_wrap)]
+pub mod postgres_db;
@@ -23,21 +23,31 @@
 use std::{
     convert::TryFrom,
     fmt::{Display, Error, Formatter},
+    time::Duration,
 };
 
 use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use tari_common_types::{
+    burnt_proof::BurntProof,
     tari_address::TariAddress,
     transaction::{TransactionConversionError, TransactionDirection, TransactionStatus, TxId},
-    types::{BlockHash, PrivateKey, Signature},
+    types::{BlockHash, Commitment, FixedHash, HashOutput, PrivateKey, PublicKey, Signature},
 };
 use tari_core::transactions::{
+    frost::{frost_verification_share, FrostNonceCommitment},
+    key_manager::TariKeyId,
     tari_amount::MicroMinotari,
-    transaction_components::Transaction,
+    transaction_components::{Transaction, TransactionInputVersion, TransactionOutputVersion},
     ReceiverTransactionProtocol,
     SenderTransactionProtocol,
 };
+use tari_p2p::tari_message::TariMessageType;
+
+use crate::transaction_service::{
+    handle::{FeePriority, TransactionEvent},
+    offline_signing::UnsignedTransaction,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct InboundTransaction {
@@ -199,6 +209,34 @@ impl CompletedTransaction {
             false
         }
     }
+
+    /// The status of this transaction when `num_confirmations_required` confirmations are needed for a mined
+    /// transaction to be regarded as confirmed, in place of the number the wallet is configured with. Confirmations are
+    /// counted from `tip_height` when it is known, otherwise the last recorded count is used.
+    pub fn status_with_confirmations_required(
+        &self,
+        num_confirmations_required: u64,
+        tip_height: Option<u64>,
+    ) -> TransactionStatus {
+        let confirmations = match (self.mined_height, tip_height) {
+            (Some(mined_height), Some(tip_height)) => tip_height.saturating_sub(mined_height),
+            _ => self.confirmations.unwrap_or_default(),
+        };
+        let is_confirmed = confirmations >= num_confirmations_required;
+        match self.status {
+            TransactionStatus::MinedUnconfirmed | TransactionStatus::MinedConfirmed if is_confirmed => {
+                TransactionStatus::MinedConfirmed
+            },
+            TransactionStatus::MinedUnconfirmed | TransactionStatus::MinedConfirmed => {
+                TransactionStatus::MinedUnconfirmed
+            },
+            TransactionStatus::FauxUnconfirmed | TransactionStatus::FauxConfirmed if is_confirmed => {
+                TransactionStatus::FauxConfirmed
+            },
+            TransactionStatus::FauxUnconfirmed | TransactionStatus::FauxConfirmed => TransactionStatus::FauxUnconfirmed,
+            status => status,
+        }
+    }
 }
 
 impl From<CompletedTransaction> for InboundTransaction {
@@ -319,6 +357,49 @@ pub enum WalletTransaction {
     Completed(CompletedTransaction),
 }
 
+impl WalletTransaction {
+    /// The address of the other party to this transaction
+    pub fn counterparty_address(&self) -> &TariAddress {
+        match self {
+            WalletTransaction::PendingInbound(tx) => &tx.source_address,
+            WalletTransaction::PendingOutbound(tx) => &tx.destination_address,
+            WalletTransaction::Completed(tx) => match tx.direction {
+                TransactionDirection::Outbound => &tx.destination_address,
+                TransactionDirection::Inbound | TransactionDirection::Unknown => &tx.source_address,
+            },
+        }
+    }
+}
+
+/// One of the payments that make up a batch transaction. The batch itself is stored as a single completed transaction
+/// under `tx_id`; each child payment records who was paid, how much and which output of the batch pays them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BatchedPayment {
+    pub tx_id: TxId,
+    pub destination_address: TariAddress,
+    pub amount: MicroMinotari,
+    pub message: String,
+    pub output_hash: HashOutput,
+}
+
+/// A version of a completed transaction that was replaced by a fee bump before it was mined. It spends the same inputs
+/// as its replacement, so the base node may still mine this version instead, in which case it is reinstated.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupersededTransaction {
+    pub id: u64,
+    pub tx_id: TxId,
+    pub fee: MicroMinotari,
+    pub transaction: Transaction,
+    pub batched_payments: Vec<BatchedPayment>,
+    pub superseded_at: NaiveDateTime,
+}
+
+impl SupersededTransaction {
+    pub fn excess_sig(&self) -> Option<&Signature> {
+        self.transaction.first_kernel_excess_sig()
+    }
+}
+
 impl From<WalletTransaction> for CompletedTransaction {
     fn from(tx: WalletTransaction) -> Self {
         match tx {
@@ -376,3 +457,1062 @@ impl Display for TxCancellationReason {
         fmt.write_str(response)
     }
 }
+
+/// The kind of transaction protocol message held in the outbound message queue
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OutboundMessageType {
+    SenderPartialTransaction,        // 0
+    ReceiverPartialTransactionReply, // 1
+    TransactionFinalized,            // 2
+}
+
+impl OutboundMessageType {
+    pub fn as_tari_message_type(&self) -> TariMessageType {
+        match self {
+            OutboundMessageType::SenderPartialTransaction => TariMessageType::SenderPartialTransaction,
+            OutboundMessageType::ReceiverPartialTransactionReply => TariMessageType::ReceiverPartialTransactionReply,
+            OutboundMessageType::TransactionFinalized => TariMessageType::TransactionFinalized,
+        }
+    }
+}
+
+impl TryFrom<i32> for OutboundMessageType {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OutboundMessageType::SenderPartialTransaction),
+            1 => Ok(OutboundMessageType::ReceiverPartialTransactionReply),
+            2 => Ok(OutboundMessageType::TransactionFinalized),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<OutboundMessageType> for i32 {
+    fn from(value: OutboundMessageType) -> Self {
+        match value {
+            OutboundMessageType::SenderPartialTransaction => 0,
+            OutboundMessageType::ReceiverPartialTransactionReply => 1,
+            OutboundMessageType::TransactionFinalized => 2,
+        }
+    }
+}
+
+impl Display for OutboundMessageType {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let response = match self {
+            OutboundMessageType::SenderPartialTransaction => "Sender Partial Transaction",
+            OutboundMessageType::ReceiverPartialTransactionReply => "Receiver Partial Transaction Reply",
+            OutboundMessageType::TransactionFinalized => "Transaction Finalized",
+        };
+        fmt.write_str(response)
+    }
+}
+
+/// Delivery state of a queued outbound message
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OutboundMessageStatus {
+    Pending,   // 0
+    Delivered, // 1
+    Failed,    // 2
+}
+
+impl TryFrom<i32> for OutboundMessageStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OutboundMessageStatus::Pending),
+            1 => Ok(OutboundMessageStatus::Delivered),
+            2 => Ok(OutboundMessageStatus::Failed),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<OutboundMessageStatus> for i32 {
+    fn from(value: OutboundMessageStatus) -> Self {
+        match value {
+            OutboundMessageStatus::Pending => 0,
+            OutboundMessageStatus::Delivered => 1,
+            OutboundMessageStatus::Failed => 2,
+        }
+    }
+}
+
+/// A transaction protocol message that has been persisted so that it survives a wallet restart until it has been
+/// delivered to the counterparty. The payload is the protobuf encoded message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueuedOutboundMessage {
+    pub tx_id: TxId,
+    pub message_type: OutboundMessageType,
+    pub destination_address: TariAddress,
+    pub payload: Vec<u8>,
+    pub status: OutboundMessageStatus,
+    pub attempts: u32,
+    pub created_at: NaiveDateTime,
+    pub last_attempt_at: Option<NaiveDateTime>,
+}
+
+impl QueuedOutboundMessage {
+    pub fn new(
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        destination_address: TariAddress,
+        payload: Vec<u8>,
+        created_at: NaiveDateTime,
+    ) -> Self {
+        Self {
+            tx_id,
+            message_type,
+            destination_address,
+            payload,
+            status: OutboundMessageStatus::Pending,
+            attempts: 0,
+            created_at,
+            last_attempt_at: None,
+        }
+    }
+}
+
+/// A block height or a point in (UTC) time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HeightOrTime {
+    Height(u64),
+    Time(NaiveDateTime),
+}
+
+impl HeightOrTime {
+    /// Returns true once the chain tip or the current time has reached this point. A height is never reached while the
+    /// chain tip is unknown.
+    pub fn is_reached(&self, tip_height: Option<u64>, now: NaiveDateTime) -> bool {
+        match self {
+            HeightOrTime::Height(height) => tip_height.map(|tip| tip >= *height).unwrap_or(false),
+            HeightOrTime::Time(time) => now >= *time,
+        }
+    }
+}
+
+impl Display for HeightOrTime {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            HeightOrTime::Height(height) => write!(f, "height {}", height),
+            HeightOrTime::Time(time) => write!(f, "{}", time),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ScheduledTransactionStatus {
+    /// Waiting for `execute_at` to be reached
+    Pending, // 0
+    /// The send has been started. A triggered transaction is never retried, even if the wallet shuts down part way
+    /// through, so that a payment is never made twice.
+    Triggered, // 1
+    /// The transaction was sent; `tx_id` holds the resulting transaction
+    Completed, // 2
+    /// The send was attempted but failed; `failure_reason` holds the error
+    Failed, // 3
+    /// `expires_at` was reached before the transaction was sent
+    Expired, // 4
+    /// Cancelled by the user before it was triggered
+    Cancelled, // 5
+}
+
+impl TryFrom<i32> for ScheduledTransactionStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(ScheduledTransactionStatus::Pending),
+            1 => Ok(ScheduledTransactionStatus::Triggered),
+            2 => Ok(ScheduledTransactionStatus::Completed),
+            3 => Ok(ScheduledTransactionStatus::Failed),
+            4 => Ok(ScheduledTransactionStatus::Expired),
+            5 => Ok(ScheduledTransactionStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<ScheduledTransactionStatus> for i32 {
+    fn from(value: ScheduledTransactionStatus) -> Self {
+        match value {
+            ScheduledTransactionStatus::Pending => 0,
+            ScheduledTransactionStatus::Triggered => 1,
+            ScheduledTransactionStatus::Completed => 2,
+            ScheduledTransactionStatus::Failed => 3,
+            ScheduledTransactionStatus::Expired => 4,
+            ScheduledTransactionStatus::Cancelled => 5,
+        }
+    }
+}
+
+impl Display for ScheduledTransactionStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let status = match self {
+            ScheduledTransactionStatus::Pending => "Pending",
+            ScheduledTransactionStatus::Triggered => "Triggered",
+            ScheduledTransactionStatus::Completed => "Completed",
+            ScheduledTransactionStatus::Failed => "Failed",
+            ScheduledTransactionStatus::Expired => "Expired",
+            ScheduledTransactionStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(status)
+    }
+}
+
+/// A payment that will be sent once `execute_at` is reached, unless `expires_at` is reached first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduledTransaction {
+    pub id: u64,
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub message: String,
+    /// Send as a one-sided payment rather than an interactive transaction
+    pub one_sided: bool,
+    pub execute_at: HeightOrTime,
+    pub expires_at: Option<HeightOrTime>,
+    pub status: ScheduledTransactionStatus,
+    /// The transaction that was sent, once the schedule has completed
+    pub tx_id: Option<TxId>,
+    pub failure_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl ScheduledTransaction {
+    /// Returns true if the schedule is still pending and its expiry has been reached
+    pub fn is_expired(&self, tip_height: Option<u64>, now: NaiveDateTime) -> bool {
+        self.status == ScheduledTransactionStatus::Pending &&
+            self.expires_at
+                .map(|expires_at| expires_at.is_reached(tip_height, now))
+                .unwrap_or(false)
+    }
+
+    /// Returns true if the schedule is still pending and should be executed now
+    pub fn is_due(&self, tip_height: Option<u64>, now: NaiveDateTime) -> bool {
+        self.status == ScheduledTransactionStatus::Pending && self.execute_at.is_reached(tip_height, now)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum QueuedTransactionStatus {
+    /// Accepted while the wallet was offline, waiting for base node connectivity to return
+    Queued, // 0
+    /// The send has been started. As with scheduled transactions, a sending transaction is never retried so that a
+    /// payment is never made twice.
+    Sending, // 1
+    /// The transaction was sent; `tx_id` holds the resulting transaction
+    Sent, // 2
+    /// The send was attempted but failed; `failure_reason` holds the error
+    Failed, // 3
+    /// Connectivity did not return before the transaction reached the maximum queue age
+    Expired, // 4
+    /// Cancelled by the user before it was sent
+    Cancelled, // 5
+}
+
+impl TryFrom<i32> for QueuedTransactionStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(QueuedTransactionStatus::Queued),
+            1 => Ok(QueuedTransactionStatus::Sending),
+            2 => Ok(QueuedTransactionStatus::Sent),
+            3 => Ok(QueuedTransactionStatus::Failed),
+            4 => Ok(QueuedTransactionStatus::Expired),
+            5 => Ok(QueuedTransactionStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<QueuedTransactionStatus> for i32 {
+    fn from(value: QueuedTransactionStatus) -> Self {
+        match value {
+            QueuedTransactionStatus::Queued => 0,
+            QueuedTransactionStatus::Sending => 1,
+            QueuedTransactionStatus::Sent => 2,
+            QueuedTransactionStatus::Failed => 3,
+            QueuedTransactionStatus::Expired => 4,
+            QueuedTransactionStatus::Cancelled => 5,
+        }
+    }
+}
+
+impl Display for QueuedTransactionStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let status = match self {
+            QueuedTransactionStatus::Queued => "Queued",
+            QueuedTransactionStatus::Sending => "Sending",
+            QueuedTransactionStatus::Sent => "Sent",
+            QueuedTransactionStatus::Failed => "Failed",
+            QueuedTransactionStatus::Expired => "Expired",
+            QueuedTransactionStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(status)
+    }
+}
+
+/// A payment that was accepted while the wallet had no base node connectivity and will be sent once it returns
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedTransaction {
+    pub id: u64,
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub message: String,
+    /// Send as a one-sided payment rather than an interactive transaction
+    pub one_sided: bool,
+    pub status: QueuedTransactionStatus,
+    /// The transaction that was sent, once connectivity returned
+    pub tx_id: Option<TxId>,
+    pub failure_reason: Option<String>,
+    pub queued_at: NaiveDateTime,
+}
+
+impl QueuedTransaction {
+    /// Returns true if the transaction is still queued and has been waiting longer than `max_age`
+    pub fn is_expired(&self, max_age: Duration, now: NaiveDateTime) -> bool {
+        self.status == QueuedTransactionStatus::Queued &&
+            chrono::Duration::from_std(max_age)
+                .map(|max_age| self.queued_at + max_age <= now)
+                .unwrap_or(false)
+    }
+}
+
+/// The side of a hash time locked contract swap this wallet is on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AtomicSwapRole {
+    /// Generated the pre-image and locked funds first
+    Initiator, // 0
+    /// Locked funds against the initiator's hash lock
+    Participant, // 1
+}
+
+impl TryFrom<i32> for AtomicSwapRole {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AtomicSwapRole::Initiator),
+            1 => Ok(AtomicSwapRole::Participant),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<AtomicSwapRole> for i32 {
+    fn from(value: AtomicSwapRole) -> Self {
+        match value {
+            AtomicSwapRole::Initiator => 0,
+            AtomicSwapRole::Participant => 1,
+        }
+    }
+}
+
+impl Display for AtomicSwapRole {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            AtomicSwapRole::Initiator => fmt.write_str("Initiator"),
+            AtomicSwapRole::Participant => fmt.write_str("Participant"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AtomicSwapState {
+    /// The counterparty locked funds that this wallet can redeem, this wallet has not acted yet
+    Proposed, // 0
+    /// This wallet locked funds in an HTLC output
+    Locked, // 1
+    /// This wallet claimed the counterparty's HTLC output
+    Redeemed, // 2
+    /// The counterparty claimed this wallet's HTLC output; `pre_image` holds the revealed pre-image
+    CounterpartyRedeemed, // 3
+    /// This wallet reclaimed its HTLC output after the lock height
+    Refunded, // 4
+    /// The counterparty reclaimed its HTLC output after the lock height
+    CounterpartyRefunded, // 5
+}
+
+impl AtomicSwapState {
+    /// Returns true once nothing is left to be done on the Tari side of the swap
+    pub fn is_final(self) -> bool {
+        matches!(
+            self,
+            AtomicSwapState::Redeemed | AtomicSwapState::Refunded | AtomicSwapState::CounterpartyRefunded
+        )
+    }
+}
+
+impl TryFrom<i32> for AtomicSwapState {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(AtomicSwapState::Proposed),
+            1 => Ok(AtomicSwapState::Locked),
+            2 => Ok(AtomicSwapState::Redeemed),
+            3 => Ok(AtomicSwapState::CounterpartyRedeemed),
+            4 => Ok(AtomicSwapState::Refunded),
+            5 => Ok(AtomicSwapState::CounterpartyRefunded),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<AtomicSwapState> for i32 {
+    fn from(value: AtomicSwapState) -> Self {
+        match value {
+            AtomicSwapState::Proposed => 0,
+            AtomicSwapState::Locked => 1,
+            AtomicSwapState::Redeemed => 2,
+            AtomicSwapState::CounterpartyRedeemed => 3,
+            AtomicSwapState::Refunded => 4,
+            AtomicSwapState::CounterpartyRefunded => 5,
+        }
+    }
+}
+
+impl Display for AtomicSwapState {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let state = match self {
+            AtomicSwapState::Proposed => "Proposed",
+            AtomicSwapState::Locked => "Locked",
+            AtomicSwapState::Redeemed => "Redeemed",
+            AtomicSwapState::CounterpartyRedeemed => "CounterpartyRedeemed",
+            AtomicSwapState::Refunded => "Refunded",
+            AtomicSwapState::CounterpartyRefunded => "CounterpartyRefunded",
+        };
+        fmt.write_str(state)
+    }
+}
+
+/// The persisted state of a hash time locked contract swap
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AtomicSwap {
+    /// The swap id agreed by both parties
+    pub swap_id: u64,
+    pub role: AtomicSwapRole,
+    pub state: AtomicSwapState,
+    pub counterparty: TariAddress,
+    pub amount: MicroMinotari,
+    /// SHA256 hash of the pre-image that unlocks both legs of the swap
+    pub hash_lock: FixedHash,
+    /// Known to the initiator from the start and to the participant once the initiator has redeemed
+    pub pre_image: Option<PublicKey>,
+    /// The block height after which the Tari HTLC output of the swap can be refunded by whoever locked it
+    pub lock_height: u64,
+    /// The transaction and HTLC output locked by this wallet
+    pub lock_tx_id: Option<TxId>,
+    pub lock_output_hash: Option<HashOutput>,
+    /// The HTLC output locked for this wallet by the counterparty, if the counterparty locked on the Tari chain
+    pub counterparty_output_hash: Option<HashOutput>,
+    /// The redeem or refund transaction
+    pub settle_tx_id: Option<TxId>,
+    pub message: String,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OfflineTransactionStatus {
+    /// Exported for signing; its inputs stay encumbered until it is imported or cancelled
+    AwaitingSignature, // 0
+    /// The signed transaction was imported and handed over for broadcast
+    Imported, // 1
+    /// Cancelled before a signed transaction was imported; its inputs were released
+    Cancelled, // 2
+}
+
+impl TryFrom<i32> for OfflineTransactionStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(OfflineTransactionStatus::AwaitingSignature),
+            1 => Ok(OfflineTransactionStatus::Imported),
+            2 => Ok(OfflineTransactionStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<OfflineTransactionStatus> for i32 {
+    fn from(value: OfflineTransactionStatus) -> Self {
+        match value {
+            OfflineTransactionStatus::AwaitingSignature => 0,
+            OfflineTransactionStatus::Imported => 1,
+            OfflineTransactionStatus::Cancelled => 2,
+        }
+    }
+}
+
+impl Display for OfflineTransactionStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let status = match self {
+            OfflineTransactionStatus::AwaitingSignature => "AwaitingSignature",
+            OfflineTransactionStatus::Imported => "Imported",
+            OfflineTransactionStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(status)
+    }
+}
+
+/// A transaction built by this wallet for signing by an offline wallet
+#[derive(Debug, Clone)]
+pub struct OfflineTransaction {
+    pub unsigned_transaction: UnsignedTransaction,
+    pub status: OfflineTransactionStatus,
+    pub created_at: NaiveDateTime,
+}
+
+impl OfflineTransaction {
+    pub fn tx_id(&self) -> TxId {
+        self.unsigned_transaction.tx_id
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BurnStatus {
+    /// The burn transaction has not been mined and confirmed yet
+    Pending, // 0
+    /// The burn transaction is mined and confirmed, so the burnt funds can be claimed on the second layer
+    Claimable, // 1
+    /// The burnt funds were claimed on the second layer
+    Claimed, // 2
+    /// The burn transaction was cancelled, so there is nothing to claim
+    Cancelled, // 3
+}
+
+impl TryFrom<i32> for BurnStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(BurnStatus::Pending),
+            1 => Ok(BurnStatus::Claimable),
+            2 => Ok(BurnStatus::Claimed),
+            3 => Ok(BurnStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<BurnStatus> for i32 {
+    fn from(value: BurnStatus) -> Self {
+        match value {
+            BurnStatus::Pending => 0,
+            BurnStatus::Claimable => 1,
+            BurnStatus::Claimed => 2,
+            BurnStatus::Cancelled => 3,
+        }
+    }
+}
+
+impl Display for BurnStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let status = match self {
+            BurnStatus::Pending => "Pending",
+            BurnStatus::Claimable => "Claimable",
+            BurnStatus::Claimed => "Claimed",
+            BurnStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(status)
+    }
+}
+
+/// A burn made by this wallet, along with the proof needed to claim the burnt funds on the second layer
+#[derive(Debug, Clone)]
+pub struct BurnRecord {
+    pub tx_id: TxId,
+    pub amount: MicroMinotari,
+    /// The public key the burn can be claimed with, if this was a claimable burn
+    pub claim_public_key: Option<PublicKey>,
+    pub proof: BurntProof,
+    pub status: BurnStatus,
+    pub burned_at: NaiveDateTime,
+    pub claimed_at: Option<NaiveDateTime>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MultisigSessionState {
+    /// Waiting for the other participants to publish the commitments to their key generation polynomials
+    AwaitingCommitments, // 0
+    /// Waiting for the other participants' shares of their polynomials
+    AwaitingShares, // 1
+    /// The group key and this wallet's signing share are known, any `threshold` participants can sign
+    Ready, // 2
+    /// The group key is known, waiting for the other participants to confirm that they saw the same commitments
+    AwaitingConfirmations, // 3
+}
+
+impl TryFrom<i32> for MultisigSessionState {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MultisigSessionState::AwaitingCommitments),
+            1 => Ok(MultisigSessionState::AwaitingShares),
+            2 => Ok(MultisigSessionState::Ready),
+            3 => Ok(MultisigSessionState::AwaitingConfirmations),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<MultisigSessionState> for i32 {
+    fn from(value: MultisigSessionState) -> Self {
+        match value {
+            MultisigSessionState::AwaitingCommitments => 0,
+            MultisigSessionState::AwaitingShares => 1,
+            MultisigSessionState::Ready => 2,
+            MultisigSessionState::AwaitingConfirmations => 3,
+        }
+    }
+}
+
+impl Display for MultisigSessionState {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            MultisigSessionState::AwaitingCommitments => fmt.write_str("AwaitingCommitments"),
+            MultisigSessionState::AwaitingShares => fmt.write_str("AwaitingShares"),
+            MultisigSessionState::Ready => fmt.write_str("Ready"),
+            MultisigSessionState::AwaitingConfirmations => fmt.write_str("AwaitingConfirmations"),
+        }
+    }
+}
+
+/// A participant of an m-of-n multisig session, identified by its wallet's comms public key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigParticipant {
+    pub public_key: PublicKey,
+    /// The commitments to the participant's key generation polynomial, once published with a valid proof of possession
+    pub coefficient_commitments: Option<Vec<PublicKey>>,
+    /// The share of the participant's polynomial this wallet received, imported into the key manager. Not set for this
+    /// wallet itself.
+    pub share_id: Option<TariKeyId>,
+    /// The transcript hash of the key generation the participant confirmed
+    #[serde(default)]
+    pub confirmation: Option<FixedHash>,
+}
+
+/// The persisted state of an m-of-n multisig session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigSession {
+    pub session_id: u64,
+    /// The number of participants that must sign
+    pub threshold: u8,
+    /// All participants, including this wallet, in the order chosen by the participant that proposed the session. A
+    /// participant's index is its position in this list, counted from 1.
+    pub participants: Vec<MultisigParticipant>,
+    /// The constant term of this wallet's key generation polynomial
+    pub polynomial_key_id: TariKeyId,
+    /// This wallet's signing share, set once the key generation is complete
+    pub signing_share_id: Option<TariKeyId>,
+    /// The key every signature of the session verifies against, whichever participants sign. Set once the key
+    /// generation is complete.
+    pub group_public_key: Option<PublicKey>,
+    pub label: String,
+    pub state: MultisigSessionState,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MultisigSession {
+    pub fn participant(&self, public_key: &PublicKey) -> Option<&MultisigParticipant> {
+        self.participants.iter().find(|p| &p.public_key == public_key)
+    }
+
+    pub fn participant_mut(&mut self, public_key: &PublicKey) -> Option<&mut MultisigParticipant> {
+        self.participants.iter_mut().find(|p| &p.public_key == public_key)
+    }
+
+    /// The index of a participant
+    pub fn participant_index(&self, public_key: &PublicKey) -> Option<u16> {
+        self.participants
+            .iter()
+            .position(|p| &p.public_key == public_key)
+            .and_then(|i| u16::try_from(i + 1).ok())
+    }
+
+    /// The public key of a participant's signing share. Returns `None` if a participant's commitments are not known.
+    pub fn verification_share(&self, public_key: &PublicKey) -> Option<PublicKey> {
+        let participant = self.participant_index(public_key)?;
+        let commitments = self
+            .participants
+            .iter()
+            .map(|p| p.coefficient_commitments.as_deref())
+            .collect::<Option<Vec<_>>>()?;
+        Some(frost_verification_share(commitments, participant))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MultisigSigningState {
+    /// A cosigner asked for this wallet's signature share, which must be approved first
+    PendingApproval, // 0
+    /// Waiting for the cosigners' nonce commitments
+    AwaitingCosignerCommitments, // 1
+    /// Waiting for the cosigners' signature shares
+    AwaitingCosignerShares, // 2
+    /// `signature` holds the group signature, or `joint_public_key` or `script_offset` the result of the other kinds
+    Complete, // 3
+    /// This wallet declined to sign or gave up on the signing
+    Cancelled, // 4
+    /// A joint key that was used up by a signing and must not be used again
+    Used, // 5
+}
+
+impl MultisigSigningState {
+    pub fn is_awaiting_cosigners(self) -> bool {
+        matches!(
+            self,
+            MultisigSigningState::AwaitingCosignerCommitments | MultisigSigningState::AwaitingCosignerShares
+        )
+    }
+}
+
+impl TryFrom<i32> for MultisigSigningState {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(MultisigSigningState::PendingApproval),
+            1 => Ok(MultisigSigningState::AwaitingCosignerCommitments),
+            2 => Ok(MultisigSigningState::AwaitingCosignerShares),
+            3 => Ok(MultisigSigningState::Complete),
+            4 => Ok(MultisigSigningState::Cancelled),
+            5 => Ok(MultisigSigningState::Used),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<MultisigSigningState> for i32 {
+    fn from(value: MultisigSigningState) -> Self {
+        match value {
+            MultisigSigningState::PendingApproval => 0,
+            MultisigSigningState::AwaitingCosignerCommitments => 1,
+            MultisigSigningState::AwaitingCosignerShares => 2,
+            MultisigSigningState::Complete => 3,
+            MultisigSigningState::Cancelled => 4,
+            MultisigSigningState::Used => 5,
+        }
+    }
+}
+
+impl Display for MultisigSigningState {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let state = match self {
+            MultisigSigningState::PendingApproval => "PendingApproval",
+            MultisigSigningState::AwaitingCosignerCommitments => "AwaitingCosignerCommitments",
+            MultisigSigningState::AwaitingCosignerShares => "AwaitingCosignerShares",
+            MultisigSigningState::Complete => "Complete",
+            MultisigSigningState::Cancelled => "Cancelled",
+            MultisigSigningState::Used => "Used",
+        };
+        fmt.write_str(state)
+    }
+}
+
+/// The contribution of one signer to a group signature, filled in round by round
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigSigner {
+    pub public_key: PublicKey,
+    /// The signer's index in the session
+    pub participant: u16,
+    pub hiding_nonce: Option<PublicKey>,
+    pub binding_nonce: Option<PublicKey>,
+    /// The signer's signature share, or its share of a metadata signature or script offset
+    pub signature_share: Option<PrivateKey>,
+    /// The signer's part of a joint key
+    #[serde(default)]
+    pub joint_key_part: Option<PublicKey>,
+}
+
+impl MultisigSigner {
+    pub fn new(public_key: PublicKey, participant: u16) -> Self {
+        Self {
+            public_key,
+            participant,
+            hiding_nonce: None,
+            binding_nonce: None,
+            signature_share: None,
+            joint_key_part: None,
+        }
+    }
+
+    pub fn nonce_commitment(&self) -> Option<FrostNonceCommitment> {
+        Some(FrostNonceCommitment {
+            participant: self.participant,
+            hiding: self.hiding_nonce.clone()?,
+            binding: self.binding_nonce.clone()?,
+        })
+    }
+}
+
+/// A key of a wallet whose keys are held by a multisig session, by key branch and index
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MultisigKey {
+    pub branch: String,
+    pub index: u64,
+}
+
+impl Display for MultisigKey {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(fmt, "{}/{}", self.branch, self.index)
+    }
+}
+
+/// What the signers of a multisig signing make together
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MultisigSigningKind {
+    /// A Schnorr signature of the signing's message by the group key
+    Message,
+    /// The script key part of the script signature of an input locked to the group's script key `script_key`
+    ScriptSignature {
+        script_key: MultisigKey,
+        txi_version: TransactionInputVersion,
+        ephemeral_commitment: Commitment,
+        commitment: Commitment,
+        script_message: FixedHash,
+    },
+    /// A sender offset key or metadata signature nonce of the requester, held jointly by the signers. Every signer
+    /// only knows its own part.
+    JointKey { key: MultisigKey },
+    /// The sender half of an output's metadata signature, made with the requester's joint keys `nonce` and
+    /// `sender_offset_key`, whose public keys are `joint_nonce` and `joint_sender_offset_key`. Uses up the nonce.
+    MetadataSignature {
+        nonce: MultisigKey,
+        sender_offset_key: MultisigKey,
+        txo_version: TransactionOutputVersion,
+        commitment: Commitment,
+        ephemeral_commitment: Commitment,
+        metadata_signature_message: FixedHash,
+        joint_nonce: PublicKey,
+        joint_sender_offset_key: PublicKey,
+    },
+    /// The group part of the script offset of a transaction spending the inputs locked to `script_keys`, minus the
+    /// requester's joint `sender_offset_keys`. Uses up the sender offset keys.
+    ScriptOffset {
+        script_keys: Vec<MultisigKey>,
+        sender_offset_keys: Vec<MultisigKey>,
+    },
+}
+
+impl MultisigSigningKind {
+    /// Whether the signers sign for the group key in two rounds. The other kinds take a single round.
+    pub fn is_group_signature(&self) -> bool {
+        matches!(
+            self,
+            MultisigSigningKind::Message | MultisigSigningKind::ScriptSignature { .. }
+        )
+    }
+
+    /// Whether a cosigner must approve a request before it takes part. Joint keys, metadata signatures and script
+    /// offsets cannot spend funds on their own, every input still needs an approved script signature.
+    pub fn needs_approval(&self) -> bool {
+        self.is_group_signature()
+    }
+}
+
+impl Display for MultisigSigningKind {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            MultisigSigningKind::Message => fmt.write_str("Message"),
+            MultisigSigningKind::ScriptSignature { script_key, .. } => write!(fmt, "ScriptSignature({})", script_key),
+            MultisigSigningKind::JointKey { key } => write!(fmt, "JointKey({})", key),
+            MultisigSigningKind::MetadataSignature { nonce, .. } => write!(fmt, "MetadataSignature({})", nonce),
+            MultisigSigningKind::ScriptOffset { script_keys, .. } => {
+                write!(fmt, "ScriptOffset({} inputs)", script_keys.len())
+            },
+        }
+    }
+}
+
+/// The persisted state of a signing by `threshold` participants of a multisig session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigSigning {
+    pub signing_id: u64,
+    pub session_id: u64,
+    pub kind: MultisigSigningKind,
+    /// The 32-byte message of a `Message` signing, zero for the other kinds
+    pub message: FixedHash,
+    /// The signers, the requester first
+    pub signers: Vec<MultisigSigner>,
+    /// This wallet's hiding nonce, a random key imported once this wallet approved the signing
+    pub hiding_nonce_id: Option<TariKeyId>,
+    /// This wallet's binding nonce, a random key imported once this wallet approved the signing
+    pub binding_nonce_id: Option<TariKeyId>,
+    /// This wallet's part of a joint key
+    pub secret_key_id: Option<TariKeyId>,
+    pub description: String,
+    pub state: MultisigSigningState,
+    /// The group signature, or the public nonce and signature scalar of a metadata signature
+    pub signature: Option<Signature>,
+    /// The joint key, once the parts of all signers are in
+    pub joint_public_key: Option<PublicKey>,
+    /// The group part of a script offset
+    pub script_offset: Option<PrivateKey>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl MultisigSigning {
+    pub fn signer(&self, public_key: &PublicKey) -> Option<&MultisigSigner> {
+        self.signers.iter().find(|s| &s.public_key == public_key)
+    }
+
+    /// The signer that requested the signing
+    pub fn requester(&self) -> Option<&PublicKey> {
+        self.signers.first().map(|s| &s.public_key)
+    }
+
+    /// The indices of the signers in the session
+    pub fn signer_indices(&self) -> Vec<u16> {
+        self.signers.iter().map(|s| s.participant).collect()
+    }
+
+    pub fn signer_mut(&mut self, public_key: &PublicKey) -> Option<&mut MultisigSigner> {
+        self.signers.iter_mut().find(|s| &s.public_key == public_key)
+    }
+
+    /// The nonce commitments of all signers, or `None` while a signer's commitments are missing
+    pub fn nonce_commitments(&self) -> Option<Vec<FrostNonceCommitment>> {
+        self.signers.iter().map(MultisigSigner::nonce_commitment).collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RecurringPaymentStatus {
+    /// Payments are sent every interval
+    Active, // 0
+    /// No payments are sent until the plan is resumed. Plans are paused by the user, or automatically when the wallet
+    /// does not have enough funds for a payment.
+    Paused, // 1
+    /// The end date was reached
+    Completed, // 2
+    /// Cancelled by the user
+    Cancelled, // 3
+}
+
+impl TryFrom<i32> for RecurringPaymentStatus {
+    type Error = TransactionConversionError;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(RecurringPaymentStatus::Active),
+            1 => Ok(RecurringPaymentStatus::Paused),
+            2 => Ok(RecurringPaymentStatus::Completed),
+            3 => Ok(RecurringPaymentStatus::Cancelled),
+            code => Err(TransactionConversionError { code }),
+        }
+    }
+}
+
+impl From<RecurringPaymentStatus> for i32 {
+    fn from(value: RecurringPaymentStatus) -> Self {
+        match value {
+            RecurringPaymentStatus::Active => 0,
+            RecurringPaymentStatus::Paused => 1,
+            RecurringPaymentStatus::Completed => 2,
+            RecurringPaymentStatus::Cancelled => 3,
+        }
+    }
+}
+
+impl Display for RecurringPaymentStatus {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> Result<(), Error> {
+        let status = match self {
+            RecurringPaymentStatus::Active => "Active",
+            RecurringPaymentStatus::Paused => "Paused",
+            RecurringPaymentStatus::Completed => "Completed",
+            RecurringPaymentStatus::Cancelled => "Cancelled",
+        };
+        fmt.write_str(status)
+    }
+}
+
+/// A payment plan that sends `amount` to `destination` every `interval` until `end_at` is reached
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecurringPayment {
+    pub id: u64,
+    pub destination: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee_per_gram: MicroMinotari,
+    pub message: String,
+    /// Send as one-sided payments rather than interactive transactions
+    pub one_sided: bool,
+    pub interval: Duration,
+    pub next_payment_at: NaiveDateTime,
+    /// No payments are sent at or after this time
+    pub end_at: Option<NaiveDateTime>,
+    pub status: RecurringPaymentStatus,
+    pub payments_made: u64,
+    /// The transaction of the most recent successful payment
+    pub last_tx_id: Option<TxId>,
+    /// The reason the most recent payment failed or the plan was paused
+    pub failure_reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl RecurringPayment {
+    /// Returns true if the plan is active and has reached its end date
+    pub fn has_ended(&self, now: NaiveDateTime) -> bool {
+        self.status == RecurringPaymentStatus::Active && self.end_at.map(|end_at| now >= end_at).unwrap_or(false)
+    }
+
+    /// Returns true if the plan is active and the next payment should be sent now
+    pub fn is_due(&self, now: NaiveDateTime) -> bool {
+        self.status == RecurringPaymentStatus::Active && now >= self.next_payment_at && !self.has_ended(now)
+    }
+
+    /// The time of the payment after the one currently due. Payments missed while the wallet was offline are not caught
+    /// up on: if more than one interval has passed, the next payment is a full interval from `now`.
+    pub fn following_payment_at(&self, now: NaiveDateTime) -> NaiveDateTime {
+        let interval = chrono::Duration::from_std(self.interval).unwrap_or_else(|_| chrono::Duration::max_value());
+        let next = self
+            .next_payment_at
+            .checked_add_signed(interval)
+            .unwrap_or(NaiveDateTime::MAX);
+        if next > now {
+            next
+        } else {
+            now.checked_add_signed(interval).unwrap_or(NaiveDateTime::MAX)
+        }
+    }
+}
+
+/// A transaction event persisted in the event journal so that it can be replayed to consumers that were not
+/// subscribed when it was published
+#[derive(Debug, Clone, PartialEq)]
+pub struct JournaledTransactionEvent {
+    /// Increases with every journaled event, pass it to `get_events_since` to fetch the events that follow this one
+    pub cursor: u64,
+    pub event: TransactionEvent,
+    pub created_at: NaiveDateTime,
+}
+
+/// A named set of payment details for a repeat payee. A payment is sent from a template with
+/// [send_from_template](crate::transaction_service::handle::TransactionServiceHandle::send_from_template).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SendTemplate {
+    pub id: u64,
+    /// Unique name of the template
+    pub name: String,
+    pub destination: TariAddress,
+    /// The amount sent when no amount is given when sending from the template
+    pub default_amount: Option<MicroMinotari>,
+    pub message: String,
+    /// The fee per gram is estimated for this priority at the time of sending
+    pub fee_priority: FeePriority,
+    /// Send as a one-sided payment rather than an interactive transaction
+    pub one_sided: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
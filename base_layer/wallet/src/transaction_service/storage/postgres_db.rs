@@ -0,0 +1,2010 @@
+// Copyright 2020. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{BTreeSet, HashMap},
+    convert::TryFrom,
+    sync::{Arc, RwLock},
+};
+
+use chacha20poly1305::XChaCha20Poly1305;
+use chrono::{NaiveDateTime, Utc};
+use diesel::{pg::PgConnection, prelude::*, result::Error as DieselError, upsert::excluded};
+use log::*;
+use tari_common_sqlite::util::diesel_ext::ExpectedRowsExtension;
+use tari_common_types::{
+    encryption::Encryptable,
+    tari_address::TariAddress,
+    transaction::{TransactionStatus, TxId},
+    types::BlockHash,
+};
+use tari_core::transactions::tari_amount::MicroMinotari;
+
+use crate::{
+    schema::{
+        atomic_swaps,
+        batched_payments,
+        burn_transactions,
+        completed_transactions,
+        inbound_transactions,
+        multisig_sessions,
+        multisig_signings,
+        offline_transactions,
+        outbound_message_queue,
+        outbound_transactions,
+        queued_transactions,
+        recurring_payments,
+        scheduled_transactions,
+        send_templates,
+        superseded_transactions,
+        transaction_counterparty_aliases,
+        transaction_events,
+        transaction_memos,
+        transaction_tags,
+    },
+    storage::postgres_utilities::WalletPostgresConnection,
+    transaction_service::{
+        error::TransactionStorageError,
+        handle::TransactionEvent,
+        storage::{
+            database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, WriteOperation},
+            models::{
+                AtomicSwap,
+                BatchedPayment,
+                BurnRecord,
+                BurnStatus,
+                CompletedTransaction,
+                InboundTransaction,
+                JournaledTransactionEvent,
+                MultisigSession,
+                MultisigSigning,
+                OfflineTransaction,
+                OfflineTransactionStatus,
+                OutboundMessageStatus,
+                OutboundMessageType,
+                OutboundTransaction,
+                QueuedOutboundMessage,
+                QueuedTransaction,
+                QueuedTransactionStatus,
+                RecurringPayment,
+                RecurringPaymentStatus,
+                ScheduledTransaction,
+                ScheduledTransactionStatus,
+                SendTemplate,
+                SupersededTransaction,
+                TxCancellationReason,
+                WalletTransaction,
+            },
+            sqlite_db::{
+                escape_like_pattern,
+                reencrypt,
+                AtomicSwapSql,
+                BatchedPaymentSql,
+                BurnTransactionSql,
+                CompletedTransactionSql,
+                CounterpartyAliasSql,
+                InboundTransactionSenderInfo,
+                InboundTransactionSenderInfoSql,
+                InboundTransactionSql,
+                MultisigSessionSql,
+                MultisigSigningSql,
+                NewBatchedPaymentSql,
+                NewTransactionEventSql,
+                OfflineTransactionSql,
+                OutboundMessageSql,
+                OutboundTransactionSql,
+                QueuedTransactionSql,
+                RecurringPaymentSql,
+                ScheduledTransactionSql,
+                SendTemplateSql,
+                SupersededTransactionSql,
+                TransactionEncryptedTable,
+                TransactionEventSql,
+                TransactionMemoSql,
+                TransactionTagSql,
+                UnconfirmedTransactionInfo,
+                UnconfirmedTransactionInfoSql,
+                UpdateCompletedTransactionSql,
+            },
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::database::postgres";
+
+/// A PostgreSQL backend for the Transaction Service. Rows are stored and encrypted exactly as in
+/// [TransactionServiceSqliteDatabase](super::sqlite_db::TransactionServiceSqliteDatabase); only the queries differ.
+#[derive(Clone)]
+pub struct TransactionServicePostgresDatabase {
+    database_connection: WalletPostgresConnection,
+    cipher: Arc<RwLock<XChaCha20Poly1305>>,
+}
+
+impl TransactionServicePostgresDatabase {
+    pub fn new(database_connection: WalletPostgresConnection, cipher: XChaCha20Poly1305) -> Self {
+        Self {
+            database_connection,
+            cipher: Arc::new(RwLock::new(cipher)),
+        }
+    }
+
+    /// Create a backend that shares its cipher with the wallet database
+    pub fn new_with_shared_cipher(
+        database_connection: WalletPostgresConnection,
+        cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    ) -> Self {
+        Self {
+            database_connection,
+            cipher,
+        }
+    }
+
+    fn insert(&self, kvp: DbKeyValuePair, conn: &mut PgConnection) -> Result<(), TransactionStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+
+        match kvp {
+            DbKeyValuePair::PendingOutboundTransaction(k, v) => {
+                if find_outbound(k, Some(false), conn)?.is_some() {
+                    return Err(TransactionStorageError::DuplicateOutput);
+                }
+                diesel::insert_into(outbound_transactions::table)
+                    .values(OutboundTransactionSql::try_from(*v, &cipher)?)
+                    .execute(conn)?;
+            },
+            DbKeyValuePair::PendingInboundTransaction(k, v) => {
+                if find_inbound(k, Some(false), conn)?.is_some() {
+                    return Err(TransactionStorageError::DuplicateOutput);
+                }
+                diesel::insert_into(inbound_transactions::table)
+                    .values(InboundTransactionSql::try_from(*v, &cipher)?)
+                    .execute(conn)?;
+            },
+            DbKeyValuePair::CompletedTransaction(k, v) => {
+                if find_completed(k, Some(false), conn)?.is_some() {
+                    return Err(TransactionStorageError::DuplicateOutput);
+                }
+                diesel::insert_into(completed_transactions::table)
+                    .values(CompletedTransactionSql::try_from(*v, &cipher)?)
+                    .execute(conn)?;
+            },
+        }
+        Ok(())
+    }
+
+    fn remove(&self, key: DbKey, conn: &mut PgConnection) -> Result<Option<DbValue>, TransactionStorageError> {
+        let cipher = acquire_read_lock!(self.cipher);
+        match key {
+            DbKey::PendingOutboundTransaction(k) | DbKey::CancelledPendingOutboundTransaction(k) => {
+                let cancelled = matches!(key, DbKey::CancelledPendingOutboundTransaction(_));
+                conn.transaction::<_, TransactionStorageError, _>(|conn| {
+                    let v = find_outbound(k, Some(cancelled), conn)?
+                        .ok_or_else(|| TransactionStorageError::ValueNotFound(key.clone()))?;
+                    diesel::delete(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(v.tx_id)))
+                        .execute(conn)?;
+                    Ok(Some(DbValue::PendingOutboundTransaction(Box::new(
+                        OutboundTransaction::try_from(v, &cipher)?,
+                    ))))
+                })
+            },
+            DbKey::PendingInboundTransaction(k) | DbKey::CancelledPendingInboundTransaction(k) => {
+                let cancelled = matches!(key, DbKey::CancelledPendingInboundTransaction(_));
+                conn.transaction::<_, TransactionStorageError, _>(|conn| {
+                    let v = find_inbound(k, Some(cancelled), conn)?
+                        .ok_or_else(|| TransactionStorageError::ValueNotFound(key.clone()))?;
+                    diesel::delete(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(v.tx_id)))
+                        .execute(conn)?;
+                    Ok(Some(DbValue::PendingInboundTransaction(Box::new(
+                        InboundTransaction::try_from(v, &cipher)?,
+                    ))))
+                })
+            },
+            DbKey::CompletedTransaction(k) => conn.transaction::<_, TransactionStorageError, _>(|conn| {
+                let v = find_completed(k, Some(false), conn)?
+                    .ok_or(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(k)))?;
+                diesel::delete(completed_transactions::table.filter(completed_transactions::tx_id.eq(v.tx_id)))
+                    .execute(conn)?;
+                Ok(Some(DbValue::CompletedTransaction(Box::new(
+                    CompletedTransaction::try_from(v, &cipher)?,
+                ))))
+            }),
+            DbKey::AnyTransaction(k) => conn.transaction::<_, TransactionStorageError, _>(|conn| {
+                // Looked up in the same order as the fetch, so that the transaction removed is the one fetched
+                let tx = if let Some(o) = find_outbound(k, None, conn)? {
+                    diesel::delete(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(o.tx_id)))
+                        .execute(conn)?;
+                    WalletTransaction::PendingOutbound(OutboundTransaction::try_from(o, &cipher)?)
+                } else if let Some(i) = find_inbound(k, None, conn)? {
+                    diesel::delete(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(i.tx_id)))
+                        .execute(conn)?;
+                    WalletTransaction::PendingInbound(InboundTransaction::try_from(i, &cipher)?)
+                } else if let Some(c) = find_completed(k, None, conn)? {
+                    diesel::delete(completed_transactions::table.filter(completed_transactions::tx_id.eq(c.tx_id)))
+                        .execute(conn)?;
+                    WalletTransaction::Completed(CompletedTransaction::try_from(c, &cipher)?)
+                } else {
+                    return Err(TransactionStorageError::ValueNotFound(DbKey::AnyTransaction(k)));
+                };
+                Ok(Some(DbValue::WalletTransaction(Box::new(tx))))
+            }),
+            DbKey::PendingOutboundTransactions |
+            DbKey::PendingInboundTransactions |
+            DbKey::CompletedTransactions |
+            DbKey::CancelledPendingOutboundTransactions |
+            DbKey::CancelledPendingInboundTransactions |
+            DbKey::CancelledCompletedTransactions => Err(TransactionStorageError::OperationNotSupported),
+        }
+    }
+}
+
+impl TransactionBackend for TransactionServicePostgresDatabase {
+    fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        let result = match key {
+            DbKey::PendingOutboundTransaction(t) | DbKey::CancelledPendingOutboundTransaction(t) => {
+                let cancelled = matches!(key, DbKey::CancelledPendingOutboundTransaction(_));
+                find_outbound(*t, Some(cancelled), &mut conn)?
+                    .map(|o| OutboundTransaction::try_from(o, &cipher))
+                    .transpose()?
+                    .map(|o| DbValue::PendingOutboundTransaction(Box::new(o)))
+            },
+            DbKey::PendingInboundTransaction(t) | DbKey::CancelledPendingInboundTransaction(t) => {
+                let cancelled = matches!(key, DbKey::CancelledPendingInboundTransaction(_));
+                find_inbound(*t, Some(cancelled), &mut conn)?
+                    .map(|i| InboundTransaction::try_from(i, &cipher))
+                    .transpose()?
+                    .map(|i| DbValue::PendingInboundTransaction(Box::new(i)))
+            },
+            DbKey::CompletedTransaction(t) => find_completed(*t, None, &mut conn)?
+                .map(|c| CompletedTransaction::try_from(c, &cipher))
+                .transpose()?
+                .map(|c| DbValue::CompletedTransaction(Box::new(c))),
+            DbKey::AnyTransaction(t) => {
+                let tx = if let Some(o) = find_outbound(*t, None, &mut conn)? {
+                    Some(WalletTransaction::PendingOutbound(OutboundTransaction::try_from(
+                        o, &cipher,
+                    )?))
+                } else if let Some(i) = find_inbound(*t, None, &mut conn)? {
+                    Some(WalletTransaction::PendingInbound(InboundTransaction::try_from(
+                        i, &cipher,
+                    )?))
+                } else if let Some(c) = find_completed(*t, None, &mut conn)? {
+                    Some(WalletTransaction::Completed(CompletedTransaction::try_from(
+                        c, &cipher,
+                    )?))
+                } else {
+                    None
+                };
+                tx.map(|tx| DbValue::WalletTransaction(Box::new(tx)))
+            },
+            DbKey::PendingOutboundTransactions | DbKey::CancelledPendingOutboundTransactions => {
+                let cancelled = matches!(key, DbKey::CancelledPendingOutboundTransactions);
+                let mut result = HashMap::new();
+                for o in index_outbound(cancelled, &mut conn)? {
+                    result.insert(TxId::from(o.tx_id as u64), OutboundTransaction::try_from(o, &cipher)?);
+                }
+                Some(DbValue::PendingOutboundTransactions(result))
+            },
+            DbKey::PendingInboundTransactions | DbKey::CancelledPendingInboundTransactions => {
+                let cancelled = matches!(key, DbKey::CancelledPendingInboundTransactions);
+                let mut result = HashMap::new();
+                for i in index_inbound(cancelled, &mut conn)? {
+                    result.insert(TxId::from(i.tx_id as u64), InboundTransaction::try_from(i, &cipher)?);
+                }
+                Some(DbValue::PendingInboundTransactions(result))
+            },
+            DbKey::CompletedTransactions | DbKey::CancelledCompletedTransactions => {
+                let cancelled = matches!(key, DbKey::CancelledCompletedTransactions);
+                let mut result = HashMap::new();
+                for c in index_completed(cancelled, None, &mut conn)? {
+                    result.insert(TxId::from(c.tx_id as u64), CompletedTransaction::try_from(c, &cipher)?);
+                }
+                Some(DbValue::CompletedTransactions(result))
+            },
+        };
+
+        Ok(result)
+    }
+
+    fn contains(&self, key: &DbKey) -> Result<bool, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        let result = match key {
+            DbKey::PendingOutboundTransaction(k) => find_outbound(*k, Some(false), &mut conn)?.is_some(),
+            DbKey::PendingInboundTransaction(k) => find_inbound(*k, Some(false), &mut conn)?.is_some(),
+            DbKey::CompletedTransaction(k) => find_completed(*k, None, &mut conn)?.is_some(),
+            DbKey::CancelledPendingOutboundTransaction(k) => find_outbound(*k, Some(true), &mut conn)?.is_some(),
+            DbKey::CancelledPendingInboundTransaction(k) => find_inbound(*k, Some(true), &mut conn)?.is_some(),
+            DbKey::AnyTransaction(k) => {
+                find_completed(*k, None, &mut conn)?.is_some() ||
+                    find_inbound(*k, None, &mut conn)?.is_some() ||
+                    find_outbound(*k, None, &mut conn)?.is_some()
+            },
+            DbKey::PendingOutboundTransactions |
+            DbKey::PendingInboundTransactions |
+            DbKey::CompletedTransactions |
+            DbKey::CancelledPendingOutboundTransactions |
+            DbKey::CancelledPendingInboundTransactions |
+            DbKey::CancelledCompletedTransactions => false,
+        };
+
+        Ok(result)
+    }
+
+    fn write(&self, op: WriteOperation) -> Result<Option<DbValue>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        match op {
+            WriteOperation::Insert(kvp) => self.insert(kvp, &mut conn).map(|_| None),
+            WriteOperation::Remove(key) => self.remove(key, &mut conn),
+        }
+    }
+
+    fn transaction_exists(&self, tx_id: TxId) -> Result<bool, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        Ok(find_outbound(tx_id, Some(false), &mut conn)?.is_some() ||
+            find_inbound(tx_id, Some(false), &mut conn)?.is_some() ||
+            find_completed(tx_id, Some(false), &mut conn)?.is_some())
+    }
+
+    fn get_pending_transaction_counterparty_address_by_tx_id(
+        &self,
+        tx_id: TxId,
+    ) -> Result<TariAddress, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        if let Some(o) = find_outbound(tx_id, Some(false), &mut conn)? {
+            return Ok(OutboundTransaction::try_from(o, &cipher)?.destination_address);
+        }
+        if let Some(i) = find_inbound(tx_id, Some(false), &mut conn)? {
+            return Ok(InboundTransaction::try_from(i, &cipher)?.source_address);
+        }
+
+        Err(TransactionStorageError::ValuesNotFound)
+    }
+
+    fn fetch_any_cancelled_transaction(
+        &self,
+        tx_id: TxId,
+    ) -> Result<Option<WalletTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        if let Some(o) = find_outbound(tx_id, Some(true), &mut conn)? {
+            return Ok(Some(WalletTransaction::PendingOutbound(OutboundTransaction::try_from(
+                o, &cipher,
+            )?)));
+        }
+        if let Some(i) = find_inbound(tx_id, Some(true), &mut conn)? {
+            return Ok(Some(WalletTransaction::PendingInbound(InboundTransaction::try_from(
+                i, &cipher,
+            )?)));
+        }
+        if let Some(c) = find_completed(tx_id, Some(true), &mut conn)? {
+            return Ok(Some(WalletTransaction::Completed(CompletedTransaction::try_from(
+                c, &cipher,
+            )?)));
+        }
+        Ok(None)
+    }
+
+    fn complete_outbound_transaction(
+        &self,
+        tx_id: TxId,
+        completed_transaction: CompletedTransaction,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        if find_completed(tx_id, Some(false), &mut conn)?.is_some() {
+            return Err(TransactionStorageError::TransactionAlreadyExists);
+        }
+        let completed_tx_sql = CompletedTransactionSql::try_from(completed_transaction, &cipher)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            diesel::delete(
+                outbound_transactions::table
+                    .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                    .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+            )
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)
+            .map_err(not_found_as(DbKey::PendingOutboundTransaction(tx_id)))?;
+            diesel::insert_into(completed_transactions::table)
+                .values(completed_tx_sql)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn complete_inbound_transaction(
+        &self,
+        tx_id: TxId,
+        completed_transaction: CompletedTransaction,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        if find_completed(tx_id, Some(false), &mut conn)?.is_some() {
+            return Err(TransactionStorageError::TransactionAlreadyExists);
+        }
+        let completed_tx_sql = CompletedTransactionSql::try_from(completed_transaction, &cipher)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            diesel::delete(
+                inbound_transactions::table
+                    .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                    .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+            )
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)
+            .map_err(not_found_as(DbKey::PendingInboundTransaction(tx_id)))?;
+            diesel::insert_into(completed_transactions::table)
+                .values(completed_tx_sql)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn broadcast_completed_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let v = find_completed(tx_id, Some(false), conn)?.ok_or(TransactionStorageError::ValueNotFound(
+                DbKey::CompletedTransaction(tx_id),
+            ))?;
+            if TransactionStatus::try_from(v.status)? == TransactionStatus::Completed {
+                update_completed(
+                    tx_id,
+                    UpdateCompletedTransactionSql {
+                        status: Some(TransactionStatus::Broadcast as i32),
+                        ..Default::default()
+                    },
+                    conn,
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    fn reject_completed_transaction(
+        &self,
+        tx_id: TxId,
+        reason: TxCancellationReason,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(completed_transactions::cancelled.is_null()),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(reason as i32)),
+            status: Some(TransactionStatus::Rejected as i32),
+            ..Default::default()
+        })
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)
+        .map_err(not_found_as(DbKey::CompletedTransaction(tx_id)))?;
+        Ok(())
+    }
+
+    fn set_pending_transaction_cancellation_status(
+        &self,
+        tx_id: TxId,
+        cancelled: bool,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tx_id = tx_id.as_u64() as i64;
+
+        let updated = diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(tx_id)))
+            .set(inbound_transactions::cancelled.eq(i32::from(cancelled)))
+            .execute(&mut conn)? >
+            0 ||
+            diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id)))
+                .set(outbound_transactions::cancelled.eq(i32::from(cancelled)))
+                .execute(&mut conn)? >
+                0;
+        if !updated {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+        Ok(())
+    }
+
+    fn mark_direct_send_success(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tx_id = tx_id.as_u64() as i64;
+
+        let updated = diesel::update(
+            inbound_transactions::table
+                .filter(inbound_transactions::tx_id.eq(tx_id))
+                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .set(inbound_transactions::direct_send_success.eq(1i32))
+        .execute(&mut conn)? >
+            0 ||
+            diesel::update(
+                outbound_transactions::table
+                    .filter(outbound_transactions::tx_id.eq(tx_id))
+                    .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+            )
+            .set(outbound_transactions::direct_send_success.eq(1i32))
+            .execute(&mut conn)? >
+                0;
+        if !updated {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+        Ok(())
+    }
+
+    fn mark_awaiting_cosigners(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let updated = diesel::update(
+            outbound_transactions::table
+                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .set(outbound_transactions::awaiting_cosigners.eq(1i32))
+        .execute(&mut conn)?;
+        if updated == 0 {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+        Ok(())
+    }
+
+    fn cancel_coinbase_transactions_at_block_height(&self, block_height: u64) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
+                .filter(completed_transactions::coinbase_block_height.eq(block_height as i64)),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(TxCancellationReason::AbandonedCoinbase as i32)),
+            status: Some(TransactionStatus::Rejected as i32),
+            ..Default::default()
+        })
+        .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn find_coinbase_transaction_at_block_height(
+        &self,
+        block_height: u64,
+        amount: MicroMinotari,
+    ) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        let coinbase_txs = completed_transactions::table
+            .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
+            .filter(completed_transactions::coinbase_block_height.eq(block_height as i64))
+            .load::<CompletedTransactionSql>(&mut conn)?;
+        for c in coinbase_txs {
+            let completed_tx = CompletedTransaction::try_from(c, &cipher)?;
+            if completed_tx.amount == amount {
+                return Ok(Some(completed_tx));
+            }
+        }
+        Ok(None)
+    }
+
+    fn increment_send_count(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tx_id = tx_id.as_u64() as i64;
+        let now = Some(Utc::now().naive_utc());
+
+        let updated = diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id)))
+            .set((
+                completed_transactions::send_count.eq(completed_transactions::send_count + 1),
+                completed_transactions::last_send_timestamp.eq(now),
+            ))
+            .execute(&mut conn)? >
+            0 ||
+            diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id)))
+                .set((
+                    outbound_transactions::send_count.eq(outbound_transactions::send_count + 1),
+                    outbound_transactions::last_send_timestamp.eq(now),
+                ))
+                .execute(&mut conn)? >
+                0 ||
+            diesel::update(
+                inbound_transactions::table
+                    .filter(inbound_transactions::tx_id.eq(tx_id))
+                    .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+            )
+            .set((
+                inbound_transactions::send_count.eq(inbound_transactions::send_count + 1),
+                inbound_transactions::last_send_timestamp.eq(now),
+            ))
+            .execute(&mut conn)? >
+                0;
+        if !updated {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+        Ok(())
+    }
+
+    fn update_mined_height(
+        &self,
+        tx_id: TxId,
+        mined_height: u64,
+        mined_in_block: BlockHash,
+        mined_timestamp: u64,
+        num_confirmations: u64,
+        is_confirmed: bool,
+        is_faux: bool,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let status = match (is_confirmed, is_faux) {
+            (true, true) => TransactionStatus::FauxConfirmed,
+            (true, false) => TransactionStatus::MinedConfirmed,
+            (false, true) => TransactionStatus::FauxUnconfirmed,
+            (false, false) => TransactionStatus::MinedUnconfirmed,
+        };
+        let timestamp = NaiveDateTime::from_timestamp_opt(mined_timestamp as i64, 0).ok_or_else(|| {
+            TransactionStorageError::UnexpectedResult(format!(
+                "Could not create timestamp mined_timestamp: {}",
+                mined_timestamp
+            ))
+        })?;
+
+        update_completed(
+            tx_id,
+            UpdateCompletedTransactionSql {
+                confirmations: Some(Some(num_confirmations as i64)),
+                status: Some(status as i32),
+                mined_height: Some(Some(mined_height as i64)),
+                mined_in_block: Some(Some(mined_in_block.to_vec())),
+                mined_timestamp: Some(timestamp),
+                ..Default::default()
+            },
+            &mut conn,
+        )
+    }
+
+    fn fetch_last_mined_transaction(&self) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        completed_transactions::table
+            .filter(completed_transactions::mined_in_block.is_not_null())
+            .filter(completed_transactions::mined_height.is_not_null())
+            .filter(completed_transactions::mined_height.gt(0))
+            .order_by(completed_transactions::mined_height.desc())
+            .first::<CompletedTransactionSql>(&mut conn)
+            .optional()?
+            .map(|tx| CompletedTransaction::try_from(tx, &cipher))
+            .transpose()
+    }
+
+    fn fetch_unconfirmed_transactions_info(&self) -> Result<Vec<UnconfirmedTransactionInfo>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        completed_transactions::table
+            .select((
+                completed_transactions::tx_id,
+                completed_transactions::status,
+                completed_transactions::transaction_signature_nonce,
+                completed_transactions::transaction_signature_key,
+                completed_transactions::coinbase_block_height,
+            ))
+            .filter(
+                completed_transactions::status
+                    .ne(TransactionStatus::Imported as i32)
+                    .and(completed_transactions::status.ne(TransactionStatus::FauxUnconfirmed as i32))
+                    .and(completed_transactions::status.ne(TransactionStatus::FauxConfirmed as i32))
+                    .and(completed_transactions::status.ne(TransactionStatus::ImportedHistory as i32))
+                    .and(
+                        completed_transactions::mined_height
+                            .is_null()
+                            .or(completed_transactions::status.eq(TransactionStatus::MinedUnconfirmed as i32)),
+                    ),
+            )
+            .filter(completed_transactions::cancelled.is_null())
+            .order_by(completed_transactions::tx_id)
+            .load::<UnconfirmedTransactionInfoSql>(&mut conn)?
+            .into_iter()
+            .map(UnconfirmedTransactionInfo::try_from)
+            .collect()
+    }
+
+    fn get_transactions_to_be_broadcast(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        completed_transactions::table
+            .filter(
+                completed_transactions::status
+                    .eq(TransactionStatus::Completed as i32)
+                    .or(completed_transactions::status.eq(TransactionStatus::Broadcast as i32)),
+            )
+            .filter(
+                completed_transactions::coinbase_block_height
+                    .is_null()
+                    .or(completed_transactions::coinbase_block_height.eq(0)),
+            )
+            .filter(completed_transactions::cancelled.is_null())
+            .order_by(completed_transactions::tx_id)
+            .load::<CompletedTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(|tx| CompletedTransaction::try_from(tx, &cipher))
+            .collect()
+    }
+
+    fn mark_all_transactions_as_unvalidated(&self) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(completed_transactions::table)
+            .set((
+                completed_transactions::cancelled.eq::<Option<i32>>(None),
+                completed_transactions::mined_height.eq::<Option<i64>>(None),
+                completed_transactions::mined_in_block.eq::<Option<Vec<u8>>>(None),
+            ))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn set_transaction_as_unmined(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        set_completed_as_unmined(tx_id, &mut conn)
+    }
+
+    fn set_transactions_mined_above_height_as_unmined(
+        &self,
+        height: u64,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let tx_ids = completed_transactions::table
+                .filter(completed_transactions::mined_height.gt(height as i64))
+                .select(completed_transactions::tx_id)
+                .load::<i64>(conn)?
+                .into_iter()
+                .map(|tx_id| TxId::from(tx_id as u64))
+                .collect::<Vec<_>>();
+            for tx_id in &tx_ids {
+                set_completed_as_unmined(*tx_id, conn)?;
+            }
+            Ok(tx_ids)
+        })
+    }
+
+    fn get_pending_inbound_transaction_sender_info(
+        &self,
+    ) -> Result<Vec<InboundTransactionSenderInfo>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        inbound_transactions::table
+            .select((inbound_transactions::tx_id, inbound_transactions::source_address))
+            .filter(inbound_transactions::cancelled.eq(i32::from(false)))
+            .load::<InboundTransactionSenderInfoSql>(&mut conn)?
+            .into_iter()
+            .map(InboundTransactionSenderInfo::try_from)
+            .collect()
+    }
+
+    fn fetch_imported_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        index_completed(false, Some(TransactionStatus::Imported), &mut conn)?
+            .into_iter()
+            .map(|ct| CompletedTransaction::try_from(ct, &cipher))
+            .collect()
+    }
+
+    fn fetch_unconfirmed_faux_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        index_completed(false, Some(TransactionStatus::FauxUnconfirmed), &mut conn)?
+            .into_iter()
+            .map(|ct| CompletedTransaction::try_from(ct, &cipher))
+            .collect()
+    }
+
+    fn fetch_confirmed_faux_transactions_from_height(
+        &self,
+        height: u64,
+    ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        completed_transactions::table
+            .filter(completed_transactions::cancelled.is_null())
+            .filter(completed_transactions::status.eq(TransactionStatus::FauxConfirmed as i32))
+            .filter(completed_transactions::mined_height.ge(height as i64))
+            .load::<CompletedTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(|ct| CompletedTransaction::try_from(ct, &cipher))
+            .collect()
+    }
+
+    fn abandon_coinbase_transaction(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(completed_transactions::cancelled.is_null())
+                .filter(completed_transactions::coinbase_block_height.is_not_null()),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(TxCancellationReason::AbandonedCoinbase as i32)),
+            ..Default::default()
+        })
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)
+        .map_err(not_found_as(DbKey::CompletedTransaction(tx_id)))?;
+        Ok(())
+    }
+
+    fn queue_outbound_message(&self, message: QueuedOutboundMessage) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        diesel::insert_into(outbound_message_queue::table)
+            .values(OutboundMessageSql::try_from(message, &cipher)?)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_outbound_message(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+    ) -> Result<Option<QueuedOutboundMessage>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        outbound_message_queue::table
+            .filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64))
+            .filter(outbound_message_queue::message_type.eq(i32::from(message_type)))
+            .first::<OutboundMessageSql>(&mut conn)
+            .optional()?
+            .map(|m| QueuedOutboundMessage::try_from(m, &cipher))
+            .transpose()
+    }
+
+    fn fetch_pending_outbound_messages(&self) -> Result<Vec<QueuedOutboundMessage>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        outbound_message_queue::table
+            .filter(outbound_message_queue::status.eq(i32::from(OutboundMessageStatus::Pending)))
+            .order_by(outbound_message_queue::created_at.asc())
+            .load::<OutboundMessageSql>(&mut conn)?
+            .into_iter()
+            .map(|m| QueuedOutboundMessage::try_from(m, &cipher))
+            .collect()
+    }
+
+    fn record_outbound_message_attempt(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        status: OutboundMessageStatus,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            outbound_message_queue::table
+                .filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_message_queue::message_type.eq(i32::from(message_type))),
+        )
+        .set((
+            outbound_message_queue::status.eq(i32::from(status)),
+            outbound_message_queue::attempts.eq(outbound_message_queue::attempts + 1),
+            outbound_message_queue::last_attempt_at.eq(Some(Utc::now().naive_utc())),
+        ))
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn remove_outbound_messages(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(outbound_message_queue::table.filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn purge_outbound_messages(&self, expired_before: NaiveDateTime) -> Result<usize, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let finished = diesel::delete(
+                outbound_message_queue::table
+                    .filter(outbound_message_queue::status.ne(i32::from(OutboundMessageStatus::Pending)))
+                    .filter(diesel::dsl::not(
+                        outbound_message_queue::tx_id
+                            .eq_any(outbound_transactions::table.select(outbound_transactions::tx_id)),
+                    ))
+                    .filter(diesel::dsl::not(
+                        outbound_message_queue::tx_id
+                            .eq_any(inbound_transactions::table.select(inbound_transactions::tx_id)),
+                    )),
+            )
+            .execute(conn)?;
+            let expired = diesel::delete(
+                outbound_message_queue::table.filter(outbound_message_queue::created_at.lt(expired_before)),
+            )
+            .execute(conn)?;
+            Ok(finished + expired)
+        })
+    }
+
+    fn set_counterparty_alias(&self, tx_id: TxId, alias: String) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(transaction_counterparty_aliases::table)
+            .values(CounterpartyAliasSql {
+                tx_id: tx_id.as_u64() as i64,
+                alias,
+            })
+            .on_conflict(transaction_counterparty_aliases::tx_id)
+            .do_update()
+            .set(transaction_counterparty_aliases::alias.eq(excluded(transaction_counterparty_aliases::alias)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_counterparty_aliases(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, String>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        Ok(transaction_counterparty_aliases::table
+            .filter(transaction_counterparty_aliases::tx_id.eq_any(tx_ids.iter().map(|id| id.as_u64() as i64)))
+            .load::<CounterpartyAliasSql>(&mut conn)?
+            .into_iter()
+            .map(|a| (TxId::from(a.tx_id as u64), a.alias))
+            .collect())
+    }
+
+    fn set_transaction_memo(&self, tx_id: TxId, memo: String) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let memo = TransactionMemoSql {
+            tx_id: tx_id.as_u64() as i64,
+            memo: memo.into_bytes(),
+        }
+        .encrypt(&cipher)
+        .map_err(TransactionStorageError::AeadError)?;
+
+        diesel::insert_into(transaction_memos::table)
+            .values(memo)
+            .on_conflict(transaction_memos::tx_id)
+            .do_update()
+            .set(transaction_memos::memo.eq(excluded(transaction_memos::memo)))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_transaction_memo(&self, tx_id: TxId) -> Result<Option<String>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        transaction_memos::table
+            .filter(transaction_memos::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<TransactionMemoSql>(&mut conn)
+            .optional()?
+            .map(|m| {
+                let m = m.decrypt(&cipher).map_err(TransactionStorageError::AeadError)?;
+                String::from_utf8(m.memo).map_err(|e| TransactionStorageError::UnexpectedResult(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn insert_offline_transaction(&self, transaction: OfflineTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        diesel::insert_into(offline_transactions::table)
+            .values(OfflineTransactionSql::try_from(transaction, &cipher)?)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_offline_transaction(&self, tx_id: TxId) -> Result<Option<OfflineTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        offline_transactions::table
+            .filter(offline_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<OfflineTransactionSql>(&mut conn)
+            .optional()?
+            .map(|t| OfflineTransaction::try_from(t, &cipher))
+            .transpose()
+    }
+
+    fn fetch_offline_transactions(
+        &self,
+        status: Option<OfflineTransactionStatus>,
+    ) -> Result<Vec<OfflineTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        let mut query = offline_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(offline_transactions::status.eq(i32::from(status)));
+        }
+        query
+            .order_by(offline_transactions::created_at.asc())
+            .load::<OfflineTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(|t| OfflineTransaction::try_from(t, &cipher))
+            .collect()
+    }
+
+    fn update_offline_transaction_status(
+        &self,
+        tx_id: TxId,
+        from: OfflineTransactionStatus,
+        to: OfflineTransactionStatus,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            offline_transactions::table
+                .filter(offline_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(offline_transactions::status.eq(i32::from(from))),
+        )
+        .set(offline_transactions::status.eq(i32::from(to)))
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn insert_burn_record(&self, record: BurnRecord) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        diesel::insert_into(burn_transactions::table)
+            .values(BurnTransactionSql::try_from(record, &cipher)?)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_burn_record(&self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        burn_transactions::table
+            .filter(burn_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<BurnTransactionSql>(&mut conn)
+            .optional()?
+            .map(|b| BurnRecord::try_from(b, &cipher))
+            .transpose()
+    }
+
+    fn fetch_burn_records(&self, status: Option<BurnStatus>) -> Result<Vec<BurnRecord>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        let mut query = burn_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(burn_transactions::status.eq(i32::from(status)));
+        }
+        query
+            .order_by(burn_transactions::burned_at.asc())
+            .load::<BurnTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(|b| BurnRecord::try_from(b, &cipher))
+            .collect()
+    }
+
+    fn update_burn_record_status(
+        &self,
+        tx_id: TxId,
+        status: BurnStatus,
+        claimed_at: Option<NaiveDateTime>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(burn_transactions::table.filter(burn_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set((
+                burn_transactions::status.eq(i32::from(status)),
+                burn_transactions::claimed_at.eq(claimed_at),
+            ))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn add_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tags = tags
+            .into_iter()
+            .map(|tag| TransactionTagSql {
+                tx_id: tx_id.as_u64() as i64,
+                tag,
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_into(transaction_tags::table)
+            .values(&tags)
+            .on_conflict_do_nothing()
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn remove_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(
+            transaction_tags::table
+                .filter(transaction_tags::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(transaction_tags::tag.eq_any(tags)),
+        )
+        .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_transaction_tags(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut tags = HashMap::<TxId, Vec<String>>::new();
+        for tag in transaction_tags::table
+            .filter(transaction_tags::tx_id.eq_any(tx_ids.iter().map(|id| id.as_u64() as i64)))
+            .order((transaction_tags::tx_id.asc(), transaction_tags::tag.asc()))
+            .load::<TransactionTagSql>(&mut conn)?
+        {
+            tags.entry(TxId::from(tag.tx_id as u64)).or_default().push(tag.tag);
+        }
+        Ok(tags)
+    }
+
+    fn fetch_tx_ids_by_tag(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tx_ids = transaction_tags::table
+            .filter(transaction_tags::tag.eq(tag))
+            .select(transaction_tags::tx_id)
+            .order(transaction_tags::tx_id.asc())
+            .load::<i64>(&mut conn)?;
+        Ok(tx_ids.into_iter().map(|id| TxId::from(id as u64)).collect())
+    }
+
+    fn search_transactions(&self, query: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+
+        // ILIKE, as Sqlite's LIKE is case insensitive for ASCII
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        let mut tx_ids = BTreeSet::new();
+        tx_ids.extend(
+            transaction_tags::table
+                .filter(transaction_tags::tag.ilike(&pattern).escape('\\'))
+                .select(transaction_tags::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            completed_transactions::table
+                .filter(completed_transactions::message.ilike(&pattern).escape('\\'))
+                .select(completed_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            inbound_transactions::table
+                .filter(inbound_transactions::message.ilike(&pattern).escape('\\'))
+                .select(inbound_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            outbound_transactions::table
+                .filter(outbound_transactions::message.ilike(&pattern).escape('\\'))
+                .select(outbound_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        Ok(tx_ids.into_iter().map(|id| TxId::from(id as u64)).collect())
+    }
+
+    fn insert_batched_payments(&self, payments: Vec<BatchedPayment>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let payments = payments.into_iter().map(NewBatchedPaymentSql::from).collect::<Vec<_>>();
+        diesel::insert_into(batched_payments::table)
+            .values(&payments)
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_batched_payments(&self, tx_id: TxId) -> Result<Vec<BatchedPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        batched_payments::table
+            .filter(batched_payments::tx_id.eq(tx_id.as_u64() as i64))
+            .order(batched_payments::id.asc())
+            .load::<BatchedPaymentSql>(&mut conn)?
+            .into_iter()
+            .map(BatchedPayment::try_from)
+            .collect()
+    }
+
+    fn insert_scheduled_transaction(&self, scheduled: ScheduledTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(scheduled_transactions::table)
+            .values(ScheduledTransactionSql::from(scheduled))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_scheduled_transactions(
+        &self,
+        status: Option<ScheduledTransactionStatus>,
+    ) -> Result<Vec<ScheduledTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = scheduled_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(scheduled_transactions::status.eq(i32::from(status)));
+        }
+        query
+            .order_by(scheduled_transactions::created_at.asc())
+            .load::<ScheduledTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(ScheduledTransaction::try_from)
+            .collect()
+    }
+
+    fn update_scheduled_transaction_status(
+        &self,
+        id: u64,
+        from: ScheduledTransactionStatus,
+        to: ScheduledTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            scheduled_transactions::table
+                .filter(scheduled_transactions::id.eq(id as i64))
+                .filter(scheduled_transactions::status.eq(i32::from(from))),
+        )
+        .set((
+            scheduled_transactions::status.eq(i32::from(to)),
+            scheduled_transactions::tx_id.eq(tx_id.map(|id| id.as_u64() as i64)),
+            scheduled_transactions::failure_reason.eq(failure_reason),
+        ))
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn insert_queued_transaction(&self, queued: QueuedTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(queued_transactions::table)
+            .values(QueuedTransactionSql::from(queued))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_queued_transactions(
+        &self,
+        status: Option<QueuedTransactionStatus>,
+    ) -> Result<Vec<QueuedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = queued_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(queued_transactions::status.eq(i32::from(status)));
+        }
+        query
+            .order_by(queued_transactions::queued_at.asc())
+            .load::<QueuedTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(QueuedTransaction::try_from)
+            .collect()
+    }
+
+    fn update_queued_transaction_status(
+        &self,
+        id: u64,
+        from: QueuedTransactionStatus,
+        to: QueuedTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            queued_transactions::table
+                .filter(queued_transactions::id.eq(id as i64))
+                .filter(queued_transactions::status.eq(i32::from(from))),
+        )
+        .set((
+            queued_transactions::status.eq(i32::from(to)),
+            queued_transactions::tx_id.eq(tx_id.map(|id| id.as_u64() as i64)),
+            queued_transactions::failure_reason.eq(failure_reason),
+        ))
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn supersede_completed_transaction(
+        &self,
+        replacement: CompletedTransaction,
+        batched_payments: Vec<BatchedPayment>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let tx_id = replacement.tx_id;
+        let replacement_sql = CompletedTransactionSql::try_from(replacement, &cipher)?;
+        let payments = batched_payments
+            .into_iter()
+            .map(NewBatchedPaymentSql::from)
+            .collect::<Vec<_>>();
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let existing = find_completed(tx_id, Some(false), conn)?.ok_or(TransactionStorageError::ValuesNotFound)?;
+            let status = TransactionStatus::try_from(existing.status)?;
+            if !(status == TransactionStatus::Completed || status == TransactionStatus::Broadcast) {
+                return Err(TransactionStorageError::ValuesNotFound);
+            }
+            supersede_current_version(existing, &cipher, conn)?;
+            diesel::insert_into(completed_transactions::table)
+                .values(replacement_sql)
+                .execute(conn)?;
+            diesel::insert_into(batched_payments::table)
+                .values(&payments)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn fetch_superseded_transactions(&self) -> Result<Vec<SupersededTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        superseded_transactions::table
+            .order(superseded_transactions::superseded_at.asc())
+            .load::<SupersededTransactionSql>(&mut conn)?
+            .into_iter()
+            .map(|version| SupersededTransaction::try_from(version, &cipher))
+            .collect()
+    }
+
+    fn reinstate_superseded_transaction(&self, id: u64) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let version_sql = superseded_transactions::table
+                .filter(superseded_transactions::id.eq(id as i64))
+                .first::<SupersededTransactionSql>(conn)
+                .optional()?
+                .ok_or(TransactionStorageError::ValuesNotFound)?;
+            let version = SupersededTransaction::try_from(version_sql, &cipher)?;
+            let existing =
+                find_completed(version.tx_id, Some(false), conn)?.ok_or(TransactionStorageError::ValuesNotFound)?;
+            let mut reinstated = CompletedTransaction::try_from(existing.clone(), &cipher)?;
+            supersede_current_version(existing, &cipher, conn)?;
+            diesel::delete(superseded_transactions::table.filter(superseded_transactions::id.eq(id as i64)))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+
+            reinstated.fee = version.fee;
+            reinstated.transaction_signature = version.excess_sig().cloned().unwrap_or_default();
+            reinstated.transaction = version.transaction;
+            diesel::insert_into(completed_transactions::table)
+                .values(CompletedTransactionSql::try_from(reinstated, &cipher)?)
+                .execute(conn)?;
+            diesel::insert_into(batched_payments::table)
+                .values(
+                    version
+                        .batched_payments
+                        .into_iter()
+                        .map(NewBatchedPaymentSql::from)
+                        .collect::<Vec<_>>(),
+                )
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn upsert_atomic_swap(&self, swap: AtomicSwap) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let swap = AtomicSwapSql::try_from(swap, &cipher)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            diesel::delete(atomic_swaps::table.filter(atomic_swaps::swap_id.eq(swap.swap_id))).execute(conn)?;
+            diesel::insert_into(atomic_swaps::table).values(swap).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn fetch_atomic_swap(&self, swap_id: u64) -> Result<Option<AtomicSwap>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        atomic_swaps::table
+            .filter(atomic_swaps::swap_id.eq(swap_id as i64))
+            .first::<AtomicSwapSql>(&mut conn)
+            .optional()?
+            .map(|swap| AtomicSwap::try_from(swap, &cipher))
+            .transpose()
+    }
+
+    fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        atomic_swaps::table
+            .order_by(atomic_swaps::created_at.asc())
+            .load::<AtomicSwapSql>(&mut conn)?
+            .into_iter()
+            .map(|swap| AtomicSwap::try_from(swap, &cipher))
+            .collect()
+    }
+
+    fn upsert_multisig_session(&self, session: MultisigSession) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let session = MultisigSessionSql::try_from(session)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            diesel::delete(multisig_sessions::table.filter(multisig_sessions::session_id.eq(session.session_id)))
+                .execute(conn)?;
+            diesel::insert_into(multisig_sessions::table)
+                .values(session)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn fetch_multisig_session(&self, session_id: u64) -> Result<Option<MultisigSession>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        multisig_sessions::table
+            .filter(multisig_sessions::session_id.eq(session_id as i64))
+            .first::<MultisigSessionSql>(&mut conn)
+            .optional()?
+            .map(MultisigSession::try_from)
+            .transpose()
+    }
+
+    fn fetch_multisig_sessions(&self) -> Result<Vec<MultisigSession>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        multisig_sessions::table
+            .order_by(multisig_sessions::created_at.asc())
+            .load::<MultisigSessionSql>(&mut conn)?
+            .into_iter()
+            .map(MultisigSession::try_from)
+            .collect()
+    }
+
+    fn upsert_multisig_signing(&self, signing: MultisigSigning) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let signing = MultisigSigningSql::try_from(signing)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            diesel::delete(multisig_signings::table.filter(multisig_signings::signing_id.eq(signing.signing_id)))
+                .execute(conn)?;
+            diesel::insert_into(multisig_signings::table)
+                .values(signing)
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn fetch_multisig_signing(&self, signing_id: u64) -> Result<Option<MultisigSigning>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        multisig_signings::table
+            .filter(multisig_signings::signing_id.eq(signing_id as i64))
+            .first::<MultisigSigningSql>(&mut conn)
+            .optional()?
+            .map(MultisigSigning::try_from)
+            .transpose()
+    }
+
+    fn fetch_multisig_signings(
+        &self,
+        session_id: Option<u64>,
+    ) -> Result<Vec<MultisigSigning>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = multisig_signings::table.into_boxed();
+        if let Some(session_id) = session_id {
+            query = query.filter(multisig_signings::session_id.eq(session_id as i64));
+        }
+        query
+            .order_by(multisig_signings::created_at.asc())
+            .load::<MultisigSigningSql>(&mut conn)?
+            .into_iter()
+            .map(MultisigSigning::try_from)
+            .collect()
+    }
+
+    fn insert_recurring_payment(&self, payment: RecurringPayment) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(recurring_payments::table)
+            .values(RecurringPaymentSql::from(payment))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_recurring_payments(
+        &self,
+        status: Option<RecurringPaymentStatus>,
+    ) -> Result<Vec<RecurringPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut query = recurring_payments::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(recurring_payments::status.eq(i32::from(status)));
+        }
+        query
+            .order_by(recurring_payments::created_at.asc())
+            .load::<RecurringPaymentSql>(&mut conn)?
+            .into_iter()
+            .map(RecurringPayment::try_from)
+            .collect()
+    }
+
+    fn update_recurring_payment_status(
+        &self,
+        id: u64,
+        from: &[RecurringPaymentStatus],
+        to: RecurringPaymentStatus,
+        next_payment_at: Option<NaiveDateTime>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let query = recurring_payments::table
+            .filter(recurring_payments::id.eq(id as i64))
+            .filter(recurring_payments::status.eq_any(from.iter().map(|s| i32::from(*s))));
+        match next_payment_at {
+            Some(next_payment_at) => diesel::update(query)
+                .set((
+                    recurring_payments::status.eq(i32::from(to)),
+                    recurring_payments::next_payment_at.eq(next_payment_at),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(&mut conn),
+            None => diesel::update(query)
+                .set((
+                    recurring_payments::status.eq(i32::from(to)),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(&mut conn),
+        }
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn advance_recurring_payment(
+        &self,
+        id: u64,
+        due_at: NaiveDateTime,
+        next_payment_at: NaiveDateTime,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::update(
+            recurring_payments::table
+                .filter(recurring_payments::id.eq(id as i64))
+                .filter(recurring_payments::status.eq(i32::from(RecurringPaymentStatus::Active)))
+                .filter(recurring_payments::next_payment_at.eq(due_at)),
+        )
+        .set(recurring_payments::next_payment_at.eq(next_payment_at))
+        .execute(&mut conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn record_recurring_payment_result(
+        &self,
+        id: u64,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let query = recurring_payments::table.filter(recurring_payments::id.eq(id as i64));
+        match tx_id {
+            Some(tx_id) => diesel::update(query)
+                .set((
+                    recurring_payments::payments_made.eq(recurring_payments::payments_made + 1),
+                    recurring_payments::last_tx_id.eq(tx_id.as_u64() as i64),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(&mut conn),
+            None => diesel::update(query)
+                .set(recurring_payments::failure_reason.eq(failure_reason))
+                .execute(&mut conn),
+        }
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn journal_transaction_event(
+        &self,
+        event: &TransactionEvent,
+        max_events: usize,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let event = NewTransactionEventSql::try_from(event)?;
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let latest_id = diesel::insert_into(transaction_events::table)
+                .values(event)
+                .returning(transaction_events::id)
+                .get_result::<i64>(conn)?;
+            diesel::delete(transaction_events::table.filter(transaction_events::id.le(latest_id - max_events as i64)))
+                .execute(conn)?;
+            Ok(())
+        })
+    }
+
+    fn fetch_transaction_events_since(
+        &self,
+        cursor: u64,
+    ) -> Result<Vec<JournaledTransactionEvent>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        transaction_events::table
+            .filter(transaction_events::id.gt(cursor as i64))
+            .order(transaction_events::id.asc())
+            .load::<TransactionEventSql>(&mut conn)?
+            .into_iter()
+            .map(JournaledTransactionEvent::try_from)
+            .collect()
+    }
+
+    fn insert_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::insert_into(send_templates::table)
+            .values(SendTemplateSql::from(template))
+            .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn update_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let template = SendTemplateSql::from(template);
+        diesel::update(send_templates::table.filter(send_templates::id.eq(template.id)))
+            .set((
+                send_templates::name.eq(&template.name),
+                send_templates::destination_address.eq(&template.destination_address),
+                send_templates::default_amount.eq(template.default_amount),
+                send_templates::message.eq(&template.message),
+                send_templates::fee_priority.eq(template.fee_priority),
+                send_templates::one_sided.eq(template.one_sided),
+                send_templates::updated_at.eq(template.updated_at),
+            ))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn fetch_send_template(&self, id: u64) -> Result<Option<SendTemplate>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        send_templates::table
+            .filter(send_templates::id.eq(id as i64))
+            .first::<SendTemplateSql>(&mut conn)
+            .optional()?
+            .map(SendTemplate::try_from)
+            .transpose()
+    }
+
+    fn fetch_send_templates(&self) -> Result<Vec<SendTemplate>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        send_templates::table
+            .order_by(send_templates::name.asc())
+            .load::<SendTemplateSql>(&mut conn)?
+            .into_iter()
+            .map(SendTemplate::try_from)
+            .collect()
+    }
+
+    fn delete_send_template(&self, id: u64) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(send_templates::table.filter(send_templates::id.eq(id as i64)))
+            .execute(&mut conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    fn insert_imported_history(
+        &self,
+        transactions: Vec<CompletedTransaction>,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let mut inserted = Vec::with_capacity(transactions.len());
+            for tx in transactions {
+                let tx_id = tx.tx_id;
+                let row = CompletedTransactionSql::try_from(tx, &cipher)?;
+                let num_inserted = diesel::insert_into(completed_transactions::table)
+                    .values(row)
+                    .on_conflict_do_nothing()
+                    .execute(conn)?;
+                if num_inserted == 0 {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Skipping imported history for {} that is already in the database", tx_id
+                    );
+                    continue;
+                }
+                inserted.push(tx_id);
+            }
+            Ok(inserted)
+        })
+    }
+}
+
+/// Maps a `NotFound` from an update or delete that was expected to affect a row to `ValueNotFound(key)`
+fn not_found_as(key: DbKey) -> impl FnOnce(DieselError) -> TransactionStorageError {
+    move |e| match e {
+        DieselError::NotFound => TransactionStorageError::ValueNotFound(key),
+        e => TransactionStorageError::DieselError(e),
+    }
+}
+
+fn find_inbound(
+    tx_id: TxId,
+    cancelled: Option<bool>,
+    conn: &mut PgConnection,
+) -> Result<Option<InboundTransactionSql>, TransactionStorageError> {
+    let mut query = inbound_transactions::table
+        .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+        .into_boxed();
+    if let Some(cancelled) = cancelled {
+        query = query.filter(inbound_transactions::cancelled.eq(i32::from(cancelled)));
+    }
+    Ok(query.first::<InboundTransactionSql>(conn).optional()?)
+}
+
+fn find_outbound(
+    tx_id: TxId,
+    cancelled: Option<bool>,
+    conn: &mut PgConnection,
+) -> Result<Option<OutboundTransactionSql>, TransactionStorageError> {
+    let mut query = outbound_transactions::table
+        .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+        .into_boxed();
+    if let Some(cancelled) = cancelled {
+        query = query.filter(outbound_transactions::cancelled.eq(i32::from(cancelled)));
+    }
+    Ok(query.first::<OutboundTransactionSql>(conn).optional()?)
+}
+
+fn find_completed(
+    tx_id: TxId,
+    cancelled: Option<bool>,
+    conn: &mut PgConnection,
+) -> Result<Option<CompletedTransactionSql>, TransactionStorageError> {
+    let mut query = completed_transactions::table
+        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+        .into_boxed();
+    match cancelled {
+        Some(true) => query = query.filter(completed_transactions::cancelled.is_not_null()),
+        Some(false) => query = query.filter(completed_transactions::cancelled.is_null()),
+        None => {},
+    }
+    Ok(query.first::<CompletedTransactionSql>(conn).optional()?)
+}
+
+/// Moves the current version of a completed transaction and its batched payments to the superseded transactions
+fn supersede_current_version(
+    existing: CompletedTransactionSql,
+    cipher: &XChaCha20Poly1305,
+    conn: &mut PgConnection,
+) -> Result<(), TransactionStorageError> {
+    let tx_id = existing.tx_id;
+    let current = CompletedTransaction::try_from(existing, cipher)?;
+    let current_payments = batched_payments::table
+        .filter(batched_payments::tx_id.eq(tx_id))
+        .order(batched_payments::id.asc())
+        .load::<BatchedPaymentSql>(conn)?
+        .into_iter()
+        .map(BatchedPayment::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    diesel::insert_into(superseded_transactions::table)
+        .values(SupersededTransactionSql::try_from(&current, &current_payments, cipher)?)
+        .execute(conn)?;
+
+    diesel::delete(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id))).execute(conn)?;
+    diesel::delete(batched_payments::table.filter(batched_payments::tx_id.eq(tx_id))).execute(conn)?;
+    Ok(())
+}
+
+fn index_inbound(
+    cancelled: bool,
+    conn: &mut PgConnection,
+) -> Result<Vec<InboundTransactionSql>, TransactionStorageError> {
+    Ok(inbound_transactions::table
+        .filter(inbound_transactions::cancelled.eq(i32::from(cancelled)))
+        .load::<InboundTransactionSql>(conn)?)
+}
+
+fn index_outbound(
+    cancelled: bool,
+    conn: &mut PgConnection,
+) -> Result<Vec<OutboundTransactionSql>, TransactionStorageError> {
+    Ok(outbound_transactions::table
+        .filter(outbound_transactions::cancelled.eq(i32::from(cancelled)))
+        .load::<OutboundTransactionSql>(conn)?)
+}
+
+fn index_completed(
+    cancelled: bool,
+    status: Option<TransactionStatus>,
+    conn: &mut PgConnection,
+) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+    let mut query = completed_transactions::table.into_boxed();
+    query = if cancelled {
+        query.filter(completed_transactions::cancelled.is_not_null())
+    } else {
+        query.filter(completed_transactions::cancelled.is_null())
+    };
+    if let Some(status) = status {
+        query = query.filter(completed_transactions::status.eq(status as i32));
+    }
+    Ok(query.load::<CompletedTransactionSql>(conn)?)
+}
+
+fn update_completed(
+    tx_id: TxId,
+    update: UpdateCompletedTransactionSql,
+    conn: &mut PgConnection,
+) -> Result<(), TransactionStorageError> {
+    diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+        .set(update)
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)
+        .map_err(not_found_as(DbKey::CompletedTransaction(tx_id)))?;
+    Ok(())
+}
+
+/// Re-encrypt every encrypted column of the given table, moving it from the `old` to the `new` cipher. The PostgreSQL
+/// counterpart of [reencrypt_table](super::sqlite_db::reencrypt_table). Returns the number of rows that were updated.
+pub(crate) fn reencrypt_table(
+    table: TransactionEncryptedTable,
+    conn: &mut PgConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, TransactionStorageError> {
+    match table {
+        TransactionEncryptedTable::InboundTransactions => {
+            let rows = inbound_transactions::table.load::<InboundTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(row.tx_id)))
+                    .set(inbound_transactions::receiver_protocol.eq(&row.receiver_protocol))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OutboundTransactions => {
+            let rows = outbound_transactions::table.load::<OutboundTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(row.tx_id)))
+                    .set(outbound_transactions::sender_protocol.eq(&row.sender_protocol))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::CompletedTransactions => {
+            let rows = completed_transactions::table.load::<CompletedTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(row.tx_id)))
+                    .set(completed_transactions::transaction_protocol.eq(&row.transaction_protocol))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::TransactionMemos => {
+            let rows = transaction_memos::table.load::<TransactionMemoSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(transaction_memos::table.filter(transaction_memos::tx_id.eq(row.tx_id)))
+                    .set(transaction_memos::memo.eq(&row.memo))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::AtomicSwaps => {
+            let rows = atomic_swaps::table.load::<AtomicSwapSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(atomic_swaps::table.filter(atomic_swaps::swap_id.eq(row.swap_id)))
+                    .set(atomic_swaps::pre_image.eq(&row.pre_image))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OutboundMessages => {
+            let rows = outbound_message_queue::table.load::<OutboundMessageSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(
+                    outbound_message_queue::table
+                        .filter(outbound_message_queue::tx_id.eq(row.tx_id))
+                        .filter(outbound_message_queue::message_type.eq(row.message_type)),
+                )
+                .set(outbound_message_queue::payload.eq(&row.payload))
+                .execute(conn)
+                .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OfflineTransactions => {
+            let rows = offline_transactions::table.load::<OfflineTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(offline_transactions::table.filter(offline_transactions::tx_id.eq(row.tx_id)))
+                    .set(offline_transactions::unsigned_transaction.eq(&row.unsigned_transaction))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::BurnTransactions => {
+            let rows = burn_transactions::table.load::<BurnTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(burn_transactions::table.filter(burn_transactions::tx_id.eq(row.tx_id)))
+                    .set(burn_transactions::ownership_proof.eq(&row.ownership_proof))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::SupersededTransactions => {
+            let rows = superseded_transactions::table.load::<SupersededTransactionSql>(conn)?;
+            for row in &rows {
+                let row = reencrypt(row.clone(), old, new)?;
+                diesel::update(superseded_transactions::table.filter(superseded_transactions::id.eq(row.id)))
+                    .set(superseded_transactions::transaction_protocol.eq(&row.transaction_protocol))
+                    .execute(conn)
+                    .num_rows_affected_or_not_found(1)?;
+            }
+            Ok(rows.len())
+        },
+    }
+}
+
+/// Clears the mined fields of a completed transaction, moving it back to the status it had before it was mined
+fn set_completed_as_unmined(tx_id: TxId, conn: &mut PgConnection) -> Result<(), TransactionStorageError> {
+    let (status, coinbase_block_height) = completed_transactions::table
+        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+        .select((
+            completed_transactions::status,
+            completed_transactions::coinbase_block_height,
+        ))
+        .first::<(i32, Option<i64>)>(conn)
+        .optional()?
+        .ok_or(TransactionStorageError::ValueNotFound(DbKey::CompletedTransaction(
+            tx_id,
+        )))?;
+    let status = if coinbase_block_height.is_some() {
+        TransactionStatus::Coinbase
+    } else if status == TransactionStatus::FauxConfirmed as i32 {
+        TransactionStatus::FauxUnconfirmed
+    } else if status == TransactionStatus::Broadcast as i32 {
+        TransactionStatus::Broadcast
+    } else {
+        TransactionStatus::Completed
+    };
+
+    update_completed(
+        tx_id,
+        UpdateCompletedTransactionSql {
+            status: Some(status as i32),
+            mined_in_block: Some(None),
+            mined_height: Some(None),
+            confirmations: Some(None),
+            cancelled: Some(None),
+            ..Default::default()
+        },
+        conn,
+    )
+}
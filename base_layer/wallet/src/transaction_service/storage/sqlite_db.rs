@@ -21,17 +21,21 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     convert::{TryFrom, TryInto},
+    str::FromStr,
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use chacha20poly1305::XChaCha20Poly1305;
 use chrono::{NaiveDateTime, Utc};
 use diesel::{prelude::*, result::Error as DieselError, SqliteConnection};
 use log::*;
+use rand::{rngs::OsRng, RngCore};
 use tari_common_sqlite::{sqlite_connection_pool::PooledDbConnection, util::diesel_ext::ExpectedRowsExtension};
 use tari_common_types::{
+    burnt_proof::BurntProof,
     encryption::{decrypt_bytes_integral_nonce, encrypt_bytes_integral_nonce, Encryptable},
     tari_address::TariAddress,
     transaction::{
@@ -41,25 +45,73 @@ use tari_common_types::{
         TransactionStatus,
         TxId,
     },
-    types::{BlockHash, PrivateKey, PublicKey, Signature},
+    types::{BlockHash, BulletRangeProof, Commitment, HashOutput, PrivateKey, PublicKey, Signature},
 };
-use tari_core::transactions::tari_amount::MicroMinotari;
+use tari_core::transactions::{key_manager::TariKeyId, tari_amount::MicroMinotari};
+use tari_crypto::ristretto::RistrettoComSig;
 use tari_utilities::{ByteArray, Hidden};
 use thiserror::Error;
 use tokio::time::Instant;
 use zeroize::Zeroize;
 
 use crate::{
-    schema::{completed_transactions, inbound_transactions, outbound_transactions},
+    schema::{
+        atomic_swaps,
+        batched_payments,
+        burn_transactions,
+        completed_transactions,
+        inbound_transactions,
+        multisig_sessions,
+        multisig_signings,
+        offline_transactions,
+        outbound_message_queue,
+        outbound_transactions,
+        queued_transactions,
+        recurring_payments,
+        scheduled_transactions,
+        send_templates,
+        superseded_transactions,
+        transaction_counterparty_aliases,
+        transaction_events,
+        transaction_memos,
+        transaction_tags,
+    },
     storage::sqlite_utilities::wallet_db_connection::WalletDbConnection,
     transaction_service::{
         error::{TransactionKeyError, TransactionStorageError},
+        handle::{FeePriority, TransactionEvent},
+        offline_signing::UnsignedTransaction,
         storage::{
             database::{DbKey, DbKeyValuePair, DbValue, TransactionBackend, WriteOperation},
             models::{
+                AtomicSwap,
+                AtomicSwapRole,
+                AtomicSwapState,
+                BatchedPayment,
+                BurnRecord,
+                BurnStatus,
                 CompletedTransaction,
+                HeightOrTime,
                 InboundTransaction,
+                JournaledTransactionEvent,
+                MultisigSession,
+                MultisigSessionState,
+                MultisigSigning,
+                MultisigSigningState,
+                OfflineTransaction,
+                OfflineTransactionStatus,
+                OutboundMessageStatus,
+                OutboundMessageType,
                 OutboundTransaction,
+                QueuedOutboundMessage,
+                QueuedTransaction,
+                QueuedTransactionStatus,
+                RecurringPayment,
+                RecurringPaymentStatus,
+                ScheduledTransaction,
+                ScheduledTransactionStatus,
+                SendTemplate,
+                SupersededTransaction,
                 TxCancellationReason,
                 WalletTransaction,
             },
@@ -83,6 +135,18 @@ impl TransactionServiceSqliteDatabase {
         }
     }
 
+    /// Create a backend that shares its cipher with the wallet database, so that a rotation of the database
+    /// encryption key is picked up without reopening the backend
+    pub fn new_with_shared_cipher(
+        database_connection: WalletDbConnection,
+        cipher: Arc<RwLock<XChaCha20Poly1305>>,
+    ) -> Self {
+        Self {
+            database_connection,
+            cipher,
+        }
+    }
+
     fn insert(&self, kvp: DbKeyValuePair, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -195,7 +259,41 @@ impl TransactionServiceSqliteDatabase {
                     Err(e) => Err(e),
                 })
             },
-            DbKey::AnyTransaction(_) => Err(TransactionStorageError::OperationNotSupported),
+            DbKey::AnyTransaction(k) => conn.transaction::<_, _, _>(|conn| {
+                // Looked up in the same order as the fetch, so that the transaction removed is the one fetched
+                match OutboundTransactionSql::find(k, conn) {
+                    Ok(o) => {
+                        o.delete(conn)?;
+                        return Ok(Some(DbValue::WalletTransaction(Box::new(
+                            WalletTransaction::PendingOutbound(OutboundTransaction::try_from(o, &cipher)?),
+                        ))));
+                    },
+                    Err(TransactionStorageError::DieselError(DieselError::NotFound)) => (),
+                    Err(e) => return Err(e),
+                };
+                match InboundTransactionSql::find(k, conn) {
+                    Ok(i) => {
+                        i.delete(conn)?;
+                        return Ok(Some(DbValue::WalletTransaction(Box::new(
+                            WalletTransaction::PendingInbound(InboundTransaction::try_from(i, &cipher)?),
+                        ))));
+                    },
+                    Err(TransactionStorageError::DieselError(DieselError::NotFound)) => (),
+                    Err(e) => return Err(e),
+                };
+                match CompletedTransactionSql::find(k, conn) {
+                    Ok(c) => {
+                        c.delete(conn)?;
+                        Ok(Some(DbValue::WalletTransaction(Box::new(
+                            WalletTransaction::Completed(CompletedTransaction::try_from(c, &cipher)?),
+                        ))))
+                    },
+                    Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                        Err(TransactionStorageError::ValueNotFound(DbKey::AnyTransaction(k)))
+                    },
+                    Err(e) => Err(e),
+                }
+            }),
         }
     }
 }
@@ -204,7 +302,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
     #[allow(clippy::too_many_lines)]
     fn fetch(&self, key: &DbKey) -> Result<Option<DbValue>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -365,7 +463,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn contains(&self, key: &DbKey) -> Result<bool, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = match key {
@@ -440,7 +538,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn transaction_exists(&self, tx_id: TxId) -> Result<bool, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
 
         let result = OutboundTransactionSql::find_by_cancelled(tx_id, false, &mut conn).is_ok() ||
@@ -463,7 +561,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         tx_id: TxId,
     ) -> Result<TariAddress, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -503,7 +601,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         &self,
         tx_id: TxId,
     ) -> Result<Option<WalletTransaction>, TransactionStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
 
         match OutboundTransactionSql::find_by_cancelled(tx_id, true, &mut conn) {
@@ -754,6 +852,17 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn mark_awaiting_cosigners(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        match OutboundTransactionSql::mark_awaiting_cosigners(tx_id, &mut conn) {
+            Ok(_) => Ok(()),
+            Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                Err(TransactionStorageError::ValuesNotFound)
+            },
+            Err(e) => Err(e),
+        }
+    }
+
     fn cancel_coinbase_transactions_at_block_height(&self, block_height: u64) -> Result<(), TransactionStorageError> {
         let start = Instant::now();
         let mut conn = self.database_connection.get_pooled_connection()?;
@@ -783,7 +892,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         amount: MicroMinotari,
     ) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -889,7 +998,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn fetch_last_mined_transaction(&self) -> Result<Option<CompletedTransaction>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -920,7 +1029,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
     // This method returns completed but unconfirmed transactions that were not imported
     fn fetch_unconfirmed_transactions_info(&self) -> Result<Vec<UnconfirmedTransactionInfo>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let mut tx_info: Vec<UnconfirmedTransactionInfo> = vec![];
         match UnconfirmedTransactionInfoSql::fetch_unconfirmed_transactions_info(&mut conn) {
@@ -946,7 +1055,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
     fn get_transactions_to_be_broadcast(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let cipher = acquire_read_lock!(self.cipher);
 
@@ -1032,11 +1141,43 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         Ok(())
     }
 
+    fn set_transactions_mined_above_height_as_unmined(
+        &self,
+        height: u64,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let tx_ids = conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let tx_ids = completed_transactions::table
+                .filter(completed_transactions::mined_height.gt(height as i64))
+                .select(completed_transactions::tx_id)
+                .load::<i64>(conn)?
+                .into_iter()
+                .map(|tx_id| TxId::from(tx_id as u64))
+                .collect::<Vec<_>>();
+            for tx_id in &tx_ids {
+                CompletedTransactionSql::set_as_unmined(*tx_id, conn)?;
+            }
+            Ok(tx_ids)
+        })?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - set_transactions_mined_above_height_as_unmined: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(tx_ids)
+    }
+
     fn get_pending_inbound_transaction_sender_info(
         &self,
     ) -> Result<Vec<InboundTransactionSenderInfo>, TransactionStorageError> {
         let start = Instant::now();
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let acquire_lock = start.elapsed();
         let mut sender_info: Vec<InboundTransactionSenderInfo> = vec![];
         match InboundTransactionSenderInfoSql::get_pending_inbound_transaction_sender_info(&mut conn) {
@@ -1060,7 +1201,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
     }
 
     fn fetch_imported_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
 
         CompletedTransactionSql::index_by_status_and_cancelled(TransactionStatus::Imported, false, &mut conn)?
@@ -1072,7 +1213,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
     }
 
     fn fetch_unconfirmed_faux_transactions(&self) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
 
         CompletedTransactionSql::index_by_status_and_cancelled(TransactionStatus::FauxUnconfirmed, false, &mut conn)?
@@ -1087,7 +1228,7 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
         &self,
         height: u64,
     ) -> Result<Vec<CompletedTransaction>, TransactionStorageError> {
-        let mut conn = self.database_connection.get_pooled_connection()?;
+        let mut conn = self.database_connection.get_read_connection()?;
         let cipher = acquire_read_lock!(self.cipher);
 
         CompletedTransactionSql::index_by_status_and_cancelled_from_block_height(
@@ -1117,251 +1258,2113 @@ impl TransactionBackend for TransactionServiceSqliteDatabase {
 
         Ok(())
     }
-}
 
-#[derive(Debug, PartialEq)]
-pub struct InboundTransactionSenderInfo {
-    pub(crate) tx_id: TxId,
-    pub(crate) source_address: TariAddress,
-}
+    fn queue_outbound_message(&self, message: QueuedOutboundMessage) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        let cipher = acquire_read_lock!(self.cipher);
 
-impl TryFrom<InboundTransactionSenderInfoSql> for InboundTransactionSenderInfo {
-    type Error = TransactionStorageError;
+        OutboundMessageSql::try_from(message, &cipher)?.commit(&mut conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - queue_outbound_message: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
 
-    fn try_from(i: InboundTransactionSenderInfoSql) -> Result<Self, Self::Error> {
-        Ok(Self {
-            tx_id: TxId::from(i.tx_id as u64),
-            source_address: TariAddress::from_bytes(&i.source_address)
-                .map_err(TransactionStorageError::TariAddressError)?,
-        })
+    fn fetch_outbound_message(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+    ) -> Result<Option<QueuedOutboundMessage>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        OutboundMessageSql::find(tx_id, message_type, &mut conn)?
+            .map(|m| QueuedOutboundMessage::try_from(m, &cipher))
+            .transpose()
     }
-}
 
-#[derive(Clone, Queryable)]
-pub struct InboundTransactionSenderInfoSql {
-    pub tx_id: i64,
-    pub source_address: Vec<u8>,
-}
+    fn fetch_pending_outbound_messages(&self) -> Result<Vec<QueuedOutboundMessage>, TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+        let cipher = acquire_read_lock!(self.cipher);
 
-impl InboundTransactionSenderInfoSql {
-    pub fn get_pending_inbound_transaction_sender_info(
-        conn: &mut SqliteConnection,
-    ) -> Result<Vec<InboundTransactionSenderInfoSql>, TransactionStorageError> {
-        let query_result = inbound_transactions::table
-            .select((inbound_transactions::tx_id, inbound_transactions::source_address))
-            .filter(inbound_transactions::cancelled.eq(i32::from(false)))
-            .load::<InboundTransactionSenderInfoSql>(conn)?;
-        Ok(query_result)
+        let messages = OutboundMessageSql::index_by_status(OutboundMessageStatus::Pending, &mut conn)?
+            .into_iter()
+            .map(|m| QueuedOutboundMessage::try_from(m, &cipher))
+            .collect::<Result<Vec<_>, _>>()?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_pending_outbound_messages: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(messages)
     }
-}
 
-#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
-#[diesel(table_name = inbound_transactions)]
-struct InboundTransactionSql {
-    tx_id: i64,
-    source_address: Vec<u8>,
-    amount: i64,
-    receiver_protocol: Vec<u8>,
-    message: String,
-    timestamp: NaiveDateTime,
-    cancelled: i32,
-    direct_send_success: i32,
-    send_count: i32,
-    last_send_timestamp: Option<NaiveDateTime>,
-}
+    fn record_outbound_message_attempt(
+        &self,
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        status: OutboundMessageStatus,
+    ) -> Result<(), TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let acquire_lock = start.elapsed();
+        OutboundMessageSql::record_attempt(tx_id, message_type, status, &mut conn)?;
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - record_outbound_message_attempt: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(())
+    }
 
-impl InboundTransactionSql {
-    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::insert_into(inbound_transactions::table)
-            .values(self.clone())
-            .execute(conn)?;
+    fn remove_outbound_messages(&self, tx_id: TxId) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutboundMessageSql::delete_by_tx_id(tx_id, &mut conn)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<InboundTransactionSql>, TransactionStorageError> {
-        Ok(inbound_transactions::table.load::<InboundTransactionSql>(conn)?)
+    fn purge_outbound_messages(&self, expired_before: NaiveDateTime) -> Result<usize, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OutboundMessageSql::delete_finished(expired_before, &mut conn)
     }
 
-    pub fn index_by_cancelled(
-        conn: &mut SqliteConnection,
-        cancelled: bool,
-    ) -> Result<Vec<InboundTransactionSql>, TransactionStorageError> {
-        Ok(inbound_transactions::table
-            .filter(inbound_transactions::cancelled.eq(i32::from(cancelled)))
-            .load::<InboundTransactionSql>(conn)?)
+    fn set_counterparty_alias(&self, tx_id: TxId, alias: String) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        CounterpartyAliasSql {
+            tx_id: tx_id.as_u64() as i64,
+            alias,
+        }
+        .commit(&mut conn)
     }
 
-    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<InboundTransactionSql, TransactionStorageError> {
-        Ok(inbound_transactions::table
-            .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .first::<InboundTransactionSql>(conn)?)
+    fn fetch_counterparty_aliases(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, String>, TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let aliases = CounterpartyAliasSql::index_by_tx_ids(tx_ids, &mut conn)?
+            .into_iter()
+            .map(|a| (TxId::from(a.tx_id as u64), a.alias))
+            .collect();
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - fetch_counterparty_aliases: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(aliases)
     }
 
-    pub fn find_by_cancelled(
+    fn set_transaction_memo(&self, tx_id: TxId, memo: String) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        TransactionMemoSql {
+            tx_id: tx_id.as_u64() as i64,
+            memo: memo.into_bytes(),
+        }
+        .encrypt(&cipher)
+        .map_err(TransactionStorageError::AeadError)?
+        .commit(&mut conn)
+    }
+
+    fn fetch_transaction_memo(&self, tx_id: TxId) -> Result<Option<String>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        TransactionMemoSql::find(tx_id, &mut conn)?
+            .map(|m| {
+                let m = m.decrypt(&cipher).map_err(TransactionStorageError::AeadError)?;
+                String::from_utf8(m.memo).map_err(|e| TransactionStorageError::UnexpectedResult(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn insert_offline_transaction(&self, transaction: OfflineTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        OfflineTransactionSql::try_from(transaction, &cipher)?.commit(&mut conn)
+    }
+
+    fn fetch_offline_transaction(&self, tx_id: TxId) -> Result<Option<OfflineTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        OfflineTransactionSql::find(tx_id, &mut conn)?
+            .map(|t| OfflineTransaction::try_from(t, &cipher))
+            .transpose()
+    }
+
+    fn fetch_offline_transactions(
+        &self,
+        status: Option<OfflineTransactionStatus>,
+    ) -> Result<Vec<OfflineTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        OfflineTransactionSql::index(status, &mut conn)?
+            .into_iter()
+            .map(|t| OfflineTransaction::try_from(t, &cipher))
+            .collect()
+    }
+
+    fn update_offline_transaction_status(
+        &self,
         tx_id: TxId,
-        cancelled: bool,
-        conn: &mut SqliteConnection,
-    ) -> Result<InboundTransactionSql, TransactionStorageError> {
-        Ok(inbound_transactions::table
-            .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .filter(inbound_transactions::cancelled.eq(i32::from(cancelled)))
-            .first::<InboundTransactionSql>(conn)?)
+        from: OfflineTransactionStatus,
+        to: OfflineTransactionStatus,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        OfflineTransactionSql::update_status(tx_id, from, to, &mut conn)
     }
 
-    pub fn mark_direct_send_success(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::update(
-            inbound_transactions::table
-                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
-        )
-        .set(UpdateInboundTransactionSql {
-            cancelled: None,
-            direct_send_success: Some(1i32),
-            receiver_protocol: None,
-            send_count: None,
-            last_send_timestamp: None,
-        })
-        .execute(conn)
-        .num_rows_affected_or_not_found(1)?;
+    fn insert_burn_record(&self, record: BurnRecord) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        BurnTransactionSql::try_from(record, &cipher)?.commit(&mut conn)
+    }
 
-        Ok(())
+    fn fetch_burn_record(&self, tx_id: TxId) -> Result<Option<BurnRecord>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        BurnTransactionSql::find(tx_id, &mut conn)?
+            .map(|b| BurnRecord::try_from(b, &cipher))
+            .transpose()
     }
 
-    pub fn complete_inbound_transaction(
+    fn fetch_burn_records(&self, status: Option<BurnStatus>) -> Result<Vec<BurnRecord>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        BurnTransactionSql::index(status, &mut conn)?
+            .into_iter()
+            .map(|b| BurnRecord::try_from(b, &cipher))
+            .collect()
+    }
+
+    fn update_burn_record_status(
+        &self,
         tx_id: TxId,
-        conn: &mut SqliteConnection,
+        status: BurnStatus,
+        claimed_at: Option<NaiveDateTime>,
     ) -> Result<(), TransactionStorageError> {
-        diesel::delete(
-            inbound_transactions::table
-                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
-        )
-        .execute(conn)
-        .num_rows_affected_or_not_found(1)?;
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        BurnTransactionSql::update_status(tx_id, status, claimed_at, &mut conn)
+    }
 
+    fn add_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let tags = tags
+            .into_iter()
+            .map(|tag| TransactionTagSql {
+                tx_id: tx_id.as_u64() as i64,
+                tag,
+            })
+            .collect::<Vec<_>>();
+        diesel::insert_or_ignore_into(transaction_tags::table)
+            .values(&tags)
+            .execute(&mut conn)?;
         Ok(())
     }
 
-    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::update(
-            inbound_transactions::table
-                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+    fn remove_transaction_tags(&self, tx_id: TxId, tags: Vec<String>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        diesel::delete(
+            transaction_tags::table
+                .filter(transaction_tags::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(transaction_tags::tag.eq_any(tags)),
+        )
+        .execute(&mut conn)?;
+        Ok(())
+    }
+
+    fn fetch_transaction_tags(&self, tx_ids: &[TxId]) -> Result<HashMap<TxId, Vec<String>>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let mut tags = HashMap::<TxId, Vec<String>>::new();
+        for tag in TransactionTagSql::index_by_tx_ids(tx_ids, &mut conn)? {
+            tags.entry(TxId::from(tag.tx_id as u64)).or_default().push(tag.tag);
+        }
+        Ok(tags)
+    }
+
+    fn fetch_tx_ids_by_tag(&self, tag: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let tx_ids = transaction_tags::table
+            .filter(transaction_tags::tag.eq(tag))
+            .select(transaction_tags::tx_id)
+            .order(transaction_tags::tx_id.asc())
+            .load::<i64>(&mut conn)?;
+        Ok(tx_ids.into_iter().map(|id| TxId::from(id as u64)).collect())
+    }
+
+    fn search_transactions(&self, query: &str) -> Result<Vec<TxId>, TransactionStorageError> {
+        let start = Instant::now();
+        let mut conn = self.database_connection.get_read_connection()?;
+        let acquire_lock = start.elapsed();
+
+        let pattern = format!("%{}%", escape_like_pattern(query));
+        let mut tx_ids = BTreeSet::new();
+        tx_ids.extend(
+            transaction_tags::table
+                .filter(transaction_tags::tag.like(&pattern).escape('\\'))
+                .select(transaction_tags::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            completed_transactions::table
+                .filter(completed_transactions::message.like(&pattern).escape('\\'))
+                .select(completed_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            inbound_transactions::table
+                .filter(inbound_transactions::message.like(&pattern).escape('\\'))
+                .select(inbound_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        tx_ids.extend(
+            outbound_transactions::table
+                .filter(outbound_transactions::message.like(&pattern).escape('\\'))
+                .select(outbound_transactions::tx_id)
+                .load::<i64>(&mut conn)?,
+        );
+        if start.elapsed().as_millis() > 0 {
+            trace!(
+                target: LOG_TARGET,
+                "sqlite profile - search_transactions: lock {} + db_op {} = {} ms",
+                acquire_lock.as_millis(),
+                (start.elapsed() - acquire_lock).as_millis(),
+                start.elapsed().as_millis()
+            );
+        }
+        Ok(tx_ids.into_iter().map(|id| TxId::from(id as u64)).collect())
+    }
+
+    fn insert_batched_payments(&self, payments: Vec<BatchedPayment>) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let payments = payments.into_iter().map(NewBatchedPaymentSql::from).collect::<Vec<_>>();
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            for payment in payments {
+                payment.commit(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn fetch_batched_payments(&self, tx_id: TxId) -> Result<Vec<BatchedPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        BatchedPaymentSql::index_by_tx_id(tx_id, &mut conn)?
+            .into_iter()
+            .map(BatchedPayment::try_from)
+            .collect()
+    }
+
+    fn insert_scheduled_transaction(&self, scheduled: ScheduledTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledTransactionSql::from(scheduled).commit(&mut conn)
+    }
+
+    fn fetch_scheduled_transactions(
+        &self,
+        status: Option<ScheduledTransactionStatus>,
+    ) -> Result<Vec<ScheduledTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        ScheduledTransactionSql::index(status, &mut conn)?
+            .into_iter()
+            .map(ScheduledTransaction::try_from)
+            .collect()
+    }
+
+    fn update_scheduled_transaction_status(
+        &self,
+        id: u64,
+        from: ScheduledTransactionStatus,
+        to: ScheduledTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        ScheduledTransactionSql::update_status(id, from, to, tx_id, failure_reason, &mut conn)
+    }
+
+    fn insert_queued_transaction(&self, queued: QueuedTransaction) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        QueuedTransactionSql::from(queued).commit(&mut conn)
+    }
+
+    fn fetch_queued_transactions(
+        &self,
+        status: Option<QueuedTransactionStatus>,
+    ) -> Result<Vec<QueuedTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        QueuedTransactionSql::index(status, &mut conn)?
+            .into_iter()
+            .map(QueuedTransaction::try_from)
+            .collect()
+    }
+
+    fn update_queued_transaction_status(
+        &self,
+        id: u64,
+        from: QueuedTransactionStatus,
+        to: QueuedTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        QueuedTransactionSql::update_status(id, from, to, tx_id, failure_reason, &mut conn)
+    }
+
+    fn supersede_completed_transaction(
+        &self,
+        replacement: CompletedTransaction,
+        batched_payments: Vec<BatchedPayment>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        let tx_id = replacement.tx_id;
+        let replacement_sql = CompletedTransactionSql::try_from(replacement, &cipher)?;
+        let payments = batched_payments
+            .into_iter()
+            .map(NewBatchedPaymentSql::from)
+            .collect::<Vec<_>>();
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let existing = match CompletedTransactionSql::find_by_cancelled(tx_id, false, conn) {
+                Ok(v) => v,
+                Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                    return Err(TransactionStorageError::ValuesNotFound)
+                },
+                Err(e) => return Err(e),
+            };
+            let status = TransactionStatus::try_from(existing.status)?;
+            if !(status == TransactionStatus::Completed || status == TransactionStatus::Broadcast) {
+                return Err(TransactionStorageError::ValuesNotFound);
+            }
+            supersede_current_version(existing, &cipher, conn)?;
+            replacement_sql.commit(conn)?;
+            for payment in payments {
+                payment.commit(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn fetch_superseded_transactions(&self) -> Result<Vec<SupersededTransaction>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        SupersededTransactionSql::index(&mut conn)?
+            .into_iter()
+            .map(|version| SupersededTransaction::try_from(version, &cipher))
+            .collect()
+    }
+
+    fn reinstate_superseded_transaction(&self, id: u64) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let version_sql =
+                SupersededTransactionSql::find(id, conn)?.ok_or(TransactionStorageError::ValuesNotFound)?;
+            let version = SupersededTransaction::try_from(version_sql.clone(), &cipher)?;
+            let existing = match CompletedTransactionSql::find_by_cancelled(version.tx_id, false, conn) {
+                Ok(v) => v,
+                Err(TransactionStorageError::DieselError(DieselError::NotFound)) => {
+                    return Err(TransactionStorageError::ValuesNotFound)
+                },
+                Err(e) => return Err(e),
+            };
+            let mut reinstated = CompletedTransaction::try_from(existing.clone(), &cipher)?;
+            supersede_current_version(existing, &cipher, conn)?;
+            version_sql.delete(conn)?;
+
+            reinstated.fee = version.fee;
+            reinstated.transaction_signature = version.excess_sig().cloned().unwrap_or_default();
+            reinstated.transaction = version.transaction;
+            CompletedTransactionSql::try_from(reinstated, &cipher)?.commit(conn)?;
+            for payment in version.batched_payments {
+                NewBatchedPaymentSql::from(payment).commit(conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    fn upsert_atomic_swap(&self, swap: AtomicSwap) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        AtomicSwapSql::try_from(swap, &cipher)?.commit(&mut conn)
+    }
+
+    fn fetch_atomic_swap(&self, swap_id: u64) -> Result<Option<AtomicSwap>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        AtomicSwapSql::find(swap_id, &mut conn)?
+            .map(|swap| AtomicSwap::try_from(swap, &cipher))
+            .transpose()
+    }
+
+    fn fetch_atomic_swaps(&self) -> Result<Vec<AtomicSwap>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        AtomicSwapSql::index(&mut conn)?
+            .into_iter()
+            .map(|swap| AtomicSwap::try_from(swap, &cipher))
+            .collect()
+    }
+
+    fn upsert_multisig_session(&self, session: MultisigSession) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        MultisigSessionSql::try_from(session)?.commit(&mut conn)
+    }
+
+    fn fetch_multisig_session(&self, session_id: u64) -> Result<Option<MultisigSession>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        MultisigSessionSql::find(session_id, &mut conn)?
+            .map(MultisigSession::try_from)
+            .transpose()
+    }
+
+    fn fetch_multisig_sessions(&self) -> Result<Vec<MultisigSession>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        MultisigSessionSql::index(&mut conn)?
+            .into_iter()
+            .map(MultisigSession::try_from)
+            .collect()
+    }
+
+    fn upsert_multisig_signing(&self, signing: MultisigSigning) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        MultisigSigningSql::try_from(signing)?.commit(&mut conn)
+    }
+
+    fn fetch_multisig_signing(&self, signing_id: u64) -> Result<Option<MultisigSigning>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        MultisigSigningSql::find(signing_id, &mut conn)?
+            .map(MultisigSigning::try_from)
+            .transpose()
+    }
+
+    fn fetch_multisig_signings(
+        &self,
+        session_id: Option<u64>,
+    ) -> Result<Vec<MultisigSigning>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        MultisigSigningSql::index(session_id, &mut conn)?
+            .into_iter()
+            .map(MultisigSigning::try_from)
+            .collect()
+    }
+
+    fn insert_recurring_payment(&self, payment: RecurringPayment) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        RecurringPaymentSql::from(payment).commit(&mut conn)
+    }
+
+    fn fetch_recurring_payments(
+        &self,
+        status: Option<RecurringPaymentStatus>,
+    ) -> Result<Vec<RecurringPayment>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        RecurringPaymentSql::index(status, &mut conn)?
+            .into_iter()
+            .map(RecurringPayment::try_from)
+            .collect()
+    }
+
+    fn update_recurring_payment_status(
+        &self,
+        id: u64,
+        from: &[RecurringPaymentStatus],
+        to: RecurringPaymentStatus,
+        next_payment_at: Option<NaiveDateTime>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        RecurringPaymentSql::update_status(id, from, to, next_payment_at, failure_reason, &mut conn)
+    }
+
+    fn advance_recurring_payment(
+        &self,
+        id: u64,
+        due_at: NaiveDateTime,
+        next_payment_at: NaiveDateTime,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        RecurringPaymentSql::advance(id, due_at, next_payment_at, &mut conn)
+    }
+
+    fn record_recurring_payment_result(
+        &self,
+        id: u64,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        RecurringPaymentSql::record_result(id, tx_id, failure_reason, &mut conn)
+    }
+
+    fn journal_transaction_event(
+        &self,
+        event: &TransactionEvent,
+        max_events: usize,
+    ) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            NewTransactionEventSql::try_from(event)?.commit(conn)?;
+            TransactionEventSql::prune(max_events, conn)
+        })
+    }
+
+    fn fetch_transaction_events_since(
+        &self,
+        cursor: u64,
+    ) -> Result<Vec<JournaledTransactionEvent>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        TransactionEventSql::index_since(cursor, &mut conn)?
+            .into_iter()
+            .map(JournaledTransactionEvent::try_from)
+            .collect()
+    }
+
+    fn insert_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SendTemplateSql::from(template).commit(&mut conn)
+    }
+
+    fn update_send_template(&self, template: SendTemplate) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SendTemplateSql::from(template).update(&mut conn)
+    }
+
+    fn fetch_send_template(&self, id: u64) -> Result<Option<SendTemplate>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        SendTemplateSql::find(id, &mut conn)?
+            .map(SendTemplate::try_from)
+            .transpose()
+    }
+
+    fn fetch_send_templates(&self) -> Result<Vec<SendTemplate>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_read_connection()?;
+        SendTemplateSql::index(&mut conn)?
+            .into_iter()
+            .map(SendTemplate::try_from)
+            .collect()
+    }
+
+    fn delete_send_template(&self, id: u64) -> Result<(), TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        SendTemplateSql::delete(id, &mut conn)
+    }
+
+    fn insert_imported_history(
+        &self,
+        transactions: Vec<CompletedTransaction>,
+    ) -> Result<Vec<TxId>, TransactionStorageError> {
+        let mut conn = self.database_connection.get_pooled_connection()?;
+        let cipher = acquire_read_lock!(self.cipher);
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let mut inserted = Vec::with_capacity(transactions.len());
+            for tx in transactions {
+                let tx_id = tx.tx_id;
+                if CompletedTransactionSql::find(tx_id, conn).is_ok() {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Skipping imported history for {} that is already in the database", tx_id
+                    );
+                    continue;
+                }
+                CompletedTransactionSql::try_from(tx, &cipher)?.commit(conn)?;
+                inserted.push(tx_id);
+            }
+            Ok(inserted)
+        })
+    }
+}
+
+/// Moves the current version of a completed transaction and its batched payments to the superseded transactions
+fn supersede_current_version(
+    existing: CompletedTransactionSql,
+    cipher: &XChaCha20Poly1305,
+    conn: &mut SqliteConnection,
+) -> Result<(), TransactionStorageError> {
+    let tx_id = TxId::from(existing.tx_id as u64);
+    let current = CompletedTransaction::try_from(existing.clone(), cipher)?;
+    let current_payments = BatchedPaymentSql::index_by_tx_id(tx_id, conn)?
+        .into_iter()
+        .map(BatchedPayment::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    SupersededTransactionSql::try_from(&current, &current_payments, cipher)?.commit(conn)?;
+
+    existing.delete(conn)?;
+    BatchedPaymentSql::delete_by_tx_id(tx_id, conn)?;
+    Ok(())
+}
+
+/// Re-encrypt every row of the given transaction service table, moving its encrypted fields from the `old` to the
+/// `new` cipher. Returns the number of rows that were updated.
+pub(crate) fn reencrypt_table(
+    table: TransactionEncryptedTable,
+    conn: &mut SqliteConnection,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<usize, TransactionStorageError> {
+    match table {
+        TransactionEncryptedTable::InboundTransactions => {
+            let rows = inbound_transactions::table.load::<InboundTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OutboundTransactions => {
+            let rows = outbound_transactions::table.load::<OutboundTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::CompletedTransactions => {
+            let rows = completed_transactions::table.load::<CompletedTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::TransactionMemos => {
+            let rows = transaction_memos::table.load::<TransactionMemoSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::AtomicSwaps => {
+            let rows = atomic_swaps::table.load::<AtomicSwapSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OutboundMessages => {
+            let rows = outbound_message_queue::table.load::<OutboundMessageSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::OfflineTransactions => {
+            let rows = offline_transactions::table.load::<OfflineTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::BurnTransactions => {
+            let rows = burn_transactions::table.load::<BurnTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+        TransactionEncryptedTable::SupersededTransactions => {
+            let rows = superseded_transactions::table.load::<SupersededTransactionSql>(conn)?;
+            for row in &rows {
+                reencrypt(row.clone(), old, new)?.update_encryption(conn)?;
+            }
+            Ok(rows.len())
+        },
+    }
+}
+
+/// The transaction service tables that hold encrypted fields
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TransactionEncryptedTable {
+    InboundTransactions,
+    OutboundTransactions,
+    CompletedTransactions,
+    TransactionMemos,
+    AtomicSwaps,
+    OutboundMessages,
+    OfflineTransactions,
+    BurnTransactions,
+    SupersededTransactions,
+}
+
+impl TransactionEncryptedTable {
+    pub const ALL: [TransactionEncryptedTable; 9] = [
+        TransactionEncryptedTable::InboundTransactions,
+        TransactionEncryptedTable::OutboundTransactions,
+        TransactionEncryptedTable::CompletedTransactions,
+        TransactionEncryptedTable::TransactionMemos,
+        TransactionEncryptedTable::AtomicSwaps,
+        TransactionEncryptedTable::OutboundMessages,
+        TransactionEncryptedTable::OfflineTransactions,
+        TransactionEncryptedTable::BurnTransactions,
+        TransactionEncryptedTable::SupersededTransactions,
+    ];
+
+    pub fn table_name(self) -> &'static str {
+        match self {
+            TransactionEncryptedTable::InboundTransactions => "inbound_transactions",
+            TransactionEncryptedTable::OutboundTransactions => "outbound_transactions",
+            TransactionEncryptedTable::CompletedTransactions => "completed_transactions",
+            TransactionEncryptedTable::TransactionMemos => "transaction_memos",
+            TransactionEncryptedTable::AtomicSwaps => "atomic_swaps",
+            TransactionEncryptedTable::OutboundMessages => "outbound_message_queue",
+            TransactionEncryptedTable::OfflineTransactions => "offline_transactions",
+            TransactionEncryptedTable::BurnTransactions => "burn_transactions",
+            TransactionEncryptedTable::SupersededTransactions => "superseded_transactions",
+        }
+    }
+}
+
+pub(crate) fn reencrypt<T: Encryptable<XChaCha20Poly1305>>(
+    value: T,
+    old: &XChaCha20Poly1305,
+    new: &XChaCha20Poly1305,
+) -> Result<T, TransactionStorageError> {
+    value
+        .decrypt(old)
+        .map_err(TransactionStorageError::AeadError)?
+        .encrypt(new)
+        .map_err(TransactionStorageError::AeadError)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct InboundTransactionSenderInfo {
+    pub(crate) tx_id: TxId,
+    pub(crate) source_address: TariAddress,
+}
+
+impl TryFrom<InboundTransactionSenderInfoSql> for InboundTransactionSenderInfo {
+    type Error = TransactionStorageError;
+
+    fn try_from(i: InboundTransactionSenderInfoSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: TxId::from(i.tx_id as u64),
+            source_address: TariAddress::from_bytes(&i.source_address)
+                .map_err(TransactionStorageError::TariAddressError)?,
+        })
+    }
+}
+
+#[derive(Clone, Queryable)]
+pub struct InboundTransactionSenderInfoSql {
+    pub tx_id: i64,
+    pub source_address: Vec<u8>,
+}
+
+impl InboundTransactionSenderInfoSql {
+    pub fn get_pending_inbound_transaction_sender_info(
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<InboundTransactionSenderInfoSql>, TransactionStorageError> {
+        let query_result = inbound_transactions::table
+            .select((inbound_transactions::tx_id, inbound_transactions::source_address))
+            .filter(inbound_transactions::cancelled.eq(i32::from(false)))
+            .load::<InboundTransactionSenderInfoSql>(conn)?;
+        Ok(query_result)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = inbound_transactions)]
+pub(crate) struct InboundTransactionSql {
+    pub(crate) tx_id: i64,
+    pub(crate) source_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) receiver_protocol: Vec<u8>,
+    pub(crate) message: String,
+    pub(crate) timestamp: NaiveDateTime,
+    pub(crate) cancelled: i32,
+    pub(crate) direct_send_success: i32,
+    pub(crate) send_count: i32,
+    pub(crate) last_send_timestamp: Option<NaiveDateTime>,
+}
+
+impl InboundTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(inbound_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_cancelled(
+        conn: &mut SqliteConnection,
+        cancelled: bool,
+    ) -> Result<Vec<InboundTransactionSql>, TransactionStorageError> {
+        Ok(inbound_transactions::table
+            .filter(inbound_transactions::cancelled.eq(i32::from(cancelled)))
+            .load::<InboundTransactionSql>(conn)?)
+    }
+
+    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<InboundTransactionSql, TransactionStorageError> {
+        Ok(inbound_transactions::table
+            .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<InboundTransactionSql>(conn)?)
+    }
+
+    pub fn find_by_cancelled(
+        tx_id: TxId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<InboundTransactionSql, TransactionStorageError> {
+        Ok(inbound_transactions::table
+            .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .filter(inbound_transactions::cancelled.eq(i32::from(cancelled)))
+            .first::<InboundTransactionSql>(conn)?)
+    }
+
+    pub fn mark_direct_send_success(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            inbound_transactions::table
+                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .set(UpdateInboundTransactionSql {
+            cancelled: None,
+            direct_send_success: Some(1i32),
+            receiver_protocol: None,
+            send_count: None,
+            last_send_timestamp: None,
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn complete_inbound_transaction(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            inbound_transactions::table
+                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            inbound_transactions::table
+                .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(inbound_transactions::cancelled.eq(i32::from(false))),
         )
         .set(UpdateInboundTransactionSql {
             cancelled: None,
             direct_send_success: None,
-            receiver_protocol: None,
-            send_count: Some(
-                if let Some(value) = inbound_transactions::table
-                    .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                    .filter(inbound_transactions::cancelled.eq(i32::from(false)))
-                    .select(inbound_transactions::send_count)
-                    .load::<i32>(conn)?
-                    .first()
-                {
-                    value + 1
-                } else {
-                    return Err(TransactionStorageError::DieselError(DieselError::NotFound));
+            receiver_protocol: None,
+            send_count: Some(
+                if let Some(value) = inbound_transactions::table
+                    .filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                    .filter(inbound_transactions::cancelled.eq(i32::from(false)))
+                    .select(inbound_transactions::send_count)
+                    .load::<i32>(conn)?
+                    .first()
+                {
+                    value + 1
+                } else {
+                    return Err(TransactionStorageError::DieselError(DieselError::NotFound));
+                },
+            ),
+            last_send_timestamp: Some(Some(Utc::now().naive_utc())),
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        let num_deleted =
+            diesel::delete(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(&self.tx_id)))
+                .execute(conn)?;
+
+        if num_deleted == 0 {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+
+        Ok(())
+    }
+
+    pub fn update(
+        &self,
+        update: UpdateInboundTransactionSql,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        let num_updated =
+            diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(&self.tx_id)))
+                .set(update)
+                .execute(conn)?;
+
+        if num_updated == 0 {
+            return Err(TransactionStorageError::UnexpectedResult(
+                "Updating inbound transactions failed. No rows were affected".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn find_and_set_cancelled(
+        tx_id: TxId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateInboundTransactionSql {
+                cancelled: Some(i32::from(cancelled)),
+                direct_send_success: None,
+                receiver_protocol: None,
+                send_count: None,
+                last_send_timestamp: None,
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        self.update(
+            UpdateInboundTransactionSql {
+                cancelled: None,
+                direct_send_success: None,
+                receiver_protocol: Some(self.receiver_protocol.clone()),
+                send_count: None,
+                last_send_timestamp: None,
+            },
+            conn,
+        )
+    }
+
+    pub(crate) fn try_from(i: InboundTransaction, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        let receiver_protocol_bytes = bincode::serialize(&i.receiver_protocol)
+            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+        let i = Self {
+            tx_id: i.tx_id.as_u64() as i64,
+            source_address: i.source_address.to_bytes().to_vec(),
+            amount: u64::from(i.amount) as i64,
+            receiver_protocol: receiver_protocol_bytes.to_vec(),
+            message: i.message,
+            timestamp: i.timestamp,
+            cancelled: i32::from(i.cancelled),
+            direct_send_success: i32::from(i.direct_send_success),
+            send_count: i.send_count as i32,
+            last_send_timestamp: i.last_send_timestamp,
+        };
+        i.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for InboundTransactionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::INBOUND_TRANSACTION,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.receiver_protocol = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("receiver_protocol"),
+            Hidden::hide(self.receiver_protocol),
+        )?;
+
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.receiver_protocol =
+            decrypt_bytes_integral_nonce(cipher, self.domain("receiver_protocol"), &self.receiver_protocol)?;
+
+        Ok(self)
+    }
+}
+
+impl InboundTransaction {
+    pub(crate) fn try_from(
+        i: InboundTransactionSql,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let i = i.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        Ok(Self {
+            tx_id: (i.tx_id as u64).into(),
+            source_address: TariAddress::from_bytes(&i.source_address).map_err(TransactionKeyError::Source)?,
+            amount: MicroMinotari::from(i.amount as u64),
+            receiver_protocol: bincode::deserialize(&i.receiver_protocol)
+                .map_err(|e| TransactionStorageError::BincodeDeserialize(e.to_string()))?,
+            status: TransactionStatus::Pending,
+            message: i.message,
+            timestamp: i.timestamp,
+            cancelled: i.cancelled != 0,
+            direct_send_success: i.direct_send_success != 0,
+            send_count: i.send_count as u32,
+            last_send_timestamp: i.last_send_timestamp,
+        })
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = inbound_transactions)]
+pub struct UpdateInboundTransactionSql {
+    pub(crate) cancelled: Option<i32>,
+    pub(crate) direct_send_success: Option<i32>,
+    pub(crate) receiver_protocol: Option<Vec<u8>>,
+    pub(crate) send_count: Option<i32>,
+    pub(crate) last_send_timestamp: Option<Option<NaiveDateTime>>,
+}
+
+/// A structure to represent a Sql compatible version of the OutboundTransaction struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = outbound_transactions)]
+pub(crate) struct OutboundTransactionSql {
+    pub(crate) tx_id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) fee: i64,
+    pub(crate) sender_protocol: Vec<u8>,
+    pub(crate) message: String,
+    pub(crate) timestamp: NaiveDateTime,
+    pub(crate) cancelled: i32,
+    pub(crate) direct_send_success: i32,
+    pub(crate) send_count: i32,
+    pub(crate) last_send_timestamp: Option<NaiveDateTime>,
+    pub(crate) awaiting_cosigners: i32,
+}
+
+impl OutboundTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(outbound_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_cancelled(
+        conn: &mut SqliteConnection,
+        cancelled: bool,
+    ) -> Result<Vec<OutboundTransactionSql>, TransactionStorageError> {
+        Ok(outbound_transactions::table
+            .filter(outbound_transactions::cancelled.eq(i32::from(cancelled)))
+            .load::<OutboundTransactionSql>(conn)?)
+    }
+
+    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<OutboundTransactionSql, TransactionStorageError> {
+        Ok(outbound_transactions::table
+            .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<OutboundTransactionSql>(conn)?)
+    }
+
+    pub fn find_by_cancelled(
+        tx_id: TxId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<OutboundTransactionSql, TransactionStorageError> {
+        Ok(outbound_transactions::table
+            .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .filter(outbound_transactions::cancelled.eq(i32::from(cancelled)))
+            .first::<OutboundTransactionSql>(conn)?)
+    }
+
+    pub fn mark_direct_send_success(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            outbound_transactions::table
+                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .set(UpdateOutboundTransactionSql {
+            cancelled: None,
+            direct_send_success: Some(1i32),
+            sender_protocol: None,
+            send_count: None,
+            last_send_timestamp: None,
+            awaiting_cosigners: None,
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn mark_awaiting_cosigners(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            outbound_transactions::table
+                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .set(UpdateOutboundTransactionSql {
+            cancelled: None,
+            direct_send_success: None,
+            sender_protocol: None,
+            send_count: None,
+            last_send_timestamp: None,
+            awaiting_cosigners: Some(1i32),
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn complete_outbound_transaction(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::delete(
+            outbound_transactions::table
+                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
+        )
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateOutboundTransactionSql {
+                cancelled: None,
+                direct_send_success: None,
+                sender_protocol: None,
+                send_count: Some(
+                    if let Some(value) = outbound_transactions::table
+                        .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                        .select(outbound_transactions::send_count)
+                        .load::<i32>(conn)?
+                        .first()
+                    {
+                        value + 1
+                    } else {
+                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
+                    },
+                ),
+                last_send_timestamp: Some(Some(Utc::now().naive_utc())),
+                awaiting_cosigners: None,
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(&self.tx_id)))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn update(
+        &self,
+        update: UpdateOutboundTransactionSql,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(&self.tx_id)))
+            .set(update)
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn find_and_set_cancelled(
+        tx_id: TxId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateOutboundTransactionSql {
+                cancelled: Some(i32::from(cancelled)),
+                direct_send_success: None,
+                sender_protocol: None,
+                send_count: None,
+                last_send_timestamp: None,
+                awaiting_cosigners: None,
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        self.update(
+            UpdateOutboundTransactionSql {
+                cancelled: None,
+                direct_send_success: None,
+                sender_protocol: Some(self.sender_protocol.clone()),
+                send_count: None,
+                last_send_timestamp: None,
+                awaiting_cosigners: None,
+            },
+            conn,
+        )
+    }
+
+    pub(crate) fn try_from(
+        o: OutboundTransaction,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let sender_protocol_bytes = bincode::serialize(&o.sender_protocol)
+            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+        let outbound_tx = Self {
+            tx_id: o.tx_id.as_u64() as i64,
+            destination_address: o.destination_address.to_bytes().to_vec(),
+            amount: u64::from(o.amount) as i64,
+            fee: u64::from(o.fee) as i64,
+            sender_protocol: sender_protocol_bytes.to_vec(),
+            message: o.message,
+            timestamp: o.timestamp,
+            cancelled: i32::from(o.cancelled),
+            direct_send_success: i32::from(o.direct_send_success),
+            send_count: o.send_count as i32,
+            last_send_timestamp: o.last_send_timestamp,
+            awaiting_cosigners: i32::from(o.status == TransactionStatus::AwaitingCosigners),
+        };
+
+        outbound_tx.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for OutboundTransactionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::OUTBOUND_TRANSACTION,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.sender_protocol = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("sender_protocol"),
+            Hidden::hide(self.sender_protocol),
+        )?;
+
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.sender_protocol =
+            decrypt_bytes_integral_nonce(cipher, self.domain("sender_protocol"), &self.sender_protocol)?;
+        Ok(self)
+    }
+}
+
+impl OutboundTransaction {
+    pub(crate) fn try_from(
+        o: OutboundTransactionSql,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let mut o = o.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        let outbound_tx = Self {
+            tx_id: (o.tx_id as u64).into(),
+            destination_address: TariAddress::from_bytes(&o.destination_address)
+                .map_err(TransactionKeyError::Destination)?,
+            amount: MicroMinotari::from(o.amount as u64),
+            fee: MicroMinotari::from(o.fee as u64),
+            sender_protocol: bincode::deserialize(&o.sender_protocol)
+                .map_err(|e| TransactionStorageError::BincodeDeserialize(e.to_string()))?,
+            status: if o.awaiting_cosigners == 0 {
+                TransactionStatus::Pending
+            } else {
+                TransactionStatus::AwaitingCosigners
+            },
+            message: o.message,
+            timestamp: o.timestamp,
+            cancelled: o.cancelled != 0,
+            direct_send_success: o.direct_send_success != 0,
+            send_count: o.send_count as u32,
+            last_send_timestamp: o.last_send_timestamp,
+        };
+
+        // zeroize decrypted data
+        o.sender_protocol.zeroize();
+
+        Ok(outbound_tx)
+    }
+}
+
+#[derive(AsChangeset)]
+#[diesel(table_name = outbound_transactions)]
+pub struct UpdateOutboundTransactionSql {
+    pub(crate) cancelled: Option<i32>,
+    pub(crate) direct_send_success: Option<i32>,
+    pub(crate) sender_protocol: Option<Vec<u8>>,
+    pub(crate) send_count: Option<i32>,
+    pub(crate) last_send_timestamp: Option<Option<NaiveDateTime>>,
+    pub(crate) awaiting_cosigners: Option<i32>,
+}
+
+/// A structure to represent a Sql compatible version of the CompletedTransaction struct
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = completed_transactions)]
+pub struct CompletedTransactionSql {
+    pub(crate) tx_id: i64,
+    pub(crate) source_address: Vec<u8>,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) fee: i64,
+    pub(crate) transaction_protocol: Vec<u8>,
+    pub(crate) status: i32,
+    pub(crate) message: String,
+    pub(crate) timestamp: NaiveDateTime,
+    pub(crate) cancelled: Option<i32>,
+    pub(crate) direction: Option<i32>,
+    pub(crate) coinbase_block_height: Option<i64>,
+    pub(crate) send_count: i32,
+    pub(crate) last_send_timestamp: Option<NaiveDateTime>,
+    pub(crate) confirmations: Option<i64>,
+    pub(crate) mined_height: Option<i64>,
+    pub(crate) mined_in_block: Option<Vec<u8>>,
+    pub(crate) mined_timestamp: Option<NaiveDateTime>,
+    pub(crate) transaction_signature_nonce: Vec<u8>,
+    pub(crate) transaction_signature_key: Vec<u8>,
+}
+
+impl CompletedTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(completed_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index_by_cancelled(
+        conn: &mut SqliteConnection,
+        cancelled: bool,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        let mut query = completed_transactions::table.into_boxed();
+
+        query = if cancelled {
+            query.filter(completed_transactions::cancelled.is_not_null())
+        } else {
+            query.filter(completed_transactions::cancelled.is_null())
+        };
+
+        Ok(query.load::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn index_by_status_and_cancelled(
+        status: TransactionStatus,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        let mut query = completed_transactions::table.into_boxed();
+        query = if cancelled {
+            query.filter(completed_transactions::cancelled.is_not_null())
+        } else {
+            query.filter(completed_transactions::cancelled.is_null())
+        };
+        Ok(query
+            .filter(completed_transactions::status.eq(status as i32))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn index_by_status_and_cancelled_from_block_height(
+        status: TransactionStatus,
+        cancelled: bool,
+        block_height: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        let mut query = completed_transactions::table.into_boxed();
+        query = if cancelled {
+            query.filter(completed_transactions::cancelled.is_not_null())
+        } else {
+            query.filter(completed_transactions::cancelled.is_null())
+        };
+
+        Ok(query
+            .filter(completed_transactions::status.eq(status as i32))
+            .filter(completed_transactions::mined_height.ge(block_height))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn index_coinbase_at_block_height(
+        block_height: i64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
+            .filter(completed_transactions::coinbase_block_height.eq(block_height))
+            .load::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn find_and_abandon_coinbase(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        let _ = diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(completed_transactions::cancelled.is_null())
+                .filter(completed_transactions::coinbase_block_height.is_not_null()),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(TxCancellationReason::AbandonedCoinbase as i32)),
+            ..Default::default()
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<CompletedTransactionSql, TransactionStorageError> {
+        Ok(completed_transactions::table
+            .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn find_by_cancelled(
+        tx_id: TxId,
+        cancelled: bool,
+        conn: &mut SqliteConnection,
+    ) -> Result<CompletedTransactionSql, TransactionStorageError> {
+        let mut query = completed_transactions::table
+            .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .into_boxed();
+
+        query = if cancelled {
+            query.filter(completed_transactions::cancelled.is_not_null())
+        } else {
+            query.filter(completed_transactions::cancelled.is_null())
+        };
+
+        Ok(query.first::<CompletedTransactionSql>(conn)?)
+    }
+
+    pub fn reject_completed_transaction(
+        tx_id: TxId,
+        reason: TxCancellationReason,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(completed_transactions::cancelled.is_null()),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(reason as i32)),
+            status: Some(TransactionStatus::Rejected as i32),
+            ..Default::default()
+        })
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        // This query uses a sub-query to retrieve an existing value in the table
+        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateCompletedTransactionSql {
+                send_count: Some(
+                    if let Some(value) = completed_transactions::table
+                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                        .select(completed_transactions::send_count)
+                        .load::<i32>(conn)?
+                        .first()
+                    {
+                        value + 1
+                    } else {
+                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
+                    },
+                ),
+                last_send_timestamp: Some(Some(Utc::now().naive_utc())),
+                ..Default::default()
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn reject_coinbases_at_block_height(
+        block_height: i64,
+        reason: TxCancellationReason,
+        conn: &mut SqliteConnection,
+    ) -> Result<usize, TransactionStorageError> {
+        Ok(diesel::update(
+            completed_transactions::table
+                .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
+                .filter(completed_transactions::coinbase_block_height.eq(block_height)),
+        )
+        .set(UpdateCompletedTransactionSql {
+            cancelled: Some(Some(reason as i32)),
+            status: Some(TransactionStatus::Rejected as i32),
+            ..Default::default()
+        })
+        .execute(conn)?)
+    }
+
+    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        let num_deleted =
+            diesel::delete(completed_transactions::table.filter(completed_transactions::tx_id.eq(&self.tx_id)))
+                .execute(conn)?;
+
+        if num_deleted == 0 {
+            return Err(TransactionStorageError::ValuesNotFound);
+        }
+
+        Ok(())
+    }
+
+    pub fn update(
+        &self,
+        updated_tx: UpdateCompletedTransactionSql,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(&self.tx_id)))
+            .set(updated_tx)
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn update_mined_height(
+        tx_id: TxId,
+        num_confirmations: u64,
+        status: TransactionStatus,
+        mined_height: u64,
+        mined_in_block: BlockHash,
+        mined_timestamp: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        let timestamp = NaiveDateTime::from_timestamp_opt(mined_timestamp as i64, 0).ok_or_else(|| {
+            TransactionStorageError::UnexpectedResult(format!(
+                "Could not create timestamp mined_timestamp: {}",
+                mined_timestamp
+            ))
+        })?;
+        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateCompletedTransactionSql {
+                confirmations: Some(Some(num_confirmations as i64)),
+                status: Some(status as i32),
+                mined_height: Some(Some(mined_height as i64)),
+                mined_in_block: Some(Some(mined_in_block.to_vec())),
+                mined_timestamp: Some(timestamp),
+                // If the tx is mined, then it can't be cancelled
+                cancelled: None,
+                ..Default::default()
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        Ok(())
+    }
+
+    pub fn set_as_unmined(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        // This query uses two sub-queries to retrieve existing values in the table
+        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set(UpdateCompletedTransactionSql {
+                status: {
+                    if let Some(Some(_coinbase_block_height)) = completed_transactions::table
+                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                        .select(completed_transactions::coinbase_block_height)
+                        .load::<Option<i64>>(conn)?
+                        .first()
+                    {
+                        Some(TransactionStatus::Coinbase as i32)
+                    } else if let Some(status) = completed_transactions::table
+                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                        .select(completed_transactions::status)
+                        .load::<i32>(conn)?
+                        .first()
+                    {
+                        if *status == TransactionStatus::FauxConfirmed as i32 {
+                            Some(TransactionStatus::FauxUnconfirmed as i32)
+                        } else if *status == TransactionStatus::Broadcast as i32 {
+                            Some(TransactionStatus::Broadcast as i32)
+                        } else {
+                            Some(TransactionStatus::Completed as i32)
+                        }
+                    } else {
+                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
+                    }
                 },
+                mined_in_block: Some(None),
+                mined_height: Some(None),
+                confirmations: Some(None),
+                // Turns out it should not be cancelled
+                cancelled: Some(None),
+                ..Default::default()
+            })
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+
+        // Ideally the outputs should be marked unmined here as well, but because of the separation of classes,
+        // that will be done in the outputs service.
+
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        self.update(
+            UpdateCompletedTransactionSql {
+                transaction_protocol: Some(self.transaction_protocol.clone()),
+                ..Default::default()
+            },
+            conn,
+        )?;
+
+        Ok(())
+    }
+
+    pub(crate) fn try_from(
+        c: CompletedTransaction,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let transaction_bytes =
+            bincode::serialize(&c.transaction).map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+
+        let output = Self {
+            tx_id: c.tx_id.as_u64() as i64,
+            source_address: c.source_address.to_bytes().to_vec(),
+            destination_address: c.destination_address.to_bytes().to_vec(),
+            amount: u64::from(c.amount) as i64,
+            fee: u64::from(c.fee) as i64,
+            transaction_protocol: transaction_bytes.to_vec(),
+            status: c.status as i32,
+            message: c.message,
+            timestamp: c.timestamp,
+            cancelled: c.cancelled.map(|v| v as i32),
+            direction: Some(c.direction as i32),
+            coinbase_block_height: c.coinbase_block_height.map(|b| b as i64),
+            send_count: c.send_count as i32,
+            last_send_timestamp: c.last_send_timestamp,
+            confirmations: c.confirmations.map(|ic| ic as i64),
+            mined_height: c.mined_height.map(|ic| ic as i64),
+            mined_in_block: c.mined_in_block.map(|v| v.to_vec()),
+            mined_timestamp: c.mined_timestamp,
+            transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
+            transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
+        };
+
+        output.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for CompletedTransactionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::COMPLETED_TRANSACTION,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.transaction_protocol = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("transaction_protocol"),
+            Hidden::hide(self.transaction_protocol),
+        )?;
+
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.transaction_protocol =
+            decrypt_bytes_integral_nonce(cipher, self.domain("transaction_protocol"), &self.transaction_protocol)?;
+
+        Ok(self)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CompletedTransactionConversionError {
+    #[error("CompletedTransaction conversion failed by wrong direction: {0}")]
+    DirectionError(#[from] TransactionDirectionError),
+    #[error("CompletedTransaction conversion failed with transaction conversion: {0}")]
+    ConversionError(#[from] TransactionConversionError),
+    #[error("CompletedTransaction conversion failed with json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("CompletedTransaction conversion failed with key error: {0}")]
+    KeyError(#[from] TransactionKeyError),
+    #[error("Aead Error: {0}")]
+    AeadError(String),
+    #[error("Bincode error: `{0}`")]
+    BincodeDeserialize(String),
+}
+
+impl CompletedTransaction {
+    pub(crate) fn try_from(
+        c: CompletedTransactionSql,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, CompletedTransactionConversionError> {
+        let mut c = c
+            .decrypt(cipher)
+            .map_err(CompletedTransactionConversionError::AeadError)?;
+        let transaction_signature = match PublicKey::from_vec(&c.transaction_signature_nonce) {
+            Ok(public_nonce) => match PrivateKey::from_vec(&c.transaction_signature_key) {
+                Ok(signature) => Signature::new(public_nonce, signature),
+                Err(_) => Signature::default(),
+            },
+            Err(_) => Signature::default(),
+        };
+        let mined_in_block = match c.mined_in_block {
+            Some(v) => match v.try_into() {
+                Ok(v) => Some(v),
+                Err(_) => None,
+            },
+            None => None,
+        };
+
+        let output = Self {
+            tx_id: (c.tx_id as u64).into(),
+            source_address: TariAddress::from_bytes(&c.source_address).map_err(TransactionKeyError::Source)?,
+            destination_address: TariAddress::from_bytes(&c.destination_address)
+                .map_err(TransactionKeyError::Destination)?,
+            amount: MicroMinotari::from(c.amount as u64),
+            fee: MicroMinotari::from(c.fee as u64),
+            transaction: bincode::deserialize(&c.transaction_protocol)
+                .map_err(|e| CompletedTransactionConversionError::BincodeDeserialize(e.to_string()))?,
+            status: TransactionStatus::try_from(c.status)?,
+            message: c.message,
+            timestamp: c.timestamp,
+            cancelled: c
+                .cancelled
+                .map(|v| TxCancellationReason::try_from(v as u32).unwrap_or(TxCancellationReason::Unknown)),
+            direction: TransactionDirection::try_from(c.direction.unwrap_or(2i32))?,
+            coinbase_block_height: c.coinbase_block_height.map(|b| b as u64),
+            send_count: c.send_count as u32,
+            last_send_timestamp: c.last_send_timestamp,
+            transaction_signature,
+            confirmations: c.confirmations.map(|ic| ic as u64),
+            mined_height: c.mined_height.map(|ic| ic as u64),
+            mined_in_block,
+            mined_timestamp: c.mined_timestamp,
+        };
+
+        // zeroize sensitive data
+        c.transaction_protocol.zeroize();
+
+        Ok(output)
+    }
+}
+
+#[derive(AsChangeset, Default)]
+#[diesel(table_name = completed_transactions)]
+pub struct UpdateCompletedTransactionSql {
+    pub(crate) status: Option<i32>,
+    pub(crate) timestamp: Option<NaiveDateTime>,
+    pub(crate) cancelled: Option<Option<i32>>,
+    pub(crate) direction: Option<i32>,
+    pub(crate) transaction_protocol: Option<Vec<u8>>,
+    pub(crate) send_count: Option<i32>,
+    pub(crate) last_send_timestamp: Option<Option<NaiveDateTime>>,
+    pub(crate) confirmations: Option<Option<i64>>,
+    pub(crate) mined_height: Option<Option<i64>>,
+    pub(crate) mined_in_block: Option<Option<Vec<u8>>>,
+    pub(crate) mined_timestamp: Option<NaiveDateTime>,
+    pub(crate) transaction_signature_nonce: Option<Vec<u8>>,
+    pub(crate) transaction_signature_key: Option<Vec<u8>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnconfirmedTransactionInfo {
+    pub tx_id: TxId,
+    pub signature: Signature,
+    pub status: TransactionStatus,
+    pub coinbase_block_height: Option<u64>,
+}
+
+impl UnconfirmedTransactionInfo {
+    pub fn is_coinbase(&self) -> bool {
+        if let Some(height) = self.coinbase_block_height {
+            height > 0
+        } else {
+            false
+        }
+    }
+}
+
+impl TryFrom<UnconfirmedTransactionInfoSql> for UnconfirmedTransactionInfo {
+    type Error = TransactionStorageError;
+
+    fn try_from(i: UnconfirmedTransactionInfoSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: (i.tx_id as u64).into(),
+            signature: Signature::new(
+                PublicKey::from_vec(&i.transaction_signature_nonce)?,
+                PrivateKey::from_vec(&i.transaction_signature_key)?,
             ),
-            last_send_timestamp: Some(Some(Utc::now().naive_utc())),
+            status: TransactionStatus::try_from(i.status)?,
+            coinbase_block_height: i.coinbase_block_height.map(|b| b as u64),
         })
+    }
+}
+
+#[derive(Clone, Queryable)]
+pub struct UnconfirmedTransactionInfoSql {
+    pub tx_id: i64,
+    pub status: i32,
+    pub transaction_signature_nonce: Vec<u8>,
+    pub transaction_signature_key: Vec<u8>,
+    pub coinbase_block_height: Option<i64>,
+}
+
+impl UnconfirmedTransactionInfoSql {
+    /// This method returns completed but unconfirmed transactions that were not imported or scanned
+    pub fn fetch_unconfirmed_transactions_info(
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<UnconfirmedTransactionInfoSql>, TransactionStorageError> {
+        let query_result = completed_transactions::table
+            .select((
+                completed_transactions::tx_id,
+                completed_transactions::status,
+                completed_transactions::transaction_signature_nonce,
+                completed_transactions::transaction_signature_key,
+                completed_transactions::coinbase_block_height,
+            ))
+            .filter(
+                completed_transactions::status
+                    .ne(TransactionStatus::Imported as i32)
+                    .and(completed_transactions::status.ne(TransactionStatus::FauxUnconfirmed as i32))
+                    .and(completed_transactions::status.ne(TransactionStatus::FauxConfirmed as i32))
+                    .and(completed_transactions::status.ne(TransactionStatus::ImportedHistory as i32))
+                    .and(
+                        completed_transactions::mined_height
+                            .is_null()
+                            .or(completed_transactions::status.eq(TransactionStatus::MinedUnconfirmed as i32)),
+                    ),
+            )
+            .filter(completed_transactions::cancelled.is_null())
+            .order_by(completed_transactions::tx_id)
+            .load::<UnconfirmedTransactionInfoSql>(conn)?;
+        Ok(query_result)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = outbound_message_queue)]
+pub(crate) struct OutboundMessageSql {
+    pub(crate) tx_id: i64,
+    pub(crate) message_type: i32,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) payload: Vec<u8>,
+    pub(crate) status: i32,
+    pub(crate) attempts: i32,
+    pub(crate) created_at: NaiveDateTime,
+    pub(crate) last_attempt_at: Option<NaiveDateTime>,
+}
+
+impl OutboundMessageSql {
+    /// Insert the message unless a message of the same type is already queued for this transaction, in which case the
+    /// existing message and its delivery history are kept
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_or_ignore_into(outbound_message_queue::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn find(
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<OutboundMessageSql>, TransactionStorageError> {
+        Ok(outbound_message_queue::table
+            .filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64))
+            .filter(outbound_message_queue::message_type.eq(i32::from(message_type)))
+            .first::<OutboundMessageSql>(conn)
+            .optional()?)
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            outbound_message_queue::table
+                .filter(outbound_message_queue::tx_id.eq(self.tx_id))
+                .filter(outbound_message_queue::message_type.eq(self.message_type)),
+        )
+        .set(outbound_message_queue::payload.eq(&self.payload))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn index_by_status(
+        status: OutboundMessageStatus,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<OutboundMessageSql>, TransactionStorageError> {
+        Ok(outbound_message_queue::table
+            .filter(outbound_message_queue::status.eq(i32::from(status)))
+            .order_by(outbound_message_queue::created_at.asc())
+            .load::<OutboundMessageSql>(conn)?)
+    }
+
+    pub fn record_attempt(
+        tx_id: TxId,
+        message_type: OutboundMessageType,
+        status: OutboundMessageStatus,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            outbound_message_queue::table
+                .filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(outbound_message_queue::message_type.eq(i32::from(message_type))),
+        )
+        .set((
+            outbound_message_queue::status.eq(i32::from(status)),
+            outbound_message_queue::attempts.eq(outbound_message_queue::attempts + 1),
+            outbound_message_queue::last_attempt_at.eq(Some(Utc::now().naive_utc())),
+        ))
         .execute(conn)
         .num_rows_affected_or_not_found(1)?;
 
         Ok(())
     }
 
-    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        let num_deleted =
-            diesel::delete(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(&self.tx_id)))
-                .execute(conn)?;
+    pub fn delete_by_tx_id(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(outbound_message_queue::table.filter(outbound_message_queue::tx_id.eq(tx_id.as_u64() as i64)))
+            .execute(conn)?;
+        Ok(())
+    }
 
-        if num_deleted == 0 {
-            return Err(TransactionStorageError::ValuesNotFound);
-        }
+    /// Delete messages that are no longer pending for transactions that are no longer being negotiated, along with any
+    /// message queued before `expired_before`
+    pub fn delete_finished(
+        expired_before: NaiveDateTime,
+        conn: &mut SqliteConnection,
+    ) -> Result<usize, TransactionStorageError> {
+        conn.transaction::<_, TransactionStorageError, _>(|conn| {
+            let finished = diesel::delete(
+                outbound_message_queue::table
+                    .filter(outbound_message_queue::status.ne(i32::from(OutboundMessageStatus::Pending)))
+                    .filter(diesel::dsl::not(
+                        outbound_message_queue::tx_id
+                            .eq_any(outbound_transactions::table.select(outbound_transactions::tx_id)),
+                    ))
+                    .filter(diesel::dsl::not(
+                        outbound_message_queue::tx_id
+                            .eq_any(inbound_transactions::table.select(inbound_transactions::tx_id)),
+                    )),
+            )
+            .execute(conn)?;
+            let expired = diesel::delete(
+                outbound_message_queue::table.filter(outbound_message_queue::created_at.lt(expired_before)),
+            )
+            .execute(conn)?;
+            Ok(finished + expired)
+        })
+    }
+
+    pub(crate) fn try_from(
+        m: QueuedOutboundMessage,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let m = Self {
+            tx_id: m.tx_id.as_u64() as i64,
+            message_type: i32::from(m.message_type),
+            destination_address: m.destination_address.to_bytes().to_vec(),
+            payload: m.payload,
+            status: i32::from(m.status),
+            attempts: m.attempts as i32,
+            created_at: m.created_at,
+            last_attempt_at: m.last_attempt_at,
+        };
+        m.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = transaction_counterparty_aliases)]
+pub(crate) struct CounterpartyAliasSql {
+    pub(crate) tx_id: i64,
+    pub(crate) alias: String,
+}
 
+impl CounterpartyAliasSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_counterparty_aliases::table)
+            .values(self.clone())
+            .execute(conn)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn update(
-        &self,
-        update: UpdateInboundTransactionSql,
+    pub fn index_by_tx_ids(
+        tx_ids: &[TxId],
         conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        let num_updated =
-            diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(&self.tx_id)))
-                .set(update)
-                .execute(conn)?;
-
-        if num_updated == 0 {
-            return Err(TransactionStorageError::UnexpectedResult(
-                "Updating inbound transactions failed. No rows were affected".to_string(),
-            ));
+    ) -> Result<Vec<CounterpartyAliasSql>, TransactionStorageError> {
+        // Keep each query well under SQLite's bound parameter limit
+        let mut aliases = Vec::with_capacity(tx_ids.len());
+        for chunk in tx_ids.chunks(500) {
+            aliases.extend(
+                transaction_counterparty_aliases::table
+                    .filter(transaction_counterparty_aliases::tx_id.eq_any(chunk.iter().map(|id| id.as_u64() as i64)))
+                    .load::<CounterpartyAliasSql>(conn)?,
+            );
         }
-
-        Ok(())
+        Ok(aliases)
     }
+}
 
-    pub fn find_and_set_cancelled(
-        tx_id: TxId,
-        cancelled: bool,
-        conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        diesel::update(inbound_transactions::table.filter(inbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateInboundTransactionSql {
-                cancelled: Some(i32::from(cancelled)),
-                direct_send_success: None,
-                receiver_protocol: None,
-                send_count: None,
-                last_send_timestamp: None,
-            })
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = transaction_memos)]
+pub(crate) struct TransactionMemoSql {
+    pub(crate) tx_id: i64,
+    pub(crate) memo: Vec<u8>,
+}
 
+impl TransactionMemoSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(transaction_memos::table)
+            .values(self.clone())
+            .execute(conn)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        self.update(
-            UpdateInboundTransactionSql {
-                cancelled: None,
-                direct_send_success: None,
-                receiver_protocol: Some(self.receiver_protocol.clone()),
-                send_count: None,
-                last_send_timestamp: None,
-            },
-            conn,
-        )
+        diesel::update(transaction_memos::table.filter(transaction_memos::tx_id.eq(self.tx_id)))
+            .set(transaction_memos::memo.eq(&self.memo))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
     }
 
-    fn try_from(i: InboundTransaction, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
-        let receiver_protocol_bytes = bincode::serialize(&i.receiver_protocol)
-            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
-        let i = Self {
-            tx_id: i.tx_id.as_u64() as i64,
-            source_address: i.source_address.to_bytes().to_vec(),
-            amount: u64::from(i.amount) as i64,
-            receiver_protocol: receiver_protocol_bytes.to_vec(),
-            message: i.message,
-            timestamp: i.timestamp,
-            cancelled: i32::from(i.cancelled),
-            direct_send_success: i32::from(i.direct_send_success),
-            send_count: i.send_count as i32,
-            last_send_timestamp: i.last_send_timestamp,
-        };
-        i.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    pub fn find(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<TransactionMemoSql>, TransactionStorageError> {
+        Ok(transaction_memos::table
+            .filter(transaction_memos::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<TransactionMemoSql>(conn)
+            .optional()?)
     }
 }
 
-impl Encryptable<XChaCha20Poly1305> for InboundTransactionSql {
+impl Encryptable<XChaCha20Poly1305> for TransactionMemoSql {
     fn domain(&self, field_name: &'static str) -> Vec<u8> {
         [
-            Self::INBOUND_TRANSACTION,
+            Self::TRANSACTION_MEMO,
             self.tx_id.to_le_bytes().as_slice(),
             field_name.as_bytes(),
         ]
@@ -1370,248 +3373,204 @@ impl Encryptable<XChaCha20Poly1305> for InboundTransactionSql {
     }
 
     fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.receiver_protocol = encrypt_bytes_integral_nonce(
-            cipher,
-            self.domain("receiver_protocol"),
-            Hidden::hide(self.receiver_protocol),
-        )?;
-
+        self.memo = encrypt_bytes_integral_nonce(cipher, self.domain("memo"), Hidden::hide(self.memo.clone()))?;
         Ok(self)
     }
 
     fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.receiver_protocol =
-            decrypt_bytes_integral_nonce(cipher, self.domain("receiver_protocol"), &self.receiver_protocol)?;
-
+        self.memo = decrypt_bytes_integral_nonce(cipher, self.domain("memo"), &self.memo)?;
         Ok(self)
     }
 }
 
-impl InboundTransaction {
-    fn try_from(i: InboundTransactionSql, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
-        let i = i.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
-        Ok(Self {
-            tx_id: (i.tx_id as u64).into(),
-            source_address: TariAddress::from_bytes(&i.source_address).map_err(TransactionKeyError::Source)?,
-            amount: MicroMinotari::from(i.amount as u64),
-            receiver_protocol: bincode::deserialize(&i.receiver_protocol)
-                .map_err(|e| TransactionStorageError::BincodeDeserialize(e.to_string()))?,
-            status: TransactionStatus::Pending,
-            message: i.message,
-            timestamp: i.timestamp,
-            cancelled: i.cancelled != 0,
-            direct_send_success: i.direct_send_success != 0,
-            send_count: i.send_count as u32,
-            last_send_timestamp: i.last_send_timestamp,
-        })
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = transaction_tags)]
+pub(crate) struct TransactionTagSql {
+    pub(crate) tx_id: i64,
+    pub(crate) tag: String,
+}
+
+impl TransactionTagSql {
+    pub fn index_by_tx_ids(
+        tx_ids: &[TxId],
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<TransactionTagSql>, TransactionStorageError> {
+        // Keep each query well under SQLite's bound parameter limit
+        let mut tags = Vec::with_capacity(tx_ids.len());
+        for chunk in tx_ids.chunks(500) {
+            tags.extend(
+                transaction_tags::table
+                    .filter(transaction_tags::tx_id.eq_any(chunk.iter().map(|id| id.as_u64() as i64)))
+                    .order((transaction_tags::tx_id.asc(), transaction_tags::tag.asc()))
+                    .load::<TransactionTagSql>(conn)?,
+            );
+        }
+        Ok(tags)
     }
 }
 
-#[derive(AsChangeset)]
-#[diesel(table_name = inbound_transactions)]
-pub struct UpdateInboundTransactionSql {
-    cancelled: Option<i32>,
-    direct_send_success: Option<i32>,
-    receiver_protocol: Option<Vec<u8>>,
-    send_count: Option<i32>,
-    last_send_timestamp: Option<Option<NaiveDateTime>>,
+/// Escapes the LIKE wildcards in `s` so that it is matched literally, using `\` as the escape character
+pub(crate) fn escape_like_pattern(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
-/// A structure to represent a Sql compatible version of the OutboundTransaction struct
-#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
-#[diesel(table_name = outbound_transactions)]
-struct OutboundTransactionSql {
-    tx_id: i64,
-    destination_address: Vec<u8>,
-    amount: i64,
-    fee: i64,
-    sender_protocol: Vec<u8>,
-    message: String,
-    timestamp: NaiveDateTime,
-    cancelled: i32,
-    direct_send_success: i32,
-    send_count: i32,
-    last_send_timestamp: Option<NaiveDateTime>,
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = batched_payments)]
+pub(crate) struct NewBatchedPaymentSql {
+    pub(crate) tx_id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) message: String,
+    pub(crate) output_hash: Vec<u8>,
 }
 
-impl OutboundTransactionSql {
+impl NewBatchedPaymentSql {
     pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::insert_into(outbound_transactions::table)
+        diesel::insert_into(batched_payments::table)
             .values(self.clone())
             .execute(conn)?;
         Ok(())
     }
+}
 
-    #[allow(dead_code)]
-    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<OutboundTransactionSql>, TransactionStorageError> {
-        Ok(outbound_transactions::table.load::<OutboundTransactionSql>(conn)?)
-    }
-
-    pub fn index_by_cancelled(
-        conn: &mut SqliteConnection,
-        cancelled: bool,
-    ) -> Result<Vec<OutboundTransactionSql>, TransactionStorageError> {
-        Ok(outbound_transactions::table
-            .filter(outbound_transactions::cancelled.eq(i32::from(cancelled)))
-            .load::<OutboundTransactionSql>(conn)?)
+impl From<BatchedPayment> for NewBatchedPaymentSql {
+    fn from(p: BatchedPayment) -> Self {
+        Self {
+            tx_id: p.tx_id.as_u64() as i64,
+            destination_address: p.destination_address.to_bytes().to_vec(),
+            amount: u64::from(p.amount) as i64,
+            message: p.message,
+            output_hash: p.output_hash.to_vec(),
+        }
     }
+}
 
-    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<OutboundTransactionSql, TransactionStorageError> {
-        Ok(outbound_transactions::table
-            .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .first::<OutboundTransactionSql>(conn)?)
-    }
+#[derive(Clone, Debug, Queryable, PartialEq)]
+#[diesel(table_name = batched_payments)]
+pub(crate) struct BatchedPaymentSql {
+    pub(crate) id: i32,
+    pub(crate) tx_id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) message: String,
+    pub(crate) output_hash: Vec<u8>,
+}
 
-    pub fn find_by_cancelled(
+impl BatchedPaymentSql {
+    pub fn index_by_tx_id(
         tx_id: TxId,
-        cancelled: bool,
         conn: &mut SqliteConnection,
-    ) -> Result<OutboundTransactionSql, TransactionStorageError> {
-        Ok(outbound_transactions::table
-            .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .filter(outbound_transactions::cancelled.eq(i32::from(cancelled)))
-            .first::<OutboundTransactionSql>(conn)?)
+    ) -> Result<Vec<BatchedPaymentSql>, TransactionStorageError> {
+        Ok(batched_payments::table
+            .filter(batched_payments::tx_id.eq(tx_id.as_u64() as i64))
+            .order(batched_payments::id.asc())
+            .load::<BatchedPaymentSql>(conn)?)
     }
 
-    pub fn mark_direct_send_success(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::update(
-            outbound_transactions::table
-                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
-        )
-        .set(UpdateOutboundTransactionSql {
-            cancelled: None,
-            direct_send_success: Some(1i32),
-            sender_protocol: None,
-            send_count: None,
-            last_send_timestamp: None,
-        })
-        .execute(conn)
-        .num_rows_affected_or_not_found(1)?;
-
+    pub fn delete_by_tx_id(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(batched_payments::table.filter(batched_payments::tx_id.eq(tx_id.as_u64() as i64)))
+            .execute(conn)?;
         Ok(())
     }
+}
 
-    pub fn complete_outbound_transaction(
-        tx_id: TxId,
-        conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        diesel::delete(
-            outbound_transactions::table
-                .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(outbound_transactions::cancelled.eq(i32::from(false))),
-        )
-        .execute(conn)
-        .num_rows_affected_or_not_found(1)?;
+impl TryFrom<BatchedPaymentSql> for BatchedPayment {
+    type Error = TransactionStorageError;
 
-        Ok(())
+    fn try_from(p: BatchedPaymentSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            tx_id: (p.tx_id as u64).into(),
+            destination_address: TariAddress::from_bytes(&p.destination_address)?,
+            amount: MicroMinotari::from(p.amount as u64),
+            message: p.message,
+            output_hash: HashOutput::try_from(p.output_hash.as_slice())
+                .map_err(|e| TransactionStorageError::ByteArrayError(e.to_string()))?,
+        })
     }
+}
 
-    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateOutboundTransactionSql {
-                cancelled: None,
-                direct_send_success: None,
-                sender_protocol: None,
-                send_count: Some(
-                    if let Some(value) = outbound_transactions::table
-                        .filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                        .select(outbound_transactions::send_count)
-                        .load::<i32>(conn)?
-                        .first()
-                    {
-                        value + 1
-                    } else {
-                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
-                    },
-                ),
-                last_send_timestamp: Some(Some(Utc::now().naive_utc())),
-            })
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = superseded_transactions)]
+pub(crate) struct SupersededTransactionSql {
+    pub(crate) id: i64,
+    pub(crate) tx_id: i64,
+    pub(crate) fee: i64,
+    pub(crate) transaction_protocol: Vec<u8>,
+    pub(crate) batched_payments: String,
+    pub(crate) superseded_at: NaiveDateTime,
+}
 
+impl SupersededTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(superseded_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
         Ok(())
     }
 
-    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::delete(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(&self.tx_id)))
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
-        Ok(())
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<SupersededTransactionSql>, TransactionStorageError> {
+        Ok(superseded_transactions::table
+            .order(superseded_transactions::superseded_at.asc())
+            .load::<SupersededTransactionSql>(conn)?)
     }
 
-    #[allow(dead_code)]
-    pub fn update(
-        &self,
-        update: UpdateOutboundTransactionSql,
+    pub fn find(
+        id: u64,
         conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(&self.tx_id)))
-            .set(update)
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
-
-        Ok(())
+    ) -> Result<Option<SupersededTransactionSql>, TransactionStorageError> {
+        Ok(superseded_transactions::table
+            .filter(superseded_transactions::id.eq(id as i64))
+            .first::<SupersededTransactionSql>(conn)
+            .optional()?)
     }
 
-    pub fn find_and_set_cancelled(
-        tx_id: TxId,
-        cancelled: bool,
-        conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        diesel::update(outbound_transactions::table.filter(outbound_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateOutboundTransactionSql {
-                cancelled: Some(i32::from(cancelled)),
-                direct_send_success: None,
-                sender_protocol: None,
-                send_count: None,
-                last_send_timestamp: None,
-            })
+    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(superseded_transactions::table.filter(superseded_transactions::id.eq(self.id)))
             .execute(conn)
             .num_rows_affected_or_not_found(1)?;
-
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        self.update(
-            UpdateOutboundTransactionSql {
-                cancelled: None,
-                direct_send_success: None,
-                sender_protocol: Some(self.sender_protocol.clone()),
-                send_count: None,
-                last_send_timestamp: None,
-            },
-            conn,
-        )
+        diesel::update(superseded_transactions::table.filter(superseded_transactions::id.eq(self.id)))
+            .set(superseded_transactions::transaction_protocol.eq(&self.transaction_protocol))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
     }
 
-    fn try_from(o: OutboundTransaction, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
-        let sender_protocol_bytes = bincode::serialize(&o.sender_protocol)
-            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
-        let outbound_tx = Self {
-            tx_id: o.tx_id.as_u64() as i64,
-            destination_address: o.destination_address.to_bytes().to_vec(),
-            amount: u64::from(o.amount) as i64,
-            fee: u64::from(o.fee) as i64,
-            sender_protocol: sender_protocol_bytes.to_vec(),
-            message: o.message,
-            timestamp: o.timestamp,
-            cancelled: i32::from(o.cancelled),
-            direct_send_success: i32::from(o.direct_send_success),
-            send_count: o.send_count as i32,
-            last_send_timestamp: o.last_send_timestamp,
-        };
-
-        outbound_tx.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    /// Keeps the current version of a completed transaction, along with its batched payments, as it is superseded
+    pub(crate) fn try_from(
+        version: &CompletedTransaction,
+        batched_payments: &[BatchedPayment],
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let transaction_bytes = bincode::serialize(&version.transaction)
+            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+        Self {
+            id: OsRng.next_u64() as i64,
+            tx_id: version.tx_id.as_u64() as i64,
+            fee: u64::from(version.fee) as i64,
+            transaction_protocol: transaction_bytes,
+            batched_payments: serde_json::to_string(batched_payments)?,
+            superseded_at: Utc::now().naive_utc(),
+        }
+        .encrypt(cipher)
+        .map_err(TransactionStorageError::AeadError)
     }
 }
 
-impl Encryptable<XChaCha20Poly1305> for OutboundTransactionSql {
+impl Encryptable<XChaCha20Poly1305> for SupersededTransactionSql {
     fn domain(&self, field_name: &'static str) -> Vec<u8> {
         [
-            Self::OUTBOUND_TRANSACTION,
+            Self::SUPERSEDED_TRANSACTION,
             self.tx_id.to_le_bytes().as_slice(),
             field_name.as_bytes(),
         ]
@@ -1620,413 +3579,664 @@ impl Encryptable<XChaCha20Poly1305> for OutboundTransactionSql {
     }
 
     fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.sender_protocol = encrypt_bytes_integral_nonce(
+        self.transaction_protocol = encrypt_bytes_integral_nonce(
             cipher,
-            self.domain("sender_protocol"),
-            Hidden::hide(self.sender_protocol),
+            self.domain("transaction_protocol"),
+            Hidden::hide(self.transaction_protocol),
         )?;
-
         Ok(self)
     }
 
     fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.sender_protocol =
-            decrypt_bytes_integral_nonce(cipher, self.domain("sender_protocol"), &self.sender_protocol)?;
+        self.transaction_protocol =
+            decrypt_bytes_integral_nonce(cipher, self.domain("transaction_protocol"), &self.transaction_protocol)?;
         Ok(self)
     }
 }
 
-impl OutboundTransaction {
-    fn try_from(o: OutboundTransactionSql, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
-        let mut o = o.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
-        let outbound_tx = Self {
-            tx_id: (o.tx_id as u64).into(),
-            destination_address: TariAddress::from_bytes(&o.destination_address)
-                .map_err(TransactionKeyError::Destination)?,
-            amount: MicroMinotari::from(o.amount as u64),
-            fee: MicroMinotari::from(o.fee as u64),
-            sender_protocol: bincode::deserialize(&o.sender_protocol)
+impl SupersededTransaction {
+    pub(crate) fn try_from(
+        s: SupersededTransactionSql,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let mut s = s.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        let version = Self {
+            id: s.id as u64,
+            tx_id: (s.tx_id as u64).into(),
+            fee: MicroMinotari::from(s.fee as u64),
+            transaction: bincode::deserialize(&s.transaction_protocol)
                 .map_err(|e| TransactionStorageError::BincodeDeserialize(e.to_string()))?,
-            status: TransactionStatus::Pending,
-            message: o.message,
-            timestamp: o.timestamp,
-            cancelled: o.cancelled != 0,
-            direct_send_success: o.direct_send_success != 0,
-            send_count: o.send_count as u32,
-            last_send_timestamp: o.last_send_timestamp,
+            batched_payments: serde_json::from_str(&s.batched_payments)?,
+            superseded_at: s.superseded_at,
         };
 
         // zeroize decrypted data
-        o.sender_protocol.zeroize();
+        s.transaction_protocol.zeroize();
 
-        Ok(outbound_tx)
+        Ok(version)
     }
 }
 
-#[derive(AsChangeset)]
-#[diesel(table_name = outbound_transactions)]
-pub struct UpdateOutboundTransactionSql {
-    cancelled: Option<i32>,
-    direct_send_success: Option<i32>,
-    sender_protocol: Option<Vec<u8>>,
-    send_count: Option<i32>,
-    last_send_timestamp: Option<Option<NaiveDateTime>>,
-}
-
-/// A structure to represent a Sql compatible version of the CompletedTransaction struct
 #[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
-#[diesel(table_name = completed_transactions)]
-pub struct CompletedTransactionSql {
-    tx_id: i64,
-    source_address: Vec<u8>,
-    destination_address: Vec<u8>,
-    amount: i64,
-    fee: i64,
-    transaction_protocol: Vec<u8>,
-    status: i32,
-    message: String,
-    timestamp: NaiveDateTime,
-    cancelled: Option<i32>,
-    direction: Option<i32>,
-    coinbase_block_height: Option<i64>,
-    send_count: i32,
-    last_send_timestamp: Option<NaiveDateTime>,
-    confirmations: Option<i64>,
-    mined_height: Option<i64>,
-    mined_in_block: Option<Vec<u8>>,
-    mined_timestamp: Option<NaiveDateTime>,
-    transaction_signature_nonce: Vec<u8>,
-    transaction_signature_key: Vec<u8>,
+#[diesel(table_name = scheduled_transactions)]
+pub(crate) struct ScheduledTransactionSql {
+    pub(crate) id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) fee_per_gram: i64,
+    pub(crate) message: String,
+    pub(crate) one_sided: i32,
+    pub(crate) execute_at_height: Option<i64>,
+    pub(crate) execute_at_time: Option<NaiveDateTime>,
+    pub(crate) expires_at_height: Option<i64>,
+    pub(crate) expires_at_time: Option<NaiveDateTime>,
+    pub(crate) status: i32,
+    pub(crate) tx_id: Option<i64>,
+    pub(crate) failure_reason: Option<String>,
+    pub(crate) created_at: NaiveDateTime,
 }
 
-impl CompletedTransactionSql {
+impl ScheduledTransactionSql {
     pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        diesel::insert_into(completed_transactions::table)
+        diesel::insert_into(scheduled_transactions::table)
             .values(self.clone())
             .execute(conn)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
-    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
-        Ok(completed_transactions::table.load::<CompletedTransactionSql>(conn)?)
+    pub fn index(
+        status: Option<ScheduledTransactionStatus>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<ScheduledTransactionSql>, TransactionStorageError> {
+        let mut query = scheduled_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(scheduled_transactions::status.eq(i32::from(status)));
+        }
+        Ok(query
+            .order_by(scheduled_transactions::created_at.asc())
+            .load::<ScheduledTransactionSql>(conn)?)
     }
 
-    pub fn index_by_cancelled(
+    pub fn update_status(
+        id: u64,
+        from: ScheduledTransactionStatus,
+        to: ScheduledTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
         conn: &mut SqliteConnection,
-        cancelled: bool,
-    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
-        let mut query = completed_transactions::table.into_boxed();
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            scheduled_transactions::table
+                .filter(scheduled_transactions::id.eq(id as i64))
+                .filter(scheduled_transactions::status.eq(i32::from(from))),
+        )
+        .set((
+            scheduled_transactions::status.eq(i32::from(to)),
+            scheduled_transactions::tx_id.eq(tx_id.map(|id| id.as_u64() as i64)),
+            scheduled_transactions::failure_reason.eq(failure_reason),
+        ))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
 
-        query = if cancelled {
-            query.filter(completed_transactions::cancelled.is_not_null())
-        } else {
-            query.filter(completed_transactions::cancelled.is_null())
-        };
+fn split_height_or_time(value: Option<HeightOrTime>) -> (Option<i64>, Option<NaiveDateTime>) {
+    match value {
+        Some(HeightOrTime::Height(height)) => (Some(height as i64), None),
+        Some(HeightOrTime::Time(time)) => (None, Some(time)),
+        None => (None, None),
+    }
+}
 
-        Ok(query.load::<CompletedTransactionSql>(conn)?)
+fn join_height_or_time(height: Option<i64>, time: Option<NaiveDateTime>) -> Option<HeightOrTime> {
+    match (height, time) {
+        (Some(height), _) => Some(HeightOrTime::Height(height as u64)),
+        (None, Some(time)) => Some(HeightOrTime::Time(time)),
+        (None, None) => None,
     }
+}
 
-    pub fn index_by_status_and_cancelled(
-        status: TransactionStatus,
-        cancelled: bool,
-        conn: &mut SqliteConnection,
-    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
-        let mut query = completed_transactions::table.into_boxed();
-        query = if cancelled {
-            query.filter(completed_transactions::cancelled.is_not_null())
-        } else {
-            query.filter(completed_transactions::cancelled.is_null())
-        };
-        Ok(query
-            .filter(completed_transactions::status.eq(status as i32))
-            .load::<CompletedTransactionSql>(conn)?)
+impl From<ScheduledTransaction> for ScheduledTransactionSql {
+    fn from(s: ScheduledTransaction) -> Self {
+        let (execute_at_height, execute_at_time) = split_height_or_time(Some(s.execute_at));
+        let (expires_at_height, expires_at_time) = split_height_or_time(s.expires_at);
+        Self {
+            id: s.id as i64,
+            destination_address: s.destination.to_bytes().to_vec(),
+            amount: u64::from(s.amount) as i64,
+            fee_per_gram: u64::from(s.fee_per_gram) as i64,
+            message: s.message,
+            one_sided: i32::from(s.one_sided),
+            execute_at_height,
+            execute_at_time,
+            expires_at_height,
+            expires_at_time,
+            status: i32::from(s.status),
+            tx_id: s.tx_id.map(|id| id.as_u64() as i64),
+            failure_reason: s.failure_reason,
+            created_at: s.created_at,
+        }
     }
+}
 
-    pub fn index_by_status_and_cancelled_from_block_height(
-        status: TransactionStatus,
-        cancelled: bool,
-        block_height: i64,
-        conn: &mut SqliteConnection,
-    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
-        let mut query = completed_transactions::table.into_boxed();
-        query = if cancelled {
-            query.filter(completed_transactions::cancelled.is_not_null())
-        } else {
-            query.filter(completed_transactions::cancelled.is_null())
-        };
+impl TryFrom<ScheduledTransactionSql> for ScheduledTransaction {
+    type Error = TransactionStorageError;
 
-        Ok(query
-            .filter(completed_transactions::status.eq(status as i32))
-            .filter(completed_transactions::mined_height.ge(block_height))
-            .load::<CompletedTransactionSql>(conn)?)
+    fn try_from(s: ScheduledTransactionSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: s.id as u64,
+            destination: TariAddress::from_bytes(&s.destination_address)?,
+            amount: MicroMinotari::from(s.amount as u64),
+            fee_per_gram: MicroMinotari::from(s.fee_per_gram as u64),
+            message: s.message,
+            one_sided: s.one_sided != 0,
+            execute_at: join_height_or_time(s.execute_at_height, s.execute_at_time).ok_or_else(|| {
+                TransactionStorageError::UnexpectedResult("Scheduled transaction has no execution point".to_string())
+            })?,
+            expires_at: join_height_or_time(s.expires_at_height, s.expires_at_time),
+            status: ScheduledTransactionStatus::try_from(s.status)?,
+            tx_id: s.tx_id.map(|id| (id as u64).into()),
+            failure_reason: s.failure_reason,
+            created_at: s.created_at,
+        })
     }
+}
 
-    pub fn index_coinbase_at_block_height(
-        block_height: i64,
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = queued_transactions)]
+pub(crate) struct QueuedTransactionSql {
+    pub(crate) id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) fee_per_gram: i64,
+    pub(crate) message: String,
+    pub(crate) one_sided: i32,
+    pub(crate) status: i32,
+    pub(crate) tx_id: Option<i64>,
+    pub(crate) failure_reason: Option<String>,
+    pub(crate) queued_at: NaiveDateTime,
+}
+
+impl QueuedTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(queued_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn index(
+        status: Option<QueuedTransactionStatus>,
         conn: &mut SqliteConnection,
-    ) -> Result<Vec<CompletedTransactionSql>, TransactionStorageError> {
-        Ok(completed_transactions::table
-            .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
-            .filter(completed_transactions::coinbase_block_height.eq(block_height))
-            .load::<CompletedTransactionSql>(conn)?)
+    ) -> Result<Vec<QueuedTransactionSql>, TransactionStorageError> {
+        let mut query = queued_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(queued_transactions::status.eq(i32::from(status)));
+        }
+        Ok(query
+            .order_by(queued_transactions::queued_at.asc())
+            .load::<QueuedTransactionSql>(conn)?)
     }
 
-    pub fn find_and_abandon_coinbase(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        let _ = diesel::update(
-            completed_transactions::table
-                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(completed_transactions::cancelled.is_null())
-                .filter(completed_transactions::coinbase_block_height.is_not_null()),
+    pub fn update_status(
+        id: u64,
+        from: QueuedTransactionStatus,
+        to: QueuedTransactionStatus,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            queued_transactions::table
+                .filter(queued_transactions::id.eq(id as i64))
+                .filter(queued_transactions::status.eq(i32::from(from))),
         )
-        .set(UpdateCompletedTransactionSql {
-            cancelled: Some(Some(TxCancellationReason::AbandonedCoinbase as i32)),
-            ..Default::default()
-        })
+        .set((
+            queued_transactions::status.eq(i32::from(to)),
+            queued_transactions::tx_id.eq(tx_id.map(|id| id.as_u64() as i64)),
+            queued_transactions::failure_reason.eq(failure_reason),
+        ))
         .execute(conn)
         .num_rows_affected_or_not_found(1)?;
-
         Ok(())
     }
+}
 
-    pub fn find(tx_id: TxId, conn: &mut SqliteConnection) -> Result<CompletedTransactionSql, TransactionStorageError> {
-        Ok(completed_transactions::table
-            .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .first::<CompletedTransactionSql>(conn)?)
+impl From<QueuedTransaction> for QueuedTransactionSql {
+    fn from(q: QueuedTransaction) -> Self {
+        Self {
+            id: q.id as i64,
+            destination_address: q.destination.to_bytes().to_vec(),
+            amount: u64::from(q.amount) as i64,
+            fee_per_gram: u64::from(q.fee_per_gram) as i64,
+            message: q.message,
+            one_sided: i32::from(q.one_sided),
+            status: i32::from(q.status),
+            tx_id: q.tx_id.map(|id| id.as_u64() as i64),
+            failure_reason: q.failure_reason,
+            queued_at: q.queued_at,
+        }
     }
+}
 
-    pub fn find_by_cancelled(
-        tx_id: TxId,
-        cancelled: bool,
-        conn: &mut SqliteConnection,
-    ) -> Result<CompletedTransactionSql, TransactionStorageError> {
-        let mut query = completed_transactions::table
-            .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-            .into_boxed();
+impl TryFrom<QueuedTransactionSql> for QueuedTransaction {
+    type Error = TransactionStorageError;
 
-        query = if cancelled {
-            query.filter(completed_transactions::cancelled.is_not_null())
-        } else {
-            query.filter(completed_transactions::cancelled.is_null())
-        };
+    fn try_from(q: QueuedTransactionSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: q.id as u64,
+            destination: TariAddress::from_bytes(&q.destination_address)?,
+            amount: MicroMinotari::from(q.amount as u64),
+            fee_per_gram: MicroMinotari::from(q.fee_per_gram as u64),
+            message: q.message,
+            one_sided: q.one_sided != 0,
+            status: QueuedTransactionStatus::try_from(q.status)?,
+            tx_id: q.tx_id.map(|id| (id as u64).into()),
+            failure_reason: q.failure_reason,
+            queued_at: q.queued_at,
+        })
+    }
+}
 
-        Ok(query.first::<CompletedTransactionSql>(conn)?)
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = recurring_payments)]
+pub(crate) struct RecurringPaymentSql {
+    pub(crate) id: i64,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) fee_per_gram: i64,
+    pub(crate) message: String,
+    pub(crate) one_sided: i32,
+    pub(crate) interval_secs: i64,
+    pub(crate) next_payment_at: NaiveDateTime,
+    pub(crate) end_at: Option<NaiveDateTime>,
+    pub(crate) status: i32,
+    pub(crate) payments_made: i64,
+    pub(crate) last_tx_id: Option<i64>,
+    pub(crate) failure_reason: Option<String>,
+    pub(crate) created_at: NaiveDateTime,
+}
+
+impl RecurringPaymentSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(recurring_payments::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
     }
 
-    pub fn reject_completed_transaction(
-        tx_id: TxId,
-        reason: TxCancellationReason,
+    pub fn index(
+        status: Option<RecurringPaymentStatus>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<RecurringPaymentSql>, TransactionStorageError> {
+        let mut query = recurring_payments::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(recurring_payments::status.eq(i32::from(status)));
+        }
+        Ok(query
+            .order_by(recurring_payments::created_at.asc())
+            .load::<RecurringPaymentSql>(conn)?)
+    }
+
+    pub fn update_status(
+        id: u64,
+        from: &[RecurringPaymentStatus],
+        to: RecurringPaymentStatus,
+        next_payment_at: Option<NaiveDateTime>,
+        failure_reason: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        let query = recurring_payments::table
+            .filter(recurring_payments::id.eq(id as i64))
+            .filter(recurring_payments::status.eq_any(from.iter().map(|s| i32::from(*s))));
+        match next_payment_at {
+            Some(next_payment_at) => diesel::update(query)
+                .set((
+                    recurring_payments::status.eq(i32::from(to)),
+                    recurring_payments::next_payment_at.eq(next_payment_at),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(conn),
+            None => diesel::update(query)
+                .set((
+                    recurring_payments::status.eq(i32::from(to)),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(conn),
+        }
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn advance(
+        id: u64,
+        due_at: NaiveDateTime,
+        next_payment_at: NaiveDateTime,
         conn: &mut SqliteConnection,
     ) -> Result<(), TransactionStorageError> {
         diesel::update(
-            completed_transactions::table
-                .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                .filter(completed_transactions::cancelled.is_null()),
+            recurring_payments::table
+                .filter(recurring_payments::id.eq(id as i64))
+                .filter(recurring_payments::status.eq(i32::from(RecurringPaymentStatus::Active)))
+                .filter(recurring_payments::next_payment_at.eq(due_at)),
         )
-        .set(UpdateCompletedTransactionSql {
-            cancelled: Some(Some(reason as i32)),
-            status: Some(TransactionStatus::Rejected as i32),
-            ..Default::default()
-        })
+        .set(recurring_payments::next_payment_at.eq(next_payment_at))
         .execute(conn)
         .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
 
+    pub fn record_result(
+        id: u64,
+        tx_id: Option<TxId>,
+        failure_reason: Option<String>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        let query = recurring_payments::table.filter(recurring_payments::id.eq(id as i64));
+        match tx_id {
+            Some(tx_id) => diesel::update(query)
+                .set((
+                    recurring_payments::payments_made.eq(recurring_payments::payments_made + 1),
+                    recurring_payments::last_tx_id.eq(tx_id.as_u64() as i64),
+                    recurring_payments::failure_reason.eq(failure_reason),
+                ))
+                .execute(conn),
+            None => diesel::update(query)
+                .set(recurring_payments::failure_reason.eq(failure_reason))
+                .execute(conn),
+        }
+        .num_rows_affected_or_not_found(1)?;
         Ok(())
     }
+}
 
-    pub fn increment_send_count(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        // This query uses a sub-query to retrieve an existing value in the table
-        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateCompletedTransactionSql {
-                send_count: Some(
-                    if let Some(value) = completed_transactions::table
-                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                        .select(completed_transactions::send_count)
-                        .load::<i32>(conn)?
-                        .first()
-                    {
-                        value + 1
-                    } else {
-                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
-                    },
-                ),
-                last_send_timestamp: Some(Some(Utc::now().naive_utc())),
-                ..Default::default()
-            })
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
+impl From<RecurringPayment> for RecurringPaymentSql {
+    fn from(p: RecurringPayment) -> Self {
+        Self {
+            id: p.id as i64,
+            destination_address: p.destination.to_bytes().to_vec(),
+            amount: u64::from(p.amount) as i64,
+            fee_per_gram: u64::from(p.fee_per_gram) as i64,
+            message: p.message,
+            one_sided: i32::from(p.one_sided),
+            interval_secs: p.interval.as_secs() as i64,
+            next_payment_at: p.next_payment_at,
+            end_at: p.end_at,
+            status: i32::from(p.status),
+            payments_made: p.payments_made as i64,
+            last_tx_id: p.last_tx_id.map(|id| id.as_u64() as i64),
+            failure_reason: p.failure_reason,
+            created_at: p.created_at,
+        }
+    }
+}
+
+impl TryFrom<RecurringPaymentSql> for RecurringPayment {
+    type Error = TransactionStorageError;
+
+    fn try_from(p: RecurringPaymentSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: p.id as u64,
+            destination: TariAddress::from_bytes(&p.destination_address)?,
+            amount: MicroMinotari::from(p.amount as u64),
+            fee_per_gram: MicroMinotari::from(p.fee_per_gram as u64),
+            message: p.message,
+            one_sided: p.one_sided != 0,
+            interval: Duration::from_secs(p.interval_secs as u64),
+            next_payment_at: p.next_payment_at,
+            end_at: p.end_at,
+            status: RecurringPaymentStatus::try_from(p.status)?,
+            payments_made: p.payments_made as u64,
+            last_tx_id: p.last_tx_id.map(|id| (id as u64).into()),
+            failure_reason: p.failure_reason,
+            created_at: p.created_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Insertable)]
+#[diesel(table_name = transaction_events)]
+pub(crate) struct NewTransactionEventSql {
+    pub(crate) event: String,
+    pub(crate) created_at: NaiveDateTime,
+}
+
+impl NewTransactionEventSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(transaction_events::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+impl TryFrom<&TransactionEvent> for NewTransactionEventSql {
+    type Error = TransactionStorageError;
+
+    fn try_from(event: &TransactionEvent) -> Result<Self, Self::Error> {
+        Ok(Self {
+            event: serde_json::to_string(event)?,
+            created_at: Utc::now().naive_utc(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, PartialEq)]
+#[diesel(table_name = transaction_events)]
+pub(crate) struct TransactionEventSql {
+    pub(crate) id: i64,
+    pub(crate) event: String,
+    pub(crate) created_at: NaiveDateTime,
+}
+
+impl TransactionEventSql {
+    pub fn index_since(
+        cursor: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<TransactionEventSql>, TransactionStorageError> {
+        Ok(transaction_events::table
+            .filter(transaction_events::id.gt(cursor as i64))
+            .order(transaction_events::id.asc())
+            .load::<TransactionEventSql>(conn)?)
+    }
 
+    /// Removes all but the `max_events` most recent events. Ids are never reused, so cursors held by consumers stay
+    /// valid after pruning.
+    pub fn prune(max_events: usize, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        let latest_id = transaction_events::table
+            .select(diesel::dsl::max(transaction_events::id))
+            .first::<Option<i64>>(conn)?
+            .unwrap_or_default();
+        diesel::delete(transaction_events::table.filter(transaction_events::id.le(latest_id - max_events as i64)))
+            .execute(conn)?;
         Ok(())
     }
+}
 
-    pub fn reject_coinbases_at_block_height(
-        block_height: i64,
-        reason: TxCancellationReason,
-        conn: &mut SqliteConnection,
-    ) -> Result<usize, TransactionStorageError> {
-        Ok(diesel::update(
-            completed_transactions::table
-                .filter(completed_transactions::status.eq(TransactionStatus::Coinbase as i32))
-                .filter(completed_transactions::coinbase_block_height.eq(block_height)),
-        )
-        .set(UpdateCompletedTransactionSql {
-            cancelled: Some(Some(reason as i32)),
-            status: Some(TransactionStatus::Rejected as i32),
-            ..Default::default()
+impl TryFrom<TransactionEventSql> for JournaledTransactionEvent {
+    type Error = TransactionStorageError;
+
+    fn try_from(e: TransactionEventSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            cursor: e.id as u64,
+            event: serde_json::from_str(&e.event)?,
+            created_at: e.created_at,
         })
-        .execute(conn)?)
     }
+}
 
-    pub fn delete(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        let num_deleted =
-            diesel::delete(completed_transactions::table.filter(completed_transactions::tx_id.eq(&self.tx_id)))
-                .execute(conn)?;
-
-        if num_deleted == 0 {
-            return Err(TransactionStorageError::ValuesNotFound);
-        }
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = send_templates)]
+pub(crate) struct SendTemplateSql {
+    pub(crate) id: i64,
+    pub(crate) name: String,
+    pub(crate) destination_address: Vec<u8>,
+    pub(crate) default_amount: Option<i64>,
+    pub(crate) message: String,
+    pub(crate) fee_priority: i32,
+    pub(crate) one_sided: i32,
+    pub(crate) created_at: NaiveDateTime,
+    pub(crate) updated_at: NaiveDateTime,
+}
 
+impl SendTemplateSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(send_templates::table)
+            .values(self.clone())
+            .execute(conn)?;
         Ok(())
     }
 
-    pub fn update(
-        &self,
-        updated_tx: UpdateCompletedTransactionSql,
-        conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(&self.tx_id)))
-            .set(updated_tx)
+    pub fn update(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(send_templates::table.filter(send_templates::id.eq(self.id)))
+            .set((
+                send_templates::name.eq(&self.name),
+                send_templates::destination_address.eq(&self.destination_address),
+                send_templates::default_amount.eq(self.default_amount),
+                send_templates::message.eq(&self.message),
+                send_templates::fee_priority.eq(self.fee_priority),
+                send_templates::one_sided.eq(self.one_sided),
+                send_templates::updated_at.eq(self.updated_at),
+            ))
             .execute(conn)
             .num_rows_affected_or_not_found(1)?;
         Ok(())
     }
 
-    pub fn update_mined_height(
-        tx_id: TxId,
-        num_confirmations: u64,
-        status: TransactionStatus,
-        mined_height: u64,
-        mined_in_block: BlockHash,
-        mined_timestamp: u64,
-        conn: &mut SqliteConnection,
-    ) -> Result<(), TransactionStorageError> {
-        let timestamp = NaiveDateTime::from_timestamp_opt(mined_timestamp as i64, 0).ok_or_else(|| {
-            TransactionStorageError::UnexpectedResult(format!(
-                "Could not create timestamp mined_timestamp: {}",
-                mined_timestamp
-            ))
-        })?;
-        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateCompletedTransactionSql {
-                confirmations: Some(Some(num_confirmations as i64)),
-                status: Some(status as i32),
-                mined_height: Some(Some(mined_height as i64)),
-                mined_in_block: Some(Some(mined_in_block.to_vec())),
-                mined_timestamp: Some(timestamp),
-                // If the tx is mined, then it can't be cancelled
-                cancelled: None,
-                ..Default::default()
-            })
-            .execute(conn)
-            .num_rows_affected_or_not_found(1)?;
+    pub fn find(id: u64, conn: &mut SqliteConnection) -> Result<Option<SendTemplateSql>, TransactionStorageError> {
+        Ok(send_templates::table
+            .filter(send_templates::id.eq(id as i64))
+            .first::<SendTemplateSql>(conn)
+            .optional()?)
+    }
 
-        Ok(())
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<SendTemplateSql>, TransactionStorageError> {
+        Ok(send_templates::table
+            .order_by(send_templates::name.asc())
+            .load::<SendTemplateSql>(conn)?)
     }
 
-    pub fn set_as_unmined(tx_id: TxId, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        // This query uses two sub-queries to retrieve existing values in the table
-        diesel::update(completed_transactions::table.filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64)))
-            .set(UpdateCompletedTransactionSql {
-                status: {
-                    if let Some(Some(_coinbase_block_height)) = completed_transactions::table
-                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                        .select(completed_transactions::coinbase_block_height)
-                        .load::<Option<i64>>(conn)?
-                        .first()
-                    {
-                        Some(TransactionStatus::Coinbase as i32)
-                    } else if let Some(status) = completed_transactions::table
-                        .filter(completed_transactions::tx_id.eq(tx_id.as_u64() as i64))
-                        .select(completed_transactions::status)
-                        .load::<i32>(conn)?
-                        .first()
-                    {
-                        if *status == TransactionStatus::FauxConfirmed as i32 {
-                            Some(TransactionStatus::FauxUnconfirmed as i32)
-                        } else if *status == TransactionStatus::Broadcast as i32 {
-                            Some(TransactionStatus::Broadcast as i32)
-                        } else {
-                            Some(TransactionStatus::Completed as i32)
-                        }
-                    } else {
-                        return Err(TransactionStorageError::DieselError(DieselError::NotFound));
-                    }
-                },
-                mined_in_block: Some(None),
-                mined_height: Some(None),
-                confirmations: Some(None),
-                // Turns out it should not be cancelled
-                cancelled: Some(None),
-                ..Default::default()
-            })
+    pub fn delete(id: u64, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::delete(send_templates::table.filter(send_templates::id.eq(id as i64)))
             .execute(conn)
             .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+}
 
-        // Ideally the outputs should be marked unmined here as well, but because of the separation of classes,
-        // that will be done in the outputs service.
+impl From<SendTemplate> for SendTemplateSql {
+    fn from(t: SendTemplate) -> Self {
+        Self {
+            id: t.id as i64,
+            name: t.name,
+            destination_address: t.destination.to_bytes().to_vec(),
+            default_amount: t.default_amount.map(|amount| u64::from(amount) as i64),
+            message: t.message,
+            fee_priority: i32::from(t.fee_priority),
+            one_sided: i32::from(t.one_sided),
+            created_at: t.created_at,
+            updated_at: t.updated_at,
+        }
+    }
+}
+
+impl TryFrom<SendTemplateSql> for SendTemplate {
+    type Error = TransactionStorageError;
+
+    fn try_from(t: SendTemplateSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: t.id as u64,
+            name: t.name,
+            destination: TariAddress::from_bytes(&t.destination_address)?,
+            default_amount: t.default_amount.map(|amount| MicroMinotari::from(amount as u64)),
+            message: t.message,
+            fee_priority: FeePriority::try_from(t.fee_priority)?,
+            one_sided: t.one_sided != 0,
+            created_at: t.created_at,
+            updated_at: t.updated_at,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = atomic_swaps)]
+pub(crate) struct AtomicSwapSql {
+    pub(crate) swap_id: i64,
+    pub(crate) role: i32,
+    pub(crate) state: i32,
+    pub(crate) counterparty_address: Vec<u8>,
+    pub(crate) amount: i64,
+    pub(crate) hash_lock: Vec<u8>,
+    pub(crate) pre_image: Option<Vec<u8>>,
+    pub(crate) lock_height: i64,
+    pub(crate) lock_tx_id: Option<i64>,
+    pub(crate) lock_output_hash: Option<Vec<u8>>,
+    pub(crate) counterparty_output_hash: Option<Vec<u8>>,
+    pub(crate) settle_tx_id: Option<i64>,
+    pub(crate) message: String,
+    pub(crate) created_at: NaiveDateTime,
+    pub(crate) updated_at: NaiveDateTime,
+}
 
+impl AtomicSwapSql {
+    /// Insert the swap, replacing the previously stored state of a swap with the same id
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(atomic_swaps::table)
+            .values(self.clone())
+            .execute(conn)?;
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
-        self.update(
-            UpdateCompletedTransactionSql {
-                transaction_protocol: Some(self.transaction_protocol.clone()),
-                ..Default::default()
-            },
-            conn,
-        )?;
-
+        diesel::update(atomic_swaps::table.filter(atomic_swaps::swap_id.eq(self.swap_id)))
+            .set(atomic_swaps::pre_image.eq(&self.pre_image))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
         Ok(())
     }
 
-    fn try_from(c: CompletedTransaction, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
-        let transaction_bytes =
-            bincode::serialize(&c.transaction).map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+    pub fn find(swap_id: u64, conn: &mut SqliteConnection) -> Result<Option<AtomicSwapSql>, TransactionStorageError> {
+        Ok(atomic_swaps::table
+            .filter(atomic_swaps::swap_id.eq(swap_id as i64))
+            .first::<AtomicSwapSql>(conn)
+            .optional()?)
+    }
 
-        let output = Self {
-            tx_id: c.tx_id.as_u64() as i64,
-            source_address: c.source_address.to_bytes().to_vec(),
-            destination_address: c.destination_address.to_bytes().to_vec(),
-            amount: u64::from(c.amount) as i64,
-            fee: u64::from(c.fee) as i64,
-            transaction_protocol: transaction_bytes.to_vec(),
-            status: c.status as i32,
-            message: c.message,
-            timestamp: c.timestamp,
-            cancelled: c.cancelled.map(|v| v as i32),
-            direction: Some(c.direction as i32),
-            coinbase_block_height: c.coinbase_block_height.map(|b| b as i64),
-            send_count: c.send_count as i32,
-            last_send_timestamp: c.last_send_timestamp,
-            confirmations: c.confirmations.map(|ic| ic as i64),
-            mined_height: c.mined_height.map(|ic| ic as i64),
-            mined_in_block: c.mined_in_block.map(|v| v.to_vec()),
-            mined_timestamp: c.mined_timestamp,
-            transaction_signature_nonce: c.transaction_signature.get_public_nonce().to_vec(),
-            transaction_signature_key: c.transaction_signature.get_signature().to_vec(),
-        };
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<AtomicSwapSql>, TransactionStorageError> {
+        Ok(atomic_swaps::table
+            .order_by(atomic_swaps::created_at.asc())
+            .load::<AtomicSwapSql>(conn)?)
+    }
 
-        output.encrypt(cipher).map_err(TransactionStorageError::AeadError)
+    pub(crate) fn try_from(s: AtomicSwap, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        Self {
+            swap_id: s.swap_id as i64,
+            role: i32::from(s.role),
+            state: i32::from(s.state),
+            counterparty_address: s.counterparty.to_bytes().to_vec(),
+            amount: u64::from(s.amount) as i64,
+            hash_lock: s.hash_lock.to_vec(),
+            pre_image: s.pre_image.map(|p| p.to_vec()),
+            lock_height: s.lock_height as i64,
+            lock_tx_id: s.lock_tx_id.map(|id| id.as_u64() as i64),
+            lock_output_hash: s.lock_output_hash.map(|h| h.to_vec()),
+            counterparty_output_hash: s.counterparty_output_hash.map(|h| h.to_vec()),
+            settle_tx_id: s.settle_tx_id.map(|id| id.as_u64() as i64),
+            message: s.message,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        }
+        .encrypt(cipher)
+        .map_err(TransactionStorageError::AeadError)
     }
 }
 
-impl Encryptable<XChaCha20Poly1305> for CompletedTransactionSql {
+impl Encryptable<XChaCha20Poly1305> for AtomicSwapSql {
     fn domain(&self, field_name: &'static str) -> Vec<u8> {
         [
-            Self::COMPLETED_TRANSACTION,
-            self.tx_id.to_le_bytes().as_slice(),
+            Self::ATOMIC_SWAP,
+            self.swap_id.to_le_bytes().as_slice(),
             field_name.as_bytes(),
         ]
         .concat()
@@ -2034,184 +4244,308 @@ impl Encryptable<XChaCha20Poly1305> for CompletedTransactionSql {
     }
 
     fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.transaction_protocol = encrypt_bytes_integral_nonce(
-            cipher,
-            self.domain("transaction_protocol"),
-            Hidden::hide(self.transaction_protocol),
-        )?;
+        if let Some(pre_image) = self.pre_image.take() {
+            self.pre_image = Some(encrypt_bytes_integral_nonce(
+                cipher,
+                self.domain("pre_image"),
+                Hidden::hide(pre_image),
+            )?);
+        }
 
         Ok(self)
     }
 
     fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
-        self.transaction_protocol =
-            decrypt_bytes_integral_nonce(cipher, self.domain("transaction_protocol"), &self.transaction_protocol)?;
+        if let Some(pre_image) = self.pre_image.take() {
+            self.pre_image = Some(decrypt_bytes_integral_nonce(
+                cipher,
+                self.domain("pre_image"),
+                &pre_image,
+            )?);
+        }
 
         Ok(self)
     }
 }
 
-#[derive(Debug, Error)]
-pub enum CompletedTransactionConversionError {
-    #[error("CompletedTransaction conversion failed by wrong direction: {0}")]
-    DirectionError(#[from] TransactionDirectionError),
-    #[error("CompletedTransaction conversion failed with transaction conversion: {0}")]
-    ConversionError(#[from] TransactionConversionError),
-    #[error("CompletedTransaction conversion failed with json error: {0}")]
-    JsonError(#[from] serde_json::Error),
-    #[error("CompletedTransaction conversion failed with key error: {0}")]
-    KeyError(#[from] TransactionKeyError),
-    #[error("Aead Error: {0}")]
-    AeadError(String),
-    #[error("Bincode error: `{0}`")]
-    BincodeDeserialize(String),
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = multisig_sessions)]
+pub(crate) struct MultisigSessionSql {
+    pub(crate) session_id: i64,
+    pub(crate) threshold: i32,
+    pub(crate) participants_json: String,
+    pub(crate) polynomial_key_id: String,
+    pub(crate) signing_share_key_id: Option<String>,
+    pub(crate) group_public_key: Option<Vec<u8>>,
+    pub(crate) label: String,
+    pub(crate) state: i32,
+    pub(crate) created_at: NaiveDateTime,
+    pub(crate) updated_at: NaiveDateTime,
 }
 
-impl CompletedTransaction {
-    fn try_from(
-        c: CompletedTransactionSql,
-        cipher: &XChaCha20Poly1305,
-    ) -> Result<Self, CompletedTransactionConversionError> {
-        let mut c = c
-            .decrypt(cipher)
-            .map_err(CompletedTransactionConversionError::AeadError)?;
-        let transaction_signature = match PublicKey::from_vec(&c.transaction_signature_nonce) {
-            Ok(public_nonce) => match PrivateKey::from_vec(&c.transaction_signature_key) {
-                Ok(signature) => Signature::new(public_nonce, signature),
-                Err(_) => Signature::default(),
-            },
-            Err(_) => Signature::default(),
-        };
-        let mined_in_block = match c.mined_in_block {
-            Some(v) => match v.try_into() {
-                Ok(v) => Some(v),
-                Err(_) => None,
-            },
-            None => None,
-        };
+impl MultisigSessionSql {
+    /// Insert the session, replacing the previously stored state of a session with the same id
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(multisig_sessions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
 
-        let output = Self {
-            tx_id: (c.tx_id as u64).into(),
-            source_address: TariAddress::from_bytes(&c.source_address).map_err(TransactionKeyError::Source)?,
-            destination_address: TariAddress::from_bytes(&c.destination_address)
-                .map_err(TransactionKeyError::Destination)?,
-            amount: MicroMinotari::from(c.amount as u64),
-            fee: MicroMinotari::from(c.fee as u64),
-            transaction: bincode::deserialize(&c.transaction_protocol)
-                .map_err(|e| CompletedTransactionConversionError::BincodeDeserialize(e.to_string()))?,
-            status: TransactionStatus::try_from(c.status)?,
-            message: c.message,
-            timestamp: c.timestamp,
-            cancelled: c
-                .cancelled
-                .map(|v| TxCancellationReason::try_from(v as u32).unwrap_or(TxCancellationReason::Unknown)),
-            direction: TransactionDirection::try_from(c.direction.unwrap_or(2i32))?,
-            coinbase_block_height: c.coinbase_block_height.map(|b| b as u64),
-            send_count: c.send_count as u32,
-            last_send_timestamp: c.last_send_timestamp,
-            transaction_signature,
-            confirmations: c.confirmations.map(|ic| ic as u64),
-            mined_height: c.mined_height.map(|ic| ic as u64),
-            mined_in_block,
-            mined_timestamp: c.mined_timestamp,
-        };
+    pub fn find(
+        session_id: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<MultisigSessionSql>, TransactionStorageError> {
+        Ok(multisig_sessions::table
+            .filter(multisig_sessions::session_id.eq(session_id as i64))
+            .first::<MultisigSessionSql>(conn)
+            .optional()?)
+    }
 
-        // zeroize sensitive data
-        c.transaction_protocol.zeroize();
+    pub fn index(conn: &mut SqliteConnection) -> Result<Vec<MultisigSessionSql>, TransactionStorageError> {
+        Ok(multisig_sessions::table
+            .order_by(multisig_sessions::created_at.asc())
+            .load::<MultisigSessionSql>(conn)?)
+    }
+}
+
+impl TryFrom<MultisigSession> for MultisigSessionSql {
+    type Error = TransactionStorageError;
 
-        Ok(output)
+    fn try_from(s: MultisigSession) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: s.session_id as i64,
+            threshold: i32::from(s.threshold),
+            participants_json: serde_json::to_string(&s.participants)?,
+            polynomial_key_id: s.polynomial_key_id.to_string(),
+            signing_share_key_id: s.signing_share_id.map(|id| id.to_string()),
+            group_public_key: s.group_public_key.map(|key| key.to_vec()),
+            label: s.label,
+            state: i32::from(s.state),
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
     }
 }
 
-#[derive(AsChangeset, Default)]
-#[diesel(table_name = completed_transactions)]
-pub struct UpdateCompletedTransactionSql {
-    status: Option<i32>,
-    timestamp: Option<NaiveDateTime>,
-    cancelled: Option<Option<i32>>,
-    direction: Option<i32>,
-    transaction_protocol: Option<Vec<u8>>,
-    send_count: Option<i32>,
-    last_send_timestamp: Option<Option<NaiveDateTime>>,
-    confirmations: Option<Option<i64>>,
-    mined_height: Option<Option<i64>>,
-    mined_in_block: Option<Option<Vec<u8>>>,
-    mined_timestamp: Option<NaiveDateTime>,
-    transaction_signature_nonce: Option<Vec<u8>>,
-    transaction_signature_key: Option<Vec<u8>>,
+impl TryFrom<MultisigSessionSql> for MultisigSession {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: MultisigSessionSql) -> Result<Self, Self::Error> {
+        Ok(Self {
+            session_id: s.session_id as u64,
+            threshold: u8::try_from(s.threshold).map_err(|_| TransactionConversionError { code: s.threshold })?,
+            participants: serde_json::from_str(&s.participants_json)?,
+            polynomial_key_id: TariKeyId::from_str(&s.polynomial_key_id)
+                .map_err(|e| TransactionStorageError::UnexpectedResult(e.to_string()))?,
+            signing_share_id: s
+                .signing_share_key_id
+                .map(|id| TariKeyId::from_str(&id))
+                .transpose()
+                .map_err(|e| TransactionStorageError::UnexpectedResult(e.to_string()))?,
+            group_public_key: s
+                .group_public_key
+                .map(|key| PublicKey::from_canonical_bytes(&key))
+                .transpose()?,
+            label: s.label,
+            state: MultisigSessionState::try_from(s.state)?,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
+    }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct UnconfirmedTransactionInfo {
-    pub tx_id: TxId,
-    pub signature: Signature,
-    pub status: TransactionStatus,
-    pub coinbase_block_height: Option<u64>,
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = multisig_signings)]
+pub(crate) struct MultisigSigningSql {
+    pub(crate) signing_id: i64,
+    pub(crate) session_id: i64,
+    pub(crate) message: Vec<u8>,
+    pub(crate) signers_json: String,
+    pub(crate) hiding_nonce_key_id: Option<String>,
+    pub(crate) binding_nonce_key_id: Option<String>,
+    pub(crate) description: String,
+    pub(crate) state: i32,
+    pub(crate) signature_nonce: Option<Vec<u8>>,
+    pub(crate) signature_key: Option<Vec<u8>>,
+    pub(crate) created_at: NaiveDateTime,
+    pub(crate) updated_at: NaiveDateTime,
+    pub(crate) kind_json: String,
+    pub(crate) secret_key_id: Option<String>,
+    pub(crate) joint_public_key: Option<Vec<u8>>,
+    pub(crate) script_offset: Option<Vec<u8>>,
 }
 
-impl UnconfirmedTransactionInfo {
-    pub fn is_coinbase(&self) -> bool {
-        if let Some(height) = self.coinbase_block_height {
-            height > 0
-        } else {
-            false
+impl MultisigSigningSql {
+    /// Insert the signing, replacing the previously stored state of a signing with the same id
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::replace_into(multisig_signings::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn find(
+        signing_id: u64,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<MultisigSigningSql>, TransactionStorageError> {
+        Ok(multisig_signings::table
+            .filter(multisig_signings::signing_id.eq(signing_id as i64))
+            .first::<MultisigSigningSql>(conn)
+            .optional()?)
+    }
+
+    pub fn index(
+        session_id: Option<u64>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<MultisigSigningSql>, TransactionStorageError> {
+        let mut query = multisig_signings::table.into_boxed();
+        if let Some(session_id) = session_id {
+            query = query.filter(multisig_signings::session_id.eq(session_id as i64));
         }
+        Ok(query
+            .order_by(multisig_signings::created_at.asc())
+            .load::<MultisigSigningSql>(conn)?)
     }
 }
 
-impl TryFrom<UnconfirmedTransactionInfoSql> for UnconfirmedTransactionInfo {
+impl TryFrom<MultisigSigning> for MultisigSigningSql {
     type Error = TransactionStorageError;
 
-    fn try_from(i: UnconfirmedTransactionInfoSql) -> Result<Self, Self::Error> {
+    fn try_from(s: MultisigSigning) -> Result<Self, Self::Error> {
         Ok(Self {
-            tx_id: (i.tx_id as u64).into(),
-            signature: Signature::new(
-                PublicKey::from_vec(&i.transaction_signature_nonce)?,
-                PrivateKey::from_vec(&i.transaction_signature_key)?,
-            ),
-            status: TransactionStatus::try_from(i.status)?,
-            coinbase_block_height: i.coinbase_block_height.map(|b| b as u64),
+            signing_id: s.signing_id as i64,
+            session_id: s.session_id as i64,
+            message: s.message.to_vec(),
+            signers_json: serde_json::to_string(&s.signers)?,
+            hiding_nonce_key_id: s.hiding_nonce_id.map(|id| id.to_string()),
+            binding_nonce_key_id: s.binding_nonce_id.map(|id| id.to_string()),
+            description: s.description,
+            state: i32::from(s.state),
+            signature_nonce: s.signature.as_ref().map(|sig| sig.get_public_nonce().to_vec()),
+            signature_key: s.signature.as_ref().map(|sig| sig.get_signature().to_vec()),
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+            kind_json: serde_json::to_string(&s.kind)?,
+            secret_key_id: s.secret_key_id.map(|id| id.to_string()),
+            joint_public_key: s.joint_public_key.map(|key| key.to_vec()),
+            script_offset: s.script_offset.map(|offset| offset.to_vec()),
         })
     }
 }
 
-#[derive(Clone, Queryable)]
-pub struct UnconfirmedTransactionInfoSql {
-    pub tx_id: i64,
-    pub status: i32,
-    pub transaction_signature_nonce: Vec<u8>,
-    pub transaction_signature_key: Vec<u8>,
-    pub coinbase_block_height: Option<i64>,
+impl TryFrom<MultisigSigningSql> for MultisigSigning {
+    type Error = TransactionStorageError;
+
+    fn try_from(s: MultisigSigningSql) -> Result<Self, Self::Error> {
+        let signature = match (s.signature_nonce, s.signature_key) {
+            (Some(nonce), Some(key)) => Some(Signature::new(
+                PublicKey::from_canonical_bytes(&nonce)?,
+                PrivateKey::from_canonical_bytes(&key)?,
+            )),
+            _ => None,
+        };
+        let to_key_id = |id: Option<String>| {
+            id.map(|id| TariKeyId::from_str(&id))
+                .transpose()
+                .map_err(|e| TransactionStorageError::UnexpectedResult(e.to_string()))
+        };
+        Ok(Self {
+            signing_id: s.signing_id as u64,
+            session_id: s.session_id as u64,
+            kind: serde_json::from_str(&s.kind_json)?,
+            message: HashOutput::try_from(s.message.as_slice())
+                .map_err(|e| TransactionStorageError::ByteArrayError(e.to_string()))?,
+            signers: serde_json::from_str(&s.signers_json)?,
+            hiding_nonce_id: to_key_id(s.hiding_nonce_key_id)?,
+            binding_nonce_id: to_key_id(s.binding_nonce_key_id)?,
+            secret_key_id: to_key_id(s.secret_key_id)?,
+            description: s.description,
+            state: MultisigSigningState::try_from(s.state)?,
+            signature,
+            joint_public_key: s
+                .joint_public_key
+                .map(|key| PublicKey::from_canonical_bytes(&key))
+                .transpose()?,
+            script_offset: s
+                .script_offset
+                .map(|offset| PrivateKey::from_canonical_bytes(&offset))
+                .transpose()?,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
+    }
 }
 
-impl UnconfirmedTransactionInfoSql {
-    /// This method returns completed but unconfirmed transactions that were not imported or scanned
-    pub fn fetch_unconfirmed_transactions_info(
-        conn: &mut SqliteConnection,
-    ) -> Result<Vec<UnconfirmedTransactionInfoSql>, TransactionStorageError> {
-        let query_result = completed_transactions::table
-            .select((
-                completed_transactions::tx_id,
-                completed_transactions::status,
-                completed_transactions::transaction_signature_nonce,
-                completed_transactions::transaction_signature_key,
-                completed_transactions::coinbase_block_height,
-            ))
-            .filter(
-                completed_transactions::status
-                    .ne(TransactionStatus::Imported as i32)
-                    .and(completed_transactions::status.ne(TransactionStatus::FauxUnconfirmed as i32))
-                    .and(completed_transactions::status.ne(TransactionStatus::FauxConfirmed as i32))
-                    .and(
-                        completed_transactions::mined_height
-                            .is_null()
-                            .or(completed_transactions::status.eq(TransactionStatus::MinedUnconfirmed as i32)),
-                    ),
-            )
-            .filter(completed_transactions::cancelled.is_null())
-            .order_by(completed_transactions::tx_id)
-            .load::<UnconfirmedTransactionInfoSql>(conn)?;
-        Ok(query_result)
+impl AtomicSwap {
+    pub(crate) fn try_from(s: AtomicSwapSql, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        let s = s.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        let to_hash = |bytes: &[u8]| {
+            HashOutput::try_from(bytes).map_err(|e| TransactionStorageError::ByteArrayError(e.to_string()))
+        };
+        Ok(Self {
+            swap_id: s.swap_id as u64,
+            role: AtomicSwapRole::try_from(s.role)?,
+            state: AtomicSwapState::try_from(s.state)?,
+            counterparty: TariAddress::from_bytes(&s.counterparty_address)?,
+            amount: MicroMinotari::from(s.amount as u64),
+            hash_lock: to_hash(&s.hash_lock)?,
+            pre_image: s
+                .pre_image
+                .map(|p| PublicKey::from_canonical_bytes(&p))
+                .transpose()
+                .map_err(|e| TransactionStorageError::ByteArrayError(e.to_string()))?,
+            lock_height: s.lock_height as u64,
+            lock_tx_id: s.lock_tx_id.map(|id| (id as u64).into()),
+            lock_output_hash: s.lock_output_hash.as_deref().map(to_hash).transpose()?,
+            counterparty_output_hash: s.counterparty_output_hash.as_deref().map(to_hash).transpose()?,
+            settle_tx_id: s.settle_tx_id.map(|id| (id as u64).into()),
+            message: s.message,
+            created_at: s.created_at,
+            updated_at: s.updated_at,
+        })
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for OutboundMessageSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::OUTBOUND_MESSAGE,
+            self.tx_id.to_le_bytes().as_slice(),
+            self.message_type.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.payload = encrypt_bytes_integral_nonce(cipher, self.domain("payload"), Hidden::hide(self.payload))?;
+
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.payload = decrypt_bytes_integral_nonce(cipher, self.domain("payload"), &self.payload)?;
+
+        Ok(self)
+    }
+}
+
+impl QueuedOutboundMessage {
+    pub(crate) fn try_from(m: OutboundMessageSql, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        let m = m.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        Ok(Self {
+            tx_id: (m.tx_id as u64).into(),
+            message_type: OutboundMessageType::try_from(m.message_type)?,
+            destination_address: TariAddress::from_bytes(&m.destination_address)
+                .map_err(TransactionKeyError::Destination)?,
+            payload: m.payload,
+            status: OutboundMessageStatus::try_from(m.status)?,
+            attempts: m.attempts as u32,
+            created_at: m.created_at,
+            last_attempt_at: m.last_attempt_at,
+        })
     }
 }
 
@@ -3204,3 +5538,302 @@ mod test {
         assert_eq!(info_list, info_list_reference);
     }
 }
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = offline_transactions)]
+pub(crate) struct OfflineTransactionSql {
+    pub(crate) tx_id: i64,
+    pub(crate) unsigned_transaction: Vec<u8>,
+    pub(crate) status: i32,
+    pub(crate) created_at: NaiveDateTime,
+}
+
+impl OfflineTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(offline_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(offline_transactions::table.filter(offline_transactions::tx_id.eq(self.tx_id)))
+            .set(offline_transactions::unsigned_transaction.eq(&self.unsigned_transaction))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn find(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<OfflineTransactionSql>, TransactionStorageError> {
+        Ok(offline_transactions::table
+            .filter(offline_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<OfflineTransactionSql>(conn)
+            .optional()?)
+    }
+
+    pub fn index(
+        status: Option<OfflineTransactionStatus>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<OfflineTransactionSql>, TransactionStorageError> {
+        let mut query = offline_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(offline_transactions::status.eq(i32::from(status)));
+        }
+        Ok(query
+            .order_by(offline_transactions::created_at.asc())
+            .load::<OfflineTransactionSql>(conn)?)
+    }
+
+    pub fn update_status(
+        tx_id: TxId,
+        from: OfflineTransactionStatus,
+        to: OfflineTransactionStatus,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(
+            offline_transactions::table
+                .filter(offline_transactions::tx_id.eq(tx_id.as_u64() as i64))
+                .filter(offline_transactions::status.eq(i32::from(from))),
+        )
+        .set(offline_transactions::status.eq(i32::from(to)))
+        .execute(conn)
+        .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub(crate) fn try_from(t: OfflineTransaction, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        let unsigned_transaction = t
+            .unsigned_transaction
+            .to_bytes()
+            .map_err(|e| TransactionStorageError::BincodeSerialize(e.to_string()))?;
+        Self {
+            tx_id: t.tx_id().as_u64() as i64,
+            unsigned_transaction,
+            status: i32::from(t.status),
+            created_at: t.created_at,
+        }
+        .encrypt(cipher)
+        .map_err(TransactionStorageError::AeadError)
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for OfflineTransactionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::OFFLINE_TRANSACTION,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.unsigned_transaction = encrypt_bytes_integral_nonce(
+            cipher,
+            self.domain("unsigned_transaction"),
+            Hidden::hide(self.unsigned_transaction),
+        )?;
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        self.unsigned_transaction =
+            decrypt_bytes_integral_nonce(cipher, self.domain("unsigned_transaction"), &self.unsigned_transaction)?;
+        Ok(self)
+    }
+}
+
+impl OfflineTransaction {
+    pub(crate) fn try_from(
+        t: OfflineTransactionSql,
+        cipher: &XChaCha20Poly1305,
+    ) -> Result<Self, TransactionStorageError> {
+        let mut t = t.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        let offline_transaction = Self {
+            unsigned_transaction: UnsignedTransaction::from_bytes(&t.unsigned_transaction)
+                .map_err(|e| TransactionStorageError::BincodeDeserialize(e.to_string()))?,
+            status: OfflineTransactionStatus::try_from(t.status)?,
+            created_at: t.created_at,
+        };
+
+        // zeroize decrypted data
+        t.unsigned_transaction.zeroize();
+
+        Ok(offline_transaction)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Insertable, PartialEq)]
+#[diesel(table_name = burn_transactions)]
+pub(crate) struct BurnTransactionSql {
+    pub(crate) tx_id: i64,
+    pub(crate) amount: i64,
+    pub(crate) claim_public_key: Option<Vec<u8>>,
+    pub(crate) reciprocal_claim_public_key: Vec<u8>,
+    pub(crate) commitment: Vec<u8>,
+    pub(crate) ownership_proof: Option<Vec<u8>>,
+    pub(crate) range_proof: Vec<u8>,
+    pub(crate) status: i32,
+    pub(crate) burned_at: NaiveDateTime,
+    pub(crate) claimed_at: Option<NaiveDateTime>,
+}
+
+impl BurnTransactionSql {
+    pub fn commit(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::insert_into(burn_transactions::table)
+            .values(self.clone())
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn update_encryption(&self, conn: &mut SqliteConnection) -> Result<(), TransactionStorageError> {
+        diesel::update(burn_transactions::table.filter(burn_transactions::tx_id.eq(self.tx_id)))
+            .set(burn_transactions::ownership_proof.eq(&self.ownership_proof))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub fn find(
+        tx_id: TxId,
+        conn: &mut SqliteConnection,
+    ) -> Result<Option<BurnTransactionSql>, TransactionStorageError> {
+        Ok(burn_transactions::table
+            .filter(burn_transactions::tx_id.eq(tx_id.as_u64() as i64))
+            .first::<BurnTransactionSql>(conn)
+            .optional()?)
+    }
+
+    pub fn index(
+        status: Option<BurnStatus>,
+        conn: &mut SqliteConnection,
+    ) -> Result<Vec<BurnTransactionSql>, TransactionStorageError> {
+        let mut query = burn_transactions::table.into_boxed();
+        if let Some(status) = status {
+            query = query.filter(burn_transactions::status.eq(i32::from(status)));
+        }
+        Ok(query
+            .order_by(burn_transactions::burned_at.asc())
+            .load::<BurnTransactionSql>(conn)?)
+    }
+
+    pub fn update_status(
+        tx_id: TxId,
+        status: BurnStatus,
+        claimed_at: Option<NaiveDateTime>,
+        conn: &mut SqliteConnection,
+    ) -> Result<(), TransactionStorageError> {
+        diesel::update(burn_transactions::table.filter(burn_transactions::tx_id.eq(tx_id.as_u64() as i64)))
+            .set((
+                burn_transactions::status.eq(i32::from(status)),
+                burn_transactions::claimed_at.eq(claimed_at),
+            ))
+            .execute(conn)
+            .num_rows_affected_or_not_found(1)?;
+        Ok(())
+    }
+
+    pub(crate) fn try_from(b: BurnRecord, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        // The ownership proof is stored as its public nonce followed by its `u` and `v` scalars
+        let ownership_proof = b
+            .proof
+            .ownership_proof
+            .map(|sig| [sig.public_nonce().as_bytes(), sig.u().as_bytes(), sig.v().as_bytes()].concat());
+        Self {
+            tx_id: b.tx_id.as_u64() as i64,
+            amount: b.amount.as_u64() as i64,
+            claim_public_key: b.claim_public_key.map(|k| k.to_vec()),
+            reciprocal_claim_public_key: b.proof.reciprocal_claim_public_key.to_vec(),
+            commitment: b.proof.commitment.to_vec(),
+            ownership_proof,
+            range_proof: b.proof.range_proof.to_vec(),
+            status: i32::from(b.status),
+            burned_at: b.burned_at,
+            claimed_at: b.claimed_at,
+        }
+        .encrypt(cipher)
+        .map_err(TransactionStorageError::AeadError)
+    }
+}
+
+impl Encryptable<XChaCha20Poly1305> for BurnTransactionSql {
+    fn domain(&self, field_name: &'static str) -> Vec<u8> {
+        [
+            Self::BURN_TRANSACTION,
+            self.tx_id.to_le_bytes().as_slice(),
+            field_name.as_bytes(),
+        ]
+        .concat()
+        .to_vec()
+    }
+
+    fn encrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        if let Some(ownership_proof) = self.ownership_proof.take() {
+            self.ownership_proof = Some(encrypt_bytes_integral_nonce(
+                cipher,
+                self.domain("ownership_proof"),
+                Hidden::hide(ownership_proof),
+            )?);
+        }
+        Ok(self)
+    }
+
+    fn decrypt(mut self, cipher: &XChaCha20Poly1305) -> Result<Self, String> {
+        if let Some(ownership_proof) = self.ownership_proof.take() {
+            self.ownership_proof = Some(decrypt_bytes_integral_nonce(
+                cipher,
+                self.domain("ownership_proof"),
+                &ownership_proof,
+            )?);
+        }
+        Ok(self)
+    }
+}
+
+impl BurnRecord {
+    pub(crate) fn try_from(b: BurnTransactionSql, cipher: &XChaCha20Poly1305) -> Result<Self, TransactionStorageError> {
+        let mut b = b.decrypt(cipher).map_err(TransactionStorageError::AeadError)?;
+        let ownership_proof = match &b.ownership_proof {
+            Some(bytes) if bytes.len() == 96 => Some(RistrettoComSig::new(
+                Commitment::from_canonical_bytes(&bytes[..32])?,
+                PrivateKey::from_canonical_bytes(&bytes[32..64])?,
+                PrivateKey::from_canonical_bytes(&bytes[64..])?,
+            )),
+            Some(_) => {
+                return Err(TransactionStorageError::UnexpectedResult(
+                    "Malformed burn ownership proof".to_string(),
+                ))
+            },
+            None => None,
+        };
+        let record = Self {
+            tx_id: (b.tx_id as u64).into(),
+            amount: MicroMinotari::from(b.amount as u64),
+            claim_public_key: b
+                .claim_public_key
+                .as_deref()
+                .map(PublicKey::from_canonical_bytes)
+                .transpose()?,
+            proof: BurntProof {
+                reciprocal_claim_public_key: PublicKey::from_canonical_bytes(&b.reciprocal_claim_public_key)?,
+                commitment: Commitment::from_canonical_bytes(&b.commitment)?,
+                ownership_proof,
+                range_proof: BulletRangeProof(b.range_proof.clone()),
+            },
+            status: BurnStatus::try_from(b.status)?,
+            burned_at: b.burned_at,
+            claimed_at: b.claimed_at,
+        };
+
+        // zeroize decrypted data
+        if let Some(ownership_proof) = b.ownership_proof.as_mut() {
+            ownership_proof.zeroize();
+        }
+
+        Ok(record)
+    }
+}
@@ -31,7 +31,6 @@ use tari_common_types::{
 use crate::{
     output_manager_service::{handle::OutputManagerHandle, storage::OutputStatus},
     transaction_service::{
-        config::TransactionServiceConfig,
         handle::{TransactionEvent, TransactionEventSender},
         storage::{
             database::{TransactionBackend, TransactionDatabase},
@@ -48,6 +47,7 @@ pub async fn check_faux_transactions<TBackend: 'static + TransactionBackend>(
     db: TransactionDatabase<TBackend>,
     event_publisher: TransactionEventSender,
     tip_height: u64,
+    num_confirmations_required: u64,
 ) {
     let mut all_faux_transactions: Vec<CompletedTransaction> = match db.get_imported_transactions() {
         Ok(txs) => txs,
@@ -121,8 +121,7 @@ pub async fn check_faux_transactions<TBackend: 'static + TransactionBackend>(
             };
             let is_valid = tip_height >= mined_height;
             let was_confirmed = tx.status == TransactionStatus::FauxConfirmed;
-            let is_confirmed = tip_height.saturating_sub(mined_height) >=
-                TransactionServiceConfig::default().num_confirmations_required;
+            let is_confirmed = tip_height.saturating_sub(mined_height) >= num_confirmations_required;
             let num_confirmations = tip_height - mined_height;
             debug!(
                 target: LOG_TARGET,
@@ -0,0 +1,159 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, sync::Arc};
+
+use log::*;
+use tari_common_types::{
+    tari_address::TariAddress,
+    transaction::{TransactionDirection, TxId},
+};
+use tari_contacts::contacts_service::{error::ContactsServiceError, handle::ContactsServiceHandle};
+use tokio::sync::RwLock;
+
+use crate::transaction_service::{
+    error::TransactionServiceError,
+    storage::database::{TransactionBackend, TransactionDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::counterparty_aliases";
+
+/// The address book aliases of the wallet's contacts, keyed by the contact's address bytes. The aliases are loaded from
+/// the contacts service on first use and dropped whenever the contacts service reports a change.
+#[derive(Default)]
+pub struct ContactAliases {
+    generation: u64,
+    aliases: Option<Arc<HashMap<Vec<u8>, String>>>,
+}
+
+impl ContactAliases {
+    /// Drops the cached aliases so that the next query reloads them. Loads that started before the invalidation are
+    /// not stored.
+    pub fn invalidate(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+        self.aliases = None;
+    }
+}
+
+pub type ContactAliasCache = Arc<RwLock<ContactAliases>>;
+
+/// Returns the alias of the counterparty of each of the given transactions. The current contact alias takes precedence
+/// over the alias persisted against the transaction. Nothing is written to the database.
+pub async fn resolve_counterparty_aliases<TBackend: TransactionBackend + 'static>(
+    db: TransactionDatabase<TBackend>,
+    contacts_service: Option<ContactsServiceHandle>,
+    cache: ContactAliasCache,
+    tx_ids: Vec<TxId>,
+) -> Result<HashMap<TxId, String>, TransactionServiceError> {
+    let mut aliases = db.fetch_counterparty_aliases(&tx_ids)?;
+    let contacts = match contacts_service {
+        Some(mut contacts_service) => match load_contact_aliases(&mut contacts_service, &cache).await {
+            Ok(contacts) => contacts,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Could not fetch contacts to resolve aliases: {}", e);
+                return Ok(aliases);
+            },
+        },
+        None => return Ok(aliases),
+    };
+    if contacts.is_empty() {
+        return Ok(aliases);
+    }
+
+    for tx_id in tx_ids {
+        let counterparty = match db.get_any_transaction(tx_id)? {
+            Some(tx) => tx.counterparty_address().to_bytes(),
+            None => continue,
+        };
+        if let Some(alias) = contacts.get(&counterparty) {
+            aliases.insert(tx_id, alias.clone());
+        }
+    }
+    Ok(aliases)
+}
+
+/// Persists the alias of a removed contact against every transaction with that contact, so that the alias can still
+/// be shown once the contact is gone
+pub fn persist_removed_contact_alias<TBackend: TransactionBackend + 'static>(
+    db: &TransactionDatabase<TBackend>,
+    address: &TariAddress,
+    alias: &str,
+) -> Result<usize, TransactionServiceError> {
+    let address = address.to_bytes();
+    let mut tx_ids = Vec::new();
+    tx_ids.extend(
+        db.get_pending_inbound_transactions()?
+            .into_iter()
+            .filter(|(_, tx)| tx.source_address.to_bytes() == address)
+            .map(|(tx_id, _)| tx_id),
+    );
+    tx_ids.extend(
+        db.get_pending_outbound_transactions()?
+            .into_iter()
+            .filter(|(_, tx)| tx.destination_address.to_bytes() == address)
+            .map(|(tx_id, _)| tx_id),
+    );
+    tx_ids.extend(
+        db.get_completed_transactions()?
+            .into_iter()
+            .filter(|(_, tx)| {
+                let counterparty = match tx.direction {
+                    TransactionDirection::Outbound => &tx.destination_address,
+                    TransactionDirection::Inbound | TransactionDirection::Unknown => &tx.source_address,
+                };
+                counterparty.to_bytes() == address
+            })
+            .map(|(tx_id, _)| tx_id),
+    );
+    for tx_id in &tx_ids {
+        db.set_counterparty_alias(*tx_id, alias.to_string())?;
+    }
+    Ok(tx_ids.len())
+}
+
+async fn load_contact_aliases(
+    contacts_service: &mut ContactsServiceHandle,
+    cache: &ContactAliasCache,
+) -> Result<Arc<HashMap<Vec<u8>, String>>, ContactsServiceError> {
+    let generation = {
+        let cache = cache.read().await;
+        if let Some(aliases) = cache.aliases.as_ref() {
+            return Ok(aliases.clone());
+        }
+        cache.generation
+    };
+
+    let aliases = Arc::new(
+        contacts_service
+            .get_contacts()
+            .await?
+            .into_iter()
+            .map(|c| (c.address.to_bytes(), c.alias))
+            .collect::<HashMap<_, _>>(),
+    );
+    let mut cache = cache.write().await;
+    // A contact changed while the aliases were being loaded, they are returned for this query but not cached
+    if cache.generation == generation {
+        cache.aliases = Some(aliases.clone());
+    }
+    Ok(aliases)
+}
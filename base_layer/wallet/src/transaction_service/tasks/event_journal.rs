@@ -0,0 +1,61 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use log::*;
+use tari_shutdown::ShutdownSignal;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::transaction_service::{
+    handle::TransactionEventReceiver,
+    storage::database::{TransactionBackend, TransactionDatabase},
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::event_journal";
+
+/// Persists every published transaction event to the event journal, so that consumers that were not subscribed when
+/// an event was published can catch up on it with `get_events_since`
+pub async fn run_event_journal<TBackend: TransactionBackend + 'static>(
+    mut events: TransactionEventReceiver,
+    db: TransactionDatabase<TBackend>,
+    max_events: usize,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Ok(event) => {
+                    if let Err(e) = db.journal_transaction_event(&event, max_events) {
+                        warn!(target: LOG_TARGET, "Could not journal transaction event '{}': {}", event, e);
+                    }
+                },
+                Err(RecvError::Lagged(n)) => {
+                    warn!(target: LOG_TARGET, "Event journal fell behind, {} transaction events were not journaled", n);
+                },
+                Err(RecvError::Closed) => break,
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Event journal shutting down because it received the shutdown signal");
+                break;
+            },
+        }
+    }
+}
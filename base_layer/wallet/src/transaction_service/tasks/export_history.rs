@@ -0,0 +1,357 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use chrono::NaiveDateTime;
+use log::*;
+use serde::{Deserialize, Serialize};
+use tari_common_types::{tari_address::TariAddress, transaction::TxId, types::Signature};
+use tari_utilities::hex::Hex;
+
+use crate::transaction_service::{
+    error::TransactionServiceError,
+    storage::{
+        database::{TransactionBackend, TransactionDatabase},
+        models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::export_history";
+
+const CSV_HEADER: [&str; 15] = [
+    "tx_id",
+    "status",
+    "direction",
+    "cancelled",
+    "timestamp",
+    "amount",
+    "fee",
+    "source_address",
+    "destination_address",
+    "kernel_public_nonce",
+    "kernel_signature",
+    "confirmations",
+    "mined_height",
+    "mined_timestamp",
+    "message",
+];
+
+/// The file format written by a transaction history export
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryExportFormat {
+    Csv,
+    Json,
+}
+
+impl Display for HistoryExportFormat {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryExportFormat::Csv => write!(fmt, "csv"),
+            HistoryExportFormat::Json => write!(fmt, "json"),
+        }
+    }
+}
+
+impl FromStr for HistoryExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(HistoryExportFormat::Csv),
+            "json" => Ok(HistoryExportFormat::Json),
+            other => Err(format!("Unsupported history export format '{}'", other)),
+        }
+    }
+}
+
+/// An inclusive range of transaction timestamps, either bound may be left open
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HistoryDateRange {
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+impl HistoryDateRange {
+    pub fn new(from: Option<NaiveDateTime>, to: Option<NaiveDateTime>) -> Self {
+        Self { from, to }
+    }
+
+    pub fn contains(&self, timestamp: &NaiveDateTime) -> bool {
+        self.from.map_or(true, |from| *timestamp >= from) && self.to.map_or(true, |to| *timestamp <= to)
+    }
+}
+
+impl Display for HistoryDateRange {
+    fn fmt(&self, fmt: &mut Formatter<'_>) -> fmt::Result {
+        match (self.from, self.to) {
+            (None, None) => write!(fmt, "all"),
+            (Some(from), None) => write!(fmt, "{}..", from),
+            (None, Some(to)) => write!(fmt, "..={}", to),
+            (Some(from), Some(to)) => write!(fmt, "{}..={}", from, to),
+        }
+    }
+}
+
+/// A single row of an exported transaction history
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransactionHistoryRecord {
+    pub tx_id: TxId,
+    pub status: String,
+    pub direction: String,
+    pub cancelled: Option<String>,
+    pub timestamp: NaiveDateTime,
+    /// Amount in µT
+    pub amount: u64,
+    /// Fee in µT
+    pub fee: u64,
+    pub source_address: String,
+    pub destination_address: String,
+    pub kernel_public_nonce: Option<String>,
+    pub kernel_signature: Option<String>,
+    pub confirmations: Option<u64>,
+    pub mined_height: Option<u64>,
+    pub mined_timestamp: Option<NaiveDateTime>,
+    pub message: String,
+}
+
+impl TransactionHistoryRecord {
+    fn csv_fields(&self) -> [String; 15] {
+        [
+            self.tx_id.to_string(),
+            self.status.clone(),
+            self.direction.clone(),
+            self.cancelled.clone().unwrap_or_default(),
+            self.timestamp.to_string(),
+            self.amount.to_string(),
+            self.fee.to_string(),
+            self.source_address.clone(),
+            self.destination_address.clone(),
+            self.kernel_public_nonce.clone().unwrap_or_default(),
+            self.kernel_signature.clone().unwrap_or_default(),
+            self.confirmations.map(|c| c.to_string()).unwrap_or_default(),
+            self.mined_height.map(|h| h.to_string()).unwrap_or_default(),
+            self.mined_timestamp.map(|t| t.to_string()).unwrap_or_default(),
+            self.message.clone(),
+        ]
+    }
+}
+
+impl From<&CompletedTransaction> for TransactionHistoryRecord {
+    fn from(tx: &CompletedTransaction) -> Self {
+        // Imported and faux transactions carry no kernel, so a default signature is not worth exporting
+        let has_signature = tx.transaction_signature != Signature::default();
+        Self {
+            tx_id: tx.tx_id,
+            status: tx.status.to_string(),
+            direction: tx.direction.to_string(),
+            cancelled: tx.cancelled.map(|reason| reason.to_string()),
+            timestamp: tx.timestamp,
+            amount: tx.amount.as_u64(),
+            fee: tx.fee.as_u64(),
+            source_address: tx.source_address.to_hex(),
+            destination_address: tx.destination_address.to_hex(),
+            kernel_public_nonce: has_signature.then(|| tx.transaction_signature.get_public_nonce().to_hex()),
+            kernel_signature: has_signature.then(|| tx.transaction_signature.get_signature().to_hex()),
+            confirmations: tx.confirmations,
+            mined_height: tx.mined_height,
+            mined_timestamp: tx.mined_timestamp,
+            message: tx.message.clone(),
+        }
+    }
+}
+
+impl TransactionHistoryRecord {
+    fn from_inbound(tx: &InboundTransaction, own_address: &TariAddress) -> Self {
+        Self {
+            tx_id: tx.tx_id,
+            status: tx.status.to_string(),
+            direction: "Inbound".to_string(),
+            cancelled: tx.cancelled.then(|| "Cancelled".to_string()),
+            timestamp: tx.timestamp,
+            amount: tx.amount.as_u64(),
+            fee: 0,
+            source_address: tx.source_address.to_hex(),
+            destination_address: own_address.to_hex(),
+            kernel_public_nonce: None,
+            kernel_signature: None,
+            confirmations: None,
+            mined_height: None,
+            mined_timestamp: None,
+            message: tx.message.clone(),
+        }
+    }
+
+    fn from_outbound(tx: &OutboundTransaction, own_address: &TariAddress) -> Self {
+        Self {
+            tx_id: tx.tx_id,
+            status: tx.status.to_string(),
+            direction: "Outbound".to_string(),
+            cancelled: tx.cancelled.then(|| "Cancelled".to_string()),
+            timestamp: tx.timestamp,
+            amount: tx.amount.as_u64(),
+            fee: tx.fee.as_u64(),
+            source_address: own_address.to_hex(),
+            destination_address: tx.destination_address.to_hex(),
+            kernel_public_nonce: None,
+            kernel_signature: None,
+            confirmations: None,
+            mined_height: None,
+            mined_timestamp: None,
+            message: tx.message.clone(),
+        }
+    }
+}
+
+/// Collects the completed, pending and cancelled transactions in `date_range` from the backend, oldest first
+pub fn collect_transaction_history<TBackend: TransactionBackend + 'static>(
+    db: &TransactionDatabase<TBackend>,
+    own_address: &TariAddress,
+    date_range: &HistoryDateRange,
+) -> Result<Vec<TransactionHistoryRecord>, TransactionServiceError> {
+    let mut records = Vec::new();
+    for tx in db
+        .get_completed_transactions()?
+        .values()
+        .chain(db.get_cancelled_completed_transactions()?.values())
+    {
+        records.push(TransactionHistoryRecord::from(tx));
+    }
+    for tx in db
+        .get_pending_inbound_transactions()?
+        .values()
+        .chain(db.get_cancelled_pending_inbound_transactions()?.values())
+    {
+        records.push(TransactionHistoryRecord::from_inbound(tx, own_address));
+    }
+    for tx in db
+        .get_pending_outbound_transactions()?
+        .values()
+        .chain(db.get_cancelled_pending_outbound_transactions()?.values())
+    {
+        records.push(TransactionHistoryRecord::from_outbound(tx, own_address));
+    }
+    records.retain(|record| date_range.contains(&record.timestamp));
+    records.sort_by(|a, b| {
+        a.timestamp
+            .cmp(&b.timestamp)
+            .then(a.tx_id.as_u64().cmp(&b.tx_id.as_u64()))
+    });
+    Ok(records)
+}
+
+/// Writes the transaction history in `date_range` to `path`, returning the number of transactions written
+pub fn export_transaction_history<TBackend: TransactionBackend + 'static>(
+    db: &TransactionDatabase<TBackend>,
+    own_address: &TariAddress,
+    format: HistoryExportFormat,
+    date_range: &HistoryDateRange,
+    path: &Path,
+) -> Result<usize, TransactionServiceError> {
+    let records = collect_transaction_history(db, own_address, date_range)?;
+    let file = File::create(path).map_err(|e| {
+        TransactionServiceError::HistoryExportError(format!("Could not create '{}': {}", path.display(), e))
+    })?;
+    let mut writer = BufWriter::new(file);
+    match format {
+        HistoryExportFormat::Csv => write_csv(&mut writer, &records),
+        HistoryExportFormat::Json => write_json(&mut writer, &records),
+    }
+    .and_then(|_| writer.flush())
+    .map_err(|e| TransactionServiceError::HistoryExportError(format!("Could not write '{}': {}", path.display(), e)))?;
+    debug!(
+        target: LOG_TARGET,
+        "Exported {} transactions ({}) as {} to '{}'",
+        records.len(),
+        date_range,
+        format,
+        path.display()
+    );
+    Ok(records.len())
+}
+
+fn write_csv<W: Write>(writer: &mut W, records: &[TransactionHistoryRecord]) -> std::io::Result<()> {
+    writeln!(writer, "{}", CSV_HEADER.join(","))?;
+    for record in records {
+        let row = record
+            .csv_fields()
+            .iter()
+            .map(|field| escape_csv_field(field))
+            .collect::<Vec<_>>()
+            .join(",");
+        writeln!(writer, "{}", row)?;
+    }
+    Ok(())
+}
+
+/// Writes the records as a JSON array one element at a time so the whole document is never held in memory
+fn write_json<W: Write>(writer: &mut W, records: &[TransactionHistoryRecord]) -> std::io::Result<()> {
+    write!(writer, "[")?;
+    for (i, record) in records.iter().enumerate() {
+        if i > 0 {
+            write!(writer, ",")?;
+        }
+        writeln!(writer)?;
+        serde_json::to_writer(&mut *writer, record)?;
+    }
+    writeln!(writer, "\n]")
+}
+
+fn escape_csv_field(field: &str) -> String {
+    format!("\"{}\"", field.replace('"', "\"\""))
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn it_filters_by_date_range() {
+        let day = |d| {
+            NaiveDate::from_ymd_opt(2023, 11, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        let range = HistoryDateRange::new(Some(day(2)), Some(day(4)));
+        assert!(!range.contains(&day(1)));
+        assert!(range.contains(&day(2)));
+        assert!(range.contains(&day(4)));
+        assert!(!range.contains(&day(5)));
+        assert!(HistoryDateRange::default().contains(&day(1)));
+    }
+
+    #[test]
+    fn it_escapes_csv_fields() {
+        assert_eq!(escape_csv_field("hello"), "\"hello\"");
+        assert_eq!(escape_csv_field("say \"hi\", ok"), "\"say \"\"hi\"\", ok\"");
+    }
+}
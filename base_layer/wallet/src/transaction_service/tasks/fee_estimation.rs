@@ -0,0 +1,230 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::VecDeque, sync::Arc};
+
+use log::*;
+use tari_core::{
+    mempool::FeePerGramStat,
+    proto::base_node as base_node_proto,
+    transactions::tari_amount::MicroMinotari,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::sync::{broadcast, RwLock};
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    connectivity_service::WalletConnectivityInterface,
+    transaction_service::{error::TransactionServiceError, handle::FeeEstimates},
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::fee_estimation";
+
+/// The number of upcoming blocks the base node is asked to project from its mempool
+const PROJECTED_BLOCKS: u64 = 3;
+
+pub type FeeHistoryCache = Arc<RwLock<FeeHistory>>;
+
+/// The low, medium and high fee per gram derived from a single mempool snapshot
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct FeeSample {
+    tip_height: u64,
+    low: MicroMinotari,
+    medium: MicroMinotari,
+    high: MicroMinotari,
+}
+
+impl FeeSample {
+    fn new(tip_height: u64, stats: &[FeePerGramStat]) -> Self {
+        let minimum = MicroMinotari::from(1);
+        let (low, medium, high) = match stats {
+            [] => (minimum, minimum, minimum),
+            // Everything in the mempool fits into the next block, so any fee will do unless the mempool grows
+            [next] => (minimum, minimum, next.avg_fee_per_gram),
+            [next, .., last] => (last.min_fee_per_gram, next.min_fee_per_gram, next.avg_fee_per_gram),
+        };
+        Self {
+            tip_height,
+            low: low.max(minimum),
+            medium: medium.max(minimum),
+            high: high.max(minimum),
+        }
+    }
+}
+
+/// A rolling window of mempool fee snapshots, one per block
+#[derive(Debug, Clone)]
+pub struct FeeHistory {
+    samples: VecDeque<FeeSample>,
+    capacity: usize,
+}
+
+impl FeeHistory {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            samples: VecDeque::with_capacity(capacity),
+            capacity: capacity.max(1),
+        }
+    }
+
+    /// Records the projected mempool blocks at the given tip height, replacing any earlier sample taken at that height
+    pub fn record(&mut self, tip_height: u64, stats: &[FeePerGramStat]) {
+        self.samples.retain(|s| s.tip_height != tip_height);
+        self.samples.push_back(FeeSample::new(tip_height, stats));
+        while self.samples.len() > self.capacity {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Returns the median of each fee tier over the sampled blocks, or None if nothing has been sampled yet
+    pub fn estimates(&self) -> Option<FeeEstimates> {
+        let latest = self.samples.back()?;
+        let low = median(self.samples.iter().map(|s| s.low));
+        let medium = median(self.samples.iter().map(|s| s.medium)).max(low);
+        let high = median(self.samples.iter().map(|s| s.high)).max(medium);
+        Some(FeeEstimates {
+            low,
+            medium,
+            high,
+            tip_height: latest.tip_height,
+            num_samples: self.samples.len(),
+        })
+    }
+}
+
+fn median<I: Iterator<Item = MicroMinotari>>(values: I) -> MicroMinotari {
+    let mut values = values.collect::<Vec<_>>();
+    values.sort();
+    values.get(values.len() / 2).copied().unwrap_or_default()
+}
+
+/// Asks the connected base node for the fee per gram statistics of the blocks it would build from its mempool
+pub async fn fetch_mempool_fee_stats<TWalletConnectivity: WalletConnectivityInterface>(
+    connectivity: &mut TWalletConnectivity,
+) -> Result<Vec<FeePerGramStat>, TransactionServiceError> {
+    let mut client = connectivity
+        .obtain_base_node_wallet_rpc_client()
+        .await
+        .ok_or(TransactionServiceError::Shutdown)?;
+    let resp = client
+        .get_mempool_fee_per_gram_stats(base_node_proto::GetMempoolFeePerGramStatsRequest {
+            count: PROJECTED_BLOCKS,
+        })
+        .await?;
+    Ok(resp.stats.into_iter().map(Into::into).collect())
+}
+
+/// Samples the mempool of the connected base node every time a new block is detected and keeps the fee history cache
+/// up to date
+pub async fn run_fee_estimation<TWalletConnectivity: WalletConnectivityInterface>(
+    cache: FeeHistoryCache,
+    mut connectivity: TWalletConnectivity,
+    base_node_service: BaseNodeServiceHandle,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    let mut base_node_events = base_node_service.get_event_stream();
+    loop {
+        let tip_height = tokio::select! {
+            event = base_node_events.recv() => match event {
+                Ok(event) => match *event {
+                    BaseNodeEvent::NewBlockDetected(_, height) => height,
+                    _ => continue,
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    trace!(target: LOG_TARGET, "Fee estimation task lagged {} base node events", n);
+                    continue;
+                },
+                Err(broadcast::error::RecvError::Closed) => {
+                    info!(target: LOG_TARGET, "Base node event stream closed, stopping fee estimation task");
+                    break;
+                },
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Fee estimation task shutting down because it received the shutdown signal");
+                break;
+            },
+        };
+
+        match fetch_mempool_fee_stats(&mut connectivity).await {
+            Ok(stats) => {
+                let mut history = cache.write().await;
+                history.record(tip_height, &stats);
+                debug!(
+                    target: LOG_TARGET,
+                    "Fee estimates at height {}: {:?}",
+                    tip_height,
+                    history.estimates()
+                );
+            },
+            Err(e) => warn!(
+                target: LOG_TARGET,
+                "Could not sample mempool fees at height {}: {}", tip_height, e
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stat(order: u64, min: u64, avg: u64) -> FeePerGramStat {
+        FeePerGramStat {
+            order,
+            min_fee_per_gram: min.into(),
+            avg_fee_per_gram: avg.into(),
+            max_fee_per_gram: avg.saturating_mul(2).into(),
+        }
+    }
+
+    #[test]
+    fn it_estimates_from_the_median_of_recent_blocks() {
+        let mut history = FeeHistory::new(3);
+        assert!(history.estimates().is_none());
+
+        history.record(10, &[]);
+        history.record(11, &[stat(0, 20, 30), stat(1, 10, 15), stat(2, 5, 8)]);
+        history.record(12, &[stat(0, 40, 60), stat(1, 25, 30)]);
+        history.record(13, &[stat(0, 30, 50), stat(1, 8, 12), stat(2, 4, 6)]);
+
+        // The sample at height 10 was evicted
+        let estimates = history.estimates().unwrap();
+        assert_eq!(estimates.num_samples, 3);
+        assert_eq!(estimates.tip_height, 13);
+        assert_eq!(estimates.low, MicroMinotari::from(5));
+        assert_eq!(estimates.medium, MicroMinotari::from(30));
+        assert_eq!(estimates.high, MicroMinotari::from(50));
+    }
+
+    #[test]
+    fn it_never_recommends_less_than_the_minimum() {
+        let mut history = FeeHistory::new(5);
+        history.record(1, &[stat(0, 0, 0)]);
+        history.record(1, &[stat(0, 3, 4)]);
+
+        let estimates = history.estimates().unwrap();
+        assert_eq!(estimates.num_samples, 1);
+        assert_eq!(estimates.low, MicroMinotari::from(1));
+        assert_eq!(estimates.medium, MicroMinotari::from(1));
+        assert_eq!(estimates.high, MicroMinotari::from(4));
+    }
+}
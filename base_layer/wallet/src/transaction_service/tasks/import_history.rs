@@ -0,0 +1,141 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use chrono::NaiveDateTime;
+use log::*;
+use tari_common_types::{
+    tari_address::TariAddress,
+    transaction::{TransactionDirection, TransactionStatus, TxId},
+    types::PrivateKey,
+};
+use tari_core::transactions::{tari_amount::MicroMinotari, transaction_components::Transaction};
+
+use crate::transaction_service::{
+    error::TransactionServiceError,
+    storage::{
+        database::{TransactionBackend, TransactionDatabase},
+        models::CompletedTransaction,
+    },
+    tasks::export_history::TransactionHistoryRecord,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::import_history";
+
+/// A transaction known from outside this wallet, such as a row of an old wallet's history export or an exchange
+/// statement. It is recorded with the `ImportedHistory` status so that it shows up in the transaction history, but it
+/// carries no outputs and does not affect the balance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoricalTransaction {
+    /// The id to record the transaction under. Importing a transaction whose id is already known is a no-op, so
+    /// keeping the original ids makes repeated imports of the same history safe. A random id is used if not given.
+    pub tx_id: Option<TxId>,
+    pub source_address: TariAddress,
+    pub destination_address: TariAddress,
+    pub amount: MicroMinotari,
+    pub fee: MicroMinotari,
+    pub direction: TransactionDirection,
+    pub timestamp: NaiveDateTime,
+    pub mined_height: Option<u64>,
+    pub mined_timestamp: Option<NaiveDateTime>,
+    pub message: String,
+}
+
+impl HistoricalTransaction {
+    fn into_completed_transaction(self) -> CompletedTransaction {
+        CompletedTransaction::new(
+            self.tx_id.unwrap_or_else(TxId::new_random),
+            self.source_address,
+            self.destination_address,
+            self.amount,
+            self.fee,
+            Transaction::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                PrivateKey::default(),
+                PrivateKey::default(),
+            ),
+            TransactionStatus::ImportedHistory,
+            self.message,
+            self.timestamp,
+            self.direction,
+            None,
+            self.mined_height,
+            self.mined_timestamp,
+        )
+    }
+}
+
+impl TryFrom<TransactionHistoryRecord> for HistoricalTransaction {
+    type Error = TransactionServiceError;
+
+    fn try_from(record: TransactionHistoryRecord) -> Result<Self, Self::Error> {
+        let parse_address = |hex: &str| {
+            TariAddress::from_hex(hex).map_err(|e| {
+                TransactionServiceError::HistoryImportError(format!(
+                    "Invalid address '{}' in transaction {}: {}",
+                    hex, record.tx_id, e
+                ))
+            })
+        };
+        let direction = match record.direction.as_str() {
+            "Inbound" => TransactionDirection::Inbound,
+            "Outbound" => TransactionDirection::Outbound,
+            _ => TransactionDirection::Unknown,
+        };
+        Ok(Self {
+            tx_id: Some(record.tx_id),
+            source_address: parse_address(&record.source_address)?,
+            destination_address: parse_address(&record.destination_address)?,
+            amount: record.amount.into(),
+            fee: record.fee.into(),
+            direction,
+            timestamp: record.timestamp,
+            mined_height: record.mined_height,
+            mined_timestamp: record.mined_timestamp,
+            message: record.message,
+        })
+    }
+}
+
+/// Records `transactions` as imported history in a single database transaction, returning the ids of the
+/// transactions that were added. Transactions that are already in the database are skipped.
+pub fn import_transaction_history<TBackend: TransactionBackend + 'static>(
+    db: &TransactionDatabase<TBackend>,
+    transactions: Vec<HistoricalTransaction>,
+) -> Result<Vec<TxId>, TransactionServiceError> {
+    let num_transactions = transactions.len();
+    let transactions = transactions
+        .into_iter()
+        .map(HistoricalTransaction::into_completed_transaction)
+        .collect();
+    let imported = db.insert_imported_history(transactions)?;
+    debug!(
+        target: LOG_TARGET,
+        "Imported {} of {} historical transactions",
+        imported.len(),
+        num_transactions
+    );
+    Ok(imported)
+}
@@ -0,0 +1,234 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::Utc;
+use log::*;
+use tari_common_types::transaction::{TransactionStatus, TxId};
+use tari_core::{
+    base_node::{
+        proto::wallet_rpc::{TxLocation, TxQueryBatchResponse},
+        rpc::MAX_TX_QUERY_BATCH_SIZE,
+    },
+    consensus::ConsensusManager,
+    mempool::FeePerGramStat,
+    proto::{
+        base_node::{GetMempoolFeePerGramStatsRequest, Signatures as SignaturesProto},
+        types::Signature as SignatureProto,
+    },
+    transactions::tari_amount::MicroMinotari,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::RwLock,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{
+    connectivity_service::WalletConnectivityInterface,
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::MempoolTransactionState,
+        storage::database::{TransactionBackend, TransactionDatabase},
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::mempool_state";
+
+/// The number of upcoming blocks the base node is asked to project from its mempool when ranking fees
+const PROJECTED_BLOCKS: u64 = 10;
+
+pub type MempoolStateCache = Arc<RwLock<HashMap<TxId, MempoolTransactionState>>>;
+
+/// Periodically asks the connected base node whether each broadcast transaction is in its mempool and how its fee
+/// ranks against the rest of the mempool
+pub async fn run_mempool_state_monitor<TBackend, TWalletConnectivity>(
+    cache: MempoolStateCache,
+    db: TransactionDatabase<TBackend>,
+    mut connectivity: TWalletConnectivity,
+    consensus_manager: ConsensusManager,
+    refresh_interval: Duration,
+    mut shutdown_signal: ShutdownSignal,
+) where
+    TBackend: TransactionBackend + 'static,
+    TWalletConnectivity: WalletConnectivityInterface,
+{
+    let mut interval = time::interval(refresh_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                if let Err(e) = refresh_mempool_states(&cache, &db, &mut connectivity, &consensus_manager).await {
+                    debug!(target: LOG_TARGET, "Could not refresh mempool state of broadcast transactions: {}", e);
+                }
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Mempool state monitor shutting down because it received the shutdown signal");
+                break;
+            },
+        }
+    }
+}
+
+async fn refresh_mempool_states<TBackend, TWalletConnectivity>(
+    cache: &MempoolStateCache,
+    db: &TransactionDatabase<TBackend>,
+    connectivity: &mut TWalletConnectivity,
+    consensus_manager: &ConsensusManager,
+) -> Result<(), TransactionServiceError>
+where
+    TBackend: TransactionBackend + 'static,
+    TWalletConnectivity: WalletConnectivityInterface,
+{
+    let broadcast = db
+        .fetch_unconfirmed_transactions_info()?
+        .into_iter()
+        .filter(|tx| tx.status == TransactionStatus::Broadcast && !tx.is_coinbase())
+        .collect::<Vec<_>>();
+    if broadcast.is_empty() {
+        cache.write().await.clear();
+        return Ok(());
+    }
+
+    let mut client = connectivity
+        .obtain_base_node_wallet_rpc_client()
+        .await
+        .ok_or(TransactionServiceError::Shutdown)?;
+    let stats = client
+        .get_mempool_fee_per_gram_stats(GetMempoolFeePerGramStatsRequest {
+            count: PROJECTED_BLOCKS,
+        })
+        .await?
+        .stats
+        .into_iter()
+        .map(FeePerGramStat::from)
+        .collect::<Vec<_>>();
+
+    let mut in_mempool = HashSet::new();
+    let mut tip_height = 0;
+    for batch in broadcast.chunks(MAX_TX_QUERY_BATCH_SIZE) {
+        let batch_response = client
+            .transaction_batch_query(SignaturesProto {
+                sigs: batch
+                    .iter()
+                    .map(|tx| SignatureProto::from(tx.signature.clone()))
+                    .collect(),
+            })
+            .await?;
+        tip_height = batch_response.height_of_longest_chain;
+        for response_proto in batch_response.responses {
+            let response = TxQueryBatchResponse::try_from(response_proto)
+                .map_err(TransactionServiceError::ProtobufConversionError)?;
+            if response.location == TxLocation::InMempool {
+                in_mempool.insert(response.signature);
+            }
+        }
+    }
+
+    let weighting = consensus_manager
+        .consensus_constants(tip_height)
+        .transaction_weight_params();
+    let checked_at = Utc::now().naive_utc();
+    let mut states = HashMap::with_capacity(broadcast.len());
+    for info in broadcast {
+        // The transaction may have been cancelled since it was listed
+        let completed = match db.get_completed_transaction(info.tx_id) {
+            Ok(completed) => completed,
+            Err(_) => continue,
+        };
+        let weight = completed.transaction.calculate_weight(weighting)?;
+        let fee_per_gram = completed.fee.as_u64().checked_div(weight).unwrap_or_default().into();
+        let in_mempool = in_mempool.contains(&info.signature);
+        states.insert(info.tx_id, MempoolTransactionState {
+            in_mempool,
+            fee_per_gram,
+            feerate_percentile: in_mempool.then(|| feerate_percentile(fee_per_gram, &stats)),
+            checked_at,
+        });
+    }
+    trace!(
+        target: LOG_TARGET,
+        "Refreshed mempool state of {} broadcast transaction(s), {} in the mempool",
+        states.len(),
+        in_mempool.len()
+    );
+    *cache.write().await = states;
+    Ok(())
+}
+
+/// Estimates where `fee_per_gram` ranks amongst the projected mempool blocks, which are ordered from the most to the
+/// least expensive, by interpolating within the fee range of the block it falls into
+pub fn feerate_percentile(fee_per_gram: MicroMinotari, stats: &[FeePerGramStat]) -> u8 {
+    let num_blocks = stats.len() as u64;
+    if num_blocks == 0 {
+        return 100;
+    }
+    for (i, block) in (0u64..).zip(stats) {
+        if fee_per_gram < block.min_fee_per_gram {
+            continue;
+        }
+        let blocks_below = num_blocks - i - 1;
+        let span = block
+            .max_fee_per_gram
+            .as_u64()
+            .saturating_sub(block.min_fee_per_gram.as_u64());
+        let within_block = if fee_per_gram >= block.max_fee_per_gram || span == 0 {
+            100
+        } else {
+            (fee_per_gram.as_u64() - block.min_fee_per_gram.as_u64()) * 100 / span
+        };
+        return u8::try_from((blocks_below * 100 + within_block) / num_blocks).unwrap_or(100);
+    }
+    0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn block(order: u64, min: u64, max: u64) -> FeePerGramStat {
+        FeePerGramStat {
+            order,
+            min_fee_per_gram: min.into(),
+            avg_fee_per_gram: ((min + max) / 2).into(),
+            max_fee_per_gram: max.into(),
+        }
+    }
+
+    #[test]
+    fn it_ranks_fees_against_projected_blocks() {
+        let stats = [block(0, 20, 40), block(1, 10, 19)];
+        assert_eq!(feerate_percentile(50.into(), &stats), 100);
+        assert_eq!(feerate_percentile(30.into(), &stats), 75);
+        assert_eq!(feerate_percentile(20.into(), &stats), 50);
+        assert_eq!(feerate_percentile(19.into(), &stats), 50);
+        assert_eq!(feerate_percentile(10.into(), &stats), 0);
+        assert_eq!(feerate_percentile(5.into(), &stats), 0);
+        assert_eq!(feerate_percentile(5.into(), &[]), 100);
+    }
+}
@@ -21,7 +21,17 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 pub mod check_faux_transaction_status;
+pub mod counterparty_aliases;
+pub mod event_journal;
+pub mod export_history;
+pub mod fee_estimation;
+pub mod import_history;
+pub mod mempool_state;
+pub mod queued_transactions;
+pub mod recurring_payments;
+pub mod scheduled_transactions;
 pub mod send_finalized_transaction;
+pub mod send_queued_outbound_message;
 pub mod send_transaction_cancelled;
 pub mod send_transaction_reply;
 pub mod wait_on_dial;
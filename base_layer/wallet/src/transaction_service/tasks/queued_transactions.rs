@@ -0,0 +1,237 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use log::*;
+use tari_common_types::transaction::TxId;
+use tari_core::transactions::transaction_components::OutputFeatures;
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::watch,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{
+    connectivity_service::OnlineStatus,
+    output_manager_service::UtxoSelectionCriteria,
+    transaction_service::{
+        handle::{TransactionEvent, TransactionEventSender, TransactionServiceHandle},
+        storage::{
+            database::{TransactionBackend, TransactionDatabase},
+            models::{QueuedTransaction, QueuedTransactionStatus},
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::queued_transactions";
+
+/// Sends transactions that were queued while the wallet was offline once base node connectivity returns, and expires
+/// those that have been queued for longer than `max_age`. The queue is checked every `check_interval` and whenever the
+/// connectivity status changes.
+pub async fn run_queued_transactions<TBackend: 'static + TransactionBackend>(
+    db: TransactionDatabase<TBackend>,
+    mut transaction_service: TransactionServiceHandle,
+    mut connectivity_status: watch::Receiver<OnlineStatus>,
+    event_publisher: TransactionEventSender,
+    max_age: Duration,
+    check_interval: Duration,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    recover_interrupted_sends(&db, &event_publisher);
+
+    let mut interval = time::interval(check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            changed = connectivity_status.changed() => {
+                if changed.is_err() {
+                    info!(target: LOG_TARGET, "Connectivity status watch closed, stopping queued transactions task");
+                    break;
+                }
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Queued transactions task shutting down because it received the shutdown signal");
+                break;
+            },
+        }
+
+        let online = *connectivity_status.borrow_and_update() == OnlineStatus::Online;
+        process_queued_transactions(&db, &mut transaction_service, &event_publisher, max_age, online).await;
+    }
+}
+
+/// Fails transactions that were left in `Sending` because the wallet stopped while sending them. Whether the send went
+/// through before the wallet stopped cannot be told, so rather than risk paying twice they are not retried and the
+/// user has to check the transaction history before sending again.
+fn recover_interrupted_sends<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    event_publisher: &TransactionEventSender,
+) {
+    let interrupted = match db.fetch_queued_transactions(Some(QueuedTransactionStatus::Sending)) {
+        Ok(interrupted) => interrupted,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Problem retrieving interrupted queued transactions: {}", e);
+            return;
+        },
+    };
+
+    for queued in interrupted {
+        let reason = "The wallet stopped while sending this transaction, check the transaction history before sending \
+                      it again"
+            .to_string();
+        if transition(db, &queued, QueuedTransactionStatus::Failed, None, Some(reason.clone())) {
+            warn!(
+                target: LOG_TARGET,
+                "Queued transaction {} was interrupted while sending and has been marked as failed", queued.id
+            );
+            publish(event_publisher, TransactionEvent::QueuedTransactionFailed {
+                queue_id: queued.id,
+                reason,
+            });
+        }
+    }
+}
+
+async fn process_queued_transactions<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    transaction_service: &mut TransactionServiceHandle,
+    event_publisher: &TransactionEventSender,
+    max_age: Duration,
+    online: bool,
+) {
+    let queued = match db.fetch_queued_transactions(Some(QueuedTransactionStatus::Queued)) {
+        Ok(queued) => queued,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Problem retrieving queued transactions: {}", e);
+            return;
+        },
+    };
+
+    for queued in queued {
+        if queued.is_expired(max_age, Utc::now().naive_utc()) {
+            if transition(db, &queued, QueuedTransactionStatus::Expired, None, None) {
+                info!(
+                    target: LOG_TARGET,
+                    "Queued transaction {} expired after waiting since {}", queued.id, queued.queued_at
+                );
+                publish(event_publisher, TransactionEvent::QueuedTransactionExpired(queued.id));
+            }
+            continue;
+        }
+        if !online {
+            continue;
+        }
+        // Claim the queued transaction before sending so that a concurrent cancellation cannot race with the send
+        if !transition(db, &queued, QueuedTransactionStatus::Sending, None, None) {
+            continue;
+        }
+
+        let mut sending = queued.clone();
+        sending.status = QueuedTransactionStatus::Sending;
+        match send(transaction_service, &queued).await {
+            Ok(tx_id) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Connectivity returned, queued transaction {} sent as TxId: {}", queued.id, tx_id
+                );
+                transition(db, &sending, QueuedTransactionStatus::Sent, Some(tx_id), None);
+                publish(event_publisher, TransactionEvent::QueuedTransactionSent {
+                    queue_id: queued.id,
+                    tx_id,
+                });
+            },
+            Err(reason) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Queued transaction {} could not be sent: {}", queued.id, reason
+                );
+                transition(
+                    db,
+                    &sending,
+                    QueuedTransactionStatus::Failed,
+                    None,
+                    Some(reason.clone()),
+                );
+                publish(event_publisher, TransactionEvent::QueuedTransactionFailed {
+                    queue_id: queued.id,
+                    reason,
+                });
+            },
+        }
+    }
+}
+
+async fn send(transaction_service: &mut TransactionServiceHandle, queued: &QueuedTransaction) -> Result<TxId, String> {
+    let result = if queued.one_sided {
+        transaction_service
+            .send_one_sided_transaction(
+                queued.destination.clone(),
+                queued.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                queued.fee_per_gram,
+                queued.message.clone(),
+            )
+            .await
+    } else {
+        transaction_service
+            .send_transaction(
+                queued.destination.clone(),
+                queued.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                queued.fee_per_gram,
+                queued.message.clone(),
+            )
+            .await
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Moves the queued transaction out of its current status, returning false if it was changed underneath us (e.g.
+/// cancelled)
+fn transition<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    queued: &QueuedTransaction,
+    to: QueuedTransactionStatus,
+    tx_id: Option<TxId>,
+    failure_reason: Option<String>,
+) -> bool {
+    match db.update_queued_transaction_status(queued.id, queued.status, to, tx_id, failure_reason) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(
+                target: LOG_TARGET,
+                "Could not move queued transaction {} from {} to {}: {}", queued.id, queued.status, to, e
+            );
+            false
+        },
+    }
+}
+
+fn publish(event_publisher: &TransactionEventSender, event: TransactionEvent) {
+    // Send only fails if there are no subscribers
+    let _size = event_publisher.send(Arc::new(event));
+}
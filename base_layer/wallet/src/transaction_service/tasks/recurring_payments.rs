@@ -0,0 +1,240 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use log::*;
+use tari_common_types::transaction::TxId;
+use tari_core::transactions::transaction_components::OutputFeatures;
+use tari_shutdown::ShutdownSignal;
+use tokio::time::{self, MissedTickBehavior};
+
+use crate::{
+    output_manager_service::{error::OutputManagerError, UtxoSelectionCriteria},
+    transaction_service::{
+        error::TransactionServiceError,
+        handle::{TransactionEvent, TransactionEventSender, TransactionServiceHandle},
+        storage::{
+            database::{TransactionBackend, TransactionDatabase},
+            models::{RecurringPayment, RecurringPaymentStatus},
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::recurring_payments";
+
+/// Sends the payments of active recurring payment plans as they fall due, every `check_interval`. A plan is paused when
+/// the wallet does not have enough funds for a payment, and completed once its end date is reached.
+pub async fn run_recurring_payments<TBackend: 'static + TransactionBackend>(
+    db: TransactionDatabase<TBackend>,
+    mut transaction_service: TransactionServiceHandle,
+    event_publisher: TransactionEventSender,
+    check_interval: Duration,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    let mut interval = time::interval(check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                process_recurring_payments(&db, &mut transaction_service, &event_publisher).await;
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Recurring payments task shutting down because it received the shutdown signal");
+                break;
+            },
+        }
+    }
+}
+
+async fn process_recurring_payments<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    transaction_service: &mut TransactionServiceHandle,
+    event_publisher: &TransactionEventSender,
+) {
+    let active = match db.fetch_recurring_payments(Some(RecurringPaymentStatus::Active)) {
+        Ok(active) => active,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Problem retrieving recurring payments: {}", e);
+            return;
+        },
+    };
+
+    for payment in active {
+        let now = Utc::now().naive_utc();
+        if payment.has_ended(now) {
+            complete(db, event_publisher, &payment);
+            continue;
+        }
+        if !payment.is_due(now) {
+            continue;
+        }
+        // Claim the payment before sending so that it is never sent twice, even if the wallet shuts down part way
+        // through or the plan is paused or cancelled concurrently
+        let following_payment_at = payment.following_payment_at(now);
+        if let Err(e) = db.advance_recurring_payment(payment.id, payment.next_payment_at, following_payment_at) {
+            debug!(
+                target: LOG_TARGET,
+                "Recurring payment {} was changed before it could be sent: {}", payment.id, e
+            );
+            continue;
+        }
+
+        match send(transaction_service, &payment).await {
+            Ok(tx_id) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Recurring payment {} sent as TxId: {}", payment.id, tx_id
+                );
+                record(db, &payment, Some(tx_id), None);
+                publish(event_publisher, TransactionEvent::RecurringPaymentSucceeded {
+                    payment_id: payment.id,
+                    tx_id,
+                });
+                if payment
+                    .end_at
+                    .map(|end_at| following_payment_at >= end_at)
+                    .unwrap_or(false)
+                {
+                    complete(db, event_publisher, &payment);
+                }
+            },
+            Err(e) if is_insufficient_funds(&e) => {
+                let reason = e.to_string();
+                warn!(
+                    target: LOG_TARGET,
+                    "Pausing recurring payment {} because of insufficient funds: {}", payment.id, reason
+                );
+                // The payment that was due is sent when the plan is resumed
+                match db.update_recurring_payment_status(
+                    payment.id,
+                    &[RecurringPaymentStatus::Active],
+                    RecurringPaymentStatus::Paused,
+                    Some(payment.next_payment_at),
+                    Some(reason.clone()),
+                ) {
+                    Ok(()) => publish(event_publisher, TransactionEvent::RecurringPaymentPaused {
+                        payment_id: payment.id,
+                        reason,
+                    }),
+                    Err(e) => warn!(
+                        target: LOG_TARGET,
+                        "Could not pause recurring payment {}: {}", payment.id, e
+                    ),
+                }
+            },
+            Err(e) => {
+                let reason = e.to_string();
+                warn!(
+                    target: LOG_TARGET,
+                    "Recurring payment {} could not be sent: {}", payment.id, reason
+                );
+                record(db, &payment, None, Some(reason.clone()));
+                publish(event_publisher, TransactionEvent::RecurringPaymentFailed {
+                    payment_id: payment.id,
+                    reason,
+                });
+            },
+        }
+    }
+}
+
+async fn send(
+    transaction_service: &mut TransactionServiceHandle,
+    payment: &RecurringPayment,
+) -> Result<TxId, TransactionServiceError> {
+    if payment.one_sided {
+        transaction_service
+            .send_one_sided_transaction(
+                payment.destination.clone(),
+                payment.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                payment.fee_per_gram,
+                payment.message.clone(),
+            )
+            .await
+    } else {
+        transaction_service
+            .send_transaction(
+                payment.destination.clone(),
+                payment.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                payment.fee_per_gram,
+                payment.message.clone(),
+            )
+            .await
+    }
+}
+
+fn is_insufficient_funds(error: &TransactionServiceError) -> bool {
+    matches!(
+        error,
+        TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds) |
+            TransactionServiceError::OutputManagerError(OutputManagerError::FundsPending)
+    )
+}
+
+fn complete<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    event_publisher: &TransactionEventSender,
+    payment: &RecurringPayment,
+) {
+    match db.update_recurring_payment_status(
+        payment.id,
+        &[RecurringPaymentStatus::Active],
+        RecurringPaymentStatus::Completed,
+        None,
+        None,
+    ) {
+        Ok(()) => {
+            info!(target: LOG_TARGET, "Recurring payment {} completed", payment.id);
+            publish(event_publisher, TransactionEvent::RecurringPaymentCompleted(payment.id));
+        },
+        Err(e) => warn!(
+            target: LOG_TARGET,
+            "Could not complete recurring payment {}: {}", payment.id, e
+        ),
+    }
+}
+
+fn record<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    payment: &RecurringPayment,
+    tx_id: Option<TxId>,
+    failure_reason: Option<String>,
+) {
+    if let Err(e) = db.record_recurring_payment_result(payment.id, tx_id, failure_reason) {
+        warn!(
+            target: LOG_TARGET,
+            "Could not record the result of recurring payment {}: {}", payment.id, e
+        );
+    }
+}
+
+fn publish(event_publisher: &TransactionEventSender, event: TransactionEvent) {
+    // Send only fails if there are no subscribers
+    let _size = event_publisher.send(Arc::new(event));
+}
@@ -0,0 +1,226 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{sync::Arc, time::Duration};
+
+use chrono::Utc;
+use log::*;
+use tari_common_types::transaction::TxId;
+use tari_core::transactions::transaction_components::OutputFeatures;
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::broadcast,
+    time::{self, MissedTickBehavior},
+};
+
+use crate::{
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    output_manager_service::UtxoSelectionCriteria,
+    transaction_service::{
+        handle::{TransactionEvent, TransactionEventSender, TransactionServiceHandle},
+        storage::{
+            database::{TransactionBackend, TransactionDatabase},
+            models::{ScheduledTransaction, ScheduledTransactionStatus},
+        },
+    },
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::scheduled_transactions";
+
+/// Sends pending scheduled transactions once their execution height or time is reached, and expires those whose expiry
+/// is reached first. Schedules are checked every `check_interval` and whenever the base node reports a new block.
+pub async fn run_scheduled_transactions<TBackend: 'static + TransactionBackend>(
+    db: TransactionDatabase<TBackend>,
+    mut transaction_service: TransactionServiceHandle,
+    mut base_node_service: BaseNodeServiceHandle,
+    event_publisher: TransactionEventSender,
+    check_interval: Duration,
+    mut shutdown_signal: ShutdownSignal,
+) {
+    let mut base_node_events = base_node_service.get_event_stream();
+    let mut interval = time::interval(check_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    loop {
+        let tip_height = tokio::select! {
+            _ = interval.tick() => match base_node_service.get_chain_metadata().await {
+                Ok(metadata) => metadata.map(|m| m.height_of_longest_chain()),
+                Err(e) => {
+                    debug!(target: LOG_TARGET, "Could not fetch chain metadata: {}", e);
+                    None
+                },
+            },
+            event = base_node_events.recv() => match event {
+                Ok(event) => match *event {
+                    BaseNodeEvent::NewBlockDetected(_, height) => Some(height),
+                    _ => continue,
+                },
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    trace!(target: LOG_TARGET, "Scheduled transactions task lagged {} base node events", n);
+                    continue;
+                },
+                Err(broadcast::error::RecvError::Closed) => {
+                    info!(target: LOG_TARGET, "Base node event stream closed, stopping scheduled transactions task");
+                    break;
+                },
+            },
+            _ = shutdown_signal.wait() => {
+                info!(target: LOG_TARGET, "Scheduled transactions task shutting down because it received the shutdown signal");
+                break;
+            },
+        };
+
+        process_scheduled_transactions(&db, &mut transaction_service, &event_publisher, tip_height).await;
+    }
+}
+
+async fn process_scheduled_transactions<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    transaction_service: &mut TransactionServiceHandle,
+    event_publisher: &TransactionEventSender,
+    tip_height: Option<u64>,
+) {
+    let pending = match db.fetch_scheduled_transactions(Some(ScheduledTransactionStatus::Pending)) {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!(target: LOG_TARGET, "Problem retrieving scheduled transactions: {}", e);
+            return;
+        },
+    };
+
+    for scheduled in pending {
+        let now = Utc::now().naive_utc();
+        if scheduled.is_expired(tip_height, now) {
+            if transition(db, &scheduled, ScheduledTransactionStatus::Expired, None, None) {
+                info!(target: LOG_TARGET, "Scheduled transaction {} expired", scheduled.id);
+                publish(
+                    event_publisher,
+                    TransactionEvent::ScheduledTransactionExpired(scheduled.id),
+                );
+            }
+            continue;
+        }
+        if !scheduled.is_due(tip_height, now) {
+            continue;
+        }
+        // Claim the schedule before sending so that a concurrent cancellation cannot race with the send
+        if !transition(db, &scheduled, ScheduledTransactionStatus::Triggered, None, None) {
+            continue;
+        }
+        info!(
+            target: LOG_TARGET,
+            "Scheduled transaction {} triggered ({})", scheduled.id, scheduled.execute_at
+        );
+        publish(
+            event_publisher,
+            TransactionEvent::ScheduledTransactionTriggered(scheduled.id),
+        );
+
+        let mut triggered = scheduled.clone();
+        triggered.status = ScheduledTransactionStatus::Triggered;
+        match send(transaction_service, &scheduled).await {
+            Ok(tx_id) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Scheduled transaction {} sent as TxId: {}", scheduled.id, tx_id
+                );
+                transition(db, &triggered, ScheduledTransactionStatus::Completed, Some(tx_id), None);
+                publish(event_publisher, TransactionEvent::ScheduledTransactionSucceeded {
+                    schedule_id: scheduled.id,
+                    tx_id,
+                });
+            },
+            Err(reason) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Scheduled transaction {} could not be sent: {}", scheduled.id, reason
+                );
+                transition(
+                    db,
+                    &triggered,
+                    ScheduledTransactionStatus::Failed,
+                    None,
+                    Some(reason.clone()),
+                );
+                publish(event_publisher, TransactionEvent::ScheduledTransactionFailed {
+                    schedule_id: scheduled.id,
+                    reason,
+                });
+            },
+        }
+    }
+}
+
+async fn send(
+    transaction_service: &mut TransactionServiceHandle,
+    scheduled: &ScheduledTransaction,
+) -> Result<TxId, String> {
+    let result = if scheduled.one_sided {
+        transaction_service
+            .send_one_sided_transaction(
+                scheduled.destination.clone(),
+                scheduled.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                scheduled.fee_per_gram,
+                scheduled.message.clone(),
+            )
+            .await
+    } else {
+        transaction_service
+            .send_transaction(
+                scheduled.destination.clone(),
+                scheduled.amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                scheduled.fee_per_gram,
+                scheduled.message.clone(),
+            )
+            .await
+    };
+    result.map_err(|e| e.to_string())
+}
+
+/// Moves the schedule out of its current status, returning false if it was changed underneath us (e.g. cancelled)
+fn transition<TBackend: 'static + TransactionBackend>(
+    db: &TransactionDatabase<TBackend>,
+    scheduled: &ScheduledTransaction,
+    to: ScheduledTransactionStatus,
+    tx_id: Option<TxId>,
+    failure_reason: Option<String>,
+) -> bool {
+    match db.update_scheduled_transaction_status(scheduled.id, scheduled.status, to, tx_id, failure_reason) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!(
+                target: LOG_TARGET,
+                "Could not move scheduled transaction {} from {} to {}: {}", scheduled.id, scheduled.status, to, e
+            );
+            false
+        },
+    }
+}
+
+fn publish(event_publisher: &TransactionEventSender, event: TransactionEvent) {
+    // Send only fails if there are no subscribers
+    let _size = event_publisher.send(Arc::new(event));
+}
@@ -0,0 +1,262 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use log::*;
+use tari_common_types::transaction::TxId;
+use tari_comms::types::CommsPublicKey;
+use tari_comms_dht::{
+    domain_message::OutboundDomainMessage,
+    outbound::{OutboundEncryption, OutboundMessageRequester, SendMessageResponse},
+};
+use tari_core::transactions::transaction_protocol::proto;
+use tari_p2p::tari_message::TariMessageType;
+
+use crate::transaction_service::{
+    config::TransactionRoutingMechanism,
+    error::TransactionServiceError,
+    storage::{
+        database::{TransactionBackend, TransactionDatabase},
+        models::{OutboundMessageStatus, OutboundMessageType, QueuedOutboundMessage},
+    },
+    tasks::wait_on_dial::wait_on_dial,
+};
+
+const LOG_TARGET: &str = "wallet::transaction_service::tasks::send_queued_outbound_message";
+
+/// Deliver the queued outbound message of the given type for a transaction. The transaction protocols send their
+/// messages and retries through this so that the persisted attempt count bounds them across restarts. Nothing is sent
+/// if the message is no longer queued or has been given up on. Returns whether the message was handed to the network.
+pub async fn deliver_queued_outbound_message<TBackend: TransactionBackend + 'static>(
+    tx_id: TxId,
+    message_type: OutboundMessageType,
+    db: TransactionDatabase<TBackend>,
+    outbound_message_service: OutboundMessageRequester,
+    direct_send_timeout: Duration,
+    transaction_routing_mechanism: TransactionRoutingMechanism,
+    max_attempts: u32,
+) -> Result<bool, TransactionServiceError> {
+    match db.fetch_outbound_message(tx_id, message_type)? {
+        Some(message) if message.status != OutboundMessageStatus::Failed => {
+            send_queued_outbound_message(
+                message,
+                db,
+                outbound_message_service,
+                direct_send_timeout,
+                transaction_routing_mechanism,
+                max_attempts,
+            )
+            .await
+        },
+        Some(message) => {
+            debug!(
+                target: LOG_TARGET,
+                "Not sending {} message (TxId: {}), it was given up on after {} attempts",
+                message_type,
+                tx_id,
+                message.attempts
+            );
+            Ok(false)
+        },
+        None => {
+            debug!(
+                target: LOG_TARGET,
+                "Not sending {} message (TxId: {}), it is no longer queued", message_type, tx_id
+            );
+            Ok(false)
+        },
+    }
+}
+
+/// A task to redeliver a transaction protocol message that was persisted in the outbound message queue. The outcome of
+/// the attempt is recorded against the queued message: it is only marked as delivered once it was sent directly to the
+/// counterparty, as a message accepted for store-and-forward may still never arrive. Once `max_attempts` is reached
+/// without a direct delivery the message is marked as failed and will no longer be retried.
+pub async fn send_queued_outbound_message<TBackend: TransactionBackend + 'static>(
+    message: QueuedOutboundMessage,
+    db: TransactionDatabase<TBackend>,
+    mut outbound_message_service: OutboundMessageRequester,
+    direct_send_timeout: Duration,
+    transaction_routing_mechanism: TransactionRoutingMechanism,
+    max_attempts: u32,
+) -> Result<bool, TransactionServiceError> {
+    let tx_id = message.tx_id;
+    let message_type = message.message_type;
+    let destination = message.destination_address.public_key().clone();
+    let tari_message_type = message_type.as_tari_message_type();
+
+    let (direct_send_result, store_and_forward_send_result) = match message_type {
+        OutboundMessageType::SenderPartialTransaction => {
+            let msg = decode_payload::<proto::TransactionSenderMessage>(&message.payload)?;
+            send_message(
+                &message,
+                destination,
+                tari_message_type,
+                msg,
+                &mut outbound_message_service,
+                direct_send_timeout,
+                transaction_routing_mechanism,
+            )
+            .await
+        },
+        OutboundMessageType::ReceiverPartialTransactionReply => {
+            let msg = decode_payload::<proto::RecipientSignedMessage>(&message.payload)?;
+            send_message(
+                &message,
+                destination,
+                tari_message_type,
+                msg,
+                &mut outbound_message_service,
+                direct_send_timeout,
+                transaction_routing_mechanism,
+            )
+            .await
+        },
+        OutboundMessageType::TransactionFinalized => {
+            let msg = decode_payload::<proto::TransactionFinalizedMessage>(&message.payload)?;
+            send_message(
+                &message,
+                destination,
+                tari_message_type,
+                msg,
+                &mut outbound_message_service,
+                direct_send_timeout,
+                transaction_routing_mechanism,
+            )
+            .await
+        },
+    };
+
+    let status = if direct_send_result {
+        OutboundMessageStatus::Delivered
+    } else if message.attempts + 1 >= max_attempts {
+        warn!(
+            target: LOG_TARGET,
+            "Giving up on queued {} message (TxId: {}) after {} attempts",
+            message_type,
+            tx_id,
+            message.attempts + 1
+        );
+        OutboundMessageStatus::Failed
+    } else {
+        OutboundMessageStatus::Pending
+    };
+    db.record_outbound_message_attempt(tx_id, message_type, status)?;
+
+    Ok(direct_send_result || store_and_forward_send_result)
+}
+
+fn decode_payload<T: prost::Message + Default>(payload: &[u8]) -> Result<T, TransactionServiceError> {
+    T::decode(payload).map_err(|e| TransactionServiceError::ProtobufConversionError(e.to_string()))
+}
+
+async fn send_message<T: prost::Message + Clone>(
+    message: &QueuedOutboundMessage,
+    destination: CommsPublicKey,
+    tari_message_type: TariMessageType,
+    msg: T,
+    outbound_message_service: &mut OutboundMessageRequester,
+    direct_send_timeout: Duration,
+    transaction_routing_mechanism: TransactionRoutingMechanism,
+) -> (bool, bool) {
+    let tx_id = message.tx_id;
+    let label = message.message_type.to_string();
+    let mut direct_send_result = false;
+    let mut store_and_forward_send_result = false;
+
+    if transaction_routing_mechanism != TransactionRoutingMechanism::StoreAndForwardOnly {
+        match outbound_message_service
+            .send_direct_unencrypted(
+                destination.clone(),
+                OutboundDomainMessage::new(&tari_message_type, msg.clone()),
+                format!("wallet queued {}", label),
+            )
+            .await
+        {
+            Ok(SendMessageResponse::Queued(send_states)) => {
+                direct_send_result =
+                    wait_on_dial(send_states, tx_id, destination.clone(), &label, direct_send_timeout).await;
+            },
+            Ok(SendMessageResponse::Failed(err)) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Queued {} Send Direct for TxID {} failed: {}", label, tx_id, err
+                );
+            },
+            Ok(SendMessageResponse::PendingDiscovery(rx)) => match rx.await {
+                Ok(SendMessageResponse::Queued(send_states)) => {
+                    direct_send_result =
+                        wait_on_dial(send_states, tx_id, destination.clone(), &label, direct_send_timeout).await;
+                },
+                Ok(SendMessageResponse::Failed(err)) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Queued {} for TxID {} failed, discovery failed: {}", label, tx_id, err
+                    );
+                },
+                Ok(SendMessageResponse::PendingDiscovery(_)) => unreachable!(),
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Error waiting for discovery while sending queued {} for TxID {}: {:?}", label, tx_id, e
+                    );
+                },
+            },
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Queued {} Direct Send failed: {:?}", label, e);
+            },
+        }
+    }
+
+    if transaction_routing_mechanism != TransactionRoutingMechanism::DirectOnly {
+        match outbound_message_service
+            .closest_broadcast(
+                destination.clone(),
+                OutboundEncryption::encrypt_for(destination),
+                vec![],
+                OutboundDomainMessage::new(&tari_message_type, msg),
+            )
+            .await
+        {
+            Ok(send_states) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Sending queued {} (TxId: {}) to Neighbours for Store and Forward successful with Message Tags: \
+                     {:?}",
+                    label,
+                    tx_id,
+                    send_states.to_tags(),
+                );
+                store_and_forward_send_result = true;
+            },
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Sending queued {} (TxId: {}) to neighbours for Store and Forward failed: {:?}", label, tx_id, e,
+                );
+            },
+        }
+    }
+
+    (direct_send_result, store_and_forward_send_result)
+}
@@ -0,0 +1,407 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Passphrase re-authentication for sensitive wallet operations.
+//!
+//! A [ReauthenticationGuard] is attached to the wallet's service handles so that every front end (gRPC, FFI, console)
+//! has the same policy enforced: operations covered by the [ReauthenticationPolicy] fail with
+//! [ReauthenticationError::PassphraseRequired] unless the caller supplies the wallet passphrase again.
+//!
+//! The send threshold applies to the total sent within the policy's send window, so a large amount cannot be sent
+//! without the passphrase by splitting it into several smaller sends.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tari_core::transactions::tari_amount::MicroMinotari;
+use tari_utilities::SafePassword;
+use thiserror::Error;
+
+use crate::{
+    error::WalletStorageError,
+    storage::database::{WalletBackend, WalletDatabase},
+};
+
+#[derive(Debug, Error)]
+pub enum ReauthenticationError {
+    #[error("The wallet passphrase is required to {0}")]
+    PassphraseRequired(SensitiveOperation),
+    #[error("The supplied wallet passphrase is incorrect")]
+    InvalidPassphrase,
+    #[error("Unable to verify the wallet passphrase: {0}")]
+    VerificationFailed(String),
+}
+
+/// An operation that may require the wallet passphrase to be supplied again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SensitiveOperation {
+    /// Sending the given amount of funds
+    Send(MicroMinotari),
+    /// Spending the wallet's outputs where the amount leaving the wallet is only known once the transaction is built,
+    /// for example fee bumps, coin splits or setting up recurring payments
+    Spend,
+    /// Revealing the wallet seed words
+    SeedExport,
+    /// Creating a backup of the wallet
+    BackupCreation,
+}
+
+impl fmt::Display for SensitiveOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SensitiveOperation::Send(amount) => write!(f, "send {}", amount),
+            SensitiveOperation::Spend => write!(f, "spend the wallet's funds"),
+            SensitiveOperation::SeedExport => write!(f, "export the seed words"),
+            SensitiveOperation::BackupCreation => write!(f, "create a backup"),
+        }
+    }
+}
+
+/// Which operations require the wallet passphrase to be supplied again. The default policy requires nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReauthenticationPolicy {
+    /// Sends that bring the total sent within `send_window` to at least this amount require re-authentication, as do
+    /// all spends of an amount not known up front
+    pub send_threshold: Option<MicroMinotari>,
+    pub send_window: Duration,
+    pub seed_export: bool,
+    pub backup_creation: bool,
+}
+
+impl Default for ReauthenticationPolicy {
+    fn default() -> Self {
+        Self {
+            send_threshold: None,
+            send_window: Duration::from_secs(24 * 60 * 60),
+            seed_export: false,
+            backup_creation: false,
+        }
+    }
+}
+
+impl ReauthenticationPolicy {
+    /// Whether `operation` requires the passphrase, where a send amount is the total sent within the send window
+    /// including this send
+    pub fn requires_passphrase(&self, operation: SensitiveOperation) -> bool {
+        match operation {
+            SensitiveOperation::Send(amount) => self.send_threshold.map(|t| amount >= t).unwrap_or(false),
+            SensitiveOperation::Spend => self.send_threshold.is_some(),
+            SensitiveOperation::SeedExport => self.seed_export,
+            SensitiveOperation::BackupCreation => self.backup_creation,
+        }
+    }
+}
+
+/// Verifies a passphrase against the one protecting the wallet database.
+pub trait PassphraseVerifier: Send + Sync {
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError>;
+}
+
+impl<T> PassphraseVerifier for WalletDatabase<T>
+where T: WalletBackend + 'static
+{
+    fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+        WalletDatabase::verify_passphrase(self, passphrase)
+    }
+}
+
+/// Enforces a [ReauthenticationPolicy] using the wallet database to check supplied passphrases. Clones share the
+/// record of recent sends, so the same guard should be attached to every handle that can send.
+#[derive(Clone)]
+pub struct ReauthenticationGuard {
+    policy: ReauthenticationPolicy,
+    verifier: Arc<dyn PassphraseVerifier>,
+    recent_sends: Arc<Mutex<RecentSends>>,
+}
+
+impl ReauthenticationGuard {
+    pub fn new<V: PassphraseVerifier + 'static>(policy: ReauthenticationPolicy, verifier: V) -> Self {
+        Self {
+            policy,
+            verifier: Arc::new(verifier),
+            recent_sends: Arc::new(Mutex::new(RecentSends::default())),
+        }
+    }
+
+    pub fn policy(&self) -> &ReauthenticationPolicy {
+        &self.policy
+    }
+
+    /// Checks that `operation` may proceed. An authorized send is counted towards the send window whether or not it
+    /// goes through, so failed sends are not a way around the threshold. A send is reserved before the passphrase is
+    /// checked, so concurrent sends count each other, and the reservation is released if the check fails. Passphrase
+    /// verification runs the wallet's key derivation function, so async callers should prefer
+    /// [ReauthenticationGuard::authorize_async].
+    pub fn authorize(
+        &self,
+        operation: SensitiveOperation,
+        passphrase: Option<&SafePassword>,
+    ) -> Result<(), ReauthenticationError> {
+        let reservation = self.reserve(operation);
+        if !reservation.requires_passphrase {
+            return Ok(());
+        }
+        let result = self.verify(operation, passphrase);
+        if result.is_err() {
+            self.release(&reservation);
+        }
+        result
+    }
+
+    pub async fn authorize_async(
+        &self,
+        operation: SensitiveOperation,
+        passphrase: Option<SafePassword>,
+    ) -> Result<(), ReauthenticationError> {
+        let reservation = self.reserve(operation);
+        if !reservation.requires_passphrase {
+            return Ok(());
+        }
+        let guard = self.clone();
+        let result = tokio::task::spawn_blocking(move || guard.verify(operation, passphrase.as_ref()))
+            .await
+            .unwrap_or_else(|e| Err(ReauthenticationError::VerificationFailed(e.to_string())));
+        if result.is_err() {
+            self.release(&reservation);
+        }
+        result
+    }
+
+    /// Decides whether `operation` requires the passphrase and, for a send, records it in the send window. Both happen
+    /// under the same lock, so two sends cannot both be judged against a total that excludes the other.
+    fn reserve(&self, operation: SensitiveOperation) -> Reservation {
+        let SensitiveOperation::Send(amount) = operation else {
+            return Reservation {
+                id: None,
+                requires_passphrase: self.policy.requires_passphrase(operation),
+            };
+        };
+        if self.policy.send_threshold.is_none() {
+            return Reservation {
+                id: None,
+                requires_passphrase: false,
+            };
+        }
+        let mut recent_sends = self.recent_sends.lock().expect("recent sends lock poisoned");
+        let total = recent_sends.total_within(self.policy.send_window) + amount;
+        Reservation {
+            id: Some(recent_sends.push(amount)),
+            requires_passphrase: self.policy.requires_passphrase(SensitiveOperation::Send(total)),
+        }
+    }
+
+    fn release(&self, reservation: &Reservation) {
+        if let Some(id) = reservation.id {
+            self.recent_sends.lock().expect("recent sends lock poisoned").remove(id);
+        }
+    }
+
+    fn verify(
+        &self,
+        operation: SensitiveOperation,
+        passphrase: Option<&SafePassword>,
+    ) -> Result<(), ReauthenticationError> {
+        let passphrase = passphrase.ok_or(ReauthenticationError::PassphraseRequired(operation))?;
+        match self.verifier.verify_passphrase(passphrase) {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(ReauthenticationError::InvalidPassphrase),
+            Err(e) => Err(ReauthenticationError::VerificationFailed(e.to_string())),
+        }
+    }
+}
+
+struct Reservation {
+    /// The id of the send recorded in the send window, if one was recorded
+    id: Option<u64>,
+    requires_passphrase: bool,
+}
+
+/// The sends authorized, or awaiting a passphrase check, within the send window, oldest first
+#[derive(Default)]
+struct RecentSends {
+    next_id: u64,
+    sends: VecDeque<(u64, Instant, MicroMinotari)>,
+}
+
+impl RecentSends {
+    /// Drops the sends that fell out of the window and returns the total of the rest
+    fn total_within(&mut self, window: Duration) -> MicroMinotari {
+        let now = Instant::now();
+        while self
+            .sends
+            .front()
+            .map_or(false, |(_, at, _)| now.duration_since(*at) > window)
+        {
+            self.sends.pop_front();
+        }
+        self.sends.iter().map(|(_, _, amount)| *amount).sum()
+    }
+
+    fn push(&mut self, amount: MicroMinotari) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.sends.push_back((id, Instant::now(), amount));
+        id
+    }
+
+    fn remove(&mut self, id: u64) {
+        self.sends.retain(|(send_id, _, _)| *send_id != id);
+    }
+}
+
+impl fmt::Debug for ReauthenticationGuard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReauthenticationGuard")
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct FixedPassphrase(&'static str);
+
+    impl PassphraseVerifier for FixedPassphrase {
+        fn verify_passphrase(&self, passphrase: &SafePassword) -> Result<bool, WalletStorageError> {
+            Ok(&passphrase.reveal()[..] == self.0.as_bytes())
+        }
+    }
+
+    #[test]
+    fn it_enforces_the_policy() {
+        let policy = ReauthenticationPolicy {
+            send_threshold: Some(MicroMinotari::from(1_000)),
+            seed_export: true,
+            ..Default::default()
+        };
+        let guard = ReauthenticationGuard::new(policy, FixedPassphrase("secret"));
+
+        assert!(guard.authorize(SensitiveOperation::Send(999.into()), None).is_ok());
+        assert!(guard.authorize(SensitiveOperation::BackupCreation, None).is_ok());
+        assert!(matches!(
+            guard.authorize(SensitiveOperation::Send(1_000.into()), None),
+            Err(ReauthenticationError::PassphraseRequired(_))
+        ));
+        assert!(matches!(
+            guard.authorize(SensitiveOperation::Spend, None),
+            Err(ReauthenticationError::PassphraseRequired(_))
+        ));
+        assert!(matches!(
+            guard.authorize(SensitiveOperation::SeedExport, Some(&SafePassword::from("wrong"))),
+            Err(ReauthenticationError::InvalidPassphrase)
+        ));
+        assert!(guard
+            .authorize(SensitiveOperation::SeedExport, Some(&SafePassword::from("secret")))
+            .is_ok());
+    }
+
+    #[test]
+    fn it_applies_the_send_threshold_to_the_total_sent_within_the_window() {
+        let policy = ReauthenticationPolicy {
+            send_threshold: Some(MicroMinotari::from(1_000)),
+            ..Default::default()
+        };
+        let guard = ReauthenticationGuard::new(policy, FixedPassphrase("secret"));
+        let clone = guard.clone();
+
+        assert!(guard.authorize(SensitiveOperation::Send(600.into()), None).is_ok());
+        assert!(matches!(
+            clone.authorize(SensitiveOperation::Send(400.into()), None),
+            Err(ReauthenticationError::PassphraseRequired(_))
+        ));
+        assert!(clone
+            .authorize(
+                SensitiveOperation::Send(400.into()),
+                Some(&SafePassword::from("secret"))
+            )
+            .is_ok());
+        assert_eq!(
+            guard
+                .recent_sends
+                .lock()
+                .unwrap()
+                .total_within(guard.policy.send_window),
+            MicroMinotari::from(1_000)
+        );
+
+        let policy = ReauthenticationPolicy {
+            send_threshold: Some(MicroMinotari::from(1_000)),
+            send_window: Duration::ZERO,
+            ..Default::default()
+        };
+        let guard = ReauthenticationGuard::new(policy, FixedPassphrase("secret"));
+        assert!(guard.authorize(SensitiveOperation::Send(600.into()), None).is_ok());
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(guard.authorize(SensitiveOperation::Send(600.into()), None).is_ok());
+    }
+
+    #[test]
+    fn it_releases_a_send_when_the_passphrase_check_fails() {
+        let policy = ReauthenticationPolicy {
+            send_threshold: Some(MicroMinotari::from(1_000)),
+            ..Default::default()
+        };
+        let guard = ReauthenticationGuard::new(policy, FixedPassphrase("secret"));
+
+        assert!(guard.authorize(SensitiveOperation::Send(600.into()), None).is_ok());
+        assert!(matches!(
+            guard.authorize(SensitiveOperation::Send(600.into()), Some(&SafePassword::from("wrong"))),
+            Err(ReauthenticationError::InvalidPassphrase)
+        ));
+        assert!(matches!(
+            guard.authorize(SensitiveOperation::Send(600.into()), None),
+            Err(ReauthenticationError::PassphraseRequired(_))
+        ));
+        // Only the authorized send counts towards the window
+        assert!(guard.authorize(SensitiveOperation::Send(399.into()), None).is_ok());
+    }
+
+    #[test]
+    fn it_counts_concurrent_sends_towards_the_threshold() {
+        let policy = ReauthenticationPolicy {
+            send_threshold: Some(MicroMinotari::from(1_000)),
+            ..Default::default()
+        };
+        let guard = ReauthenticationGuard::new(policy, FixedPassphrase("secret"));
+        let barrier = Arc::new(std::sync::Barrier::new(20));
+
+        let handles = (0..20)
+            .map(|_| {
+                let guard = guard.clone();
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    guard.authorize(SensitiveOperation::Send(100.into()), None).is_ok()
+                })
+            })
+            .collect::<Vec<_>>();
+        let authorized = handles.into_iter().map(|h| h.join().unwrap()).filter(|ok| *ok).count();
+        // 9 sends of 100 stay under the threshold, a 10th would reach it
+        assert_eq!(authorized, 9);
+    }
+}
@@ -23,9 +23,13 @@
 use std::{fmt, fmt::Display, sync::Arc};
 
 use tari_common::configuration::Network;
-use tari_common_types::tari_address::TariAddress;
+use tari_common_types::{
+    tari_address::TariAddress,
+    types::{PrivateKey, PublicKey},
+};
 use tari_comms::peer_manager::NodeIdentity;
-use tari_core::transactions::key_manager::TariKeyId;
+use tari_core::transactions::key_manager::{TariKeyId, ViewKeyExport};
+use tari_crypto::keys::PublicKey as PublicKeyTrait;
 
 #[derive(Clone, Debug)]
 pub struct WalletIdentity {
@@ -33,6 +37,8 @@ pub struct WalletIdentity {
     pub network: Network,
     pub address: TariAddress,
     pub wallet_node_key_id: TariKeyId,
+    /// Set if this is a watch-only wallet, which detects payments to the watched address but cannot spend
+    pub watch_only: Option<WatchOnlyIdentity>,
 }
 
 impl WalletIdentity {
@@ -46,8 +52,69 @@ impl WalletIdentity {
             network,
             address,
             wallet_node_key_id,
+            watch_only: None,
         }
     }
+
+    /// Turns this into the identity of a watch-only wallet for the address of `keys`. The node identity is still used
+    /// for communication, but the wallet address becomes the watched address.
+    pub fn with_watch_only_keys(mut self, keys: &WatchOnlyKeys) -> Self {
+        self.address = TariAddress::new(keys.spend_public_key.clone(), self.network);
+        self.watch_only = Some(WatchOnlyIdentity {
+            view_key_id: TariKeyId::Imported {
+                key: keys.view_public_key(),
+            },
+            spend_public_key: keys.spend_public_key.clone(),
+        });
+        self
+    }
+
+    pub fn is_watch_only(&self) -> bool {
+        self.watch_only.is_some()
+    }
+
+    /// The key used to detect one-sided payments and the public key they are paid to. For a watch-only wallet these
+    /// are the imported view key and the watched spend public key, otherwise both are the wallet node key.
+    pub fn one_sided_scanning_keys(&self) -> (TariKeyId, PublicKey) {
+        match &self.watch_only {
+            Some(watch_only) => (watch_only.view_key_id.clone(), watch_only.spend_public_key.clone()),
+            None => (self.wallet_node_key_id.clone(), self.node_identity.public_key().clone()),
+        }
+    }
+}
+
+/// The keys a watch-only wallet is initialized with. The private view key is enough to detect and decrypt one-sided
+/// payments to the address of the spend public key, while spending them is refused by the wallet services. Senders
+/// derive their shared secrets from the single public key in the address, so the view key has to be the one paired
+/// with that key for payments to be detected.
+#[derive(Clone)]
+pub struct WatchOnlyKeys {
+    pub view_key: PrivateKey,
+    pub spend_public_key: PublicKey,
+}
+
+impl WatchOnlyKeys {
+    pub fn view_public_key(&self) -> PublicKey {
+        PublicKey::from_secret_key(&self.view_key)
+    }
+}
+
+/// A watch-only wallet can be initialized from another wallet's view key export. The exported script keys are not
+/// needed, the watch-only wallet registers the one-sided payment script of the watched spend public key itself.
+impl From<ViewKeyExport> for WatchOnlyKeys {
+    fn from(export: ViewKeyExport) -> Self {
+        Self {
+            view_key: export.view_key,
+            spend_public_key: export.spend_public_key,
+        }
+    }
+}
+
+/// The key manager id of the imported view key and the watched spend public key of a watch-only wallet
+#[derive(Clone, Debug)]
+pub struct WatchOnlyIdentity {
+    pub view_key_id: TariKeyId,
+    pub spend_public_key: PublicKey,
 }
 
 impl Display for WalletIdentity {
@@ -26,7 +26,13 @@ use tari_comms::peer_manager::NodeId;
 use tari_core::transactions::tari_amount::MicroMinotari;
 use tokio::sync::{broadcast, watch};
 
-use crate::util::watch::Watch;
+use crate::{
+    util::watch::Watch,
+    utxo_scanner_service::{
+        throttle::{ScanRateLimiter, ScanThrottle},
+        throughput::ScanThroughput,
+    },
+};
 
 #[derive(Debug, Clone)]
 pub enum UtxoScannerEvent {
@@ -43,11 +49,21 @@ pub enum UtxoScannerEvent {
         retry_limit: usize,
         error: String,
     },
-    /// Progress of the recovery process (current_block, current_chain_height)
+    /// Progress of the recovery process (current_block, current_chain_height, value of the outputs recovered so far in
+    /// this scanning round, recent scanning rate and time remaining)
     Progress {
         current_height: u64,
         tip_height: u64,
+        value_recovered: MicroMinotari,
+        throughput: ScanThroughput,
     },
+    /// A batch of outputs was received from the base node and scanned, along with the totals of the scan so far
+    BatchScanned {
+        batch: ScanBatchStats,
+        totals: ScanStats,
+    },
+    /// The totals of a completed scan, published just before `Completed`
+    Summary(ScanStats),
     /// Completed Recovery (Number scanned, Num of Recovered outputs, Value of recovered outputs, Time taken)
     Completed {
         final_height: u64,
@@ -59,11 +75,134 @@ pub enum UtxoScannerEvent {
     ScanningFailed,
 }
 
+/// Statistics of a single batch of outputs received from the base node
+#[derive(Debug, Clone, Default)]
+pub struct ScanBatchStats {
+    /// The height of the block the outputs belong to
+    pub current_height: u64,
+    pub tip_height: u64,
+    pub num_outputs: u64,
+    /// The number of outputs in the batch that belong to the wallet and their value
+    pub num_found: u64,
+    pub value_found: MicroMinotari,
+    /// How long it took the base node to send the batch
+    pub rpc_latency: Duration,
+}
+
+/// Running totals of a scan
+#[derive(Debug, Clone, Default)]
+pub struct ScanStats {
+    pub start_height: u64,
+    /// The height of the last block that was scanned
+    pub current_height: u64,
+    pub num_blocks: u64,
+    pub num_outputs: u64,
+    pub num_found: u64,
+    pub value_recovered: MicroMinotari,
+    pub num_batches: u64,
+    pub total_rpc_latency: Duration,
+    pub time_taken: Duration,
+}
+
+impl ScanStats {
+    pub fn average_rpc_latency(&self) -> Duration {
+        self.total_rpc_latency
+            .checked_div(u32::try_from(self.num_batches).unwrap_or(u32::MAX))
+            .unwrap_or_default()
+    }
+
+    pub fn blocks_per_second(&self) -> f64 {
+        if self.time_taken.is_zero() {
+            return 0.0;
+        }
+        self.num_blocks as f64 / self.time_taken.as_secs_f64()
+    }
+}
+
+/// The stage a wallet recovery is at, see [RecoveryStatus]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RecoveryState {
+    /// No recovery has been started since the wallet started
+    #[default]
+    Idle,
+    Connecting,
+    Scanning,
+    /// A scanning round failed and is being retried
+    Retrying,
+    Completed,
+    /// All retries have been exhausted
+    Failed,
+}
+
+/// A snapshot of the progress of the latest wallet recovery, for UIs that poll rather than follow the event stream
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecoveryStatus {
+    pub state: RecoveryState,
+    pub current_height: u64,
+    pub tip_height: u64,
+    pub blocks_scanned: u64,
+    pub outputs_scanned: u64,
+    pub num_recovered: u64,
+    pub value_recovered: MicroMinotari,
+    pub throughput: ScanThroughput,
+}
+
+impl RecoveryStatus {
+    /// Updates the snapshot with an event published by the recovery
+    pub(crate) fn apply(&mut self, event: &UtxoScannerEvent) {
+        match event {
+            UtxoScannerEvent::ConnectingToBaseNode(_) => self.state = RecoveryState::Connecting,
+            UtxoScannerEvent::ConnectedToBaseNode(..) => self.state = RecoveryState::Scanning,
+            UtxoScannerEvent::ConnectionFailedToBaseNode { .. } | UtxoScannerEvent::ScanningRoundFailed { .. } => {
+                self.state = RecoveryState::Retrying;
+            },
+            UtxoScannerEvent::Progress {
+                current_height,
+                tip_height,
+                throughput,
+                ..
+            } => {
+                self.current_height = *current_height;
+                self.tip_height = *tip_height;
+                self.throughput = *throughput;
+            },
+            UtxoScannerEvent::BatchScanned { batch, totals } => {
+                self.current_height = batch.current_height;
+                self.tip_height = batch.tip_height;
+                self.blocks_scanned = totals.num_blocks;
+                self.outputs_scanned = totals.num_outputs;
+                self.num_recovered = totals.num_found;
+                self.value_recovered = totals.value_recovered;
+            },
+            UtxoScannerEvent::Summary(_) => {},
+            UtxoScannerEvent::Completed {
+                final_height,
+                num_recovered,
+                value_recovered,
+                ..
+            } => {
+                self.state = RecoveryState::Completed;
+                self.current_height = *final_height;
+                self.tip_height = *final_height;
+                self.num_recovered = *num_recovered;
+                self.value_recovered = *value_recovered;
+                self.throughput.eta = Some(Duration::ZERO);
+            },
+            UtxoScannerEvent::ScanningFailed => {
+                self.state = RecoveryState::Failed;
+                self.throughput.eta = None;
+            },
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct UtxoScannerHandle {
     event_sender: broadcast::Sender<UtxoScannerEvent>,
     one_sided_message_watch: Watch<String>,
     recovery_message_watch: Watch<String>,
+    rate_limiter: ScanRateLimiter,
+    recovery_status: Watch<RecoveryStatus>,
 }
 
 impl UtxoScannerHandle {
@@ -76,6 +215,8 @@ impl UtxoScannerHandle {
             event_sender,
             one_sided_message_watch,
             recovery_message_watch,
+            rate_limiter: ScanRateLimiter::default(),
+            recovery_status: Watch::new(RecoveryStatus::default()),
         }
     }
 
@@ -91,6 +232,28 @@ impl UtxoScannerHandle {
         self.recovery_message_watch.send(note);
     }
 
+    /// Changes the limits on scanning, which apply to every scanner of this wallet from their next block or request
+    pub fn set_scan_throttle(&mut self, throttle: ScanThrottle) {
+        self.rate_limiter.set_throttle(throttle);
+    }
+
+    pub fn get_scan_throttle(&self) -> ScanThrottle {
+        self.rate_limiter.throttle()
+    }
+
+    /// Returns the progress of the latest recovery of this wallet. Background scanning rounds are not reported.
+    pub fn get_recovery_status(&self) -> RecoveryStatus {
+        self.recovery_status.borrow().clone()
+    }
+
+    pub(crate) fn get_recovery_status_watch(&self) -> Watch<RecoveryStatus> {
+        self.recovery_status.clone()
+    }
+
+    pub(crate) fn get_rate_limiter(&self) -> ScanRateLimiter {
+        self.rate_limiter.clone()
+    }
+
     pub(crate) fn get_one_sided_payment_message_watcher(&self) -> watch::Receiver<String> {
         self.one_sided_message_watch.get_receiver()
     }
@@ -79,6 +79,7 @@ where T: WalletBackend + 'static
         // Register handle before waiting for handles to be ready
         let utxo_scanner_handle =
             UtxoScannerHandle::new(event_sender.clone(), one_sided_message_watch, recovery_message_watch);
+        let rate_limiter = utxo_scanner_handle.get_rate_limiter();
         context.register_handle(utxo_scanner_handle);
 
         let backend = self
@@ -99,6 +100,7 @@ where T: WalletBackend + 'static
                 .with_peers(vec![])
                 .with_retry_limit(2)
                 .with_mode(UtxoScannerMode::Scanning)
+                .with_rate_limiter(rate_limiter)
                 .build_with_resources(
                     backend,
                     comms_connectivity,
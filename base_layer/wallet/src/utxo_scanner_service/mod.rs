@@ -32,7 +32,11 @@ pub mod error;
 pub mod handle;
 pub mod initializer;
 pub mod service;
+pub mod throttle;
+pub mod throughput;
 mod utxo_scanner_task;
 pub mod uxto_scanner_service_builder;
 
 pub const RECOVERY_KEY: &str = "recovery_data";
+/// The client key under which the progress through the block being scanned is kept, see [service::ScanCheckpoint]
+pub const SCAN_CHECKPOINT_KEY: &str = "utxo_scan_checkpoint";
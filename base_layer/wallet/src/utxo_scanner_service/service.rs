@@ -20,9 +20,17 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::{
+    fmt,
+    fmt::{Display, Formatter},
+    str::FromStr,
+    time::Instant,
+};
+
 use chrono::NaiveDateTime;
 use futures::FutureExt;
 use log::*;
+use serde::{Deserialize, Serialize};
 use tari_common_types::types::HashOutput;
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::Peer, types::CommsPublicKey};
 use tari_core::transactions::{tari_amount::MicroMinotari, CryptoFactories};
@@ -39,9 +47,12 @@ use crate::{
     output_manager_service::handle::OutputManagerHandle,
     storage::database::{WalletBackend, WalletDatabase},
     transaction_service::handle::TransactionServiceHandle,
-    util::wallet_identity::WalletIdentity,
+    util::{wallet_identity::WalletIdentity, watch::Watch},
     utxo_scanner_service::{
-        handle::UtxoScannerEvent,
+        error::UtxoScannerError,
+        handle::{RecoveryStatus, ScanStats, UtxoScannerEvent},
+        throttle::ScanRateLimiter,
+        throughput::ThroughputTracker,
         utxo_scanner_task::UtxoScannerTask,
         uxto_scanner_service_builder::{UtxoScannerMode, UtxoScannerServiceBuilder},
     },
@@ -57,6 +68,8 @@ pub struct UtxoScannerService<TBackend, TWalletConnectivity> {
     pub(crate) retry_limit: usize,
     pub(crate) peer_seeds: Vec<CommsPublicKey>,
     pub(crate) mode: UtxoScannerMode,
+    pub(crate) max_blocks: Option<u64>,
+    pub(crate) full_scan: bool,
     pub(crate) shutdown_signal: ShutdownSignal,
     pub(crate) event_sender: broadcast::Sender<UtxoScannerEvent>,
     pub(crate) base_node_service: BaseNodeServiceHandle,
@@ -73,6 +86,8 @@ where
         peer_seeds: Vec<CommsPublicKey>,
         retry_limit: usize,
         mode: UtxoScannerMode,
+        max_blocks: Option<u64>,
+        full_scan: bool,
         resources: UtxoScannerResources<TBackend, TWalletConnectivity>,
         shutdown_signal: ShutdownSignal,
         event_sender: broadcast::Sender<UtxoScannerEvent>,
@@ -85,6 +100,8 @@ where
             peer_seeds,
             retry_limit,
             mode,
+            max_blocks,
+            full_scan,
             shutdown_signal,
             event_sender,
             base_node_service,
@@ -102,7 +119,12 @@ where
             peer_index: 0,
             num_retries: 1,
             mode: self.mode.clone(),
+            max_blocks: self.max_blocks,
+            full_scan: self.full_scan,
             shutdown_signal,
+            stats: ScanStats::default(),
+            throughput: ThroughputTracker::default(),
+            started_at: Instant::now(),
         }
     }
 
@@ -114,6 +136,11 @@ where
         self.event_sender.subscribe()
     }
 
+    /// Runs a single scanning round and waits for it to finish, rather than starting a new round for every new block
+    pub async fn run_once(self) -> Result<(), UtxoScannerError> {
+        self.create_task(self.shutdown_signal.clone()).run().await
+    }
+
     pub async fn run(mut self) -> Result<(), WalletError> {
         info!(target: LOG_TARGET, "UTXO scanning service starting");
 
@@ -198,6 +225,9 @@ pub struct UtxoScannerResources<TBackend, TWalletConnectivity> {
     pub factories: CryptoFactories,
     pub recovery_message: String,
     pub one_sided_payment_message: String,
+    pub rate_limiter: ScanRateLimiter,
+    /// Where the progress of a recovery is published, for polling through the scanner handle
+    pub recovery_status: Watch<RecoveryStatus>,
 }
 
 #[derive(Debug, Clone)]
@@ -208,3 +238,32 @@ pub struct ScannedBlock {
     pub amount: Option<MicroMinotari>,
     pub timestamp: NaiveDateTime,
 }
+
+/// How far the scanner got into the block it was scanning, persisted after every batch of outputs so that an
+/// interrupted scan resumes within that block rather than at its start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    pub header_hash: HashOutput,
+    pub height: u64,
+    /// The number of the block's outputs that have been scanned
+    pub output_index: u64,
+    /// The number of outputs recovered from the scanned part of the block
+    pub num_outputs: u64,
+    /// The value recovered from the scanned part of the block
+    pub amount: MicroMinotari,
+}
+
+impl FromStr for ScanCheckpoint {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+impl Display for ScanCheckpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).map_err(|_| fmt::Error)?;
+        f.write_str(&json)
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard},
+    time::Duration,
+};
+
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::{sleep_until, Instant},
+};
+
+/// Limits on how hard the UTXO scanner may use the link to the base node, e.g. so that a mobile wallet on a metered
+/// connection can scan in the background without saturating it. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScanThrottle {
+    /// The maximum number of blocks scanned per second
+    pub max_blocks_per_second: Option<u32>,
+    /// The maximum number of RPC requests to the base node that may be in flight at the same time
+    pub max_concurrent_requests: Option<usize>,
+}
+
+/// Applies a [ScanThrottle] to every scanner that shares it. The throttle can be changed while scanning and takes
+/// effect from the next block or request.
+#[derive(Clone)]
+pub struct ScanRateLimiter {
+    state: Arc<Mutex<RateLimiterState>>,
+    requests: Arc<Semaphore>,
+}
+
+struct RateLimiterState {
+    throttle: ScanThrottle,
+    // The number of permits the request semaphore was sized with, including those that are currently acquired
+    num_permits: usize,
+    next_block_at: Option<Instant>,
+}
+
+impl ScanRateLimiter {
+    pub fn new(throttle: ScanThrottle) -> Self {
+        let rate_limiter = Self {
+            state: Arc::new(Mutex::new(RateLimiterState {
+                throttle: ScanThrottle::default(),
+                num_permits: 0,
+                next_block_at: None,
+            })),
+            requests: Arc::new(Semaphore::new(0)),
+        };
+        rate_limiter.set_throttle(throttle);
+        rate_limiter
+    }
+
+    pub fn throttle(&self) -> ScanThrottle {
+        self.lock_state().throttle
+    }
+
+    pub fn set_throttle(&self, throttle: ScanThrottle) {
+        // A limit of zero would stall the scanner for good, so it is taken to mean no limit
+        let throttle = ScanThrottle {
+            max_blocks_per_second: throttle.max_blocks_per_second.filter(|n| *n > 0),
+            max_concurrent_requests: throttle.max_concurrent_requests.filter(|n| *n > 0),
+        };
+        let mut state = self.lock_state();
+        state.throttle = throttle;
+        if throttle.max_blocks_per_second.is_none() {
+            state.next_block_at = None;
+        }
+        // The semaphore grows here, it only shrinks as permits are returned (see `acquire_request`)
+        if let Some(limit) = throttle.max_concurrent_requests {
+            if limit > state.num_permits {
+                self.requests.add_permits(limit - state.num_permits);
+                state.num_permits = limit;
+            }
+        }
+    }
+
+    /// Waits until another RPC request may be made to the base node. The request should be held for as long as the
+    /// returned permit lives, `None` is returned if the number of requests is not limited.
+    pub(crate) async fn acquire_request(&self) -> Option<OwnedSemaphorePermit> {
+        loop {
+            self.lock_state().throttle.max_concurrent_requests?;
+            let permit = self
+                .requests
+                .clone()
+                .acquire_owned()
+                .await
+                .expect("the request semaphore is never closed");
+            let mut state = self.lock_state();
+            match state.throttle.max_concurrent_requests {
+                // The limit was lowered while this permit was in use, so retire it rather than hand it out again
+                Some(limit) if state.num_permits > limit => {
+                    permit.forget();
+                    state.num_permits -= 1;
+                },
+                Some(_) => return Some(permit),
+                None => return None,
+            }
+        }
+    }
+
+    /// Waits until the next block may be scanned
+    pub(crate) async fn pace_block(&self) {
+        let scan_at = {
+            let mut state = self.lock_state();
+            let blocks_per_second = match state.throttle.max_blocks_per_second {
+                Some(n) => n,
+                None => return,
+            };
+            let now = Instant::now();
+            let scan_at = state.next_block_at.map_or(now, |at| at.max(now));
+            state.next_block_at = Some(scan_at + Duration::from_secs(1) / blocks_per_second);
+            scan_at
+        };
+        sleep_until(scan_at).await;
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, RateLimiterState> {
+        // PANIC: the lock is never held across an await or a call that can panic
+        self.state.lock().expect("the rate limiter lock is never poisoned")
+    }
+}
+
+impl Default for ScanRateLimiter {
+    fn default() -> Self {
+        Self::new(ScanThrottle::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn it_retires_requests_when_the_limit_is_lowered() {
+        let rate_limiter = ScanRateLimiter::new(ScanThrottle {
+            max_blocks_per_second: None,
+            max_concurrent_requests: Some(2),
+        });
+        let first = rate_limiter.acquire_request().await.unwrap();
+        let second = rate_limiter.acquire_request().await.unwrap();
+        assert_eq!(rate_limiter.requests.available_permits(), 0);
+
+        rate_limiter.set_throttle(ScanThrottle {
+            max_blocks_per_second: None,
+            max_concurrent_requests: Some(1),
+        });
+        drop(first);
+        drop(second);
+        let _third = rate_limiter.acquire_request().await.unwrap();
+        assert_eq!(rate_limiter.lock_state().num_permits, 1);
+        assert_eq!(rate_limiter.requests.available_permits(), 0);
+
+        rate_limiter.set_throttle(ScanThrottle::default());
+        assert!(rate_limiter.acquire_request().await.is_none());
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// How quickly a scan is progressing, measured over the last few seconds of scanning
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanThroughput {
+    pub blocks_per_second: f64,
+    pub outputs_per_second: f64,
+    /// The estimated time until the scan reaches the chain tip, `None` until there is a rate to go by
+    pub eta: Option<Duration>,
+}
+
+/// Tracks the running totals of a scan over a rolling window, so that the reported rate follows changes in the base
+/// node's speed or the scan throttle rather than averaging over the whole scan
+pub(crate) struct ThroughputTracker {
+    window: Duration,
+    // The time of each sample with the total number of blocks and outputs scanned by then
+    samples: VecDeque<(Instant, u64, u64)>,
+}
+
+impl ThroughputTracker {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    pub fn record(&mut self, num_blocks: u64, num_outputs: u64) {
+        self.record_at(Instant::now(), num_blocks, num_outputs);
+    }
+
+    fn record_at(&mut self, now: Instant, num_blocks: u64, num_outputs: u64) {
+        self.samples.push_back((now, num_blocks, num_outputs));
+        // Keep the newest sample from before the window, so that the rate covers the whole window
+        while self.samples.len() > 2 && now.saturating_duration_since(self.samples[1].0) >= self.window {
+            self.samples.pop_front();
+        }
+    }
+
+    pub fn throughput(&self, current_height: u64, tip_height: u64) -> ScanThroughput {
+        let (Some(first), Some(last)) = (self.samples.front(), self.samples.back()) else {
+            return ScanThroughput::default();
+        };
+        let elapsed = last.0.saturating_duration_since(first.0).as_secs_f64();
+        if elapsed == 0.0 {
+            return ScanThroughput::default();
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let blocks_per_second = last.1.saturating_sub(first.1) as f64 / elapsed;
+        #[allow(clippy::cast_precision_loss)]
+        let outputs_per_second = last.2.saturating_sub(first.2) as f64 / elapsed;
+        let remaining = tip_height.saturating_sub(current_height);
+        #[allow(clippy::cast_precision_loss)]
+        let eta = (blocks_per_second > 0.0).then(|| Duration::from_secs_f64(remaining as f64 / blocks_per_second));
+        ScanThroughput {
+            blocks_per_second,
+            outputs_per_second,
+            eta,
+        }
+    }
+}
+
+impl Default for ThroughputTracker {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_measures_throughput_over_the_window() {
+        let mut tracker = ThroughputTracker::new(Duration::from_secs(10));
+        assert_eq!(tracker.throughput(0, 100), ScanThroughput::default());
+
+        let start = Instant::now();
+        // A slow start that falls out of the window
+        tracker.record_at(start, 0, 0);
+        tracker.record_at(start + Duration::from_secs(10), 10, 100);
+        tracker.record_at(start + Duration::from_secs(15), 60, 600);
+        tracker.record_at(start + Duration::from_secs(20), 110, 1100);
+
+        let throughput = tracker.throughput(110, 210);
+        assert_eq!(throughput.blocks_per_second, 10.0);
+        assert_eq!(throughput.outputs_per_second, 100.0);
+        assert_eq!(throughput.eta, Some(Duration::from_secs(10)));
+    }
+}
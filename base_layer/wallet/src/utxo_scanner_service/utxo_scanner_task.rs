@@ -34,6 +34,7 @@ use tari_common_types::{
     types::HashOutput,
 };
 use tari_comms::{
+    compression::Compression,
     peer_manager::NodeId,
     protocol::rpc::RpcClientLease,
     traits::OrOptional,
@@ -61,10 +62,12 @@ use crate::{
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
     utxo_scanner_service::{
         error::UtxoScannerError,
-        handle::UtxoScannerEvent,
-        service::{ScannedBlock, UtxoScannerResources, SCANNED_BLOCK_CACHE_SIZE},
+        handle::{RecoveryState, RecoveryStatus, ScanBatchStats, ScanStats, UtxoScannerEvent},
+        service::{ScanCheckpoint, ScannedBlock, UtxoScannerResources, SCANNED_BLOCK_CACHE_SIZE},
+        throughput::ThroughputTracker,
         uxto_scanner_service_builder::UtxoScannerMode,
         RECOVERY_KEY,
+        SCAN_CHECKPOINT_KEY,
     },
 };
 
@@ -78,7 +81,12 @@ pub struct UtxoScannerTask<TBackend, TWalletConnectivity> {
     pub(crate) peer_seeds: Vec<CommsPublicKey>,
     pub(crate) peer_index: usize,
     pub(crate) mode: UtxoScannerMode,
+    pub(crate) max_blocks: Option<u64>,
+    pub(crate) full_scan: bool,
     pub(crate) shutdown_signal: ShutdownSignal,
+    pub(crate) stats: ScanStats,
+    pub(crate) throughput: ThroughputTracker,
+    pub(crate) started_at: Instant,
 }
 impl<TBackend, TWalletConnectivity> UtxoScannerTask<TBackend, TWalletConnectivity>
 where
@@ -87,6 +95,10 @@ where
 {
     pub async fn run(mut self) -> Result<(), UtxoScannerError> {
         if self.mode == UtxoScannerMode::Recovery {
+            self.resources.recovery_status.send(RecoveryStatus {
+                state: RecoveryState::Connecting,
+                ..Default::default()
+            });
             self.set_recovery_mode()?;
         } else {
             let in_progress = self.check_recovery_mode()?;
@@ -156,7 +168,25 @@ where
         self.publish_event(UtxoScannerEvent::Progress {
             current_height: final_height,
             tip_height: final_height,
+            value_recovered: total_value,
+            throughput: self.throughput.throughput(final_height, final_height),
         });
+        let mut stats = self.stats.clone();
+        stats.time_taken = self.started_at.elapsed();
+        info!(
+            target: LOG_TARGET,
+            "Scanned {} blocks ({} outputs) from height {} to {} in {:.2?}, found {} outputs worth {} (average RPC \
+             latency {:.2?})",
+            stats.num_blocks,
+            stats.num_outputs,
+            stats.start_height,
+            stats.current_height,
+            stats.time_taken,
+            stats.num_found,
+            stats.value_recovered,
+            stats.average_rpc_latency(),
+        );
+        self.publish_event(UtxoScannerEvent::Summary(stats));
         self.publish_event(UtxoScannerEvent::Completed {
             final_height,
             num_recovered: num_outputs_recovered,
@@ -242,6 +272,7 @@ where
                     ));
                 }
 
+                let _permit = self.resources.rate_limiter.acquire_request().await;
                 let next_header =
                     BlockHeader::try_from(client.get_header_by_height(last_scanned_block.height + 1).await?)
                         .map_err(UtxoScannerError::ConversionError)?;
@@ -269,6 +300,8 @@ where
                 }
             };
 
+            let checkpoint = self.get_scan_checkpoint(&next_block_to_scan)?;
+
             if self.shutdown_signal.is_triggered() {
                 return Ok((
                     next_block_to_scan.num_outputs.unwrap_or(0),
@@ -278,10 +311,27 @@ where
                 ));
             }
 
+            // A bounded round stops short of the tip, the next round carries on from the last scanned block
+            let end_height = self
+                .max_blocks
+                .map(|max_blocks| next_block_to_scan.height.saturating_add(max_blocks.saturating_sub(1)))
+                .filter(|end_height| *end_height < tip_header.height);
+            let end_header_hash = match end_height {
+                Some(end_height) => {
+                    let _permit = self.resources.rate_limiter.acquire_request().await;
+                    BlockHeader::try_from(client.get_header_by_height(end_height).await?)
+                        .map_err(UtxoScannerError::ConversionError)?
+                        .hash()
+                },
+                None => tip_header_hash,
+            };
+
             debug!(
                 target: LOG_TARGET,
-                "Scanning UTXO's from height = {} to current tip_height = {} (starting header_hash: {})",
+                "Scanning UTXO's from height = {} to height = {} with current tip_height = {} (starting header_hash: \
+                 {})",
                 next_block_to_scan.height,
+                end_height.unwrap_or(tip_header.height),
                 tip_header.height,
                 next_block_to_scan.header_hash.to_hex(),
             );
@@ -290,8 +340,9 @@ where
                 .scan_utxos(
                     &mut client,
                     next_block_to_scan.header_hash,
-                    tip_header_hash,
+                    end_header_hash,
                     tip_header.height,
+                    checkpoint,
                 )
                 .await?;
             if num_scanned == 0 {
@@ -299,6 +350,19 @@ where
                     "Peer returned 0 UTXOs to scan".to_string(),
                 ));
             }
+            if let Some(end_height) = end_height {
+                debug!(
+                    target: LOG_TARGET,
+                    "Bounded scanning round completed up to height {} in {:.2?} ({} outputs scanned, {} recovered \
+                     with value {})",
+                    end_height,
+                    timer.elapsed(),
+                    num_scanned,
+                    num_recovered,
+                    amount
+                );
+                return Ok((num_recovered, end_height, amount, timer.elapsed()));
+            }
             debug!(
                 target: LOG_TARGET,
                 "Scanning round completed up to height {} in {:.2?} ({} outputs scanned, {} recovered with value {})",
@@ -317,7 +381,11 @@ where
     ) -> Result<RpcClientLease<BaseNodeWalletRpcClient>, UtxoScannerError> {
         let mut connection = self.connect_to_peer(peer.clone()).await?;
         let client = connection
-            .connect_rpc_using_builder(BaseNodeWalletRpcClient::builder().with_deadline(Duration::from_secs(60)))
+            .connect_rpc_using_builder(
+                BaseNodeWalletRpcClient::builder()
+                    .with_deadline(Duration::from_secs(60))
+                    .with_compression(Compression::Zstd),
+            )
             .await?;
         Ok(RpcClientLease::new(client))
     }
@@ -326,6 +394,7 @@ where
         &self,
         client: &mut BaseNodeWalletRpcClient,
     ) -> Result<BlockHeader, UtxoScannerError> {
+        let _permit = self.resources.rate_limiter.acquire_request().await;
         let tip_info = client.get_tip_info().await?;
         let chain_height = tip_info.metadata.map(|m| m.height_of_longest_chain()).unwrap_or(0);
         let end_header = client.get_header_by_height(chain_height).await?;
@@ -367,6 +436,7 @@ where
             }
 
             if found_scanned_block.is_none() {
+                let _permit = self.resources.rate_limiter.acquire_request().await;
                 let header = client.get_header_by_height(sb.height).await.or_optional()?;
                 let header = header
                     .map(BlockHeader::try_from)
@@ -437,6 +507,7 @@ where
         start_header_hash: HashOutput,
         end_header_hash: HashOutput,
         tip_height: u64,
+        checkpoint: Option<ScanCheckpoint>,
     ) -> Result<(u64, u64, MicroMinotari), UtxoScannerError> {
         // Setting how often the progress event and log should occur during scanning. Defined in blocks
         const PROGRESS_REPORT_INTERVAL: u64 = 100;
@@ -450,6 +521,8 @@ where
             end_header_hash: end_header_hash.to_vec(),
         };
 
+        // The request lasts for as long as the outputs are streamed
+        let _permit = self.resources.rate_limiter.acquire_request().await;
         let start = Instant::now();
         let mut utxo_stream = client.sync_utxos_by_block(request).await?;
         trace!(
@@ -461,6 +534,23 @@ where
         let mut utxo_next_await_profiling = Vec::new();
         let mut scan_for_outputs_profiling = Vec::new();
         let mut prev_scanned_block: Option<ScannedBlock> = None;
+        // The number of outputs of the first block that were scanned before an earlier scan was interrupted
+        let mut outputs_to_skip = 0u64;
+        if let Some(checkpoint) = checkpoint {
+            info!(
+                target: LOG_TARGET,
+                "Resuming the scan of block {} after {} outputs", checkpoint.height, checkpoint.output_index
+            );
+            outputs_to_skip = checkpoint.output_index;
+            prev_scanned_block = Some(ScannedBlock {
+                header_hash: checkpoint.header_hash,
+                height: checkpoint.height,
+                num_outputs: Some(checkpoint.num_outputs),
+                amount: Some(checkpoint.amount),
+                timestamp: Utc::now().naive_utc(),
+            });
+        }
+        let mut block_output_index = 0u64;
         while let Some(response) = {
             let start = Instant::now();
             let utxo_stream_next = utxo_stream.next().await;
@@ -474,15 +564,34 @@ where
 
             let response = response.map_err(|e| UtxoScannerError::RpcStatus(e.to_string()))?;
             let current_height = response.height;
-            let current_header_hash = response.header_hash;
+            let block_hash: HashOutput = response.header_hash.try_into()?;
             let mined_timestamp =
                 NaiveDateTime::from_timestamp_opt(response.mined_timestamp as i64, 0).unwrap_or(NaiveDateTime::MIN);
-            let outputs = response
+            let mut outputs = response
                 .outputs
                 .into_iter()
                 .map(|utxo| TransactionOutput::try_from(utxo).map_err(UtxoScannerError::ConversionError))
                 .collect::<Result<Vec<_>, _>>()?;
-            total_scanned += outputs.len();
+            if prev_scanned_block.as_ref().map(|b| b.header_hash) != Some(block_hash) {
+                block_output_index = 0;
+                if self.stats.num_blocks == 0 {
+                    self.stats.start_height = current_height;
+                }
+                self.stats.num_blocks += 1;
+                // Hold off on the next block if the scan is throttled, which in turn slows down the stream
+                tokio::select! {
+                    _ = self.resources.rate_limiter.pace_block() => {},
+                    _ = self.shutdown_signal.wait() => return Ok((num_recovered, total_scanned as u64, total_amount)),
+                }
+            }
+            block_output_index += outputs.len() as u64;
+            if outputs_to_skip > 0 {
+                let num_skipped = outputs_to_skip.min(outputs.len() as u64);
+                outputs.drain(..num_skipped as usize);
+                outputs_to_skip -= num_skipped;
+            }
+            let num_outputs = outputs.len();
+            total_scanned += num_outputs;
 
             let start = Instant::now();
             let found_outputs = self.scan_for_outputs(outputs).await?;
@@ -491,7 +600,19 @@ where
             let (mut count, mut amount) = self
                 .import_utxos_to_transaction_service(found_outputs, current_height, mined_timestamp)
                 .await?;
-            let block_hash = current_header_hash.try_into()?;
+            let batch = ScanBatchStats {
+                current_height,
+                tip_height,
+                num_outputs: num_outputs as u64,
+                num_found: count,
+                value_found: amount,
+                rpc_latency: utxo_next_await_profiling.last().copied().unwrap_or_default(),
+            };
+            self.record_batch(&batch);
+            self.publish_event(UtxoScannerEvent::BatchScanned {
+                batch,
+                totals: self.stats.clone(),
+            });
             if let Some(scanned_block) = prev_scanned_block {
                 if block_hash == scanned_block.header_hash {
                     count += scanned_block.num_outputs.unwrap_or(0);
@@ -511,6 +632,8 @@ where
                         self.publish_event(UtxoScannerEvent::Progress {
                             current_height,
                             tip_height,
+                            value_recovered: total_amount,
+                            throughput: self.throughput.throughput(current_height, tip_height),
                         });
                     }
 
@@ -525,6 +648,17 @@ where
                 amount: Some(amount),
                 timestamp: Utc::now().naive_utc(),
             });
+            self.resources.db.set_client_key_value(
+                SCAN_CHECKPOINT_KEY.to_owned(),
+                ScanCheckpoint {
+                    header_hash: block_hash,
+                    height: current_height,
+                    output_index: block_output_index,
+                    num_outputs: count,
+                    amount,
+                }
+                .to_string(),
+            )?;
         }
         // We need to update the last one
         if let Some(scanned_block) = prev_scanned_block {
@@ -533,6 +667,7 @@ where
                 true,
             )?;
             self.resources.db.save_scanned_block(scanned_block)?;
+            self.resources.db.clear_client_value(SCAN_CHECKPOINT_KEY.to_owned())?;
         }
         trace!(
             target: LOG_TARGET,
@@ -553,25 +688,34 @@ where
     async fn scan_for_outputs(
         &mut self,
         outputs: Vec<TransactionOutput>,
-    ) -> Result<Vec<(WalletOutput, String, ImportStatus, TxId)>, UtxoScannerError> {
-        let mut found_outputs: Vec<(WalletOutput, String, ImportStatus, TxId)> = Vec::new();
-        found_outputs.append(
-            &mut self
-                .resources
-                .output_manager_service
-                .scan_for_recoverable_outputs(outputs.clone())
-                .await?
-                .into_iter()
-                .map(|ro| {
-                    let status = if ro.output.features.is_coinbase() {
-                        ImportStatus::Coinbase
-                    } else {
-                        ImportStatus::Imported
-                    };
-                    (ro.output, self.resources.recovery_message.clone(), status, ro.tx_id)
-                })
-                .collect(),
-        );
+    ) -> Result<Vec<(WalletOutput, String, ImportStatus, TxId, Option<TariAddress>)>, UtxoScannerError> {
+        let mut found_outputs: Vec<(WalletOutput, String, ImportStatus, TxId, Option<TariAddress>)> = Vec::new();
+        // A watch-only wallet's seed does not own any outputs, so only one-sided payments to the watched key are found
+        if !self.resources.wallet_identity.is_watch_only() && self.mode != UtxoScannerMode::OneSidedOnly {
+            found_outputs.append(
+                &mut self
+                    .resources
+                    .output_manager_service
+                    .scan_for_recoverable_outputs(outputs.clone())
+                    .await?
+                    .into_iter()
+                    .map(|ro| {
+                        let status = if ro.output.features.is_coinbase() {
+                            ImportStatus::Coinbase
+                        } else {
+                            ImportStatus::Imported
+                        };
+                        (
+                            ro.output,
+                            self.resources.recovery_message.clone(),
+                            status,
+                            ro.tx_id,
+                            None,
+                        )
+                    })
+                    .collect(),
+            );
+        }
 
         found_outputs.append(
             &mut self
@@ -581,11 +725,16 @@ where
                 .await?
                 .into_iter()
                 .map(|ro| {
+                    // Payments to a subaddress are recorded with the subaddress as their destination
+                    let destination_address = ro
+                        .subaddress
+                        .map(|s| TariAddress::new(s.public_key, self.resources.wallet_identity.network));
                     (
                         ro.output,
                         self.resources.one_sided_payment_message.clone(),
                         ImportStatus::FauxUnconfirmed,
                         ro.tx_id,
+                        destination_address,
                     )
                 })
                 .collect(),
@@ -595,13 +744,13 @@ where
 
     async fn import_utxos_to_transaction_service(
         &mut self,
-        utxos: Vec<(WalletOutput, String, ImportStatus, TxId)>,
+        utxos: Vec<(WalletOutput, String, ImportStatus, TxId, Option<TariAddress>)>,
         current_height: u64,
         mined_timestamp: NaiveDateTime,
     ) -> Result<(u64, MicroMinotari), UtxoScannerError> {
         let mut num_recovered = 0u64;
         let mut total_amount = MicroMinotari::from(0);
-        for (uo, message, import_status, tx_id) in utxos {
+        for (uo, message, import_status, tx_id, destination_address) in utxos {
             let source_address = if uo.features.is_coinbase() {
                 // its a coinbase, so we know we mined it and it comes from us.
                 self.resources.wallet_identity.address.clone()
@@ -614,6 +763,7 @@ where
                 .import_key_manager_utxo_to_transaction_service(
                     uo.clone(),
                     source_address,
+                    destination_address,
                     message,
                     import_status,
                     tx_id,
@@ -642,6 +792,38 @@ where
         Ok((num_recovered, total_amount))
     }
 
+    /// The checkpoint of an interrupted scan, if it is within the block the scan is about to start from. Any other
+    /// checkpoint is stale and removed.
+    fn get_scan_checkpoint(
+        &self,
+        next_block_to_scan: &ScannedBlock,
+    ) -> Result<Option<ScanCheckpoint>, UtxoScannerError> {
+        let checkpoint = match self
+            .resources
+            .db
+            .get_client_key_from_str::<ScanCheckpoint>(SCAN_CHECKPOINT_KEY.to_owned())
+        {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                warn!(target: LOG_TARGET, "Ignoring unreadable scan checkpoint: {}", e);
+                None
+            },
+        };
+        match checkpoint {
+            Some(checkpoint)
+                if checkpoint.height == next_block_to_scan.height &&
+                    checkpoint.header_hash == next_block_to_scan.header_hash =>
+            {
+                Ok(Some(checkpoint))
+            },
+            Some(_) => {
+                self.resources.db.clear_client_value(SCAN_CHECKPOINT_KEY.to_owned())?;
+                Ok(None)
+            },
+            None => Ok(None),
+        }
+    }
+
     fn set_recovery_mode(&self) -> Result<(), UtxoScannerError> {
         self.resources
             .db
@@ -663,6 +845,11 @@ where
     }
 
     fn publish_event(&self, event: UtxoScannerEvent) {
+        if self.mode == UtxoScannerMode::Recovery {
+            let mut status = self.resources.recovery_status.borrow().clone();
+            status.apply(&event);
+            self.resources.recovery_status.send(status);
+        }
         let _size = self.event_sender.send(event);
     }
 
@@ -672,6 +859,7 @@ where
         &mut self,
         wallet_output: WalletOutput,
         source_address: TariAddress,
+        destination_address: Option<TariAddress>,
         message: String,
         import_status: ImportStatus,
         tx_id: TxId,
@@ -684,6 +872,7 @@ where
             .import_utxo_with_status(
                 wallet_output.value,
                 source_address,
+                destination_address,
                 message,
                 Some(wallet_output.features.maturity),
                 import_status.clone(),
@@ -701,6 +890,17 @@ where
         Ok(tx_id)
     }
 
+    fn record_batch(&mut self, batch: &ScanBatchStats) {
+        self.stats.current_height = batch.current_height;
+        self.stats.num_outputs += batch.num_outputs;
+        self.stats.num_found += batch.num_found;
+        self.stats.value_recovered += batch.value_found;
+        self.stats.num_batches += 1;
+        self.stats.total_rpc_latency += batch.rpc_latency;
+        self.stats.time_taken = self.started_at.elapsed();
+        self.throughput.record(self.stats.num_blocks, self.stats.num_outputs);
+    }
+
     fn get_next_peer(&mut self) -> Option<NodeId> {
         let peer = self.peer_seeds.get(self.peer_index).map(NodeId::from_public_key);
         self.peer_index += 1;
@@ -711,20 +911,29 @@ where
         &self,
         client: &mut BaseNodeWalletRpcClient,
     ) -> Result<HeightHash, UtxoScannerError> {
-        let birthday = self.resources.db.get_wallet_birthday()?;
-        // Calculate the unix epoch time of two weeks (14 days), in seconds, before the
-        // wallet birthday. The latter avoids any possible issues with reorgs.
-        let epoch_time = get_birthday_from_unix_epoch_in_seconds(birthday, 14u16);
-
-        let block_height = match client.get_height_at_time(epoch_time).await {
-            Ok(b) => b,
-            Err(e) => {
-                warn!(
-                    target: LOG_TARGET,
-                    "Problem requesting `height_at_time` from Base Node: {}", e
-                );
-                0
-            },
+        let _permit = self.resources.rate_limiter.acquire_request().await;
+        let block_height = if self.full_scan {
+            info!(
+                target: LOG_TARGET,
+                "Full scan requested, ignoring the wallet birthday and starting from the genesis block"
+            );
+            0
+        } else {
+            let birthday = self.resources.db.get_wallet_birthday()?;
+            // Calculate the unix epoch time of two weeks (14 days), in seconds, before the
+            // wallet birthday. The latter avoids any possible issues with reorgs.
+            let epoch_time = get_birthday_from_unix_epoch_in_seconds(birthday, 14u16);
+
+            match client.get_height_at_time(epoch_time).await {
+                Ok(b) => b,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Problem requesting `height_at_time` from Base Node: {}", e
+                    );
+                    0
+                },
+            }
         };
         let header = client.get_header_by_height(block_height).await?;
         let header = BlockHeader::try_from(header).map_err(UtxoScannerError::ConversionError)?;
@@ -34,10 +34,11 @@ use crate::{
         sqlite_db::wallet::WalletSqliteDatabase,
     },
     transaction_service::handle::TransactionServiceHandle,
-    util::wallet_identity::WalletIdentity,
+    util::{wallet_identity::WalletIdentity, watch::Watch},
     utxo_scanner_service::{
-        handle::UtxoScannerEvent,
+        handle::{RecoveryStatus, UtxoScannerEvent},
         service::{UtxoScannerResources, UtxoScannerService},
+        throttle::ScanRateLimiter,
     },
     WalletSqlite,
 };
@@ -47,6 +48,10 @@ pub enum UtxoScannerMode {
     #[default]
     Recovery,
     Scanning,
+    /// Scan like `Scanning`, but only look for one-sided payments to the wallet's addresses and skip the recovery of
+    /// outputs derived from the seed. Blocks scanned in this mode are recorded as scanned, so it suits wallets that
+    /// only receive one-sided payments, e.g. integrations watching for payments to a known address.
+    OneSidedOnly,
 }
 
 #[derive(Debug, Clone)]
@@ -54,6 +59,11 @@ pub struct UtxoScannerServiceBuilder {
     retry_limit: usize,
     peers: Vec<CommsPublicKey>,
     mode: Option<UtxoScannerMode>,
+    max_blocks: Option<u64>,
+    full_scan: bool,
+    max_blocks_per_second: Option<u32>,
+    max_concurrent_requests: Option<usize>,
+    rate_limiter: Option<ScanRateLimiter>,
     one_sided_message: String,
     recovery_message: String,
 }
@@ -64,6 +74,11 @@ impl Default for UtxoScannerServiceBuilder {
             retry_limit: 0,
             peers: vec![],
             mode: None,
+            max_blocks: None,
+            full_scan: false,
+            max_blocks_per_second: None,
+            max_concurrent_requests: None,
+            rate_limiter: None,
             one_sided_message: "Detected one-sided payment on blockchain".to_string(),
             recovery_message: "Output found on blockchain during Wallet Recovery".to_string(),
         }
@@ -88,6 +103,40 @@ impl UtxoScannerServiceBuilder {
         self
     }
 
+    /// Limit each scanning round to at most this many blocks. A round that stops short of the chain tip completes, and
+    /// the next round carries on from the last scanned block.
+    pub fn with_max_blocks(&mut self, max_blocks: u64) -> &mut Self {
+        self.max_blocks = Some(max_blocks);
+        self
+    }
+
+    /// Ignore the wallet birthday and scan from the genesis block. By default a fresh scan starts a little before the
+    /// birthday recorded in the cipher seed, since no outputs can belong to the wallet before it was created.
+    pub fn with_full_scan(&mut self, full_scan: bool) -> &mut Self {
+        self.full_scan = full_scan;
+        self
+    }
+
+    /// Scan at most this many blocks per second, so that a scan on a metered connection does not saturate it. The
+    /// limit can be changed while scanning through the scanner handle.
+    pub fn with_max_blocks_per_second(&mut self, max_blocks_per_second: u32) -> &mut Self {
+        self.max_blocks_per_second = Some(max_blocks_per_second);
+        self
+    }
+
+    /// Have at most this many RPC requests to the base node in flight at the same time. The limit can be changed while
+    /// scanning through the scanner handle.
+    pub fn with_max_concurrent_requests(&mut self, max_concurrent_requests: usize) -> &mut Self {
+        self.max_concurrent_requests = Some(max_concurrent_requests);
+        self
+    }
+
+    /// Share the given rate limiter, and its limits, with the scanner instead of giving it its own
+    pub(crate) fn with_rate_limiter(&mut self, rate_limiter: ScanRateLimiter) -> &mut Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
     pub fn with_one_sided_message(&mut self, message: String) -> &mut Self {
         self.one_sided_message = message;
         self
@@ -115,6 +164,9 @@ impl UtxoScannerServiceBuilder {
             factories: wallet.factories.clone(),
             recovery_message: self.recovery_message.clone(),
             one_sided_payment_message: self.one_sided_message.clone(),
+            // Scanners of the same wallet share the limits on its connection
+            rate_limiter: self.configure_rate_limiter(wallet.utxo_scanner_service.get_rate_limiter()),
+            recovery_status: wallet.utxo_scanner_service.get_recovery_status_watch(),
         };
 
         let (event_sender, _) = broadcast::channel(200);
@@ -123,6 +175,8 @@ impl UtxoScannerServiceBuilder {
             self.peers.drain(..).collect(),
             self.retry_limit,
             self.mode.clone().unwrap_or_default(),
+            self.max_blocks,
+            self.full_scan,
             resources,
             shutdown_signal,
             event_sender,
@@ -158,12 +212,16 @@ impl UtxoScannerServiceBuilder {
             factories,
             recovery_message: self.recovery_message.clone(),
             one_sided_payment_message: self.one_sided_message.clone(),
+            rate_limiter: self.configure_rate_limiter(ScanRateLimiter::default()),
+            recovery_status: Watch::new(RecoveryStatus::default()),
         };
 
         UtxoScannerService::new(
             self.peers.drain(..).collect(),
             self.retry_limit,
             self.mode.clone().unwrap_or_default(),
+            self.max_blocks,
+            self.full_scan,
             resources,
             shutdown_signal,
             event_sender,
@@ -172,4 +230,17 @@ impl UtxoScannerServiceBuilder {
             recovery_message_watch,
         )
     }
+
+    fn configure_rate_limiter(&mut self, default: ScanRateLimiter) -> ScanRateLimiter {
+        let rate_limiter = self.rate_limiter.take().unwrap_or(default);
+        let mut throttle = rate_limiter.throttle();
+        if let Some(max_blocks_per_second) = self.max_blocks_per_second {
+            throttle.max_blocks_per_second = Some(max_blocks_per_second);
+        }
+        if let Some(max_concurrent_requests) = self.max_concurrent_requests {
+            throttle.max_concurrent_requests = Some(max_concurrent_requests);
+        }
+        rate_limiter.set_throttle(throttle);
+        rate_limiter
+    }
 }
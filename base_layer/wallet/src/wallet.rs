@@ -20,9 +20,10 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{cmp, marker::PhantomData, sync::Arc};
+use std::{cmp, marker::PhantomData, path::Path, sync::Arc, time::Duration};
 
 use blake2::Blake2b;
+use chrono::{NaiveDateTime, Utc};
 use digest::consts::U32;
 use log::*;
 use rand::rngs::OsRng;
@@ -45,6 +46,7 @@ use tari_comms_dht::{store_forward::StoreAndForwardRequester, Dht};
 use tari_contacts::contacts_service::{
     handle::ContactsServiceHandle,
     storage::database::ContactsBackend,
+    types::{LivenessPrivacyMode, MessageDeliveryStrategy},
     ContactsServiceInitializer,
 };
 use tari_core::{
@@ -61,7 +63,7 @@ use tari_crypto::{hash_domain, signatures::SchnorrSignatureError};
 use tari_key_manager::{
     cipher_seed::CipherSeed,
     key_manager::KeyManager,
-    key_manager_service::{storage::database::KeyManagerBackend, KeyDigest},
+    key_manager_service::{storage::database::KeyManagerBackend, KeyDigest, KeyManagerInterface},
     mnemonic::{Mnemonic, MnemonicLanguage},
     SeedWords,
 };
@@ -70,37 +72,48 @@ use tari_p2p::{
     comms_connector::pubsub_connector,
     initialization,
     initialization::P2pInitializer,
-    services::liveness::{config::LivenessConfig, LivenessInitializer},
+    services::liveness::{config::LivenessConfig, LivenessHandle, LivenessInitializer},
     PeerSeedsConfig,
 };
 use tari_script::{one_sided_payment_script, ExecutionStack, TariScript};
 use tari_service_framework::StackBuilder;
 use tari_shutdown::ShutdownSignal;
-use tari_utilities::{hex::Hex, ByteArray};
+use tari_utilities::{hex::Hex, ByteArray, SafePassword};
 
 use crate::{
+    backup_service::BackupServiceInitializer,
     base_node_service::{handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
     config::{WalletConfig, KEY_MANAGER_COMMS_SECRET_KEY_BRANCH_KEY},
     connectivity_service::{WalletConnectivityHandle, WalletConnectivityInitializer, WalletConnectivityInterface},
     consts,
+    derivation_export::DerivationExport,
     error::{WalletError, WalletStorageError},
     output_manager_service::{
         error::OutputManagerError,
         handle::OutputManagerHandle,
+        service::DustSweep,
         storage::{
             database::{OutputManagerBackend, OutputManagerDatabase},
             models::KnownOneSidedPaymentScript,
         },
         OutputManagerServiceInitializer,
     },
-    storage::database::{WalletBackend, WalletDatabase},
+    spent_output_proof::{SpentOutputProof, SpentOutputRecord, SpentOutputStatement},
+    storage::{
+        database::{WalletBackend, WalletDatabase},
+        integrity::StorageIntegrityReport,
+    },
     transaction_service::{
         handle::TransactionServiceHandle,
         storage::database::TransactionBackend,
         TransactionServiceInitializer,
     },
-    util::wallet_identity::WalletIdentity,
+    util::{
+        reauthentication::{ReauthenticationGuard, ReauthenticationPolicy, SensitiveOperation},
+        wallet_identity::{WalletIdentity, WatchOnlyKeys},
+    },
     utxo_scanner_service::{handle::UtxoScannerHandle, initializer::UtxoScannerServiceInitializer, RECOVERY_KEY},
+    webhook_service::WebhookServiceInitializer,
 };
 
 const LOG_TARGET: &str = "wallet";
@@ -127,12 +140,16 @@ pub struct Wallet<T, U, V, W, TKeyManagerInterface> {
     pub transaction_service: TransactionServiceHandle,
     pub wallet_connectivity: WalletConnectivityHandle,
     pub contacts_service: ContactsServiceHandle,
+    pub liveness_service: LivenessHandle,
     pub base_node_service: BaseNodeServiceHandle,
     pub utxo_scanner_service: UtxoScannerHandle,
     pub updater_service: Option<SoftwareUpdaterHandle>,
+    pub reauthentication: ReauthenticationGuard,
     pub db: WalletDatabase<T>,
     pub output_db: OutputManagerDatabase<V>,
     pub factories: CryptoFactories,
+    /// The result of the storage integrity check run on startup, if it was enabled and completed
+    pub storage_integrity_report: Option<StorageIntegrityReport>,
     _u: PhantomData<U>,
     _v: PhantomData<V>,
     _w: PhantomData<W>,
@@ -168,6 +185,8 @@ where
         let peer_message_subscription_factory = Arc::new(subscription_factory);
 
         debug!(target: LOG_TARGET, "Wallet Initializing");
+        check_onion_only(&config, &auto_update)?;
+        wallet_database.ensure_network(&config.network.to_string())?;
         info!(
             target: LOG_TARGET,
             "Transaction sending mechanism is {}", config.transaction_service_config.transaction_routing_mechanism
@@ -180,7 +199,37 @@ where
             config.transaction_service_config,
             config.buffer_size,
         );
-        let wallet_identity = WalletIdentity::new(node_identity.clone(), config.network);
+        let storage_integrity_report = if config.storage_integrity_check {
+            match wallet_database.check_storage_integrity(config.storage_integrity_repair) {
+                Ok(report) if report.is_clean() => {
+                    debug!(target: LOG_TARGET, "Wallet storage integrity check passed");
+                    Some(report)
+                },
+                Ok(report) => {
+                    warn!(target: LOG_TARGET, "Wallet storage integrity check found issues: {}", report);
+                    Some(report)
+                },
+                Err(e) => {
+                    warn!(target: LOG_TARGET, "Wallet storage integrity check could not be completed: {}", e);
+                    None
+                },
+            }
+        } else {
+            None
+        };
+
+        let watch_only_keys = wallet_database.get_watch_only_keys()?;
+        let mut wallet_identity = WalletIdentity::new(node_identity.clone(), config.network);
+        if let Some(keys) = &watch_only_keys {
+            wallet_identity = wallet_identity.with_watch_only_keys(keys);
+            info!(
+                target: LOG_TARGET,
+                "Starting as a watch-only wallet for address {}",
+                wallet_identity.address.to_hex()
+            );
+        }
+        // The tip height is not known yet, so the genesis future time limit is used as the clock skew tolerance
+        let max_clock_skew = Duration::from_secs(consensus_manager.consensus_constants(0).future_time_limit());
         let stack = StackBuilder::new(shutdown_signal)
             .add_initializer(P2pInitializer::new(
                 config.p2p.clone(),
@@ -215,16 +264,26 @@ where
                     auto_ping_interval: Some(config.contacts_auto_ping_interval),
                     num_peers_per_round: 0,       // No random peers
                     max_allowed_ping_failures: 0, // Peer with failed ping-pong will never be removed
+                    max_clock_skew: Some(max_clock_skew),
                     ..Default::default()
                 },
                 peer_message_subscription_factory.clone(),
             ))
-            .add_initializer(ContactsServiceInitializer::new(
-                contacts_backend,
-                peer_message_subscription_factory,
-                config.contacts_auto_ping_interval,
-                config.contacts_online_ping_window,
-            ))
+            .add_initializer(
+                ContactsServiceInitializer::new(
+                    contacts_backend,
+                    peer_message_subscription_factory,
+                    config.contacts_auto_ping_interval,
+                    config.contacts_online_ping_window,
+                )
+                .with_delivery_strategy(MessageDeliveryStrategy::from_direct_send_timeout(
+                    config.contacts_direct_send_timeout,
+                ))
+                .with_liveness_privacy(LivenessPrivacyMode::from_settings(
+                    config.contacts_hide_online_status,
+                    config.contacts_allow_outbound_pings,
+                )),
+            )
             .add_initializer(BaseNodeServiceInitializer::new(
                 config.base_node_service_config.clone(),
                 wallet_database.clone(),
@@ -250,6 +309,21 @@ where
             stack
         };
 
+        let stack = if config.webhooks.is_enabled() {
+            stack.add_initializer(WebhookServiceInitializer::new(config.webhooks.clone()))
+        } else {
+            stack
+        };
+
+        let stack = if config.backups.is_enabled() {
+            stack.add_initializer(BackupServiceInitializer::new(
+                config.backups.clone(),
+                wallet_database.clone(),
+            ))
+        } else {
+            stack
+        };
+
         let mut handles = stack.build().await?;
 
         let comms = handles
@@ -257,10 +331,24 @@ where
             .expect("P2pInitializer was not added to the stack");
         let comms = initialization::spawn_comms_using_transport(comms, config.p2p.transport).await?;
 
-        let mut output_manager_handle = handles.expect_handle::<OutputManagerHandle>();
         let key_manager_handle = handles.expect_handle::<TKeyManagerInterface>();
-        let transaction_service_handle = handles.expect_handle::<TransactionServiceHandle>();
+        let reauthentication = ReauthenticationGuard::new(
+            ReauthenticationPolicy {
+                send_threshold: config.reauthentication_send_threshold.map(MicroMinotari::from),
+                send_window: config.reauthentication_send_window,
+                seed_export: config.reauthentication_for_seed_export,
+                backup_creation: config.reauthentication_for_backups,
+            },
+            wallet_database.clone(),
+        );
+        let mut output_manager_handle = handles
+            .expect_handle::<OutputManagerHandle>()
+            .with_reauthentication_guard(reauthentication.clone());
+        let transaction_service_handle = handles
+            .expect_handle::<TransactionServiceHandle>()
+            .with_reauthentication_guard(reauthentication.clone());
         let contacts_handle = handles.expect_handle::<ContactsServiceHandle>();
+        let liveness_handle = handles.expect_handle::<LivenessHandle>();
         let dht = handles.expect_handle::<Dht>();
         let store_and_forward_requester = dht.store_and_forward_requester();
 
@@ -273,6 +361,9 @@ where
             None
         };
 
+        if let Some(keys) = watch_only_keys {
+            key_manager_handle.import_key(keys.view_key).await?;
+        }
         persist_one_sided_payment_script_for_node_identity(&mut output_manager_handle, wallet_identity.clone())
             .await
             .map_err(|e| {
@@ -310,13 +401,16 @@ where
             key_manager_service: key_manager_handle,
             transaction_service: transaction_service_handle,
             contacts_service: contacts_handle,
+            liveness_service: liveness_handle,
             base_node_service: base_node_service_handle,
             utxo_scanner_service: utxo_scanner_service_handle,
             updater_service: updater_handle,
+            reauthentication,
             wallet_connectivity,
             db: wallet_database,
             output_db: output_manager_database,
             factories,
+            storage_integrity_report,
             #[cfg(feature = "test_harness")]
             transaction_backend: transaction_backend_handle,
             _u: PhantomData,
@@ -388,6 +482,52 @@ where
         Ok(())
     }
 
+    /// Set the base nodes the wallet uses, in order of preference. The wallet connects to the first one and fails over
+    /// to the next when the active base node becomes unreachable or unresponsive.
+    pub async fn set_base_node_peers(&mut self, peers: Vec<Peer>) -> Result<(), WalletError> {
+        if peers.is_empty() {
+            return Err(WalletError::ArgumentError {
+                argument: "peers".to_string(),
+                value: "[]".to_string(),
+                message: "At least one base node peer is required".to_string(),
+            });
+        }
+        info!(
+            target: LOG_TARGET,
+            "Wallet setting {} base node peers, preferred: {}",
+            peers.len(),
+            peers[0].public_key
+        );
+
+        let peer_manager = self.comms.peer_manager();
+        let mut connectivity = self.comms.connectivity();
+        for previous in self.wallet_connectivity.get_base_node_peers() {
+            connectivity.remove_peer_from_allow_list(previous.node_id).await?;
+        }
+        let mut base_node_peers = Vec::with_capacity(peers.len());
+        for peer in peers {
+            let peer = match peer_manager.find_by_public_key(&peer.public_key).await? {
+                Some(mut current_peer) => {
+                    for address in peer.addresses.addresses() {
+                        if !current_peer.addresses.contains(address.address()) {
+                            current_peer
+                                .addresses
+                                .add_address(address.address(), &PeerAddressSource::Config);
+                        }
+                    }
+                    current_peer
+                },
+                None => peer,
+            };
+            peer_manager.add_peer(peer.clone()).await?;
+            connectivity.add_peer_to_allow_list(peer.node_id.clone()).await?;
+            base_node_peers.push(peer);
+        }
+        self.wallet_connectivity.set_base_node_peers(base_node_peers);
+
+        Ok(())
+    }
+
     pub async fn get_base_node_peer(&mut self) -> Option<Peer> {
         self.wallet_connectivity.get_current_base_node_peer()
     }
@@ -481,6 +621,7 @@ where
             .import_utxo_with_status(
                 unblinded_output.value,
                 source_address,
+                None,
                 message,
                 Some(unblinded_output.features.maturity),
                 ImportStatus::Imported,
@@ -521,6 +662,74 @@ where
         signature.verify(public_key, message)
     }
 
+    /// Produces a statement of every output this wallet spent in a transaction mined between `from` and `to`, with the
+    /// block and kernel of the spending transaction, signed with the wallet's public key so that a third party can
+    /// verify it against the chain. Outputs whose spending transaction is not in the wallet's history are left out, as
+    /// they have no kernel to refer to.
+    pub async fn create_spent_output_proof(
+        &mut self,
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    ) -> Result<SpentOutputProof, WalletError> {
+        let spent_outputs = self.output_manager_service.get_spent_outputs().await?;
+        let mut records = Vec::with_capacity(spent_outputs.len());
+        for output in spent_outputs {
+            let (tx_id, spent_at_height, spent_in_block) = match (
+                output.spent_in_tx_id,
+                output.marked_deleted_at_height,
+                output.marked_deleted_in_block,
+            ) {
+                (Some(tx_id), Some(height), Some(block)) => (tx_id, height, block),
+                _ => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Spent output {} has no known spending transaction, leaving it out of the proof",
+                        output.commitment.to_hex()
+                    );
+                    continue;
+                },
+            };
+            let spending_tx = self.transaction_service.get_completed_transaction(tx_id).await?;
+            let spent_timestamp = match spending_tx.mined_timestamp {
+                Some(timestamp) if timestamp >= from && timestamp <= to => timestamp,
+                _ => continue,
+            };
+            let kernel = match spending_tx.transaction.body.kernels().first() {
+                Some(kernel) => kernel,
+                None => continue,
+            };
+            records.push(SpentOutputRecord {
+                commitment: output.commitment,
+                output_hash: output.hash,
+                value: output.wallet_output.value,
+                spent_in_tx_id: tx_id,
+                spent_at_height,
+                spent_in_block,
+                spent_timestamp,
+                kernel_excess: kernel.excess.clone(),
+                kernel_excess_sig: kernel.excess_sig.clone(),
+            });
+        }
+        records.sort_by_key(|r| r.spent_at_height);
+        info!(
+            target: LOG_TARGET,
+            "Created a spent output proof for {} output(s) spent between {} and {}",
+            records.len(),
+            from,
+            to
+        );
+
+        let node_identity = self.comms.node_identity();
+        let statement = SpentOutputStatement::new(
+            node_identity.public_key().clone(),
+            from,
+            to,
+            Utc::now().naive_utc(),
+            records,
+        );
+        Ok(statement.sign(node_identity.secret_key())?)
+    }
+
     /// Appraise the expected outputs and a fee
     pub async fn preview_coin_split_with_commitments_no_amount(
         &mut self,
@@ -575,6 +784,23 @@ where
         }
     }
 
+    /// Do a coin split into outputs of exactly the given denominations
+    pub async fn coin_split_with_denominations(
+        &mut self,
+        denominations: Vec<MicroMinotari>,
+        fee_per_gram: MicroMinotari,
+        message: String,
+    ) -> Result<TxId, WalletError> {
+        let (tx_id, split_tx, amount) = self
+            .output_manager_service
+            .create_coin_split_with_denominations(denominations, fee_per_gram)
+            .await?;
+        self.transaction_service
+            .submit_transaction(tx_id, split_tx, amount, message)
+            .await?;
+        Ok(tx_id)
+    }
+
     /// Do a coin split
     pub async fn coin_split_even(
         &mut self,
@@ -658,13 +884,36 @@ where
         }
     }
 
+    /// Joins the wallet's outputs worth less than `threshold` into one and broadcasts the transaction. Returns `None`
+    /// if there was not enough dust for the sweep to pay for itself.
+    pub async fn sweep_dust(
+        &mut self,
+        threshold: MicroMinotari,
+        fee_per_gram: MicroMinotari,
+        msg: Option<String>,
+    ) -> Result<Option<DustSweep>, WalletError> {
+        let sweep = match self.output_manager_service.sweep_dust(threshold, fee_per_gram).await? {
+            Some(sweep) => sweep,
+            None => return Ok(None),
+        };
+        self.transaction_service
+            .submit_transaction(
+                sweep.tx_id,
+                sweep.transaction.clone(),
+                sweep.amount_swept,
+                msg.unwrap_or_else(|| "Dust sweep".to_string()),
+            )
+            .await?;
+        Ok(Some(sweep))
+    }
+
     /// Utility function to find out if there is data in the database indicating that there is an incomplete recovery
     /// process in progress
     pub fn is_recovery_in_progress(&self) -> Result<bool, WalletError> {
         Ok(self.db.get_client_key_value(RECOVERY_KEY.to_string())?.is_some())
     }
 
-    pub fn get_seed_words(&self, language: &MnemonicLanguage) -> Result<SeedWords, WalletError> {
+    fn get_seed_words(&self, language: &MnemonicLanguage) -> Result<SeedWords, WalletError> {
         let master_seed = self.db.get_master_seed()?.ok_or_else(|| {
             WalletError::WalletStorageError(WalletStorageError::RecoverySeedError(
                 "Cipher Seed not found".to_string(),
@@ -674,10 +923,92 @@ where
         let seed_words = master_seed.to_mnemonic(*language, None)?;
         Ok(seed_words)
     }
+
+    /// Returns the seed words for display to the user, requiring the wallet passphrase to be supplied again if the
+    /// re-authentication policy covers seed export
+    pub async fn export_seed_words(
+        &self,
+        language: &MnemonicLanguage,
+        passphrase: Option<SafePassword>,
+    ) -> Result<SeedWords, WalletError> {
+        self.reauthentication
+            .authorize_async(SensitiveOperation::SeedExport, passphrase)
+            .await?;
+        self.get_seed_words(language)
+    }
+
+    /// Splits the seed into `share_count` Shamir shares of which any `threshold` recover it, each as seed words
+    fn get_seed_shares(
+        &self,
+        threshold: u8,
+        share_count: u8,
+        language: &MnemonicLanguage,
+    ) -> Result<Vec<SeedWords>, WalletError> {
+        let master_seed = self.db.get_master_seed()?.ok_or_else(|| {
+            WalletError::WalletStorageError(WalletStorageError::RecoverySeedError(
+                "Cipher Seed not found".to_string(),
+            ))
+        })?;
+
+        let shares = master_seed.split_into_shares(threshold, share_count, None)?;
+        Ok(shares
+            .iter()
+            .map(|share| share.to_mnemonic(*language))
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Returns the seed shares for display to the user, requiring the wallet passphrase to be supplied again if the
+    /// re-authentication policy covers seed export
+    pub async fn export_seed_shares(
+        &self,
+        threshold: u8,
+        share_count: u8,
+        language: &MnemonicLanguage,
+        passphrase: Option<SafePassword>,
+    ) -> Result<Vec<SeedWords>, WalletError> {
+        self.reauthentication
+            .authorize_async(SensitiveOperation::SeedExport, passphrase)
+            .await?;
+        self.get_seed_shares(threshold, share_count, language)
+    }
+
+    /// Writes an encrypted snapshot of the wallet database to `path`, requiring the wallet passphrase to be supplied
+    /// again if the re-authentication policy covers backup creation. Scheduled backups are authorized by the
+    /// configuration that enables the backup service.
+    pub async fn create_backup(&self, path: &Path, passphrase: Option<SafePassword>) -> Result<(), WalletError> {
+        self.reauthentication
+            .authorize_async(SensitiveOperation::BackupCreation, passphrase)
+            .await?;
+        self.db.create_snapshot(path)?;
+        Ok(())
+    }
+
+    /// Describes how this wallet derives its keys and which scripts its outputs use, see [DerivationExport]. The
+    /// export holds no secrets, it is meant to be kept with the seed words so that funds can be recovered with a
+    /// third-party tool.
+    pub async fn export_derivation_scheme(&self) -> Result<DerivationExport, WalletError> {
+        let master_seed = self.db.get_master_seed()?.ok_or_else(|| {
+            WalletError::WalletStorageError(WalletStorageError::RecoverySeedError(
+                "Cipher Seed not found".to_string(),
+            ))
+        })?;
+        let branch_indices = self.key_manager_service.get_branch_key_indices().await?;
+        Ok(DerivationExport::new(
+            self.network.as_network(),
+            master_seed.birthday(),
+            self.comms.node_identity().public_key().clone(),
+            branch_indices,
+        ))
+    }
 }
 
+/// Returns the seed the wallet keys are derived from, creating the master seed or storing the recovery seed for a new
+/// wallet. A seed passphrase turns the same master seed into a different wallet, it is only needed when the wallet is
+/// created or recovered, after which the combined seed is kept in the encrypted database. The seed words shown to the
+/// user remain those of the master seed.
 pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
     recovery_seed: Option<CipherSeed>,
+    seed_passphrase: Option<SafePassword>,
     db: &WalletDatabase<T>,
 ) -> Result<CipherSeed, WalletError> {
     let db_master_seed = db.get_master_seed()?;
@@ -686,15 +1017,23 @@ pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
         None => match db_master_seed {
             None => {
                 let seed = CipherSeed::new();
-                db.set_master_seed(seed.clone())?;
-                seed
+                store_master_seed(seed, seed_passphrase, db)?
+            },
+            Some(seed) => match (db.get_passphrase_seed()?, seed_passphrase) {
+                (Some(passphrase_seed), None) => passphrase_seed,
+                (Some(passphrase_seed), Some(seed_passphrase)) => {
+                    if seed.derive_with_seed_passphrase(&seed_passphrase)? != passphrase_seed {
+                        return Err(WalletError::SeedPassphraseMismatch);
+                    }
+                    passphrase_seed
+                },
+                (None, Some(_)) => return Err(WalletError::SeedPassphraseMismatch),
+                (None, None) => seed,
             },
-            Some(seed) => seed,
         },
         Some(recovery_seed) => {
             if db_master_seed.is_none() {
-                db.set_master_seed(recovery_seed.clone())?;
-                recovery_seed
+                store_master_seed(recovery_seed, seed_passphrase, db)?
             } else {
                 error!(
                     target: LOG_TARGET,
@@ -709,6 +1048,48 @@ pub fn read_or_create_master_seed<T: WalletBackend + 'static>(
     Ok(master_seed)
 }
 
+/// Stores the master seed of a new wallet and returns the seed its keys are derived from. The passphrase seed is
+/// derived before anything is written, so an interrupted derivation never leaves a master seed without the passphrase
+/// seed it was created with.
+fn store_master_seed<T: WalletBackend + 'static>(
+    master_seed: CipherSeed,
+    seed_passphrase: Option<SafePassword>,
+    db: &WalletDatabase<T>,
+) -> Result<CipherSeed, WalletError> {
+    match seed_passphrase {
+        Some(seed_passphrase) => {
+            let passphrase_seed = master_seed.derive_with_seed_passphrase(&seed_passphrase)?;
+            db.set_master_and_passphrase_seed(master_seed, passphrase_seed.clone())?;
+            Ok(passphrase_seed)
+        },
+        None => {
+            db.set_master_seed(master_seed.clone())?;
+            Ok(master_seed)
+        },
+    }
+}
+
+/// The software updater and webhooks make HTTP requests that do not go through tor, so they cannot be used when the
+/// transport is onion-only
+fn check_onion_only(config: &WalletConfig, auto_update: &AutoUpdateConfig) -> Result<(), WalletError> {
+    if !config.p2p.transport.onion_only {
+        return Ok(());
+    }
+    if auto_update.is_update_enabled() {
+        return Err(initialization::CommsInitializationError::OnionOnlyViolation(
+            "onion_only cannot be combined with the software updater, disable auto_update".to_string(),
+        )
+        .into());
+    }
+    if config.webhooks.is_enabled() {
+        return Err(initialization::CommsInitializationError::OnionOnlyViolation(
+            "onion_only cannot be combined with webhooks, disable them".to_string(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
 pub fn derive_comms_secret_key(master_seed: &CipherSeed) -> Result<CommsSecretKey, WalletError> {
     let comms_key_manager = KeyManager::<PublicKey, KeyDigest>::from(
         master_seed.clone(),
@@ -718,20 +1099,37 @@ pub fn derive_comms_secret_key(master_seed: &CipherSeed) -> Result<CommsSecretKe
     Ok(comms_key_manager.derive_key(0)?.key)
 }
 
-/// Persist the one-sided payment script for the current wallet NodeIdentity for use during scanning for One-sided
-/// payment outputs. This is peristed so that if the Node Identity changes the wallet will still scan for outputs
-/// using old node identities.
+/// Marks a new wallet database as a watch-only wallet for the given keys. The wallet detects incoming one-sided
+/// payments to the watched address and tracks their balance, but refuses to spend. This has to be done before the
+/// wallet is first started, an existing wallet cannot be turned into a watch-only wallet.
+pub fn initialize_watch_only_wallet<T: WalletBackend + 'static>(
+    keys: WatchOnlyKeys,
+    db: &WalletDatabase<T>,
+) -> Result<(), WalletError> {
+    if db.get_master_seed()?.is_some() || db.get_watch_only_keys()?.is_some() {
+        return Err(WalletError::WatchOnlyInitializationError(
+            "Wallet already exists! Move the existing wallet database file.".to_string(),
+        ));
+    }
+    db.set_watch_only_keys(keys)?;
+    Ok(())
+}
+
+/// Persist the one-sided payment script for the current wallet NodeIdentity, or the watched address of a watch-only
+/// wallet, for use during scanning for One-sided payment outputs. This is peristed so that if the Node Identity
+/// changes the wallet will still scan for outputs using old node identities.
 async fn persist_one_sided_payment_script_for_node_identity(
     output_manager_service: &mut OutputManagerHandle,
     wallet_identity: WalletIdentity,
 ) -> Result<(), WalletError> {
-    let script = one_sided_payment_script(wallet_identity.node_identity.public_key());
+    let (script_key_id, script_public_key) = wallet_identity.one_sided_scanning_keys();
+    let script = one_sided_payment_script(&script_public_key);
     let known_script = KnownOneSidedPaymentScript {
         script_hash: script
             .as_hash::<Blake2b<U32>>()
             .map_err(|e| WalletError::OutputManagerError(OutputManagerError::ScriptError(e)))?
             .to_vec(),
-        script_key_id: wallet_identity.wallet_node_key_id.clone(),
+        script_key_id,
         script,
         input: ExecutionStack::default(),
         script_lock_height: 0,
@@ -0,0 +1,297 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Hosts several [Wallet] instances in a single process.
+//!
+//! Each wallet managed here is started with its own databases, comms identity and shutdown signal, while sharing the
+//! tokio runtime of the host process. Wallets are addressed by a [WalletId], so that the service handles of one wallet
+//! are never confused with those of another. A wallet can be stopped on its own without affecting the others, and all
+//! wallets are stopped when the shutdown signal the manager was created with is triggered.
+//!
+//! The manager does not start wallets itself, as [Wallet::start] needs the backends, config and identity of each
+//! wallet. Instead, a wallet is started in two steps:
+//! 1. [WalletManager::reserve] claims the id and returns the shutdown signal that must be passed to [Wallet::start].
+//! 2. [WalletManager::insert] hands the started wallet to the manager.
+//!
+//! Each wallet must be configured with its own database file, comms datastore path and listener address. Opening the
+//! same database twice is already refused by the exclusive database file lock, and the manager refuses two wallets
+//! with the same comms identity. The transaction service metrics are labelled with the node id of each wallet, so
+//! wallets sharing a process are reported separately.
+
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
+
+use log::*;
+use tari_contacts::contacts_service::storage::database::ContactsBackend;
+use tari_core::transactions::key_manager::SecretTransactionKeyManagerInterface;
+use tari_shutdown::{Shutdown, ShutdownSignal};
+use tokio::task;
+
+use crate::{
+    error::WalletError,
+    output_manager_service::storage::database::OutputManagerBackend,
+    storage::database::WalletBackend,
+    transaction_service::storage::database::TransactionBackend,
+    wallet::Wallet,
+};
+
+const LOG_TARGET: &str = "wallet::wallet_manager";
+
+/// The name under which a wallet is registered with a [WalletManager]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WalletId(String);
+
+impl WalletId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for WalletId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl From<&str> for WalletId {
+    fn from(id: &str) -> Self {
+        Self(id.to_string())
+    }
+}
+
+impl fmt::Display for WalletId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The shutdown of a single managed wallet. The wallet's signal is triggered either when the wallet is stopped, or when
+/// the parent signal of the manager is triggered.
+struct WalletShutdown {
+    stop: Shutdown,
+    signal: ShutdownSignal,
+}
+
+impl WalletShutdown {
+    fn new(mut parent: ShutdownSignal) -> Self {
+        let mut wallet_shutdown = Shutdown::new();
+        let signal = wallet_shutdown.to_signal();
+        let stop = Shutdown::new();
+        let mut stop_signal = stop.to_signal();
+        task::spawn(async move {
+            tokio::select! {
+                _ = parent.wait() => {},
+                _ = stop_signal.wait() => {},
+            }
+            wallet_shutdown.trigger();
+        });
+        Self { stop, signal }
+    }
+
+    fn to_signal(&self) -> ShutdownSignal {
+        self.signal.clone()
+    }
+
+    fn trigger(&mut self) {
+        self.stop.trigger();
+    }
+}
+
+enum ManagedWallet<T, U, V, W, TKeyManagerInterface> {
+    /// The id has been reserved and the wallet is being started
+    Reserved(WalletShutdown),
+    Running {
+        wallet: Box<Wallet<T, U, V, W, TKeyManagerInterface>>,
+        shutdown: WalletShutdown,
+    },
+}
+
+/// A registry of the wallets running in this process, keyed by [WalletId]
+pub struct WalletManager<T, U, V, W, TKeyManagerInterface> {
+    shutdown_signal: ShutdownSignal,
+    wallets: HashMap<WalletId, ManagedWallet<T, U, V, W, TKeyManagerInterface>>,
+}
+
+impl<T, U, V, W, TKeyManagerInterface> WalletManager<T, U, V, W, TKeyManagerInterface>
+where
+    T: WalletBackend + 'static,
+    U: TransactionBackend + 'static,
+    V: OutputManagerBackend + 'static,
+    W: ContactsBackend + 'static,
+    TKeyManagerInterface: SecretTransactionKeyManagerInterface,
+{
+    /// Create a manager whose wallets are all shut down when `shutdown_signal` is triggered
+    pub fn new(shutdown_signal: ShutdownSignal) -> Self {
+        Self {
+            shutdown_signal,
+            wallets: HashMap::new(),
+        }
+    }
+
+    /// Reserve `id` for a wallet that is about to be started, returning the shutdown signal that must be passed to
+    /// [Wallet::start] for that wallet. Must be called from within the tokio runtime.
+    pub fn reserve<I: Into<WalletId>>(&mut self, id: I) -> Result<ShutdownSignal, WalletError> {
+        match self.wallets.entry(id.into()) {
+            Entry::Occupied(entry) => Err(WalletError::DuplicateWalletId(entry.key().to_string())),
+            Entry::Vacant(entry) => {
+                let shutdown = WalletShutdown::new(self.shutdown_signal.clone());
+                let signal = shutdown.to_signal();
+                debug!(target: LOG_TARGET, "Reserved wallet `{}`", entry.key());
+                entry.insert(ManagedWallet::Reserved(shutdown));
+                Ok(signal)
+            },
+        }
+    }
+
+    /// Release a reservation made with [WalletManager::reserve] for a wallet that failed to start. Running wallets are
+    /// not affected, use [WalletManager::stop] for those.
+    pub fn release(&mut self, id: &WalletId) {
+        if !matches!(self.wallets.get(id), Some(ManagedWallet::Reserved(_))) {
+            return;
+        }
+        if let Some(ManagedWallet::Reserved(mut shutdown)) = self.wallets.remove(id) {
+            shutdown.trigger();
+        }
+    }
+
+    /// Hand a started wallet over to the manager. The wallet must have been started with the shutdown signal returned
+    /// by [WalletManager::reserve] for the same `id`.
+    pub fn insert(
+        &mut self,
+        id: &WalletId,
+        wallet: Wallet<T, U, V, W, TKeyManagerInterface>,
+    ) -> Result<(), WalletError> {
+        let public_key = wallet.comms.node_identity_ref().public_key().clone();
+        let duplicate = self
+            .iter()
+            .find(|(_, w)| w.comms.node_identity_ref().public_key() == &public_key);
+        if let Some((other, _)) = duplicate {
+            return Err(WalletError::DuplicateWalletIdentity {
+                id: id.to_string(),
+                other: other.to_string(),
+            });
+        }
+
+        match self.wallets.remove(id) {
+            Some(ManagedWallet::Reserved(shutdown)) => {
+                info!(target: LOG_TARGET, "Wallet `{}` is running as {}", id, public_key);
+                self.wallets.insert(id.clone(), ManagedWallet::Running {
+                    wallet: Box::new(wallet),
+                    shutdown,
+                });
+                Ok(())
+            },
+            Some(running) => {
+                self.wallets.insert(id.clone(), running);
+                Err(WalletError::DuplicateWalletId(id.to_string()))
+            },
+            None => Err(WalletError::UnknownWalletId(id.to_string())),
+        }
+    }
+
+    /// The running wallet registered under `id`
+    pub fn get(&self, id: &WalletId) -> Option<&Wallet<T, U, V, W, TKeyManagerInterface>> {
+        match self.wallets.get(id) {
+            Some(ManagedWallet::Running { wallet, .. }) => Some(wallet.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The running wallet registered under `id`, for the wallet APIs that need mutable access
+    pub fn get_mut(&mut self, id: &WalletId) -> Option<&mut Wallet<T, U, V, W, TKeyManagerInterface>> {
+        match self.wallets.get_mut(id) {
+            Some(ManagedWallet::Running { wallet, .. }) => Some(wallet.as_mut()),
+            _ => None,
+        }
+    }
+
+    /// All running wallets and their ids, in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&WalletId, &Wallet<T, U, V, W, TKeyManagerInterface>)> {
+        self.wallets.iter().filter_map(|(id, managed)| match managed {
+            ManagedWallet::Running { wallet, .. } => Some((id, wallet.as_ref())),
+            ManagedWallet::Reserved(_) => None,
+        })
+    }
+
+    /// The number of running wallets
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Shut down the wallet registered under `id` and wait for its services to exit. The other wallets keep running.
+    pub async fn stop(&mut self, id: &WalletId) -> Result<(), WalletError> {
+        match self.wallets.remove(id) {
+            Some(ManagedWallet::Running { wallet, mut shutdown }) => {
+                info!(target: LOG_TARGET, "Stopping wallet `{}`", id);
+                shutdown.trigger();
+                wallet.wait_until_shutdown().await;
+                debug!(target: LOG_TARGET, "Wallet `{}` stopped", id);
+                Ok(())
+            },
+            Some(ManagedWallet::Reserved(mut shutdown)) => {
+                shutdown.trigger();
+                Ok(())
+            },
+            None => Err(WalletError::UnknownWalletId(id.to_string())),
+        }
+    }
+
+    /// Shut down all wallets and wait for their services to exit
+    pub async fn stop_all(&mut self) {
+        let ids = self.wallets.keys().cloned().collect::<Vec<_>>();
+        for id in ids {
+            if let Err(e) = self.stop(&id).await {
+                warn!(target: LOG_TARGET, "Could not stop wallet `{}`: {}", id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_shuts_down_wallets_independently_and_with_the_parent() {
+        let mut parent = Shutdown::new();
+        let mut first = WalletShutdown::new(parent.to_signal());
+        let second = WalletShutdown::new(parent.to_signal());
+
+        first.trigger();
+        timeout(Duration::from_secs(5), first.to_signal()).await.unwrap();
+        tokio::task::yield_now().await;
+        assert!(!second.to_signal().is_triggered());
+
+        parent.trigger();
+        timeout(Duration::from_secs(5), second.to_signal()).await.unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tari_common::configuration::{serializers, StringList};
+use tari_utilities::SafePassword;
+
+use crate::config::deserialize_safe_password_option;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    /// The HTTP endpoints that transaction event notifications are POSTed to. The webhook service is only started if
+    /// at least one endpoint is configured.
+    pub endpoints: StringList,
+    /// The shared secret used to sign each payload with HMAC-SHA256. Payloads are sent unsigned if this is not set.
+    #[serde(deserialize_with = "deserialize_safe_password_option")]
+    pub secret: Option<SafePassword>,
+    /// The number of times delivery of a notification to an endpoint is attempted before it is dropped
+    pub max_attempts: u32,
+    /// The delay before the first retry, doubled after every further failed attempt
+    #[serde(with = "serializers::seconds")]
+    pub retry_delay: Duration,
+    /// The time to wait for an endpoint to respond to a single delivery attempt
+    #[serde(with = "serializers::seconds")]
+    pub request_timeout: Duration,
+}
+
+impl WebhookConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.endpoints.is_empty()
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            endpoints: StringList::default(),
+            secret: None,
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(2),
+            request_timeout: Duration::from_secs(10),
+        }
+    }
+}
@@ -0,0 +1,388 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{cmp::min, sync::Arc, time::Duration};
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use log::*;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tari_common_types::transaction::TxId;
+use tari_shutdown::ShutdownSignal;
+use tari_utilities::hex::{to_hex, Hex};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::{
+    transaction_service::{
+        handle::{TransactionEvent, TransactionServiceHandle},
+        storage::models::{CompletedTransaction, TxCancellationReason},
+    },
+    webhook_service::{config::WebhookConfig, error::WebhookError},
+};
+
+const LOG_TARGET: &str = "wallet::webhook_service::service";
+
+/// The header carrying the hex encoded HMAC-SHA256 of the request body, keyed with the configured secret
+pub const SIGNATURE_HEADER: &str = "X-Tari-Signature";
+/// The header carrying the event kind, so that receivers can route a notification without parsing the body
+pub const EVENT_HEADER: &str = "X-Tari-Event";
+/// Retries back off exponentially, but never wait longer than this between two attempts
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(300);
+
+/// The transaction events that are pushed to webhook endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    Received,
+    ReceivedFinalized,
+    Broadcast,
+    MinedUnconfirmed,
+    Mined,
+    Cancelled,
+}
+
+impl WebhookEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEventKind::Received => "received",
+            WebhookEventKind::ReceivedFinalized => "received_finalized",
+            WebhookEventKind::Broadcast => "broadcast",
+            WebhookEventKind::MinedUnconfirmed => "mined_unconfirmed",
+            WebhookEventKind::Mined => "mined",
+            WebhookEventKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// The JSON body POSTed to every endpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookPayload {
+    pub event: WebhookEventKind,
+    pub tx_id: u64,
+    /// Unix timestamp of when the wallet observed the event. It is covered by the signature so receivers can reject
+    /// replayed notifications.
+    pub timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_confirmations: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cancellation_reason: Option<TxCancellationReason>,
+    /// Details of the transaction, if the wallet still knew about it when the event was handled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<WebhookTransaction>,
+}
+
+impl WebhookPayload {
+    /// Builds the payload for a transaction event, or returns None if the event is not pushed to endpoints
+    pub fn from_event(event: &TransactionEvent) -> Option<Self> {
+        let (event, tx_id, num_confirmations, is_valid, cancellation_reason) = match event {
+            TransactionEvent::ReceivedTransaction(tx_id) => (WebhookEventKind::Received, *tx_id, None, None, None),
+            TransactionEvent::ReceivedFinalizedTransaction(tx_id) => {
+                (WebhookEventKind::ReceivedFinalized, *tx_id, None, None, None)
+            },
+            TransactionEvent::TransactionBroadcast(tx_id) => (WebhookEventKind::Broadcast, *tx_id, None, None, None),
+            TransactionEvent::TransactionMinedUnconfirmed {
+                tx_id,
+                num_confirmations,
+                is_valid,
+            } |
+            TransactionEvent::FauxTransactionUnconfirmed {
+                tx_id,
+                num_confirmations,
+                is_valid,
+            } => (
+                WebhookEventKind::MinedUnconfirmed,
+                *tx_id,
+                Some(*num_confirmations),
+                Some(*is_valid),
+                None,
+            ),
+            TransactionEvent::TransactionMined { tx_id, is_valid } |
+            TransactionEvent::FauxTransactionConfirmed { tx_id, is_valid } => {
+                (WebhookEventKind::Mined, *tx_id, None, Some(*is_valid), None)
+            },
+            TransactionEvent::TransactionCancelled(tx_id, reason) => {
+                (WebhookEventKind::Cancelled, *tx_id, None, None, Some(*reason))
+            },
+            _ => return None,
+        };
+        Some(Self {
+            event,
+            tx_id: tx_id.as_u64(),
+            timestamp: Utc::now().timestamp(),
+            num_confirmations,
+            is_valid,
+            cancellation_reason,
+            transaction: None,
+        })
+    }
+
+    pub fn with_transaction(mut self, transaction: WebhookTransaction) -> Self {
+        self.transaction = Some(transaction);
+        self
+    }
+}
+
+/// The transaction fields included in a webhook payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WebhookTransaction {
+    pub source_address: String,
+    pub destination_address: String,
+    pub amount: u64,
+    pub fee: u64,
+    pub status: String,
+    pub direction: String,
+    pub message: String,
+    pub timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mined_height: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mined_in_block: Option<String>,
+}
+
+impl From<CompletedTransaction> for WebhookTransaction {
+    fn from(tx: CompletedTransaction) -> Self {
+        Self {
+            source_address: tx.source_address.to_hex(),
+            destination_address: tx.destination_address.to_hex(),
+            amount: tx.amount.as_u64(),
+            fee: tx.fee.as_u64(),
+            status: tx.status.to_string(),
+            direction: tx.direction.to_string(),
+            message: tx.message,
+            timestamp: tx.timestamp.timestamp(),
+            mined_height: tx.mined_height,
+            mined_in_block: tx.mined_in_block.map(|hash| hash.to_hex()),
+        }
+    }
+}
+
+/// Returns the hex encoded HMAC-SHA256 of `body` keyed with `secret`
+pub fn sign_payload(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    to_hex(&mac.finalize().into_bytes())
+}
+
+/// Subscribes to transaction service events and delivers the ones merchants care about to the configured endpoints
+pub struct WebhookService {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    transaction_service: TransactionServiceHandle,
+    shutdown_signal: ShutdownSignal,
+}
+
+impl WebhookService {
+    pub fn new(
+        config: WebhookConfig,
+        client: reqwest::Client,
+        transaction_service: TransactionServiceHandle,
+        shutdown_signal: ShutdownSignal,
+    ) -> Self {
+        Self {
+            config,
+            client,
+            transaction_service,
+            shutdown_signal,
+        }
+    }
+
+    pub async fn start(mut self) {
+        let mut event_stream = self.transaction_service.get_event_stream();
+        let mut shutdown_signal = self.shutdown_signal.clone();
+        loop {
+            tokio::select! {
+                event = event_stream.recv() => match event {
+                    Ok(event) => self.handle_event(event).await,
+                    Err(RecvError::Lagged(n)) => {
+                        warn!(target: LOG_TARGET, "Webhook service missed {} transaction events", n);
+                    },
+                    Err(RecvError::Closed) => {
+                        info!(target: LOG_TARGET, "Transaction event stream closed");
+                        break;
+                    },
+                },
+                _ = shutdown_signal.wait() => {
+                    info!(target: LOG_TARGET, "Webhook service shutting down because it received the shutdown signal");
+                    break;
+                }
+            }
+        }
+    }
+
+    async fn handle_event(&mut self, event: Arc<TransactionEvent>) {
+        let payload = match WebhookPayload::from_event(&event) {
+            Some(payload) => payload,
+            None => return,
+        };
+        let payload = match self
+            .transaction_service
+            .get_any_transaction(TxId::from(payload.tx_id))
+            .await
+        {
+            Ok(Some(tx)) => payload.with_transaction(CompletedTransaction::from(tx).into()),
+            Ok(None) => payload,
+            Err(e) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Could not fetch transaction {} for webhook payload: {}", payload.tx_id, e
+                );
+                payload
+            },
+        };
+        let body = match serde_json::to_vec(&payload) {
+            Ok(body) => body,
+            Err(e) => {
+                error!(target: LOG_TARGET, "{}", WebhookError::from(e));
+                return;
+            },
+        };
+        let signature = self.config.secret.as_ref().map(|s| sign_payload(s.reveal(), &body));
+
+        // Each endpoint is delivered to on its own task so that a slow or unreachable endpoint does not hold up
+        // the event stream or the other endpoints
+        for endpoint in self.config.endpoints.iter() {
+            let delivery = Delivery {
+                client: self.client.clone(),
+                endpoint: endpoint.clone(),
+                event: payload.event,
+                tx_id: payload.tx_id,
+                body: body.clone(),
+                signature: signature.clone(),
+                max_attempts: self.config.max_attempts,
+                retry_delay: self.config.retry_delay,
+            };
+            let mut shutdown_signal = self.shutdown_signal.clone();
+            tokio::spawn(async move {
+                tokio::select! {
+                    _ = delivery.run() => {},
+                    _ = shutdown_signal.wait() => {},
+                }
+            });
+        }
+    }
+}
+
+struct Delivery {
+    client: reqwest::Client,
+    endpoint: String,
+    event: WebhookEventKind,
+    tx_id: u64,
+    body: Vec<u8>,
+    signature: Option<String>,
+    max_attempts: u32,
+    retry_delay: Duration,
+}
+
+impl Delivery {
+    async fn run(self) {
+        let mut delay = self.retry_delay;
+        for attempt in 1..=self.max_attempts {
+            match self.send().await {
+                Ok(()) => {
+                    debug!(
+                        target: LOG_TARGET,
+                        "Delivered '{}' webhook for tx {} to {} (attempt {})",
+                        self.event.as_str(),
+                        self.tx_id,
+                        self.endpoint,
+                        attempt
+                    );
+                    return;
+                },
+                Err(e) if e.is_retryable() && attempt < self.max_attempts => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Webhook delivery for tx {} to {} failed (attempt {}/{}): {}. Retrying in {:.2?}",
+                        self.tx_id,
+                        self.endpoint,
+                        attempt,
+                        self.max_attempts,
+                        e,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay = min(delay * 2, MAX_RETRY_DELAY);
+                },
+                Err(e) => {
+                    error!(
+                        target: LOG_TARGET,
+                        "Dropping '{}' webhook for tx {} to {} after {} attempt(s): {}",
+                        self.event.as_str(),
+                        self.tx_id,
+                        self.endpoint,
+                        attempt,
+                        e
+                    );
+                    return;
+                },
+            }
+        }
+    }
+
+    async fn send(&self) -> Result<(), WebhookError> {
+        let mut request = self
+            .client
+            .post(&self.endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_HEADER, self.event.as_str())
+            .body(self.body.clone());
+        if let Some(signature) = &self.signature {
+            request = request.header(SIGNATURE_HEADER, signature);
+        }
+        let response = request.send().await?;
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(WebhookError::UnexpectedStatus(response.status().as_u16()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_signs_payloads_with_hmac_sha256() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn it_only_pushes_merchant_events() {
+        let tx_id = TxId::from(7u64);
+        let payload = WebhookPayload::from_event(&TransactionEvent::TransactionCancelled(
+            tx_id,
+            TxCancellationReason::UserCancelled,
+        ))
+        .unwrap();
+        assert_eq!(payload.event, WebhookEventKind::Cancelled);
+        assert_eq!(payload.tx_id, 7);
+        assert!(WebhookPayload::from_event(&TransactionEvent::ReceivedTransactionReply(tx_id)).is_none());
+    }
+}
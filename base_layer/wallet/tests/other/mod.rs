@@ -150,6 +150,8 @@ async fn create_wallet(
         },
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: StringList::new(),
+        allowed_peers: StringList::new(),
+        denied_peers: StringList::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
         auxiliary_tcp_listener_address: None,
@@ -185,7 +187,7 @@ async fn create_wallet(
     let _db_value = wallet_backend.write(WriteOperation::Insert(DbKeyValuePair::BaseNodeChainMetadata(metadata)));
 
     let wallet_db = WalletDatabase::new(wallet_backend);
-    let master_seed = read_or_create_master_seed(recovery_seed, &wallet_db)?;
+    let master_seed = read_or_create_master_seed(recovery_seed, None, &wallet_db)?;
 
     let output_db = OutputManagerDatabase::new(output_manager_backend.clone());
 
@@ -688,6 +690,8 @@ async fn test_import_utxo() {
         dht: Default::default(),
         allow_test_addresses: true,
         listener_liveness_allowlist_cidrs: StringList::new(),
+        allowed_peers: StringList::new(),
+        denied_peers: StringList::new(),
         listener_liveness_max_sessions: 0,
         user_agent: "tari/test-wallet".to_string(),
         auxiliary_tcp_listener_address: None,
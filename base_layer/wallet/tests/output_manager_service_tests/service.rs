@@ -23,9 +23,10 @@
 use std::{collections::HashMap, convert::TryInto, sync::Arc, time::Duration};
 
 use minotari_wallet::{
-    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle},
+    base_node_service::handle::{BaseNodeEvent, BaseNodeServiceHandle, ChainTip},
     connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityMock},
     output_manager_service::{
+        coin_selection::CoinSelectionMethod,
         config::OutputManagerServiceConfig,
         error::{OutputManagerError, OutputManagerStorageError},
         handle::{OutputManagerEvent, OutputManagerHandle},
@@ -46,7 +47,7 @@ use rand::{rngs::OsRng, RngCore};
 use tari_common::configuration::Network;
 use tari_common_types::{
     transaction::TxId,
-    types::{ComAndPubSignature, PublicKey},
+    types::{ComAndPubSignature, FixedHash, PublicKey},
 };
 use tari_comms::{
     peer_manager::{NodeIdentity, PeerFeatures},
@@ -61,7 +62,7 @@ use tari_core::{
     proto::base_node::{QueryDeletedData, QueryDeletedResponse, UtxoQueryResponse, UtxoQueryResponses},
     transactions::{
         fee::Fee,
-        key_manager::{TransactionKeyManagerBranch, TransactionKeyManagerInterface},
+        key_manager::{TariKeyId, TransactionKeyManagerBranch, TransactionKeyManagerInterface, ViewKeyExport},
         tari_amount::{uT, MicroMinotari, T},
         test_helpers::{
             create_test_core_key_manager_with_memory_db,
@@ -69,7 +70,7 @@ use tari_core::{
             TestKeyManager,
             TestParams,
         },
-        transaction_components::{OutputFeatures, OutputType, TransactionOutput, WalletOutput},
+        transaction_components::{OutputFeatures, OutputType, TransactionOutput, WalletOutput, WalletOutputBuilder},
         transaction_protocol::{sender::TransactionSenderMessage, TransactionMetadata},
         weight::TransactionWeight,
         CryptoFactories,
@@ -80,6 +81,7 @@ use tari_key_manager::key_manager_service::KeyManagerInterface;
 use tari_script::{inputs, script, TariScript};
 use tari_service_framework::reply_channel;
 use tari_shutdown::Shutdown;
+use tari_utilities::SafePassword;
 use tokio::{
     sync::{broadcast, broadcast::channel},
     task,
@@ -111,11 +113,19 @@ struct TestOmsService {
     pub key_manager_handle: TestKeyManager,
 }
 
+async fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
+    backend: T,
+    with_connection: bool,
+) -> TestOmsService {
+    setup_output_manager_service_with_config(backend, with_connection, OutputManagerServiceConfig::default()).await
+}
+
 #[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_lines)]
-async fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
+async fn setup_output_manager_service_with_config<T: OutputManagerBackend + 'static>(
     backend: T,
     with_connection: bool,
+    config: OutputManagerServiceConfig,
 ) -> TestOmsService {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -163,7 +173,7 @@ async fn setup_output_manager_service<T: OutputManagerBackend + 'static>(
 
     let wallet_identity = WalletIdentity::new(server_node_identity.clone(), Network::LocalNet);
     let output_manager_service = OutputManagerService::new(
-        OutputManagerServiceConfig { ..Default::default() },
+        config,
         oms_request_receiver,
         OutputManagerDatabase::new(backend),
         oms_event_publisher.clone(),
@@ -379,6 +389,58 @@ async fn fee_estimate() {
     assert_eq!(fee, MicroMinotari::from(375));
 }
 
+#[tokio::test]
+async fn test_preview_transaction() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let uo = make_input(
+        &mut OsRng.clone(),
+        MicroMinotari::from(3000),
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let fee_calc = Fee::new(*create_consensus_constants(0).transaction_weight_params());
+    let fee_per_gram = MicroMinotari::from(5);
+
+    let preview = oms
+        .output_manager_handle
+        .preview_transaction(MicroMinotari::from(100), fee_per_gram, UtxoSelectionCriteria::default())
+        .await
+        .unwrap();
+    let expected_fee = fee_calc.calculate(
+        fee_per_gram,
+        1,
+        1,
+        2,
+        2 * default_features_and_scripts_size_byte_size()
+            .expect("Failed to get default features and scripts size byte size"),
+    );
+    assert_eq!(preview.inputs.len(), 1);
+    assert_eq!(preview.total_input_value, MicroMinotari::from(3000));
+    assert_eq!(preview.fee, expected_fee);
+    assert_eq!(preview.change, MicroMinotari::from(2900) - expected_fee);
+
+    // Nothing was encumbered by the preview
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroMinotari::from(3000));
+    assert_eq!(balance.pending_outgoing_balance, MicroMinotari::from(0));
+
+    let err = oms
+        .output_manager_handle
+        .preview_transaction(
+            MicroMinotari::from(3000),
+            fee_per_gram,
+            UtxoSelectionCriteria::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds));
+}
+
 #[allow(clippy::identity_op)]
 #[allow(clippy::too_many_lines)]
 #[tokio::test]
@@ -659,6 +721,88 @@ async fn test_utxo_selection_with_chain_metadata() {
         assert_ne!(utxo.wallet_output.features.maturity, 5);
         assert_ne!(utxo.wallet_output.value, 5 * amount);
     }
+
+    // the remaining utxos above the tip become spendable at their maturity heights
+    let schedule = oms.get_maturity_schedule().await.unwrap();
+    assert_eq!(
+        schedule.iter().map(|e| e.spendable_at_height).collect::<Vec<_>>(),
+        vec![7, 8, 9, 10]
+    );
+    for entry in schedule {
+        assert_eq!(entry.num_outputs, 1);
+        assert_eq!(entry.amount, entry.spendable_at_height * amount);
+        assert_eq!(entry.coinbase_amount, MicroMinotari::zero());
+    }
+}
+
+#[tokio::test]
+async fn test_utxo_selection_reports_when_time_locked_funds_mature() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+
+    let server_node_identity = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    // setup with chain metadata at a height of 6
+    let (mut oms, _shutdown, _, _, _, key_manager) = setup_oms_with_bn_state(
+        OutputManagerSqliteDatabase::new(connection),
+        Some(6),
+        server_node_identity,
+    )
+    .await;
+    let amount = MicroMinotari::from(1000);
+    let fee_per_gram = MicroMinotari::from(2);
+
+    // one spendable utxo, one that matures at height 8 and one that is locked by its script until height 9
+    let uo = make_input_with_features(&mut OsRng.clone(), amount, OutputFeatures::default(), &key_manager).await;
+    oms.add_output(uo, None).await.unwrap();
+    let uo = make_input_with_features(
+        &mut OsRng.clone(),
+        5 * amount,
+        OutputFeatures {
+            maturity: 8,
+            ..Default::default()
+        },
+        &key_manager,
+    )
+    .await;
+    oms.add_output(uo, None).await.unwrap();
+    let mut uo =
+        make_input_with_features(&mut OsRng.clone(), 5 * amount, OutputFeatures::default(), &key_manager).await;
+    uo.script_lock_height = 9;
+    oms.add_output(uo, None).await.unwrap();
+
+    let balance = oms.get_balance().await.unwrap();
+    assert_eq!(balance.time_locked_balance, Some(10 * amount));
+
+    let schedule = oms.get_maturity_schedule().await.unwrap();
+    assert_eq!(
+        schedule
+            .iter()
+            .map(|e| (e.spendable_at_height, e.amount, e.num_outputs))
+            .collect::<Vec<_>>(),
+        vec![(8, 5 * amount, 1), (9, 5 * amount, 1)]
+    );
+
+    // the error reports the first height at which enough funds have matured to cover the shortfall
+    for (send_amount, maturity_height) in [(3 * amount, Some(8)), (8 * amount, Some(9)), (20 * amount, None)] {
+        let err = oms
+            .prepare_transaction_to_send(
+                TxId::new_random(),
+                send_amount,
+                UtxoSelectionCriteria::default(),
+                OutputFeatures::default(),
+                fee_per_gram,
+                TransactionMetadata::default(),
+                "".to_string(),
+                script!(Nop),
+                Covenant::default(),
+                MicroMinotari::zero(),
+            )
+            .await
+            .unwrap_err();
+        match maturity_height {
+            Some(height) => assert!(matches!(err, OutputManagerError::FundsTimeLocked(h) if h == height)),
+            None => assert!(matches!(err, OutputManagerError::NotEnoughFunds)),
+        }
+    }
 }
 
 #[tokio::test]
@@ -727,6 +871,64 @@ async fn test_utxo_selection_with_tx_priority() {
     assert_ne!(utxos[0].wallet_output.features.output_type, OutputType::Coinbase);
 }
 
+#[tokio::test]
+async fn send_with_specific_outputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    for _i in 0..3 {
+        let uo = make_input(
+            &mut OsRng.clone(),
+            MicroMinotari::from(2000),
+            &OutputFeatures::default(),
+            &oms.key_manager_handle,
+        )
+        .await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    let utxos = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+
+    // A single selected output cannot be topped up from the others
+    let err = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroMinotari::from(3000),
+            UtxoSelectionCriteria::specific(vec![utxos[0].commitment.clone()]),
+            OutputFeatures::default(),
+            MicroMinotari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::SelectedOutputsInsufficient { .. }));
+
+    // Both selected outputs are spent even though one would cover the amount
+    oms.output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroMinotari::from(1000),
+            UtxoSelectionCriteria::specific(vec![utxos[0].commitment.clone(), utxos[1].commitment.clone()]),
+            OutputFeatures::default(),
+            MicroMinotari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap();
+    let remaining = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(remaining.len(), 1);
+    assert_eq!(remaining[0].commitment, utxos[2].commitment);
+}
+
 #[tokio::test]
 async fn send_not_enough_funds() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -845,6 +1047,69 @@ async fn send_no_change() {
     );
 }
 
+#[tokio::test]
+async fn send_no_change_with_branch_and_bound() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let fee_per_gram = MicroMinotari::from(4);
+    let constants = create_consensus_constants(0);
+    let fee_without_change = Fee::new(*constants.transaction_weight_params()).calculate(
+        fee_per_gram,
+        1,
+        2,
+        1,
+        default_features_and_scripts_size_byte_size()
+            .expect("Failed to get default features and scripts size byte size"),
+    );
+    for value in [5000, 8000, 20000] {
+        let key_manager = create_test_core_key_manager_with_memory_db();
+        oms.output_manager_handle
+            .add_output(
+                create_wallet_output_with_data(
+                    script!(Nop),
+                    OutputFeatures::default(),
+                    &TestParams::new(&key_manager).await,
+                    MicroMinotari::from(value),
+                    &key_manager,
+                )
+                .await
+                .unwrap(),
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    // The two smallest outputs leave a small excess that is not worth a change output
+    let stp = oms
+        .output_manager_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroMinotari::from(5000 + 8000) - fee_without_change - MicroMinotari::from(10),
+            UtxoSelectionCriteria::default().with_coin_selection(CoinSelectionMethod::BranchAndBound),
+            OutputFeatures::default(),
+            fee_per_gram,
+            TransactionMetadata::default(),
+            "".to_string(),
+            TariScript::default(),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(stp.get_amount_to_self().unwrap(), MicroMinotari::from(0));
+    assert_eq!(
+        stp.get_fee_amount().unwrap(),
+        fee_without_change + MicroMinotari::from(10)
+    );
+    let unspent = oms.output_manager_handle.get_unspent_outputs().await.unwrap();
+    assert_eq!(unspent.len(), 1);
+    assert_eq!(unspent[0].wallet_output.value, MicroMinotari::from(20000));
+}
+
 #[tokio::test]
 async fn send_not_enough_for_change() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -959,6 +1224,50 @@ async fn cancel_transaction() {
     );
 }
 
+#[tokio::test]
+async fn output_state_changes_are_published() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+    let mut event_stream = oms.output_manager_handle.get_event_stream();
+
+    let uo = make_input(
+        &mut OsRng.clone(),
+        MicroMinotari::from(10_000),
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let tx_id = TxId::new_random();
+    oms.output_manager_handle
+        .prepare_transaction_to_send(
+            tx_id,
+            MicroMinotari::from(1000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(4),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap();
+    oms.output_manager_handle.cancel_transaction(tx_id).await.unwrap();
+
+    let mut events = Vec::new();
+    while let Ok(event) = event_stream.try_recv() {
+        events.push((*event).clone());
+    }
+    assert!(events
+        .iter()
+        .any(|e| matches!(e, OutputManagerEvent::OutputImported { tx_id: None, .. })));
+    assert!(events.contains(&OutputManagerEvent::OutputsEncumbered(tx_id)));
+    assert!(events.contains(&OutputManagerEvent::OutputsReleased(tx_id)));
+}
+
 #[tokio::test]
 async fn cancel_transaction_and_reinstate_inbound_tx() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -1257,6 +1566,41 @@ async fn it_handles_large_coin_splits() {
     assert_eq!(coin_split_tx.body.outputs().len(), split_count + 1);
 }
 
+#[tokio::test]
+async fn coin_split_with_denominations() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let uo = make_input(
+        &mut OsRng,
+        10_000 * uT,
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    assert!(oms.output_manager_handle.add_output(uo, None).await.is_ok());
+
+    let fee_per_gram = MicroMinotari::from(5);
+    let err = oms
+        .output_manager_handle
+        .create_coin_split_with_denominations(vec![1_000 * uT, MicroMinotari::zero()], fee_per_gram)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::InvalidArgument(_)));
+
+    let denominations = vec![1_000 * uT, 2_500 * uT, 4_000 * uT];
+    let (_tx_id, coin_split_tx, amount) = oms
+        .output_manager_handle
+        .create_coin_split_with_denominations(denominations.clone(), fee_per_gram)
+        .await
+        .unwrap();
+    assert_eq!(coin_split_tx.body.inputs().len(), 1);
+    // One output per denomination plus change
+    assert_eq!(coin_split_tx.body.outputs().len(), denominations.len() + 1);
+    assert_eq!(amount, 7_500 * uT);
+}
+
 #[tokio::test]
 async fn handle_coinbase_with_bulletproofs_rewinding() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -2179,6 +2523,53 @@ async fn test_get_status_by_tx_id() {
     );
 }
 
+#[tokio::test]
+async fn test_export_and_import_outputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let value = MicroMinotari::from(2000);
+    let uo = make_input(
+        &mut OsRng.clone(),
+        value,
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    let commitment = oms.output_manager_handle.get_unspent_outputs().await.unwrap()[0]
+        .commitment
+        .clone();
+
+    let passphrase = SafePassword::from("migrate me");
+    let blob = oms
+        .output_manager_handle
+        .export_outputs(vec![commitment.clone()], passphrase.clone())
+        .await
+        .unwrap();
+
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let mut other_oms = setup_output_manager_service(backend, true).await;
+
+    let err = other_oms
+        .output_manager_handle
+        .import_outputs(blob.clone(), SafePassword::from("wrong"))
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::InvalidArgument(_)));
+
+    let imported = other_oms
+        .output_manager_handle
+        .import_outputs(blob, passphrase)
+        .await
+        .unwrap();
+    assert_eq!(imported, vec![commitment]);
+    let balance = other_oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.pending_incoming_balance, value);
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn scan_for_recovery_test() {
@@ -2307,3 +2698,311 @@ async fn recovered_output_key_not_in_keychain() {
         "It should not reach an error condition or return an output"
     );
 }
+
+#[tokio::test]
+async fn test_auto_consolidation() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection));
+    let config = OutputManagerServiceConfig {
+        auto_consolidation_interval: Duration::from_secs(1),
+        auto_consolidation_threshold: 5000,
+        auto_consolidation_min_outputs: 3,
+        auto_consolidation_max_inputs: 2,
+        auto_consolidation_fee_per_gram: 1,
+        ..Default::default()
+    };
+    let mut oms = setup_output_manager_service_with_config(backend, true, config).await;
+    let mut event_stream = oms.output_manager_handle.get_event_stream();
+
+    for value in [2000, 3000, 4000, 100_000] {
+        let uo = make_input(
+            &mut OsRng.clone(),
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &oms.key_manager_handle,
+        )
+        .await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    for output in oms.output_manager_handle.get_unspent_outputs().await.unwrap() {
+        db.set_received_output_mined_height_and_status(output.hash, 1, FixedHash::zero(), true, 0)
+            .unwrap();
+    }
+
+    // Consolidation is off by default
+    sleep(Duration::from_secs(3)).await;
+    while let Ok(event) = event_stream.try_recv() {
+        assert!(!matches!(
+            &*event,
+            OutputManagerEvent::ConsolidationTransactionCreated { .. }
+        ));
+    }
+
+    oms.output_manager_handle.set_auto_consolidation(true).await.unwrap();
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut consolidation = None;
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                if let OutputManagerEvent::ConsolidationTransactionCreated { amount, fee, num_inputs, .. } =
+                    &*event.unwrap()
+                {
+                    consolidation = Some((*amount, *fee, *num_inputs));
+                    break;
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    let (amount, fee, num_inputs) = consolidation.expect("Should have created a consolidation transaction");
+    // Only the smallest fragments are joined, up to the input limit
+    assert_eq!(num_inputs, 2);
+    assert_eq!(amount + fee, MicroMinotari::from(5000));
+}
+
+#[tokio::test]
+async fn test_reorg_resets_outputs_above_the_fork() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection));
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let mut outputs = Vec::new();
+    for mined_height in [5u64, 8, 10] {
+        let uo = make_input(
+            &mut OsRng.clone(),
+            MicroMinotari::from(10_000),
+            &OutputFeatures::default(),
+            &oms.key_manager_handle,
+        )
+        .await;
+        let commitment = uo.commitment(&oms.key_manager_handle).await.unwrap();
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+        let output = db.fetch_by_commitment(commitment.clone()).unwrap();
+        db.set_received_output_mined_height_and_status(output.hash, mined_height, FixedHash::zero(), true, 0)
+            .unwrap();
+        outputs.push((mined_height, commitment));
+    }
+
+    // The wallet saw the tip at height 10, the base node replaced the last 3 blocks so anything mined above 7 is stale
+    oms.node_event
+        .send(Arc::new(BaseNodeEvent::Reorged {
+            depth: 3,
+            old_tip: ChainTip {
+                height: 10,
+                hash: FixedHash::zero(),
+            },
+            new_tip: ChainTip {
+                height: 9,
+                hash: FixedHash::from([1u8; 32]),
+            },
+        }))
+        .unwrap();
+
+    let mut reset = false;
+    for _ in 0..20 {
+        sleep(Duration::from_millis(500)).await;
+        reset = outputs.iter().filter(|(height, _)| *height > 7).all(|(_, commitment)| {
+            db.fetch_by_commitment(commitment.clone())
+                .unwrap()
+                .mined_height
+                .is_none()
+        });
+        if reset {
+            break;
+        }
+    }
+    assert!(reset, "Outputs mined above the fork should be revalidated");
+
+    let (_, commitment) = &outputs[0];
+    let output = db.fetch_by_commitment(commitment.clone()).unwrap();
+    assert_eq!(output.mined_height, Some(5));
+    assert_eq!(output.status, OutputStatus::Unspent);
+}
+
+#[tokio::test]
+async fn test_sweep_dust() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection.clone());
+    let db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(connection));
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    for value in [2000, 3000, 4000, 100_000] {
+        let uo = make_input(
+            &mut OsRng.clone(),
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &oms.key_manager_handle,
+        )
+        .await;
+        oms.output_manager_handle.add_output(uo, None).await.unwrap();
+    }
+    for output in oms.output_manager_handle.get_unspent_outputs().await.unwrap() {
+        db.set_received_output_mined_height_and_status(output.hash, 1, FixedHash::zero(), true, 0)
+            .unwrap();
+    }
+
+    // A single dust output is not worth sweeping
+    let sweep = oms
+        .output_manager_handle
+        .sweep_dust(MicroMinotari::from(2500), MicroMinotari::from(1))
+        .await
+        .unwrap();
+    assert!(sweep.is_none());
+
+    let sweep = oms
+        .output_manager_handle
+        .sweep_dust(MicroMinotari::from(5000), MicroMinotari::from(1))
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(sweep.num_outputs_swept, 3);
+    assert_eq!(sweep.amount_swept, MicroMinotari::from(9000));
+    assert_eq!(sweep.transaction.body.inputs().len(), 3);
+    assert_eq!(sweep.fee, sweep.transaction.body.get_total_fee().unwrap());
+}
+
+#[tokio::test]
+async fn test_export_view_key() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let export = oms.output_manager_handle.export_view_key().await.unwrap();
+    assert_eq!(&export.view_public_key(), oms.node_id.public_key());
+    assert_eq!(&export.spend_public_key, oms.node_id.public_key());
+
+    let decoded = ViewKeyExport::from_hex(&export.to_hex().unwrap()).unwrap();
+    assert_eq!(decoded, export);
+}
+
+/// Creates an output paying `value` to another wallet, signed with a new sender offset key as a batch payment is
+async fn create_batch_recipient_output(
+    value: MicroMinotari,
+    key_manager: &TestKeyManager,
+) -> (WalletOutput, TariKeyId) {
+    let (sender_offset_key_id, sender_offset_public_key) = key_manager
+        .get_next_key(TransactionKeyManagerBranch::SenderOffset.get_branch_key())
+        .await
+        .unwrap();
+    let (spending_key_id, _) = key_manager
+        .get_next_key(TransactionKeyManagerBranch::CommitmentMask.get_branch_key())
+        .await
+        .unwrap();
+    let (script_key_id, script_public_key) = key_manager
+        .get_next_key(TransactionKeyManagerBranch::ScriptKey.get_branch_key())
+        .await
+        .unwrap();
+    let output = WalletOutputBuilder::new(value, spending_key_id)
+        .with_features(OutputFeatures::default())
+        .with_script(script!(Nop))
+        .encrypt_data_for_recovery(key_manager, None)
+        .await
+        .unwrap()
+        .with_input_data(inputs!(script_public_key))
+        .with_sender_offset_public_key(sender_offset_public_key)
+        .with_script_key(script_key_id)
+        .with_minimum_value_promise(MicroMinotari::zero())
+        .sign_as_sender_and_receiver(key_manager, &sender_offset_key_id)
+        .await
+        .unwrap()
+        .try_build(key_manager)
+        .await
+        .unwrap();
+    (output, sender_offset_key_id)
+}
+
+#[tokio::test]
+async fn test_create_batch_transaction() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let available = 100_000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        available,
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let recipient_outputs = vec![
+        create_batch_recipient_output(20_000 * uT, &oms.key_manager_handle).await,
+        create_batch_recipient_output(30_000 * uT, &oms.key_manager_handle).await,
+    ];
+    let recipient_commitments = recipient_outputs
+        .iter()
+        .map(|(o, _)| o.commitment.clone())
+        .collect::<Vec<_>>();
+    let tx_id = TxId::new_random();
+    let (fee, tx) = oms
+        .output_manager_handle
+        .create_batch_transaction(tx_id, recipient_outputs, 5 * uT, UtxoSelectionCriteria::default())
+        .await
+        .unwrap();
+
+    // A single kernel pays every recipient, with change back to the wallet
+    assert_eq!(tx.body.kernels().len(), 1);
+    assert_eq!(tx.body.inputs().len(), 1);
+    assert_eq!(tx.body.outputs().len(), 3);
+    assert_eq!(fee, tx.body.get_total_fee().unwrap());
+    for commitment in &recipient_commitments {
+        assert!(tx.body.outputs().iter().any(|o| &o.commitment == commitment));
+    }
+
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, MicroMinotari::zero());
+    assert_eq!(balance.pending_outgoing_balance, available);
+    assert_eq!(balance.pending_incoming_balance, available - 50_000 * uT - fee);
+}
+
+#[tokio::test]
+async fn test_create_batch_transaction_rejections() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let mut oms = setup_output_manager_service(backend, true).await;
+
+    let available = 10_000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        available,
+        &OutputFeatures::default(),
+        &oms.key_manager_handle,
+    )
+    .await;
+    oms.output_manager_handle.add_output(uo, None).await.unwrap();
+
+    let err = oms
+        .output_manager_handle
+        .create_batch_transaction(TxId::new_random(), vec![], 5 * uT, UtxoSelectionCriteria::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::BuildError(_)), "{:?}", err);
+
+    let recipient_outputs = vec![
+        create_batch_recipient_output(6_000 * uT, &oms.key_manager_handle).await,
+        create_batch_recipient_output(6_000 * uT, &oms.key_manager_handle).await,
+    ];
+    let err = oms
+        .output_manager_handle
+        .create_batch_transaction(
+            TxId::new_random(),
+            recipient_outputs,
+            5 * uT,
+            UtxoSelectionCriteria::default(),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, OutputManagerError::NotEnoughFunds), "{:?}", err);
+
+    // Nothing was encumbered by the rejected batches
+    let balance = oms.output_manager_handle.get_balance().await.unwrap();
+    assert_eq!(balance.available_balance, available);
+    assert_eq!(balance.pending_outgoing_balance, MicroMinotari::zero());
+}
@@ -20,23 +20,34 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::collections::BTreeMap;
+
 use minotari_wallet::output_manager_service::{
     error::OutputManagerStorageError,
-    service::Balance,
+    script_lock::{htlc_script, time_locked_script},
+    service::{Balance, BalanceByOrigin, BalanceCutoff},
     storage::{
         database::{OutputManagerBackend, OutputManagerDatabase},
         models::DbWalletOutput,
         sqlite_db::OutputManagerSqliteDatabase,
         OutputSource,
+        OutputStatus,
     },
+    UtxoSelectionCriteria,
 };
 use rand::{rngs::OsRng, RngCore};
-use tari_common_types::{transaction::TxId, types::FixedHash};
+use tari_common_types::{
+    transaction::TxId,
+    types::{Commitment, FixedHash},
+};
 use tari_core::transactions::{
+    key_manager::{TransactionKeyManagerBranch, TransactionKeyManagerInterface},
     tari_amount::MicroMinotari,
-    test_helpers::create_test_core_key_manager_with_memory_db,
+    test_helpers::{create_test_core_key_manager_with_memory_db, create_wallet_output_with_data, TestParams},
     transaction_components::OutputFeatures,
 };
+use tari_key_manager::key_manager_service::KeyManagerInterface;
+use tari_script::TariScript;
 
 use crate::support::{data::get_temp_sqlite_database_connection, utils::make_input};
 
@@ -333,6 +344,20 @@ pub async fn test_output_manager_sqlite_db() {
     test_db_backend(OutputManagerSqliteDatabase::new(connection)).await;
 }
 
+#[cfg(feature = "postgres")]
+#[tokio::test]
+pub async fn test_output_manager_postgres_db() {
+    use minotari_wallet::{
+        output_manager_service::storage::postgres_db::OutputManagerPostgresDatabase,
+        storage::postgres_utilities::run_migration_and_create_postgres_connection,
+        test_utils::make_postgres_database_url,
+    };
+
+    let connection = run_migration_and_create_postgres_connection(&make_postgres_database_url(), 4).unwrap();
+
+    test_db_backend(OutputManagerPostgresDatabase::new(connection)).await;
+}
+
 #[tokio::test]
 pub async fn test_output_manager_sqlite_db_encrypted() {
     let (connection, _tempdir) = get_temp_sqlite_database_connection();
@@ -473,3 +498,401 @@ pub async fn test_mark_as_unmined() {
     assert!(o.mined_height.is_none());
     assert!(o.mined_in_block.is_none());
 }
+
+#[tokio::test]
+pub async fn test_balance_at_cutoff() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut outputs = Vec::new();
+    for value in [1000, 2000] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        outputs.push(kmo);
+    }
+    db.set_received_output_mined_height_and_status(outputs[0].hash, 2, FixedHash::zero(), true, 1000)
+        .unwrap();
+    db.set_received_output_mined_height_and_status(outputs[1].hash, 5, FixedHash::zero(), true, 5000)
+        .unwrap();
+    db.mark_output_as_spent(outputs[0].hash, 7, FixedHash::zero(), true)
+        .unwrap();
+
+    let balance_at = |cutoff| db.get_balance_at(cutoff).unwrap();
+    assert_eq!(balance_at(BalanceCutoff::Height(1)).balance, MicroMinotari::from(0));
+    assert_eq!(balance_at(BalanceCutoff::Height(3)).balance, MicroMinotari::from(1000));
+    assert_eq!(balance_at(BalanceCutoff::Height(5)).balance, MicroMinotari::from(3000));
+    let balance = balance_at(BalanceCutoff::Height(7));
+    assert_eq!(balance.balance, MicroMinotari::from(2000));
+    assert_eq!(balance.num_outputs, 1);
+
+    let balance = balance_at(BalanceCutoff::Timestamp(4000));
+    assert_eq!(balance.height, Some(2));
+    assert_eq!(balance.balance, MicroMinotari::from(1000));
+    let balance = balance_at(BalanceCutoff::Timestamp(10));
+    assert_eq!(balance.height, None);
+    assert_eq!(balance.balance, MicroMinotari::from(0));
+}
+
+#[tokio::test]
+pub async fn test_balance_by_origin() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut outputs = Vec::new();
+    for (value, source) in [
+        (1000, OutputSource::Coinbase),
+        (2000, OutputSource::Standard),
+        (3000, OutputSource::StealthOneSided),
+        (4000, OutputSource::Change),
+    ] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, source, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        outputs.push(kmo);
+    }
+    for (output, timestamp) in outputs.iter().zip([1000, 2000, 3000, 4000]) {
+        db.set_received_output_mined_height_and_status(output.hash, 1, FixedHash::zero(), true, timestamp)
+            .unwrap();
+    }
+    db.mark_output_as_spent(outputs[2].hash, 2, FixedHash::zero(), true)
+        .unwrap();
+
+    let balance = db.get_balance_by_origin(None).unwrap();
+    assert_eq!(balance, BalanceByOrigin {
+        coinbase: MicroMinotari::from(1000),
+        interactive: MicroMinotari::from(2000),
+        one_sided: MicroMinotari::from(0),
+        change: MicroMinotari::from(4000),
+        other: MicroMinotari::from(0),
+    });
+
+    // Spent outputs still count as income received in the period
+    let balance = db.get_balance_by_origin(Some(2500)).unwrap();
+    assert_eq!(balance.one_sided, MicroMinotari::from(3000));
+    assert_eq!(balance.change, MicroMinotari::from(4000));
+    assert_eq!(balance.total(), MicroMinotari::from(7000));
+}
+
+#[tokio::test]
+pub async fn test_frozen_outputs_are_not_selected() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut outputs = Vec::new();
+    for value in [1000, 2000] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        outputs.push(kmo);
+    }
+    let selectable = || {
+        db.fetch_unspent_outputs_for_spending(&UtxoSelectionCriteria::default(), MicroMinotari::from(500), None)
+            .unwrap()
+    };
+
+    db.set_outputs_frozen(&[outputs[0].commitment.clone()], true).unwrap();
+    let selected = selectable();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].commitment, outputs[1].commitment);
+    let frozen = db.fetch_by_commitment(outputs[0].commitment.clone()).unwrap();
+    assert!(frozen.frozen);
+
+    db.set_outputs_frozen(&[outputs[0].commitment.clone()], false).unwrap();
+    assert_eq!(selectable().len(), 2);
+}
+
+#[tokio::test]
+pub async fn test_revalidate_outputs_in_height_range() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut outputs = Vec::new();
+    for (value, mined_height) in [(1000, 5), (2000, 15)] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        db.set_received_output_mined_height_and_status(kmo.hash, mined_height, FixedHash::zero(), true, 0)
+            .unwrap();
+        outputs.push(kmo);
+    }
+
+    db.set_outputs_in_height_range_to_be_revalidated(10, 20).unwrap();
+
+    let untouched = db.fetch_by_commitment(outputs[0].commitment.clone()).unwrap();
+    assert_eq!(untouched.status, OutputStatus::Unspent);
+    assert_eq!(untouched.mined_height, Some(5));
+    let reset = db.fetch_by_commitment(outputs[1].commitment.clone()).unwrap();
+    assert_eq!(reset.status, OutputStatus::Invalid);
+    assert_eq!(reset.mined_height, None);
+}
+
+#[tokio::test]
+pub async fn test_script_locked_outputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let test_params = TestParams::new(&key_manager).await;
+    let script_public_key = key_manager
+        .get_public_key_at_key_id(&test_params.script_key_id)
+        .await
+        .unwrap();
+    let scripts = [
+        TariScript::default(),
+        time_locked_script(50, script_public_key.clone()),
+        htlc_script(
+            FixedHash::from([1u8; 32]),
+            script_public_key.clone(),
+            script_public_key,
+            40,
+        ),
+    ];
+    let mut outputs = Vec::new();
+    for script in scripts {
+        let uo = create_wallet_output_with_data(
+            script,
+            OutputFeatures::default(),
+            &TestParams::new(&key_manager).await,
+            MicroMinotari::from(1000),
+            &key_manager,
+        )
+        .await
+        .unwrap();
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        outputs.push(kmo);
+    }
+
+    let locked = db.get_script_locked_outputs().unwrap();
+    assert_eq!(locked.len(), 2);
+    assert_eq!(locked[0].commitment, outputs[2].commitment);
+    assert_eq!(locked[0].claimable_at_height, 40);
+    assert_eq!(locked[0].hash_lock, Some(FixedHash::from([1u8; 32])));
+    assert_eq!(locked[1].commitment, outputs[1].commitment);
+    assert_eq!(locked[1].claimable_at_height, 50);
+
+    // The time-locked output only becomes selectable once the lock height is reached, and the hash-locked output is
+    // never selected implicitly
+    let selectable = |tip| {
+        db.fetch_unspent_outputs_for_spending(&UtxoSelectionCriteria::default(), MicroMinotari::from(500), Some(tip))
+            .unwrap()
+            .len()
+    };
+    assert_eq!(selectable(10), 1);
+    assert_eq!(selectable(50), 2);
+}
+
+#[tokio::test]
+pub async fn test_output_labels() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let uo = make_input(
+        &mut OsRng,
+        MicroMinotari::from(1000),
+        &OutputFeatures::default(),
+        &key_manager,
+    )
+    .await;
+    let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+        .await
+        .unwrap();
+    db.add_unspent_output(kmo.clone()).unwrap();
+
+    let metadata = BTreeMap::from([("exchange".to_string(), "withdrawal #42".to_string())]);
+    db.set_output_label(&kmo.commitment, Some("exchange withdrawal".to_string()), &metadata)
+        .unwrap();
+    let unspent = db.fetch_all_unspent_outputs().unwrap();
+    assert_eq!(unspent[0].label.as_deref(), Some("exchange withdrawal"));
+    assert_eq!(unspent[0].metadata, metadata);
+
+    db.set_output_label(&kmo.commitment, None, &BTreeMap::new()).unwrap();
+    let output = db.fetch_by_commitment(kmo.commitment.clone()).unwrap();
+    assert_eq!(output.label, None);
+    assert!(output.metadata.is_empty());
+
+    assert!(db
+        .set_output_label(&Commitment::default(), None, &BTreeMap::new())
+        .is_err());
+}
+
+#[tokio::test]
+pub async fn test_archive_spent_outputs() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut outputs = Vec::new();
+    for (value, spent_height) in [(1000, 5), (2000, 20)] {
+        let uo = make_input(
+            &mut OsRng,
+            MicroMinotari::from(value),
+            &OutputFeatures::default(),
+            &key_manager,
+        )
+        .await;
+        let kmo = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+            .await
+            .unwrap();
+        db.add_unspent_output(kmo.clone()).unwrap();
+        db.set_output_label(&kmo.commitment, Some(format!("output {}", value)), &BTreeMap::new())
+            .unwrap();
+        db.set_received_output_mined_height_and_status(kmo.hash, 1, FixedHash::zero(), true, 0)
+            .unwrap();
+        db.mark_output_as_spent(kmo.hash, spent_height, FixedHash::zero(), true)
+            .unwrap();
+        outputs.push(kmo);
+    }
+
+    assert_eq!(db.archive_spent_outputs(10).unwrap(), 1);
+    let spent = db.fetch_spent_outputs().unwrap();
+    assert_eq!(spent.len(), 1);
+    assert_eq!(spent[0].hash, outputs[1].hash);
+
+    let archived = db.fetch_archived_outputs().unwrap();
+    assert_eq!(archived.len(), 1);
+    assert_eq!(archived[0].hash, outputs[0].hash);
+    assert_eq!(archived[0].value, MicroMinotari::from(1000));
+    assert_eq!(archived[0].spent_height, Some(5));
+    assert_eq!(archived[0].label.as_deref(), Some("output 1000"));
+
+    // Archiving is idempotent
+    assert_eq!(db.archive_spent_outputs(10).unwrap(), 0);
+}
+
+#[tokio::test]
+pub async fn test_wallet_accounts() {
+    let (connection, _tempdir) = get_temp_sqlite_database_connection();
+    let backend = OutputManagerSqliteDatabase::new(connection);
+    let db = OutputManagerDatabase::new(backend);
+
+    let account = db.create_account("savings".to_string()).unwrap();
+    assert_eq!(account.id, 1);
+    assert!(db.create_account("savings".to_string()).is_err());
+    assert_eq!(db.fetch_accounts().unwrap(), vec![account.clone()]);
+
+    assert_eq!(db.get_receiving_account().unwrap(), 0);
+    db.set_receiving_account(account.id).unwrap();
+    assert_eq!(db.get_receiving_account().unwrap(), account.id);
+    db.set_receiving_account(0).unwrap();
+    assert_eq!(db.get_receiving_account().unwrap(), 0);
+    assert!(db.set_receiving_account(5).is_err());
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    for branch in [
+        TransactionKeyManagerBranch::CommitmentMask,
+        TransactionKeyManagerBranch::ScriptKey,
+    ] {
+        key_manager
+            .add_new_branch(branch.get_account_branch_key(account.id))
+            .await
+            .unwrap();
+    }
+    let mut test_params = TestParams::new(&key_manager).await;
+    let (spend_key_id, spend_key_pk, script_key_id, script_key_pk) = key_manager
+        .get_next_spend_and_script_key_ids_for_account(account.id)
+        .await
+        .unwrap();
+    test_params.spend_key_id = spend_key_id;
+    test_params.spend_key_pk = spend_key_pk;
+    test_params.script_key_id = script_key_id;
+    test_params.script_key_pk = script_key_pk;
+    let uo = create_wallet_output_with_data(
+        TariScript::default(),
+        OutputFeatures::default(),
+        &test_params,
+        MicroMinotari::from(1000),
+        &key_manager,
+    )
+    .await
+    .unwrap();
+    let account_output = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+        .await
+        .unwrap();
+    assert_eq!(account_output.account(), account.id);
+    db.add_unspent_output(account_output.clone()).unwrap();
+
+    let uo = make_input(
+        &mut OsRng,
+        MicroMinotari::from(2000),
+        &OutputFeatures::default(),
+        &key_manager,
+    )
+    .await;
+    let default_output = DbWalletOutput::from_wallet_output(uo, &key_manager, None, OutputSource::Unknown, None, None)
+        .await
+        .unwrap();
+    assert_eq!(default_output.account(), 0);
+    db.add_unspent_output(default_output.clone()).unwrap();
+
+    assert_eq!(
+        db.get_account_balance(account.id, None).unwrap().available_balance,
+        MicroMinotari::from(1000)
+    );
+    assert_eq!(
+        db.get_account_balance(0, None).unwrap().available_balance,
+        MicroMinotari::from(2000)
+    );
+
+    let selected = db
+        .fetch_unspent_outputs_for_spending(
+            &UtxoSelectionCriteria::default().with_account(account.id),
+            MicroMinotari::from(500),
+            None,
+        )
+        .unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].commitment, account_output.commitment);
+    let selected = db
+        .fetch_unspent_outputs_for_spending(&UtxoSelectionCriteria::default(), MicroMinotari::from(500), None)
+        .unwrap();
+    assert_eq!(selected.len(), 1);
+    assert_eq!(selected[0].commitment, default_output.commitment);
+}
@@ -53,6 +53,8 @@ use tari_core::{
             QueryDeletedRequest,
             QueryDeletedResponse,
             Signatures as SignaturesProto,
+            SpendingInputsRequest,
+            SpendingInputsResponse,
             SyncUtxosByBlockRequest,
             SyncUtxosByBlockResponse,
             TipInfoResponse,
@@ -103,6 +105,7 @@ pub struct BaseNodeWalletRpcMockState {
     tip_info_response: Arc<Mutex<TipInfoResponse>>,
     utxo_query_response: Arc<Mutex<UtxoQueryResponses>>,
     query_deleted_response: Arc<Mutex<QueryDeletedResponse>>,
+    spending_inputs_response: Arc<Mutex<SpendingInputsResponse>>,
     fetch_utxos_calls: Arc<Mutex<Vec<Vec<Vec<u8>>>>>,
     response_delay: Arc<Mutex<Option<Duration>>>,
     rpc_status_error: Arc<Mutex<Option<RpcStatus>>>,
@@ -167,6 +170,11 @@ impl BaseNodeWalletRpcMockState {
                 best_block_height: 1,
                 data: Vec::new(),
             })),
+            spending_inputs_response: Arc::new(Mutex::new(SpendingInputsResponse {
+                inputs: Vec::new(),
+                best_block_hash: vec![],
+                best_block_height: 1,
+            })),
             fetch_utxos_calls: Arc::new(Mutex::new(Vec::new())),
             response_delay: Arc::new(Mutex::new(None)),
             rpc_status_error: Arc::new(Mutex::new(None)),
@@ -211,6 +219,11 @@ impl BaseNodeWalletRpcMockState {
         *lock = response;
     }
 
+    pub fn set_spending_inputs_response(&self, response: SpendingInputsResponse) {
+        let mut lock = acquire_lock!(self.spending_inputs_response);
+        *lock = response;
+    }
+
     pub fn set_response_delay(&self, delay: Option<Duration>) {
         let mut lock = acquire_lock!(self.response_delay);
         *lock = delay;
@@ -850,6 +863,16 @@ impl BaseNodeWalletService for BaseNodeWalletRpcMockService {
             acquire_lock!(self.state.get_mempool_fee_per_gram_stats).clone(),
         ))
     }
+
+    async fn get_spending_inputs(
+        &self,
+        request: Request<SpendingInputsRequest>,
+    ) -> Result<Response<SpendingInputsResponse>, RpcStatus> {
+        let output_hashes = request.into_message().output_hashes;
+        let mut response = acquire_lock!(self.state.spending_inputs_response).clone();
+        response.inputs.retain(|input| output_hashes.contains(&input.output_hash));
+        Ok(Response::new(response))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -21,11 +21,11 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     mem::size_of,
     path::Path,
-    sync::Arc,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
@@ -35,11 +35,17 @@ use chrono::{Duration as ChronoDuration, Utc};
 use digest::consts::U32;
 use futures::{
     channel::{mpsc, mpsc::Sender},
+    stream,
     FutureExt,
     SinkExt,
+    StreamExt,
 };
 use minotari_wallet::{
-    base_node_service::{config::BaseNodeServiceConfig, handle::BaseNodeServiceHandle, BaseNodeServiceInitializer},
+    base_node_service::{
+        config::BaseNodeServiceConfig,
+        handle::{BaseNodeEvent, BaseNodeServiceHandle},
+        BaseNodeServiceInitializer,
+    },
     connectivity_service::{
         create_wallet_connectivity_mock,
         WalletConnectivityHandle,
@@ -49,6 +55,7 @@ use minotari_wallet::{
     },
     output_manager_service::{
         config::OutputManagerServiceConfig,
+        error::OutputManagerError,
         handle::{OutputManagerEvent, OutputManagerHandle},
         service::{Balance, OutputManagerService},
         storage::{
@@ -66,18 +73,27 @@ use minotari_wallet::{
     },
     test_utils::{create_consensus_constants, make_wallet_database_connection, random_string},
     transaction_service::{
-        config::TransactionServiceConfig,
+        config::{TransactionRoutingMechanism, TransactionServiceConfig},
         error::TransactionServiceError,
-        handle::{TransactionEvent, TransactionSendStatus, TransactionServiceHandle},
+        handle::{BatchPayment, TransactionEvent, TransactionSendStatus, TransactionServiceHandle},
+        offline_signing::{SignedTransaction, UnsignedTransaction},
         service::TransactionService,
         storage::{
             database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-            models::{CompletedTransaction, InboundTransaction, OutboundTransaction, WalletTransaction},
+            models::{
+                BurnStatus,
+                CompletedTransaction,
+                InboundTransaction,
+                OutboundMessageType,
+                OutboundTransaction,
+                QueuedOutboundMessage,
+                WalletTransaction,
+            },
             sqlite_db::TransactionServiceSqliteDatabase,
         },
         TransactionServiceInitializer,
     },
-    util::wallet_identity::WalletIdentity,
+    util::wallet_identity::{WalletIdentity, WatchOnlyKeys},
 };
 use prost::Message;
 use rand::{rngs::OsRng, RngCore};
@@ -86,7 +102,7 @@ use tari_common_types::{
     chain_metadata::ChainMetadata,
     tari_address::TariAddress,
     transaction::{ImportStatus, TransactionDirection, TransactionStatus, TxId},
-    types::{FixedHash, PrivateKey, PublicKey, Signature},
+    types::{Commitment, FixedHash, PrivateKey, PublicKey, Signature},
 };
 use tari_comms::{
     message::EnvelopeBody,
@@ -103,6 +119,11 @@ use tari_comms_dht::outbound::mock::{
     OutboundServiceMockState,
     ResponseType,
 };
+use tari_contacts::contacts_service::{
+    error::ContactsServiceError,
+    handle::{ContactsLivenessEvent, ContactsServiceHandle, ContactsServiceRequest, ContactsServiceResponse},
+    types::Contact,
+};
 use tari_core::{
     base_node::{
         proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
@@ -301,6 +322,7 @@ pub struct TransactionServiceNoCommsInterface {
     wallet_connectivity_service_mock: WalletConnectivityMock,
     _rpc_server_connection: PeerConnection,
     output_manager_service_event_publisher: broadcast::Sender<Arc<OutputManagerEvent>>,
+    base_node_service_event_publisher: broadcast::Sender<Arc<BaseNodeEvent>>,
     ts_db: TransactionServiceSqliteDatabase,
 }
 
@@ -311,6 +333,36 @@ async fn setup_transaction_service_no_comms(
     factories: CryptoFactories,
     db_connection: WalletDbConnection,
     config: Option<TransactionServiceConfig>,
+) -> TransactionServiceNoCommsInterface {
+    setup_transaction_service_no_comms_with_contacts(factories, db_connection, config, None).await
+}
+
+#[allow(clippy::type_complexity)]
+async fn setup_transaction_service_no_comms_with_contacts(
+    factories: CryptoFactories,
+    db_connection: WalletDbConnection,
+    config: Option<TransactionServiceConfig>,
+    contacts_service: Option<ContactsServiceHandle>,
+) -> TransactionServiceNoCommsInterface {
+    setup_transaction_service_no_comms_with_identity(factories, db_connection, config, contacts_service, None).await
+}
+
+/// Sets up a watch-only wallet of the address of `watch_only_keys`
+async fn setup_watch_only_transaction_service_no_comms(
+    factories: CryptoFactories,
+    db_connection: WalletDbConnection,
+    watch_only_keys: WatchOnlyKeys,
+) -> TransactionServiceNoCommsInterface {
+    setup_transaction_service_no_comms_with_identity(factories, db_connection, None, None, Some(watch_only_keys)).await
+}
+
+#[allow(clippy::type_complexity)]
+async fn setup_transaction_service_no_comms_with_identity(
+    factories: CryptoFactories,
+    db_connection: WalletDbConnection,
+    config: Option<TransactionServiceConfig>,
+    contacts_service: Option<ContactsServiceHandle>,
+    watch_only_keys: Option<WatchOnlyKeys>,
 ) -> TransactionServiceNoCommsInterface {
     let (oms_request_sender, oms_request_receiver) = reply_channel::unbounded();
 
@@ -360,7 +412,7 @@ async fn setup_transaction_service_no_comms(
     let (sender, receiver_bns) = reply_channel::unbounded();
     let (base_node_service_event_publisher, _) = broadcast::channel(100);
 
-    let base_node_service_handle = BaseNodeServiceHandle::new(sender, base_node_service_event_publisher);
+    let base_node_service_handle = BaseNodeServiceHandle::new(sender, base_node_service_event_publisher.clone());
     let mut mock_base_node_service = MockBaseNodeService::new(receiver_bns, shutdown.to_signal());
     mock_base_node_service.set_default_base_node_state();
     task::spawn(mock_base_node_service.run());
@@ -375,7 +427,11 @@ async fn setup_transaction_service_no_comms(
     let ts_db = TransactionDatabase::new(ts_service_db.clone());
     let key_manager = create_test_core_key_manager_with_memory_db();
     let oms_db = OutputManagerDatabase::new(OutputManagerSqliteDatabase::new(db_connection));
-    let wallet_identity = WalletIdentity::new(node_identity.clone(), Network::LocalNet);
+    let mut wallet_identity = WalletIdentity::new(node_identity.clone(), Network::LocalNet);
+    if let Some(keys) = watch_only_keys {
+        key_manager.import_key(keys.view_key.clone()).await.unwrap();
+        wallet_identity = wallet_identity.with_watch_only_keys(&keys);
+    }
     let output_manager_service = OutputManagerService::new(
         OutputManagerServiceConfig::default(),
         oms_request_receiver,
@@ -386,7 +442,7 @@ async fn setup_transaction_service_no_comms(
         shutdown.to_signal(),
         base_node_service_handle.clone(),
         wallet_connectivity_service_mock.clone(),
-        wallet_identity,
+        wallet_identity.clone(),
         key_manager.clone(),
     )
     .await
@@ -408,7 +464,6 @@ async fn setup_transaction_service_no_comms(
         max_tx_query_batch_size: 2,
         ..Default::default()
     });
-    let wallet_identity = WalletIdentity::new(node_identity.clone(), Network::LocalNet);
     let ts_service = TransactionService::new(
         test_config,
         ts_db.clone(),
@@ -419,6 +474,8 @@ async fn setup_transaction_service_no_comms(
         tx_finalized_receiver,
         base_node_response_receiver,
         tx_cancelled_receiver,
+        stream::empty(),
+        stream::empty(),
         output_manager_service_handle.clone(),
         key_manager.clone(),
         outbound_message_requester,
@@ -429,6 +486,7 @@ async fn setup_transaction_service_no_comms(
         factories,
         shutdown.to_signal(),
         base_node_service_handle,
+        contacts_service,
     );
     task::spawn(async move { output_manager_service.start().await.unwrap() });
     task::spawn(async move { ts_service.start().await.unwrap() });
@@ -449,6 +507,7 @@ async fn setup_transaction_service_no_comms(
         wallet_connectivity_service_mock,
         _rpc_server_connection: rpc_server_connection,
         output_manager_service_event_publisher,
+        base_node_service_event_publisher,
         ts_db: ts_service_db,
     }
 }
@@ -1137,46 +1196,24 @@ async fn single_transaction_burn_tari() {
 }
 
 #[tokio::test]
-async fn send_one_sided_transaction_to_other() {
+async fn burn_funds_stores_a_proof_that_verifies_against_the_kernel() {
     let network = Network::LocalNet;
     let consensus_manager = ConsensusManager::builder(network).build().unwrap();
     let factories = CryptoFactories::default();
-    // Alice's parameters
     let alice_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
         PeerFeatures::COMMUNICATION_NODE,
     ));
 
-    // Bob's parameters
-    let bob_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    let base_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    log::info!(
-        "manage_single_transaction: Alice: '{}', Bob: '{}', Base: '{}'",
-        alice_node_identity.node_id().short_str(),
-        bob_node_identity.node_id().short_str(),
-        base_node_identity.node_id().short_str()
-    );
-
     let temp_dir = tempdir().unwrap();
     let database_path = temp_dir.path().to_str().unwrap().to_string();
-
     let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
 
     let shutdown = Shutdown::new();
     let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) =
         setup_transaction_service(
-            alice_node_identity,
+            alice_node_identity.clone(),
             vec![],
             consensus_manager,
             factories.clone(),
@@ -1187,70 +1224,125 @@ async fn send_one_sided_transaction_to_other() {
         )
         .await;
 
-    let mut alice_event_stream = alice_ts.get_event_stream();
-
-    let initial_wallet_value = 25000.into();
     let uo1 = make_input(
         &mut OsRng,
-        initial_wallet_value,
+        25000.into(),
         &OutputFeatures::default(),
         &key_manager_handle,
     )
     .await;
-    let mut alice_oms_clone = alice_oms.clone();
-    alice_oms_clone.add_output(uo1, None).await.unwrap();
+    alice_oms.add_output(uo1, None).await.unwrap();
 
-    let message = "SEE IF YOU CAN CATCH THIS ONE..... SIDED TX!".to_string();
-    let value = 10000.into();
-    let mut alice_ts_clone = alice_ts.clone();
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
-    let tx_id = alice_ts_clone
-        .send_one_sided_transaction(
-            bob_address,
-            value,
+    let burn_value = 10000.into();
+    let (_, claim_public_key) = PublicKey::random_keypair(&mut OsRng);
+    let (tx_id, burn_proof) = alice_ts
+        .burn_funds(
+            burn_value,
+            claim_public_key.clone(),
             UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
             20.into(),
-            message.clone(),
+            "Burn for L2".to_string(),
         )
         .await
-        .expect("Alice sending one-sided tx to Bob");
+        .expect("Alice sending burn tx");
 
-    let completed_tx = alice_ts
-        .get_completed_transaction(tx_id)
+    // The stored proof is the one returned to the caller
+    let record = alice_ts
+        .get_burn_proof(tx_id)
         .await
-        .expect("Could not find completed one-sided tx");
-
-    let fees = completed_tx.fee;
-
+        .unwrap()
+        .expect("Burn record stored");
+    assert_eq!(record.amount, burn_value);
+    assert_eq!(record.status, BurnStatus::Pending);
+    assert_eq!(record.claim_public_key, Some(claim_public_key.clone()));
+    assert_eq!(record.proof.commitment, burn_proof.commitment);
     assert_eq!(
-        alice_oms.get_balance().await.unwrap().pending_incoming_balance,
-        initial_wallet_value - value - fees
+        record.proof.reciprocal_claim_public_key,
+        burn_proof.reciprocal_claim_public_key
     );
+    assert_eq!(record.proof.range_proof.to_vec(), burn_proof.range_proof.to_vec());
+
+    // The proof commits to the burnt output and the burn commitment in the signed kernel
+    let completed_tx = alice_ts.get_completed_transaction(tx_id).await.unwrap();
+    let kernel = completed_tx
+        .transaction
+        .body
+        .kernels()
+        .iter()
+        .find(|k| k.burn_commitment.is_some())
+        .expect("Burn kernel");
+    assert_eq!(kernel.get_burn_commitment().unwrap(), &record.proof.commitment);
+    kernel.verify_signature().unwrap();
+    let burned_output = completed_tx
+        .transaction
+        .body
+        .outputs()
+        .iter()
+        .find(|o| o.is_burned())
+        .expect("Burnt output");
+    assert_eq!(burned_output.commitment, record.proof.commitment);
+
+    let ownership_proof = record
+        .proof
+        .ownership_proof
+        .clone()
+        .expect("Claimable burn has an ownership proof");
+    let verify_ownership = |commitment: &Commitment, claim_key: &PublicKey| {
+        let challenge_bytes = ConfidentialOutputHasher::new("commitment_signature")
+            .chain(&ownership_proof.public_nonce())
+            .chain(commitment)
+            .chain(claim_key)
+            .finalize();
+        let challenge = PrivateKey::from_uniform_bytes(&challenge_bytes).unwrap();
+        ownership_proof.verify(commitment, &challenge, factories.commitment.as_ref())
+    };
+    let verify_range_proof = |commitment: &Commitment, range_proof: Vec<u8>| {
+        let statement = RistrettoAggregatedPublicStatement {
+            statements: vec![Statement {
+                commitment: commitment.clone(),
+                minimum_value_promise: MicroMinotari::zero().as_u64(),
+            }],
+        };
+        factories
+            .range_proof
+            .verify_batch(vec![&range_proof], vec![&statement])
+            .is_ok()
+    };
+    assert!(verify_ownership(&record.proof.commitment, &claim_public_key));
+    assert!(verify_range_proof(
+        &record.proof.commitment,
+        record.proof.range_proof.to_vec()
+    ));
 
-    let delay = sleep(Duration::from_secs(30));
-    tokio::pin!(delay);
-    let mut found = false;
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                if let TransactionEvent::TransactionCompletedImmediately(id) = &*event.unwrap() {
-                    if id == &tx_id {
-                        found = true;
-                        break;
-                    }
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
+    // A proof presented for another commitment or claim key, or with a tampered range proof, is rejected
+    let other_commitment = factories
+        .commitment
+        .commit_value(&PrivateKey::random(&mut OsRng), burn_value.as_u64());
+    let (_, other_claim_public_key) = PublicKey::random_keypair(&mut OsRng);
+    assert!(!verify_ownership(&other_commitment, &claim_public_key));
+    assert!(!verify_ownership(&record.proof.commitment, &other_claim_public_key));
+    assert!(!verify_range_proof(
+        &other_commitment,
+        record.proof.range_proof.to_vec()
+    ));
+    let mut tampered_range_proof = record.proof.range_proof.to_vec();
+    let last = tampered_range_proof.len() - 1;
+    tampered_range_proof[last] ^= 0x01;
+    assert!(!verify_range_proof(&record.proof.commitment, tampered_range_proof));
+
+    // The burn is not claimable until its transaction is mined and confirmed
+    match alice_ts.mark_burn_claimed(tx_id).await {
+        Err(TransactionServiceError::BurnNotClaimable { status, .. }) => assert_eq!(status, BurnStatus::Pending),
+        r => panic!("Expected the burn to not be claimable, got {:?}", r),
     }
-    assert!(found, "'TransactionCompletedImmediately(_)' event not found");
+    assert!(matches!(
+        alice_ts.mark_burn_claimed(TxId::new_random()).await,
+        Err(TransactionServiceError::BurnNotFound(_))
+    ));
 }
 
 #[tokio::test]
-async fn recover_one_sided_transaction() {
+async fn send_one_sided_transaction_to_other() {
     let network = Network::LocalNet;
     let consensus_manager = ConsensusManager::builder(network).build().unwrap();
     let factories = CryptoFactories::default();
@@ -1282,67 +1374,41 @@ async fn recover_one_sided_transaction() {
     );
 
     let temp_dir = tempdir().unwrap();
-    let temp_dir2 = tempdir().unwrap();
     let database_path = temp_dir.path().to_str().unwrap().to_string();
-    let database_path2 = temp_dir2.path().to_str().unwrap().to_string();
 
-    let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
-    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path2.clone()));
+    let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
 
     let shutdown = Shutdown::new();
-    let (mut alice_ts, alice_oms, _alice_comms, _alice_connectivity, alice_key_manager_handle) =
+    let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) =
         setup_transaction_service(
             alice_node_identity,
             vec![],
-            consensus_manager.clone(),
+            consensus_manager,
             factories.clone(),
-            alice_connection,
+            db_connection,
             database_path,
             Duration::from_secs(0),
             shutdown.to_signal(),
         )
         .await;
 
-    let (_bob_ts, mut bob_oms, _bob_comms, _bob_connectivity, bob_key_manager_handle) = setup_transaction_service(
-        bob_node_identity.clone(),
-        vec![],
-        consensus_manager,
-        factories.clone(),
-        bob_connection,
-        database_path2,
-        Duration::from_secs(0),
-        shutdown.to_signal(),
-    )
-    .await;
-    let script = one_sided_payment_script(bob_node_identity.public_key());
-    let known_script = KnownOneSidedPaymentScript {
-        script_hash: script.as_hash::<Blake2b<U32>>().unwrap().to_vec(),
-        script_key_id: bob_key_manager_handle
-            .import_key(bob_node_identity.secret_key().clone())
-            .await
-            .unwrap(),
-        script,
-        input: ExecutionStack::default(),
-        script_lock_height: 0,
-    };
-    let mut cloned_bob_oms = bob_oms.clone();
-    cloned_bob_oms.add_known_script(known_script).await.unwrap();
+    let mut alice_event_stream = alice_ts.get_event_stream();
 
     let initial_wallet_value = 25000.into();
     let uo1 = make_input(
         &mut OsRng,
         initial_wallet_value,
         &OutputFeatures::default(),
-        &alice_key_manager_handle,
+        &key_manager_handle,
     )
     .await;
-    let mut alice_oms_clone = alice_oms;
+    let mut alice_oms_clone = alice_oms.clone();
     alice_oms_clone.add_output(uo1, None).await.unwrap();
 
-    let message = "".to_string();
+    let message = "SEE IF YOU CAN CATCH THIS ONE..... SIDED TX!".to_string();
     let value = 10000.into();
     let mut alice_ts_clone = alice_ts.clone();
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), network);
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
     let tx_id = alice_ts_clone
         .send_one_sided_transaction(
             bob_address,
@@ -1359,54 +1425,54 @@ async fn recover_one_sided_transaction() {
         .get_completed_transaction(tx_id)
         .await
         .expect("Could not find completed one-sided tx");
-    let outputs = completed_tx.transaction.body.outputs().clone();
 
-    let recovered_outputs_1 = bob_oms
-        .scan_outputs_for_one_sided_payments(outputs.clone())
-        .await
-        .unwrap();
-    // Bob should be able to claim 1 output.
-    assert_eq!(1, recovered_outputs_1.len());
-    assert_eq!(value, recovered_outputs_1[0].output.value);
+    let fees = completed_tx.fee;
 
-    // Should ignore already existing outputs
-    let recovered_outputs_2 = bob_oms.scan_outputs_for_one_sided_payments(outputs).await.unwrap();
-    assert!(recovered_outputs_2.is_empty());
+    assert_eq!(
+        alice_oms.get_balance().await.unwrap().pending_incoming_balance,
+        initial_wallet_value - value - fees
+    );
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut found = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionCompletedImmediately(id) = &*event.unwrap() {
+                    if id == &tx_id {
+                        found = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(found, "'TransactionCompletedImmediately(_)' event not found");
 }
 
 #[tokio::test]
-async fn test_htlc_send_and_claim() {
+async fn bump_fee_of_pending_one_sided_transaction() {
     let network = Network::LocalNet;
     let consensus_manager = ConsensusManager::builder(network).build().unwrap();
     let factories = CryptoFactories::default();
-    // Alice's parameters
     let alice_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
         PeerFeatures::COMMUNICATION_NODE,
     ));
-
-    let base_node_identity = Arc::new(NodeIdentity::random(
+    let bob_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
         PeerFeatures::COMMUNICATION_NODE,
     ));
 
-    log::info!(
-        "manage_single_transaction: Alice: '{}', Base: '{}'",
-        alice_node_identity.node_id().short_str(),
-        base_node_identity.node_id().short_str()
-    );
-
     let temp_dir = tempdir().unwrap();
-    let temp_dir_bob = tempdir().unwrap();
     let database_path = temp_dir.path().to_str().unwrap().to_string();
-    let path_string = temp_dir_bob.path().to_str().unwrap().to_string();
-    let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
-    let bob_db_path = format!("{}/{}", path_string, bob_db_name);
-
     let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
-    let bob_connection = run_migration_and_create_sqlite_connection(&bob_db_path, 16).unwrap();
 
     let shutdown = Shutdown::new();
     let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) =
@@ -1414,21 +1480,13 @@ async fn test_htlc_send_and_claim() {
             alice_node_identity,
             vec![],
             consensus_manager,
-            factories.clone(),
+            factories,
             db_connection,
             database_path,
             Duration::from_secs(0),
             shutdown.to_signal(),
         )
         .await;
-
-    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
-
-    log::info!(
-        "manage_single_transaction: Bob: '{}'",
-        bob_ts_interface.base_node_identity.node_id().short_str(),
-    );
-
     let mut alice_event_stream = alice_ts.get_event_stream();
 
     let initial_wallet_value = 25000.into();
@@ -1439,44 +1497,52 @@ async fn test_htlc_send_and_claim() {
         &key_manager_handle,
     )
     .await;
-    let mut alice_oms_clone = alice_oms.clone();
-    alice_oms_clone.add_output(uo1, None).await.unwrap();
+    alice_oms.add_output(uo1, None).await.unwrap();
 
-    let message = "".to_string();
     let value = 10000.into();
-    let mut alice_ts_clone = alice_ts.clone();
-    let bob_pubkey = bob_ts_interface.base_node_identity.public_key().clone();
-    let bob_address = TariAddress::new(bob_pubkey.clone(), Network::LocalNet);
-    let (tx_id, pre_image, output) = alice_ts_clone
-        .send_sha_atomic_swap_transaction(
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id = alice_ts
+        .send_one_sided_transaction(
             bob_address,
             value,
             UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
             20.into(),
-            message.clone(),
+            "Bump me".to_string(),
         )
         .await
-        .expect("Alice sending HTLC transaction");
+        .unwrap();
+    let original_fee = alice_ts.get_completed_transaction(tx_id).await.unwrap().fee;
 
-    let completed_tx = alice_ts
-        .get_completed_transaction(tx_id)
-        .await
-        .expect("Could not find completed HTLC tx");
+    // The fee can only go up
+    let err = alice_ts.bump_fee(tx_id, 20.into()).await.unwrap_err();
+    assert!(matches!(err, TransactionServiceError::FeeBumpError(_)));
+    let err = alice_ts.bump_fee(TxId::new_random(), 40.into()).await.unwrap_err();
+    assert!(!matches!(err, TransactionServiceError::FeeBumpError(_)));
 
-    let fees = completed_tx.fee;
+    let fee = alice_ts.bump_fee(tx_id, 40.into()).await.unwrap();
+    assert!(fee > original_fee);
 
+    // The replacement takes the place of the original under the same tx_id
+    let completed_tx = alice_ts.get_completed_transaction(tx_id).await.unwrap();
+    assert_eq!(completed_tx.fee, fee);
+    assert_eq!(completed_tx.amount, value);
+    assert_eq!(completed_tx.status, TransactionStatus::Completed);
+    assert_eq!(completed_tx.transaction.body.get_total_fee().unwrap(), fee);
     assert_eq!(
         alice_oms.get_balance().await.unwrap().pending_incoming_balance,
-        initial_wallet_value - value - fees
+        initial_wallet_value - value - fee
     );
 
     let delay = sleep(Duration::from_secs(30));
     tokio::pin!(delay);
+    let mut bumped_fee = None;
     loop {
         tokio::select! {
             event = alice_event_stream.recv() => {
-                if let TransactionEvent::TransactionCompletedImmediately(id) = &*event.unwrap() {
+                if let TransactionEvent::TransactionFeeBumped { tx_id: id, fee } = &*event.unwrap() {
                     if id == &tx_id {
+                        bumped_fee = Some(*fee);
                         break;
                     }
                 }
@@ -1486,32 +1552,159 @@ async fn test_htlc_send_and_claim() {
             },
         }
     }
-    let hash = output.hash();
-    bob_ts_interface.base_node_rpc_mock_state.set_utxos(vec![output]);
-    let (tx_id_htlc, _htlc_fee, htlc_amount, tx) = bob_ts_interface
+    assert_eq!(bumped_fee, Some(fee), "'TransactionFeeBumped' event not found");
+}
+
+#[tokio::test]
+async fn bumped_transaction_mined_in_its_original_version() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let initial_wallet_value = 25000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        initial_wallet_value,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
         .output_manager_service_handle
-        .create_claim_sha_atomic_swap_transaction(hash, pre_image, 20.into())
+        .add_output(uo, None)
         .await
         .unwrap();
 
-    bob_ts_interface
+    let value = 10000 * uT;
+    let bob_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let tx_id = alice_ts_interface
         .transaction_service_handle
-        .submit_transaction(tx_id_htlc, tx, htlc_amount, "".to_string())
+        .send_one_sided_transaction(
+            bob_address,
+            value,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            20 * uT,
+            "Bump me".to_string(),
+        )
+        .await
+        .unwrap();
+    let original = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    let fee = alice_ts_interface
+        .transaction_service_handle
+        .bump_fee(tx_id, 40 * uT)
+        .await
+        .unwrap();
+    let replacement = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(replacement.fee, fee);
+    assert_ne!(
+        replacement.transaction.first_kernel_excess_sig(),
+        original.transaction.first_kernel_excess_sig()
+    );
+
+    // The base node never saw the replacement, the original was mined instead
+    let timestamp = EpochTime::now().as_u64();
+    let mut block_headers = HashMap::new();
+    for i in 0..=1 {
+        let mut block_header = BlockHeader::new(1);
+        block_header.height = i;
+        block_headers.insert(i, block_header.clone());
+    }
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_blocks(block_headers.clone());
+    let batch_query_response = TxQueryBatchResponsesProto {
+        responses: vec![
+            TxQueryBatchResponseProto {
+                signature: Some(SignatureProto::from(
+                    replacement.transaction.first_kernel_excess_sig().unwrap().clone(),
+                )),
+                location: TxLocationProto::from(TxLocation::NotStored) as i32,
+                block_hash: vec![],
+                confirmations: 0,
+                block_height: 0,
+                mined_timestamp: 0,
+            },
+            TxQueryBatchResponseProto {
+                signature: Some(SignatureProto::from(
+                    original.transaction.first_kernel_excess_sig().unwrap().clone(),
+                )),
+                location: TxLocationProto::from(TxLocation::Mined) as i32,
+                block_hash: block_headers.get(&1).unwrap().hash().to_vec(),
+                confirmations: 0,
+                block_height: 1,
+                mined_timestamp: timestamp,
+            },
+        ],
+        is_synced: true,
+        tip_hash: block_headers.get(&1).unwrap().hash().to_vec(),
+        height_of_longest_chain: 1,
+        tip_mined_timestamp: timestamp,
+    };
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_transaction_query_batch_responses(batch_query_response);
+
+    alice_ts_interface
+        .transaction_service_handle
+        .validate_transactions()
+        .await
+        .expect("Validation should start");
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut mined = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionMinedUnconfirmed { tx_id: id, .. } = &*event.unwrap() {
+                    if id == &tx_id {
+                        mined = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(mined, "'TransactionMinedUnconfirmed' event not found");
+
+    // The original is the mined version again, rather than being cancelled as a double spend of its own inputs
+    let completed_tx = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
         .await
         .unwrap();
+    assert_eq!(completed_tx.status, TransactionStatus::MinedUnconfirmed);
+    assert_eq!(completed_tx.fee, original.fee);
+    assert_eq!(completed_tx.transaction, original.transaction);
     assert_eq!(
-        bob_ts_interface
+        alice_ts_interface
             .output_manager_service_handle
             .get_balance()
             .await
             .unwrap()
             .pending_incoming_balance,
-        htlc_amount
+        initial_wallet_value - value - original.fee
     );
 }
 
 #[tokio::test]
-async fn send_one_sided_transaction_to_self() {
+async fn recover_one_sided_transaction() {
     let network = Network::LocalNet;
     let consensus_manager = ConsensusManager::builder(network).build().unwrap();
     let factories = CryptoFactories::default();
@@ -1522,6 +1715,13 @@ async fn send_one_sided_transaction_to_self() {
         PeerFeatures::COMMUNICATION_NODE,
     ));
 
+    // Bob's parameters
+    let bob_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
     let base_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
@@ -1529,47 +1729,77 @@ async fn send_one_sided_transaction_to_self() {
     ));
 
     log::info!(
-        "manage_single_transaction: Alice: '{}', Base: '{}'",
+        "manage_single_transaction: Alice: '{}', Bob: '{}', Base: '{}'",
         alice_node_identity.node_id().short_str(),
+        bob_node_identity.node_id().short_str(),
         base_node_identity.node_id().short_str()
     );
 
     let temp_dir = tempdir().unwrap();
+    let temp_dir2 = tempdir().unwrap();
     let database_path = temp_dir.path().to_str().unwrap().to_string();
+    let database_path2 = temp_dir2.path().to_str().unwrap().to_string();
 
     let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path2.clone()));
 
     let shutdown = Shutdown::new();
-    let (alice_ts, alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) = setup_transaction_service(
-        alice_node_identity.clone(),
+    let (mut alice_ts, alice_oms, _alice_comms, _alice_connectivity, alice_key_manager_handle) =
+        setup_transaction_service(
+            alice_node_identity,
+            vec![],
+            consensus_manager.clone(),
+            factories.clone(),
+            alice_connection,
+            database_path,
+            Duration::from_secs(0),
+            shutdown.to_signal(),
+        )
+        .await;
+
+    let (_bob_ts, mut bob_oms, _bob_comms, _bob_connectivity, bob_key_manager_handle) = setup_transaction_service(
+        bob_node_identity.clone(),
         vec![],
         consensus_manager,
         factories.clone(),
-        alice_connection,
-        database_path,
+        bob_connection,
+        database_path2,
         Duration::from_secs(0),
         shutdown.to_signal(),
     )
     .await;
+    let script = one_sided_payment_script(bob_node_identity.public_key());
+    let known_script = KnownOneSidedPaymentScript {
+        script_hash: script.as_hash::<Blake2b<U32>>().unwrap().to_vec(),
+        script_key_id: bob_key_manager_handle
+            .import_key(bob_node_identity.secret_key().clone())
+            .await
+            .unwrap(),
+        script,
+        input: ExecutionStack::default(),
+        script_lock_height: 0,
+    };
+    let mut cloned_bob_oms = bob_oms.clone();
+    cloned_bob_oms.add_known_script(known_script).await.unwrap();
 
-    let initial_wallet_value = 2500.into();
+    let initial_wallet_value = 25000.into();
     let uo1 = make_input(
         &mut OsRng,
         initial_wallet_value,
         &OutputFeatures::default(),
-        &key_manager_handle,
+        &alice_key_manager_handle,
     )
     .await;
     let mut alice_oms_clone = alice_oms;
     alice_oms_clone.add_output(uo1, None).await.unwrap();
 
-    let message = "SEE IF YOU CAN CATCH THIS ONE..... SIDED TX!".to_string();
-    let value = 1000.into();
-    let mut alice_ts_clone = alice_ts;
-    let alice_address = TariAddress::new(alice_node_identity.public_key().clone(), network);
-    match alice_ts_clone
+    let message = "".to_string();
+    let value = 10000.into();
+    let mut alice_ts_clone = alice_ts.clone();
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), network);
+    let tx_id = alice_ts_clone
         .send_one_sided_transaction(
-            alice_address,
+            bob_address,
             value,
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
@@ -1577,267 +1807,231 @@ async fn send_one_sided_transaction_to_self() {
             message.clone(),
         )
         .await
-    {
-        Err(TransactionServiceError::OneSidedTransactionError(e)) => {
-            assert_eq!(e.as_str(), "One-sided spend-to-self transactions not supported");
-        },
-        _ => {
-            panic!("Expected: OneSidedTransactionError(\"One-sided spend-to-self transactions not supported\")");
-        },
-    };
+        .expect("Alice sending one-sided tx to Bob");
+
+    let completed_tx = alice_ts
+        .get_completed_transaction(tx_id)
+        .await
+        .expect("Could not find completed one-sided tx");
+    let outputs = completed_tx.transaction.body.outputs().clone();
+
+    let recovered_outputs_1 = bob_oms
+        .scan_outputs_for_one_sided_payments(outputs.clone())
+        .await
+        .unwrap();
+    // Bob should be able to claim 1 output.
+    assert_eq!(1, recovered_outputs_1.len());
+    assert_eq!(value, recovered_outputs_1[0].output.value);
+
+    // Should ignore already existing outputs
+    let recovered_outputs_2 = bob_oms.scan_outputs_for_one_sided_payments(outputs).await.unwrap();
+    assert!(recovered_outputs_2.is_empty());
 }
 
-#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
-async fn manage_multiple_transactions() {
+#[tokio::test]
+async fn recover_one_sided_transactions_to_subaddresses() {
     let network = Network::LocalNet;
     let consensus_manager = ConsensusManager::builder(network).build().unwrap();
     let factories = CryptoFactories::default();
-    // Alice's parameters
     let alice_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
         PeerFeatures::COMMUNICATION_NODE,
     ));
-
-    // Bob's parameters
     let bob_node_identity = Arc::new(NodeIdentity::random(
         &mut OsRng,
         get_next_memory_address(),
         PeerFeatures::COMMUNICATION_NODE,
     ));
 
-    // Carols's parameters
-    let carol_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
-
-    log::info!(
-        "wallet::manage_multiple_transactions: Alice: '{}', Bob: '{}', carol: '{}'",
-        alice_node_identity.node_id().short_str(),
-        bob_node_identity.node_id().short_str(),
-        carol_node_identity.node_id().short_str()
-    );
-
     let temp_dir = tempdir().unwrap();
-
+    let temp_dir2 = tempdir().unwrap();
     let database_path = temp_dir.path().to_str().unwrap().to_string();
-
+    let database_path2 = temp_dir2.path().to_str().unwrap().to_string();
     let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
-    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
-    let (carol_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
-
-    let mut shutdown = Shutdown::new();
+    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path2.clone()));
 
-    let (mut alice_ts, mut alice_oms, alice_comms, _alice_connectivity, _key_manager_handle) =
+    let shutdown = Shutdown::new();
+    let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity, alice_key_manager_handle) =
         setup_transaction_service(
-            alice_node_identity.clone(),
-            vec![bob_node_identity.clone(), carol_node_identity.clone()],
+            alice_node_identity,
+            vec![],
             consensus_manager.clone(),
             factories.clone(),
             alice_connection,
-            database_path.clone(),
-            Duration::from_secs(1),
+            database_path,
+            Duration::from_secs(0),
             shutdown.to_signal(),
         )
         .await;
-    let mut alice_event_stream = alice_ts.get_event_stream();
-
-    sleep(Duration::from_secs(5)).await;
-
-    // Spin up Bob and Carol
-    let (mut bob_ts, mut bob_oms, bob_comms, _bob_connectivity, _key_manager_handle) = setup_transaction_service(
-        bob_node_identity.clone(),
-        vec![alice_node_identity.clone()],
-        consensus_manager.clone(),
-        factories.clone(),
+    let (_bob_ts, mut bob_oms, _bob_comms, _bob_connectivity, _bob_key_manager_handle) = setup_transaction_service(
+        bob_node_identity,
+        vec![],
+        consensus_manager,
+        factories,
         bob_connection,
-        database_path.clone(),
-        Duration::from_secs(1),
+        database_path2,
+        Duration::from_secs(0),
         shutdown.to_signal(),
     )
     .await;
-    let mut bob_event_stream = bob_ts.get_event_stream();
-    sleep(Duration::from_secs(5)).await;
 
-    let (mut carol_ts, mut carol_oms, carol_comms, _carol_connectivity, key_manager_handle) =
-        setup_transaction_service(
-            carol_node_identity.clone(),
-            vec![alice_node_identity.clone()],
-            consensus_manager,
-            factories.clone(),
-            carol_connection,
-            database_path,
-            Duration::from_secs(1),
-            shutdown.to_signal(),
-        )
-        .await;
-    let mut carol_event_stream = carol_ts.get_event_stream();
+    let first = bob_oms.create_subaddress(None).await.unwrap();
+    let second = bob_oms.create_subaddress(Some("invoice 42".to_string())).await.unwrap();
+    assert_ne!(first.public_key, second.public_key);
+    assert_eq!(bob_oms.get_subaddresses().await.unwrap(), vec![first, second.clone()]);
 
-    // Establish some connections beforehand, to reduce the amount of work done concurrently in tests
-    // Connect Bob and Alice
-    sleep(Duration::from_secs(3)).await;
+    let uo1 = make_input(
+        &mut OsRng,
+        50000.into(),
+        &OutputFeatures::default(),
+        &alice_key_manager_handle,
+    )
+    .await;
+    alice_oms.add_output(uo1, None).await.unwrap();
 
-    let _peer_connection = bob_comms
-        .connectivity()
-        .dial_peer(alice_node_identity.node_id().clone())
-        .await
-        .unwrap();
-    sleep(Duration::from_secs(3)).await;
-
-    // Connect alice to carol
-    let _peer_connection = alice_comms
-        .connectivity()
-        .dial_peer(carol_node_identity.node_id().clone())
-        .await
-        .unwrap();
-
-    let uo2 = make_input(
-        &mut OsRng,
-        MicroMinotari(35000),
-        &OutputFeatures::default(),
-        &key_manager_handle,
-    )
-    .await;
-    bob_oms.add_output(uo2, None).await.unwrap();
-    let uo3 = make_input(
-        &mut OsRng,
-        MicroMinotari(45000),
-        &OutputFeatures::default(),
-        &key_manager_handle,
-    )
-    .await;
-    carol_oms.add_output(uo3, None).await.unwrap();
-
-    // Add some funds to Alices wallet
-    let uo1a = make_input(
-        &mut OsRng,
-        MicroMinotari(55000),
-        &OutputFeatures::default(),
-        &key_manager_handle,
-    )
-    .await;
-    alice_oms.add_output(uo1a, None).await.unwrap();
-    let uo1b = make_input(
-        &mut OsRng,
-        MicroMinotari(30000),
-        &OutputFeatures::default(),
-        &key_manager_handle,
-    )
-    .await;
-    alice_oms.add_output(uo1b, None).await.unwrap();
-    let uo1c = make_input(
-        &mut OsRng,
-        MicroMinotari(30000),
-        &OutputFeatures::default(),
-        &key_manager_handle,
-    )
-    .await;
-    alice_oms.add_output(uo1c, None).await.unwrap();
-
-    // A series of interleaved transactions. First with Bob and Carol offline and then two with them online
-    let value_a_to_b_1 = MicroMinotari::from(10000);
-    let value_a_to_b_2 = MicroMinotari::from(8000);
-    let value_b_to_a_1 = MicroMinotari::from(11000);
-    let value_a_to_c_1 = MicroMinotari::from(14000);
-    log::trace!("Sending A to B 1");
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
-    let tx_id_a_to_b_1 = alice_ts
-        .send_transaction(
-            bob_address.clone(),
-            value_a_to_b_1,
+    let subaddress = TariAddress::new(second.public_key.clone(), network);
+    let one_sided_tx_id = alice_ts
+        .send_one_sided_transaction(
+            subaddress.clone(),
+            10000.into(),
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
-            MicroMinotari::from(20),
-            "a to b 1".to_string(),
+            20.into(),
+            String::new(),
         )
         .await
         .unwrap();
-    log::trace!("A to B 1 TxID: {}", tx_id_a_to_b_1);
-    log::trace!("Sending A to C 1");
-    let carol_address = TariAddress::new(carol_node_identity.public_key().clone(), Network::LocalNet);
-    let tx_id_a_to_c_1 = alice_ts
-        .send_transaction(
-            carol_address,
-            value_a_to_c_1,
+    let stealth_tx_id = alice_ts
+        .send_one_sided_to_stealth_address_transaction(
+            subaddress,
+            12000.into(),
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
-            MicroMinotari::from(20),
-            "a to c 1".to_string(),
+            20.into(),
+            String::new(),
         )
         .await
         .unwrap();
-    let alice_completed_tx = alice_ts.get_completed_transactions().await.unwrap();
-    assert_eq!(alice_completed_tx.len(), 0);
-    log::trace!("A to C 1 TxID: {}", tx_id_a_to_c_1);
 
-    let alice_address = TariAddress::new(alice_node_identity.public_key().clone(), Network::LocalNet);
-    bob_ts
-        .send_transaction(
-            alice_address,
-            value_b_to_a_1,
-            UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            MicroMinotari::from(20),
-            "b to a 1".to_string(),
+    for (tx_id, value) in [(one_sided_tx_id, 10000.into()), (stealth_tx_id, 12000.into())] {
+        let completed_tx = alice_ts.get_completed_transaction(tx_id).await.unwrap();
+        let outputs = completed_tx.transaction.body.outputs().clone();
+        let recovered_outputs = bob_oms.scan_outputs_for_one_sided_payments(outputs).await.unwrap();
+        assert_eq!(recovered_outputs.len(), 1);
+        assert_eq!(recovered_outputs[0].output.value, value);
+        assert_eq!(recovered_outputs[0].subaddress, Some(second.clone()));
+    }
+    let received = bob_oms.get_unspent_outputs().await.unwrap();
+    assert_eq!(received.len(), 2);
+    assert!(received.iter().all(|o| o.subaddress_index == Some(second.index)));
+}
+
+#[tokio::test]
+async fn test_htlc_send_and_claim() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build().unwrap();
+    let factories = CryptoFactories::default();
+    // Alice's parameters
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let base_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    log::info!(
+        "manage_single_transaction: Alice: '{}', Base: '{}'",
+        alice_node_identity.node_id().short_str(),
+        base_node_identity.node_id().short_str()
+    );
+
+    let temp_dir = tempdir().unwrap();
+    let temp_dir_bob = tempdir().unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
+    let path_string = temp_dir_bob.path().to_str().unwrap().to_string();
+    let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let bob_db_path = format!("{}/{}", path_string, bob_db_name);
+
+    let (db_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+    let bob_connection = run_migration_and_create_sqlite_connection(&bob_db_path, 16).unwrap();
+
+    let shutdown = Shutdown::new();
+    let (mut alice_ts, mut alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) =
+        setup_transaction_service(
+            alice_node_identity,
+            vec![],
+            consensus_manager,
+            factories.clone(),
+            db_connection,
+            database_path,
+            Duration::from_secs(0),
+            shutdown.to_signal(),
         )
-        .await
-        .unwrap();
-    alice_ts
-        .send_transaction(
+        .await;
+
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
+
+    log::info!(
+        "manage_single_transaction: Bob: '{}'",
+        bob_ts_interface.base_node_identity.node_id().short_str(),
+    );
+
+    let mut alice_event_stream = alice_ts.get_event_stream();
+
+    let initial_wallet_value = 25000.into();
+    let uo1 = make_input(
+        &mut OsRng,
+        initial_wallet_value,
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    let mut alice_oms_clone = alice_oms.clone();
+    alice_oms_clone.add_output(uo1, None).await.unwrap();
+
+    let message = "".to_string();
+    let value = 10000.into();
+    let mut alice_ts_clone = alice_ts.clone();
+    let bob_pubkey = bob_ts_interface.base_node_identity.public_key().clone();
+    let bob_address = TariAddress::new(bob_pubkey.clone(), Network::LocalNet);
+    let (tx_id, pre_image, output) = alice_ts_clone
+        .send_sha_atomic_swap_transaction(
             bob_address,
-            value_a_to_b_2,
+            value,
             UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            MicroMinotari::from(20),
-            "a to b 2".to_string(),
+            20.into(),
+            message.clone(),
         )
         .await
-        .unwrap();
-
-    let delay = sleep(Duration::from_secs(90));
-    tokio::pin!(delay);
-    let mut tx_reply = 0;
-    let mut finalized = 0;
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                match &*event.unwrap() {
-                    TransactionEvent::ReceivedTransactionReply(_) => tx_reply+=1,
-                    TransactionEvent::ReceivedFinalizedTransaction(_) => finalized+=1,
-                    _ => (),
-                }
+        .expect("Alice sending HTLC transaction");
 
-                if tx_reply == 3 && finalized ==1 {
-                    break;
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
-    }
-    assert_eq!(tx_reply, 3, "Need 3 replies");
-    assert_eq!(finalized, 1);
+    let completed_tx = alice_ts
+        .get_completed_transaction(tx_id)
+        .await
+        .expect("Could not find completed HTLC tx");
 
-    log::trace!("Alice received all Tx messages");
+    let fees = completed_tx.fee;
 
-    let delay = sleep(Duration::from_secs(90));
+    assert_eq!(
+        alice_oms.get_balance().await.unwrap().pending_incoming_balance,
+        initial_wallet_value - value - fees
+    );
 
+    let delay = sleep(Duration::from_secs(30));
     tokio::pin!(delay);
-    let mut tx_reply = 0;
-    let mut finalized = 0;
     loop {
         tokio::select! {
-            event = bob_event_stream.recv() => {
-                match &*event.unwrap() {
-                    TransactionEvent::ReceivedTransactionReply(_) => tx_reply+=1,
-                    TransactionEvent::ReceivedFinalizedTransaction(_) => finalized+=1,
-                    _ => (),
-                }
-                if tx_reply == 1 && finalized == 2 {
-                    break;
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionCompletedImmediately(id) = &*event.unwrap() {
+                    if id == &tx_id {
+                        break;
+                    }
                 }
             },
             () = &mut delay => {
@@ -1845,244 +2039,379 @@ async fn manage_multiple_transactions() {
             },
         }
     }
-    assert_eq!(tx_reply, 1);
-    assert_eq!(finalized, 2);
+    let hash = output.hash();
+    bob_ts_interface.base_node_rpc_mock_state.set_utxos(vec![output]);
+    let (tx_id_htlc, _htlc_fee, htlc_amount, tx) = bob_ts_interface
+        .output_manager_service_handle
+        .create_claim_sha_atomic_swap_transaction(hash, pre_image, 20.into())
+        .await
+        .unwrap();
 
-    let delay = sleep(Duration::from_secs(90));
-    tokio::pin!(delay);
+    bob_ts_interface
+        .transaction_service_handle
+        .submit_transaction(tx_id_htlc, tx, htlc_amount, "".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        bob_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap()
+            .pending_incoming_balance,
+        htlc_amount
+    );
+}
 
-    tokio::pin!(delay);
-    let mut finalized = 0;
-    loop {
-        tokio::select! {
-            event = carol_event_stream.recv() => {
-                if let TransactionEvent::ReceivedFinalizedTransaction(_) = &*event.unwrap() {
-                    finalized+=1;
-                    break;
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
-    }
-    assert_eq!(finalized, 1);
-
-    let alice_pending_outbound = alice_ts.get_pending_outbound_transactions().await.unwrap();
-    let alice_completed_tx = alice_ts.get_completed_transactions().await.unwrap();
-    assert_eq!(alice_pending_outbound.len(), 0);
-    assert_eq!(alice_completed_tx.len(), 4, "Not enough transactions for Alice");
-    let bob_pending_outbound = bob_ts.get_pending_outbound_transactions().await.unwrap();
-    let bob_completed_tx = bob_ts.get_completed_transactions().await.unwrap();
-    assert_eq!(bob_pending_outbound.len(), 0);
-    assert_eq!(bob_completed_tx.len(), 3, "Not enough transactions for Bob");
+#[tokio::test]
+async fn send_one_sided_transaction_to_self() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build().unwrap();
+    let factories = CryptoFactories::default();
+    // Alice's parameters
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-    let carol_pending_inbound = carol_ts.get_pending_inbound_transactions().await.unwrap();
-    let carol_completed_tx = carol_ts.get_completed_transactions().await.unwrap();
-    assert_eq!(carol_pending_inbound.len(), 0);
-    assert_eq!(carol_completed_tx.len(), 1);
+    let base_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-    shutdown.trigger();
-    alice_comms.wait_until_shutdown().await;
-    bob_comms.wait_until_shutdown().await;
-    carol_comms.wait_until_shutdown().await;
-}
+    log::info!(
+        "manage_single_transaction: Alice: '{}', Base: '{}'",
+        alice_node_identity.node_id().short_str(),
+        base_node_identity.node_id().short_str()
+    );
 
-#[tokio::test]
-async fn test_accepting_unknown_tx_id_and_malformed_reply() {
-    let factories = CryptoFactories::default();
-    let consensus_constants = create_consensus_constants(0);
     let temp_dir = tempdir().unwrap();
-    let path_string = temp_dir.path().to_str().unwrap().to_string();
-    let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
-    let alice_db_path = format!("{}/{}", path_string, alice_db_name);
-    let connection_alice = run_migration_and_create_sqlite_connection(&alice_db_path, 16).unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
 
-    let bob_node_identity =
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
 
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
+    let shutdown = Shutdown::new();
+    let (alice_ts, alice_oms, _alice_comms, _alice_connectivity, key_manager_handle) = setup_transaction_service(
+        alice_node_identity.clone(),
+        vec![],
+        consensus_manager,
+        factories.clone(),
+        alice_connection,
+        database_path,
+        Duration::from_secs(0),
+        shutdown.to_signal(),
+    )
+    .await;
 
-    let uo = make_input(
+    let initial_wallet_value = 2500.into();
+    let uo1 = make_input(
         &mut OsRng,
-        MicroMinotari(250000),
+        initial_wallet_value,
         &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
+        &key_manager_handle,
     )
     .await;
+    let mut alice_oms_clone = alice_oms;
+    alice_oms_clone.add_output(uo1, None).await.unwrap();
 
-    alice_ts_interface
-        .output_manager_service_handle
-        .add_output(uo, None)
-        .await
-        .unwrap();
-
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
-    alice_ts_interface
-        .transaction_service_handle
-        .send_transaction(
-            bob_address,
-            MicroMinotari::from(5000),
+    let message = "SEE IF YOU CAN CATCH THIS ONE..... SIDED TX!".to_string();
+    let value = 1000.into();
+    let mut alice_ts_clone = alice_ts;
+    let alice_address = TariAddress::new(alice_node_identity.public_key().clone(), network);
+    match alice_ts_clone
+        .send_one_sided_transaction(
+            alice_address,
+            value,
             UtxoSelectionCriteria::default(),
             OutputFeatures::default(),
-            MicroMinotari::from(20),
-            "".to_string(),
+            20.into(),
+            message.clone(),
         )
         .await
-        .unwrap();
-    alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(1, Duration::from_secs(60))
-        .await
-        .unwrap();
-    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-
-    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
-    let sender_message = envelope_body
-        .decode_part::<proto::TransactionSenderMessage>(1)
-        .unwrap()
-        .unwrap();
+    {
+        Err(TransactionServiceError::OneSidedTransactionError(e)) => {
+            assert_eq!(e.as_str(), "One-sided spend-to-self transactions not supported");
+        },
+        _ => {
+            panic!("Expected: OneSidedTransactionError(\"One-sided spend-to-self transactions not supported\")");
+        },
+    };
+}
 
-    let sender = sender_message.try_into().unwrap();
-    let output = create_wallet_output_from_sender_data(&sender, &alice_ts_interface.key_manager_handle).await;
-    let rtp = ReceiverTransactionProtocol::new(
-        sender,
-        output,
-        &alice_ts_interface.key_manager_handle,
-        &consensus_constants,
-    )
-    .await;
+#[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+async fn manage_multiple_transactions() {
+    let network = Network::LocalNet;
+    let consensus_manager = ConsensusManager::builder(network).build().unwrap();
+    let factories = CryptoFactories::default();
+    // Alice's parameters
+    let alice_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-    let mut tx_reply = rtp.get_signed_data().unwrap().clone();
-    let mut wrong_tx_id = tx_reply.clone();
-    wrong_tx_id.tx_id = 2u64.into();
-    let (_p, pub_key) = PublicKey::random_keypair(&mut OsRng);
-    tx_reply.public_spend_key = pub_key;
-    alice_ts_interface
-        .transaction_ack_message_channel
-        .send(create_dummy_message(
-            wrong_tx_id.try_into().unwrap(),
-            bob_node_identity.public_key(),
-        ))
-        .await
-        .unwrap();
+    // Bob's parameters
+    let bob_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-    alice_ts_interface
-        .transaction_ack_message_channel
-        .send(create_dummy_message(
-            tx_reply.try_into().unwrap(),
-            bob_node_identity.public_key(),
-        ))
-        .await
-        .unwrap();
-}
+    // Carols's parameters
+    let carol_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
 
-#[tokio::test]
-async fn finalize_tx_with_incorrect_pubkey() {
-    let factories = CryptoFactories::default();
-    let key_manager = create_test_core_key_manager_with_memory_db();
+    log::info!(
+        "wallet::manage_multiple_transactions: Alice: '{}', Bob: '{}', carol: '{}'",
+        alice_node_identity.node_id().short_str(),
+        bob_node_identity.node_id().short_str(),
+        carol_node_identity.node_id().short_str()
+    );
 
     let temp_dir = tempdir().unwrap();
-    let path_string = temp_dir.path().to_str().unwrap().to_string();
 
-    let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
-    let alice_db_path = format!("{}/{}", path_string, alice_db_name);
-    let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
-    let bob_db_path = format!("{}/{}", path_string, bob_db_name);
-    let connection_alice = run_migration_and_create_sqlite_connection(&alice_db_path, 16).unwrap();
-    let connection_bob = run_migration_and_create_sqlite_connection(&bob_db_path, 16).unwrap();
+    let database_path = temp_dir.path().to_str().unwrap().to_string();
 
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
-    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    let (alice_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+    let (bob_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
+    let (carol_connection, _tempdir) = make_wallet_database_connection(Some(database_path.clone()));
 
-    let bob_node_identity =
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
-    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_bob, None).await;
+    let mut shutdown = Shutdown::new();
 
-    let uo = make_input(
-        &mut OsRng,
-        MicroMinotari(250000),
-        &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
+    let (mut alice_ts, mut alice_oms, alice_comms, _alice_connectivity, _key_manager_handle) =
+        setup_transaction_service(
+            alice_node_identity.clone(),
+            vec![bob_node_identity.clone(), carol_node_identity.clone()],
+            consensus_manager.clone(),
+            factories.clone(),
+            alice_connection,
+            database_path.clone(),
+            Duration::from_secs(1),
+            shutdown.to_signal(),
+        )
+        .await;
+    let mut alice_event_stream = alice_ts.get_event_stream();
+
+    sleep(Duration::from_secs(5)).await;
+
+    // Spin up Bob and Carol
+    let (mut bob_ts, mut bob_oms, bob_comms, _bob_connectivity, _key_manager_handle) = setup_transaction_service(
+        bob_node_identity.clone(),
+        vec![alice_node_identity.clone()],
+        consensus_manager.clone(),
+        factories.clone(),
+        bob_connection,
+        database_path.clone(),
+        Duration::from_secs(1),
+        shutdown.to_signal(),
     )
     .await;
-    bob_ts_interface
-        .output_manager_service_handle
-        .add_output(uo, None)
-        .await
-        .unwrap();
-    let mut stp = bob_ts_interface
-        .output_manager_service_handle
-        .prepare_transaction_to_send(
-            TxId::new_random(),
-            MicroMinotari::from(5000),
-            UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            MicroMinotari::from(25),
-            TransactionMetadata::default(),
-            "".to_string(),
-            script!(Nop),
-            Covenant::default(),
-            MicroMinotari::zero(),
+    let mut bob_event_stream = bob_ts.get_event_stream();
+    sleep(Duration::from_secs(5)).await;
+
+    let (mut carol_ts, mut carol_oms, carol_comms, _carol_connectivity, key_manager_handle) =
+        setup_transaction_service(
+            carol_node_identity.clone(),
+            vec![alice_node_identity.clone()],
+            consensus_manager,
+            factories.clone(),
+            carol_connection,
+            database_path,
+            Duration::from_secs(1),
+            shutdown.to_signal(),
         )
+        .await;
+    let mut carol_event_stream = carol_ts.get_event_stream();
+
+    // Establish some connections beforehand, to reduce the amount of work done concurrently in tests
+    // Connect Bob and Alice
+    sleep(Duration::from_secs(3)).await;
+
+    let _peer_connection = bob_comms
+        .connectivity()
+        .dial_peer(alice_node_identity.node_id().clone())
         .await
         .unwrap();
-    let msg = stp.build_single_round_message(&key_manager).await.unwrap();
-    let tx_message = create_dummy_message(
-        TransactionSenderMessage::Single(Box::new(msg)).try_into().unwrap(),
-        bob_node_identity.public_key(),
-    );
+    sleep(Duration::from_secs(3)).await;
 
-    alice_ts_interface
-        .transaction_send_message_channel
-        .send(tx_message)
+    // Connect alice to carol
+    let _peer_connection = alice_comms
+        .connectivity()
+        .dial_peer(carol_node_identity.node_id().clone())
         .await
         .unwrap();
 
-    alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(1, Duration::from_secs(10))
+    let uo2 = make_input(
+        &mut OsRng,
+        MicroMinotari(35000),
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    bob_oms.add_output(uo2, None).await.unwrap();
+    let uo3 = make_input(
+        &mut OsRng,
+        MicroMinotari(45000),
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    carol_oms.add_output(uo3, None).await.unwrap();
+
+    // Add some funds to Alices wallet
+    let uo1a = make_input(
+        &mut OsRng,
+        MicroMinotari(55000),
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    alice_oms.add_output(uo1a, None).await.unwrap();
+    let uo1b = make_input(
+        &mut OsRng,
+        MicroMinotari(30000),
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    alice_oms.add_output(uo1b, None).await.unwrap();
+    let uo1c = make_input(
+        &mut OsRng,
+        MicroMinotari(30000),
+        &OutputFeatures::default(),
+        &key_manager_handle,
+    )
+    .await;
+    alice_oms.add_output(uo1c, None).await.unwrap();
+
+    // A series of interleaved transactions. First with Bob and Carol offline and then two with them online
+    let value_a_to_b_1 = MicroMinotari::from(10000);
+    let value_a_to_b_2 = MicroMinotari::from(8000);
+    let value_b_to_a_1 = MicroMinotari::from(11000);
+    let value_a_to_c_1 = MicroMinotari::from(14000);
+    log::trace!("Sending A to B 1");
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id_a_to_b_1 = alice_ts
+        .send_transaction(
+            bob_address.clone(),
+            value_a_to_b_1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(20),
+            "a to b 1".to_string(),
+        )
         .await
         .unwrap();
-    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-
-    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
-    let recipient_reply: RecipientSignedMessage = envelope_body
-        .decode_part::<proto::RecipientSignedMessage>(1)
-        .unwrap()
-        .unwrap()
-        .try_into()
+    log::trace!("A to B 1 TxID: {}", tx_id_a_to_b_1);
+    log::trace!("Sending A to C 1");
+    let carol_address = TariAddress::new(carol_node_identity.public_key().clone(), Network::LocalNet);
+    let tx_id_a_to_c_1 = alice_ts
+        .send_transaction(
+            carol_address,
+            value_a_to_c_1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(20),
+            "a to c 1".to_string(),
+        )
+        .await
         .unwrap();
+    let alice_completed_tx = alice_ts.get_completed_transactions().await.unwrap();
+    assert_eq!(alice_completed_tx.len(), 0);
+    log::trace!("A to C 1 TxID: {}", tx_id_a_to_c_1);
 
-    stp.add_single_recipient_info(recipient_reply.clone(), &key_manager)
+    let alice_address = TariAddress::new(alice_node_identity.public_key().clone(), Network::LocalNet);
+    bob_ts
+        .send_transaction(
+            alice_address,
+            value_b_to_a_1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(20),
+            "b to a 1".to_string(),
+        )
         .await
         .unwrap();
-    stp.finalize(&key_manager).await.unwrap();
-    let tx = stp.get_transaction().unwrap();
-
-    let finalized_transaction_message = proto::TransactionFinalizedMessage {
-        tx_id: recipient_reply.tx_id.as_u64(),
-        transaction: Some(tx.clone().try_into().unwrap()),
-    };
-
-    alice_ts_interface
-        .transaction_finalize_message_channel
-        .send(create_dummy_message(
-            finalized_transaction_message,
-            &PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
-        ))
+    alice_ts
+        .send_transaction(
+            bob_address,
+            value_a_to_b_2,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(20),
+            "a to b 2".to_string(),
+        )
         .await
         .unwrap();
 
-    let delay = sleep(Duration::from_secs(15));
+    let delay = sleep(Duration::from_secs(90));
+    tokio::pin!(delay);
+    let mut tx_reply = 0;
+    let mut finalized = 0;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                match &*event.unwrap() {
+                    TransactionEvent::ReceivedTransactionReply(_) => tx_reply+=1,
+                    TransactionEvent::ReceivedFinalizedTransaction(_) => finalized+=1,
+                    _ => (),
+                }
+
+                if tx_reply == 3 && finalized ==1 {
+                    break;
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(tx_reply, 3, "Need 3 replies");
+    assert_eq!(finalized, 1);
+
+    log::trace!("Alice received all Tx messages");
+
+    let delay = sleep(Duration::from_secs(90));
+
+    tokio::pin!(delay);
+    let mut tx_reply = 0;
+    let mut finalized = 0;
+    loop {
+        tokio::select! {
+            event = bob_event_stream.recv() => {
+                match &*event.unwrap() {
+                    TransactionEvent::ReceivedTransactionReply(_) => tx_reply+=1,
+                    TransactionEvent::ReceivedFinalizedTransaction(_) => finalized+=1,
+                    _ => (),
+                }
+                if tx_reply == 1 && finalized == 2 {
+                    break;
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(tx_reply, 1);
+    assert_eq!(finalized, 2);
+
+    let delay = sleep(Duration::from_secs(90));
     tokio::pin!(delay);
 
     tokio::pin!(delay);
+    let mut finalized = 0;
     loop {
         tokio::select! {
-            event = alice_event_stream.recv() => {
-                 if let TransactionEvent::ReceivedFinalizedTransaction(_) = (*event.unwrap()).clone() {
-                     panic!("Should not have received finalized event!");
+            event = carol_event_stream.recv() => {
+                if let TransactionEvent::ReceivedFinalizedTransaction(_) = &*event.unwrap() {
+                    finalized+=1;
+                    break;
                 }
             },
             () = &mut delay => {
@@ -2090,21 +2419,245 @@ async fn finalize_tx_with_incorrect_pubkey() {
             },
         }
     }
+    assert_eq!(finalized, 1);
 
-    assert!(alice_ts_interface
-        .transaction_service_handle
-        .get_completed_transaction(recipient_reply.tx_id)
-        .await
-        .is_err());
+    let alice_pending_outbound = alice_ts.get_pending_outbound_transactions().await.unwrap();
+    let alice_completed_tx = alice_ts.get_completed_transactions().await.unwrap();
+    assert_eq!(alice_pending_outbound.len(), 0);
+    assert_eq!(alice_completed_tx.len(), 4, "Not enough transactions for Alice");
+    let bob_pending_outbound = bob_ts.get_pending_outbound_transactions().await.unwrap();
+    let bob_completed_tx = bob_ts.get_completed_transactions().await.unwrap();
+    assert_eq!(bob_pending_outbound.len(), 0);
+    assert_eq!(bob_completed_tx.len(), 3, "Not enough transactions for Bob");
+
+    let carol_pending_inbound = carol_ts.get_pending_inbound_transactions().await.unwrap();
+    let carol_completed_tx = carol_ts.get_completed_transactions().await.unwrap();
+    assert_eq!(carol_pending_inbound.len(), 0);
+    assert_eq!(carol_completed_tx.len(), 1);
+
+    shutdown.trigger();
+    alice_comms.wait_until_shutdown().await;
+    bob_comms.wait_until_shutdown().await;
+    carol_comms.wait_until_shutdown().await;
 }
 
 #[tokio::test]
-async fn finalize_tx_with_missing_output() {
+async fn test_accepting_unknown_tx_id_and_malformed_reply() {
     let factories = CryptoFactories::default();
-    let key_manager = create_test_core_key_manager_with_memory_db();
+    let consensus_constants = create_consensus_constants(0);
     let temp_dir = tempdir().unwrap();
     let path_string = temp_dir.path().to_str().unwrap().to_string();
-
+    let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let alice_db_path = format!("{}/{}", path_string, alice_db_name);
+    let connection_alice = run_migration_and_create_sqlite_connection(&alice_db_path, 16).unwrap();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
+
+    let uo = make_input(
+        &mut OsRng,
+        MicroMinotari(250000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            MicroMinotari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(20),
+            "".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let sender_message = envelope_body
+        .decode_part::<proto::TransactionSenderMessage>(1)
+        .unwrap()
+        .unwrap();
+
+    let sender = sender_message.try_into().unwrap();
+    let output = create_wallet_output_from_sender_data(&sender, &alice_ts_interface.key_manager_handle).await;
+    let rtp = ReceiverTransactionProtocol::new(
+        sender,
+        output,
+        &alice_ts_interface.key_manager_handle,
+        &consensus_constants,
+    )
+    .await;
+
+    let mut tx_reply = rtp.get_signed_data().unwrap().clone();
+    let mut wrong_tx_id = tx_reply.clone();
+    wrong_tx_id.tx_id = 2u64.into();
+    let (_p, pub_key) = PublicKey::random_keypair(&mut OsRng);
+    tx_reply.public_spend_key = pub_key;
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            wrong_tx_id.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            tx_reply.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn finalize_tx_with_incorrect_pubkey() {
+    let factories = CryptoFactories::default();
+    let key_manager = create_test_core_key_manager_with_memory_db();
+
+    let temp_dir = tempdir().unwrap();
+    let path_string = temp_dir.path().to_str().unwrap().to_string();
+
+    let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let alice_db_path = format!("{}/{}", path_string, alice_db_name);
+    let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
+    let bob_db_path = format!("{}/{}", path_string, bob_db_name);
+    let connection_alice = run_migration_and_create_sqlite_connection(&alice_db_path, 16).unwrap();
+    let connection_bob = run_migration_and_create_sqlite_connection(&bob_db_path, 16).unwrap();
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_alice, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection_bob, None).await;
+
+    let uo = make_input(
+        &mut OsRng,
+        MicroMinotari(250000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    bob_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let mut stp = bob_ts_interface
+        .output_manager_service_handle
+        .prepare_transaction_to_send(
+            TxId::new_random(),
+            MicroMinotari::from(5000),
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            MicroMinotari::from(25),
+            TransactionMetadata::default(),
+            "".to_string(),
+            script!(Nop),
+            Covenant::default(),
+            MicroMinotari::zero(),
+        )
+        .await
+        .unwrap();
+    let msg = stp.build_single_round_message(&key_manager).await.unwrap();
+    let tx_message = create_dummy_message(
+        TransactionSenderMessage::Single(Box::new(msg)).try_into().unwrap(),
+        bob_node_identity.public_key(),
+    );
+
+    alice_ts_interface
+        .transaction_send_message_channel
+        .send(tx_message)
+        .await
+        .unwrap();
+
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(10))
+        .await
+        .unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let recipient_reply: RecipientSignedMessage = envelope_body
+        .decode_part::<proto::RecipientSignedMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    stp.add_single_recipient_info(recipient_reply.clone(), &key_manager)
+        .await
+        .unwrap();
+    stp.finalize(&key_manager).await.unwrap();
+    let tx = stp.get_transaction().unwrap();
+
+    let finalized_transaction_message = proto::TransactionFinalizedMessage {
+        tx_id: recipient_reply.tx_id.as_u64(),
+        transaction: Some(tx.clone().try_into().unwrap()),
+    };
+
+    alice_ts_interface
+        .transaction_finalize_message_channel
+        .send(create_dummy_message(
+            finalized_transaction_message,
+            &PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        ))
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(15));
+    tokio::pin!(delay);
+
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::ReceivedFinalizedTransaction(_) = (*event.unwrap()).clone() {
+                     panic!("Should not have received finalized event!");
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(recipient_reply.tx_id)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn finalize_tx_with_missing_output() {
+    let factories = CryptoFactories::default();
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let temp_dir = tempdir().unwrap();
+    let path_string = temp_dir.path().to_str().unwrap().to_string();
+
     let alice_db_name = format!("{}.sqlite3", random::string(8).as_str());
     let alice_db_path = format!("{}/{}", path_string, alice_db_name);
     let bob_db_name = format!("{}.sqlite3", random::string(8).as_str());
@@ -3525,53 +4078,147 @@ async fn test_tx_direct_send_behaviour() {
 }
 
 #[tokio::test]
-async fn test_restarting_transaction_protocols() {
-    let network = Network::LocalNet;
+async fn test_tx_routing_mechanism_override() {
     let factories = CryptoFactories::default();
-    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
-
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
-
-    let alice_backend = alice_ts_interface.ts_db;
-
-    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
-    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
-
-    let bob_backend = bob_ts_interface.ts_db;
-
-    let base_node_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
 
-    let alice_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
 
-    let bob_identity = Arc::new(NodeIdentity::random(
-        &mut OsRng,
-        get_next_memory_address(),
-        PeerFeatures::COMMUNICATION_NODE,
-    ));
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
 
-    // Bob is going to send a transaction to Alice
-    let input = make_input(
+    let uo = make_input(
         &mut OsRng,
-        MicroMinotari(2000),
+        1000000 * uT,
         &OutputFeatures::default(),
         &alice_ts_interface.key_manager_handle,
     )
     .await;
-    let constants = create_consensus_constants(0);
-    let fee_calc = Fee::new(*constants.transaction_weight_params());
-    let key_manager = create_test_core_key_manager_with_memory_db();
-    let mut builder = SenderTransactionProtocol::builder(constants.clone(), key_manager.clone());
-    let fee = fee_calc.calculate(MicroMinotari(4), 1, 1, 1, 0);
-    let change = TestParams::new(&key_manager).await;
-    builder
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    alice_ts_interface
+        .outbound_service_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Queued,
+        })
+        .await;
+
+    // A direct-only send fails fast when the recipient cannot be reached and releases the selected outputs
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let err = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction_with_routing(
+            bob_address.clone(),
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            100 * uT,
+            "Testing Message1".to_string(),
+            TransactionRoutingMechanism::DirectOnly,
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TransactionServiceError::DirectSendFailed(_)));
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, 1000000 * uT);
+
+    // A store-and-forward-only send skips the direct send altogether
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction_with_routing(
+            bob_address,
+            100000 * uT,
+            UtxoSelectionCriteria::default(),
+            100 * uT,
+            "Testing Message2".to_string(),
+            TransactionRoutingMechanism::StoreAndForwardOnly,
+        )
+        .await
+        .unwrap();
+
+    let mut transaction_send_status = None;
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionSendResult(id, status) = &*event.unwrap() {
+                    if *id == tx_id {
+                        transaction_send_status = Some(status.clone());
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    let transaction_send_status = transaction_send_status.expect("Should have received the send result");
+    assert!(!transaction_send_status.direct_send_result, "Should not send directly");
+    assert!(
+        transaction_send_status.store_and_forward_send_result,
+        "Should send via store and forward"
+    );
+}
+
+#[tokio::test]
+async fn test_restarting_transaction_protocols() {
+    let network = Network::LocalNet;
+    let factories = CryptoFactories::default();
+    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
+
+    let alice_backend = alice_ts_interface.ts_db;
+
+    let (bob_connection, _temp_dir2) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
+
+    let bob_backend = bob_ts_interface.ts_db;
+
+    let base_node_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let alice_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    let bob_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+
+    // Bob is going to send a transaction to Alice
+    let input = make_input(
+        &mut OsRng,
+        MicroMinotari(2000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    let constants = create_consensus_constants(0);
+    let fee_calc = Fee::new(*constants.transaction_weight_params());
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let mut builder = SenderTransactionProtocol::builder(constants.clone(), key_manager.clone());
+    let fee = fee_calc.calculate(MicroMinotari(4), 1, 1, 1, 0);
+    let change = TestParams::new(&key_manager).await;
+    builder
         .with_lock_height(0)
         .with_fee_per_gram(MicroMinotari(4))
         .with_input(input)
@@ -3748,6 +4395,245 @@ async fn test_restarting_transaction_protocols() {
     assert!(received_finalized, "Should have received finalized tx");
 }
 
+/// Builds a pending inbound transaction from `source_address` along with the finalized transaction the sender would
+/// send
+async fn build_pending_inbound_transaction(source_address: TariAddress) -> (InboundTransaction, Transaction) {
+    let constants = create_consensus_constants(0);
+    let fee_calc = Fee::new(*constants.transaction_weight_params());
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let input = make_input(
+        &mut OsRng,
+        MicroMinotari(2000),
+        &OutputFeatures::default(),
+        &key_manager,
+    )
+    .await;
+    let mut builder = SenderTransactionProtocol::builder(constants.clone(), key_manager.clone());
+    let fee = fee_calc.calculate(MicroMinotari(4), 1, 1, 1, 0);
+    let change = TestParams::new(&key_manager).await;
+    builder
+        .with_lock_height(0)
+        .with_fee_per_gram(MicroMinotari(4))
+        .with_input(input)
+        .await
+        .unwrap()
+        .with_recipient_data(
+            script!(Nop),
+            Default::default(),
+            Covenant::default(),
+            MicroMinotari::zero(),
+            MicroMinotari(2000) - fee - MicroMinotari(10),
+        )
+        .await
+        .unwrap()
+        .with_change_data(
+            script!(Nop),
+            inputs!(change.script_key_pk),
+            change.script_key_id.clone(),
+            change.spend_key_id.clone(),
+            Covenant::default(),
+        );
+    let mut stp = builder.build().await.unwrap();
+    let msg = stp.build_single_round_message(&key_manager).await.unwrap();
+    let sender_info = TransactionSenderMessage::Single(Box::new(msg.clone()));
+    let output = create_wallet_output_from_sender_data(&sender_info, &key_manager).await;
+    let receiver_protocol = ReceiverTransactionProtocol::new(sender_info, output, &key_manager, &constants).await;
+    stp.add_single_recipient_info(receiver_protocol.get_signed_data().unwrap().clone(), &key_manager)
+        .await
+        .unwrap();
+    stp.finalize(&key_manager).await.unwrap();
+    let tx = stp.get_transaction().unwrap().clone();
+
+    let inbound_tx = InboundTransaction {
+        tx_id: msg.tx_id,
+        source_address,
+        amount: msg.amount,
+        receiver_protocol,
+        status: TransactionStatus::Pending,
+        message: msg.message,
+        timestamp: Utc::now().naive_utc(),
+        cancelled: false,
+        direct_send_success: false,
+        send_count: 0,
+        last_send_timestamp: None,
+    };
+    (inbound_tx, tx)
+}
+
+#[tokio::test]
+async fn test_restarted_receive_protocols_are_queued_when_the_queue_is_full() {
+    let factories = CryptoFactories::default();
+    let (alice_connection, _temp_dir) = make_wallet_database_connection(None);
+    // Only one receive protocol may run and none may be queued
+    let config = TransactionServiceConfig {
+        max_concurrent_receive_protocols: 1,
+        receive_protocol_queue_size: 0,
+        ..Default::default()
+    };
+    let mut alice_ts_interface =
+        setup_transaction_service_no_comms(factories.clone(), alice_connection, Some(config)).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    let bob_identity = Arc::new(NodeIdentity::random(
+        &mut OsRng,
+        get_next_memory_address(),
+        PeerFeatures::COMMUNICATION_NODE,
+    ));
+    let bob_address = TariAddress::new(bob_identity.public_key().clone(), Network::LocalNet);
+
+    let mut finalized_txs = Vec::new();
+    for _ in 0..3 {
+        let (inbound_tx, tx) = build_pending_inbound_transaction(bob_address.clone()).await;
+        let tx_id = inbound_tx.tx_id;
+        alice_ts_interface
+            .ts_db
+            .write(WriteOperation::Insert(DbKeyValuePair::PendingInboundTransaction(
+                tx_id,
+                Box::new(inbound_tx),
+            )))
+            .unwrap();
+        finalized_txs.push((tx_id, tx));
+    }
+
+    alice_ts_interface
+        .transaction_service_handle
+        .restart_transaction_protocols()
+        .await
+        .unwrap();
+
+    let mut expected_tx_ids = HashSet::new();
+    for (tx_id, tx) in finalized_txs {
+        expected_tx_ids.insert(tx_id);
+        let finalized_transaction_message = proto::TransactionFinalizedMessage {
+            tx_id: tx_id.as_u64(),
+            transaction: Some(tx.try_into().unwrap()),
+        };
+        alice_ts_interface
+            .transaction_finalize_message_channel
+            .send(create_dummy_message(
+                finalized_transaction_message,
+                bob_identity.public_key(),
+            ))
+            .await
+            .unwrap();
+    }
+
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    let mut finalized_tx_ids = HashSet::new();
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                match (*event.unwrap()).clone() {
+                    TransactionEvent::ReceivedFinalizedTransaction(id) => {
+                        finalized_tx_ids.insert(id);
+                        if finalized_tx_ids.len() == expected_tx_ids.len() {
+                            break;
+                        }
+                    },
+                    TransactionEvent::ReceiveProtocolQueueOverflow(id) => {
+                        panic!("Restarted receive protocol for {} should not be dropped", id);
+                    },
+                    _ => {},
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert_eq!(finalized_tx_ids, expected_tx_ids);
+}
+
+/// Creates a contacts service handle that answers `GetContacts` with the current contents of `contacts`, along with
+/// the sender of its contact events
+fn create_contacts_service_mock(
+    contacts: Arc<Mutex<Vec<Contact>>>,
+) -> (ContactsServiceHandle, broadcast::Sender<Arc<ContactsLivenessEvent>>) {
+    let (request_sender, mut request_receiver) = reply_channel::unbounded();
+    let (liveness_events, _) = broadcast::channel(10);
+    let (message_events, _) = broadcast::channel(10);
+    task::spawn(async move {
+        while let Some(request_context) = request_receiver.next().await {
+            let (request, reply_tx) = request_context.split();
+            let response = match request {
+                ContactsServiceRequest::GetContacts => {
+                    Ok(ContactsServiceResponse::Contacts(contacts.lock().unwrap().clone()))
+                },
+                _ => Err(ContactsServiceError::UnexpectedApiResponse),
+            };
+            let _result = reply_tx.send(response);
+        }
+    });
+    (
+        ContactsServiceHandle::new(request_sender, liveness_events.clone(), message_events),
+        liveness_events,
+    )
+}
+
+/// Polls the counterparty alias of `tx_id` until it matches `expected`, the contact events are handled asynchronously
+async fn wait_for_counterparty_alias(ts_handle: &mut TransactionServiceHandle, tx_id: TxId, expected: Option<&str>) {
+    for _ in 0..50 {
+        if ts_handle.get_counterparty_alias(tx_id).await.unwrap().as_deref() == expected {
+            return;
+        }
+        sleep(Duration::from_millis(100)).await;
+    }
+    panic!("Counterparty alias did not become {:?}", expected);
+}
+
+#[tokio::test]
+async fn test_counterparty_aliases_follow_contact_renames() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let contacts = Arc::new(Mutex::new(Vec::new()));
+    let (contacts_service, contacts_events) = create_contacts_service_mock(contacts.clone());
+    let mut alice_ts_interface =
+        setup_transaction_service_no_comms_with_contacts(factories, connection, None, Some(contacts_service)).await;
+
+    let bob_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let (inbound_tx, _) = build_pending_inbound_transaction(bob_address.clone()).await;
+    let tx_id = inbound_tx.tx_id;
+    alice_ts_interface
+        .ts_db
+        .write(WriteOperation::Insert(DbKeyValuePair::PendingInboundTransaction(
+            tx_id,
+            Box::new(inbound_tx),
+        )))
+        .unwrap();
+    let ts_handle = &mut alice_ts_interface.transaction_service_handle;
+
+    // Bob is not a contact yet
+    assert_eq!(ts_handle.get_counterparty_alias(tx_id).await.unwrap(), None);
+
+    let bob = Contact::new("Bob".to_string(), bob_address.clone(), None, None, false);
+    contacts.lock().unwrap().push(bob.clone());
+    let _size = contacts_events.send(Arc::new(ContactsLivenessEvent::ContactSaved(Box::new(bob))));
+    wait_for_counterparty_alias(ts_handle, tx_id, Some("Bob")).await;
+
+    // The contact aliases are cached until the contacts service reports a change
+    contacts.lock().unwrap()[0].alias = "Robert".to_string();
+    assert_eq!(
+        ts_handle.get_counterparty_alias(tx_id).await.unwrap(),
+        Some("Bob".to_string())
+    );
+    let robert = contacts.lock().unwrap()[0].clone();
+    let _size = contacts_events.send(Arc::new(ContactsLivenessEvent::ContactSaved(Box::new(robert.clone()))));
+    wait_for_counterparty_alias(ts_handle, tx_id, Some("Robert")).await;
+
+    // The last known alias is kept once the contact is removed
+    contacts.lock().unwrap().clear();
+    let _size = contacts_events.send(Arc::new(ContactsLivenessEvent::ContactRemoved(Box::new(robert))));
+    sleep(Duration::from_millis(500)).await;
+    assert_eq!(
+        ts_handle.get_counterparty_alias(tx_id).await.unwrap(),
+        Some("Robert".to_string())
+    );
+}
+
 #[tokio::test]
 async fn test_coinbase_transactions_rejection_same_hash_but_accept_on_same_height() {
     let factories = CryptoFactories::default();
@@ -5593,205 +6479,862 @@ async fn test_transaction_timeout_cancellation() {
             },
         }
     }
-    assert!(transaction_cancelled, "Transaction must be cancelled");
+    assert!(transaction_cancelled, "Transaction must be cancelled");
+}
+
+/// This test will check that the Transaction Service starts the tx broadcast protocol correctly and reacts correctly
+/// to a tx being broadcast and to a tx being rejected.
+#[tokio::test]
+async fn transaction_service_tx_broadcast() {
+    let factories = CryptoFactories::default();
+
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+
+    alice_ts_interface
+        .wallet_connectivity_service_mock
+        .set_base_node(alice_ts_interface.base_node_identity.to_peer());
+
+    let (connection2, _temp_dir2) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection2, None).await;
+
+    let alice_output_value = MicroMinotari(250000);
+
+    let uo = make_input(
+        &mut OsRng,
+        alice_output_value,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+
+    let uo2 = make_input(
+        &mut OsRng,
+        alice_output_value,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo2, None)
+        .await
+        .unwrap();
+
+    let amount_sent1 = 100000 * uT;
+
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    // Send Tx1
+    let tx_id1 = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address.clone(),
+            amount_sent1,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            100 * uT,
+            "Testing Message".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(2, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 1");
+    let (_, _body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let tx_sender_msg: TransactionSenderMessage = envelope_body
+        .decode_part::<proto::TransactionSenderMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+    match tx_sender_msg {
+        TransactionSenderMessage::Single(_) => (),
+        _ => {
+            panic!("Transaction is the not a single rounder sender variant");
+        },
+    };
+
+    bob_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            tx_sender_msg.try_into().unwrap(),
+            alice_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(2, Duration::from_secs(60))
+        .await
+        .expect("bob call wait 1");
+
+    let _result = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+
+    let envelope_body = EnvelopeBody::decode(&mut call.1.to_vec().as_slice()).unwrap();
+    let bob_tx_reply_msg1: RecipientSignedMessage = envelope_body
+        .decode_part::<proto::RecipientSignedMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    // Send Tx2
+    let amount_sent2 = 100001 * uT;
+    let tx_id2 = alice_ts_interface
+        .transaction_service_handle
+        .send_transaction(
+            bob_address,
+            amount_sent2,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            20 * uT,
+            "Testing Message2".to_string(),
+        )
+        .await
+        .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(2, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 2");
+
+    let _result = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let tx_sender_msg = try_decode_sender_message(call.1.to_vec()).unwrap();
+
+    match tx_sender_msg {
+        TransactionSenderMessage::Single(_) => (),
+        _ => {
+            panic!("Transaction is the not a single rounder sender variant");
+        },
+    };
+
+    bob_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            tx_sender_msg.try_into().unwrap(),
+            alice_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(2, Duration::from_secs(60))
+        .await
+        .expect("Bob call wait 2");
+
+    let (_, _body) = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let (_, body) = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+
+    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
+    let bob_tx_reply_msg2: RecipientSignedMessage = envelope_body
+        .decode_part::<proto::RecipientSignedMessage>(1)
+        .unwrap()
+        .unwrap()
+        .try_into()
+        .unwrap();
+
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(balance.available_balance, MicroMinotari(0));
+
+    // Give Alice the first of tx reply to start the broadcast process.
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            bob_tx_reply_msg1.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut tx1_received = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap(){
+                    if tx_id == &tx_id1 {
+                        tx1_received = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(tx1_received);
+
+    let alice_completed_tx1 = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transactions()
+        .await
+        .unwrap()
+        .remove(&tx_id1)
+        .expect("Transaction must be in collection");
+
+    let tx1_fee = alice_completed_tx1.fee;
+
+    assert!(
+        alice_completed_tx1.status == TransactionStatus::Completed ||
+            alice_completed_tx1.status == TransactionStatus::Broadcast
+    );
+
+    let _transactions = alice_ts_interface
+        .base_node_rpc_mock_state
+        .wait_pop_submit_transaction_calls(1, Duration::from_secs(30))
+        .await
+        .expect("Should receive a tx submission");
+    let _schnorr_signatures = alice_ts_interface
+        .base_node_rpc_mock_state
+        .wait_pop_transaction_query_calls(1, Duration::from_secs(30))
+        .await
+        .expect("Should receive a tx query");
+
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_transaction_query_response(TxQueryResponse {
+            location: TxLocation::Mined,
+            block_hash: None,
+            confirmations: TransactionServiceConfig::default().num_confirmations_required,
+            is_synced: true,
+            height_of_longest_chain: 0,
+            mined_timestamp: None,
+        });
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut tx1_broadcast = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                println!("Event: {:?}", event);
+                 if let TransactionEvent::TransactionBroadcast(tx_id) = &*event.unwrap(){
+                    if tx_id == &tx_id1 {
+                        tx1_broadcast = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(tx1_broadcast);
+
+    alice_ts_interface
+        .transaction_ack_message_channel
+        .send(create_dummy_message(
+            bob_tx_reply_msg2.try_into().unwrap(),
+            bob_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut tx2_received = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap(){
+                    if tx_id == &tx_id2 {
+                        tx2_received = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(tx2_received);
+
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_submit_transaction_response(TxSubmissionResponse {
+            accepted: false,
+            rejection_reason: TxSubmissionRejectionReason::Orphan,
+            is_synced: true,
+        });
+
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_transaction_query_response(TxQueryResponse {
+            location: TxLocation::NotStored,
+            block_hash: None,
+            confirmations: TransactionServiceConfig::default().num_confirmations_required,
+            is_synced: true,
+            height_of_longest_chain: 0,
+            mined_timestamp: None,
+        });
+
+    let alice_completed_tx2 = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transactions()
+        .await
+        .unwrap()
+        .remove(&tx_id2)
+        .expect("Transaction must be in collection");
+
+    assert!(
+        alice_completed_tx2.status == TransactionStatus::Completed ||
+            alice_completed_tx2.status == TransactionStatus::Broadcast
+    );
+
+    let _transactions = alice_ts_interface
+        .base_node_rpc_mock_state
+        .wait_pop_submit_transaction_calls(1, Duration::from_secs(30))
+        .await
+        .expect("Should receive a tx submission");
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut tx2_cancelled = false;
+    loop {
+        tokio::select! {
+            event = alice_event_stream.recv() => {
+                 if let TransactionEvent::TransactionCancelled(tx_id, _) = &*event.unwrap(){
+                    if tx_id == &tx_id2 {
+                        tx2_cancelled = true;
+                        break;
+                    }
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(tx2_cancelled);
+
+    // Check that the cancelled Tx value + change from tx1 is available
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+
+    assert_eq!(
+        balance.pending_incoming_balance,
+        alice_output_value - amount_sent1 - tx1_fee
+    );
+    assert_eq!(balance.available_balance, alice_output_value);
+}
+
+#[tokio::test]
+async fn broadcast_all_completed_transactions_on_startup() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let db = alice_ts_interface.ts_db.clone();
+
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(Signature::default())
+        .build()
+        .unwrap();
+
+    let tx = Transaction::new(
+        vec![],
+        vec![],
+        vec![kernel],
+        PrivateKey::random(&mut OsRng),
+        PrivateKey::random(&mut OsRng),
+    );
+    let source_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let destination_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let completed_tx1 = CompletedTransaction {
+        tx_id: 1u64.into(),
+        source_address,
+        destination_address,
+        amount: 5000 * uT,
+        fee: MicroMinotari::from(20),
+        transaction: tx.clone(),
+        status: TransactionStatus::Completed,
+        message: "Yo!".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: None,
+        direction: TransactionDirection::Outbound,
+        coinbase_block_height: None,
+        send_count: 0,
+        last_send_timestamp: None,
+        transaction_signature: tx.first_kernel_excess_sig().unwrap_or(&Signature::default()).clone(),
+        confirmations: None,
+        mined_height: None,
+        mined_in_block: None,
+        mined_timestamp: None,
+    };
+
+    let completed_tx2 = CompletedTransaction {
+        tx_id: 2u64.into(),
+        status: TransactionStatus::MinedConfirmed,
+        ..completed_tx1.clone()
+    };
+
+    let completed_tx3 = CompletedTransaction {
+        tx_id: 3u64.into(),
+        status: TransactionStatus::Completed,
+        ..completed_tx1.clone()
+    };
+
+    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+        completed_tx1.tx_id,
+        Box::new(completed_tx1),
+    )))
+    .unwrap();
+
+    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+        completed_tx2.tx_id,
+        Box::new(completed_tx2),
+    )))
+    .unwrap();
+
+    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+        completed_tx3.tx_id,
+        Box::new(completed_tx3),
+    )))
+    .unwrap();
+
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_transaction_query_response(TxQueryResponse {
+            location: TxLocation::Mined,
+            block_hash: None,
+            confirmations: TransactionServiceConfig::default().num_confirmations_required,
+            is_synced: true,
+            height_of_longest_chain: 0,
+            mined_timestamp: None,
+        });
+
+    // Note: The event stream has to be assigned before the broadcast protocol is restarted otherwise the events will be
+    // dropped
+    let mut event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    alice_ts_interface
+        .transaction_service_handle
+        .restart_broadcast_protocols()
+        .await
+        .unwrap();
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .restart_broadcast_protocols()
+        .await
+        .is_ok());
+
+    let delay = sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    let mut found1 = false;
+    let mut found2 = false;
+    let mut found3 = false;
+    loop {
+        tokio::select! {
+            event = event_stream.recv() => {
+                if let TransactionEvent::TransactionBroadcast(tx_id) = (*event.unwrap()).clone() {
+                    if tx_id == 1u64 {
+                        found1 = true
+                    }
+                    if tx_id == 2u64 {
+                        found2 = true
+                    }
+                    if tx_id == 3u64 {
+                        found3 = true
+                    }
+                    if found1 && found3 {
+                        break;
+                    }
+
+                }
+            },
+            () = &mut delay => {
+                break;
+            },
+        }
+    }
+    assert!(found1);
+    assert!(!found2);
+    assert!(found3);
 }
 
-/// This test will check that the Transaction Service starts the tx broadcast protocol correctly and reacts correctly
-/// to a tx being broadcast and to a tx being rejected.
 #[tokio::test]
-async fn transaction_service_tx_broadcast() {
+async fn bounded_background_work_is_batched() {
     let factories = CryptoFactories::default();
-
-    let alice_node_identity =
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
-
-    let bob_node_identity =
-        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
     let (connection, _temp_dir) = make_wallet_database_connection(None);
 
     let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
-    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
-
-    alice_ts_interface
-        .wallet_connectivity_service_mock
-        .set_base_node(alice_ts_interface.base_node_identity.to_peer());
-
-    let (connection2, _temp_dir2) = make_wallet_database_connection(None);
-    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection2, None).await;
-
-    let alice_output_value = MicroMinotari(250000);
+    let db = alice_ts_interface.ts_db.clone();
 
-    let uo = make_input(
-        &mut OsRng,
-        alice_output_value,
-        &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
-    )
-    .await;
-    alice_ts_interface
-        .output_manager_service_handle
-        .add_output(uo, None)
-        .await
+    let kernel = KernelBuilder::new()
+        .with_excess(&factories.commitment.zero())
+        .with_signature(Signature::default())
+        .build()
+        .unwrap();
+    let tx = Transaction::new(
+        vec![],
+        vec![],
+        vec![kernel],
+        PrivateKey::random(&mut OsRng),
+        PrivateKey::random(&mut OsRng),
+    );
+    let address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let completed_tx = CompletedTransaction {
+        tx_id: 1u64.into(),
+        source_address: address.clone(),
+        destination_address: address.clone(),
+        amount: 5000 * uT,
+        fee: MicroMinotari::from(20),
+        transaction: tx.clone(),
+        status: TransactionStatus::Broadcast,
+        message: "Yo!".to_string(),
+        timestamp: Utc::now().naive_utc(),
+        cancelled: None,
+        direction: TransactionDirection::Outbound,
+        coinbase_block_height: None,
+        send_count: 0,
+        last_send_timestamp: None,
+        transaction_signature: tx.first_kernel_excess_sig().unwrap_or(&Signature::default()).clone(),
+        confirmations: None,
+        mined_height: None,
+        mined_in_block: None,
+        mined_timestamp: None,
+    };
+    for tx_id in 1u64..=3 {
+        let completed_tx = CompletedTransaction {
+            tx_id: tx_id.into(),
+            ..completed_tx.clone()
+        };
+        db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
+            completed_tx.tx_id,
+            Box::new(completed_tx),
+        )))
+        .unwrap();
+        db.queue_outbound_message(QueuedOutboundMessage::new(
+            tx_id.into(),
+            OutboundMessageType::TransactionFinalized,
+            address.clone(),
+            proto::TransactionFinalizedMessage {
+                tx_id,
+                transaction: None,
+            }
+            .encode_to_vec(),
+            Utc::now().naive_utc(),
+        ))
         .unwrap();
+    }
 
-    let uo2 = make_input(
-        &mut OsRng,
-        alice_output_value,
-        &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
-    )
-    .await;
-    alice_ts_interface
-        .output_manager_service_handle
-        .add_output(uo2, None)
+    // Only the requested number of queued messages are resent
+    let (resent, remaining) = alice_ts_interface
+        .transaction_service_handle
+        .resend_queued_outbound_messages(2)
         .await
         .unwrap();
+    assert_eq!((resent, remaining), (2, 1));
 
-    let amount_sent1 = 100000 * uT;
-
-    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
-    // Send Tx1
-    let tx_id1 = alice_ts_interface
+    // Each batch continues after the previous one, wrapping around to the start
+    let validation = alice_ts_interface
         .transaction_service_handle
-        .send_transaction(
-            bob_address.clone(),
-            amount_sent1,
-            UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            100 * uT,
-            "Testing Message".to_string(),
-        )
+        .validate_transaction_batch(2)
         .await
         .unwrap();
-    alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(2, Duration::from_secs(60))
+    assert_eq!((validation.num_transactions, validation.remaining), (2, 1));
+    let next_validation = alice_ts_interface
+        .transaction_service_handle
+        .validate_transaction_batch(2)
         .await
-        .expect("Alice call wait 1");
-    let (_, _body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let (_, body) = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-
-    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
-    let tx_sender_msg: TransactionSenderMessage = envelope_body
-        .decode_part::<proto::TransactionSenderMessage>(1)
-        .unwrap()
-        .unwrap()
-        .try_into()
         .unwrap();
-    match tx_sender_msg {
-        TransactionSenderMessage::Single(_) => (),
-        _ => {
-            panic!("Transaction is the not a single rounder sender variant");
-        },
-    };
+    assert_eq!((next_validation.num_transactions, next_validation.remaining), (2, 1));
+    assert_ne!(validation.operation_id, next_validation.operation_id);
 
-    bob_ts_interface
-        .transaction_send_message_channel
-        .send(create_dummy_message(
-            tx_sender_msg.try_into().unwrap(),
-            alice_node_identity.public_key(),
-        ))
+    // A batch is never larger than the number of unconfirmed transactions
+    let validation = alice_ts_interface
+        .transaction_service_handle
+        .validate_transaction_batch(10)
         .await
         .unwrap();
-    bob_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(2, Duration::from_secs(60))
-        .await
-        .expect("bob call wait 1");
+    assert_eq!((validation.num_transactions, validation.remaining), (3, 0));
+}
 
-    let _result = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+#[tokio::test]
+async fn test_update_faux_tx_on_oms_validation() {
+    let factories = CryptoFactories::default();
 
-    let envelope_body = EnvelopeBody::decode(&mut call.1.to_vec().as_slice()).unwrap();
-    let bob_tx_reply_msg1: RecipientSignedMessage = envelope_body
-        .decode_part::<proto::RecipientSignedMessage>(1)
-        .unwrap()
-        .unwrap()
-        .try_into()
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let alice_address = TariAddress::new(
+        alice_ts_interface.base_node_identity.public_key().clone(),
+        Network::LocalNet,
+    );
+    let tx_id_1 = alice_ts_interface
+        .transaction_service_handle
+        .import_utxo_with_status(
+            MicroMinotari::from(10000),
+            alice_address.clone(),
+            None,
+            "blah".to_string(),
+            None,
+            ImportStatus::Imported,
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    let tx_id_2 = alice_ts_interface
+        .transaction_service_handle
+        .import_utxo_with_status(
+            MicroMinotari::from(20000),
+            alice_address.clone(),
+            None,
+            "one-sided 1".to_string(),
+            None,
+            ImportStatus::FauxUnconfirmed,
+            None,
+            None,
+            None,
+        )
+        .await
         .unwrap();
 
-    // Send Tx2
-    let amount_sent2 = 100001 * uT;
-    let tx_id2 = alice_ts_interface
+    let tx_id_3 = alice_ts_interface
         .transaction_service_handle
-        .send_transaction(
-            bob_address,
-            amount_sent2,
-            UtxoSelectionCriteria::default(),
-            OutputFeatures::default(),
-            20 * uT,
-            "Testing Message2".to_string(),
+        .import_utxo_with_status(
+            MicroMinotari::from(30000),
+            alice_address,
+            None,
+            "one-sided 2".to_string(),
+            None,
+            ImportStatus::FauxConfirmed,
+            None,
+            None,
+            None,
         )
         .await
         .unwrap();
+
+    let uo_1 = make_input(
+        &mut OsRng.clone(),
+        MicroMinotari::from(10000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    let uo_2 = make_input(
+        &mut OsRng.clone(),
+        MicroMinotari::from(20000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    let uo_3 = make_input(
+        &mut OsRng.clone(),
+        MicroMinotari::from(30000),
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    for (tx_id, uo) in [(tx_id_1, uo_1), (tx_id_2, uo_2), (tx_id_3, uo_3)] {
+        alice_ts_interface
+            .output_manager_service_handle
+            .add_output_with_tx_id(tx_id, uo, None)
+            .await
+            .unwrap();
+    }
+
+    for tx_id in [tx_id_1, tx_id_2, tx_id_3] {
+        let transaction = alice_ts_interface
+            .transaction_service_handle
+            .get_any_transaction(tx_id)
+            .await
+            .unwrap()
+            .unwrap();
+        if tx_id == tx_id_1 {
+            if let WalletTransaction::Completed(tx) = &transaction {
+                assert_eq!(tx.status, TransactionStatus::Imported);
+            } else {
+                panic!("Should find a complete Imported transaction");
+            }
+        }
+        if tx_id == tx_id_2 {
+            if let WalletTransaction::Completed(tx) = &transaction {
+                assert_eq!(tx.status, TransactionStatus::FauxUnconfirmed);
+            } else {
+                panic!("Should find a complete FauxUnconfirmed transaction");
+            }
+        }
+        if tx_id == tx_id_3 {
+            if let WalletTransaction::Completed(tx) = &transaction {
+                assert_eq!(tx.status, TransactionStatus::FauxConfirmed);
+            } else {
+                panic!("Should find a complete FauxConfirmed transaction");
+            }
+        }
+    }
+
+    // This will change the status of the imported transaction
     alice_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(2, Duration::from_secs(60))
-        .await
-        .expect("Alice call wait 2");
+        .output_manager_service_event_publisher
+        .send(Arc::new(OutputManagerEvent::TxoValidationSuccess(1u64)))
+        .unwrap();
 
-    let _result = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let tx_sender_msg = try_decode_sender_message(call.1.to_vec()).unwrap();
+    let mut found_imported = false;
+    let mut found_faux_unconfirmed = false;
+    let mut found_faux_confirmed = false;
+    for _ in 0..20 {
+        sleep(Duration::from_secs(1)).await;
+        for tx_id in [tx_id_1, tx_id_2, tx_id_3] {
+            let transaction = alice_ts_interface
+                .transaction_service_handle
+                .get_any_transaction(tx_id)
+                .await
+                .unwrap()
+                .unwrap();
+            if let WalletTransaction::Completed(tx) = transaction {
+                if tx_id == tx_id_1 && tx.status == TransactionStatus::FauxUnconfirmed && !found_imported {
+                    found_imported = true;
+                }
+                if tx_id == tx_id_2 && tx.status == TransactionStatus::FauxUnconfirmed && !found_faux_unconfirmed {
+                    found_faux_unconfirmed = true;
+                }
+                if tx_id == tx_id_3 && tx.status == TransactionStatus::FauxConfirmed && !found_faux_confirmed {
+                    found_faux_confirmed = true;
+                }
+            }
+        }
+        if found_imported && found_faux_unconfirmed && found_faux_confirmed {
+            break;
+        }
+    }
+    assert!(
+        found_imported && found_faux_unconfirmed && found_faux_confirmed,
+        "Should have found the updated statuses"
+    );
+}
 
-    match tx_sender_msg {
-        TransactionSenderMessage::Single(_) => (),
-        _ => {
-            panic!("Transaction is the not a single rounder sender variant");
-        },
-    };
+#[tokio::test]
+async fn test_get_fee_per_gram_per_block_basic() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let stats = vec![base_node_proto::MempoolFeePerGramStat {
+        order: 0,
+        min_fee_per_gram: 1,
+        avg_fee_per_gram: 2,
+        max_fee_per_gram: 3,
+    }];
+    alice_ts_interface
+        .base_node_rpc_mock_state
+        .set_fee_per_gram_stats_response(base_node_proto::GetMempoolFeePerGramStatsResponse { stats: stats.clone() });
 
-    bob_ts_interface
-        .transaction_send_message_channel
-        .send(create_dummy_message(
-            tx_sender_msg.try_into().unwrap(),
-            alice_node_identity.public_key(),
-        ))
+    let estimates = alice_ts_interface
+        .transaction_service_handle
+        .get_fee_per_gram_stats_per_block(10)
         .await
         .unwrap();
-    bob_ts_interface
-        .outbound_service_mock_state
-        .wait_call_count(2, Duration::from_secs(60))
-        .await
-        .expect("Bob call wait 2");
-
-    let (_, _body) = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
-    let (_, body) = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
+    assert_eq!(estimates.stats.len(), 1)
+}
 
-    let envelope_body = EnvelopeBody::decode(body.to_vec().as_slice()).unwrap();
-    let bob_tx_reply_msg2: RecipientSignedMessage = envelope_body
-        .decode_part::<proto::RecipientSignedMessage>(1)
-        .unwrap()
-        .unwrap()
-        .try_into()
-        .unwrap();
+async fn wait_for_pending_outbound_transaction(ts_interface: &mut TransactionServiceNoCommsInterface, tx_id: TxId) {
+    for _ in 0..=12 {
+        if ts_interface
+            .transaction_service_handle
+            .get_pending_outbound_transactions()
+            .await
+            .unwrap()
+            .contains_key(&tx_id)
+        {
+            return;
+        }
+        sleep(Duration::from_secs(5)).await;
+    }
+    panic!("Pending outbound transaction should have been added by now");
+}
 
-    let balance = alice_ts_interface
+#[tokio::test]
+async fn test_batch_transaction_pays_every_recipient_with_one_kernel() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    let uo = make_input(
+        &mut OsRng,
+        2_500_000 * uT,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
         .output_manager_service_handle
-        .get_balance()
+        .add_output(uo, None)
         .await
         .unwrap();
-    assert_eq!(balance.available_balance, MicroMinotari(0));
 
-    // Give Alice the first of tx reply to start the broadcast process.
-    alice_ts_interface
-        .transaction_ack_message_channel
-        .send(create_dummy_message(
-            bob_tx_reply_msg1.try_into().unwrap(),
-            bob_node_identity.public_key(),
-        ))
+    let payments = (0..3)
+        .map(|i| BatchPayment {
+            destination: TariAddress::new(
+                PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                Network::LocalNet,
+            ),
+            amount: (i + 1) * 50_000 * uT,
+            message: format!("Payment {}", i),
+        })
+        .collect::<Vec<_>>();
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_batch_transaction(
+            payments.clone(),
+            UtxoSelectionCriteria::default(),
+            20 * uT,
+            "Batch".to_string(),
+        )
         .await
         .unwrap();
 
-    let delay = sleep(Duration::from_secs(60));
+    let delay = sleep(Duration::from_secs(30));
     tokio::pin!(delay);
-    let mut tx1_received = false;
+    let mut completed_immediately = false;
     loop {
         tokio::select! {
             event = alice_event_stream.recv() => {
-                 if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap(){
-                    if tx_id == &tx_id1 {
-                        tx1_received = true;
+                if let TransactionEvent::TransactionCompletedImmediately(id) = &*event.unwrap() {
+                    if *id == tx_id {
+                        completed_immediately = true;
                         break;
                     }
                 }
@@ -5801,295 +7344,355 @@ async fn transaction_service_tx_broadcast() {
             },
         }
     }
-    assert!(tx1_received);
+    assert!(
+        completed_immediately,
+        "The batch should complete without a reply from the recipients"
+    );
 
-    let alice_completed_tx1 = alice_ts_interface
+    let completed = alice_ts_interface
         .transaction_service_handle
-        .get_completed_transactions()
+        .get_completed_transaction(tx_id)
         .await
-        .unwrap()
-        .remove(&tx_id1)
-        .expect("Transaction must be in collection");
+        .unwrap();
+    assert_eq!(completed.amount, 300_000 * uT);
+    assert_eq!(completed.destination_address, payments[0].destination);
+    assert_eq!(completed.transaction.body.kernels().len(), 1);
+    let batched_payments = alice_ts_interface
+        .transaction_service_handle
+        .get_batched_payments(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(batched_payments.len(), payments.len());
+    for (batched, payment) in batched_payments.iter().zip(payments.iter()) {
+        assert_eq!(batched.destination_address, payment.destination);
+        assert_eq!(batched.amount, payment.amount);
+        assert!(completed
+            .transaction
+            .body
+            .outputs()
+            .iter()
+            .any(|o| o.hash() == batched.output_hash));
+    }
+}
 
-    let tx1_fee = alice_completed_tx1.fee;
+#[tokio::test]
+async fn test_batch_transaction_with_insufficient_funds() {
+    let factories = CryptoFactories::default();
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let alice_total_available = 100_000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        alice_total_available,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
 
+    let payments = (0..2)
+        .map(|_| BatchPayment {
+            destination: TariAddress::new(
+                PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                Network::LocalNet,
+            ),
+            amount: 60_000 * uT,
+            message: "Batch".to_string(),
+        })
+        .collect();
+    let err = alice_ts_interface
+        .transaction_service_handle
+        .send_batch_transaction(payments, UtxoSelectionCriteria::default(), 5 * uT, "Batch".to_string())
+        .await
+        .unwrap_err();
     assert!(
-        alice_completed_tx1.status == TransactionStatus::Completed ||
-            alice_completed_tx1.status == TransactionStatus::Broadcast
+        matches!(
+            err,
+            TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds)
+        ),
+        "Unexpected error: {:?}",
+        err
     );
 
-    let _transactions = alice_ts_interface
-        .base_node_rpc_mock_state
-        .wait_pop_submit_transaction_calls(1, Duration::from_secs(30))
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transactions()
         .await
-        .expect("Should receive a tx submission");
-    let _schnorr_signatures = alice_ts_interface
-        .base_node_rpc_mock_state
-        .wait_pop_transaction_query_calls(1, Duration::from_secs(30))
+        .unwrap()
+        .is_empty());
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
         .await
-        .expect("Should receive a tx query");
+        .unwrap();
+    assert_eq!(balance.available_balance, alice_total_available);
+    assert_eq!(balance.pending_outgoing_balance, MicroMinotari::zero());
+}
+
+#[tokio::test]
+async fn test_multi_recipient_transaction_records_interactive_and_one_sided_payments_separately() {
+    let factories = CryptoFactories::default();
+    let alice_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
 
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
+    let uo = make_input(
+        &mut OsRng,
+        2_500_000 * uT,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
     alice_ts_interface
-        .base_node_rpc_mock_state
-        .set_transaction_query_response(TxQueryResponse {
-            location: TxLocation::Mined,
-            block_hash: None,
-            confirmations: TransactionServiceConfig::default().num_confirmations_required,
-            is_synced: true,
-            height_of_longest_chain: 0,
-            mined_timestamp: None,
-        });
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
 
-    let delay = sleep(Duration::from_secs(60));
-    tokio::pin!(delay);
-    let mut tx1_broadcast = false;
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                println!("Event: {:?}", event);
-                 if let TransactionEvent::TransactionBroadcast(tx_id) = &*event.unwrap(){
-                    if tx_id == &tx_id1 {
-                        tx1_broadcast = true;
-                        break;
-                    }
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
-    }
-    assert!(tx1_broadcast);
+    let bob_address = TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet);
+    let carol_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let interactive_amount = 100_000 * uT;
+    let one_sided_amount = 50_000 * uT;
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_multi_recipient_transaction(
+            bob_address.clone(),
+            interactive_amount,
+            vec![BatchPayment {
+                destination: carol_address.clone(),
+                amount: one_sided_amount,
+                message: "One-sided".to_string(),
+            }],
+            100 * uT,
+            "Interactive".to_string(),
+        )
+        .await
+        .unwrap();
+    wait_for_pending_outbound_transaction(&mut alice_ts_interface, tx_id).await;
+
+    let pending = alice_ts_interface
+        .transaction_service_handle
+        .get_pending_outbound_transactions()
+        .await
+        .unwrap()
+        .remove(&tx_id)
+        .unwrap();
+    assert_eq!(pending.amount, interactive_amount);
+    assert_eq!(pending.destination_address, bob_address);
+    let batched_payments = alice_ts_interface
+        .transaction_service_handle
+        .get_batched_payments(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(batched_payments.len(), 1);
+    assert_eq!(batched_payments[0].destination_address, carol_address);
+    assert_eq!(batched_payments[0].amount, one_sided_amount);
+
+    // Complete the negotiation with Bob
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 1");
+    let call = alice_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let alice_sender_message = try_decode_sender_message(call.1.to_vec()).unwrap();
+    let _result = alice_ts_interface.outbound_service_mock_state.take_calls().await;
+
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    bob_ts_interface
+        .transaction_send_message_channel
+        .send(create_dummy_message(
+            alice_sender_message.try_into().unwrap(),
+            alice_node_identity.public_key(),
+        ))
+        .await
+        .unwrap();
+    bob_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Bob call wait 1");
+    let call = bob_ts_interface.outbound_service_mock_state.pop_call().await.unwrap();
+    let bob_reply_message = try_decode_transaction_reply_message(call.1.to_vec()).unwrap();
 
     alice_ts_interface
         .transaction_ack_message_channel
         .send(create_dummy_message(
-            bob_tx_reply_msg2.try_into().unwrap(),
+            bob_reply_message.try_into().unwrap(),
             bob_node_identity.public_key(),
         ))
         .await
         .unwrap();
+    alice_ts_interface
+        .outbound_service_mock_state
+        .wait_call_count(1, Duration::from_secs(60))
+        .await
+        .expect("Alice call wait 2");
 
-    let delay = sleep(Duration::from_secs(60));
-    tokio::pin!(delay);
-    let mut tx2_received = false;
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                 if let TransactionEvent::ReceivedTransactionReply(tx_id) = &*event.unwrap(){
-                    if tx_id == &tx_id2 {
-                        tx2_received = true;
-                        break;
-                    }
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
-    }
-    assert!(tx2_received);
+    let completed = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(completed.amount, interactive_amount);
+    assert_eq!(completed.destination_address, bob_address);
+    let batched_payments = alice_ts_interface
+        .transaction_service_handle
+        .get_batched_payments(tx_id)
+        .await
+        .unwrap();
+    assert_eq!(batched_payments.len(), 1);
+    assert_eq!(batched_payments[0].destination_address, carol_address);
+}
 
-    alice_ts_interface
-        .base_node_rpc_mock_state
-        .set_submit_transaction_response(TxSubmissionResponse {
-            accepted: false,
-            rejection_reason: TxSubmissionRejectionReason::Orphan,
-            is_synced: true,
-        });
+#[tokio::test]
+async fn test_multi_recipient_transaction_with_insufficient_funds() {
+    let factories = CryptoFactories::default();
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
 
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let alice_total_available = 100_000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        alice_total_available,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
     alice_ts_interface
-        .base_node_rpc_mock_state
-        .set_transaction_query_response(TxQueryResponse {
-            location: TxLocation::NotStored,
-            block_hash: None,
-            confirmations: TransactionServiceConfig::default().num_confirmations_required,
-            is_synced: true,
-            height_of_longest_chain: 0,
-            mined_timestamp: None,
-        });
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
 
-    let alice_completed_tx2 = alice_ts_interface
+    // The interactive payment alone is affordable, but not together with the one-sided payment
+    let carol_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let err = alice_ts_interface
         .transaction_service_handle
-        .get_completed_transactions()
+        .send_multi_recipient_transaction(
+            TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet),
+            60_000 * uT,
+            vec![BatchPayment {
+                destination: carol_address,
+                amount: 60_000 * uT,
+                message: "One-sided".to_string(),
+            }],
+            5 * uT,
+            "Interactive".to_string(),
+        )
         .await
-        .unwrap()
-        .remove(&tx_id2)
-        .expect("Transaction must be in collection");
-
+        .unwrap_err();
     assert!(
-        alice_completed_tx2.status == TransactionStatus::Completed ||
-            alice_completed_tx2.status == TransactionStatus::Broadcast
+        matches!(
+            err,
+            TransactionServiceError::OutputManagerError(OutputManagerError::NotEnoughFunds)
+        ),
+        "Unexpected error: {:?}",
+        err
     );
 
-    let _transactions = alice_ts_interface
-        .base_node_rpc_mock_state
-        .wait_pop_submit_transaction_calls(1, Duration::from_secs(30))
+    assert!(alice_ts_interface
+        .transaction_service_handle
+        .get_pending_outbound_transactions()
         .await
-        .expect("Should receive a tx submission");
-
-    let delay = sleep(Duration::from_secs(60));
-    tokio::pin!(delay);
-    let mut tx2_cancelled = false;
-    loop {
-        tokio::select! {
-            event = alice_event_stream.recv() => {
-                 if let TransactionEvent::TransactionCancelled(tx_id, _) = &*event.unwrap(){
-                    if tx_id == &tx_id2 {
-                        tx2_cancelled = true;
-                        break;
-                    }
-                }
-            },
-            () = &mut delay => {
-                break;
-            },
-        }
-    }
-    assert!(tx2_cancelled);
-
-    // Check that the cancelled Tx value + change from tx1 is available
+        .unwrap()
+        .is_empty());
     let balance = alice_ts_interface
         .output_manager_service_handle
         .get_balance()
         .await
         .unwrap();
-
-    assert_eq!(
-        balance.pending_incoming_balance,
-        alice_output_value - amount_sent1 - tx1_fee
-    );
-    assert_eq!(balance.available_balance, alice_output_value);
+    assert_eq!(balance.available_balance, alice_total_available);
+    assert_eq!(balance.pending_outgoing_balance, MicroMinotari::zero());
+    assert_eq!(alice_ts_interface.outbound_service_mock_state.call_count().await, 0);
 }
 
 #[tokio::test]
-async fn broadcast_all_completed_transactions_on_startup() {
+async fn test_multi_recipient_transaction_cancellation() {
     let factories = CryptoFactories::default();
-    let (connection, _temp_dir) = make_wallet_database_connection(None);
-
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
-    let db = alice_ts_interface.ts_db.clone();
+    let bob_node_identity =
+        NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
 
-    let kernel = KernelBuilder::new()
-        .with_excess(&factories.commitment.zero())
-        .with_signature(Signature::default())
-        .build()
+    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
+    let mut alice_event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
+    let alice_total_available = 2_500_000 * uT;
+    let uo = make_input(
+        &mut OsRng,
+        alice_total_available,
+        &OutputFeatures::default(),
+        &alice_ts_interface.key_manager_handle,
+    )
+    .await;
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
         .unwrap();
 
-    let tx = Transaction::new(
-        vec![],
-        vec![],
-        vec![kernel],
-        PrivateKey::random(&mut OsRng),
-        PrivateKey::random(&mut OsRng),
-    );
-    let source_address = TariAddress::new(
+    let carol_address = TariAddress::new(
         PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
         Network::LocalNet,
     );
-    let destination_address = TariAddress::new(
-        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
-        Network::LocalNet,
+    let tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_multi_recipient_transaction(
+            TariAddress::new(bob_node_identity.public_key().clone(), Network::LocalNet),
+            100_000 * uT,
+            vec![BatchPayment {
+                destination: carol_address,
+                amount: 50_000 * uT,
+                message: "One-sided".to_string(),
+            }],
+            100 * uT,
+            "Interactive".to_string(),
+        )
+        .await
+        .unwrap();
+    wait_for_pending_outbound_transaction(&mut alice_ts_interface, tx_id).await;
+    assert!(
+        alice_ts_interface
+            .output_manager_service_handle
+            .get_balance()
+            .await
+            .unwrap()
+            .pending_outgoing_balance >
+            MicroMinotari::zero()
     );
-    let completed_tx1 = CompletedTransaction {
-        tx_id: 1u64.into(),
-        source_address,
-        destination_address,
-        amount: 5000 * uT,
-        fee: MicroMinotari::from(20),
-        transaction: tx.clone(),
-        status: TransactionStatus::Completed,
-        message: "Yo!".to_string(),
-        timestamp: Utc::now().naive_utc(),
-        cancelled: None,
-        direction: TransactionDirection::Outbound,
-        coinbase_block_height: None,
-        send_count: 0,
-        last_send_timestamp: None,
-        transaction_signature: tx.first_kernel_excess_sig().unwrap_or(&Signature::default()).clone(),
-        confirmations: None,
-        mined_height: None,
-        mined_in_block: None,
-        mined_timestamp: None,
-    };
-
-    let completed_tx2 = CompletedTransaction {
-        tx_id: 2u64.into(),
-        status: TransactionStatus::MinedConfirmed,
-        ..completed_tx1.clone()
-    };
-
-    let completed_tx3 = CompletedTransaction {
-        tx_id: 3u64.into(),
-        status: TransactionStatus::Completed,
-        ..completed_tx1.clone()
-    };
-
-    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
-        completed_tx1.tx_id,
-        Box::new(completed_tx1),
-    )))
-    .unwrap();
-
-    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
-        completed_tx2.tx_id,
-        Box::new(completed_tx2),
-    )))
-    .unwrap();
-
-    db.write(WriteOperation::Insert(DbKeyValuePair::CompletedTransaction(
-        completed_tx3.tx_id,
-        Box::new(completed_tx3),
-    )))
-    .unwrap();
-
-    alice_ts_interface
-        .base_node_rpc_mock_state
-        .set_transaction_query_response(TxQueryResponse {
-            location: TxLocation::Mined,
-            block_hash: None,
-            confirmations: TransactionServiceConfig::default().num_confirmations_required,
-            is_synced: true,
-            height_of_longest_chain: 0,
-            mined_timestamp: None,
-        });
 
-    // Note: The event stream has to be assigned before the broadcast protocol is restarted otherwise the events will be
-    // dropped
-    let mut event_stream = alice_ts_interface.transaction_service_handle.get_event_stream();
     alice_ts_interface
         .transaction_service_handle
-        .restart_broadcast_protocols()
+        .cancel_transaction(tx_id)
         .await
         .unwrap();
-    assert!(alice_ts_interface
-        .transaction_service_handle
-        .restart_broadcast_protocols()
-        .await
-        .is_ok());
 
     let delay = sleep(Duration::from_secs(60));
     tokio::pin!(delay);
-    let mut found1 = false;
-    let mut found2 = false;
-    let mut found3 = false;
+    let mut cancelled = false;
     loop {
         tokio::select! {
-            event = event_stream.recv() => {
-                if let TransactionEvent::TransactionBroadcast(tx_id) = (*event.unwrap()).clone() {
-                    if tx_id == 1u64 {
-                        found1 = true
-                    }
-                    if tx_id == 2u64 {
-                        found2 = true
-                    }
-                    if tx_id == 3u64 {
-                        found3 = true
-                    }
-                    if found1 && found3 {
+            event = alice_event_stream.recv() => {
+                if let TransactionEvent::TransactionCancelled(id, _) = &*event.unwrap() {
+                    if *id == tx_id {
+                        cancelled = true;
                         break;
                     }
-
                 }
             },
             () = &mut delay => {
@@ -6097,185 +7700,207 @@ async fn broadcast_all_completed_transactions_on_startup() {
             },
         }
     }
-    assert!(found1);
-    assert!(!found2);
-    assert!(found3);
-}
-
-#[tokio::test]
-async fn test_update_faux_tx_on_oms_validation() {
-    let factories = CryptoFactories::default();
-
-    let (connection, _temp_dir) = make_wallet_database_connection(None);
+    assert!(cancelled, "Cancelled event should have occurred");
 
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), connection, None).await;
-    let alice_address = TariAddress::new(
-        alice_ts_interface.base_node_identity.public_key().clone(),
-        Network::LocalNet,
-    );
-    let tx_id_1 = alice_ts_interface
+    assert!(alice_ts_interface
         .transaction_service_handle
-        .import_utxo_with_status(
-            MicroMinotari::from(10000),
-            alice_address.clone(),
-            "blah".to_string(),
-            None,
-            ImportStatus::Imported,
-            None,
-            None,
-            None,
-        )
+        .get_pending_outbound_transactions()
         .await
-        .unwrap();
-    let tx_id_2 = alice_ts_interface
-        .transaction_service_handle
-        .import_utxo_with_status(
-            MicroMinotari::from(20000),
-            alice_address.clone(),
-            "one-sided 1".to_string(),
-            None,
-            ImportStatus::FauxUnconfirmed,
-            None,
-            None,
-            None,
-        )
+        .unwrap()
+        .is_empty());
+    // The inputs encumbered for the interactive and one-sided outputs are released again
+    let balance = alice_ts_interface
+        .output_manager_service_handle
+        .get_balance()
         .await
         .unwrap();
+    assert_eq!(balance.available_balance, alice_total_available);
+    assert_eq!(balance.pending_outgoing_balance, MicroMinotari::zero());
+}
 
-    let tx_id_3 = alice_ts_interface
-        .transaction_service_handle
-        .import_utxo_with_status(
-            MicroMinotari::from(30000),
-            alice_address,
-            "one-sided 2".to_string(),
-            None,
-            ImportStatus::FauxConfirmed,
-            None,
-            None,
-            None,
-        )
+async fn add_one_sided_payment_script(
+    output_manager: &mut OutputManagerHandle,
+    key_manager: &TestKeyManager,
+    secret_key: PrivateKey,
+) {
+    let script = one_sided_payment_script(&PublicKey::from_secret_key(&secret_key));
+    output_manager
+        .add_known_script(KnownOneSidedPaymentScript {
+            script_hash: script.as_hash::<Blake2b<U32>>().unwrap().to_vec(),
+            script_key_id: key_manager.import_key(secret_key).await.unwrap(),
+            script,
+            input: ExecutionStack::default(),
+            script_lock_height: 0,
+        })
         .await
         .unwrap();
+}
 
-    let uo_1 = make_input(
-        &mut OsRng.clone(),
-        MicroMinotari::from(10000),
-        &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
+#[tokio::test]
+async fn offline_signing_from_a_watch_only_wallet() {
+    let factories = CryptoFactories::default();
+    let (alice_connection, _alice_temp_dir) = make_wallet_database_connection(None);
+    let (bob_connection, _bob_temp_dir) = make_wallet_database_connection(None);
+    let (watch_only_connection, _watch_only_temp_dir) = make_wallet_database_connection(None);
+
+    let mut alice_ts_interface = setup_transaction_service_no_comms(factories.clone(), alice_connection, None).await;
+    // Bob holds the spending keys offline, the watch-only wallet of Bob's address is online
+    let mut bob_ts_interface = setup_transaction_service_no_comms(factories.clone(), bob_connection, None).await;
+    let bob_secret_key = bob_ts_interface.base_node_identity.secret_key().clone();
+    let bob_address = TariAddress::new(
+        bob_ts_interface.base_node_identity.public_key().clone(),
+        Network::LocalNet,
+    );
+    let mut watch_only_ts_interface =
+        setup_watch_only_transaction_service_no_comms(factories.clone(), watch_only_connection, WatchOnlyKeys {
+            view_key: bob_secret_key.clone(),
+            spend_public_key: bob_address.public_key().clone(),
+        })
+        .await;
+    add_one_sided_payment_script(
+        &mut bob_ts_interface.output_manager_service_handle,
+        &bob_ts_interface.key_manager_handle,
+        bob_secret_key.clone(),
     )
     .await;
-    let uo_2 = make_input(
-        &mut OsRng.clone(),
-        MicroMinotari::from(20000),
-        &OutputFeatures::default(),
-        &alice_ts_interface.key_manager_handle,
+    add_one_sided_payment_script(
+        &mut watch_only_ts_interface.output_manager_service_handle,
+        &watch_only_ts_interface.key_manager_handle,
+        bob_secret_key,
     )
     .await;
-    let uo_3 = make_input(
-        &mut OsRng.clone(),
-        MicroMinotari::from(30000),
+
+    // Alice pays Bob, and both of Bob's wallets find the payment
+    let uo = make_input(
+        &mut OsRng,
+        2_000_000 * uT,
         &OutputFeatures::default(),
         &alice_ts_interface.key_manager_handle,
     )
     .await;
-    for (tx_id, uo) in [(tx_id_1, uo_1), (tx_id_2, uo_2), (tx_id_3, uo_3)] {
-        alice_ts_interface
-            .output_manager_service_handle
-            .add_output_with_tx_id(tx_id, uo, None)
-            .await
-            .unwrap();
-    }
+    alice_ts_interface
+        .output_manager_service_handle
+        .add_output(uo, None)
+        .await
+        .unwrap();
+    let funding_tx_id = alice_ts_interface
+        .transaction_service_handle
+        .send_one_sided_transaction(
+            bob_address.clone(),
+            1_000_000 * uT,
+            UtxoSelectionCriteria::default(),
+            OutputFeatures::default(),
+            5 * uT,
+            "Funding".to_string(),
+        )
+        .await
+        .unwrap();
+    let funding_outputs = alice_ts_interface
+        .transaction_service_handle
+        .get_completed_transaction(funding_tx_id)
+        .await
+        .unwrap()
+        .transaction
+        .body
+        .outputs()
+        .clone();
+    let recovered = bob_ts_interface
+        .output_manager_service_handle
+        .scan_outputs_for_one_sided_payments(funding_outputs.clone())
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), 1);
+    let recovered = watch_only_ts_interface
+        .output_manager_service_handle
+        .scan_outputs_for_one_sided_payments(funding_outputs)
+        .await
+        .unwrap();
+    assert_eq!(recovered.len(), 1);
 
-    for tx_id in [tx_id_1, tx_id_2, tx_id_3] {
-        let transaction = alice_ts_interface
+    // The watch-only wallet cannot export a transaction before it knows the chain tip
+    watch_only_ts_interface
+        .base_node_service_event_publisher
+        .send(Arc::new(BaseNodeEvent::NewBlockDetected(FixedHash::zero(), 10)))
+        .unwrap();
+    let carol_address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let amount = 200_000 * uT;
+    let mut attempts = 0;
+    let unsigned = loop {
+        match watch_only_ts_interface
             .transaction_service_handle
-            .get_any_transaction(tx_id)
+            .create_unsigned_transaction(
+                carol_address.clone(),
+                amount,
+                UtxoSelectionCriteria::default(),
+                5 * uT,
+                "Offline".to_string(),
+            )
             .await
-            .unwrap()
-            .unwrap();
-        if tx_id == tx_id_1 {
-            if let WalletTransaction::Completed(tx) = &transaction {
-                assert_eq!(tx.status, TransactionStatus::Imported);
-            } else {
-                panic!("Should find a complete Imported transaction");
-            }
-        }
-        if tx_id == tx_id_2 {
-            if let WalletTransaction::Completed(tx) = &transaction {
-                assert_eq!(tx.status, TransactionStatus::FauxUnconfirmed);
-            } else {
-                panic!("Should find a complete FauxUnconfirmed transaction");
-            }
-        }
-        if tx_id == tx_id_3 {
-            if let WalletTransaction::Completed(tx) = &transaction {
-                assert_eq!(tx.status, TransactionStatus::FauxConfirmed);
-            } else {
-                panic!("Should find a complete FauxConfirmed transaction");
-            }
+        {
+            Ok(unsigned) => break unsigned,
+            Err(TransactionServiceError::OfflineTransactionError(_)) if attempts < 50 => {
+                attempts += 1;
+                sleep(Duration::from_millis(100)).await;
+            },
+            Err(e) => panic!("Could not create the unsigned transaction: {}", e),
         }
-    }
+    };
+    let unsigned = UnsignedTransaction::from_bytes(&unsigned.to_bytes().unwrap()).unwrap();
+    let tx_id = unsigned.tx_id;
 
-    // This will change the status of the imported transaction
-    alice_ts_interface
-        .output_manager_service_event_publisher
-        .send(Arc::new(OutputManagerEvent::TxoValidationSuccess(1u64)))
+    // A transaction that pays the destination less than requested is refused
+    let mut tampered = unsigned.clone();
+    tampered.amount = amount - 1000 * uT;
+    let signed = bob_ts_interface
+        .transaction_service_handle
+        .sign_unsigned_transaction(tampered)
+        .await
+        .unwrap();
+    let err = watch_only_ts_interface
+        .transaction_service_handle
+        .import_signed_transaction(signed)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TransactionServiceError::OfflineTransactionError(_)));
+    bob_ts_interface
+        .output_manager_service_handle
+        .cancel_transaction(tx_id)
+        .await
         .unwrap();
 
-    let mut found_imported = false;
-    let mut found_faux_unconfirmed = false;
-    let mut found_faux_confirmed = false;
-    for _ in 0..20 {
-        sleep(Duration::from_secs(1)).await;
-        for tx_id in [tx_id_1, tx_id_2, tx_id_3] {
-            let transaction = alice_ts_interface
-                .transaction_service_handle
-                .get_any_transaction(tx_id)
-                .await
-                .unwrap()
-                .unwrap();
-            if let WalletTransaction::Completed(tx) = transaction {
-                if tx_id == tx_id_1 && tx.status == TransactionStatus::FauxUnconfirmed && !found_imported {
-                    found_imported = true;
-                }
-                if tx_id == tx_id_2 && tx.status == TransactionStatus::FauxUnconfirmed && !found_faux_unconfirmed {
-                    found_faux_unconfirmed = true;
-                }
-                if tx_id == tx_id_3 && tx.status == TransactionStatus::FauxConfirmed && !found_faux_confirmed {
-                    found_faux_confirmed = true;
-                }
-            }
-        }
-        if found_imported && found_faux_unconfirmed && found_faux_confirmed {
-            break;
-        }
-    }
-    assert!(
-        found_imported && found_faux_unconfirmed && found_faux_confirmed,
-        "Should have found the updated statuses"
+    let signed = bob_ts_interface
+        .transaction_service_handle
+        .sign_unsigned_transaction(unsigned)
+        .await
+        .unwrap();
+    let signed = SignedTransaction::from_bytes(&signed.to_bytes().unwrap()).unwrap();
+    assert_eq!(
+        watch_only_ts_interface
+            .transaction_service_handle
+            .import_signed_transaction(signed)
+            .await
+            .unwrap(),
+        tx_id
     );
-}
-
-#[tokio::test]
-async fn test_get_fee_per_gram_per_block_basic() {
-    let factories = CryptoFactories::default();
-    let (connection, _temp_dir) = make_wallet_database_connection(None);
-    let mut alice_ts_interface = setup_transaction_service_no_comms(factories, connection, None).await;
-    let stats = vec![base_node_proto::MempoolFeePerGramStat {
-        order: 0,
-        min_fee_per_gram: 1,
-        avg_fee_per_gram: 2,
-        max_fee_per_gram: 3,
-    }];
-    alice_ts_interface
-        .base_node_rpc_mock_state
-        .set_fee_per_gram_stats_response(base_node_proto::GetMempoolFeePerGramStatsResponse { stats: stats.clone() });
 
-    let estimates = alice_ts_interface
+    let completed = watch_only_ts_interface
         .transaction_service_handle
-        .get_fee_per_gram_stats_per_block(10)
+        .get_completed_transaction(tx_id)
         .await
         .unwrap();
-    assert_eq!(estimates.stats, stats.into_iter().map(Into::into).collect::<Vec<_>>());
-    assert_eq!(estimates.stats.len(), 1)
+    assert_eq!(completed.amount, amount);
+    assert_eq!(completed.destination_address, carol_address);
+    // The change paid back to Bob's address was found by the watch-only wallet
+    let balance = watch_only_ts_interface
+        .output_manager_service_handle
+        .get_balance()
+        .await
+        .unwrap();
+    assert_eq!(
+        balance.available_balance + balance.pending_incoming_balance,
+        1_000_000 * uT - amount - completed.fee
+    );
 }
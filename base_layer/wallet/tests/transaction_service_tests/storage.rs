@@ -21,23 +21,39 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use core::default::Default;
-use std::mem::size_of;
+use std::{mem::size_of, time::Duration};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
-use chrono::{NaiveDateTime, Utc};
+use chrono::{Duration as ChronoDuration, NaiveDateTime, Utc};
 use minotari_wallet::{
     storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
     test_utils::create_consensus_constants,
-    transaction_service::storage::{
-        database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
-        models::{
-            CompletedTransaction,
-            InboundTransaction,
-            OutboundTransaction,
-            TxCancellationReason,
-            WalletTransaction,
+    transaction_service::{
+        handle::{FeePriority, TransactionEvent},
+        storage::{
+            database::{DbKeyValuePair, TransactionBackend, TransactionDatabase, WriteOperation},
+            models::{
+                BatchedPayment,
+                CompletedTransaction,
+                HeightOrTime,
+                InboundTransaction,
+                OutboundMessageStatus,
+                OutboundMessageType,
+                OutboundTransaction,
+                QueuedOutboundMessage,
+                QueuedTransaction,
+                QueuedTransactionStatus,
+                RecurringPayment,
+                RecurringPaymentStatus,
+                ScheduledTransaction,
+                ScheduledTransactionStatus,
+                SendTemplate,
+                TxCancellationReason,
+                WalletTransaction,
+            },
+            sqlite_db::TransactionServiceSqliteDatabase,
         },
-        sqlite_db::TransactionServiceSqliteDatabase,
+        tasks::import_history::{import_transaction_history, HistoricalTransaction},
     },
 };
 use rand::{rngs::OsRng, RngCore};
@@ -525,6 +541,19 @@ pub async fn test_db_backend<T: TransactionBackend + 'static>(backend: T) {
 
     let unmined_txs = db.fetch_unconfirmed_transactions_info().unwrap();
     assert_eq!(unmined_txs.len(), 5);
+
+    // A reorg back to height 9 unmines the transaction mined at height 10
+    db.set_transaction_mined_height(completed_txs[0].tx_id, 10, FixedHash::zero(), 0, 5, true, false)
+        .unwrap();
+    assert!(db
+        .set_transactions_mined_above_height_as_unmined(10)
+        .unwrap()
+        .is_empty());
+    assert_eq!(db.set_transactions_mined_above_height_as_unmined(9).unwrap(), vec![
+        completed_txs[0].tx_id
+    ]);
+    let unmined_txs = db.fetch_unconfirmed_transactions_info().unwrap();
+    assert_eq!(unmined_txs.len(), 5);
 }
 
 #[tokio::test]
@@ -543,6 +572,25 @@ pub async fn test_transaction_service_sqlite_db() {
     test_db_backend(TransactionServiceSqliteDatabase::new(connection, cipher)).await;
 }
 
+#[cfg(feature = "postgres")]
+#[tokio::test]
+pub async fn test_transaction_service_postgres_db() {
+    use minotari_wallet::{
+        storage::postgres_utilities::run_migration_and_create_postgres_connection,
+        test_utils::make_postgres_database_url,
+        transaction_service::storage::postgres_db::TransactionServicePostgresDatabase,
+    };
+
+    let connection = run_migration_and_create_postgres_connection(&make_postgres_database_url(), 4).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let cipher = XChaCha20Poly1305::new(key_ga);
+
+    test_db_backend(TransactionServicePostgresDatabase::new(connection, cipher)).await;
+}
+
 #[tokio::test]
 async fn import_tx_and_read_it_from_db() {
     let db_name = format!("{}.sqlite3", random::string(8));
@@ -661,3 +709,824 @@ async fn import_tx_and_read_it_from_db() {
     assert_eq!(db_tx.first().unwrap().tx_id, TxId::from(3u64));
     assert_eq!(db_tx.first().unwrap().mined_height, Some(7));
 }
+
+#[tokio::test]
+async fn outbound_message_queue_survives_reconnect() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+
+    let tx_id = TxId::from(42u64);
+    let address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    {
+        let connection = run_migration_and_create_sqlite_connection(db_path.clone(), 16).unwrap();
+        let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+            connection,
+            XChaCha20Poly1305::new(key_ga),
+        ));
+        db.queue_outbound_message(QueuedOutboundMessage::new(
+            tx_id,
+            OutboundMessageType::TransactionFinalized,
+            address.clone(),
+            vec![1, 2, 3, 4],
+            Utc::now().naive_utc(),
+        ))
+        .unwrap();
+        db.record_outbound_message_attempt(
+            tx_id,
+            OutboundMessageType::TransactionFinalized,
+            OutboundMessageStatus::Pending,
+        )
+        .unwrap();
+    }
+
+    // Reopen the database as a restarted wallet would
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+    let pending = db.fetch_pending_outbound_messages().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].tx_id, tx_id);
+    assert_eq!(pending[0].message_type, OutboundMessageType::TransactionFinalized);
+    assert_eq!(pending[0].destination_address, address);
+    assert_eq!(pending[0].payload, vec![1, 2, 3, 4]);
+    assert_eq!(pending[0].attempts, 1);
+    assert!(pending[0].last_attempt_at.is_some());
+
+    // Queuing the message again keeps the queued message along with its delivery history
+    db.queue_outbound_message(QueuedOutboundMessage::new(
+        tx_id,
+        OutboundMessageType::TransactionFinalized,
+        address.clone(),
+        vec![5, 6, 7, 8],
+        Utc::now().naive_utc(),
+    ))
+    .unwrap();
+    let queued = db
+        .fetch_outbound_message(tx_id, OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .unwrap();
+    assert_eq!(queued.payload, vec![1, 2, 3, 4]);
+    assert_eq!(queued.attempts, 1);
+    assert_eq!(queued.status, OutboundMessageStatus::Pending);
+    assert!(db
+        .fetch_outbound_message(tx_id, OutboundMessageType::ReceiverPartialTransactionReply)
+        .unwrap()
+        .is_none());
+
+    db.record_outbound_message_attempt(
+        tx_id,
+        OutboundMessageType::TransactionFinalized,
+        OutboundMessageStatus::Delivered,
+    )
+    .unwrap();
+    assert!(db.fetch_pending_outbound_messages().unwrap().is_empty());
+
+    db.remove_outbound_messages(tx_id).unwrap();
+    assert!(db
+        .record_outbound_message_attempt(
+            tx_id,
+            OutboundMessageType::TransactionFinalized,
+            OutboundMessageStatus::Delivered
+        )
+        .is_err());
+}
+
+#[test]
+fn delivered_and_expired_outbound_messages_are_purged() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+    let address = TariAddress::new(
+        PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+        Network::LocalNet,
+    );
+    let queue = |tx_id: u64, created_at: NaiveDateTime| {
+        db.queue_outbound_message(QueuedOutboundMessage::new(
+            tx_id.into(),
+            OutboundMessageType::TransactionFinalized,
+            address.clone(),
+            vec![1, 2, 3, 4],
+            created_at,
+        ))
+        .unwrap();
+    };
+    let now = Utc::now().naive_utc();
+    queue(1, now);
+    queue(2, now);
+    queue(3, now - ChronoDuration::days(4));
+    db.record_outbound_message_attempt(
+        1u64.into(),
+        OutboundMessageType::TransactionFinalized,
+        OutboundMessageStatus::Delivered,
+    )
+    .unwrap();
+
+    assert_eq!(db.purge_outbound_messages(now - ChronoDuration::days(3)).unwrap(), 2);
+    let pending = db.fetch_pending_outbound_messages().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].tx_id, TxId::from(2u64));
+    assert!(db
+        .fetch_outbound_message(1u64.into(), OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn confirmation_threshold_can_be_overridden() {
+    let completed_tx = |status, confirmations, mined_height| {
+        let mut tx = CompletedTransaction::new(
+            TxId::from(1u64),
+            TariAddress::default(),
+            TariAddress::default(),
+            MicroMinotari::from(100000),
+            MicroMinotari::from(0),
+            Transaction::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                PrivateKey::random(&mut OsRng),
+                PrivateKey::random(&mut OsRng),
+            ),
+            status,
+            "message".to_string(),
+            Utc::now().naive_utc(),
+            TransactionDirection::Inbound,
+            None,
+            mined_height,
+            None,
+        );
+        tx.confirmations = confirmations;
+        tx
+    };
+
+    // Confirmations are counted from the tip when it is known
+    let tx = completed_tx(TransactionStatus::MinedUnconfirmed, Some(2), Some(100));
+    assert_eq!(
+        tx.status_with_confirmations_required(3, Some(103)),
+        TransactionStatus::MinedConfirmed
+    );
+    assert_eq!(
+        tx.status_with_confirmations_required(4, Some(103)),
+        TransactionStatus::MinedUnconfirmed
+    );
+    // Otherwise the recorded count is used
+    assert_eq!(
+        tx.status_with_confirmations_required(2, None),
+        TransactionStatus::MinedConfirmed
+    );
+    assert_eq!(
+        tx.status_with_confirmations_required(3, None),
+        TransactionStatus::MinedUnconfirmed
+    );
+
+    // A stricter threshold than the wallet's can make a confirmed transaction unconfirmed again
+    let tx = completed_tx(TransactionStatus::MinedConfirmed, Some(5), Some(100));
+    assert_eq!(
+        tx.status_with_confirmations_required(10, Some(105)),
+        TransactionStatus::MinedUnconfirmed
+    );
+    let tx = completed_tx(TransactionStatus::FauxConfirmed, Some(5), Some(100));
+    assert_eq!(
+        tx.status_with_confirmations_required(10, None),
+        TransactionStatus::FauxUnconfirmed
+    );
+    let tx = completed_tx(TransactionStatus::FauxUnconfirmed, Some(1), Some(100));
+    assert_eq!(
+        tx.status_with_confirmations_required(1, None),
+        TransactionStatus::FauxConfirmed
+    );
+
+    // Transactions that have not been mined are unaffected
+    let tx = completed_tx(TransactionStatus::Broadcast, None, None);
+    assert_eq!(
+        tx.status_with_confirmations_required(0, Some(105)),
+        TransactionStatus::Broadcast
+    );
+}
+
+#[test]
+fn counterparty_aliases_are_persisted() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let tx_ids = [TxId::from(1u64), TxId::from(2u64), TxId::from(3u64)];
+    db.set_counterparty_alias(tx_ids[0], "Alice".to_string()).unwrap();
+    db.set_counterparty_alias(tx_ids[1], "Bob".to_string()).unwrap();
+    // Setting the alias again replaces it
+    db.set_counterparty_alias(tx_ids[1], "Bobby".to_string()).unwrap();
+
+    let aliases = db.fetch_counterparty_aliases(&tx_ids).unwrap();
+    assert_eq!(aliases.len(), 2);
+    assert_eq!(aliases.get(&tx_ids[0]).unwrap(), "Alice");
+    assert_eq!(aliases.get(&tx_ids[1]).unwrap(), "Bobby");
+    assert!(aliases.get(&tx_ids[2]).is_none());
+
+    let aliases = db.fetch_counterparty_aliases(&tx_ids[..1]).unwrap();
+    assert_eq!(aliases.len(), 1);
+}
+
+#[test]
+fn transaction_tags_are_persisted_and_searchable() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let address = || {
+        TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        )
+    };
+    let history = (1..=3u64)
+        .map(|i| HistoricalTransaction {
+            tx_id: Some(TxId::from(i)),
+            source_address: address(),
+            destination_address: address(),
+            amount: MicroMinotari::from(i * 1000),
+            fee: MicroMinotari::from(0),
+            direction: TransactionDirection::Outbound,
+            timestamp: Utc::now().naive_utc(),
+            mined_height: None,
+            mined_timestamp: None,
+            message: ["Rent for March", "Groceries", "Pays 100% of the bill"][i as usize - 1].to_string(),
+        })
+        .collect::<Vec<_>>();
+    import_transaction_history(&db, history).unwrap();
+    let tx_ids = [TxId::from(1u64), TxId::from(2u64), TxId::from(3u64)];
+
+    db.add_transaction_tags(tx_ids[0], vec!["home".to_string(), "monthly".to_string()])
+        .unwrap();
+    db.add_transaction_tags(tx_ids[1], vec!["home".to_string()]).unwrap();
+    // Adding a tag that is already attached is ignored
+    db.add_transaction_tags(tx_ids[1], vec!["home".to_string(), "food".to_string()])
+        .unwrap();
+
+    let tags = db.fetch_transaction_tags(&tx_ids).unwrap();
+    assert_eq!(tags.len(), 2);
+    assert_eq!(tags.get(&tx_ids[0]).unwrap(), &vec![
+        "home".to_string(),
+        "monthly".to_string()
+    ]);
+    assert_eq!(tags.get(&tx_ids[1]).unwrap(), &vec![
+        "food".to_string(),
+        "home".to_string()
+    ]);
+    assert!(tags.get(&tx_ids[2]).is_none());
+
+    assert_eq!(db.fetch_tx_ids_by_tag("home").unwrap(), vec![tx_ids[0], tx_ids[1]]);
+    assert!(db.fetch_tx_ids_by_tag("hom").unwrap().is_empty());
+
+    // Search matches parts of tags and messages, ignoring case
+    assert_eq!(db.search_transactions("MONTH").unwrap(), vec![tx_ids[0]]);
+    assert_eq!(db.search_transactions("groc").unwrap(), vec![tx_ids[1]]);
+    assert_eq!(db.search_transactions("o").unwrap(), tx_ids.to_vec());
+    // LIKE wildcards are matched literally
+    assert_eq!(db.search_transactions("100%").unwrap(), vec![tx_ids[2]]);
+    assert!(db.search_transactions("_").unwrap().is_empty());
+
+    db.remove_transaction_tags(tx_ids[0], vec!["monthly".to_string(), "unknown".to_string()])
+        .unwrap();
+    assert_eq!(
+        db.fetch_transaction_tags(&tx_ids[..1])
+            .unwrap()
+            .get(&tx_ids[0])
+            .unwrap(),
+        &vec!["home".to_string()]
+    );
+    assert!(db.search_transactions("monthly").unwrap().is_empty());
+}
+
+#[test]
+fn batched_payments_are_persisted_in_order() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let tx_id = TxId::from(1u64);
+    let payments = (1..=3u8)
+        .map(|i| BatchedPayment {
+            tx_id,
+            destination_address: TariAddress::new(
+                PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+                Network::LocalNet,
+            ),
+            amount: MicroMinotari::from(u64::from(i) * 1000),
+            message: format!("Payout {}", i),
+            output_hash: FixedHash::from([i; 32]),
+        })
+        .collect::<Vec<_>>();
+    db.insert_batched_payments(payments.clone()).unwrap();
+
+    assert_eq!(db.fetch_batched_payments(tx_id).unwrap(), payments);
+    assert!(db.fetch_batched_payments(TxId::from(2u64)).unwrap().is_empty());
+}
+
+#[test]
+fn scheduled_transactions_only_transition_from_expected_status() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let created_at = Utc::now().naive_utc();
+    let scheduled = ScheduledTransaction {
+        id: 42,
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        ),
+        amount: MicroMinotari::from(5000),
+        fee_per_gram: MicroMinotari::from(5),
+        message: "Rent".to_string(),
+        one_sided: true,
+        execute_at: HeightOrTime::Height(100),
+        expires_at: Some(HeightOrTime::Time(created_at + ChronoDuration::days(1))),
+        status: ScheduledTransactionStatus::Pending,
+        tx_id: None,
+        failure_reason: None,
+        created_at,
+    };
+    db.insert_scheduled_transaction(scheduled.clone()).unwrap();
+    assert!(!scheduled.is_due(Some(99), created_at));
+    assert!(scheduled.is_due(Some(100), created_at));
+
+    let pending = db
+        .fetch_scheduled_transactions(Some(ScheduledTransactionStatus::Pending))
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, scheduled.id);
+    assert_eq!(pending[0].execute_at, scheduled.execute_at);
+
+    db.update_scheduled_transaction_status(
+        42,
+        ScheduledTransactionStatus::Pending,
+        ScheduledTransactionStatus::Triggered,
+        None,
+        None,
+    )
+    .unwrap();
+    // A triggered schedule can no longer be cancelled
+    assert!(db
+        .update_scheduled_transaction_status(
+            42,
+            ScheduledTransactionStatus::Pending,
+            ScheduledTransactionStatus::Cancelled,
+            None,
+            None,
+        )
+        .is_err());
+    db.update_scheduled_transaction_status(
+        42,
+        ScheduledTransactionStatus::Triggered,
+        ScheduledTransactionStatus::Completed,
+        Some(TxId::from(7u64)),
+        None,
+    )
+    .unwrap();
+
+    let all = db.fetch_scheduled_transactions(None).unwrap();
+    assert_eq!(all[0].status, ScheduledTransactionStatus::Completed);
+    assert_eq!(all[0].tx_id, Some(TxId::from(7u64)));
+    assert!(db
+        .fetch_scheduled_transactions(Some(ScheduledTransactionStatus::Pending))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn queued_transactions_expire_and_only_transition_from_expected_status() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let queued_at = Utc::now().naive_utc();
+    let queued = QueuedTransaction {
+        id: 7,
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        ),
+        amount: MicroMinotari::from(5000),
+        fee_per_gram: MicroMinotari::from(5),
+        message: "Sent from the train".to_string(),
+        one_sided: false,
+        status: QueuedTransactionStatus::Queued,
+        tx_id: None,
+        failure_reason: None,
+        queued_at,
+    };
+    db.insert_queued_transaction(queued.clone()).unwrap();
+    let max_age = Duration::from_secs(3600);
+    assert!(!queued.is_expired(max_age, queued_at + ChronoDuration::minutes(59)));
+    assert!(queued.is_expired(max_age, queued_at + ChronoDuration::hours(1)));
+
+    let pending = db
+        .fetch_queued_transactions(Some(QueuedTransactionStatus::Queued))
+        .unwrap();
+    assert_eq!(pending, vec![queued]);
+
+    db.update_queued_transaction_status(
+        7,
+        QueuedTransactionStatus::Queued,
+        QueuedTransactionStatus::Sending,
+        None,
+        None,
+    )
+    .unwrap();
+    // A transaction that is being sent can no longer be cancelled
+    assert!(db
+        .update_queued_transaction_status(
+            7,
+            QueuedTransactionStatus::Queued,
+            QueuedTransactionStatus::Cancelled,
+            None,
+            None,
+        )
+        .is_err());
+    db.update_queued_transaction_status(
+        7,
+        QueuedTransactionStatus::Sending,
+        QueuedTransactionStatus::Sent,
+        Some(TxId::from(9u64)),
+        None,
+    )
+    .unwrap();
+
+    let all = db.fetch_queued_transactions(None).unwrap();
+    assert_eq!(all[0].status, QueuedTransactionStatus::Sent);
+    assert_eq!(all[0].tx_id, Some(TxId::from(9u64)));
+    assert!(!all[0].is_expired(max_age, queued_at + ChronoDuration::days(1)));
+}
+
+#[test]
+fn transaction_events_are_journaled_and_pruned() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let events = vec![
+        TransactionEvent::ReceivedTransaction(TxId::from(1u64)),
+        TransactionEvent::TransactionMined {
+            tx_id: TxId::from(1u64),
+            is_valid: true,
+        },
+        TransactionEvent::TransactionCancelled(TxId::from(2u64), TxCancellationReason::UserCancelled),
+    ];
+    for event in &events {
+        db.journal_transaction_event(event, 2).unwrap();
+    }
+
+    // Only the two most recent events are kept
+    let journaled = db.fetch_transaction_events_since(0).unwrap();
+    assert_eq!(
+        journaled.iter().map(|e| e.event.clone()).collect::<Vec<_>>(),
+        events[1..].to_vec()
+    );
+    let since = db.fetch_transaction_events_since(journaled[0].cursor).unwrap();
+    assert_eq!(since.len(), 1);
+    assert_eq!(since[0].event, events[2]);
+    assert!(db.fetch_transaction_events_since(since[0].cursor).unwrap().is_empty());
+}
+
+#[test]
+fn send_templates_are_persisted() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let now = NaiveDateTime::from_timestamp_opt(Utc::now().timestamp(), 0).unwrap();
+    let mut payroll = SendTemplate {
+        id: 1,
+        name: "payroll".to_string(),
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        ),
+        default_amount: Some(MicroMinotari::from(1_000)),
+        message: "Salary".to_string(),
+        fee_priority: FeePriority::Medium,
+        one_sided: true,
+        created_at: now,
+        updated_at: now,
+    };
+    let rent = SendTemplate {
+        id: 2,
+        name: "rent".to_string(),
+        default_amount: None,
+        fee_priority: FeePriority::Low,
+        one_sided: false,
+        ..payroll.clone()
+    };
+    db.insert_send_template(rent.clone()).unwrap();
+    db.insert_send_template(payroll.clone()).unwrap();
+    assert_eq!(db.fetch_send_templates().unwrap(), vec![payroll.clone(), rent.clone()]);
+
+    payroll.default_amount = Some(MicroMinotari::from(2_000));
+    payroll.fee_priority = FeePriority::High;
+    db.update_send_template(payroll.clone()).unwrap();
+    assert_eq!(db.fetch_send_template(1).unwrap(), Some(payroll));
+
+    db.delete_send_template(2).unwrap();
+    assert!(db.fetch_send_template(2).unwrap().is_none());
+    assert!(db.delete_send_template(2).is_err());
+}
+
+#[test]
+fn imported_history_is_recorded_once() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+
+    let address = || {
+        TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        )
+    };
+    let now = NaiveDateTime::from_timestamp_opt(Utc::now().timestamp(), 0).unwrap();
+    let history = vec![
+        HistoricalTransaction {
+            tx_id: Some(TxId::from(1u64)),
+            source_address: address(),
+            destination_address: address(),
+            amount: MicroMinotari::from(5_000),
+            fee: MicroMinotari::from(0),
+            direction: TransactionDirection::Inbound,
+            timestamp: now - ChronoDuration::days(30),
+            mined_height: Some(100),
+            mined_timestamp: None,
+            message: "Exchange withdrawal".to_string(),
+        },
+        HistoricalTransaction {
+            tx_id: None,
+            source_address: address(),
+            destination_address: address(),
+            amount: MicroMinotari::from(1_000),
+            fee: MicroMinotari::from(25),
+            direction: TransactionDirection::Outbound,
+            timestamp: now - ChronoDuration::days(10),
+            mined_height: None,
+            mined_timestamp: None,
+            message: "Old wallet".to_string(),
+        },
+    ];
+
+    let imported = import_transaction_history(&db, history.clone()).unwrap();
+    assert_eq!(imported.len(), 2);
+    let tx = db.get_completed_transaction(TxId::from(1u64)).unwrap();
+    assert_eq!(tx.status, TransactionStatus::ImportedHistory);
+    assert_eq!(tx.amount, MicroMinotari::from(5_000));
+    assert_eq!(tx.message, "Exchange withdrawal");
+    assert!(db.fetch_unconfirmed_transactions_info().unwrap().is_empty());
+
+    // Only the transaction without an original id is recorded again
+    let imported = import_transaction_history(&db, history).unwrap();
+    assert_eq!(imported.len(), 1);
+    assert_ne!(imported[0], TxId::from(1u64));
+}
+
+fn recurring_payment(id: u64, next_payment_at: NaiveDateTime, end_at: Option<NaiveDateTime>) -> RecurringPayment {
+    RecurringPayment {
+        id,
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        ),
+        amount: MicroMinotari::from(5000),
+        fee_per_gram: MicroMinotari::from(5),
+        message: "Subscription".to_string(),
+        one_sided: true,
+        interval: Duration::from_secs(60 * 60),
+        next_payment_at,
+        end_at,
+        status: RecurringPaymentStatus::Active,
+        payments_made: 0,
+        last_tx_id: None,
+        failure_reason: None,
+        created_at: next_payment_at,
+    }
+}
+
+#[test]
+fn recurring_payment_schedule_skips_missed_periods_and_stops_at_end_date() {
+    let start = NaiveDateTime::from_timestamp_opt(1_700_000_000, 0).unwrap();
+    let hour = ChronoDuration::hours(1);
+    let payment = recurring_payment(1, start, Some(start + ChronoDuration::hours(3)));
+
+    assert!(!payment.is_due(start - ChronoDuration::seconds(1)));
+    assert!(payment.is_due(start));
+    // Sent on time, the following payment is a full interval after the one that was due
+    assert_eq!(payment.following_payment_at(start), start + hour);
+    assert_eq!(
+        payment.following_payment_at(start + ChronoDuration::minutes(59)),
+        start + hour
+    );
+    // After downtime spanning several intervals, the missed payments are not caught up on
+    let back_online = start + ChronoDuration::minutes(150);
+    assert!(payment.is_due(back_online));
+    assert_eq!(payment.following_payment_at(back_online), back_online + hour);
+    assert_eq!(payment.following_payment_at(start + hour), start + hour + hour);
+
+    // No payments are due at or after the end date
+    let end_at = start + ChronoDuration::hours(3);
+    assert!(!payment.has_ended(end_at - ChronoDuration::seconds(1)));
+    assert!(payment.has_ended(end_at));
+    assert!(!payment.is_due(end_at));
+    assert!(recurring_payment(2, start, None).is_due(end_at + ChronoDuration::days(365)));
+
+    // Plans that are not active are never due and never end
+    let mut paused = payment;
+    paused.status = RecurringPaymentStatus::Paused;
+    assert!(!paused.is_due(start));
+    assert!(!paused.has_ended(end_at));
+}
+
+#[test]
+fn recurring_payments_are_persisted_and_cancelled() {
+    let db_name = format!("{}.sqlite3", random::string(8));
+    let db_tempdir = tempdir().unwrap();
+    let db_folder = db_tempdir.path().to_str().unwrap().to_string();
+    let db_path = format!("{}/{}", db_folder, db_name);
+
+    let mut key = [0u8; size_of::<Key>()];
+    OsRng.fill_bytes(&mut key);
+    let key_ga = Key::from_slice(&key);
+
+    let start = NaiveDateTime::from_timestamp_opt(1_700_000_000, 0).unwrap();
+    let first = recurring_payment(1, start, Some(start + ChronoDuration::days(1)));
+    let second = recurring_payment(2, start + ChronoDuration::minutes(1), None);
+    let following = first.following_payment_at(start);
+    {
+        let connection = run_migration_and_create_sqlite_connection(db_path.clone(), 16).unwrap();
+        let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+            connection,
+            XChaCha20Poly1305::new(key_ga),
+        ));
+        db.insert_recurring_payment(first.clone()).unwrap();
+        db.insert_recurring_payment(second.clone()).unwrap();
+
+        // A due payment can only be claimed once
+        db.advance_recurring_payment(1, start, following).unwrap();
+        assert!(db.advance_recurring_payment(1, start, following).is_err());
+        db.record_recurring_payment_result(1, Some(TxId::from(7u64)), None)
+            .unwrap();
+    }
+
+    // Reopen the database as a restarted wallet would
+    let connection = run_migration_and_create_sqlite_connection(db_path, 16).unwrap();
+    let db = TransactionDatabase::new(TransactionServiceSqliteDatabase::new(
+        connection,
+        XChaCha20Poly1305::new(key_ga),
+    ));
+    let all = db.fetch_recurring_payments(None).unwrap();
+    assert_eq!(all.len(), 2);
+    let mut expected = first;
+    expected.next_payment_at = following;
+    expected.payments_made = 1;
+    expected.last_tx_id = Some(TxId::from(7u64));
+    assert_eq!(all[0], expected);
+    assert_eq!(all[1], second);
+
+    // Cancelling stops the plan from being claimed, resumed or cancelled again
+    db.update_recurring_payment_status(
+        2,
+        &[RecurringPaymentStatus::Active, RecurringPaymentStatus::Paused],
+        RecurringPaymentStatus::Cancelled,
+        None,
+        None,
+    )
+    .unwrap();
+    assert!(db
+        .advance_recurring_payment(2, second.next_payment_at, second.following_payment_at(start))
+        .is_err());
+    assert!(db
+        .update_recurring_payment_status(
+            2,
+            &[RecurringPaymentStatus::Paused],
+            RecurringPaymentStatus::Active,
+            None,
+            None,
+        )
+        .is_err());
+    assert!(db
+        .update_recurring_payment_status(
+            2,
+            &[RecurringPaymentStatus::Active, RecurringPaymentStatus::Paused],
+            RecurringPaymentStatus::Cancelled,
+            None,
+            None,
+        )
+        .is_err());
+
+    let active = db
+        .fetch_recurring_payments(Some(RecurringPaymentStatus::Active))
+        .unwrap();
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].id, 1);
+    let cancelled = db
+        .fetch_recurring_payments(Some(RecurringPaymentStatus::Cancelled))
+        .unwrap();
+    assert_eq!(cancelled.len(), 1);
+    assert_eq!(cancelled[0].id, 2);
+}
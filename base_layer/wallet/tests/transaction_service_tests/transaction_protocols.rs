@@ -20,40 +20,65 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::HashMap, mem::size_of, sync::Arc, time::Duration};
+use std::{collections::HashMap, convert::TryInto, mem::size_of, sync::Arc, time::Duration};
 
 use chacha20poly1305::{Key, KeyInit, XChaCha20Poly1305};
 use chrono::Utc;
-use futures::StreamExt;
+use futures::{channel::mpsc, StreamExt};
 use minotari_wallet::{
     connectivity_service::{create_wallet_connectivity_mock, WalletConnectivityMock},
     output_manager_service::{
         error::OutputManagerError,
         handle::{OutputManagerHandle, OutputManagerRequest, OutputManagerResponse},
+        UtxoSelectionCriteria,
     },
     storage::sqlite_utilities::run_migration_and_create_sqlite_connection,
     transaction_service::{
-        config::TransactionServiceConfig,
+        config::{TransactionRoutingMechanism, TransactionServiceConfig},
         error::TransactionServiceError,
-        handle::{TransactionEvent, TransactionEventReceiver, TransactionEventSender},
+        handle::{BatchPayment, TransactionEvent, TransactionEventReceiver, TransactionEventSender},
         protocols::{
+            atomic_swap_protocol::{
+                check_participant_lock_height,
+                find_redeemed_pre_images,
+                hash_lock_for,
+                redeemed_message,
+                refunded_message,
+                run_atomic_swap_message_handler,
+                INITIATOR_LOCK_BLOCKS,
+                LOCK_HEIGHT_SAFETY_MARGIN,
+                PARTICIPANT_LOCK_BLOCKS,
+            },
+            transaction_batch_send_protocol::TransactionBatchSendProtocol,
             transaction_broadcast_protocol::TransactionBroadcastProtocol,
             transaction_validation_protocol::TransactionValidationProtocol,
         },
         service::TransactionServiceResources,
         storage::{
             database::TransactionDatabase,
-            models::{CompletedTransaction, TxCancellationReason},
+            models::{
+                AtomicSwap,
+                AtomicSwapRole,
+                AtomicSwapState,
+                CompletedTransaction,
+                OutboundMessageStatus,
+                OutboundMessageType,
+                QueuedOutboundMessage,
+                TxCancellationReason,
+            },
             sqlite_db::TransactionServiceSqliteDatabase,
         },
+        tasks::send_queued_outbound_message::deliver_queued_outbound_message,
     },
     util::{wallet_identity::WalletIdentity, watch::Watch},
 };
+use prost::Message;
 use rand::{rngs::OsRng, RngCore};
 use tari_common::configuration::Network;
 use tari_common_types::{
     tari_address::TariAddress,
     transaction::{TransactionDirection, TransactionStatus, TxId},
+    types::{FixedHash, PrivateKey, PublicKey},
 };
 use tari_comms::{
     peer_manager::PeerFeatures,
@@ -61,16 +86,30 @@ use tari_comms::{
     test_utils::node_identity::build_node_identity,
     NodeIdentity,
 };
-use tari_comms_dht::outbound::mock::{create_outbound_service_mock, OutboundServiceMockState};
+use tari_comms_dht::outbound::mock::{
+    create_outbound_service_mock,
+    MockBehaviour,
+    OutboundServiceMockState,
+    ResponseType,
+};
 use tari_core::{
     base_node::{
-        proto::wallet_rpc::{TxLocation, TxQueryResponse, TxSubmissionRejectionReason, TxSubmissionResponse},
+        proto::wallet_rpc::{
+            SpendingInput,
+            SpendingInputsResponse,
+            TxLocation,
+            TxQueryResponse,
+            TxSubmissionRejectionReason,
+            TxSubmissionResponse,
+        },
         rpc::BaseNodeWalletRpcServer,
     },
     blocks::BlockHeader,
     consensus::ConsensusManager,
     proto::{
         base_node::{
+            QueryDeletedData,
+            QueryDeletedResponse,
             TxLocation as TxLocationProto,
             TxQueryBatchResponse as TxQueryBatchResponseProto,
             TxQueryBatchResponses as TxQueryBatchResponsesProto,
@@ -80,11 +119,14 @@ use tari_core::{
     transactions::{
         tari_amount::{uT, MicroMinotari, T},
         test_helpers::{create_test_core_key_manager_with_memory_db, schema_to_transaction, TestKeyManager},
-        transaction_components::OutputFeatures,
+        transaction_components::{OutputFeatures, Transaction, TransactionInput},
+        transaction_protocol::proto::protocol as proto,
         CryptoFactories,
     },
     txn_schema,
 };
+use tari_crypto::keys::{PublicKey as PK, SecretKey as SK};
+use tari_script::inputs;
 use tari_service_framework::{reply_channel, reply_channel::Receiver};
 use tari_shutdown::Shutdown;
 use tari_test_utils::random;
@@ -93,6 +135,7 @@ use tempfile::{tempdir, TempDir};
 use tokio::{sync::broadcast, task, time::sleep};
 
 use crate::support::{
+    comms_and_services::create_dummy_message,
     comms_rpc::{connect_rpc_client, BaseNodeWalletRpcMockService, BaseNodeWalletRpcMockState},
     utils::make_input,
 };
@@ -899,6 +942,266 @@ async fn tx_validation_protocol_tx_becomes_mined_unconfirmed_then_confirmed() {
     assert_eq!(completed_txs.get(&2u64.into()).unwrap().confirmations.unwrap(), 4);
 }
 
+/// Test that unconfirmed transactions are queried in batches of at most `max_tx_query_batch_size`
+#[tokio::test]
+async fn tx_validation_protocol_queries_in_batches() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+
+    let timestamp = EpochTime::now().as_u64();
+    let mut responses = Vec::new();
+    for i in 1u64..=5 {
+        add_transaction_to_database(
+            i.into(),
+            i * T,
+            Some(TransactionStatus::Completed),
+            None,
+            resources.db.clone(),
+        )
+        .await;
+        let tx = resources.db.get_completed_transaction(i.into()).unwrap();
+        responses.push(TxQueryBatchResponseProto {
+            signature: Some(SignatureProto::from(
+                tx.transaction.first_kernel_excess_sig().unwrap().clone(),
+            )),
+            location: TxLocationProto::from(TxLocation::Mined) as i32,
+            block_hash: [1u8; 32].to_vec(),
+            confirmations: 0,
+            block_height: 1,
+            mined_timestamp: timestamp,
+        });
+    }
+    rpc_service_state.set_transaction_query_batch_responses(TxQueryBatchResponsesProto {
+        responses,
+        is_synced: true,
+        tip_hash: [1u8; 32].to_vec(),
+        height_of_longest_chain: 1,
+        tip_mined_timestamp: timestamp,
+    });
+
+    let protocol = TransactionValidationProtocol::new(
+        1.into(),
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        resources.config.clone(),
+        resources.event_publisher.clone(),
+        resources.output_manager_service.clone(),
+    );
+    let result = task::spawn(protocol.execute()).await.unwrap();
+    assert!(result.is_ok());
+
+    // The setup uses a batch size of 2
+    let batch_sizes = rpc_service_state
+        .take_transaction_batch_query_calls()
+        .iter()
+        .map(|c| c.len())
+        .collect::<Vec<_>>();
+    assert_eq!(batch_sizes, vec![2, 2, 1]);
+
+    let completed_txs = resources.db.get_completed_transactions().unwrap();
+    for i in 1u64..=5 {
+        assert_eq!(
+            completed_txs.get(&i.into()).unwrap().status,
+            TransactionStatus::MinedUnconfirmed
+        );
+    }
+
+    // A batch size above the base node's limit is capped to it rather than failing every query
+    let protocol = TransactionValidationProtocol::new(
+        2.into(),
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        TransactionServiceConfig {
+            max_tx_query_batch_size: usize::MAX,
+            ..resources.config.clone()
+        },
+        resources.event_publisher.clone(),
+        resources.output_manager_service.clone(),
+    );
+    let result = task::spawn(protocol.execute()).await.unwrap();
+    assert!(result.is_ok());
+    let batch_sizes = rpc_service_state
+        .take_transaction_batch_query_calls()
+        .iter()
+        .map(|c| c.len())
+        .collect::<Vec<_>>();
+    assert_eq!(batch_sizes, vec![5]);
+}
+
+fn not_stored_response(tx: &CompletedTransaction) -> TxQueryBatchResponseProto {
+    TxQueryBatchResponseProto {
+        signature: Some(SignatureProto::from(
+            tx.transaction.first_kernel_excess_sig().unwrap().clone(),
+        )),
+        location: TxLocationProto::from(TxLocation::NotStored) as i32,
+        block_hash: vec![],
+        confirmations: 0,
+        block_height: 0,
+        mined_timestamp: 0,
+    }
+}
+
+/// Test that an unmined outbound transaction whose input was spent on-chain by another transaction is cancelled
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn tx_validation_protocol_inputs_spent_elsewhere() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+    add_transaction_to_database(
+        1u64.into(),
+        1 * T,
+        Some(TransactionStatus::Broadcast),
+        None,
+        resources.db.clone(),
+    )
+    .await;
+    let tx1 = resources.db.get_completed_transaction(1u64.into()).unwrap();
+
+    let timestamp = EpochTime::now().as_u64();
+    rpc_service_state.set_transaction_query_batch_responses(TxQueryBatchResponsesProto {
+        responses: vec![not_stored_response(&tx1)],
+        is_synced: true,
+        tip_hash: [3u8; 32].to_vec(),
+        height_of_longest_chain: 3,
+        tip_mined_timestamp: timestamp,
+    });
+    rpc_service_state.set_query_deleted_response(QueryDeletedResponse {
+        data: vec![QueryDeletedData {
+            mined_at_height: 1,
+            block_mined_in: [1u8; 32].to_vec(),
+            height_deleted_at: 2,
+            block_deleted_in: [2u8; 32].to_vec(),
+        }],
+        best_block_hash: [3u8; 32].to_vec(),
+        best_block_height: 3,
+    });
+
+    let protocol = TransactionValidationProtocol::new(
+        1.into(),
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        resources.config.clone(),
+        resources.event_publisher.clone(),
+        resources.output_manager_service.clone(),
+    );
+    let result = task::spawn(protocol.execute()).await.unwrap();
+    assert!(result.is_ok());
+
+    // Spends are only looked up on the chain that the kernels were queried on, and the kernel is queried again
+    // before cancelling
+    let query_deleted_calls = rpc_service_state.take_query_deleted_calls();
+    assert_eq!(query_deleted_calls.len(), 1);
+    assert_eq!(query_deleted_calls[0].chain_must_include_header, [3u8; 32].to_vec());
+    assert_eq!(rpc_service_state.take_transaction_batch_query_calls().len(), 2);
+
+    assert!(resources.db.get_completed_transaction(1u64.into()).is_err());
+    let cancelled_tx = resources.db.get_cancelled_completed_transaction(1u64.into()).unwrap();
+    assert_eq!(cancelled_tx.cancelled, Some(TxCancellationReason::DoubleSpend));
+
+    let mut spent_elsewhere = None;
+    while let Ok(event) = transaction_event_receiver.try_recv() {
+        if let TransactionEvent::InputsSpentElsewhere { tx_id, conflicts } = &*event {
+            spent_elsewhere = Some((*tx_id, conflicts.clone()));
+        }
+    }
+    let (tx_id, conflicts) = spent_elsewhere.expect("InputsSpentElsewhere event");
+    assert_eq!(tx_id, 1u64.into());
+    assert_eq!(conflicts.len(), 1);
+    assert_eq!(conflicts[0].spent_at_height, 2);
+    assert_eq!(conflicts[0].spent_in_block.to_vec(), [2u8; 32].to_vec());
+    assert!(conflicts[0].conflicting_tx_id.is_none());
+}
+
+/// Test that an input spent above the tip that the kernels were queried at does not cancel the transaction
+#[tokio::test]
+#[allow(clippy::identity_op)]
+async fn tx_validation_protocol_ignores_spends_above_the_queried_tip() {
+    let (
+        resources,
+        _outbound_mock_state,
+        mock_rpc_server,
+        server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+    let mut connection = mock_rpc_server
+        .create_connection(server_node_identity.to_peer(), "t/bnwallet/1".into())
+        .await;
+    wallet_connectivity.set_base_node_wallet_rpc_client(connect_rpc_client(&mut connection).await);
+    add_transaction_to_database(
+        1u64.into(),
+        1 * T,
+        Some(TransactionStatus::Broadcast),
+        None,
+        resources.db.clone(),
+    )
+    .await;
+    let tx1 = resources.db.get_completed_transaction(1u64.into()).unwrap();
+
+    rpc_service_state.set_transaction_query_batch_responses(TxQueryBatchResponsesProto {
+        responses: vec![not_stored_response(&tx1)],
+        is_synced: true,
+        tip_hash: [3u8; 32].to_vec(),
+        height_of_longest_chain: 3,
+        tip_mined_timestamp: EpochTime::now().as_u64(),
+    });
+    rpc_service_state.set_query_deleted_response(QueryDeletedResponse {
+        data: vec![QueryDeletedData {
+            mined_at_height: 1,
+            block_mined_in: [1u8; 32].to_vec(),
+            height_deleted_at: 4,
+            block_deleted_in: [4u8; 32].to_vec(),
+        }],
+        best_block_hash: [4u8; 32].to_vec(),
+        best_block_height: 4,
+    });
+
+    let protocol = TransactionValidationProtocol::new(
+        1.into(),
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        resources.config.clone(),
+        resources.event_publisher.clone(),
+        resources.output_manager_service.clone(),
+    );
+    let result = task::spawn(protocol.execute()).await.unwrap();
+    assert!(result.is_ok());
+
+    assert_eq!(
+        resources.db.get_completed_transaction(1u64.into()).unwrap().status,
+        TransactionStatus::Broadcast
+    );
+}
+
 /// Test that revalidation clears the correct db fields and calls for validation of is said transactions
 #[tokio::test]
 #[allow(clippy::identity_op)]
@@ -1345,3 +1648,705 @@ async fn tx_validation_protocol_reorg() {
         Some(TxCancellationReason::AbandonedCoinbase)
     ));
 }
+
+fn queued_finalized_message(tx_id: TxId) -> QueuedOutboundMessage {
+    let destination = TariAddress::new(
+        build_node_identity(PeerFeatures::COMMUNICATION_NODE)
+            .public_key()
+            .clone(),
+        Network::LocalNet,
+    );
+    QueuedOutboundMessage::new(
+        tx_id,
+        OutboundMessageType::TransactionFinalized,
+        destination,
+        proto::TransactionFinalizedMessage {
+            tx_id: tx_id.as_u64(),
+            transaction: None,
+        }
+        .encode_to_vec(),
+        Utc::now().naive_utc(),
+    )
+}
+
+/// A queued message is only marked as delivered once it was sent directly to the counterparty
+#[tokio::test]
+async fn queued_outbound_message_is_delivered_only_when_sent_directly() {
+    let (
+        resources,
+        outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    let deliver = |tx_id| {
+        deliver_queued_outbound_message(
+            tx_id,
+            OutboundMessageType::TransactionFinalized,
+            resources.db.clone(),
+            resources.outbound_message_service.clone(),
+            resources.config.direct_send_timeout,
+            TransactionRoutingMechanism::DirectAndStoreAndForward,
+            10,
+        )
+    };
+
+    // Only accepted for store-and-forward, so it may still never arrive
+    outbound_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Queued,
+        })
+        .await;
+    let saf_tx_id = TxId::from(1u64);
+    resources
+        .db
+        .queue_outbound_message(queued_finalized_message(saf_tx_id))
+        .unwrap();
+    assert!(deliver(saf_tx_id).await.unwrap());
+    let message = resources
+        .db
+        .fetch_outbound_message(saf_tx_id, OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.status, OutboundMessageStatus::Pending);
+    assert_eq!(message.attempts, 1);
+
+    outbound_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Queued,
+            broadcast: ResponseType::Failed,
+        })
+        .await;
+    let direct_tx_id = TxId::from(2u64);
+    resources
+        .db
+        .queue_outbound_message(queued_finalized_message(direct_tx_id))
+        .unwrap();
+    assert!(deliver(direct_tx_id).await.unwrap());
+    let message = resources
+        .db
+        .fetch_outbound_message(direct_tx_id, OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.status, OutboundMessageStatus::Delivered);
+    assert_eq!(message.attempts, 1);
+
+    let pending = resources.db.fetch_pending_outbound_messages().unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].tx_id, saf_tx_id);
+}
+
+/// The attempt count survives the message being queued again, as a restarted protocol does, so the maximum number of
+/// attempts bounds the resends and a failed message is no longer sent
+#[tokio::test]
+async fn queued_outbound_message_attempts_are_bounded_across_requeues() {
+    let (
+        resources,
+        outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        _transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    outbound_mock_state
+        .set_behaviour(MockBehaviour {
+            direct: ResponseType::Failed,
+            broadcast: ResponseType::Queued,
+        })
+        .await;
+    let tx_id = TxId::from(1u64);
+    let deliver = || {
+        deliver_queued_outbound_message(
+            tx_id,
+            OutboundMessageType::TransactionFinalized,
+            resources.db.clone(),
+            resources.outbound_message_service.clone(),
+            resources.config.direct_send_timeout,
+            TransactionRoutingMechanism::DirectAndStoreAndForward,
+            3,
+        )
+    };
+
+    resources
+        .db
+        .queue_outbound_message(queued_finalized_message(tx_id))
+        .unwrap();
+    assert!(deliver().await.unwrap());
+    assert!(deliver().await.unwrap());
+
+    resources
+        .db
+        .queue_outbound_message(queued_finalized_message(tx_id))
+        .unwrap();
+    let message = resources
+        .db
+        .fetch_outbound_message(tx_id, OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.status, OutboundMessageStatus::Pending);
+    assert_eq!(message.attempts, 2);
+
+    assert!(deliver().await.unwrap());
+    let message = resources
+        .db
+        .fetch_outbound_message(tx_id, OutboundMessageType::TransactionFinalized)
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.status, OutboundMessageStatus::Failed);
+    assert_eq!(message.attempts, 3);
+    assert_eq!(outbound_mock_state.take_calls().await.len(), 3);
+
+    assert!(!deliver().await.unwrap());
+    assert_eq!(outbound_mock_state.call_count().await, 0);
+    assert!(resources.db.fetch_pending_outbound_messages().unwrap().is_empty());
+
+    // Nothing is sent for a message that is no longer queued
+    resources.db.remove_outbound_messages(tx_id).unwrap();
+    assert!(!deliver().await.unwrap());
+    assert_eq!(outbound_mock_state.call_count().await, 0);
+}
+
+fn atomic_swap_locked_message(swap_id: u64, hash_lock: FixedHash, output_hash: FixedHash) -> proto::AtomicSwapMessage {
+    proto::AtomicSwapMessage {
+        swap_id,
+        message: Some(proto::atomic_swap_message::Message::Locked(proto::AtomicSwapLocked {
+            hash_lock: hash_lock.to_vec(),
+            amount: 10_000,
+            lock_height: 720,
+            output_hash: output_hash.to_vec(),
+            message: "Swap".to_string(),
+        })),
+    }
+}
+
+fn locked_atomic_swap(
+    swap_id: u64,
+    role: AtomicSwapRole,
+    counterparty: TariAddress,
+    hash_lock: FixedHash,
+) -> AtomicSwap {
+    let now = Utc::now().naive_utc();
+    AtomicSwap {
+        swap_id,
+        role,
+        state: AtomicSwapState::Locked,
+        counterparty,
+        amount: MicroMinotari::from(10_000),
+        hash_lock,
+        pre_image: None,
+        lock_height: 360,
+        lock_tx_id: Some(TxId::new_random()),
+        lock_output_hash: Some(FixedHash::from([2u8; 32])),
+        counterparty_output_hash: None,
+        settle_tx_id: None,
+        message: "Swap".to_string(),
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+async fn next_atomic_swap_update(event_receiver: &mut TransactionEventReceiver) -> (u64, AtomicSwapState) {
+    let delay = sleep(Duration::from_secs(30));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            event = event_receiver.recv() => {
+                if let TransactionEvent::AtomicSwapUpdated { swap_id, state } = &*event.unwrap() {
+                    return (*swap_id, *state);
+                }
+            },
+            () = &mut delay => panic!("Timed out waiting for an atomic swap update"),
+        }
+    }
+}
+
+/// The participant records the initiator's locked funds, then learns the pre-image once the initiator redeems
+#[tokio::test]
+async fn atomic_swap_happy_path() {
+    let (
+        resources,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    let (message_sender, message_receiver) = mpsc::unbounded();
+    task::spawn(run_atomic_swap_message_handler(
+        resources.db.clone(),
+        message_receiver,
+        Network::LocalNet,
+        resources.event_publisher.clone(),
+        shutdown.to_signal(),
+    ));
+
+    let initiator = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    let hash_lock = hash_lock_for(&pre_image);
+    let initiator_output_hash = FixedHash::from([1u8; 32]);
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(1, hash_lock, initiator_output_hash),
+            initiator.public_key(),
+        ))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::Proposed)
+    );
+    let swap = resources.db.fetch_atomic_swap(1).unwrap().unwrap();
+    assert_eq!(swap.role, AtomicSwapRole::Participant);
+    assert_eq!(swap.counterparty.public_key(), initiator.public_key());
+    assert_eq!(swap.amount, MicroMinotari::from(10_000));
+    assert_eq!(swap.hash_lock, hash_lock);
+    assert_eq!(swap.counterparty_output_hash, Some(initiator_output_hash));
+    assert_eq!(swap.pre_image, None);
+
+    // This wallet locks its side of the swap
+    let mut participant_swap = locked_atomic_swap(1, AtomicSwapRole::Participant, swap.counterparty, hash_lock);
+    participant_swap.counterparty_output_hash = swap.counterparty_output_hash;
+    resources.db.upsert_atomic_swap(participant_swap).unwrap();
+
+    message_sender
+        .unbounded_send(create_dummy_message(
+            redeemed_message(1, &pre_image),
+            initiator.public_key(),
+        ))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::CounterpartyRedeemed)
+    );
+    let swap = resources.db.fetch_atomic_swap(1).unwrap().unwrap();
+    assert_eq!(swap.pre_image, Some(pre_image));
+    assert_eq!(swap.counterparty_output_hash, Some(initiator_output_hash));
+}
+
+/// The participant reads the pre-image from the input that spent its HTLC output, without the initiator's message
+#[tokio::test]
+async fn atomic_swap_pre_image_read_from_chain() {
+    let (
+        resources,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        rpc_service_state,
+        _shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        wallet_connectivity,
+    ) = setup().await;
+
+    let initiator = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let initiator_address = TariAddress::new(initiator.public_key().clone(), Network::LocalNet);
+    let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    let hash_lock = hash_lock_for(&pre_image);
+    let swap = locked_atomic_swap(1, AtomicSwapRole::Participant, initiator_address, hash_lock);
+    let output_hash = swap.lock_output_hash.unwrap();
+    resources.db.upsert_atomic_swap(swap).unwrap();
+
+    // Nothing has spent the output yet
+    find_redeemed_pre_images(
+        resources.db.clone(),
+        wallet_connectivity.clone(),
+        resources.event_publisher.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resources.db.fetch_atomic_swap(1).unwrap().unwrap().pre_image, None);
+
+    let redeem_input =
+        TransactionInput::new_with_output_hash(output_hash, inputs!(pre_image.clone()), Default::default());
+    rpc_service_state.set_spending_inputs_response(SpendingInputsResponse {
+        inputs: vec![SpendingInput {
+            output_hash: output_hash.to_vec(),
+            input: Some(redeem_input.try_into().unwrap()),
+            spent_height: 10,
+            block_spent_in: vec![0u8; 32],
+        }],
+        best_block_hash: vec![0u8; 32],
+        best_block_height: 10,
+    });
+    find_redeemed_pre_images(
+        resources.db.clone(),
+        wallet_connectivity,
+        resources.event_publisher.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::CounterpartyRedeemed)
+    );
+    let swap = resources.db.fetch_atomic_swap(1).unwrap().unwrap();
+    assert_eq!(swap.pre_image, Some(pre_image));
+}
+
+/// A participant may only lock funds if its lock expires well before the initiator's
+#[test]
+fn atomic_swap_participant_lock_height_margin() {
+    assert!(check_participant_lock_height(100 + PARTICIPANT_LOCK_BLOCKS, 100 + INITIATOR_LOCK_BLOCKS).is_ok());
+    assert!(check_participant_lock_height(100 + PARTICIPANT_LOCK_BLOCKS, 100 + PARTICIPANT_LOCK_BLOCKS).is_err());
+    assert!(check_participant_lock_height(
+        100 + PARTICIPANT_LOCK_BLOCKS,
+        100 + PARTICIPANT_LOCK_BLOCKS + LOCK_HEIGHT_SAFETY_MARGIN
+    )
+    .is_err());
+}
+
+/// When the counterparty refunds after the lock height, a swap this wallet never locked funds for is over, while a swap
+/// it did lock funds for is left for this wallet to refund its own output
+#[tokio::test]
+async fn atomic_swap_refund_after_timeout() {
+    let (
+        resources,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    let (message_sender, message_receiver) = mpsc::unbounded();
+    task::spawn(run_atomic_swap_message_handler(
+        resources.db.clone(),
+        message_receiver,
+        Network::LocalNet,
+        resources.event_publisher.clone(),
+        shutdown.to_signal(),
+    ));
+
+    let counterparty = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let hash_lock = hash_lock_for(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(1, hash_lock, FixedHash::from([1u8; 32])),
+            counterparty.public_key(),
+        ))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::Proposed)
+    );
+    message_sender
+        .unbounded_send(create_dummy_message(refunded_message(1), counterparty.public_key()))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::CounterpartyRefunded)
+    );
+
+    let counterparty_address = TariAddress::new(counterparty.public_key().clone(), Network::LocalNet);
+    resources
+        .db
+        .upsert_atomic_swap(locked_atomic_swap(
+            2,
+            AtomicSwapRole::Participant,
+            counterparty_address,
+            hash_lock,
+        ))
+        .unwrap();
+    message_sender
+        .unbounded_send(create_dummy_message(refunded_message(2), counterparty.public_key()))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (2, AtomicSwapState::Locked)
+    );
+    let swap = resources.db.fetch_atomic_swap(2).unwrap().unwrap();
+    assert_eq!(swap.state, AtomicSwapState::Locked);
+    assert!(swap.settle_tx_id.is_none());
+}
+
+/// A redeemed message whose pre-image does not match the hash lock is rejected and the swap is left unchanged
+#[tokio::test]
+async fn atomic_swap_wrong_pre_image() {
+    let (
+        resources,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    let (message_sender, message_receiver) = mpsc::unbounded();
+    task::spawn(run_atomic_swap_message_handler(
+        resources.db.clone(),
+        message_receiver,
+        Network::LocalNet,
+        resources.event_publisher.clone(),
+        shutdown.to_signal(),
+    ));
+
+    let counterparty = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let counterparty_address = TariAddress::new(counterparty.public_key().clone(), Network::LocalNet);
+    let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    resources
+        .db
+        .upsert_atomic_swap(locked_atomic_swap(
+            1,
+            AtomicSwapRole::Participant,
+            counterparty_address,
+            hash_lock_for(&pre_image),
+        ))
+        .unwrap();
+
+    let wrong_pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    message_sender
+        .unbounded_send(create_dummy_message(
+            redeemed_message(1, &wrong_pre_image),
+            counterparty.public_key(),
+        ))
+        .unwrap();
+    // Messages are handled in order, so once this update arrives the wrong pre-image has been dealt with
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(2, hash_lock_for(&pre_image), FixedHash::from([1u8; 32])),
+            counterparty.public_key(),
+        ))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (2, AtomicSwapState::Proposed)
+    );
+
+    let swap = resources.db.fetch_atomic_swap(1).unwrap().unwrap();
+    assert_eq!(swap.state, AtomicSwapState::Locked);
+    assert_eq!(swap.pre_image, None);
+}
+
+/// A locked message with a different hash lock than the swap was agreed with is rejected, as is any message about the
+/// swap from a wallet other than the counterparty
+#[tokio::test]
+async fn atomic_swap_wrong_hash_lock() {
+    let (
+        resources,
+        _outbound_mock_state,
+        _mock_rpc_server,
+        _server_node_identity,
+        _rpc_service_state,
+        shutdown,
+        _temp_dir,
+        mut transaction_event_receiver,
+        _wallet_connectivity,
+    ) = setup().await;
+    let (message_sender, message_receiver) = mpsc::unbounded();
+    task::spawn(run_atomic_swap_message_handler(
+        resources.db.clone(),
+        message_receiver,
+        Network::LocalNet,
+        resources.event_publisher.clone(),
+        shutdown.to_signal(),
+    ));
+
+    let counterparty = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    let counterparty_address = TariAddress::new(counterparty.public_key().clone(), Network::LocalNet);
+    let pre_image = PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng));
+    let hash_lock = hash_lock_for(&pre_image);
+    resources
+        .db
+        .upsert_atomic_swap(locked_atomic_swap(
+            1,
+            AtomicSwapRole::Initiator,
+            counterparty_address,
+            hash_lock,
+        ))
+        .unwrap();
+
+    let wrong_hash_lock = hash_lock_for(&PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)));
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(1, wrong_hash_lock, FixedHash::from([1u8; 32])),
+            counterparty.public_key(),
+        ))
+        .unwrap();
+    let other_wallet = build_node_identity(PeerFeatures::COMMUNICATION_NODE);
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(1, hash_lock, FixedHash::from([3u8; 32])),
+            other_wallet.public_key(),
+        ))
+        .unwrap();
+    message_sender
+        .unbounded_send(create_dummy_message(refunded_message(1), other_wallet.public_key()))
+        .unwrap();
+    // Messages are handled in order, so once this update arrives the rejected messages have been dealt with
+    message_sender
+        .unbounded_send(create_dummy_message(
+            atomic_swap_locked_message(1, hash_lock, FixedHash::from([1u8; 32])),
+            counterparty.public_key(),
+        ))
+        .unwrap();
+    assert_eq!(
+        next_atomic_swap_update(&mut transaction_event_receiver).await,
+        (1, AtomicSwapState::Locked)
+    );
+
+    let swap = resources.db.fetch_atomic_swap(1).unwrap().unwrap();
+    assert_eq!(swap.hash_lock, hash_lock);
+    assert_eq!(swap.counterparty.public_key(), counterparty.public_key());
+    assert_eq!(swap.counterparty_output_hash, Some(FixedHash::from([1u8; 32])));
+    assert_eq!(swap.state, AtomicSwapState::Locked);
+}
+
+/// Answers `CreateBatchTransaction` with `transaction`, as if the output manager had funded the batch
+async fn oms_batch_transaction_task(
+    mut receiver: Receiver<OutputManagerRequest, Result<OutputManagerResponse, OutputManagerError>>,
+    transaction: Transaction,
+) {
+    while let Some(request_context) = receiver.next().await {
+        let (request, reply_tx) = request_context.split();
+        let response = match request {
+            OutputManagerRequest::CreateBatchTransaction { .. } => Ok(OutputManagerResponse::BatchTransaction((
+                transaction.body.get_total_fee().unwrap(),
+                transaction.clone(),
+            ))),
+            _ => Err(OutputManagerError::InvalidResponseError(
+                "Unhandled request type".to_string(),
+            )),
+        };
+        let _result = reply_tx.send(response);
+    }
+}
+
+fn random_batch_payment(amount: MicroMinotari) -> BatchPayment {
+    BatchPayment {
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::LocalNet,
+        ),
+        amount,
+        message: "Batch".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn batch_send_protocol_records_every_recipient() {
+    let (mut resources, _outbound_mock_state, _mock_rpc_server, _server_node_identity, _, _shutdown, _temp_dir, _, _) =
+        setup().await;
+    let key_manager = resources.transaction_key_manager_service.clone();
+    let input = make_input(&mut OsRng, 10 * T, &OutputFeatures::default(), &key_manager).await;
+    let (txs, _) = schema_to_transaction(&[txn_schema!(from: vec![input], to: vec![T])], &key_manager).await;
+    let (oms_request_sender, oms_request_receiver) = reply_channel::unbounded();
+    task::spawn(oms_batch_transaction_task(oms_request_receiver, (*txs[0]).clone()));
+    let (oms_event_publisher, _) = broadcast::channel(200);
+    resources.output_manager_service = OutputManagerHandle::new(oms_request_sender, oms_event_publisher);
+
+    let payments = vec![random_batch_payment(5_000 * uT), random_batch_payment(7_000 * uT)];
+    let tx_id = TxId::new_random();
+    let completed = TransactionBatchSendProtocol::new(
+        tx_id,
+        resources.clone(),
+        payments.clone(),
+        UtxoSelectionCriteria::default(),
+        5 * uT,
+        "Batch".to_string(),
+    )
+    .execute()
+    .await
+    .unwrap();
+
+    assert_eq!(completed.tx_id, tx_id);
+    assert_eq!(completed.status, TransactionStatus::Completed);
+    assert_eq!(completed.amount, 12_000 * uT);
+    assert_eq!(completed.fee, txs[0].body.get_total_fee().unwrap());
+    // The batch is recorded against its first recipient
+    assert_eq!(completed.destination_address, payments[0].destination);
+    let batched_payments = resources.db.fetch_batched_payments(tx_id).unwrap();
+    assert_eq!(batched_payments.len(), 2);
+    for (batched, payment) in batched_payments.iter().zip(payments.iter()) {
+        assert_eq!(batched.destination_address, payment.destination);
+        assert_eq!(batched.amount, payment.amount);
+    }
+}
+
+#[tokio::test]
+async fn batch_send_protocol_rejects_invalid_payments() {
+    let (resources, _outbound_mock_state, _mock_rpc_server, _server_node_identity, _, _shutdown, _temp_dir, _, _) =
+        setup().await;
+    let to_self = BatchPayment {
+        destination: resources.wallet_identity.address.clone(),
+        ..random_batch_payment(5_000 * uT)
+    };
+    let wrong_network = BatchPayment {
+        destination: TariAddress::new(
+            PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
+            Network::MainNet,
+        ),
+        ..random_batch_payment(5_000 * uT)
+    };
+    let invalid_batches = vec![
+        vec![],
+        vec![
+            random_batch_payment(5_000 * uT),
+            random_batch_payment(MicroMinotari::zero()),
+        ],
+        vec![random_batch_payment(5_000 * uT), to_self],
+        vec![wrong_network],
+    ];
+
+    for payments in invalid_batches {
+        let tx_id = TxId::new_random();
+        let err = TransactionBatchSendProtocol::new(
+            tx_id,
+            resources.clone(),
+            payments,
+            UtxoSelectionCriteria::default(),
+            5 * uT,
+            "Batch".to_string(),
+        )
+        .execute()
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(
+                err.error,
+                TransactionServiceError::BatchTransactionError(_) | TransactionServiceError::InvalidNetwork
+            ),
+            "Unexpected error: {:?}",
+            err.error
+        );
+        assert!(resources.db.fetch_batched_payments(tx_id).unwrap().is_empty());
+    }
+}
+
+#[tokio::test]
+async fn batch_send_protocol_stores_nothing_when_the_batch_cannot_be_funded() {
+    let (resources, _outbound_mock_state, _mock_rpc_server, _server_node_identity, _, _shutdown, _temp_dir, _, _) =
+        setup().await;
+    let tx_id = TxId::new_random();
+    // The output manager mock answers every batch request with an error
+    let err = TransactionBatchSendProtocol::new(
+        tx_id,
+        resources.clone(),
+        vec![random_batch_payment(5_000 * uT)],
+        UtxoSelectionCriteria::default(),
+        5 * uT,
+        "Batch".to_string(),
+    )
+    .execute()
+    .await
+    .unwrap_err();
+    assert!(
+        matches!(err.error, TransactionServiceError::OutputManagerError(_)),
+        "Unexpected error: {:?}",
+        err.error
+    );
+    assert!(resources.db.fetch_batched_payments(tx_id).unwrap().is_empty());
+}
@@ -40,9 +40,10 @@ use minotari_wallet::{
     transaction_service::handle::TransactionServiceRequest,
     util::{wallet_identity::WalletIdentity, watch::Watch},
     utxo_scanner_service::{
-        handle::{UtxoScannerEvent, UtxoScannerHandle},
-        service::{ScannedBlock, UtxoScannerService},
+        handle::{ScanStats, UtxoScannerEvent, UtxoScannerHandle},
+        service::{ScanCheckpoint, ScannedBlock, UtxoScannerService},
         uxto_scanner_service_builder::UtxoScannerMode,
+        SCAN_CHECKPOINT_KEY,
     },
 };
 use rand::{rngs::OsRng, RngCore};
@@ -106,6 +107,16 @@ async fn setup(
     previous_db: Option<WalletDatabase<WalletSqliteDatabase>>,
     recovery_message: Option<String>,
     one_sided_message: Option<String>,
+) -> UtxoScannerTestInterface {
+    setup_with_full_scan(mode, previous_db, recovery_message, one_sided_message, false).await
+}
+
+async fn setup_with_full_scan(
+    mode: UtxoScannerMode,
+    previous_db: Option<WalletDatabase<WalletSqliteDatabase>>,
+    recovery_message: Option<String>,
+    one_sided_message: Option<String>,
+    full_scan: bool,
 ) -> UtxoScannerTestInterface {
     let shutdown = Shutdown::new();
     let factories = CryptoFactories::default();
@@ -183,7 +194,8 @@ async fn setup(
     scanner_service_builder
         .with_peers(vec![server_node_identity.public_key().clone()])
         .with_retry_limit(1)
-        .with_mode(mode);
+        .with_mode(mode)
+        .with_full_scan(full_scan);
 
     if let Some(message) = one_sided_message {
         scanner_service_builder.with_one_sided_message(message);
@@ -377,6 +389,208 @@ async fn test_utxo_scanner_recovery() {
         }
     }
 }
+#[tokio::test]
+async fn test_utxo_scanner_full_scan_ignores_birthday() {
+    let mut test_interface = setup_with_full_scan(UtxoScannerMode::Recovery, None, None, None, true).await;
+
+    let cipher_seed = CipherSeed::new();
+    // get birthday duration, in seconds, from unix epoch
+    let birthday_epoch_time = get_birthday_from_unix_epoch_in_seconds(cipher_seed.birthday(), 14u16);
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 11;
+    const BIRTHDAY_OFFSET: u64 = 5;
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let TestBlockData {
+        block_headers,
+        wallet_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false, &key_manager)
+        .await;
+
+    test_interface
+        .rpc_service_state
+        .set_utxos_by_block(utxos_by_block.clone());
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+
+    let chain_metadata = ChainMetadata {
+        height_of_longest_chain: NUM_BLOCKS - 1,
+        best_block: block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec(),
+        accumulated_difficulty: Vec::new(),
+        pruned_height: 0,
+        timestamp: 0,
+    };
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(chain_metadata),
+        is_synced: true,
+    });
+
+    // Adding half the outputs of the blocks to the OMS mock, including those from before the birthday
+    let mut db_wallet_outputs = Vec::new();
+    let mut total_outputs_to_recover = 0;
+    let mut total_amount_to_recover = MicroMinotari::from(0);
+    for outputs in wallet_outputs.values() {
+        for output in outputs.iter().skip(outputs.len() / 2) {
+            let dbo = DbWalletOutput::from_wallet_output(
+                output.clone(),
+                &key_manager,
+                None,
+                OutputSource::Unknown,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            total_outputs_to_recover += 1;
+            total_amount_to_recover += dbo.wallet_output.value;
+            db_wallet_outputs.push(dbo);
+        }
+    }
+    test_interface.oms_mock_state.set_recoverable_outputs(db_wallet_outputs);
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                if let UtxoScannerEvent::Completed {
+                    final_height,
+                    num_recovered,
+                    value_recovered,
+                    time_taken: _,
+                } = event.unwrap() {
+                    assert_eq!(final_height, NUM_BLOCKS - 1);
+                    assert_eq!(num_recovered, total_outputs_to_recover);
+                    assert_eq!(value_recovered, total_amount_to_recover);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_utxo_scanner_publishes_scan_statistics() {
+    let mut test_interface = setup_with_full_scan(UtxoScannerMode::Recovery, None, None, None, true).await;
+
+    let cipher_seed = CipherSeed::new();
+    // get birthday duration, in seconds, from unix epoch
+    let birthday_epoch_time = get_birthday_from_unix_epoch_in_seconds(cipher_seed.birthday(), 14u16);
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 6;
+    const BIRTHDAY_OFFSET: u64 = 3;
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let TestBlockData {
+        block_headers,
+        wallet_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false, &key_manager)
+        .await;
+
+    test_interface
+        .rpc_service_state
+        .set_utxos_by_block(utxos_by_block.clone());
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+
+    let chain_metadata = ChainMetadata {
+        height_of_longest_chain: NUM_BLOCKS - 1,
+        best_block: block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec(),
+        accumulated_difficulty: Vec::new(),
+        pruned_height: 0,
+        timestamp: 0,
+    };
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(chain_metadata),
+        is_synced: true,
+    });
+
+    // Adding half the outputs of the blocks to the OMS mock
+    let mut db_wallet_outputs = Vec::new();
+    let mut total_outputs = 0;
+    let mut total_outputs_to_recover = 0;
+    let mut total_amount_to_recover = MicroMinotari::from(0);
+    for outputs in wallet_outputs.values() {
+        total_outputs += outputs.len() as u64;
+        for output in outputs.iter().skip(outputs.len() / 2) {
+            let dbo = DbWalletOutput::from_wallet_output(
+                output.clone(),
+                &key_manager,
+                None,
+                OutputSource::Unknown,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            total_outputs_to_recover += 1;
+            total_amount_to_recover += dbo.wallet_output.value;
+            db_wallet_outputs.push(dbo);
+        }
+    }
+    test_interface.oms_mock_state.set_recoverable_outputs(db_wallet_outputs);
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let mut batches = Vec::new();
+    let mut last_totals = ScanStats::default();
+    let mut summary = None;
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                match event.unwrap() {
+                    UtxoScannerEvent::BatchScanned { batch, totals } => {
+                        assert_eq!(batch.tip_height, NUM_BLOCKS - 1);
+                        assert_eq!(totals.current_height, batch.current_height);
+                        assert_eq!(totals.num_batches, last_totals.num_batches + 1);
+                        assert_eq!(totals.num_found, last_totals.num_found + batch.num_found);
+                        last_totals = totals;
+                        batches.push(batch);
+                    },
+                    UtxoScannerEvent::Summary(stats) => summary = Some(stats),
+                    UtxoScannerEvent::Completed { .. } => break,
+                    _ => {},
+                }
+            }
+        }
+    }
+
+    // The summary is published before the scan completes and agrees with the batches
+    let summary = summary.expect("Summary event should have arrived before the scan completed");
+    assert_eq!(summary.start_height, 0);
+    assert_eq!(summary.current_height, NUM_BLOCKS - 1);
+    assert_eq!(summary.num_blocks, NUM_BLOCKS);
+    assert_eq!(summary.num_outputs, total_outputs);
+    assert_eq!(summary.num_found, total_outputs_to_recover);
+    assert_eq!(summary.value_recovered, total_amount_to_recover);
+    assert_eq!(summary.num_batches, batches.len() as u64);
+    assert_eq!(batches.iter().map(|b| b.num_outputs).sum::<u64>(), summary.num_outputs);
+    assert_eq!(
+        summary.total_rpc_latency,
+        batches.iter().map(|b| b.rpc_latency).sum::<Duration>()
+    );
+    assert_eq!(
+        summary.average_rpc_latency(),
+        summary.total_rpc_latency / u32::try_from(summary.num_batches).unwrap()
+    );
+}
+
 #[tokio::test]
 #[allow(clippy::too_many_lines)]
 async fn test_utxo_scanner_recovery_with_restart() {
@@ -1061,6 +1275,91 @@ async fn test_utxo_scanner_one_sided_payments() {
     }
 }
 
+#[tokio::test]
+async fn test_utxo_scanner_one_sided_only_skips_recovery() {
+    let mut test_interface = setup(UtxoScannerMode::OneSidedOnly, None, None, None).await;
+
+    let cipher_seed = CipherSeed::new();
+    let birthday_epoch_time = get_birthday_from_unix_epoch_in_seconds(cipher_seed.birthday(), 14u16);
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 11;
+    const BIRTHDAY_OFFSET: u64 = 5;
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let TestBlockData {
+        block_headers,
+        wallet_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false, &key_manager)
+        .await;
+
+    test_interface.rpc_service_state.set_utxos_by_block(utxos_by_block);
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(ChainMetadata {
+            height_of_longest_chain: NUM_BLOCKS - 1,
+            best_block: block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec(),
+            accumulated_difficulty: Vec::new(),
+            pruned_height: 0,
+            timestamp: 0,
+        }),
+        is_synced: true,
+    });
+
+    // Outputs derived from the seed are offered for recovery, but must not be looked for
+    let mut db_wallet_outputs = Vec::new();
+    for outputs in wallet_outputs.values() {
+        for output in outputs {
+            db_wallet_outputs.push(
+                DbWalletOutput::from_wallet_output(
+                    output.clone(),
+                    &key_manager,
+                    None,
+                    OutputSource::Unknown,
+                    None,
+                    None,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+    }
+    test_interface.oms_mock_state.set_recoverable_outputs(db_wallet_outputs);
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                if let UtxoScannerEvent::Completed {
+                    final_height,
+                    num_recovered,
+                    value_recovered,
+                    time_taken: _,
+                } = event.unwrap() {
+                    assert_eq!(final_height, NUM_BLOCKS - 1);
+                    assert_eq!(num_recovered, 0);
+                    assert_eq!(value_recovered, MicroMinotari::from(0));
+                    break;
+                }
+            }
+        }
+    }
+
+    assert!(test_interface
+        .transaction_service_mock_state
+        .drain_requests()
+        .is_empty());
+}
+
 #[tokio::test]
 async fn test_birthday_timestamp_over_chain() {
     let test_interface = setup(UtxoScannerMode::Recovery, None, None, None).await;
@@ -1113,3 +1412,131 @@ async fn test_birthday_timestamp_over_chain() {
         birthday_epoch_time >= before_birthday_block_timestamp && birthday_epoch_time <= after_birthday_block_timestamp
     );
 }
+
+#[tokio::test]
+async fn test_utxo_scanner_resumes_from_scan_checkpoint() {
+    let mut test_interface = setup(UtxoScannerMode::Recovery, None, None, None).await;
+
+    let cipher_seed = CipherSeed::new();
+    // get birthday duration, in seconds, from unix epoch
+    let birthday_epoch_time = get_birthday_from_unix_epoch_in_seconds(cipher_seed.birthday(), 14);
+    test_interface.wallet_db.set_master_seed(cipher_seed).unwrap();
+
+    const NUM_BLOCKS: u64 = 11;
+    const BIRTHDAY_OFFSET: u64 = 5;
+    const LAST_SCANNED_HEIGHT: u64 = 4;
+    const OUTPUTS_SCANNED_BEFORE_INTERRUPT: u64 = 3;
+
+    let key_manager = create_test_core_key_manager_with_memory_db();
+    let TestBlockData {
+        block_headers,
+        wallet_outputs,
+        utxos_by_block,
+    } = generate_block_headers_and_utxos(0, NUM_BLOCKS, birthday_epoch_time, BIRTHDAY_OFFSET, false, &key_manager)
+        .await;
+
+    test_interface
+        .rpc_service_state
+        .set_utxos_by_block(utxos_by_block.clone());
+    test_interface.rpc_service_state.set_blocks(block_headers.clone());
+
+    let chain_metadata = ChainMetadata {
+        height_of_longest_chain: NUM_BLOCKS - 1,
+        best_block: block_headers.get(&(NUM_BLOCKS - 1)).unwrap().clone().hash().to_vec(),
+        accumulated_difficulty: Vec::new(),
+        pruned_height: 0,
+        timestamp: 0,
+    };
+    test_interface.rpc_service_state.set_tip_info_response(TipInfoResponse {
+        metadata: Some(chain_metadata),
+        is_synced: true,
+    });
+
+    // All of the outputs belong to the wallet
+    let mut db_wallet_outputs = Vec::new();
+    for outputs in wallet_outputs.values() {
+        for output in outputs {
+            let dbo = DbWalletOutput::from_wallet_output(
+                output.clone(),
+                &key_manager,
+                None,
+                OutputSource::Unknown,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+            db_wallet_outputs.push(dbo);
+        }
+    }
+    test_interface.oms_mock_state.set_recoverable_outputs(db_wallet_outputs);
+
+    // An earlier scan completed the previous block and was interrupted part way through the next one
+    let last_scanned_header = block_headers.get(&LAST_SCANNED_HEIGHT).unwrap();
+    test_interface
+        .wallet_db
+        .save_scanned_block(ScannedBlock {
+            header_hash: last_scanned_header.hash(),
+            height: LAST_SCANNED_HEIGHT,
+            num_outputs: Some(0),
+            amount: None,
+            timestamp: Utc::now().naive_utc(),
+        })
+        .unwrap();
+    let interrupted_header = block_headers.get(&(LAST_SCANNED_HEIGHT + 1)).unwrap();
+    test_interface
+        .wallet_db
+        .set_client_key_value(
+            SCAN_CHECKPOINT_KEY.to_string(),
+            ScanCheckpoint {
+                header_hash: interrupted_header.hash(),
+                height: interrupted_header.height,
+                output_index: OUTPUTS_SCANNED_BEFORE_INTERRUPT,
+                num_outputs: OUTPUTS_SCANNED_BEFORE_INTERRUPT,
+                amount: MicroMinotari::from(0),
+            }
+            .to_string(),
+        )
+        .unwrap();
+
+    let mut scanner_event_stream = test_interface.scanner_handle.get_event_receiver();
+    tokio::spawn(test_interface.scanner_service.take().unwrap().run());
+
+    let delay = time::sleep(Duration::from_secs(60));
+    tokio::pin!(delay);
+    loop {
+        tokio::select! {
+            _ = &mut delay => {
+                panic!("Completed event should have arrived by now.");
+            }
+            event = scanner_event_stream.recv() => {
+                if let UtxoScannerEvent::Completed { final_height, .. } = event.unwrap() {
+                    assert_eq!(final_height, NUM_BLOCKS - 1);
+                    break;
+                }
+            }
+        }
+    }
+
+    // The outputs scanned before the interruption are not imported again
+    let expected_imports = wallet_outputs
+        .iter()
+        .filter(|(h, _)| **h > LAST_SCANNED_HEIGHT)
+        .map(|(_, outputs)| outputs.len())
+        .sum::<usize>() -
+        usize::try_from(OUTPUTS_SCANNED_BEFORE_INTERRUPT).unwrap();
+    let num_imports = test_interface
+        .transaction_service_mock_state
+        .drain_requests()
+        .into_iter()
+        .filter(|req| matches!(req, TransactionServiceRequest::ImportUtxoWithStatus { .. }))
+        .count();
+    assert_eq!(num_imports, expected_imports);
+
+    // The checkpoint is removed once the scan completes
+    assert!(test_interface
+        .wallet_db
+        .get_client_key_value(SCAN_CHECKPOINT_KEY.to_string())
+        .unwrap()
+        .is_none());
+}
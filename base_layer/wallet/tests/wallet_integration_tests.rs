@@ -25,3 +25,4 @@ mod output_manager_service_tests;
 pub mod support;
 mod transaction_service_tests;
 mod utxo_scanner;
+mod wallet_manager;
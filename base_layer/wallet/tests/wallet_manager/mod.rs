@@ -0,0 +1,133 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{path::Path, sync::Arc};
+
+use minotari_wallet::{
+    output_manager_service::storage::database::OutputManagerDatabase,
+    storage::{database::WalletDatabase, sqlite_utilities::initialize_sqlite_database_backends},
+    wallet::read_or_create_master_seed,
+    wallet_manager::{WalletId, WalletManager},
+    Wallet,
+    WalletConfig,
+    WalletSqlite,
+};
+use rand::rngs::OsRng;
+use tari_common::configuration::Network;
+use tari_comms::peer_manager::{NodeIdentity, PeerFeatures};
+use tari_comms_dht::DhtConfig;
+use tari_core::{consensus::ConsensusManager, transactions::CryptoFactories};
+use tari_p2p::{
+    auto_update::AutoUpdateConfig,
+    transport::MemoryTransportConfig,
+    P2pConfig,
+    PeerSeedsConfig,
+    TransportConfig,
+};
+use tari_shutdown::{Shutdown, ShutdownSignal};
+use tari_test_utils::{comms_and_services::get_next_memory_address, random};
+use tari_utilities::SafePassword;
+use tempfile::tempdir;
+
+async fn start_wallet(data_path: &Path, shutdown_signal: ShutdownSignal) -> WalletSqlite {
+    let node_identity = NodeIdentity::random(&mut OsRng, get_next_memory_address(), PeerFeatures::COMMUNICATION_NODE);
+    let config = WalletConfig {
+        p2p: P2pConfig {
+            transport: TransportConfig::new_memory(MemoryTransportConfig {
+                listener_address: node_identity.first_public_address().unwrap(),
+            }),
+            datastore_path: data_path.to_path_buf(),
+            peer_database_name: random::string(8),
+            dht: DhtConfig::default_local_test(),
+            allow_test_addresses: true,
+            ..Default::default()
+        },
+        network: Network::LocalNet,
+        ..Default::default()
+    };
+    let (wallet_backend, transaction_backend, output_manager_backend, contacts_backend, key_manager_backend) =
+        initialize_sqlite_database_backends(
+            data_path.join("wallet.sqlite3"),
+            SafePassword::from("a wallet passphrase"),
+            16,
+        )
+        .unwrap();
+    let wallet_db = WalletDatabase::new(wallet_backend);
+    let master_seed = read_or_create_master_seed(None, None, &wallet_db).unwrap();
+
+    Wallet::start(
+        config,
+        PeerSeedsConfig::default(),
+        AutoUpdateConfig::default(),
+        Arc::new(node_identity),
+        ConsensusManager::builder(Network::LocalNet).build().unwrap(),
+        CryptoFactories::default(),
+        wallet_db,
+        OutputManagerDatabase::new(output_manager_backend.clone()),
+        transaction_backend,
+        output_manager_backend,
+        contacts_backend,
+        key_manager_backend,
+        shutdown_signal,
+        master_seed,
+    )
+    .await
+    .unwrap()
+}
+
+/// Two wallets with their own databases and identities run side by side in the test runtime, and stopping one leaves
+/// the other running
+#[tokio::test]
+async fn it_runs_two_wallets_in_one_runtime() {
+    let mut shutdown = Shutdown::new();
+    let mut manager = WalletManager::new(shutdown.to_signal());
+    let (alice, bob) = (WalletId::from("alice"), WalletId::from("bob"));
+    let (alice_dir, bob_dir) = (tempdir().unwrap(), tempdir().unwrap());
+
+    let signal = manager.reserve(alice.clone()).unwrap();
+    let wallet = start_wallet(alice_dir.path(), signal).await;
+    manager.insert(&alice, wallet).unwrap();
+    let signal = manager.reserve(bob.clone()).unwrap();
+    let wallet = start_wallet(bob_dir.path(), signal).await;
+    manager.insert(&bob, wallet).unwrap();
+    assert_eq!(manager.len(), 2);
+    assert!(manager.reserve(alice.clone()).is_err());
+
+    let alice_key = manager.get(&alice).unwrap().comms.node_identity().public_key().clone();
+    let bob_key = manager.get(&bob).unwrap().comms.node_identity().public_key().clone();
+    assert_ne!(alice_key, bob_key);
+
+    // Each wallet answers through its own service handles
+    for id in [&alice, &bob] {
+        let mut output_manager = manager.get(id).unwrap().output_manager_service.clone();
+        output_manager.get_balance().await.unwrap();
+    }
+
+    manager.stop(&alice).await.unwrap();
+    assert!(manager.get(&alice).is_none());
+    let mut output_manager = manager.get(&bob).unwrap().output_manager_service.clone();
+    output_manager.get_balance().await.unwrap();
+
+    shutdown.trigger();
+    manager.stop_all().await;
+    assert!(manager.is_empty());
+}
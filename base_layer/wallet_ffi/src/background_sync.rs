@@ -0,0 +1,143 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::time::Duration;
+
+use log::*;
+use minotari_wallet::{
+    connectivity_service::{WalletConnectivityHandle, WalletConnectivityInterface},
+    error::WalletError,
+    storage::sqlite_db::wallet::WalletSqliteDatabase,
+    transaction_service::handle::{TransactionEvent, TransactionEventReceiver},
+    utxo_scanner_service::{service::UtxoScannerService, uxto_scanner_service_builder::UtxoScannerMode},
+    OperationId,
+    WalletSqlite,
+};
+use tari_shutdown::ShutdownSignal;
+use tokio::{
+    sync::broadcast::error::RecvError,
+    time::{timeout_at, Instant},
+};
+
+use crate::TariBackgroundSyncReport;
+
+const LOG_TARGET: &str = "wallet_ffi::background_sync";
+
+/// The most work a single lightweight sync will do
+#[derive(Debug, Clone, Copy)]
+pub struct BackgroundSyncLimits {
+    pub max_outbound_messages: usize,
+    pub max_transactions: usize,
+    pub max_blocks: u64,
+    pub time_budget: Duration,
+}
+
+/// Does a bounded amount of sync work so that it fits in the short background task windows given to mobile apps. The
+/// queued outbound messages are flushed first, then a batch of unconfirmed transactions is validated and finally a
+/// bounded number of blocks is scanned. Work that does not fit in the time budget is left for the next sync and
+/// reported as remaining.
+pub async fn run_background_sync(
+    wallet: &WalletSqlite,
+    limits: BackgroundSyncLimits,
+    shutdown_signal: ShutdownSignal,
+) -> Result<TariBackgroundSyncReport, WalletError> {
+    let deadline = Instant::now() + limits.time_budget;
+    let mut report = TariBackgroundSyncReport::default();
+    let mut transaction_service = wallet.transaction_service.clone();
+
+    let (sent, remaining) = transaction_service
+        .resend_queued_outbound_messages(limits.max_outbound_messages)
+        .await?;
+    report.outbound_messages_sent = sent as u64;
+    report.outbound_messages_remaining = remaining as u64;
+
+    let mut transaction_events = transaction_service.get_event_stream();
+    let validation = transaction_service
+        .validate_transaction_batch(limits.max_transactions)
+        .await?;
+    report.transactions_remaining = validation.remaining as u64;
+    match timeout_at(
+        deadline,
+        wait_for_validation(&mut transaction_events, validation.operation_id),
+    )
+    .await
+    {
+        Ok(true) => report.transactions_validated = validation.num_transactions as u64,
+        Ok(false) => report.transactions_remaining += validation.num_transactions as u64,
+        Err(_) => {
+            debug!(target: LOG_TARGET, "Time budget ran out while validating transactions");
+            report.transactions_remaining += validation.num_transactions as u64;
+            report.timed_out = true;
+            return Ok(report);
+        },
+    }
+
+    let Some(base_node) = wallet.wallet_connectivity.get_current_base_node_peer_public_key() else {
+        debug!(target: LOG_TARGET, "No base node is set, skipping scanning");
+        return Ok(report);
+    };
+    let scanner = UtxoScannerService::<WalletSqliteDatabase, WalletConnectivityHandle>::builder()
+        .with_mode(UtxoScannerMode::Scanning)
+        .with_peers(vec![base_node])
+        .with_max_blocks(limits.max_blocks)
+        .with_retry_limit(1)
+        .build_with_wallet(wallet, shutdown_signal);
+    if timeout_at(deadline, scanner.run_once()).await.is_err() {
+        debug!(target: LOG_TARGET, "Time budget ran out while scanning blocks");
+        report.timed_out = true;
+    }
+
+    report.scanned_height = wallet
+        .db
+        .get_scanned_blocks()?
+        .iter()
+        .map(|block| block.height)
+        .max()
+        .unwrap_or_default();
+    let tip_height = wallet
+        .base_node_service
+        .clone()
+        .get_chain_metadata()
+        .await?
+        .map(|metadata| metadata.height_of_longest_chain())
+        .unwrap_or_default();
+    report.blocks_remaining = tip_height.saturating_sub(report.scanned_height);
+
+    Ok(report)
+}
+
+/// Waits for the validation with the given operation id to finish, returning whether it succeeded
+async fn wait_for_validation(events: &mut TransactionEventReceiver, operation_id: OperationId) -> bool {
+    loop {
+        match events.recv().await {
+            Ok(event) => match &*event {
+                TransactionEvent::TransactionValidationCompleted(id) if *id == operation_id => return true,
+                TransactionEvent::TransactionValidationFailed(id, _) if *id == operation_id => return false,
+                _ => {},
+            },
+            Err(RecvError::Lagged(n)) => {
+                warn!(target: LOG_TARGET, "Missed {} transaction events while validating", n);
+            },
+            Err(RecvError::Closed) => return false,
+        }
+    }
+}
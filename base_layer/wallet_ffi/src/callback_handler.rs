@@ -34,6 +34,10 @@
 //! `callback_base_node_sync_complete` - This is called when a Base Node Sync process is completed or times out. The
 //! request_key is used to identify which request this callback references and a result of true means it was successful
 //! and false that the process timed out and new one will be started
+//!
+//! `callback_clock_skew` - This is called when the local clock drifts beyond, or back within, the tolerated offset from
+//! the network. The first parameter is true while the clock is skewed and the second is the estimated offset in
+//! milliseconds
 
 use std::{ops::Deref, sync::Arc};
 
@@ -59,6 +63,7 @@ use minotari_wallet::{
 use tari_common_types::{tari_address::TariAddress, transaction::TxId, types::BlockHash};
 use tari_comms_dht::event::{DhtEvent, DhtEventReceiver};
 use tari_contacts::contacts_service::handle::{ContactsLivenessData, ContactsLivenessEvent};
+use tari_p2p::services::liveness::{ClockSkew, LivenessEvent, LivenessEventReceiver};
 use tari_shutdown::ShutdownSignal;
 use tokio::sync::{broadcast, watch};
 
@@ -86,6 +91,7 @@ where TBackend: TransactionBackend + 'static
     callback_saf_messages_received: unsafe extern "C" fn(),
     callback_connectivity_status: unsafe extern "C" fn(u64),
     callback_base_node_state: unsafe extern "C" fn(*mut TariBaseNodeState),
+    callback_clock_skew: unsafe extern "C" fn(bool, i64),
     db: TransactionDatabase<TBackend>,
     base_node_service_event_stream: BaseNodeEventReceiver,
     transaction_service_event_stream: TransactionEventReceiver,
@@ -97,6 +103,7 @@ where TBackend: TransactionBackend + 'static
     balance_cache: Balance,
     connectivity_status_watch: watch::Receiver<OnlineStatus>,
     contacts_liveness_events: broadcast::Receiver<Arc<ContactsLivenessEvent>>,
+    liveness_events: LivenessEventReceiver,
 }
 
 impl<TBackend> CallbackHandler<TBackend>
@@ -114,6 +121,7 @@ where TBackend: TransactionBackend + 'static
         comms_address: TariAddress,
         connectivity_status_watch: watch::Receiver<OnlineStatus>,
         contacts_liveness_events: broadcast::Receiver<Arc<ContactsLivenessEvent>>,
+        liveness_events: LivenessEventReceiver,
         callback_received_transaction: unsafe extern "C" fn(*mut InboundTransaction),
         callback_received_transaction_reply: unsafe extern "C" fn(*mut CompletedTransaction),
         callback_received_finalized_transaction: unsafe extern "C" fn(*mut CompletedTransaction),
@@ -131,6 +139,7 @@ where TBackend: TransactionBackend + 'static
         callback_saf_messages_received: unsafe extern "C" fn(),
         callback_connectivity_status: unsafe extern "C" fn(u64),
         callback_base_node_state: unsafe extern "C" fn(*mut TariBaseNodeState),
+        callback_clock_skew: unsafe extern "C" fn(bool, i64),
     ) -> Self {
         info!(
             target: LOG_TARGET,
@@ -196,6 +205,10 @@ where TBackend: TransactionBackend + 'static
             target: LOG_TARGET,
             "ConnectivityStatusCallback -> Assigning Fn:  {:?}", callback_connectivity_status
         );
+        info!(
+            target: LOG_TARGET,
+            "ClockSkewCallback -> Assigning Fn:  {:?}", callback_clock_skew
+        );
 
         Self {
             callback_received_transaction,
@@ -215,6 +228,7 @@ where TBackend: TransactionBackend + 'static
             callback_saf_messages_received,
             callback_connectivity_status,
             callback_base_node_state,
+            callback_clock_skew,
             db,
             base_node_service_event_stream,
             transaction_service_event_stream,
@@ -226,6 +240,7 @@ where TBackend: TransactionBackend + 'static
             balance_cache: Balance::zero(),
             connectivity_status_watch,
             contacts_liveness_events,
+            liveness_events,
         }
     }
 
@@ -328,6 +343,20 @@ where TBackend: TransactionBackend + 'static
                                 OutputManagerEvent::TxoValidationCommunicationFailure(request_key) => {
                                     self.output_validation_complete_event(request_key,  3);
                                 },
+                                // Changes to individual outputs have no callbacks of their own, they are reported
+                                // through the balance they change
+                                OutputManagerEvent::OutputsEncumbered(_) |
+                                OutputManagerEvent::OutputsReleased(_) |
+                                OutputManagerEvent::OutputInvalidated(_) |
+                                OutputManagerEvent::OutputImported { .. } => {
+                                    self.trigger_balance_refresh().await;
+                                },
+                                // A consolidation is submitted as a transaction, so it is reported through the
+                                // transaction callbacks
+                                OutputManagerEvent::ConsolidationTransactionCreated { .. } => (),
+                                OutputManagerEvent::ConsolidationFailed(reason) => {
+                                    warn!(target: LOG_TARGET, "Background consolidation failed: {}", reason);
+                                },
                             }
                         },
                         Err(_e) => error!(target: LOG_TARGET, "Error reading from Output Manager Service event broadcast channel"),
@@ -362,7 +391,8 @@ where TBackend: TransactionBackend + 'static
                                     self.base_node_state_changed(state);
                                 },
 
-                                BaseNodeEvent::NewBlockDetected(_hash, _new_block_number) => {
+                                BaseNodeEvent::NewBlockDetected(_hash, _new_block_number) |
+                                BaseNodeEvent::Reorged { .. } => {
                                     //
                                 },
                             }
@@ -381,7 +411,9 @@ where TBackend: TransactionBackend + 'static
                                     );
                                     self.trigger_contacts_refresh(data.deref().clone());
                                 }
-                                ContactsLivenessEvent::NetworkSilence => {},
+                                ContactsLivenessEvent::NetworkSilence |
+                                ContactsLivenessEvent::ContactSaved(_) |
+                                ContactsLivenessEvent::ContactRemoved(_) => {},
                             }
                         }
                         Err(broadcast::error::RecvError::Lagged(n)) => {
@@ -390,6 +422,21 @@ where TBackend: TransactionBackend + 'static
                         Err(broadcast::error::RecvError::Closed) => {}
                     }
                 }
+
+                event = self.liveness_events.recv() => {
+                    match event {
+                        Ok(msg) => match &*msg {
+                            LivenessEvent::ClockSkewDetected(skew) => self.clock_skew_changed(true, skew),
+                            LivenessEvent::ClockSkewResolved(skew) => self.clock_skew_changed(false, skew),
+                            // Only clock skew is mapped to a callback, contact liveness has its own events
+                            _ => (),
+                        },
+                        Err(broadcast::error::RecvError::Lagged(n)) => {
+                            warn!(target: LOG_TARGET, "Missed {} from Liveness Service events", n);
+                        }
+                        Err(broadcast::error::RecvError::Closed) => {}
+                    }
+                }
                  _ = shutdown_signal.wait() => {
                     info!(target: LOG_TARGET, "Transaction Callback Handler shutting down because the shutdown signal was received");
                     break;
@@ -652,6 +699,16 @@ where TBackend: TransactionBackend + 'static
         }
     }
 
+    fn clock_skew_changed(&mut self, is_skewed: bool, skew: &ClockSkew) {
+        debug!(
+            target: LOG_TARGET,
+            "Calling Clock Skew callback function, skewed: {}, offset: {}", is_skewed, skew
+        );
+        unsafe {
+            (self.callback_clock_skew)(is_skewed, skew.offset_ms().unwrap_or_default());
+        }
+    }
+
     // casting here is okay as we dont care about the super high latency
     #[allow(clippy::cast_possible_truncation)]
     fn base_node_state_changed(&mut self, state: BaseNodeState) {
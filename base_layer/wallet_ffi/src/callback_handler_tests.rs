@@ -51,6 +51,7 @@ mod test {
         SenderTransactionProtocol,
     };
     use tari_crypto::keys::{PublicKey as PublicKeyTrait, SecretKey};
+    use tari_p2p::services::liveness::{ClockSkew, LivenessEvent};
     use tari_service_framework::reply_channel;
     use tari_shutdown::Shutdown;
     use tokio::{
@@ -92,6 +93,7 @@ mod test {
         pub saf_messages_received: bool,
         pub connectivity_status_callback_called: u64,
         pub base_node_state_changed_callback_invoked: bool,
+        pub clock_skew_callback_offset_ms: Option<i64>,
     }
 
     impl CallbackState {
@@ -121,6 +123,7 @@ mod test {
                 saf_messages_received: false,
                 connectivity_status_callback_called: 0,
                 base_node_state_changed_callback_invoked: false,
+                clock_skew_callback_offset_ms: None,
             }
         }
     }
@@ -263,6 +266,12 @@ mod test {
         drop(Box::from_raw(state))
     }
 
+    unsafe extern "C" fn clock_skew_callback(is_skewed: bool, offset_ms: i64) {
+        let mut lock = CALLBACK_STATE.lock().unwrap();
+        lock.clock_skew_callback_offset_ms = if is_skewed { Some(offset_ms) } else { None };
+        drop(lock);
+    }
+
     #[test]
     // casting casting is okay in tests
     #[allow(clippy::cast_possible_truncation)]
@@ -456,6 +465,7 @@ mod test {
         let (connectivity_tx, connectivity_rx) = watch::channel(OnlineStatus::Offline);
         let (contacts_liveness_events_sender, _) = broadcast::channel(250);
         let contacts_liveness_events = contacts_liveness_events_sender.subscribe();
+        let (liveness_events_sender, liveness_events) = broadcast::channel(250);
         let comms_address = TariAddress::new(
             PublicKey::from_secret_key(&PrivateKey::random(&mut OsRng)),
             Network::LocalNet,
@@ -472,6 +482,7 @@ mod test {
             comms_address,
             connectivity_rx,
             contacts_liveness_events,
+            liveness_events,
             received_tx_callback,
             received_tx_reply_callback,
             received_tx_finalized_callback,
@@ -489,6 +500,7 @@ mod test {
             saf_messages_received_callback,
             connectivity_status_callback,
             base_node_state_changed_callback,
+            clock_skew_callback,
         );
 
         runtime.spawn(callback_handler.start());
@@ -790,6 +802,26 @@ mod test {
         }
         assert_eq!(callback_balance_updated, 7);
 
+        balance.available_balance -= faux_confirmed_tx.amount;
+        balance.pending_outgoing_balance += faux_confirmed_tx.amount;
+        mock_output_manager_service_state.set_balance(balance.clone());
+        // Balance updated should be detected with following event, total = 8 times
+        oms_event_sender
+            .send(Arc::new(OutputManagerEvent::OutputsEncumbered(8u64.into())))
+            .unwrap();
+        let start = Instant::now();
+        while start.elapsed().as_secs() < 10 {
+            {
+                let lock = CALLBACK_STATE.lock().unwrap();
+                if lock.callback_balance_updated == 8 {
+                    callback_balance_updated = 8;
+                    break;
+                }
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        assert_eq!(callback_balance_updated, 8);
+
         let contact = Contact::new(
             "My friend".to_string(),
             faux_unconfirmed_tx.destination_address,
@@ -832,6 +864,14 @@ mod test {
         thread::sleep(Duration::from_secs(2));
         connectivity_tx.send(OnlineStatus::Connecting).unwrap();
 
+        liveness_events_sender
+            .send(Arc::new(LivenessEvent::ClockSkewDetected(ClockSkew {
+                peer_offset_ms: Some(-90_000),
+                num_peer_samples: 5,
+                ntp_offset_ms: None,
+            })))
+            .unwrap();
+
         thread::sleep(Duration::from_secs(10));
 
         let lock = CALLBACK_STATE.lock().unwrap();
@@ -855,9 +895,10 @@ mod test {
         assert!(lock.callback_txo_validation_already_busy);
         assert!(lock.callback_txo_validation_internal_failure);
         assert_eq!(lock.callback_contacts_liveness_data_updated, 2);
-        assert_eq!(lock.callback_balance_updated, 7);
+        assert_eq!(lock.callback_balance_updated, 8);
         assert_eq!(lock.callback_transaction_validation_complete, 13);
         assert_eq!(lock.connectivity_status_callback_called, 7);
+        assert_eq!(lock.clock_skew_callback_offset_ms, Some(-90_000));
 
         drop(lock);
     }
@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 use log::*;
 use minotari_wallet::{
-    error::{WalletError, WalletStorageError},
+    error::{ErrorHint, WalletError, WalletStorageError},
     output_manager_service::error::{OutputManagerError, OutputManagerStorageError},
     transaction_service::error::{TransactionServiceError, TransactionStorageError},
 };
@@ -122,6 +122,10 @@ impl From<WalletError> for LibWalletError {
                 code: 115,
                 message: format!("{:?}", w),
             },
+            WalletError::OutputManagerError(OutputManagerError::FundsTimeLocked(_)) => Self {
+                code: 116,
+                message: format!("{:?}", w),
+            },
             WalletError::OutputManagerError(OutputManagerError::IncompleteTransaction(_)) => Self {
                 code: 102,
                 message: format!("{:?}", w),
@@ -188,6 +192,11 @@ impl From<WalletError> for LibWalletError {
                 code: 113,
                 message: format!("{:?}", w),
             },
+            // Errors that may succeed if retried unchanged, see `LibWalletError::is_retryable_code`
+            WalletError::OutputManagerError(ref e) if e.retry_hint().is_retryable() => Self {
+                code: 117,
+                message: format!("{:?}", w),
+            },
             WalletError::OutputManagerError(_) => Self {
                 code: 114,
                 message: format!("{:?}", w),
@@ -215,6 +224,14 @@ impl From<WalletError> for LibWalletError {
                 code: 212,
                 message: format!("{:?}", w),
             },
+            WalletError::TransactionServiceError(TransactionServiceError::OutputManagerError(ref e))
+                if e.retry_hint().is_retryable() =>
+            {
+                Self {
+                    code: 117,
+                    message: format!("{:?}", w),
+                }
+            },
             WalletError::TransactionServiceError(TransactionServiceError::OutputManagerError(_)) => Self {
                 code: 206,
                 message: format!("{:?}", w),
@@ -231,6 +248,10 @@ impl From<WalletError> for LibWalletError {
                 code: 212,
                 message: format!("{:?}", w),
             },
+            WalletError::TransactionServiceError(ref e) if e.retry_hint().is_retryable() => Self {
+                code: 213,
+                message: format!("{:?}", w),
+            },
             WalletError::TransactionServiceError(_) => Self {
                 code: 211,
                 message: format!("{:?}", w),
@@ -314,6 +335,18 @@ impl From<WalletError> for LibWalletError {
                 code: 432,
                 message: format!("{:?}", w),
             },
+            WalletError::KeyManagerError(KeyManagerError::InvalidShareThreshold { .. }) => Self {
+                code: 435,
+                message: format!("{:?}", w),
+            },
+            WalletError::KeyManagerError(KeyManagerError::InsufficientSeedShares(_)) => Self {
+                code: 436,
+                message: format!("{:?}", w),
+            },
+            WalletError::KeyManagerError(KeyManagerError::SeedShareMismatch) => Self {
+                code: 437,
+                message: format!("{:?}", w),
+            },
             WalletError::WalletStorageError(WalletStorageError::IoError(_)) => Self {
                 code: 433,
                 message: format!("{:?}", w),
@@ -352,6 +385,14 @@ impl From<WalletError> for LibWalletError {
     }
 }
 
+impl LibWalletError {
+    /// Returns true if a request that failed with this error code may succeed if it is retried unchanged later, e.g.
+    /// once pending funds are confirmed or the base node can be reached again
+    pub fn is_retryable_code(code: i32) -> bool {
+        matches!(code, 109 | 115 | 116 | 117 | 210 | 213 | 995)
+    }
+}
+
 /// This implementation maps the internal HexError to a set of LibWalletErrors.
 /// The mapping is explicitly managed here.
 impl From<HexError> for LibWalletError {
@@ -414,6 +455,14 @@ impl From<TariAddressError> for LibWalletError {
                 code: 704,
                 message: format!("{:?}", e),
             },
+            TariAddressError::InvalidHex => Self {
+                code: 705,
+                message: format!("{:?}", e),
+            },
+            TariAddressError::NetworkMismatch { .. } => Self {
+                code: 706,
+                message: format!("{:?}", e),
+            },
         }
     }
 }
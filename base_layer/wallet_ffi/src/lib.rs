@@ -104,12 +104,14 @@ use minotari_wallet::{
     transaction_service::{
         config::TransactionServiceConfig,
         error::TransactionServiceError,
+        handle::{FeePriority, ScheduledPayment, SendOrQueueResult},
+        offline_signing::{SignedTransaction, UnsignedTransaction},
         storage::{
             database::TransactionDatabase,
             models::{CompletedTransaction, InboundTransaction, OutboundTransaction},
         },
     },
-    utxo_scanner_service::{service::UtxoScannerService, RECOVERY_KEY},
+    utxo_scanner_service::{service::UtxoScannerService, throttle::ScanThrottle, RECOVERY_KEY},
     wallet::{derive_comms_secret_key, read_or_create_master_seed, WalletMessageSigningDomain},
     Wallet,
     WalletConfig,
@@ -131,7 +133,7 @@ use tari_comms::{
     types::CommsPublicKey,
 };
 use tari_comms_dht::{store_forward::SafConfig, DbConnectionUrl, DhtConfig};
-use tari_contacts::contacts_service::types::Contact;
+use tari_contacts::contacts_service::types::{Contact, ContactLivenessPolicy};
 use tari_core::{
     borsh::FromBytes,
     consensus::ConsensusManager,
@@ -145,7 +147,12 @@ use tari_crypto::{
     keys::{PublicKey as PublicKeyTrait, SecretKey},
     tari_utilities::{ByteArray, Hidden},
 };
-use tari_key_manager::{cipher_seed::CipherSeed, mnemonic::MnemonicLanguage, SeedWords};
+use tari_key_manager::{
+    cipher_seed::CipherSeed,
+    mnemonic::MnemonicLanguage,
+    seed_shares::{combine_seed_share_words, SeedShare},
+    SeedWords,
+};
 use tari_p2p::{
     auto_update::AutoUpdateConfig,
     transport::MemoryTransportConfig,
@@ -176,6 +183,7 @@ use crate::{
     tasks::recovery_event_monitoring,
 };
 
+mod background_sync;
 mod callback_handler;
 #[cfg(test)]
 mod callback_handler_tests;
@@ -237,6 +245,9 @@ pub struct EmojiSet(Vec<ByteVector>);
 #[derive(Debug, PartialEq)]
 pub struct TariSeedWords(SeedWords);
 
+#[derive(Debug, PartialEq)]
+pub struct TariSeedShares(Vec<SeedWords>);
+
 #[derive(Debug, PartialEq)]
 pub struct TariPublicKeys(Vec<TariPublicKey>);
 
@@ -253,6 +264,19 @@ pub struct TariCoinPreview {
     pub fee: u64,
 }
 
+/// Summary of the work done by `wallet_background_sync` and what was left for the next run
+#[derive(Debug, Default)]
+#[repr(C)]
+pub struct TariBackgroundSyncReport {
+    pub outbound_messages_sent: u64,
+    pub outbound_messages_remaining: u64,
+    pub transactions_validated: u64,
+    pub transactions_remaining: u64,
+    pub scanned_height: u64,
+    pub blocks_remaining: u64,
+    pub timed_out: bool,
+}
+
 #[derive(Debug)]
 #[repr(C)]
 pub enum TariUtxoSort {
@@ -595,6 +619,23 @@ pub unsafe extern "C" fn destroy_tari_coin_preview(p: *mut TariCoinPreview) {
     }
 }
 
+/// Frees memory allocated for `TariBackgroundSyncReport`.
+///
+/// ## Arguments
+/// `report` - The pointer to `TariBackgroundSyncReport`
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn destroy_tari_background_sync_report(report: *mut TariBackgroundSyncReport) {
+    if !report.is_null() {
+        drop(Box::from_raw(report));
+    }
+}
+
 /// -------------------------------- Strings ------------------------------------------------ ///
 
 /// Frees memory for a char array
@@ -1132,8 +1173,6 @@ pub unsafe extern "C" fn tari_address_get_bytes(
 ///
 /// # Safety
 /// The ```private_key_destroy``` method must be called when finished with a private key to prevent a memory leak
-// casting here is network is a u8
-#[allow(clippy::cast_possible_truncation)]
 #[no_mangle]
 pub unsafe extern "C" fn tari_address_from_private_key(
     secret_key: *mut TariPrivateKey,
@@ -1148,9 +1187,9 @@ pub unsafe extern "C" fn tari_address_from_private_key(
         return ptr::null_mut();
     }
     let key = PublicKey::from_secret_key(&(*secret_key));
-    let network = match (network as u8).try_into() {
-        Ok(network) => network,
-        Err(_) => {
+    let network = match u8::try_from(network).ok().and_then(|n| Network::try_from(n).ok()) {
+        Some(network) => network,
+        None => {
             error = LibWalletError::from(InterfaceError::InvalidArgument("network".to_string())).code;
             ptr::swap(error_out, &mut error as *mut c_int);
             return ptr::null_mut();
@@ -1280,6 +1319,153 @@ pub unsafe extern "C" fn emoji_id_to_tari_address(
     }
 }
 
+/// Creates a TariWalletAddress from a char array in either emoji or hex format
+///
+/// ## Arguments
+/// `address` - The pointer to a char array containing an emoji ID or a hex encoded address
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter. The error code identifies why the address is invalid, i.e. invalid hex (705), invalid emoji
+/// (704), invalid size (703), invalid network or checksum (701) or an invalid public key (702).
+///
+/// ## Returns
+/// `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns null on error.
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory
+/// leak
+#[no_mangle]
+pub unsafe extern "C" fn tari_address_from_string(
+    address: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let address_str = match CStr::from_ptr(address).to_str() {
+        Ok(v) => v,
+        _ => {
+            error = LibWalletError::from(InterfaceError::PointerError("address".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    match TariWalletAddress::parse(address_str) {
+        Ok(address) => Box::into_raw(Box::new(address)),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Validates a char array containing an emoji ID or a hex encoded address for the given network
+///
+/// ## Arguments
+/// `address` - The pointer to a char array containing an emoji ID or a hex encoded address
+/// `network` - The network the address is expected to belong to
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter. When the address is invalid the error code gives the reason, as for
+/// `tari_address_from_string`, or 706 if the address is valid but belongs to a different network.
+///
+/// ## Returns
+/// `bool` - Returns true if the address is valid for the network, otherwise false
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn tari_address_validate(address: *const c_char, network: c_uint, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    let address_str = match CStr::from_ptr(address).to_str() {
+        Ok(v) => v,
+        _ => {
+            error = LibWalletError::from(InterfaceError::PointerError("address".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+    };
+    let network = match u8::try_from(network).ok().and_then(|n| Network::try_from(n).ok()) {
+        Some(network) => network,
+        None => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("network".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+    };
+
+    match TariWalletAddress::parse_with_network(address_str, network) {
+        Ok(_) => true,
+        Err(e) => {
+            debug!(target: LOG_TARGET, "Address '{}' is not valid: {}", address_str, e);
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Creates a char array from a TariWalletAddress in hex format
+///
+/// ## Arguments
+/// `address` - The pointer to a TariWalletAddress
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns empty if address is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn tari_address_to_hex(address: *mut TariWalletAddress, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+    result = CString::new((*address).to_hex()).expect("Hex will not fail.");
+    CString::into_raw(result)
+}
+
+/// Gets the network a TariWalletAddress belongs to
+///
+/// ## Arguments
+/// `address` - The pointer to a TariWalletAddress
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `c_uint` - Returns the network byte, as accepted by `tari_address_from_private_key`. Note that it returns 0 if the
+/// address is null
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn tari_address_get_network(address: *mut TariWalletAddress, error_out: *mut c_int) -> c_uint {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    c_uint::from((*address).network().as_byte())
+}
+
 /// -------------------------------------------------------------------------------------------- ///
 ///
 /// ------------------------------- ComAndPubSignature Signature ---------------------------------------///
@@ -2639,152 +2825,329 @@ pub unsafe extern "C" fn seed_words_destroy(seed_words: *mut TariSeedWords) {
     }
 }
 
-/// -------------------------------------------------------------------------------------------- ///
-
-/// ----------------------------------- Contact -------------------------------------------------///
+/// Create an empty instance of TariSeedShares, to collect the shares a seed is recovered from
+///
+/// ## Arguments
+/// None
+///
+/// ## Returns
+/// `TariSeedShares` - Returns an empty TariSeedShares instance
+///
+/// # Safety
+/// The `seed_shares_destroy` method must be called when finished with a TariSeedShares to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn seed_shares_create() -> *mut TariSeedShares {
+    Box::into_raw(Box::new(TariSeedShares(vec![])))
+}
 
-/// Creates a TariContact
+/// Gets the number of shares in TariSeedShares
 ///
 /// ## Arguments
-/// `alias` - The pointer to a char array
-/// `address` - The pointer to a TariWalletAddress
+/// `seed_shares` - The pointer to a TariSeedShares
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `*mut TariContact` - Returns a pointer to a TariContact. Note that it returns ptr::null_mut()
-/// if alias is null or if pk is null
+/// `c_uint` - Returns number of shares in seed_shares, zero if seed_shares is null
 ///
 /// # Safety
-/// The ```contact_destroy``` method must be called when finished with a TariContact
+/// None
 #[no_mangle]
-pub unsafe extern "C" fn contact_create(
-    alias: *const c_char,
-    address: *mut TariWalletAddress,
-    favourite: bool,
-    error_out: *mut c_int,
-) -> *mut TariContact {
+pub unsafe extern "C" fn seed_shares_get_length(seed_shares: *const TariSeedShares, error_out: *mut c_int) -> c_uint {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
-    let alias_string;
-    if alias.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("alias".to_string())).code;
+    let mut len = 0;
+    if seed_shares.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed shares".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-        return ptr::null_mut();
     } else {
-        match CStr::from_ptr(alias).to_str() {
-            Ok(v) => {
-                alias_string = v.to_owned();
-            },
-            _ => {
-                error = LibWalletError::from(InterfaceError::PointerError("alias".to_string())).code;
-                ptr::swap(error_out, &mut error as *mut c_int);
-                return ptr::null_mut();
-            },
-        }
+        len = (*seed_shares).0.len();
     }
-
-    if address.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        return ptr::null_mut();
-    }
-
-    let contact = Contact::new(alias_string, (*address).clone(), None, None, favourite);
-    Box::into_raw(Box::new(contact))
+    len as c_uint
 }
 
-/// Gets the alias of the TariContact
+/// Gets the seed words of the share in TariSeedShares at position
 ///
 /// ## Arguments
-/// `contact` - The pointer to a TariContact
+/// `seed_shares` - The pointer to a TariSeedShares
+/// `position` - The integer position
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if
-/// contact is null
+/// `*mut TariSeedWords` - Returns the seed words of the share, note that it returns ptr::null_mut() if seed_shares is
+/// null or position is invalid
 ///
 /// # Safety
-/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+/// The `seed_words_destroy` method must be called when finished with a TariSeedWords to prevent a memory leak
 #[no_mangle]
-pub unsafe extern "C" fn contact_get_alias(contact: *mut TariContact, error_out: *mut c_int) -> *mut c_char {
+pub unsafe extern "C" fn seed_shares_get_at(
+    seed_shares: *mut TariSeedShares,
+    position: c_uint,
+    error_out: *mut c_int,
+) -> *mut TariSeedWords {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
-    let mut a = CString::new("").expect("Blank CString will not fail.");
-    if contact.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+    if seed_shares.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed shares".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-    } else {
-        match CString::new((*contact).alias.clone()) {
-            Ok(v) => a = v,
-            _ => {
-                error = LibWalletError::from(InterfaceError::PointerError("contact".to_string())).code;
-                ptr::swap(error_out, &mut error as *mut c_int);
-            },
-        }
+        return ptr::null_mut();
+    }
+    match (*seed_shares).0.get(position as usize) {
+        Some(share) => Box::into_raw(Box::new(TariSeedWords(share.clone()))),
+        None => {
+            error = LibWalletError::from(InterfaceError::PositionInvalidError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
     }
-    CString::into_raw(a)
 }
 
-/// Gets the favourite status of the TariContact
+/// Adds the seed words of a share to the provided TariSeedShares instance
 ///
 /// ## Arguments
-/// `contact` - The pointer to a TariContact
+/// `seed_shares` - The pointer to a TariSeedShares
+/// `share` - The pointer to the TariSeedWords of one share
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `bool` - Returns a bool indicating the favourite status of a contact. NOTE this will return false if the pointer is
-/// null as well.
+/// `bool` - Returns true if the words are a valid share and were added, false otherwise
 ///
 /// # Safety
-/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+/// None
 #[no_mangle]
-pub unsafe extern "C" fn contact_get_favourite(contact: *mut TariContact, error_out: *mut c_int) -> bool {
+pub unsafe extern "C" fn seed_shares_push(
+    seed_shares: *mut TariSeedShares,
+    share: *const TariSeedWords,
+    error_out: *mut c_int,
+) -> bool {
     let mut error = 0;
-    let mut favourite = false;
     ptr::swap(error_out, &mut error as *mut c_int);
-    if contact.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+    if seed_shares.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed shares".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
-    } else {
-        favourite = (*contact).favourite;
+        return false;
+    }
+    if share.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("share".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
     }
 
-    favourite
+    match SeedShare::from_mnemonic(&(*share).0) {
+        Ok(_) => {
+            (*seed_shares).0.push((*share).0.clone());
+            true
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::KeyManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
 }
 
-/// Gets the TariWalletAddress of the TariContact
+/// Recovers the seed words of the seed the shares were split from. The returned seed words can be passed to
+/// `wallet_create` to recover the wallet.
 ///
 /// ## Arguments
-/// `contact` - The pointer to a TariContact
+/// `seed_shares` - The pointer to a TariSeedShares holding at least as many shares as are needed to recover the seed
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
 /// ## Returns
-/// `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns
-/// ptr::null_mut() if contact is null
+/// `*mut TariSeedWords` - The seed words of the seed, note that it returns ptr::null_mut() if there are not enough
+/// shares or they are not from the same split
 ///
 /// # Safety
-/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+/// The `seed_words_destroy` method must be called when finished with a TariSeedWords to prevent a memory leak
 #[no_mangle]
-pub unsafe extern "C" fn contact_get_tari_address(
-    contact: *mut TariContact,
+pub unsafe extern "C" fn seed_shares_combine(
+    seed_shares: *const TariSeedShares,
     error_out: *mut c_int,
-) -> *mut TariWalletAddress {
+) -> *mut TariSeedWords {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
-    if contact.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+    if seed_shares.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("seed shares".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
         return ptr::null_mut();
     }
-    Box::into_raw(Box::new((*contact).address.clone()))
-}
 
-/// Frees memory for a TariContact
-///
+    match combine_seed_share_words(&(*seed_shares).0) {
+        Ok(seed_words) => Box::into_raw(Box::new(TariSeedWords(seed_words))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::KeyManagerError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Frees memory for a TariSeedShares
+///
+/// ## Arguments
+/// `seed_shares` - The pointer to a TariSeedShares
+///
+/// ## Returns
+/// `()` - Does not return a value, equivalent to void in C
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn seed_shares_destroy(seed_shares: *mut TariSeedShares) {
+    if !seed_shares.is_null() {
+        drop(Box::from_raw(seed_shares))
+    }
+}
+
+/// -------------------------------------------------------------------------------------------- ///
+
+/// ----------------------------------- Contact -------------------------------------------------///
+
+/// Creates a TariContact
+///
+/// ## Arguments
+/// `alias` - The pointer to a char array
+/// `address` - The pointer to a TariWalletAddress
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariContact` - Returns a pointer to a TariContact. Note that it returns ptr::null_mut()
+/// if alias is null or if pk is null
+///
+/// # Safety
+/// The ```contact_destroy``` method must be called when finished with a TariContact
+#[no_mangle]
+pub unsafe extern "C" fn contact_create(
+    alias: *const c_char,
+    address: *mut TariWalletAddress,
+    favourite: bool,
+    error_out: *mut c_int,
+) -> *mut TariContact {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let alias_string;
+    if alias.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("alias".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    } else {
+        match CStr::from_ptr(alias).to_str() {
+            Ok(v) => {
+                alias_string = v.to_owned();
+            },
+            _ => {
+                error = LibWalletError::from(InterfaceError::PointerError("alias".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    }
+
+    if address.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("address".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let contact = Contact::new(alias_string, (*address).clone(), None, None, favourite);
+    Box::into_raw(Box::new(contact))
+}
+
+/// Gets the alias of the TariContact
+///
+/// ## Arguments
+/// `contact` - The pointer to a TariContact
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if
+/// contact is null
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn contact_get_alias(contact: *mut TariContact, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut a = CString::new("").expect("Blank CString will not fail.");
+    if contact.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        match CString::new((*contact).alias.clone()) {
+            Ok(v) => a = v,
+            _ => {
+                error = LibWalletError::from(InterfaceError::PointerError("contact".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+            },
+        }
+    }
+    CString::into_raw(a)
+}
+
+/// Gets the favourite status of the TariContact
+///
+/// ## Arguments
+/// `contact` - The pointer to a TariContact
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns a bool indicating the favourite status of a contact. NOTE this will return false if the pointer is
+/// null as well.
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn contact_get_favourite(contact: *mut TariContact, error_out: *mut c_int) -> bool {
+    let mut error = 0;
+    let mut favourite = false;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if contact.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+    } else {
+        favourite = (*contact).favourite;
+    }
+
+    favourite
+}
+
+/// Gets the TariWalletAddress of the TariContact
+///
+/// ## Arguments
+/// `contact` - The pointer to a TariContact
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns
+/// ptr::null_mut() if contact is null
+///
+/// # Safety
+/// The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn contact_get_tari_address(
+    contact: *mut TariContact,
+    error_out: *mut c_int,
+) -> *mut TariWalletAddress {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if contact.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    Box::into_raw(Box::new((*contact).address.clone()))
+}
+
+/// Frees memory for a TariContact
+///
 /// ## Arguments
 /// `contact` - The pointer to a TariContact
 ///
@@ -4086,6 +4449,7 @@ pub unsafe extern "C" fn pending_outbound_transaction_get_message(
 /// |   2 | Mined       |
 /// |   3 | Imported    |
 /// |   4 | Pending     |
+/// |  12 | AwaitingCosigners |
 ///
 /// # Safety
 /// None
@@ -4826,6 +5190,8 @@ pub unsafe extern "C" fn comms_config_create(
                 },
                 allow_test_addresses: true,
                 listener_liveness_allowlist_cidrs: StringList::new(),
+                allowed_peers: StringList::new(),
+                denied_peers: StringList::new(),
                 listener_liveness_max_sessions: 0,
                 user_agent: format!("tari/mobile_wallet/{}", env!("CARGO_PKG_VERSION")),
                 rpc_max_simultaneous_sessions: 0,
@@ -5121,6 +5487,9 @@ unsafe fn init_logging(
 /// encrypted then the correct passphrase is required or this function will fail.
 /// `seed_words` - An optional instance of TariSeedWords, used to create a wallet for recovery purposes.
 /// If this is null, then a new master key is created for the wallet.
+/// `seed_passphrase` - An optional seed passphrase, the "25th word", combined with the master key of a new or recovered
+/// wallet. Each seed passphrase yields a different wallet from the same seed words. It is not needed when opening an
+/// existing wallet, if given it must match the one the wallet was created with.
 /// `callback_received_transaction` - The callback function pointer matching the function signature. This will be
 /// called when an inbound transaction is received.
 /// `callback_received_transaction_reply` - The callback function
@@ -5170,7 +5539,8 @@ unsafe fn init_logging(
 /// `callback_contacts_liveness_data_updated` - The callback function pointer matching the function signature. This is
 /// called when a contact's liveness status changed. The data represents the contact's updated status information.
 /// `callback_balance_updated` - The callback function pointer matching the function signature. This is called whenever
-/// the balance changes.
+/// the balance changes, including when outputs are encumbered, released, imported or invalidated, which have no
+/// callbacks of their own.
 /// `callback_transaction_validation_complete` - The callback function pointer matching the function signature. This is
 /// called when a Transaction validation process is completed. The request_key is used to identify which request this
 /// callback references and the second parameter is a u64 that returns if the validation was successful or not.
@@ -5188,6 +5558,12 @@ unsafe fn init_logging(
 ///     Online,         // 1
 ///     Offline,        // 2
 /// }
+/// `callback_base_node_state` - The callback function pointer matching the function signature. This is called when the
+/// state of the base node the wallet is connected to changes.
+/// `callback_clock_skew` - The callback function pointer matching the function signature. This is called when the
+/// local clock drifts beyond, or back within, the tolerated offset from the network. The first parameter is true while
+/// the clock is skewed and the second is the estimated offset in milliseconds, positive if the local clock is behind.
+/// Transactions with time locks may be rejected while the clock is skewed.
 /// `recovery_in_progress` - Pointer to an bool which will be modified to indicate if there is an outstanding recovery
 /// that should be completed or not to an error code should one occur, may not be null. Functions as an out parameter.
 /// `error_out` - Pointer to an int which will be modified
@@ -5208,6 +5584,7 @@ pub unsafe extern "C" fn wallet_create(
     size_per_log_file_bytes: c_uint,
     passphrase: *const c_char,
     seed_words: *const TariSeedWords,
+    seed_passphrase: *const c_char,
     network_str: *const c_char,
     callback_received_transaction: unsafe extern "C" fn(*mut TariPendingInboundTransaction),
     callback_received_transaction_reply: unsafe extern "C" fn(*mut TariCompletedTransaction),
@@ -5226,6 +5603,7 @@ pub unsafe extern "C" fn wallet_create(
     callback_saf_messages_received: unsafe extern "C" fn(),
     callback_connectivity_status: unsafe extern "C" fn(u64),
     callback_base_node_state: unsafe extern "C" fn(*mut TariBaseNodeState),
+    callback_clock_skew: unsafe extern "C" fn(bool, i64),
     recovery_in_progress: *mut bool,
     error_out: *mut c_int,
 ) -> *mut TariWallet {
@@ -5298,6 +5676,19 @@ pub unsafe extern "C" fn wallet_create(
         }
     };
 
+    let seed_passphrase = if seed_passphrase.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(seed_passphrase).to_str() {
+            Ok(v) => Some(SafePassword::from(v.to_owned())),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("seed_passphrase".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    };
+
     let runtime = match Runtime::new() {
         Ok(r) => r,
         Err(e) => {
@@ -5337,7 +5728,7 @@ pub unsafe extern "C" fn wallet_create(
     }
 
     let result = runtime.block_on(async {
-        let master_seed = read_or_create_master_seed(recovery_seed, &wallet_database)
+        let master_seed = read_or_create_master_seed(recovery_seed, seed_passphrase, &wallet_database)
             .map_err(|err| WalletStorageError::RecoverySeedError(err.to_string()))?;
         let comms_secret_key = derive_comms_secret_key(&master_seed)
             .map_err(|err| WalletStorageError::RecoverySeedError(err.to_string()))?;
@@ -5468,6 +5859,7 @@ pub unsafe extern "C" fn wallet_create(
                 wallet_address,
                 w.wallet_connectivity.get_connectivity_status_watch(),
                 w.contacts_service.get_contacts_liveness_event_stream(),
+                w.liveness_service.get_event_stream(),
                 callback_received_transaction,
                 callback_received_transaction_reply,
                 callback_received_finalized_transaction,
@@ -5485,6 +5877,7 @@ pub unsafe extern "C" fn wallet_create(
                 callback_saf_messages_received,
                 callback_connectivity_status,
                 callback_base_node_state,
+                callback_clock_skew,
             );
 
             runtime.spawn(callback_handler.start());
@@ -5626,6 +6019,54 @@ pub unsafe extern "C" fn wallet_get_balance(wallet: *mut TariWallet, error_out:
     }
 }
 
+/// Reconstructs the confirmed balance of a wallet at a point in the past from its output and transaction history
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `cutoff` - The block height, or the unix timestamp in seconds if `is_timestamp` is true, to reconstruct the balance
+/// at
+/// `is_timestamp` - Whether `cutoff` is a unix timestamp rather than a block height
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+/// ## Returns
+/// `c_ulonglong` - Returns the confirmed balance in micro Minotari at the cutoff, 0 if an error occurs
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_balance_at(
+    wallet: *mut TariWallet,
+    cutoff: c_ulonglong,
+    is_timestamp: bool,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    let mut output_manager_service = (*wallet).wallet.output_manager_service.clone();
+    let balance = if is_timestamp {
+        (*wallet)
+            .runtime
+            .block_on(output_manager_service.get_balance_at_timestamp(cutoff))
+    } else {
+        (*wallet)
+            .runtime
+            .block_on(output_manager_service.get_balance_at_height(cutoff))
+    };
+    match balance {
+        Ok(balance) => balance.balance.as_u64(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::BalanceError).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
 /// This function returns a list of unspent UTXO values and commitments.
 ///
 /// ## Arguments
@@ -6006,37 +6447,65 @@ pub unsafe extern "C" fn wallet_preview_coin_join(
     }
 }
 
-/// This function will tell what the outcome of a coin split would be.
+/// This function freezes unspent outputs so that they are never selected as inputs until they are unfrozen.
 ///
 /// ## Arguments
 /// * `wallet` - The TariWallet pointer
 /// * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
 ///   (see `Commitment::to_hex()`)
-/// * `number_of_splits` - The number of times to split the amount
-/// * `fee_per_gram` - The transaction fee
 /// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
 ///   Functions as an out parameter.
 ///
 /// ## Returns
-/// `*mut TariCoinPreview` - A struct with expected output values and the fee.
+/// `bool` - Returns true if the outputs were frozen, false otherwise
 ///
 /// # Safety
 /// `TariVector` must be freed after use with `destroy_tari_vector()`
 #[no_mangle]
-pub unsafe extern "C" fn wallet_preview_coin_split(
+pub unsafe extern "C" fn wallet_freeze_outputs(
     wallet: *mut TariWallet,
     commitments: *mut TariVector,
-    number_of_splits: usize,
-    fee_per_gram: u64,
     error_ptr: *mut i32,
-) -> *mut TariCoinPreview {
+) -> bool {
+    wallet_set_outputs_frozen(wallet, commitments, true, error_ptr)
+}
+
+/// This function makes previously frozen outputs available for input selection again.
+///
+/// ## Arguments
+/// * `wallet` - The TariWallet pointer
+/// * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+///   (see `Commitment::to_hex()`)
+/// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+///   Functions as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the outputs were unfrozen, false otherwise
+///
+/// # Safety
+/// `TariVector` must be freed after use with `destroy_tari_vector()`
+#[no_mangle]
+pub unsafe extern "C" fn wallet_unfreeze_outputs(
+    wallet: *mut TariWallet,
+    commitments: *mut TariVector,
+    error_ptr: *mut i32,
+) -> bool {
+    wallet_set_outputs_frozen(wallet, commitments, false, error_ptr)
+}
+
+unsafe fn wallet_set_outputs_frozen(
+    wallet: *mut TariWallet,
+    commitments: *mut TariVector,
+    frozen: bool,
+    error_ptr: *mut i32,
+) -> bool {
     if wallet.is_null() {
         error!(target: LOG_TARGET, "wallet pointer is null");
         ptr::replace(
             error_ptr,
             LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code as c_int,
         );
-        return ptr::null_mut();
+        return false;
     }
 
     let commitments = match commitments.as_ref() {
@@ -6046,24 +6515,95 @@ pub unsafe extern "C" fn wallet_preview_coin_split(
                 error_ptr,
                 LibWalletError::from(InterfaceError::NullError("commitments vector".to_string())).code as c_int,
             );
-            return ptr::null_mut();
+            return false;
         },
         Some(cs) => match cs.to_commitment_vec() {
             Ok(cs) => cs,
             Err(e) => {
                 error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
                 ptr::replace(error_ptr, LibWalletError::from(e).code as c_int);
-                return ptr::null_mut();
+                return false;
             },
         },
     };
 
-    match (*wallet)
-        .runtime
-        .block_on((*wallet).wallet.preview_coin_split_with_commitments_no_amount(
-            commitments,
-            number_of_splits,
-            MicroMinotari(fee_per_gram),
+    let mut output_manager = (*wallet).wallet.output_manager_service.clone();
+    let result = if frozen {
+        (*wallet).runtime.block_on(output_manager.freeze_outputs(commitments))
+    } else {
+        (*wallet).runtime.block_on(output_manager.unfreeze_outputs(commitments))
+    };
+    match result {
+        Ok(()) => {
+            ptr::replace(error_ptr, 0);
+            true
+        },
+        Err(e) => {
+            error!(target: LOG_TARGET, "failed to set the frozen state of outputs: {:#?}", e);
+            ptr::replace(error_ptr, LibWalletError::from(WalletError::OutputManagerError(e)).code);
+            false
+        },
+    }
+}
+
+/// This function will tell what the outcome of a coin split would be.
+///
+/// ## Arguments
+/// * `wallet` - The TariWallet pointer
+/// * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+///   (see `Commitment::to_hex()`)
+/// * `number_of_splits` - The number of times to split the amount
+/// * `fee_per_gram` - The transaction fee
+/// * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+///   Functions as an out parameter.
+///
+/// ## Returns
+/// `*mut TariCoinPreview` - A struct with expected output values and the fee.
+///
+/// # Safety
+/// `TariVector` must be freed after use with `destroy_tari_vector()`
+#[no_mangle]
+pub unsafe extern "C" fn wallet_preview_coin_split(
+    wallet: *mut TariWallet,
+    commitments: *mut TariVector,
+    number_of_splits: usize,
+    fee_per_gram: u64,
+    error_ptr: *mut i32,
+) -> *mut TariCoinPreview {
+    if wallet.is_null() {
+        error!(target: LOG_TARGET, "wallet pointer is null");
+        ptr::replace(
+            error_ptr,
+            LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code as c_int,
+        );
+        return ptr::null_mut();
+    }
+
+    let commitments = match commitments.as_ref() {
+        None => {
+            error!(target: LOG_TARGET, "failed to obtain commitments as reference");
+            ptr::replace(
+                error_ptr,
+                LibWalletError::from(InterfaceError::NullError("commitments vector".to_string())).code as c_int,
+            );
+            return ptr::null_mut();
+        },
+        Some(cs) => match cs.to_commitment_vec() {
+            Ok(cs) => cs,
+            Err(e) => {
+                error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
+                ptr::replace(error_ptr, LibWalletError::from(e).code as c_int);
+                return ptr::null_mut();
+            },
+        },
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.preview_coin_split_with_commitments_no_amount(
+            commitments,
+            number_of_splits,
+            MicroMinotari(fee_per_gram),
         )) {
         Ok((expected_outputs, fee)) => {
             ptr::replace(error_ptr, 0);
@@ -6418,6 +6958,64 @@ pub unsafe extern "C" fn wallet_remove_contact(
     }
 }
 
+/// Sets how the liveness of a TariContact is monitored by the TariWallet
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `contact` - The TariContact pointer
+/// `liveness_policy` - 0 to ping the contact every liveness round, 1 to only ping the contact when it is fetched and 2
+/// to never ping the contact
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns if successful or not
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_contact_liveness_policy(
+    wallet: *mut TariWallet,
+    contact: *mut TariContact,
+    liveness_policy: c_uchar,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    if contact.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("contact".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+    let liveness_policy = match ContactLivenessPolicy::from_byte(liveness_policy) {
+        Some(policy) => policy,
+        None => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("liveness_policy".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return false;
+        },
+    };
+
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .contacts_service
+            .set_contact_liveness_policy((*contact).address.clone(), liveness_policy),
+    ) {
+        Ok(_) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::ContactsServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
 /// Gets the available balance from a TariBalance. This is the balance the user can spend.
 ///
 /// ## Arguments
@@ -6576,90 +7174,803 @@ pub unsafe extern "C" fn wallet_send_transaction(
         ptr::swap(error_out, &mut error as *mut c_int);
         return 0;
     }
-
-    if destination.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("dest_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let selection_criteria = match commitments.as_ref() {
+        None => UtxoSelectionCriteria::default(),
+        Some(cs) => match cs.to_commitment_vec() {
+            Ok(cs) => UtxoSelectionCriteria::specific(cs),
+            Err(e) => {
+                error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
+                ptr::replace(error_out, LibWalletError::from(e).code as c_int);
+                return 0;
+            },
+        },
+    };
+
+    let message_string;
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        message_string = CString::new("")
+            .expect("Blank CString will not fail")
+            .to_str()
+            .expect("CString.to_str() will not fail")
+            .to_owned();
+    } else {
+        match CStr::from_ptr(message).to_str() {
+            Ok(v) => {
+                message_string = v.to_owned();
+            },
+            _ => {
+                error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                message_string = CString::new("")
+                    .expect("Blank CString will not fail")
+                    .to_str()
+                    .expect("CString.to_str() will not fail")
+                    .to_owned();
+            },
+        }
+    };
+
+    if one_sided {
+        match (*wallet).runtime.block_on(
+            (*wallet)
+                .wallet
+                .transaction_service
+                .send_one_sided_to_stealth_address_transaction(
+                    (*destination).clone(),
+                    MicroMinotari::from(amount),
+                    selection_criteria,
+                    OutputFeatures::default(),
+                    MicroMinotari::from(fee_per_gram),
+                    message_string,
+                ),
+        ) {
+            Ok(tx_id) => tx_id.as_u64(),
+            Err(e) => {
+                error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                0
+            },
+        }
+    } else {
+        match (*wallet)
+            .runtime
+            .block_on((*wallet).wallet.transaction_service.send_transaction(
+                (*destination).clone(),
+                MicroMinotari::from(amount),
+                selection_criteria,
+                OutputFeatures::default(),
+                MicroMinotari::from(fee_per_gram),
+                message_string,
+            )) {
+            Ok(tx_id) => tx_id.as_u64(),
+            Err(e) => {
+                error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                0
+            },
+        }
+    }
+}
+
+/// Sends a transaction straight away if the wallet is connected to a base node. Otherwise, if the wallet is configured
+/// to queue offline transactions, the transaction is queued and sent automatically once connectivity returns.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `destination` - The TariWalletAddress pointer of the peer
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `one_sided` - Send the transaction one-sided rather than interactively
+/// `queued_out` - Pointer to a bool which will be set to true if the transaction was queued rather than sent, may not
+/// be null. Functions as an out parameter.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful, the TxId of the sent transaction if it was sent or the queue id
+/// of the queued transaction if it was queued
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_or_queue_transaction(
+    wallet: *mut TariWallet,
+    destination: *mut TariWalletAddress,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    one_sided: bool,
+    queued_out: *mut bool,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("destination".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if queued_out.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("queued_out".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    *queued_out = false;
+
+    let message_string = if message.is_null() {
+        String::new()
+    } else {
+        match CStr::from_ptr(message).to_str() {
+            Ok(v) => v.to_owned(),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("message".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return 0;
+            },
+        }
+    };
+
+    let payment = ScheduledPayment {
+        destination: (*destination).clone(),
+        amount: MicroMinotari::from(amount),
+        fee_per_gram: MicroMinotari::from(fee_per_gram),
+        message: message_string,
+        one_sided,
+    };
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.send_or_queue_transaction(payment))
+    {
+        Ok(SendOrQueueResult::Sent(tx_id)) => tx_id.as_u64(),
+        Ok(SendOrQueueResult::Queued(queue_id)) => {
+            *queued_out = true;
+            queue_id
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Burns an amount so that it can be claimed on the second layer with the given claim public key. The burn proof is
+/// stored by the wallet and can be retrieved with `wallet_get_burn_proof`.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `amount` - The amount to burn
+/// `claim_public_key` - The TariPublicKey pointer of the key the burn will be claimed with
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the burn transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_burn_funds(
+    wallet: *mut TariWallet,
+    amount: c_ulonglong,
+    claim_public_key: *mut TariPublicKey,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if claim_public_key.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("claim_public_key".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let message_string = if message.is_null() {
+        String::new()
+    } else {
+        match CStr::from_ptr(message).to_str() {
+            Ok(v) => v.to_owned(),
+            _ => {
+                error = LibWalletError::from(InterfaceError::PointerError("message".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return 0;
+            },
+        }
+    };
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.burn_funds(
+            MicroMinotari::from(amount),
+            (*claim_public_key).clone(),
+            UtxoSelectionCriteria::default(),
+            MicroMinotari::from(fee_per_gram),
+            message_string,
+        )) {
+        Ok((tx_id, _)) => tx_id.as_u64(),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets the proof of a burn made by the wallet as a json string. Byte fields are hex encoded and `status` is one of
+/// "Pending", "Claimable", "Claimed" or "Cancelled".
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `tx_id` - The TxId of the burn transaction
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs or
+/// the wallet did not make a burn with the given TxId
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_burn_proof(
+    wallet: *mut TariWallet,
+    tx_id: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+
+    let record = match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.get_burn_proof(TxId::from(tx_id)))
+    {
+        Ok(Some(record)) => record,
+        Ok(None) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(
+                TransactionServiceError::BurnNotFound(TxId::from(tx_id)),
+            ))
+            .code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return CString::into_raw(result);
+        },
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return CString::into_raw(result);
+        },
+    };
+
+    let json = serde_json::json!({
+        "tx_id": record.tx_id.as_u64(),
+        "amount": record.amount.as_u64(),
+        "claim_public_key": record.claim_public_key.map(|k| k.to_hex()),
+        "reciprocal_claim_public_key": record.proof.reciprocal_claim_public_key.to_hex(),
+        "commitment": record.proof.commitment.to_hex(),
+        "ownership_proof": record.proof.ownership_proof.map(|sig| serde_json::json!({
+            "public_nonce": sig.public_nonce().to_hex(),
+            "u": sig.u().to_hex(),
+            "v": sig.v().to_hex(),
+        })),
+        "range_proof": record.proof.range_proof.to_hex(),
+        "status": record.status.to_string(),
+        "burned_at": record.burned_at.timestamp(),
+        "claimed_at": record.claimed_at.map(|t| t.timestamp()),
+    });
+    match CString::new(json.to_string()) {
+        Ok(v) => result = v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("burn proof".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    CString::into_raw(result)
+}
+
+/// Gets the transaction events journaled after the given cursor as a JSON array, oldest first. Each entry holds the
+/// `cursor` of the event, the `event` itself and the unix timestamp it was `created_at`. Pass the cursor of the last
+/// event received to the next call to catch up on events missed while the wallet was not running.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `cursor` - The cursor of the last event already processed, or 0 to get every event still in the journal
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_transaction_events_since(
+    wallet: *mut TariWallet,
+    cursor: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+
+    let events = match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.get_events_since(cursor))
+    {
+        Ok(events) => events,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return CString::into_raw(result);
+        },
+    };
+
+    let json = events
+        .into_iter()
+        .map(|e| {
+            serde_json::json!({
+                "cursor": e.cursor,
+                "event": e.event,
+                "created_at": e.created_at.timestamp(),
+            })
+        })
+        .collect::<Vec<_>>();
+    match CString::new(serde_json::Value::Array(json).to_string()) {
+        Ok(v) => result = v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("transaction events".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    CString::into_raw(result)
+}
+
+/// Saves the payment details of a repeat payee as a named send template
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `name` - The unique name of the template
+/// `destination` - The TariWalletAddress pointer of the payee
+/// `default_amount` - The amount sent when no amount is given to `wallet_send_from_template`, or 0 for none
+/// `message` - The pointer to a char array
+/// `fee_priority` - 0 for low, 1 for medium or 2 for high. The fee per gram is estimated for this priority when
+/// sending. `one_sided` - Send one-sided payments rather than interactive transactions
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns the id of the template, or 0 if unsuccessful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_send_template(
+    wallet: *mut TariWallet,
+    name: *const c_char,
+    destination: *mut TariWalletAddress,
+    default_amount: c_ulonglong,
+    message: *const c_char,
+    fee_priority: c_uint,
+    one_sided: bool,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("destination".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    let name_string = match name.as_ref().map(|n| CStr::from_ptr(n).to_str()) {
+        Some(Ok(v)) => v.to_owned(),
+        _ => {
+            error = LibWalletError::from(InterfaceError::NullError("name".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return 0;
+        },
+    };
+    let message_string = match message.as_ref().map(|m| CStr::from_ptr(m).to_str()) {
+        Some(Ok(v)) => v.to_owned(),
+        _ => {
+            error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return 0;
+        },
+    };
+    let fee_priority = match i32::try_from(fee_priority)
+        .ok()
+        .and_then(|p| FeePriority::try_from(p).ok())
+    {
+        Some(p) => p,
+        None => {
+            error = LibWalletError::from(InterfaceError::InvalidArgument("fee_priority".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return 0;
+        },
+    };
+    let default_amount = Some(default_amount).filter(|a| *a > 0).map(MicroMinotari::from);
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.create_send_template(
+            name_string,
+            (*destination).clone(),
+            default_amount,
+            message_string,
+            fee_priority,
+            one_sided,
+        )) {
+        Ok(id) => id,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Gets all send templates as a JSON array ordered by name. Each entry holds the `id`, `name`, `destination` (hex),
+/// `default_amount` (or null), `message`, `fee_priority` (0 low, 1 medium, 2 high) and `one_sided` of the template.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs
+///
+/// # Safety
+/// The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_send_templates(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut c_char {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    let mut result = CString::new("").expect("Blank CString will not fail.");
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return CString::into_raw(result);
+    }
+
+    let templates = match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.get_send_templates())
+    {
+        Ok(templates) => templates,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return CString::into_raw(result);
+        },
+    };
+
+    let json = templates
+        .into_iter()
+        .map(|t| {
+            serde_json::json!({
+                "id": t.id,
+                "name": t.name,
+                "destination": t.destination.to_hex(),
+                "default_amount": t.default_amount.map(u64::from),
+                "message": t.message,
+                "fee_priority": i32::from(t.fee_priority),
+                "one_sided": t.one_sided,
+            })
+        })
+        .collect::<Vec<_>>();
+    match CString::new(serde_json::Value::Array(json).to_string()) {
+        Ok(v) => result = v,
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("send templates".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+        },
+    }
+    CString::into_raw(result)
+}
+
+/// Deletes a send template
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `id` - The id of the template
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Returns true if the template was deleted
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_delete_send_template(
+    wallet: *mut TariWallet,
+    id: c_ulonglong,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.delete_send_template(id))
+    {
+        Ok(()) => true,
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            false
+        },
+    }
+}
+
+/// Sends a payment using the details saved in a send template
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `id` - The id of the template
+/// `amount` - The amount to send, or 0 to send the template's default amount
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_send_from_template(
+    wallet: *mut TariWallet,
+    id: c_ulonglong,
+    amount: c_ulonglong,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+
+    let amount = Some(amount).filter(|a| *a > 0).map(MicroMinotari::from);
+    match (*wallet)
+        .runtime
+        .block_on((*wallet).wallet.transaction_service.send_from_template(id, amount))
+    {
+        Ok(tx_id) => tx_id.as_u64(),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
+        },
+    }
+}
+
+/// Selects and encumbers the inputs of a one-sided payment to be built and signed by an offline wallet. This can be
+/// called on a watch-only wallet. The returned bytes are passed to `wallet_sign_unsigned_transaction` on the offline
+/// wallet.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `destination` - The TariWalletAddress pointer of the payee
+/// `amount` - The amount
+/// `fee_per_gram` - The transaction fee
+/// `message` - The pointer to a char array
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns a pointer to the serialized unsigned transaction. Note that it will be ptr::null_mut()
+/// if an error occurs
+///
+/// # Safety
+/// The ```byte_vector_destroy``` function must be called when finished with a ByteVector to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_create_unsigned_transaction(
+    wallet: *mut TariWallet,
+    destination: *mut TariWalletAddress,
+    amount: c_ulonglong,
+    fee_per_gram: c_ulonglong,
+    message: *const c_char,
+    error_out: *mut c_int,
+) -> *mut ByteVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if destination.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("destination".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if message.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    let message = match CStr::from_ptr(message).to_str() {
+        Ok(v) => v.to_owned(),
+        Err(_) => {
+            error = LibWalletError::from(InterfaceError::PointerError("message".to_string())).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            return ptr::null_mut();
+        },
+    };
+
+    let destination = (*destination).clone();
+    let mut transaction_service = (*wallet).wallet.transaction_service.clone();
+    let result = (*wallet).runtime.block_on(async {
+        let unsigned = transaction_service
+            .create_unsigned_transaction(
+                destination,
+                MicroMinotari::from(amount),
+                UtxoSelectionCriteria::default(),
+                MicroMinotari::from(fee_per_gram),
+                message,
+            )
+            .await?;
+        match unsigned.to_bytes() {
+            Ok(bytes) => Ok::<_, TransactionServiceError>(bytes),
+            Err(e) => {
+                transaction_service.cancel_offline_transaction(unsigned.tx_id).await?;
+                Err(TransactionServiceError::from(e))
+            },
+        }
+    });
+    match result {
+        Ok(bytes) => Box::into_raw(Box::new(ByteVector(bytes))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Builds and signs a transaction created by `wallet_create_unsigned_transaction`. This is called on the offline
+/// wallet that holds the spending keys and does not need a network connection. The returned bytes are passed to
+/// `wallet_import_signed_transaction` on the online wallet.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `unsigned_transaction` - The ByteVector pointer of the serialized unsigned transaction
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut ByteVector` - Returns a pointer to the serialized signed transaction. Note that it will be ptr::null_mut()
+/// if an error occurs
+///
+/// # Safety
+/// The ```byte_vector_destroy``` function must be called when finished with a ByteVector to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_sign_unsigned_transaction(
+    wallet: *mut TariWallet,
+    unsigned_transaction: *mut ByteVector,
+    error_out: *mut c_int,
+) -> *mut ByteVector {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+    if unsigned_transaction.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("unsigned_transaction".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let bytes = &(*unsigned_transaction).0;
+    let mut transaction_service = (*wallet).wallet.transaction_service.clone();
+    let result = (*wallet).runtime.block_on(async {
+        let unsigned = UnsignedTransaction::from_bytes(bytes)?;
+        let signed = transaction_service.sign_unsigned_transaction(unsigned).await?;
+        Ok::<_, TransactionServiceError>(signed.to_bytes()?)
+    });
+    match result {
+        Ok(bytes) => Box::into_raw(Box::new(ByteVector(bytes))),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
+/// Checks a transaction signed by `wallet_sign_unsigned_transaction` against the inputs encumbered by
+/// `wallet_create_unsigned_transaction` and broadcasts it
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `signed_transaction` - The ByteVector pointer of the serialized signed transaction
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `unsigned long long` - Returns 0 if unsuccessful or the TxId of the transaction if successful
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_import_signed_transaction(
+    wallet: *mut TariWallet,
+    signed_transaction: *mut ByteVector,
+    error_out: *mut c_int,
+) -> c_ulonglong {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return 0;
+    }
+    if signed_transaction.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("signed_transaction".to_string())).code;
         ptr::swap(error_out, &mut error as *mut c_int);
         return 0;
     }
 
-    let selection_criteria = match commitments.as_ref() {
-        None => UtxoSelectionCriteria::default(),
-        Some(cs) => match cs.to_commitment_vec() {
-            Ok(cs) => UtxoSelectionCriteria::specific(cs),
-            Err(e) => {
-                error!(target: LOG_TARGET, "failed to convert from tari vector: {:?}", e);
-                ptr::replace(error_out, LibWalletError::from(e).code as c_int);
-                return 0;
-            },
+    let bytes = &(*signed_transaction).0;
+    let mut transaction_service = (*wallet).wallet.transaction_service.clone();
+    let result = (*wallet).runtime.block_on(async {
+        let signed = SignedTransaction::from_bytes(bytes)?;
+        transaction_service.import_signed_transaction(signed).await
+    });
+    match result {
+        Ok(tx_id) => tx_id.as_u64(),
+        Err(e) => {
+            error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            0
         },
-    };
-
-    let message_string;
-    if message.is_null() {
-        error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
-        ptr::swap(error_out, &mut error as *mut c_int);
-        message_string = CString::new("")
-            .expect("Blank CString will not fail")
-            .to_str()
-            .expect("CString.to_str() will not fail")
-            .to_owned();
-    } else {
-        match CStr::from_ptr(message).to_str() {
-            Ok(v) => {
-                message_string = v.to_owned();
-            },
-            _ => {
-                error = LibWalletError::from(InterfaceError::NullError("message".to_string())).code;
-                ptr::swap(error_out, &mut error as *mut c_int);
-                message_string = CString::new("")
-                    .expect("Blank CString will not fail")
-                    .to_str()
-                    .expect("CString.to_str() will not fail")
-                    .to_owned();
-            },
-        }
-    };
-
-    if one_sided {
-        match (*wallet).runtime.block_on(
-            (*wallet)
-                .wallet
-                .transaction_service
-                .send_one_sided_to_stealth_address_transaction(
-                    (*destination).clone(),
-                    MicroMinotari::from(amount),
-                    selection_criteria,
-                    OutputFeatures::default(),
-                    MicroMinotari::from(fee_per_gram),
-                    message_string,
-                ),
-        ) {
-            Ok(tx_id) => tx_id.as_u64(),
-            Err(e) => {
-                error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
-                ptr::swap(error_out, &mut error as *mut c_int);
-                0
-            },
-        }
-    } else {
-        match (*wallet)
-            .runtime
-            .block_on((*wallet).wallet.transaction_service.send_transaction(
-                (*destination).clone(),
-                MicroMinotari::from(amount),
-                selection_criteria,
-                OutputFeatures::default(),
-                MicroMinotari::from(fee_per_gram),
-                message_string,
-            )) {
-            Ok(tx_id) => tx_id.as_u64(),
-            Err(e) => {
-                error = LibWalletError::from(WalletError::TransactionServiceError(e)).code;
-                ptr::swap(error_out, &mut error as *mut c_int);
-                0
-            },
-        }
     }
 }
 
@@ -7652,10 +8963,70 @@ pub unsafe extern "C" fn wallet_restart_transaction_broadcast(wallet: *mut TariW
     }
 }
 
+/// Does a bounded amount of wallet sync work, intended to be called from the short background task windows that
+/// mobile operating systems give to apps. Queued outbound messages are flushed, a batch of unconfirmed transactions
+/// is validated and a limited number of blocks is scanned. Any work that does not fit in the given limits is left for
+/// the next call and reported as remaining.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `max_outbound_messages` - The maximum number of queued outbound transaction messages to resend
+/// `max_transactions` - The maximum number of unconfirmed transactions to validate
+/// `max_blocks` - The maximum number of blocks to scan for outputs
+/// `time_budget_ms` - The time in milliseconds the sync may take, after which the remaining steps are skipped
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariBackgroundSyncReport` - Returns a pointer to a report of the work done, note that it returns
+/// ptr::null_mut() if an error occurred.
+///
+/// # Safety
+/// The ```destroy_tari_background_sync_report``` method must be called when finished with a TariBackgroundSyncReport
+/// to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_background_sync(
+    wallet: *mut TariWallet,
+    max_outbound_messages: c_uint,
+    max_transactions: c_uint,
+    max_blocks: c_ulonglong,
+    time_budget_ms: c_ulonglong,
+    error_out: *mut c_int,
+) -> *mut TariBackgroundSyncReport {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let limits = background_sync::BackgroundSyncLimits {
+        max_outbound_messages: max_outbound_messages as usize,
+        max_transactions: max_transactions as usize,
+        max_blocks,
+        time_budget: Duration::from_millis(time_budget_ms),
+    };
+    match (*wallet).runtime.block_on(background_sync::run_background_sync(
+        &(*wallet).wallet,
+        limits,
+        (*wallet).shutdown.to_signal(),
+    )) {
+        Ok(report) => Box::into_raw(Box::new(report)),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Gets the seed words representing the seed private key of the provided `TariWallet`.
 ///
 /// ## Arguments
 /// `wallet` - The TariWallet pointer
+/// `passphrase` - The wallet passphrase, required when the wallet's re-authentication policy covers seed export. May be
+/// null otherwise.
 /// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
 /// as an out parameter.
 ///
@@ -7666,7 +9037,11 @@ pub unsafe extern "C" fn wallet_restart_transaction_broadcast(wallet: *mut TariW
 /// The ```tari_seed_words_destroy``` method must be called when finished with a
 /// TariSeedWords to prevent a memory leak
 #[no_mangle]
-pub unsafe extern "C" fn wallet_get_seed_words(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut TariSeedWords {
+pub unsafe extern "C" fn wallet_get_seed_words(
+    wallet: *mut TariWallet,
+    passphrase: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariSeedWords {
     let mut error = 0;
     ptr::swap(error_out, &mut error as *mut c_int);
 
@@ -7676,7 +9051,24 @@ pub unsafe extern "C" fn wallet_get_seed_words(wallet: *mut TariWallet, error_ou
         return ptr::null_mut();
     }
 
-    match (*wallet).wallet.get_seed_words(&MnemonicLanguage::English) {
+    let passphrase = if passphrase.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(passphrase).to_str() {
+            Ok(v) => Some(SafePassword::from(v.to_owned())),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("passphrase".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    };
+
+    match (*wallet).runtime.block_on(
+        (*wallet)
+            .wallet
+            .export_seed_words(&MnemonicLanguage::English, passphrase),
+    ) {
         Ok(seed_words) => Box::into_raw(Box::new(TariSeedWords(seed_words))),
         Err(e) => {
             error = LibWalletError::from(e).code;
@@ -7686,6 +9078,67 @@ pub unsafe extern "C" fn wallet_get_seed_words(wallet: *mut TariWallet, error_ou
     }
 }
 
+/// Splits the seed of the provided `TariWallet` into `share_count` Shamir shares of which any `threshold` recover it
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer
+/// `threshold` - The number of shares needed to recover the seed
+/// `share_count` - The number of shares to create
+/// `passphrase` - The wallet passphrase, required when the wallet's re-authentication policy covers seed export. May be
+/// null otherwise.
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `*mut TariSeedShares` - The shares, each as seed words
+///
+/// # Safety
+/// The ```seed_shares_destroy``` method must be called when finished with a TariSeedShares to prevent a memory leak
+#[no_mangle]
+pub unsafe extern "C" fn wallet_get_seed_shares(
+    wallet: *mut TariWallet,
+    threshold: c_uchar,
+    share_count: c_uchar,
+    passphrase: *const c_char,
+    error_out: *mut c_int,
+) -> *mut TariSeedShares {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return ptr::null_mut();
+    }
+
+    let passphrase = if passphrase.is_null() {
+        None
+    } else {
+        match CStr::from_ptr(passphrase).to_str() {
+            Ok(v) => Some(SafePassword::from(v.to_owned())),
+            Err(_) => {
+                error = LibWalletError::from(InterfaceError::PointerError("passphrase".to_string())).code;
+                ptr::swap(error_out, &mut error as *mut c_int);
+                return ptr::null_mut();
+            },
+        }
+    };
+
+    match (*wallet).runtime.block_on((*wallet).wallet.export_seed_shares(
+        threshold,
+        share_count,
+        &MnemonicLanguage::English,
+        passphrase,
+    )) {
+        Ok(shares) => Box::into_raw(Box::new(TariSeedShares(shares))),
+        Err(e) => {
+            error = LibWalletError::from(e).code;
+            ptr::swap(error_out, &mut error as *mut c_int);
+            ptr::null_mut()
+        },
+    }
+}
+
 /// Set the power mode of the wallet to Low Power mode which will reduce the amount of network operations the wallet
 /// performs to conserve power
 ///
@@ -8138,6 +9591,48 @@ pub unsafe extern "C" fn wallet_set_one_sided_payment_message(
     true
 }
 
+/// Limit how hard UTXO scanning may use the connection to the base node, e.g. while on a metered connection. The
+/// limits apply to all of the wallet's scanning, including background syncs and recovery, and take effect while a scan
+/// is running.
+///
+/// ## Arguments
+/// `wallet` - The TariWallet pointer.
+/// `max_blocks_per_second` - The maximum number of blocks to scan per second, 0 for no limit
+/// `max_concurrent_requests` - The maximum number of requests to the base node in flight at the same time, 0 for no
+/// limit
+/// `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+/// as an out parameter.
+///
+/// ## Returns
+/// `bool` - Return a boolean value indicating the operation's success or failure. The error_ptr will hold the error
+/// code if there was a failure
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_set_scan_throttle(
+    wallet: *mut TariWallet,
+    max_blocks_per_second: c_uint,
+    max_concurrent_requests: c_uint,
+    error_out: *mut c_int,
+) -> bool {
+    let mut error = 0;
+    ptr::swap(error_out, &mut error as *mut c_int);
+
+    if wallet.is_null() {
+        error = LibWalletError::from(InterfaceError::NullError("wallet".to_string())).code;
+        ptr::swap(error_out, &mut error as *mut c_int);
+        return false;
+    }
+
+    (*wallet).wallet.utxo_scanner_service.set_scan_throttle(ScanThrottle {
+        max_blocks_per_second: Some(max_blocks_per_second),
+        max_concurrent_requests: Some(max_concurrent_requests as usize),
+    });
+
+    true
+}
+
 /// Gets the current emoji set
 ///
 /// ## Arguments
@@ -8288,6 +9783,22 @@ pub unsafe extern "C" fn log_debug_message(msg: *const c_char, error_out: *mut c
     }
 }
 
+/// Indicates whether a function that failed with the provided error code may succeed if it is called again later with
+/// the same arguments, e.g. once pending funds are confirmed or the base node can be reached again.
+///
+/// ## Arguments
+/// `error_code` - An error code returned through the `error_out` parameter of another function
+///
+/// ## Returns
+/// `bool` - Returns true if the failed call is worth retrying, false otherwise
+///
+/// # Safety
+/// None
+#[no_mangle]
+pub unsafe extern "C" fn wallet_error_is_retryable(error_code: c_int) -> bool {
+    LibWalletError::is_retryable_code(error_code)
+}
+
 /// ------------------------------------- FeePerGramStats ------------------------------------ ///
 
 /// Get the TariFeePerGramStats from a TariWallet.
@@ -8830,6 +10341,10 @@ mod test {
         // assert!(true); //optimized out by compiler
     }
 
+    unsafe extern "C" fn clock_skew_callback(_is_skewed: bool, _offset_ms: i64) {
+        // assert!(true); //optimized out by compiler
+    }
+
     const NETWORK_STRING: &str = "localnet";
 
     #[test]
@@ -8871,6 +10386,26 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_wallet_error_is_retryable() {
+        unsafe {
+            let pending = LibWalletError::from(WalletError::OutputManagerError(OutputManagerError::FundsPending));
+            assert!(wallet_error_is_retryable(pending.code));
+            let no_funds = LibWalletError::from(WalletError::OutputManagerError(OutputManagerError::NotEnoughFunds));
+            assert!(!wallet_error_is_retryable(no_funds.code));
+            let not_synced = LibWalletError::from(WalletError::TransactionServiceError(
+                TransactionServiceError::BaseNodeNotSynced,
+            ));
+            assert_eq!(not_synced.code, 213);
+            assert!(wallet_error_is_retryable(not_synced.code));
+            let invalid = LibWalletError::from(WalletError::TransactionServiceError(
+                TransactionServiceError::InvalidTransaction,
+            ));
+            assert_eq!(invalid.code, 211);
+            assert!(!wallet_error_is_retryable(invalid.code));
+        }
+    }
+
     #[test]
     fn test_emoji_set() {
         unsafe {
@@ -9089,6 +10624,59 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_address_validation() {
+        unsafe {
+            let mut error = 0;
+            let error_ptr = &mut error as *mut c_int;
+            let private_key = private_key_generate();
+            let address = tari_address_from_private_key(private_key, 0x26, error_ptr);
+            assert_eq!(error, 0);
+            assert_eq!(tari_address_get_network(address, error_ptr), 0x26);
+            assert_eq!(error, 0);
+
+            let hex = tari_address_to_hex(address, error_ptr);
+            assert_eq!(error, 0);
+            let emoji = tari_address_to_emoji_id(address, error_ptr);
+            assert_eq!(error, 0);
+            assert!(tari_address_validate(hex, 0x26, error_ptr));
+            assert_eq!(error, 0);
+            assert!(tari_address_validate(emoji, 0x26, error_ptr));
+            assert_eq!(error, 0);
+
+            // Converting between the emoji and hex representations round trips
+            let address_from_emoji = tari_address_from_string(emoji, error_ptr);
+            assert_eq!(error, 0);
+            let hex_from_emoji = tari_address_to_hex(address_from_emoji, error_ptr);
+            assert_eq!(CStr::from_ptr(hex_from_emoji), CStr::from_ptr(hex));
+
+            // The error code describes why validation failed
+            assert!(!tari_address_validate(
+                hex,
+                Network::MainNet.as_byte() as c_uint,
+                error_ptr
+            ));
+            assert_eq!(error, 706);
+            let bad_hex = CString::into_raw(CString::new("abc").unwrap());
+            assert!(!tari_address_validate(bad_hex, 0x26, error_ptr));
+            assert_eq!(error, 705);
+            let short_hex = CString::into_raw(CString::new("abcd").unwrap());
+            assert!(!tari_address_validate(short_hex, 0x26, error_ptr));
+            assert_eq!(error, 703);
+            assert!(tari_address_from_string(short_hex, error_ptr).is_null());
+            assert_eq!(error, 703);
+
+            string_destroy(bad_hex);
+            string_destroy(short_hex);
+            string_destroy(hex_from_emoji);
+            string_destroy(hex);
+            string_destroy(emoji);
+            tari_address_destroy(address_from_emoji);
+            tari_address_destroy(address);
+            private_key_destroy(private_key);
+        }
+    }
+
     #[test]
     fn test_covenant_create_empty() {
         unsafe {
@@ -9422,6 +11010,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 alice_network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9440,6 +11029,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -9465,6 +11055,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 alice_network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9483,6 +11074,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -9577,6 +11169,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9595,6 +11188,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -9800,6 +11394,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9818,12 +11413,13 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
 
             assert_eq!(error, 0);
-            let seed_words = wallet_get_seed_words(wallet, error_ptr);
+            let seed_words = wallet_get_seed_words(wallet, ptr::null(), error_ptr);
             assert_eq!(error, 0);
             let public_address = wallet_get_tari_address(wallet, error_ptr);
             assert_eq!(error, 0);
@@ -9862,6 +11458,7 @@ mod test {
                 0,
                 passphrase,
                 seed_words,
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9880,12 +11477,13 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
             assert_eq!(error, 0);
 
-            let recovered_seed_words = wallet_get_seed_words(recovered_wallet, error_ptr);
+            let recovered_seed_words = wallet_get_seed_words(recovered_wallet, ptr::null(), error_ptr);
             assert_eq!(error, 0);
             let recovered_address = wallet_get_tari_address(recovered_wallet, error_ptr);
             assert_eq!(error, 0);
@@ -9938,6 +11536,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -9956,6 +11555,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -10086,6 +11686,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -10104,6 +11705,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -10203,6 +11805,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -10221,6 +11824,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -10403,6 +12007,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -10421,6 +12026,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -10610,6 +12216,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -10628,6 +12235,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
@@ -10860,6 +12468,7 @@ mod test {
                 0,
                 passphrase,
                 ptr::null(),
+                ptr::null(),
                 network_str,
                 received_tx_callback,
                 received_tx_reply_callback,
@@ -10878,6 +12487,7 @@ mod test {
                 saf_messages_received_callback,
                 connectivity_status_callback,
                 base_node_state_callback,
+                clock_skew_callback,
                 recovery_in_progress_ptr,
                 error_ptr,
             );
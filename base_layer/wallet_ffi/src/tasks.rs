@@ -90,12 +90,36 @@ pub async fn recovery_event_monitoring(
             Ok(UtxoScannerEvent::Progress {
                 current_height: current,
                 tip_height: total,
+                ..
             }) => {
                 unsafe {
                     (recovery_progress_callback)(RecoveryEvent::Progress as u8, current, total);
                 }
                 info!(target: LOG_TARGET, "Recovery progress: {}/{}", current, total);
             },
+            Ok(UtxoScannerEvent::BatchScanned { batch, totals }) => {
+                debug!(
+                    target: LOG_TARGET,
+                    "Scanned {} outputs of block {}/{} in {:.2?}, found {} worth {} ({} blocks scanned so far)",
+                    batch.num_outputs,
+                    batch.current_height,
+                    batch.tip_height,
+                    batch.rpc_latency,
+                    batch.num_found,
+                    batch.value_found,
+                    totals.num_blocks
+                );
+            },
+            Ok(UtxoScannerEvent::Summary(stats)) => {
+                info!(
+                    target: LOG_TARGET,
+                    "Scanned {} outputs in {} blocks at {:.2} blocks/s, average base node latency {:.2?}",
+                    stats.num_outputs,
+                    stats.num_blocks,
+                    stats.blocks_per_second(),
+                    stats.average_rpc_latency()
+                );
+            },
             Ok(UtxoScannerEvent::Completed {
                 final_height,
                 num_recovered,
@@ -168,6 +168,8 @@ struct TariPendingOutboundTransactions;
 
 struct TariPublicKeys;
 
+struct TariSeedShares;
+
 struct TariSeedWords;
 
 struct TariUnblindedOutputs;
@@ -209,6 +211,19 @@ struct TariCoinPreview {
   uint64_t fee;
 };
 
+/**
+ * Summary of the work done by `wallet_background_sync` and what was left for the next run
+ */
+struct TariBackgroundSyncReport {
+  uint64_t outbound_messages_sent;
+  uint64_t outbound_messages_remaining;
+  uint64_t transactions_validated;
+  uint64_t transactions_remaining;
+  uint64_t scanned_height;
+  uint64_t blocks_remaining;
+  bool timed_out;
+};
+
 typedef struct TransactionKernel TariTransactionKernel;
 
 /**
@@ -406,6 +421,20 @@ void destroy_tari_vector(struct TariVector *v);
  */
 void destroy_tari_coin_preview(struct TariCoinPreview *p);
 
+/**
+ * Frees memory allocated for `TariBackgroundSyncReport`.
+ *
+ * ## Arguments
+ * `report` - The pointer to `TariBackgroundSyncReport`
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void destroy_tari_background_sync_report(struct TariBackgroundSyncReport *report);
+
 /**
  * -------------------------------- Strings ------------------------------------------------ ///
  * Frees memory for a char array
@@ -783,6 +812,80 @@ char *tari_address_to_emoji_id(TariWalletAddress *address,
 TariWalletAddress *emoji_id_to_tari_address(const char *emoji,
                                             int *error_out);
 
+/**
+ * Creates a TariWalletAddress from a char array in either emoji or hex format
+ *
+ * ## Arguments
+ * `address` - The pointer to a char array containing an emoji ID or a hex encoded address
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter. The error code identifies why the address is invalid, i.e. invalid hex (705), invalid emoji
+ * (704), invalid size (703), invalid network or checksum (701) or an invalid public key (702).
+ *
+ * ## Returns
+ * `*mut TariWalletAddress` - Returns a pointer to a TariWalletAddress. Note that it returns null on error.
+ *
+ * # Safety
+ * The ```tari_address_destroy``` method must be called when finished with a TariWalletAddress to prevent a memory
+ * leak
+ */
+TariWalletAddress *tari_address_from_string(const char *address,
+                                            int *error_out);
+
+/**
+ * Validates a char array containing an emoji ID or a hex encoded address for the given network
+ *
+ * ## Arguments
+ * `address` - The pointer to a char array containing an emoji ID or a hex encoded address
+ * `network` - The network the address is expected to belong to
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter. When the address is invalid the error code gives the reason, as for
+ * `tari_address_from_string`, or 706 if the address is valid but belongs to a different network.
+ *
+ * ## Returns
+ * `bool` - Returns true if the address is valid for the network, otherwise false
+ *
+ * # Safety
+ * None
+ */
+bool tari_address_validate(const char *address,
+                           unsigned int network,
+                           int *error_out);
+
+/**
+ * Creates a char array from a TariWalletAddress in hex format
+ *
+ * ## Arguments
+ * `address` - The pointer to a TariWalletAddress
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns empty if address is null
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *tari_address_to_hex(TariWalletAddress *address,
+                          int *error_out);
+
+/**
+ * Gets the network a TariWalletAddress belongs to
+ *
+ * ## Arguments
+ * `address` - The pointer to a TariWalletAddress
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_uint` - Returns the network byte, as accepted by `tari_address_from_private_key`. Note that it returns 0 if the
+ * address is null
+ *
+ * # Safety
+ * None
+ */
+unsigned int tari_address_get_network(TariWalletAddress *address,
+                                      int *error_out);
+
 /**
  * -------------------------------------------------------------------------------------------- ///
  *
@@ -1344,6 +1447,109 @@ unsigned char seed_words_push_word(struct TariSeedWords *seed_words,
  */
 void seed_words_destroy(struct TariSeedWords *seed_words);
 
+/**
+ * Create an empty instance of TariSeedShares, to collect the shares a seed is recovered from
+ *
+ * ## Arguments
+ * None
+ *
+ * ## Returns
+ * `TariSeedShares` - Returns an empty TariSeedShares instance
+ *
+ * # Safety
+ * The `seed_shares_destroy` method must be called when finished with a TariSeedShares to prevent a memory leak
+ */
+struct TariSeedShares *seed_shares_create(void);
+
+/**
+ * Gets the number of shares in TariSeedShares
+ *
+ * ## Arguments
+ * `seed_shares` - The pointer to a TariSeedShares
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `c_uint` - Returns number of shares in seed_shares, zero if seed_shares is null
+ *
+ * # Safety
+ * None
+ */
+unsigned int seed_shares_get_length(const struct TariSeedShares *seed_shares,
+                                    int *error_out);
+
+/**
+ * Gets the seed words of the share in TariSeedShares at position
+ *
+ * ## Arguments
+ * `seed_shares` - The pointer to a TariSeedShares
+ * `position` - The integer position
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariSeedWords` - Returns the seed words of the share, note that it returns ptr::null_mut() if seed_shares is
+ * null or position is invalid
+ *
+ * # Safety
+ * The `seed_words_destroy` method must be called when finished with a TariSeedWords to prevent a memory leak
+ */
+struct TariSeedWords *seed_shares_get_at(struct TariSeedShares *seed_shares,
+                                         unsigned int position,
+                                         int *error_out);
+
+/**
+ * Adds the seed words of a share to the provided TariSeedShares instance
+ *
+ * ## Arguments
+ * `seed_shares` - The pointer to a TariSeedShares
+ * `share` - The pointer to the TariSeedWords of one share
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the words are a valid share and were added, false otherwise
+ *
+ * # Safety
+ * None
+ */
+bool seed_shares_push(struct TariSeedShares *seed_shares,
+                      const struct TariSeedWords *share,
+                      int *error_out);
+
+/**
+ * Recovers the seed words of the seed the shares were split from. The returned seed words can be passed to
+ * `wallet_create` to recover the wallet.
+ *
+ * ## Arguments
+ * `seed_shares` - The pointer to a TariSeedShares holding at least as many shares as are needed to recover the seed
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariSeedWords` - The seed words of the seed, note that it returns ptr::null_mut() if there are not enough
+ * shares or they are not from the same split
+ *
+ * # Safety
+ * The `seed_words_destroy` method must be called when finished with a TariSeedWords to prevent a memory leak
+ */
+struct TariSeedWords *seed_shares_combine(const struct TariSeedShares *seed_shares,
+                                          int *error_out);
+
+/**
+ * Frees memory for a TariSeedShares
+ *
+ * ## Arguments
+ * `seed_shares` - The pointer to a TariSeedShares
+ *
+ * ## Returns
+ * `()` - Does not return a value, equivalent to void in C
+ *
+ * # Safety
+ * None
+ */
+void seed_shares_destroy(struct TariSeedShares *seed_shares);
+
 /**
  * -------------------------------------------------------------------------------------------- ///
  * ----------------------------------- Contact -------------------------------------------------///
@@ -2181,6 +2387,7 @@ const char *pending_outbound_transaction_get_message(TariPendingOutboundTransact
  * |   2 | Mined       |
  * |   3 | Imported    |
  * |   4 | Pending     |
+ * |  12 | AwaitingCosigners |
  *
  * # Safety
  * None
@@ -2593,6 +2800,9 @@ TariPublicKey *public_keys_get_at(const struct TariPublicKeys *public_keys,
  * encrypted then the correct passphrase is required or this function will fail.
  * `seed_words` - An optional instance of TariSeedWords, used to create a wallet for recovery purposes.
  * If this is null, then a new master key is created for the wallet.
+ * `seed_passphrase` - An optional seed passphrase, the "25th word", combined with the master key of a new or recovered
+ * wallet. Each seed passphrase yields a different wallet from the same seed words. It is not needed when opening an
+ * existing wallet, if given it must match the one the wallet was created with.
  * `callback_received_transaction` - The callback function pointer matching the function signature. This will be
  * called when an inbound transaction is received.
  * `callback_received_transaction_reply` - The callback function
@@ -2642,7 +2852,8 @@ TariPublicKey *public_keys_get_at(const struct TariPublicKeys *public_keys,
  * `callback_contacts_liveness_data_updated` - The callback function pointer matching the function signature. This is
  * called when a contact's liveness status changed. The data represents the contact's updated status information.
  * `callback_balance_updated` - The callback function pointer matching the function signature. This is called whenever
- * the balance changes.
+ * the balance changes, including when outputs are encumbered, released, imported or invalidated, which have no
+ * callbacks of their own.
  * `callback_transaction_validation_complete` - The callback function pointer matching the function signature. This is
  * called when a Transaction validation process is completed. The request_key is used to identify which request this
  * callback references and the second parameter is a u64 that returns if the validation was successful or not.
@@ -2660,6 +2871,12 @@ TariPublicKey *public_keys_get_at(const struct TariPublicKeys *public_keys,
  *     Online,         // 1
  *     Offline,        // 2
  * }
+ * `callback_base_node_state` - The callback function pointer matching the function signature. This is called when the
+ * state of the base node the wallet is connected to changes.
+ * `callback_clock_skew` - The callback function pointer matching the function signature. This is called when the
+ * local clock drifts beyond, or back within, the tolerated offset from the network. The first parameter is true while
+ * the clock is skewed and the second is the estimated offset in milliseconds, positive if the local clock is behind.
+ * Transactions with time locks may be rejected while the clock is skewed.
  * `recovery_in_progress` - Pointer to an bool which will be modified to indicate if there is an outstanding recovery
  * that should be completed or not to an error code should one occur, may not be null. Functions as an out parameter.
  * `error_out` - Pointer to an int which will be modified
@@ -2677,6 +2894,7 @@ struct TariWallet *wallet_create(TariCommsConfig *config,
                                  unsigned int size_per_log_file_bytes,
                                  const char *passphrase,
                                  const struct TariSeedWords *seed_words,
+                                 const char *seed_passphrase,
                                  const char *network_str,
                                  void (*callback_received_transaction)(TariPendingInboundTransaction*),
                                  void (*callback_received_transaction_reply)(TariCompletedTransaction*),
@@ -2699,6 +2917,7 @@ struct TariWallet *wallet_create(TariCommsConfig *config,
                                  void (*callback_saf_messages_received)(void),
                                  void (*callback_connectivity_status)(uint64_t),
                                  void (*callback_base_node_state)(struct TariBaseNodeState*),
+                                 void (*callback_clock_skew)(bool, int64_t),
                                  bool *recovery_in_progress,
                                  int *error_out);
 
@@ -2750,6 +2969,27 @@ char *wallet_get_last_network(TariCommsConfig *config,
 TariBalance *wallet_get_balance(struct TariWallet *wallet,
                                 int *error_out);
 
+/**
+ * Reconstructs the confirmed balance of a wallet at a point in the past from its output and transaction history
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `cutoff` - The block height, or the unix timestamp in seconds if `is_timestamp` is true, to reconstruct the balance
+ * at
+ * `is_timestamp` - Whether `cutoff` is a unix timestamp rather than a block height
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ * ## Returns
+ * `c_ulonglong` - Returns the confirmed balance in micro Minotari at the cutoff, 0 if an error occurs
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_get_balance_at(struct TariWallet *wallet,
+                                         unsigned long long cutoff,
+                                         bool is_timestamp,
+                                         int *error_out);
+
 /**
  * This function returns a list of unspent UTXO values and commitments.
  *
@@ -2883,6 +3123,46 @@ struct TariCoinPreview *wallet_preview_coin_join(struct TariWallet *wallet,
                                                  uint64_t fee_per_gram,
                                                  int32_t *error_ptr);
 
+/**
+ * This function freezes unspent outputs so that they are never selected as inputs until they are unfrozen.
+ *
+ * ## Arguments
+ * * `wallet` - The TariWallet pointer
+ * * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+ *   (see `Commitment::to_hex()`)
+ * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+ *   Functions as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the outputs were frozen, false otherwise
+ *
+ * # Safety
+ * `TariVector` must be freed after use with `destroy_tari_vector()`
+ */
+bool wallet_freeze_outputs(struct TariWallet *wallet,
+                           struct TariVector *commitments,
+                           int32_t *error_ptr);
+
+/**
+ * This function makes previously frozen outputs available for input selection again.
+ *
+ * ## Arguments
+ * * `wallet` - The TariWallet pointer
+ * * `commitments` - A `TariVector` of "strings", tagged as `TariTypeTag::String`, containing commitment's hex values
+ *   (see `Commitment::to_hex()`)
+ * * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null.
+ *   Functions as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the outputs were unfrozen, false otherwise
+ *
+ * # Safety
+ * `TariVector` must be freed after use with `destroy_tari_vector()`
+ */
+bool wallet_unfreeze_outputs(struct TariWallet *wallet,
+                             struct TariVector *commitments,
+                             int32_t *error_ptr);
+
 /**
  * This function will tell what the outcome of a coin split would be.
  *
@@ -3009,6 +3289,28 @@ bool wallet_remove_contact(struct TariWallet *wallet,
                            TariContact *contact,
                            int *error_out);
 
+/**
+ * Sets how the liveness of a TariContact is monitored by the TariWallet
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `contact` - The TariContact pointer
+ * `liveness_policy` - 0 to ping the contact every liveness round, 1 to only ping the contact when it is fetched and 2
+ * to never ping the contact
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns if successful or not
+ *
+ * # Safety
+ * None
+ */
+bool wallet_set_contact_liveness_policy(struct TariWallet *wallet,
+                                        TariContact *contact,
+                                        unsigned char liveness_policy,
+                                        int *error_out);
+
 /**
  * Gets the available balance from a TariBalance. This is the balance the user can spend.
  *
@@ -3120,6 +3422,263 @@ unsigned long long wallet_send_transaction(struct TariWallet *wallet,
                                            bool one_sided,
                                            int *error_out);
 
+/**
+ * Sends a transaction straight away if the wallet is connected to a base node. Otherwise, if the wallet is configured
+ * to queue offline transactions, the transaction is queued and sent automatically once connectivity returns.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `destination` - The TariWalletAddress pointer of the peer
+ * `amount` - The amount
+ * `fee_per_gram` - The transaction fee
+ * `message` - The pointer to a char array
+ * `one_sided` - Send the transaction one-sided rather than interactively
+ * `queued_out` - Pointer to a bool which will be set to true if the transaction was queued rather than sent, may not
+ * be null. Functions as an out parameter.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns 0 if unsuccessful, the TxId of the sent transaction if it was sent or the queue id
+ * of the queued transaction if it was queued
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_send_or_queue_transaction(struct TariWallet *wallet,
+                                                    TariWalletAddress *destination,
+                                                    unsigned long long amount,
+                                                    unsigned long long fee_per_gram,
+                                                    const char *message,
+                                                    bool one_sided,
+                                                    bool *queued_out,
+                                                    int *error_out);
+
+/**
+ * Burns an amount so that it can be claimed on the second layer with the given claim public key. The burn proof is
+ * stored by the wallet and can be retrieved with `wallet_get_burn_proof`.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `amount` - The amount to burn
+ * `claim_public_key` - The TariPublicKey pointer of the key the burn will be claimed with
+ * `fee_per_gram` - The transaction fee
+ * `message` - The pointer to a char array
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns 0 if unsuccessful or the TxId of the burn transaction if successful
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_burn_funds(struct TariWallet *wallet,
+                                     unsigned long long amount,
+                                     TariPublicKey *claim_public_key,
+                                     unsigned long long fee_per_gram,
+                                     const char *message,
+                                     int *error_out);
+
+/**
+ * Gets the proof of a burn made by the wallet as a json string. Byte fields are hex encoded and `status` is one of
+ * "Pending", "Claimable", "Claimed" or "Cancelled".
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `tx_id` - The TxId of the burn transaction
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs or
+ * the wallet did not make a burn with the given TxId
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *wallet_get_burn_proof(struct TariWallet *wallet,
+                            unsigned long long tx_id,
+                            int *error_out);
+
+/**
+ * Gets the transaction events journaled after the given cursor as a JSON array, oldest first. Each entry holds the
+ * `cursor` of the event, the `event` itself and the unix timestamp it was `created_at`. Pass the cursor of the last
+ * event received to the next call to catch up on events missed while the wallet was not running.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `cursor` - The cursor of the last event already processed, or 0 to get every event still in the journal
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *wallet_get_transaction_events_since(struct TariWallet *wallet,
+                                          unsigned long long cursor,
+                                          int *error_out);
+
+/**
+ * Saves the payment details of a repeat payee as a named send template
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `name` - The unique name of the template
+ * `destination` - The TariWalletAddress pointer of the payee
+ * `default_amount` - The amount sent when no amount is given to `wallet_send_from_template`, or 0 for none
+ * `message` - The pointer to a char array
+ * `fee_priority` - 0 for low, 1 for medium or 2 for high. The fee per gram is estimated for this priority when sending.
+ * `one_sided` - Send one-sided payments rather than interactive transactions
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns the id of the template, or 0 if unsuccessful
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_create_send_template(struct TariWallet *wallet,
+                                               const char *name,
+                                               TariWalletAddress *destination,
+                                               unsigned long long default_amount,
+                                               const char *message,
+                                               unsigned int fee_priority,
+                                               bool one_sided,
+                                               int *error_out);
+
+/**
+ * Gets all send templates as a JSON array ordered by name. Each entry holds the `id`, `name`, `destination` (hex),
+ * `default_amount` (or null), `message`, `fee_priority` (0 low, 1 medium, 2 high) and `one_sided` of the template.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut c_char` - Returns a pointer to a char array. Note that it returns an empty char array if an error occurs
+ *
+ * # Safety
+ * The ```string_destroy``` method must be called when finished with a string from rust to prevent a memory leak
+ */
+char *wallet_get_send_templates(struct TariWallet *wallet,
+                                int *error_out);
+
+/**
+ * Deletes a send template
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `id` - The id of the template
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Returns true if the template was deleted
+ *
+ * # Safety
+ * None
+ */
+bool wallet_delete_send_template(struct TariWallet *wallet,
+                                 unsigned long long id,
+                                 int *error_out);
+
+/**
+ * Sends a payment using the details saved in a send template
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `id` - The id of the template
+ * `amount` - The amount to send, or 0 to send the template's default amount
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns 0 if unsuccessful or the TxId of the sent transaction if successful
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_send_from_template(struct TariWallet *wallet,
+                                             unsigned long long id,
+                                             unsigned long long amount,
+                                             int *error_out);
+
+/**
+ * Selects and encumbers the inputs of a one-sided payment to be built and signed by an offline wallet. This can be
+ * called on a watch-only wallet. The returned bytes are passed to `wallet_sign_unsigned_transaction` on the offline
+ * wallet.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `destination` - The TariWalletAddress pointer of the payee
+ * `amount` - The amount
+ * `fee_per_gram` - The transaction fee
+ * `message` - The pointer to a char array
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut ByteVector` - Returns a pointer to the serialized unsigned transaction. Note that it will be ptr::null_mut()
+ * if an error occurs
+ *
+ * # Safety
+ * The ```byte_vector_destroy``` function must be called when finished with a ByteVector to prevent a memory leak
+ */
+struct ByteVector *wallet_create_unsigned_transaction(struct TariWallet *wallet,
+                                                      TariWalletAddress *destination,
+                                                      unsigned long long amount,
+                                                      unsigned long long fee_per_gram,
+                                                      const char *message,
+                                                      int *error_out);
+
+/**
+ * Builds and signs a transaction created by `wallet_create_unsigned_transaction`. This is called on the offline
+ * wallet that holds the spending keys and does not need a network connection. The returned bytes are passed to
+ * `wallet_import_signed_transaction` on the online wallet.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `unsigned_transaction` - The ByteVector pointer of the serialized unsigned transaction
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut ByteVector` - Returns a pointer to the serialized signed transaction. Note that it will be ptr::null_mut()
+ * if an error occurs
+ *
+ * # Safety
+ * The ```byte_vector_destroy``` function must be called when finished with a ByteVector to prevent a memory leak
+ */
+struct ByteVector *wallet_sign_unsigned_transaction(struct TariWallet *wallet,
+                                                    struct ByteVector *unsigned_transaction,
+                                                    int *error_out);
+
+/**
+ * Checks a transaction signed by `wallet_sign_unsigned_transaction` against the inputs encumbered by
+ * `wallet_create_unsigned_transaction` and broadcasts it
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `signed_transaction` - The ByteVector pointer of the serialized signed transaction
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `unsigned long long` - Returns 0 if unsuccessful or the TxId of the transaction if successful
+ *
+ * # Safety
+ * None
+ */
+unsigned long long wallet_import_signed_transaction(struct TariWallet *wallet,
+                                                    struct ByteVector *signed_transaction,
+                                                    int *error_out);
+
 /**
  * Gets a fee estimate for an amount
  *
@@ -3460,11 +4019,43 @@ unsigned long long wallet_start_transaction_validation(struct TariWallet *wallet
 bool wallet_restart_transaction_broadcast(struct TariWallet *wallet,
                                           int *error_out);
 
+/**
+ * Does a bounded amount of wallet sync work, intended to be called from the short background task windows that
+ * mobile operating systems give to apps. Queued outbound messages are flushed, a batch of unconfirmed transactions
+ * is validated and a limited number of blocks is scanned. Any work that does not fit in the given limits is left for
+ * the next call and reported as remaining.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `max_outbound_messages` - The maximum number of queued outbound transaction messages to resend
+ * `max_transactions` - The maximum number of unconfirmed transactions to validate
+ * `max_blocks` - The maximum number of blocks to scan for outputs
+ * `time_budget_ms` - The time in milliseconds the sync may take, after which the remaining steps are skipped
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariBackgroundSyncReport` - Returns a pointer to a report of the work done, note that it returns
+ * ptr::null_mut() if an error occurred.
+ *
+ * # Safety
+ * The ```destroy_tari_background_sync_report``` method must be called when finished with a TariBackgroundSyncReport
+ * to prevent a memory leak
+ */
+struct TariBackgroundSyncReport *wallet_background_sync(struct TariWallet *wallet,
+                                                        unsigned int max_outbound_messages,
+                                                        unsigned int max_transactions,
+                                                        unsigned long long max_blocks,
+                                                        unsigned long long time_budget_ms,
+                                                        int *error_out);
+
 /**
  * Gets the seed words representing the seed private key of the provided `TariWallet`.
  *
  * ## Arguments
  * `wallet` - The TariWallet pointer
+ * `passphrase` - The wallet passphrase, required when the wallet's re-authentication policy covers seed export. May be
+ * null otherwise.
  * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
  * as an out parameter.
  *
@@ -3476,8 +4067,33 @@ bool wallet_restart_transaction_broadcast(struct TariWallet *wallet,
  * TariSeedWords to prevent a memory leak
  */
 struct TariSeedWords *wallet_get_seed_words(struct TariWallet *wallet,
+                                            const char *passphrase,
                                             int *error_out);
 
+/**
+ * Splits the seed of the provided `TariWallet` into `share_count` Shamir shares of which any `threshold` recover it
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer
+ * `threshold` - The number of shares needed to recover the seed
+ * `share_count` - The number of shares to create
+ * `passphrase` - The wallet passphrase, required when the wallet's re-authentication policy covers seed export. May be
+ * null otherwise.
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `*mut TariSeedShares` - The shares, each as seed words
+ *
+ * # Safety
+ * The ```seed_shares_destroy``` method must be called when finished with a TariSeedShares to prevent a memory leak
+ */
+struct TariSeedShares *wallet_get_seed_shares(struct TariWallet *wallet,
+                                              unsigned char threshold,
+                                              unsigned char share_count,
+                                              const char *passphrase,
+                                              int *error_out);
+
 /**
  * Set the power mode of the wallet to Low Power mode which will reduce the amount of network operations the wallet
  * performs to conserve power
@@ -3669,6 +4285,31 @@ bool wallet_set_one_sided_payment_message(struct TariWallet *wallet,
                                           const char *message,
                                           int *error_out);
 
+/**
+ * Limit how hard UTXO scanning may use the connection to the base node, e.g. while on a metered connection. The
+ * limits apply to all of the wallet's scanning, including background syncs and recovery, and take effect while a scan
+ * is running.
+ *
+ * ## Arguments
+ * `wallet` - The TariWallet pointer.
+ * `max_blocks_per_second` - The maximum number of blocks to scan per second, 0 for no limit
+ * `max_concurrent_requests` - The maximum number of requests to the base node in flight at the same time, 0 for no
+ * limit
+ * `error_out` - Pointer to an int which will be modified to an error code should one occur, may not be null. Functions
+ * as an out parameter.
+ *
+ * ## Returns
+ * `bool` - Return a boolean value indicating the operation's success or failure. The error_ptr will hold the error
+ * code if there was a failure
+ *
+ * # Safety
+ * None
+ */
+bool wallet_set_scan_throttle(struct TariWallet *wallet,
+                              unsigned int max_blocks_per_second,
+                              unsigned int max_concurrent_requests,
+                              int *error_out);
+
 /**
  * Gets the current emoji set
  *
@@ -3760,6 +4401,21 @@ void wallet_destroy(struct TariWallet *wallet);
 void log_debug_message(const char *msg,
                        int *error_out);
 
+/**
+ * Indicates whether a function that failed with the provided error code may succeed if it is called again later with
+ * the same arguments, e.g. once pending funds are confirmed or the base node can be reached again.
+ *
+ * ## Arguments
+ * `error_code` - An error code returned through the `error_out` parameter of another function
+ *
+ * ## Returns
+ * `bool` - Returns true if the failed call is worth retrying, false otherwise
+ *
+ * # Safety
+ * None
+ */
+bool wallet_error_is_retryable(int error_code);
+
 /**
  * ------------------------------------- FeePerGramStats ------------------------------------ ///
  * Get the TariFeePerGramStats from a TariWallet.
@@ -33,6 +33,7 @@ pub struct ConnectionOptions {
     enable_wal: bool,
     enable_foreign_keys: bool,
     busy_timeout: Option<Duration>,
+    read_only: bool,
 }
 
 impl ConnectionOptions {
@@ -41,8 +42,16 @@ impl ConnectionOptions {
             enable_wal,
             enable_foreign_keys,
             busy_timeout: Some(busy_timeout),
+            read_only: false,
         }
     }
+
+    /// Connections will refuse to modify the database. Journal mode is left as is, as WAL mode is persisted in the
+    /// database file by the writer connection.
+    pub fn with_read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
 }
 
 impl diesel::r2d2::CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ConnectionOptions {
@@ -51,7 +60,9 @@ impl diesel::r2d2::CustomizeConnection<SqliteConnection, diesel::r2d2::Error> fo
             if let Some(d) = self.busy_timeout {
                 conn.batch_execute(&format!("PRAGMA busy_timeout = {};", d.as_millis()))?;
             }
-            if self.enable_wal {
+            if self.read_only {
+                conn.batch_execute("PRAGMA query_only = ON;")?;
+            } else if self.enable_wal {
                 conn.batch_execute("PRAGMA journal_mode = WAL; PRAGMA synchronous = NORMAL;")?;
             }
             if self.enable_foreign_keys {
@@ -64,6 +64,12 @@ impl SqliteConnectionPool {
         }
     }
 
+    /// Connections handed out by this pool will be read-only. Must be called before `create_pool`.
+    pub fn with_read_only(mut self) -> Self {
+        self.connection_options = self.connection_options.with_read_only();
+        self
+    }
+
     /// Create an sqlite connection pool managed by the pool connection manager
     pub fn create_pool(&mut self) -> Result<(), SqliteStorageError> {
         if self.pool.is_none() {
@@ -148,4 +154,10 @@ pub trait PooledDbConnection: Send + Sync + Clone {
     type Error;
 
     fn get_pooled_connection(&self) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, Self::Error>;
+
+    /// Returns a connection that should only be used for reads. Implementations that keep a separate pool of
+    /// read-only connections can return one of those so that long reads do not hold up writers.
+    fn get_read_connection(&self) -> Result<PooledConnection<ConnectionManager<SqliteConnection>>, Self::Error> {
+        self.get_pooled_connection()
+    }
 }
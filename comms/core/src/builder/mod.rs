@@ -42,7 +42,7 @@ use tokio::sync::{broadcast, mpsc};
 
 use crate::{
     backoff::{Backoff, BoxedBackoff, ConstantBackoff},
-    connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester},
+    connection_manager::{ConnectionManagerConfig, ConnectionManagerRequester, PeerAccessConfig},
     connectivity::{ConnectivityConfig, ConnectivityRequester},
     multiaddr::Multiaddr,
     peer_manager::{NodeIdentity, PeerManager},
@@ -246,6 +246,12 @@ impl CommsBuilder {
         self
     }
 
+    /// Restrict inbound and outbound connections using peer allow and deny lists. Defaults to unrestricted.
+    pub fn with_peer_access_config(mut self, config: PeerAccessConfig) -> Self {
+        self.connection_manager_config.peer_access = config;
+        self
+    }
+
     /// The maximum number of connection tasks that will be spawned at the same time. Once this limit is reached, peers
     /// attempting to connect will have to wait for another connection attempt to complete.
     pub fn with_max_simultaneous_inbound_connects(mut self, max_simultaneous_inbound_connects: usize) -> Self {
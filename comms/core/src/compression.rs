@@ -0,0 +1,138 @@
+//  Copyright 2023, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+//! Payload compression shared by the RPC protocol and other bulk transfers.
+//!
+//! Compression is always negotiated with the remote peer before it is used, so peers that do not support a particular
+//! algorithm continue to receive uncompressed payloads.
+
+use std::{
+    convert::TryFrom,
+    fmt,
+    io,
+    io::{Read, Write},
+};
+
+/// The zstd compression level. Level 3 is the zstd default and offers a good balance between speed and ratio.
+const ZSTD_COMPRESSION_LEVEL: i32 = 3;
+
+/// Supported payload compression algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    /// Payloads are sent as-is
+    #[default]
+    None,
+    /// Payloads are compressed using zstd
+    Zstd,
+}
+
+impl Compression {
+    /// Returns true if this is not `Compression::None`
+    pub fn is_enabled(self) -> bool {
+        !matches!(self, Compression::None)
+    }
+
+    /// Compresses the given bytes using this compression algorithm. `Compression::None` returns a copy of the input.
+    pub fn compress(self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match self {
+            Compression::None => Ok(data.to_vec()),
+            Compression::Zstd => {
+                let mut encoder =
+                    zstd::stream::write::Encoder::new(Vec::with_capacity(data.len()), ZSTD_COMPRESSION_LEVEL)?;
+                encoder.write_all(data)?;
+                encoder.finish()
+            },
+        }
+    }
+
+    /// Decompresses the given bytes using this compression algorithm. An error is returned if the decompressed size
+    /// exceeds `max_size` bytes, which prevents a malicious peer from sending a small payload that expands to an
+    /// excessive size.
+    pub fn decompress(self, data: &[u8], max_size: usize) -> io::Result<Vec<u8>> {
+        let mut buf = Vec::new();
+        match self {
+            Compression::None => buf.extend_from_slice(data),
+            Compression::Zstd => {
+                let limit = u64::try_from(max_size).unwrap_or(u64::MAX).saturating_add(1);
+                zstd::stream::read::Decoder::new(data)?
+                    .take(limit)
+                    .read_to_end(&mut buf)?;
+            },
+        }
+
+        if buf.len() > max_size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("decompressed payload exceeds the maximum size of {} bytes", max_size),
+            ));
+        }
+        Ok(buf)
+    }
+
+    /// Returns the wire representation of this compression algorithm
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Compression::None => 0,
+            Compression::Zstd => 1,
+        }
+    }
+
+    /// Returns the compression algorithm for the given wire representation, or None if it is not recognised.
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Compression::None),
+            1 => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Compression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Compression::None => write!(f, "none"),
+            Compression::Zstd => write!(f, "zstd"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_round_trips_zstd_payloads() {
+        let data = b"tari".repeat(1024);
+        let compressed = Compression::Zstd.compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        let decompressed = Compression::Zstd.decompress(&compressed, data.len()).unwrap();
+        assert_eq!(decompressed, data);
+    }
+
+    #[test]
+    fn it_rejects_payloads_that_exceed_the_max_size() {
+        let data = vec![0u8; 10 * 1024];
+        let compressed = Compression::Zstd.compress(&data).unwrap();
+        let err = Compression::Zstd.decompress(&compressed, data.len() - 1).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(Compression::None.decompress(&data, data.len() - 1).is_err());
+    }
+}
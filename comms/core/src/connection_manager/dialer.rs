@@ -51,6 +51,7 @@ use crate::{
         common::ValidatedPeerIdentityExchange,
         dial_state::DialState,
         manager::{ConnectionManagerConfig, ConnectionManagerEvent},
+        peer_access::PeerAccessConfig,
         peer_connection,
     },
     multiaddr::Multiaddr,
@@ -347,6 +348,11 @@ where
 
         let span = span!(Level::TRACE, "handle_dial_peer_request_inner1");
         let dial_fut = async move {
+            if let Err(err) = config.peer_access.check_outbound(dial_state.peer()) {
+                debug!(target: LOG_TARGET, "Not dialing peer: {}", err);
+                return (dial_state, Err(err));
+            }
+
             let (dial_state, dial_result) =
                 Self::dial_peer_with_retry(dial_state, noise_config, transport, backoff, &config).await;
 
@@ -511,7 +517,7 @@ where
             tokio::select! {
                 _ = delay => {
                     debug!(target: LOG_TARGET, "[Attempt {}] Connecting to peer '{}'", current_state.num_attempts(), current_state.peer().node_id.short_str());
-                    match Self::dial_peer(current_state, &noise_config, &current_transport, config.network_info.network_byte).await {
+                    match Self::dial_peer(current_state, &noise_config, &current_transport, config.network_info.network_byte, &config.peer_access).await {
                         (state, Ok((socket, addr))) => {
                             debug!(target: LOG_TARGET, "Dial succeeded for peer '{}' after {} attempt(s)", state.peer().node_id.short_str(), state.num_attempts());
                             break (state, Ok((socket, addr)));
@@ -547,6 +553,7 @@ where
         noise_config: &NoiseConfig,
         transport: &TTransport,
         network_byte: u8,
+        peer_access: &PeerAccessConfig,
     ) -> (
         DialState,
         Result<(NoiseSocket<TTransport::Output>, Multiaddr), ConnectionManagerError>,
@@ -554,6 +561,16 @@ where
         let addresses = dial_state.peer().addresses.clone().into_vec();
         let cancel_signal = dial_state.get_cancel_signal();
         for address in addresses {
+            if !peer_access.is_dial_address_permitted(dial_state.peer(), &address) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Skipping address '{}' for peer '{}' because it is not permitted",
+                    address,
+                    dial_state.peer().node_id.short_str()
+                );
+                continue;
+            }
+
             debug!(
                 target: LOG_TARGET,
                 "Attempting address '{}' for peer '{}'",
@@ -70,6 +70,8 @@ pub enum ConnectionManagerError {
     NoiseError(String),
     #[error("Peer is banned, denying connection")]
     PeerBanned,
+    #[error("Peer is not permitted by the peer access configuration: {0}")]
+    PeerNotPermitted(String),
     #[error("Identity protocol failed: {0}")]
     IdentityProtocolError(#[from] IdentityProtocolError),
     #[error("The dial was cancelled")]
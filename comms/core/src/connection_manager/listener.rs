@@ -241,6 +241,14 @@ where
 
         let span = span!(Level::TRACE, "connection_mann::listener::inbound_task",);
         let inbound_fut = async move {
+            if config.peer_access.is_address_denied(&peer_addr) {
+                debug!(
+                    target: LOG_TARGET,
+                    "Inbound connection from address '{}' is denied by the peer access configuration", peer_addr
+                );
+                let _result = socket.shutdown().await;
+                return;
+            }
             #[cfg(feature = "metrics")]
             metrics::pending_connections(None, ConnectionDirection::Inbound).inc();
             match Self::read_wire_format(&mut socket, config.time_to_first_byte).await {
@@ -367,6 +375,8 @@ where
             authenticated_public_key
         );
 
+        config.peer_access.check_peer(&authenticated_public_key, &peer_addr)?;
+
         // Check if we know the peer and if it is banned
         let known_peer = common::find_unbanned_peer(peer_manager, &authenticated_public_key).await?;
 
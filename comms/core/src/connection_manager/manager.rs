@@ -38,6 +38,7 @@ use super::{
     dialer::{Dialer, DialerRequest},
     error::ConnectionManagerError,
     listener::PeerListener,
+    peer_access::PeerAccessConfig,
     peer_connection::PeerConnection,
     requester::ConnectionManagerRequest,
 };
@@ -130,6 +131,9 @@ pub struct ConnectionManagerConfig {
     pub auxiliary_tcp_listener_address: Option<Multiaddr>,
     /// Peer validation configuration. See [PeerValidatorConfig]
     pub peer_validation_config: PeerValidatorConfig,
+    /// Peer allow and deny lists applied to inbound and outbound connections. See [PeerAccessConfig]
+    /// Default: unrestricted
+    pub peer_access: PeerAccessConfig,
 }
 
 impl Default for ConnectionManagerConfig {
@@ -150,6 +154,7 @@ impl Default for ConnectionManagerConfig {
             liveness_self_check_interval: None,
             auxiliary_tcp_listener_address: None,
             peer_validation_config: PeerValidatorConfig::default(),
+            peer_access: PeerAccessConfig::default(),
             noise_handshake_recv_timeout: Duration::from_secs(6),
         }
     }
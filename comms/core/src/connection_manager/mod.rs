@@ -45,6 +45,9 @@ mod manager;
 pub(crate) use manager::ConnectionManager;
 pub use manager::{ConnectionManagerConfig, ConnectionManagerEvent, ListenerInfo};
 
+mod peer_access;
+pub use peer_access::{PeerAccessConfig, PeerAccessEntry, PeerAccessList};
+
 mod error;
 pub use error::{ConnectionManagerError, PeerConnectionError};
 
@@ -0,0 +1,275 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, str::FromStr};
+
+use tari_utilities::hex::Hex;
+
+use super::error::ConnectionManagerError;
+use crate::{
+    multiaddr::Multiaddr,
+    peer_manager::{NodeId, Peer},
+    types::CommsPublicKey,
+    utils::multiaddr::multiaddr_to_socketaddr,
+};
+
+/// A single entry of a [PeerAccessList]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerAccessEntry {
+    NodeId(NodeId),
+    PublicKey(CommsPublicKey),
+    /// An IP address range. This only matches clearnet (TCP/IP) addresses.
+    Cidr(cidr::AnyIpCidr),
+}
+
+impl PeerAccessEntry {
+    fn matches_identity(&self, node_id: &NodeId, public_key: &CommsPublicKey) -> bool {
+        match self {
+            PeerAccessEntry::NodeId(id) => id == node_id,
+            PeerAccessEntry::PublicKey(pk) => pk == public_key,
+            PeerAccessEntry::Cidr(_) => false,
+        }
+    }
+
+    fn matches_address(&self, addr: &Multiaddr) -> bool {
+        match self {
+            PeerAccessEntry::Cidr(cidr) => {
+                multiaddr_to_socketaddr(addr).map_or(false, |socket_addr| cidr.contains(&socket_addr.ip()))
+            },
+            PeerAccessEntry::NodeId(_) | PeerAccessEntry::PublicKey(_) => false,
+        }
+    }
+}
+
+impl FromStr for PeerAccessEntry {
+    type Err = String;
+
+    /// Parses a CIDR range (e.g. `10.0.0.0/8`), a hex node ID or a hex public key
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.contains('/') {
+            return cidr::AnyIpCidr::from_str(s)
+                .map(PeerAccessEntry::Cidr)
+                .map_err(|e| format!("Invalid CIDR '{}': {}", s, e));
+        }
+        if let Ok(node_id) = NodeId::from_hex(s) {
+            return Ok(PeerAccessEntry::NodeId(node_id));
+        }
+        CommsPublicKey::from_hex(s)
+            .map(PeerAccessEntry::PublicKey)
+            .map_err(|_| format!("'{}' is not a CIDR range, node ID or public key", s))
+    }
+}
+
+impl fmt::Display for PeerAccessEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PeerAccessEntry::NodeId(node_id) => write!(f, "node ID {}", node_id),
+            PeerAccessEntry::PublicKey(public_key) => write!(f, "public key {}", public_key),
+            PeerAccessEntry::Cidr(cidr) => write!(f, "address range {}", cidr),
+        }
+    }
+}
+
+/// A list of peer identities and address ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerAccessList {
+    entries: Vec<PeerAccessEntry>,
+}
+
+impl PeerAccessList {
+    pub fn new(entries: Vec<PeerAccessEntry>) -> Self {
+        Self { entries }
+    }
+
+    /// Parses each string as a [PeerAccessEntry], failing if any of them are invalid
+    pub fn parse<I: IntoIterator<Item = T>, T: AsRef<str>>(strs: I) -> Result<Self, String> {
+        let entries = strs
+            .into_iter()
+            .map(|s| s.as_ref().parse())
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { entries })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[PeerAccessEntry] {
+        &self.entries
+    }
+
+    fn find_identity_match(&self, node_id: &NodeId, public_key: &CommsPublicKey) -> Option<&PeerAccessEntry> {
+        self.entries.iter().find(|e| e.matches_identity(node_id, public_key))
+    }
+
+    fn find_address_match(&self, addr: &Multiaddr) -> Option<&PeerAccessEntry> {
+        self.entries.iter().find(|e| e.matches_address(addr))
+    }
+}
+
+/// Restricts the peers that the connection manager will connect with, for both inbound and outbound connections.
+///
+/// A peer is refused if its node ID, public key or connection address matches an entry in the deny list. If the allow
+/// list is not empty, a peer is only accepted if its node ID, public key or connection address matches an entry in the
+/// allow list. The deny list always takes precedence.
+///
+/// Address ranges are matched against the TCP/IP address of the connection, so they only apply to clearnet
+/// connections. Inbound connections forwarded by a Tor hidden service appear to come from the local Tor proxy.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerAccessConfig {
+    pub allow: PeerAccessList,
+    pub deny: PeerAccessList,
+}
+
+impl PeerAccessConfig {
+    /// Returns true if no allow or deny list is configured
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Returns true if connections from or to this address are refused regardless of the peer's identity. This is
+    /// used to drop inbound connections before the peer has identified itself.
+    pub fn is_address_denied(&self, addr: &Multiaddr) -> bool {
+        self.deny.find_address_match(addr).is_some()
+    }
+
+    /// Checks whether a connection with the peer identified by `public_key` on `addr` is permitted
+    pub fn check_peer(&self, public_key: &CommsPublicKey, addr: &Multiaddr) -> Result<(), ConnectionManagerError> {
+        let node_id = NodeId::from_public_key(public_key);
+        self.check_identity(&node_id, public_key)?;
+        self.check_address(&node_id, public_key, addr)
+    }
+
+    /// Checks that the peer may be dialed on at least one of its addresses
+    pub(crate) fn check_outbound(&self, peer: &Peer) -> Result<(), ConnectionManagerError> {
+        self.check_identity(&peer.node_id, &peer.public_key)?;
+        let mut last_err = None;
+        for addr in peer.addresses.iter() {
+            match self.check_address(&peer.node_id, &peer.public_key, addr.address()) {
+                Ok(()) => return Ok(()),
+                Err(err) => last_err = Some(err),
+            }
+        }
+        match last_err {
+            Some(err) => Err(err),
+            // A peer without addresses fails to dial in the usual way
+            None => Ok(()),
+        }
+    }
+
+    /// Returns true if the peer may be dialed on this address
+    pub(crate) fn is_dial_address_permitted(&self, peer: &Peer, addr: &Multiaddr) -> bool {
+        self.check_address(&peer.node_id, &peer.public_key, addr).is_ok()
+    }
+
+    fn check_identity(&self, node_id: &NodeId, public_key: &CommsPublicKey) -> Result<(), ConnectionManagerError> {
+        if let Some(entry) = self.deny.find_identity_match(node_id, public_key) {
+            return Err(ConnectionManagerError::PeerNotPermitted(format!(
+                "peer '{}' is denied by {}",
+                node_id, entry
+            )));
+        }
+        Ok(())
+    }
+
+    fn check_address(
+        &self,
+        node_id: &NodeId,
+        public_key: &CommsPublicKey,
+        addr: &Multiaddr,
+    ) -> Result<(), ConnectionManagerError> {
+        if let Some(entry) = self.deny.find_address_match(addr) {
+            return Err(ConnectionManagerError::PeerNotPermitted(format!(
+                "address '{}' of peer '{}' is denied by {}",
+                addr, node_id, entry
+            )));
+        }
+        if self.allow.is_empty() ||
+            self.allow.find_identity_match(node_id, public_key).is_some() ||
+            self.allow.find_address_match(addr).is_some()
+        {
+            return Ok(());
+        }
+        Err(ConnectionManagerError::PeerNotPermitted(format!(
+            "peer '{}' at address '{}' is not in the allow list",
+            node_id, addr
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_crypto::keys::PublicKey;
+
+    use super::*;
+
+    fn random_public_key() -> CommsPublicKey {
+        CommsPublicKey::random_keypair(&mut rand::rngs::OsRng).1
+    }
+
+    #[test]
+    fn parse_entries() {
+        let public_key = random_public_key();
+        let node_id = NodeId::from_public_key(&public_key);
+        let list = PeerAccessList::parse(["10.0.0.0/8".to_string(), node_id.to_hex(), public_key.to_hex()]).unwrap();
+        assert!(matches!(list.entries()[0], PeerAccessEntry::Cidr(_)));
+        assert_eq!(list.entries()[1], PeerAccessEntry::NodeId(node_id));
+        assert_eq!(list.entries()[2], PeerAccessEntry::PublicKey(public_key));
+
+        PeerAccessList::parse(["10.0.0.0/33"]).unwrap_err();
+        PeerAccessList::parse(["not-a-peer"]).unwrap_err();
+    }
+
+    #[test]
+    fn deny_takes_precedence_over_allow() {
+        let public_key = random_public_key();
+        let node_id = NodeId::from_public_key(&public_key);
+        let config = PeerAccessConfig {
+            allow: PeerAccessList::new(vec![PeerAccessEntry::NodeId(node_id)]),
+            deny: PeerAccessList::parse(["192.168.0.0/16"]).unwrap(),
+        };
+
+        let allowed_addr = "/ip4/10.0.0.1/tcp/18189".parse().unwrap();
+        let denied_addr = "/ip4/192.168.1.1/tcp/18189".parse().unwrap();
+        config.check_peer(&public_key, &allowed_addr).unwrap();
+        assert!(matches!(
+            config.check_peer(&public_key, &denied_addr),
+            Err(ConnectionManagerError::PeerNotPermitted(_))
+        ));
+        assert!(config.is_address_denied(&denied_addr));
+
+        // Not in the allow list
+        let other_public_key = random_public_key();
+        config.check_peer(&other_public_key, &allowed_addr).unwrap_err();
+    }
+
+    #[test]
+    fn unrestricted_by_default() {
+        let config = PeerAccessConfig::default();
+        assert!(config.is_unrestricted());
+        let onion = "/onion3/vww6ybal4bd7szmgncyruucpgfkqahzddi37ktceo3ah7ngmcopnpyyd:1234"
+            .parse()
+            .unwrap();
+        config.check_peer(&random_public_key(), &onion).unwrap();
+    }
+}
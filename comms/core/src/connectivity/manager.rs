@@ -335,16 +335,105 @@ impl ConnectivityManagerActor {
                     },
                 }
 
-                if let Err(err) = self.connection_manager.send_dial_peer(node_id, reply_tx).await {
-                    error!(
-                        target: LOG_TARGET,
-                        "Failed to send dial request to connection manager: {:?}", err
-                    );
-                }
+                self.dial_peer_or_successor(node_id, reply_tx).await;
             },
         }
     }
 
+    /// Dials the peer, falling back to the identity that replaced it if the peer has announced an identity rotation.
+    /// A peer that is already known to be offline is not dialed at all and the dial goes straight to its successor.
+    async fn dial_peer_or_successor(
+        &mut self,
+        node_id: NodeId,
+        reply_tx: Option<oneshot::Sender<Result<PeerConnection, ConnectionManagerError>>>,
+    ) {
+        let successor = match self.peer_manager.find_successor(&node_id).await {
+            Ok(successor) => successor.filter(|peer| !peer.is_banned()),
+            Err(err) => {
+                warn!(
+                    target: LOG_TARGET,
+                    "Failed to look up the successor of peer {}: {}",
+                    node_id.short_str(),
+                    err
+                );
+                None
+            },
+        };
+
+        let Some(successor) = successor else {
+            if let Err(err) = self.connection_manager.send_dial_peer(node_id, reply_tx).await {
+                error!(
+                    target: LOG_TARGET,
+                    "Failed to send dial request to connection manager: {:?}", err
+                );
+            }
+            return;
+        };
+
+        if let Some(conn) = self.pool.get(&successor.node_id).and_then(|state| state.connection()) {
+            if conn.is_connected() {
+                debug!(
+                    target: LOG_TARGET,
+                    "Peer {} rotated its identity to {} which is already connected",
+                    node_id.short_str(),
+                    successor.node_id.short_str()
+                );
+                if let Some(reply_tx) = reply_tx {
+                    let _result = reply_tx.send(Ok(conn.clone()));
+                }
+                return;
+            }
+        }
+
+        let previous_is_offline = self
+            .peer_manager
+            .find_by_node_id(&node_id)
+            .await
+            .ok()
+            .flatten()
+            .map_or(true, |peer| peer.is_offline());
+        if previous_is_offline {
+            info!(
+                target: LOG_TARGET,
+                "Peer {} is offline and rotated its identity to {}. Dialing the new identity...",
+                node_id.short_str(),
+                successor.node_id.short_str()
+            );
+            if let Err(err) = self
+                .connection_manager
+                .send_dial_peer(successor.node_id, reply_tx)
+                .await
+            {
+                error!(
+                    target: LOG_TARGET,
+                    "Failed to send dial request to connection manager: {:?}", err
+                );
+            }
+            return;
+        }
+
+        // The previous identity remains reachable for the rotation window, so it is tried first
+        let mut connection_manager = self.connection_manager.clone();
+        tokio::spawn(async move {
+            let result = match connection_manager.dial_peer(node_id.clone()).await {
+                Err(err) if !matches!(err, ConnectionManagerError::DialCancelled) => {
+                    info!(
+                        target: LOG_TARGET,
+                        "Failed to dial peer {} ({}). It rotated its identity to {}. Dialing the new identity...",
+                        node_id.short_str(),
+                        err,
+                        successor.node_id.short_str()
+                    );
+                    connection_manager.dial_peer(successor.node_id).await
+                },
+                result => result,
+            };
+            if let Some(reply_tx) = reply_tx {
+                let _result = reply_tx.send(result);
+            }
+        });
+    }
+
     async fn disconnect_all(&mut self) {
         let mut node_ids = Vec::with_capacity(self.pool.count_connected());
         for mut state in self.pool.filter_drain(|_| true) {
@@ -36,7 +36,7 @@ use super::{
 };
 use crate::{
     connection_manager::{ConnectionManagerError, ConnectionManagerEvent},
-    connectivity::ConnectivityEventRx,
+    connectivity::{ConnectivityError, ConnectivityEventRx},
     peer_manager::{Peer, PeerFeatures},
     test_utils::{
         build_peer_manager,
@@ -310,6 +310,32 @@ async fn ban_peer() {
     assert!(conn.is_none());
 }
 
+#[tokio::test]
+async fn dial_falls_back_to_successor() {
+    let (connectivity, mut event_stream, node_identity, peer_manager, cm_mock_state, _shutdown) =
+        setup_connectivity_manager(Default::default());
+    let mut peers = add_test_peers(&peer_manager, 2).await;
+    let next = peers.pop().unwrap();
+    let previous = peers.pop().unwrap();
+
+    let mut events = collect_try_recv!(event_stream, take = 1, timeout = Duration::from_secs(10));
+    unpack_enum!(ConnectivityEvent::ConnectivityStateInitialized = events.remove(0));
+
+    // Only the new identity is reachable
+    let (conn, _, _, _) = create_peer_connection_mock_pair(node_identity.to_peer(), next.clone()).await;
+    cm_mock_state.add_active_connection(next.node_id.clone(), conn).await;
+
+    let err = connectivity.dial_peer(previous.node_id.clone()).await.unwrap_err();
+    unpack_enum!(ConnectivityError::ConnectionFailed(_err) = err);
+
+    peer_manager
+        .set_peer_successor(&previous.node_id, &next.public_key)
+        .await
+        .unwrap();
+    let conn = connectivity.dial_peer(previous.node_id.clone()).await.unwrap();
+    assert_eq!(conn.peer_node_id(), &next.node_id);
+}
+
 #[tokio::test]
 async fn peer_selection() {
     let config = ConnectivityConfig {
@@ -36,6 +36,7 @@ mod stream_id;
 
 pub mod backoff;
 pub mod bounded_executor;
+pub mod compression;
 pub mod memsocket;
 pub mod protocol;
 #[macro_use]
@@ -0,0 +1,204 @@
+//  Copyright 2021, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::convert::TryFrom;
+
+use blake2::Blake2b;
+use chrono::{DateTime, Utc};
+use digest::consts::U64;
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use tari_crypto::{hashing::DomainSeparatedHasher, keys::PublicKey as PublicKeyTrait};
+use tari_utilities::ByteArray;
+
+use super::hashing::{comms_core_peer_manager_domain, CommsCorePeerManagerDomain, IDENTITY_ROTATION};
+use crate::{
+    peer_manager::NodeIdentity,
+    types::{CommsPublicKey, CommsSecretKey, Signature},
+};
+
+/// A signed link from a node's previous identity to the identity that replaces it.
+///
+/// The link is signed by both keys. The previous key authorises the hand-over and the new key proves that its owner
+/// accepts it, which prevents a node from claiming another node's identity (and the standing that comes with it) as
+/// its successor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct IdentityRotation {
+    previous_public_key: CommsPublicKey,
+    new_public_key: CommsPublicKey,
+    rotated_at: DateTime<Utc>,
+    previous_signature: Signature,
+    new_signature: Signature,
+}
+
+impl IdentityRotation {
+    pub fn new(
+        previous_public_key: CommsPublicKey,
+        new_public_key: CommsPublicKey,
+        rotated_at: DateTime<Utc>,
+        previous_signature: Signature,
+        new_signature: Signature,
+    ) -> Self {
+        Self {
+            previous_public_key,
+            new_public_key,
+            rotated_at,
+            previous_signature,
+            new_signature,
+        }
+    }
+
+    /// Signs a rotation from the `previous` identity to the `new` identity.
+    pub fn sign(previous: &NodeIdentity, new: &NodeIdentity) -> Self {
+        let rotated_at = Utc::now();
+        let previous_signature = Self::sign_link(
+            previous.secret_key(),
+            previous.public_key(),
+            new.public_key(),
+            rotated_at,
+        );
+        let new_signature = Self::sign_link(new.secret_key(), previous.public_key(), new.public_key(), rotated_at);
+        Self {
+            previous_public_key: previous.public_key().clone(),
+            new_public_key: new.public_key().clone(),
+            rotated_at,
+            previous_signature,
+            new_signature,
+        }
+    }
+
+    pub fn previous_public_key(&self) -> &CommsPublicKey {
+        &self.previous_public_key
+    }
+
+    pub fn new_public_key(&self) -> &CommsPublicKey {
+        &self.new_public_key
+    }
+
+    pub fn rotated_at(&self) -> DateTime<Utc> {
+        self.rotated_at
+    }
+
+    pub fn previous_signature(&self) -> &Signature {
+        &self.previous_signature
+    }
+
+    pub fn new_signature(&self) -> &Signature {
+        &self.new_signature
+    }
+
+    /// Returns true if both keys signed this link
+    pub fn is_valid(&self) -> bool {
+        if self.previous_public_key == self.new_public_key {
+            return false;
+        }
+        // A negative timestamp is considered invalid
+        if self.rotated_at.timestamp() < 0 {
+            return false;
+        }
+        // Do not accept timestamp more than 1 day in the future
+        if self.rotated_at > Utc::now() + chrono::Duration::days(1) {
+            return false;
+        }
+
+        self.verify_link(&self.previous_signature, &self.previous_public_key) &&
+            self.verify_link(&self.new_signature, &self.new_public_key)
+    }
+
+    fn verify_link(&self, signature: &Signature, signer: &CommsPublicKey) -> bool {
+        let challenge = Self::construct_challenge(
+            signer,
+            signature.get_public_nonce(),
+            &self.previous_public_key,
+            &self.new_public_key,
+            self.rotated_at,
+        )
+        .finalize();
+        signature.verify_raw_uniform(signer, challenge.as_ref())
+    }
+
+    fn sign_link(
+        secret_key: &CommsSecretKey,
+        previous_public_key: &CommsPublicKey,
+        new_public_key: &CommsPublicKey,
+        rotated_at: DateTime<Utc>,
+    ) -> Signature {
+        let public_key = CommsPublicKey::from_secret_key(secret_key);
+        let (secret_nonce, public_nonce) = CommsPublicKey::random_keypair(&mut OsRng);
+        let challenge = Self::construct_challenge(
+            &public_key,
+            &public_nonce,
+            previous_public_key,
+            new_public_key,
+            rotated_at,
+        )
+        .finalize();
+        Signature::sign_raw_uniform(secret_key, secret_nonce, challenge.as_ref())
+            .expect("unreachable panic: challenge hash digest is the correct length")
+    }
+
+    fn construct_challenge(
+        public_key: &CommsPublicKey,
+        public_nonce: &CommsPublicKey,
+        previous_public_key: &CommsPublicKey,
+        new_public_key: &CommsPublicKey,
+        rotated_at: DateTime<Utc>,
+    ) -> DomainSeparatedHasher<Blake2b<U64>, CommsCorePeerManagerDomain> {
+        // e = H(P||R||m)
+        comms_core_peer_manager_domain::<Blake2b<U64>>(IDENTITY_ROTATION)
+            .chain(public_key.as_bytes())
+            .chain(public_nonce.as_bytes())
+            .chain(previous_public_key.as_bytes())
+            .chain(new_public_key.as_bytes())
+            .chain(u64::try_from(rotated_at.timestamp()).unwrap_or_default().to_le_bytes())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::peer_manager::PeerFeatures;
+
+    #[test]
+    fn it_returns_true_for_valid_rotation() {
+        let previous = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let new = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let rotation = IdentityRotation::sign(&previous, &new);
+        assert!(rotation.is_valid());
+    }
+
+    #[test]
+    fn it_returns_false_if_the_new_key_did_not_sign() {
+        let previous = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let new = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let victim = NodeIdentity::random_for_test(None, PeerFeatures::COMMUNICATION_NODE);
+        let rotation = IdentityRotation::sign(&previous, &new);
+        let forged = IdentityRotation::new(
+            previous.public_key().clone(),
+            victim.public_key().clone(),
+            rotation.rotated_at(),
+            rotation.previous_signature().clone(),
+            rotation.new_signature().clone(),
+        );
+        assert!(!forged.is_valid());
+    }
+}
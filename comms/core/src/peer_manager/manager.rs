@@ -24,6 +24,7 @@ use std::{fmt, fs::File, time::Duration};
 
 use multiaddr::Multiaddr;
 use tari_storage::{lmdb_store::LMDBDatabase, CachedStore, IterationResult};
+use tari_utilities::ByteArray;
 use tokio::sync::RwLock;
 
 #[cfg(feature = "metrics")]
@@ -32,7 +33,7 @@ use crate::{
     net_address::{MultiaddressesWithStats, PeerAddressSource},
     peer_manager::{
         migrations,
-        peer::{Peer, PeerFlags},
+        peer::{Peer, PeerFlags, PEER_SUCCESSOR_METADATA_KEY},
         peer_id::PeerId,
         peer_storage::PeerStorage,
         wrapper::KeyValueWrapper,
@@ -45,6 +46,9 @@ use crate::{
     types::{CommsDatabase, CommsPublicKey},
 };
 
+/// The maximum number of identity rotations that are followed when looking up the successor of a peer
+const MAX_SUCCESSOR_HOPS: usize = 8;
+
 /// The PeerManager consist of a routing table of previously discovered peers.
 /// It also provides functionality to add, find and delete peers.
 pub struct PeerManager {
@@ -356,6 +360,40 @@ impl PeerManager {
     ) -> Result<Option<Vec<u8>>, PeerManagerError> {
         self.peer_storage.write().await.set_peer_metadata(node_id, key, data)
     }
+
+    /// Records that the peer identified by `node_id` has rotated its identity to `successor`.
+    pub async fn set_peer_successor(
+        &self,
+        node_id: &NodeId,
+        successor: &CommsPublicKey,
+    ) -> Result<(), PeerManagerError> {
+        self.set_peer_metadata(node_id, PEER_SUCCESSOR_METADATA_KEY, successor.as_bytes().to_vec())
+            .await?;
+        Ok(())
+    }
+
+    /// Returns the latest known identity of the peer identified by `node_id`, following any identity rotations it
+    /// has announced. Returns None if the peer has not rotated its identity or its successor is not known.
+    pub async fn find_successor(&self, node_id: &NodeId) -> Result<Option<Peer>, PeerManagerError> {
+        let storage = self.peer_storage.read().await;
+        let Some(mut current) = storage.find_by_node_id(node_id)? else {
+            return Ok(None);
+        };
+        let mut latest = None;
+        for _ in 0..MAX_SUCCESSOR_HOPS {
+            let Some(successor) = current.successor() else {
+                break;
+            };
+            match storage.find_by_public_key(&successor)? {
+                Some(peer) if peer.node_id != *node_id => {
+                    current = peer.clone();
+                    latest = Some(peer);
+                },
+                _ => break,
+            }
+        }
+        Ok(latest)
+    }
 }
 
 impl fmt::Debug for PeerManager {
@@ -653,4 +691,44 @@ mod test {
 
         assert!(!peer.is_offline());
     }
+
+    #[tokio::test]
+    async fn test_find_successor() {
+        let peer_manager = PeerManager::new(HashmapDatabase::new(), None).unwrap();
+        let previous = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        let next = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        let latest = create_test_peer(false, PeerFeatures::COMMUNICATION_NODE);
+        peer_manager.add_peer(previous.clone()).await.unwrap();
+        peer_manager.add_peer(next.clone()).await.unwrap();
+        assert!(peer_manager.find_successor(&previous.node_id).await.unwrap().is_none());
+
+        peer_manager
+            .set_peer_successor(&previous.node_id, &next.public_key)
+            .await
+            .unwrap();
+        let successor = peer_manager.find_successor(&previous.node_id).await.unwrap().unwrap();
+        assert_eq!(successor.node_id, next.node_id);
+
+        // The chain is followed up to the last known identity
+        peer_manager.add_peer(latest.clone()).await.unwrap();
+        peer_manager
+            .set_peer_successor(&next.node_id, &latest.public_key)
+            .await
+            .unwrap();
+        let successor = peer_manager.find_successor(&previous.node_id).await.unwrap().unwrap();
+        assert_eq!(successor.node_id, latest.node_id);
+
+        // Re-adding the previous peer, as discovery does, keeps the link
+        peer_manager.add_peer(previous.clone()).await.unwrap();
+        let successor = peer_manager.find_successor(&previous.node_id).await.unwrap().unwrap();
+        assert_eq!(successor.node_id, latest.node_id);
+
+        // A cycle back to the original peer stops the lookup
+        peer_manager
+            .set_peer_successor(&latest.node_id, &previous.public_key)
+            .await
+            .unwrap();
+        let successor = peer_manager.find_successor(&previous.node_id).await.unwrap().unwrap();
+        assert_eq!(successor.node_id, latest.node_id);
+    }
 }
@@ -76,6 +76,9 @@ pub use error::PeerManagerError;
 mod identity_signature;
 pub use identity_signature::IdentitySignature;
 
+mod identity_rotation;
+pub use identity_rotation::IdentityRotation;
+
 mod hashing;
 
 pub mod node_id;
@@ -88,7 +91,7 @@ mod node_identity;
 pub use node_identity::NodeIdentity;
 
 mod peer;
-pub use peer::{Peer, PeerFlags};
+pub use peer::{Peer, PeerFlags, PEER_SUCCESSOR_METADATA_KEY};
 
 mod peer_features;
 pub use peer_features::PeerFeatures;
@@ -33,7 +33,7 @@ use bitflags::bitflags;
 use chrono::{NaiveDateTime, Utc};
 use multiaddr::Multiaddr;
 use serde::{Deserialize, Serialize};
-use tari_utilities::hex::serialize_to_hex;
+use tari_utilities::{hex::serialize_to_hex, ByteArray};
 
 use super::{
     node_id::{deserialize_node_id_from_hex, NodeId},
@@ -47,6 +47,9 @@ use crate::{
     utils::datetime::{format_local_datetime, is_max_datetime, safe_future_datetime_from_duration},
 };
 
+/// The peer metadata key under which the public key of the identity that replaced a peer is stored
+pub const PEER_SUCCESSOR_METADATA_KEY: u8 = 0x10;
+
 bitflags! {
     /// Miscellaneous Peer flags
     #[derive(Default, Deserialize, Serialize, Eq, PartialEq, Debug, Clone, Copy)]
@@ -142,7 +145,13 @@ impl Peer {
                 self.supported_protocols.push(protocol.clone());
             }
         }
+        // A successor is learnt from an identity rotation announcement, which the other peer record will usually not
+        // know about, so it is kept unless the other record replaces it
+        let successor = self.metadata.remove(&PEER_SUCCESSOR_METADATA_KEY);
         self.metadata = other.metadata.clone();
+        if let Some(successor) = successor {
+            self.metadata.entry(PEER_SUCCESSOR_METADATA_KEY).or_insert(successor);
+        }
         self.features = other.features;
         self.flags = other.flags;
         if !other.user_agent.is_empty() {
@@ -243,6 +252,12 @@ impl Peer {
         self.metadata.get(&key)
     }
 
+    /// Returns the public key of the identity that replaced this peer, if the peer has announced an identity rotation
+    pub fn successor(&self) -> Option<CommsPublicKey> {
+        self.get_metadata(PEER_SUCCESSOR_METADATA_KEY)
+            .and_then(|bytes| CommsPublicKey::from_canonical_bytes(bytes).ok())
+    }
+
     /// Update the peer's addresses. This call will invalidate the identity signature.
     pub fn update_addresses(&mut self, addresses: &[Multiaddr], source: &PeerAddressSource) -> &mut Self {
         self.addresses.update_addresses(addresses, source);
@@ -394,4 +409,31 @@ mod test {
         assert_eq!(json["public_key"], expected_pk_hex);
         assert_eq!(json["node_id"], expected_nodeid_hex);
     }
+
+    #[test]
+    fn merge_keeps_successor() {
+        let mut rng = rand::rngs::OsRng;
+        let (_sk, pk) = RistrettoPublicKey::random_keypair(&mut rng);
+        let (_sk, successor) = RistrettoPublicKey::random_keypair(&mut rng);
+        let node_id = NodeId::from_key(&pk);
+        let mut peer = Peer::new(
+            pk,
+            node_id,
+            MultiaddressesWithStats::from_addresses_with_source(
+                vec!["/ip4/123.0.0.123/tcp/8000".parse::<Multiaddr>().unwrap()],
+                &PeerAddressSource::Config,
+            ),
+            PeerFlags::default(),
+            PeerFeatures::empty(),
+            Default::default(),
+            Default::default(),
+        );
+        let update = peer.clone();
+        assert!(peer.successor().is_none());
+
+        peer.set_metadata(PEER_SUCCESSOR_METADATA_KEY, successor.as_bytes().to_vec());
+        assert_eq!(peer.successor(), Some(successor.clone()));
+        peer.merge(&update);
+        assert_eq!(peer.successor(), Some(successor));
+    }
 }
@@ -60,6 +60,7 @@ use tracing::{span, Instrument, Level};
 
 use super::message::RpcMethod;
 use crate::{
+    compression::Compression,
     framing::CanonicalFraming,
     message::MessageExt,
     peer_manager::NodeId,
@@ -256,6 +257,15 @@ impl<TClient> RpcClientBuilder<TClient> {
         self
     }
 
+    /// Request that the server compresses response payloads using the given compression algorithm. The server may
+    /// decline, in which case responses are received uncompressed. This is worthwhile for clients that download large
+    /// amounts of data, such as block sync.
+    /// Default: `Compression::None`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.config.compression = compression;
+        self
+    }
+
     /// Set the protocol ID associated with this client. This is used for logging purposes only.
     pub fn with_protocol_id(mut self, protocol_id: ProtocolId) -> Self {
         self.protocol_id = Some(protocol_id);
@@ -294,6 +304,7 @@ pub struct RpcClientConfig {
     pub deadline: Option<Duration>,
     pub deadline_grace_period: Duration,
     pub handshake_timeout: Duration,
+    pub compression: Compression,
 }
 
 impl RpcClientConfig {
@@ -314,6 +325,7 @@ impl Default for RpcClientConfig {
             deadline: Some(Duration::from_secs(120)),
             deadline_grace_period: Duration::from_secs(60),
             handshake_timeout: Duration::from_secs(90),
+            compression: Compression::None,
         }
     }
 }
@@ -402,6 +414,7 @@ struct RpcClientWorker<TSubstream> {
     // Request ids are limited to u16::MAX because varint encoding is used over the wire and the magnitude of the value
     // sent determines the byte size. A u16 will be more than enough for the purpose
     next_request_id: u16,
+    compression: Compression,
     ready_tx: Option<oneshot::Sender<Result<(), RpcError>>>,
     protocol_id: ProtocolId,
     shutdown_signal: ShutdownSignal,
@@ -426,6 +439,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             request_rx,
             framed,
             next_request_id: 0,
+            compression: Compression::None,
             ready_tx: Some(ready_tx),
             last_request_latency_tx,
             protocol_id,
@@ -449,16 +463,20 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             self.protocol_name()
         );
         let start = Instant::now();
-        let mut handshake = Handshake::new(&mut self.framed).with_timeout(self.config.handshake_timeout());
+        let mut handshake = Handshake::new(&mut self.framed)
+            .with_timeout(self.config.handshake_timeout())
+            .with_compression(self.config.compression);
         match handshake.perform_client_handshake().await {
             Ok(_) => {
+                self.compression = handshake.negotiated_compression();
                 let latency = start.elapsed();
                 debug!(
                     target: LOG_TARGET,
-                    "(stream={}) RPC Session ({}) negotiation completed. Latency: {:.0?}",
+                    "(stream={}) RPC Session ({}) negotiation completed. Latency: {:.0?}, Compression: {}",
                     self.stream_id(),
                     self.protocol_name(),
-                    latency
+                    latency,
+                    self.compression
                 );
                 let _ = self.last_request_latency_tx.send(Some(latency));
                 if let Some(r) = self.ready_tx.take() {
@@ -557,7 +575,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
             self.protocol_name(),
             start.elapsed()
         );
-        let mut reader = RpcResponseReader::new(&mut self.framed, self.config, 0);
+        let mut reader = RpcResponseReader::new(&mut self.framed, self.config, self.compression, 0);
         let resp = match reader.read_ack().await {
             Ok(resp) => resp,
             Err(RpcError::ReplyTimeout) => {
@@ -821,7 +839,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin + Send + StreamId
         let stream_id = self.stream_id();
         let protocol_name = self.protocol_name().to_string();
 
-        let mut reader = RpcResponseReader::new(&mut self.framed, self.config, request_id);
+        let mut reader = RpcResponseReader::new(&mut self.framed, self.config, self.compression, request_id);
         let mut num_ignored = 0;
         let resp = loop {
             match reader.read_response().await {
@@ -904,6 +922,7 @@ pub enum ClientRequest {
 struct RpcResponseReader<'a, TSubstream> {
     framed: &'a mut CanonicalFraming<TSubstream>,
     config: RpcClientConfig,
+    compression: Compression,
     request_id: u16,
     bytes_read: usize,
     time_to_first_msg: Option<Duration>,
@@ -912,10 +931,16 @@ struct RpcResponseReader<'a, TSubstream> {
 impl<'a, TSubstream> RpcResponseReader<'a, TSubstream>
 where TSubstream: AsyncRead + AsyncWrite + Unpin
 {
-    pub fn new(framed: &'a mut CanonicalFraming<TSubstream>, config: RpcClientConfig, request_id: u16) -> Self {
+    pub fn new(
+        framed: &'a mut CanonicalFraming<TSubstream>,
+        config: RpcClientConfig,
+        compression: Compression,
+        request_id: u16,
+    ) -> Self {
         Self {
             framed,
             config,
+            compression,
             request_id,
             bytes_read: 0,
             time_to_first_msg: None,
@@ -956,7 +981,7 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
                 resp.payload.len()
             );
             if !last_chunk_flags.is_more() {
-                return Ok(resp);
+                return self.decompress_response(resp);
             }
 
             if chunk_count >= RPC_CHUNKING_MAX_CHUNKS {
@@ -981,6 +1006,33 @@ where TSubstream: AsyncRead + AsyncWrite + Unpin
         }
     }
 
+    fn decompress_response(&self, mut resp: proto::rpc::RpcResponse) -> Result<proto::rpc::RpcResponse, RpcError> {
+        let flags = resp.flags().map_err(|e| RpcStatus::protocol_error(&e))?;
+        if !flags.is_compressed() {
+            return Ok(resp);
+        }
+        if !self.compression.is_enabled() {
+            return Err(RpcStatus::protocol_error(
+                &"server sent a compressed payload but compression was not negotiated",
+            )
+            .into());
+        }
+
+        let payload = self
+            .compression
+            .decompress(&resp.payload, rpc::max_response_payload_size())
+            .map_err(|e| RpcStatus::protocol_error(&format!("failed to decompress response payload: {}", e)))?;
+        trace!(
+            target: LOG_TARGET,
+            "Decompressed response payload from {} to {} bytes",
+            resp.payload.len(),
+            payload.len()
+        );
+        resp.payload = payload;
+        resp.flags = u32::from((flags - RpcMessageFlags::COMPRESSED).bits());
+        Ok(resp)
+    }
+
     pub async fn read_ack(&mut self) -> Result<proto::rpc::RpcResponse, RpcError> {
         let resp = self.next().await?;
         Ok(resp)
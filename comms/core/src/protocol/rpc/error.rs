@@ -91,6 +91,7 @@ impl RpcError {
             RpcError::HandshakeError(RpcHandshakeError::ServerClosedRequest) |
             RpcError::HandshakeError(RpcHandshakeError::Rejected(_)) |
             RpcError::HandshakeError(RpcHandshakeError::TimedOut) |
+            RpcError::HandshakeError(RpcHandshakeError::UnsupportedCompression(_)) |
             RpcError::ServerClosedRequest |
             RpcError::UnexpectedAckResponse |
             RpcError::ResponseIdDidNotMatchRequest { .. } => true,
@@ -31,7 +31,13 @@ use tokio::{
 };
 use tracing::{debug, error, span, warn, Instrument, Level};
 
-use crate::{framing::CanonicalFraming, message::MessageExt, proto, protocol::rpc::error::HandshakeRejectReason};
+use crate::{
+    compression::Compression,
+    framing::CanonicalFraming,
+    message::MessageExt,
+    proto,
+    protocol::rpc::error::HandshakeRejectReason,
+};
 
 const LOG_TARGET: &str = "comms::rpc::handshake";
 
@@ -55,12 +61,16 @@ pub enum RpcHandshakeError {
     Rejected(#[from] HandshakeRejectReason),
     #[error("The client connection is closed")]
     ClientClosed,
+    #[error("The server selected a compression algorithm ({0}) that was not offered by the client")]
+    UnsupportedCompression(i32),
 }
 
 /// Handshake protocol
 pub struct Handshake<'a, T> {
     framed: &'a mut CanonicalFraming<T>,
     timeout: Option<Duration>,
+    compression: Compression,
+    negotiated_compression: Compression,
 }
 
 impl<'a, T> Handshake<'a, T>
@@ -68,7 +78,12 @@ where T: AsyncRead + AsyncWrite + Unpin
 {
     /// Create a Handshake using the given framing and no timeout. To set a timeout, use `with_timeout`.
     pub fn new(framed: &'a mut CanonicalFraming<T>) -> Self {
-        Self { framed, timeout: None }
+        Self {
+            framed,
+            timeout: None,
+            compression: Compression::None,
+            negotiated_compression: Compression::None,
+        }
     }
 
     /// Set the length of time that a client/server should wait for the other side to respond before timing out.
@@ -77,6 +92,19 @@ where T: AsyncRead + AsyncWrite + Unpin
         self
     }
 
+    /// Set the compression algorithm that this side of the session supports. The compression is only used if both the
+    /// client and server support it. Default: `Compression::None`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Returns the compression negotiated for the session. This is `Compression::None` until the handshake has
+    /// completed successfully.
+    pub fn negotiated_compression(&self) -> Compression {
+        self.negotiated_compression
+    }
+
     /// Server-side handshake protocol
     pub async fn perform_server_handshake(&mut self) -> Result<u32, RpcHandshakeError> {
         match self.recv_next_frame().await {
@@ -86,9 +114,16 @@ where T: AsyncRead + AsyncWrite + Unpin
                     .iter()
                     .find(|v| msg.supported_versions.contains(v));
                 if let Some(version) = version {
-                    debug!(target: LOG_TARGET, "Server accepted version: {}", version);
+                    if self.compression.is_enabled() && msg.supported_compression.contains(&self.compression.as_i32()) {
+                        self.negotiated_compression = self.compression;
+                    }
+                    debug!(
+                        target: LOG_TARGET,
+                        "Server accepted version: {} (compression: {})", version, self.negotiated_compression
+                    );
                     let reply = proto::rpc::RpcSessionReply {
                         session_result: Some(proto::rpc::rpc_session_reply::SessionResult::AcceptedVersion(*version)),
+                        compression: self.negotiated_compression.as_i32(),
                         ..Default::default()
                     };
                     let span = span!(Level::INFO, "rpc::server::handshake::send_accept_version_reply");
@@ -125,6 +160,7 @@ where T: AsyncRead + AsyncWrite + Unpin
         let reply = proto::rpc::RpcSessionReply {
             session_result: Some(proto::rpc::rpc_session_reply::SessionResult::Rejected(true)),
             reject_reason: reject_reason.as_i32(),
+            ..Default::default()
         };
         self.framed.send(reply.to_encoded_bytes().into()).await?;
         self.framed.close().await?;
@@ -135,6 +171,11 @@ where T: AsyncRead + AsyncWrite + Unpin
     pub async fn perform_client_handshake(&mut self) -> Result<(), RpcHandshakeError> {
         let msg = proto::rpc::RpcSession {
             supported_versions: SUPPORTED_RPC_VERSIONS.to_vec(),
+            supported_compression: if self.compression.is_enabled() {
+                vec![self.compression.as_i32()]
+            } else {
+                vec![]
+            },
         };
         let payload = msg.to_encoded_bytes();
         debug!(target: LOG_TARGET, "Sending client handshake ({} bytes)", payload.len());
@@ -152,7 +193,14 @@ where T: AsyncRead + AsyncWrite + Unpin
             Ok(Some(Ok(msg))) => {
                 let msg = proto::rpc::RpcSessionReply::decode(&mut msg.freeze())?;
                 let version = msg.result()?;
-                debug!(target: LOG_TARGET, "Server accepted version {}", version);
+                let compression = Compression::from_i32(msg.compression)
+                    .filter(|c| !c.is_enabled() || *c == self.compression)
+                    .ok_or(RpcHandshakeError::UnsupportedCompression(msg.compression))?;
+                self.negotiated_compression = compression;
+                debug!(
+                    target: LOG_TARGET,
+                    "Server accepted version {} (compression: {})", version, compression
+                );
                 Ok(())
             },
             Ok(Some(Err(err))) => {
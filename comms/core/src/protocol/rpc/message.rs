@@ -205,6 +205,8 @@ bitflags! {
         const ACK = 0x02;
         /// Another chunk to be received
         const MORE = 0x04;
+        /// The payload is compressed using the compression negotiated for the session
+        const COMPRESSED = 0x08;
     }
 }
 impl RpcMessageFlags {
@@ -219,6 +221,10 @@ impl RpcMessageFlags {
     pub fn is_more(self) -> bool {
         self.contains(Self::MORE)
     }
+
+    pub fn is_compressed(self) -> bool {
+        self.contains(Self::COMPRESSED)
+    }
 }
 
 impl Default for RpcMessageFlags {
@@ -35,6 +35,8 @@ pub const RPC_MAX_FRAME_SIZE: usize = 3 * 1024 * 1024; // 3 MiB
 const RPC_CHUNKING_MAX_CHUNKS: usize = 16; // 16 x 256 Kib = 4 MiB max combined message size
 const RPC_CHUNKING_THRESHOLD: usize = 256 * 1024;
 const RPC_CHUNKING_SIZE_LIMIT: usize = 384 * 1024;
+/// Response payloads smaller than this are never compressed, as the savings do not justify the overhead.
+const RPC_COMPRESSION_THRESHOLD: usize = 1024;
 
 /// The maximum request payload size
 const fn max_request_size() -> usize {
@@ -63,7 +65,15 @@ pub use body::{Body, ClientStreaming, IntoBody, Streaming};
 mod context;
 
 mod server;
-pub use server::{mock, NamedProtocolService, RpcServer, RpcServerBuilder, RpcServerError, RpcServerHandle};
+pub use server::{
+    mock,
+    NamedProtocolService,
+    RpcServer,
+    RpcServerBuilder,
+    RpcServerError,
+    RpcServerHandle,
+    RpcSessionInfo,
+};
 
 mod client;
 pub use client::{
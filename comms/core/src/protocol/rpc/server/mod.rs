@@ -38,6 +38,7 @@ pub mod mock;
 mod early_close;
 mod router;
 
+mod session_stats;
 use std::{
     borrow::Cow,
     cmp,
@@ -56,6 +57,8 @@ use futures::{future, stream, stream::FuturesUnordered, SinkExt, StreamExt};
 use log::*;
 use prost::Message;
 use router::Router;
+pub use session_stats::RpcSessionInfo;
+use session_stats::RpcSessionStats;
 use tokio::{sync::mpsc, task::JoinHandle, time};
 use tokio_stream::Stream;
 use tower::{make::MakeService, Service};
@@ -69,10 +72,12 @@ use super::{
     not_found::ProtocolServiceNotFound,
     status::RpcStatus,
     Handshake,
+    RPC_COMPRESSION_THRESHOLD,
     RPC_MAX_FRAME_SIZE,
 };
 use crate::{
     bounded_executor::BoundedExecutor,
+    compression::Compression,
     framing,
     framing::CanonicalFraming,
     message::MessageExt,
@@ -179,6 +184,7 @@ pub struct RpcServerBuilder {
     maximum_sessions_per_client: Option<usize>,
     minimum_client_deadline: Duration,
     handshake_timeout: Duration,
+    compression: Compression,
 }
 
 impl RpcServerBuilder {
@@ -211,6 +217,15 @@ impl RpcServerBuilder {
         self
     }
 
+    /// Set the compression algorithm that the server supports for response payloads. Compression is only used for
+    /// sessions where the client requested the same algorithm in the handshake. Set to `Compression::None` to disable.
+    ///
+    /// Default: `Compression::Zstd`
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
     pub fn finish(self) -> RpcServer {
         let (request_tx, request_rx) = mpsc::channel(10);
         RpcServer {
@@ -228,6 +243,7 @@ impl Default for RpcServerBuilder {
             maximum_sessions_per_client: None,
             minimum_client_deadline: Duration::from_secs(1),
             handshake_timeout: Duration::from_secs(15),
+            compression: Compression::Zstd,
         }
     }
 }
@@ -240,7 +256,9 @@ pub(super) struct PeerRpcServer<TSvc, TCommsProvider> {
     comms_provider: TCommsProvider,
     request_rx: mpsc::Receiver<RpcServerRequest>,
     sessions: HashMap<NodeId, usize>,
-    tasks: FuturesUnordered<JoinHandle<NodeId>>,
+    session_stats: HashMap<u64, Arc<RpcSessionStats>>,
+    next_session_id: u64,
+    tasks: FuturesUnordered<JoinHandle<(NodeId, u64)>>,
 }
 
 impl<TSvc, TCommsProvider> PeerRpcServer<TSvc, TCommsProvider>
@@ -277,6 +295,8 @@ where
             comms_provider,
             request_rx,
             sessions: HashMap::new(),
+            session_stats: HashMap::new(),
+            next_session_id: 0,
             tasks: FuturesUnordered::new(),
         }
     }
@@ -297,8 +317,8 @@ where
                     }
                 }
 
-                Some(Ok(node_id)) = self.tasks.next() => {
-                    self.on_session_complete(&node_id);
+                Some(Ok((node_id, session_id))) = self.tasks.next() => {
+                    self.on_session_complete(&node_id, session_id);
                 },
 
                 Some(req) = self.request_rx.recv() => {
@@ -331,6 +351,11 @@ where
                 let num_active = self.sessions.get(&node_id).copied().unwrap_or(0);
                 let _ = reply.send(num_active);
             },
+            GetActiveSessions(reply) => {
+                let mut sessions = self.session_stats.values().map(|s| s.to_info()).collect::<Vec<_>>();
+                sessions.sort_by(|a, b| b.duration.cmp(&a.duration));
+                let _ = reply.send(sessions);
+            },
         }
     }
 
@@ -384,8 +409,9 @@ where
         Ok(*count)
     }
 
-    fn on_session_complete(&mut self, node_id: &NodeId) {
+    fn on_session_complete(&mut self, node_id: &NodeId, session_id: u64) {
         info!(target: LOG_TARGET, "Session complete for {}", node_id);
+        self.session_stats.remove(&session_id);
         if let Some(v) = self.sessions.get_mut(node_id) {
             *v -= 1;
             if *v == 0 {
@@ -400,7 +426,9 @@ where
         node_id: &NodeId,
         mut framed: CanonicalFraming<Substream>,
     ) -> Result<(), RpcServerError> {
-        let mut handshake = Handshake::new(&mut framed).with_timeout(self.config.handshake_timeout);
+        let mut handshake = Handshake::new(&mut framed)
+            .with_timeout(self.config.handshake_timeout)
+            .with_compression(self.config.compression);
 
         if !self.executor.can_spawn() {
             debug!(
@@ -448,18 +476,25 @@ where
         }
 
         let version = handshake.perform_server_handshake().await?;
+        let compression = handshake.negotiated_compression();
         debug!(
             target: LOG_TARGET,
-            "Server negotiated RPC v{} with client node `{}`", version, node_id
+            "Server negotiated RPC v{} (compression: {}) with client node `{}`", version, compression, node_id
         );
 
+        let session_id = self.next_session_id;
+        self.next_session_id += 1;
+        let stats = Arc::new(RpcSessionStats::new(session_id, node_id.clone(), protocol.clone()));
+
         let service = ActivePeerRpcService::new(
             self.config.clone(),
             protocol,
             node_id.clone(),
             service,
             framed,
+            compression,
             self.comms_provider.clone(),
+            stats.clone(),
         );
 
         let node_id = node_id.clone();
@@ -475,10 +510,11 @@ where
                 #[cfg(feature = "metrics")]
                 num_sessions.dec();
 
-                node_id
+                (node_id, session_id)
             })
             .map_err(|_| RpcServerError::MaximumSessionsReached)?;
 
+        self.session_stats.insert(stats.session_id(), stats);
         self.tasks.push(handle);
 
         Ok(())
@@ -491,8 +527,10 @@ struct ActivePeerRpcService<TSvc, TCommsProvider> {
     node_id: NodeId,
     service: TSvc,
     framed: EarlyClose<CanonicalFraming<Substream>>,
+    compression: Compression,
     comms_provider: TCommsProvider,
     logging_context_string: Arc<String>,
+    stats: Arc<RpcSessionStats>,
 }
 
 impl<TSvc, TCommsProvider> ActivePeerRpcService<TSvc, TCommsProvider>
@@ -506,7 +544,9 @@ where
         node_id: NodeId,
         service: TSvc,
         framed: CanonicalFraming<Substream>,
+        compression: Compression,
         comms_provider: TCommsProvider,
+        stats: Arc<RpcSessionStats>,
     ) -> Self {
         Self {
             logging_context_string: Arc::new(format!(
@@ -521,7 +561,9 @@ where
             node_id,
             service,
             framed: EarlyClose::new(framed),
+            compression,
             comms_provider,
+            stats,
         }
     }
 
@@ -632,7 +674,7 @@ where
             };
             #[cfg(feature = "metrics")]
             metrics::status_error_counter(&self.node_id, &self.protocol, status.as_status_code()).inc();
-            self.framed.send(bad_request.to_encoded_bytes().into()).await?;
+            self.send_response(bad_request.to_encoded_bytes().into()).await?;
             return Ok(());
         }
 
@@ -676,6 +718,7 @@ where
             method,
             decoded_msg.payload.into(),
         );
+        self.stats.inc_requests_served();
 
         let service_call = log_timing(
             self.logging_context_string.clone(),
@@ -723,13 +766,20 @@ where
 
                 #[cfg(feature = "metrics")]
                 metrics::status_error_counter(&self.node_id, &self.protocol, err.as_status_code()).inc();
-                self.framed.send(resp.to_encoded_bytes().into()).await?;
+                self.send_response(resp.to_encoded_bytes().into()).await?;
             },
         }
 
         Ok(())
     }
 
+    async fn send_response(&mut self, msg: Bytes) -> Result<(), RpcServerError> {
+        let len = msg.len();
+        self.framed.send(msg).await?;
+        self.stats.add_bytes_out(len);
+        Ok(())
+    }
+
     fn protocol_name(&self) -> Cow<'_, str> {
         String::from_utf8_lossy(&self.protocol)
     }
@@ -746,9 +796,11 @@ where
         let node_id = self.node_id.clone();
         #[cfg(feature = "metrics")]
         let protocol = self.protocol.clone();
+        let compression = self.compression;
         let mut stream = body
             .into_message()
             .map(|result| into_response(request_id, result))
+            .map(move |message| compress_response(compression, message))
             .flat_map(move |message| {
                 #[cfg(feature = "metrics")]
                 if !message.status.is_ok() {
@@ -793,7 +845,7 @@ where
                                 msg.len()
                             );
 
-                            self.framed.send(msg).await?;
+                            self.send_response(msg).await?;
                         },
                         None => {
                             debug!(target: LOG_TARGET, "{} Request complete", self.logging_context_string,);
@@ -921,6 +973,37 @@ fn into_response(request_id: u32, result: Result<BodyBytes, RpcStatus>) -> RpcRe
     }
 }
 
+/// Compresses the response payload if compression was negotiated for the session and the payload is large enough to
+/// benefit from it. Compression is applied before chunking, so every chunk of a compressed payload carries the
+/// COMPRESSED flag and the client decompresses the reassembled payload.
+fn compress_response(compression: Compression, mut response: RpcResponse) -> RpcResponse {
+    if !compression.is_enabled() || !response.status.is_ok() || response.payload.len() < RPC_COMPRESSION_THRESHOLD {
+        return response;
+    }
+
+    match compression.compress(&response.payload) {
+        Ok(compressed) if compressed.len() < response.payload.len() => {
+            trace!(
+                target: LOG_TARGET,
+                "Compressed response payload from {} to {} bytes ({})",
+                response.payload.len(),
+                compressed.len(),
+                compression
+            );
+            response.payload = Bytes::from(compressed);
+            response.flags |= RpcMessageFlags::COMPRESSED;
+        },
+        Ok(_) => {},
+        Err(err) => {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to compress response payload. Sending uncompressed: {}", err
+            );
+        },
+    }
+    response
+}
+
 fn err_to_log_level(err: &io::Error) -> log::Level {
     match err.kind() {
         ErrorKind::ConnectionReset |
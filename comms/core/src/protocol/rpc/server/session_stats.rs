@@ -0,0 +1,98 @@
+// Copyright 2023. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use crate::{peer_manager::NodeId, protocol::ProtocolId};
+
+/// A snapshot of the statistics of an active RPC session
+#[derive(Debug, Clone)]
+pub struct RpcSessionInfo {
+    /// Unique (for the lifetime of the RPC server) identifier of the session
+    pub session_id: u64,
+    /// The peer that opened the session
+    pub peer_node_id: NodeId,
+    /// The protocol of the RPC service being served
+    pub protocol: ProtocolId,
+    /// The number of requests that have been passed on to the service
+    pub requests_served: u64,
+    /// The number of response bytes sent to the peer
+    pub bytes_out: u64,
+    /// The time since the session was established
+    pub duration: Duration,
+}
+
+impl RpcSessionInfo {
+    pub fn protocol_name(&self) -> String {
+        String::from_utf8_lossy(&self.protocol).to_string()
+    }
+}
+
+/// Live statistics for an RPC session, shared between the session task and the RPC server
+#[derive(Debug)]
+pub(super) struct RpcSessionStats {
+    session_id: u64,
+    peer_node_id: NodeId,
+    protocol: ProtocolId,
+    started_at: Instant,
+    requests_served: AtomicU64,
+    bytes_out: AtomicU64,
+}
+
+impl RpcSessionStats {
+    pub fn new(session_id: u64, peer_node_id: NodeId, protocol: ProtocolId) -> Self {
+        Self {
+            session_id,
+            peer_node_id,
+            protocol,
+            started_at: Instant::now(),
+            requests_served: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+        }
+    }
+
+    pub fn session_id(&self) -> u64 {
+        self.session_id
+    }
+
+    pub fn inc_requests_served(&self) {
+        self.requests_served.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes_out(&self, num_bytes: usize) {
+        self.bytes_out.fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn to_info(&self) -> RpcSessionInfo {
+        RpcSessionInfo {
+            session_id: self.session_id,
+            peer_node_id: self.peer_node_id.clone(),
+            protocol: self.protocol.clone(),
+            requests_served: self.requests_served.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            duration: self.started_at.elapsed(),
+        }
+    }
+}
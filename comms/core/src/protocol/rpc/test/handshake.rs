@@ -24,6 +24,7 @@ use tari_test_utils::unpack_enum;
 use tokio::task;
 
 use crate::{
+    compression::Compression,
     framing,
     memsocket::MemorySocket,
     protocol::rpc::{
@@ -51,6 +52,37 @@ async fn it_performs_the_handshake() {
     assert!(SUPPORTED_RPC_VERSIONS.contains(&v));
 }
 
+#[tokio::test]
+async fn it_negotiates_compression() {
+    async fn negotiate(client_compression: Compression, server_compression: Compression) -> (Compression, Compression) {
+        let (client, server) = MemorySocket::new_pair();
+
+        let server_result = task::spawn(async move {
+            let mut server_framed = framing::canonical(server, 1024);
+            let mut handshake_server = Handshake::new(&mut server_framed).with_compression(server_compression);
+            handshake_server.perform_server_handshake().await.unwrap();
+            handshake_server.negotiated_compression()
+        });
+
+        let mut client_framed = framing::canonical(client, 1024);
+        let mut handshake_client = Handshake::new(&mut client_framed).with_compression(client_compression);
+        handshake_client.perform_client_handshake().await.unwrap();
+        (handshake_client.negotiated_compression(), server_result.await.unwrap())
+    }
+
+    let (client, server) = negotiate(Compression::Zstd, Compression::Zstd).await;
+    assert_eq!(client, Compression::Zstd);
+    assert_eq!(server, Compression::Zstd);
+
+    let (client, server) = negotiate(Compression::None, Compression::Zstd).await;
+    assert_eq!(client, Compression::None);
+    assert_eq!(server, Compression::None);
+
+    let (client, server) = negotiate(Compression::Zstd, Compression::None).await;
+    assert_eq!(client, Compression::None);
+    assert_eq!(server, Compression::None);
+}
+
 #[tokio::test]
 async fn it_rejects_the_handshake() {
     let (client, server) = MemorySocket::new_pair();
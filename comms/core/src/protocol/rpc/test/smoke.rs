@@ -583,3 +583,75 @@ async fn max_per_client_sessions() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn active_session_stats() {
+    let (notif_tx, notif_rx) = mpsc::channel(10);
+    let (context, _) = create_mocked_rpc_context();
+    let server = RpcServer::builder()
+        .with_minimum_client_deadline(Duration::from_secs(0))
+        .finish()
+        .add_service(GreetingServer::new(GreetingService::default()));
+    let mut server_handle = server.get_handle();
+    let _server_hnd = task::spawn(server.serve(notif_rx, context.clone()));
+
+    let (_, mut inbound, outbound) = build_multiplexed_connections().await;
+    let node_identity = build_node_identity(Default::default());
+    context.peer_manager().add_peer(node_identity.to_peer()).await.unwrap();
+    let substream = outbound.get_yamux_control().open_stream().await.unwrap();
+    notif_tx
+        .send(ProtocolNotification::new(
+            ProtocolId::from_static(b"/test/greeting/1.0"),
+            ProtocolEvent::NewInboundSubstream(node_identity.node_id().clone(), substream),
+        ))
+        .await
+        .unwrap();
+
+    let socket = inbound.incoming_mut().next().await.unwrap();
+    let framed = framing::canonical(socket, 1024);
+    let mut client = GreetingClient::builder()
+        .with_deadline(Duration::from_secs(5))
+        .connect(framed)
+        .await
+        .unwrap();
+
+    let sessions = server_handle.get_active_sessions().await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].peer_node_id, *node_identity.node_id());
+    assert_eq!(sessions[0].protocol_name(), "/test/greeting/1.0");
+    assert_eq!(sessions[0].requests_served, 0);
+
+    for _ in 0..2 {
+        client
+            .say_hello(SayHelloRequest {
+                name: "Stats".to_string(),
+                language: 0,
+            })
+            .await
+            .unwrap();
+    }
+
+    // The byte count is updated once the response has been flushed, which can be just after the client receives it
+    let session = time::timeout(Duration::from_secs(5), async {
+        loop {
+            let sessions = server_handle.get_active_sessions().await.unwrap();
+            if sessions[0].bytes_out > 0 {
+                break sessions[0].clone();
+            }
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    assert_eq!(session.requests_served, 2);
+
+    // The stats of a session are dropped when it ends
+    client.close().await;
+    time::timeout(Duration::from_secs(5), async {
+        while !server_handle.get_active_sessions().await.unwrap().is_empty() {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
@@ -175,6 +175,15 @@ impl ConnectivityManagerMockState {
         .await
     }
 
+    /// Answers dials to `node_id` with `conn`, as the connectivity manager does for a peer that has rotated its
+    /// identity
+    pub async fn add_redirected_connection(&self, node_id: NodeId, conn: PeerConnection) {
+        self.with_state(|state| {
+            state.active_conns.insert(node_id, conn);
+        })
+        .await
+    }
+
     pub async fn set_pending_connection(&self, peer: &NodeId) {
         self.with_state(|state| {
             state.pending_conns.entry(peer.clone()).or_default();
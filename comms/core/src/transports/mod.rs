@@ -41,6 +41,9 @@ pub mod predicate;
 mod memory;
 pub use memory::MemoryTransport;
 
+mod onion_only;
+pub use onion_only::OnionOnlyTransport;
+
 mod socks;
 pub use socks::{SocksConfig, SocksTransport};
 
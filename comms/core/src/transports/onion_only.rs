@@ -0,0 +1,143 @@
+// Copyright 2023, The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::io;
+
+use futures::{future, stream, StreamExt};
+use log::*;
+use multiaddr::{Multiaddr, Protocol};
+
+use super::Transport;
+use crate::transports::predicate::is_onion_address;
+
+const LOG_TARGET: &str = "comms::transports::onion_only";
+
+type InboundResult<T> = Result<(T, Multiaddr), io::Error>;
+
+/// Transport that only allows onion addresses, for nodes that must never touch the clearnet.
+///
+/// Dialing anything but an onion address is refused, and the wrapped transport may only listen on a loopback address
+/// to which the tor hidden service forwards its traffic. Inbound connections that did not come from a loopback address
+/// (and so did not come through tor) are refused.
+#[derive(Debug, Clone)]
+pub struct OnionOnlyTransport<T> {
+    inner: T,
+}
+
+impl<T> OnionOnlyTransport<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+}
+
+#[crate::async_trait]
+impl<T> Transport for OnionOnlyTransport<T>
+where
+    T: Transport<Error = io::Error> + Send + Sync,
+    T::Output: Send + 'static,
+{
+    type Error = io::Error;
+    type Listener =
+        stream::Filter<T::Listener, future::Ready<bool>, fn(&InboundResult<T::Output>) -> future::Ready<bool>>;
+    type Output = T::Output;
+
+    async fn listen(&self, addr: &Multiaddr) -> Result<(Self::Listener, Multiaddr), Self::Error> {
+        if !is_loopback_address(addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Refusing to listen on non-loopback address '{}' in onion-only mode",
+                    addr
+                ),
+            ));
+        }
+        let (listener, addr) = self.inner.listen(addr).await?;
+        let filter: fn(&InboundResult<T::Output>) -> future::Ready<bool> = is_allowed_inbound::<T::Output>;
+        Ok((listener.filter(filter), addr))
+    }
+
+    async fn dial(&self, addr: &Multiaddr) -> Result<Self::Output, Self::Error> {
+        if !is_onion_address(addr) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("Refusing to dial non-onion address '{}' in onion-only mode", addr),
+            ));
+        }
+        self.inner.dial(addr).await
+    }
+}
+
+fn is_allowed_inbound<T>(result: &InboundResult<T>) -> future::Ready<bool> {
+    let allowed = match result {
+        Ok((_, peer_addr)) if !is_loopback_address(peer_addr) => {
+            warn!(
+                target: LOG_TARGET,
+                "Refusing inbound connection from non-loopback address '{}' in onion-only mode",
+                peer_addr
+            );
+            false
+        },
+        _ => true,
+    };
+    future::ready(allowed)
+}
+
+fn is_loopback_address(addr: &Multiaddr) -> bool {
+    match addr.iter().next() {
+        Some(Protocol::Ip4(ip)) => ip.is_loopback(),
+        Some(Protocol::Ip6(ip)) => ip.is_loopback(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::transports::TcpTransport;
+
+    #[tokio::test]
+    async fn it_refuses_clearnet_addresses() {
+        let transport = OnionOnlyTransport::new(TcpTransport::new());
+
+        let err = transport
+            .dial(&"/ip4/1.2.3.4/tcp/1234".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let err = transport
+            .dial(&"/dns4/mikes-node-nook.com/tcp/80".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        let err = transport
+            .listen(&"/ip4/0.0.0.0/tcp/0".parse().unwrap())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        let (_listener, addr) = transport
+            .listen(&"/ip4/127.0.0.1/tcp/0".parse().unwrap())
+            .await
+            .unwrap();
+        assert!(is_loopback_address(&addr));
+    }
+}
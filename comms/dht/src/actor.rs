@@ -35,7 +35,16 @@ use log::*;
 use tari_comms::{
     connection_manager::ConnectionManagerError,
     connectivity::{ConnectivityError, ConnectivityRequester, ConnectivitySelection},
-    peer_manager::{NodeId, NodeIdentity, PeerFeatures, PeerManager, PeerManagerError, PeerQuery, PeerQuerySortBy},
+    peer_manager::{
+        IdentityRotation,
+        NodeId,
+        NodeIdentity,
+        PeerFeatures,
+        PeerManager,
+        PeerManagerError,
+        PeerQuery,
+        PeerQuerySortBy,
+    },
     types::CommsPublicKey,
     PeerConnection,
 };
@@ -57,7 +66,10 @@ use crate::{
     dedup::DedupCacheDatabase,
     discovery::DhtDiscoveryError,
     outbound::{DhtOutboundError, OutboundMessageRequester, SendMessageParams},
-    proto::{dht::JoinMessage, envelope::DhtMessageType},
+    proto::{
+        dht::{IdentityRotationMessage, JoinMessage},
+        envelope::DhtMessageType,
+    },
     storage::{DbConnection, DhtDatabase, DhtMetadataKey, StorageError},
     DhtConfig,
     DhtDiscoveryRequester,
@@ -76,6 +88,8 @@ pub enum DhtActorError {
     PeerManagerError(#[from] PeerManagerError),
     #[error("Failed to broadcast join message: {0}")]
     FailedToBroadcastJoinMessage(DhtOutboundError),
+    #[error("Failed to broadcast identity rotation message: {0}")]
+    FailedToBroadcastIdentityRotationMessage(DhtOutboundError),
     #[error("DiscoveryError: {0}")]
     DiscoveryError(#[from] DhtDiscoveryError),
     #[error("StorageError: {0}")]
@@ -102,6 +116,8 @@ impl<T> From<mpsc::error::SendError<T>> for DhtActorError {
 pub enum DhtRequest {
     /// Send a Join request to the network
     SendJoin,
+    /// Announce to the network that this node's identity is being replaced
+    SendIdentityRotation(IdentityRotationMessage),
     /// Inserts a message signature to the msg hash cache. This operation replies with the number of times this message
     /// has previously been seen (hit count)
     MsgHashCacheInsert {
@@ -138,6 +154,7 @@ impl Display for DhtRequest {
         use DhtRequest::*;
         match self {
             SendJoin => write!(f, "SendJoin"),
+            SendIdentityRotation(msg) => write!(f, "SendIdentityRotation({})", msg),
             MsgHashCacheInsert {
                 message_hash,
                 received_from,
@@ -184,6 +201,22 @@ impl DhtRequester {
         self.sender.send(DhtRequest::SendJoin).await.map_err(Into::into)
     }
 
+    /// Announce to the network that this node's identity is being replaced by `new_identity`. The rotation must be
+    /// signed by this node's current identity.
+    pub async fn send_identity_rotation(
+        &mut self,
+        rotation: &IdentityRotation,
+        new_identity: &NodeIdentity,
+    ) -> Result<(), DhtActorError> {
+        self.sender
+            .send(DhtRequest::SendIdentityRotation(IdentityRotationMessage::new(
+                rotation,
+                new_identity,
+            )))
+            .await
+            .map_err(Into::into)
+    }
+
     /// Select peers by [BroadcastStrategy](crate::broadcast_strategy::BroadcastStrategy]
     pub async fn select_peers(&mut self, broadcast_strategy: BroadcastStrategy) -> Result<Vec<NodeId>, DhtActorError> {
         let (reply_tx, reply_rx) = oneshot::channel();
@@ -391,6 +424,15 @@ impl DhtActor {
                 let outbound_requester = self.outbound_requester.clone();
                 Box::pin(Self::broadcast_join(node_identity, outbound_requester))
             },
+            SendIdentityRotation(message) => {
+                let node_identity = Arc::clone(&self.node_identity);
+                let outbound_requester = self.outbound_requester.clone();
+                Box::pin(Self::broadcast_identity_rotation(
+                    node_identity,
+                    outbound_requester,
+                    message,
+                ))
+            },
             MsgHashCacheInsert {
                 message_hash,
                 received_from,
@@ -514,6 +556,31 @@ impl DhtActor {
         Ok(())
     }
 
+    async fn broadcast_identity_rotation(
+        node_identity: Arc<NodeIdentity>,
+        mut outbound_requester: OutboundMessageRequester,
+        message: IdentityRotationMessage,
+    ) -> Result<(), DhtActorError> {
+        debug!(target: LOG_TARGET, "Sending {} to closest peers", message);
+
+        // Peers closest to the current identity are sent the announcement, they propagate it towards the new identity
+        outbound_requester
+            .send_message_no_header(
+                SendMessageParams::new()
+                    .closest(node_identity.node_id().clone(), vec![])
+                    .with_destination(node_identity.public_key().clone().into())
+                    .with_dht_message_type(DhtMessageType::IdentityRotation)
+                    .with_debug_info("Broadcast identity rotation".to_string())
+                    .force_origin()
+                    .finish(),
+                message,
+            )
+            .await
+            .map_err(DhtActorError::FailedToBroadcastIdentityRotationMessage)?;
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_lines)]
     async fn select_peers(
         config: &DhtConfig,
@@ -103,11 +103,14 @@ impl DhtMessageType {
     }
 
     pub fn is_dht_message(self) -> bool {
-        self.is_dht_discovery() || self.is_dht_discovery_response() || self.is_dht_join()
+        self.is_dht_discovery() ||
+            self.is_dht_discovery_response() ||
+            self.is_dht_join() ||
+            self.is_dht_identity_rotation()
     }
 
     pub fn is_forwardable(self) -> bool {
-        self.is_domain_message() || self.is_dht_discovery() || self.is_dht_join()
+        self.is_domain_message() || self.is_dht_discovery() || self.is_dht_join() || self.is_dht_identity_rotation()
     }
 
     pub fn is_dht_discovery(self) -> bool {
@@ -122,6 +125,10 @@ impl DhtMessageType {
         matches!(self, DhtMessageType::Join)
     }
 
+    pub fn is_dht_identity_rotation(self) -> bool {
+        matches!(self, DhtMessageType::IdentityRotation)
+    }
+
     pub fn is_saf_message(self) -> bool {
         use DhtMessageType::{SafRequestMessages, SafStoredMessages};
         matches!(self, SafRequestMessages | SafStoredMessages)
@@ -20,12 +20,15 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{convert::TryInto, sync::Arc};
+use std::{
+    convert::{TryFrom, TryInto},
+    sync::Arc,
+};
 
 use log::*;
 use tari_comms::{
     message::MessageExt,
-    peer_manager::{NodeId, NodeIdentity, PeerManager},
+    peer_manager::{IdentityRotation, NodeId, NodeIdentity, PeerManager},
     pipeline::PipelineError,
     types::CommsPublicKey,
     OrNotFound,
@@ -41,7 +44,7 @@ use crate::{
     outbound::{OutboundMessageRequester, SendMessageParams},
     peer_validator::{DhtPeerValidatorError, PeerValidator},
     proto::{
-        dht::{DiscoveryMessage, DiscoveryResponseMessage, JoinMessage},
+        dht::{DiscoveryMessage, DiscoveryResponseMessage, IdentityRotationMessage, JoinMessage},
         envelope::DhtMessageType,
     },
     rpc::UnvalidatedPeerInfo,
@@ -128,6 +131,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             DhtMessageType::Join => self.handle_join(message).await?,
             DhtMessageType::Discovery => self.handle_discover(message).await?,
             DhtMessageType::DiscoveryResponse => self.handle_discover_response(message).await?,
+            DhtMessageType::IdentityRotation => self.handle_identity_rotation(message).await?,
             // Not a DHT message, call downstream middleware
             _ => {
                 trace!(
@@ -275,6 +279,166 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(())
     }
 
+    #[allow(clippy::too_many_lines)]
+    async fn handle_identity_rotation(&mut self, message: DecryptedDhtMessage) -> Result<(), DhtInboundError> {
+        let DecryptedDhtMessage {
+            decryption_result,
+            dht_header,
+            source_peer,
+            authenticated_origin,
+            is_saf_message,
+            ..
+        } = message;
+
+        let Some(authenticated_pk) = authenticated_origin else {
+            warn!(
+                target: LOG_TARGET,
+                "Received IdentityRotationMessage that did not have an authenticated origin from source peer {}. Banning source", source_peer
+            );
+
+            self.dht
+                .ban_peer(
+                    source_peer.public_key.clone(),
+                    OffenceSeverity::Low,
+                    "Received IdentityRotationMessage that did not have an authenticated origin",
+                )
+                .await;
+            return Ok(());
+        };
+
+        if authenticated_pk == *self.node_identity.public_key() {
+            debug!(target: LOG_TARGET, "Received our own identity rotation message. Discarding it.");
+            return Ok(());
+        }
+
+        let body = decryption_result.expect("already checked that this message decrypted successfully");
+        let rotation_msg = self
+            .ban_on_offence(
+                &authenticated_pk,
+                body.decode_part::<IdentityRotationMessage>(0)
+                    .map_err(Into::into)
+                    .and_then(|o| o.ok_or(DhtInboundError::InvalidMessageBody)),
+            )
+            .await?;
+
+        if rotation_msg.previous_public_key.as_slice() != authenticated_pk.as_bytes() {
+            warn!(
+                target: LOG_TARGET,
+                "Received IdentityRotationMessage from peer that mismatches the authenticated origin. \
+                This message was signed by another party which may be attempting to get other nodes banned. \
+                Banning the message signer."
+            );
+            self.dht
+                .ban_peer(
+                    authenticated_pk,
+                    OffenceSeverity::High,
+                    "Received IdentityRotationMessage from peer with a public key that does not match the previous \
+                     identity",
+                )
+                .await;
+
+            return Ok(());
+        }
+
+        let rotation = self
+            .ban_on_offence(
+                &authenticated_pk,
+                IdentityRotation::try_from(&rotation_msg)
+                    .map_err(|e| DhtInboundError::InvalidIdentityRotation(e.to_string()))
+                    .and_then(|rotation| {
+                        if rotation.is_valid() {
+                            Ok(rotation)
+                        } else {
+                            Err(DhtInboundError::InvalidIdentityRotation(
+                                "rotation signature is invalid".to_string(),
+                            ))
+                        }
+                    }),
+            )
+            .await?;
+
+        debug!(target: LOG_TARGET, "Received {}", rotation_msg);
+
+        let new_identity = rotation_msg
+            .new_identity
+            .expect("already checked that the rotation message contains the new identity");
+        let validator = PeerValidator::new(&self.config);
+        let maybe_existing = self.peer_manager.find_by_public_key(rotation.new_public_key()).await?;
+        let mut valid_peer = self
+            .ban_on_offence(
+                &authenticated_pk,
+                validator
+                    .validate_peer(new_identity.try_into()?, maybe_existing)
+                    .map_err(Into::into),
+            )
+            .await?;
+
+        // The new identity inherits the standing of the identity it replaces, so that rotating keys cannot be used to
+        // shake off a ban
+        let previous_peer = self.peer_manager.find_by_public_key(&authenticated_pk).await?;
+        if let Some(previous_peer) = previous_peer.as_ref() {
+            if previous_peer.is_banned() && !valid_peer.is_banned() {
+                valid_peer.banned_until = previous_peer.banned_until;
+                valid_peer.banned_reason = format!("Rotated from banned peer: {}", previous_peer.banned_reason);
+            }
+        }
+
+        let is_banned = valid_peer.is_banned();
+        let valid_peer_node_id = valid_peer.node_id.clone();
+        let valid_peer_public_key = valid_peer.public_key.clone();
+        self.peer_manager.add_peer(valid_peer).await?;
+
+        // Link the previous identity to the new one so that dials to the previous identity are redirected once it is
+        // retired
+        if let Some(previous_peer) = previous_peer {
+            self.peer_manager
+                .set_peer_successor(&previous_peer.node_id, &valid_peer_public_key)
+                .await?;
+        }
+
+        if is_banned {
+            debug!(
+                target: LOG_TARGET,
+                "Received identity rotation for banned peer. This rotation will not be propagated."
+            );
+            return Ok(());
+        }
+
+        if is_saf_message {
+            debug!(
+                target: LOG_TARGET,
+                "Not re-propagating identity rotation message received from store and forward"
+            );
+            return Ok(());
+        }
+
+        if dht_header.destination != self.node_identity.public_key() {
+            debug!(
+                target: LOG_TARGET,
+                "Propagating identity rotation from peer '{}' to '{}'",
+                authenticated_pk,
+                valid_peer_node_id.short_str()
+            );
+            // Propagate message towards the neighbourhood of the new identity
+            self.outbound_service
+                .send_raw_no_wait(
+                    SendMessageParams::new()
+                        .propagate(valid_peer_public_key.into(), vec![
+                            valid_peer_node_id,
+                            NodeId::from_public_key(&authenticated_pk),
+                            source_peer.node_id.clone(),
+                        ])
+                        .with_debug_info("Propagating identity rotation message".to_string())
+                        .with_dht_header(dht_header)
+                        .finish(),
+                    body.encode_into_bytes_mut(),
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
     async fn handle_discover_response(&mut self, message: DecryptedDhtMessage) -> Result<(), DhtInboundError> {
         trace!(
             target: LOG_TARGET,
@@ -486,6 +650,11 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                             .await;
                     },
                     DhtInboundError::ConnectivityError(_) => {},
+                    err @ DhtInboundError::InvalidIdentityRotation(_) => {
+                        self.dht
+                            .ban_peer(authenticated_pk.clone(), OffenceSeverity::High, err)
+                            .await;
+                    },
                 }
                 Err(err)
             },
@@ -30,13 +30,16 @@ use chrono::{DateTime, NaiveDateTime, Utc};
 use rand::{rngs::OsRng, RngCore};
 use tari_comms::{
     multiaddr::Multiaddr,
-    peer_manager::{IdentitySignature, PeerFeatures, PeerIdentityClaim},
+    peer_manager::{IdentityRotation, IdentitySignature, PeerFeatures, PeerIdentityClaim},
     types::{CommsPublicKey, CommsSecretKey, Signature},
     NodeIdentity,
 };
 use tari_utilities::{hex::Hex, ByteArray};
 
-use crate::{proto::dht::JoinMessage, rpc::UnvalidatedPeerInfo};
+use crate::{
+    proto::dht::{IdentityRotationMessage, JoinMessage},
+    rpc::UnvalidatedPeerInfo,
+};
 
 pub mod common {
     tari_comms::outdir_include!("tari.dht.common.rs");
@@ -90,6 +93,74 @@ impl fmt::Display for dht::JoinMessage {
     }
 }
 
+//---------------------------------- IdentityRotationMessage --------------------------------------------//
+
+impl IdentityRotationMessage {
+    pub fn new(rotation: &IdentityRotation, new_identity: &NodeIdentity) -> Self {
+        Self {
+            previous_public_key: rotation.previous_public_key().to_vec(),
+            new_identity: Some(JoinMessage::from(new_identity)),
+            rotated_at: rotation.rotated_at().timestamp(),
+            previous_signature: rotation.previous_signature().get_signature().to_vec(),
+            previous_public_nonce: rotation.previous_signature().get_public_nonce().to_vec(),
+            new_signature: rotation.new_signature().get_signature().to_vec(),
+            new_public_nonce: rotation.new_signature().get_public_nonce().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<&IdentityRotationMessage> for IdentityRotation {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &IdentityRotationMessage) -> Result<Self, Self::Error> {
+        let previous_public_key = CommsPublicKey::from_canonical_bytes(&value.previous_public_key)
+            .map_err(|e| anyhow!("Invalid previous public key: {}", e))?;
+        let new_identity = value
+            .new_identity
+            .as_ref()
+            .ok_or_else(|| anyhow!("IdentityRotationMessage missing new identity"))?;
+        let new_public_key = CommsPublicKey::from_canonical_bytes(&new_identity.public_key)
+            .map_err(|e| anyhow!("Invalid new public key: {}", e))?;
+        let rotated_at = NaiveDateTime::from_timestamp_opt(value.rotated_at, 0)
+            .ok_or_else(|| anyhow::anyhow!("rotated_at overflowed"))?;
+        let rotated_at = DateTime::<Utc>::from_naive_utc_and_offset(rotated_at, Utc);
+        let previous_signature = Signature::new(
+            CommsPublicKey::from_canonical_bytes(&value.previous_public_nonce)
+                .map_err(|e| anyhow!("Invalid public nonce: {}", e))?,
+            CommsSecretKey::from_canonical_bytes(&value.previous_signature)
+                .map_err(|e| anyhow!("Invalid signature: {}", e))?,
+        );
+        let new_signature = Signature::new(
+            CommsPublicKey::from_canonical_bytes(&value.new_public_nonce)
+                .map_err(|e| anyhow!("Invalid public nonce: {}", e))?,
+            CommsSecretKey::from_canonical_bytes(&value.new_signature)
+                .map_err(|e| anyhow!("Invalid signature: {}", e))?,
+        );
+
+        Ok(Self::new(
+            previous_public_key,
+            new_public_key,
+            rotated_at,
+            previous_signature,
+            new_signature,
+        ))
+    }
+}
+
+impl fmt::Display for dht::IdentityRotationMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "IdentityRotationMessage(Previous PK = {}, New identity = {})",
+            self.previous_public_key.to_hex(),
+            self.new_identity
+                .as_ref()
+                .map(ToString::to_string)
+                .unwrap_or_else(|| "<none>".to_string()),
+        )
+    }
+}
+
 //---------------------------------- Rpc Message Conversions --------------------------------------------//
 
 impl From<UnvalidatedPeerInfo> for rpc::PeerInfo {
@@ -61,6 +61,10 @@ pub struct SafConfig {
     /// The maximum number of peer nodes that a message must be closer than to get stored by SAF
     /// Default: 8
     pub num_neighbouring_nodes: usize,
+    /// When true, stored messages are requested in compressed form and are compressed when responding to peers that
+    /// request it. This reduces the bandwidth used when retrieving a large number of stored messages.
+    /// Default: true
+    pub enable_compression: bool,
 }
 
 impl Default for SafConfig {
@@ -76,6 +80,7 @@ impl Default for SafConfig {
             max_message_size: 512 * 1024,
             max_inflight_request_age: Duration::from_secs(120),
             num_neighbouring_nodes: 8,
+            enable_compression: true,
         }
     }
 }
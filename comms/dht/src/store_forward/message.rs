@@ -25,6 +25,7 @@ use std::convert::TryFrom;
 use chrono::{DateTime, Utc};
 use prost::Message;
 use rand::{rngs::OsRng, RngCore};
+use tari_comms::compression::Compression;
 
 use crate::{
     envelope::datetime_to_epochtime,
@@ -41,6 +42,7 @@ impl StoredMessagesRequest {
             since: 0,
             request_id: OsRng.next_u32(),
             limit: 0,
+            compression: Compression::None.as_i32(),
         }
     }
 
@@ -49,6 +51,7 @@ impl StoredMessagesRequest {
             since: datetime_to_epochtime(since).as_u64(),
             request_id: OsRng.next_u32(),
             limit: 0,
+            compression: Compression::None.as_i32(),
         }
     }
 }
@@ -23,6 +23,7 @@
 use std::{
     cmp,
     convert::{TryFrom, TryInto},
+    mem,
     sync::Arc,
 };
 
@@ -31,6 +32,7 @@ use futures::{future, stream, StreamExt};
 use log::*;
 use prost::Message;
 use tari_comms::{
+    compression::Compression,
     message::{EnvelopeBody, MessageTag},
     peer_manager::{NodeId, NodeIdentity, Peer, PeerFeatures, PeerManagerError},
     pipeline::PipelineError,
@@ -222,6 +224,12 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
             },
         };
 
+        // Only compress if the requester asked for a compression algorithm that we support
+        let compression = Some(retrieve_msgs.compression)
+            .filter(|_| self.config.enable_compression)
+            .and_then(Compression::from_i32)
+            .unwrap_or_default();
+
         let response_types = vec![SafResponseType::ForMe];
 
         for resp_type in response_types {
@@ -240,6 +248,7 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
                 stored_messages.messages().len(),
                 resp_type
             );
+            let stored_messages = compress_stored_messages(compression, stored_messages);
 
             match self
                 .outbound_service
@@ -297,9 +306,10 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         let msg = message
             .success()
             .expect("already checked that this message decrypted successfully");
-        let response = msg
+        let mut response = msg
             .decode_part::<StoredMessagesResponse>(0)?
             .ok_or(StoreAndForwardError::EnvelopeBodyMissingMessagePart)?;
+        self.decompress_stored_messages(&mut response)?;
 
         if response.messages.len() > self.config.max_returned_messages {
             warn!(
@@ -363,6 +373,40 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
         Ok(())
     }
 
+    fn decompress_stored_messages(&self, response: &mut StoredMessagesResponse) -> Result<(), StoreAndForwardError> {
+        let compression = Compression::from_i32(response.compression)
+            .filter(|c| self.config.enable_compression || !c.is_enabled())
+            .ok_or_else(|| StoreAndForwardError::InvalidSafResponseMessage {
+                field: "compression",
+                details: format!("unsupported or unrequested compression ({})", response.compression),
+            })?;
+        if !compression.is_enabled() {
+            return Ok(());
+        }
+
+        let max_size = self
+            .config
+            .max_returned_messages
+            .saturating_mul(self.config.max_message_size);
+        let decompressed = compression
+            .decompress(&response.compressed_messages, max_size)
+            .map_err(|err| StoreAndForwardError::InvalidSafResponseMessage {
+                field: "compressed_messages",
+                details: err.to_string(),
+            })?;
+        let inner = StoredMessagesResponse::decode(decompressed.as_slice())?;
+        trace!(
+            target: LOG_TARGET,
+            "Decompressed {} stored message(s) from {} to {} bytes",
+            inner.messages.len(),
+            response.compressed_messages.len(),
+            decompressed.len()
+        );
+        response.messages = inner.messages;
+        response.compressed_messages = Vec::new();
+        Ok(())
+    }
+
     async fn process_incoming_stored_messages(
         &mut self,
         source_peer: Arc<Peer>,
@@ -748,6 +792,44 @@ where S: Service<DecryptedDhtMessage, Response = (), Error = PipelineError>
     }
 }
 
+/// Moves the stored messages into the compressed_messages field if compression is enabled and reduces the response
+/// size.
+fn compress_stored_messages(compression: Compression, mut response: StoredMessagesResponse) -> StoredMessagesResponse {
+    if !compression.is_enabled() || response.messages.is_empty() {
+        return response;
+    }
+
+    let inner = StoredMessagesResponse {
+        messages: mem::take(&mut response.messages),
+        ..Default::default()
+    };
+    let encoded = inner.encode_to_vec();
+    match compression.compress(&encoded) {
+        Ok(compressed) if compressed.len() < encoded.len() => {
+            trace!(
+                target: LOG_TARGET,
+                "Compressed {} stored message(s) from {} to {} bytes",
+                inner.messages.len(),
+                encoded.len(),
+                compressed.len()
+            );
+            response.compression = compression.as_i32();
+            response.compressed_messages = compressed;
+        },
+        Ok(_) => {
+            response.messages = inner.messages;
+        },
+        Err(err) => {
+            warn!(
+                target: LOG_TARGET,
+                "Failed to compress stored messages. Sending uncompressed: {}", err
+            );
+            response.messages = inner.messages;
+        },
+    }
+    response
+}
+
 #[cfg(test)]
 mod test {
     use std::time::Duration;
@@ -1004,7 +1086,8 @@ mod test {
             wrap_in_envelope_body!(StoredMessagesResponse {
                 messages: vec![msg1.clone(), msg2, msg_clear],
                 request_id: 123,
-                response_type: 0
+                response_type: 0,
+                ..Default::default()
             }),
             None,
             make_dht_inbound_message(
@@ -1113,7 +1196,8 @@ mod test {
             wrap_in_envelope_body!(StoredMessagesResponse {
                 messages: vec![msg1.clone(), msg2],
                 request_id: 123,
-                response_type: 0
+                response_type: 0,
+                ..Default::default()
             }),
             None,
             make_dht_inbound_message(
@@ -1190,7 +1274,8 @@ mod test {
             wrap_in_envelope_body!(StoredMessagesResponse {
                 messages: vec![msg1.clone()],
                 request_id: 123,
-                response_type: 0
+                response_type: 0,
+                ..Default::default()
             }),
             None,
             make_dht_inbound_message(
@@ -1263,7 +1348,8 @@ mod test {
             wrap_in_envelope_body!(StoredMessagesResponse {
                 messages: vec![msg1.clone()],
                 request_id: 123,
-                response_type: 0
+                response_type: 0,
+                ..Default::default()
             }),
             None,
             make_dht_inbound_message(
@@ -29,6 +29,7 @@ use std::{
 use chrono::{DateTime, NaiveDateTime, Utc};
 use log::*;
 use tari_comms::{
+    compression::Compression,
     connectivity::{ConnectivityEvent, ConnectivityEventRx, ConnectivityRequester},
     peer_manager::{NodeId, PeerFeatures},
     types::CommsPublicKey,
@@ -453,6 +454,9 @@ impl StoreAndForwardService {
             .unwrap_or_else(StoredMessagesRequest::new);
 
         request.limit = self.config.max_returned_messages.try_into().unwrap_or(u32::MAX);
+        if self.config.enable_compression {
+            request.compression = Compression::Zstd.as_i32();
+        }
 
         Ok(request)
     }
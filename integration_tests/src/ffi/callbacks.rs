@@ -296,6 +296,15 @@ impl Callbacks {
         );
     }
 
+    pub fn on_clock_skew(&mut self, is_skewed: bool, offset_ms: i64) {
+        println!(
+            "{} Clock Skew {} with offset {}ms.",
+            chrono::Local::now().format("%Y/%m/%d %H:%M:%S"),
+            if is_skewed { "Detected" } else { "Resolved" },
+            offset_ms
+        );
+    }
+
     pub fn on_basenode_state_update(&mut self, state: *mut c_void) {
         *self.basenode_state_updated.lock().unwrap() += 1;
         println!(
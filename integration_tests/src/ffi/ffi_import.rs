@@ -384,6 +384,7 @@ extern "C" {
         size_per_log_file_bytes: c_uint,
         passphrase: *const c_char,
         seed_words: *const TariSeedWords,
+        seed_passphrase: *const c_char,
         network_str: *const c_char,
         callback_received_transaction: unsafe extern "C" fn(*mut TariPendingInboundTransaction),
         callback_received_transaction_reply: unsafe extern "C" fn(*mut TariCompletedTransaction),
@@ -402,6 +403,7 @@ extern "C" {
         callback_saf_messages_received: unsafe extern "C" fn(),
         callback_connectivity_status: unsafe extern "C" fn(u64),
         callback_base_node_state_updated: unsafe extern "C" fn(*mut TariBaseNodeState),
+        callback_clock_skew: unsafe extern "C" fn(bool, i64),
         recovery_in_progress: *mut bool,
         error_out: *mut c_int,
     ) -> *mut TariWallet;
@@ -530,7 +532,11 @@ extern "C" {
     pub fn wallet_start_txo_validation(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong;
     pub fn wallet_start_transaction_validation(wallet: *mut TariWallet, error_out: *mut c_int) -> c_ulonglong;
     pub fn wallet_restart_transaction_broadcast(wallet: *mut TariWallet, error_out: *mut c_int) -> bool;
-    pub fn wallet_get_seed_words(wallet: *mut TariWallet, error_out: *mut c_int) -> *mut TariSeedWords;
+    pub fn wallet_get_seed_words(
+        wallet: *mut TariWallet,
+        passphrase: *const c_char,
+        error_out: *mut c_int,
+    ) -> *mut TariSeedWords;
     pub fn wallet_set_low_power_mode(wallet: *mut TariWallet, error_out: *mut c_int);
     pub fn wallet_set_normal_power_mode(wallet: *mut TariWallet, error_out: *mut c_int);
     pub fn wallet_set_key_value(
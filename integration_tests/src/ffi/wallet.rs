@@ -22,7 +22,7 @@
 
 use std::{
     ffi::CString,
-    ptr::null_mut,
+    ptr::{null, null_mut},
     sync::{Arc, Mutex},
 };
 
@@ -140,6 +140,10 @@ extern "C" fn callback_base_node_state(state: *mut TariBaseNodeState) {
     let callbacks = Callbacks::instance();
     callbacks.on_basenode_state_update(state);
 }
+extern "C" fn callback_clock_skew(is_skewed: bool, offset_ms: i64) {
+    let callbacks = Callbacks::instance();
+    callbacks.on_clock_skew(is_skewed, offset_ms);
+}
 
 #[derive(Default, Debug)]
 struct CachedBalance {
@@ -176,6 +180,7 @@ impl Wallet {
                 104857600, // 100 MB
                 CString::new("kensentme").unwrap().into_raw(),
                 seed_words_ptr,
+                null(),
                 CString::new("localnet").unwrap().into_raw(),
                 callback_received_transaction,
                 callback_received_transaction_reply,
@@ -194,6 +199,7 @@ impl Wallet {
                 callback_saf_messages_received,
                 callback_connectivity_status,
                 callback_base_node_state,
+                callback_clock_skew,
                 &mut recovery_in_progress,
                 &mut error,
             );
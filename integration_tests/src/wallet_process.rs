@@ -24,7 +24,7 @@ use std::{path::PathBuf, str::FromStr, thread, time::Duration};
 
 use minotari_app_grpc::tari_rpc::SetBaseNodeRequest;
 use minotari_app_utilities::common_cli_args::CommonCliArgs;
-use minotari_console_wallet::{run_wallet_with_cli, Cli};
+use minotari_console_wallet::{run_wallet_with_cli, Cli, MetricsConfig};
 use minotari_wallet::{transaction_service::config::TransactionRoutingMechanism, WalletConfig};
 use minotari_wallet_grpc_client::WalletGrpcClient;
 use tari_common::configuration::{CommonConfig, MultiaddrList};
@@ -118,6 +118,7 @@ pub async fn spawn_wallet(
                 peer_seeds: peer_addresses.into(),
                 ..Default::default()
             },
+            metrics: MetricsConfig::default(),
         };
 
         eprintln!("Using wallet temp_dir: {}", temp_dir_path.clone().display());
@@ -212,8 +213,12 @@ pub fn get_default_cli() -> Cli {
         },
         password: None,
         change_password: false,
+        restore_backup: None,
+        dry_run: false,
         recovery: false,
+        full_scan: false,
         seed_words: None,
+        seed_passphrase: None,
         seed_words_file_name: None,
         non_interactive_mode: true,
         input_file: None,
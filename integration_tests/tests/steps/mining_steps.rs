@@ -130,6 +130,7 @@ async fn while_mining_all_txs_in_wallet_are_mined_confirmed(world: &mut TariWorl
         'inner: for retry in 0..=num_retries {
             let req = GetTransactionInfoRequest {
                 transaction_ids: vec![*tx_id],
+                confirmation_threshold: None,
             };
             let res = wallet_client.get_transaction_info(req).await.unwrap().into_inner();
             let tx_status = res.transactions.first().unwrap().status;
@@ -189,6 +190,7 @@ async fn while_mining_in_node_all_txs_in_wallet_are_mined_confirmed(
         'inner: for _ in 0..num_retries {
             let req = GetTransactionInfoRequest {
                 transaction_ids: vec![*tx_id],
+                confirmation_threshold: None,
             };
             let res = wallet_client.get_transaction_info(req).await.unwrap().into_inner();
             let tx_status = res.transactions.first().unwrap().status;
@@ -577,7 +577,12 @@ async fn stop_node(world: &mut TariWorld, node: String) {
 async fn node_lists_heights(world: &mut TariWorld, node: String, start: u64, end: u64) {
     let mut node_client = world.get_node_client(&node).await.unwrap();
     let heights = (start..=end).collect::<Vec<_>>();
-    let blocks_req = GetBlocksRequest { heights };
+    let blocks_req = GetBlocksRequest {
+        heights,
+        only_coinbases: false,
+        only_kernels: false,
+        exclude_range_proofs: false,
+    };
     let mut blocks_stream = node_client.get_blocks(blocks_req).await.unwrap().into_inner();
 
     let mut height = start;
@@ -157,7 +157,9 @@ async fn wallet_detects_all_txs_as_mined_confirmed(world: &mut TariWorld, wallet
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
 
     let mut completed_tx_stream = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -172,6 +174,7 @@ async fn wallet_detects_all_txs_as_mined_confirmed(world: &mut TariWorld, wallet
         for retry in 0..=num_retries {
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -266,6 +269,7 @@ async fn wallet_detects_all_txs_are_at_least_in_some_status(
         for retry in 0..=num_retries {
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![*tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -338,6 +342,7 @@ async fn wallet_detects_all_txs_as_broadcast(world: &mut TariWorld, wallet_name:
         for retry in 0..=num_retries {
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![*tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -390,6 +395,7 @@ async fn wallet_detects_last_tx_as_pending(world: &mut TariWorld, wallet: String
     for retry in 0..=num_retries {
         let request = GetTransactionInfoRequest {
             transaction_ids: vec![*tx_id],
+            confirmation_threshold: None,
         };
         let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
         let tx_info = tx_info.transactions.first().unwrap();
@@ -436,6 +442,7 @@ async fn wallet_detects_last_tx_as_cancelled(world: &mut TariWorld, wallet: Stri
     for retry in 0..=num_retries {
         let request = GetTransactionInfoRequest {
             transaction_ids: vec![*tx_id],
+            confirmation_threshold: None,
         };
         let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
         let tx_info = tx_info.transactions.first().unwrap();
@@ -472,7 +479,9 @@ async fn list_all_txs_for_wallet(world: &mut TariWorld, transaction_type: String
     }
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
 
-    let request = GetCompletedTransactionsRequest {};
+    let request = GetCompletedTransactionsRequest {
+        confirmation_threshold: None,
+    };
     let mut completed_txs = client.get_completed_transactions(request).await.unwrap().into_inner();
 
     while let Some(tx) = completed_txs.next().await {
@@ -516,7 +525,9 @@ async fn wallet_has_at_least_num_txs(world: &mut TariWorld, wallet: String, num_
 
     for _ in 0..num_retries {
         let mut txs = client
-            .get_completed_transactions(grpc::GetCompletedTransactionsRequest {})
+            .get_completed_transactions(grpc::GetCompletedTransactionsRequest {
+                confirmation_threshold: None,
+            })
             .await
             .unwrap()
             .into_inner();
@@ -684,6 +695,7 @@ async fn send_amount_from_source_wallet_to_dest_wallet_without_broadcast(
             dest_wallet.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -744,6 +756,7 @@ async fn send_one_sided_transaction_from_source_wallet_to_dest_wallt(
             dest_wallet.as_str()
         ),
         payment_type: 1, // one sided transaction
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -768,6 +781,7 @@ async fn send_one_sided_transaction_from_source_wallet_to_dest_wallt(
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..num_retries {
@@ -843,6 +857,7 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
             fee_per_gram
         ),
         payment_type: 0, // mimblewimble transaction
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -866,6 +881,7 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..num_retries {
@@ -920,7 +936,9 @@ async fn send_amount_from_wallet_to_wallet_at_fee(
 async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wallet_name: String, coinbases: u64) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -934,6 +952,7 @@ async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wa
             let tx_id = tx_info.unwrap().transaction.unwrap().tx_id;
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -972,7 +991,9 @@ async fn wallet_detects_at_least_coinbase_transactions(world: &mut TariWorld, wa
 async fn wallet_detects_at_least_unmined_transactions(world: &mut TariWorld, wallet_name: String, coinbases: u64) {
     let mut client = create_wallet_client(world, wallet_name.clone()).await.unwrap();
     let mut completed_tx_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -986,6 +1007,7 @@ async fn wallet_detects_at_least_unmined_transactions(world: &mut TariWorld, wal
             let tx_id = tx_info.unwrap().transaction.unwrap().tx_id;
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -1034,6 +1056,7 @@ async fn wallet_detects_exactly_coinbase_transactions(world: &mut TariWorld, wal
         'inner: for tx_id in tx_ids {
             let request = GetTransactionInfoRequest {
                 transaction_ids: vec![*tx_id],
+                confirmation_threshold: None,
             };
             let tx_info = client.get_transaction_info(request).await.unwrap().into_inner();
             let tx_info = tx_info.transactions.first().unwrap();
@@ -1130,6 +1153,7 @@ async fn all_wallets_detect_all_txs_as_mined_confirmed(world: &mut TariWorld) {
             'inner: for retry in 0..=num_retries {
                 let req = GetTransactionInfoRequest {
                     transaction_ids: vec![tx_id],
+                    confirmation_threshold: None,
                 };
                 let res = wallet_client.get_transaction_info(req).await.unwrap().into_inner();
                 let tx_status = res.transactions.first().unwrap().status;
@@ -1186,7 +1210,9 @@ async fn wallets_should_have_at_least_num_spendable_coinbase_outs(
 
         'inner: for _ in 0..num_retries {
             let mut stream = client
-                .get_completed_transactions(GetCompletedTransactionsRequest {})
+                .get_completed_transactions(GetCompletedTransactionsRequest {
+                    confirmation_threshold: None,
+                })
                 .await
                 .unwrap()
                 .into_inner();
@@ -1260,6 +1286,7 @@ async fn send_num_transactions_to_wallets_at_fee(
                 receiver_wallet.as_str()
             ),
             payment_type: 0, // standard mimblewimble transaction
+            input_commitments: vec![],
         };
         let transfer_req = TransferRequest {
             recipients: vec![payment_recipient],
@@ -1297,6 +1324,7 @@ async fn send_num_transactions_to_wallets_at_fee(
         println!("Waiting for transaction with id {} to be broadcasted", tx_id);
         let request = GetTransactionInfoRequest {
             transaction_ids: vec![tx_id],
+            confirmation_threshold: None,
         };
 
         let mut is_broadcast = false;
@@ -1401,6 +1429,7 @@ async fn transfer_tari_from_wallet_to_receiver(world: &mut TariWorld, amount: u6
             receiver.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1425,6 +1454,7 @@ async fn transfer_tari_from_wallet_to_receiver(world: &mut TariWorld, amount: u6
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -1594,6 +1624,7 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver1.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        input_commitments: vec![],
     };
 
     let payment_recipient2 = PaymentRecipient {
@@ -1607,6 +1638,7 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
             receiver2.as_str()
         ),
         payment_type: 0, // normal mimblewimble payment type
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient1, payment_recipient2],
@@ -1643,6 +1675,7 @@ async fn transfer_from_wallet_to_two_recipients_at_fee(
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id1, tx_id2],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -1718,6 +1751,7 @@ async fn transfer_tari_to_self(world: &mut TariWorld, amount: u64, sender: Strin
         fee_per_gram,
         message: format!("transfer amount {} from {} to self", amount, sender.as_str(),),
         payment_type: 0, // normal mimblewimble payment type
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -1741,6 +1775,7 @@ async fn transfer_tari_to_self(world: &mut TariWorld, amount: u64, sender: Strin
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -1803,6 +1838,7 @@ async fn htlc_transaction(world: &mut TariWorld, amount: u64, sender: String, re
             fee_per_gram
         ),
         payment_type: 0, // normal mimblewimble transaction
+        input_commitments: vec![],
     };
 
     let atomic_swap_request = SendShaAtomicSwapRequest {
@@ -1828,6 +1864,7 @@ async fn htlc_transaction(world: &mut TariWorld, amount: u64, sender: String, re
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -1909,6 +1946,7 @@ async fn claim_htlc_refund_transaction_with_wallet_at_fee(world: &mut TariWorld,
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -1981,6 +2019,7 @@ async fn wallet_claims_htlc_transaction_at_fee(world: &mut TariWorld, wallet: St
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..=num_retries {
@@ -2091,6 +2130,7 @@ async fn send_one_sided_stealth_transaction(
             receiver.as_str()
         ),
         payment_type: 2, // one sided stealth transaction
+        input_commitments: vec![],
     };
     let transfer_req = TransferRequest {
         recipients: vec![payment_recipient],
@@ -2115,6 +2155,7 @@ async fn send_one_sided_stealth_transaction(
     let num_retries = 100;
     let tx_info_req = GetTransactionInfoRequest {
         transaction_ids: vec![tx_id],
+        confirmation_threshold: None,
     };
 
     for i in 0..num_retries {
@@ -2509,7 +2550,9 @@ async fn restart_wallet(world: &mut TariWorld, wallet: String) {
 async fn check_if_wallet_has_num_transactions(world: &mut TariWorld, wallet: String, num_txs: u64) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -2572,6 +2615,7 @@ async fn multi_send_txs_from_wallet(
                 fee_per_gram
             ),
             payment_type: 0, // mimblewimble transaction
+            input_commitments: vec![],
         };
 
         let transfer_req = TransferRequest {
@@ -2601,6 +2645,7 @@ async fn multi_send_txs_from_wallet(
         let tx_id = tx_res.transaction_id;
         let tx_info_req = GetTransactionInfoRequest {
             transaction_ids: vec![tx_id],
+            confirmation_threshold: None,
         };
 
         for i in 0..num_retries {
@@ -2656,7 +2701,9 @@ async fn multi_send_txs_from_wallet(
 async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -2679,7 +2726,9 @@ async fn check_if_last_imported_txs_are_invalid_in_wallet(world: &mut TariWorld,
 async fn check_if_last_imported_txs_are_valid_in_wallet(world: &mut TariWorld, wallet: String) {
     let mut client = create_wallet_client(world, wallet.clone()).await.unwrap();
     let mut get_completed_txs_res = client
-        .get_completed_transactions(GetCompletedTransactionsRequest {})
+        .get_completed_transactions(GetCompletedTransactionsRequest {
+            confirmation_threshold: None,
+        })
         .await
         .unwrap()
         .into_inner();
@@ -2742,6 +2791,7 @@ async fn burn_transaction(world: &mut TariWorld, amount: u64, wallet: String, fe
         let result = client
             .get_transaction_info(grpc::GetTransactionInfoRequest {
                 transaction_ids: vec![tx_id],
+                confirmation_threshold: None,
             })
             .await
             .unwrap();